@@ -96,6 +96,8 @@ CREATE TABLE IF NOT EXISTS main.seclog.actor_population (
   timezone_offset INT,
   weekend_active BOOLEAN,
   service_pattern STRING,
+  insider_persona STRING,
+  on_call STRING,
   tags_json STRING,
   aws_principals_json STRING,
   identity_json STRING,
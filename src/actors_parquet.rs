@@ -3,8 +3,11 @@
 //! Stores `ActorSeed` data so sources can reuse a shared population.
 
 use crate::core::actors::{
-    ActorKind, ActorPopulation, ActorRole, ActorSeed, RoleRates, ServicePattern, ServiceProfile,
+    ActorKind, ActorPopulation, ActorRole, ActorSeed, InsiderPersona, OnCallShift, RoleRates,
+    ServicePattern, ServiceProfile,
 };
+use crate::core::config::ParquetOptions;
+use crate::formats::parquet::writer_properties;
 use arrow_array::builder::{
     BooleanBuilder, Float64Builder, Int16Builder, Int8Builder, StringBuilder,
 };
@@ -15,7 +18,6 @@ use arrow_schema::{DataType, Field, Schema, SchemaRef};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use parquet::arrow::arrow_writer::ArrowWriter;
 use parquet::errors::ParquetError;
-use parquet::file::properties::WriterProperties;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs::File;
@@ -26,6 +28,16 @@ use std::sync::Arc;
 
 /// Writes an actor population to a Parquet file.
 pub fn write_population(path: impl AsRef<Path>, population: &ActorPopulation) -> io::Result<()> {
+    write_population_with_options(path, population, &ParquetOptions::default())
+}
+
+/// Writes an actor population to a Parquet file with configured writer properties.
+pub fn write_population_with_options(
+    path: impl AsRef<Path>,
+    population: &ActorPopulation,
+    options: &ParquetOptions,
+) -> io::Result<()> {
+    let props = writer_properties(options)?;
     let schema = build_schema();
     let mut kind_builder = StringBuilder::new();
     let mut role_builder = StringBuilder::new();
@@ -52,6 +64,8 @@ pub fn write_population(path: impl AsRef<Path>, population: &ActorPopulation) ->
     let mut actor_id_builder = StringBuilder::new();
     let mut tags_builder = StringBuilder::new();
     let mut event_bias_builder = StringBuilder::new();
+    let mut insider_persona_builder = StringBuilder::new();
+    let mut on_call_builder = StringBuilder::new();
 
     for actor in &population.actors {
         kind_builder.append_value(kind_to_str(&actor.kind));
@@ -124,6 +138,16 @@ pub fn write_population(path: impl AsRef<Path>, population: &ActorPopulation) ->
         } else {
             event_bias_builder.append_value(encode_event_bias(&actor.event_bias));
         }
+        if let Some(persona) = actor.insider_persona {
+            insider_persona_builder.append_value(persona.key());
+        } else {
+            insider_persona_builder.append_null();
+        }
+        if let Some(shift) = &actor.on_call {
+            on_call_builder.append_value(shift.key());
+        } else {
+            on_call_builder.append_null();
+        }
     }
 
     let batch = RecordBatch::try_new(
@@ -154,12 +178,13 @@ pub fn write_population(path: impl AsRef<Path>, population: &ActorPopulation) ->
             Arc::new(email_builder.finish()),
             Arc::new(home_location_builder.finish()),
             Arc::new(normal_regions_builder.finish()),
+            Arc::new(insider_persona_builder.finish()),
+            Arc::new(on_call_builder.finish()),
         ],
     )
     .map_err(map_arrow_err)?;
 
     let file = File::create(path)?;
-    let props = WriterProperties::builder().build();
     let mut writer = ArrowWriter::try_new(file, schema, Some(props)).map_err(map_parquet_err)?;
     writer.write(&batch).map_err(map_parquet_err)?;
     writer.close().map_err(map_parquet_err)?;
@@ -207,6 +232,8 @@ fn read_batch(batch: &RecordBatch) -> io::Result<Vec<ActorSeed>> {
     let email = column_as_string_optional_fallback(batch, 22)?;
     let home_location = column_as_string_optional_fallback(batch, 23)?;
     let normal_regions = column_as_string_optional_fallback(batch, 24)?;
+    let insider_persona = column_as_string_optional_fallback(batch, 25)?;
+    let on_call = column_as_string_optional_fallback(batch, 26)?;
 
     let mut actors = Vec::with_capacity(batch.num_rows());
     for idx in 0..batch.num_rows() {
@@ -275,6 +302,14 @@ fn read_batch(batch: &RecordBatch) -> io::Result<Vec<ActorSeed>> {
             event_bias,
             service_profile: resolved_profile,
             service_pattern: resolved_pattern,
+            insider_persona: insider_persona
+                .get(idx)
+                .and_then(|value| value.as_deref())
+                .and_then(InsiderPersona::parse),
+            on_call: on_call
+                .get(idx)
+                .and_then(|value| value.as_deref())
+                .and_then(OnCallShift::parse),
             user_name: user_name.get(idx).cloned().flatten(),
             display_name: display_name.get(idx).cloned().flatten(),
             email: email.get(idx).cloned().flatten(),
@@ -325,19 +360,21 @@ fn build_schema() -> SchemaRef {
         Field::new("email", DataType::Utf8, true),
         Field::new("home_location", DataType::Utf8, true),
         Field::new("normal_countries_regions", DataType::Utf8, true),
+        Field::new("insider_persona", DataType::Utf8, true),
+        Field::new("on_call", DataType::Utf8, true),
     ];
 
     Arc::new(Schema::new(fields))
 }
 
-fn kind_to_str(kind: &ActorKind) -> &'static str {
+pub(crate) fn kind_to_str(kind: &ActorKind) -> &'static str {
     match kind {
         ActorKind::Human => "human",
         ActorKind::Service => "service",
     }
 }
 
-fn role_to_str(role: &ActorRole) -> &'static str {
+pub(crate) fn role_to_str(role: &ActorRole) -> &'static str {
     match role {
         ActorRole::Admin => "admin",
         ActorRole::Developer => "developer",
@@ -364,7 +401,7 @@ fn parse_role(value: &str) -> io::Result<ActorRole> {
     }
 }
 
-fn service_profile_to_str(profile: &ServiceProfile) -> &'static str {
+pub(crate) fn service_profile_to_str(profile: &ServiceProfile) -> &'static str {
     match profile {
         ServiceProfile::Generic => "generic",
         ServiceProfile::Ec2Reaper => "ec2_reaper",
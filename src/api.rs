@@ -1,18 +1,38 @@
 //! Programmatic generation API used by language bindings.
 
 use crate::core::actors::generate_population;
-use crate::core::config::{Config, MultiSourceConfig, PopulationConfig, SourceConfig};
+use crate::core::config::{
+    AlbAccessLogConfig, Config, MultiSourceConfig, PaddingConfig, PopulationConfig,
+    RedactionConfig, ResolverQueryLogConfig, S3AccessLogConfig, SourceConfig, TenantConfig,
+    TrafficCalendarConfig, TransformConfig,
+};
 use crate::core::event::Event;
 use crate::core::identity::IdentityRegistry;
+use crate::core::traffic::{parse_duration, TrafficCalendar};
 use crate::core::traits::EventSource;
-use crate::sources::cloudtrail::CloudTrailGenerator;
-use crate::sources::composite::CompositeEventSource;
+use crate::formats::written_bytes::{SharedWrittenBytes, WrittenBytes};
+use crate::sources::alb_access::AlbAccessLogSource;
+use crate::sources::calendar::{CalendarEventSource, SharedCalendar};
+use crate::sources::cloudtrail::{CloudTrailGenerator, SharedScenarios};
+use crate::sources::composite::{
+    CompositeEventSource, LimitedEventSource, SourceLimits, TenantEventSource,
+};
 use crate::sources::databricks::DatabricksAuditGenerator;
+use crate::sources::github::GitHubAuditGenerator;
+use crate::sources::google_workspace::GoogleWorkspaceGenerator;
+use crate::sources::kubernetes::KubernetesAuditGenerator;
 use crate::sources::okta::OktaSystemLogGenerator;
+use crate::sources::padding::PaddedEventSource;
+use crate::sources::redaction::RedactedEventSource;
+use crate::sources::route53_resolver::ResolverQueryLogSource;
+use crate::sources::s3_access::S3AccessLogSource;
+use crate::sources::transform::{transforms_from_config, TransformedEventSource};
+use crate::sources::windows_security::WindowsSecurityGenerator;
 use chrono::{DateTime, Utc};
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::error::Error;
 use std::io;
+use std::sync::Arc;
 
 pub type ApiResult<T> = Result<T, Box<dyn Error>>;
 
@@ -41,6 +61,7 @@ impl EventStream {
     pub fn from_config(config: &Config) -> ApiResult<Self> {
         let start_time = parse_start_time(config.traffic.start_time.as_deref())?;
         let source = build_event_source(&config.source, config.seed, start_time)?;
+        let source = apply_event_stages(source, config)?;
         Ok(Self { source })
     }
 
@@ -141,7 +162,121 @@ pub fn build_event_source(
     seed: Option<u64>,
     start_time: DateTime<Utc>,
 ) -> ApiResult<Box<dyn EventSource>> {
-    build_event_source_with_registry(config, seed, start_time, None)
+    build_event_source_with_registry(config, seed, start_time, None, None, None)
+}
+
+/// Like [`build_event_source`], but a top-level CloudTrail source re-plans its
+/// storms, remediation waves, and impossible travel from `scenarios`, which a
+/// live config reload can replace while the run goes on.
+pub fn build_event_source_with_scenarios(
+    config: &SourceConfig,
+    seed: Option<u64>,
+    start_time: DateTime<Utc>,
+    scenarios: &SharedScenarios,
+) -> ApiResult<Box<dyn EventSource>> {
+    build_event_source_with_registry(config, seed, start_time, None, Some(scenarios), None)
+}
+
+/// Like [`build_event_source`], but `source.limits` byte caps count the record
+/// bytes writers wrapped in a
+/// [`WrittenBytesWriter`](crate::formats::written_bytes::WrittenBytesWriter)
+/// report to `written`; sources built without a tally never reach
+/// `max_bytes`. A top-level CloudTrail source follows `scenarios` when given,
+/// as [`build_event_source_with_scenarios`] does.
+pub fn build_event_source_with_written_bytes(
+    config: &SourceConfig,
+    seed: Option<u64>,
+    start_time: DateTime<Utc>,
+    scenarios: Option<&SharedScenarios>,
+    written: &SharedWrittenBytes,
+) -> ApiResult<Box<dyn EventSource>> {
+    build_event_source_with_registry(config, seed, start_time, None, scenarios, Some(written))
+}
+
+/// Applies the config's traffic calendar, padding, redaction, and transforms to
+/// `source`, in that order.
+pub fn apply_event_stages(
+    source: Box<dyn EventSource>,
+    config: &Config,
+) -> ApiResult<Box<dyn EventSource>> {
+    let source = apply_calendar(source, config.traffic.calendar.as_ref(), config.seed)?;
+    apply_payload_stages(source, config)
+}
+
+/// Like [`apply_event_stages`], but thins by `calendar`, which a live config
+/// reload can replace while the run goes on.
+pub fn apply_event_stages_with_calendar(
+    source: Box<dyn EventSource>,
+    config: &Config,
+    calendar: &SharedCalendar,
+) -> ApiResult<Box<dyn EventSource>> {
+    let source = Box::new(CalendarEventSource::shared(
+        source,
+        Arc::clone(calendar),
+        config.seed,
+    ));
+    apply_payload_stages(source, config)
+}
+
+fn apply_payload_stages(
+    source: Box<dyn EventSource>,
+    config: &Config,
+) -> ApiResult<Box<dyn EventSource>> {
+    let source = apply_padding(source, config.padding.as_ref(), config.seed)?;
+    let source = apply_redaction(source, config.redaction.as_ref())?;
+    apply_transforms(source, &config.transforms)
+}
+
+/// Thins `source` by day when the config has a `[traffic.calendar]` table.
+pub fn apply_calendar(
+    source: Box<dyn EventSource>,
+    calendar: Option<&TrafficCalendarConfig>,
+    seed: Option<u64>,
+) -> ApiResult<Box<dyn EventSource>> {
+    let Some(calendar) = calendar else {
+        return Ok(source);
+    };
+    let calendar = TrafficCalendar::from_config(calendar)?;
+    Ok(Box::new(CalendarEventSource::new(source, calendar, seed)))
+}
+
+/// Wraps `source` with payload padding when the config has a `[padding]` table.
+pub fn apply_padding(
+    source: Box<dyn EventSource>,
+    padding: Option<&PaddingConfig>,
+    seed: Option<u64>,
+) -> ApiResult<Box<dyn EventSource>> {
+    let Some(padding) = padding else {
+        return Ok(source);
+    };
+    // Keep the filler stream independent of the generator RNGs.
+    let seed = seed.map(|seed| seed ^ 0x7061_6464_696e_6721);
+    Ok(Box::new(PaddedEventSource::new(source, padding, seed)?))
+}
+
+/// Wraps `source` with field redaction when the config has a `[redaction]` table.
+pub fn apply_redaction(
+    source: Box<dyn EventSource>,
+    redaction: Option<&RedactionConfig>,
+) -> ApiResult<Box<dyn EventSource>> {
+    let Some(redaction) = redaction else {
+        return Ok(source);
+    };
+    Ok(Box::new(RedactedEventSource::new(source, redaction)?))
+}
+
+/// Wraps `source` with the `[[transforms]]` field rewrites, if any.
+pub fn apply_transforms(
+    source: Box<dyn EventSource>,
+    transforms: &[TransformConfig],
+) -> ApiResult<Box<dyn EventSource>> {
+    if transforms.is_empty() {
+        return Ok(source);
+    }
+    Ok(Box::new(TransformedEventSource::new(
+        source,
+        transforms_from_config(transforms)?,
+    )))
 }
 
 fn build_event_source_with_registry(
@@ -149,23 +284,83 @@ fn build_event_source_with_registry(
     seed: Option<u64>,
     start_time: DateTime<Utc>,
     inherited_registry: Option<&IdentityRegistry>,
+    scenarios: Option<&SharedScenarios>,
+    written: Option<&SharedWrittenBytes>,
 ) -> ApiResult<Box<dyn EventSource>> {
     match config {
         SourceConfig::CloudTrail(config) => {
-            if let Some(registry) = inherited_registry {
-                if config.actor_population_path.is_none() && config.identity_registry_path.is_none()
+            let mut generator = match inherited_registry {
+                Some(registry)
+                    if config.actor_population_path.is_none()
+                        && config.identity_registry_path.is_none() =>
                 {
-                    return Ok(Box::new(CloudTrailGenerator::from_registry(
-                        config,
-                        registry.clone(),
-                        seed,
-                        start_time,
-                    )?));
+                    CloudTrailGenerator::from_registry(config, registry.clone(), seed, start_time)?
                 }
+                _ => CloudTrailGenerator::from_config(config, seed, start_time)?,
+            };
+            if let Some(scenarios) = scenarios {
+                generator.follow_scenarios(Arc::clone(scenarios));
             }
-            Ok(Box::new(CloudTrailGenerator::from_config(
-                config, seed, start_time,
-            )?))
+            let mut source: Box<dyn EventSource> = Box::new(generator);
+            // Every derived log stage needs the CloudTrail events, so only the
+            // last one decides whether they are kept.
+            let includes = [
+                config
+                    .resolver_query_logs
+                    .as_ref()
+                    .map(|logs| logs.include_cloudtrail),
+                config
+                    .alb_access_logs
+                    .as_ref()
+                    .map(|logs| logs.include_cloudtrail),
+                config
+                    .s3_access_logs
+                    .as_ref()
+                    .map(|logs| logs.include_cloudtrail),
+            ];
+            let keep_cloudtrail = includes
+                .iter()
+                .flatten()
+                .all(|include| *include != Some(false));
+            let mut remaining = includes.iter().flatten().count();
+            let mut include_cloudtrail = || {
+                remaining -= 1;
+                Some(remaining > 0 || keep_cloudtrail)
+            };
+            if let Some(query_logs) = &config.resolver_query_logs {
+                let query_logs = ResolverQueryLogConfig {
+                    include_cloudtrail: include_cloudtrail(),
+                    ..query_logs.clone()
+                };
+                // Keep the query log fields independent of the CloudTrail RNG.
+                let seed = seed.map(|seed| seed ^ 0x7265_736f_6c76_6572);
+                source = Box::new(
+                    ResolverQueryLogSource::new(source, &query_logs, seed)
+                        .map_err(|err| invalid_input(format!("resolver_query_logs: {err}")))?,
+                );
+            }
+            if let Some(access_logs) = &config.alb_access_logs {
+                let access_logs = AlbAccessLogConfig {
+                    include_cloudtrail: include_cloudtrail(),
+                    ..access_logs.clone()
+                };
+                // Keep the access log fields independent of the CloudTrail RNG.
+                let seed = seed.map(|seed| seed ^ 0x616c_625f_6c6f_6773);
+                source = Box::new(
+                    AlbAccessLogSource::new(source, &access_logs, seed)
+                        .map_err(|err| invalid_input(format!("alb_access_logs: {err}")))?,
+                );
+            }
+            if let Some(access_logs) = &config.s3_access_logs {
+                let access_logs = S3AccessLogConfig {
+                    include_cloudtrail: include_cloudtrail(),
+                    ..access_logs.clone()
+                };
+                // Keep the access log fields independent of the CloudTrail RNG.
+                let seed = seed.map(|seed| seed ^ 0x7333_6163_6365_7373);
+                source = Box::new(S3AccessLogSource::new(source, &access_logs, seed));
+            }
+            Ok(source)
         }
         SourceConfig::DatabricksAudit(config) => {
             if let Some(registry) = inherited_registry {
@@ -195,8 +390,64 @@ fn build_event_source_with_registry(
                 config, start_time,
             )?))
         }
+        SourceConfig::GitHubAudit(config) => {
+            if let Some(registry) = inherited_registry {
+                if config.identity_registry_path.trim().is_empty() {
+                    return Ok(Box::new(GitHubAuditGenerator::from_registry(
+                        config,
+                        registry.clone(),
+                        start_time,
+                    )?));
+                }
+            }
+            Ok(Box::new(GitHubAuditGenerator::from_config(
+                config, start_time,
+            )?))
+        }
+        SourceConfig::KubernetesAudit(config) => {
+            if let Some(registry) = inherited_registry {
+                if config.identity_registry_path.trim().is_empty() {
+                    return Ok(Box::new(KubernetesAuditGenerator::from_registry(
+                        config,
+                        registry.clone(),
+                        start_time,
+                    )?));
+                }
+            }
+            Ok(Box::new(KubernetesAuditGenerator::from_config(
+                config, start_time,
+            )?))
+        }
+        SourceConfig::WindowsSecurity(config) => {
+            if let Some(registry) = inherited_registry {
+                if config.identity_registry_path.trim().is_empty() {
+                    return Ok(Box::new(WindowsSecurityGenerator::from_registry(
+                        config,
+                        registry.clone(),
+                        start_time,
+                    )?));
+                }
+            }
+            Ok(Box::new(WindowsSecurityGenerator::from_config(
+                config, start_time,
+            )?))
+        }
+        SourceConfig::GoogleWorkspace(config) => {
+            if let Some(registry) = inherited_registry {
+                if config.identity_registry_path.trim().is_empty() {
+                    return Ok(Box::new(GoogleWorkspaceGenerator::from_registry(
+                        config,
+                        registry.clone(),
+                        start_time,
+                    )?));
+                }
+            }
+            Ok(Box::new(GoogleWorkspaceGenerator::from_config(
+                config, start_time,
+            )?))
+        }
         SourceConfig::Multi(config) => {
-            build_multi_event_source(config, seed, start_time, inherited_registry)
+            build_multi_event_source(config, seed, start_time, inherited_registry, written, None)
         }
     }
 }
@@ -206,27 +457,160 @@ fn build_multi_event_source(
     seed: Option<u64>,
     start_time: DateTime<Utc>,
     inherited_registry: Option<&IdentityRegistry>,
+    written: Option<&SharedWrittenBytes>,
+    tenant: Option<&str>,
 ) -> ApiResult<Box<dyn EventSource>> {
     if config.sources.is_empty() {
         return Err(invalid_input(
             "multi source requires at least one child source",
         ));
     }
+    if !config.tenants.is_empty() {
+        return build_multi_tenant_source(config, seed, start_time, written);
+    }
     let generated_registry = shared_registry_for_multi(config)?;
     let registry = generated_registry.as_ref().or(inherited_registry);
-    let mut sources = Vec::with_capacity(config.sources.len());
+    let limits = source_limits(config, start_time)?;
+    let written = written.cloned().unwrap_or_else(WrittenBytes::new);
+    let mut sources: Vec<Box<dyn EventSource>> = Vec::with_capacity(config.sources.len());
 
     for (idx, source) in config.sources.iter().enumerate() {
-        let source = inherit_identity_registry(source, config.identity_registry_path.as_deref());
+        let mut source =
+            inherit_identity_registry(source, config.identity_registry_path.as_deref());
+        if config.link_sessions || config.session_correlation.is_some() {
+            link_child_sessions(&mut source, config.session_correlation);
+        }
         let child_seed = seed.map(|seed| seed.wrapping_add(idx as u64));
-        sources.push(build_event_source_with_registry(
-            &source, child_seed, start_time, registry,
-        )?);
+        let child = build_event_source_with_registry(
+            &source,
+            child_seed,
+            start_time,
+            registry,
+            None,
+            Some(&written),
+        )?;
+        let emits = source.envelope_sources();
+        if emits.iter().any(|name| limits.contains_key(name)) {
+            sources.push(Box::new(LimitedEventSource::new(
+                child,
+                &emits,
+                &limits,
+                Arc::clone(&written),
+                tenant.map(str::to_string),
+            )));
+        } else {
+            sources.push(child);
+        }
     }
 
     Ok(Box::new(CompositeEventSource::new(sources)))
 }
 
+/// Runs the multi source's children once per tenant, each against that tenant's
+/// identities, and merges all tenants by timestamp.
+fn build_multi_tenant_source(
+    config: &MultiSourceConfig,
+    seed: Option<u64>,
+    start_time: DateTime<Utc>,
+    written: Option<&SharedWrittenBytes>,
+) -> ApiResult<Box<dyn EventSource>> {
+    if config.identity_registry_path.is_some()
+        || config.population_config_path.is_some()
+        || config.population_config.is_some()
+    {
+        return Err(invalid_input(
+            "multi source with tenants must configure identities per tenant",
+        ));
+    }
+    let max_weight = validate_tenants(&config.tenants)?;
+
+    let mut sources: Vec<Box<dyn EventSource>> = Vec::with_capacity(config.tenants.len());
+    for (idx, tenant) in config.tenants.iter().enumerate() {
+        let mut tenant_config = config.clone();
+        tenant_config.tenants = Vec::new();
+        tenant_config.identity_registry_path = tenant.identity_registry_path.clone();
+        tenant_config.population_config_path = tenant.population_config_path.clone();
+        tenant_config.population_config = tenant.population_config.clone();
+
+        let tenant_seed = seed.map(|seed| seed.wrapping_add(((idx as u64) + 1) << 32));
+        let source = build_multi_event_source(
+            &tenant_config,
+            tenant_seed,
+            start_time,
+            None,
+            written,
+            Some(&tenant.id),
+        )
+        .map_err(|err| invalid_input(format!("tenant {}: {err}", tenant.id)))?;
+        let keep_ratio = tenant.weight.unwrap_or(1.0) / max_weight;
+        sources.push(Box::new(TenantEventSource::new(
+            source,
+            tenant.id.clone(),
+            keep_ratio,
+            tenant_seed,
+        )));
+    }
+
+    Ok(Box::new(CompositeEventSource::new(sources)))
+}
+
+/// Resolves `limits` against the run start; `max_sim_duration` becomes an
+/// absolute cutoff.
+fn source_limits(
+    config: &MultiSourceConfig,
+    start_time: DateTime<Utc>,
+) -> ApiResult<HashMap<String, SourceLimits>> {
+    let Some(limits) = &config.limits else {
+        return Ok(HashMap::new());
+    };
+    limits
+        .iter()
+        .map(|(name, limit)| {
+            let until = match &limit.max_sim_duration {
+                Some(value) => {
+                    let duration = parse_duration(value)
+                        .and_then(|duration| chrono::Duration::from_std(duration).ok())
+                        .ok_or_else(|| {
+                            invalid_input(format!(
+                                "limits.{name}.max_sim_duration: invalid duration {value:?}"
+                            ))
+                        })?;
+                    Some(start_time + duration)
+                }
+                None => None,
+            };
+            let limits = SourceLimits {
+                max_events: limit.max_events,
+                max_bytes: limit.max_bytes,
+                until,
+            };
+            Ok((name.clone(), limits))
+        })
+        .collect()
+}
+
+fn validate_tenants(tenants: &[TenantConfig]) -> ApiResult<f64> {
+    let mut ids = BTreeSet::new();
+    let mut max_weight = 0.0_f64;
+    for tenant in tenants {
+        if non_empty_str(&tenant.id).is_none() {
+            return Err(invalid_input("tenant id must not be empty"));
+        }
+        if !ids.insert(tenant.id.as_str()) {
+            return Err(invalid_input(format!("duplicate tenant id: {}", tenant.id)));
+        }
+        let weight = tenant.weight.unwrap_or(1.0);
+        if !weight.is_finite() || weight <= 0.0 {
+            return Err(invalid_input(format!(
+                "tenant {} weight must be greater than 0",
+                tenant.id
+            )));
+        }
+        max_weight = max_weight.max(weight);
+    }
+    Ok(max_weight)
+}
+
 fn shared_registry_for_multi(config: &MultiSourceConfig) -> ApiResult<Option<IdentityRegistry>> {
     let identity_registry_path = config
         .identity_registry_path
@@ -290,6 +674,59 @@ fn identity_registry_from_population_config(
     )?)
 }
 
+/// Puts a child on the shared per-identity clock. A multi-level
+/// `correlation` overrides the child's own `session_correlation`.
+fn link_child_sessions(config: &mut SourceConfig, correlation: Option<f64>) {
+    fn link(linked: &mut bool, own: &mut f64, correlation: Option<f64>) {
+        *linked = true;
+        if let Some(correlation) = correlation {
+            *own = correlation;
+        }
+    }
+
+    match config {
+        SourceConfig::CloudTrail(config) => link(
+            &mut config.linked_sessions,
+            &mut config.session_correlation,
+            correlation,
+        ),
+        SourceConfig::DatabricksAudit(config) => link(
+            &mut config.linked_sessions,
+            &mut config.session_correlation,
+            correlation,
+        ),
+        SourceConfig::OktaSystemLog(config) => link(
+            &mut config.linked_sessions,
+            &mut config.session_correlation,
+            correlation,
+        ),
+        SourceConfig::GitHubAudit(config) => link(
+            &mut config.linked_sessions,
+            &mut config.session_correlation,
+            correlation,
+        ),
+        SourceConfig::KubernetesAudit(config) => link(
+            &mut config.linked_sessions,
+            &mut config.session_correlation,
+            correlation,
+        ),
+        SourceConfig::WindowsSecurity(config) => link(
+            &mut config.linked_sessions,
+            &mut config.session_correlation,
+            correlation,
+        ),
+        SourceConfig::GoogleWorkspace(config) => link(
+            &mut config.linked_sessions,
+            &mut config.session_correlation,
+            correlation,
+        ),
+        SourceConfig::Multi(config) => {
+            config.link_sessions = true;
+            config.session_correlation = correlation.or(config.session_correlation);
+        }
+    }
+}
+
 fn inherit_identity_registry(config: &SourceConfig, path: Option<&str>) -> SourceConfig {
     let mut inherited = config.clone();
     let Some(path) = path.and_then(non_empty_str) else {
@@ -312,6 +749,26 @@ fn inherit_identity_registry(config: &SourceConfig, path: Option<&str>) -> Sourc
                 config.identity_registry_path = path.to_string();
             }
         }
+        SourceConfig::GitHubAudit(config) => {
+            if config.identity_registry_path.trim().is_empty() {
+                config.identity_registry_path = path.to_string();
+            }
+        }
+        SourceConfig::KubernetesAudit(config) => {
+            if config.identity_registry_path.trim().is_empty() {
+                config.identity_registry_path = path.to_string();
+            }
+        }
+        SourceConfig::WindowsSecurity(config) => {
+            if config.identity_registry_path.trim().is_empty() {
+                config.identity_registry_path = path.to_string();
+            }
+        }
+        SourceConfig::GoogleWorkspace(config) => {
+            if config.identity_registry_path.trim().is_empty() {
+                config.identity_registry_path = path.to_string();
+            }
+        }
         SourceConfig::Multi(config) => {
             if config.identity_registry_path.is_none() {
                 config.identity_registry_path = Some(path.to_string());
@@ -350,6 +807,30 @@ fn collect_identity_registry_paths(
                 non_empty_str(&config.identity_registry_path).or(inherited_path),
             );
         }
+        SourceConfig::GitHubAudit(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
+        SourceConfig::KubernetesAudit(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
+        SourceConfig::WindowsSecurity(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
+        SourceConfig::GoogleWorkspace(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
         SourceConfig::Multi(config) => {
             let next_inherited = config
                 .identity_registry_path
@@ -449,6 +930,252 @@ mod tests {
         }
     }
 
+    #[test]
+    fn linked_sessions_align_identity_activity_across_sources() {
+        let config = json!({
+            "seed": 7,
+            "traffic": {"start_time": "2026-01-05T00:00:00Z"},
+            "output": {
+                "dir": "./out-test",
+                "files": {"target_size_mb": 50, "max_age_seconds": 10},
+                "format": {"type": "jsonl"}
+            },
+            "source": {
+                "type": "multi",
+                "population_config": population_config_json(),
+                "link_sessions": true,
+                "sources": [
+                    {
+                        "type": "databricks_audit",
+                        "account_id": "example-account-id",
+                        "workspace_id": "1234567890"
+                    },
+                    {"type": "okta", "org_id": "okta-example-org"}
+                ]
+            }
+        });
+
+        let events = generate_events(&serde_json::from_value(config).unwrap(), Some(400)).unwrap();
+        let mut by_actor: std::collections::HashMap<(String, String), Vec<DateTime<Utc>>> =
+            std::collections::HashMap::new();
+        for event in &events {
+            let at = DateTime::parse_from_rfc3339(&event.envelope.timestamp)
+                .unwrap()
+                .with_timezone(&Utc);
+            by_actor
+                .entry((
+                    event.envelope.actor.id.clone(),
+                    event.envelope.source.clone(),
+                ))
+                .or_default()
+                .push(at);
+        }
+        let mut linked = 0;
+        for ((actor_id, source), databricks) in &by_actor {
+            if source != "databricks_audit" {
+                continue;
+            }
+            let Some(okta) = by_actor.get(&(actor_id.clone(), "okta_system_log".to_string()))
+            else {
+                continue;
+            };
+            for (okta_at, databricks_at) in okta.iter().zip(databricks) {
+                let lag = (*databricks_at - *okta_at).num_seconds();
+                assert!((25..=180).contains(&lag), "actor={actor_id} lag={lag}");
+                linked += 1;
+            }
+        }
+        assert!(linked > 0);
+    }
+
+    #[test]
+    fn linked_cloudtrail_console_sessions_follow_identity_provider_sign_ins() {
+        let config = json!({
+            "seed": 7,
+            "traffic": {"start_time": "2026-01-05T00:00:00Z"},
+            "output": {
+                "dir": "./out-test",
+                "files": {"target_size_mb": 50, "max_age_seconds": 10},
+                "format": {"type": "jsonl"}
+            },
+            "source": {
+                "type": "multi",
+                "population_config": population_config_json(),
+                "link_sessions": true,
+                "sources": [
+                    {
+                        "type": "cloudtrail",
+                        "curated": true,
+                        "entra_federation": {}
+                    },
+                    {"type": "okta", "org_id": "okta-example-org"}
+                ]
+            }
+        });
+
+        let events = generate_events(&serde_json::from_value(config).unwrap(), Some(3000)).unwrap();
+        let at = |event: &Event| {
+            DateTime::parse_from_rfc3339(&event.envelope.timestamp)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let mut okta: std::collections::HashMap<String, Vec<DateTime<Utc>>> =
+            std::collections::HashMap::new();
+        for event in &events {
+            if event.envelope.source == "okta_system_log" {
+                okta.entry(event.envelope.actor.id.clone())
+                    .or_default()
+                    .push(at(event));
+            }
+        }
+        let mut sign_ins = std::collections::HashMap::new();
+        let mut sessions = 0;
+        for event in &events {
+            let actor_id = &event.envelope.actor.id;
+            match (
+                event.envelope.source.as_str(),
+                event.envelope.event_type.as_str(),
+            ) {
+                ("entra_signin", _) => {
+                    sign_ins.insert(actor_id.clone(), at(event));
+                }
+                ("cloudtrail", "ConsoleLogin") => {
+                    let login_at = at(event);
+                    let signed_in_at = sign_ins.remove(actor_id).unwrap_or_else(|| {
+                        panic!("actor={actor_id} console login without sign-in")
+                    });
+                    assert!(signed_in_at <= login_at);
+                    assert!(
+                        okta.get(actor_id)
+                            .is_some_and(|times| times.iter().any(|okta_at| {
+                                (25..=180).contains(&(login_at - *okta_at).num_seconds())
+                            })),
+                        "actor={actor_id} console login at {login_at} without an Okta sign-in"
+                    );
+                    sessions += 1;
+                }
+                _ => {}
+            }
+        }
+        assert!(sessions > 0);
+    }
+
+    #[test]
+    fn linked_cloudtrail_and_okta_name_the_same_human() {
+        let config = json!({
+            "seed": 11,
+            "traffic": {"start_time": "2026-01-05T00:00:00Z"},
+            "output": {
+                "dir": "./out-test",
+                "files": {"target_size_mb": 50, "max_age_seconds": 10},
+                "format": {"type": "jsonl"}
+            },
+            "source": {
+                "type": "multi",
+                "population_config": population_config_json(),
+                "link_sessions": true,
+                "sources": [
+                    {
+                        "type": "cloudtrail",
+                        "curated": true,
+                        "entra_federation": {}
+                    },
+                    {"type": "okta", "org_id": "okta-example-org"}
+                ]
+            }
+        });
+
+        let events = generate_events(&serde_json::from_value(config).unwrap(), Some(3000)).unwrap();
+        let mut okta_logins = std::collections::HashMap::new();
+        for event in &events {
+            if event.envelope.source == "okta_system_log" {
+                if let Some(login) = event.payload["actor"]["alternateId"].as_str() {
+                    okta_logins.insert(event.envelope.actor.id.clone(), login.to_string());
+                }
+            }
+        }
+        let mut joined = 0;
+        for event in &events {
+            let upn = match event.envelope.source.as_str() {
+                "entra_signin" => &event.payload["userPrincipalName"],
+                "cloudtrail" if event.payload["userIdentity"]["type"] == "SAMLUser" => {
+                    &event.payload["userIdentity"]["userName"]
+                }
+                _ => continue,
+            };
+            let actor_id = &event.envelope.actor.id;
+            let login = okta_logins
+                .get(actor_id)
+                .unwrap_or_else(|| panic!("actor={actor_id} has no Okta activity"));
+            assert_eq!(upn.as_str(), Some(login.as_str()), "actor={actor_id}");
+            joined += 1;
+        }
+        assert!(joined > 0);
+    }
+
+    #[test]
+    fn tenants_stamp_envelope_and_scale_volume_by_weight() {
+        let mut small_tenant = population_config_json();
+        small_tenant["seed"] = json!(43);
+        let config = json!({
+            "seed": 7,
+            "traffic": {"start_time": "2026-01-05T00:00:00Z"},
+            "output": {
+                "dir": "./out-test",
+                "files": {"target_size_mb": 50, "max_age_seconds": 10},
+                "format": {"type": "jsonl"}
+            },
+            "source": {
+                "type": "multi",
+                "tenants": [
+                    {"id": "tenant-a", "population_config": population_config_json()},
+                    {"id": "tenant-b", "weight": 0.25, "population_config": small_tenant}
+                ],
+                "sources": [
+                    {"type": "okta", "org_id": "okta-example-org"}
+                ]
+            }
+        });
+
+        let events = generate_events(&serde_json::from_value(config).unwrap(), Some(800)).unwrap();
+        let count = |tenant: &str| {
+            events
+                .iter()
+                .filter(|event| event.envelope.tenant.as_deref() == Some(tenant))
+                .count()
+        };
+        assert_eq!(count("tenant-a") + count("tenant-b"), events.len());
+        assert!(events
+            .iter()
+            .all(|event| event.envelope.tenant_id.as_deref() == Some("okta-example-org")));
+        assert!(count("tenant-b") > 0);
+        assert!(count("tenant-a") > count("tenant-b") * 2);
+    }
+
+    #[test]
+    fn tenants_reject_shared_population() {
+        let config = json!({
+            "traffic": {"start_time": "2026-01-05T00:00:00Z"},
+            "output": {
+                "dir": "./out-test",
+                "files": {"target_size_mb": 50, "max_age_seconds": 10},
+                "format": {"type": "jsonl"}
+            },
+            "source": {
+                "type": "multi",
+                "population_config": population_config_json(),
+                "tenants": [{"id": "tenant-a"}],
+                "sources": [{"type": "okta", "org_id": "okta-example-org"}]
+            }
+        });
+
+        let err = match generate_events(&serde_json::from_value(config).unwrap(), Some(1)) {
+            Ok(_) => panic!("expected tenant validation error"),
+            Err(err) => err.to_string(),
+        };
+        assert!(err.contains("configure identities per tenant"));
+    }
+
     fn population_config_json() -> serde_json::Value {
         json!({
             "seed": 42,
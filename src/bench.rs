@@ -0,0 +1,339 @@
+//! Generator and serialization throughput measurement.
+//!
+//! Runs an event source flat out on one thread and encodes every event into
+//! Parquet record batches, timing generation and each column group (envelope,
+//! payload JSON, CloudTrail struct) per envelope source. Batches are dropped
+//! (`null` sink) or kept (`memory` sink), so no file I/O skews the numbers.
+//! Used by `seclog bench` to find the event rates a config can sustain before
+//! a load test.
+
+use crate::core::event::Event;
+use crate::core::traits::EventSource;
+use crate::formats::parquet::{build_schema, encode_payload, EventBatchBuilder};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::io;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Rows per encoded batch, matching the Parquet writer.
+const BATCH_ROWS: usize = 1024;
+
+/// Where encoded batches go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BenchSink {
+    /// Drops each batch once it is finished.
+    #[default]
+    Null,
+    /// Keeps every batch, to show the memory a run's output occupies.
+    Memory,
+}
+
+impl FromStr for BenchSink {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "null" => Ok(BenchSink::Null),
+            "memory" => Ok(BenchSink::Memory),
+            other => Err(format!(
+                "unknown bench sink {other:?} (expected null or memory)"
+            )),
+        }
+    }
+}
+
+/// Controls how long [`run_bench`] runs.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Wall-clock time to run.
+    pub duration: Duration,
+    /// Stops early after this many events.
+    pub max_events: Option<u64>,
+    pub sink: BenchSink,
+    /// Writes envelope and CloudTrail timestamps as strings, like `timestamp_strings`.
+    pub timestamp_strings: bool,
+}
+
+/// Time and allocations spent on one envelope source's events.
+#[derive(Debug, Clone, Default)]
+pub struct SourceBench {
+    pub events: u64,
+    /// Time inside `next_event` for calls that returned this source's events.
+    pub generate: Duration,
+    pub envelope: Duration,
+    /// Payload JSON serialization plus the `payload_json` column append.
+    pub payload: Duration,
+    pub cloudtrail: Duration,
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+impl SourceBench {
+    /// Time spent generating and encoding this source's events.
+    pub fn busy(&self) -> Duration {
+        self.generate + self.envelope + self.payload + self.cloudtrail
+    }
+
+    /// Events per second one thread sustains for this source alone.
+    pub fn events_per_second(&self) -> f64 {
+        rate(self.events, self.busy())
+    }
+}
+
+/// Results of a benchmark run.
+#[derive(Debug, Clone, Default)]
+pub struct BenchReport {
+    pub events: u64,
+    pub elapsed: Duration,
+    /// Time finishing record batches, shared by all sources.
+    pub batch_finish: Duration,
+    pub batches: u64,
+    /// Arrow memory held by batches at the end of a `memory` run.
+    pub retained_bytes: u64,
+    /// Keyed by `envelope.source`.
+    pub sources: BTreeMap<String, SourceBench>,
+    /// Whether allocation counts were collected; see [`CountingAllocator`].
+    pub counts_allocations: bool,
+}
+
+impl BenchReport {
+    /// Overall events per second, wall clock.
+    pub fn events_per_second(&self) -> f64 {
+        rate(self.events, self.elapsed)
+    }
+}
+
+/// Pulls events from `source` until the duration or event limit is reached
+/// or the source is exhausted, encoding each into Parquet record batches.
+pub fn run_bench(source: &mut dyn EventSource, options: &BenchOptions) -> io::Result<BenchReport> {
+    let mut builder = EventBatchBuilder::new(build_schema(options.timestamp_strings), BATCH_ROWS);
+    let mut retained = Vec::new();
+    let mut scratch = Vec::new();
+    let mut report = BenchReport {
+        counts_allocations: ALLOCATOR_INSTALLED.load(Ordering::Relaxed),
+        ..BenchReport::default()
+    };
+    COUNTING.store(true, Ordering::Relaxed);
+    let started = Instant::now();
+    let result = loop {
+        if started.elapsed() >= options.duration
+            || options.max_events.is_some_and(|max| report.events >= max)
+        {
+            break Ok(());
+        }
+        let before = AllocationCounts::now();
+        let generate_start = Instant::now();
+        let Some(event) = source.next_event() else {
+            break Ok(());
+        };
+        let generate = generate_start.elapsed();
+        let timings = encode(&mut builder, &mut scratch, &event);
+        let allocated = AllocationCounts::now().since(before);
+
+        let stats = report
+            .sources
+            .entry(event.envelope.source.clone())
+            .or_default();
+        stats.events += 1;
+        stats.generate += generate;
+        match timings {
+            Ok([envelope, payload, cloudtrail]) => {
+                stats.envelope += envelope;
+                stats.payload += payload;
+                stats.cloudtrail += cloudtrail;
+            }
+            Err(err) => break Err(err),
+        }
+        stats.allocations += allocated.allocations;
+        stats.allocated_bytes += allocated.bytes;
+        report.events += 1;
+
+        if builder.len() >= BATCH_ROWS {
+            if let Err(err) = finish_batch(&mut builder, &mut report, &mut retained, options.sink) {
+                break Err(err);
+            }
+        }
+    };
+    COUNTING.store(false, Ordering::Relaxed);
+    result?;
+    if builder.len() > 0 {
+        finish_batch(&mut builder, &mut report, &mut retained, options.sink)?;
+    }
+    report.elapsed = started.elapsed();
+    Ok(report)
+}
+
+/// Appends one row, returning the envelope, payload, and CloudTrail times.
+fn encode(
+    builder: &mut EventBatchBuilder,
+    scratch: &mut Vec<u8>,
+    event: &Event,
+) -> io::Result<[Duration; 3]> {
+    let start = Instant::now();
+    builder.append_envelope(&event.envelope);
+    let envelope = start.elapsed();
+
+    let start = Instant::now();
+    let payload_json = encode_payload(scratch, &event.payload)?;
+    builder.append_payload(payload_json);
+    let payload = start.elapsed();
+
+    let start = Instant::now();
+    builder.append_cloudtrail(event);
+    let cloudtrail = start.elapsed();
+    builder.end_row();
+    Ok([envelope, payload, cloudtrail])
+}
+
+fn finish_batch(
+    builder: &mut EventBatchBuilder,
+    report: &mut BenchReport,
+    retained: &mut Vec<arrow_array::RecordBatch>,
+    sink: BenchSink,
+) -> io::Result<()> {
+    let start = Instant::now();
+    let batch = builder.finish().map_err(io::Error::other)?;
+    report.batch_finish += start.elapsed();
+    report.batches += 1;
+    if sink == BenchSink::Memory {
+        report.retained_bytes += batch.get_array_memory_size() as u64;
+        retained.push(batch);
+    }
+    Ok(())
+}
+
+fn rate(events: u64, time: Duration) -> f64 {
+    if time.is_zero() {
+        return 0.0;
+    }
+    events as f64 / time.as_secs_f64()
+}
+
+static ALLOCATOR_INSTALLED: AtomicBool = AtomicBool::new(false);
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// System allocator that counts allocations while a benchmark runs.
+///
+/// Install it as the `#[global_allocator]` of a binary to get allocation
+/// columns from [`run_bench`]; outside a run it only checks one flag.
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Marks the allocator as installed; call once at startup.
+    pub fn install(&self) {
+        ALLOCATOR_INSTALLED.store(true, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+fn count(size: usize) {
+    if COUNTING.load(Ordering::Relaxed) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AllocationCounts {
+    allocations: u64,
+    bytes: u64,
+}
+
+impl AllocationCounts {
+    fn now() -> Self {
+        Self {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    fn since(self, before: Self) -> Self {
+        Self {
+            allocations: self.allocations - before.allocations,
+            bytes: self.bytes - before.bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    struct Counter(u64);
+
+    impl EventSource for Counter {
+        fn next_event(&mut self) -> Option<Event> {
+            if self.0 == 0 {
+                return None;
+            }
+            self.0 -= 1;
+            let source = if self.0.is_multiple_of(2) {
+                "cloudtrail"
+            } else {
+                "okta"
+            };
+            Some(test_event(
+                source,
+                "test",
+                json!({ "eventName": "GetObject" }),
+            ))
+        }
+    }
+
+    #[test]
+    fn attributes_events_to_sources_and_keeps_memory_batches() {
+        let options = BenchOptions {
+            duration: Duration::from_secs(60),
+            max_events: None,
+            sink: BenchSink::Memory,
+            timestamp_strings: false,
+        };
+        let report = run_bench(&mut Counter(3_000), &options).expect("bench");
+        assert_eq!(report.events, 3_000);
+        assert_eq!(report.batches, 3);
+        assert!(report.retained_bytes > 0);
+        assert_eq!(report.sources["cloudtrail"].events, 1_500);
+        assert_eq!(report.sources["okta"].events, 1_500);
+        assert!(report.sources["okta"].events_per_second() > 0.0);
+    }
+
+    #[test]
+    fn stops_at_the_event_limit() {
+        let options = BenchOptions {
+            duration: Duration::from_secs(60),
+            max_events: Some(10),
+            sink: BenchSink::Null,
+            timestamp_strings: false,
+        };
+        let report = run_bench(&mut Counter(100), &options).expect("bench");
+        assert_eq!(report.events, 10);
+        assert_eq!(report.retained_bytes, 0);
+        assert_eq!("memory".parse::<BenchSink>(), Ok(BenchSink::Memory));
+    }
+}
@@ -0,0 +1,267 @@
+//! Operator control of a running `seclog gen`.
+//!
+//! The CLI polls a shared [`Control`] between events; [`serve`] answers plain
+//! HTTP requests on a loopback listener so long soak tests can be paused,
+//! resumed, sped up or slowed down, and flushed without restarting the run.
+
+use serde_json::json;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Largest rate multiplier the endpoint accepts.
+pub const MAX_RATE_MULTIPLIER: f64 = 1000.0;
+
+/// Requests shared between the control endpoint and the generation loop.
+#[derive(Debug)]
+pub struct Control {
+    paused: AtomicBool,
+    flush_requested: AtomicBool,
+    rate_multiplier: AtomicU64,
+    lock: Mutex<()>,
+    resumed: Condvar,
+}
+
+impl Default for Control {
+    fn default() -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            flush_requested: AtomicBool::new(false),
+            rate_multiplier: AtomicU64::new(1.0_f64.to_bits()),
+            lock: Mutex::new(()),
+            resumed: Condvar::new(),
+        }
+    }
+}
+
+impl Control {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pauses generation and asks for a flush, so a paused run leaves its
+    /// events on disk.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+        self.request_flush();
+    }
+
+    pub fn resume(&self) {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.paused.store(false, Ordering::Relaxed);
+        self.resumed.notify_all();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Blocks until the run is resumed and returns how long it waited.
+    pub fn wait_while_paused(&self) -> Duration {
+        let started = Instant::now();
+        let mut guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while self.is_paused() {
+            guard = self
+                .resumed
+                .wait_timeout(guard, Duration::from_secs(1))
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+        started.elapsed()
+    }
+
+    /// Multiplier applied to the paced rate; 1 keeps the configured rate.
+    pub fn rate_multiplier(&self) -> f64 {
+        f64::from_bits(self.rate_multiplier.load(Ordering::Relaxed))
+    }
+
+    pub fn set_rate_multiplier(&self, multiplier: f64) -> Result<(), String> {
+        if !multiplier.is_finite() || multiplier <= 0.0 || multiplier > MAX_RATE_MULTIPLIER {
+            return Err(format!(
+                "multiplier must be greater than 0 and at most {MAX_RATE_MULTIPLIER}"
+            ));
+        }
+        self.rate_multiplier
+            .store(multiplier.to_bits(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn request_flush(&self) {
+        self.flush_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether a flush was requested since the last call.
+    pub fn take_flush(&self) -> bool {
+        self.flush_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// Current state as the JSON body the endpoint returns.
+    pub fn status(&self) -> String {
+        json!({
+            "paused": self.is_paused(),
+            "rate_multiplier": self.rate_multiplier(),
+            "flush_pending": self.flush_requested.load(Ordering::Relaxed),
+        })
+        .to_string()
+    }
+}
+
+/// Starts a background listener that applies requests to `control`.
+///
+/// `listen` must be a loopback address, since the endpoint has no
+/// authentication. Returns the bound address, which is useful when `listen`
+/// uses port 0.
+pub fn serve(listen: &str, control: Arc<Control>) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(listen)?;
+    let addr = listener.local_addr()?;
+    if !addr.ip().is_loopback() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("control endpoint must listen on a loopback address, got {addr}"),
+        ));
+    }
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let _ = respond(stream, &control);
+        }
+    });
+    Ok(addr)
+}
+
+fn respond(mut stream: TcpStream, control: &Control) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request = Vec::new();
+    let mut buf = [0_u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < 16 * 1024 {
+        let read = stream.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut words = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    let method = words.next().unwrap_or_default();
+    let target = words.next().unwrap_or_default();
+    let (status, body) = match handle(control, method, target) {
+        Ok(()) => ("200 OK", control.status()),
+        Err((status, message)) => (status, json!({ "error": message }).to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+fn handle(control: &Control, method: &str, target: &str) -> Result<(), (&'static str, String)> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let expected = if path == "/status" { "GET" } else { "POST" };
+    if !matches!(path, "/status" | "/pause" | "/resume" | "/rate" | "/flush") {
+        return Err(("404 Not Found", format!("unknown path {path}")));
+    }
+    if method != expected {
+        return Err((
+            "405 Method Not Allowed",
+            format!("{path} expects {expected}"),
+        ));
+    }
+    match path {
+        "/pause" => control.pause(),
+        "/resume" => control.resume(),
+        "/flush" => control.request_flush(),
+        "/rate" => {
+            let multiplier = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("multiplier="))
+                .ok_or_else(|| {
+                    (
+                        "400 Bad Request",
+                        "missing multiplier parameter".to_string(),
+                    )
+                })?;
+            let multiplier = multiplier.parse::<f64>().map_err(|_| {
+                (
+                    "400 Bad Request",
+                    format!("invalid multiplier {multiplier:?}"),
+                )
+            })?;
+            control
+                .set_rate_multiplier(multiplier)
+                .map_err(|message| ("400 Bad Request", message))?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(addr: SocketAddr, request_line: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "{request_line} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn controls_a_run_over_http() {
+        let control = Arc::new(Control::new());
+        let addr = serve("127.0.0.1:0", Arc::clone(&control)).unwrap();
+
+        let response = send(addr, "POST /pause");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("\"paused\":true"));
+        assert!(control.is_paused());
+        assert!(control.take_flush());
+        assert!(!control.take_flush());
+
+        let response = send(addr, "POST /rate?multiplier=2.5");
+        assert!(response.contains("\"rate_multiplier\":2.5"));
+        assert!(send(addr, "POST /rate?multiplier=0").starts_with("HTTP/1.1 400 "));
+        assert!(send(addr, "POST /rate").starts_with("HTTP/1.1 400 "));
+        assert_eq!(control.rate_multiplier(), 2.5);
+
+        let waiter = {
+            let control = Arc::clone(&control);
+            thread::spawn(move || control.wait_while_paused())
+        };
+        thread::sleep(Duration::from_millis(50));
+        send(addr, "POST /resume");
+        assert!(waiter.join().unwrap() >= Duration::from_millis(50));
+        assert!(!control.is_paused());
+
+        send(addr, "POST /flush");
+        assert!(control.take_flush());
+        assert!(send(addr, "GET /status").contains("\"paused\":false"));
+        assert!(send(addr, "GET /pause").starts_with("HTTP/1.1 405 "));
+        assert!(send(addr, "POST /stop").starts_with("HTTP/1.1 404 "));
+    }
+
+    #[test]
+    fn refuses_non_loopback_addresses() {
+        let err = serve("0.0.0.0:0", Arc::new(Control::new())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -4,18 +4,31 @@
 //! population model without requiring each source to recreate CloudTrail's
 //! actor scheduler.
 
+use crate::core::actors::OnCallShift;
+use crate::core::arrival::Arrivals;
+use crate::core::config::WorkdayConfig;
 use crate::core::identity::Identity;
 use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
 
 const MIN_RATE_PER_HOUR: f64 = 0.001;
+const DEFAULT_RAMP_UP_FROM: f64 = 0.55;
+const DEFAULT_WIND_DOWN: [f64; 2] = [0.9, 0.4];
+const DEFAULT_LUNCH_HOUR: u8 = 12;
+const DEFAULT_LUNCH_DIP: [f64; 2] = [0.4, 0.75];
+const DEFAULT_MEETING_SHARE: f64 = 0.12;
+const DEFAULT_MEETING_FACTOR: f64 = 0.2;
+const DEFAULT_WORKING_FACTOR: f64 = 1.3;
+const LINKED_SESSION_SALT: &str = "linked_session";
+const IDENTITY_PROVIDER_SOURCES: &[&str] = &["okta_system_log"];
 
 /// Returns the first scheduled baseline event time for an identity.
 pub fn first_identity_event_at(
     identity: &Identity,
     start_time: DateTime<Utc>,
     source_salt: &str,
+    arrivals: &Arrivals,
 ) -> DateTime<Utc> {
-    next_identity_event_after(identity, start_time, 0, source_salt)
+    next_identity_event_after(identity, start_time, 0, source_salt, arrivals)
 }
 
 /// Returns the next scheduled baseline event after the supplied timestamp.
@@ -24,9 +37,71 @@ pub fn next_identity_event_after(
     after: DateTime<Utc>,
     sequence: usize,
     source_salt: &str,
+    arrivals: &Arrivals,
 ) -> DateTime<Utc> {
-    let rate = effective_rate_per_hour(identity, after, sequence, source_salt);
-    after + deterministic_interval(identity, source_salt, sequence, rate)
+    let rate = effective_rate_per_hour(identity, after, sequence, source_salt, arrivals);
+    after + deterministic_interval(identity, source_salt, sequence, rate, arrivals)
+}
+
+/// Positions of a source's identities on their shared cross-source clocks.
+///
+/// Every linked source walks the same underlying activity instants for an
+/// identity, shifted by a stable per-source lag so identity-provider sign-ins
+/// lead the downstream application activity they authorize. Linked sources
+/// only share instants while their `arrival` settings match.
+///
+/// `correlation` is the share of instants every linked source keeps. The
+/// choice is made per identity and instant, not per source, so with `0.5`
+/// half of an actor's Okta sign-ins are followed by the matching Databricks
+/// row. The remaining events land at a source-specific point between the
+/// previous shared instant and this one, keeping each source's volume and
+/// daily shape while their timing no longer lines up.
+#[derive(Debug, Clone)]
+pub struct LinkedSessionClock {
+    correlation: f64,
+    shared: Vec<DateTime<Utc>>,
+}
+
+impl LinkedSessionClock {
+    /// Starts `identities` clocks at `start_time`.
+    pub fn new(identities: usize, start_time: DateTime<Utc>, correlation: f64) -> Self {
+        Self {
+            correlation: correlation.clamp(0.0, 1.0),
+            shared: vec![start_time; identities],
+        }
+    }
+
+    /// Advances identity `idx` to its next shared instant and returns when
+    /// this source emits the event for it; `sequence` counts the identity's
+    /// events from zero.
+    pub fn next_event_at(
+        &mut self,
+        idx: usize,
+        identity: &Identity,
+        sequence: usize,
+        source_salt: &str,
+        arrivals: &Arrivals,
+    ) -> DateTime<Utc> {
+        let previous = self.shared[idx];
+        let instant =
+            next_identity_event_after(identity, previous, sequence, LINKED_SESSION_SALT, arrivals);
+        self.shared[idx] = instant;
+
+        let lag = linked_source_lag(identity, source_salt);
+        let kept = stable_hash(&format!(
+            "{}:{LINKED_SESSION_SALT}:correlated:{sequence}",
+            identity.actor_id
+        ));
+        if unit_interval(kept) <= self.correlation {
+            return instant + lag;
+        }
+        let placement = stable_hash(&format!(
+            "{}:{source_salt}:uncorrelated:{sequence}",
+            identity.actor_id
+        ));
+        let gap_ms = (instant - previous).num_milliseconds() as f64;
+        previous + lag + Duration::milliseconds((gap_ms * unit_interval(placement)) as i64)
+    }
 }
 
 /// Returns the configured or inferred UTC offset for identity local-time logic.
@@ -79,6 +154,130 @@ pub fn identity_in_active_window(identity: &Identity, now: DateTime<Utc>) -> boo
     within_active_hour(identity, local.hour() as u8)
 }
 
+/// Intra-day curve of a human's rate inside the active window.
+///
+/// The rate ramps up over the first hour, dips over the local lunch hour,
+/// tails off over the last hour, and drops in meetings: about one working
+/// half-hour in eight by default, chosen per actor and day by a stable hash.
+/// The remaining hours run above the flat rate, so a working day keeps
+/// roughly its volume.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workday {
+    ramp_up_from: f64,
+    wind_down: [f64; 2],
+    lunch_hour: u8,
+    lunch_dip: [f64; 2],
+    meeting_share: f64,
+    meeting_factor: f64,
+    working_factor: f64,
+}
+
+impl Default for Workday {
+    fn default() -> Self {
+        Self {
+            ramp_up_from: DEFAULT_RAMP_UP_FROM,
+            wind_down: DEFAULT_WIND_DOWN,
+            lunch_hour: DEFAULT_LUNCH_HOUR,
+            lunch_dip: DEFAULT_LUNCH_DIP,
+            meeting_share: DEFAULT_MEETING_SHARE,
+            meeting_factor: DEFAULT_MEETING_FACTOR,
+            working_factor: DEFAULT_WORKING_FACTOR,
+        }
+    }
+}
+
+impl Workday {
+    pub fn from_config(config: Option<&WorkdayConfig>) -> Result<Self, String> {
+        let Some(config) = config else {
+            return Ok(Self::default());
+        };
+        let workday = Self {
+            ramp_up_from: config.ramp_up_from.unwrap_or(DEFAULT_RAMP_UP_FROM),
+            wind_down: config.wind_down.unwrap_or(DEFAULT_WIND_DOWN),
+            lunch_hour: config.lunch_hour.unwrap_or(DEFAULT_LUNCH_HOUR),
+            lunch_dip: config.lunch_dip.unwrap_or(DEFAULT_LUNCH_DIP),
+            meeting_share: config.meeting_share.unwrap_or(DEFAULT_MEETING_SHARE),
+            meeting_factor: config.meeting_factor.unwrap_or(DEFAULT_MEETING_FACTOR),
+            working_factor: config.working_factor.unwrap_or(DEFAULT_WORKING_FACTOR),
+        };
+        let factors = [
+            ("workday.ramp_up_from", workday.ramp_up_from),
+            ("workday.wind_down", workday.wind_down[0]),
+            ("workday.wind_down", workday.wind_down[1]),
+            ("workday.lunch_dip", workday.lunch_dip[0]),
+            ("workday.lunch_dip", workday.lunch_dip[1]),
+            ("workday.meeting_factor", workday.meeting_factor),
+            ("workday.working_factor", workday.working_factor),
+        ];
+        if let Some((name, factor)) = factors
+            .into_iter()
+            .find(|(_, factor)| !factor.is_finite() || *factor < 0.0)
+        {
+            return Err(format!(
+                "{name} must be a non-negative factor, got {factor}"
+            ));
+        }
+        if workday.lunch_hour >= 24 {
+            return Err(format!(
+                "workday.lunch_hour must be below 24, got {}",
+                workday.lunch_hour
+            ));
+        }
+        if !(0.0..=1.0).contains(&workday.meeting_share) {
+            return Err(format!(
+                "workday.meeting_share must be between 0 and 1, got {}",
+                workday.meeting_share
+            ));
+        }
+        Ok(workday)
+    }
+
+    /// Returns the factor on a human's rate at local time `local`. Outside
+    /// the active window, and for around-the-clock windows, the factor is 1.
+    pub fn multiplier(
+        &self,
+        actor_key: &str,
+        local: DateTime<Utc>,
+        active_start_hour: u8,
+        active_hours: u8,
+    ) -> f64 {
+        if active_hours == 0 || active_hours >= 24 {
+            return 1.0;
+        }
+        let minute_of_day = local.hour() * 60 + local.minute();
+        let elapsed =
+            (minute_of_day as i64 - i64::from(active_start_hour) * 60).rem_euclid(1440) as f64;
+        let window = f64::from(active_hours) * 60.0;
+        if elapsed >= window {
+            return 1.0;
+        }
+        if elapsed < 60.0 {
+            return self.ramp_up_from + (1.0 - self.ramp_up_from) * elapsed / 60.0;
+        }
+        if elapsed >= window - 60.0 {
+            let [start, end] = self.wind_down;
+            return start + (end - start) * (elapsed - (window - 60.0)) / 60.0;
+        }
+        let lunch = u32::from(self.lunch_hour) * 60;
+        if (lunch..lunch + 60).contains(&minute_of_day) {
+            return self.lunch_dip[0];
+        }
+        if (lunch + 60..lunch + 90).contains(&minute_of_day) {
+            return self.lunch_dip[1];
+        }
+        let meeting = stable_hash(&format!(
+            "{actor_key}:meeting:{}:{}",
+            local.date_naive(),
+            minute_of_day / 30
+        ));
+        if ((meeting % 100) as f64) < self.meeting_share * 100.0 {
+            self.meeting_factor
+        } else {
+            self.working_factor
+        }
+    }
+}
+
 /// Returns the positive base actor rate for an identity.
 pub fn identity_base_rate_per_hour(identity: &Identity) -> f64 {
     identity
@@ -92,12 +291,13 @@ fn effective_rate_per_hour(
     now: DateTime<Utc>,
     sequence: usize,
     source_salt: &str,
+    arrivals: &Arrivals,
 ) -> f64 {
     let base = identity_base_rate_per_hour(identity);
     let multiplier = if identity.service_account {
-        service_multiplier(identity, now, sequence, source_salt)
+        service_multiplier(identity, now, sequence, source_salt, arrivals)
     } else {
-        human_multiplier(identity, now)
+        human_multiplier(identity, now, arrivals)
     };
     (base * multiplier).max(MIN_RATE_PER_HOUR)
 }
@@ -118,17 +318,28 @@ fn default_rate_for_identity(identity: &Identity) -> f64 {
     }
 }
 
-fn human_multiplier(identity: &Identity, now: DateTime<Utc>) -> f64 {
+fn human_multiplier(identity: &Identity, now: DateTime<Utc>, arrivals: &Arrivals) -> f64 {
     let local = local_time(identity, now);
     let weekend = is_weekend_date(local.date_naive());
     let active_hour = within_active_hour(identity, local.hour() as u8);
-    match (weekend, identity_weekend_active(identity), active_hour) {
-        (true, false, _) => 0.035,
+    let off_hours = !active_hour || (weekend && !identity_weekend_active(identity));
+    if off_hours && identity_on_call(identity, local) {
+        return 1.8 * OnCallShift::OFF_HOURS_RATE;
+    }
+    let multiplier = match (weekend, identity_weekend_active(identity), active_hour) {
+        (true, false, _) => return 0.035,
         (true, true, true) => 0.65,
         (true, true, false) => 0.10,
         (false, _, true) => 1.8,
         (false, _, false) => 0.12,
-    }
+    };
+    multiplier
+        * arrivals.workday().multiplier(
+            &identity.actor_id,
+            local,
+            identity_active_start_hour(identity),
+            identity_active_hours(identity),
+        )
 }
 
 fn service_multiplier(
@@ -136,6 +347,7 @@ fn service_multiplier(
     now: DateTime<Utc>,
     sequence: usize,
     source_salt: &str,
+    arrivals: &Arrivals,
 ) -> f64 {
     match identity
         .service_pattern
@@ -145,7 +357,7 @@ fn service_multiplier(
         .as_str()
     {
         "diurnal" => diurnal_multiplier(identity, now),
-        "bursty" => burst_multiplier(identity, sequence, source_salt),
+        "bursty" => burst_multiplier(identity, sequence, source_salt, arrivals),
         _ => 1.0,
     }
 }
@@ -160,24 +372,41 @@ fn diurnal_multiplier(identity: &Identity, now: DateTime<Utc>) -> f64 {
     }
 }
 
-fn burst_multiplier(identity: &Identity, sequence: usize, source_salt: &str) -> f64 {
+fn burst_multiplier(
+    identity: &Identity,
+    sequence: usize,
+    source_salt: &str,
+    arrivals: &Arrivals,
+) -> f64 {
     let hash = stable_hash(&format!(
         "{}:{source_salt}:burst:{}",
         identity.actor_id, sequence
     ));
     let unit = unit_interval(hash);
-    if hash % 100 < 12 {
-        2.0 + unit * 3.0
+    if ((hash % 100) as f64) < arrivals.burst_probability() * 100.0 {
+        let burst = arrivals.burst_range();
+        burst.start + unit * (burst.end - burst.start)
     } else {
         0.4 + unit * 0.6
     }
 }
 
+fn linked_source_lag(identity: &Identity, source_salt: &str) -> Duration {
+    let hash = stable_hash(&format!("{}:{source_salt}:linked_lag", identity.actor_id));
+    let (min_ms, spread_ms) = if IDENTITY_PROVIDER_SOURCES.contains(&source_salt) {
+        (0, 5_000)
+    } else {
+        (30_000, 150_000)
+    };
+    Duration::milliseconds(min_ms + (hash % spread_ms) as i64)
+}
+
 fn deterministic_interval(
     identity: &Identity,
     source_salt: &str,
     sequence: usize,
     rate_per_hour: f64,
+    arrivals: &Arrivals,
 ) -> Duration {
     let rate = rate_per_hour.max(MIN_RATE_PER_HOUR);
     let hash = stable_hash(&format!(
@@ -185,8 +414,7 @@ fn deterministic_interval(
         identity.actor_id
     ));
     let unit = unit_interval(hash);
-    let lambda = rate / 3600.0;
-    let secs = -unit.ln() / lambda;
+    let secs = arrivals.interval_secs(rate, unit);
     Duration::milliseconds((secs * 1000.0).round().clamp(1.0, i64::MAX as f64) as i64)
 }
 
@@ -223,6 +451,14 @@ fn identity_active_hours(identity: &Identity) -> u8 {
         .unwrap_or(if identity.service_account { 24 } else { 10 })
 }
 
+fn identity_on_call(identity: &Identity, local: DateTime<Utc>) -> bool {
+    identity
+        .on_call
+        .as_deref()
+        .and_then(OnCallShift::parse)
+        .is_some_and(|shift| shift.covers(local.date_naive()))
+}
+
 fn identity_weekend_active(identity: &Identity) -> bool {
     identity.weekend_active.unwrap_or(identity.service_account)
 }
@@ -271,6 +507,7 @@ mod tests {
     #[test]
     fn generated_schedule_prefers_local_active_hours() {
         let identity = identity("sg", &["Singapore"], Some(8));
+        let arrivals = Arrivals::default();
         let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
             .unwrap()
             .with_timezone(&Utc);
@@ -278,7 +515,7 @@ mod tests {
         let mut active = 0;
         let mut off_hours = 0;
         for sequence in 0..300 {
-            current = next_identity_event_after(&identity, current, sequence, "test");
+            current = next_identity_event_after(&identity, current, sequence, "test", &arrivals);
             if identity_in_active_window(&identity, current) {
                 active += 1;
             } else {
@@ -289,6 +526,124 @@ mod tests {
         assert!(active > off_hours * 3, "active={active} off={off_hours}");
     }
 
+    #[test]
+    fn human_activity_dips_at_lunch_and_ramps_in_the_morning() {
+        let at = |time: &str| {
+            DateTime::parse_from_rfc3339(time)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        let workday = Workday::default();
+        assert!(workday.multiplier("a", at("2026-01-05T08:10:00Z"), 8, 10) < 0.7);
+        assert_eq!(
+            workday.multiplier("a", at("2026-01-05T12:20:00Z"), 8, 10),
+            0.4
+        );
+        assert!(workday.multiplier("a", at("2026-01-05T17:50:00Z"), 8, 10) < 0.5);
+        assert_eq!(
+            workday.multiplier("a", at("2026-01-05T20:00:00Z"), 8, 10),
+            1.0
+        );
+        assert_eq!(
+            workday.multiplier("a", at("2026-01-05T12:20:00Z"), 0, 24),
+            1.0
+        );
+        let late_lunch = Workday::from_config(Some(&WorkdayConfig {
+            lunch_hour: Some(13),
+            lunch_dip: Some([0.1, 0.5]),
+            ..WorkdayConfig::default()
+        }))
+        .unwrap();
+        assert_eq!(
+            late_lunch.multiplier("a", at("2026-01-05T13:20:00Z"), 8, 10),
+            0.1
+        );
+        assert_eq!(
+            late_lunch.multiplier("a", at("2026-01-05T14:10:00Z"), 8, 10),
+            0.5
+        );
+
+        let identity = identity("ldn", &["United Kingdom"], Some(0));
+        let arrivals = Arrivals::default();
+        let mut current = at("2026-01-05T00:00:00Z");
+        let mut by_hour = [0_u32; 24];
+        for sequence in 0..6000 {
+            current = next_identity_event_after(&identity, current, sequence, "test", &arrivals);
+            if identity_in_active_window(&identity, current) {
+                by_hour[current.hour() as usize] += 1;
+            }
+        }
+        let midmorning = by_hour[10] + by_hour[11];
+        assert!(by_hour[12] * 3 < midmorning, "{by_hour:?}");
+        assert!(by_hour[8] * 2 < midmorning, "{by_hour:?}");
+    }
+
+    #[test]
+    fn linked_sources_share_session_clock_with_idp_leading() {
+        let identity = identity("sg", &["Singapore"], Some(8));
+        let arrivals = Arrivals::default();
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut okta_clock = LinkedSessionClock::new(1, start, 1.0);
+        let mut databricks_clock = LinkedSessionClock::new(1, start, 1.0);
+        for sequence in 0..50 {
+            let okta =
+                okta_clock.next_event_at(0, &identity, sequence, "okta_system_log", &arrivals);
+            let databricks = databricks_clock.next_event_at(
+                0,
+                &identity,
+                sequence,
+                "databricks_audit",
+                &arrivals,
+            );
+            let gap = databricks - okta;
+            assert!(
+                gap >= Duration::seconds(25) && gap <= Duration::seconds(180),
+                "sequence={sequence} gap={gap}"
+            );
+        }
+    }
+
+    #[test]
+    fn correlation_sets_share_of_aligned_sessions() {
+        let identity = identity("sg", &["Singapore"], Some(8));
+        let arrivals = Arrivals::default();
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        // Aligned sessions keep the fixed lag between the two sources.
+        let lag = linked_source_lag(&identity, "databricks_audit")
+            - linked_source_lag(&identity, "okta_system_log");
+        let aligned_share = |correlation: f64| {
+            let mut okta_clock = LinkedSessionClock::new(1, start, correlation);
+            let mut databricks_clock = LinkedSessionClock::new(1, start, correlation);
+            let (mut aligned, mut last_databricks) = (0, start);
+            for sequence in 0..400 {
+                let okta =
+                    okta_clock.next_event_at(0, &identity, sequence, "okta_system_log", &arrivals);
+                let databricks = databricks_clock.next_event_at(
+                    0,
+                    &identity,
+                    sequence,
+                    "databricks_audit",
+                    &arrivals,
+                );
+                assert!(databricks >= last_databricks, "sequence={sequence}");
+                last_databricks = databricks;
+                if databricks - okta == lag {
+                    aligned += 1;
+                }
+            }
+            aligned as f64 / 400.0
+        };
+
+        assert!(aligned_share(0.0) < 0.05, "{}", aligned_share(0.0));
+        let half = aligned_share(0.5);
+        assert!((0.4..0.6).contains(&half), "{half}");
+        assert_eq!(aligned_share(1.0), 1.0);
+    }
+
     fn identity(actor_id: &str, regions: &[&str], offset: Option<i8>) -> Identity {
         Identity {
             actor_id: actor_id.to_string(),
@@ -317,6 +672,8 @@ mod tests {
             timezone_offset: offset,
             weekend_active: Some(false),
             service_pattern: None,
+            insider_persona: None,
+            on_call: None,
         }
     }
 }
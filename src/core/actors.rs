@@ -1,15 +1,19 @@
 use crate::config::{
-    ErrorRateConfig, ErrorRateDistribution, ExplicitActorConfig, PopulationActorsConfig,
-    PopulationConfig, RoleConfig, ServicePatternConfig, ServiceProfileConfig, TimezoneWeight,
+    ErrorRateConfig, ErrorRateDistribution, ExplicitActorConfig, InsiderPersonaConfig,
+    OnCallConfig, PopulationActorsConfig, PopulationConfig, RoleConfig, ServicePatternConfig,
+    ServiceProfileConfig, TimezoneWeight,
 };
+use crate::core::ip_plan::IpPlan;
 use chrono::{offset::Offset, DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
 use chrono_tz::Tz;
 use rand::distributions::{Distribution, WeightedIndex};
 use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use rand::SeedableRng;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::Arc;
 
 /// High-level actor type used for session behavior and weighting.
 #[derive(Debug, Clone)]
@@ -36,6 +40,13 @@ pub enum ServiceProfile {
     MetricsCollector,
 }
 
+impl ServiceProfile {
+    /// Parses a config name such as `ec2_reaper` or `logs-shipper`.
+    pub fn parse(value: &str) -> Option<Self> {
+        normalize_profile_name(value)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ServicePattern {
     Constant,
@@ -43,6 +54,84 @@ pub enum ServicePattern {
     Bursty,
 }
 
+/// Insider-threat persona layered on an actor.
+///
+/// Personas bias event selection and timing without changing the actor's
+/// role, so the actor looks ordinary in any single session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsiderPersona {
+    /// Reads far more objects than peers, growing over weeks.
+    DataHoarder,
+    /// Works late nights on a share of days, mostly on admin actions.
+    OffHoursAdmin,
+    /// Gradually grants itself policies and access keys.
+    PermissionAccumulator,
+}
+
+impl InsiderPersona {
+    pub const ALL: [InsiderPersona; 3] = [
+        InsiderPersona::DataHoarder,
+        InsiderPersona::OffHoursAdmin,
+        InsiderPersona::PermissionAccumulator,
+    ];
+
+    /// Name used in configs and the actor Parquet `insider_persona` column.
+    pub fn key(self) -> &'static str {
+        match self {
+            InsiderPersona::DataHoarder => "data_hoarder",
+            InsiderPersona::OffHoursAdmin => "off_hours_admin",
+            InsiderPersona::PermissionAccumulator => "permission_accumulator",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|persona| persona.key() == value)
+    }
+}
+
+/// A human's place in an on-call rotation.
+///
+/// Members of a rotation take turns in `shift_days`-long shifts; shifts hand
+/// over at local midnight, on Mondays for weekly rotations. While on call the
+/// actor can be active around the clock, weekends included, and works mostly
+/// on read and incident-response calls outside its regular window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnCallShift {
+    /// Shift index within the rotation, from 0.
+    pub slot: u32,
+    /// Number of members in the rotation.
+    pub rotation: u32,
+    pub shift_days: u32,
+}
+
+impl OnCallShift {
+    /// Rate factor for on-call activity outside the regular window.
+    pub const OFF_HOURS_RATE: f64 = 0.25;
+
+    /// Returns whether the actor is on call on local `date`.
+    pub fn covers(&self, date: chrono::NaiveDate) -> bool {
+        let shift = (date.num_days_from_ce() - 1).div_euclid(self.shift_days.max(1) as i32);
+        shift.rem_euclid(self.rotation.max(1) as i32) == self.slot as i32
+    }
+
+    /// Encodes the shift as `slot/rotation/shift_days`, e.g. `1/4/7`, for the
+    /// actor Parquet `on_call` column and identity registries.
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.slot, self.rotation, self.shift_days)
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().splitn(3, '/').map(str::parse::<u32>);
+        let shift = Self {
+            slot: parts.next()?.ok()?,
+            rotation: parts.next()?.ok()?,
+            shift_days: parts.next()?.ok()?,
+        };
+        (shift.slot < shift.rotation && shift.shift_days > 0).then_some(shift)
+    }
+}
+
 #[derive(Debug)]
 pub struct ActorConfigError(pub String);
 
@@ -71,6 +160,8 @@ pub struct ActorSeed {
     pub event_bias: HashMap<String, f64>,
     pub service_profile: Option<ServiceProfile>,
     pub service_pattern: Option<ServicePattern>,
+    pub insider_persona: Option<InsiderPersona>,
+    pub on_call: Option<OnCallShift>,
     pub user_name: Option<String>,
     pub display_name: Option<String>,
     pub email: Option<String>,
@@ -85,11 +176,47 @@ pub struct ActorSeed {
     pub weekend_active: bool,
 }
 
+/// How the actor's current session signed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionOrigin {
+    /// Started with a console sign-in; calls come from the browser session.
+    Console { mfa_authenticated: bool },
+    /// Access keys or temporary credentials used from the CLI or an SDK.
+    Programmatic,
+}
+
+/// An actor's identity strings, shared by every event it emits so building
+/// an event clones reference counts rather than strings.
+#[derive(Debug, Clone)]
+pub struct SharedIdentity {
+    pub identity_type: Arc<str>,
+    pub principal_id: Arc<str>,
+    pub arn: Arc<str>,
+    pub account_id: Arc<str>,
+    pub access_key_id: Arc<str>,
+    pub user_name: Option<Arc<str>>,
+}
+
+impl SharedIdentity {
+    pub fn from_seed(seed: &ActorSeed) -> Self {
+        Self {
+            identity_type: seed.identity_type.as_str().into(),
+            principal_id: seed.principal_id.as_str().into(),
+            arn: seed.arn.as_str().into(),
+            account_id: seed.account_id.as_str().into(),
+            access_key_id: seed.access_key_id.as_str().into(),
+            user_name: seed.user_name.as_deref().map(Arc::from),
+        }
+    }
+}
+
 /// Mutable runtime state for an actor across event generation.
 #[derive(Debug, Clone)]
 pub struct ActorProfile {
     /// Stable actor attributes.
     pub seed: ActorSeed,
+    /// The seed's identity strings, interned when the profile is built.
+    pub identity: SharedIdentity,
     /// Previous event name for sequence-aware selection.
     pub last_event: Option<String>,
     /// Remaining events in the current session.
@@ -102,12 +229,15 @@ pub struct ActorProfile {
     pub session_user_agent: Option<String>,
     /// Sticky source IP for the current session.
     pub session_source_ip: Option<String>,
+    /// How the current session signed in; set by its first event.
+    pub session_origin: Option<SessionOrigin>,
 }
 
 impl ActorProfile {
     /// Builds a fresh profile from a seed with no active session.
     pub fn from_seed(seed: ActorSeed) -> Self {
         Self {
+            identity: SharedIdentity::from_seed(&seed),
             seed,
             last_event: None,
             session_remaining: 0,
@@ -115,6 +245,7 @@ impl ActorProfile {
             next_session_at: None,
             session_user_agent: None,
             session_source_ip: None,
+            session_origin: None,
         }
     }
 
@@ -123,14 +254,8 @@ impl ActorProfile {
     /// This updates session boundaries when a session ends. Session state affects
     /// event context, while event cadence comes from the actor rate scheduler.
     pub fn is_available(&mut self, now: DateTime<Utc>, rng: &mut impl Rng) -> bool {
-        if let Some(end) = self.session_end_at {
-            if now >= end {
-                self.session_end_at = None;
-                self.last_event = None;
-                self.session_remaining = 0;
-                self.session_user_agent = None;
-                self.session_source_ip = None;
-            }
+        if self.session_end_at.is_some_and(|end| now >= end) {
+            self.end_session();
         }
 
         if !within_active_window(&self.seed, now) {
@@ -140,6 +265,16 @@ impl ActorProfile {
         true
     }
 
+    /// Ends the current session, so the next event starts a new one.
+    pub fn end_session(&mut self) {
+        self.session_end_at = None;
+        self.last_event = None;
+        self.session_remaining = 0;
+        self.session_user_agent = None;
+        self.session_source_ip = None;
+        self.session_origin = None;
+    }
+
     /// Starts or resumes a session if needed and chooses session-level UA/IP.
     pub fn ensure_session(&mut self, now: DateTime<Utc>, rng: &mut impl Rng) {
         if let Some(next) = self.next_session_at {
@@ -154,6 +289,7 @@ impl ActorProfile {
             self.session_end_at = Some(now + Duration::minutes(minutes));
             self.session_user_agent = Some(self.pick_user_agent(rng));
             self.session_source_ip = Some(self.pick_source_ip(rng));
+            self.session_origin = None;
         }
 
         if self.session_remaining == 0 {
@@ -191,6 +327,47 @@ impl ActorProfile {
             .unwrap_or_else(|| "0.0.0.0".to_string())
     }
 
+    /// Marks the session as a console session, switching to one of the actor's
+    /// browser user agents so every call in it looks like the same browser.
+    pub fn start_console_session(&mut self, mfa_authenticated: bool) {
+        if !self
+            .session_user_agent
+            .as_deref()
+            .is_some_and(is_browser_user_agent)
+        {
+            let browser = self
+                .seed
+                .user_agents
+                .iter()
+                .find(|agent| is_browser_user_agent(agent))
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_BROWSER_USER_AGENT.to_string());
+            self.session_user_agent = Some(browser);
+        }
+        self.session_origin = Some(SessionOrigin::Console { mfa_authenticated });
+    }
+
+    /// Marks the session as programmatic. Humans whose session user agent is a
+    /// browser switch to one of their CLI or SDK agents, since API calls outside
+    /// a console session do not come from a browser.
+    pub fn start_programmatic_session(&mut self) {
+        let browser = self
+            .session_user_agent
+            .as_deref()
+            .is_some_and(is_browser_user_agent);
+        if browser && matches!(self.seed.kind, ActorKind::Human) {
+            let cli = self
+                .seed
+                .user_agents
+                .iter()
+                .find(|agent| !is_browser_user_agent(agent))
+                .cloned()
+                .unwrap_or_else(|| cli_user_agent(&self.seed));
+            self.session_user_agent = Some(cli);
+        }
+        self.session_origin = Some(SessionOrigin::Programmatic);
+    }
+
     /// Returns the next time this actor can emit an event.
     pub fn next_available_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
         let mut candidate = now;
@@ -283,7 +460,26 @@ pub struct PopulationSpec<'a> {
     pub hot_actor_multiplier: f64,
     pub human_error_rate: ErrorRateSpec,
     pub service_error_rate: ErrorRateSpec,
-    pub account_ids: &'a [String],
+    pub accounts: &'a AccountPool,
+}
+
+/// Account IDs actors are assigned to, weighted by activity.
+#[derive(Debug, Clone, Default)]
+pub struct AccountPool {
+    ids: Vec<String>,
+    /// Draws accounts by weight; `None` draws them uniformly.
+    index: Option<WeightedIndex<f64>>,
+    /// `events_per_hour` targets, in pool order.
+    targets: Vec<(String, f64)>,
+}
+
+impl AccountPool {
+    fn pick(&self, rng: &mut impl Rng) -> String {
+        match &self.index {
+            Some(index) => self.ids[index.sample(rng)].clone(),
+            None => pick_account_id(rng, &self.ids),
+        }
+    }
 }
 
 impl ActorPopulation {
@@ -315,7 +511,7 @@ pub fn generate_population(config: &PopulationConfig) -> Result<ActorPopulation,
     let hot_actor_ratio = population.hot_actor_ratio.unwrap_or(0.1).clamp(0.0, 1.0);
     let hot_actor_multiplier = population.hot_actor_multiplier.unwrap_or(6.0).max(1.0);
     let (role_weights, role_rates) = build_role_config(population.role.as_ref());
-    let account_ids = build_account_pool(population);
+    let accounts = build_account_pool(population)?;
     let service_rate = optional_positive_rate(
         population.service_events_per_hour,
         "population.service_events_per_hour",
@@ -332,7 +528,7 @@ pub fn generate_population(config: &PopulationConfig) -> Result<ActorPopulation,
         population.actor.as_ref(),
         human_error,
         service_error,
-        &account_ids,
+        &accounts,
         start_time,
     )?;
     let total = population
@@ -353,12 +549,23 @@ pub fn generate_population(config: &PopulationConfig) -> Result<ActorPopulation,
         hot_actor_multiplier,
         human_error_rate: human_error,
         service_error_rate: service_error,
-        account_ids: &account_ids,
+        accounts: &accounts,
     };
 
     let mut population = ActorPopulation {
         actors: generate_actor_seeds(&mut rng, &spec),
     };
+    apply_account_targets(&mut population.actors, &explicit, &accounts)?;
+    assign_insider_personas(
+        &mut population.actors,
+        config.population.insider_persona.as_deref(),
+        &mut rng,
+    )?;
+    assign_on_call_rotation(
+        &mut population.actors,
+        config.population.on_call.as_ref(),
+        &mut rng,
+    )?;
     population.actors.extend(explicit);
     apply_timezone_distribution(
         &mut population,
@@ -367,6 +574,23 @@ pub fn generate_population(config: &PopulationConfig) -> Result<ActorPopulation,
         &mut rng,
     );
     apply_generated_human_identity_defaults(&mut population.actors, &mut rng);
+    if let Some(plan) = &config.ip_plan {
+        let mut plan =
+            IpPlan::new(plan).map_err(|err| ActorConfigError(format!("ip_plan.{err}")))?;
+        let fixed = config
+            .population
+            .actor
+            .iter()
+            .flatten()
+            .filter(|entry| entry.source_ips.is_some())
+            .map(|entry| entry.id.trim())
+            .collect::<HashSet<_>>();
+        for actor in &mut population.actors {
+            if actor.id.as_deref().is_none_or(|id| !fixed.contains(id)) {
+                plan.assign(actor, &mut rng);
+            }
+        }
+    }
     Ok(population)
 }
 
@@ -375,7 +599,7 @@ fn build_explicit_actors(
     entries: Option<&Vec<ExplicitActorConfig>>,
     human_error: ErrorRateSpec,
     service_error: ErrorRateSpec,
-    account_ids: &[String],
+    accounts: &AccountPool,
     start_time: DateTime<Utc>,
 ) -> Result<Vec<ActorSeed>, ActorConfigError> {
     let Some(entries) = entries else {
@@ -411,7 +635,7 @@ fn build_explicit_actors(
         };
         let account_id = match &entry.account_id {
             Some(value) => validate_account_id(value, id)?,
-            None => pick_account_id(rng, account_ids),
+            None => accounts.pick(rng),
         };
         let tags = normalize_tags(&entry.tags);
         let event_bias = normalize_event_bias(&entry.event_bias);
@@ -579,6 +803,19 @@ fn build_explicit_actors(
             }
         }
 
+        if let Some(name) = &entry.insider_persona {
+            if matches!(actor.kind, ActorKind::Service) {
+                return Err(ActorConfigError(format!(
+                    "population.actor {id} is service but insider_persona is set"
+                )));
+            }
+            actor.insider_persona = Some(InsiderPersona::parse(name).ok_or_else(|| {
+                ActorConfigError(format!(
+                    "population.actor {id} has invalid insider_persona: {name}"
+                ))
+            })?);
+        }
+
         actor.id = Some(id.to_string());
         actor.tags = tags;
         actor.event_bias = event_bias;
@@ -588,6 +825,94 @@ fn build_explicit_actors(
     Ok(actors)
 }
 
+/// Assigns configured insider personas to random generated humans.
+///
+/// `off_hours_admin` goes to admins first; other personas pick from all
+/// humans. Each actor gets at most one persona.
+fn assign_insider_personas(
+    actors: &mut [ActorSeed],
+    entries: Option<&[InsiderPersonaConfig]>,
+    rng: &mut impl Rng,
+) -> Result<(), ActorConfigError> {
+    for entry in entries.unwrap_or_default() {
+        let persona = InsiderPersona::parse(&entry.name).ok_or_else(|| {
+            ActorConfigError(format!(
+                "population.insider_persona has invalid name: {}",
+                entry.name
+            ))
+        })?;
+        let mut free = actors
+            .iter()
+            .enumerate()
+            .filter(|(_, actor)| {
+                matches!(actor.kind, ActorKind::Human) && actor.insider_persona.is_none()
+            })
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        if free.len() < entry.count {
+            return Err(ActorConfigError(format!(
+                "population.insider_persona {} needs {} human actors but only {} are available",
+                persona.key(),
+                entry.count,
+                free.len()
+            )));
+        }
+        free.shuffle(rng);
+        if persona == InsiderPersona::OffHoursAdmin {
+            free.sort_by_key(|idx| actors[*idx].role != Some(ActorRole::Admin));
+        }
+        for idx in free.into_iter().take(entry.count) {
+            actors[idx].insider_persona = Some(persona);
+        }
+    }
+    Ok(())
+}
+
+/// Puts `count` generated humans into an on-call rotation, admins first and
+/// then developers, giving each a consecutive shift.
+fn assign_on_call_rotation(
+    actors: &mut [ActorSeed],
+    config: Option<&OnCallConfig>,
+    rng: &mut impl Rng,
+) -> Result<(), ActorConfigError> {
+    let Some(config) = config.filter(|config| config.count > 0) else {
+        return Ok(());
+    };
+    let shift_days = config.shift_days.unwrap_or(7);
+    if shift_days == 0 {
+        return Err(ActorConfigError(
+            "population.on_call.shift_days must be at least 1".to_string(),
+        ));
+    }
+    let mut humans = actors
+        .iter()
+        .enumerate()
+        .filter(|(_, actor)| matches!(actor.kind, ActorKind::Human))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+    if humans.len() < config.count {
+        return Err(ActorConfigError(format!(
+            "population.on_call needs {} human actors but only {} are available",
+            config.count,
+            humans.len()
+        )));
+    }
+    humans.shuffle(rng);
+    humans.sort_by_key(|idx| match actors[*idx].role {
+        Some(ActorRole::Admin) => 0,
+        Some(ActorRole::Developer) => 1,
+        _ => 2,
+    });
+    for (slot, idx) in humans.into_iter().take(config.count).enumerate() {
+        actors[idx].on_call = Some(OnCallShift {
+            slot: slot as u32,
+            rotation: config.count as u32,
+            shift_days,
+        });
+    }
+    Ok(())
+}
+
 fn generate_actor_seeds(rng: &mut impl Rng, spec: &PopulationSpec<'_>) -> Vec<ActorSeed> {
     let total = spec.total;
     if total == 0 {
@@ -597,9 +922,11 @@ fn generate_actor_seeds(rng: &mut impl Rng, spec: &PopulationSpec<'_>) -> Vec<Ac
     let service_count = ((total as f64) * spec.service_ratio.clamp(0.0, 1.0)).round() as usize;
     let human_count = total.saturating_sub(service_count);
     let mut actors = Vec::with_capacity(total);
+    // Every account with a rate target gets at least one actor to scale.
+    let mut reserved = spec.accounts.targets.iter().map(|(id, _)| id.clone());
 
     for _ in 0..human_count {
-        let account_id = pick_account_id(rng, spec.account_ids);
+        let account_id = reserved.next().unwrap_or_else(|| spec.accounts.pick(rng));
         let error_rate = sample_error_rate(rng, spec.human_error_rate);
         actors.push(ActorSeed::new_human(
             rng,
@@ -610,7 +937,7 @@ fn generate_actor_seeds(rng: &mut impl Rng, spec: &PopulationSpec<'_>) -> Vec<Ac
         ));
     }
     for _ in 0..service_count {
-        let account_id = pick_account_id(rng, spec.account_ids);
+        let account_id = reserved.next().unwrap_or_else(|| spec.accounts.pick(rng));
         let profile = pick_service_profile(rng, spec.service_profiles, spec.service_rate_per_hour);
         let error_rate = sample_error_rate(rng, spec.service_error_rate);
         actors.push(ActorSeed::new_service(
@@ -780,6 +1107,8 @@ impl ActorSeed {
             event_bias: HashMap::new(),
             service_profile: None,
             service_pattern: None,
+            insider_persona: None,
+            on_call: None,
             user_name: Some(placeholder_user_name),
             display_name: None,
             email: None,
@@ -830,6 +1159,8 @@ impl ActorSeed {
             event_bias: HashMap::new(),
             service_profile: Some(profile),
             service_pattern: Some(pattern),
+            insider_persona: None,
+            on_call: None,
             user_name: None,
             display_name: Some(display_name),
             email: None,
@@ -846,6 +1177,41 @@ impl ActorSeed {
     }
 }
 
+impl ActorSeed {
+    /// Builds an around-the-clock service actor outside a generated population,
+    /// e.g. for actors that join a run after it started.
+    pub fn always_on_service(
+        rng: &mut impl Rng,
+        id: &str,
+        account_id: &str,
+        profile: ServiceProfile,
+        rate_per_hour: f64,
+        error_rate: f64,
+    ) -> Self {
+        let mut seed = Self::new_service(
+            rng,
+            account_id,
+            profile,
+            ServicePattern::Constant,
+            rate_per_hour,
+            error_rate,
+        );
+        seed.id = Some(id.to_string());
+        seed.active_start_hour = 0;
+        seed.active_hours = 24;
+        seed
+    }
+
+    /// Returns whether the actor is on call at `now` but outside its regular
+    /// working window, e.g. paged at night or over a weekend.
+    pub fn on_call_off_hours(&self, now: DateTime<Utc>) -> bool {
+        let local = now + Duration::hours(self.timezone_offset as i64);
+        self.on_call
+            .is_some_and(|shift| shift.covers(local.date_naive()))
+            && !within_regular_window(self, local)
+    }
+}
+
 fn pick_human_role(rng: &mut impl Rng, role_weights: &[(ActorRole, f64)]) -> ActorRole {
     if role_weights.is_empty() {
         return ActorRole::Developer;
@@ -910,17 +1276,107 @@ fn pick_account_id(rng: &mut impl Rng, account_ids: &[String]) -> String {
     account_ids[idx].clone()
 }
 
-fn build_account_pool(config: &PopulationActorsConfig) -> Vec<String> {
-    if let Some(ids) = &config.account_ids {
-        let filtered: Vec<String> = ids.iter().cloned().filter(|id| id.len() == 12).collect();
-        if !filtered.is_empty() {
-            return filtered;
+fn build_account_pool(config: &PopulationActorsConfig) -> Result<AccountPool, ActorConfigError> {
+    let mut ids: Vec<String> = config
+        .account_ids
+        .iter()
+        .flatten()
+        .filter(|id| id.len() == 12)
+        .cloned()
+        .collect();
+    let entries = config.account.as_deref().unwrap_or_default();
+    for entry in entries {
+        let id = entry.id.trim();
+        if id.len() != 12 || !id.chars().all(|ch| ch.is_ascii_digit()) {
+            return Err(ActorConfigError(format!(
+                "population.account id must be a 12-digit string, got {id:?}"
+            )));
         }
+        if !ids.iter().any(|known| known == id) {
+            ids.push(id.to_string());
+        }
+    }
+    if ids.is_empty() {
+        let count = config.account_count.unwrap_or(1).max(1);
+        let mut rng = rand::thread_rng();
+        ids = (0..count).map(|_| random_account_id(&mut rng)).collect();
     }
 
-    let count = config.account_count.unwrap_or(1).max(1);
-    let mut rng = rand::thread_rng();
-    (0..count).map(|_| random_account_id(&mut rng)).collect()
+    let skew = config.account_skew.unwrap_or(0.0);
+    if !skew.is_finite() || skew < 0.0 {
+        return Err(ActorConfigError(
+            "population.account_skew must be >= 0".to_string(),
+        ));
+    }
+    let mut weights = Vec::with_capacity(ids.len());
+    let mut targets = Vec::new();
+    for (rank, id) in ids.iter().enumerate() {
+        let entry = entries.iter().find(|entry| entry.id.trim() == id);
+        let weight = match entry.and_then(|entry| entry.weight) {
+            Some(weight) if !weight.is_finite() || weight < 0.0 => {
+                return Err(ActorConfigError(format!(
+                    "population.account {id} weight must be >= 0"
+                )))
+            }
+            Some(weight) => weight,
+            None => 1.0 / ((rank + 1) as f64).powf(skew),
+        };
+        weights.push(weight);
+        if let Some(rate) = optional_positive_rate(
+            entry.and_then(|entry| entry.events_per_hour),
+            &format!("population.account {id} events_per_hour"),
+        )? {
+            targets.push((id.clone(), rate));
+        }
+    }
+    let index = if weights.iter().all(|weight| *weight == 1.0) {
+        None
+    } else {
+        Some(WeightedIndex::new(&weights).map_err(|_| {
+            ActorConfigError("population.account weights must not all be 0".to_string())
+        })?)
+    };
+    Ok(AccountPool {
+        ids,
+        index,
+        targets,
+    })
+}
+
+/// Scales generated actors' rates so each account with an `events_per_hour`
+/// target adds up to it, counting explicit actors' fixed rates.
+fn apply_account_targets(
+    generated: &mut [ActorSeed],
+    explicit: &[ActorSeed],
+    accounts: &AccountPool,
+) -> Result<(), ActorConfigError> {
+    for (id, target) in &accounts.targets {
+        let fixed: f64 = explicit
+            .iter()
+            .filter(|actor| actor.account_id == *id)
+            .map(|actor| actor.rate_per_hour)
+            .sum();
+        let scaled: f64 = generated
+            .iter()
+            .filter(|actor| actor.account_id == *id)
+            .map(|actor| actor.rate_per_hour)
+            .sum();
+        if scaled <= 0.0 {
+            return Err(ActorConfigError(format!(
+                "population.account {id} events_per_hour needs a generated actor in the account; raise population.actor_count"
+            )));
+        }
+        if fixed >= *target {
+            return Err(ActorConfigError(format!(
+                "population.account {id} events_per_hour ({target}) must exceed its explicit actors' combined rate ({fixed})"
+            )));
+        }
+        let factor = (target - fixed) / scaled;
+        for actor in generated.iter_mut().filter(|actor| actor.account_id == *id) {
+            actor.rate_per_hour *= factor;
+        }
+    }
+    Ok(())
 }
 
 fn build_role_config(config: Option<&Vec<RoleConfig>>) -> (Vec<(ActorRole, f64)>, RoleRates) {
@@ -1120,6 +1576,20 @@ fn pick_timezone_offset(rng: &mut impl Rng) -> i8 {
 fn within_active_window(seed: &ActorSeed, now: DateTime<Utc>) -> bool {
     let offset = Duration::hours(seed.timezone_offset as i64);
     let local = now + offset;
+    if local.hour() >= OFF_HOURS_START_HOUR && is_off_hours_night(seed, local.date_naive()) {
+        return true;
+    }
+    if seed
+        .on_call
+        .is_some_and(|shift| shift.covers(local.date_naive()))
+    {
+        return true;
+    }
+    within_regular_window(seed, local)
+}
+
+/// Returns whether local time `local` is in the actor's regular working window.
+fn within_regular_window(seed: &ActorSeed, local: DateTime<Utc>) -> bool {
     if !seed.weekend_active && is_weekend_date(local.date_naive()) {
         return false;
     }
@@ -1795,9 +2265,51 @@ fn is_weekend_date(date: chrono::NaiveDate) -> bool {
     day >= 6
 }
 
+/// Local hour when an `off_hours_admin` late-night session starts; it runs to midnight.
+const OFF_HOURS_START_HOUR: u32 = 22;
+/// One in this many nights is a late night for an `off_hours_admin`.
+const OFF_HOURS_NIGHT_PERIOD: u64 = 5;
+
+/// Returns whether `date` is a late-night working day for an `off_hours_admin`.
+///
+/// Derived from the actor and date so availability checks and scheduling agree.
+fn is_off_hours_night(seed: &ActorSeed, date: chrono::NaiveDate) -> bool {
+    use std::hash::{Hash, Hasher};
+
+    if seed.insider_persona != Some(InsiderPersona::OffHoursAdmin) {
+        return false;
+    }
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    seed.principal_id.hash(&mut hasher);
+    date.num_days_from_ce().hash(&mut hasher);
+    hasher.finish().is_multiple_of(OFF_HOURS_NIGHT_PERIOD)
+}
+
 fn next_active_window_start(seed: &ActorSeed, now: DateTime<Utc>) -> DateTime<Utc> {
     let offset = Duration::hours(seed.timezone_offset as i64);
     let local = now + offset;
+    let regular = next_regular_window_start(seed, local);
+    let late_night = (0..=OFF_HOURS_NIGHT_PERIOD as i64 * 4)
+        .map(|days| local.date_naive() + Duration::days(days))
+        .filter(|date| is_off_hours_night(seed, *date))
+        .filter_map(|date| date.and_hms_opt(OFF_HOURS_START_HOUR, 0, 0))
+        .find(|start| *start > local.naive_utc());
+    // An on-call shift makes the actor available from the local midnight it starts.
+    let shift = seed.on_call.and_then(|shift| {
+        (1..=i64::from(shift.rotation * shift.shift_days))
+            .map(|days| local.date_naive() + Duration::days(days))
+            .find(|date| shift.covers(*date))
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    });
+    let start = [late_night, shift]
+        .into_iter()
+        .flatten()
+        .fold(regular, |start, candidate| start.min(candidate));
+    Utc.from_utc_datetime(&(start - offset))
+}
+
+/// Returns the local start of the next regular working window after `local`.
+fn next_regular_window_start(seed: &ActorSeed, local: DateTime<Utc>) -> chrono::NaiveDateTime {
     let mut date = local.date_naive();
 
     loop {
@@ -1815,8 +2327,7 @@ fn next_active_window_start(seed: &ActorSeed, now: DateTime<Utc>) -> DateTime<Ut
         };
 
         if date > local.date_naive() || local.time() < start.time() {
-            let start_utc = start - offset;
-            return Utc.from_utc_datetime(&start_utc);
+            return start;
         }
 
         date = date + Duration::days(1);
@@ -1925,6 +2436,38 @@ fn random_human_user_agent(rng: &mut impl Rng) -> String {
     }
 }
 
+/// Browser used for console sessions of actors without a browser user agent.
+pub const DEFAULT_BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+/// Returns whether a user agent belongs to a web browser.
+pub fn is_browser_user_agent(user_agent: &str) -> bool {
+    user_agent.starts_with("Mozilla/")
+}
+
+/// AWS CLI user agent for a human, stable per principal and matching the
+/// operating system of their primary browser.
+fn cli_user_agent(seed: &ActorSeed) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    seed.principal_id.hash(&mut hasher);
+    let hash = hasher.finish();
+    let primary = seed.user_agents.first().map(String::as_str).unwrap_or("");
+    let os = if primary.contains("Macintosh") || primary.contains("iPhone") {
+        "Darwin/23.4.0"
+    } else if primary.contains("Windows") {
+        "Windows/10"
+    } else {
+        "Linux/6.5.0-28-generic"
+    };
+    format!(
+        "aws-cli/2.{}.{} Python/3.11.{} {os} exe/x86_64 prompt/off",
+        13 + hash % 4,
+        (hash >> 8) % 40,
+        (hash >> 16) % 9 + 1
+    )
+}
+
 fn random_service_user_agent(rng: &mut impl Rng) -> String {
     match rng.gen_range(0..5) {
         0 => "aws-sdk-go/1.44.2 (go1.20.5; linux; amd64)".to_string(),
@@ -2070,6 +2613,7 @@ mod tests {
             role: Some("developer".to_string()),
             service_profile: None,
             service_pattern: None,
+            insider_persona: None,
             events_per_hour: Some(12.0),
             error_rate: Some(0.02),
             account_id: Some("123456789012".to_string()),
@@ -2115,6 +2659,7 @@ mod tests {
             role: Some("developer".to_string()),
             service_profile: None,
             service_pattern: None,
+            insider_persona: None,
             events_per_hour: Some(12.0),
             error_rate: Some(0.02),
             account_id: Some("123456789012".to_string()),
@@ -2178,6 +2723,146 @@ mod tests {
         assert_eq!(actor.rate_per_hour, 0.02);
     }
 
+    #[test]
+    fn insider_personas_go_to_generated_humans() {
+        let mut config = population_config("Europe/London", 40, 0.25, Vec::new());
+        config.population.insider_persona = Some(vec![
+            InsiderPersonaConfig {
+                name: "data_hoarder".to_string(),
+                count: 2,
+            },
+            InsiderPersonaConfig {
+                name: "off-hours-admin".to_string(),
+                count: 1,
+            },
+        ]);
+
+        let population = generate_population(&config).unwrap();
+        let personas = population
+            .actors
+            .iter()
+            .filter_map(|actor| actor.insider_persona.map(|persona| (actor, persona)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(personas.len(), 3);
+        assert!(personas
+            .iter()
+            .all(|(actor, _)| matches!(actor.kind, ActorKind::Human)));
+        let (admin, _) = personas
+            .iter()
+            .find(|(_, persona)| *persona == InsiderPersona::OffHoursAdmin)
+            .unwrap();
+        assert_eq!(admin.role, Some(ActorRole::Admin));
+
+        config.population.insider_persona = Some(vec![InsiderPersonaConfig {
+            name: "data_hoarder".to_string(),
+            count: 31,
+        }]);
+        assert!(generate_population(&config).is_err());
+    }
+
+    #[test]
+    fn on_call_rotation_covers_nights_and_weekends_one_shift_at_a_time() {
+        let mut config = population_config("UTC", 40, 0.25, Vec::new());
+        config.population.on_call = Some(OnCallConfig {
+            count: 3,
+            shift_days: None,
+        });
+        let population = generate_population(&config).unwrap();
+        let members = population
+            .actors
+            .iter()
+            .filter(|actor| actor.on_call.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(members.len(), 3);
+        assert!(members
+            .iter()
+            .all(|actor| matches!(actor.kind, ActorKind::Human)));
+
+        // 2026-01-05 is a Monday; weekly shifts hand over at local midnight.
+        let monday = chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let slots = (0..21)
+            .map(|day| {
+                let date = monday + Duration::days(day);
+                let on_call = members
+                    .iter()
+                    .filter(|actor| actor.on_call.unwrap().covers(date))
+                    .map(|actor| actor.on_call.unwrap().slot)
+                    .collect::<Vec<_>>();
+                assert_eq!(on_call.len(), 1, "{date}");
+                on_call[0]
+            })
+            .collect::<Vec<_>>();
+        assert!(slots
+            .chunks(7)
+            .all(|week| week.iter().all(|slot| *slot == week[0])));
+        assert_eq!(
+            slots.iter().step_by(7).collect::<HashSet<_>>().len(),
+            3,
+            "{slots:?}"
+        );
+        let member = members
+            .iter()
+            .find(|actor| actor.on_call.unwrap().covers(monday))
+            .unwrap();
+        let saturday_night =
+            Utc.from_utc_datetime(&(monday + Duration::days(5)).and_hms_opt(3, 0, 0).unwrap());
+        assert!(within_active_window(member, saturday_night));
+        assert!(member.on_call_off_hours(saturday_night));
+        assert!(!within_active_window(
+            member,
+            saturday_night + Duration::days(7)
+        ));
+        let next = next_active_window_start(member, saturday_night + Duration::days(7));
+        assert!(next <= saturday_night + Duration::days(16));
+
+        let shift = member.on_call.unwrap();
+        assert_eq!(OnCallShift::parse(&shift.key()), Some(shift));
+        assert_eq!(OnCallShift::parse("3/3/7"), None);
+
+        config.population.on_call = Some(OnCallConfig {
+            count: 31,
+            shift_days: None,
+        });
+        assert!(generate_population(&config).is_err());
+    }
+
+    #[test]
+    fn account_weights_skew_assignment_and_targets_set_account_rates() {
+        let busy = "111111111111";
+        let quiet = "222222222222";
+        let mut config = population_config("UTC", 200, 0.2, Vec::new());
+        config.population.account_ids = Some(vec![busy.to_string(), quiet.to_string()]);
+        config.population.account_skew = Some(2.0);
+        config.population.account = Some(vec![crate::config::AccountConfig {
+            id: quiet.to_string(),
+            weight: None,
+            events_per_hour: Some(30.0),
+        }]);
+
+        let population = generate_population(&config).unwrap();
+        let in_account = |id: &str| {
+            population
+                .actors
+                .iter()
+                .filter(|actor| actor.account_id == id)
+                .collect::<Vec<_>>()
+        };
+        assert!(in_account(busy).len() > in_account(quiet).len() * 2);
+        let quiet_rate: f64 = in_account(quiet)
+            .iter()
+            .map(|actor| actor.rate_per_hour)
+            .sum();
+        assert!((quiet_rate - 30.0).abs() < 1e-6, "{quiet_rate}");
+
+        config.population.account = Some(vec![crate::config::AccountConfig {
+            id: "1234".to_string(),
+            weight: Some(1.0),
+            events_per_hour: None,
+        }]);
+        assert!(generate_population(&config).is_err());
+    }
+
     fn population_config(
         timezone: &str,
         actor_count: usize,
@@ -2197,6 +2882,8 @@ mod tests {
                 hot_actor_multiplier: Some(1.0),
                 account_ids: Some(vec!["123456789012".to_string()]),
                 account_count: None,
+                account_skew: None,
+                account: None,
                 error_rate: None,
                 human_error_rate: None,
                 service_error_rate: None,
@@ -2208,7 +2895,11 @@ mod tests {
                 } else {
                     Some(explicit)
                 },
+                insider_persona: None,
+                on_call: None,
             },
+            ip_plan: None,
+            parquet: None,
         }
     }
 }
@@ -0,0 +1,183 @@
+//! Inter-arrival processes for baseline actor activity.
+//!
+//! Schedulers turn an actor's hourly rate into the gap before its next event.
+//! The default is exponential gaps (Poisson arrivals); sources can switch to
+//! heavy-tailed Pareto gaps or evenly spaced gaps, and tune how often and how
+//! hard bursty service actors burst, to shape workloads for queueing tests.
+//! Both the seeded RNG scheduler and the hash-based identity scheduler feed a
+//! uniform draw in, so each keeps its own source of randomness.
+
+use crate::core::activity::Workday;
+use crate::core::config::{ArrivalConfig, ArrivalProcess};
+
+const DEFAULT_PARETO_SHAPE: f64 = 1.5;
+const DEFAULT_BURST_PROBABILITY: f64 = 0.12;
+const DEFAULT_BURST_MULTIPLIER: [f64; 2] = [2.0, 5.0];
+const MIN_RATE_PER_HOUR: f64 = 0.001;
+
+/// Resolved arrival settings for one source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrivals {
+    process: ArrivalProcess,
+    pareto_shape: f64,
+    burst_probability: f64,
+    burst_multiplier: [f64; 2],
+    workday: Workday,
+}
+
+impl Default for Arrivals {
+    fn default() -> Self {
+        Self {
+            process: ArrivalProcess::Poisson,
+            pareto_shape: DEFAULT_PARETO_SHAPE,
+            burst_probability: DEFAULT_BURST_PROBABILITY,
+            burst_multiplier: DEFAULT_BURST_MULTIPLIER,
+            workday: Workday::default(),
+        }
+    }
+}
+
+impl Arrivals {
+    pub fn from_config(config: Option<&ArrivalConfig>) -> Result<Self, String> {
+        let Some(config) = config else {
+            return Ok(Self::default());
+        };
+        let pareto_shape = config.pareto_shape.unwrap_or(DEFAULT_PARETO_SHAPE);
+        if !pareto_shape.is_finite() || pareto_shape <= 1.0 {
+            return Err(format!(
+                "pareto_shape must be greater than 1, got {pareto_shape}"
+            ));
+        }
+        let burst_probability = config
+            .burst_probability
+            .unwrap_or(DEFAULT_BURST_PROBABILITY);
+        if !(0.0..=1.0).contains(&burst_probability) {
+            return Err(format!(
+                "burst_probability must be between 0 and 1, got {burst_probability}"
+            ));
+        }
+        let burst_multiplier = config.burst_multiplier.unwrap_or(DEFAULT_BURST_MULTIPLIER);
+        let [min, max] = burst_multiplier;
+        if !min.is_finite() || !max.is_finite() || min <= 0.0 || min >= max {
+            return Err(format!(
+                "burst_multiplier must be [min, max] with 0 < min < max, got [{min}, {max}]"
+            ));
+        }
+        Ok(Self {
+            process: config.process,
+            pareto_shape,
+            burst_probability,
+            burst_multiplier,
+            workday: Workday::from_config(config.workday.as_ref())?,
+        })
+    }
+
+    /// Seconds before the next event at `rate_per_hour`, from a uniform draw in [0, 1).
+    pub fn interval_secs(&self, rate_per_hour: f64, unit: f64) -> f64 {
+        let rate = if rate_per_hour.is_finite() && rate_per_hour > 0.0 {
+            rate_per_hour
+        } else {
+            MIN_RATE_PER_HOUR
+        };
+        let lambda = rate / 3600.0;
+        match self.process {
+            ArrivalProcess::Poisson => -unit.ln() / lambda,
+            ArrivalProcess::Pareto => {
+                // Scale chosen so the mean gap matches the exponential one.
+                let alpha = self.pareto_shape;
+                let scale = (alpha - 1.0) / (alpha * lambda);
+                scale / (1.0 - unit).powf(1.0 / alpha)
+            }
+            ArrivalProcess::Fixed => 1.0 / lambda,
+        }
+    }
+
+    /// Chance that a bursty actor's next gap uses a burst rate.
+    pub fn burst_probability(&self) -> f64 {
+        self.burst_probability
+    }
+
+    /// Rate multiplier range during a burst.
+    pub fn burst_range(&self) -> std::ops::Range<f64> {
+        self.burst_multiplier[0]..self.burst_multiplier[1]
+    }
+
+    /// Intra-day curve applied to human rates.
+    pub fn workday(&self) -> &Workday {
+        &self.workday
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::WorkdayConfig;
+
+    fn mean_gap_secs(arrivals: &Arrivals) -> f64 {
+        let samples = 20_000;
+        (0..samples)
+            .map(|i| {
+                let unit = (i as f64 + 0.5) / samples as f64;
+                arrivals.interval_secs(60.0, unit)
+            })
+            .sum::<f64>()
+            / samples as f64
+    }
+
+    #[test]
+    fn processes_share_the_mean_rate() {
+        for process in [
+            ArrivalProcess::Poisson,
+            ArrivalProcess::Pareto,
+            ArrivalProcess::Fixed,
+        ] {
+            let arrivals = Arrivals::from_config(Some(&ArrivalConfig {
+                process,
+                pareto_shape: Some(2.5),
+                ..ArrivalConfig::default()
+            }))
+            .unwrap();
+            let mean = mean_gap_secs(&arrivals);
+            assert!((mean - 60.0).abs() < 3.0, "{process:?}: {mean}");
+        }
+
+        let pareto = Arrivals::from_config(Some(&ArrivalConfig {
+            process: ArrivalProcess::Pareto,
+            ..ArrivalConfig::default()
+        }))
+        .unwrap();
+        // Heavy tail: the 99.9th percentile gap is well past the exponential one.
+        assert!(pareto.interval_secs(60.0, 0.999) > Arrivals::default().interval_secs(60.0, 0.001));
+
+        for bad in [
+            ArrivalConfig {
+                pareto_shape: Some(1.0),
+                ..ArrivalConfig::default()
+            },
+            ArrivalConfig {
+                burst_probability: Some(1.5),
+                ..ArrivalConfig::default()
+            },
+            ArrivalConfig {
+                burst_multiplier: Some([4.0, 2.0]),
+                ..ArrivalConfig::default()
+            },
+            ArrivalConfig {
+                workday: Some(WorkdayConfig {
+                    lunch_hour: Some(24),
+                    ..WorkdayConfig::default()
+                }),
+                ..ArrivalConfig::default()
+            },
+            ArrivalConfig {
+                workday: Some(WorkdayConfig {
+                    meeting_factor: Some(-0.5),
+                    ..WorkdayConfig::default()
+                }),
+                ..ArrivalConfig::default()
+            },
+        ] {
+            assert!(Arrivals::from_config(Some(&bad)).is_err());
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! MITRE ATT&CK metadata for scenario events.
+//!
+//! Scenario events declare technique IDs; this module validates them, resolves
+//! names and tactics for the techniques seclog scenarios commonly exercise, and
+//! summarizes which techniques a config covers.
+
+use crate::core::config::{ResolverQueryLogConfig, SourceConfig};
+use crate::core::event::EventLabel;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// ATT&CK technique or sub-technique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Technique {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub tactics: &'static [&'static str],
+}
+
+/// Cloud and identity techniques that map onto the generated sources.
+pub const TECHNIQUES: &[Technique] = &[
+    entry(
+        "T1069.003",
+        "Permission Groups Discovery: Cloud Groups",
+        &["discovery"],
+    ),
+    entry(
+        "T1071.004",
+        "Application Layer Protocol: DNS",
+        &["command-and-control"],
+    ),
+    entry(
+        "T1078",
+        "Valid Accounts",
+        &[
+            "defense-evasion",
+            "persistence",
+            "privilege-escalation",
+            "initial-access",
+        ],
+    ),
+    entry(
+        "T1078.004",
+        "Valid Accounts: Cloud Accounts",
+        &[
+            "defense-evasion",
+            "persistence",
+            "privilege-escalation",
+            "initial-access",
+        ],
+    ),
+    entry(
+        "T1087.004",
+        "Account Discovery: Cloud Account",
+        &["discovery"],
+    ),
+    entry(
+        "T1098",
+        "Account Manipulation",
+        &["persistence", "privilege-escalation"],
+    ),
+    entry(
+        "T1098.001",
+        "Account Manipulation: Additional Cloud Credentials",
+        &["persistence", "privilege-escalation"],
+    ),
+    entry(
+        "T1098.003",
+        "Account Manipulation: Additional Cloud Roles",
+        &["persistence", "privilege-escalation"],
+    ),
+    entry("T1110", "Brute Force", &["credential-access"]),
+    entry(
+        "T1110.003",
+        "Brute Force: Password Spraying",
+        &["credential-access"],
+    ),
+    entry(
+        "T1110.004",
+        "Brute Force: Credential Stuffing",
+        &["credential-access"],
+    ),
+    entry(
+        "T1136.003",
+        "Create Account: Cloud Account",
+        &["persistence"],
+    ),
+    entry(
+        "T1484.002",
+        "Domain or Tenant Policy Modification: Trust Modification",
+        &["defense-evasion", "privilege-escalation"],
+    ),
+    entry("T1485", "Data Destruction", &["impact"]),
+    entry("T1496", "Resource Hijacking", &["impact"]),
+    entry("T1526", "Cloud Service Discovery", &["discovery"]),
+    entry(
+        "T1528",
+        "Steal Application Access Token",
+        &["credential-access"],
+    ),
+    entry("T1530", "Data from Cloud Storage", &["collection"]),
+    entry("T1537", "Transfer Data to Cloud Account", &["exfiltration"]),
+    entry("T1538", "Cloud Service Dashboard", &["discovery"]),
+    entry(
+        "T1550.001",
+        "Use Alternate Authentication Material: Application Access Token",
+        &["defense-evasion", "lateral-movement"],
+    ),
+    entry(
+        "T1552.005",
+        "Unsecured Credentials: Cloud Instance Metadata API",
+        &["credential-access"],
+    ),
+    entry(
+        "T1556",
+        "Modify Authentication Process",
+        &["credential-access", "defense-evasion", "persistence"],
+    ),
+    entry(
+        "T1562.008",
+        "Impair Defenses: Disable or Modify Cloud Logs",
+        &["defense-evasion"],
+    ),
+    entry(
+        "T1568.002",
+        "Dynamic Resolution: Domain Generation Algorithms",
+        &["command-and-control"],
+    ),
+    entry("T1580", "Cloud Infrastructure Discovery", &["discovery"]),
+    entry(
+        "T1621",
+        "Multi-Factor Authentication Request Generation",
+        &["credential-access"],
+    ),
+];
+
+const fn entry(
+    id: &'static str,
+    name: &'static str,
+    tactics: &'static [&'static str],
+) -> Technique {
+    Technique { id, name, tactics }
+}
+
+/// Looks up a technique in the built-in catalog.
+pub fn technique(id: &str) -> Option<&'static Technique> {
+    TECHNIQUES.iter().find(|technique| technique.id == id)
+}
+
+/// Accepts `T1234` and `T1234.567`; IDs outside the built-in catalog are allowed.
+pub fn is_valid_technique_id(id: &str) -> bool {
+    let Some(rest) = id.strip_prefix('T') else {
+        return false;
+    };
+    let (base, sub) = match rest.split_once('.') {
+        Some((base, sub)) => (base, Some(sub)),
+        None => (rest, None),
+    };
+    let digits =
+        |value: &str, len: usize| value.len() == len && value.chars().all(|ch| ch.is_ascii_digit());
+    digits(base, 4) && sub.is_none_or(|sub| digits(sub, 3))
+}
+
+/// Builds the label for an injected event; returns the offending ID if one is malformed.
+pub fn scenario_label(
+    scenario_id: Option<&String>,
+    technique_id: Option<&String>,
+    technique_ids: &[String],
+) -> Result<Option<EventLabel>, String> {
+    let mut ids = Vec::new();
+    for id in technique_id.into_iter().chain(technique_ids) {
+        if !is_valid_technique_id(id) {
+            return Err(id.clone());
+        }
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+    }
+    Ok(scenario_id.map(|scenario_id| EventLabel {
+        scenario_id: scenario_id.clone(),
+        technique_ids: ids,
+    }))
+}
+
+/// Scenario declared by injected events in a source config.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScenarioSummary {
+    pub id: String,
+    pub sources: BTreeSet<String>,
+    pub events: usize,
+    pub technique_ids: BTreeSet<String>,
+}
+
+/// Collects scenarios from injected events, including multi-source children.
+pub fn scenarios(config: &SourceConfig) -> Vec<ScenarioSummary> {
+    let mut summaries = BTreeMap::new();
+    collect_scenarios(config, &mut summaries);
+    summaries.into_values().collect()
+}
+
+fn collect_scenarios(config: &SourceConfig, summaries: &mut BTreeMap<String, ScenarioSummary>) {
+    let mut add = |source: &str, scenario_id: &Option<String>, ids: Vec<&String>, events| {
+        let Some(scenario_id) = scenario_id else {
+            return;
+        };
+        let summary = summaries
+            .entry(scenario_id.clone())
+            .or_insert_with(|| ScenarioSummary {
+                id: scenario_id.clone(),
+                ..ScenarioSummary::default()
+            });
+        summary.sources.insert(source.to_string());
+        summary.events += events;
+        summary.technique_ids.extend(ids.into_iter().cloned());
+    };
+    match config {
+        SourceConfig::OktaSystemLog(config) => {
+            for event in &config.events {
+                let ids = event
+                    .technique_id
+                    .iter()
+                    .chain(&event.technique_ids)
+                    .collect();
+                add("okta_system_log", &event.scenario_id, ids, 1);
+            }
+            if let Some(access) = &config.privileged_access {
+                let ids = access.technique_ids();
+                add(
+                    "okta_system_log",
+                    &Some(access.scenario_id()),
+                    ids.iter().collect(),
+                    0,
+                );
+            }
+        }
+        SourceConfig::DatabricksAudit(config) => {
+            for event in &config.events {
+                let ids = event
+                    .technique_id
+                    .iter()
+                    .chain(&event.technique_ids)
+                    .collect();
+                add("databricks_audit", &event.scenario_id, ids, 1);
+            }
+        }
+        SourceConfig::GitHubAudit(config) => {
+            for event in &config.events {
+                let ids = event
+                    .technique_id
+                    .iter()
+                    .chain(&event.technique_ids)
+                    .collect();
+                add("github_audit", &event.scenario_id, ids, 1);
+            }
+        }
+        SourceConfig::KubernetesAudit(config) => {
+            for event in &config.events {
+                let ids = event
+                    .technique_id
+                    .iter()
+                    .chain(&event.technique_ids)
+                    .collect();
+                add("kubernetes_audit", &event.scenario_id, ids, 1);
+            }
+        }
+        SourceConfig::WindowsSecurity(config) => {
+            for event in &config.events {
+                let ids = event
+                    .technique_id
+                    .iter()
+                    .chain(&event.technique_ids)
+                    .collect();
+                add("windows_security", &event.scenario_id, ids, 1);
+            }
+        }
+        SourceConfig::GoogleWorkspace(config) => {
+            for event in &config.events {
+                let ids = event
+                    .technique_id
+                    .iter()
+                    .chain(&event.technique_ids)
+                    .collect();
+                add("google_workspace", &event.scenario_id, ids, 1);
+            }
+        }
+        SourceConfig::CloudTrail(config) => {
+            // Takeovers, storms, remediation waves, and resolver lookups
+            // generate events at a rate or at random, not as a fixed count, so
+            // they add none.
+            for takeover in &config.credential_takeovers {
+                let ids = takeover.technique_ids();
+                add(
+                    "cloudtrail",
+                    &Some(takeover.scenario_id()),
+                    ids.iter().collect(),
+                    0,
+                );
+            }
+            for storm in &config.api_storms {
+                let ids = storm.technique_id.iter().chain(&storm.technique_ids);
+                add("cloudtrail", &Some(storm.scenario_id()), ids.collect(), 0);
+            }
+            for wave in &config.policy_remediations {
+                let ids = wave.technique_id.iter().chain(&wave.technique_ids);
+                add("cloudtrail", &Some(wave.scenario_id()), ids.collect(), 0);
+            }
+            for travel in &config.impossible_travels {
+                let ids = travel.technique_ids();
+                let scenario_id = Some(travel.scenario_id());
+                add("entra_signin", &scenario_id, ids.iter().collect(), 1);
+                add(
+                    "cloudtrail",
+                    &scenario_id,
+                    ids.iter().collect(),
+                    travel.events(),
+                );
+            }
+            if let Some(resolver) = &config.resolver_query_logs {
+                for (share, scenario_id, technique_id) in [
+                    (
+                        resolver.dga_share(),
+                        ResolverQueryLogConfig::DGA_SCENARIO_ID,
+                        ResolverQueryLogConfig::DGA_TECHNIQUE_ID,
+                    ),
+                    (
+                        resolver.known_bad_share(),
+                        ResolverQueryLogConfig::KNOWN_BAD_SCENARIO_ID,
+                        ResolverQueryLogConfig::KNOWN_BAD_TECHNIQUE_ID,
+                    ),
+                ] {
+                    if share > 0.0 {
+                        let ids = [technique_id.to_string()];
+                        add(
+                            "route53_resolver",
+                            &Some(scenario_id.to_string()),
+                            ids.iter().collect(),
+                            0,
+                        );
+                    }
+                }
+            }
+        }
+        SourceConfig::Multi(config) => {
+            for source in &config.sources {
+                collect_scenarios(source, summaries);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_and_resolves_technique_ids() {
+        assert!(is_valid_technique_id("T1078"));
+        assert!(is_valid_technique_id("T1110.003"));
+        assert!(is_valid_technique_id("T9999"));
+        assert!(!is_valid_technique_id("1078"));
+        assert!(!is_valid_technique_id("T1110.3"));
+        assert_eq!(
+            technique("T1098").map(|t| t.name),
+            Some("Account Manipulation")
+        );
+        assert!(TECHNIQUES.windows(2).all(|pair| pair[0].id < pair[1].id));
+
+        let label = scenario_label(
+            Some(&"spray".to_string()),
+            Some(&"T1110.003".to_string()),
+            &["T1078".to_string(), "T1110.003".to_string()],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(label.technique_ids, vec!["T1110.003", "T1078"]);
+        assert_eq!(
+            scenario_label(None, Some(&"bogus".to_string()), &[]),
+            Err("bogus".to_string())
+        );
+    }
+}
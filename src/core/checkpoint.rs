@@ -0,0 +1,370 @@
+//! Checkpoint state for resumable generation runs.
+//!
+//! Generation is deterministic for a fixed seed, start time, and config, so a
+//! checkpoint records how many events were durably written rather than the full
+//! in-memory generator state. Resuming rebuilds the sources, fast-forwards past
+//! the committed events, and removes output files written after the checkpoint.
+
+use crate::core::event::Event;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const CHECKPOINT_VERSION: u32 = 2;
+
+/// Error while loading, saving, or applying a checkpoint.
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Mismatch(String),
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointError::Io(err) => write!(f, "checkpoint io error: {err}"),
+            CheckpointError::Parse(err) => write!(f, "checkpoint parse error: {err}"),
+            CheckpointError::Mismatch(message) => write!(f, "checkpoint mismatch: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl From<io::Error> for CheckpointError {
+    fn from(err: io::Error) -> Self {
+        CheckpointError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for CheckpointError {
+    fn from(err: serde_json::Error) -> Self {
+        CheckpointError::Parse(err)
+    }
+}
+
+/// Persisted progress of a generation run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    /// Stable hash of the resolved config; resume refuses a different config.
+    pub config_fingerprint: String,
+    /// RNG seed used by the run (generated when the config has none).
+    pub seed: u64,
+    /// Simulated start time (RFC3339) resolved at the beginning of the run.
+    pub start_time: String,
+    /// Events dispatched and flushed to output before this checkpoint.
+    pub events_committed: u64,
+    /// Envelope timestamp of the last committed event.
+    pub last_event_time: Option<String>,
+    /// [`StreamFingerprint`] of the committed events, checked after fast-forwarding.
+    pub last_event_fingerprint: Option<String>,
+    /// Output files present when the checkpoint was taken.
+    pub files: BTreeSet<PathBuf>,
+}
+
+impl Checkpoint {
+    /// Starts an empty checkpoint for a fresh run.
+    pub fn new(config_fingerprint: String, seed: u64, start_time: String) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            config_fingerprint,
+            seed,
+            start_time,
+            events_committed: 0,
+            last_event_time: None,
+            last_event_fingerprint: None,
+            files: BTreeSet::new(),
+        }
+    }
+
+    /// Loads a checkpoint from a JSON state file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CheckpointError> {
+        let contents = fs::read_to_string(path)?;
+        let checkpoint: Self = serde_json::from_str(&contents)?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::Mismatch(format!(
+                "unsupported checkpoint version {}",
+                checkpoint.version
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Writes the checkpoint atomically (temp file + rename).
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        let path = path.as_ref();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Records that `events_committed` events, fingerprinted by `stream`, are on disk.
+    pub fn commit(
+        &mut self,
+        events_committed: u64,
+        stream: &StreamFingerprint,
+        output_dirs: &[PathBuf],
+    ) -> Result<(), CheckpointError> {
+        self.events_committed = events_committed;
+        self.last_event_time = stream.last_event_time();
+        self.last_event_fingerprint = stream.fingerprint();
+        self.files = list_output_files(output_dirs)?;
+        Ok(())
+    }
+
+    /// Fails unless `config_fingerprint` matches the checkpointed run.
+    pub fn ensure_config(&self, config_fingerprint: &str) -> Result<(), CheckpointError> {
+        if self.config_fingerprint != config_fingerprint {
+            return Err(CheckpointError::Mismatch(
+                "config changed since the checkpoint was written".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails unless the regenerated stream up to the resume point matches the checkpoint.
+    pub fn ensure_resume_point(&self, stream: &StreamFingerprint) -> Result<(), CheckpointError> {
+        let expected = self.last_event_fingerprint.as_deref();
+        let actual = stream.fingerprint();
+        if expected != actual.as_deref() {
+            return Err(CheckpointError::Mismatch(format!(
+                "regenerated stream diverged at event {} (is the population seeded?)",
+                self.events_committed
+            )));
+        }
+        Ok(())
+    }
+
+    /// Removes output files written after the checkpoint; returns how many were removed.
+    ///
+    /// `state_path` is never removed, so the state file may live inside an output directory.
+    pub fn remove_uncommitted_files(
+        &self,
+        output_dirs: &[PathBuf],
+        state_path: &Path,
+    ) -> Result<usize, CheckpointError> {
+        let keep = [state_path.to_path_buf(), state_path.with_extension("tmp")];
+        let mut removed = 0;
+        for path in list_output_files(output_dirs)? {
+            if keep.iter().any(|keep| same_file(keep, &path)) {
+                continue;
+            }
+            if !self.files.contains(&path) {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Stable FNV-1a hash of a serializable value, used to fingerprint configs.
+///
+/// The value goes through `serde_json::Value` first so map keys are sorted and
+/// `HashMap` iteration order does not change the hash.
+pub fn fingerprint<T: Serialize>(value: &T) -> String {
+    let bytes = serde_json::to_value(value)
+        .and_then(|value| serde_json::to_vec(&value))
+        .unwrap_or_default();
+    format!("{:016x}", fnv1a(&bytes))
+}
+
+/// Fingerprint of an event stream, folded in one event at a time.
+///
+/// Each event adds its envelope timestamp, source, event type, actor, and
+/// source-native id to a chained hash, so a stream that diverges anywhere
+/// before the resume point changes the fingerprint. Observing an event neither
+/// clones nor serializes it.
+#[derive(Debug, Clone)]
+pub struct StreamFingerprint {
+    hash: u64,
+    events: u64,
+    last_event_time: String,
+}
+
+impl Default for StreamFingerprint {
+    fn default() -> Self {
+        Self {
+            hash: FNV_OFFSET,
+            events: 0,
+            last_event_time: String::new(),
+        }
+    }
+}
+
+impl StreamFingerprint {
+    pub fn observe(&mut self, event: &Event) {
+        let envelope = &event.envelope;
+        for field in [
+            envelope.timestamp.as_str(),
+            envelope.source.as_str(),
+            envelope.event_type.as_str(),
+            envelope.actor.id.as_str(),
+            event.event_id().unwrap_or_default(),
+        ] {
+            self.hash = fnv1a_extend(self.hash, field.as_bytes());
+            // Field separator; never valid in UTF-8.
+            self.hash = fnv1a_extend(self.hash, &[0xff]);
+        }
+        self.events += 1;
+        self.last_event_time.clear();
+        self.last_event_time.push_str(&envelope.timestamp);
+    }
+
+    /// `None` before the first event.
+    pub fn fingerprint(&self) -> Option<String> {
+        (self.events > 0).then(|| format!("{:016x}", self.hash))
+    }
+
+    /// Envelope timestamp of the last observed event.
+    pub fn last_event_time(&self) -> Option<String> {
+        (self.events > 0).then(|| self.last_event_time.clone())
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_extend(FNV_OFFSET, bytes)
+}
+
+fn fnv1a_extend(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn same_file(left: &Path, right: &Path) -> bool {
+    match (fs::canonicalize(left), fs::canonicalize(right)) {
+        (Ok(left), Ok(right)) => left == right,
+        _ => left == right,
+    }
+}
+
+fn list_output_files(dirs: &[PathBuf]) -> io::Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    for dir in dirs {
+        collect_files(dir, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect_files(dir: &Path, files: &mut BTreeSet<PathBuf>) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.insert(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    #[test]
+    fn resume_removes_files_written_after_checkpoint() {
+        let root = std::env::temp_dir().join(format!("seclog-checkpoint-{}", std::process::id()));
+        let out = root.join("out");
+        fs::create_dir_all(out.join("nested")).unwrap();
+        fs::write(out.join("committed.json"), "{}").unwrap();
+        let dirs = vec![out.clone()];
+
+        let mut stream = StreamFingerprint::default();
+        stream.observe(
+            &test_event(
+                "cloudtrail",
+                "test.event",
+                json!({"eventName": "GetObject"}),
+            )
+            .at("2026-01-01T00:00:04Z"),
+        );
+        stream.observe(
+            &test_event(
+                "cloudtrail",
+                "test.event",
+                json!({"eventName": "GetObject"}),
+            )
+            .at("2026-01-01T00:00:05Z"),
+        );
+        let mut checkpoint = Checkpoint::new(
+            fingerprint(&json!({"seed": 1})),
+            1,
+            "2026-01-01T00:00:00Z".to_string(),
+        );
+        checkpoint.commit(10, &stream, &dirs).unwrap();
+        let state = root.join("state.json");
+        checkpoint.save(&state).unwrap();
+
+        fs::write(out.join("nested/late.json"), "{}").unwrap();
+        let loaded = Checkpoint::load(&state).unwrap();
+        assert_eq!(loaded, checkpoint);
+        loaded
+            .ensure_config(&fingerprint(&json!({"seed": 1})))
+            .unwrap();
+        assert!(loaded
+            .ensure_config(&fingerprint(&json!({"seed": 2})))
+            .is_err());
+        assert_eq!(
+            loaded.last_event_time.as_deref(),
+            Some("2026-01-01T00:00:05Z")
+        );
+        loaded.ensure_resume_point(&stream).unwrap();
+        // A stream that diverged before its last event no longer matches.
+        let mut diverged = StreamFingerprint::default();
+        diverged.observe(
+            &test_event(
+                "cloudtrail",
+                "test.event",
+                json!({"eventName": "GetObject"}),
+            )
+            .at("2026-01-01T00:00:03Z"),
+        );
+        diverged.observe(
+            &test_event(
+                "cloudtrail",
+                "test.event",
+                json!({"eventName": "GetObject"}),
+            )
+            .at("2026-01-01T00:00:05Z"),
+        );
+        assert!(loaded.ensure_resume_point(&diverged).is_err());
+
+        let inner_state = out.join("state.json");
+        fs::write(&inner_state, "{}").unwrap();
+        assert_eq!(
+            loaded
+                .remove_uncommitted_files(&dirs, &inner_state)
+                .unwrap(),
+            1
+        );
+        assert!(inner_state.exists());
+        assert!(out.join("committed.json").exists());
+        assert!(!out.join("nested/late.json").exists());
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use toml::Value as TomlValue;
 
 /// Error while loading or parsing a config file.
@@ -45,6 +45,21 @@ pub struct Config {
     pub output: OutputConfig,
     /// Source-specific configuration.
     pub source: SourceConfig,
+    /// Optional ground-truth label sidecar for scenario events.
+    #[serde(default)]
+    pub labels: Option<LabelOutputConfig>,
+    /// Optional payload padding for byte-rate testing.
+    #[serde(default)]
+    pub padding: Option<PaddingConfig>,
+    /// Optional field redaction applied before events are written.
+    #[serde(default)]
+    pub redaction: Option<RedactionConfig>,
+    /// Field rewrites applied in order, after redaction and before writing.
+    #[serde(default)]
+    pub transforms: Vec<TransformConfig>,
+    /// Optional run report written when `gen` finishes.
+    #[serde(default)]
+    pub report: Option<RunReportConfig>,
 }
 
 impl Config {
@@ -64,6 +79,58 @@ pub struct TrafficConfig {
     pub until_time: Option<String>,
     /// Time scale multiplier (1.0 = real time, 60.0 = 1 minute per second).
     pub time_scale: Option<f64>,
+    /// Largest lag behind `time_scale` recovered by running unthrottled, such as `"60s"`.
+    pub max_catch_up: Option<String>,
+    /// Optional load-test schedule; when set, wall-clock pacing follows these phases.
+    #[serde(default)]
+    pub phases: Vec<TrafficPhaseConfig>,
+    /// Optional weekly seasonality, holidays, and incident days for simulated time.
+    pub calendar: Option<TrafficCalendarConfig>,
+}
+
+/// Day-level traffic multipliers applied over the simulated clock.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrafficCalendarConfig {
+    /// Seven multipliers, Monday through Sunday (default all 1.0).
+    pub weekdays: Option<Vec<f64>>,
+    /// Dates with reduced traffic; multipliers scale the weekday value.
+    #[serde(default)]
+    pub holidays: Vec<CalendarDayConfig>,
+    /// Dates with elevated traffic; multipliers scale the weekday value.
+    #[serde(default)]
+    pub incident_days: Vec<CalendarDayConfig>,
+}
+
+/// One dated entry in a traffic calendar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarDayConfig {
+    /// UTC date such as `"2026-12-25"`.
+    pub date: String,
+    /// Multiplier for the day (holidays default to 0.2, incident days to 3.0).
+    pub multiplier: Option<f64>,
+    /// Optional label, only used in messages.
+    pub name: Option<String>,
+}
+
+/// One phase of a wall-clock load schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficPhaseConfig {
+    /// Phase length such as `"90s"`, `"10m"`, or `"2h"`.
+    pub duration: String,
+    /// Target events per second at the end of the phase.
+    pub eps: f64,
+    /// `hold` keeps `eps` flat; `ramp` moves linearly from the previous phase rate.
+    #[serde(default)]
+    pub shape: TrafficPhaseShape,
+}
+
+/// Rate curve within a traffic phase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficPhaseShape {
+    #[default]
+    Hold,
+    Ramp,
 }
 
 /// Weight for a timezone used in actor population generation.
@@ -73,12 +140,126 @@ pub struct TimezoneWeight {
     pub weight: f64,
 }
 
+/// Label sidecar written alongside the main output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelOutputConfig {
+    /// Sidecar file path.
+    pub path: String,
+    /// Sidecar file format.
+    #[serde(default)]
+    pub format: LabelFormat,
+}
+
+/// Run report written at the end of `seclog gen`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReportConfig {
+    /// JSON report path.
+    pub path: String,
+    /// Optional HTML report path.
+    pub html_path: Option<String>,
+}
+
+/// Pads event payloads with source-shaped filler up to a target size.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaddingConfig {
+    /// Target serialized payload size in bytes for every source.
+    pub target_bytes: Option<usize>,
+    /// Per-source targets keyed by envelope source (e.g. `cloudtrail`); overrides `target_bytes`.
+    pub source_target_bytes: Option<HashMap<String, usize>>,
+    /// Spreads each target uniformly by this fraction in either direction (default: 0.0).
+    pub jitter: Option<f64>,
+}
+
+/// Hashes, masks, or drops event fields to mimic privacy-scrubbed feeds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Secret mixed into hashed values so they cannot be reversed by lookup.
+    pub salt: Option<String>,
+    /// Field rules, applied in order.
+    #[serde(default)]
+    pub fields: Vec<RedactionFieldConfig>,
+}
+
+/// One redaction rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionFieldConfig {
+    /// Dotted field path rooted at `payload` or `envelope`; `*` matches every
+    /// array element or object value.
+    pub path: String,
+    /// What to do with matching values.
+    pub action: RedactionAction,
+    /// Envelope sources the rule applies to; all sources when unset.
+    pub sources: Option<Vec<String>>,
+    /// `mask` only: trailing characters left visible (default: 0).
+    pub keep: Option<usize>,
+    /// `anonymize_ip` only: IPv4 prefix bits kept (default: 24).
+    pub ipv4_prefix: Option<u8>,
+    /// `anonymize_ip` only: IPv6 prefix bits kept (default: 48).
+    pub ipv6_prefix: Option<u8>,
+}
+
+/// Redaction applied to a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionAction {
+    /// Replace with a salted SHA-256 digest; equal inputs stay equal.
+    Hash,
+    /// Replace characters with `*`.
+    Mask,
+    /// Remove the field.
+    Drop,
+    /// Zero the host bits of an IP address.
+    AnonymizeIp,
+}
+
+/// One payload rewrite in the `[[transforms]]` list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformConfig {
+    pub action: TransformAction,
+    /// Dotted field path rooted at `payload`, e.g. `payload.userIdentity.arn`.
+    pub path: String,
+    /// `rename` only: new dotted path rooted at `payload`.
+    pub to: Option<String>,
+    /// `set` only: value written at `path`.
+    pub value: Option<serde_json::Value>,
+    /// Envelope event types the rule applies to; all event types when unset.
+    pub event_types: Option<Vec<String>>,
+    /// Envelope sources the rule applies to; all sources when unset.
+    pub sources: Option<Vec<String>>,
+}
+
+/// Rewrite applied by a transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformAction {
+    /// Write `value`, creating parent objects as needed.
+    Set,
+    /// Move the field to `to`.
+    Rename,
+    /// Remove the field.
+    Remove,
+}
+
+/// Label sidecar file format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelFormat {
+    #[default]
+    Jsonl,
+    Parquet,
+}
+
 /// Output sink configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum OutputConfig {
     Zerobus(ZerobusOutputConfig),
     DatabricksVolume(DatabricksVolumeOutputConfig),
+    Stdout(StdoutOutputConfig),
+    ArrowIpc(ArrowIpcOutputConfig),
+    DuckDb(DuckDbOutputConfig),
+    SplunkHec(SplunkHecOutputConfig),
+    Sentinel(SentinelOutputConfig),
     File(FileOutputConfig),
 }
 
@@ -88,6 +269,11 @@ impl OutputConfig {
             OutputConfig::File(config) => Some(config),
             OutputConfig::Zerobus(_) => None,
             OutputConfig::DatabricksVolume(_) => None,
+            OutputConfig::Stdout(_) => None,
+            OutputConfig::ArrowIpc(_) => None,
+            OutputConfig::DuckDb(_) => None,
+            OutputConfig::SplunkHec(_) => None,
+            OutputConfig::Sentinel(_) => None,
         }
     }
 
@@ -100,7 +286,12 @@ impl OutputConfig {
             OutputConfig::Zerobus(_) => {
                 Err("--output can only override file output directories".to_string())
             }
-            OutputConfig::DatabricksVolume(_) => {
+            OutputConfig::DatabricksVolume(_)
+            | OutputConfig::Stdout(_)
+            | OutputConfig::ArrowIpc(_)
+            | OutputConfig::DuckDb(_)
+            | OutputConfig::SplunkHec(_)
+            | OutputConfig::Sentinel(_) => {
                 Err("--output can only override file output directories".to_string())
             }
         }
@@ -116,6 +307,144 @@ pub struct FileOutputConfig {
     pub files: FileConfig,
     /// Output format selection.
     pub format: FormatConfig,
+    /// Additional formats written from the same event stream.
+    #[serde(default)]
+    pub formats: Vec<AdditionalFormatConfig>,
+    /// Hive-style time partitioning of output directories.
+    #[serde(default)]
+    pub partition_by: OutputPartitioning,
+    /// Writes a manifest with per-file event counts and SHA-256 checksums.
+    #[serde(default)]
+    pub manifest: bool,
+    /// What the generator does when a writer shard's queue is full.
+    #[serde(default)]
+    pub backpressure: BackpressurePolicy,
+    /// Retries and error budget for failed writes; the first error aborts when unset.
+    pub write_retry: Option<WriteRetryConfig>,
+    /// Makes writers fail at random to exercise `write_retry`.
+    pub fault_injection: Option<FaultInjectionConfig>,
+}
+
+impl FileOutputConfig {
+    /// Every format this output writes with its directory, `format` first.
+    pub fn format_dirs(&self) -> Vec<(&FormatConfig, PathBuf)> {
+        let mut formats = vec![(&self.format, PathBuf::from(&self.dir))];
+        for extra in &self.formats {
+            let dir = match &extra.dir {
+                Some(dir) => PathBuf::from(dir),
+                None => Path::new(&self.dir).join(extra.format.type_name()),
+            };
+            formats.push((&extra.format, dir));
+        }
+        formats
+    }
+}
+
+/// A format written alongside `output.format`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdditionalFormatConfig {
+    /// Output directory; defaults to `<output.dir>/<type>`.
+    pub dir: Option<String>,
+    #[serde(flatten)]
+    pub format: FormatConfig,
+}
+
+/// Directory partitioning by simulated event time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputPartitioning {
+    /// Flat output directory.
+    #[default]
+    None,
+    /// `<source>/date=YYYY-MM-DD/`.
+    Date,
+    /// `<source>/date=YYYY-MM-DD/hour=HH/`.
+    Hour,
+}
+
+/// Handling of events when a writer shard cannot keep up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressurePolicy {
+    /// Wait for the shard, slowing the generator.
+    #[default]
+    Block,
+    /// Discard the oldest queued event to make room.
+    DropOldest,
+    /// Discard the event that does not fit.
+    DropNewest,
+}
+
+/// Retry policy for failed writes, flushes, and closes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteRetryConfig {
+    /// Retries after the first failure (default: 3).
+    #[serde(default = "default_write_retries")]
+    pub retries: u32,
+    /// Wait before the first retry; doubles per retry (default: 100).
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Longest wait between retries (default: 5000).
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    /// What happens once the retries are spent.
+    #[serde(default)]
+    pub on_failure: WriteFailurePolicy,
+    /// Events or flushes a writer may give up on under `skip` or
+    /// `dead_letter` before the run aborts; unlimited when unset.
+    pub error_budget: Option<u64>,
+    /// Directory for events given up on under `dead_letter`.
+    pub dead_letter_dir: Option<String>,
+}
+
+fn default_write_retries() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    100
+}
+
+fn default_max_backoff_ms() -> u64 {
+    5000
+}
+
+/// Handling of a write that still fails after its retries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteFailurePolicy {
+    /// Stop the run with the error.
+    #[default]
+    Abort,
+    /// Drop the event (or flush) and keep going, within `error_budget`.
+    Skip,
+    /// Write the event and its error to `dead_letter_dir` and keep going,
+    /// within `error_budget`; failed flushes are skipped.
+    DeadLetter,
+}
+
+/// Random writer failures for resilience testing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaultInjectionConfig {
+    /// Chance (0-1) that a write or flush fails.
+    pub probability: f64,
+    /// Failures to pick from (default: both).
+    #[serde(default = "default_injected_faults")]
+    pub errors: Vec<InjectedFault>,
+    /// Seed for reproducible failures; random when unset.
+    pub seed: Option<u64>,
+}
+
+fn default_injected_faults() -> Vec<InjectedFault> {
+    vec![InjectedFault::DiskFull, InjectedFault::PermissionDenied]
+}
+
+/// IO error a fault-injecting writer returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectedFault {
+    DiskFull,
+    PermissionDenied,
 }
 
 /// Controls file output and flush behavior.
@@ -125,14 +454,94 @@ pub struct FileConfig {
     pub target_size_mb: u64,
     /// Maximum age for a file before a new one is started.
     pub max_age_seconds: u64,
+    /// Cap on bytes buffered across all open files; the oldest are flushed early
+    /// when it is exceeded. Unbounded when unset.
+    pub max_buffered_mb: Option<u64>,
+    /// Names rotated JSONL, Parquet, and text files, e.g.
+    /// `"{source}/{account}/{region}/{ts}-{seq}.{ext}"`; see
+    /// [`crate::formats::filename::FilenameTemplate`].
+    pub filename_template: Option<String>,
+    /// Keeps event time from going backwards within each file stream.
+    pub ordering: Option<OrderingConfig>,
+}
+
+/// Event-time ordering of each source/account/region file stream; see
+/// [`crate::formats::ordering`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderingConfig {
+    /// Simulated seconds events are held so that ones arriving out of order
+    /// can be sorted in (default: 0).
+    #[serde(default)]
+    pub window_seconds: f64,
+    /// What happens to an event older than its stream has already written.
+    #[serde(default)]
+    pub late: LateEventPolicy,
+}
+
+/// Handling of an event that arrives after its stream has moved past it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LateEventPolicy {
+    /// Drop the event and keep going.
+    #[default]
+    Drop,
+    /// Stop the run with an error.
+    Error,
 }
 
 /// Output format selection.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum FormatConfig {
-    Jsonl(FormatOptions),
-    Parquet(FormatOptions),
+    Jsonl(JsonlOptions),
+    Parquet(ParquetOptions),
+    /// Parquet files plus a `_delta_log` transaction log, readable as a Delta table.
+    Delta(ParquetOptions),
+    /// Parquet files plus Iceberg table metadata (manifests and manifest lists).
+    Iceberg(IcebergOptions),
+    /// CloudWatch Logs subscription records as delivered by Kinesis Firehose.
+    CloudwatchLogs(CloudWatchLogsOptions),
+    /// Azure Monitor diagnostic export records (`{"records": [...]}`).
+    AzureMonitor(AzureMonitorOptions),
+    /// Raw text lines: S3 server access log lines per bucket, JSON lines for other sources.
+    Text(FormatOptions),
+    /// Comma-separated rows: flattened envelope columns plus configured payload columns.
+    Csv(CsvOptions),
+    /// Avro object container files with the writer schema embedded.
+    Avro(AvroOptions),
+    /// OCSF class records, as JSON lines or Parquet.
+    Ocsf(OcsfOptions),
+}
+
+impl FormatConfig {
+    /// The `type` value this format is configured with.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            FormatConfig::Jsonl(_) => "jsonl",
+            FormatConfig::Parquet(_) => "parquet",
+            FormatConfig::Delta(_) => "delta",
+            FormatConfig::Iceberg(_) => "iceberg",
+            FormatConfig::CloudwatchLogs(_) => "cloudwatch_logs",
+            FormatConfig::AzureMonitor(_) => "azure_monitor",
+            FormatConfig::Text(_) => "text",
+            FormatConfig::Csv(_) => "csv",
+            FormatConfig::Avro(_) => "avro",
+            FormatConfig::Ocsf(_) => "ocsf",
+        }
+    }
+
+    /// Whether this format names its files with `files.filename_template`.
+    pub fn uses_filename_template(&self) -> bool {
+        matches!(
+            self,
+            FormatConfig::Jsonl(_)
+                | FormatConfig::AzureMonitor(_)
+                | FormatConfig::Parquet(_)
+                | FormatConfig::Delta(_)
+                | FormatConfig::Iceberg(_)
+                | FormatConfig::Text(_)
+        )
+    }
 }
 
 /// Per-format options (compression, etc.).
@@ -141,6 +550,150 @@ pub struct FormatOptions {
     pub compression: Option<String>,
 }
 
+/// CSV writer options.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CsvOptions {
+    pub compression: Option<String>,
+    /// Payload columns appended after the envelope columns, in order.
+    #[serde(default)]
+    pub columns: Vec<CsvColumnConfig>,
+}
+
+/// Avro writer options.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AvroOptions {
+    /// Block codec: `none` (default) or `deflate`.
+    pub compression: Option<String>,
+    /// Write timestamps as RFC3339 strings instead of `timestamp-micros` longs.
+    pub timestamp_strings: Option<bool>,
+}
+
+/// OCSF writer options.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OcsfOptions {
+    /// File container for OCSF records.
+    #[serde(default)]
+    pub container: OcsfContainer,
+    /// `gzip` for `json`; a Parquet codec (`snappy`, `zstd`, ...) for `parquet`.
+    pub compression: Option<String>,
+    /// Envelope sources to map; events from other sources are skipped (default: all).
+    pub sources: Option<Vec<String>>,
+}
+
+/// OCSF file container.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcsfContainer {
+    /// One JSON record per line.
+    #[default]
+    Json,
+    /// One row per record, nested objects as JSON strings.
+    Parquet,
+}
+
+/// One payload column in CSV output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvColumnConfig {
+    /// Header name.
+    pub name: String,
+    /// Dotted path rooted at `payload`, e.g. `payload.userIdentity.arn`.
+    pub path: String,
+}
+
+/// JSONL writer options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonlOptions {
+    pub compression: Option<String>,
+    /// Writes CloudTrail digest files alongside CloudTrail log files.
+    pub digest: Option<CloudTrailDigestOptions>,
+    /// Writes CloudTrail files under `AWSLogs/<account>/CloudTrail/<region>/YYYY/MM/DD/`.
+    #[serde(default)]
+    pub s3_layout: bool,
+}
+
+/// CloudTrail log file integrity digest options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudTrailDigestOptions {
+    /// Trail name used in digest file names.
+    #[serde(default = "default_digest_trail_name")]
+    pub trail_name: String,
+    /// Trail home region used in digest file names.
+    #[serde(default = "default_digest_home_region")]
+    pub home_region: String,
+    /// Bucket reported as `s3Bucket` and `digestS3Bucket`.
+    #[serde(default = "default_digest_s3_bucket")]
+    pub s3_bucket: String,
+    /// Prefix prepended to file names to form `s3Object` keys.
+    pub s3_key_prefix: Option<String>,
+    /// Seconds between digests per account/region (CloudTrail delivers hourly).
+    #[serde(default = "default_digest_interval_seconds")]
+    pub interval_seconds: u64,
+    /// Dummy key the placeholder signatures are derived from.
+    #[serde(default = "default_digest_signing_key")]
+    pub signing_key: String,
+}
+
+/// Parquet writer properties.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParquetOptions {
+    /// `none` (default), `snappy`, `gzip`, `zstd`, `lz4`, or `brotli`.
+    pub compression: Option<String>,
+    /// Codec level for `gzip` (0-9), `zstd` (1-22), and `brotli` (0-11).
+    pub compression_level: Option<u32>,
+    /// Maximum rows per row group (Parquet default: 1048576).
+    pub row_group_size: Option<usize>,
+    /// Dictionary encoding for all columns (default: enabled).
+    pub dictionary: Option<bool>,
+    /// Column statistics level: `none`, `chunk`, or `page` (default).
+    pub statistics: Option<String>,
+    /// Writes `envelope.timestamp` and `cloudtrail.eventTime` as RFC3339 strings
+    /// instead of `Timestamp(Microsecond, UTC)` (default: false).
+    pub timestamp_strings: Option<bool>,
+    /// Background threads that encode and write finished files (default: 1);
+    /// 0 encodes inline on the writer thread.
+    pub encoder_threads: Option<usize>,
+    /// Finished batches that may wait for an encoder thread before writes block (default: 4).
+    pub encoder_queue: Option<usize>,
+}
+
+/// Iceberg table options.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IcebergOptions {
+    /// Table location recorded in metadata, e.g. `s3://bucket/prefix` when the
+    /// output directory is uploaded there (default: `file://` + the output directory).
+    pub location: Option<String>,
+    /// Writer properties for the Parquet data files.
+    #[serde(flatten)]
+    pub parquet: ParquetOptions,
+}
+
+/// CloudWatch Logs / Firehose framing options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudWatchLogsOptions {
+    /// `logGroup` value; defaults to a per-source group name.
+    pub log_group: Option<String>,
+    /// `logStream` value; defaults to `<account>_<source>_<region>`.
+    pub log_stream: Option<String>,
+    /// Subscription filter name reported in `subscriptionFilters`.
+    #[serde(default = "default_cloudwatch_subscription_filter")]
+    pub subscription_filter: String,
+    /// Maximum log events per `DATA_MESSAGE` record.
+    #[serde(default = "default_cloudwatch_events_per_message")]
+    pub events_per_message: usize,
+}
+
+/// Azure Monitor diagnostic export options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureMonitorOptions {
+    /// `gzip` writes `.json.gz` files.
+    pub compression: Option<String>,
+    /// `resourceId` value; defaults to `/tenants/<tenant>/providers/Microsoft.aadiam`.
+    pub resource_id: Option<String>,
+    /// Fixed `category`; defaults to `SignInLogs` or `AuditLogs` for Entra ID
+    /// events and to the source name otherwise.
+    pub category: Option<String>,
+}
+
 /// Zerobus output sink configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZerobusOutputConfig {
@@ -162,115 +715,1088 @@ pub struct ZerobusOutputConfig {
     pub tables: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ZerobusOutputType {
-    Zerobus,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZerobusOutputType {
+    Zerobus,
+}
+
+/// Databricks Unity Catalog volume output sink configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabricksVolumeOutputConfig {
+    #[serde(rename = "type")]
+    pub output_type: DatabricksVolumeOutputType,
+    /// Databricks workspace URL, for example `https://dbc-...cloud.databricks.com`.
+    pub workspace_url: String,
+    /// Base UC volume directory, for example `/Volumes/main/seclog/raw/seclog`.
+    pub volume_path: String,
+    /// Environment variable containing a Databricks bearer token.
+    #[serde(default = "default_databricks_volume_token_env")]
+    pub token_env: String,
+    /// Target uncompressed file size before upload.
+    #[serde(default = "default_databricks_volume_target_size_mb")]
+    pub target_size_mb: u64,
+    /// Maximum buffered age before upload.
+    #[serde(default = "default_databricks_volume_max_age_seconds")]
+    pub max_age_seconds: u64,
+    /// Periodic flush cadence used by the generator loop.
+    #[serde(default = "default_databricks_volume_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Optional compression for uploaded JSON files. Supported: `gzip`, `gz`.
+    pub compression: Option<String>,
+    /// Files API overwrite flag. Generated names are unique, so false is the safer default.
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DatabricksVolumeOutputType {
+    #[serde(
+        rename = "databricks_volume",
+        alias = "databricks_files",
+        alias = "volume"
+    )]
+    DatabricksVolume,
+}
+
+/// Streams events as NDJSON to the process's stdout or stderr.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StdoutOutputConfig {
+    #[serde(rename = "type")]
+    pub output_type: StdoutOutputType,
+    /// Stream to write events to.
+    #[serde(default)]
+    pub stream: OutputStream,
+    /// Periodic flush cadence used by the generator loop.
+    #[serde(default = "default_stdout_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+}
+
+impl Default for StdoutOutputConfig {
+    fn default() -> Self {
+        Self {
+            output_type: StdoutOutputType::Stdout,
+            stream: OutputStream::Stdout,
+            flush_interval_ms: default_stdout_flush_interval_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StdoutOutputType {
+    Stdout,
+}
+
+/// Streams events as Arrow IPC record batches with the Parquet event schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArrowIpcOutputConfig {
+    #[serde(rename = "type")]
+    pub output_type: ArrowIpcOutputType,
+    /// File or named pipe to write one stream to; `-` writes to stdout.
+    pub path: Option<String>,
+    /// TCP address to serve the stream on; each client gets the schema, then live batches.
+    pub listen: Option<String>,
+    /// Rows per record batch.
+    #[serde(default = "default_arrow_ipc_batch_size")]
+    pub batch_size: usize,
+    /// Periodic flush cadence; a partial batch is sent on every flush.
+    #[serde(default = "default_arrow_ipc_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Writes timestamp columns as RFC3339 strings, like Parquet `timestamp_strings`.
+    #[serde(default)]
+    pub timestamp_strings: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrowIpcOutputType {
+    ArrowIpc,
+}
+
+/// Appends events to a DuckDB table with the Parquet event schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuckDbOutputConfig {
+    #[serde(rename = "type")]
+    pub output_type: DuckDbOutputType,
+    /// Database file; created when the writer opens it if missing.
+    pub path: String,
+    /// Table to append to; created from the event schema if missing.
+    #[serde(default = "default_duckdb_table")]
+    pub table: String,
+    /// Rows per committed batch.
+    #[serde(default = "default_duckdb_batch_size")]
+    pub batch_size: usize,
+    /// Periodic flush cadence; buffered rows are committed on every flush.
+    #[serde(default = "default_duckdb_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Writes timestamp columns as RFC3339 strings, like Parquet `timestamp_strings`.
+    #[serde(default)]
+    pub timestamp_strings: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DuckDbOutputType {
+    #[serde(rename = "duckdb")]
+    DuckDb,
+}
+
+/// Sends events to a Splunk HTTP Event Collector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplunkHecOutputConfig {
+    #[serde(rename = "type")]
+    pub output_type: SplunkHecOutputType,
+    /// HEC base URL, for example `https://splunk.example.com:8088`.
+    pub url: String,
+    /// Environment variable containing the HEC token.
+    #[serde(default = "default_splunk_hec_token_env")]
+    pub token_env: String,
+    /// Default index; omitted to use the token's default index.
+    pub index: Option<String>,
+    /// Per-source indexes keyed by envelope source; overrides `index`.
+    #[serde(default)]
+    pub indexes: HashMap<String, String>,
+    /// Per-source sourcetypes keyed by envelope source; overrides the built-in defaults.
+    #[serde(default)]
+    pub sourcetypes: HashMap<String, String>,
+    /// `host` value sent with every event.
+    pub host: Option<String>,
+    /// Events per HEC request.
+    #[serde(default = "default_splunk_hec_batch_size")]
+    pub batch_size: usize,
+    /// HEC requests sent at once; later batches wait for a free slot.
+    #[serde(default = "default_splunk_hec_max_in_flight")]
+    pub max_in_flight: usize,
+    /// Periodic flush cadence; buffered events are sent and acks polled on every flush.
+    #[serde(default = "default_splunk_hec_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Optional request compression. Supported: `gzip`.
+    pub compression: Option<String>,
+    /// Polls `/services/collector/ack` until every request is indexed.
+    #[serde(default)]
+    pub ack: bool,
+    /// Request channel GUID; generated when `ack` is set and this is omitted.
+    pub channel: Option<String>,
+    /// How long a request may stay unacknowledged before the run fails.
+    #[serde(default = "default_splunk_hec_ack_timeout_ms")]
+    pub ack_timeout_ms: u64,
+    /// Retries for `503` (server busy) and `429` responses, with exponential backoff.
+    #[serde(default = "default_splunk_hec_max_retries")]
+    pub max_retries: u32,
+    /// Skips TLS certificate verification for self-signed HEC endpoints.
+    #[serde(default)]
+    pub insecure_skip_verify: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplunkHecOutputType {
+    SplunkHec,
+}
+
+/// Sends events to Microsoft Sentinel custom tables through the Azure Monitor
+/// Logs Ingestion API and a data collection rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentinelOutputConfig {
+    #[serde(rename = "type")]
+    pub output_type: SentinelOutputType,
+    /// Logs ingestion endpoint of the data collection endpoint or rule, for
+    /// example `https://my-dce-a1b2.eastus-1.ingest.monitor.azure.com`.
+    pub endpoint: String,
+    /// Immutable ID (`dcr-...`) of the data collection rule.
+    pub dcr_immutable_id: String,
+    /// Default DCR stream, for example `Custom-SeclogEvents`.
+    pub stream: Option<String>,
+    /// Per-source DCR streams keyed by envelope source; overrides `stream`.
+    #[serde(default)]
+    pub streams: HashMap<String, String>,
+    /// Environment variable containing the Entra ID tenant ID.
+    #[serde(default = "default_sentinel_tenant_id_env")]
+    pub tenant_id_env: String,
+    /// Environment variable containing the app registration's client ID.
+    #[serde(default = "default_sentinel_client_id_env")]
+    pub client_id_env: String,
+    /// Environment variable containing the app registration's client secret.
+    #[serde(default = "default_sentinel_client_secret_env")]
+    pub client_secret_env: String,
+    /// Entra ID authority host; change for sovereign clouds.
+    #[serde(default = "default_sentinel_authority_host")]
+    pub authority_host: String,
+    /// Maximum rows per upload.
+    #[serde(default = "default_sentinel_batch_size")]
+    pub batch_size: usize,
+    /// Maximum uncompressed JSON bytes per upload; the API rejects calls over 1 MB.
+    #[serde(default = "default_sentinel_max_request_bytes")]
+    pub max_request_bytes: usize,
+    /// Uploads sent at once; later batches wait for a free slot.
+    #[serde(default = "default_sentinel_max_in_flight")]
+    pub max_in_flight: usize,
+    /// Periodic flush cadence for buffered rows.
+    #[serde(default = "default_sentinel_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Optional request compression. Supported: `gzip`.
+    pub compression: Option<String>,
+    /// Retries for throttled (`429`) and server error responses.
+    #[serde(default = "default_sentinel_max_retries")]
+    pub max_retries: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SentinelOutputType {
+    Sentinel,
+}
+
+/// Standard stream used by stdout output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+fn default_zerobus_client_id_env() -> String {
+    "DATABRICKS_CLIENT_ID".to_string()
+}
+
+fn default_zerobus_client_secret_env() -> String {
+    "DATABRICKS_CLIENT_SECRET".to_string()
+}
+
+fn default_session_correlation() -> f64 {
+    1.0
+}
+
+fn default_digest_trail_name() -> String {
+    "seclog-trail".to_string()
+}
+
+fn default_digest_home_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_digest_s3_bucket() -> String {
+    "seclog-cloudtrail".to_string()
+}
+
+fn default_digest_interval_seconds() -> u64 {
+    3600
+}
+
+fn default_digest_signing_key() -> String {
+    "seclog-dummy-digest-key".to_string()
+}
+
+fn default_cloudwatch_subscription_filter() -> String {
+    "seclog-firehose".to_string()
+}
+
+fn default_cloudwatch_events_per_message() -> usize {
+    100
+}
+
+fn default_stdout_flush_interval_ms() -> u64 {
+    200
+}
+
+fn default_arrow_ipc_batch_size() -> usize {
+    1024
+}
+
+fn default_arrow_ipc_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_duckdb_table() -> String {
+    "events".to_string()
+}
+
+fn default_duckdb_batch_size() -> usize {
+    10_000
+}
+
+fn default_duckdb_flush_interval_ms() -> u64 {
+    5000
+}
+
+fn default_splunk_hec_token_env() -> String {
+    "SPLUNK_HEC_TOKEN".to_string()
+}
+
+fn default_splunk_hec_batch_size() -> usize {
+    500
+}
+
+fn default_splunk_hec_max_in_flight() -> usize {
+    4
+}
+
+fn default_splunk_hec_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_splunk_hec_ack_timeout_ms() -> u64 {
+    60_000
+}
+
+fn default_splunk_hec_max_retries() -> u32 {
+    3
+}
+
+fn default_sentinel_tenant_id_env() -> String {
+    "AZURE_TENANT_ID".to_string()
+}
+
+fn default_sentinel_client_id_env() -> String {
+    "AZURE_CLIENT_ID".to_string()
+}
+
+fn default_sentinel_client_secret_env() -> String {
+    "AZURE_CLIENT_SECRET".to_string()
+}
+
+fn default_sentinel_authority_host() -> String {
+    "https://login.microsoftonline.com".to_string()
+}
+
+fn default_sentinel_batch_size() -> usize {
+    500
+}
+
+fn default_sentinel_max_request_bytes() -> usize {
+    1_000_000
+}
+
+fn default_sentinel_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_sentinel_max_in_flight() -> usize {
+    4
+}
+
+fn default_sentinel_max_retries() -> u32 {
+    3
+}
+
+fn default_zerobus_batch_size() -> usize {
+    500
+}
+
+fn default_zerobus_max_inflight_requests() -> usize {
+    10_000
+}
+
+fn default_zerobus_flush_interval_ms() -> u64 {
+    1_000
+}
+
+fn default_databricks_volume_token_env() -> String {
+    "DATABRICKS_TOKEN".to_string()
+}
+
+fn default_databricks_volume_target_size_mb() -> u64 {
+    50
+}
+
+fn default_databricks_volume_max_age_seconds() -> u64 {
+    30
+}
+
+fn default_databricks_volume_flush_interval_ms() -> u64 {
+    1_000
+}
+
+/// Source configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum SourceConfig {
+    #[serde(rename = "cloudtrail", alias = "cloud_trail")]
+    CloudTrail(CloudTrailSourceConfig),
+    #[serde(rename = "databricks_audit", alias = "databricks")]
+    DatabricksAudit(DatabricksAuditSourceConfig),
+    #[serde(rename = "okta", alias = "okta_system_log")]
+    OktaSystemLog(OktaSystemLogSourceConfig),
+    #[serde(rename = "github_audit", alias = "github")]
+    GitHubAudit(GitHubAuditSourceConfig),
+    #[serde(rename = "kubernetes_audit", alias = "k8s_audit")]
+    KubernetesAudit(KubernetesAuditSourceConfig),
+    #[serde(rename = "windows_security", alias = "windows")]
+    WindowsSecurity(WindowsSecuritySourceConfig),
+    #[serde(rename = "google_workspace", alias = "gws")]
+    GoogleWorkspace(GoogleWorkspaceSourceConfig),
+    #[serde(rename = "multi", alias = "combined")]
+    Multi(MultiSourceConfig),
+}
+
+impl SourceConfig {
+    /// Envelope `source` values this config emits, in config order.
+    pub fn envelope_sources(&self) -> Vec<String> {
+        match self {
+            SourceConfig::CloudTrail(config) => {
+                let derived = [
+                    config
+                        .s3_access_logs
+                        .as_ref()
+                        .map(|logs| ("s3_access_log", logs.include_cloudtrail)),
+                    config
+                        .resolver_query_logs
+                        .as_ref()
+                        .map(|logs| ("route53_resolver", logs.include_cloudtrail)),
+                    config
+                        .alb_access_logs
+                        .as_ref()
+                        .map(|logs| ("alb_access_log", logs.include_cloudtrail)),
+                ];
+                let derived = derived.into_iter().flatten().collect::<Vec<_>>();
+                let mut sources = Vec::new();
+                if derived.iter().all(|(_, include)| *include != Some(false)) {
+                    sources.push("cloudtrail".to_string());
+                }
+                if config.entra_federation.is_some() {
+                    sources.push("entra_signin".to_string());
+                    if config.credential_rotation.is_some() {
+                        sources.push("entra_audit".to_string());
+                    }
+                }
+                sources.extend(derived.iter().map(|(name, _)| name.to_string()));
+                sources
+            }
+            SourceConfig::DatabricksAudit(_) => vec!["databricks_audit".to_string()],
+            SourceConfig::OktaSystemLog(_) => vec!["okta_system_log".to_string()],
+            SourceConfig::GitHubAudit(_) => vec!["github_audit".to_string()],
+            SourceConfig::KubernetesAudit(_) => vec!["kubernetes_audit".to_string()],
+            SourceConfig::WindowsSecurity(_) => vec!["windows_security".to_string()],
+            SourceConfig::GoogleWorkspace(_) => vec!["google_workspace".to_string()],
+            SourceConfig::Multi(config) => config
+                .sources
+                .iter()
+                .flat_map(SourceConfig::envelope_sources)
+                .collect(),
+        }
+    }
+}
+
+/// Inter-arrival distribution for baseline actor activity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrivalProcess {
+    /// Exponential gaps (Poisson arrivals).
+    #[default]
+    Poisson,
+    /// Heavy-tailed Pareto gaps with the same mean rate.
+    Pareto,
+    /// Evenly spaced gaps at the actor rate.
+    Fixed,
+}
+
+/// Arrival-process settings for a source.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArrivalConfig {
+    /// Gap distribution.
+    #[serde(default)]
+    pub process: ArrivalProcess,
+    /// Pareto tail index; must be greater than 1 (default 1.5).
+    pub pareto_shape: Option<f64>,
+    /// Chance that a bursty service actor's next gap uses a burst rate (default 0.12).
+    pub burst_probability: Option<f64>,
+    /// Rate multiplier range `[min, max]` during a burst (default `[2.0, 5.0]`).
+    pub burst_multiplier: Option<[f64; 2]>,
+    /// Intra-day curve of human activity inside the active window.
+    pub workday: Option<WorkdayConfig>,
+}
+
+/// Rate factors that shape a human's working day.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkdayConfig {
+    /// Factor at the start of the window, rising to 1 over the first hour (default 0.55).
+    pub ramp_up_from: Option<f64>,
+    /// Factor `[start, end]` over the last hour of the window (default `[0.9, 0.4]`).
+    pub wind_down: Option<[f64; 2]>,
+    /// Local hour the lunch dip starts (default 12).
+    pub lunch_hour: Option<u8>,
+    /// Factors for the lunch hour and the half hour after it (default `[0.4, 0.75]`).
+    pub lunch_dip: Option<[f64; 2]>,
+    /// Share of working half-hours spent in meetings (default 0.12).
+    pub meeting_share: Option<f64>,
+    /// Factor during a meeting (default 0.2).
+    pub meeting_factor: Option<f64>,
+    /// Factor for the remaining working hours; above 1 so a day keeps
+    /// roughly its volume (default 1.3).
+    pub working_factor: Option<f64>,
+}
+
+/// CloudTrail-specific configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudTrailSourceConfig {
+    /// Use curated event weights for CloudTrail.
+    pub curated: bool,
+    /// Optional path to an actor population Parquet file.
+    pub actor_population_path: Option<String>,
+    /// Optional path to a shared identity registry TOML file.
+    pub identity_registry_path: Option<String>,
+    /// Narrows the population or registry to a sub-population for this source.
+    pub actor_selector: Option<ActorSelectorConfig>,
+    /// Inter-arrival process and burst behavior for baseline actor activity.
+    pub arrival: Option<ArrivalConfig>,
+    /// Optional deterministic source IP pools for registry-backed rows, keyed by actor ID.
+    pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
+    /// Allowed regions.
+    pub regions: Option<Vec<String>>,
+    /// Optional region weighting for selection.
+    pub region_distribution: Option<Vec<f64>>,
+    /// Optional path to a custom event catalog (YAML, JSON, or TOML).
+    pub catalog_path: Option<String>,
+    /// Run service actors through role-chained, multi-service workflows.
+    #[serde(default)]
+    pub service_call_graph: bool,
+    /// Web identity federation keyed by service profile (`generic`, `ec2_reaper`, ...).
+    pub web_identity: Option<HashMap<String, WebIdentityConfig>>,
+    /// Federates human console sign-ins through Entra ID.
+    pub entra_federation: Option<EntraFederationConfig>,
+    /// Runs human sessions through task narratives instead of only
+    /// next-event transitions.
+    pub narratives: Option<NarrativeConfig>,
+    /// Emits S3 server access log records for S3 object-level events.
+    pub s3_access_logs: Option<S3AccessLogConfig>,
+    /// Emits Route 53 Resolver query logs for workload API calls.
+    pub resolver_query_logs: Option<ResolverQueryLogConfig>,
+    /// Emits Application Load Balancer access logs for human console activity.
+    pub alb_access_logs: Option<AlbAccessLogConfig>,
+    /// Emits data events (S3 object, Lambda invoke, DynamoDB item) alongside management events.
+    pub data_events: Option<DataEventsConfig>,
+    /// Stable per-account buckets, roles, instances, log groups, and KMS keys
+    /// that templates draw from.
+    pub resource_pool: Option<ResourcePoolConfig>,
+    /// Stolen-credential takeovers of existing human actors.
+    #[serde(default, rename = "credential_takeover")]
+    pub credential_takeovers: Vec<CredentialTakeoverConfig>,
+    /// Service actors that join the run at a point in time.
+    #[serde(default, rename = "actor_injection")]
+    pub actor_injections: Vec<ActorInjectionConfig>,
+    /// Per-event error rates and codes keyed by event name; `*` applies to other events.
+    pub error_profiles: Option<HashMap<String, EventErrorConfig>>,
+    /// Time windows with elevated error rates.
+    #[serde(default, rename = "error_surge")]
+    pub error_surges: Vec<ErrorSurgeConfig>,
+    /// Throttling storms of service actors against one AWS service.
+    #[serde(default, rename = "api_storm")]
+    pub api_storms: Vec<ApiStormConfig>,
+    /// Per-account caps on calls to AWS services, like service quotas.
+    #[serde(default, rename = "rate_limit")]
+    pub rate_limits: Vec<RateLimitConfig>,
+    /// Waves of IAM policy changes by a remediation automation actor.
+    #[serde(default, rename = "policy_remediation")]
+    pub policy_remediations: Vec<PolicyRemediationConfig>,
+    /// Human actors who sign in from one city and work from a distant one
+    /// minutes later.
+    #[serde(default, rename = "impossible_travel")]
+    pub impossible_travels: Vec<ImpossibleTravelConfig>,
+    /// Periodic access key and client secret rotation for service actors.
+    pub credential_rotation: Option<CredentialRotationConfig>,
+    /// Derives `eventID` and `requestID` from the seed, actor, and the actor's
+    /// event sequence instead of the RNG.
+    #[serde(default)]
+    pub deterministic_ids: bool,
+    /// Starts human sessions from the identity's shared cross-source clock.
+    #[serde(default)]
+    pub linked_sessions: bool,
+    /// Share of linked sessions that line up with the other linked sources.
+    #[serde(default = "default_session_correlation")]
+    pub session_correlation: f64,
+}
+
+/// Sub-population filter applied to actors loaded from a shared pool.
+///
+/// All set criteria must match; `sample` is applied to the actors that do.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActorSelectorConfig {
+    /// Keeps actors carrying any of these tags.
+    pub tags: Option<Vec<String>>,
+    /// `human` or `service`.
+    pub kind: Option<String>,
+    /// Human roles to keep: `admin`, `developer`, `readonly`, `auditor`.
+    pub roles: Option<Vec<String>>,
+    /// Attribute expressions that must all hold, e.g. `rate_per_hour > 20`.
+    #[serde(default, rename = "where")]
+    pub conditions: Vec<String>,
+    /// Fraction (0-1] of matching actors to keep, chosen stably per principal.
+    pub sample: Option<f64>,
+}
+
+/// Error rate and codes for one CloudTrail event name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventErrorConfig {
+    /// Error probability for the event; overrides the actor's error rate.
+    pub rate: Option<f64>,
+    /// Weighted error codes; defaults to the built-in code for the event.
+    #[serde(default, rename = "error")]
+    pub errors: Vec<ErrorCodeConfig>,
+}
+
+/// One possible error code for an event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorCodeConfig {
+    pub code: String,
+    /// Error message; defaults to a generic message for the code.
+    pub message: Option<String>,
+    /// Relative weight among the event's codes (default: 1.0).
+    pub weight: Option<f64>,
+}
+
+/// Window during which matching events fail more often, e.g. an outage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorSurgeConfig {
+    /// Seconds after generation start when the surge begins (default: 0).
+    pub offset_seconds: Option<i64>,
+    /// RFC 3339 surge start; overrides `offset_seconds`.
+    pub start_time: Option<String>,
+    pub duration_seconds: i64,
+    /// Error probability during the surge.
+    pub rate: f64,
+    /// Event names affected; all events when unset.
+    pub events: Option<Vec<String>>,
+    /// Weighted error codes during the surge; defaults to each event's codes.
+    #[serde(default, rename = "error")]
+    pub errors: Vec<ErrorCodeConfig>,
+}
+
+/// Attacker sessions that reuse a human actor's credentials from a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialTakeoverConfig {
+    /// Victim actor ID (population `id`, registry `actor_id`) or principal ID.
+    pub actor_id: String,
+    /// Seconds after generation start when the credentials are stolen (default: 0).
+    pub offset_seconds: Option<i64>,
+    /// RFC 3339 time the credentials are stolen; overrides `offset_seconds`.
+    pub start_time: Option<String>,
+    /// How long the attacker keeps using the credentials; unbounded when unset.
+    pub duration_seconds: Option<i64>,
+    /// Attacker API call rate (default: 30).
+    pub events_per_hour: Option<f64>,
+    /// Attacker source IPs; defaults to hosting and VPN ranges unrelated to the victim.
+    pub source_ips: Option<Vec<String>>,
+    /// Attacker user agents; defaults to CLI and SDK tooling.
+    pub user_agents: Option<Vec<String>>,
+    /// Regions the attacker calls; defaults to the source's regions.
+    pub regions: Option<Vec<String>>,
+    /// Scenario label for attacker events (default: `credential_takeover`).
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id` (default: `T1078.004`).
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+impl CredentialTakeoverConfig {
+    /// Scenario label for attacker events.
+    pub fn scenario_id(&self) -> String {
+        self.scenario_id
+            .clone()
+            .unwrap_or_else(|| "credential_takeover".to_string())
+    }
+
+    /// Technique IDs for attacker events; Valid Accounts: Cloud Accounts when none are set.
+    pub fn technique_ids(&self) -> Vec<String> {
+        let ids = self
+            .technique_id
+            .iter()
+            .chain(&self.technique_ids)
+            .cloned()
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            vec!["T1078.004".to_string()]
+        } else {
+            ids
+        }
+    }
+}
+
+/// Service actors that join a running CloudTrail stream, e.g. a new automation
+/// account that suddenly takes a share of the traffic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorInjectionConfig {
+    /// Actor ID; numbered `<id>-1`, `<id>-2`, ... when `count` is above 1.
+    pub id: String,
+    /// Seconds after generation start when the actors join (default: 0).
+    pub offset_seconds: Option<i64>,
+    /// RFC 3339 time the actors join; overrides `offset_seconds`.
+    pub start_time: Option<String>,
+    /// How long the actors keep calling; unbounded when unset.
+    pub duration_seconds: Option<i64>,
+    /// Number of actors to add (default: 1).
+    pub count: Option<usize>,
+    /// `generic`, `ec2_reaper`, `datalake_bot`, `logs_shipper`, or `metrics_collector` (default: `generic`).
+    pub service_profile: Option<String>,
+    /// Share (0-1) of the configured call rate the actors take once they join.
+    pub traffic_share: Option<f64>,
+    /// Per-actor call rate when `traffic_share` is unset (default: 60).
+    pub events_per_hour: Option<f64>,
+    /// AWS account of the actors; defaults to the first actor's account.
+    pub account_id: Option<String>,
+    /// Error rate of the actors (default: 0.01).
+    pub error_rate: Option<f64>,
+    /// Scenario label for the actors' events; unlabeled when unset.
+    pub scenario_id: Option<String>,
+}
+
+/// Burst of throttled calls by many service actors against one AWS service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiStormConfig {
+    /// Event source the storm targets, e.g. `ec2.amazonaws.com`.
+    pub service: String,
+    /// Seconds after generation start when the storm begins (default: 0).
+    pub offset_seconds: Option<i64>,
+    /// RFC 3339 storm start; overrides `offset_seconds`.
+    pub start_time: Option<String>,
+    pub duration_seconds: i64,
+    /// Number of service actors taking part (default: 10, capped at the service actors available).
+    pub actors: Option<usize>,
+    /// Calls per second across all participating actors (default: 20).
+    pub events_per_second: Option<f64>,
+    /// Fraction of storm calls that are throttled (default: 0.85).
+    pub error_rate: Option<f64>,
+    /// Error code for throttled calls; defaults to `RequestLimitExceeded` for EC2
+    /// and `ThrottlingException` otherwise.
+    pub error_code: Option<String>,
+    /// Event names to call; defaults to the enabled events of `service`.
+    pub events: Option<Vec<String>>,
+    /// Region of the storm; defaults to one region picked from the source's regions.
+    pub region: Option<String>,
+    /// Scenario label for storm events (default: `api_storm`).
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`; none by default.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+impl ApiStormConfig {
+    /// Scenario label for storm events.
+    pub fn scenario_id(&self) -> String {
+        self.scenario_id
+            .clone()
+            .unwrap_or_else(|| "api_storm".to_string())
+    }
+}
+
+/// Per-account cap on actor calls to one AWS service, like a service quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Event source capped, e.g. `iam.amazonaws.com`.
+    pub service: String,
+    /// Calls an account may make within `window_seconds`.
+    pub max_calls: usize,
+    /// Length of the sliding window calls are counted in (default: 3600).
+    pub window_seconds: Option<i64>,
+    /// Event names counted; defaults to every event of `service`.
+    pub events: Option<Vec<String>>,
+    /// Counts only read-only (`true`) or only mutating (`false`) calls.
+    pub read_only: Option<bool>,
+    /// What happens to a call over the cap (default: `throttle`).
+    #[serde(default)]
+    pub on_limit: RateLimitAction,
+    /// Error code for throttled calls; defaults to `RequestLimitExceeded` for
+    /// EC2 and `ThrottlingException` otherwise.
+    pub error_code: Option<String>,
+}
+
+/// Outcome of a call over a rate limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitAction {
+    /// The call goes out failed with the service's throttling error.
+    #[default]
+    Throttle,
+    /// The actor retries the call once the window has room.
+    Defer,
+}
+
+/// Credential lifecycle for service actors: each rotates its long-term access
+/// key, and its Entra application client secret, on a fixed interval.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialRotationConfig {
+    /// Days between rotations of an actor's credentials (default: 90).
+    pub interval_days: Option<f64>,
+    /// Hours after a rotation until the old access key is deactivated (default: 24).
+    pub deactivate_after_hours: Option<f64>,
+    /// Hours after deactivation until the old access key is deleted (default: 168).
+    pub delete_after_hours: Option<f64>,
+}
+
+/// Misconfiguration remediation wave: an automation actor rewrites IAM role
+/// policies across accounts while humans spot-check some of the changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyRemediationConfig {
+    /// Automation actor ID (population `id`, registry `actor_id`) or principal
+    /// ID; defaults to a service actor picked at random.
+    pub actor_id: Option<String>,
+    /// Seconds after generation start when the wave begins (default: 0).
+    pub offset_seconds: Option<i64>,
+    /// RFC 3339 wave start; overrides `offset_seconds`.
+    pub start_time: Option<String>,
+    /// Wave length (default: 3600).
+    pub duration_seconds: Option<i64>,
+    /// Number of policy changes in the wave (default: 60).
+    pub changes: Option<usize>,
+    /// Accounts the changes land in; defaults to every actor account.
+    pub accounts: Option<Vec<String>>,
+    /// Role the automation assumes in each account (default: `SecurityRemediation`).
+    pub role_name: Option<String>,
+    /// Human actor IDs that spot-check changes; defaults to the human admins
+    /// and auditors.
+    pub reviewers: Option<Vec<String>>,
+    /// Fraction of changes a reviewer spot-checks (default: 0.2).
+    pub spot_check_rate: Option<f64>,
+    /// Scenario label for wave events (default: `policy_remediation`).
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`; none by default.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+impl PolicyRemediationConfig {
+    /// Scenario label for wave events.
+    pub fn scenario_id(&self) -> String {
+        self.scenario_id
+            .clone()
+            .unwrap_or_else(|| "policy_remediation".to_string())
+    }
+}
+
+/// Impossible travel: a human actor signs in to Entra ID from one city and
+/// uses AWS from a distant one minutes later.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImpossibleTravelConfig {
+    /// Human actor ID (population `id`, registry `actor_id`) or principal ID.
+    pub actor_id: String,
+    /// Seconds after generation start of the Entra sign-in (default: 0).
+    pub offset_seconds: Option<i64>,
+    /// RFC 3339 time of the Entra sign-in; overrides `offset_seconds`.
+    pub start_time: Option<String>,
+    /// Seconds between the sign-in and the AWS console login (default: 600).
+    pub gap_seconds: Option<i64>,
+    /// City of the Entra sign-in; defaults to the actor's home location when
+    /// it is a known city, otherwise New York.
+    pub sign_in_location: Option<String>,
+    /// Source IP of the Entra sign-in; defaults to an address in the city.
+    pub sign_in_ip: Option<String>,
+    /// City of the AWS activity; defaults to the known city farthest from
+    /// the sign-in.
+    pub activity_location: Option<String>,
+    /// Source IP of the AWS activity; defaults to an address in the city.
+    pub activity_ip: Option<String>,
+    /// CloudTrail calls from the activity city, `ConsoleLogin` first (default: 6).
+    pub events: Option<usize>,
+    /// Scenario label for the scenario's events (default: `impossible_travel`).
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id` (default: `T1078.004`).
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
 }
 
-/// Databricks Unity Catalog volume output sink configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DatabricksVolumeOutputConfig {
-    #[serde(rename = "type")]
-    pub output_type: DatabricksVolumeOutputType,
-    /// Databricks workspace URL, for example `https://dbc-...cloud.databricks.com`.
-    pub workspace_url: String,
-    /// Base UC volume directory, for example `/Volumes/main/seclog/raw/seclog`.
-    pub volume_path: String,
-    /// Environment variable containing a Databricks bearer token.
-    #[serde(default = "default_databricks_volume_token_env")]
-    pub token_env: String,
-    /// Target uncompressed file size before upload.
-    #[serde(default = "default_databricks_volume_target_size_mb")]
-    pub target_size_mb: u64,
-    /// Maximum buffered age before upload.
-    #[serde(default = "default_databricks_volume_max_age_seconds")]
-    pub max_age_seconds: u64,
-    /// Periodic flush cadence used by the generator loop.
-    #[serde(default = "default_databricks_volume_flush_interval_ms")]
-    pub flush_interval_ms: u64,
-    /// Optional compression for uploaded JSON files. Supported: `gzip`, `gz`.
-    pub compression: Option<String>,
-    /// Files API overwrite flag. Generated names are unique, so false is the safer default.
-    #[serde(default)]
-    pub overwrite: bool,
+impl ImpossibleTravelConfig {
+    /// CloudTrail calls from the activity city when `events` is unset.
+    pub const DEFAULT_EVENTS: usize = 6;
+
+    /// CloudTrail calls from the activity city.
+    pub fn events(&self) -> usize {
+        self.events.unwrap_or(Self::DEFAULT_EVENTS)
+    }
+
+    /// Scenario label for the scenario's events.
+    pub fn scenario_id(&self) -> String {
+        self.scenario_id
+            .clone()
+            .unwrap_or_else(|| "impossible_travel".to_string())
+    }
+
+    /// Technique IDs for the scenario's events; Valid Accounts: Cloud Accounts
+    /// when none are set.
+    pub fn technique_ids(&self) -> Vec<String> {
+        let ids = self
+            .technique_id
+            .iter()
+            .chain(&self.technique_ids)
+            .cloned()
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            vec!["T1078.004".to_string()]
+        } else {
+            ids
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub enum DatabricksVolumeOutputType {
-    #[serde(
-        rename = "databricks_volume",
-        alias = "databricks_files",
-        alias = "volume"
-    )]
-    DatabricksVolume,
+/// CloudTrail data event generation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataEventsConfig {
+    /// Upper bound on the fraction of CloudTrail events that are data events (default: 0.8).
+    pub max_share: Option<f64>,
+    /// Mean data events per burst started by a management call (default: 20).
+    pub burst_size: Option<u32>,
+    /// Relative weights of `s3`, `lambda`, and `dynamodb` (default: 0.7, 0.15, 0.15).
+    pub services: Option<HashMap<String, f64>>,
 }
 
-fn default_zerobus_client_id_env() -> String {
-    "DATABRICKS_CLIENT_ID".to_string()
+/// Per-account CloudTrail resource pool sizes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourcePoolConfig {
+    /// S3 buckets per account (default: 8).
+    pub buckets: Option<usize>,
+    /// IAM roles per account (default: 6).
+    pub roles: Option<usize>,
+    /// EC2 instances per account and region (default: 12).
+    pub instances: Option<usize>,
+    /// CloudWatch Logs log groups per account and region (default: 10).
+    pub log_groups: Option<usize>,
+    /// KMS keys per account and region (default: 4).
+    pub kms_keys: Option<usize>,
 }
 
-fn default_zerobus_client_secret_env() -> String {
-    "DATABRICKS_CLIENT_SECRET".to_string()
+/// S3 server access log records derived from CloudTrail S3 events.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct S3AccessLogConfig {
+    /// Keep the CloudTrail events alongside the access log records (default: true).
+    pub include_cloudtrail: Option<bool>,
+    /// Canonical user ID of the bucket owner; defaults to one derived per account.
+    pub bucket_owner: Option<String>,
 }
 
-fn default_zerobus_batch_size() -> usize {
-    500
+/// Route 53 Resolver query logs derived from CloudTrail workload activity.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResolverQueryLogConfig {
+    /// Keep the CloudTrail events alongside the query logs (default: true).
+    pub include_cloudtrail: Option<bool>,
+    /// Chance that a workload API call also looks up a third-party domain (default 0.3).
+    pub external_lookup_rate: Option<f64>,
+    /// Third-party domains for those lookups; defaults to a built-in list.
+    pub domains: Option<Vec<DomainWeight>>,
+    /// Share of third-party lookups for DGA-looking names (default 0.01).
+    pub dga_share: Option<f64>,
+    /// Share of third-party lookups for `known_bad_domains` (default 0.005).
+    pub known_bad_share: Option<f64>,
+    /// Known-bad domains; defaults to a built-in list.
+    pub known_bad_domains: Option<Vec<String>>,
+    /// Seconds a workload caches an answer before looking the name up again (default 60).
+    pub cache_ttl_seconds: Option<u64>,
 }
 
-fn default_zerobus_max_inflight_requests() -> usize {
-    10_000
+impl ResolverQueryLogConfig {
+    /// Scenario label for lookups of DGA-looking names.
+    pub const DGA_SCENARIO_ID: &'static str = "resolver_dga";
+    /// Dynamic Resolution: Domain Generation Algorithms.
+    pub const DGA_TECHNIQUE_ID: &'static str = "T1568.002";
+    /// Scenario label for lookups of known-bad domains.
+    pub const KNOWN_BAD_SCENARIO_ID: &'static str = "resolver_known_bad";
+    /// Application Layer Protocol: DNS.
+    pub const KNOWN_BAD_TECHNIQUE_ID: &'static str = "T1071.004";
+
+    /// Share of third-party lookups for DGA-looking names.
+    pub fn dga_share(&self) -> f64 {
+        self.dga_share.unwrap_or(0.01)
+    }
+
+    /// Share of third-party lookups for known-bad domains.
+    pub fn known_bad_share(&self) -> f64 {
+        self.known_bad_share.unwrap_or(0.005)
+    }
 }
 
-fn default_zerobus_flush_interval_ms() -> u64 {
-    1_000
+/// Weight for a domain in resolver query logs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainWeight {
+    pub name: String,
+    pub weight: f64,
 }
 
-fn default_databricks_volume_token_env() -> String {
-    "DATABRICKS_TOKEN".to_string()
+/// Application Load Balancer access logs derived from human console activity.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlbAccessLogConfig {
+    /// Keep the CloudTrail events alongside the access logs (default: true).
+    pub include_cloudtrail: Option<bool>,
+    /// Load balancer name, logged as `app/<name>/<id>` (default `internal-portal`).
+    pub load_balancer: Option<String>,
+    /// Host name clients request (default `portal.example.com`).
+    pub domain_name: Option<String>,
+    /// Chance that a console event is preceded by a visit to the app (default 0.5).
+    pub visit_rate: Option<f64>,
+    /// Most requests in one visit, including the page itself (default 8).
+    pub max_requests: Option<usize>,
+    /// Share of requests the load balancer fails with 502, 503, or 504 (default 0.01).
+    pub error_rate: Option<f64>,
 }
 
-fn default_databricks_volume_target_size_mb() -> u64 {
-    50
+/// Task narratives for human CloudTrail sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NarrativeConfig {
+    /// Share of human sessions that run a task (default: 0.5).
+    pub session_share: Option<f64>,
+    /// Include the built-in narratives (default: true).
+    pub builtin: Option<bool>,
+    /// Narrative files (YAML, JSON, or TOML) with additional tasks.
+    #[serde(default)]
+    pub paths: Vec<String>,
 }
 
-fn default_databricks_volume_max_age_seconds() -> u64 {
-    30
+/// Entra ID federation for human console sign-ins.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntraFederationConfig {
+    /// Entra tenant ID; defaults to one derived from the actors' email domain.
+    pub tenant_id: Option<String>,
+    /// How AWS trusts Entra ID (default `saml`).
+    pub protocol: Option<FederationProtocol>,
+    /// Enterprise application users sign in to
+    /// (default `AWS IAM Identity Center (successor to AWS Single Sign-On)`).
+    pub app_name: Option<String>,
+    /// IAM SAML provider name (default `AWSSSO_<id>_DO_NOT_DELETE`).
+    pub saml_provider: Option<String>,
+    /// Role name or ARN per actor role (`admin`, `developer`, `readonly`,
+    /// `auditor`); defaults to Identity Center permission set roles.
+    pub role_arns: Option<HashMap<String, String>>,
+    /// Per-actor Entra ID Protection risk for the sign-ins; no risk when unset.
+    pub risk: Option<OktaRiskConfig>,
 }
 
-fn default_databricks_volume_flush_interval_ms() -> u64 {
-    1_000
+/// Federation protocol between Entra ID and AWS STS.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FederationProtocol {
+    /// `AssumeRoleWithSAML` through an IAM SAML provider.
+    #[default]
+    Saml,
+    /// `AssumeRoleWithWebIdentity` through an IAM OIDC provider.
+    Oidc,
 }
 
-/// Source configuration.
+/// OIDC provider a service profile federates through instead of AssumeRole.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum SourceConfig {
-    #[serde(rename = "cloudtrail", alias = "cloud_trail")]
-    CloudTrail(CloudTrailSourceConfig),
-    #[serde(rename = "databricks_audit", alias = "databricks")]
-    DatabricksAudit(DatabricksAuditSourceConfig),
-    #[serde(rename = "okta", alias = "okta_system_log")]
-    OktaSystemLog(OktaSystemLogSourceConfig),
-    #[serde(rename = "multi", alias = "combined")]
-    Multi(MultiSourceConfig),
+pub struct WebIdentityConfig {
+    pub provider: WebIdentityProvider,
+    /// OIDC issuer host; defaults to a per-account EKS issuer or
+    /// `token.actions.githubusercontent.com`.
+    pub issuer: Option<String>,
+    /// Token subject; defaults to `system:serviceaccount:<namespace>:<name>` or
+    /// `repo:<org>/<repo>:ref:refs/heads/main`.
+    pub subject: Option<String>,
 }
 
-/// CloudTrail-specific configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CloudTrailSourceConfig {
-    /// Use curated event weights for CloudTrail.
-    pub curated: bool,
-    /// Optional path to an actor population Parquet file.
-    pub actor_population_path: Option<String>,
-    /// Optional path to a shared identity registry TOML file.
-    pub identity_registry_path: Option<String>,
-    /// Optional deterministic source IP pools for registry-backed rows, keyed by actor ID.
-    pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
-    /// Allowed regions.
-    pub regions: Option<Vec<String>>,
-    /// Optional region weighting for selection.
-    pub region_distribution: Option<Vec<f64>>,
+/// Web identity federation variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebIdentityProvider {
+    /// EKS IAM roles for service accounts.
+    EksIrsa,
+    /// GitHub Actions OIDC.
+    GithubOidc,
 }
 
 /// Composite source configuration.
@@ -287,6 +1813,45 @@ pub struct MultiSourceConfig {
     pub sources: Vec<SourceConfig>,
     /// Optional source-specific output sinks keyed by event envelope source.
     pub outputs: Option<HashMap<String, FileOutputConfig>>,
+    /// Schedule registry-backed child sources from one shared per-identity clock.
+    #[serde(default)]
+    pub link_sessions: bool,
+    /// Share of linked sessions that line up across child sources, from 0.0
+    /// (independent timing) to 1.0 (every session lines up); setting it links
+    /// sessions.
+    pub session_correlation: Option<f64>,
+    /// Optional tenants; when set, every child source runs once per tenant.
+    #[serde(default)]
+    pub tenants: Vec<TenantConfig>,
+    /// Optional caps keyed by event envelope source.
+    pub limits: Option<HashMap<String, SourceLimitsConfig>>,
+}
+
+/// Caps on one envelope source in a multi-source run; the source stops once
+/// any cap is reached while the other sources carry on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceLimitsConfig {
+    /// Events to emit.
+    pub max_events: Option<u64>,
+    /// Record bytes the output's writers may write for the source.
+    pub max_bytes: Option<u64>,
+    /// Simulated time after the run start, such as `"6h"` or `"2d"`.
+    pub max_sim_duration: Option<String>,
+}
+
+/// One tenant in a multi-tenant run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// Tenant identifier placed on the event envelope `tenant_id`.
+    pub id: String,
+    /// Relative event volume; tenants are thinned against the heaviest tenant.
+    pub weight: Option<f64>,
+    /// Shared identity registry for this tenant's actors.
+    pub identity_registry_path: Option<String>,
+    /// Actor population config used to synthesize this tenant's registry.
+    pub population_config_path: Option<String>,
+    /// Inline actor population config used to synthesize this tenant's registry.
+    pub population_config: Option<PopulationConfig>,
 }
 
 /// Databricks audit-log generation configuration.
@@ -303,6 +1868,14 @@ pub struct DatabricksAuditSourceConfig {
     pub baseline_events_per_actor: Option<usize>,
     /// Optional deterministic source IP pools for baseline rows, keyed by actor ID.
     pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
+    /// Schedule baseline rows from the identity's shared cross-source clock.
+    #[serde(default)]
+    pub linked_sessions: bool,
+    /// Share of linked sessions that line up with the other linked sources.
+    #[serde(default = "default_session_correlation")]
+    pub session_correlation: f64,
+    /// Inter-arrival process and burst behavior for baseline rows.
+    pub arrival: Option<ArrivalConfig>,
     /// Deterministic audit events to inject into the stream.
     #[serde(default, rename = "event")]
     pub events: Vec<DatabricksAuditEventConfig>,
@@ -327,6 +1900,13 @@ pub struct DatabricksAuditEventConfig {
     pub source_geo_country: Option<String>,
     pub source_geo_region: Option<String>,
     pub source_geo_city: Option<String>,
+    /// Scenario label written to the label sidecar; the audit row is unchanged.
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
 }
 
 /// Okta System Log generation configuration.
@@ -341,6 +1921,22 @@ pub struct OktaSystemLogSourceConfig {
     pub baseline_events_per_actor: Option<usize>,
     /// Optional deterministic source IP pools for baseline rows, keyed by actor ID.
     pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
+    /// Schedule baseline rows from the identity's shared cross-source clock.
+    #[serde(default)]
+    pub linked_sessions: bool,
+    /// Share of linked sessions that line up with the other linked sources.
+    #[serde(default = "default_session_correlation")]
+    pub session_correlation: f64,
+    /// Inter-arrival process and burst behavior for baseline rows.
+    pub arrival: Option<ArrivalConfig>,
+    /// Sign-on policy rules applied to baseline human sign-ins.
+    pub sign_on_policy: Option<OktaSignOnPolicyConfig>,
+    /// Risk-based sign-in modeling for baseline human sign-ins.
+    pub risk: Option<OktaRiskConfig>,
+    /// Directory of groups, admin roles, and app assignments that admins change.
+    pub directory: Option<OktaDirectoryConfig>,
+    /// Just-in-time super admin activations; requires `directory`.
+    pub privileged_access: Option<OktaPrivilegedAccessConfig>,
     /// Deterministic System Log events to inject into the stream.
     #[serde(default, rename = "event")]
     pub events: Vec<OktaSystemLogEventConfig>,
@@ -384,6 +1980,97 @@ pub struct OktaSystemLogEventConfig {
     pub security_context: Option<OktaSecurityContextConfig>,
     #[serde(default, rename = "target")]
     pub targets: Vec<OktaTargetConfig>,
+    /// Scenario label written to the label sidecar; the System Log row is unchanged.
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+/// Sign-on policy rules evaluated for baseline Okta sign-ins.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OktaSignOnPolicyConfig {
+    /// Challenge administrator personas for MFA on every sign-in (default: true).
+    pub require_mfa_for_admins: Option<bool>,
+    /// Deny sign-ins from legacy authentication clients (default: true).
+    pub block_legacy_auth: Option<bool>,
+    /// Fraction of human sign-ins attempted from legacy clients (default: 0.02).
+    pub legacy_auth_rate: Option<f64>,
+}
+
+/// Per-actor risk profiles for baseline Okta sign-ins and Entra federation
+/// sign-ins.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OktaRiskConfig {
+    /// Fraction of human actors with an elevated risk profile (default: 0.05).
+    pub risky_actor_ratio: Option<f64>,
+    /// Fraction of a risky actor's sign-ins that are risky (default: 0.2).
+    pub risky_sign_in_rate: Option<f64>,
+    /// Share of risky sign-ins from anonymizing proxies rather than unfamiliar
+    /// locations (default: 0.5).
+    pub anonymized_ip_share: Option<f64>,
+}
+
+/// Okta directory derived from the identity registry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OktaDirectoryConfig {
+    /// Actor IDs seeded into the `Okta Administrators` group; defaults to
+    /// admin personas.
+    pub admins: Option<Vec<String>>,
+    /// Fraction of an admin's sessions that make a directory change instead
+    /// of an app sign-on (default: 0.3).
+    pub admin_operation_rate: Option<f64>,
+    /// Fraction of a super admin's group changes that touch an admin-role
+    /// group (default: 0.05).
+    pub admin_group_share: Option<f64>,
+    /// Admin operation catalog files added to the built-in catalog.
+    #[serde(default)]
+    pub catalog_paths: Vec<String>,
+}
+
+/// Time-boxed super admin activations requested by eligible users.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OktaPrivilegedAccessConfig {
+    /// Actor IDs that may activate super admin; defaults to the `Help Desk`
+    /// members.
+    pub eligible: Option<Vec<String>>,
+    /// Fraction of an eligible user's sessions that request an activation
+    /// (default: 0.05).
+    pub activation_rate: Option<f64>,
+    /// How long an activation lasts before it is revoked (default: 60).
+    pub duration_minutes: Option<u64>,
+    /// Fraction of sessions during an activation that run an admin operation
+    /// (default: 0.5).
+    pub elevated_operation_rate: Option<f64>,
+    /// Fraction of activations granted without an approval (default: 0.1).
+    pub unapproved_share: Option<f64>,
+    /// Scenario label for the events of unapproved activations (default:
+    /// `unapproved_privileged_activation`).
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique IDs recorded with `scenario_id` (default: T1098.003).
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+impl OktaPrivilegedAccessConfig {
+    /// Scenario label for unapproved activations.
+    pub fn scenario_id(&self) -> String {
+        self.scenario_id
+            .clone()
+            .unwrap_or_else(|| "unapproved_privileged_activation".to_string())
+    }
+
+    /// Technique IDs for unapproved activations; Account Manipulation:
+    /// Additional Cloud Roles when none are set.
+    pub fn technique_ids(&self) -> Vec<String> {
+        if self.technique_ids.is_empty() {
+            vec!["T1098.003".to_string()]
+        } else {
+            self.technique_ids.clone()
+        }
+    }
 }
 
 /// Dynamic security context overrides for explicit Okta System Log events.
@@ -464,6 +2151,238 @@ pub enum OktaTransactionType {
     Job,
 }
 
+/// GitHub Enterprise audit log generation configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubAuditSourceConfig {
+    /// Path to a shared identity registry TOML file.
+    #[serde(default)]
+    pub identity_registry_path: String,
+    /// Enterprise slug placed on audit entries as `business`.
+    pub enterprise: Option<String>,
+    /// Organization login; also used as the envelope `tenant_id`.
+    pub org: String,
+    /// Repository names (without the org) that baseline activity touches.
+    pub repositories: Option<Vec<String>>,
+    /// Backwards-compatible baseline switch: set to 0 to disable continuous baseline rows.
+    pub baseline_events_per_actor: Option<usize>,
+    /// Optional deterministic source IP pools for baseline rows, keyed by actor ID.
+    pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
+    /// Schedule baseline rows from the identity's shared cross-source clock.
+    #[serde(default)]
+    pub linked_sessions: bool,
+    /// Share of linked sessions that line up with the other linked sources.
+    #[serde(default = "default_session_correlation")]
+    pub session_correlation: f64,
+    /// Inter-arrival process and burst behavior for baseline rows.
+    pub arrival: Option<ArrivalConfig>,
+    /// Deterministic audit events to inject into the stream.
+    #[serde(default, rename = "event")]
+    pub events: Vec<GitHubAuditEventConfig>,
+}
+
+/// Explicit GitHub audit event injection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubAuditEventConfig {
+    pub actor_id: String,
+    pub offset_seconds: Option<i64>,
+    pub created_at: Option<String>,
+    pub action: String,
+    pub actor_ip: String,
+    /// Repository name, with or without the `org/` prefix.
+    pub repo: Option<String>,
+    /// Target user login (e.g. the member added by `org.add_member`).
+    pub user: Option<String>,
+    pub user_agent: Option<String>,
+    pub country_code: Option<String>,
+    /// Action-specific fields merged into the audit entry.
+    pub fields: Option<BTreeMap<String, TomlValue>>,
+    /// Scenario label written to the label sidecar; the audit entry is unchanged.
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+/// Google Workspace Admin SDK Reports API activity generation configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleWorkspaceSourceConfig {
+    /// Path to a shared identity registry TOML file.
+    #[serde(default)]
+    pub identity_registry_path: String,
+    /// Workspace customer ID (`C0...`); also used as the envelope `tenant_id`.
+    pub customer_id: String,
+    /// Users sign in through a SAML identity provider, so logins carry
+    /// `login_type = "saml"` and password failures happen at the provider.
+    #[serde(default)]
+    pub saml_sso: bool,
+    /// Backwards-compatible baseline switch: set to 0 to disable continuous baseline rows.
+    pub baseline_events_per_actor: Option<usize>,
+    /// Optional deterministic source IP pools for baseline rows, keyed by actor ID.
+    pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
+    /// Schedule baseline rows from the identity's shared cross-source clock.
+    #[serde(default)]
+    pub linked_sessions: bool,
+    /// Share of linked sessions that line up with the other linked sources.
+    #[serde(default = "default_session_correlation")]
+    pub session_correlation: f64,
+    /// Inter-arrival process and burst behavior for baseline rows.
+    pub arrival: Option<ArrivalConfig>,
+    /// Deterministic activities to inject into the stream.
+    #[serde(default, rename = "event")]
+    pub events: Vec<GoogleWorkspaceEventConfig>,
+}
+
+/// Explicit Google Workspace activity injection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleWorkspaceEventConfig {
+    pub actor_id: String,
+    pub offset_seconds: Option<i64>,
+    pub time: Option<String>,
+    /// Reports API application, such as `login`, `drive`, `admin`, or `token`.
+    pub application: String,
+    /// Event name, such as `login_success` or `change_document_visibility`.
+    pub name: String,
+    /// Event type; defaults from the application (`login`, `access`, `USER_SETTINGS`, ...).
+    #[serde(rename = "type")]
+    pub event_type: Option<String>,
+    pub ip_address: String,
+    /// Event parameters; strings, integers, booleans, and arrays map to
+    /// `value`, `intValue`, `boolValue`, and `multiValue`/`multiIntValue`.
+    pub parameters: Option<BTreeMap<String, TomlValue>>,
+    /// Scenario label written to the label sidecar; the activity is unchanged.
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+/// Kubernetes API server audit log generation configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KubernetesAuditSourceConfig {
+    /// Path to a shared identity registry TOML file.
+    #[serde(default)]
+    pub identity_registry_path: String,
+    /// Cluster name; used as the envelope `tenant_id`.
+    pub cluster: String,
+    /// Namespaces that baseline activity and service accounts live in.
+    pub namespaces: Option<Vec<String>>,
+    /// Prefix the API server's OIDC authenticator adds to usernames and groups (default: `oidc:`).
+    pub oidc_prefix: Option<String>,
+    /// Drop `RequestReceived` stage events, as an audit policy with `omitStages` does.
+    #[serde(default)]
+    pub omit_request_received: bool,
+    /// Backwards-compatible baseline switch: set to 0 to disable continuous baseline rows.
+    pub baseline_events_per_actor: Option<usize>,
+    /// Optional deterministic source IP pools for baseline rows, keyed by actor ID.
+    pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
+    /// Schedule baseline rows from the identity's shared cross-source clock.
+    #[serde(default)]
+    pub linked_sessions: bool,
+    /// Share of linked sessions that line up with the other linked sources.
+    #[serde(default = "default_session_correlation")]
+    pub session_correlation: f64,
+    /// Inter-arrival process and burst behavior for baseline rows.
+    pub arrival: Option<ArrivalConfig>,
+    /// Deterministic API requests to inject into the stream.
+    #[serde(default, rename = "event")]
+    pub events: Vec<KubernetesAuditEventConfig>,
+}
+
+/// Explicit Kubernetes API request injection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KubernetesAuditEventConfig {
+    pub actor_id: String,
+    pub offset_seconds: Option<i64>,
+    pub request_received_at: Option<String>,
+    /// API verb (`get`, `list`, `create`, `patch`, `delete`, ...).
+    pub verb: String,
+    /// Plural resource name, e.g. `secrets`.
+    pub resource: String,
+    pub subresource: Option<String>,
+    /// API group; empty or unset for the core group.
+    pub api_group: Option<String>,
+    /// Namespace; unset for cluster-scoped requests.
+    pub namespace: Option<String>,
+    /// Object name; unset for collection requests.
+    pub name: Option<String>,
+    pub source_ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// HTTP response code; 403 records an RBAC denial (default: 201 for `create`, else 200).
+    pub response_code: Option<u16>,
+    /// Scenario label written to the label sidecar; the audit events are unchanged.
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
+/// Windows Security event log generation configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowsSecuritySourceConfig {
+    /// Path to a shared identity registry TOML file.
+    #[serde(default)]
+    pub identity_registry_path: String,
+    /// Active Directory DNS domain, e.g. `corp.example.com`; used as the envelope `tenant_id`.
+    pub domain: String,
+    /// NetBIOS domain name (default: the first DNS label, uppercased).
+    pub netbios_domain: Option<String>,
+    /// Server host names that service accounts and administrators log on to.
+    pub servers: Option<Vec<String>>,
+    /// Backwards-compatible baseline switch: set to 0 to disable continuous baseline rows.
+    pub baseline_events_per_actor: Option<usize>,
+    /// Optional deterministic workstation IP pools for baseline rows, keyed by actor ID.
+    pub baseline_source_ips: Option<HashMap<String, Vec<String>>>,
+    /// Schedule baseline rows from the identity's shared cross-source clock.
+    #[serde(default)]
+    pub linked_sessions: bool,
+    /// Share of linked sessions that line up with the other linked sources.
+    #[serde(default = "default_session_correlation")]
+    pub session_correlation: f64,
+    /// Inter-arrival process and burst behavior for baseline rows.
+    pub arrival: Option<ArrivalConfig>,
+    /// Deterministic Security events to inject into the stream.
+    #[serde(default, rename = "event")]
+    pub events: Vec<WindowsSecurityEventConfig>,
+}
+
+/// Explicit Windows Security event injection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowsSecurityEventConfig {
+    pub actor_id: String,
+    pub offset_seconds: Option<i64>,
+    pub time_created: Option<String>,
+    /// Security event ID: 4624, 4625, 4672, or 4688.
+    pub event_id: u32,
+    /// Host name or FQDN that logs the event (default: the actor's workstation).
+    pub computer: Option<String>,
+    /// Logon ID shared with the actor's other events; use it to chain a logon to its processes.
+    pub logon_id: Option<String>,
+    /// Logon type for 4624 and 4625 (default: 2, interactive).
+    pub logon_type: Option<u8>,
+    /// Client address for network and remote interactive logons.
+    pub ip_address: Option<String>,
+    /// New process image path for 4688.
+    pub process_name: Option<String>,
+    pub parent_process_name: Option<String>,
+    pub command_line: Option<String>,
+    /// `EventData` values that override the generated ones.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    /// Scenario label written to the label sidecar; the event is unchanged.
+    pub scenario_id: Option<String>,
+    /// ATT&CK technique ID recorded with `scenario_id`.
+    pub technique_id: Option<String>,
+    /// Additional ATT&CK technique IDs recorded with `scenario_id`.
+    #[serde(default)]
+    pub technique_ids: Vec<String>,
+}
+
 /// Role weight for actor generation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoleWeight {
@@ -480,6 +2399,43 @@ pub struct PopulationConfig {
     pub timezone_distribution: Option<Vec<TimezoneWeight>>,
     /// Actor population parameters.
     pub population: PopulationActorsConfig,
+    /// Address ranges for actor source IPs; without it, IPs are random.
+    pub ip_plan: Option<IpPlanConfig>,
+    /// Writer properties for the population Parquet file.
+    pub parquet: Option<ParquetOptions>,
+}
+
+/// Network ranges actor source IPs are drawn from.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpPlanConfig {
+    /// Office NAT ranges humans share.
+    #[serde(default)]
+    pub corporate_egress: Vec<IpRangeConfig>,
+    /// Corporate VPN exit ranges.
+    #[serde(default)]
+    pub vpn: Vec<IpRangeConfig>,
+    /// Residential ISP ranges, matched to humans by `country`.
+    #[serde(default)]
+    pub home_isp: Vec<IpRangeConfig>,
+    /// Cloud provider ranges for service actors.
+    #[serde(default)]
+    pub cloud: Vec<IpRangeConfig>,
+    /// Chance each human address comes from `corporate_egress` (default 0.5).
+    pub corporate_share: Option<f64>,
+    /// Chance each human address comes from `vpn` (default 0.2).
+    pub vpn_share: Option<f64>,
+}
+
+/// One CIDR block in an IP plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpRangeConfig {
+    /// IPv4 or IPv6 block, e.g. `198.51.100.0/24`.
+    pub cidr: String,
+    /// Country a `home_isp` range serves, matched against the actor's home
+    /// location and normal countries; ranges without one serve everyone.
+    pub country: Option<String>,
+    /// Distinct addresses handed out from the range across the population.
+    pub max_addresses: Option<usize>,
 }
 
 impl PopulationConfig {
@@ -489,6 +2445,20 @@ impl PopulationConfig {
     }
 }
 
+/// Output sink for `seclog replay`; a full generation config also parses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkConfig {
+    /// Output sink configuration.
+    pub output: OutputConfig,
+}
+
+impl SinkConfig {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+}
+
 /// Actor population parameters.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PopulationActorsConfig {
@@ -498,6 +2468,11 @@ pub struct PopulationActorsConfig {
     pub hot_actor_multiplier: Option<f64>,
     pub account_ids: Option<Vec<String>>,
     pub account_count: Option<usize>,
+    /// Skews actors toward the first accounts: the nth account gets weight
+    /// `1 / n^account_skew`. 0 (the default) spreads actors evenly.
+    pub account_skew: Option<f64>,
+    /// Per-account weights and event-rate targets.
+    pub account: Option<Vec<AccountConfig>>,
     pub error_rate: Option<ErrorRateConfig>,
     pub human_error_rate: Option<ErrorRateConfig>,
     pub service_error_rate: Option<ErrorRateConfig>,
@@ -507,6 +2482,41 @@ pub struct PopulationActorsConfig {
     pub service_profiles: Option<Vec<ServiceProfileConfig>>,
     /// Explicit actors with fixed traits and overrides.
     pub actor: Option<Vec<ExplicitActorConfig>>,
+    /// Insider-threat personas assigned to generated human actors.
+    pub insider_persona: Option<Vec<InsiderPersonaConfig>>,
+    /// On-call rotation among generated human actors.
+    pub on_call: Option<OnCallConfig>,
+}
+
+/// Activity settings for one account in the population.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountConfig {
+    /// 12-digit account ID; added to the pool if not in `account_ids`.
+    pub id: String,
+    /// Share of generated actors relative to other accounts; overrides
+    /// `account_skew` for this account.
+    pub weight: Option<f64>,
+    /// Combined hourly rate of the account's actors; generated actors' rates
+    /// are scaled to reach it.
+    pub events_per_hour: Option<f64>,
+}
+
+/// Insider-threat persona assignment entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsiderPersonaConfig {
+    /// Persona name: `data_hoarder`, `off_hours_admin`, or `permission_accumulator`.
+    pub name: String,
+    /// Number of generated human actors that get the persona.
+    pub count: usize,
+}
+
+/// On-call rotation settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnCallConfig {
+    /// Number of generated human actors in the rotation; one is on call at a time.
+    pub count: usize,
+    /// Length of each shift in days. Defaults to 7.
+    pub shift_days: Option<u32>,
 }
 
 /// Per-role configuration entry.
@@ -547,6 +2557,7 @@ pub struct ExplicitActorConfig {
     pub tags: Vec<String>,
     #[serde(default)]
     pub event_bias: HashMap<String, f64>,
+    pub insider_persona: Option<String>,
 }
 
 /// Error rate range configuration for actor populations.
@@ -670,4 +2681,35 @@ mod tests {
         assert_eq!(output.compression.as_deref(), Some("gzip"));
         assert!(!output.overwrite);
     }
+
+    #[test]
+    fn file_output_parses_additional_formats() {
+        let output: FileOutputConfig = toml::from_str(
+            r#"
+            dir = "out"
+            files = { target_size_mb = 10, max_age_seconds = 30 }
+            format = { type = "parquet" }
+
+            [[formats]]
+            type = "jsonl"
+            compression = "gzip"
+
+            [[formats]]
+            type = "text"
+            dir = "raw"
+            "#,
+        )
+        .unwrap();
+
+        let formats = output.format_dirs();
+        assert_eq!(formats.len(), 3);
+        assert_eq!(formats[0].1, PathBuf::from("out"));
+        assert!(matches!(
+            formats[1].0,
+            FormatConfig::Jsonl(JsonlOptions { compression: Some(c), .. }) if c == "gzip"
+        ));
+        assert_eq!(formats[1].1, Path::new("out").join("jsonl"));
+        assert_eq!(formats[2].0.type_name(), "text");
+        assert_eq!(formats[2].1, PathBuf::from("raw"));
+    }
 }
@@ -8,6 +8,44 @@ pub struct Event {
     pub envelope: EventEnvelope,
     /// Source-specific payload (CloudTrail, Okta, etc.).
     pub payload: Value,
+    /// Ground-truth label for scenario events; only written to the label sidecar.
+    #[serde(skip)]
+    pub label: Option<EventLabel>,
+}
+
+impl Event {
+    /// Source-native event identifier (`eventID`, `uuid`, `event_id`, `_document_id`,
+    /// `request_id`, `auditID`, Workspace `id.uniqueQualifier`, or the Entra
+    /// sign-in or audit `id`).
+    pub fn event_id(&self) -> Option<&str> {
+        if matches!(self.envelope.source.as_str(), "entra_signin" | "entra_audit") {
+            return self.payload.get("id").and_then(Value::as_str);
+        }
+        [
+            "eventID",
+            "uuid",
+            "event_id",
+            "_document_id",
+            "request_id",
+            "auditID",
+        ]
+        .iter()
+        .find_map(|key| self.payload.get(*key).and_then(Value::as_str))
+        .or_else(|| {
+            self.payload
+                .pointer("/id/uniqueQualifier")
+                .and_then(Value::as_str)
+        })
+    }
+}
+
+/// Scenario metadata used to evaluate detections against generated data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventLabel {
+    /// Scenario that produced the event.
+    pub scenario_id: String,
+    /// ATT&CK technique IDs (e.g. `T1078`) exercised by the event.
+    pub technique_ids: Vec<String>,
 }
 
 /// Standard envelope fields applied to every event.
@@ -37,6 +75,10 @@ pub struct EventEnvelope {
     pub session_id: Option<String>,
     /// Optional tenant/account identifier.
     pub tenant_id: Option<String>,
+    /// Owning tenant in a multi-tenant run. `tenant_id` keeps the source's
+    /// own account or directory, which file outputs key streams by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
 }
 
 /// Actor identity for an event.
@@ -5,7 +5,7 @@
 //! while generators consume the normalized identities through this module.
 
 use super::actors::{
-    ActorKind, ActorPopulation, ActorRole, ActorSeed, ServicePattern, ServiceProfile,
+    ActorKind, ActorPopulation, ActorRole, ActorSeed, OnCallShift, ServicePattern, ServiceProfile,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -99,6 +99,12 @@ pub struct Identity {
     pub weekend_active: Option<bool>,
     #[serde(default)]
     pub service_pattern: Option<String>,
+    /// Insider-threat persona, e.g. `data_hoarder`; ground truth for evaluations.
+    #[serde(default)]
+    pub insider_persona: Option<String>,
+    /// On-call rotation shift as `slot/rotation/shift_days`, e.g. `1/4/7`.
+    #[serde(default)]
+    pub on_call: Option<String>,
 }
 
 /// Registry file format.
@@ -384,6 +390,10 @@ fn identity_from_actor_seed(actor: &ActorSeed, idx: usize, ordinal: usize) -> Id
         timezone_offset: Some(actor.timezone_offset),
         weekend_active: Some(actor.weekend_active),
         service_pattern: actor.service_pattern.as_ref().map(service_pattern_name),
+        insider_persona: actor
+            .insider_persona
+            .map(|persona| persona.key().to_string()),
+        on_call: actor.on_call.as_ref().map(OnCallShift::key),
     }
 }
 
@@ -675,6 +685,8 @@ mod tests {
                 hot_actor_multiplier: Some(1.0),
                 account_ids: Some(vec!["123456789012".to_string()]),
                 account_count: None,
+                account_skew: None,
+                account: None,
                 error_rate: None,
                 human_error_rate: None,
                 service_error_rate: None,
@@ -682,7 +694,11 @@ mod tests {
                 service_events_per_hour: None,
                 service_profiles: None,
                 actor: None,
+                insider_persona: None,
+                on_call: None,
             },
+            ip_plan: None,
+            parquet: None,
         })
         .unwrap();
         let registry = IdentityRegistry::from_population("generated", &population).unwrap();
@@ -739,6 +755,8 @@ mod tests {
             timezone_offset: None,
             weekend_active: None,
             service_pattern: None,
+            insider_persona: None,
+            on_call: None,
         }
     }
 }
@@ -0,0 +1,299 @@
+//! IP plan for actor source addresses.
+//!
+//! Random public addresses land in arbitrary networks and countries, so
+//! IP-to-org and geo-IP enrichment of generated logs returns noise. An IP plan
+//! draws human addresses from corporate egress, VPN, and home ISP ranges
+//! matched to the actor's country, and service addresses from cloud provider
+//! ranges. Each range can cap the distinct addresses it hands out, so an
+//! office shows up as a handful of NAT addresses shared by everyone there.
+
+use crate::core::actors::{ActorKind, ActorSeed};
+use crate::core::config::{IpPlanConfig, IpRangeConfig};
+use rand::Rng;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const DEFAULT_CORPORATE_SHARE: f64 = 0.5;
+const DEFAULT_VPN_SHARE: f64 = 0.2;
+
+/// Parsed CIDR block and the addresses handed out from it so far.
+#[derive(Debug, Clone)]
+struct Range {
+    base: u128,
+    /// Addresses in the block; saturates for IPv6 blocks wider than /1.
+    size: u128,
+    v6: bool,
+    country: Option<String>,
+    max_addresses: Option<usize>,
+    handed_out: Vec<String>,
+}
+
+impl Range {
+    fn parse(config: &IpRangeConfig) -> Result<Self, String> {
+        let invalid = || format!("invalid CIDR {:?}", config.cidr);
+        let (address, prefix) = config.cidr.trim().split_once('/').ok_or_else(invalid)?;
+        let address = address.parse::<IpAddr>().map_err(|_| invalid())?;
+        let prefix = prefix.parse::<u32>().map_err(|_| invalid())?;
+        let (value, width, v6) = match address {
+            IpAddr::V4(address) => (u32::from(address) as u128, 32, false),
+            IpAddr::V6(address) => (u128::from(address), 128, true),
+        };
+        if prefix > width {
+            return Err(invalid());
+        }
+        if config.max_addresses == Some(0) {
+            return Err("max_addresses must be greater than 0".to_string());
+        }
+        let host_bits = width - prefix;
+        let size = if host_bits >= 128 {
+            u128::MAX
+        } else {
+            1u128 << host_bits
+        };
+        Ok(Self {
+            base: value & !(size - 1),
+            size,
+            v6,
+            country: config
+                .country
+                .as_deref()
+                .map(str::trim)
+                .filter(|country| !country.is_empty())
+                .map(str::to_ascii_lowercase),
+            max_addresses: config.max_addresses,
+            handed_out: Vec::new(),
+        })
+    }
+
+    /// Reuses a handed-out address once the range is at its cap, otherwise
+    /// draws a host address (skipping IPv4 network and broadcast addresses).
+    fn pick(&mut self, rng: &mut impl Rng) -> String {
+        if self
+            .max_addresses
+            .is_some_and(|max| self.handed_out.len() >= max)
+        {
+            return self.handed_out[rng.gen_range(0..self.handed_out.len())].clone();
+        }
+        let offset = if !self.v6 && self.size >= 4 {
+            rng.gen_range(1..self.size - 1)
+        } else {
+            rng.gen_range(0..self.size)
+        };
+        let value = self.base + offset;
+        let address = if self.v6 {
+            Ipv6Addr::from(value).to_string()
+        } else {
+            Ipv4Addr::from(value as u32).to_string()
+        };
+        if !self.handed_out.contains(&address) {
+            self.handed_out.push(address.clone());
+        }
+        address
+    }
+
+    fn serves(&self, actor: &ActorSeed) -> bool {
+        let Some(country) = &self.country else {
+            return true;
+        };
+        actor
+            .home_location
+            .as_deref()
+            .is_some_and(|location| location.to_ascii_lowercase().contains(country.as_str()))
+            || actor.normal_countries_regions.iter().any(|region| {
+                region
+                    .split('/')
+                    .next()
+                    .is_some_and(|name| name.trim().eq_ignore_ascii_case(country))
+            })
+    }
+}
+
+/// Source IP assignment for a population, built from `[ip_plan]`.
+#[derive(Debug, Clone)]
+pub struct IpPlan {
+    corporate: Vec<Range>,
+    vpn: Vec<Range>,
+    home: Vec<Range>,
+    cloud: Vec<Range>,
+    corporate_share: f64,
+    vpn_share: f64,
+}
+
+impl IpPlan {
+    /// Parses the plan's ranges and shares; errors name the offending field.
+    pub fn new(config: &IpPlanConfig) -> Result<Self, String> {
+        let ranges = |field: &str, ranges: &[IpRangeConfig]| {
+            ranges
+                .iter()
+                .enumerate()
+                .map(|(idx, range)| {
+                    Range::parse(range).map_err(|err| format!("{field}[{idx}]: {err}"))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let corporate_share = config.corporate_share.unwrap_or(DEFAULT_CORPORATE_SHARE);
+        let vpn_share = config.vpn_share.unwrap_or(DEFAULT_VPN_SHARE);
+        for (field, share) in [
+            ("corporate_share", corporate_share),
+            ("vpn_share", vpn_share),
+        ] {
+            if !(0.0..=1.0).contains(&share) {
+                return Err(format!("{field} must be between 0.0 and 1.0"));
+            }
+        }
+        if corporate_share + vpn_share > 1.0 {
+            return Err("corporate_share and vpn_share must add up to at most 1.0".to_string());
+        }
+        Ok(Self {
+            corporate: ranges("corporate_egress", &config.corporate_egress)?,
+            vpn: ranges("vpn", &config.vpn)?,
+            home: ranges("home_isp", &config.home_isp)?,
+            cloud: ranges("cloud", &config.cloud)?,
+            corporate_share,
+            vpn_share,
+        })
+    }
+
+    /// Replaces an actor's source IPs with addresses from the plan, keeping
+    /// how many it has. Humans draw each address from corporate egress, VPN,
+    /// or their one home address; services draw from cloud ranges. Addresses
+    /// without a matching range keep their generated value.
+    pub fn assign(&mut self, actor: &mut ActorSeed, rng: &mut impl Rng) {
+        let slots = actor.source_ips.len().max(1);
+        let mut addresses = Vec::with_capacity(slots);
+        let mut home = None;
+        for slot in 0..slots {
+            let address = match actor.kind {
+                ActorKind::Service => pick_from(&mut self.cloud, rng),
+                ActorKind::Human => {
+                    let draw = rng.gen_range(0.0..1.0);
+                    if draw < self.corporate_share && !self.corporate.is_empty() {
+                        pick_from(&mut self.corporate, rng)
+                    } else if draw < self.corporate_share + self.vpn_share && !self.vpn.is_empty() {
+                        pick_from(&mut self.vpn, rng)
+                    } else {
+                        if home.is_none() {
+                            home = self.home_address(actor, rng);
+                        }
+                        home.clone()
+                    }
+                }
+            };
+            let address = address.or_else(|| actor.source_ips.get(slot).cloned());
+            if let Some(address) = address {
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        }
+        if !addresses.is_empty() {
+            actor.source_ips = addresses;
+        }
+    }
+
+    /// Home ISP address from a range serving the actor's country, falling
+    /// back to ranges without a country.
+    fn home_address(&mut self, actor: &ActorSeed, rng: &mut impl Rng) -> Option<String> {
+        let matching = |country: bool| {
+            self.home
+                .iter()
+                .enumerate()
+                .filter(|(_, range)| range.country.is_some() == country && range.serves(actor))
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>()
+        };
+        let mut candidates = matching(true);
+        if candidates.is_empty() {
+            candidates = matching(false);
+        }
+        if candidates.is_empty() {
+            return None;
+        }
+        let idx = candidates[rng.gen_range(0..candidates.len())];
+        Some(self.home[idx].pick(rng))
+    }
+}
+
+fn pick_from(ranges: &mut [Range], rng: &mut impl Rng) -> Option<String> {
+    if ranges.is_empty() {
+        return None;
+    }
+    let idx = rng.gen_range(0..ranges.len());
+    Some(ranges[idx].pick(rng))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::actors::{generate_population, ActorKind};
+    use crate::core::config::PopulationConfig;
+    use std::collections::HashSet;
+
+    #[test]
+    fn plan_draws_addresses_from_matching_ranges() {
+        let config: PopulationConfig = toml::from_str(
+            r#"
+seed = 11
+
+[[timezone_distribution]]
+name = "Europe/London"
+weight = 1.0
+
+[population]
+actor_count = 60
+service_ratio = 0.25
+
+[ip_plan]
+corporate_share = 0.5
+vpn_share = 0.2
+
+[[ip_plan.corporate_egress]]
+cidr = "198.51.100.0/24"
+max_addresses = 2
+
+[[ip_plan.vpn]]
+cidr = "192.0.2.16/28"
+
+[[ip_plan.home_isp]]
+cidr = "81.2.69.0/24"
+country = "United Kingdom"
+
+[[ip_plan.home_isp]]
+cidr = "73.0.0.0/16"
+country = "United States"
+
+[[ip_plan.cloud]]
+cidr = "3.5.140.0/22"
+"#,
+        )
+        .unwrap();
+        let population = generate_population(&config).unwrap();
+
+        let mut corporate = HashSet::new();
+        let mut homes = 0;
+        for actor in &population.actors {
+            assert!(!actor.source_ips.is_empty());
+            for ip in &actor.source_ips {
+                match actor.kind {
+                    ActorKind::Service => {
+                        let third = ip.split('.').nth(2).unwrap().parse::<u8>().unwrap();
+                        assert!(ip.starts_with("3.5.") && (140..144).contains(&third));
+                    }
+                    ActorKind::Human if ip.starts_with("198.51.100.") => {
+                        corporate.insert(ip.clone());
+                    }
+                    ActorKind::Human if ip.starts_with("81.2.69.") => homes += 1,
+                    ActorKind::Human => {
+                        let last = ip.rsplit('.').next().unwrap().parse::<u8>().unwrap();
+                        assert!(ip.starts_with("192.0.2.") && (17..31).contains(&last));
+                    }
+                }
+            }
+        }
+        assert_eq!(corporate.len(), 2);
+        assert!(homes > 0);
+
+        let mut invalid = config.clone();
+        invalid.ip_plan.as_mut().unwrap().vpn[0].cidr = "192.0.2.0/33".to_string();
+        let err = generate_population(&invalid).unwrap_err();
+        assert!(err.to_string().contains("ip_plan.vpn[0]"));
+    }
+}
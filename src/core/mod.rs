@@ -5,7 +5,14 @@
 
 pub mod activity;
 pub mod actors;
+pub mod arrival;
+pub mod attack;
+pub mod checkpoint;
 pub mod config;
 pub mod event;
 pub mod identity;
+pub mod ip_plan;
+pub mod selector;
+pub mod traffic;
 pub mod traits;
+pub mod util;
@@ -0,0 +1,315 @@
+//! Actor sub-population selection.
+//!
+//! Sources that read a shared actor pool (a population Parquet file or an
+//! identity registry) can narrow it with an `actor_selector`: tags, kind,
+//! roles, attribute expressions such as `rate_per_hour > 20` or
+//! `account_id in [111111111111, 222222222222]`, and a stable sample fraction.
+
+use crate::actors_parquet::{kind_to_str, role_to_str, service_profile_to_str};
+use crate::core::actors::ActorSeed;
+use crate::core::config::ActorSelectorConfig;
+
+const NUMERIC_ATTRIBUTES: [&str; 5] = [
+    "rate_per_hour",
+    "error_rate",
+    "active_start_hour",
+    "active_hours",
+    "timezone_offset",
+];
+const TEXT_ATTRIBUTES: [&str; 9] = [
+    "kind",
+    "role",
+    "tag",
+    "account_id",
+    "identity_type",
+    "service_profile",
+    "insider_persona",
+    "principal_id",
+    "weekend_active",
+];
+
+/// Compiled `actor_selector`.
+#[derive(Debug, Clone)]
+pub struct ActorSelector {
+    tags: Option<Vec<String>>,
+    kind: Option<String>,
+    roles: Option<Vec<String>>,
+    conditions: Vec<Condition>,
+    sample: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct Condition {
+    attribute: String,
+    op: Op,
+    values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    In,
+}
+
+impl ActorSelector {
+    pub fn new(config: &ActorSelectorConfig) -> Result<Self, String> {
+        if let Some(kind) = &config.kind {
+            if kind != "human" && kind != "service" {
+                return Err(format!("kind must be human or service, got {kind}"));
+            }
+        }
+        if let Some(sample) = config.sample {
+            if !(sample > 0.0 && sample <= 1.0) {
+                return Err(format!("sample must be in (0, 1], got {sample}"));
+            }
+        }
+        let conditions = config
+            .conditions
+            .iter()
+            .map(|expr| Condition::parse(expr))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            tags: config.tags.clone(),
+            kind: config.kind.clone(),
+            roles: config.roles.clone(),
+            conditions,
+            sample: config.sample,
+        })
+    }
+
+    /// Returns whether the actor belongs to the selected sub-population.
+    pub fn matches(&self, seed: &ActorSeed) -> bool {
+        if let Some(tags) = &self.tags {
+            if !seed.tags.iter().any(|tag| tags.contains(tag)) {
+                return false;
+            }
+        }
+        if let Some(kind) = &self.kind {
+            if kind_to_str(&seed.kind) != kind {
+                return false;
+            }
+        }
+        if let Some(roles) = &self.roles {
+            let role = seed.role.as_ref().map(role_to_str);
+            if !role.is_some_and(|role| roles.iter().any(|wanted| wanted == role)) {
+                return false;
+            }
+        }
+        if !self
+            .conditions
+            .iter()
+            .all(|condition| condition.matches(seed))
+        {
+            return false;
+        }
+        self.sample
+            .is_none_or(|sample| sample_point(&seed.principal_id) < sample)
+    }
+
+    /// Keeps the selected actors, in order.
+    pub fn select<T>(&self, actors: Vec<T>, seed: impl Fn(&T) -> &ActorSeed) -> Vec<T> {
+        actors
+            .into_iter()
+            .filter(|actor| self.matches(seed(actor)))
+            .collect()
+    }
+}
+
+impl Condition {
+    fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let split = expr
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(expr.len());
+        let (attribute, rest) = expr.split_at(split);
+        let rest = rest.trim_start();
+        let numeric = NUMERIC_ATTRIBUTES.contains(&attribute);
+        if !numeric && !TEXT_ATTRIBUTES.contains(&attribute) {
+            return Err(format!("unknown attribute in `{expr}`: {attribute}"));
+        }
+
+        let (op, raw) = [
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+            ("=", Op::Eq),
+            ("in ", Op::In),
+        ]
+        .into_iter()
+        .find_map(|(token, op)| rest.strip_prefix(token).map(|raw| (op, raw.trim())))
+        .ok_or_else(|| format!("missing operator in `{expr}`"))?;
+
+        let values = if op == Op::In {
+            raw.strip_prefix('[')
+                .and_then(|raw| raw.strip_suffix(']'))
+                .ok_or_else(|| format!("`in` needs a [list] in `{expr}`"))?
+                .split(',')
+                .map(unquote)
+                .filter(|value| !value.is_empty())
+                .collect()
+        } else {
+            vec![unquote(raw)]
+        };
+        if values.is_empty() || values.iter().any(String::is_empty) {
+            return Err(format!("missing value in `{expr}`"));
+        }
+        if numeric {
+            if let Some(value) = values.iter().find(|value| value.parse::<f64>().is_err()) {
+                return Err(format!(
+                    "{attribute} needs a number in `{expr}`, got {value}"
+                ));
+            }
+        } else if !matches!(op, Op::Eq | Op::Ne | Op::In) {
+            return Err(format!(
+                "{attribute} only supports ==, !=, and in: `{expr}`"
+            ));
+        }
+        Ok(Self {
+            attribute: attribute.to_string(),
+            op,
+            values,
+        })
+    }
+
+    fn matches(&self, seed: &ActorSeed) -> bool {
+        if let Some(actual) = numeric_attribute(seed, &self.attribute) {
+            let mut values = self
+                .values
+                .iter()
+                .filter_map(|value| value.parse::<f64>().ok());
+            return match self.op {
+                Op::Eq => values.any(|value| actual == value),
+                Op::Ne => values.all(|value| actual != value),
+                Op::Gt => values.all(|value| actual > value),
+                Op::Ge => values.all(|value| actual >= value),
+                Op::Lt => values.all(|value| actual < value),
+                Op::Le => values.all(|value| actual <= value),
+                Op::In => values.any(|value| actual == value),
+            };
+        }
+        // Text attributes can have several values (tags); a condition matches
+        // when any of them equals any wanted value.
+        let actual = text_attribute(seed, &self.attribute);
+        let found = actual.iter().any(|value| self.values.contains(value));
+        match self.op {
+            Op::Ne => !found,
+            _ => found,
+        }
+    }
+}
+
+fn numeric_attribute(seed: &ActorSeed, attribute: &str) -> Option<f64> {
+    match attribute {
+        "rate_per_hour" => Some(seed.rate_per_hour),
+        "error_rate" => Some(seed.error_rate),
+        "active_start_hour" => Some(f64::from(seed.active_start_hour)),
+        "active_hours" => Some(f64::from(seed.active_hours)),
+        "timezone_offset" => Some(f64::from(seed.timezone_offset)),
+        _ => None,
+    }
+}
+
+fn text_attribute(seed: &ActorSeed, attribute: &str) -> Vec<String> {
+    match attribute {
+        "kind" => vec![kind_to_str(&seed.kind).to_string()],
+        "role" => seed
+            .role
+            .as_ref()
+            .map(|role| role_to_str(role).to_string())
+            .into_iter()
+            .collect(),
+        "tag" => seed.tags.clone(),
+        "account_id" => vec![seed.account_id.clone()],
+        "identity_type" => vec![seed.identity_type.clone()],
+        "service_profile" => seed
+            .service_profile
+            .as_ref()
+            .map(|profile| service_profile_to_str(profile).to_string())
+            .into_iter()
+            .collect(),
+        "insider_persona" => seed
+            .insider_persona
+            .map(|persona| persona.key().to_string())
+            .into_iter()
+            .collect(),
+        "principal_id" => vec![seed.principal_id.clone()],
+        "weekend_active" => vec![seed.weekend_active.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['"', '\'']).to_string()
+}
+
+/// Stable point in [0, 1) for a principal, so a sample keeps the same actors
+/// across runs and seeds.
+fn sample_point(principal_id: &str) -> f64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in principal_id.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (hash >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::actors::generate_population;
+    use crate::core::config::PopulationConfig;
+
+    #[test]
+    fn selector_combines_criteria_and_expressions() {
+        let config: PopulationConfig =
+            toml::from_str("seed = 3\n[population]\nactor_count = 200\nservice_ratio = 0.2\n")
+                .unwrap();
+        let population = generate_population(&config).unwrap();
+        let selector: ActorSelectorConfig = toml::from_str(
+            r#"
+            kind = "human"
+            where = ["role in [admin, auditor]", "rate_per_hour >= 10"]
+            "#,
+        )
+        .unwrap();
+        let selected = ActorSelector::new(&selector)
+            .unwrap()
+            .select(population.actors.clone(), |seed| seed);
+        assert!(!selected.is_empty());
+        assert!(selected.iter().all(|seed| {
+            matches!(
+                seed.role.as_ref().map(role_to_str),
+                Some("admin" | "auditor")
+            ) && seed.rate_per_hour >= 10.0
+        }));
+
+        let half = ActorSelector::new(&ActorSelectorConfig {
+            sample: Some(0.5),
+            ..ActorSelectorConfig::default()
+        })
+        .unwrap()
+        .select(population.actors, |seed| seed);
+        assert!((60..140).contains(&half.len()));
+
+        for bad in [
+            "rate_per_hour > fast",
+            "role > admin",
+            "colour == red",
+            "kind",
+        ] {
+            let config = ActorSelectorConfig {
+                conditions: vec![bad.to_string()],
+                ..ActorSelectorConfig::default()
+            };
+            assert!(ActorSelector::new(&config).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! Wall-clock load schedules for ingestion testing, and day-level traffic
+//! calendars for simulated time.
+//!
+//! A schedule is a list of phases, each holding or linearly ramping a target
+//! events-per-second rate. Step, spike, and soak tests are expressed as
+//! sequences of hold phases with different durations and rates.
+//!
+//! A calendar gives every simulated UTC date a multiplier: a weekly curve,
+//! scaled down on holidays and up on incident days, so month-long backfills
+//! show the weekly and seasonal shape of real logs.
+
+use crate::core::config::{
+    CalendarDayConfig, TrafficCalendarConfig, TrafficPhaseConfig, TrafficPhaseShape,
+};
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const MAX_PACING_SLEEP: Duration = Duration::from_millis(50);
+
+#[derive(Debug)]
+pub struct TrafficConfigError(pub String);
+
+impl std::fmt::Display for TrafficConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "traffic config error: {}", self.0)
+    }
+}
+
+impl std::error::Error for TrafficConfigError {}
+
+/// Resolved phase with start and end rates in events per second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadPhase {
+    pub duration: Duration,
+    pub start_eps: f64,
+    pub end_eps: f64,
+}
+
+/// Ordered load phases with helpers to integrate the target rate over time.
+#[derive(Debug, Clone)]
+pub struct LoadSchedule {
+    phases: Vec<LoadPhase>,
+}
+
+impl LoadSchedule {
+    /// Builds a schedule from traffic phase config entries.
+    pub fn from_phases(configs: &[TrafficPhaseConfig]) -> Result<Self, TrafficConfigError> {
+        let mut phases = Vec::with_capacity(configs.len());
+        let mut previous_eps = 0.0;
+        for (idx, config) in configs.iter().enumerate() {
+            let duration = parse_duration(&config.duration).ok_or_else(|| {
+                TrafficConfigError(format!(
+                    "traffic.phases[{idx}].duration is not a valid duration: {}",
+                    config.duration
+                ))
+            })?;
+            if duration.is_zero() {
+                return Err(TrafficConfigError(format!(
+                    "traffic.phases[{idx}].duration must be > 0"
+                )));
+            }
+            if !config.eps.is_finite() || config.eps < 0.0 {
+                return Err(TrafficConfigError(format!(
+                    "traffic.phases[{idx}].eps must be >= 0"
+                )));
+            }
+            let start_eps = match config.shape {
+                TrafficPhaseShape::Hold => config.eps,
+                TrafficPhaseShape::Ramp => previous_eps,
+            };
+            phases.push(LoadPhase {
+                duration,
+                start_eps,
+                end_eps: config.eps,
+            });
+            previous_eps = config.eps;
+        }
+        Ok(Self { phases })
+    }
+
+    pub fn phases(&self) -> &[LoadPhase] {
+        &self.phases
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phases.is_empty()
+    }
+
+    /// Returns the combined length of every phase.
+    pub fn total_duration(&self) -> Duration {
+        self.phases.iter().map(|phase| phase.duration).sum()
+    }
+
+    /// Returns the active phase index for an elapsed time, or `None` once finished.
+    pub fn phase_at(&self, elapsed: Duration) -> Option<usize> {
+        let mut phase_start = Duration::ZERO;
+        for (idx, phase) in self.phases.iter().enumerate() {
+            if elapsed < phase_start + phase.duration {
+                return Some(idx);
+            }
+            phase_start += phase.duration;
+        }
+        None
+    }
+
+    /// Returns the target rate at an elapsed time, or `None` once finished.
+    pub fn target_eps(&self, elapsed: Duration) -> Option<f64> {
+        let mut phase_start = Duration::ZERO;
+        for phase in &self.phases {
+            if elapsed < phase_start + phase.duration {
+                let progress = (elapsed - phase_start).as_secs_f64() / phase.duration.as_secs_f64();
+                return Some(phase.start_eps + (phase.end_eps - phase.start_eps) * progress);
+            }
+            phase_start += phase.duration;
+        }
+        None
+    }
+
+    /// Returns how many events the schedule allows by an elapsed time.
+    pub fn expected_events(&self, elapsed: Duration) -> f64 {
+        let mut total = 0.0;
+        let mut remaining = elapsed.as_secs_f64();
+        for phase in &self.phases {
+            let length = phase.duration.as_secs_f64();
+            let within = remaining.min(length);
+            let slope = (phase.end_eps - phase.start_eps) / length;
+            total += phase.start_eps * within + slope * within * within / 2.0;
+            remaining -= within;
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+        total
+    }
+}
+
+/// Paces a generation loop against a load schedule on the wall clock.
+pub struct LoadPacer {
+    schedule: LoadSchedule,
+    started_at: Instant,
+    current_phase: Option<usize>,
+    rate_multiplier: f64,
+    /// Elapsed time and allowed events at the last rate multiplier change.
+    rescaled: (Duration, f64),
+}
+
+impl LoadPacer {
+    pub fn new(schedule: LoadSchedule) -> Self {
+        Self {
+            schedule,
+            started_at: Instant::now(),
+            current_phase: None,
+            rate_multiplier: 1.0,
+            rescaled: (Duration::ZERO, 0.0),
+        }
+    }
+
+    pub fn schedule(&self) -> &LoadSchedule {
+        &self.schedule
+    }
+
+    /// Blocks until the schedule allows another event after `dispatched` events.
+    ///
+    /// Returns `false` once every phase has elapsed.
+    pub fn wait_for_slot(&mut self, dispatched: u64) -> bool {
+        loop {
+            let elapsed = self.started_at.elapsed();
+            let Some(eps) = self.schedule.target_eps(elapsed) else {
+                return false;
+            };
+            if self.allowed_events(elapsed) > dispatched as f64 {
+                return true;
+            }
+            let eps = eps * self.rate_multiplier;
+            let wait = if eps > 0.0 {
+                Duration::from_secs_f64(1.0 / eps).min(MAX_PACING_SLEEP)
+            } else {
+                MAX_PACING_SLEEP
+            };
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Scales the schedule's rate by `multiplier` from now on; phases keep
+    /// their wall-clock durations.
+    pub fn set_rate_multiplier(&mut self, multiplier: f64) {
+        if multiplier == self.rate_multiplier {
+            return;
+        }
+        let elapsed = self.started_at.elapsed();
+        self.rescaled = (elapsed, self.allowed_events(elapsed));
+        self.rate_multiplier = multiplier;
+    }
+
+    /// Switches to `schedule` at the current elapsed time, keeping the events
+    /// allowed so far.
+    pub fn set_schedule(&mut self, schedule: LoadSchedule) {
+        let elapsed = self.started_at.elapsed();
+        self.rescaled = (elapsed, self.allowed_events(elapsed));
+        self.schedule = schedule;
+        self.current_phase = None;
+    }
+
+    /// Moves the schedule later by `paused`, so phases resume where they were
+    /// paused instead of skipping ahead.
+    pub fn resume_after(&mut self, paused: Duration) {
+        self.started_at = self
+            .started_at
+            .checked_add(paused)
+            .unwrap_or(self.started_at);
+    }
+
+    fn allowed_events(&self, elapsed: Duration) -> f64 {
+        let (since, allowed) = self.rescaled;
+        allowed
+            + self.rate_multiplier
+                * (self.schedule.expected_events(elapsed) - self.schedule.expected_events(since))
+    }
+
+    /// Returns the new phase index the first time the pacer observes it.
+    pub fn take_phase_change(&mut self) -> Option<usize> {
+        let phase = self.schedule.phase_at(self.started_at.elapsed())?;
+        if self.current_phase == Some(phase) {
+            return None;
+        }
+        self.current_phase = Some(phase);
+        Some(phase)
+    }
+}
+
+const DEFAULT_HOLIDAY_MULTIPLIER: f64 = 0.2;
+const DEFAULT_INCIDENT_MULTIPLIER: f64 = 3.0;
+
+/// Resolved traffic calendar.
+#[derive(Debug, Clone)]
+pub struct TrafficCalendar {
+    /// Monday through Sunday.
+    weekdays: [f64; 7],
+    /// Combined holiday and incident multipliers per date.
+    days: HashMap<NaiveDate, f64>,
+    peak: f64,
+}
+
+impl TrafficCalendar {
+    pub fn from_config(config: &TrafficCalendarConfig) -> Result<Self, TrafficConfigError> {
+        let weekdays = match &config.weekdays {
+            Some(values) => {
+                let weekdays: [f64; 7] = values.as_slice().try_into().map_err(|_| {
+                    TrafficConfigError(format!(
+                        "traffic.calendar.weekdays needs 7 values (Monday to Sunday), got {}",
+                        values.len()
+                    ))
+                })?;
+                if weekdays
+                    .iter()
+                    .any(|value| !value.is_finite() || *value < 0.0)
+                {
+                    return Err(TrafficConfigError(
+                        "traffic.calendar.weekdays values must be >= 0".to_string(),
+                    ));
+                }
+                weekdays
+            }
+            None => [1.0; 7],
+        };
+
+        let mut days = HashMap::new();
+        for (field, entries, default) in [
+            ("holidays", &config.holidays, DEFAULT_HOLIDAY_MULTIPLIER),
+            (
+                "incident_days",
+                &config.incident_days,
+                DEFAULT_INCIDENT_MULTIPLIER,
+            ),
+        ] {
+            for (idx, entry) in entries.iter().enumerate() {
+                let (date, multiplier) = calendar_day(entry, default).map_err(|reason| {
+                    let name = entry
+                        .name
+                        .as_ref()
+                        .map(|name| format!(" ({name})"))
+                        .unwrap_or_default();
+                    TrafficConfigError(format!("traffic.calendar.{field}[{idx}]{name}: {reason}"))
+                })?;
+                *days.entry(date).or_insert(1.0) *= multiplier;
+            }
+        }
+
+        let peak = days
+            .iter()
+            .map(|(date, multiplier)| weekdays[weekday_index(*date)] * multiplier)
+            .chain(weekdays)
+            .fold(0.0, f64::max);
+        if peak <= 0.0 {
+            return Err(TrafficConfigError(
+                "traffic.calendar has no day with a multiplier above 0".to_string(),
+            ));
+        }
+        Ok(Self {
+            weekdays,
+            days,
+            peak,
+        })
+    }
+
+    /// Multiplier for a UTC date.
+    pub fn multiplier(&self, date: NaiveDate) -> f64 {
+        self.weekdays[weekday_index(date)] * self.days.get(&date).copied().unwrap_or(1.0)
+    }
+
+    /// Share of the configured rate kept on `date`.
+    ///
+    /// Sources cannot generate above their configured rates, so the busiest
+    /// calendar day runs at 1.0 and every other day is scaled against it.
+    pub fn keep_fraction(&self, date: NaiveDate) -> f64 {
+        self.multiplier(date) / self.peak
+    }
+}
+
+fn calendar_day(entry: &CalendarDayConfig, default: f64) -> Result<(NaiveDate, f64), String> {
+    let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d")
+        .map_err(|_| format!("date must look like 2026-12-25, got {}", entry.date))?;
+    let multiplier = entry.multiplier.unwrap_or(default);
+    if !multiplier.is_finite() || multiplier < 0.0 {
+        return Err(format!("multiplier must be >= 0, got {multiplier}"));
+    }
+    Ok((date, multiplier))
+}
+
+fn weekday_index(date: NaiveDate) -> usize {
+    date.weekday().num_days_from_monday() as usize
+}
+
+/// Parses durations like `"45s"`, `"10m"`, `"1.5h"`, `"250ms"`, or bare seconds.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "s" => 1.0,
+        "ms" => 0.001,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "d" => 86_400.0,
+        _ => return None,
+    };
+    let secs = number * multiplier;
+    if !secs.is_finite() || secs < 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_duration_units() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("10m"), Some(Duration::from_secs(600)));
+        assert_eq!(parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_duration("ten minutes"), None);
+        assert_eq!(parse_duration("5w"), None);
+    }
+
+    #[test]
+    fn ramp_phase_starts_from_previous_rate() {
+        let schedule = LoadSchedule::from_phases(&[
+            phase("10s", 100.0, TrafficPhaseShape::Hold),
+            phase("10s", 300.0, TrafficPhaseShape::Ramp),
+            phase("5s", 1000.0, TrafficPhaseShape::Hold),
+        ])
+        .unwrap();
+
+        assert_eq!(schedule.total_duration(), Duration::from_secs(25));
+        assert_eq!(schedule.target_eps(Duration::from_secs(5)), Some(100.0));
+        assert_eq!(schedule.target_eps(Duration::from_secs(15)), Some(200.0));
+        assert_eq!(schedule.target_eps(Duration::from_secs(22)), Some(1000.0));
+        assert_eq!(schedule.target_eps(Duration::from_secs(25)), None);
+        assert_eq!(schedule.phase_at(Duration::from_secs(12)), Some(1));
+    }
+
+    #[test]
+    fn expected_events_integrates_ramps() {
+        let schedule = LoadSchedule::from_phases(&[
+            phase("10s", 100.0, TrafficPhaseShape::Ramp),
+            phase("10s", 100.0, TrafficPhaseShape::Hold),
+        ])
+        .unwrap();
+
+        assert!((schedule.expected_events(Duration::from_secs(10)) - 500.0).abs() < 1e-6);
+        assert!((schedule.expected_events(Duration::from_secs(20)) - 1500.0).abs() < 1e-6);
+        assert!((schedule.expected_events(Duration::from_secs(60)) - 1500.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn load_pacer_rescales_from_the_current_count_and_skips_pauses() {
+        let schedule =
+            LoadSchedule::from_phases(&[phase("1m", 100.0, TrafficPhaseShape::Hold)]).unwrap();
+        let mut pacer = LoadPacer::new(schedule);
+        pacer.started_at = Instant::now() - Duration::from_secs(10);
+        pacer.set_rate_multiplier(2.0);
+
+        let (since, allowed) = pacer.rescaled;
+        assert!((allowed - 1000.0).abs() < 5.0, "{allowed}");
+        let later = pacer.allowed_events(since + Duration::from_secs(5));
+        assert!((later - allowed - 1000.0).abs() < 1e-6);
+
+        pacer.resume_after(Duration::from_secs(5));
+        assert!(pacer.started_at.elapsed() < Duration::from_millis(5_100));
+    }
+
+    #[test]
+    fn rejects_invalid_phases() {
+        let err = LoadSchedule::from_phases(&[phase("0s", 10.0, TrafficPhaseShape::Hold)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("duration must be > 0"));
+
+        let err = LoadSchedule::from_phases(&[phase("1m", -1.0, TrafficPhaseShape::Hold)])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("eps must be >= 0"));
+    }
+
+    #[test]
+    fn calendar_scales_days_against_the_busiest_one() {
+        let config: TrafficCalendarConfig = toml::from_str(
+            r#"
+            weekdays = [1.0, 1.0, 1.0, 1.0, 1.0, 0.25, 0.25]
+            holidays = [{ date = "2026-12-25", name = "Christmas" }]
+            incident_days = [{ date = "2026-12-09", multiplier = 2.0 }]
+            "#,
+        )
+        .unwrap();
+        let calendar = TrafficCalendar::from_config(&config).unwrap();
+        let date = |raw: &str| NaiveDate::parse_from_str(raw, "%Y-%m-%d").unwrap();
+
+        // Wednesday incident day is the peak.
+        assert_eq!(calendar.keep_fraction(date("2026-12-09")), 1.0);
+        assert_eq!(calendar.keep_fraction(date("2026-12-10")), 0.5);
+        assert_eq!(calendar.keep_fraction(date("2026-12-12")), 0.125);
+        // Friday holiday.
+        assert!((calendar.multiplier(date("2026-12-25")) - 0.2).abs() < 1e-9);
+
+        let bad = TrafficCalendarConfig {
+            weekdays: Some(vec![1.0; 5]),
+            ..TrafficCalendarConfig::default()
+        };
+        assert!(TrafficCalendar::from_config(&bad).is_err());
+    }
+
+    fn phase(duration: &str, eps: f64, shape: TrafficPhaseShape) -> TrafficPhaseConfig {
+        TrafficPhaseConfig {
+            duration: duration.to_string(),
+            eps,
+            shape,
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use crate::event::Event;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
 
 /// Produces events one at a time for the generator loop.
 pub trait EventSource {
@@ -6,12 +8,92 @@ pub trait EventSource {
     fn next_event(&mut self) -> Option<Event>;
 }
 
+/// Rewrites events after generation and before they reach a writer.
+///
+/// Closures taking `&mut Event` implement this trait, so embedders can pass
+/// `|event: &mut Event| { ... }` wherever a transform is expected.
+pub trait EventTransform {
+    fn transform(&mut self, event: &mut Event);
+}
+
+impl<F: FnMut(&mut Event)> EventTransform for F {
+    fn transform(&mut self, event: &mut Event) {
+        self(event)
+    }
+}
+
 /// Writes events to a sink (files, streams, etc.).
 pub trait EventWriter {
     /// Writes a single event and returns the number of bytes written.
     fn write_event(&mut self, event: &Event) -> std::io::Result<u64>;
     /// Flushes buffered data without closing the writer.
     fn flush(&mut self) -> std::io::Result<()>;
+    /// Writes out every event written so far, finishing open files whatever
+    /// their age, and returns once they are durable; the writer stays open
+    /// and starts new files. Checkpoints sync before they commit.
+    ///
+    /// Defaults to `flush`, for writers that keep nothing back on a flush.
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.flush()
+    }
     /// Closes the writer, flushing any remaining data.
     fn close(&mut self) -> std::io::Result<()>;
+    /// Drains the files completed since the last call.
+    ///
+    /// File writers report every file they finish so manifests can be built
+    /// without rescanning the output; other sinks report nothing.
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        Vec::new()
+    }
+}
+
+/// Event count and event-time span of one output file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileStats {
+    pub events: u64,
+    pub first_event_time: Option<DateTime<Utc>>,
+    pub last_event_time: Option<DateTime<Utc>>,
+    /// Events earlier than one recorded before them, in this file or, for
+    /// the file's first events, at the end of the stream's previous file.
+    pub out_of_order: u64,
+    /// Latest event time of the stream's previous file, set by [`FileStats::rotate`].
+    pub previous_end: Option<DateTime<Utc>>,
+}
+
+impl FileStats {
+    /// Counts `event` and widens the time span to include its timestamp.
+    pub fn record(&mut self, event: &Event) {
+        self.events += 1;
+        let Ok(time) = DateTime::parse_from_rfc3339(&event.envelope.timestamp) else {
+            return;
+        };
+        let time = time.with_timezone(&Utc);
+        if self
+            .last_event_time
+            .or(self.previous_end)
+            .is_some_and(|high| time < high)
+        {
+            self.out_of_order += 1;
+        }
+        self.first_event_time = Some(self.first_event_time.map_or(time, |first| first.min(time)));
+        self.last_event_time = Some(self.last_event_time.map_or(time, |last| last.max(time)));
+    }
+
+    /// Takes the stats of a finished file, leaving fresh stats for the
+    /// stream's next file that remember where this one ended.
+    pub fn rotate(&mut self) -> FileStats {
+        let end = self.last_event_time.or(self.previous_end);
+        let finished = std::mem::take(self);
+        self.previous_end = end;
+        finished
+    }
+}
+
+/// A file a writer has finished and closed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenFile {
+    pub path: PathBuf,
+    /// Source label used in the file name (e.g. `CloudTrail`).
+    pub source: String,
+    pub stats: FileStats,
 }
@@ -0,0 +1,6 @@
+//! Small helpers shared by sources and writers.
+
+/// Lowercase hex encoding of `bytes`, such as a digest.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
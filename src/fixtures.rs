@@ -0,0 +1,489 @@
+//! Single-event fixtures for tests.
+//!
+//! The builders here produce one realistic CloudTrail record, Entra ID
+//! sign-in, or Entra ID audit event without a config, population, or
+//! generator, through the same templates the generators use. Every field has
+//! a fixed default and randomness comes from the builder's seed, so a fixture
+//! is the same on every run.
+//!
+//! ```
+//! use seclog::{ActorContext, CloudTrailEventBuilder, EntraSignInEventBuilder};
+//!
+//! let event = CloudTrailEventBuilder::new("GetObject")
+//!     .actor(ActorContext::iam_user("123456789012", "alice").with_region("eu-west-1"))
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(event.aws_region, "eu-west-1");
+//!
+//! let sign_in = EntraSignInEventBuilder::new("alice@example.com").failed().build();
+//! assert_eq!(sign_in.payload["status"]["errorCode"], 50126);
+//! ```
+
+use crate::core::actors::{ActorKind, ActorRole, ActorSeed};
+use crate::core::config::EntraFederationConfig;
+use crate::core::event::Event;
+use crate::sources::cloudtrail::federation::EntraFederation;
+use crate::sources::cloudtrail::generator::envelope_for;
+use crate::sources::cloudtrail::rotation::secret_event;
+use crate::sources::cloudtrail::templates::derived_uuid;
+use crate::sources::cloudtrail::{
+    build_cloudtrail_event, ActorContext, CloudTrailEvent, ErrorProfile, TemplateError,
+};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+
+/// Account of default fixture actors.
+pub const FIXTURE_ACCOUNT_ID: &str = "123456789012";
+/// Browser of default Entra sign-ins, Chrome on Windows.
+const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+/// Time of fixture events unless set: 2026-01-01T00:00:00Z.
+fn default_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0)
+        .single()
+        .expect("valid fixture time")
+}
+
+/// Builds one CloudTrail record.
+#[derive(Debug, Clone)]
+pub struct CloudTrailEventBuilder {
+    event_name: String,
+    actor: ActorContext,
+    time: DateTime<Utc>,
+    error: Option<ErrorProfile>,
+    seed: u64,
+}
+
+impl CloudTrailEventBuilder {
+    /// A successful `event_name` call by the IAM user `alice` in
+    /// [`FIXTURE_ACCOUNT_ID`].
+    pub fn new(event_name: &str) -> Self {
+        Self {
+            event_name: event_name.to_string(),
+            actor: ActorContext::iam_user(FIXTURE_ACCOUNT_ID, "alice"),
+            time: default_time(),
+            error: None,
+            seed: 0,
+        }
+    }
+
+    pub fn actor(mut self, actor: ActorContext) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    pub fn time(mut self, time: DateTime<Utc>) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Makes the call fail with `error`, such as a
+    /// [`default_error_profile`](crate::sources::cloudtrail::default_error_profile).
+    pub fn error(mut self, error: ErrorProfile) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Seeds request IDs, resource names, and other random fields.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn build(&self) -> Result<CloudTrailEvent, TemplateError> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        build_cloudtrail_event(
+            &self.event_name,
+            &self.actor,
+            &mut rng,
+            &self.time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            self.error.clone(),
+            1.0,
+        )
+    }
+
+    /// The record wrapped in a normalized event, as the CloudTrail source
+    /// emits it.
+    pub fn build_event(&self) -> Result<Event, TemplateError> {
+        let cloudtrail = self.build()?;
+        Ok(Event {
+            envelope: envelope_for(&cloudtrail, self.actor.principal_id.to_string()),
+            payload: cloudtrail.to_value(),
+            label: None,
+        })
+    }
+}
+
+/// Builds one interactive Entra ID sign-in to the AWS enterprise application.
+#[derive(Debug, Clone)]
+pub struct EntraSignInEventBuilder {
+    user_principal_name: String,
+    display_name: Option<String>,
+    home_location: String,
+    config: EntraFederationConfig,
+    source_ip: String,
+    user_agent: String,
+    time: DateTime<Utc>,
+    mfa_authenticated: bool,
+    succeeded: bool,
+    seed: u64,
+}
+
+impl EntraSignInEventBuilder {
+    /// A successful sign-in with MFA by `user_principal_name` from Chrome on
+    /// Windows in Seattle. The tenant is derived from the name's domain.
+    pub fn new(user_principal_name: &str) -> Self {
+        Self {
+            user_principal_name: user_principal_name.to_string(),
+            display_name: None,
+            home_location: "Seattle, Washington, United States".to_string(),
+            config: EntraFederationConfig::default(),
+            source_ip: "203.0.113.10".to_string(),
+            user_agent: BROWSER_USER_AGENT.to_string(),
+            time: default_time(),
+            mfa_authenticated: true,
+            succeeded: true,
+            seed: 0,
+        }
+    }
+
+    pub fn display_name(mut self, display_name: &str) -> Self {
+        self.display_name = Some(display_name.to_string());
+        self
+    }
+
+    /// `City, State, Country` the sign-in is located in.
+    pub fn home_location(mut self, home_location: &str) -> Self {
+        self.home_location = home_location.to_string();
+        self
+    }
+
+    pub fn tenant_id(mut self, tenant_id: &str) -> Self {
+        self.config.tenant_id = Some(tenant_id.to_string());
+        self
+    }
+
+    /// Enterprise application signed in to.
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.config.app_name = Some(app_name.to_string());
+        self
+    }
+
+    pub fn source_ip(mut self, source_ip: &str) -> Self {
+        self.source_ip = source_ip.to_string();
+        self
+    }
+
+    /// Browser user agent; the device and browser details follow from it.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    pub fn time(mut self, time: DateTime<Utc>) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn mfa(mut self, mfa_authenticated: bool) -> Self {
+        self.mfa_authenticated = mfa_authenticated;
+        self
+    }
+
+    /// Fails the sign-in on an invalid password (`AADSTS50126`).
+    pub fn failed(mut self) -> Self {
+        self.succeeded = false;
+        self
+    }
+
+    /// Seeds the sign-in and correlation IDs.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn build(&self) -> Event {
+        let user_name = self
+            .user_principal_name
+            .split_once('@')
+            .map_or(self.user_principal_name.as_str(), |(name, _)| name);
+        let mut seed = fixture_seed(ActorKind::Human, user_name);
+        seed.role = Some(ActorRole::Developer);
+        seed.email = Some(self.user_principal_name.clone());
+        seed.display_name = self.display_name.clone();
+        seed.home_location = Some(self.home_location.clone());
+        let federation = EntraFederation::new(&self.config);
+        let user = federation.user(&seed);
+        let actor = ActorContext::iam_user(FIXTURE_ACCOUNT_ID, user_name)
+            .with_source_ip(&self.source_ip)
+            .with_user_agent(&self.user_agent);
+        federation.sign_in_event(
+            &seed,
+            &user,
+            &actor,
+            self.time,
+            self.mfa_authenticated,
+            self.succeeded,
+            &mut StdRng::seed_from_u64(self.seed),
+        )
+    }
+}
+
+/// Builds one Entra ID audit event: an application's client secret being
+/// added or removed, as credential rotation records it.
+#[derive(Debug, Clone)]
+pub struct EntraAuditEventBuilder {
+    app_name: String,
+    tenant_id: String,
+    time: DateTime<Utc>,
+    removed: String,
+    added: Option<String>,
+}
+
+impl EntraAuditEventBuilder {
+    /// Application `app_name` adding a client secret next to its current one,
+    /// in the tenant Entra sign-ins of `example.com` users default to.
+    pub fn new(app_name: &str) -> Self {
+        Self {
+            app_name: app_name.to_string(),
+            tenant_id: derived_uuid("entra:tenant:example.com"),
+            time: default_time(),
+            removed: derived_uuid(&format!("entra:secret:{app_name}:0")),
+            added: Some(derived_uuid(&format!("entra:secret:{app_name}:1"))),
+        }
+    }
+
+    pub fn tenant_id(mut self, tenant_id: &str) -> Self {
+        self.tenant_id = tenant_id.to_string();
+        self
+    }
+
+    pub fn time(mut self, time: DateTime<Utc>) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Adds client secret `added` next to `current`.
+    pub fn secret_added(mut self, current: &str, added: &str) -> Self {
+        self.removed = current.to_string();
+        self.added = Some(added.to_string());
+        self
+    }
+
+    /// Removes client secret `removed`.
+    pub fn secret_removed(mut self, removed: &str) -> Self {
+        self.removed = removed.to_string();
+        self.added = None;
+        self
+    }
+
+    pub fn build(&self) -> Event {
+        let mut seed = fixture_seed(ActorKind::Service, &self.app_name);
+        seed.display_name = Some(self.app_name.clone());
+        secret_event(
+            &seed,
+            &self.tenant_id,
+            self.time,
+            &self.removed,
+            self.added.as_deref(),
+        )
+    }
+}
+
+/// Actor seed for IAM user `user_name` in [`FIXTURE_ACCOUNT_ID`].
+fn fixture_seed(kind: ActorKind, user_name: &str) -> ActorSeed {
+    let actor = ActorContext::iam_user(FIXTURE_ACCOUNT_ID, user_name);
+    ActorSeed {
+        kind,
+        role: None,
+        id: None,
+        identity_type: actor.identity_type.to_string(),
+        principal_id: actor.principal_id.to_string(),
+        arn: actor.arn.to_string(),
+        account_id: FIXTURE_ACCOUNT_ID.to_string(),
+        access_key_id: actor
+            .access_key_id
+            .as_deref()
+            .unwrap_or_default()
+            .to_string(),
+        rate_per_hour: 0.0,
+        error_rate: 0.0,
+        tags: Vec::new(),
+        event_bias: HashMap::new(),
+        service_profile: None,
+        service_pattern: None,
+        insider_persona: None,
+        on_call: None,
+        user_name: Some(user_name.to_string()),
+        display_name: None,
+        email: None,
+        home_location: None,
+        normal_countries_regions: Vec::new(),
+        user_agents: vec![actor.user_agent],
+        source_ips: vec![actor.source_ip],
+        active_start_hour: 0,
+        active_hours: 24,
+        timezone_offset: 0,
+        timezone_fixed: false,
+        weekend_active: true,
+    }
+}
+
+/// Bare event for unit tests: `event_type` from `source` at
+/// 2026-01-01T00:00:00Z by human `actor-1`, with no other envelope detail.
+/// Tests set the fields they depend on with the setters below.
+#[cfg(test)]
+pub(crate) fn test_event(source: &str, event_type: &str, payload: serde_json::Value) -> Event {
+    use crate::core::event::{Actor, EventEnvelope, Outcome};
+
+    Event {
+        envelope: EventEnvelope {
+            schema_version: "v1".to_string(),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            source: source.to_string(),
+            event_type: event_type.to_string(),
+            actor: Actor {
+                id: "actor-1".to_string(),
+                kind: "human".to_string(),
+                name: None,
+            },
+            target: None,
+            outcome: Outcome::Success,
+            geo: None,
+            ip: None,
+            user_agent: None,
+            session_id: None,
+            tenant_id: None,
+            tenant: None,
+        },
+        payload,
+        label: None,
+    }
+}
+
+/// CloudTrail `event_name` record at `timestamp` in account 123456789012 and
+/// `us-east-1`, the fields writers key files, digests, and partitions by.
+#[cfg(test)]
+pub(crate) fn cloudtrail_test_event(event_name: &str, timestamp: &str) -> Event {
+    test_event(
+        "cloudtrail",
+        event_name,
+        serde_json::json!({
+            "eventTime": timestamp,
+            "eventName": event_name,
+            "awsRegion": "us-east-1",
+        }),
+    )
+    .at(timestamp)
+    .in_account("123456789012")
+}
+
+/// Queues `events` the way the async sink bridge does.
+#[cfg(all(test, feature = "tokio"))]
+pub(crate) fn sink_records(events: &[Event]) -> Vec<crate::formats::async_sink::SinkRecord> {
+    events
+        .iter()
+        .map(|event| crate::formats::async_sink::SinkRecord {
+            event: event.clone(),
+            bytes: crate::formats::json::record_bytes_for_event(event).unwrap(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+impl Event {
+    /// Sets the envelope timestamp.
+    pub(crate) fn at(mut self, timestamp: &str) -> Self {
+        self.envelope.timestamp = timestamp.to_string();
+        self
+    }
+
+    /// Sets the envelope actor ID and kind.
+    pub(crate) fn by(mut self, id: &str, kind: &str) -> Self {
+        self.envelope.actor.id = id.to_string();
+        self.envelope.actor.kind = kind.to_string();
+        self
+    }
+
+    /// Sets the envelope `tenant_id`, the account or org the event came from.
+    pub(crate) fn in_account(mut self, account: &str) -> Self {
+        self.envelope.tenant_id = Some(account.to_string());
+        self
+    }
+
+    /// Sets the envelope `tenant`, the configured tenant owning the output.
+    pub(crate) fn owned_by(mut self, tenant: &str) -> Self {
+        self.envelope.tenant = Some(tenant.to_string());
+        self
+    }
+
+    /// Marks the event as failed.
+    pub(crate) fn failed(mut self) -> Self {
+        self.envelope.outcome = crate::core::event::Outcome::Failure;
+        self
+    }
+
+    /// Sets the envelope client IP.
+    pub(crate) fn with_ip(mut self, ip: &str) -> Self {
+        self.envelope.ip = Some(ip.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::cloudtrail::default_error_profile;
+
+    #[test]
+    fn cloudtrail_fixtures_are_stable_and_follow_the_actor() {
+        let builder = CloudTrailEventBuilder::new("AssumeRole").seed(7);
+        let first = builder.build().unwrap();
+        assert_eq!(first.to_value(), builder.build().unwrap().to_value());
+        assert_eq!(&*first.user_identity.identity_type, "IAMUser");
+        assert_eq!(first.event_time, "2026-01-01T00:00:00.000Z");
+
+        let at = default_time();
+        let role = "arn:aws:iam::210987654321:role/deployer";
+        let event = CloudTrailEventBuilder::new("ConsoleLogin")
+            .actor(ActorContext::assumed_role(role, "ci", at).with_console_session(true))
+            .error(default_error_profile("ConsoleLogin").unwrap())
+            .build_event()
+            .unwrap();
+        assert_eq!(event.envelope.source, "cloudtrail");
+        assert_eq!(event.envelope.tenant_id.as_deref(), Some("210987654321"));
+        assert_eq!(event.payload["userIdentity"]["type"], "AssumedRole");
+        assert_eq!(event.payload["errorCode"], "SigninFailure");
+        assert!(CloudTrailEventBuilder::new(" ").build().is_err());
+    }
+
+    #[test]
+    fn entra_fixtures_share_the_default_tenant() {
+        let sign_in = EntraSignInEventBuilder::new("alice@example.com")
+            .mfa(false)
+            .build();
+        assert_eq!(sign_in.envelope.source, "entra_signin");
+        assert_eq!(sign_in.payload["userPrincipalName"], "alice@example.com");
+        assert_eq!(
+            sign_in.payload["authenticationRequirement"],
+            "singleFactorAuthentication"
+        );
+        assert_eq!(sign_in.payload["location"]["countryOrRegion"], "US");
+
+        let audit = EntraAuditEventBuilder::new("payments-api")
+            .secret_removed("old-secret")
+            .build();
+        assert_eq!(audit.envelope.source, "entra_audit");
+        assert_eq!(audit.envelope.tenant_id, sign_in.envelope.tenant_id);
+        assert_eq!(
+            audit.payload["initiatedBy"]["app"]["displayName"],
+            "payments-api"
+        );
+        assert_eq!(
+            audit.payload["targetResources"][0]["modifiedProperties"][0]["newValue"],
+            "[]"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Arrow IPC stream writer.
+//!
+//! Emits record batches with the same schema as the Parquet writer, so DuckDB,
+//! Polars, or a Flight server can read a live event stream without waiting for
+//! Parquet files to rotate. The stream goes to a file or named pipe, or to
+//! every client connected to a TCP listener.
+
+use super::parquet::{build_schema, map_arrow_err, EventBatchBuilder};
+use crate::core::config::ArrowIpcOutputConfig;
+use crate::core::event::Event;
+use crate::core::traits::EventWriter;
+use arrow_array::RecordBatch;
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::SchemaRef;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Stream = StreamWriter<BufWriter<Box<dyn Write + Send>>>;
+
+/// Event writer that sends Arrow IPC record batches to a stream or TCP clients.
+pub struct ArrowIpcWriter {
+    schema: SchemaRef,
+    batch: EventBatchBuilder,
+    batch_size: usize,
+    sink: Sink,
+}
+
+enum Sink {
+    Stream(Stream),
+    Clients(Clients),
+}
+
+/// Connected TCP clients plus connections accepted since the last batch.
+struct Clients {
+    addr: SocketAddr,
+    accepted: Arc<Mutex<Vec<TcpStream>>>,
+    streams: Vec<Stream>,
+}
+
+impl ArrowIpcWriter {
+    /// Writes one IPC stream to `out`; the schema message is written immediately.
+    pub fn new(
+        out: Box<dyn Write + Send>,
+        batch_size: usize,
+        timestamp_strings: bool,
+    ) -> io::Result<Self> {
+        let schema = build_schema(timestamp_strings);
+        let stream = open_stream(out, &schema)?;
+        Ok(Self::with_sink(schema, batch_size, Sink::Stream(stream)))
+    }
+
+    /// Serves the stream on `listen`. Clients that connect mid-run receive the
+    /// schema and then only batches written after they joined; batches written
+    /// while no client is connected are discarded.
+    pub fn listen(listen: &str, batch_size: usize, timestamp_strings: bool) -> io::Result<Self> {
+        let listener = TcpListener::bind(listen)?;
+        let addr = listener.local_addr()?;
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+        let queue = Arc::clone(&accepted);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                // A client that stops reading is dropped instead of stalling the run.
+                let _ = stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT));
+                queue
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push(stream);
+            }
+        });
+        Ok(Self::with_sink(
+            build_schema(timestamp_strings),
+            batch_size,
+            Sink::Clients(Clients {
+                addr,
+                accepted,
+                streams: Vec::new(),
+            }),
+        ))
+    }
+
+    /// Opens the sink selected by an `arrow_ipc` output config.
+    pub fn from_config(config: &ArrowIpcOutputConfig) -> io::Result<Self> {
+        match (config.path.as_deref(), config.listen.as_deref()) {
+            (Some("-"), None) => Self::new(
+                Box::new(io::stdout()),
+                config.batch_size,
+                config.timestamp_strings,
+            ),
+            (Some(path), None) => Self::new(
+                Box::new(File::create(path)?),
+                config.batch_size,
+                config.timestamp_strings,
+            ),
+            (None, Some(listen)) => {
+                Self::listen(listen, config.batch_size, config.timestamp_strings)
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "arrow_ipc output requires exactly one of path or listen",
+            )),
+        }
+    }
+
+    fn with_sink(schema: SchemaRef, batch_size: usize, sink: Sink) -> Self {
+        let batch_size = batch_size.max(1);
+        Self {
+            batch: EventBatchBuilder::new(schema.clone(), batch_size),
+            schema,
+            batch_size,
+            sink,
+        }
+    }
+
+    /// Address the TCP listener is bound to, useful when `listen` uses port 0.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.sink {
+            Sink::Stream(_) => None,
+            Sink::Clients(clients) => Some(clients.addr),
+        }
+    }
+
+    /// Sends the buffered rows, if any, as one record batch.
+    fn send_batch(&mut self) -> io::Result<()> {
+        if self.batch.len() == 0 {
+            return Ok(());
+        }
+        let batch = self.batch.finish().map_err(map_arrow_err)?;
+        match &mut self.sink {
+            Sink::Stream(stream) => write_batch(stream, &batch),
+            Sink::Clients(clients) => {
+                clients.admit(&self.schema);
+                // A client that hung up or timed out is dropped; the run goes on.
+                clients
+                    .streams
+                    .retain_mut(|stream| write_batch(stream, &batch).is_ok());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Clients {
+    /// Starts a stream, schema first, for every newly accepted connection.
+    fn admit(&mut self, schema: &SchemaRef) {
+        let accepted = std::mem::take(
+            &mut *self
+                .accepted
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        for stream in accepted {
+            if let Ok(stream) = open_stream(Box::new(stream), schema) {
+                self.streams.push(stream);
+            }
+        }
+    }
+}
+
+impl EventWriter for ArrowIpcWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let size = self.batch.append_event(event)?;
+        if self.batch.len() >= self.batch_size {
+            self.send_batch()?;
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send_batch()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.send_batch()?;
+        match &mut self.sink {
+            Sink::Stream(stream) => finish_stream(stream),
+            Sink::Clients(clients) => {
+                clients.admit(&self.schema);
+                for stream in &mut clients.streams {
+                    let _ = finish_stream(stream);
+                }
+                clients.streams.clear();
+                Ok(())
+            }
+        }
+    }
+}
+
+fn open_stream(out: Box<dyn Write + Send>, schema: &SchemaRef) -> io::Result<Stream> {
+    let mut stream = StreamWriter::try_new(BufWriter::new(out), schema).map_err(map_arrow_err)?;
+    stream.get_mut().flush()?;
+    Ok(stream)
+}
+
+fn write_batch(stream: &mut Stream, batch: &RecordBatch) -> io::Result<()> {
+    stream.write(batch).map_err(map_arrow_err)?;
+    stream.get_mut().flush()
+}
+
+/// Writes the end-of-stream marker.
+fn finish_stream(stream: &mut Stream) -> io::Result<()> {
+    stream.finish().map_err(map_arrow_err)?;
+    stream.get_mut().flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use arrow_array::cast::AsArray;
+    use arrow_array::Array;
+    use arrow_ipc::reader::StreamReader;
+    use serde_json::json;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streams_batches_with_the_parquet_schema() {
+        let buffer = SharedBuffer::default();
+        let mut writer = ArrowIpcWriter::new(Box::new(buffer.clone()), 2, false).unwrap();
+        for name in ["GetObject", "PutObject", "ListBuckets"] {
+            writer
+                .write_event(&test_event(
+                    "cloudtrail",
+                    name,
+                    json!({ "eventName": name, "awsRegion": "us-east-1" }),
+                ))
+                .unwrap();
+        }
+        writer.flush().unwrap();
+        writer.close().unwrap();
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let reader = StreamReader::try_new(io::Cursor::new(bytes), None).unwrap();
+        assert_eq!(reader.schema(), build_schema(false));
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            batches
+                .iter()
+                .map(RecordBatch::num_rows)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        let cloudtrail = batches[1].column(2).as_struct();
+        let names = cloudtrail
+            .column_by_name("eventName")
+            .unwrap()
+            .as_string::<i32>();
+        assert_eq!(names.value(0), "ListBuckets");
+        assert!(!names.is_null(0));
+    }
+}
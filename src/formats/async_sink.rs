@@ -0,0 +1,534 @@
+//! Async event sinks.
+//!
+//! [`AsyncEventSink`] is the async counterpart of [`EventWriter`] for network
+//! sinks (HTTP, Kafka, object stores) that should batch and overlap requests
+//! instead of blocking a writer shard thread. Two adapters let both kinds share
+//! the shard architecture:
+//!
+//! - [`BlockingSink`] runs an existing [`EventWriter`] on tokio's blocking pool.
+//! - [`AsyncSinkWriter`] drives an async sink from a shard thread: it
+//!   implements [`EventWriter`], queues events to a task on a tokio runtime,
+//!   and hands them to the sink in batches, keeping up to
+//!   [`AsyncEventSink::max_in_flight`] batch requests running at once.
+//!
+//! Each event is serialized once, by `write_event`, and travels to the sink
+//! as a [`SinkRecord`] so the sink can splice the bytes into its requests.
+
+use super::json::record_bytes_for_event;
+use crate::core::event::Event;
+use crate::core::traits::EventWriter;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Mutex};
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::mpsc as async_mpsc;
+use tokio::task::{JoinError, JoinHandle, JoinSet};
+
+/// Boxed future returned by [`AsyncEventSink`] methods.
+pub type SinkFuture<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+
+/// An event queued for an [`AsyncEventSink`] with its source-native JSON
+/// record, serialized once when the event was written.
+pub struct SinkRecord {
+    pub event: Event,
+    pub bytes: Vec<u8>,
+}
+
+/// Writes events asynchronously.
+///
+/// Like [`EventWriter`], `close` drains the sink but may be followed by more
+/// writes.
+pub trait AsyncEventSink: Send {
+    /// Batch requests that may run at once. The default of 1 writes batches
+    /// strictly in order.
+    fn max_in_flight(&self) -> usize {
+        1
+    }
+    /// Starts writing a batch and returns the request, which resolves to the
+    /// number of bytes written. The request owns what it needs, so up to
+    /// `max_in_flight` of them run alongside each other; `flush` and `close`
+    /// are only called once every request has finished.
+    fn write_batch(&mut self, records: Vec<SinkRecord>) -> SinkFuture<'static, u64>;
+    /// Flushes buffered data without closing the sink.
+    fn flush(&mut self) -> SinkFuture<'_, ()>;
+    /// Writes out everything buffered without closing the sink, like
+    /// [`EventWriter::sync`]. Defaults to `flush`.
+    fn sync(&mut self) -> SinkFuture<'_, ()> {
+        self.flush()
+    }
+    /// Closes the sink, flushing any remaining data.
+    fn close(&mut self) -> SinkFuture<'_, ()>;
+}
+
+/// Runs a synchronous [`EventWriter`] as an [`AsyncEventSink`] on the blocking pool.
+///
+/// The writer serializes events itself, so one batch runs at a time.
+pub struct BlockingSink<W> {
+    writer: Arc<Mutex<W>>,
+}
+
+impl<W: EventWriter + Send + 'static> BlockingSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    /// Runs `op` against the writer on the blocking pool.
+    fn run<T, F>(&self, op: F) -> SinkFuture<'static, T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut W) -> io::Result<T> + Send + 'static,
+    {
+        let writer = Arc::clone(&self.writer);
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || {
+                let mut writer = writer
+                    .lock()
+                    .map_err(|_| io::Error::other("blocking sink writer was lost to a panic"))?;
+                op(&mut writer)
+            })
+            .await
+            .map_err(io::Error::other)?
+        })
+    }
+}
+
+impl<W: EventWriter + Send + 'static> AsyncEventSink for BlockingSink<W> {
+    fn write_batch(&mut self, records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+        self.run(move |writer| {
+            records.iter().try_fold(0, |bytes, record| {
+                Ok(bytes + writer.write_event(&record.event)?)
+            })
+        })
+    }
+
+    fn flush(&mut self) -> SinkFuture<'_, ()> {
+        self.run(|writer| writer.flush())
+    }
+
+    fn sync(&mut self) -> SinkFuture<'_, ()> {
+        self.run(|writer| writer.sync())
+    }
+
+    fn close(&mut self) -> SinkFuture<'_, ()> {
+        self.run(|writer| writer.close())
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+enum SinkCommand {
+    Event(SinkRecord),
+    Flush,
+    Sync(mpsc::SyncSender<io::Result<()>>),
+    Close(mpsc::SyncSender<io::Result<()>>),
+}
+
+/// Drives an [`AsyncEventSink`] from synchronous code such as a writer shard.
+///
+/// `write_event` only blocks when the queue is full; batches are written on
+/// the runtime while the caller keeps producing, up to the sink's
+/// `max_in_flight` at a time. `flush` is fire-and-forget; `sync` and `close`
+/// wait until every queued event is written and the sink is synced or closed.
+/// Sink errors are reported by the next call after they happen. Must not be
+/// called from inside the runtime it drives.
+pub struct AsyncSinkWriter {
+    runtime: Handle,
+    tx: Option<async_mpsc::Sender<SinkCommand>>,
+    task: Option<JoinHandle<()>>,
+    error: Arc<Mutex<Option<String>>>,
+    /// Set by [`AsyncSinkWriter::spawn`]; shut down after the task drains.
+    owned: Option<Runtime>,
+}
+
+impl AsyncSinkWriter {
+    /// Spawns the batching task for `sink` on `runtime`.
+    ///
+    /// Events are handed to the sink `batch_size` at a time; at most
+    /// `queue_depth` events wait in between.
+    pub fn new(
+        sink: impl AsyncEventSink + 'static,
+        runtime: Handle,
+        batch_size: usize,
+        queue_depth: usize,
+    ) -> Self {
+        let (tx, rx) = async_mpsc::channel(queue_depth.max(1));
+        let error = Arc::new(Mutex::new(None));
+        let task = runtime.spawn(run_sink(
+            Box::new(sink),
+            rx,
+            batch_size.max(1),
+            Arc::clone(&error),
+        ));
+        Self {
+            runtime,
+            tx: Some(tx),
+            task: Some(task),
+            error,
+            owned: None,
+        }
+    }
+
+    /// Like [`AsyncSinkWriter::new`], on a runtime of its own that lives as
+    /// long as the writer. For callers that are not running tokio already.
+    pub fn spawn(
+        sink: impl AsyncEventSink + 'static,
+        batch_size: usize,
+        queue_depth: usize,
+    ) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("seclog-async-sink")
+            .enable_all()
+            .build()?;
+        let mut writer = Self::new(sink, runtime.handle().clone(), batch_size, queue_depth);
+        writer.owned = Some(runtime);
+        Ok(writer)
+    }
+
+    fn check_error(&self) -> io::Result<()> {
+        match self.error.lock().expect("sink error lock").as_ref() {
+            Some(message) => Err(io::Error::other(format!("async sink failed: {message}"))),
+            None => Ok(()),
+        }
+    }
+
+    fn send(&self, command: SinkCommand) -> io::Result<()> {
+        self.check_error()?;
+        self.tx
+            .as_ref()
+            .ok_or_else(|| io::Error::other("async sink is shut down"))?
+            .blocking_send(command)
+            .map_err(|_| io::Error::other("async sink task stopped"))
+    }
+}
+
+impl EventWriter for AsyncSinkWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        // Serialized once: the record's size is counted here and the sink
+        // splices the same bytes into its requests.
+        let bytes = record_bytes_for_event(event)?;
+        let size = bytes.len() as u64;
+        self.send(SinkCommand::Event(SinkRecord {
+            event: event.clone(),
+            bytes,
+        }))?;
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send(SinkCommand::Flush)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        let (ack_tx, ack_rx) = mpsc::sync_channel(1);
+        self.send(SinkCommand::Sync(ack_tx))?;
+        ack_rx
+            .recv()
+            .map_err(|_| io::Error::other("async sink task stopped before syncing"))?
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        let (ack_tx, ack_rx) = mpsc::sync_channel(1);
+        self.send(SinkCommand::Close(ack_tx))?;
+        ack_rx
+            .recv()
+            .map_err(|_| io::Error::other("async sink task stopped before closing"))?
+    }
+}
+
+impl Drop for AsyncSinkWriter {
+    fn drop(&mut self) {
+        // Dropping the sender ends the task once it drains the queue.
+        self.tx.take();
+        if let Some(task) = self.task.take() {
+            if Handle::try_current().is_err() {
+                let _ = self.runtime.block_on(task);
+            }
+        }
+    }
+}
+
+async fn run_sink(
+    mut sink: Box<dyn AsyncEventSink>,
+    mut rx: async_mpsc::Receiver<SinkCommand>,
+    batch_size: usize,
+    error: Arc<Mutex<Option<String>>>,
+) {
+    let max_in_flight = sink.max_in_flight().max(1);
+    let mut in_flight = JoinSet::new();
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut failed = None;
+    while let Some(command) = rx.recv().await {
+        let closing = matches!(command, SinkCommand::Close(_));
+        let result = match command {
+            SinkCommand::Event(record) => {
+                if failed.is_some() {
+                    continue;
+                }
+                batch.push(record);
+                if batch.len() < batch_size {
+                    continue;
+                }
+                start_batch(sink.as_mut(), &mut batch, &mut in_flight, max_in_flight).await
+            }
+            SinkCommand::Flush => {
+                if failed.is_some() {
+                    continue;
+                }
+                match drain(sink.as_mut(), &mut batch, &mut in_flight, max_in_flight).await {
+                    Ok(()) => sink.flush().await,
+                    Err(err) => Err(err),
+                }
+            }
+            SinkCommand::Sync(ack) | SinkCommand::Close(ack) => {
+                let result = match &failed {
+                    Some(message) => Err(io::Error::other(format!("async sink failed: {message}"))),
+                    None => {
+                        match drain(sink.as_mut(), &mut batch, &mut in_flight, max_in_flight).await
+                        {
+                            Ok(()) if closing => sink.close().await,
+                            Ok(()) => sink.sync().await,
+                            Err(err) => Err(err),
+                        }
+                    }
+                };
+                let _ = ack.send(result);
+                continue;
+            }
+        };
+        if let Err(err) = result {
+            let message = err.to_string();
+            *error.lock().expect("sink error lock") = Some(message.clone());
+            failed = Some(message);
+            batch.clear();
+            in_flight.abort_all();
+        }
+    }
+    if failed.is_none() {
+        if let Ok(()) = drain(sink.as_mut(), &mut batch, &mut in_flight, max_in_flight).await {
+            let _ = sink.close().await;
+        }
+    }
+}
+
+/// Starts a request for `batch`, first waiting for a slot when
+/// `max_in_flight` requests are running. Surfaces errors of finished requests.
+async fn start_batch(
+    sink: &mut dyn AsyncEventSink,
+    batch: &mut Vec<SinkRecord>,
+    in_flight: &mut JoinSet<io::Result<u64>>,
+    max_in_flight: usize,
+) -> io::Result<()> {
+    while let Some(done) = in_flight.try_join_next() {
+        finished(done)?;
+    }
+    while in_flight.len() >= max_in_flight {
+        if let Some(done) = in_flight.join_next().await {
+            finished(done)?;
+        }
+    }
+    let records = std::mem::replace(batch, Vec::with_capacity(batch.capacity()));
+    in_flight.spawn(sink.write_batch(records));
+    Ok(())
+}
+
+/// Starts the partial batch, if any, and waits for every request in flight.
+async fn drain(
+    sink: &mut dyn AsyncEventSink,
+    batch: &mut Vec<SinkRecord>,
+    in_flight: &mut JoinSet<io::Result<u64>>,
+    max_in_flight: usize,
+) -> io::Result<()> {
+    let mut result = Ok(());
+    if !batch.is_empty() {
+        result = start_batch(sink, batch, in_flight, max_in_flight).await;
+    }
+    while let Some(done) = in_flight.join_next().await {
+        if result.is_ok() {
+            result = finished(done);
+        }
+    }
+    result
+}
+
+fn finished(done: Result<io::Result<u64>, JoinError>) -> io::Result<()> {
+    done.map_err(io::Error::other)?.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    #[derive(Clone, Default)]
+    struct Recorded {
+        batches: Arc<Mutex<Vec<usize>>>,
+        closes: Arc<Mutex<usize>>,
+        /// Requests running right now and the most seen at once.
+        running: Arc<Mutex<(usize, usize)>>,
+    }
+
+    struct RecordingSink {
+        recorded: Recorded,
+        max_in_flight: usize,
+    }
+
+    impl RecordingSink {
+        fn new(recorded: &Recorded) -> Self {
+            Self {
+                recorded: recorded.clone(),
+                max_in_flight: 1,
+            }
+        }
+    }
+
+    impl AsyncEventSink for RecordingSink {
+        fn max_in_flight(&self) -> usize {
+            self.max_in_flight
+        }
+
+        fn write_batch(&mut self, records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+            let recorded = self.recorded.clone();
+            Box::pin(async move {
+                {
+                    let mut running = recorded.running.lock().unwrap();
+                    running.0 += 1;
+                    running.1 = running.1.max(running.0);
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                recorded.running.lock().unwrap().0 -= 1;
+                recorded.batches.lock().unwrap().push(records.len());
+                Ok(records.iter().map(|record| record.bytes.len() as u64).sum())
+            })
+        }
+
+        fn flush(&mut self) -> SinkFuture<'_, ()> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn close(&mut self) -> SinkFuture<'_, ()> {
+            Box::pin(async move {
+                *self.recorded.closes.lock().unwrap() += 1;
+                Ok(())
+            })
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct CollectingWriter {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventWriter for CollectingWriter {
+        fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+            self.events
+                .lock()
+                .unwrap()
+                .push(event.envelope.timestamp.clone());
+            Ok(1)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .expect("runtime")
+    }
+
+    #[test]
+    fn bridge_batches_events_and_drains_on_close() {
+        let event = test_event("cloudtrail", "test", json!({ "eventName": "test" }));
+        let runtime = runtime();
+        let recorded = Recorded::default();
+        let mut writer = AsyncSinkWriter::new(
+            RecordingSink::new(&recorded),
+            runtime.handle().clone(),
+            4,
+            2,
+        );
+        for _ in 0..10 {
+            writer.write_event(&event).expect("write");
+        }
+        writer.close().expect("close");
+        assert_eq!(*recorded.batches.lock().unwrap(), vec![4, 4, 2]);
+        assert_eq!(*recorded.closes.lock().unwrap(), 1);
+
+        writer.write_event(&event).expect("write after close");
+        drop(writer);
+        assert_eq!(*recorded.batches.lock().unwrap(), vec![4, 4, 2, 1]);
+        assert_eq!(recorded.running.lock().unwrap().1, 1);
+    }
+
+    #[test]
+    fn bridge_overlaps_up_to_max_in_flight_requests() {
+        let event = test_event("cloudtrail", "test", json!({ "eventName": "test" }));
+        let runtime = runtime();
+        let recorded = Recorded::default();
+        let sink = RecordingSink {
+            max_in_flight: 3,
+            ..RecordingSink::new(&recorded)
+        };
+        let mut writer = AsyncSinkWriter::new(sink, runtime.handle().clone(), 2, 32);
+        let mut bytes = 0;
+        for _ in 0..20 {
+            bytes += writer.write_event(&event).expect("write");
+        }
+        writer.close().expect("close");
+        let batches = recorded.batches.lock().unwrap();
+        assert_eq!(batches.iter().sum::<usize>(), 20);
+        assert_eq!(batches.len(), 10);
+        assert_eq!(recorded.running.lock().unwrap().1, 3);
+        assert_eq!(
+            bytes,
+            record_bytes_for_event(&event).unwrap().len() as u64 * 20
+        );
+    }
+
+    #[test]
+    fn spawned_writer_drains_on_its_own_runtime() {
+        let event = test_event("cloudtrail", "test", json!({ "eventName": "test" }));
+        let recorded = Recorded::default();
+        let mut writer =
+            AsyncSinkWriter::spawn(RecordingSink::new(&recorded), 4, 2).expect("spawn");
+        for _ in 0..6 {
+            writer.write_event(&event).expect("write");
+        }
+        drop(writer);
+        assert_eq!(*recorded.batches.lock().unwrap(), vec![4, 2]);
+        assert_eq!(*recorded.closes.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn blocking_sink_runs_sync_writers_through_the_bridge() {
+        let runtime = runtime();
+        let inner = CollectingWriter::default();
+        let mut writer = AsyncSinkWriter::new(
+            BlockingSink::new(inner.clone()),
+            runtime.handle().clone(),
+            3,
+            8,
+        );
+        for second in 0..5 {
+            let event = test_event("cloudtrail", "test", json!({ "eventName": "test" }))
+                .at(&format!("2026-01-01T00:00:{second:02}Z"));
+            writer.write_event(&event).expect("write");
+        }
+        writer.close().expect("close");
+        let events = inner.events.lock().unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(events[4], "2026-01-01T00:00:04Z");
+    }
+}
@@ -0,0 +1,696 @@
+//! Avro object container sink.
+//!
+//! Writes one container file per source/account/region with the writer schema
+//! embedded in the header (`avro.schema`), so consumers that validate against a
+//! registry can check files as-is. Records mirror the Parquet layout: an
+//! `envelope` record, the raw `payload_json` string, and a nullable
+//! `cloudtrail` record with the flattened CloudTrail columns. Timestamps are
+//! `timestamp-micros` longs unless `timestamp_strings` is set. Blocks use the
+//! `null` codec by default or `deflate` with `compression = "deflate"`.
+
+use super::json::{file_context_from_event, unique_id};
+use super::parquet::encode_payload;
+use crate::core::config::AvroOptions;
+use crate::core::event::{Actor, Event, EventEnvelope, Geo, Outcome, Target};
+use crate::core::traits::{EventWriter, FileStats, WrittenFile};
+use chrono::{DateTime, Utc};
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const MAGIC: &[u8; 4] = b"Obj\x01";
+/// Uncompressed bytes buffered before a data block is sealed.
+const BLOCK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AvroCodec {
+    Null,
+    Deflate,
+}
+
+impl AvroCodec {
+    fn name(self) -> &'static str {
+        match self {
+            AvroCodec::Null => "null",
+            AvroCodec::Deflate => "deflate",
+        }
+    }
+}
+
+/// Avro writer that buffers container blocks per source/account/region.
+pub struct AvroWriter {
+    dir: PathBuf,
+    target_size_bytes: u64,
+    max_age: Option<Duration>,
+    codec: AvroCodec,
+    timestamp_strings: bool,
+    schema: String,
+    files: HashMap<StreamKey, BlockBuffer>,
+    written: Vec<WrittenFile>,
+    /// Payload JSON of the event being encoded, reused across events.
+    payload_scratch: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct StreamKey {
+    source: String,
+    account_id: String,
+    region: String,
+}
+
+#[derive(Default)]
+struct BlockBuffer {
+    sync: [u8; 16],
+    /// Sealed blocks, framed with count, size, and sync marker.
+    sealed: Vec<u8>,
+    block: Vec<u8>,
+    block_count: i64,
+    first_event_at: Option<Instant>,
+    stats: FileStats,
+}
+
+impl AvroWriter {
+    /// Creates an Avro writer with size-based rotation and optional max age.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        options: &AvroOptions,
+    ) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let max_age = max_age_seconds
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs);
+        let timestamp_strings = options.timestamp_strings.unwrap_or(false);
+        Ok(Self {
+            dir,
+            target_size_bytes: target_size_mb.saturating_mul(1024 * 1024),
+            max_age,
+            codec: parse_codec(options.compression.as_deref())?,
+            timestamp_strings,
+            schema: event_schema(timestamp_strings).to_string(),
+            files: HashMap::new(),
+            written: Vec::new(),
+            payload_scratch: Vec::new(),
+        })
+    }
+
+    fn flush_stream(&mut self, key: &StreamKey) -> io::Result<()> {
+        let Some(stream) = self.files.get_mut(key) else {
+            return Ok(());
+        };
+        if let Some(file) = finish_file(&self.dir, key, stream, self.codec, &self.schema)? {
+            self.written.push(file);
+        }
+        Ok(())
+    }
+}
+
+impl EventWriter for AvroWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let context = file_context_from_event(event);
+        let key = StreamKey {
+            source: context.source,
+            account_id: context.account_id,
+            region: context.region,
+        };
+        let stream = self.files.entry(key.clone()).or_default();
+        if stream.first_event_at.is_none() {
+            stream.first_event_at = Some(Instant::now());
+            stream.sync = rand::random();
+        }
+        let start = stream.block.len();
+        if let Err(err) = encode_event(
+            &mut stream.block,
+            &mut self.payload_scratch,
+            event,
+            self.timestamp_strings,
+        ) {
+            stream.block.truncate(start);
+            return Err(err);
+        }
+        let size = (stream.block.len() - start) as u64;
+        stream.block_count += 1;
+        stream.stats.record(event);
+        if stream.block.len() >= BLOCK_BYTES {
+            seal_block(stream, self.codec)?;
+        }
+
+        // Sealed blocks are already deflated; the open one is counted raw.
+        if (stream.sealed.len() + stream.block.len()) as u64 >= self.target_size_bytes {
+            self.flush_stream(&key)?;
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        let due = self
+            .files
+            .iter()
+            .filter(|(_, stream)| {
+                stream.first_event_at.is_some_and(|start| {
+                    self.max_age
+                        .is_none_or(|max_age| now.duration_since(start) >= max_age)
+                })
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in due {
+            self.flush_stream(&key)?;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        let keys = self.files.keys().cloned().collect::<Vec<_>>();
+        for key in keys {
+            self.flush_stream(&key)?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.sync()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.written)
+    }
+}
+
+fn parse_codec(value: Option<&str>) -> io::Result<AvroCodec> {
+    match value.map(|value| value.trim().to_ascii_lowercase()) {
+        None => Ok(AvroCodec::Null),
+        Some(value) if value.is_empty() || value == "none" || value == "null" => {
+            Ok(AvroCodec::Null)
+        }
+        Some(value) if value == "deflate" => Ok(AvroCodec::Deflate),
+        Some(value) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported avro compression: {value}"),
+        )),
+    }
+}
+
+fn string(name: &str) -> Value {
+    json!({ "name": name, "type": "string" })
+}
+
+fn optional(name: &str, kind: &str) -> Value {
+    json!({ "name": name, "type": ["null", kind], "default": null })
+}
+
+fn optional_record(name: &str, record: Value) -> Value {
+    json!({ "name": name, "type": ["null", record], "default": null })
+}
+
+/// Writer schema for event records.
+pub fn event_schema(timestamp_strings: bool) -> Value {
+    let timestamp = if timestamp_strings {
+        json!("string")
+    } else {
+        json!(["null", { "type": "long", "logicalType": "timestamp-micros" }])
+    };
+    let event_time = if timestamp_strings {
+        json!(["null", "string"])
+    } else {
+        timestamp.clone()
+    };
+    let named = |name: &str| {
+        json!({
+            "type": "record",
+            "name": name,
+            "fields": [string("id"), string("kind"), optional("name", "string")],
+        })
+    };
+
+    let geo = json!({
+        "type": "record",
+        "name": "Geo",
+        "fields": [
+            string("country"),
+            optional("region", "string"),
+            optional("city", "string"),
+            optional("lat", "double"),
+            optional("lon", "double"),
+        ],
+    });
+    let envelope = json!({
+        "type": "record",
+        "name": "Envelope",
+        "fields": [
+            string("schema_version"),
+            { "name": "timestamp", "type": timestamp },
+            string("source"),
+            string("event_type"),
+            { "name": "actor", "type": named("Actor") },
+            optional_record("target", named("Target")),
+            string("outcome"),
+            optional_record("geo", geo),
+            optional("ip", "string"),
+            optional("user_agent", "string"),
+            optional("session_id", "string"),
+            optional("tenant_id", "string"),
+            optional("tenant", "string"),
+        ],
+    });
+    let identity = json!({
+        "type": "record",
+        "name": "UserIdentity",
+        "fields": [
+            optional("type", "string"),
+            optional("principalId", "string"),
+            optional("arn", "string"),
+            optional("accountId", "string"),
+            optional("accessKeyId", "string"),
+            optional("userName", "string"),
+        ],
+    });
+    let cloudtrail = json!({
+        "type": "record",
+        "name": "CloudTrail",
+        "fields": [
+            optional("eventVersion", "string"),
+            { "name": "eventTime", "type": event_time, "default": null },
+            optional("eventSource", "string"),
+            optional("eventName", "string"),
+            optional("awsRegion", "string"),
+            optional("sourceIPAddress", "string"),
+            optional("userAgent", "string"),
+            optional_record("userIdentity", identity),
+            optional("requestParametersJson", "string"),
+            optional("responseElementsJson", "string"),
+            optional("errorCode", "string"),
+            optional("errorMessage", "string"),
+            optional("requestID", "string"),
+            optional("eventID", "string"),
+            optional("readOnly", "boolean"),
+            optional("eventType", "string"),
+            optional("managementEvent", "boolean"),
+            optional("recipientAccountId", "string"),
+            optional("eventCategory", "string"),
+            optional("tlsDetailsJson", "string"),
+            optional("sessionCredentialFromConsole", "boolean"),
+        ],
+    });
+    json!({
+        "type": "record",
+        "name": "Event",
+        "namespace": "seclog",
+        "fields": [
+            { "name": "envelope", "type": envelope },
+            optional("payload_json", "string"),
+            optional_record("cloudtrail", cloudtrail),
+        ],
+    })
+}
+
+/// Appends one record to `buf`; `scratch` holds the payload JSON meanwhile.
+fn encode_event(
+    buf: &mut Vec<u8>,
+    scratch: &mut Vec<u8>,
+    event: &Event,
+    timestamp_strings: bool,
+) -> io::Result<()> {
+    let payload_json = encode_payload(scratch, &event.payload)?;
+    encode_envelope(buf, &event.envelope, timestamp_strings);
+    put_optional_str(buf, payload_json);
+    match event.payload.as_object() {
+        Some(payload) if event.envelope.source == "cloudtrail" => {
+            put_long(buf, 1);
+            encode_cloudtrail(buf, payload, timestamp_strings);
+        }
+        _ => put_long(buf, 0),
+    }
+    Ok(())
+}
+
+fn encode_envelope(buf: &mut Vec<u8>, envelope: &EventEnvelope, timestamp_strings: bool) {
+    put_str(buf, &envelope.schema_version);
+    if timestamp_strings {
+        put_str(buf, &envelope.timestamp);
+    } else {
+        put_optional_timestamp(buf, Some(&envelope.timestamp));
+    }
+    put_str(buf, &envelope.source);
+    put_str(buf, &envelope.event_type);
+    encode_actor(buf, &envelope.actor);
+    encode_target(buf, envelope.target.as_ref());
+    put_str(buf, outcome_to_str(&envelope.outcome));
+    encode_geo(buf, envelope.geo.as_ref());
+    put_optional_str(buf, envelope.ip.as_deref());
+    put_optional_str(buf, envelope.user_agent.as_deref());
+    put_optional_str(buf, envelope.session_id.as_deref());
+    put_optional_str(buf, envelope.tenant_id.as_deref());
+    put_optional_str(buf, envelope.tenant.as_deref());
+}
+
+fn encode_actor(buf: &mut Vec<u8>, actor: &Actor) {
+    put_str(buf, &actor.id);
+    put_str(buf, &actor.kind);
+    put_optional_str(buf, actor.name.as_deref());
+}
+
+fn encode_target(buf: &mut Vec<u8>, target: Option<&Target>) {
+    let Some(target) = target else {
+        put_long(buf, 0);
+        return;
+    };
+    put_long(buf, 1);
+    put_str(buf, &target.id);
+    put_str(buf, &target.kind);
+    put_optional_str(buf, target.name.as_deref());
+}
+
+fn encode_geo(buf: &mut Vec<u8>, geo: Option<&Geo>) {
+    let Some(geo) = geo else {
+        put_long(buf, 0);
+        return;
+    };
+    put_long(buf, 1);
+    put_str(buf, &geo.country);
+    put_optional_str(buf, geo.region.as_deref());
+    put_optional_str(buf, geo.city.as_deref());
+    put_optional_double(buf, geo.lat);
+    put_optional_double(buf, geo.lon);
+}
+
+fn encode_cloudtrail(buf: &mut Vec<u8>, payload: &Map<String, Value>, timestamp_strings: bool) {
+    let get =
+        |primary: &str, fallback: &str| payload.get(primary).or_else(|| payload.get(fallback));
+    let get_str = |primary: &str, fallback: &str| get(primary, fallback).and_then(Value::as_str);
+    let get_bool = |primary: &str, fallback: &str| get(primary, fallback).and_then(Value::as_bool);
+    let get_json = |primary: &str, fallback: &str| {
+        get(primary, fallback).and_then(|value| serde_json::to_string(value).ok())
+    };
+
+    put_optional_str(buf, get_str("eventVersion", "event_version"));
+    let event_time = get_str("eventTime", "event_time");
+    if timestamp_strings {
+        put_optional_str(buf, event_time);
+    } else {
+        put_optional_timestamp(buf, event_time);
+    }
+    put_optional_str(buf, get_str("eventSource", "event_source"));
+    put_optional_str(buf, get_str("eventName", "event_name"));
+    put_optional_str(buf, get_str("awsRegion", "aws_region"));
+    put_optional_str(buf, get_str("sourceIPAddress", "source_ip_address"));
+    put_optional_str(buf, get_str("userAgent", "user_agent"));
+    encode_identity(buf, get("userIdentity", "user_identity"));
+    put_optional_str(
+        buf,
+        get_json("requestParameters", "request_parameters").as_deref(),
+    );
+    put_optional_str(
+        buf,
+        get_json("responseElements", "response_elements").as_deref(),
+    );
+    put_optional_str(buf, get_str("errorCode", "error_code"));
+    put_optional_str(buf, get_str("errorMessage", "error_message"));
+    put_optional_str(buf, get_str("requestID", "request_id"));
+    put_optional_str(buf, get_str("eventID", "event_id"));
+    put_optional_bool(buf, get_bool("readOnly", "read_only"));
+    put_optional_str(buf, get_str("eventType", "event_type"));
+    put_optional_bool(buf, get_bool("managementEvent", "management_event"));
+    put_optional_str(buf, get_str("recipientAccountId", "recipient_account_id"));
+    put_optional_str(buf, get_str("eventCategory", "event_category"));
+    put_optional_str(buf, get_json("tlsDetails", "tls_details").as_deref());
+    put_optional_bool(
+        buf,
+        get_bool(
+            "sessionCredentialFromConsole",
+            "session_credential_from_console",
+        ),
+    );
+}
+
+fn encode_identity(buf: &mut Vec<u8>, value: Option<&Value>) {
+    let Some(map) = value.and_then(Value::as_object) else {
+        put_long(buf, 0);
+        return;
+    };
+    let get_str = |primary: &str, fallback: &str| {
+        map.get(primary)
+            .and_then(Value::as_str)
+            .or_else(|| map.get(fallback).and_then(Value::as_str))
+    };
+    put_long(buf, 1);
+    put_optional_str(buf, get_str("type", "identity_type"));
+    put_optional_str(buf, get_str("principalId", "principal_id"));
+    put_optional_str(buf, get_str("arn", "arn"));
+    put_optional_str(buf, get_str("accountId", "account_id"));
+    put_optional_str(buf, get_str("accessKeyId", "access_key_id"));
+    put_optional_str(buf, get_str("userName", "user_name"));
+}
+
+/// Zigzag varint, used for `int`, `long`, lengths, counts, and union branches.
+pub(crate) fn put_long(buf: &mut Vec<u8>, value: i64) {
+    let mut n = ((value << 1) ^ (value >> 63)) as u64;
+    while n >= 0x80 {
+        buf.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+pub(crate) fn put_bytes(buf: &mut Vec<u8>, value: &[u8]) {
+    put_long(buf, value.len() as i64);
+    buf.extend_from_slice(value);
+}
+
+pub(crate) fn put_str(buf: &mut Vec<u8>, value: &str) {
+    put_bytes(buf, value.as_bytes());
+}
+
+fn put_optional_str(buf: &mut Vec<u8>, value: Option<&str>) {
+    match value {
+        Some(value) => {
+            put_long(buf, 1);
+            put_str(buf, value);
+        }
+        None => put_long(buf, 0),
+    }
+}
+
+fn put_optional_double(buf: &mut Vec<u8>, value: Option<f64>) {
+    match value {
+        Some(value) => {
+            put_long(buf, 1);
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        None => put_long(buf, 0),
+    }
+}
+
+fn put_optional_bool(buf: &mut Vec<u8>, value: Option<bool>) {
+    match value {
+        Some(value) => {
+            put_long(buf, 1);
+            buf.push(u8::from(value));
+        }
+        None => put_long(buf, 0),
+    }
+}
+
+/// Writes an RFC3339 value as microseconds; unparseable values become null.
+fn put_optional_timestamp(buf: &mut Vec<u8>, value: Option<&str>) {
+    match value.and_then(|value| DateTime::parse_from_rfc3339(value).ok()) {
+        Some(time) => {
+            put_long(buf, 1);
+            put_long(buf, time.timestamp_micros());
+        }
+        None => put_long(buf, 0),
+    }
+}
+
+fn outcome_to_str(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Success => "success",
+        Outcome::Failure => "failure",
+        Outcome::Unknown => "unknown",
+    }
+}
+
+/// Frames the open block as `count, size, data, sync` and appends it to the sealed blocks.
+fn seal_block(stream: &mut BlockBuffer, codec: AvroCodec) -> io::Result<()> {
+    if stream.block_count == 0 {
+        return Ok(());
+    }
+    let data = match codec {
+        AvroCodec::Null => std::mem::take(&mut stream.block),
+        AvroCodec::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&stream.block)?;
+            stream.block.clear();
+            encoder.finish()?
+        }
+    };
+    put_long(&mut stream.sealed, stream.block_count);
+    put_bytes(&mut stream.sealed, &data);
+    stream.sealed.extend_from_slice(&stream.sync);
+    stream.block_count = 0;
+    Ok(())
+}
+
+fn header(schema: &str, codec: AvroCodec, sync: &[u8; 16]) -> Vec<u8> {
+    let mut buf = MAGIC.to_vec();
+    put_long(&mut buf, 2);
+    put_str(&mut buf, "avro.schema");
+    put_str(&mut buf, schema);
+    put_str(&mut buf, "avro.codec");
+    put_str(&mut buf, codec.name());
+    put_long(&mut buf, 0);
+    buf.extend_from_slice(sync);
+    buf
+}
+
+fn finish_file(
+    dir: &Path,
+    key: &StreamKey,
+    stream: &mut BlockBuffer,
+    codec: AvroCodec,
+    schema: &str,
+) -> io::Result<Option<WrittenFile>> {
+    seal_block(stream, codec)?;
+    if stream.sealed.is_empty() {
+        return Ok(None);
+    }
+    let path = dir.join(format!(
+        "{}_{}_{}_{}_{}.avro",
+        key.account_id,
+        key.source,
+        key.region,
+        Utc::now().format("%Y%m%dT%H%MZ"),
+        unique_id()
+    ));
+    let mut file = File::create(&path)?;
+    file.write_all(&header(schema, codec, &stream.sync))?;
+    file.write_all(&stream.sealed)?;
+    file.flush()?;
+
+    stream.sealed.clear();
+    stream.first_event_at = None;
+    Ok(Some(WrittenFile {
+        path,
+        source: key.source.clone(),
+        stats: stream.stats.rotate(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    struct Reader<'a>(&'a [u8]);
+
+    impl Reader<'_> {
+        fn long(&mut self) -> i64 {
+            let mut value = 0_u64;
+            let mut shift = 0;
+            loop {
+                let byte = self.0[0];
+                self.0 = &self.0[1..];
+                value |= u64::from(byte & 0x7f) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+            }
+            (value >> 1) as i64 ^ -((value & 1) as i64)
+        }
+
+        fn bytes(&mut self) -> &[u8] {
+            let len = self.long() as usize;
+            let (value, rest) = self.0.split_at(len);
+            self.0 = rest;
+            value
+        }
+
+        fn string(&mut self) -> String {
+            String::from_utf8(self.bytes().to_vec()).unwrap()
+        }
+    }
+
+    #[test]
+    fn writes_container_with_embedded_schema() {
+        let dir = std::env::temp_dir().join(format!("seclog-avro-{}", unique_id()));
+        let options = AvroOptions {
+            compression: Some("deflate".to_string()),
+            ..AvroOptions::default()
+        };
+        let event = test_event(
+            "cloudtrail",
+            "GetObject",
+            json!({
+                "eventTime": "2026-01-01T00:00:01Z",
+                "eventName": "GetObject",
+                "awsRegion": "us-east-1",
+                "readOnly": true,
+            }),
+        )
+        .at("2026-01-01T00:00:01Z")
+        .by("alice", "human")
+        .with_ip("203.0.113.7")
+        .in_account("123456789012");
+        let mut writer = AvroWriter::new(&dir, 1, None, &options).unwrap();
+        writer.write_event(&event).unwrap();
+        writer.write_event(&event).unwrap();
+        writer.close().unwrap();
+        let files = writer.take_written_files();
+        assert_eq!(files.len(), 1);
+        let data = fs::read(&files[0].path).unwrap();
+
+        assert_eq!(&data[..4], MAGIC);
+        let mut reader = Reader(&data[4..]);
+        assert_eq!(reader.long(), 2);
+        let mut meta = HashMap::new();
+        for _ in 0..2 {
+            let key = reader.string();
+            meta.insert(key, reader.string());
+        }
+        assert_eq!(reader.long(), 0);
+        assert_eq!(meta["avro.codec"], "deflate");
+        let schema: Value = serde_json::from_str(&meta["avro.schema"]).unwrap();
+        assert_eq!(schema, event_schema(false));
+        let sync = reader.0[..16].to_vec();
+        reader.0 = &reader.0[16..];
+
+        assert_eq!(reader.long(), 2);
+        let mut block = Vec::new();
+        DeflateDecoder::new(reader.bytes())
+            .read_to_end(&mut block)
+            .unwrap();
+        assert_eq!(reader.0, &sync[..]);
+
+        let mut record = Vec::new();
+        encode_event(&mut record, &mut Vec::new(), &event, false).unwrap();
+        assert_eq!(block, [record.clone(), record.clone()].concat());
+        let mut fields = Reader(&record);
+        assert_eq!(fields.string(), "v1");
+        assert_eq!(fields.long(), 1);
+        assert_eq!(fields.long(), 1_767_225_601_000_000);
+        assert_eq!(fields.string(), "cloudtrail");
+
+        assert!(AvroWriter::new(
+            &dir,
+            1,
+            None,
+            &AvroOptions {
+                compression: Some("gzip".to_string()),
+                ..AvroOptions::default()
+            },
+        )
+        .is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,125 @@
+//! Azure Monitor diagnostic export framing.
+//!
+//! Maps seclog events to the record schema used by diagnostic settings exports
+//! to Event Hubs and storage (`{"records": [...]}`), so collectors built for
+//! identity provider exports parse generated files unchanged.
+
+use crate::core::config::AzureMonitorOptions;
+use crate::core::event::{Event, Outcome};
+use serde_json::{json, Map, Value};
+
+/// Builds one Azure Monitor export record from an event.
+pub fn azure_monitor_record(event: &Event, options: &AzureMonitorOptions) -> Value {
+    let envelope = &event.envelope;
+    let tenant_id = envelope
+        .tenant_id
+        .clone()
+        .unwrap_or_else(|| "00000000-0000-0000-0000-000000000000".to_string());
+    let resource_id = options
+        .resource_id
+        .clone()
+        .unwrap_or_else(|| format!("/tenants/{tenant_id}/providers/Microsoft.aadiam"));
+    let category = options
+        .category
+        .clone()
+        .unwrap_or_else(|| default_category(&envelope.source).to_string());
+    let properties = if event.payload.is_null() {
+        serde_json::to_value(event).unwrap_or(Value::Null)
+    } else {
+        event.payload.clone()
+    };
+
+    let mut record = Map::new();
+    record.insert("time".to_string(), json!(envelope.timestamp));
+    record.insert("resourceId".to_string(), json!(resource_id));
+    record.insert("operationName".to_string(), json!(envelope.event_type));
+    record.insert("operationVersion".to_string(), json!("1.0"));
+    record.insert("category".to_string(), json!(category));
+    record.insert("tenantId".to_string(), json!(tenant_id));
+    record.insert(
+        "resultType".to_string(),
+        json!(result_type(&envelope.outcome)),
+    );
+    if let Some(ip) = &envelope.ip {
+        record.insert("callerIpAddress".to_string(), json!(ip));
+    }
+    if let Some(session_id) = &envelope.session_id {
+        record.insert("correlationId".to_string(), json!(session_id));
+    }
+    record.insert(
+        "identity".to_string(),
+        json!(envelope.actor.name.as_deref().unwrap_or(&envelope.actor.id)),
+    );
+    record.insert("Level".to_string(), json!(4));
+    if let Some(geo) = &envelope.geo {
+        record.insert("location".to_string(), json!(geo.country));
+    }
+    record.insert("properties".to_string(), properties);
+    Value::Object(record)
+}
+
+/// Entra ID events keep their diagnostic categories. Other identity providers
+/// do not follow the Entra schemas, so their events go under their own source
+/// name rather than being misread as Entra sign-ins or audits.
+fn default_category(source: &str) -> &str {
+    match source {
+        "entra_signin" => "SignInLogs",
+        "entra_audit" => "AuditLogs",
+        other => other,
+    }
+}
+
+fn result_type(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Success => "Success",
+        Outcome::Failure => "Failure",
+        Outcome::Unknown => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::Actor;
+    use crate::fixtures::test_event;
+
+    #[test]
+    fn maps_envelope_into_export_record() {
+        let options = AzureMonitorOptions {
+            compression: None,
+            resource_id: None,
+            category: None,
+        };
+        let mut event = test_event(
+            "okta_system_log",
+            "user.session.start",
+            json!({ "eventType": "user.session.start" }),
+        )
+        .at("2025-01-01T08:00:00.000Z")
+        .failed()
+        .with_ip("203.0.113.7")
+        .in_account("example.okta.com");
+        event.envelope.actor = Actor {
+            id: "00u1".to_string(),
+            kind: "User".to_string(),
+            name: Some("alice@example.com".to_string()),
+        };
+        let record = azure_monitor_record(&event, &options);
+        assert_eq!(record["time"], "2025-01-01T08:00:00.000Z");
+        assert_eq!(record["operationName"], "user.session.start");
+        assert_eq!(record["category"], "okta_system_log");
+        assert_eq!(
+            record["resourceId"],
+            "/tenants/example.okta.com/providers/Microsoft.aadiam"
+        );
+        assert_eq!(record["resultType"], "Failure");
+        assert_eq!(record["callerIpAddress"], "203.0.113.7");
+        assert_eq!(record["identity"], "alice@example.com");
+        assert_eq!(record["properties"]["eventType"], "user.session.start");
+
+        let mut sign_in = event;
+        sign_in.envelope.source = "entra_signin".to_string();
+        let sign_in = azure_monitor_record(&sign_in, &options);
+        assert_eq!(sign_in["category"], "SignInLogs");
+    }
+}
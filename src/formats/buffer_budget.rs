@@ -0,0 +1,142 @@
+//! Memory budget for buffered file output.
+//!
+//! JSONL and Parquet writers buffer each source/account/region until it
+//! reaches the target file size, so many accounts × regions can hold a lot of
+//! memory at once. A [`BufferBudget`] shared by the writers of one output caps
+//! the total: once it is exceeded, the writer that just buffered an event
+//! flushes its oldest regions first until the total is back under the limit.
+//! Writers can also report to a gauge owned by their pipeline, which sums
+//! the buffered bytes of every output for its metrics.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Limit on bytes buffered across the writers that share it.
+#[derive(Debug)]
+pub struct BufferBudget {
+    limit: u64,
+    buffered: AtomicU64,
+}
+
+impl BufferBudget {
+    pub fn new(limit_bytes: u64) -> Arc<Self> {
+        Arc::new(Self {
+            limit: limit_bytes,
+            buffered: AtomicU64::new(0),
+        })
+    }
+
+    /// Bytes buffered by the writers sharing this budget.
+    pub fn buffered(&self) -> u64 {
+        self.buffered.load(Ordering::Relaxed)
+    }
+
+    fn is_exceeded(&self) -> bool {
+        self.buffered() > self.limit
+    }
+}
+
+/// One writer's buffered bytes, kept in step with its budget and gauge.
+#[derive(Debug, Default)]
+pub(crate) struct BufferAccount {
+    budget: Option<Arc<BufferBudget>>,
+    gauge: Option<Arc<AtomicU64>>,
+    bytes: u64,
+}
+
+impl BufferAccount {
+    pub(crate) fn set_budget(&mut self, budget: Arc<BufferBudget>) {
+        if let Some(old) = self.budget.replace(budget.clone()) {
+            old.buffered.fetch_sub(self.bytes, Ordering::Relaxed);
+        }
+        budget.buffered.fetch_add(self.bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_gauge(&mut self, gauge: Arc<AtomicU64>) {
+        if let Some(old) = self.gauge.replace(gauge.clone()) {
+            old.fetch_sub(self.bytes, Ordering::Relaxed);
+        }
+        gauge.fetch_add(self.bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add(&mut self, bytes: u64) {
+        self.bytes += bytes;
+        if let Some(gauge) = &self.gauge {
+            gauge.fetch_add(bytes, Ordering::Relaxed);
+        }
+        if let Some(budget) = &self.budget {
+            budget.buffered.fetch_add(bytes, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn release(&mut self, bytes: u64) {
+        let bytes = bytes.min(self.bytes);
+        self.bytes -= bytes;
+        if let Some(gauge) = &self.gauge {
+            gauge.fetch_sub(bytes, Ordering::Relaxed);
+        }
+        if let Some(budget) = &self.budget {
+            budget.buffered.fetch_sub(bytes, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns whether the shared budget is exceeded while this writer still
+    /// holds buffered bytes it could flush.
+    pub(crate) fn over_budget(&self) -> bool {
+        self.bytes > 0
+            && self
+                .budget
+                .as_ref()
+                .is_some_and(|budget| budget.is_exceeded())
+    }
+}
+
+impl Drop for BufferAccount {
+    fn drop(&mut self) {
+        self.release(self.bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::traits::EventWriter;
+    use crate::fixtures::test_event;
+    use crate::formats::json::JsonlWriter;
+    use serde_json::json;
+
+    #[test]
+    fn budget_flushes_oldest_region_first() {
+        let dir = std::env::temp_dir().join(format!("seclog-budget-{}", std::process::id()));
+        let budget = BufferBudget::new(2_000);
+        let gauge = Arc::new(AtomicU64::new(0));
+        let mut writer = JsonlWriter::new(&dir, 50, None, None)
+            .unwrap()
+            .with_buffer_budget(budget.clone())
+            .with_buffer_gauge(gauge.clone());
+
+        for region in ["us-east-1", "eu-west-1", "ap-south-1"] {
+            let event = test_event(
+                "cloudtrail",
+                "GetObject",
+                json!({ "eventName": "GetObject", "awsRegion": region, "padding": "x".repeat(400) }),
+            );
+            for _ in 0..2 {
+                writer.write_event(&event).unwrap();
+                assert!(budget.buffered() <= 2_000);
+                assert_eq!(gauge.load(Ordering::Relaxed), budget.buffered());
+            }
+        }
+        let flushed = writer.take_written_files();
+        assert!(!flushed.is_empty());
+        assert!(flushed[0].path.to_string_lossy().contains("us-east-1"));
+        assert!(flushed
+            .iter()
+            .all(|file| !file.path.to_string_lossy().contains("ap-south-1")));
+
+        writer.close().unwrap();
+        assert_eq!(budget.buffered(), 0);
+        assert_eq!(gauge.load(Ordering::Relaxed), 0);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,413 @@
+//! CloudTrail log file integrity digests.
+//!
+//! Emulates the digest files CloudTrail delivers next to log files: per
+//! account/region, each digest lists the log files delivered since the previous
+//! one with the SHA-256 of their uncompressed content, and links back to the
+//! previous digest by object key, hash, and signature. Signatures are
+//! placeholders derived from a dummy key, written to a `.metadata` sidecar the
+//! way S3 carries them as `x-amz-meta-signature`; the chain and hashes are real.
+
+use crate::core::config::CloudTrailDigestOptions;
+use crate::core::traits::{FileStats, WrittenFile};
+use crate::core::util::hex;
+use chrono::{DateTime, Duration, Timelike, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Source label reported for digest files in manifests.
+pub const DIGEST_SOURCE: &str = "CloudTrailDigest";
+const SIGNATURE_ALGORITHM: &str = "SHA256withRSA";
+const HASH_ALGORITHM: &str = "SHA-256";
+
+/// CloudTrail digest file body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudTrailDigest {
+    pub aws_account_id: String,
+    pub digest_start_time: String,
+    pub digest_end_time: String,
+    pub digest_s3_bucket: String,
+    pub digest_s3_object: String,
+    pub digest_public_key_fingerprint: String,
+    pub digest_signature_algorithm: String,
+    pub newest_event_time: Option<String>,
+    pub oldest_event_time: Option<String>,
+    pub previous_digest_s3_bucket: Option<String>,
+    pub previous_digest_s3_object: Option<String>,
+    pub previous_digest_hash_value: Option<String>,
+    pub previous_digest_hash_algorithm: Option<String>,
+    pub previous_digest_signature: Option<String>,
+    pub log_files: Vec<DigestLogFile>,
+}
+
+/// One delivered log file referenced by a digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestLogFile {
+    pub s3_bucket: String,
+    pub s3_object: String,
+    /// Hex SHA-256 of the uncompressed log file.
+    pub hash_value: String,
+    pub hash_algorithm: String,
+    pub newest_event_time: Option<String>,
+    pub oldest_event_time: Option<String>,
+}
+
+/// Tracks one digest chain per account/region and writes digests as they come due.
+pub(crate) struct CloudTrailDigester {
+    dir: PathBuf,
+    options: CloudTrailDigestOptions,
+    /// Writes digests under `AWSLogs/<account>/CloudTrail-Digest/<region>/YYYY/MM/DD/`.
+    s3_layout: bool,
+    chains: BTreeMap<(String, String), DigestChain>,
+}
+
+struct DigestChain {
+    period_start: DateTime<Utc>,
+    log_files: Vec<DigestLogFile>,
+    previous: Option<PreviousDigest>,
+}
+
+struct PreviousDigest {
+    object: String,
+    end_time: DateTime<Utc>,
+    hash: String,
+    signature: String,
+}
+
+impl CloudTrailDigester {
+    pub(crate) fn new(dir: PathBuf, options: CloudTrailDigestOptions) -> Self {
+        Self {
+            dir,
+            options,
+            s3_layout: false,
+            chains: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn set_s3_layout(&mut self, s3_layout: bool) {
+        self.s3_layout = s3_layout;
+    }
+
+    /// Adds a delivered CloudTrail log file to its account/region chain.
+    pub(crate) fn record(
+        &mut self,
+        account_id: &str,
+        region: &str,
+        file: &WrittenFile,
+        hash: String,
+    ) {
+        let Some(name) = relative_key(&self.dir, &file.path) else {
+            return;
+        };
+        let log_file = DigestLogFile {
+            s3_bucket: self.options.s3_bucket.clone(),
+            s3_object: object_key(self.options.s3_key_prefix.as_deref(), &name),
+            hash_value: hash,
+            hash_algorithm: HASH_ALGORITHM.to_string(),
+            newest_event_time: file.stats.last_event_time.map(format_time),
+            oldest_event_time: file.stats.first_event_time.map(format_time),
+        };
+        self.chains
+            .entry((account_id.to_string(), region.to_string()))
+            .or_insert_with(|| DigestChain {
+                period_start: Utc::now(),
+                log_files: Vec::new(),
+                previous: None,
+            })
+            .log_files
+            .push(log_file);
+    }
+
+    /// Writes a digest for every chain whose interval has elapsed; `force`
+    /// also delivers chains with pending log files (on close).
+    pub(crate) fn deliver(&mut self, force: bool) -> io::Result<Vec<WrittenFile>> {
+        let now = Utc::now();
+        let interval = Duration::seconds(self.options.interval_seconds.max(1) as i64);
+        let mut written = Vec::new();
+        let keys = self.chains.keys().cloned().collect::<Vec<_>>();
+        for key in keys {
+            let chain = &self.chains[&key];
+            let due = now - chain.period_start >= interval;
+            if !due && (!force || chain.log_files.is_empty()) {
+                continue;
+            }
+            written.push(self.write_digest(&key, now)?);
+        }
+        Ok(written)
+    }
+
+    fn write_digest(
+        &mut self,
+        key: &(String, String),
+        now: DateTime<Utc>,
+    ) -> io::Result<WrittenFile> {
+        let (account_id, region) = key;
+        let chain = self.chains.get_mut(key).expect("digest chain exists");
+        // Digest names have second precision; keep end times strictly increasing.
+        let now = now.with_nanosecond(0).unwrap_or(now);
+        let end_time = match &chain.previous {
+            Some(previous) => now.max(previous.end_time + Duration::seconds(1)),
+            None => now,
+        };
+        let name = format!(
+            "{account_id}_CloudTrail-Digest_{region}_{}_{}_{}.json.gz",
+            self.options.trail_name,
+            self.options.home_region,
+            end_time.format("%Y%m%dT%H%M%SZ")
+        );
+        let name = if self.s3_layout {
+            let dir = format!(
+                "AWSLogs/{account_id}/CloudTrail-Digest/{region}/{}",
+                end_time.format("%Y/%m/%d")
+            );
+            fs::create_dir_all(self.dir.join(&dir))?;
+            format!("{dir}/{name}")
+        } else {
+            name
+        };
+        let object = object_key(self.options.s3_key_prefix.as_deref(), &name);
+        let log_files = std::mem::take(&mut chain.log_files);
+        let digest = CloudTrailDigest {
+            aws_account_id: account_id.clone(),
+            digest_start_time: format_time(chain.period_start),
+            digest_end_time: format_time(end_time),
+            digest_s3_bucket: self.options.s3_bucket.clone(),
+            digest_s3_object: object.clone(),
+            digest_public_key_fingerprint: public_key_fingerprint(&self.options.signing_key),
+            digest_signature_algorithm: SIGNATURE_ALGORITHM.to_string(),
+            newest_event_time: log_files
+                .iter()
+                .filter_map(|file| file.newest_event_time.clone())
+                .max(),
+            oldest_event_time: log_files
+                .iter()
+                .filter_map(|file| file.oldest_event_time.clone())
+                .min(),
+            previous_digest_s3_bucket: chain
+                .previous
+                .as_ref()
+                .map(|_| self.options.s3_bucket.clone()),
+            previous_digest_s3_object: chain.previous.as_ref().map(|prev| prev.object.clone()),
+            previous_digest_hash_value: chain.previous.as_ref().map(|prev| prev.hash.clone()),
+            previous_digest_hash_algorithm: chain
+                .previous
+                .as_ref()
+                .map(|_| HASH_ALGORITHM.to_string()),
+            previous_digest_signature: chain.previous.as_ref().map(|prev| prev.signature.clone()),
+            log_files,
+        };
+
+        let body = serde_json::to_vec(&digest).map_err(io::Error::other)?;
+        let hash = hex(&Sha256::digest(&body));
+        // CloudTrail's data signing string; the first digest signs the literal "null".
+        let signing_string = format!(
+            "{}\n{}/{}\n{}\n{}",
+            digest.digest_end_time,
+            digest.digest_s3_bucket,
+            digest.digest_s3_object,
+            hash,
+            digest
+                .previous_digest_signature
+                .as_deref()
+                .unwrap_or("null")
+        );
+        let signature = sign(&self.options.signing_key, &signing_string);
+
+        let path = self.dir.join(&name);
+        let mut encoder = GzEncoder::new(File::create(&path)?, Compression::default());
+        encoder.write_all(&body)?;
+        encoder.finish()?;
+        let metadata = serde_json::json!({
+            "signature": signature,
+            "signature-algorithm": SIGNATURE_ALGORITHM,
+        });
+        fs::write(
+            self.dir.join(format!("{name}.metadata")),
+            serde_json::to_vec(&metadata).map_err(io::Error::other)?,
+        )?;
+
+        chain.period_start = end_time;
+        chain.previous = Some(PreviousDigest {
+            object,
+            end_time,
+            hash,
+            signature,
+        });
+        Ok(WrittenFile {
+            path,
+            source: DIGEST_SOURCE.to_string(),
+            stats: FileStats::default(),
+        })
+    }
+}
+
+/// `path` relative to `dir` with `/` separators, as it appears in an S3 key.
+fn relative_key(dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?;
+    let parts = relative
+        .components()
+        .map(|part| part.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+fn object_key(prefix: Option<&str>, name: &str) -> String {
+    format!("{}{name}", prefix.unwrap_or_default())
+}
+
+/// Returns true for digest files and their signature sidecars.
+pub fn is_digest_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.contains("_CloudTrail-Digest_"))
+}
+
+/// Placeholder signature: hex SHA-256 over the dummy key and signing string.
+fn sign(key: &str, signing_string: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(signing_string.as_bytes());
+    hex(&hasher.finalize())
+}
+
+/// 32 hex characters, the length of CloudTrail's MD5 key fingerprints.
+fn public_key_fingerprint(key: &str) -> String {
+    hex(&Sha256::digest(key.as_bytes())[..16])
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::traits::EventWriter;
+    use crate::fixtures::cloudtrail_test_event;
+    use crate::formats::json::{unique_id, JsonlWriter};
+    use flate2::read::GzDecoder;
+    use serde_json::json;
+    use std::io::Read;
+
+    fn gunzip(path: &Path) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        GzDecoder::new(File::open(path).expect("open"))
+            .read_to_end(&mut bytes)
+            .expect("gunzip");
+        bytes
+    }
+
+    #[test]
+    fn digests_hash_log_files_and_chain_to_previous_digest() {
+        let dir = std::env::temp_dir().join(format!("seclog-digest-{}", unique_id()));
+        let options: CloudTrailDigestOptions =
+            serde_json::from_value(json!({ "s3_key_prefix": "AWSLogs/" })).expect("options");
+        let mut writer = JsonlWriter::new(&dir, 64, None, Some("gzip"))
+            .expect("writer")
+            .with_cloudtrail_digest(options);
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:05Z"))
+            .expect("write");
+        writer.close().expect("close");
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:10:00Z"))
+            .expect("write");
+        writer.close().expect("close");
+
+        let mut digests = fs::read_dir(&dir)
+            .expect("dir")
+            .map(|entry| entry.expect("entry").path())
+            .filter(|path| is_digest_file(path) && path.extension().is_some_and(|ext| ext == "gz"))
+            .collect::<Vec<_>>();
+        digests.sort();
+        assert_eq!(digests.len(), 2);
+        let first_body = gunzip(&digests[0]);
+        let first: CloudTrailDigest = serde_json::from_slice(&first_body).expect("first");
+        let second: CloudTrailDigest =
+            serde_json::from_slice(&gunzip(&digests[1])).expect("second");
+
+        assert_eq!(first.previous_digest_hash_value, None);
+        assert_eq!(
+            first.oldest_event_time.as_deref(),
+            Some("2026-01-01T00:00:05Z")
+        );
+        let log_file = &first.log_files[0];
+        let log_path = dir.join(log_file.s3_object.trim_start_matches("AWSLogs/"));
+        assert_eq!(log_file.hash_value, hex(&Sha256::digest(gunzip(&log_path))));
+
+        assert_eq!(
+            second.previous_digest_s3_object.as_deref(),
+            Some(first.digest_s3_object.as_str())
+        );
+        assert_eq!(
+            second.previous_digest_hash_value,
+            Some(hex(&Sha256::digest(&first_body)))
+        );
+        let metadata: serde_json::Value = serde_json::from_slice(
+            &fs::read(digests[0].with_extension("gz.metadata")).expect("metadata"),
+        )
+        .expect("parse");
+        assert_eq!(
+            second.previous_digest_signature.as_deref(),
+            metadata["signature"].as_str()
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn s3_layout_dates_log_files_by_event_time() {
+        let dir = std::env::temp_dir().join(format!("seclog-s3-layout-{}", unique_id()));
+        let options: CloudTrailDigestOptions = serde_json::from_value(json!({})).expect("options");
+        let mut writer = JsonlWriter::new(&dir, 64, None, Some("gzip"))
+            .expect("writer")
+            .with_s3_layout()
+            .with_cloudtrail_digest(options);
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T23:59:30Z"))
+            .expect("write");
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-02T00:00:10Z"))
+            .expect("write");
+        writer.close().expect("close");
+
+        let written = writer.take_written_files();
+        let mut logs = written
+            .iter()
+            .filter(|file| file.source == "CloudTrail")
+            .map(|file| relative_key(&dir, &file.path).expect("relative"))
+            .collect::<Vec<_>>();
+        logs.sort();
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].starts_with(
+            "AWSLogs/123456789012/CloudTrail/us-east-1/2026/01/01/123456789012_CloudTrail_us-east-1_20260101T2359Z_"
+        ));
+        assert!(logs[1].starts_with("AWSLogs/123456789012/CloudTrail/us-east-1/2026/01/02/"));
+
+        let digest = written
+            .iter()
+            .find(|file| file.source == DIGEST_SOURCE)
+            .expect("digest");
+        assert!(relative_key(&dir, &digest.path)
+            .expect("relative")
+            .starts_with("AWSLogs/123456789012/CloudTrail-Digest/us-east-1/"));
+        let body: CloudTrailDigest = serde_json::from_slice(&gunzip(&digest.path)).expect("digest");
+        let mut objects = body
+            .log_files
+            .iter()
+            .map(|file| file.s3_object.clone())
+            .collect::<Vec<_>>();
+        objects.sort();
+        assert_eq!(objects, logs);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,368 @@
+//! CloudWatch Logs subscription sink delivered through Kinesis Firehose.
+//!
+//! Groups source-native records into `DATA_MESSAGE` payloads, gzips and base64
+//! encodes each message, and writes Firehose HTTP endpoint request bodies per
+//! source/account/region.
+
+use super::json::{
+    current_stamp, file_context_from_event, record_bytes_for_event, unique_id, FileContext,
+};
+use crate::core::config::CloudWatchLogsOptions;
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, FileStats, WrittenFile};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Firehose writer that emits CloudWatch Logs subscription framing.
+pub struct CloudWatchLogsWriter {
+    dir: PathBuf,
+    target_size_bytes: u64,
+    max_age: Option<Duration>,
+    options: CloudWatchLogsOptions,
+    files: HashMap<StreamKey, StreamBuffer>,
+    written: Vec<WrittenFile>,
+}
+
+impl CloudWatchLogsWriter {
+    /// Creates a CloudWatch Logs writer with size-based rotation and optional max age.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        options: CloudWatchLogsOptions,
+    ) -> io::Result<Self> {
+        if options.events_per_message == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cloudwatch_logs events_per_message must be greater than 0",
+            ));
+        }
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let max_age = max_age_seconds.and_then(|seconds| {
+            if seconds > 0 {
+                Some(Duration::from_secs(seconds))
+            } else {
+                None
+            }
+        });
+        Ok(Self {
+            dir,
+            target_size_bytes: target_size_mb.saturating_mul(1024 * 1024),
+            max_age,
+            options,
+            files: HashMap::new(),
+            written: Vec::new(),
+        })
+    }
+}
+
+impl EventWriter for CloudWatchLogsWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let record_bytes = record_bytes_for_event(event)?;
+        let size = record_bytes.len() as u64;
+        let message = String::from_utf8(record_bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let key = StreamKey::from(file_context_from_event(event));
+        let stream = self
+            .files
+            .entry(key.clone())
+            .or_insert_with(StreamBuffer::new);
+        if stream.is_empty() {
+            stream.first_event_at = Some(Instant::now());
+        }
+        stream.log_events.push(json!({
+            "id": random_event_id(),
+            "timestamp": event_timestamp_millis(&event.envelope.timestamp),
+            "message": message,
+        }));
+        stream.pending_size += size;
+        stream.stats.record(event);
+
+        if stream.log_events.len() >= self.options.events_per_message {
+            seal_message(&key, stream, &self.options)?;
+        }
+        if stream.current_size() >= self.target_size_bytes {
+            self.written
+                .extend(flush_stream(&self.dir, &key, stream, &self.options)?);
+        }
+
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        for (key, stream) in self.files.iter_mut() {
+            if stream.is_empty() {
+                continue;
+            }
+            if let Some(max_age) = self.max_age {
+                let start = match stream.first_event_at {
+                    Some(start) => start,
+                    None => {
+                        stream.first_event_at = Some(now);
+                        continue;
+                    }
+                };
+                if now.duration_since(start) < max_age {
+                    continue;
+                }
+            }
+            self.written
+                .extend(flush_stream(&self.dir, key, stream, &self.options)?);
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        for (key, stream) in self.files.iter_mut() {
+            if !stream.is_empty() {
+                self.written
+                    .extend(flush_stream(&self.dir, key, stream, &self.options)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.sync()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.written)
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct StreamKey {
+    source: String,
+    account_id: String,
+    region: String,
+}
+
+impl From<FileContext> for StreamKey {
+    fn from(context: FileContext) -> Self {
+        Self {
+            source: context.source,
+            account_id: context.account_id,
+            region: context.region,
+        }
+    }
+}
+
+struct StreamBuffer {
+    log_events: Vec<Value>,
+    pending_size: u64,
+    records: Vec<String>,
+    records_size: u64,
+    first_event_at: Option<Instant>,
+    stats: FileStats,
+}
+
+impl StreamBuffer {
+    fn new() -> Self {
+        Self {
+            log_events: Vec::new(),
+            pending_size: 0,
+            records: Vec::new(),
+            records_size: 0,
+            first_event_at: None,
+            stats: FileStats::default(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.log_events.is_empty() && self.records.is_empty()
+    }
+
+    fn current_size(&self) -> u64 {
+        self.records_size + self.pending_size
+    }
+}
+
+/// Builds the `DATA_MESSAGE` payload for one batch of log events.
+fn data_message(key: &StreamKey, options: &CloudWatchLogsOptions, log_events: Vec<Value>) -> Value {
+    let log_group = options
+        .log_group
+        .clone()
+        .unwrap_or_else(|| default_log_group(key));
+    let log_stream = options
+        .log_stream
+        .clone()
+        .unwrap_or_else(|| format!("{}_{}_{}", key.account_id, key.source, key.region));
+    json!({
+        "messageType": "DATA_MESSAGE",
+        "owner": key.account_id,
+        "logGroup": log_group,
+        "logStream": log_stream,
+        "subscriptionFilters": [options.subscription_filter],
+        "logEvents": log_events,
+    })
+}
+
+fn default_log_group(key: &StreamKey) -> String {
+    match key.source.as_str() {
+        "CloudTrail" => format!("aws-cloudtrail-logs-{}", key.account_id),
+        other => format!("/seclog/{other}"),
+    }
+}
+
+/// Gzips and base64-encodes the pending log events as one Firehose record.
+fn seal_message(
+    key: &StreamKey,
+    stream: &mut StreamBuffer,
+    options: &CloudWatchLogsOptions,
+) -> io::Result<()> {
+    if stream.log_events.is_empty() {
+        return Ok(());
+    }
+    let log_events = std::mem::take(&mut stream.log_events);
+    let message = data_message(key, options, log_events);
+    let bytes = serde_json::to_vec(&message).map_err(io::Error::other)?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&bytes)?;
+    let data = STANDARD.encode(encoder.finish()?);
+    stream.records_size += data.len() as u64;
+    stream.records.push(data);
+    stream.pending_size = 0;
+    Ok(())
+}
+
+fn flush_stream(
+    dir: &Path,
+    key: &StreamKey,
+    stream: &mut StreamBuffer,
+    options: &CloudWatchLogsOptions,
+) -> io::Result<Option<WrittenFile>> {
+    seal_message(key, stream, options)?;
+    if stream.records.is_empty() {
+        return Ok(None);
+    }
+
+    let records = std::mem::take(&mut stream.records)
+        .into_iter()
+        .map(|data| json!({ "data": data }))
+        .collect::<Vec<_>>();
+    let body = json!({
+        "requestId": uuid_like(),
+        "timestamp": Utc::now().timestamp_millis(),
+        "records": records,
+    });
+    let path = dir.join(format!(
+        "{}_{}_{}_{}_{}.json",
+        key.account_id,
+        key.source,
+        key.region,
+        current_stamp(),
+        unique_id()
+    ));
+    let mut file = File::create(&path)?;
+    serde_json::to_writer(&mut file, &body).map_err(io::Error::other)?;
+    file.flush()?;
+
+    stream.records_size = 0;
+    stream.first_event_at = None;
+    Ok(Some(WrittenFile {
+        path,
+        source: key.source.clone(),
+        stats: stream.stats.rotate(),
+    }))
+}
+
+fn event_timestamp_millis(timestamp: &str) -> i64 {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|parsed| parsed.with_timezone(&Utc).timestamp_millis())
+        .unwrap_or_else(|_| Utc::now().timestamp_millis())
+}
+
+/// CloudWatch Logs event IDs are 56-digit decimal strings.
+fn random_event_id() -> String {
+    let mut rng = rand::thread_rng();
+    (0..56)
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect()
+}
+
+fn uuid_like() -> String {
+    let mut rng = rand::thread_rng();
+    let hex: String = (0..32)
+        .map(|_| std::char::from_digit(rng.gen_range(0..16), 16).unwrap())
+        .collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::cloudtrail_test_event;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn writes_firehose_records_with_data_messages() {
+        let dir = std::env::temp_dir().join(format!("seclog-cwl-{}", unique_id()));
+        let options = CloudWatchLogsOptions {
+            log_group: None,
+            log_stream: None,
+            subscription_filter: "seclog".to_string(),
+            events_per_message: 2,
+        };
+        let mut writer = CloudWatchLogsWriter::new(&dir, 50, None, options).expect("writer");
+        for name in ["GetObject", "PutObject", "ListBuckets"] {
+            let event = cloudtrail_test_event(name, "2025-01-01T00:00:01.500Z");
+            writer.write_event(&event).expect("write");
+        }
+        writer.close().expect("close");
+
+        let entries = fs::read_dir(&dir)
+            .expect("dir")
+            .map(|entry| entry.expect("entry").path())
+            .collect::<Vec<_>>();
+        assert_eq!(entries.len(), 1);
+        let body: Value =
+            serde_json::from_slice(&fs::read(&entries[0]).expect("read")).expect("json");
+        let records = body["records"].as_array().expect("records");
+        assert_eq!(records.len(), 2);
+
+        let compressed = STANDARD
+            .decode(records[0]["data"].as_str().expect("data"))
+            .expect("base64");
+        let mut decoded = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decoded)
+            .expect("gzip");
+        let message: Value = serde_json::from_str(&decoded).expect("message");
+        assert_eq!(message["messageType"], "DATA_MESSAGE");
+        assert_eq!(message["owner"], "123456789012");
+        assert_eq!(message["logGroup"], "aws-cloudtrail-logs-123456789012");
+        assert_eq!(message["subscriptionFilters"][0], "seclog");
+        let log_events = message["logEvents"].as_array().expect("log events");
+        assert_eq!(log_events.len(), 2);
+        assert_eq!(log_events[0]["timestamp"], 1_735_689_601_500_i64);
+        assert_eq!(log_events[0]["id"].as_str().expect("id").len(), 56);
+        let inner: Value =
+            serde_json::from_str(log_events[0]["message"].as_str().expect("message"))
+                .expect("inner");
+        assert_eq!(inner["eventName"], "GetObject");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
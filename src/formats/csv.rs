@@ -0,0 +1,380 @@
+//! CSV sink.
+//!
+//! Writes one file per source/account/region, each starting with a header
+//! row. Every row carries the flattened envelope (`actor.id` becomes
+//! `actor_id`, and so on) followed by the payload columns configured under
+//! `output.format.columns`. Objects and arrays are written as compact JSON,
+//! missing values as empty cells. Files rotate on the same size and age
+//! limits as JSONL.
+
+use super::json::{
+    file_context_from_event, parse_compression, unique_id, FileBody, JsonlCompression,
+};
+use crate::core::config::CsvOptions;
+use crate::core::event::{Event, Outcome};
+use crate::core::traits::{EventWriter, FileStats, WrittenFile};
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Envelope columns, in output order.
+pub const ENVELOPE_COLUMNS: [&str; 21] = [
+    "schema_version",
+    "timestamp",
+    "source",
+    "event_type",
+    "actor_id",
+    "actor_kind",
+    "actor_name",
+    "target_id",
+    "target_kind",
+    "target_name",
+    "outcome",
+    "geo_country",
+    "geo_region",
+    "geo_city",
+    "geo_lat",
+    "geo_lon",
+    "ip",
+    "user_agent",
+    "session_id",
+    "tenant_id",
+    "tenant",
+];
+
+/// CSV writer that buffers rows per source/account/region.
+pub struct CsvWriter {
+    dir: PathBuf,
+    target_size_bytes: u64,
+    max_age: Option<Duration>,
+    compression: JsonlCompression,
+    header: String,
+    columns: Vec<Vec<String>>,
+    files: HashMap<StreamKey, RowBuffer>,
+    written: Vec<WrittenFile>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct StreamKey {
+    source: String,
+    account_id: String,
+    region: String,
+}
+
+struct RowBuffer {
+    body: FileBody,
+    first_event_at: Option<Instant>,
+    stats: FileStats,
+}
+
+impl CsvWriter {
+    /// Creates a CSV writer with size-based rotation and optional max age.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        options: &CsvOptions,
+    ) -> io::Result<Self> {
+        let mut header = ENVELOPE_COLUMNS.map(str::to_string).to_vec();
+        let mut columns = Vec::with_capacity(options.columns.len());
+        for column in &options.columns {
+            let path = column
+                .path
+                .strip_prefix("payload.")
+                .filter(|rest| !rest.split('.').any(str::is_empty))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "csv column {} path must be a field under payload., got {}",
+                            column.name, column.path
+                        ),
+                    )
+                })?;
+            if header.contains(&column.name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("duplicate csv column name: {}", column.name),
+                ));
+            }
+            header.push(column.name.clone());
+            columns.push(path.split('.').map(str::to_string).collect());
+        }
+
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let max_age = max_age_seconds
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs);
+        Ok(Self {
+            dir,
+            target_size_bytes: target_size_mb.saturating_mul(1024 * 1024),
+            max_age,
+            compression: parse_compression(options.compression.as_deref())?,
+            header: csv_row(header.iter().map(|name| name.as_str())),
+            columns,
+            files: HashMap::new(),
+            written: Vec::new(),
+        })
+    }
+
+    fn row(&self, event: &Event) -> String {
+        let envelope = &event.envelope;
+        let target = envelope.target.as_ref();
+        let geo = envelope.geo.as_ref();
+        let float = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+        let mut cells = vec![
+            envelope.schema_version.clone(),
+            envelope.timestamp.clone(),
+            envelope.source.clone(),
+            envelope.event_type.clone(),
+            envelope.actor.id.clone(),
+            envelope.actor.kind.clone(),
+            envelope.actor.name.clone().unwrap_or_default(),
+            target.map(|target| target.id.clone()).unwrap_or_default(),
+            target.map(|target| target.kind.clone()).unwrap_or_default(),
+            target
+                .and_then(|target| target.name.clone())
+                .unwrap_or_default(),
+            outcome_to_str(&envelope.outcome).to_string(),
+            geo.map(|geo| geo.country.clone()).unwrap_or_default(),
+            geo.and_then(|geo| geo.region.clone()).unwrap_or_default(),
+            geo.and_then(|geo| geo.city.clone()).unwrap_or_default(),
+            float(geo.and_then(|geo| geo.lat)),
+            float(geo.and_then(|geo| geo.lon)),
+            envelope.ip.clone().unwrap_or_default(),
+            envelope.user_agent.clone().unwrap_or_default(),
+            envelope.session_id.clone().unwrap_or_default(),
+            envelope.tenant_id.clone().unwrap_or_default(),
+            envelope.tenant.clone().unwrap_or_default(),
+        ];
+        cells.extend(
+            self.columns
+                .iter()
+                .map(|path| payload_cell(&event.payload, path)),
+        );
+        csv_row(cells.iter().map(String::as_str))
+    }
+}
+
+impl EventWriter for CsvWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let context = file_context_from_event(event);
+        let key = StreamKey {
+            source: context.source,
+            account_id: context.account_id,
+            region: context.region,
+        };
+        let row = self.row(event);
+        let size = row.len() as u64;
+
+        let compression = self.compression;
+        let stream = self.files.entry(key.clone()).or_insert_with(|| RowBuffer {
+            body: FileBody::new(compression),
+            first_event_at: None,
+            stats: FileStats::default(),
+        });
+        if stream.stats.events == 0 {
+            stream.first_event_at = Some(Instant::now());
+            stream.body.write(self.header.as_bytes())?;
+        }
+        stream.body.write(row.as_bytes())?;
+        stream.stats.record(event);
+
+        if stream.body.len() >= self.target_size_bytes {
+            self.written
+                .extend(flush_stream(&self.dir, &key, stream, self.compression)?);
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        for (key, stream) in self.files.iter_mut() {
+            if stream.stats.events == 0 {
+                continue;
+            }
+            if let (Some(max_age), Some(start)) = (self.max_age, stream.first_event_at) {
+                if now.duration_since(start) < max_age {
+                    continue;
+                }
+            }
+            self.written
+                .extend(flush_stream(&self.dir, key, stream, self.compression)?);
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        for (key, stream) in self.files.iter_mut() {
+            self.written
+                .extend(flush_stream(&self.dir, key, stream, self.compression)?);
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.sync()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.written)
+    }
+}
+
+/// Joins cells into one CRLF-terminated row, quoting per RFC 4180.
+fn csv_row<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let mut row = String::new();
+    for (idx, cell) in cells.enumerate() {
+        if idx > 0 {
+            row.push(',');
+        }
+        if cell.contains([',', '"', '\r', '\n']) {
+            row.push('"');
+            row.push_str(&cell.replace('"', "\"\""));
+            row.push('"');
+        } else {
+            row.push_str(cell);
+        }
+    }
+    row.push_str("\r\n");
+    row
+}
+
+fn payload_cell(payload: &Value, path: &[String]) -> String {
+    let value = path
+        .iter()
+        .try_fold(payload, |value, key| value.get(key.as_str()));
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(value)) => value.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn outcome_to_str(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Success => "success",
+        Outcome::Failure => "failure",
+        Outcome::Unknown => "unknown",
+    }
+}
+
+fn stream_path(dir: &Path, key: &StreamKey, compression: JsonlCompression) -> PathBuf {
+    let mut name = format!(
+        "{}_{}_{}_{}_{}.csv",
+        key.account_id,
+        key.source,
+        key.region,
+        Utc::now().format("%Y%m%dT%H%MZ"),
+        unique_id()
+    );
+    if matches!(compression, JsonlCompression::Gzip) {
+        name.push_str(".gz");
+    }
+    dir.join(name)
+}
+
+fn flush_stream(
+    dir: &Path,
+    key: &StreamKey,
+    stream: &mut RowBuffer,
+    compression: JsonlCompression,
+) -> io::Result<Option<WrittenFile>> {
+    if stream.stats.events == 0 {
+        return Ok(None);
+    }
+    let path = stream_path(dir, key, compression);
+    File::create(&path)?.write_all(&stream.body.finish()?)?;
+    stream.first_event_at = None;
+    Ok(Some(WrittenFile {
+        path,
+        source: key.source.clone(),
+        stats: stream.stats.rotate(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::Actor;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    #[test]
+    fn writes_header_and_quoted_rows() {
+        let dir = std::env::temp_dir().join(format!("seclog-csv-{}", unique_id()));
+        let options: CsvOptions = toml::from_str(
+            r#"
+            columns = [
+              { name = "user_arn", path = "payload.userIdentity.arn" },
+              { name = "user_agent_raw", path = "payload.userAgent" },
+              { name = "request", path = "payload.requestParameters" },
+              { name = "missing", path = "payload.errorCode" },
+            ]
+            "#,
+        )
+        .unwrap();
+        let mut event = test_event(
+            "cloudtrail",
+            "GetObject",
+            json!({
+                "eventName": "GetObject",
+                "awsRegion": "us-east-1",
+                "userAgent": "aws-cli/2.15 \"x\"",
+                "userIdentity": { "arn": "arn:aws:iam::123456789012:user/alice" },
+                "requestParameters": { "bucketName": "logs" },
+            }),
+        )
+        .with_ip("203.0.113.7")
+        .in_account("123456789012");
+        event.envelope.actor = Actor {
+            id: "alice".to_string(),
+            kind: "human".to_string(),
+            name: Some("Alice, Example".to_string()),
+        };
+        let mut writer = CsvWriter::new(&dir, 1, None, &options).unwrap();
+        writer.write_event(&event).unwrap();
+        writer
+            .write_event(
+                &test_event(
+                    "cloudtrail",
+                    "PutObject",
+                    json!({ "awsRegion": "us-east-1", "userAgent": "console" }),
+                )
+                .in_account("123456789012"),
+            )
+            .unwrap();
+        writer.close().unwrap();
+
+        let files = writer.take_written_files();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].path.to_string_lossy().ends_with(".csv"));
+        let text = fs::read_to_string(&files[0].path).unwrap();
+        let lines = text.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("schema_version,timestamp,source,event_type,actor_id"));
+        assert!(lines[0].ends_with(",tenant_id,tenant,user_arn,user_agent_raw,request,missing"));
+        assert_eq!(
+            lines[1],
+            "v1,2026-01-01T00:00:00Z,cloudtrail,GetObject,alice,human,\"Alice, Example\",,,,\
+             success,,,,,,203.0.113.7,,,123456789012,,arn:aws:iam::123456789012:user/alice,\
+             \"aws-cli/2.15 \"\"x\"\"\",\"{\"\"bucketName\"\":\"\"logs\"\"}\","
+        );
+        assert_eq!(lines[3], "");
+
+        let bad = CsvOptions {
+            columns: vec![crate::core::config::CsvColumnConfig {
+                name: "ip".to_string(),
+                path: "payload.sourceIPAddress".to_string(),
+            }],
+            ..CsvOptions::default()
+        };
+        assert!(CsvWriter::new(&dir, 1, None, &bad).is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
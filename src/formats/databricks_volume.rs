@@ -4,6 +4,7 @@
 //! the Databricks Files API. This is a file sink; it does not create or mutate
 //! Delta tables.
 
+use super::json::record_bytes_for_event;
 use crate::core::config::DatabricksVolumeOutputConfig;
 use crate::core::event::Event;
 use crate::core::traits::EventWriter;
@@ -41,6 +42,10 @@ impl EventWriter for DatabricksVolumeWriter {
         self.inner.flush()
     }
 
+    fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync()
+    }
+
     fn close(&mut self) -> io::Result<()> {
         self.inner.close()
     }
@@ -87,6 +92,10 @@ impl PlatformDatabricksVolumeWriter {
         Ok(())
     }
 
+    fn sync(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
     fn close(&mut self) -> io::Result<()> {
         Ok(())
     }
@@ -209,7 +218,7 @@ impl<C: VolumeFilesClient> VolumeFileWriter<C> {
         Ok(())
     }
 
-    fn close(&mut self) -> io::Result<()> {
+    fn sync(&mut self) -> io::Result<()> {
         let keys = self.files.keys().cloned().collect::<Vec<_>>();
         for key in keys {
             self.flush_region(&key)?;
@@ -217,6 +226,10 @@ impl<C: VolumeFilesClient> VolumeFileWriter<C> {
         Ok(())
     }
 
+    fn close(&mut self) -> io::Result<()> {
+        self.sync()
+    }
+
     fn flush_region(&mut self, key: &RegionKey) -> io::Result<()> {
         let bytes = {
             let Some(region) = self.files.get(key) else {
@@ -267,14 +280,6 @@ fn validate_target_size(target_size_mb: u64) -> io::Result<()> {
     Ok(())
 }
 
-fn record_bytes_for_event(event: &Event) -> io::Result<Vec<u8>> {
-    if event.payload.is_null() {
-        serde_json::to_vec(event).map_err(|err| io::Error::new(io::ErrorKind::Other, err))
-    } else {
-        serde_json::to_vec(&event.payload).map_err(|err| io::Error::new(io::ErrorKind::Other, err))
-    }
-}
-
 fn append_record(region: &mut RegionBuffer, record_bytes: &[u8]) {
     if region.record_count == 0 {
         region.buffer.extend_from_slice(b"{\"Records\":[");
@@ -369,7 +374,7 @@ fn parse_compression(value: Option<&str>) -> io::Result<VolumeCompression> {
     }
 }
 
-fn normalize_volume_path(path: &str) -> io::Result<String> {
+pub(crate) fn normalize_volume_path(path: &str) -> io::Result<String> {
     let trimmed = path.trim().trim_end_matches('/');
     let without_scheme = trimmed.strip_prefix("dbfs:").unwrap_or(trimmed);
     if !without_scheme.starts_with("/Volumes/") {
@@ -579,7 +584,7 @@ fn map_reqwest_error(err: reqwest::Error) -> io::Error {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::event::{Actor, EventEnvelope, Outcome};
+    use crate::fixtures::{cloudtrail_test_event, test_event};
     use flate2::read::GzDecoder;
     use serde_json::{json, Value};
     use std::cell::RefCell;
@@ -622,13 +627,20 @@ mod tests {
         let mut writer = VolumeFileWriter::new(&config, client).unwrap();
 
         writer
-            .write_event(&test_event("cloudtrail", "ConsoleLogin"))
+            .write_event(&cloudtrail_test_event(
+                "ConsoleLogin",
+                "2026-01-01T00:00:00Z",
+            ))
             .unwrap();
         writer
-            .write_event(&test_event("databricks_audit", "IpAccessDenied"))
+            .write_event(&test_event("databricks_audit", "IpAccessDenied", json!({})))
             .unwrap();
         writer
-            .write_event(&test_event("okta_system_log", "user.session.start"))
+            .write_event(&test_event(
+                "okta_system_log",
+                "user.session.start",
+                json!({}),
+            ))
             .unwrap();
         writer.close().unwrap();
 
@@ -648,10 +660,9 @@ mod tests {
         assert!(paths
             .iter()
             .any(|path| path.contains("/source=okta_system_log/")));
-        assert!(state
-            .directories
-            .iter()
-            .any(|path| path.contains("/source=cloudtrail/tenant_id=tenant-1/region=us-east-1")));
+        assert!(state.directories.iter().any(
+            |path| path.contains("/source=cloudtrail/tenant_id=123456789012/region=us-east-1")
+        ));
     }
 
     #[test]
@@ -664,10 +675,13 @@ mod tests {
         let mut writer = VolumeFileWriter::new(&config, client).unwrap();
 
         writer
-            .write_event(&test_event("cloudtrail", "ConsoleLogin"))
+            .write_event(&cloudtrail_test_event(
+                "ConsoleLogin",
+                "2026-01-01T00:00:00Z",
+            ))
             .unwrap();
         writer
-            .write_event(&test_event("cloudtrail", "AssumeRole"))
+            .write_event(&cloudtrail_test_event("AssumeRole", "2026-01-01T00:00:00Z"))
             .unwrap();
         writer.close().unwrap();
 
@@ -691,7 +705,10 @@ mod tests {
         let mut writer = VolumeFileWriter::new(&config, client).unwrap();
 
         writer
-            .write_event(&test_event("cloudtrail", "ConsoleLogin"))
+            .write_event(&cloudtrail_test_event(
+                "ConsoleLogin",
+                "2026-01-01T00:00:00Z",
+            ))
             .unwrap();
         writer.close().unwrap();
 
@@ -724,7 +741,7 @@ mod tests {
     #[test]
     fn creates_only_directories_below_volume_root() {
         let directories = subdirectories_below_volume_root(
-            "/Volumes/main/seclog/raw/seclog/source=cloudtrail/tenant_id=tenant-1",
+            "/Volumes/main/seclog/raw/seclog/source=cloudtrail/tenant_id=123456789012",
         )
         .unwrap();
         assert_eq!(
@@ -732,7 +749,8 @@ mod tests {
             vec![
                 "/Volumes/main/seclog/raw/seclog".to_string(),
                 "/Volumes/main/seclog/raw/seclog/source=cloudtrail".to_string(),
-                "/Volumes/main/seclog/raw/seclog/source=cloudtrail/tenant_id=tenant-1".to_string()
+                "/Volumes/main/seclog/raw/seclog/source=cloudtrail/tenant_id=123456789012"
+                    .to_string()
             ]
         );
         assert!(subdirectories_below_volume_root("/Volumes/main/seclog/raw")
@@ -802,31 +820,4 @@ mod tests {
             overwrite: false,
         }
     }
-
-    fn test_event(source: &str, event_type: &str) -> Event {
-        Event {
-            envelope: EventEnvelope {
-                schema_version: "v1".to_string(),
-                timestamp: "2026-01-01T00:00:00Z".to_string(),
-                source: source.to_string(),
-                event_type: event_type.to_string(),
-                actor: Actor {
-                    id: "user-001".to_string(),
-                    kind: "human".to_string(),
-                    name: Some("Amelia Chen".to_string()),
-                },
-                target: None,
-                outcome: Outcome::Success,
-                geo: None,
-                ip: Some("198.51.100.10".to_string()),
-                user_agent: Some("Mozilla/5.0".to_string()),
-                session_id: Some("session-1".to_string()),
-                tenant_id: Some("tenant-1".to_string()),
-            },
-            payload: json!({
-                "eventName": event_type,
-                "awsRegion": "us-east-1"
-            }),
-        }
-    }
 }
@@ -0,0 +1,502 @@
+//! Delta Lake table output.
+//!
+//! [`DeltaWriter`] wraps the Parquet writer of a table directory and, whenever
+//! the inner writer finishes files, appends a commit to `_delta_log/` with one
+//! `add` action per file. The first commit also carries the `protocol` and
+//! `metaData` actions, so the directory reads as a Delta table in Spark or
+//! Databricks without a conversion job. Writer shards share a table: each
+//! commit claims the next log version with an atomic create and moves on to
+//! the following version when another writer got there first.
+
+use super::json::unique_id;
+use super::parquet::build_schema;
+use crate::core::config::{OutputPartitioning, ParquetOptions};
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use arrow_schema::{DataType, Field};
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Directory holding the transaction log under the table root.
+pub const LOG_DIR: &str = "_delta_log";
+/// Hive's placeholder for a missing partition value; Delta records it as null.
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Partition column of the table and its Delta type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionColumn {
+    pub name: &'static str,
+    pub data_type: &'static str,
+}
+
+impl PartitionColumn {
+    /// Columns matching the Hive-style directories written for `partitioning`,
+    /// behind `tenant` when tenant partitions are on.
+    pub fn for_layout(partitioning: OutputPartitioning, tenants: bool) -> Vec<Self> {
+        let mut columns = Vec::new();
+        if tenants {
+            columns.push(Self {
+                name: "tenant",
+                data_type: "string",
+            });
+        }
+        if partitioning != OutputPartitioning::None {
+            columns.push(Self {
+                name: "date",
+                data_type: "date",
+            });
+        }
+        if partitioning == OutputPartitioning::Hour {
+            columns.push(Self {
+                name: "hour",
+                data_type: "integer",
+            });
+        }
+        columns
+    }
+}
+
+/// Writer wrapper that commits the Parquet files of its inner writer to a
+/// Delta table rooted at `root`.
+pub struct DeltaWriter {
+    inner: Box<dyn EventWriter>,
+    root: PathBuf,
+    schema_string: String,
+    partition_columns: Vec<PartitionColumn>,
+    /// Whether `envelope.timestamp` is a timestamp column with usable min/max stats.
+    timestamp_stats: bool,
+    /// Finished files not yet committed, kept across a failed commit.
+    pending: Vec<WrittenFile>,
+    /// Committed files not yet handed to `take_written_files`.
+    committed: Vec<WrittenFile>,
+}
+
+impl DeltaWriter {
+    /// Wraps `inner`, whose Parquet files are all written under `root` in the
+    /// partition directories of `partition_columns`.
+    pub fn new(
+        inner: Box<dyn EventWriter>,
+        root: impl Into<PathBuf>,
+        options: &ParquetOptions,
+        partition_columns: Vec<PartitionColumn>,
+    ) -> io::Result<Self> {
+        let timestamp_strings = options.timestamp_strings.unwrap_or(false);
+        let mut fields = build_schema(timestamp_strings)
+            .fields()
+            .iter()
+            .map(|field| delta_field(field))
+            .collect::<Vec<_>>();
+        fields.extend(partition_columns.iter().map(|column| {
+            json!({
+                "name": column.name,
+                "type": column.data_type,
+                "nullable": true,
+                "metadata": {},
+            })
+        }));
+        let schema_string = json!({ "type": "struct", "fields": fields }).to_string();
+        Ok(Self {
+            inner,
+            root: root.into(),
+            schema_string,
+            partition_columns,
+            timestamp_stats: !timestamp_strings,
+            pending: Vec::new(),
+            committed: Vec::new(),
+        })
+    }
+
+    /// Commits every file the inner writer finished since the last commit.
+    fn commit(&mut self) -> io::Result<()> {
+        self.pending.extend(self.inner.take_written_files());
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let adds = self
+            .pending
+            .iter()
+            .map(|file| self.add_action(file))
+            .collect::<io::Result<Vec<_>>>()?;
+        let log_dir = self.root.join(LOG_DIR);
+        fs::create_dir_all(&log_dir)?;
+        let mut version = latest_version(&log_dir)?.map_or(0, |version| version + 1);
+        loop {
+            let mut actions = vec![self.commit_info()];
+            if version == 0 {
+                actions.push(json!({
+                    "protocol": { "minReaderVersion": 1, "minWriterVersion": 2 }
+                }));
+                actions.push(self.metadata());
+            }
+            actions.extend(adds.iter().cloned());
+            match write_commit(&log_dir, version, &actions) {
+                Ok(()) => break,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => version += 1,
+                Err(err) => return Err(err),
+            }
+        }
+        self.committed.append(&mut self.pending);
+        Ok(())
+    }
+
+    fn commit_info(&self) -> Value {
+        let partition_by = self
+            .partition_columns
+            .iter()
+            .map(|column| column.name)
+            .collect::<Vec<_>>();
+        json!({
+            "commitInfo": {
+                "timestamp": Utc::now().timestamp_millis(),
+                "operation": "WRITE",
+                "operationParameters": {
+                    "mode": "Append",
+                    "partitionBy": json!(partition_by).to_string(),
+                },
+                "isBlindAppend": true,
+                "engineInfo": concat!("seclog/", env!("CARGO_PKG_VERSION")),
+            }
+        })
+    }
+
+    fn metadata(&self) -> Value {
+        let partition_columns = self
+            .partition_columns
+            .iter()
+            .map(|column| column.name)
+            .collect::<Vec<_>>();
+        json!({
+            "metaData": {
+                "id": random_uuid(),
+                "format": { "provider": "parquet", "options": {} },
+                "schemaString": self.schema_string,
+                "partitionColumns": partition_columns,
+                "configuration": {},
+                "createdTime": Utc::now().timestamp_millis(),
+            }
+        })
+    }
+
+    fn add_action(&self, file: &WrittenFile) -> io::Result<Value> {
+        let metadata = fs::metadata(&file.path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let relative = file.path.strip_prefix(&self.root).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is outside the Delta table {}",
+                    file.path.display(),
+                    self.root.display()
+                ),
+            )
+        })?;
+        let mut partition_values = Map::new();
+        for column in &self.partition_columns {
+            partition_values.insert(column.name.to_string(), Value::Null);
+        }
+        for component in relative.parent().into_iter().flat_map(Path::components) {
+            let segment = component.as_os_str().to_string_lossy();
+            if let Some((name, value)) = segment.split_once('=') {
+                if let Some(slot) = partition_values.get_mut(name) {
+                    if value != DEFAULT_PARTITION {
+                        *slot = Value::String(value.to_string());
+                    }
+                }
+            }
+        }
+        let mut stats = json!({ "numRecords": file.stats.events });
+        if let (true, Some(first), Some(last)) = (
+            self.timestamp_stats,
+            file.stats.first_event_time,
+            file.stats.last_event_time,
+        ) {
+            let time =
+                |value: chrono::DateTime<Utc>| value.to_rfc3339_opts(SecondsFormat::Micros, true);
+            stats["minValues"] = json!({ "envelope": { "timestamp": time(first) } });
+            stats["maxValues"] = json!({ "envelope": { "timestamp": time(last) } });
+        }
+        Ok(json!({
+            "add": {
+                "path": encode_path(relative),
+                "partitionValues": partition_values,
+                "size": metadata.len(),
+                "modificationTime": modified,
+                "dataChange": true,
+                "stats": stats.to_string(),
+            }
+        }))
+    }
+}
+
+impl EventWriter for DeltaWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        self.inner.write_event(event)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.commit()
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync()?;
+        self.commit()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.inner.close()?;
+        self.commit()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.committed)
+    }
+}
+
+/// Returns true for Delta transaction log files, which are not event output.
+pub fn is_delta_log_file(path: &Path) -> bool {
+    path.parent()
+        .and_then(Path::file_name)
+        .is_some_and(|name| name == LOG_DIR)
+}
+
+/// Highest committed version in the log directory, if any.
+fn latest_version(log_dir: &Path) -> io::Result<Option<u64>> {
+    let mut latest = None;
+    for entry in fs::read_dir(log_dir)? {
+        let name = entry?.file_name();
+        let version = name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".json"))
+            .filter(|stem| stem.len() == 20)
+            .and_then(|stem| stem.parse::<u64>().ok());
+        if version > latest {
+            latest = version;
+        }
+    }
+    Ok(latest)
+}
+
+/// Writes the commit for `version`, failing with `AlreadyExists` if another
+/// writer committed it first.
+fn write_commit(log_dir: &Path, version: u64, actions: &[Value]) -> io::Result<()> {
+    let mut contents = Vec::new();
+    for action in actions {
+        serde_json::to_writer(&mut contents, action).map_err(io::Error::other)?;
+        contents.push(b'\n');
+    }
+    create_new(&log_dir.join(format!("{version:020}.json")), &contents)
+}
+
+/// Creates `path` with `contents`, failing with `AlreadyExists` if it exists.
+/// The contents go to a temp file that is hard-linked into place, so readers
+/// never see a partial file and concurrent writers never overwrite each other.
+pub(crate) fn create_new(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    let temp = path.with_file_name(format!(".{name}.{}.tmp", unique_id()));
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    let result = fs::hard_link(&temp, path);
+    fs::remove_file(&temp)?;
+    result
+}
+
+/// Converts an Arrow field to a Delta schema field.
+fn delta_field(field: &Field) -> Value {
+    json!({
+        "name": field.name(),
+        "type": delta_type(field.data_type()),
+        "nullable": field.is_nullable(),
+        "metadata": {},
+    })
+}
+
+fn delta_type(data_type: &DataType) -> Value {
+    match data_type {
+        DataType::Struct(fields) => json!({
+            "type": "struct",
+            "fields": fields.iter().map(|field| delta_field(field)).collect::<Vec<_>>(),
+        }),
+        DataType::Boolean => json!("boolean"),
+        DataType::Int32 => json!("integer"),
+        DataType::Int64 => json!("long"),
+        DataType::Float64 => json!("double"),
+        DataType::Timestamp(_, _) => json!("timestamp"),
+        _ => json!("string"),
+    }
+}
+
+/// Percent-encodes a table-relative path as the URI Delta expects.
+fn encode_path(relative: &Path) -> String {
+    let mut encoded = String::new();
+    for (idx, component) in relative.components().enumerate() {
+        if idx > 0 {
+            encoded.push('/');
+        }
+        for byte in component.as_os_str().to_string_lossy().bytes() {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~' | b'=') {
+                encoded.push(byte as char);
+            } else {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    encoded
+}
+
+/// Random version 4 UUID.
+pub(crate) fn random_uuid() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::cloudtrail_test_event;
+    use crate::formats::parquet::ParquetWriter;
+    use crate::formats::partitioned::{hive_time_partition, PartitionedWriter};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("seclog-delta-{name}-{}", unique_id()));
+        fs::create_dir_all(&dir).expect("temp dir");
+        dir
+    }
+
+    fn read_log(dir: &Path, version: u64) -> Vec<Value> {
+        let path = dir.join(LOG_DIR).join(format!("{version:020}.json"));
+        fs::read_to_string(path)
+            .expect("commit")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("action"))
+            .collect()
+    }
+
+    #[test]
+    fn commits_partitioned_parquet_files_to_the_log() {
+        let dir = temp_dir("partitioned");
+        let root = dir.clone();
+        let inner = PartitionedWriter::new(
+            &dir,
+            |event| hive_time_partition(event, OutputPartitioning::Hour),
+            |dir| Ok(Box::new(ParquetWriter::new(dir, 64, None)?) as Box<dyn EventWriter>),
+        );
+        let columns = PartitionColumn::for_layout(OutputPartitioning::Hour, false);
+        let mut writer =
+            DeltaWriter::new(Box::new(inner), &root, &ParquetOptions::default(), columns)
+                .expect("writer");
+        for timestamp in [
+            "2026-01-01T09:15:00Z",
+            "2026-01-01T09:45:00Z",
+            "2026-01-01T10:05:00Z",
+        ] {
+            writer
+                .write_event(&cloudtrail_test_event("GetObject", timestamp))
+                .expect("write");
+        }
+        writer.close().expect("close");
+        assert_eq!(writer.take_written_files().len(), 2);
+
+        let actions = read_log(&dir, 0);
+        assert!(actions[0].get("commitInfo").is_some());
+        assert_eq!(actions[1]["protocol"]["minReaderVersion"], 1);
+        let metadata = &actions[2]["metaData"];
+        assert_eq!(metadata["partitionColumns"], json!(["date", "hour"]));
+        let schema: Value =
+            serde_json::from_str(metadata["schemaString"].as_str().unwrap()).expect("schema");
+        let names = schema["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["envelope", "payload_json", "cloudtrail", "date", "hour"]
+        );
+
+        let mut adds = actions[3..]
+            .iter()
+            .map(|action| &action["add"])
+            .collect::<Vec<_>>();
+        adds.sort_by_key(|add| add["path"].as_str().unwrap().to_string());
+        assert_eq!(adds.len(), 2);
+        assert!(adds[0]["path"]
+            .as_str()
+            .unwrap()
+            .starts_with("date=2026-01-01/hour=09/"));
+        assert_eq!(
+            adds[0]["partitionValues"],
+            json!({ "date": "2026-01-01", "hour": "09" })
+        );
+        let stats: Value = serde_json::from_str(adds[0]["stats"].as_str().unwrap()).unwrap();
+        assert_eq!(stats["numRecords"], 2);
+        assert_eq!(
+            stats["minValues"]["envelope"]["timestamp"],
+            "2026-01-01T09:15:00.000000Z"
+        );
+        for add in adds {
+            let path = dir.join(add["path"].as_str().unwrap());
+            assert_eq!(add["size"], fs::metadata(path).expect("data file").len());
+        }
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn writers_sharing_a_table_take_successive_versions() {
+        let dir = temp_dir("shared");
+        let mut writers = (0..2)
+            .map(|_| {
+                let inner = ParquetWriter::new(&dir, 64, None).expect("parquet");
+                DeltaWriter::new(
+                    Box::new(inner),
+                    &dir,
+                    &ParquetOptions::default(),
+                    Vec::new(),
+                )
+                .expect("writer")
+            })
+            .collect::<Vec<_>>();
+        for writer in &mut writers {
+            writer
+                .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T09:15:00Z"))
+                .expect("write");
+            writer.close().expect("close");
+        }
+
+        let first = read_log(&dir, 0);
+        let second = read_log(&dir, 1);
+        assert!(first.iter().any(|action| action.get("metaData").is_some()));
+        assert!(second.iter().all(|action| action.get("metaData").is_none()));
+        assert_eq!(second.last().unwrap()["add"]["partitionValues"], json!({}));
+        assert!(is_delta_log_file(
+            &dir.join(LOG_DIR).join(format!("{:020}.json", 1))
+        ));
+        fs::remove_dir_all(dir).ok();
+    }
+}
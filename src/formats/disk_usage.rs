@@ -0,0 +1,492 @@
+//! On-disk size accounting for byte-budgeted runs.
+//!
+//! `write_event` reports serialized record bytes, which say little about the
+//! size of gzip, Parquet, or Avro files on disk. A [`DiskUsageWriter`] wraps a
+//! file output and records the on-disk size of every file its inner writer
+//! finishes in a [`DiskUsage`] shared with the generation loop. Data still
+//! buffered in open files is estimated from the serialized bytes written since
+//! the last [`DiskUsage::calibrate`], at the on-disk to serialized ratio seen
+//! then; after the writers sync and the usage is calibrated, the total is exact.
+//! Events the generation loop has dispatched but the writers have not yet
+//! received, because they sit in writer queues, count at the average size of
+//! the events written so far.
+//! [`ByteBudget`] does that syncing only when the estimate reaches a limit, so
+//! runs stop at the target size without closing files early.
+
+use super::json::source_file_label;
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// Output bytes per source file label, shared by writer threads.
+#[derive(Debug, Default)]
+pub struct DiskUsage {
+    sources: Mutex<HashMap<String, SourceUsage>>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SourceUsage {
+    /// Bytes of finished files on disk.
+    disk: u64,
+    /// Serialized bytes handed to writers.
+    written: u64,
+    /// Events handed to writers.
+    events: u64,
+    /// `disk` and `written` at the last calibration.
+    calibrated_disk: u64,
+    calibrated_written: u64,
+}
+
+impl SourceUsage {
+    /// On-disk bytes including data still buffered in open files and the
+    /// `queued` events not yet handed to writers.
+    fn estimate(&self, queued: u64) -> u64 {
+        let ratio = if self.calibrated_written > 0 {
+            self.calibrated_disk as f64 / self.calibrated_written as f64
+        } else {
+            1.0
+        };
+        let per_event = if self.events > 0 {
+            self.written as f64 / self.events as f64
+        } else {
+            0.0
+        };
+        let pending = (self.written.saturating_sub(self.calibrated_written) as f64
+            + queued as f64 * per_event)
+            * ratio;
+        self.disk.max(self.calibrated_disk + pending as u64)
+    }
+}
+
+impl DiskUsage {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    fn record_written(&self, label: String, bytes: u64) {
+        let mut sources = self.sources.lock().expect("disk usage lock");
+        let usage = sources.entry(label).or_default();
+        usage.written += bytes;
+        usage.events += 1;
+    }
+
+    fn record_file(&self, file: &WrittenFile) -> io::Result<()> {
+        let bytes = fs::metadata(&file.path)?.len();
+        let mut sources = self.sources.lock().expect("disk usage lock");
+        sources.entry(file.source.clone()).or_default().disk += bytes;
+        Ok(())
+    }
+
+    /// Marks everything written so far as on disk. Call only after every
+    /// writer sharing this usage has synced.
+    pub fn calibrate(&self) {
+        let mut sources = self.sources.lock().expect("disk usage lock");
+        for usage in sources.values_mut() {
+            usage.calibrated_disk = usage.disk;
+            usage.calibrated_written = usage.written;
+        }
+    }
+
+    /// Estimated on-disk bytes across all sources.
+    pub fn total(&self) -> u64 {
+        self.measure(None, &HashMap::new()).0
+    }
+
+    /// Estimated on-disk bytes for one envelope source, such as `cloudtrail`.
+    pub fn source(&self, source: &str) -> u64 {
+        self.measure(Some(source), &HashMap::new()).0
+    }
+
+    /// Events handed to writers per source file label.
+    fn events(&self) -> HashMap<String, u64> {
+        let sources = self.sources.lock().expect("disk usage lock");
+        sources
+            .iter()
+            .map(|(label, usage)| (label.clone(), usage.events))
+            .collect()
+    }
+
+    /// Estimated bytes and bytes measured at the last calibration, for one
+    /// envelope source or, with `None`, all output. `dispatched` counts events
+    /// sent towards writers per source file label, including queued ones.
+    fn measure(&self, source: Option<&str>, dispatched: &HashMap<String, u64>) -> (u64, u64) {
+        let sources = self.sources.lock().expect("disk usage lock");
+        let usage = |label: &String, usage: &SourceUsage| {
+            let queued = dispatched
+                .get(label)
+                .map_or(0, |dispatched| dispatched.saturating_sub(usage.events));
+            (usage.estimate(queued), usage.calibrated_disk)
+        };
+        match source {
+            Some(source) => {
+                let label = source_file_label(source);
+                sources
+                    .get(&label)
+                    .map_or((0, 0), |found| usage(&label, found))
+            }
+            None => sources
+                .iter()
+                .map(|(label, found)| usage(label, found))
+                .fold((0, 0), |(estimate, measured), (more, measured_more)| {
+                    (estimate + more, measured + measured_more)
+                }),
+        }
+    }
+}
+
+/// Writer wrapper that reports its inner writer's output to a [`DiskUsage`].
+///
+/// Finished files are passed on through `take_written_files`, so manifests
+/// still see them.
+pub struct DiskUsageWriter {
+    inner: Box<dyn EventWriter>,
+    usage: Arc<DiskUsage>,
+    files: Vec<WrittenFile>,
+}
+
+impl DiskUsageWriter {
+    pub fn new(inner: Box<dyn EventWriter>, usage: Arc<DiskUsage>) -> Self {
+        Self {
+            inner,
+            usage,
+            files: Vec::new(),
+        }
+    }
+
+    fn collect(&mut self) -> io::Result<()> {
+        for file in self.inner.take_written_files() {
+            self.usage.record_file(&file)?;
+            self.files.push(file);
+        }
+        Ok(())
+    }
+}
+
+impl EventWriter for DiskUsageWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let bytes = self.inner.write_event(event)?;
+        self.usage
+            .record_written(source_file_label(&event.envelope.source), bytes);
+        self.collect()?;
+        Ok(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.collect()
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync()?;
+        self.collect()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.inner.close()?;
+        self.collect()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.files)
+    }
+}
+
+/// Share of the room left below a limit that estimated growth may fill before
+/// the writers sync; estimates run low when later files compress worse.
+const SYNC_AT_SHARE: f64 = 0.9;
+/// The same share before anything was measured, when the estimate assumes
+/// files are as large as the serialized events, which Parquet can exceed.
+const FIRST_SYNC_AT_SHARE: f64 = 0.5;
+/// A limit counts as reached once real output is within this share of it, so
+/// the last stretch does not sync every few events.
+const STOP_WITHIN_SHARE: f64 = 0.01;
+
+/// What to do with the next event of a byte-budgeted run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetDecision {
+    Write,
+    /// The event's source reached its limit; drop the event.
+    Skip,
+    /// The total limit is reached, or every source reached its limit.
+    Stop,
+}
+
+/// Total and per-source limits on on-disk output bytes.
+pub struct ByteBudget {
+    usage: Arc<DiskUsage>,
+    total: Option<u64>,
+    /// Limits keyed by envelope source.
+    sources: HashMap<String, u64>,
+    /// Envelope sources the run emits.
+    emitted: Vec<String>,
+    capped: HashSet<String>,
+    /// Events admitted per source file label, some of which may still be
+    /// queued for the writers.
+    dispatched: HashMap<String, u64>,
+}
+
+impl ByteBudget {
+    /// `emitted` lists the envelope sources the run produces; once all of them
+    /// are capped the run stops.
+    pub fn new(
+        usage: Arc<DiskUsage>,
+        total: Option<u64>,
+        sources: HashMap<String, u64>,
+        emitted: Vec<String>,
+    ) -> Self {
+        Self {
+            usage,
+            total,
+            sources,
+            emitted,
+            capped: HashSet::new(),
+            dispatched: HashMap::new(),
+        }
+    }
+
+    /// Decides whether an event from `source` still fits, counting it as
+    /// dispatched when it does. When an estimate reaches a limit, `sync` is
+    /// called to write out every buffered file first, so limits are checked
+    /// against real file sizes.
+    pub fn admit<E>(
+        &mut self,
+        source: &str,
+        sync: impl FnMut() -> Result<(), E>,
+    ) -> Result<BudgetDecision, E> {
+        let decision = self.decide(source, sync)?;
+        if decision == BudgetDecision::Write {
+            *self
+                .dispatched
+                .entry(source_file_label(source))
+                .or_default() += 1;
+        }
+        Ok(decision)
+    }
+
+    fn decide<E>(
+        &mut self,
+        source: &str,
+        mut sync: impl FnMut() -> Result<(), E>,
+    ) -> Result<BudgetDecision, E> {
+        if self.capped.contains(source) {
+            return Ok(BudgetDecision::Skip);
+        }
+        if let Some(total) = self.total {
+            if self.reached(None, total, &mut sync)? {
+                return Ok(BudgetDecision::Stop);
+            }
+        }
+        let Some(&limit) = self.sources.get(source) else {
+            return Ok(BudgetDecision::Write);
+        };
+        if !self.reached(Some(source), limit, &mut sync)? {
+            return Ok(BudgetDecision::Write);
+        }
+        self.capped.insert(source.to_string());
+        if self.emitted.iter().all(|name| self.capped.contains(name)) {
+            return Ok(BudgetDecision::Stop);
+        }
+        Ok(BudgetDecision::Skip)
+    }
+
+    /// Whether output for `source`, or all output, reached `limit`. Syncs
+    /// once the estimate nears the limit and answers from real file sizes.
+    fn reached<E>(
+        &mut self,
+        source: Option<&str>,
+        limit: u64,
+        sync: &mut impl FnMut() -> Result<(), E>,
+    ) -> Result<bool, E> {
+        let (estimate, measured) = self.usage.measure(source, &self.dispatched);
+        let room = limit.saturating_sub(measured) as f64;
+        let share = if measured == 0 {
+            FIRST_SYNC_AT_SHARE
+        } else {
+            SYNC_AT_SHARE
+        };
+        if (estimate as f64) < limit as f64 - room * (1.0 - share) {
+            return Ok(false);
+        }
+        sync()?;
+        self.usage.calibrate();
+        // The writers have taken every queued event.
+        self.dispatched = self.usage.events();
+        let (measured, _) = self.usage.measure(source, &self.dispatched);
+        Ok(measured as f64 >= limit as f64 * (1.0 - STOP_WITHIN_SHARE))
+    }
+
+    /// Usage the run's file writers report to.
+    pub fn usage(&self) -> &Arc<DiskUsage> {
+        &self.usage
+    }
+
+    /// Estimated on-disk bytes written so far.
+    pub fn total_bytes(&self) -> u64 {
+        self.usage.total()
+    }
+}
+
+/// Parses sizes like `"50GB"`, `"512MiB"`, `"1.5TB"`, or bare bytes. Decimal
+/// units are powers of 1000 and binary units (`KiB`, `MiB`, ...) powers of 1024.
+pub fn parse_byte_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    let bytes = number * multiplier;
+    if !bytes.is_finite() || bytes < 0.0 || bytes > u64::MAX as f64 {
+        return None;
+    }
+    Some(bytes.round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use crate::formats::json::JsonlWriter;
+    use serde_json::json;
+    use std::convert::Infallible;
+    use std::path::Path;
+
+    fn disk_bytes(dir: &Path) -> u64 {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .sum()
+    }
+
+    #[test]
+    fn usage_counts_compressed_file_sizes() {
+        let event = test_event(
+            "cloudtrail",
+            "GetObject",
+            json!({ "eventName": "GetObject", "awsRegion": "us-east-1", "padding": "x".repeat(400) }),
+        );
+        let dir = std::env::temp_dir().join(format!("seclog-disk-usage-{}", std::process::id()));
+        let usage = DiskUsage::new();
+        let inner = JsonlWriter::new(&dir, 64, None, Some("gzip")).unwrap();
+        let mut writer = DiskUsageWriter::new(Box::new(inner), usage.clone());
+
+        let mut serialized = 0;
+        for _ in 0..200 {
+            serialized += writer.write_event(&event).unwrap();
+        }
+        assert_eq!(usage.source("cloudtrail"), serialized);
+        writer.close().unwrap();
+        usage.calibrate();
+
+        let on_disk = disk_bytes(&dir);
+        assert!(on_disk * 10 < serialized);
+        assert_eq!(usage.total(), on_disk);
+        assert_eq!(usage.source("cloudtrail"), on_disk);
+        assert_eq!(writer.take_written_files().len(), 1);
+
+        for _ in 0..200 {
+            writer.write_event(&event).unwrap();
+        }
+        let estimate = usage.total();
+        assert!(estimate > on_disk * 3 / 2 && estimate < on_disk * 5 / 2);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn queued_events_count_at_the_average_written_size() {
+        let event = test_event(
+            "cloudtrail",
+            "GetObject",
+            json!({ "eventName": "GetObject", "awsRegion": "us-east-1", "padding": "x".repeat(400) }),
+        );
+        let dir = std::env::temp_dir().join(format!("seclog-disk-queued-{}", std::process::id()));
+        let usage = DiskUsage::new();
+        let inner = JsonlWriter::new(&dir, 64, None, None).unwrap();
+        let mut writer = DiskUsageWriter::new(Box::new(inner), usage.clone());
+        for _ in 0..10 {
+            writer.write_event(&event).unwrap();
+        }
+        let written = usage.total();
+        let dispatched = [(source_file_label("cloudtrail"), 30)].into();
+        let (estimate, _) = usage.measure(None, &dispatched);
+        assert!(estimate.abs_diff(written * 3) <= 1, "estimate: {estimate}");
+        writer.close().unwrap();
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn budget_skips_capped_sources_and_stops_when_all_are_capped() {
+        let event = test_event(
+            "okta_system_log",
+            "GetObject",
+            json!({ "eventName": "GetObject", "awsRegion": "us-east-1", "padding": "x".repeat(400) }),
+        );
+        let dir = std::env::temp_dir().join(format!("seclog-byte-budget-{}", std::process::id()));
+        let usage = DiskUsage::new();
+        let inner = JsonlWriter::new(&dir, 64, None, None).unwrap();
+        let mut writer = DiskUsageWriter::new(Box::new(inner), usage.clone());
+        let mut budget = ByteBudget::new(
+            usage.clone(),
+            None,
+            [("okta_system_log".to_string(), 5_000)].into(),
+            vec!["cloudtrail".to_string(), "okta_system_log".to_string()],
+        );
+
+        let mut syncs = 0;
+        let mut written = 0;
+        for _ in 0..100 {
+            let decision = budget
+                .admit("okta_system_log", || {
+                    syncs += 1;
+                    writer.close()
+                })
+                .unwrap();
+            if decision != BudgetDecision::Write {
+                assert_eq!(decision, BudgetDecision::Skip);
+                break;
+            }
+            writer.write_event(&event).unwrap();
+            written += 1;
+        }
+        assert_eq!(syncs, 2);
+        assert!((10..=12).contains(&written), "written: {written}");
+        assert!(usage.source("okta_system_log") >= 5_000);
+        assert_eq!(
+            budget.admit("cloudtrail", || Ok::<_, Infallible>(())),
+            Ok(BudgetDecision::Write)
+        );
+
+        budget.sources.insert("cloudtrail".to_string(), 0);
+        assert_eq!(
+            budget.admit("cloudtrail", || Ok::<_, Infallible>(())),
+            Ok(BudgetDecision::Stop)
+        );
+        writer.close().unwrap();
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn parses_byte_size_units() {
+        assert_eq!(parse_byte_size("4096"), Some(4096));
+        assert_eq!(parse_byte_size("50GB"), Some(50_000_000_000));
+        assert_eq!(parse_byte_size("1.5 kb"), Some(1_500));
+        assert_eq!(parse_byte_size("512MiB"), Some(512 * 1024 * 1024));
+        assert_eq!(parse_byte_size("ten gigs"), None);
+        assert_eq!(parse_byte_size("5PB"), None);
+    }
+}
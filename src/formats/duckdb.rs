@@ -0,0 +1,389 @@
+//! DuckDB table sink.
+//!
+//! Buffers events into Arrow batches with the Parquet event schema and appends
+//! each batch to one table in a `.duckdb` file, in one transaction, through a
+//! connection held for the writer's lifetime. A batch whose transaction fails
+//! is kept and retried with the next commit, so no rows are lost. The table,
+//! and its schema for a dotted name, is created from the event schema if
+//! missing.
+
+// Without the `duckdb` feature only the tests use the DDL helpers.
+#![cfg_attr(not(feature = "duckdb"), allow(dead_code))]
+
+use crate::core::config::DuckDbOutputConfig;
+use crate::core::event::Event;
+use crate::core::traits::EventWriter;
+use arrow_schema::{DataType, Schema};
+use std::io;
+
+#[cfg(feature = "duckdb")]
+use super::parquet::{build_schema, map_arrow_err, EventBatchBuilder};
+#[cfg(feature = "duckdb")]
+use arrow_array::RecordBatch;
+#[cfg(feature = "duckdb")]
+use std::path::Path;
+
+/// Rows DuckDB takes per appended data chunk (its standard vector size).
+#[cfg(feature = "duckdb")]
+const CHUNK_ROWS: usize = 2048;
+
+/// DuckDB writer used by the CLI.
+pub struct DuckDbWriter {
+    inner: PlatformDuckDbWriter,
+}
+
+impl DuckDbWriter {
+    pub fn new(config: &DuckDbOutputConfig) -> io::Result<Self> {
+        Ok(Self {
+            inner: PlatformDuckDbWriter::new(config)?,
+        })
+    }
+}
+
+impl EventWriter for DuckDbWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        self.inner.write_event(event)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.inner.close()
+    }
+}
+
+#[cfg(feature = "duckdb")]
+type PlatformDuckDbWriter = TableWriter;
+
+#[cfg(not(feature = "duckdb"))]
+struct PlatformDuckDbWriter;
+
+#[cfg(not(feature = "duckdb"))]
+impl PlatformDuckDbWriter {
+    fn new(_config: &DuckDbOutputConfig) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "duckdb output requires building with --features duckdb",
+        ))
+    }
+
+    fn write_event(&mut self, _event: &Event) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "duckdb output requires building with --features duckdb",
+        ))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Appends batches to `table` over one open connection.
+#[cfg(feature = "duckdb")]
+struct TableWriter {
+    conn: duckdb::Connection,
+    /// DuckDB schema holding the table; `main` unless the name is dotted.
+    schema_name: String,
+    table: String,
+    batch: EventBatchBuilder,
+    batch_size: usize,
+    /// Finished batches not yet committed, oldest first.
+    pending: Vec<RecordBatch>,
+}
+
+#[cfg(feature = "duckdb")]
+impl TableWriter {
+    fn new(config: &DuckDbOutputConfig) -> io::Result<Self> {
+        let database = Path::new(&config.path);
+        if let Some(parent) = database.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let conn = duckdb::Connection::open(database).map_err(|err| {
+            io::Error::other(format!(
+                "duckdb output could not open {}: {err}",
+                database.display()
+            ))
+        })?;
+        let schema = build_schema(config.timestamp_strings);
+        conn.execute_batch(&create_table_sql(&config.table, &schema)?)
+            .map_err(|err| duckdb_error(&config.table, err))?;
+        let (schema_name, table) = match config.table.rsplit_once('.') {
+            Some((schema_name, table)) => (schema_name.to_string(), table.to_string()),
+            None => ("main".to_string(), config.table.clone()),
+        };
+        let batch_size = config.batch_size.max(1);
+        Ok(Self {
+            conn,
+            schema_name,
+            table,
+            batch: EventBatchBuilder::new(schema, batch_size),
+            batch_size,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Appends the buffered rows, and any batches left by a failed commit,
+    /// in one transaction. On failure the transaction rolls back and the
+    /// batches stay pending.
+    fn commit(&mut self) -> io::Result<()> {
+        if self.batch.len() > 0 {
+            let batch = self.batch.finish().map_err(map_arrow_err)?;
+            self.pending.push(batch);
+        }
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(|err| duckdb_error(&self.table, err))?;
+        {
+            let mut appender = tx
+                .appender_to_db(&self.table, &self.schema_name)
+                .map_err(|err| duckdb_error(&self.table, err))?;
+            for batch in &self.pending {
+                for offset in (0..batch.num_rows()).step_by(CHUNK_ROWS) {
+                    let rows = CHUNK_ROWS.min(batch.num_rows() - offset);
+                    appender
+                        .append_record_batch(batch.slice(offset, rows))
+                        .map_err(|err| duckdb_error(&self.table, err))?;
+                }
+            }
+            appender
+                .flush()
+                .map_err(|err| duckdb_error(&self.table, err))?;
+        }
+        tx.commit().map_err(|err| duckdb_error(&self.table, err))?;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "duckdb")]
+impl EventWriter for TableWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let size = self.batch.append_event(event)?;
+        if self.batch.len() >= self.batch_size {
+            self.commit()?;
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.commit()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.commit()
+    }
+}
+
+#[cfg(feature = "duckdb")]
+fn duckdb_error(table: &str, err: duckdb::Error) -> io::Error {
+    io::Error::other(format!("duckdb append into {table} failed: {err}"))
+}
+
+/// Builds the statements that create `table`, and its schema, if missing.
+fn create_table_sql(table: &str, schema: &Schema) -> io::Result<String> {
+    let mut sql = String::new();
+    if let Some((schema_name, _)) = table.rsplit_once('.') {
+        sql.push_str(&format!(
+            "CREATE SCHEMA IF NOT EXISTS {};\n",
+            quote_identifier(schema_name)
+        ));
+    }
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            Ok(format!(
+                "{} {}",
+                quote_identifier(field.name()),
+                column_type(field.data_type())?
+            ))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    sql.push_str(&format!(
+        "CREATE TABLE IF NOT EXISTS {} ({});\n",
+        quote_identifier(table),
+        columns.join(", ")
+    ));
+    Ok(sql)
+}
+
+/// DuckDB column type for an event schema column.
+fn column_type(data_type: &DataType) -> io::Result<String> {
+    Ok(match data_type {
+        DataType::Utf8 => "VARCHAR".to_string(),
+        DataType::Boolean => "BOOLEAN".to_string(),
+        DataType::Float64 => "DOUBLE".to_string(),
+        DataType::Timestamp(_, Some(_)) => "TIMESTAMPTZ".to_string(),
+        DataType::Timestamp(_, None) => "TIMESTAMP".to_string(),
+        DataType::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    Ok(format!(
+                        "{} {}",
+                        quote_identifier(field.name()),
+                        column_type(field.data_type())?
+                    ))
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            format!("STRUCT({})", fields.join(", "))
+        }
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("duckdb output has no column type for {other}"),
+            ))
+        }
+    })
+}
+
+/// Quotes each dot-separated part, so `analytics.events` targets a schema.
+fn quote_identifier(name: &str) -> String {
+    name.split('.')
+        .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::DuckDbOutputType;
+    #[cfg(feature = "duckdb")]
+    use crate::fixtures::test_event;
+    use crate::formats::parquet::build_schema;
+    #[cfg(feature = "duckdb")]
+    use serde_json::json;
+    use std::path::Path;
+
+    fn test_config(dir: &Path) -> DuckDbOutputConfig {
+        DuckDbOutputConfig {
+            output_type: DuckDbOutputType::DuckDb,
+            path: dir.join("seclog.duckdb").to_string_lossy().to_string(),
+            table: "analytics.events".to_string(),
+            batch_size: 2,
+            flush_interval_ms: 5000,
+            timestamp_strings: false,
+        }
+    }
+
+    #[test]
+    fn creates_the_table_from_the_event_schema() {
+        let sql = create_table_sql("analytics.events", &build_schema(false)).unwrap();
+        assert!(sql.starts_with("CREATE SCHEMA IF NOT EXISTS \"analytics\";\n"));
+        assert!(sql
+            .contains("CREATE TABLE IF NOT EXISTS \"analytics\".\"events\" (\"envelope\" STRUCT("));
+        assert!(sql.contains("\"timestamp\" TIMESTAMPTZ"));
+        assert!(
+            sql.contains("\"actor\" STRUCT(\"id\" VARCHAR, \"kind\" VARCHAR, \"name\" VARCHAR)")
+        );
+        assert!(create_table_sql("events", &build_schema(true))
+            .unwrap()
+            .starts_with("CREATE TABLE IF NOT EXISTS \"events\""));
+    }
+
+    #[cfg(feature = "duckdb")]
+    #[test]
+    fn appends_batches_to_the_table() {
+        let dir = std::env::temp_dir().join(format!("seclog-duckdb-{}", std::process::id()));
+        let config = test_config(&dir);
+        let mut writer = DuckDbWriter::new(&config).unwrap();
+        for name in ["GetObject", "PutObject", "ListBuckets"] {
+            writer
+                .write_event(&test_event(
+                    "cloudtrail",
+                    name,
+                    json!({ "eventName": name }),
+                ))
+                .unwrap();
+        }
+        writer.close().unwrap();
+        drop(writer);
+
+        let conn = duckdb::Connection::open(&config.path).unwrap();
+        let mut statement = conn
+            .prepare(
+                "SELECT envelope.event_type, envelope.target IS NULL \
+                 FROM analytics.events ORDER BY 1",
+            )
+            .unwrap();
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("GetObject".to_string(), true),
+                ("ListBuckets".to_string(), true),
+                ("PutObject".to_string(), true),
+            ]
+        );
+        drop(statement);
+        drop(conn);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "duckdb")]
+    #[test]
+    fn keeps_rows_of_a_failed_commit_for_the_next_one() {
+        let dir = std::env::temp_dir().join(format!("seclog-duckdb-retry-{}", std::process::id()));
+        let config = test_config(&dir);
+        let mut writer = DuckDbWriter::new(&config).unwrap();
+        let event = |name: &str| test_event("cloudtrail", name, json!({ "eventName": name }));
+        let count = |conn: &duckdb::Connection| {
+            conn.query_row("SELECT count(*) FROM analytics.events", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .unwrap()
+        };
+
+        writer.write_event(&event("GetObject")).unwrap();
+        writer
+            .inner
+            .conn
+            .execute_batch("DROP TABLE analytics.events")
+            .unwrap();
+        let err = writer.write_event(&event("PutObject")).unwrap_err();
+        assert!(err.to_string().contains("analytics.events"), "{err}");
+        assert!(writer.flush().is_err());
+
+        let create = create_table_sql(&config.table, &build_schema(false)).unwrap();
+        writer.inner.conn.execute_batch(&create).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(count(&writer.inner.conn), 2);
+        writer.write_event(&event("ListBuckets")).unwrap();
+        writer.close().unwrap();
+        assert_eq!(count(&writer.inner.conn), 3);
+        drop(writer);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(not(feature = "duckdb"))]
+    #[test]
+    fn platform_writer_requires_feature() {
+        let err = DuckDbWriter::new(&test_config(Path::new(".")))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "duckdb output requires building with --features duckdb"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Output file naming.
+//!
+//! `output.files.filename_template` names the files the JSONL, Parquet, and
+//! text writers rotate out, so collectors that route on object names can be
+//! fed without renaming. Templates are relative paths with placeholders; a
+//! `/` in a template puts files in subdirectories of the writer's directory.
+
+use crate::core::traits::FileStats;
+use chrono::{DateTime, Utc};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name writers use when no template is configured.
+pub const DEFAULT_TEMPLATE: &str = "{account}_{source}_{region}_{ts}_{uuid}.{ext}";
+
+/// A parsed filename template.
+///
+/// Placeholders: `{account}`, `{region}`, `{source}` (the file's source label,
+/// e.g. `CloudTrail`), `{ts}` (`YYYYMMDDTHHMMZ`), `{uuid}` (random 16-character
+/// id), `{seq}` (the file's zero-padded sequence number within its
+/// source/account/region stream), `{min_ts}` and `{max_ts}` (the earliest and
+/// latest event time in the file, `YYYYMMDDTHHMMSSZ`), and `{ext}` (e.g.
+/// `json.gz`, `parquet`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilenameTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Account,
+    Region,
+    Source,
+    Ts,
+    Uuid,
+    Seq,
+    MinTs,
+    MaxTs,
+    Ext,
+}
+
+/// Values substituted into a template for one file.
+#[derive(Debug, Clone, Copy)]
+pub struct FileName<'a> {
+    pub source: &'a str,
+    pub account_id: &'a str,
+    pub region: &'a str,
+    pub ts: &'a str,
+    pub uuid: &'a str,
+    pub seq: u64,
+    pub ext: &'a str,
+    /// Events in the file, for `{min_ts}` and `{max_ts}`; files without event
+    /// times use `ts` instead.
+    pub stats: &'a FileStats,
+}
+
+impl FilenameTemplate {
+    /// Parses a template, rejecting unknown placeholders, templates that could
+    /// name two files the same (no `{uuid}` or `{seq}`), and paths that leave
+    /// the writer's directory.
+    pub fn parse(template: &str) -> io::Result<Self> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let Some(len) = rest[start..].find('}') else {
+                return Err(invalid(format!(
+                    "filename_template {template:?} has an unclosed '{{'"
+                )));
+            };
+            let part = match &rest[start + 1..start + len] {
+                "account" => Part::Account,
+                "region" => Part::Region,
+                "source" => Part::Source,
+                "ts" => Part::Ts,
+                "uuid" => Part::Uuid,
+                "seq" => Part::Seq,
+                "min_ts" => Part::MinTs,
+                "max_ts" => Part::MaxTs,
+                "ext" => Part::Ext,
+                other => {
+                    return Err(invalid(format!(
+                        "filename_template has unknown placeholder {{{other}}} (expected account, region, source, ts, uuid, seq, min_ts, max_ts, or ext)"
+                    )))
+                }
+            };
+            parts.push(part);
+            rest = &rest[start + len + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+
+        if !parts
+            .iter()
+            .any(|part| matches!(part, Part::Uuid | Part::Seq))
+        {
+            return Err(invalid(format!(
+                "filename_template {template:?} must include {{uuid}} or {{seq}} so rotated files get distinct names"
+            )));
+        }
+        if template.starts_with('/')
+            || template.ends_with('/')
+            || template
+                .split('/')
+                .any(|segment| segment.is_empty() || segment == "..")
+        {
+            return Err(invalid(format!(
+                "filename_template {template:?} must be a relative path inside the output directory"
+            )));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Renders the file name, relative to the writer's directory.
+    pub fn render(&self, name: &FileName) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Account => out.push_str(name.account_id),
+                Part::Region => out.push_str(name.region),
+                Part::Source => out.push_str(name.source),
+                Part::Ts => out.push_str(name.ts),
+                Part::Uuid => out.push_str(name.uuid),
+                Part::Seq => {
+                    let _ = write!(out, "{:06}", name.seq);
+                }
+                Part::MinTs => push_event_time(&mut out, name.stats.first_event_time, name.ts),
+                Part::MaxTs => push_event_time(&mut out, name.stats.last_event_time, name.ts),
+                Part::Ext => out.push_str(name.ext),
+            }
+        }
+        out
+    }
+
+    /// Path of the file under `dir`, creating any subdirectories the template
+    /// adds.
+    pub fn path(&self, dir: &Path, name: &FileName) -> io::Result<PathBuf> {
+        let path = dir.join(self.render(name));
+        if let Some(parent) = path.parent().filter(|parent| *parent != dir) {
+            fs::create_dir_all(parent)?;
+        }
+        Ok(path)
+    }
+}
+
+impl Default for FilenameTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TEMPLATE).expect("default filename template parses")
+    }
+}
+
+fn push_event_time(out: &mut String, time: Option<DateTime<Utc>>, fallback: &str) {
+    match time {
+        Some(time) => {
+            let _ = write!(out, "{}", time.format("%Y%m%dT%H%M%SZ"));
+        }
+        None => out.push_str(fallback),
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static NO_STATS: FileStats = FileStats {
+        events: 0,
+        first_event_time: None,
+        last_event_time: None,
+        out_of_order: 0,
+        previous_end: None,
+    };
+
+    fn name(seq: u64) -> FileName<'static> {
+        FileName {
+            source: "CloudTrail",
+            account_id: "123456789012",
+            region: "us-east-1",
+            ts: "20260101T0000Z",
+            uuid: "abcdefgh12345678",
+            seq,
+            ext: "json.gz",
+            stats: &NO_STATS,
+        }
+    }
+
+    #[test]
+    fn renders_placeholders_and_keeps_the_default_name() {
+        assert_eq!(
+            FilenameTemplate::default().render(&name(0)),
+            "123456789012_CloudTrail_us-east-1_20260101T0000Z_abcdefgh12345678.json.gz"
+        );
+        let template =
+            FilenameTemplate::parse("{source}/{account}/{region}/{ts}-{seq}.{ext}").unwrap();
+        assert_eq!(
+            template.render(&name(42)),
+            "CloudTrail/123456789012/us-east-1/20260101T0000Z-000042.json.gz"
+        );
+
+        let dir = std::env::temp_dir().join(format!("seclog-filename-{}", std::process::id()));
+        let path = template.path(&dir, &name(1)).unwrap();
+        assert!(path.parent().unwrap().is_dir());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn renders_the_event_time_range() {
+        let template = FilenameTemplate::parse("{source}_{min_ts}_{max_ts}_{seq}.{ext}").unwrap();
+        assert_eq!(
+            template.render(&name(0)),
+            "CloudTrail_20260101T0000Z_20260101T0000Z_000000.json.gz"
+        );
+        let stats = FileStats {
+            events: 2,
+            first_event_time: Some("2026-01-01T00:00:05Z".parse().unwrap()),
+            last_event_time: Some("2026-01-01T00:04:59Z".parse().unwrap()),
+            ..FileStats::default()
+        };
+        assert_eq!(
+            template.render(&FileName {
+                stats: &stats,
+                ..name(0)
+            }),
+            "CloudTrail_20260101T000005Z_20260101T000459Z_000000.json.gz"
+        );
+    }
+
+    #[test]
+    fn rejects_unusable_templates() {
+        for template in [
+            "{account}_{bucket}_{uuid}",
+            "{account}_{uuid",
+            "{account}_{region}.{ext}",
+            "/tmp/{uuid}.{ext}",
+            "../{uuid}.{ext}",
+            "{source}//{uuid}",
+        ] {
+            let err = FilenameTemplate::parse(template).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{template}");
+        }
+    }
+}
@@ -0,0 +1,925 @@
+//! Apache Iceberg table output.
+//!
+//! [`IcebergWriter`] wraps the Parquet writer of a table's `data/` directory
+//! and, whenever the inner writer finishes files, commits an append snapshot:
+//! a manifest listing the new files, a manifest list that carries every
+//! earlier manifest forward, and the next `metadata/v<N>.metadata.json`
+//! (format version 2) plus `version-hint.text`. Trino, Athena, and Spark read
+//! the directory as an Iceberg table once it is registered at its location.
+//! The Parquet files carry no field IDs, so the table sets
+//! `schema.name-mapping.default` to resolve columns by name.
+
+use super::avro::{put_bytes, put_long, put_str};
+use super::delta::{create_new, random_uuid};
+use super::parquet::build_schema;
+use crate::core::config::{IcebergOptions, OutputPartitioning};
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use arrow_schema::{DataType, Fields};
+use chrono::{NaiveDate, Utc};
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory holding data files under the table root.
+pub const DATA_DIR: &str = "data";
+/// Directory holding table metadata, manifest lists, and manifests.
+pub const METADATA_DIR: &str = "metadata";
+const VERSION_HINT: &str = "version-hint.text";
+const MAGIC: &[u8; 4] = b"Obj\x01";
+/// Iceberg numbers partition fields from 1000.
+const PARTITION_FIELD_ID: i32 = 1000;
+
+/// Time transform of the partition spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transform {
+    Day,
+    Hour,
+}
+
+impl Transform {
+    fn name(self) -> &'static str {
+        match self {
+            Transform::Day => "day",
+            Transform::Hour => "hour",
+        }
+    }
+}
+
+/// Writer wrapper that commits the Parquet files of its inner writer to an
+/// Iceberg table rooted at `root`.
+pub struct IcebergWriter {
+    inner: Box<dyn EventWriter>,
+    root: PathBuf,
+    /// Table location recorded in metadata; file paths are absolute under it.
+    location: String,
+    schema: Value,
+    last_column_id: i32,
+    name_mapping: String,
+    /// Field ID of `envelope.timestamp` when it is a timestamp column.
+    timestamp_field: Option<i32>,
+    transform: Option<Transform>,
+    /// Finished files not yet committed, kept across a failed commit.
+    pending: Vec<WrittenFile>,
+    /// Committed files not yet handed to `take_written_files`.
+    committed: Vec<WrittenFile>,
+}
+
+impl IcebergWriter {
+    /// Wraps `inner`, whose Parquet files are all written under `root/data` in
+    /// the Hive-style directories of `partitioning`.
+    pub fn new(
+        inner: Box<dyn EventWriter>,
+        root: impl Into<PathBuf>,
+        options: &IcebergOptions,
+        partitioning: OutputPartitioning,
+    ) -> io::Result<Self> {
+        let root = root.into();
+        let timestamp_strings = options.parquet.timestamp_strings.unwrap_or(false);
+        let transform = match partitioning {
+            OutputPartitioning::None => None,
+            OutputPartitioning::Date => Some(Transform::Day),
+            OutputPartitioning::Hour => Some(Transform::Hour),
+        };
+        if transform.is_some() && timestamp_strings {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "iceberg partitioning requires timestamp columns; unset timestamp_strings",
+            ));
+        }
+        let location = match &options.location {
+            Some(location) => location.trim_end_matches('/').to_string(),
+            None => {
+                fs::create_dir_all(&root)?;
+                format!("file://{}", fs::canonicalize(&root)?.display())
+            }
+        };
+
+        let mut schema = SchemaBuilder::default();
+        let (fields, mapping) = schema.convert(build_schema(timestamp_strings).fields(), "");
+        Ok(Self {
+            inner,
+            root,
+            location,
+            schema: json!({ "type": "struct", "schema-id": 0, "fields": fields }),
+            last_column_id: schema.next_id,
+            name_mapping: Value::Array(mapping).to_string(),
+            timestamp_field: schema.timestamp_field,
+            transform,
+            pending: Vec::new(),
+            committed: Vec::new(),
+        })
+    }
+
+    /// Commits every file the inner writer finished since the last commit as
+    /// one append snapshot.
+    fn commit(&mut self) -> io::Result<()> {
+        self.pending.extend(self.inner.take_written_files());
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let metadata_dir = self.root.join(METADATA_DIR);
+        fs::create_dir_all(&metadata_dir)?;
+        let manifest = self.write_manifest(&metadata_dir)?;
+        loop {
+            let current = latest_metadata(&metadata_dir)?;
+            let version = current.as_ref().map_or(0, |(version, _)| *version);
+            let metadata = current.map(|(_, metadata)| metadata);
+            let sequence_number = metadata
+                .as_ref()
+                .and_then(|metadata| metadata["last-sequence-number"].as_i64())
+                .unwrap_or(0)
+                + 1;
+            let parent_id = metadata
+                .as_ref()
+                .and_then(|metadata| metadata["current-snapshot-id"].as_i64())
+                .filter(|id| *id >= 0);
+            let snapshot_id = rand::random::<i64>() & i64::MAX;
+
+            let mut manifests =
+                match parent_id.and_then(|id| manifest_list_of(metadata.as_ref()?, id)) {
+                    Some(list) => read_manifest_list(&metadata_dir.join(file_name(&list)))?,
+                    None => Vec::new(),
+                };
+            manifests.push(ManifestFile {
+                sequence_number,
+                min_sequence_number: sequence_number,
+                added_snapshot_id: snapshot_id,
+                ..manifest.clone()
+            });
+            let list_name = format!("snap-{snapshot_id}-1-{}.avro", random_uuid());
+            let list_path = metadata_dir.join(&list_name);
+            fs::write(
+                &list_path,
+                manifest_list(&manifests, snapshot_id, parent_id, sequence_number),
+            )?;
+
+            let mut snapshot = json!({
+                "snapshot-id": snapshot_id,
+                "sequence-number": sequence_number,
+                "timestamp-ms": Utc::now().timestamp_millis(),
+                "manifest-list": format!("{}/{METADATA_DIR}/{list_name}", self.location),
+                "summary": {
+                    "operation": "append",
+                    "added-data-files": manifest.added_files.to_string(),
+                    "added-records": manifest.added_rows.to_string(),
+                },
+                "schema-id": 0,
+            });
+            if let Some(parent_id) = parent_id {
+                snapshot["parent-snapshot-id"] = json!(parent_id);
+            }
+            let next = match metadata {
+                Some(metadata) => self.next_metadata(metadata, version, snapshot),
+                None => self.initial_metadata(snapshot),
+            };
+            let contents = serde_json::to_vec_pretty(&next).map_err(io::Error::other)?;
+            let target = metadata_dir.join(format!("v{}.metadata.json", version + 1));
+            match create_new(&target, &contents) {
+                Ok(()) => {
+                    write_version_hint(&metadata_dir, version + 1)?;
+                    break;
+                }
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    fs::remove_file(&list_path)?;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        self.committed.append(&mut self.pending);
+        Ok(())
+    }
+
+    fn initial_metadata(&self, snapshot: Value) -> Value {
+        let now = snapshot["timestamp-ms"].clone();
+        let snapshot_id = snapshot["snapshot-id"].clone();
+        json!({
+            "format-version": 2,
+            "table-uuid": random_uuid(),
+            "location": self.location,
+            "last-sequence-number": snapshot["sequence-number"],
+            "last-updated-ms": now,
+            "last-column-id": self.last_column_id,
+            "current-schema-id": 0,
+            "schemas": [self.schema],
+            "default-spec-id": 0,
+            "partition-specs": [{ "spec-id": 0, "fields": self.partition_fields() }],
+            "last-partition-id": match self.transform {
+                Some(_) => PARTITION_FIELD_ID,
+                None => PARTITION_FIELD_ID - 1,
+            },
+            "default-sort-order-id": 0,
+            "sort-orders": [{ "order-id": 0, "fields": [] }],
+            "properties": {
+                "write.format.default": "parquet",
+                "schema.name-mapping.default": self.name_mapping,
+            },
+            "current-snapshot-id": snapshot_id,
+            "refs": { "main": { "snapshot-id": snapshot_id, "type": "branch" } },
+            "snapshots": [snapshot],
+            "snapshot-log": [{ "timestamp-ms": now, "snapshot-id": snapshot_id }],
+            "metadata-log": [],
+        })
+    }
+
+    fn next_metadata(&self, mut metadata: Value, version: u64, snapshot: Value) -> Value {
+        let now = snapshot["timestamp-ms"].clone();
+        let snapshot_id = snapshot["snapshot-id"].clone();
+        let previous = json!({
+            "timestamp-ms": metadata["last-updated-ms"],
+            "metadata-file": format!("{}/{METADATA_DIR}/v{version}.metadata.json", self.location),
+        });
+        metadata["last-sequence-number"] = snapshot["sequence-number"].clone();
+        metadata["last-updated-ms"] = now.clone();
+        metadata["current-snapshot-id"] = snapshot_id.clone();
+        metadata["refs"]["main"] = json!({ "snapshot-id": snapshot_id, "type": "branch" });
+        for (key, entry) in [
+            ("snapshots", snapshot),
+            (
+                "snapshot-log",
+                json!({ "timestamp-ms": now, "snapshot-id": snapshot_id }),
+            ),
+            ("metadata-log", previous),
+        ] {
+            match metadata[key].as_array_mut() {
+                Some(entries) => entries.push(entry),
+                None => metadata[key] = json!([entry]),
+            }
+        }
+        metadata
+    }
+
+    fn partition_fields(&self) -> Vec<Value> {
+        match (self.transform, self.timestamp_field) {
+            (Some(transform), Some(source_id)) => vec![json!({
+                "name": format!("envelope_timestamp_{}", transform.name()),
+                "transform": transform.name(),
+                "source-id": source_id,
+                "field-id": PARTITION_FIELD_ID,
+            })],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Writes the manifest for the pending files; sequence numbers and the
+    /// snapshot ID are inherited from the manifest list entry.
+    fn write_manifest(&self, metadata_dir: &Path) -> io::Result<ManifestFile> {
+        let mut records = Vec::new();
+        let mut added_rows = 0;
+        for file in &self.pending {
+            let size = fs::metadata(&file.path)?.len();
+            let relative = file.path.strip_prefix(&self.root).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} is outside the Iceberg table {}",
+                        file.path.display(),
+                        self.root.display()
+                    ),
+                )
+            })?;
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            added_rows += file.stats.events as i64;
+
+            put_long(&mut records, 1); // status: ADDED
+            for _ in 0..3 {
+                // snapshot_id, sequence_number, file_sequence_number: inherited
+                put_long(&mut records, 0);
+            }
+            put_long(&mut records, 0); // content: DATA
+            put_str(&mut records, &format!("{}/{relative}", self.location));
+            put_str(&mut records, "PARQUET");
+            if self.transform.is_some() {
+                match self.partition_value(Path::new(&relative)) {
+                    Some(value) => {
+                        put_long(&mut records, 1);
+                        put_long(&mut records, value);
+                    }
+                    None => put_long(&mut records, 0),
+                }
+            }
+            put_long(&mut records, file.stats.events as i64);
+            put_long(&mut records, size as i64);
+            let bounds = match (
+                self.timestamp_field,
+                file.stats.first_event_time,
+                file.stats.last_event_time,
+            ) {
+                (Some(field), Some(first), Some(last)) => Some((field, first, last)),
+                _ => None,
+            };
+            match bounds {
+                Some((field, first, last)) => {
+                    for time in [first, last] {
+                        put_long(&mut records, 1);
+                        put_long(&mut records, 1);
+                        put_long(&mut records, i64::from(field));
+                        put_bytes(&mut records, &time.timestamp_micros().to_le_bytes());
+                        put_long(&mut records, 0);
+                    }
+                }
+                None => {
+                    put_long(&mut records, 0);
+                    put_long(&mut records, 0);
+                }
+            }
+        }
+
+        let spec = Value::Array(self.partition_fields()).to_string();
+        let schema = self.schema.to_string();
+        let contents = container(
+            &self.manifest_schema(),
+            &[
+                ("schema", schema.as_str()),
+                ("schema-id", "0"),
+                ("partition-spec", spec.as_str()),
+                ("partition-spec-id", "0"),
+                ("format-version", "2"),
+                ("content", "data"),
+            ],
+            self.pending.len() as i64,
+            &records,
+        );
+        let name = format!("{}-m0.avro", random_uuid());
+        fs::write(metadata_dir.join(&name), &contents)?;
+        Ok(ManifestFile {
+            path: format!("{}/{METADATA_DIR}/{name}", self.location),
+            length: contents.len() as i64,
+            sequence_number: 0,
+            min_sequence_number: 0,
+            added_snapshot_id: 0,
+            added_files: self.pending.len() as i64,
+            existing_files: 0,
+            deleted_files: 0,
+            added_rows,
+            existing_rows: 0,
+            deleted_rows: 0,
+        })
+    }
+
+    /// Partition value of a data file, from its `date=`/`hour=` directories;
+    /// `None` for `__HIVE_DEFAULT_PARTITION__`.
+    fn partition_value(&self, relative: &Path) -> Option<i64> {
+        let mut date = None;
+        let mut hour = None;
+        for component in relative.parent()?.components() {
+            let segment = component.as_os_str().to_string_lossy();
+            match segment.split_once('=') {
+                Some(("date", value)) => date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+                Some(("hour", value)) => hour = value.parse::<i64>().ok(),
+                _ => {}
+            }
+        }
+        let days = (date? - NaiveDate::from_ymd_opt(1970, 1, 1)?).num_days();
+        match self.transform? {
+            Transform::Day => Some(days),
+            Transform::Hour => Some(days * 24 + hour?),
+        }
+    }
+
+    fn manifest_schema(&self) -> Value {
+        let partition_fields = match (self.transform, self.timestamp_field) {
+            (Some(transform), Some(_)) => {
+                let kind = match transform {
+                    Transform::Day => json!({ "type": "int", "logicalType": "date" }),
+                    Transform::Hour => json!("int"),
+                };
+                vec![json!({
+                    "name": format!("envelope_timestamp_{}", transform.name()),
+                    "type": ["null", kind],
+                    "default": null,
+                    "field-id": PARTITION_FIELD_ID,
+                })]
+            }
+            _ => Vec::new(),
+        };
+        let bounds = |name: &str, id: i32| {
+            json!({
+                "name": name,
+                "type": ["null", {
+                    "type": "array",
+                    "logicalType": "map",
+                    "items": {
+                        "type": "record",
+                        "name": format!("k{}_v{}", id + 1, id + 2),
+                        "fields": [
+                            { "name": "key", "type": "int", "field-id": id + 1 },
+                            { "name": "value", "type": "bytes", "field-id": id + 2 },
+                        ],
+                    },
+                }],
+                "default": null,
+                "field-id": id,
+            })
+        };
+        let optional_long = |name: &str, id: i32| json!({ "name": name, "type": ["null", "long"], "default": null, "field-id": id });
+        json!({
+            "type": "record",
+            "name": "manifest_entry",
+            "fields": [
+                { "name": "status", "type": "int", "field-id": 0 },
+                optional_long("snapshot_id", 1),
+                optional_long("sequence_number", 3),
+                optional_long("file_sequence_number", 4),
+                {
+                    "name": "data_file",
+                    "type": {
+                        "type": "record",
+                        "name": "r2",
+                        "fields": [
+                            { "name": "content", "type": "int", "field-id": 134 },
+                            { "name": "file_path", "type": "string", "field-id": 100 },
+                            { "name": "file_format", "type": "string", "field-id": 101 },
+                            {
+                                "name": "partition",
+                                "type": { "type": "record", "name": "r102", "fields": partition_fields },
+                                "field-id": 102,
+                            },
+                            { "name": "record_count", "type": "long", "field-id": 103 },
+                            { "name": "file_size_in_bytes", "type": "long", "field-id": 104 },
+                            bounds("lower_bounds", 125),
+                            bounds("upper_bounds", 128),
+                        ],
+                    },
+                    "field-id": 2,
+                },
+            ],
+        })
+    }
+}
+
+impl EventWriter for IcebergWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        self.inner.write_event(event)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.commit()
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync()?;
+        self.commit()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.inner.close()?;
+        self.commit()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.committed)
+    }
+}
+
+/// Returns true for Iceberg table metadata files, which are not event output.
+pub fn is_iceberg_metadata_file(path: &Path) -> bool {
+    path.parent()
+        .and_then(Path::file_name)
+        .is_some_and(|name| name == METADATA_DIR)
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".metadata.json"))
+}
+
+/// Assigns Iceberg field IDs depth-first while converting the Arrow schema.
+#[derive(Default)]
+struct SchemaBuilder {
+    next_id: i32,
+    timestamp_field: Option<i32>,
+}
+
+impl SchemaBuilder {
+    /// Returns the Iceberg fields and their name-mapping entries.
+    fn convert(&mut self, fields: &Fields, prefix: &str) -> (Vec<Value>, Vec<Value>) {
+        let mut converted = Vec::new();
+        let mut mapping = Vec::new();
+        for field in fields {
+            self.next_id += 1;
+            let id = self.next_id;
+            let path = format!("{prefix}{}", field.name());
+            let (kind, nested) = match field.data_type() {
+                DataType::Struct(children) => {
+                    let (children, nested) = self.convert(children, &format!("{path}."));
+                    (json!({ "type": "struct", "fields": children }), nested)
+                }
+                DataType::Timestamp(_, _) => {
+                    if path == "envelope.timestamp" {
+                        self.timestamp_field = Some(id);
+                    }
+                    (json!("timestamptz"), Vec::new())
+                }
+                DataType::Boolean => (json!("boolean"), Vec::new()),
+                DataType::Int32 => (json!("int"), Vec::new()),
+                DataType::Int64 => (json!("long"), Vec::new()),
+                DataType::Float64 => (json!("double"), Vec::new()),
+                _ => (json!("string"), Vec::new()),
+            };
+            converted.push(json!({
+                "id": id,
+                "name": field.name(),
+                "required": !field.is_nullable(),
+                "type": kind,
+            }));
+            let mut entry = json!({ "field-id": id, "names": [field.name()] });
+            if !nested.is_empty() {
+                entry["fields"] = Value::Array(nested);
+            }
+            mapping.push(entry);
+        }
+        (converted, mapping)
+    }
+}
+
+/// Entry of a manifest list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ManifestFile {
+    path: String,
+    length: i64,
+    sequence_number: i64,
+    min_sequence_number: i64,
+    added_snapshot_id: i64,
+    added_files: i64,
+    existing_files: i64,
+    deleted_files: i64,
+    added_rows: i64,
+    existing_rows: i64,
+    deleted_rows: i64,
+}
+
+impl ManifestFile {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_str(buf, &self.path);
+        put_long(buf, self.length);
+        put_long(buf, 0); // partition_spec_id
+        put_long(buf, 0); // content: DATA
+        for value in [
+            self.sequence_number,
+            self.min_sequence_number,
+            self.added_snapshot_id,
+            self.added_files,
+            self.existing_files,
+            self.deleted_files,
+            self.added_rows,
+            self.existing_rows,
+            self.deleted_rows,
+        ] {
+            put_long(buf, value);
+        }
+        put_long(buf, 0); // partitions: null
+    }
+
+    fn decode(reader: &mut Reader) -> io::Result<Self> {
+        let path = String::from_utf8(reader.bytes()?.to_vec()).map_err(io::Error::other)?;
+        let length = reader.long()?;
+        reader.long()?;
+        reader.long()?;
+        let mut values = [0; 9];
+        for value in &mut values {
+            *value = reader.long()?;
+        }
+        if reader.long()? != 0 {
+            return Err(invalid(
+                "manifest list partition summaries are not supported",
+            ));
+        }
+        let [sequence_number, min_sequence_number, added_snapshot_id, added_files, existing_files, deleted_files, added_rows, existing_rows, deleted_rows] =
+            values;
+        Ok(Self {
+            path,
+            length,
+            sequence_number,
+            min_sequence_number,
+            added_snapshot_id,
+            added_files,
+            existing_files,
+            deleted_files,
+            added_rows,
+            existing_rows,
+            deleted_rows,
+        })
+    }
+}
+
+fn manifest_list(
+    manifests: &[ManifestFile],
+    snapshot_id: i64,
+    parent_id: Option<i64>,
+    sequence_number: i64,
+) -> Vec<u8> {
+    let field =
+        |name: &str, kind: &str, id: i32| json!({ "name": name, "type": kind, "field-id": id });
+    let optional = |name: &str, kind: &str, id: i32| json!({ "name": name, "type": ["null", kind], "default": null, "field-id": id });
+    let schema = json!({
+        "type": "record",
+        "name": "manifest_file",
+        "fields": [
+            field("manifest_path", "string", 500),
+            field("manifest_length", "long", 501),
+            field("partition_spec_id", "int", 502),
+            field("content", "int", 517),
+            field("sequence_number", "long", 515),
+            field("min_sequence_number", "long", 516),
+            field("added_snapshot_id", "long", 503),
+            field("added_files_count", "int", 504),
+            field("existing_files_count", "int", 505),
+            field("deleted_files_count", "int", 506),
+            field("added_rows_count", "long", 512),
+            field("existing_rows_count", "long", 513),
+            field("deleted_rows_count", "long", 514),
+            {
+                "name": "partitions",
+                "type": ["null", {
+                    "type": "array",
+                    "items": {
+                        "type": "record",
+                        "name": "r508",
+                        "fields": [
+                            field("contains_null", "boolean", 509),
+                            optional("contains_nan", "boolean", 518),
+                            optional("lower_bound", "bytes", 510),
+                            optional("upper_bound", "bytes", 511),
+                        ],
+                    },
+                    "element-id": 508,
+                }],
+                "default": null,
+                "field-id": 507,
+            },
+        ],
+    });
+    let mut records = Vec::new();
+    for manifest in manifests {
+        manifest.encode(&mut records);
+    }
+    let snapshot_id = snapshot_id.to_string();
+    let parent_id = parent_id.map_or_else(|| "null".to_string(), |id| id.to_string());
+    let sequence_number = sequence_number.to_string();
+    container(
+        &schema,
+        &[
+            ("snapshot-id", snapshot_id.as_str()),
+            ("parent-snapshot-id", parent_id.as_str()),
+            ("sequence-number", sequence_number.as_str()),
+            ("format-version", "2"),
+        ],
+        manifests.len() as i64,
+        &records,
+    )
+}
+
+/// Reads a manifest list written by this writer (null codec, no partition summaries).
+fn read_manifest_list(path: &Path) -> io::Result<Vec<ManifestFile>> {
+    let data = fs::read(path)?;
+    let mut reader = Reader(
+        data.strip_prefix(MAGIC.as_slice())
+            .ok_or_else(|| invalid("not an Avro container"))?,
+    );
+    loop {
+        let count = reader.long()?;
+        if count == 0 {
+            break;
+        }
+        if count < 0 {
+            reader.long()?;
+        }
+        for _ in 0..count.unsigned_abs() {
+            reader.bytes()?;
+            reader.bytes()?;
+        }
+    }
+    reader.take(16)?;
+    let mut manifests = Vec::new();
+    while !reader.0.is_empty() {
+        let count = reader.long()?;
+        let mut block = Reader(reader.bytes()?);
+        reader.take(16)?;
+        for _ in 0..count {
+            manifests.push(ManifestFile::decode(&mut block)?);
+        }
+    }
+    Ok(manifests)
+}
+
+/// Avro object container with one uncompressed block.
+fn container(schema: &Value, metadata: &[(&str, &str)], count: i64, records: &[u8]) -> Vec<u8> {
+    let sync: [u8; 16] = rand::random();
+    let mut buf = MAGIC.to_vec();
+    put_long(&mut buf, metadata.len() as i64 + 2);
+    put_str(&mut buf, "avro.schema");
+    put_str(&mut buf, &schema.to_string());
+    put_str(&mut buf, "avro.codec");
+    put_str(&mut buf, "null");
+    for (key, value) in metadata {
+        put_str(&mut buf, key);
+        put_str(&mut buf, value);
+    }
+    put_long(&mut buf, 0);
+    buf.extend_from_slice(&sync);
+    if count > 0 {
+        put_long(&mut buf, count);
+        put_bytes(&mut buf, records);
+        buf.extend_from_slice(&sync);
+    }
+    buf
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(invalid("truncated Avro data"));
+        }
+        let (value, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(value)
+    }
+
+    fn long(&mut self) -> io::Result<i64> {
+        let mut value = 0_u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift > 63 {
+                return Err(invalid("malformed Avro long"));
+            }
+        }
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = usize::try_from(self.long()?).map_err(|_| invalid("negative Avro length"))?;
+        self.take(len)
+    }
+}
+
+/// Latest `v<N>.metadata.json` in the metadata directory and its contents.
+fn latest_metadata(metadata_dir: &Path) -> io::Result<Option<(u64, Value)>> {
+    let mut latest = None;
+    for entry in fs::read_dir(metadata_dir)? {
+        let name = entry?.file_name();
+        let version = name
+            .to_str()
+            .and_then(|name| name.strip_prefix('v'))
+            .and_then(|name| name.strip_suffix(".metadata.json"))
+            .and_then(|version| version.parse::<u64>().ok());
+        if version > latest {
+            latest = version;
+        }
+    }
+    let Some(version) = latest else {
+        return Ok(None);
+    };
+    let contents = fs::read(metadata_dir.join(format!("v{version}.metadata.json")))?;
+    let metadata = serde_json::from_slice(&contents).map_err(io::Error::other)?;
+    Ok(Some((version, metadata)))
+}
+
+fn manifest_list_of(metadata: &Value, snapshot_id: i64) -> Option<String> {
+    metadata["snapshots"]
+        .as_array()?
+        .iter()
+        .find(|snapshot| snapshot["snapshot-id"].as_i64() == Some(snapshot_id))?["manifest-list"]
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Last path segment of a location URI.
+fn file_name(location: &str) -> &str {
+    location.rsplit('/').next().unwrap_or(location)
+}
+
+fn write_version_hint(metadata_dir: &Path, version: u64) -> io::Result<()> {
+    let temp = metadata_dir.join(format!(".{VERSION_HINT}.{}.tmp", random_uuid()));
+    fs::write(&temp, version.to_string())?;
+    fs::rename(temp, metadata_dir.join(VERSION_HINT))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::ParquetOptions;
+    use crate::fixtures::cloudtrail_test_event;
+    use crate::formats::json::unique_id;
+    use crate::formats::parquet::ParquetWriter;
+    use crate::formats::partitioned::{hive_time_partition, PartitionedWriter};
+
+    fn writer(dir: &Path) -> IcebergWriter {
+        let data = dir.join(DATA_DIR);
+        let inner = PartitionedWriter::new(
+            &data,
+            |event| hive_time_partition(event, OutputPartitioning::Hour),
+            |dir| Ok(Box::new(ParquetWriter::new(dir, 64, None)?) as Box<dyn EventWriter>),
+        );
+        let options = IcebergOptions {
+            location: Some("s3://bucket/events/".to_string()),
+            parquet: ParquetOptions::default(),
+        };
+        IcebergWriter::new(Box::new(inner), dir, &options, OutputPartitioning::Hour)
+            .expect("writer")
+    }
+
+    #[test]
+    fn commits_append_snapshots_with_carried_manifests() {
+        let dir = std::env::temp_dir().join(format!("seclog-iceberg-{}", unique_id()));
+        for timestamps in [
+            ["2026-01-01T09:15:00Z", "2026-01-01T10:05:00Z"],
+            ["2026-01-01T11:15:00Z", "2026-01-01T11:45:00Z"],
+        ] {
+            let mut writer = writer(&dir);
+            for timestamp in timestamps {
+                writer
+                    .write_event(&cloudtrail_test_event("GetObject", timestamp))
+                    .expect("write");
+            }
+            writer.close().expect("close");
+        }
+
+        let metadata_dir = dir.join(METADATA_DIR);
+        assert_eq!(
+            fs::read_to_string(metadata_dir.join(VERSION_HINT)).unwrap(),
+            "2"
+        );
+        let (version, metadata) = latest_metadata(&metadata_dir).unwrap().unwrap();
+        assert_eq!(version, 2);
+        assert_eq!(metadata["format-version"], 2);
+        assert_eq!(metadata["location"], "s3://bucket/events");
+        assert_eq!(metadata["last-sequence-number"], 2);
+        assert_eq!(metadata["snapshots"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            metadata["metadata-log"][0]["metadata-file"],
+            "s3://bucket/events/metadata/v1.metadata.json"
+        );
+        let spec = &metadata["partition-specs"][0]["fields"][0];
+        assert_eq!(spec["transform"], "hour");
+        let schema_fields = metadata["schemas"][0]["fields"].as_array().unwrap();
+        let envelope = &schema_fields[0]["type"]["fields"];
+        let timestamp = envelope
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|field| field["name"] == "timestamp")
+            .unwrap();
+        assert_eq!(timestamp["type"], "timestamptz");
+        assert_eq!(spec["source-id"], timestamp["id"]);
+        let mapping: Value = serde_json::from_str(
+            metadata["properties"]["schema.name-mapping.default"]
+                .as_str()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(mapping[0]["names"], json!(["envelope"]));
+
+        let current = metadata["current-snapshot-id"].as_i64().unwrap();
+        let list = manifest_list_of(&metadata, current).unwrap();
+        let manifests = read_manifest_list(&metadata_dir.join(file_name(&list))).unwrap();
+        assert_eq!(manifests.len(), 2);
+        assert_eq!(manifests[0].sequence_number, 1);
+        assert_eq!(manifests[1].sequence_number, 2);
+        assert_eq!(manifests[1].added_snapshot_id, current);
+        assert_eq!(manifests.iter().map(|m| m.added_files).sum::<i64>(), 3);
+        assert_eq!(manifests.iter().map(|m| m.added_rows).sum::<i64>(), 4);
+        for manifest in &manifests {
+            let path = metadata_dir.join(file_name(&manifest.path));
+            assert_eq!(fs::metadata(path).unwrap().len() as i64, manifest.length);
+        }
+        assert!(is_iceberg_metadata_file(
+            &metadata_dir.join("v2.metadata.json")
+        ));
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn partition_values_follow_hive_directories() {
+        let dir = std::env::temp_dir().join(format!("seclog-iceberg-{}", unique_id()));
+        let writer = writer(&dir);
+        assert_eq!(
+            writer.partition_value(Path::new("data/date=1970-01-02/hour=03/file.parquet")),
+            Some(27)
+        );
+        assert_eq!(
+            writer.partition_value(Path::new(
+                "data/date=__HIVE_DEFAULT_PARTITION__/hour=__HIVE_DEFAULT_PARTITION__/file.parquet"
+            )),
+            None
+        );
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -2,17 +2,30 @@
 //!
 //! Writes JSON files per source/account/region and rotates by size or age.
 
-use crate::core::event::Event;
-use crate::core::traits::EventWriter;
-use chrono::Utc;
+use super::azure_monitor::azure_monitor_record;
+use super::buffer_budget::{BufferAccount, BufferBudget};
+use super::cloudtrail_digest::CloudTrailDigester;
+use super::filename::{FileName, FilenameTemplate};
+use crate::core::config::{AzureMonitorOptions, CloudTrailDigestOptions};
+use crate::core::event::{Actor, Event, EventEnvelope, Outcome};
+use crate::core::traits::{EventWriter, FileStats, WrittenFile};
+use crate::core::util::hex;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{self, Write};
+use std::io::{self, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 /// JSON writer that buffers source-native records per source/account/region.
@@ -21,15 +34,104 @@ pub struct JsonlWriter {
     target_size_bytes: u64,
     max_age: Option<Duration>,
     compression: JsonlCompression,
+    layout: RecordLayout,
     files: HashMap<RegionKey, RegionBuffer>,
+    digest: Option<CloudTrailDigester>,
+    /// Lays CloudTrail files out as CloudTrail delivers them to S3.
+    s3_layout: bool,
+    filename: FilenameTemplate,
+    written: Vec<WrittenFile>,
+    buffered: BufferAccount,
 }
 
 #[derive(Debug, Clone, Copy)]
-enum JsonlCompression {
+pub(crate) enum JsonlCompression {
     None,
     Gzip,
 }
 
+/// Contents of a file being buffered, compressed as it is appended so that
+/// [`FileBody::len`] tracks the size the file will have on disk rather than
+/// its uncompressed length.
+pub(crate) enum FileBody {
+    Plain(Vec<u8>),
+    Gzip(GzEncoder<Vec<u8>>),
+}
+
+impl FileBody {
+    pub(crate) fn new(compression: JsonlCompression) -> Self {
+        match compression {
+            JsonlCompression::None => FileBody::Plain(Vec::new()),
+            JsonlCompression::Gzip => {
+                FileBody::Gzip(GzEncoder::new(Vec::new(), Compression::default()))
+            }
+        }
+    }
+
+    pub(crate) fn compression(&self) -> JsonlCompression {
+        match self {
+            FileBody::Plain(_) => JsonlCompression::None,
+            FileBody::Gzip(_) => JsonlCompression::Gzip,
+        }
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            FileBody::Plain(buffer) => {
+                buffer.extend_from_slice(bytes);
+                Ok(())
+            }
+            FileBody::Gzip(encoder) => encoder.write_all(bytes),
+        }
+    }
+
+    /// Bytes of output so far. For gzip this trails the finished size by what
+    /// the encoder still holds, at most a few tens of KB.
+    pub(crate) fn len(&self) -> u64 {
+        match self {
+            FileBody::Plain(buffer) => buffer.len() as u64,
+            FileBody::Gzip(encoder) => encoder.get_ref().len() as u64,
+        }
+    }
+
+    /// Finishes the file, returning its bytes and leaving the body empty.
+    pub(crate) fn finish(&mut self) -> io::Result<Vec<u8>> {
+        match self {
+            FileBody::Plain(buffer) => Ok(std::mem::take(buffer)),
+            FileBody::Gzip(encoder) => {
+                std::mem::replace(encoder, GzEncoder::new(Vec::new(), Compression::default()))
+                    .finish()
+            }
+        }
+    }
+}
+
+/// Per-file record framing.
+enum RecordLayout {
+    /// Source-native payloads under `{"Records": [...]}`.
+    Records,
+    /// Azure Monitor export records under `{"records": [...]}`.
+    AzureMonitor(AzureMonitorOptions),
+}
+
+impl RecordLayout {
+    fn opening(&self) -> &'static [u8] {
+        match self {
+            RecordLayout::Records => b"{\"Records\":[",
+            RecordLayout::AzureMonitor(_) => b"{\"records\":[",
+        }
+    }
+
+    fn record_bytes(&self, event: &Event) -> io::Result<Vec<u8>> {
+        match self {
+            RecordLayout::Records => record_bytes_for_event(event),
+            RecordLayout::AzureMonitor(options) => {
+                serde_json::to_vec(&azure_monitor_record(event, options)).map_err(io::Error::other)
+            }
+        }
+    }
+}
+
 impl JsonlWriter {
     /// Creates a JSONL writer with size-based rotation and optional max age.
     pub fn new(
@@ -37,6 +139,39 @@ impl JsonlWriter {
         target_size_mb: u64,
         max_age_seconds: Option<u64>,
         compression: Option<&str>,
+    ) -> io::Result<Self> {
+        Self::with_layout(
+            dir,
+            target_size_mb,
+            max_age_seconds,
+            compression,
+            RecordLayout::Records,
+        )
+    }
+
+    /// Creates a writer that emits Azure Monitor diagnostic export files.
+    pub fn azure_monitor(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        options: AzureMonitorOptions,
+    ) -> io::Result<Self> {
+        let compression = options.compression.clone();
+        Self::with_layout(
+            dir,
+            target_size_mb,
+            max_age_seconds,
+            compression.as_deref(),
+            RecordLayout::AzureMonitor(options),
+        )
+    }
+
+    fn with_layout(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        compression: Option<&str>,
+        layout: RecordLayout,
     ) -> io::Result<Self> {
         let dir = dir.into();
         fs::create_dir_all(&dir)?;
@@ -53,35 +188,142 @@ impl JsonlWriter {
             target_size_bytes: target_size_mb.saturating_mul(1024 * 1024),
             max_age,
             compression,
+            layout,
             files: HashMap::new(),
+            digest: None,
+            s3_layout: false,
+            filename: FilenameTemplate::default(),
+            written: Vec::new(),
+            buffered: BufferAccount::default(),
         })
     }
+
+    /// Also writes CloudTrail digest files chaining the CloudTrail log files.
+    pub fn with_cloudtrail_digest(mut self, options: CloudTrailDigestOptions) -> Self {
+        let mut digest = CloudTrailDigester::new(self.dir.clone(), options);
+        digest.set_s3_layout(self.s3_layout);
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Writes CloudTrail files (and digests) under the key layout CloudTrail
+    /// uses in S3, `AWSLogs/<account>/CloudTrail/<region>/YYYY/MM/DD/`, dated
+    /// by the simulated event time. Files never span two days.
+    pub fn with_s3_layout(mut self) -> Self {
+        self.s3_layout = true;
+        if let Some(digest) = self.digest.as_mut() {
+            digest.set_s3_layout(true);
+        }
+        self
+    }
+
+    /// Counts buffered records against `budget`, flushing the oldest regions
+    /// when it is exceeded.
+    pub fn with_buffer_budget(mut self, budget: Arc<BufferBudget>) -> Self {
+        self.buffered.set_budget(budget);
+        self
+    }
+
+    /// Adds buffered records to `gauge`, the buffered-bytes metric of the pipeline.
+    pub fn with_buffer_gauge(mut self, gauge: Arc<AtomicU64>) -> Self {
+        self.buffered.set_gauge(gauge);
+        self
+    }
+
+    /// Names files with `template` instead of [`DEFAULT_TEMPLATE`](super::filename::DEFAULT_TEMPLATE).
+    pub fn with_filename_template(mut self, template: FilenameTemplate) -> Self {
+        self.filename = template;
+        self
+    }
+
+    /// Flushes the oldest regions while the shared buffer budget is exceeded.
+    fn enforce_budget(&mut self) -> io::Result<()> {
+        while self.buffered.over_budget() {
+            let Some(key) = self
+                .files
+                .iter()
+                .filter(|(_, region)| region.record_count > 0)
+                .min_by_key(|(_, region)| region.first_event_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            let region = self.files.get_mut(&key).expect("region exists");
+            self.written.extend(flush_region(
+                &self.dir,
+                &key,
+                region,
+                &self.filename,
+                self.digest.as_mut(),
+                &mut self.buffered,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Writes out every region holding records.
+    fn flush_all(&mut self) -> io::Result<()> {
+        for (key, region) in self.files.iter_mut() {
+            if region.record_count > 0 {
+                self.written.extend(flush_region(
+                    &self.dir,
+                    key,
+                    region,
+                    &self.filename,
+                    self.digest.as_mut(),
+                    &mut self.buffered,
+                )?);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl EventWriter for JsonlWriter {
     fn write_event(&mut self, event: &Event) -> io::Result<u64> {
-        let record_bytes = record_bytes_for_event(event)?;
+        let record_bytes = self.layout.record_bytes(event)?;
         let size = record_bytes.len() as u64;
 
         let context = file_context_from_event(event);
+        let date = if self.s3_layout && context.source == "CloudTrail" {
+            DateTime::parse_from_rfc3339(&event.envelope.timestamp)
+                .ok()
+                .map(|time| time.with_timezone(&Utc).date_naive())
+        } else {
+            None
+        };
         let key = RegionKey {
             source: context.source,
             account_id: context.account_id,
             region: context.region,
+            date,
         };
 
+        let hashed = self.digest.is_some() && key.source == "CloudTrail";
+        let compression = self.compression;
         let region = self
             .files
             .entry(key.clone())
-            .or_insert_with(RegionBuffer::new);
-        if region.current_size == 0 {
+            .or_insert_with(|| RegionBuffer::new(compression, hashed));
+        if region.record_count == 0 {
             region.first_event_at = Some(Instant::now());
         }
-        append_record(region, &record_bytes);
+        let before = region.current_size;
+        append_record(region, self.layout.opening(), &record_bytes)?;
+        self.buffered.add(region.current_size - before);
+        region.stats.record(event);
 
         if region.current_size >= self.target_size_bytes {
-            flush_region(&self.dir, &key, region, self.compression)?;
+            self.written.extend(flush_region(
+                &self.dir,
+                &key,
+                region,
+                &self.filename,
+                self.digest.as_mut(),
+                &mut self.buffered,
+            )?);
         }
+        self.enforce_budget()?;
 
         Ok(size)
     }
@@ -89,7 +331,7 @@ impl EventWriter for JsonlWriter {
     fn flush(&mut self) -> io::Result<()> {
         let now = Instant::now();
         for (key, region) in self.files.iter_mut() {
-            if region.current_size == 0 {
+            if region.record_count == 0 {
                 continue;
             }
             if let Some(max_age) = self.max_age {
@@ -104,25 +346,46 @@ impl EventWriter for JsonlWriter {
                     continue;
                 }
             }
-            flush_region(&self.dir, key, region, self.compression)?;
+            self.written.extend(flush_region(
+                &self.dir,
+                key,
+                region,
+                &self.filename,
+                self.digest.as_mut(),
+                &mut self.buffered,
+            )?);
+        }
+        if let Some(digest) = self.digest.as_mut() {
+            self.written.extend(digest.deliver(false)?);
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.flush_all()?;
+        if let Some(digest) = self.digest.as_mut() {
+            self.written.extend(digest.deliver(false)?);
         }
         Ok(())
     }
 
     fn close(&mut self) -> io::Result<()> {
-        for (key, region) in self.files.iter_mut() {
-            if region.current_size > 0 {
-                flush_region(&self.dir, key, region, self.compression)?;
-            }
+        self.flush_all()?;
+        if let Some(digest) = self.digest.as_mut() {
+            self.written.extend(digest.deliver(true)?);
         }
         Ok(())
     }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.written)
+    }
 }
 
-struct FileContext {
-    source: String,
-    account_id: String,
-    region: String,
+pub(crate) struct FileContext {
+    pub(crate) source: String,
+    pub(crate) account_id: String,
+    pub(crate) region: String,
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -130,89 +393,133 @@ struct RegionKey {
     source: String,
     account_id: String,
     region: String,
+    /// Simulated event date, set when writing the S3 key layout.
+    date: Option<NaiveDate>,
 }
 
 struct RegionBuffer {
+    /// Size the file would have on disk if closed now.
     current_size: u64,
-    buffer: Vec<u8>,
+    body: FileBody,
+    /// Hash of the uncompressed file so far, kept for CloudTrail digests.
+    hasher: Option<Sha256>,
     first_event_at: Option<Instant>,
     record_count: u64,
+    stats: FileStats,
+    /// Files written for this key so far, for `{seq}`.
+    files: u64,
 }
 
 impl RegionBuffer {
-    fn new() -> Self {
+    fn new(compression: JsonlCompression, hashed: bool) -> Self {
         Self {
             current_size: 0,
-            buffer: Vec::new(),
+            body: FileBody::new(compression),
+            hasher: hashed.then(Sha256::new),
             first_event_at: None,
             record_count: 0,
+            stats: FileStats::default(),
+            files: 0,
         }
     }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(bytes);
+        }
+        self.body.write(bytes)
+    }
 }
 
-fn record_bytes_for_event(event: &Event) -> io::Result<Vec<u8>> {
+/// Top-level key that carries `envelope.tenant` in a source-native record.
+/// Only written in multi-tenant runs; readers move it back to the envelope.
+pub const TENANT_KEY: &str = "seclog_tenant";
+
+pub(crate) fn record_bytes_for_event(event: &Event) -> io::Result<Vec<u8>> {
     if event.payload.is_null() {
-        serde_json::to_vec(event).map_err(|err| io::Error::new(io::ErrorKind::Other, err))
-    } else {
-        serde_json::to_vec(&event.payload).map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+        return serde_json::to_vec(event).map_err(|err| io::Error::new(io::ErrorKind::Other, err));
+    }
+    let mut bytes = serde_json::to_vec(&event.payload)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    if let (Some(tenant), Some(b'{')) = (&event.envelope.tenant, bytes.first()) {
+        // Spliced in after `{` rather than inserted into a clone of the payload.
+        let mut field = serde_json::to_vec(&serde_json::json!({ TENANT_KEY: tenant }))
+            .map_err(io::Error::other)?;
+        field.pop();
+        if bytes.len() > 2 {
+            field.push(b',');
+        }
+        bytes.splice(0..1, field);
     }
+    Ok(bytes)
 }
 
-fn append_record(region: &mut RegionBuffer, record_bytes: &[u8]) {
+fn append_record(region: &mut RegionBuffer, opening: &[u8], record_bytes: &[u8]) -> io::Result<()> {
     if region.record_count == 0 {
-        region.buffer.extend_from_slice(b"{\"Records\":[");
+        region.write(opening)?;
     } else {
-        region.buffer.push(b',');
+        region.write(b",")?;
     }
-    region.buffer.extend_from_slice(record_bytes);
+    region.write(record_bytes)?;
     region.record_count += 1;
-    region.current_size = region.buffer.len() as u64 + 2;
+    region.current_size = region.body.len() + 2;
+    Ok(())
 }
 
 fn open_region_file(
     dir: &Path,
     key: &RegionKey,
     compression: JsonlCompression,
-) -> io::Result<File> {
-    let stamp = current_stamp();
+    filename: &FilenameTemplate,
+    seq: u64,
+    stats: &FileStats,
+) -> io::Result<(File, PathBuf)> {
     let unique = unique_id();
     let ext = match compression {
         JsonlCompression::None => "json",
         JsonlCompression::Gzip => "json.gz",
     };
-    let file = open_file(
-        dir,
-        &key.source,
-        &key.account_id,
-        &key.region,
-        &stamp,
-        &unique,
-        ext,
+    let (dir, stamp) = match key.date {
+        Some(date) => {
+            let dir = dir
+                .join("AWSLogs")
+                .join(&key.account_id)
+                .join(&key.source)
+                .join(&key.region)
+                .join(date.format("%Y/%m/%d").to_string());
+            fs::create_dir_all(&dir)?;
+            // The stamp is the (simulated) delivery time, on the file's day.
+            let stamp = stats
+                .last_event_time
+                .filter(|time| time.date_naive() == date)
+                .map(|time| time.format("%Y%m%dT%H%MZ").to_string())
+                .unwrap_or_else(|| format!("{}T2359Z", date.format("%Y%m%d")));
+            (dir, stamp)
+        }
+        None => (dir.to_path_buf(), current_stamp()),
+    };
+    let path = filename.path(
+        &dir,
+        &FileName {
+            source: &key.source,
+            account_id: &key.account_id,
+            region: &key.region,
+            ts: &stamp,
+            uuid: &unique,
+            seq,
+            ext,
+            stats,
+        },
     )?;
-    Ok(file)
+    Ok((File::create(&path)?, path))
 }
 
-fn open_file(
-    dir: &Path,
-    source: &str,
-    account_id: &str,
-    region: &str,
-    stamp: &str,
-    unique: &str,
-    ext: &str,
-) -> io::Result<File> {
-    let path = dir.join(format!(
-        "{account_id}_{source}_{region}_{stamp}_{unique}.{ext}"
-    ));
-    File::create(path)
-}
-
-fn current_stamp() -> String {
+pub(crate) fn current_stamp() -> String {
     let now = Utc::now();
     format!("{}", now.format("%Y%m%dT%H%MZ"))
 }
 
-fn unique_id() -> String {
+pub(crate) fn unique_id() -> String {
     rand::thread_rng()
         .sample_iter(&Alphanumeric)
         .take(16)
@@ -221,60 +528,73 @@ fn unique_id() -> String {
         .to_lowercase()
 }
 
-fn file_context_from_event(event: &Event) -> FileContext {
-    let source = source_file_label(&event.envelope.source);
+pub(crate) fn file_context_from_event(event: &Event) -> FileContext {
+    let (account_id, region) = file_key(event);
+    FileContext {
+        source: source_file_label(&event.envelope.source),
+        account_id: account_id.to_string(),
+        region: region.to_string(),
+    }
+}
+
+/// Account and region that file writers group an event's output by. Writer
+/// sharding uses the same key, so each file stream has one owning shard.
+pub(crate) fn file_key(event: &Event) -> (&str, &str) {
     let account_id = event
         .envelope
         .tenant_id
-        .clone()
-        .unwrap_or_else(|| "000000000000".to_string());
+        .as_deref()
+        .unwrap_or("000000000000");
     let region = event
         .payload
         .get("awsRegion")
         .or_else(|| event.payload.get("aws_region"))
+        .or_else(|| event.payload.get("region"))
         .and_then(|value| value.as_str())
-        .unwrap_or("global")
-        .to_string();
-
-    FileContext {
-        source,
-        account_id,
-        region,
-    }
+        .unwrap_or("global");
+    (account_id, region)
 }
 
 fn flush_region(
     dir: &Path,
     key: &RegionKey,
     region: &mut RegionBuffer,
-    compression: JsonlCompression,
-) -> io::Result<()> {
-    if region.current_size == 0 {
-        return Ok(());
+    filename: &FilenameTemplate,
+    digest: Option<&mut CloudTrailDigester>,
+    buffered: &mut BufferAccount,
+) -> io::Result<Option<WrittenFile>> {
+    if region.record_count == 0 {
+        return Ok(None);
     }
+    region.write(b"]}")?;
+    // Digests hash the uncompressed log file.
+    let hash = region
+        .hasher
+        .replace(Sha256::new())
+        .map(|hasher| hex(&hasher.finalize()));
+    let digest = digest.zip(hash);
 
-    let file = open_region_file(dir, key, compression)?;
-    match compression {
-        JsonlCompression::None => {
-            let mut file = file;
-            file.write_all(&region.buffer)?;
-            file.write_all(b"]}")?;
-        }
-        JsonlCompression::Gzip => {
-            let mut encoder = GzEncoder::new(file, Compression::default());
-            encoder.write_all(&region.buffer)?;
-            encoder.write_all(b"]}")?;
-            encoder.finish()?;
-        }
-    }
-    region.buffer.clear();
+    let compression = region.body.compression();
+    let (mut file, path) =
+        open_region_file(dir, key, compression, filename, region.files, &region.stats)?;
+    region.files += 1;
+    file.write_all(&region.body.finish()?)?;
+    buffered.release(region.current_size);
     region.current_size = 0;
     region.first_event_at = None;
     region.record_count = 0;
-    Ok(())
+    let written = WrittenFile {
+        path,
+        source: key.source.clone(),
+        stats: region.stats.rotate(),
+    };
+    if let Some((digest, hash)) = digest {
+        digest.record(&key.account_id, &key.region, &written, hash);
+    }
+    Ok(Some(written))
 }
 
-fn parse_compression(value: Option<&str>) -> io::Result<JsonlCompression> {
+pub(crate) fn parse_compression(value: Option<&str>) -> io::Result<JsonlCompression> {
     let Some(value) = value else {
         return Ok(JsonlCompression::None);
     };
@@ -291,11 +611,20 @@ fn parse_compression(value: Option<&str>) -> io::Result<JsonlCompression> {
     }
 }
 
-fn source_file_label(source: &str) -> String {
+pub(crate) fn source_file_label(source: &str) -> String {
     match source {
+        "alb_access_log" => "AlbAccessLog".to_string(),
         "cloudtrail" => "CloudTrail".to_string(),
         "databricks_audit" => "DatabricksAudit".to_string(),
+        "entra_audit" => "EntraAudit".to_string(),
+        "entra_signin" => "EntraSignIn".to_string(),
+        "github_audit" => "GitHubAudit".to_string(),
+        "google_workspace" => "GoogleWorkspace".to_string(),
+        "kubernetes_audit" => "KubernetesAudit".to_string(),
         "okta_system_log" => "OktaSystemLog".to_string(),
+        "route53_resolver" => "Route53Resolver".to_string(),
+        "s3_access_log" => "S3AccessLog".to_string(),
+        "windows_security" => "WindowsSecurity".to_string(),
         other => other
             .chars()
             .map(|ch| {
@@ -308,3 +637,626 @@ fn source_file_label(source: &str) -> String {
             .collect(),
     }
 }
+
+/// Reads a JSON output file back into source-native records, in file order.
+///
+/// Accepts `{"Records": [...]}` files, Azure Monitor `{"records": [...]}`
+/// exports (each record's `properties`), CloudWatch Logs Firehose files, and
+/// newline-delimited JSON. Files ending in `.gz` are decompressed.
+pub fn read_records(path: impl AsRef<Path>) -> io::Result<Vec<Value>> {
+    stream_records(path)?.collect()
+}
+
+/// Like [`read_records`], but reads one top-level JSON value at a time, so a
+/// JSONL file is never held in memory whole. A framed file is still one value.
+pub fn stream_records(
+    path: impl AsRef<Path>,
+) -> io::Result<impl Iterator<Item = io::Result<Value>>> {
+    let path = path.as_ref();
+    let file = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(RecordStream {
+        values: serde_json::Deserializer::from_reader(reader).into_iter(),
+        pending: Vec::new().into_iter(),
+    })
+}
+
+struct RecordStream<R: Read> {
+    values: serde_json::StreamDeserializer<'static, serde_json::de::IoRead<R>, Value>,
+    /// Records unwrapped from the last framed value.
+    pending: std::vec::IntoIter<Value>,
+}
+
+impl<R: Read> Iterator for RecordStream<R> {
+    type Item = io::Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(record) = self.pending.next() {
+                return Some(Ok(record));
+            }
+            let value = match self.values.next()? {
+                Ok(value) => value,
+                Err(err) => return Some(Err(io::Error::other(err))),
+            };
+            let mut records = Vec::new();
+            if let Err(err) = unwrap_framing(value, &mut records) {
+                return Some(Err(err));
+            }
+            self.pending = records.into_iter();
+        }
+    }
+}
+
+fn unwrap_framing(value: Value, records: &mut Vec<Value>) -> io::Result<()> {
+    match value {
+        Value::Object(mut object) => {
+            if let Some(Value::Array(rows)) = object.remove("Records") {
+                records.extend(rows);
+            } else if let Some(Value::Array(rows)) = object.remove("records") {
+                for mut row in rows {
+                    if let Some(data) = row.get("data").and_then(Value::as_str) {
+                        read_firehose_record(data, records)?;
+                    } else if let Some(properties) = row.get_mut("properties") {
+                        records.push(properties.take());
+                    } else {
+                        records.push(row);
+                    }
+                }
+            } else {
+                records.push(Value::Object(object));
+            }
+        }
+        other => records.push(other),
+    }
+    Ok(())
+}
+
+/// Decodes a base64 gzip CloudWatch Logs `DATA_MESSAGE` into its log event messages.
+fn read_firehose_record(data: &str, records: &mut Vec<Value>) -> io::Result<()> {
+    let compressed = STANDARD.decode(data).map_err(io::Error::other)?;
+    let message: Value =
+        serde_json::from_reader(GzDecoder::new(compressed.as_slice())).map_err(io::Error::other)?;
+    for log_event in message["logEvents"].as_array().into_iter().flatten() {
+        if let Some(text) = log_event["message"].as_str() {
+            records.push(serde_json::from_str(text).map_err(io::Error::other)?);
+        }
+    }
+    Ok(())
+}
+
+/// Copies a JSON output file to `output`, keeping only the records `keep` accepts.
+///
+/// Framing is preserved: `{"Records": [...]}`, Azure Monitor, and CloudWatch
+/// Logs Firehose wrappers keep their shape, and `.gz` files stay compressed.
+/// Wrappers left without records are dropped. Returns the number of records
+/// read and kept; nothing is written when none are kept.
+pub fn filter_records(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    mut keep: impl FnMut(&Value) -> io::Result<bool>,
+) -> io::Result<(u64, u64)> {
+    let input = input.as_ref();
+    let file = BufReader::new(File::open(input)?);
+    let reader: Box<dyn Read> = if input.extension().is_some_and(|ext| ext == "gz") {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let (mut read, mut kept) = (0, 0);
+    let mut counted = |record: &Value| {
+        read += 1;
+        let accepted = keep(record)?;
+        kept += u64::from(accepted);
+        Ok(accepted)
+    };
+    let mut buffer = Vec::new();
+    for value in serde_json::Deserializer::from_reader(reader).into_iter::<Value>() {
+        if let Some(value) = retain_framed(value.map_err(io::Error::other)?, &mut counted)? {
+            serde_json::to_writer(&mut buffer, &value).map_err(io::Error::other)?;
+            buffer.push(b'\n');
+        }
+    }
+    if kept == 0 {
+        return Ok((read, kept));
+    }
+
+    let output = output.as_ref();
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::create(output)?;
+    if output.extension().is_some_and(|ext| ext == "gz") {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(&buffer)?;
+        encoder.finish()?;
+    } else {
+        let mut file = file;
+        file.write_all(&buffer)?;
+    }
+    Ok((read, kept))
+}
+
+/// Filters the records inside one top-level value the way [`unwrap_framing`]
+/// reads them; `None` when nothing is left.
+fn retain_framed(
+    mut value: Value,
+    keep: &mut impl FnMut(&Value) -> io::Result<bool>,
+) -> io::Result<Option<Value>> {
+    if let Some(Value::Array(rows)) = value.get_mut("Records") {
+        retain_rows(rows, |row| keep(row))?;
+        let remaining = rows.len();
+        return Ok((remaining > 0).then_some(value));
+    }
+    if let Some(Value::Array(rows)) = value.get_mut("records") {
+        retain_rows(rows, |row| {
+            let Some(data) = row.get("data").and_then(Value::as_str) else {
+                return keep(row.get("properties").unwrap_or(row));
+            };
+            match retain_firehose_record(data, &mut *keep)? {
+                Some(data) => {
+                    row["data"] = Value::String(data);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })?;
+        let remaining = rows.len();
+        return Ok((remaining > 0).then_some(value));
+    }
+    Ok(keep(&value)?.then_some(value))
+}
+
+fn retain_rows(
+    rows: &mut Vec<Value>,
+    mut keep: impl FnMut(&mut Value) -> io::Result<bool>,
+) -> io::Result<()> {
+    let mut kept = Vec::with_capacity(rows.len());
+    for mut row in rows.drain(..) {
+        if keep(&mut row)? {
+            kept.push(row);
+        }
+    }
+    *rows = kept;
+    Ok(())
+}
+
+/// Re-encodes a CloudWatch Logs `DATA_MESSAGE` with only the accepted log
+/// events; `None` when none are left.
+fn retain_firehose_record(
+    data: &str,
+    keep: &mut impl FnMut(&Value) -> io::Result<bool>,
+) -> io::Result<Option<String>> {
+    let compressed = STANDARD.decode(data).map_err(io::Error::other)?;
+    let mut message: Value =
+        serde_json::from_reader(GzDecoder::new(compressed.as_slice())).map_err(io::Error::other)?;
+    let Some(Value::Array(log_events)) = message.get_mut("logEvents") else {
+        return Ok(None);
+    };
+    retain_rows(log_events, |log_event| {
+        match log_event["message"].as_str() {
+            Some(text) => keep(&serde_json::from_str(text).map_err(io::Error::other)?),
+            None => Ok(false),
+        }
+    })?;
+    if log_events.is_empty() {
+        return Ok(None);
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, &message).map_err(io::Error::other)?;
+    Ok(Some(STANDARD.encode(encoder.finish()?)))
+}
+
+/// Identifies the generator that produced a source-native record.
+pub fn record_source(record: &Value) -> Option<&'static str> {
+    let has = |key: &str| record.get(key).is_some();
+    if has("eventSource") && has("eventName") {
+        Some("cloudtrail")
+    } else if has("eventType") && has("published") {
+        Some("okta_system_log")
+    } else if has("service_name") && has("action_name") {
+        Some("databricks_audit")
+    } else if has("_document_id") && has("action") {
+        Some("github_audit")
+    } else if has("userPrincipalName") && has("appDisplayName") {
+        Some("entra_signin")
+    } else if has("bucket_owner") && has("operation") {
+        Some("s3_access_log")
+    } else if has("elb") && has("request_verb") {
+        Some("alb_access_log")
+    } else if has("query_name") && has("srcids") {
+        Some("route53_resolver")
+    } else if has("auditID") && has("stage") {
+        Some("kubernetes_audit")
+    } else if has("System") && has("EventData") {
+        Some("windows_security")
+    } else if record.get("kind").and_then(Value::as_str) == Some("admin#reports#activity") {
+        Some("google_workspace")
+    } else {
+        None
+    }
+}
+
+/// Rebuilds an [`Event`] from a record returned by [`read_records`].
+///
+/// Whole events (written when the payload was null) are decoded as-is. For
+/// source-native records the envelope is derived from the payload the same
+/// way the generators fill it, except that `actor.id` falls back to the
+/// record's principal ARN, alternate ID, user principal name, email, GitHub login, resolver source
+/// instance, Kubernetes username, Windows `DOMAIN\account`, or Workspace actor email. Unrecognized records keep
+/// `source = "unknown"`. A [`TENANT_KEY`] field moves from the record to
+/// `envelope.tenant`.
+pub fn event_from_record(mut record: Value) -> io::Result<Event> {
+    if record.get("envelope").is_some() {
+        return serde_json::from_value(record).map_err(io::Error::other);
+    }
+    let tenant = record
+        .as_object_mut()
+        .and_then(|record| record.remove(TENANT_KEY))
+        .and_then(|tenant| tenant.as_str().map(str::to_string));
+    let text = |pointer: &str| {
+        record
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let source = record_source(&record);
+    let (event_type, timestamp, actor, failed, ip, user_agent, session_id, tenant_id) = match source
+    {
+        Some("cloudtrail") => (
+            text("/eventName"),
+            text("/eventTime"),
+            Actor {
+                id: text("/userIdentity/arn")
+                    .or_else(|| text("/userIdentity/principalId"))
+                    .unwrap_or_default(),
+                kind: text("/userIdentity/type").unwrap_or_default(),
+                name: text("/userIdentity/userName"),
+            },
+            record.get("errorCode").is_some_and(|code| !code.is_null()),
+            text("/sourceIPAddress"),
+            text("/userAgent"),
+            None,
+            text("/recipientAccountId"),
+        ),
+        Some("okta_system_log") => (
+            text("/eventType"),
+            text("/published"),
+            Actor {
+                id: text("/actor/alternateId")
+                    .or_else(|| text("/actor/id"))
+                    .unwrap_or_default(),
+                kind: text("/actor/type").unwrap_or_default(),
+                name: text("/actor/displayName"),
+            },
+            matches!(
+                record.pointer("/outcome/result").and_then(Value::as_str),
+                Some("FAILURE" | "DENY")
+            ),
+            text("/client/ipAddress"),
+            text("/client/userAgent/rawUserAgent"),
+            text("/authenticationContext/externalSessionId"),
+            None,
+        ),
+        Some("github_audit") => (
+            text("/action"),
+            record
+                .get("@timestamp")
+                .and_then(Value::as_i64)
+                .and_then(DateTime::from_timestamp_millis)
+                .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            Actor {
+                id: text("/actor").unwrap_or_default(),
+                kind: String::new(),
+                name: None,
+            },
+            record.get("conclusion").and_then(Value::as_str) == Some("failure"),
+            text("/actor_ip"),
+            text("/user_agent"),
+            None,
+            text("/org"),
+        ),
+        Some("entra_signin") => (
+            Some("Sign-in activity".to_string()),
+            text("/createdDateTime"),
+            Actor {
+                id: text("/userPrincipalName").unwrap_or_default(),
+                kind: "User".to_string(),
+                name: text("/userPrincipalName"),
+            },
+            record
+                .pointer("/status/errorCode")
+                .and_then(Value::as_u64)
+                .is_some_and(|code| code != 0),
+            text("/ipAddress"),
+            text("/userAgent"),
+            text("/correlationId"),
+            text("/homeTenantId"),
+        ),
+        Some("s3_access_log") => (
+            text("/operation"),
+            text("/time").and_then(|time| access_log_time(&time)),
+            Actor {
+                id: text("/requester").unwrap_or_default(),
+                kind: String::new(),
+                name: None,
+            },
+            record
+                .get("http_status")
+                .and_then(Value::as_u64)
+                .is_some_and(|status| status >= 400),
+            text("/remote_ip"),
+            text("/user_agent"),
+            None,
+            None,
+        ),
+        Some("alb_access_log") => (
+            text("/request_verb"),
+            text("/time"),
+            Actor {
+                id: String::new(),
+                kind: String::new(),
+                name: None,
+            },
+            record
+                .get("elb_status_code")
+                .and_then(Value::as_u64)
+                .is_some_and(|status| status >= 400),
+            text("/client_ip"),
+            text("/user_agent"),
+            None,
+            None,
+        ),
+        Some("route53_resolver") => (
+            text("/query_type"),
+            text("/query_timestamp"),
+            Actor {
+                id: text("/srcids/instance").unwrap_or_default(),
+                kind: String::new(),
+                name: None,
+            },
+            record.get("rcode").and_then(Value::as_str) != Some("NOERROR"),
+            text("/srcaddr"),
+            None,
+            None,
+            text("/account_id"),
+        ),
+        Some("kubernetes_audit") => (
+            kubernetes_event_type(&record),
+            text("/stageTimestamp"),
+            Actor {
+                id: text("/user/username").unwrap_or_default(),
+                kind: String::new(),
+                name: None,
+            },
+            record
+                .pointer("/responseStatus/code")
+                .and_then(Value::as_u64)
+                .is_some_and(|code| code >= 400),
+            text("/sourceIPs/0"),
+            text("/userAgent"),
+            None,
+            None,
+        ),
+        Some("windows_security") => (
+            record
+                .pointer("/System/EventID")
+                .and_then(Value::as_u64)
+                .map(|event_id| event_id.to_string()),
+            text("/System/TimeCreated/SystemTime"),
+            Actor {
+                id: windows_account(&record).unwrap_or_default(),
+                kind: String::new(),
+                name: None,
+            },
+            record.pointer("/System/EventID").and_then(Value::as_u64) == Some(4625),
+            text("/EventData/IpAddress").filter(|ip| ip != "-"),
+            None,
+            text("/EventData/TargetLogonId")
+                .filter(|id| id != "0x0")
+                .or_else(|| text("/EventData/SubjectLogonId")),
+            None,
+        ),
+        Some("google_workspace") => (
+            text("/events/0/name"),
+            text("/id/time"),
+            Actor {
+                id: text("/actor/email").unwrap_or_default(),
+                kind: String::new(),
+                name: None,
+            },
+            record.pointer("/events/0/name").and_then(Value::as_str) == Some("login_failure"),
+            text("/ipAddress"),
+            None,
+            None,
+            text("/id/customerId"),
+        ),
+        Some("databricks_audit") => (
+            text("/action_name"),
+            text("/event_time"),
+            Actor {
+                id: text("/user_identity/email").unwrap_or_default(),
+                kind: String::new(),
+                name: None,
+            },
+            record
+                .pointer("/response/status_code")
+                .and_then(Value::as_i64)
+                .is_some_and(|status| status >= 400),
+            text("/source_ip_address"),
+            text("/user_agent"),
+            text("/session_id"),
+            text("/account_id"),
+        ),
+        _ => (
+            text("/eventType").or_else(|| text("/event_type")),
+            text("/time").or_else(|| text("/timestamp")),
+            Actor {
+                id: String::new(),
+                kind: String::new(),
+                name: None,
+            },
+            false,
+            None,
+            None,
+            None,
+            None,
+        ),
+    };
+    Ok(Event {
+        envelope: EventEnvelope {
+            schema_version: "v1".to_string(),
+            timestamp: timestamp.unwrap_or_default(),
+            source: source.unwrap_or("unknown").to_string(),
+            event_type: event_type.unwrap_or_default(),
+            actor,
+            target: None,
+            outcome: if failed {
+                Outcome::Failure
+            } else {
+                Outcome::Success
+            },
+            geo: None,
+            ip,
+            user_agent,
+            session_id,
+            tenant_id,
+            tenant,
+        },
+        payload: record,
+        label: None,
+    })
+}
+
+/// `<resource>[/<subresource>].<verb>`, matching the generator's event type.
+pub(crate) fn kubernetes_event_type(record: &Value) -> Option<String> {
+    let object = record.get("objectRef")?;
+    let resource = object.get("resource")?.as_str()?;
+    let verb = record.get("verb")?.as_str()?;
+    Some(match object.get("subresource").and_then(Value::as_str) {
+        Some(subresource) => format!("{resource}/{subresource}.{verb}"),
+        None => format!("{resource}.{verb}"),
+    })
+}
+
+/// `DOMAIN\account` a Windows Security event is about: the logon target for
+/// 4624/4625, otherwise the subject.
+pub(crate) fn windows_account(record: &Value) -> Option<String> {
+    let field = |name: &str| {
+        record
+            .pointer(&format!("/EventData/{name}"))
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty() && *value != "-")
+    };
+    let prefix = match record.pointer("/System/EventID").and_then(Value::as_u64) {
+        Some(4624 | 4625) => "Target",
+        _ => "Subject",
+    };
+    let name = field(&format!("{prefix}UserName"))?;
+    Some(match field(&format!("{prefix}DomainName")) {
+        Some(domain) => format!("{domain}\\{name}"),
+        None => name.to_string(),
+    })
+}
+
+/// Converts an access log time (`06/Feb/2019:00:00:38 +0000`) to RFC 3339.
+pub(crate) fn access_log_time(value: &str) -> Option<String> {
+    DateTime::parse_from_str(value, "%d/%b/%Y:%H:%M:%S %z")
+        .ok()
+        .map(|time| {
+            time.with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{cloudtrail_test_event, test_event};
+    use serde_json::json;
+
+    #[test]
+    fn gzip_files_rotate_on_compressed_size() {
+        let dir = std::env::temp_dir().join(format!("seclog-jsonl-size-{}", unique_id()));
+        let mut writer = JsonlWriter::new(&dir, 1, None, Some("gzip")).expect("writer");
+        writer.target_size_bytes = 64 * 1024;
+        let mut written = Vec::new();
+        let mut uncompressed = 0;
+        let mut index = 0_u64;
+        while written.is_empty() {
+            let request_id = hex(&Sha256::digest(index.to_le_bytes()));
+            let event = test_event(
+                "cloudtrail",
+                "GetObject",
+                json!({
+                    "eventName": "GetObject",
+                    "awsRegion": "us-east-1",
+                    "requestID": request_id,
+                    "requestParameters": { "bucketName": "logs", "key": format!("data/{index}.json") },
+                }),
+            )
+            .in_account("123456789012");
+            uncompressed += writer.write_event(&event).expect("write");
+            written.extend(writer.take_written_files());
+            index += 1;
+        }
+
+        let size = fs::metadata(&written[0].path).expect("metadata").len();
+        assert!(size >= 64 * 1024, "{size} bytes");
+        assert!(size < 96 * 1024, "{size} bytes");
+        assert!(uncompressed > 2 * size);
+        let records = read_records(&written[0].path).expect("read");
+        assert_eq!(records.len() as u64, index);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn sync_finishes_young_files_and_stays_open() {
+        let dir = std::env::temp_dir().join(format!("seclog-jsonl-sync-{}", unique_id()));
+        let mut writer = JsonlWriter::new(&dir, 1, Some(3600), None).expect("writer");
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:00Z"))
+            .expect("write");
+        writer.flush().expect("flush");
+        assert!(writer.take_written_files().is_empty());
+
+        writer.sync().expect("sync");
+        let first = writer.take_written_files();
+        assert_eq!(first.len(), 1);
+
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:01Z"))
+            .expect("write after sync");
+        writer.close().expect("close");
+        let second = writer.take_written_files();
+        assert_eq!(second.len(), 1);
+        assert_ne!(first[0].path, second[0].path);
+        assert_eq!(read_records(&first[0].path).expect("read").len(), 1);
+        assert_eq!(read_records(&second[0].path).expect("read").len(), 1);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn tenant_round_trips_through_source_native_records() {
+        let mut event = cloudtrail_test_event("GetObject", "2026-01-01T00:00:00Z").owned_by("acme");
+        let record: Value =
+            serde_json::from_slice(&record_bytes_for_event(&event).expect("bytes")).expect("json");
+        assert_eq!(record[TENANT_KEY], "acme");
+        assert_eq!(record["eventName"], "GetObject");
+
+        let read = event_from_record(record).expect("event");
+        assert_eq!(read.envelope.tenant.as_deref(), Some("acme"));
+        assert_eq!(read.payload, event.payload);
+
+        event.payload = json!({});
+        assert_eq!(
+            record_bytes_for_event(&event).expect("bytes"),
+            br#"{"seclog_tenant":"acme"}"#
+        );
+    }
+}
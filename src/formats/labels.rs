@@ -0,0 +1,256 @@
+//! Ground-truth label sidecar.
+//!
+//! Writes one row per labeled scenario event, keyed by the source-native event
+//! ID, to a JSONL or Parquet file. The main output is never modified, so
+//! detection benchmarks can join the sidecar to compute precision and recall.
+
+use crate::core::attack::technique;
+use crate::core::config::{LabelFormat, LabelOutputConfig};
+use crate::core::event::Event;
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::arrow_writer::ArrowWriter;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// One sidecar row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LabelRecord {
+    pub event_id: Option<String>,
+    pub source: String,
+    pub timestamp: String,
+    pub event_type: String,
+    pub actor_id: String,
+    pub scenario_id: String,
+    pub technique_ids: Vec<String>,
+    /// Technique names from the built-in ATT&CK catalog (`null` when unknown).
+    pub technique_names: Vec<Option<String>>,
+    /// Distinct tactics covered by the known techniques.
+    pub tactics: Vec<String>,
+}
+
+impl LabelRecord {
+    /// Returns the sidecar row for a labeled event, or `None` for unlabeled events.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let label = event.label.as_ref()?;
+        let known = label
+            .technique_ids
+            .iter()
+            .map(|id| technique(id))
+            .collect::<Vec<_>>();
+        let mut tactics = Vec::new();
+        for tactic in known
+            .iter()
+            .flatten()
+            .flat_map(|technique| technique.tactics)
+        {
+            if !tactics.iter().any(|seen| seen == tactic) {
+                tactics.push(tactic.to_string());
+            }
+        }
+        Some(Self {
+            event_id: event
+                .event_id()
+                .map(str::to_string)
+                .or_else(|| windows_record_key(event)),
+            source: event.envelope.source.clone(),
+            timestamp: event.envelope.timestamp.clone(),
+            event_type: event.envelope.event_type.clone(),
+            actor_id: event.envelope.actor.id.clone(),
+            scenario_id: label.scenario_id.clone(),
+            technique_ids: label.technique_ids.clone(),
+            technique_names: known
+                .iter()
+                .map(|technique| technique.map(|technique| technique.name.to_string()))
+                .collect(),
+            tactics,
+        })
+    }
+}
+
+/// Windows events carry no global ID; `Computer:EventRecordID` is unique per log.
+fn windows_record_key(event: &Event) -> Option<String> {
+    let system = event.payload.get("System")?;
+    Some(format!(
+        "{}:{}",
+        system.get("Computer")?.as_str()?,
+        system.get("EventRecordID")?.as_u64()?
+    ))
+}
+
+/// Sidecar writer for scenario labels.
+pub struct LabelWriter {
+    path: PathBuf,
+    format: LabelFormat,
+    jsonl: Option<BufWriter<File>>,
+    rows: Vec<LabelRecord>,
+    labeled: u64,
+}
+
+impl LabelWriter {
+    /// Creates (or truncates) the sidecar file described by `config`.
+    pub fn new(config: &LabelOutputConfig) -> io::Result<Self> {
+        let path = PathBuf::from(&config.path);
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let jsonl = match config.format {
+            LabelFormat::Jsonl => Some(BufWriter::new(File::create(&path)?)),
+            LabelFormat::Parquet => None,
+        };
+        Ok(Self {
+            path,
+            format: config.format,
+            jsonl,
+            rows: Vec::new(),
+            labeled: 0,
+        })
+    }
+
+    /// Records the event's label if it has one; returns whether a row was written.
+    pub fn write_event(&mut self, event: &Event) -> io::Result<bool> {
+        let Some(record) = LabelRecord::from_event(event) else {
+            return Ok(false);
+        };
+        match self.jsonl.as_mut() {
+            Some(file) => {
+                serde_json::to_writer(&mut *file, &record).map_err(io::Error::other)?;
+                file.write_all(b"\n")?;
+            }
+            None => self.rows.push(record),
+        }
+        self.labeled += 1;
+        Ok(true)
+    }
+
+    /// Number of labeled events seen so far.
+    pub fn labeled(&self) -> u64 {
+        self.labeled
+    }
+
+    /// Flushes the sidecar; Parquet sidecars are written here in one file.
+    pub fn close(&mut self) -> io::Result<()> {
+        match self.format {
+            LabelFormat::Jsonl => {
+                if let Some(file) = self.jsonl.as_mut() {
+                    file.flush()?;
+                }
+                Ok(())
+            }
+            LabelFormat::Parquet => write_parquet(&self.path, &self.rows),
+        }
+    }
+}
+
+fn write_parquet(path: &Path, rows: &[LabelRecord]) -> io::Result<()> {
+    let list = DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)));
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("event_id", DataType::Utf8, true),
+        Field::new("source", DataType::Utf8, false),
+        Field::new("timestamp", DataType::Utf8, false),
+        Field::new("event_type", DataType::Utf8, false),
+        Field::new("actor_id", DataType::Utf8, false),
+        Field::new("scenario_id", DataType::Utf8, false),
+        Field::new("technique_ids", list.clone(), false),
+        Field::new("technique_names", list.clone(), false),
+        Field::new("tactics", list, false),
+    ]));
+
+    let mut strings = (0..6).map(|_| StringBuilder::new()).collect::<Vec<_>>();
+    let mut lists = (0..3)
+        .map(|_| ListBuilder::new(StringBuilder::new()))
+        .collect::<Vec<_>>();
+    for row in rows {
+        strings[0].append_option(row.event_id.as_deref());
+        strings[1].append_value(&row.source);
+        strings[2].append_value(&row.timestamp);
+        strings[3].append_value(&row.event_type);
+        strings[4].append_value(&row.actor_id);
+        strings[5].append_value(&row.scenario_id);
+        lists[0].append_value(row.technique_ids.iter().map(Some));
+        lists[1].append_value(row.technique_names.iter().map(Option::as_deref));
+        lists[2].append_value(row.tactics.iter().map(Some));
+    }
+    let arrays = strings
+        .iter_mut()
+        .map(|builder| Arc::new(builder.finish()) as ArrayRef)
+        .chain(
+            lists
+                .iter_mut()
+                .map(|builder| Arc::new(builder.finish()) as ArrayRef),
+        )
+        .collect::<Vec<_>>();
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(io::Error::other)?;
+
+    let mut writer =
+        ArrowWriter::try_new(File::create(path)?, schema, None).map_err(io::Error::other)?;
+    writer.write(&batch).map_err(io::Error::other)?;
+    writer.close().map_err(io::Error::other)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::EventLabel;
+    use crate::fixtures::test_event;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn writes_only_labeled_events_keyed_by_event_id() {
+        let dir = std::env::temp_dir().join(format!("seclog-labels-{}", std::process::id()));
+        let path = dir.join("labels.jsonl");
+        let mut writer = LabelWriter::new(&LabelOutputConfig {
+            path: path.to_string_lossy().to_string(),
+            format: LabelFormat::Jsonl,
+        })
+        .expect("writer");
+
+        let unlabeled = test_event(
+            "okta_system_log",
+            "user.session.start",
+            json!({ "uuid": "evt-1" }),
+        );
+        let mut labeled = test_event(
+            "okta_system_log",
+            "user.session.start",
+            json!({ "uuid": "evt-2" }),
+        );
+        labeled.label = Some(EventLabel {
+            scenario_id: "password-spray".to_string(),
+            technique_ids: vec!["T1110.003".to_string(), "T9999".to_string()],
+        });
+        assert!(!writer.write_event(&unlabeled).expect("write"));
+        assert!(writer.write_event(&labeled).expect("write"));
+        writer.close().expect("close");
+
+        let rows = fs::read_to_string(&path)
+            .expect("read")
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).expect("json"))
+            .collect::<Vec<_>>();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["event_id"], "evt-2");
+        assert_eq!(rows[0]["scenario_id"], "password-spray");
+        assert_eq!(rows[0]["technique_ids"], json!(["T1110.003", "T9999"]));
+        assert_eq!(
+            rows[0]["technique_names"],
+            json!(["Brute Force: Password Spraying", null])
+        );
+        assert_eq!(rows[0]["tactics"], json!(["credential-access"]));
+        assert_eq!(writer.labeled(), 1);
+        assert!(serde_json::to_value(&labeled)
+            .expect("event json")
+            .get("label")
+            .is_none());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,390 @@
+//! Output manifests.
+//!
+//! [`ManifestWriter`] wraps a file writer and records every file it finishes
+//! with its event count, byte size, event-time span, and SHA-256 checksum.
+//! Each writer rewrites `manifest-<run>-<id>.json` in its output directory on
+//! close, and [`write_run_manifest`] aggregates one run's writer manifests
+//! into `manifest-<run>.json` with per-source totals.
+
+use super::json::unique_id;
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use crate::core::util::hex;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// One finished output file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// Path relative to the manifest's directory, `/`-separated.
+    pub path: String,
+    pub source: String,
+    pub events: u64,
+    pub bytes: u64,
+    pub first_event_time: Option<String>,
+    pub last_event_time: Option<String>,
+    pub sha256: String,
+}
+
+/// Manifest written by one [`ManifestWriter`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriterManifest {
+    pub run_id: String,
+    pub generated_at: String,
+    pub files: Vec<ManifestFile>,
+}
+
+/// Per-source totals in the run manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceTotals {
+    pub files: u64,
+    pub events: u64,
+    pub bytes: u64,
+    pub first_event_time: Option<String>,
+    pub last_event_time: Option<String>,
+}
+
+impl SourceTotals {
+    fn add(&mut self, file: &ManifestFile) {
+        self.files += 1;
+        self.events += file.events;
+        self.bytes += file.bytes;
+        // Times share one fixed-width UTC format, so they order lexically.
+        if let Some(first) = &file.first_event_time {
+            if self
+                .first_event_time
+                .as_ref()
+                .is_none_or(|seen| first < seen)
+            {
+                self.first_event_time = Some(first.clone());
+            }
+        }
+        if let Some(last) = &file.last_event_time {
+            if self.last_event_time.as_ref().is_none_or(|seen| last > seen) {
+                self.last_event_time = Some(last.clone());
+            }
+        }
+    }
+}
+
+/// Run-level manifest aggregating every writer manifest from one run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunManifest {
+    pub run_id: String,
+    pub generated_at: String,
+    /// Writer manifests included in the totals.
+    pub manifests: Vec<String>,
+    pub sources: BTreeMap<String, SourceTotals>,
+    pub files: u64,
+    pub events: u64,
+    pub bytes: u64,
+}
+
+/// Writer wrapper that keeps a manifest of the files its inner writer finishes.
+pub struct ManifestWriter {
+    inner: Box<dyn EventWriter>,
+    dir: PathBuf,
+    path: PathBuf,
+    run_id: String,
+    files: Vec<ManifestFile>,
+}
+
+impl ManifestWriter {
+    /// Wraps `inner`, whose files are all written under `dir`.
+    pub fn new(inner: Box<dyn EventWriter>, dir: impl Into<PathBuf>, run_id: &str) -> Self {
+        let dir = dir.into();
+        let path = dir.join(format!("manifest-{run_id}-{}.json", unique_id()));
+        Self {
+            inner,
+            dir,
+            path,
+            run_id: run_id.to_string(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Path of the manifest this writer maintains.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn collect(&mut self) -> io::Result<()> {
+        for written in self.inner.take_written_files() {
+            self.files.push(manifest_file(&self.dir, &written)?);
+        }
+        Ok(())
+    }
+
+    /// Rewrites the manifest with every file so far.
+    fn write_manifest(&self) -> io::Result<()> {
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        let manifest = WriterManifest {
+            run_id: self.run_id.clone(),
+            generated_at: format_time(Utc::now()),
+            files: self.files.clone(),
+        };
+        write_json(&self.path, &manifest)
+    }
+}
+
+impl EventWriter for ManifestWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        self.inner.write_event(event)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.collect()
+    }
+
+    /// Syncs the inner writer and rewrites the manifest with every file so far.
+    fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync()?;
+        self.collect()?;
+        self.write_manifest()
+    }
+
+    /// Closes the inner writer and rewrites the manifest with every file so far.
+    fn close(&mut self) -> io::Result<()> {
+        self.inner.close()?;
+        self.collect()?;
+        self.write_manifest()
+    }
+}
+
+/// Returns a run ID for naming manifests, unique per call.
+pub fn new_run_id() -> String {
+    format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+        &unique_id()[..8]
+    )
+}
+
+/// Returns true for writer and run manifests, which are not event output.
+pub fn is_manifest_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("manifest-") && name.ends_with(".json"))
+}
+
+/// Aggregates the writer manifests for `run_id` found in `dirs` into
+/// `<out_dir>/manifest-<run_id>.json`.
+///
+/// Returns the run manifest path, or `None` when no writer from the run
+/// wrote a manifest.
+pub fn write_run_manifest(
+    dirs: &[PathBuf],
+    out_dir: &Path,
+    run_id: &str,
+) -> io::Result<Option<PathBuf>> {
+    let prefix = format!("manifest-{run_id}-");
+    let mut paths = Vec::new();
+    for dir in dirs {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let matches = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".json"));
+            if matches {
+                paths.push(path);
+            }
+        }
+    }
+    if paths.is_empty() {
+        return Ok(None);
+    }
+    paths.sort();
+
+    let mut run = RunManifest {
+        run_id: run_id.to_string(),
+        generated_at: format_time(Utc::now()),
+        manifests: Vec::new(),
+        sources: BTreeMap::new(),
+        files: 0,
+        events: 0,
+        bytes: 0,
+    };
+    for path in &paths {
+        let manifest: WriterManifest = serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {err}", path.display()),
+            )
+        })?;
+        for file in &manifest.files {
+            run.sources
+                .entry(file.source.clone())
+                .or_default()
+                .add(file);
+            run.files += 1;
+            run.events += file.events;
+            run.bytes += file.bytes;
+        }
+        run.manifests.push(relative_path(out_dir, path));
+    }
+
+    fs::create_dir_all(out_dir)?;
+    let path = out_dir.join(format!("manifest-{run_id}.json"));
+    write_json(&path, &run)?;
+    Ok(Some(path))
+}
+
+fn manifest_file(dir: &Path, written: &WrittenFile) -> io::Result<ManifestFile> {
+    let (sha256, bytes) = sha256_file(&written.path)?;
+    Ok(ManifestFile {
+        path: relative_path(dir, &written.path),
+        source: written.source.clone(),
+        events: written.stats.events,
+        bytes,
+        first_event_time: written.stats.first_event_time.map(format_time),
+        last_event_time: written.stats.last_event_time.map(format_time),
+        sha256,
+    })
+}
+
+/// Returns the hex SHA-256 digest and byte size of a file.
+pub(crate) fn sha256_file(path: &Path) -> io::Result<(String, u64)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0_u8; 64 * 1024];
+    let mut bytes = 0_u64;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        bytes += read as u64;
+    }
+    Ok((hex(&hasher.finalize()), bytes))
+}
+
+pub(crate) fn relative_path(base: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub(crate) fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Writes through a temp file so readers never see a partial manifest.
+fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let temp = path.with_extension("json.tmp");
+    let bytes = serde_json::to_vec_pretty(value).map_err(io::Error::other)?;
+    fs::write(&temp, bytes)?;
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{cloudtrail_test_event, test_event};
+    use crate::formats::json::JsonlWriter;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("seclog-manifest-{name}-{}", unique_id()));
+        fs::create_dir_all(&dir).expect("temp dir");
+        dir
+    }
+
+    #[test]
+    fn writer_manifest_lists_files_with_counts_and_checksums() {
+        let dir = temp_dir("writer");
+        let inner = JsonlWriter::new(&dir, 64, None, None).expect("writer");
+        let mut writer = ManifestWriter::new(Box::new(inner), &dir, "run-1");
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:05Z"))
+            .expect("write");
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:01Z"))
+            .expect("write");
+        writer
+            .write_event(
+                &test_event("okta_system_log", "user.session.start", json!({}))
+                    .at("2026-01-01T00:00:03Z"),
+            )
+            .expect("write");
+        writer.close().expect("close");
+
+        let manifest: WriterManifest =
+            serde_json::from_slice(&fs::read(writer.path()).expect("manifest")).expect("parse");
+        assert_eq!(manifest.files.len(), 2);
+        let cloudtrail = manifest
+            .files
+            .iter()
+            .find(|file| file.source == "CloudTrail")
+            .expect("cloudtrail file");
+        assert_eq!(cloudtrail.events, 2);
+        assert_eq!(
+            cloudtrail.first_event_time.as_deref(),
+            Some("2026-01-01T00:00:01.000Z")
+        );
+        assert_eq!(
+            cloudtrail.last_event_time.as_deref(),
+            Some("2026-01-01T00:00:05.000Z")
+        );
+        let contents = fs::read(dir.join(&cloudtrail.path)).expect("event file");
+        assert_eq!(cloudtrail.bytes, contents.len() as u64);
+        assert_eq!(cloudtrail.sha256, hex(&Sha256::digest(&contents)));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn run_manifest_totals_sources_across_writers() {
+        let dir = temp_dir("run");
+        for timestamp in ["2026-01-01T00:00:01Z", "2026-01-01T00:00:09Z"] {
+            let inner = JsonlWriter::new(&dir, 64, None, None).expect("writer");
+            let mut writer = ManifestWriter::new(Box::new(inner), &dir, "run-2");
+            writer
+                .write_event(&cloudtrail_test_event("GetObject", timestamp))
+                .expect("write");
+            writer.close().expect("close");
+        }
+        let path = write_run_manifest(std::slice::from_ref(&dir), &dir, "run-2")
+            .expect("run manifest")
+            .expect("manifest written");
+        let run: RunManifest =
+            serde_json::from_slice(&fs::read(&path).expect("read")).expect("parse");
+        assert_eq!(run.manifests.len(), 2);
+        assert_eq!(run.events, 2);
+        let totals = &run.sources["CloudTrail"];
+        assert_eq!(totals.files, 2);
+        assert_eq!(
+            totals.first_event_time.as_deref(),
+            Some("2026-01-01T00:00:01.000Z")
+        );
+        assert_eq!(
+            totals.last_event_time.as_deref(),
+            Some("2026-01-01T00:00:09.000Z")
+        );
+        assert!(is_manifest_file(&path));
+        assert_eq!(
+            write_run_manifest(std::slice::from_ref(&dir), &dir, "run-3").expect("scan"),
+            None
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
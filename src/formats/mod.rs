@@ -1,4 +1,30 @@
+pub mod arrow_ipc;
+#[cfg(feature = "tokio")]
+pub mod async_sink;
+pub mod avro;
+pub mod azure_monitor;
+pub mod buffer_budget;
+pub mod cloudtrail_digest;
+pub mod cloudwatch_logs;
+pub mod csv;
 pub mod databricks_volume;
+pub mod delta;
+pub mod disk_usage;
+pub mod duckdb;
+pub mod filename;
+pub mod iceberg;
 pub mod json;
+pub mod labels;
+pub mod manifest;
+pub mod ocsf;
+pub mod ordering;
 pub mod parquet;
+pub mod partitioned;
+pub mod resilience;
+pub mod sentinel;
+pub mod splunk_hec;
+pub mod stdout;
+pub mod tee;
+pub mod text;
+pub mod written_bytes;
 pub mod zerobus;
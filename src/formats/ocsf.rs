@@ -0,0 +1,1094 @@
+//! OCSF (Open Cybersecurity Schema Framework) mapping and sink.
+//!
+//! Maps seclog events onto OCSF 1.1 classes so Amazon Security Lake and other
+//! OCSF consumers can read generated data:
+//!
+//! - Authentication (3002): console logins, Okta sign-ins, sign-outs, and
+//!   OAuth grants, Windows logons (4624/4625), and `*login`/`*logout` audit actions.
+//! - Account Change (3001) and Group Management (3006): Okta user lifecycle
+//!   and group membership events.
+//! - Authorize Session (3003): Windows special privileges at logon (4672).
+//! - Process Activity (1007): Windows process creation (4688).
+//! - HTTP Activity (4002): ALB access log requests.
+//! - DNS Activity (4003): Route 53 Resolver queries.
+//! - API Activity (6003): every other CloudTrail, Okta, Databricks, GitHub,
+//!   Kubernetes, and S3 access log event.
+//!
+//! Anything else becomes a Base Event (0). The source-native record is kept as
+//! `raw_data`. Files hold one record per line (`json`) or one row per record
+//! (`parquet`, with nested objects as JSON strings), per source/account/region.
+
+use super::json::{file_context_from_event, parse_compression, unique_id, JsonlCompression};
+use super::parquet::writer_properties;
+use crate::core::config::{OcsfContainer, OcsfOptions, ParquetOptions};
+use crate::core::event::{Event, Outcome};
+use crate::core::traits::{EventWriter, FileStats, WrittenFile};
+use arrow_array::builder::{Int32Builder, Int64Builder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use parquet::arrow::arrow_writer::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// OCSF schema version reported in `metadata.version`.
+pub const OCSF_VERSION: &str = "1.1.0";
+
+/// Integer attributes, written as typed Parquet columns.
+const INT_COLUMNS: [&str; 9] = [
+    "activity_id",
+    "category_uid",
+    "class_uid",
+    "severity_id",
+    "status_id",
+    "type_uid",
+    "time",
+    "logon_type_id",
+    "rcode_id",
+];
+/// String attributes, written as typed Parquet columns.
+const STRING_COLUMNS: [&str; 9] = [
+    "activity_name",
+    "category_name",
+    "class_name",
+    "severity",
+    "status",
+    "status_detail",
+    "type_name",
+    "rcode",
+    "raw_data",
+];
+/// Object attributes, written to Parquet as `<name>_json` columns.
+const OBJECT_COLUMNS: [&str; 15] = [
+    "metadata",
+    "actor",
+    "user",
+    "group",
+    "src_endpoint",
+    "device",
+    "http_request",
+    "cloud",
+    "api",
+    "resources",
+    "process",
+    "query",
+    "answers",
+    "http_response",
+    "dst_endpoint",
+];
+
+/// OCSF class, category, and activity of one record.
+struct Classification {
+    class_uid: i64,
+    class_name: &'static str,
+    category_uid: i64,
+    category_name: &'static str,
+    activity_id: i64,
+    activity_name: &'static str,
+}
+
+impl Classification {
+    fn new(class_uid: i64, activity: (i64, &'static str)) -> Self {
+        let (class_name, category_uid, category_name) = match class_uid {
+            1007 => ("Process Activity", 1, "System Activity"),
+            3001 => ("Account Change", 3, "Identity & Access Management"),
+            3002 => ("Authentication", 3, "Identity & Access Management"),
+            3003 => ("Authorize Session", 3, "Identity & Access Management"),
+            3006 => ("Group Management", 3, "Identity & Access Management"),
+            4002 => ("HTTP Activity", 4, "Network Activity"),
+            4003 => ("DNS Activity", 4, "Network Activity"),
+            6003 => ("API Activity", 6, "Application Activity"),
+            _ => ("Base Event", 0, "Uncategorized"),
+        };
+        Self {
+            class_uid,
+            class_name,
+            category_uid,
+            category_name,
+            activity_id: activity.0,
+            activity_name: activity.1,
+        }
+    }
+}
+
+const LOGON: (i64, &str) = (1, "Logon");
+const LOGOFF: (i64, &str) = (2, "Logoff");
+const OTHER: (i64, &str) = (99, "Other");
+
+/// Builds one OCSF record from an event.
+pub fn ocsf_record(event: &Event) -> Value {
+    let envelope = &event.envelope;
+    let class = classify(event);
+    let (status_id, status) = match envelope.outcome {
+        Outcome::Success => (1, "Success"),
+        Outcome::Failure => (2, "Failure"),
+        Outcome::Unknown => (0, "Unknown"),
+    };
+
+    let mut record = Map::new();
+    record.insert("activity_id".to_string(), json!(class.activity_id));
+    record.insert("activity_name".to_string(), json!(class.activity_name));
+    record.insert("category_uid".to_string(), json!(class.category_uid));
+    record.insert("category_name".to_string(), json!(class.category_name));
+    record.insert("class_uid".to_string(), json!(class.class_uid));
+    record.insert("class_name".to_string(), json!(class.class_name));
+    record.insert(
+        "type_uid".to_string(),
+        json!(class.class_uid * 100 + class.activity_id),
+    );
+    record.insert(
+        "type_name".to_string(),
+        json!(format!("{}: {}", class.class_name, class.activity_name)),
+    );
+    record.insert("severity_id".to_string(), json!(1));
+    record.insert("severity".to_string(), json!("Informational"));
+    record.insert("status_id".to_string(), json!(status_id));
+    record.insert("status".to_string(), json!(status));
+    if matches!(envelope.outcome, Outcome::Failure) {
+        if let Some(detail) = payload_str(
+            event,
+            &["errorCode", "errorMessage", "error_code", "reason", "rcode"],
+        ) {
+            record.insert("status_detail".to_string(), json!(detail));
+        }
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(&envelope.timestamp) {
+        record.insert("time".to_string(), json!(time.timestamp_millis()));
+    }
+    record.insert("metadata".to_string(), metadata(event));
+
+    let user = json!({
+        "uid": envelope.actor.id,
+        "name": envelope.actor.name.as_deref().unwrap_or(&envelope.actor.id),
+        "type": envelope.actor.kind,
+    });
+    let mut actor = Map::new();
+    actor.insert("user".to_string(), user.clone());
+    if let Some(session_id) = &envelope.session_id {
+        actor.insert("session".to_string(), json!({ "uid": session_id }));
+    }
+    record.insert("actor".to_string(), Value::Object(actor));
+
+    let mut src_endpoint = Map::new();
+    if let Some(ip) = &envelope.ip {
+        src_endpoint.insert("ip".to_string(), json!(ip));
+    }
+    if let Some(geo) = &envelope.geo {
+        let mut location = Map::new();
+        location.insert("country".to_string(), json!(geo.country));
+        for (key, value) in [("region", &geo.region), ("city", &geo.city)] {
+            if let Some(value) = value {
+                location.insert(key.to_string(), json!(value));
+            }
+        }
+        for (key, value) in [("lat", geo.lat), ("long", geo.lon)] {
+            if let Some(value) = value {
+                location.insert(key.to_string(), json!(value));
+            }
+        }
+        src_endpoint.insert("location".to_string(), Value::Object(location));
+    }
+    if !src_endpoint.is_empty() {
+        record.insert("src_endpoint".to_string(), Value::Object(src_endpoint));
+    }
+    if let Some(user_agent) = &envelope.user_agent {
+        record.insert(
+            "http_request".to_string(),
+            json!({ "user_agent": user_agent }),
+        );
+    }
+    if matches!(
+        envelope.source.as_str(),
+        "cloudtrail" | "s3_access_log" | "route53_resolver" | "alb_access_log"
+    ) {
+        let mut cloud = Map::new();
+        cloud.insert("provider".to_string(), json!("AWS"));
+        if let Some(region) = payload_str(event, &["awsRegion", "aws_region", "region"]) {
+            cloud.insert("region".to_string(), json!(region));
+        }
+        if let Some(account) = &envelope.tenant_id {
+            cloud.insert("account".to_string(), json!({ "uid": account }));
+        }
+        record.insert("cloud".to_string(), Value::Object(cloud));
+    }
+
+    match class.class_uid {
+        6003 => {
+            record.insert("api".to_string(), api(event));
+            if let Some(target) = &envelope.target {
+                let mut resource = Map::new();
+                resource.insert("uid".to_string(), json!(target.id));
+                resource.insert("type".to_string(), json!(target.kind));
+                if let Some(name) = &target.name {
+                    resource.insert("name".to_string(), json!(name));
+                }
+                record.insert(
+                    "resources".to_string(),
+                    Value::Array(vec![Value::Object(resource)]),
+                );
+            }
+        }
+        3002 | 3003 => {
+            record.insert("user".to_string(), user);
+            if let Some(logon_type) = payload_field(event, "LogonType")
+                .and_then(Value::as_str)
+                .and_then(|value| value.parse::<i64>().ok())
+            {
+                record.insert("logon_type_id".to_string(), json!(logon_type));
+            }
+        }
+        3001 | 3006 => {
+            let target = envelope.target.as_ref();
+            let user = target
+                .filter(|target| target.kind.eq_ignore_ascii_case("user"))
+                .map(|target| {
+                    json!({
+                        "uid": target.id,
+                        "name": target.name.as_deref().unwrap_or(&target.id),
+                    })
+                })
+                .unwrap_or(user);
+            record.insert("user".to_string(), user);
+            if let Some(group) = okta_target(event, "UserGroup") {
+                record.insert("group".to_string(), group);
+            }
+        }
+        1007 => {
+            record.insert("process".to_string(), process(event));
+        }
+        4002 => {
+            let mut request = Map::new();
+            request.insert("http_method".to_string(), json!(envelope.event_type));
+            if let Some(url) = payload_str(event, &["request_url"]) {
+                request.insert("url".to_string(), json!({ "url_string": url }));
+            }
+            if let Some(version) = payload_str(event, &["request_proto"]) {
+                request.insert("version".to_string(), json!(version));
+            }
+            if let Some(user_agent) = &envelope.user_agent {
+                request.insert("user_agent".to_string(), json!(user_agent));
+            }
+            record.insert("http_request".to_string(), Value::Object(request));
+            if let Some(code) = payload_field(event, "elb_status_code") {
+                record.insert("http_response".to_string(), json!({ "code": code }));
+            }
+            if let (Some(ip), Some(port)) = (
+                payload_str(event, &["target_ip"]),
+                payload_field(event, "target_port"),
+            ) {
+                record.insert(
+                    "dst_endpoint".to_string(),
+                    json!({ "ip": ip, "port": port }),
+                );
+            }
+        }
+        4003 => {
+            record.insert(
+                "query".to_string(),
+                json!({
+                    "hostname": payload_str(event, &["query_name"]).unwrap_or_default(),
+                    "type": envelope.event_type,
+                    "class": payload_str(event, &["query_class"]).unwrap_or("IN"),
+                }),
+            );
+            let answers = payload_field(event, "answers")
+                .and_then(Value::as_array)
+                .map(|answers| {
+                    answers
+                        .iter()
+                        .map(|answer| {
+                            json!({
+                                "rdata": answer["Rdata"],
+                                "type": answer["Type"],
+                                "class": answer["Class"],
+                            })
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            record.insert("answers".to_string(), Value::Array(answers));
+            if let Some(rcode) = payload_str(event, &["rcode"]) {
+                record.insert("rcode_id".to_string(), json!(rcode_id(rcode)));
+                record.insert("rcode".to_string(), json!(rcode));
+            }
+        }
+        _ => {}
+    }
+    if class.class_uid == 1007 || envelope.source == "windows_security" {
+        if let Some(target) = &envelope.target {
+            record.insert(
+                "device".to_string(),
+                json!({
+                    "uid": target.id,
+                    "hostname": target.name.as_deref().unwrap_or(&target.id),
+                    "type_id": 0,
+                }),
+            );
+        }
+    }
+    if !event.payload.is_null() {
+        record.insert("raw_data".to_string(), json!(event.payload.to_string()));
+    }
+    Value::Object(record)
+}
+
+fn classify(event: &Event) -> Classification {
+    let operation = event.envelope.event_type.as_str();
+    match event.envelope.source.as_str() {
+        "cloudtrail" if operation == "ConsoleLogin" => Classification::new(3002, LOGON),
+        "okta_system_log" => {
+            if operation == "user.session.start" || operation.starts_with("user.authentication.") {
+                Classification::new(3002, LOGON)
+            } else if operation.starts_with("app.oauth2.") {
+                Classification::new(3002, OTHER)
+            } else if operation == "user.session.end" {
+                Classification::new(3002, LOGOFF)
+            } else if operation.starts_with("user.lifecycle.")
+                || operation.starts_with("user.account.")
+            {
+                Classification::new(3001, account_change_activity(operation))
+            } else if operation.starts_with("group.user_membership.") {
+                let activity = match operation.rsplit('.').next() {
+                    Some("add") => (3, "Add User"),
+                    Some("remove") => (4, "Remove User"),
+                    _ => OTHER,
+                };
+                Classification::new(3006, activity)
+            } else {
+                Classification::new(6003, api_activity(operation))
+            }
+        }
+        "windows_security" => match operation {
+            "4624" | "4625" => Classification::new(3002, LOGON),
+            "4634" | "4647" => Classification::new(3002, LOGOFF),
+            "4672" => Classification::new(3003, (1, "Assign Privileges")),
+            "4688" => Classification::new(1007, (1, "Launch")),
+            "4689" => Classification::new(1007, (2, "Terminate")),
+            _ => Classification::new(0, (0, "Unknown")),
+        },
+        "cloudtrail" | "databricks_audit" | "github_audit" | "kubernetes_audit"
+        | "s3_access_log" => {
+            let lower = operation.to_ascii_lowercase();
+            if lower.ends_with("login") {
+                Classification::new(3002, LOGON)
+            } else if lower.ends_with("logout") {
+                Classification::new(3002, LOGOFF)
+            } else {
+                Classification::new(6003, api_activity(operation))
+            }
+        }
+        "google_workspace" => match operation {
+            "login_success" | "login_failure" => Classification::new(3002, LOGON),
+            "logout" => Classification::new(3002, LOGOFF),
+            _ => Classification::new(6003, api_activity(operation)),
+        },
+        "entra_signin" => Classification::new(3002, LOGON),
+        "entra_audit" => Classification::new(6003, api_activity(operation)),
+        "route53_resolver" => Classification::new(4003, (1, "Query")),
+        "alb_access_log" => Classification::new(4002, http_activity(operation)),
+        _ => Classification::new(0, (0, "Unknown")),
+    }
+}
+
+/// HTTP Activity verb from a request method.
+fn http_activity(method: &str) -> (i64, &'static str) {
+    match method {
+        "CONNECT" => (1, "Connect"),
+        "DELETE" => (2, "Delete"),
+        "GET" => (3, "Get"),
+        "HEAD" => (4, "Head"),
+        "OPTIONS" => (5, "Options"),
+        "POST" => (6, "Post"),
+        "PUT" => (7, "Put"),
+        "TRACE" => (8, "Trace"),
+        _ => OTHER,
+    }
+}
+
+/// OCSF `rcode_id` for a DNS response code name.
+fn rcode_id(rcode: &str) -> i64 {
+    match rcode {
+        "NOERROR" => 0,
+        "FORMERR" => 1,
+        "SERVFAIL" => 2,
+        "NXDOMAIN" => 3,
+        "NOTIMP" => 4,
+        "REFUSED" => 5,
+        _ => 99,
+    }
+}
+
+/// API Activity verb from an operation name such as `GetObject`,
+/// `pods.create`, `repo.destroy`, or `REST.PUT.OBJECT`.
+fn api_activity(operation: &str) -> (i64, &'static str) {
+    const VERBS: [(&str, (i64, &str)); 36] = [
+        ("create", (1, "Create")),
+        ("put", (1, "Create")),
+        ("post", (1, "Create")),
+        ("add", (1, "Create")),
+        ("run", (1, "Create")),
+        ("register", (1, "Create")),
+        ("import", (1, "Create")),
+        ("submit", (1, "Create")),
+        ("get", (2, "Read")),
+        ("list", (2, "Read")),
+        ("describe", (2, "Read")),
+        ("head", (2, "Read")),
+        ("watch", (2, "Read")),
+        ("read", (2, "Read")),
+        ("search", (2, "Read")),
+        ("lookup", (2, "Read")),
+        ("clone", (2, "Read")),
+        ("download", (2, "Read")),
+        ("view", (2, "Read")),
+        ("update", (3, "Update")),
+        ("patch", (3, "Update")),
+        ("modify", (3, "Update")),
+        ("set", (3, "Update")),
+        ("attach", (3, "Update")),
+        ("change", (3, "Update")),
+        ("enable", (3, "Update")),
+        ("disable", (3, "Update")),
+        ("edit", (3, "Update")),
+        ("push", (3, "Update")),
+        ("merge", (3, "Update")),
+        ("delete", (4, "Delete")),
+        ("remove", (4, "Delete")),
+        ("terminate", (4, "Delete")),
+        ("detach", (4, "Delete")),
+        ("destroy", (4, "Delete")),
+        ("revoke", (4, "Delete")),
+    ];
+    // Dotted names put the verb last (`pods.create`) or in the middle (`REST.GET.OBJECT`).
+    operation
+        .rsplit('.')
+        .find_map(|segment| {
+            let segment = segment.to_ascii_lowercase();
+            VERBS
+                .iter()
+                .find(|(verb, _)| segment.starts_with(verb))
+                .map(|(_, activity)| *activity)
+        })
+        .unwrap_or(OTHER)
+}
+
+fn account_change_activity(operation: &str) -> (i64, &'static str) {
+    match operation.rsplit('.').next().unwrap_or_default() {
+        "create" => (1, "Create"),
+        "activate" | "reactivate" | "unsuspend" => (2, "Enable"),
+        "update_password" => (3, "Password Change"),
+        "reset_password" => (4, "Password Reset"),
+        "deactivate" | "suspend" => (5, "Disable"),
+        "delete" => (6, "Delete"),
+        "lock" => (9, "Lock"),
+        "unlock" => (12, "Unlock"),
+        _ => OTHER,
+    }
+}
+
+fn metadata(event: &Event) -> Value {
+    let (product, vendor) = match event.envelope.source.as_str() {
+        "cloudtrail" => ("CloudTrail", "AWS"),
+        "s3_access_log" => ("S3 Server Access Logs", "AWS"),
+        "route53_resolver" => ("Route 53 Resolver Query Logs", "AWS"),
+        "alb_access_log" => ("Elastic Load Balancing", "AWS"),
+        "okta_system_log" => ("Okta System Log", "Okta"),
+        "entra_signin" | "entra_audit" => ("Microsoft Entra ID", "Microsoft"),
+        "databricks_audit" => ("Databricks Audit Logs", "Databricks"),
+        "github_audit" => ("GitHub Audit Log", "GitHub"),
+        "google_workspace" => ("Google Workspace Reports", "Google"),
+        "kubernetes_audit" => ("Kubernetes Audit", "Kubernetes"),
+        "windows_security" => ("Windows Security Event Log", "Microsoft"),
+        other => (other, "seclog"),
+    };
+    let mut metadata = Map::new();
+    metadata.insert("version".to_string(), json!(OCSF_VERSION));
+    metadata.insert(
+        "product".to_string(),
+        json!({ "name": product, "vendor_name": vendor }),
+    );
+    metadata.insert("log_name".to_string(), json!(event.envelope.source));
+    let uid = payload_str(
+        event,
+        &[
+            "eventID",
+            "uuid",
+            "event_id",
+            "_document_id",
+            "auditID",
+            "id",
+        ],
+    )
+    .map(str::to_string)
+    .or_else(|| {
+        payload_field(event, "EventRecordID")
+            .or_else(|| {
+                event
+                    .payload
+                    .get("System")
+                    .and_then(|system| system.get("EventRecordID"))
+            })
+            .map(|value| value.to_string())
+    });
+    if let Some(uid) = uid {
+        metadata.insert("uid".to_string(), json!(uid));
+    }
+    if let Some(tenant) = &event.envelope.tenant {
+        metadata.insert("tenant_uid".to_string(), json!(tenant));
+    }
+    Value::Object(metadata)
+}
+
+fn api(event: &Event) -> Value {
+    let mut api = Map::new();
+    api.insert("operation".to_string(), json!(event.envelope.event_type));
+    let service = match event.envelope.source.as_str() {
+        "s3_access_log" => Some("s3.amazonaws.com"),
+        "kubernetes_audit" => event
+            .payload
+            .get("objectRef")
+            .and_then(|object| object.get("apiGroup"))
+            .and_then(Value::as_str)
+            .or(Some("core")),
+        _ => payload_str(event, &["eventSource", "service_name", "serviceName"]),
+    };
+    if let Some(service) = service {
+        api.insert("service".to_string(), json!({ "name": service }));
+    }
+    if let Some(request_id) =
+        payload_str(event, &["requestID", "request_id", "requestId", "auditID"])
+    {
+        api.insert("request".to_string(), json!({ "uid": request_id }));
+    }
+    if matches!(event.envelope.outcome, Outcome::Failure) {
+        let mut response = Map::new();
+        if let Some(error) = payload_str(event, &["errorCode", "error_code"]) {
+            response.insert("error".to_string(), json!(error));
+        }
+        if let Some(message) = payload_str(event, &["errorMessage", "error_message"]) {
+            response.insert("message".to_string(), json!(message));
+        }
+        if !response.is_empty() {
+            api.insert("response".to_string(), Value::Object(response));
+        }
+    }
+    Value::Object(api)
+}
+
+fn process(event: &Event) -> Value {
+    let data = |key: &str| payload_field(event, key).and_then(Value::as_str);
+    let mut process = Map::new();
+    if let Some(path) = data("NewProcessName") {
+        let name = path.rsplit(['\\', '/']).next().unwrap_or(path);
+        process.insert("name".to_string(), json!(name));
+        process.insert("file".to_string(), json!({ "path": path, "name": name }));
+    }
+    if let Some(pid) = data("NewProcessId").and_then(parse_pid) {
+        process.insert("pid".to_string(), json!(pid));
+    }
+    if let Some(cmd_line) = data("CommandLine").filter(|value| !value.is_empty()) {
+        process.insert("cmd_line".to_string(), json!(cmd_line));
+    }
+    if let Some(parent) = data("ParentProcessName") {
+        let mut parent_process = Map::new();
+        parent_process.insert(
+            "name".to_string(),
+            json!(parent.rsplit(['\\', '/']).next().unwrap_or(parent)),
+        );
+        if let Some(pid) = data("ProcessId").and_then(parse_pid) {
+            parent_process.insert("pid".to_string(), json!(pid));
+        }
+        process.insert("parent_process".to_string(), Value::Object(parent_process));
+    }
+    Value::Object(process)
+}
+
+/// Windows writes process IDs as hex (`0x1a2c`).
+fn parse_pid(value: &str) -> Option<i64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// First Okta target of `kind` as an OCSF object with `uid` and `name`.
+fn okta_target(event: &Event, kind: &str) -> Option<Value> {
+    event
+        .payload
+        .get("target")?
+        .as_array()?
+        .iter()
+        .find(|target| target.get("type").and_then(Value::as_str) == Some(kind))
+        .map(|target| {
+            json!({
+                "uid": target.get("id").cloned().unwrap_or(Value::Null),
+                "name": target.get("displayName").cloned().unwrap_or(Value::Null),
+            })
+        })
+}
+
+/// Top-level payload value, or a Windows `EventData` value.
+fn payload_field<'a>(event: &'a Event, key: &str) -> Option<&'a Value> {
+    event.payload.get(key).or_else(|| {
+        event
+            .payload
+            .get("EventData")
+            .and_then(|data| data.get(key))
+    })
+}
+
+fn payload_str<'a>(event: &'a Event, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|key| event.payload.get(*key).and_then(Value::as_str))
+}
+
+/// Writer that maps events to OCSF and buffers them per source/account/region.
+pub struct OcsfWriter {
+    dir: PathBuf,
+    target_size_bytes: u64,
+    max_age: Option<Duration>,
+    encoding: Encoding,
+    sources: Option<Vec<String>>,
+    files: HashMap<StreamKey, RecordBuffer>,
+    written: Vec<WrittenFile>,
+}
+
+enum Encoding {
+    Json(JsonlCompression),
+    Parquet(Box<WriterProperties>),
+}
+
+impl Encoding {
+    fn from_options(options: &OcsfOptions) -> io::Result<Self> {
+        match options.container {
+            OcsfContainer::Json => {
+                let compression = options
+                    .compression
+                    .as_deref()
+                    .filter(|value| !value.trim().eq_ignore_ascii_case("none"));
+                Ok(Encoding::Json(parse_compression(compression)?))
+            }
+            OcsfContainer::Parquet => Ok(Encoding::Parquet(Box::new(writer_properties(
+                &ParquetOptions {
+                    compression: options.compression.clone(),
+                    ..ParquetOptions::default()
+                },
+            )?))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct StreamKey {
+    source: String,
+    account_id: String,
+    region: String,
+}
+
+#[derive(Default)]
+struct RecordBuffer {
+    records: Vec<Value>,
+    size: u64,
+    first_event_at: Option<Instant>,
+    stats: FileStats,
+}
+
+impl OcsfWriter {
+    /// Creates an OCSF writer with size-based rotation and optional max age.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        options: &OcsfOptions,
+    ) -> io::Result<Self> {
+        let encoding = Encoding::from_options(options)?;
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let max_age = max_age_seconds
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs);
+        Ok(Self {
+            dir,
+            target_size_bytes: target_size_mb.saturating_mul(1024 * 1024),
+            max_age,
+            encoding,
+            sources: options.sources.clone(),
+            files: HashMap::new(),
+            written: Vec::new(),
+        })
+    }
+
+    /// Checks the container and compression settings without opening a writer.
+    pub fn check_options(options: &OcsfOptions) -> io::Result<()> {
+        Encoding::from_options(options).map(|_| ())
+    }
+
+    fn flush_stream(&mut self, key: &StreamKey) -> io::Result<()> {
+        let Some(stream) = self.files.get_mut(key) else {
+            return Ok(());
+        };
+        if stream.records.is_empty() {
+            return Ok(());
+        }
+        let records = std::mem::take(&mut stream.records);
+        let path = match &self.encoding {
+            Encoding::Json(compression) => write_json(&self.dir, key, &records, *compression)?,
+            Encoding::Parquet(props) => write_parquet(&self.dir, key, &records, props)?,
+        };
+        stream.size = 0;
+        stream.first_event_at = None;
+        self.written.push(WrittenFile {
+            path,
+            source: key.source.clone(),
+            stats: stream.stats.rotate(),
+        });
+        Ok(())
+    }
+}
+
+impl EventWriter for OcsfWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let selected = self.sources.as_ref().is_none_or(|sources| {
+            sources
+                .iter()
+                .any(|source| source == &event.envelope.source)
+        });
+        if !selected {
+            return Ok(0);
+        }
+        let record = ocsf_record(event);
+        let size = record.to_string().len() as u64 + 1;
+        let context = file_context_from_event(event);
+        let key = StreamKey {
+            source: context.source,
+            account_id: context.account_id,
+            region: context.region,
+        };
+
+        let stream = self.files.entry(key.clone()).or_default();
+        if stream.records.is_empty() {
+            stream.first_event_at = Some(Instant::now());
+        }
+        stream.records.push(record);
+        stream.size += size;
+        stream.stats.record(event);
+        if stream.size >= self.target_size_bytes {
+            self.flush_stream(&key)?;
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        let due = self
+            .files
+            .iter()
+            .filter(|(_, stream)| {
+                stream.first_event_at.is_some_and(|start| {
+                    self.max_age
+                        .is_none_or(|max_age| now.duration_since(start) >= max_age)
+                })
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in due {
+            self.flush_stream(&key)?;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        let keys = self.files.keys().cloned().collect::<Vec<_>>();
+        for key in keys {
+            self.flush_stream(&key)?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.sync()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.written)
+    }
+}
+
+fn stream_path(dir: &Path, key: &StreamKey, ext: &str) -> PathBuf {
+    dir.join(format!(
+        "{}_{}_{}_{}_{}.{ext}",
+        key.account_id,
+        key.source,
+        key.region,
+        Utc::now().format("%Y%m%dT%H%MZ"),
+        unique_id()
+    ))
+}
+
+fn write_json(
+    dir: &Path,
+    key: &StreamKey,
+    records: &[Value],
+    compression: JsonlCompression,
+) -> io::Result<PathBuf> {
+    let mut buffer = Vec::new();
+    for record in records {
+        serde_json::to_writer(&mut buffer, record).map_err(io::Error::other)?;
+        buffer.push(b'\n');
+    }
+    let path = match compression {
+        JsonlCompression::None => {
+            let path = stream_path(dir, key, "ocsf.jsonl");
+            let mut file = File::create(&path)?;
+            file.write_all(&buffer)?;
+            file.flush()?;
+            path
+        }
+        JsonlCompression::Gzip => {
+            let path = stream_path(dir, key, "ocsf.jsonl.gz");
+            let mut encoder = GzEncoder::new(File::create(&path)?, Compression::default());
+            encoder.write_all(&buffer)?;
+            encoder.finish()?;
+            path
+        }
+    };
+    Ok(path)
+}
+
+/// Parquet schema: typed scalar attributes, then nested objects as JSON strings.
+pub fn parquet_schema() -> SchemaRef {
+    let int_fields = INT_COLUMNS.iter().map(|name| {
+        let data_type = if matches!(*name, "type_uid" | "time") {
+            DataType::Int64
+        } else {
+            DataType::Int32
+        };
+        Field::new(*name, data_type, true)
+    });
+    let string_fields = STRING_COLUMNS
+        .iter()
+        .map(|name| Field::new(*name, DataType::Utf8, true));
+    let object_fields = OBJECT_COLUMNS
+        .iter()
+        .map(|name| Field::new(format!("{name}_json"), DataType::Utf8, true));
+    Arc::new(Schema::new(
+        int_fields
+            .chain(string_fields)
+            .chain(object_fields)
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn write_parquet(
+    dir: &Path,
+    key: &StreamKey,
+    records: &[Value],
+    props: &WriterProperties,
+) -> io::Result<PathBuf> {
+    let schema = parquet_schema();
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+    for name in INT_COLUMNS {
+        let values = records
+            .iter()
+            .map(|record| record.get(name).and_then(Value::as_i64));
+        if matches!(name, "type_uid" | "time") {
+            let mut builder = Int64Builder::with_capacity(records.len());
+            values.for_each(|value| builder.append_option(value));
+            arrays.push(Arc::new(builder.finish()));
+        } else {
+            let mut builder = Int32Builder::with_capacity(records.len());
+            values.for_each(|value| {
+                builder.append_option(value.and_then(|value| i32::try_from(value).ok()))
+            });
+            arrays.push(Arc::new(builder.finish()));
+        }
+    }
+    for name in STRING_COLUMNS {
+        let mut builder = StringBuilder::new();
+        for record in records {
+            builder.append_option(record.get(name).and_then(Value::as_str));
+        }
+        arrays.push(Arc::new(builder.finish()));
+    }
+    for name in OBJECT_COLUMNS {
+        let mut builder = StringBuilder::new();
+        for record in records {
+            builder.append_option(record.get(name).map(Value::to_string));
+        }
+        arrays.push(Arc::new(builder.finish()));
+    }
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(io::Error::other)?;
+
+    let path = stream_path(dir, key, "ocsf.parquet");
+    let mut writer = ArrowWriter::try_new(File::create(&path)?, schema, Some(props.clone()))
+        .map_err(io::Error::other)?;
+    writer.write(&batch).map_err(io::Error::other)?;
+    writer.close().map_err(io::Error::other)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::{Actor, Target};
+    use crate::fixtures::test_event;
+
+    const AT: &str = "2026-01-01T00:00:01.500Z";
+
+    #[test]
+    fn maps_sources_to_classes_and_columns() {
+        let mut delete = test_event(
+            "cloudtrail",
+            "DeleteObject",
+            json!({
+                "eventID": "e-1",
+                "eventSource": "s3.amazonaws.com",
+                "awsRegion": "us-east-1",
+                "requestID": "r-1",
+                "errorCode": "AccessDenied",
+            }),
+        )
+        .at(AT)
+        .failed()
+        .with_ip("203.0.113.7")
+        .in_account("123456789012");
+        delete.envelope.actor = Actor {
+            id: "alice".to_string(),
+            kind: "IAMUser".to_string(),
+            name: Some("alice".to_string()),
+        };
+        delete.envelope.target = Some(Target {
+            id: "logs-bucket".to_string(),
+            kind: "bucket".to_string(),
+            name: None,
+        });
+        delete.envelope.user_agent = Some("aws-cli/2.15".to_string());
+        let record = ocsf_record(&delete);
+        assert_eq!(record["class_uid"], 6003);
+        assert_eq!(record["category_uid"], 6);
+        assert_eq!(record["activity_id"], 4);
+        assert_eq!(record["type_uid"], 600304);
+        assert_eq!(record["status_id"], 2);
+        assert_eq!(record["status_detail"], "AccessDenied");
+        assert_eq!(record["time"], 1_767_225_601_500_i64);
+        assert_eq!(record["metadata"]["uid"], "e-1");
+        assert_eq!(record["api"]["service"]["name"], "s3.amazonaws.com");
+        assert_eq!(record["api"]["response"]["error"], "AccessDenied");
+        assert_eq!(record["resources"][0]["uid"], "logs-bucket");
+        assert_eq!(record["cloud"]["region"], "us-east-1");
+
+        let login = ocsf_record(
+            &test_event("cloudtrail", "ConsoleLogin", json!({}))
+                .at(AT)
+                .by("alice", "IAMUser"),
+        );
+        assert_eq!(login["class_uid"], 3002);
+        assert_eq!(login["type_uid"], 300201);
+        assert_eq!(login["user"]["uid"], "alice");
+        let logoff = test_event("okta_system_log", "user.session.end", json!({}))
+            .at(AT)
+            .by("alice", "IAMUser");
+        assert_eq!(ocsf_record(&logoff)["activity_id"], 2);
+        let membership = test_event(
+            "okta_system_log",
+            "group.user_membership.add",
+            json!({ "target": [{ "id": "00g1", "type": "UserGroup", "displayName": "Admins" }] }),
+        )
+        .at(AT)
+        .by("alice", "IAMUser");
+        assert_eq!(ocsf_record(&membership)["type_uid"], 300603);
+        assert_eq!(ocsf_record(&membership)["group"]["name"], "Admins");
+        let launch = test_event(
+            "windows_security",
+            "4688",
+            json!({ "EventData": { "NewProcessName": "C:\\Windows\\System32\\cmd.exe", "NewProcessId": "0x1a2c" } })
+        ).at(AT).by("alice", "IAMUser");
+        let launch = ocsf_record(&launch);
+        assert_eq!(launch["class_uid"], 1007);
+        assert_eq!(launch["process"]["name"], "cmd.exe");
+        assert_eq!(launch["process"]["pid"], 0x1a2c);
+        let lookup = ocsf_record(&test_event(
+            "route53_resolver",
+            "A",
+            json!({ "query_name": "qxkzvbjw.xyz.", "region": "us-east-1", "rcode": "NXDOMAIN", "answers": [] })
+        ).at(AT).by("alice", "IAMUser").failed());
+        assert_eq!(lookup["type_uid"], 400301);
+        assert_eq!(lookup["query"]["hostname"], "qxkzvbjw.xyz.");
+        assert_eq!(lookup["rcode_id"], 3);
+        assert_eq!(lookup["cloud"]["region"], "us-east-1");
+        let request = ocsf_record(&test_event(
+            "alb_access_log",
+            "POST",
+            json!({ "elb": "app/internal-portal/0f1e2d3c4b5a6978", "elb_status_code": 502, "target_ip": "10.0.4.12", "target_port": 8080 })
+        ).at(AT).by("alice", "IAMUser").failed());
+        assert_eq!(request["type_uid"], 400206);
+        assert_eq!(request["http_response"]["code"], 502);
+        assert_eq!(request["dst_endpoint"]["port"], 8080);
+        let sign_in = ocsf_record(&test_event(
+            "entra_signin",
+            "Sign-in activity",
+            json!({ "id": "8a2f0c1e-51d4-4b6e-9f3a-2c7d1e0b5a94", "appDisplayName": "AWS IAM Identity Center (successor to AWS Single Sign-On)" })
+        ).at(AT).by("alice", "IAMUser"));
+        assert_eq!(sign_in["type_uid"], 300201);
+        assert_eq!(sign_in["metadata"]["product"]["vendor_name"], "Microsoft");
+        assert_eq!(
+            sign_in["metadata"]["uid"],
+            "8a2f0c1e-51d4-4b6e-9f3a-2c7d1e0b5a94"
+        );
+        assert_eq!(api_activity("REST.GET.OBJECT"), (2, "Read"));
+        assert_eq!(api_activity("pods.create"), (1, "Create"));
+
+        // Every attribute the mapping emits has a Parquet column.
+        for record in [
+            record,
+            login,
+            launch,
+            lookup,
+            request,
+            sign_in,
+            ocsf_record(&membership),
+        ] {
+            for key in record.as_object().unwrap().keys() {
+                assert!(
+                    INT_COLUMNS.contains(&key.as_str())
+                        || STRING_COLUMNS.contains(&key.as_str())
+                        || OBJECT_COLUMNS.contains(&key.as_str()),
+                    "{key}"
+                );
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("seclog-ocsf-{}", unique_id()));
+        let options = OcsfOptions {
+            container: OcsfContainer::Parquet,
+            sources: Some(vec!["cloudtrail".to_string()]),
+            ..OcsfOptions::default()
+        };
+        let mut writer = OcsfWriter::new(&dir, 1, None, &options).unwrap();
+        assert!(writer.write_event(&delete).unwrap() > 0);
+        assert_eq!(writer.write_event(&logoff).unwrap(), 0);
+        writer.close().unwrap();
+        let files = writer.take_written_files();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].stats.events, 1);
+        assert!(files[0].path.to_string_lossy().ends_with(".ocsf.parquet"));
+        let reader =
+            parquet::file::reader::SerializedFileReader::new(File::open(&files[0].path).unwrap())
+                .unwrap();
+        let metadata = parquet::file::reader::FileReader::metadata(&reader);
+        assert_eq!(metadata.file_metadata().num_rows(), 1);
+        assert_eq!(
+            metadata.file_metadata().schema_descr().num_columns(),
+            parquet_schema().fields().len()
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,335 @@
+//! Event-time ordering of file streams.
+//!
+//! Writers group events into one stream of files per source/account/region,
+//! and writer sharding sends each stream to one shard, but events can still
+//! reach a shard out of time order. With `output.files.ordering` set, an
+//! [`OrderingWriter`] in front of the format writers holds each stream's
+//! events for `window_seconds` of simulated time and passes them on sorted.
+//! Each stream keeps a watermark, the latest event time it has passed on, that
+//! survives file rotation; an event older than its stream's watermark is
+//! dropped or fails the run, so event time never goes backwards within a
+//! file or from one file of a stream to the next.
+//!
+//! Every finished file is checked as well: [`FileStats`] counts events
+//! recorded out of order, comparing a file's first events with where the
+//! stream's previous file ended, and the writer fails on any file that has
+//! some.
+//!
+//! [`FileStats`]: crate::core::traits::FileStats
+
+use super::json::file_context_from_event;
+use crate::core::config::{LateEventPolicy, OrderingConfig};
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+
+/// Writer wrapper that passes events on in event-time order per file stream.
+///
+/// Finished files are checked and passed on through `take_written_files`, so
+/// manifests still see them.
+pub struct OrderingWriter {
+    inner: Box<dyn EventWriter>,
+    window: Duration,
+    late: LateEventPolicy,
+    streams: HashMap<StreamKey, Stream>,
+    /// Latest event time seen across all streams.
+    newest: Option<DateTime<Utc>>,
+    /// Arrival counter; keeps events with equal times in arrival order.
+    arrivals: u64,
+    dropped: u64,
+    /// `dropped` as of the last close, to report new drops once.
+    reported: u64,
+    files: Vec<WrittenFile>,
+}
+
+/// Source file label, account, and region.
+type StreamKey = (String, String, String);
+
+#[derive(Default)]
+struct Stream {
+    /// Latest event time passed to the inner writer.
+    watermark: Option<DateTime<Utc>>,
+    /// Held events by time, then arrival.
+    pending: BTreeMap<(DateTime<Utc>, u64), Event>,
+}
+
+impl OrderingWriter {
+    pub fn new(inner: Box<dyn EventWriter>, config: &OrderingConfig) -> io::Result<Self> {
+        if !config.window_seconds.is_finite() || config.window_seconds < 0.0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "ordering window_seconds must be 0 or greater",
+            ));
+        }
+        Ok(Self {
+            inner,
+            window: Duration::milliseconds((config.window_seconds * 1_000.0) as i64),
+            late: config.late,
+            streams: HashMap::new(),
+            newest: None,
+            arrivals: 0,
+            dropped: 0,
+            reported: 0,
+            files: Vec::new(),
+        })
+    }
+
+    /// Late events dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Passes on every held event up to `cutoff`, or all of them when unset.
+    fn release(&mut self, cutoff: Option<DateTime<Utc>>) -> io::Result<u64> {
+        let mut bytes = 0;
+        for stream in self.streams.values_mut() {
+            bytes += release_stream(self.inner.as_mut(), stream, cutoff)?;
+        }
+        Ok(bytes)
+    }
+
+    fn cutoff(&self) -> Option<DateTime<Utc>> {
+        self.newest.map(|newest| newest - self.window)
+    }
+
+    fn collect(&mut self) -> io::Result<()> {
+        for file in self.inner.take_written_files() {
+            if file.stats.out_of_order > 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{}: out-of-order events: {}",
+                        file.path.display(),
+                        file.stats.out_of_order
+                    ),
+                ));
+            }
+            self.files.push(file);
+        }
+        Ok(())
+    }
+}
+
+fn release_stream(
+    inner: &mut dyn EventWriter,
+    stream: &mut Stream,
+    cutoff: Option<DateTime<Utc>>,
+) -> io::Result<u64> {
+    let mut bytes = 0;
+    while let Some(entry) = stream.pending.first_entry() {
+        let time = entry.key().0;
+        if cutoff.is_some_and(|cutoff| time > cutoff) {
+            break;
+        }
+        bytes += inner.write_event(entry.get())?;
+        entry.remove();
+        stream.watermark = Some(time);
+    }
+    Ok(bytes)
+}
+
+impl EventWriter for OrderingWriter {
+    /// Returns the bytes of the events this call passed on, which may be
+    /// none while the event is held or others.
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        // Events without a time cannot be ordered and go straight through.
+        let Ok(time) = DateTime::parse_from_rfc3339(&event.envelope.timestamp) else {
+            let bytes = self.inner.write_event(event)?;
+            self.collect()?;
+            return Ok(bytes);
+        };
+        let time = time.with_timezone(&Utc);
+        let context = file_context_from_event(event);
+        let key = (context.source, context.account_id, context.region);
+        let stream = self.streams.entry(key.clone()).or_default();
+        if let Some(watermark) = stream.watermark.filter(|watermark| time < *watermark) {
+            let (source, account_id, region) = key;
+            let message = format!(
+                "{source} event at {} is older than {} already written for {account_id}/{region}",
+                time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                watermark.to_rfc3339_opts(SecondsFormat::Millis, true),
+            );
+            return match self.late {
+                LateEventPolicy::Error => Err(io::Error::new(io::ErrorKind::InvalidData, message)),
+                LateEventPolicy::Drop => {
+                    self.dropped += 1;
+                    if self.dropped == 1 {
+                        tracing::warn!("dropping late events: {message}");
+                    } else {
+                        tracing::debug!(dropped = self.dropped, "dropping late event: {message}");
+                    }
+                    Ok(0)
+                }
+            };
+        }
+        stream.pending.insert((time, self.arrivals), event.clone());
+        self.arrivals += 1;
+        let newest = self.newest.map_or(time, |newest| newest.max(time));
+        self.newest = Some(newest);
+        let bytes = release_stream(self.inner.as_mut(), stream, Some(newest - self.window))?;
+        self.collect()?;
+        Ok(bytes)
+    }
+
+    /// Passes on the events of every stream that are past the window, so
+    /// quiet streams are not held back by the ones still writing.
+    fn flush(&mut self) -> io::Result<()> {
+        self.release(self.cutoff())?;
+        self.inner.flush()?;
+        self.collect()
+    }
+
+    /// Passes on every held event; watermarks are kept, so late events are
+    /// still rejected after a sync.
+    fn sync(&mut self) -> io::Result<()> {
+        self.release(None)?;
+        self.inner.sync()?;
+        self.collect()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.release(None)?;
+        self.inner.close()?;
+        self.collect()?;
+        if self.dropped > self.reported {
+            tracing::warn!(dropped = self.dropped, "late events dropped");
+            self.reported = self.dropped;
+        }
+        Ok(())
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::traits::FileStats;
+    use crate::fixtures::cloudtrail_test_event;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the times of the events it is given and hands out `files`.
+    #[derive(Default)]
+    struct Recorder {
+        times: Arc<Mutex<Vec<String>>>,
+        files: Vec<WrittenFile>,
+    }
+
+    impl EventWriter for Recorder {
+        fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+            self.times
+                .lock()
+                .unwrap()
+                .push(event.envelope.timestamp.clone());
+            Ok(10)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn take_written_files(&mut self) -> Vec<WrittenFile> {
+            std::mem::take(&mut self.files)
+        }
+    }
+
+    fn ordering(window_seconds: f64, late: LateEventPolicy) -> OrderingConfig {
+        OrderingConfig {
+            window_seconds,
+            late,
+        }
+    }
+
+    #[test]
+    fn sorts_within_the_window_and_drops_late_events() {
+        let recorder = Recorder::default();
+        let times = recorder.times.clone();
+        let mut writer =
+            OrderingWriter::new(Box::new(recorder), &ordering(60.0, LateEventPolicy::Drop))
+                .unwrap();
+        for second in [10, 5, 100] {
+            writer
+                .write_event(&cloudtrail_test_event(
+                    "GetObject",
+                    &format!("2026-01-01T00:{:02}:{:02}Z", second / 60, second % 60),
+                ))
+                .unwrap();
+        }
+        // 100 moves the cutoff to 40, passing on 5 and 10.
+        assert_eq!(times.lock().unwrap().len(), 2);
+        assert_eq!(
+            writer
+                .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:30Z"))
+                .unwrap(),
+            10
+        );
+        assert_eq!(
+            writer
+                .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:20Z"))
+                .unwrap(),
+            0
+        );
+        writer.close().unwrap();
+
+        assert_eq!(
+            *times.lock().unwrap(),
+            [
+                "2026-01-01T00:00:05Z",
+                "2026-01-01T00:00:10Z",
+                "2026-01-01T00:00:30Z",
+                "2026-01-01T00:01:40Z"
+            ]
+        );
+        assert_eq!(writer.dropped(), 1);
+    }
+
+    #[test]
+    fn late_events_fail_under_error() {
+        let mut writer = OrderingWriter::new(
+            Box::new(Recorder::default()),
+            &ordering(0.0, LateEventPolicy::Error),
+        )
+        .unwrap();
+        writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:10Z"))
+            .unwrap();
+        let err = writer
+            .write_event(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:05Z"))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("123456789012/us-east-1"));
+    }
+
+    #[test]
+    fn files_starting_before_the_previous_file_ended_fail() {
+        let mut stats = FileStats::default();
+        stats.record(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:10Z"));
+        stats.record(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:20Z"));
+        assert_eq!(stats.rotate().out_of_order, 0);
+        stats.record(&cloudtrail_test_event("GetObject", "2026-01-01T00:00:15Z"));
+        let stats = stats.rotate();
+        assert_eq!(stats.out_of_order, 1);
+
+        let recorder = Recorder {
+            files: vec![WrittenFile {
+                path: PathBuf::from("b.json"),
+                source: "CloudTrail".to_string(),
+                stats,
+            }],
+            ..Recorder::default()
+        };
+        let mut writer =
+            OrderingWriter::new(Box::new(recorder), &OrderingConfig::default()).unwrap();
+        let err = writer.flush().unwrap_err();
+        assert!(err.to_string().contains("b.json: out-of-order events: 1"));
+    }
+}
@@ -2,35 +2,69 @@
 //!
 //! Buffers Arrow batches per account/region and rotates by size or age.
 
+use super::buffer_budget::{BufferAccount, BufferBudget};
+use super::filename::{FileName, FilenameTemplate};
+use super::json::file_key;
+use crate::core::config::ParquetOptions;
 use crate::core::event::{Actor, Event, Geo, Outcome, Target};
-use crate::core::traits::EventWriter;
-use arrow_array::builder::{BooleanBuilder, Float64Builder, StringBuilder, StructBuilder};
-use arrow_array::{ArrayRef, RecordBatch};
-use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef};
-use chrono::Utc;
+use crate::core::traits::{EventWriter, FileStats, WrittenFile};
+use arrow_array::builder::{
+    BooleanBuilder, Float64Builder, StringBuilder, StructBuilder, TimestampMicrosecondBuilder,
+};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, TimestampMicrosecondType};
+use arrow_array::{Array, ArrayRef, RecordBatch, StringArray, StructArray};
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
 use parquet::arrow::arrow_writer::ArrowWriter;
+use parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
 use parquet::errors::ParquetError;
-use parquet::file::properties::WriterProperties;
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
+use parquet::format::KeyValue;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
-use serde_json::Value;
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, VecDeque};
 use std::fs::{self, File};
 use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
 const DEFAULT_BATCH_SIZE: usize = 1024;
+const DEFAULT_ENCODER_THREADS: usize = 1;
+const DEFAULT_ENCODER_QUEUE: usize = 4;
+
+/// File key-value metadata key for the number of events in the file.
+pub const EVENT_COUNT_KEY: &str = "seclog.event_count";
+/// File key-value metadata key for the earliest event time (RFC3339).
+pub const MIN_EVENT_TIME_KEY: &str = "seclog.min_event_time";
+/// File key-value metadata key for the latest event time (RFC3339).
+pub const MAX_EVENT_TIME_KEY: &str = "seclog.max_event_time";
 
 /// Parquet writer that buffers events per account/region.
+///
+/// Finished batches are encoded and written by background encoder threads so
+/// rotation does not stall `write_event`; the queue between them is bounded,
+/// so a slow disk applies backpressure instead of buffering without limit.
 pub struct ParquetWriter {
-    dir: PathBuf,
+    files: Arc<FileSettings>,
     target_size_bytes: u64,
-    schema: SchemaRef,
     batch_size: usize,
     max_age: Option<Duration>,
+    encoder_threads: usize,
+    encoder_queue: usize,
+    encoder: Option<Encoder>,
     regions: HashMap<RegionKey, RegionState>,
+    written: Vec<WrittenFile>,
+    sizes: EncodedSizes,
+    buffered: BufferAccount,
+    /// Encoder failure seen by `take_written_files`, reported on the next call
+    /// that can return an error.
+    deferred_error: Option<io::Error>,
 }
 
 impl ParquetWriter {
@@ -43,6 +77,30 @@ impl ParquetWriter {
         Self::with_batch_size(dir, target_size_mb, max_age_seconds, DEFAULT_BATCH_SIZE)
     }
 
+    /// Creates a Parquet writer with configured writer properties.
+    pub fn with_options(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        options: &ParquetOptions,
+    ) -> io::Result<Self> {
+        let mut writer = Self::new(dir, target_size_mb, max_age_seconds)?;
+        writer.files = Arc::new(FileSettings {
+            dir: writer.files.dir.clone(),
+            schema: build_schema(options.timestamp_strings.unwrap_or(false)),
+            props: writer_properties(options)?,
+            filename: writer.files.filename.clone(),
+        });
+        writer.encoder_threads = options.encoder_threads.unwrap_or(DEFAULT_ENCODER_THREADS);
+        writer.encoder_queue = options.encoder_queue.unwrap_or(DEFAULT_ENCODER_QUEUE);
+        if writer.encoder_queue == 0 {
+            return Err(invalid_option(
+                "encoder_queue must be greater than 0".to_string(),
+            ));
+        }
+        Ok(writer)
+    }
+
     /// Creates a Parquet writer with a custom batch size.
     pub fn with_batch_size(
         dir: impl Into<PathBuf>,
@@ -52,7 +110,6 @@ impl ParquetWriter {
     ) -> io::Result<Self> {
         let dir = dir.into();
         fs::create_dir_all(&dir)?;
-        let schema = build_schema();
         let max_age = max_age_seconds.and_then(|seconds| {
             if seconds > 0 {
                 Some(Duration::from_secs(seconds))
@@ -61,28 +118,107 @@ impl ParquetWriter {
             }
         });
         Ok(Self {
-            dir,
+            files: Arc::new(FileSettings {
+                dir,
+                schema: build_schema(false),
+                props: WriterProperties::builder().build(),
+                filename: FilenameTemplate::default(),
+            }),
             target_size_bytes: target_size_mb.saturating_mul(1024 * 1024),
-            schema,
             batch_size,
             max_age,
+            encoder_threads: DEFAULT_ENCODER_THREADS,
+            encoder_queue: DEFAULT_ENCODER_QUEUE,
+            encoder: None,
             regions: HashMap::new(),
+            written: Vec::new(),
+            sizes: EncodedSizes::default(),
+            buffered: BufferAccount::default(),
+            deferred_error: None,
         })
     }
+
+    /// Counts buffered events against `budget`, flushing the oldest regions
+    /// when it is exceeded.
+    pub fn with_buffer_budget(mut self, budget: Arc<BufferBudget>) -> Self {
+        self.buffered.set_budget(budget);
+        self
+    }
+
+    /// Adds buffered events to `gauge`, the buffered-bytes metric of the pipeline.
+    pub fn with_buffer_gauge(mut self, gauge: Arc<AtomicU64>) -> Self {
+        self.buffered.set_gauge(gauge);
+        self
+    }
+
+    /// Names files with `template` instead of [`DEFAULT_TEMPLATE`](super::filename::DEFAULT_TEMPLATE).
+    pub fn with_filename_template(mut self, template: FilenameTemplate) -> Self {
+        self.files = Arc::new(FileSettings {
+            dir: self.files.dir.clone(),
+            schema: self.files.schema.clone(),
+            props: self.files.props.clone(),
+            filename: template,
+        });
+        self
+    }
+
+    /// Flushes the oldest regions while the shared buffer budget is exceeded.
+    fn enforce_budget(&mut self) -> io::Result<()> {
+        while self.buffered.over_budget() {
+            let Some((key, state)) = self
+                .regions
+                .iter_mut()
+                .filter(|(_, state)| state.current_size > 0)
+                .min_by_key(|(_, state)| state.first_event_at)
+            else {
+                break;
+            };
+            let key = key.clone();
+            if let Some(job) = finish_region(&key, state, &mut self.buffered)? {
+                self.submit(job)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Hands a finished batch to the encoder, or encodes it inline when
+    /// background encoding is off.
+    fn submit(&mut self, job: EncodeJob) -> io::Result<()> {
+        if self.encoder_threads == 0 {
+            let encoded = encode_file(&self.files, job)?;
+            self.sizes.record(&encoded);
+            self.written.push(encoded.file);
+            return Ok(());
+        }
+        let encoder = self.encoder.get_or_insert_with(|| {
+            Encoder::spawn(self.files.clone(), self.encoder_threads, self.encoder_queue)
+        });
+        encoder.submit(job)?;
+        encoder.collect(false, &mut self.written, &mut self.sizes)
+    }
+
+    /// Finishes every buffered region that `due` selects.
+    fn flush_regions(&mut self, mut due: impl FnMut(&mut RegionState) -> bool) -> io::Result<()> {
+        let mut jobs = Vec::new();
+        for (key, state) in self.regions.iter_mut() {
+            if state.current_size > 0 && due(state) {
+                jobs.extend(finish_region(key, state, &mut self.buffered)?);
+            }
+        }
+        for job in jobs {
+            self.submit(job)?;
+        }
+        Ok(())
+    }
+
+    fn check_deferred(&mut self) -> io::Result<()> {
+        self.deferred_error.take().map_or(Ok(()), Err)
+    }
 }
 
 impl EventWriter for ParquetWriter {
     fn write_event(&mut self, event: &Event) -> io::Result<u64> {
-        let payload_json = if event.payload.is_null() {
-            None
-        } else {
-            Some(
-                serde_json::to_string(&event.payload)
-                    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?,
-            )
-        };
-
-        let size = estimate_event_size(event, payload_json.as_deref());
+        self.check_deferred()?;
         let context = file_context_from_event(event);
         let key = RegionKey {
             source: context.source,
@@ -92,61 +228,240 @@ impl EventWriter for ParquetWriter {
         let state = self
             .regions
             .entry(key.clone())
-            .or_insert_with(|| RegionState::new(self.schema.clone(), self.batch_size));
+            .or_insert_with(|| RegionState::new(self.files.schema.clone(), self.batch_size));
 
         if state.current_size == 0 {
             state.first_event_at = Some(Instant::now());
         }
-        state
-            .batch
-            .append_event(event, payload_json.as_deref())
-            .map_err(map_arrow_err)?;
+        let size = state.batch.append_event(event)?;
         state.current_size += size;
+        state.stats.record(event);
+        self.buffered.add(size);
 
-        if state.current_size >= self.target_size_bytes {
-            flush_region(&self.dir, &self.schema, &key, state)?;
+        if self.sizes.on_disk(&key.source, state.current_size) >= self.target_size_bytes {
+            if let Some(job) = finish_region(&key, state, &mut self.buffered)? {
+                self.submit(job)?;
+            }
         }
+        self.enforce_budget()?;
 
         Ok(size)
     }
 
     fn flush(&mut self) -> io::Result<()> {
+        self.check_deferred()?;
         let now = Instant::now();
-        for (key, state) in self.regions.iter_mut() {
-            if state.current_size > 0 {
-                if let Some(max_age) = self.max_age {
-                    let start = match state.first_event_at {
-                        Some(start) => start,
-                        None => {
-                            state.first_event_at = Some(now);
-                            continue;
-                        }
+        let max_age = self.max_age;
+        self.flush_regions(|state| {
+            let Some(max_age) = max_age else {
+                return true;
+            };
+            match state.first_event_at {
+                Some(start) => now.duration_since(start) >= max_age,
+                None => {
+                    state.first_event_at = Some(now);
+                    false
+                }
+            }
+        })?;
+        match self.encoder.as_mut() {
+            Some(encoder) => encoder.collect(false, &mut self.written, &mut self.sizes),
+            None => Ok(()),
+        }
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.check_deferred()?;
+        self.flush_regions(|_| true)?;
+        match self.encoder.as_mut() {
+            Some(encoder) => encoder.collect(true, &mut self.written, &mut self.sizes),
+            None => Ok(()),
+        }
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.sync()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        if let Some(encoder) = self.encoder.as_mut() {
+            if let Err(err) = encoder.collect(false, &mut self.written, &mut self.sizes) {
+                self.deferred_error.get_or_insert(err);
+            }
+        }
+        std::mem::take(&mut self.written)
+    }
+}
+
+/// Output directory, schema, properties, and file naming shared with encoder
+/// threads.
+struct FileSettings {
+    dir: PathBuf,
+    schema: SchemaRef,
+    props: WriterProperties,
+    filename: FilenameTemplate,
+}
+
+/// Finished batch waiting to be written as one file.
+struct EncodeJob {
+    batch: RecordBatch,
+    key: RegionKey,
+    stamp: String,
+    unique: String,
+    seq: u64,
+    stats: FileStats,
+    /// [`estimate_event_size`] total of the batch's events.
+    estimated_size: u64,
+}
+
+/// File written by an encode job, with its estimated and actual sizes.
+struct EncodedFile {
+    file: WrittenFile,
+    estimated_size: u64,
+    size: u64,
+}
+
+/// Encoded bytes per estimated byte, learned per source from written files.
+///
+/// [`estimate_event_size`] counts raw string lengths, which compress and
+/// dictionary-encode to a small and source-dependent fraction, so rotating on
+/// the estimate alone writes files far below the target size. Until a
+/// source's first file is written its estimate is taken as is.
+#[derive(Default)]
+struct EncodedSizes {
+    /// `(estimated, encoded)` byte totals per source.
+    sources: HashMap<String, (u64, u64)>,
+}
+
+impl EncodedSizes {
+    fn record(&mut self, encoded: &EncodedFile) {
+        let totals = self.sources.entry(encoded.file.source.clone()).or_default();
+        totals.0 += encoded.estimated_size;
+        totals.1 += encoded.size;
+    }
+
+    /// Expected on-disk size of a file of `source` events whose estimated
+    /// size is `estimated`.
+    fn on_disk(&self, source: &str, estimated: u64) -> u64 {
+        match self.sources.get(source) {
+            Some(&(total, encoded)) if total > 0 => {
+                (estimated as f64 * encoded as f64 / total as f64) as u64
+            }
+            _ => estimated,
+        }
+    }
+}
+
+/// Background encoder threads fed through a bounded queue.
+struct Encoder {
+    jobs: Option<mpsc::SyncSender<EncodeJob>>,
+    results: mpsc::Receiver<io::Result<EncodedFile>>,
+    workers: Vec<JoinHandle<()>>,
+    /// Jobs submitted whose result has not been collected yet.
+    pending: usize,
+}
+
+impl Encoder {
+    fn spawn(files: Arc<FileSettings>, threads: usize, queue: usize) -> Self {
+        let (jobs, job_rx) = mpsc::sync_channel::<EncodeJob>(queue);
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let (result_tx, results) = mpsc::channel();
+        let workers = (0..threads)
+            .map(|_| {
+                let (job_rx, result_tx, files) = (job_rx.clone(), result_tx.clone(), files.clone());
+                thread::spawn(move || loop {
+                    let job = match job_rx.lock() {
+                        Ok(job_rx) => job_rx.recv(),
+                        Err(_) => return,
+                    };
+                    let Ok(job) = job else {
+                        return;
                     };
-                    if now.duration_since(start) < max_age {
-                        continue;
+                    if result_tx.send(encode_file(&files, job)).is_err() {
+                        return;
                     }
+                })
+            })
+            .collect();
+        Self {
+            jobs: Some(jobs),
+            results,
+            workers,
+            pending: 0,
+        }
+    }
+
+    /// Queues a job, blocking while the queue is full.
+    fn submit(&mut self, job: EncodeJob) -> io::Result<()> {
+        self.jobs
+            .as_ref()
+            .and_then(|jobs| jobs.send(job).ok())
+            .ok_or_else(encoder_gone)?;
+        self.pending += 1;
+        Ok(())
+    }
+
+    /// Moves finished files into `written` and their sizes into `sizes`; with
+    /// `wait`, blocks until every submitted job is done. Returns the first
+    /// encoding error.
+    fn collect(
+        &mut self,
+        wait: bool,
+        written: &mut Vec<WrittenFile>,
+        sizes: &mut EncodedSizes,
+    ) -> io::Result<()> {
+        let mut first_err = None;
+        while self.pending > 0 {
+            let result = if wait {
+                self.results.recv().map_err(|_| encoder_gone())?
+            } else {
+                match self.results.try_recv() {
+                    Ok(result) => result,
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => return Err(encoder_gone()),
+                }
+            };
+            self.pending -= 1;
+            match result {
+                Ok(encoded) => {
+                    sizes.record(&encoded);
+                    written.push(encoded.file);
+                }
+                Err(err) => {
+                    first_err.get_or_insert(err);
                 }
-                flush_region(&self.dir, &self.schema, key, state)?;
             }
         }
-        Ok(())
+        first_err.map_or(Ok(()), Err)
     }
+}
 
-    fn close(&mut self) -> io::Result<()> {
-        self.flush()
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        // Closing the queue lets workers finish queued jobs and exit.
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
     }
 }
 
-struct EventBatchBuilder {
+fn encoder_gone() -> io::Error {
+    io::Error::other("parquet encoder thread exited")
+}
+
+pub(crate) struct EventBatchBuilder {
     schema: SchemaRef,
     envelope: StructBuilder,
     payload_json: StringBuilder,
     cloudtrail: StructBuilder,
     len: usize,
+    /// Payload JSON of the row being appended, reused across rows.
+    payload_scratch: Vec<u8>,
 }
 
 impl EventBatchBuilder {
-    fn new(schema: SchemaRef, capacity: usize) -> Self {
+    pub(crate) fn new(schema: SchemaRef, capacity: usize) -> Self {
         let envelope_fields = match schema.field(0).data_type() {
             DataType::Struct(fields) => fields.clone(),
             _ => Fields::empty(),
@@ -161,29 +476,48 @@ impl EventBatchBuilder {
             payload_json: StringBuilder::with_capacity(capacity, capacity * 128),
             cloudtrail: StructBuilder::from_fields(cloudtrail_fields, capacity),
             len: 0,
+            payload_scratch: Vec::new(),
         }
     }
 
-    fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         self.len
     }
 
-    fn append_event(
-        &mut self,
-        event: &Event,
-        payload_json: Option<&str>,
-    ) -> Result<(), arrow_schema::ArrowError> {
-        self.append_envelope(&event.envelope);
+    /// Appends one row, serializing the payload once for both the
+    /// `payload_json` column and the returned size estimate.
+    pub(crate) fn append_event(&mut self, event: &Event) -> io::Result<u64> {
+        let mut scratch = std::mem::take(&mut self.payload_scratch);
+        let result = encode_payload(&mut scratch, &event.payload).map(|payload_json| {
+            self.append_envelope(&event.envelope);
+            self.append_payload(payload_json);
+            self.append_cloudtrail(event);
+            self.end_row();
+            estimate_event_size(event, payload_json)
+        });
+        self.payload_scratch = scratch;
+        result
+    }
+
+    /// Appends the `payload_json` column of the current row.
+    pub(crate) fn append_payload(&mut self, payload_json: Option<&str>) {
         match payload_json {
             Some(value) => self.payload_json.append_value(value),
             None => self.payload_json.append_null(),
         }
+    }
+
+    /// Appends the `cloudtrail` struct of the current row.
+    pub(crate) fn append_cloudtrail(&mut self, event: &Event) {
         append_cloudtrail(&mut self.cloudtrail, event);
+    }
+
+    /// Completes a row started with the `append_*` column methods.
+    pub(crate) fn end_row(&mut self) {
         self.len += 1;
-        Ok(())
     }
 
-    fn finish(&mut self) -> Result<RecordBatch, arrow_schema::ArrowError> {
+    pub(crate) fn finish(&mut self) -> Result<RecordBatch, arrow_schema::ArrowError> {
         let envelope_array: ArrayRef = Arc::new(self.envelope.finish());
         let payload_array: ArrayRef = Arc::new(self.payload_json.finish());
         let cloudtrail_array: ArrayRef = Arc::new(self.cloudtrail.finish());
@@ -195,17 +529,15 @@ impl EventBatchBuilder {
         Ok(batch)
     }
 
-    fn append_envelope(&mut self, envelope: &crate::core::event::EventEnvelope) {
+    /// Appends the `envelope` struct of the current row.
+    pub(crate) fn append_envelope(&mut self, envelope: &crate::core::event::EventEnvelope) {
         let builder = &mut self.envelope;
 
         append_string(
             builder.field_builder::<StringBuilder>(0).unwrap(),
             Some(&envelope.schema_version),
         );
-        append_string(
-            builder.field_builder::<StringBuilder>(1).unwrap(),
-            Some(&envelope.timestamp),
-        );
+        append_timestamp(builder, 1, Some(&envelope.timestamp));
         append_string(
             builder.field_builder::<StringBuilder>(2).unwrap(),
             Some(&envelope.source),
@@ -245,12 +577,23 @@ impl EventBatchBuilder {
             builder.field_builder::<StringBuilder>(11).unwrap(),
             envelope.tenant_id.as_deref(),
         );
+        append_string(
+            builder.field_builder::<StringBuilder>(12).unwrap(),
+            envelope.tenant.as_deref(),
+        );
 
         builder.append(true);
     }
 }
 
-fn build_schema() -> SchemaRef {
+/// Builds the event schema; `timestamp_strings` keeps the RFC3339 Utf8 timestamp columns.
+pub(crate) fn build_schema(timestamp_strings: bool) -> SchemaRef {
+    let timestamp = if timestamp_strings {
+        DataType::Utf8
+    } else {
+        DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+    };
+
     let actor_fields = Fields::from(vec![
         Field::new("id", DataType::Utf8, false),
         Field::new("kind", DataType::Utf8, false),
@@ -273,7 +616,7 @@ fn build_schema() -> SchemaRef {
 
     let envelope_fields = Fields::from(vec![
         Field::new("schema_version", DataType::Utf8, false),
-        Field::new("timestamp", DataType::Utf8, false),
+        Field::new("timestamp", timestamp.clone(), !timestamp_strings),
         Field::new("source", DataType::Utf8, false),
         Field::new("event_type", DataType::Utf8, false),
         Field::new("actor", DataType::Struct(actor_fields), false),
@@ -284,6 +627,7 @@ fn build_schema() -> SchemaRef {
         Field::new("user_agent", DataType::Utf8, true),
         Field::new("session_id", DataType::Utf8, true),
         Field::new("tenant_id", DataType::Utf8, true),
+        Field::new("tenant", DataType::Utf8, true),
     ]);
 
     let cloudtrail_identity_fields = Fields::from(vec![
@@ -297,7 +641,7 @@ fn build_schema() -> SchemaRef {
 
     let cloudtrail_fields = Fields::from(vec![
         Field::new("eventVersion", DataType::Utf8, true),
-        Field::new("eventTime", DataType::Utf8, true),
+        Field::new("eventTime", timestamp, true),
         Field::new("eventSource", DataType::Utf8, true),
         Field::new("eventName", DataType::Utf8, true),
         Field::new("awsRegion", DataType::Utf8, true),
@@ -332,35 +676,80 @@ fn build_schema() -> SchemaRef {
     Arc::new(Schema::new(fields))
 }
 
-fn build_file_path(
-    dir: &Path,
-    source: &str,
-    account_id: &str,
-    region: &str,
-    stamp: &str,
-    unique: &str,
-    ext: &str,
-) -> PathBuf {
-    dir.join(format!(
-        "{account_id}_{source}_{region}_{stamp}_{unique}.{ext}"
-    ))
-}
-
-fn open_writer(
-    dir: &Path,
-    source: &str,
-    account_id: &str,
-    region: &str,
-    stamp: &str,
-    unique: &str,
-    ext: &str,
-    schema: SchemaRef,
-) -> io::Result<(ArrowWriter<File>, PathBuf)> {
-    let path = build_file_path(dir, source, account_id, region, stamp, unique, ext);
-    let file = File::create(&path)?;
-    let props = WriterProperties::builder().build();
-    let writer = ArrowWriter::try_new(file, schema, Some(props)).map_err(map_parquet_err)?;
-    Ok((writer, path))
+/// Builds Parquet writer properties from format options.
+pub fn writer_properties(options: &ParquetOptions) -> io::Result<WriterProperties> {
+    let mut builder = WriterProperties::builder().set_compression(parse_compression(options)?);
+    if let Some(rows) = options.row_group_size {
+        if rows == 0 {
+            return Err(invalid_option(
+                "parquet row_group_size must be > 0".to_string(),
+            ));
+        }
+        builder = builder.set_max_row_group_size(rows);
+    }
+    if let Some(enabled) = options.dictionary {
+        builder = builder.set_dictionary_enabled(enabled);
+    }
+    if let Some(statistics) = options.statistics.as_deref() {
+        let level = match statistics {
+            "none" => EnabledStatistics::None,
+            "chunk" => EnabledStatistics::Chunk,
+            "page" => EnabledStatistics::Page,
+            other => {
+                return Err(invalid_option(format!(
+                    "unsupported parquet statistics level: {other}"
+                )))
+            }
+        };
+        builder = builder.set_statistics_enabled(level);
+    }
+    Ok(builder.build())
+}
+
+fn parse_compression(options: &ParquetOptions) -> io::Result<Compression> {
+    let codec = options.compression.as_deref().unwrap_or("none");
+    let level = options.compression_level;
+    let compression = match codec {
+        "none" | "uncompressed" => Compression::UNCOMPRESSED,
+        "snappy" => Compression::SNAPPY,
+        "lz4" => Compression::LZ4_RAW,
+        "gzip" => Compression::GZIP(
+            level
+                .map(GzipLevel::try_new)
+                .transpose()
+                .map_err(map_parquet_err)?
+                .unwrap_or_default(),
+        ),
+        "zstd" => Compression::ZSTD(
+            level
+                .map(|level| ZstdLevel::try_new(level as i32))
+                .transpose()
+                .map_err(map_parquet_err)?
+                .unwrap_or_default(),
+        ),
+        "brotli" => Compression::BROTLI(
+            level
+                .map(BrotliLevel::try_new)
+                .transpose()
+                .map_err(map_parquet_err)?
+                .unwrap_or_default(),
+        ),
+        other => {
+            return Err(invalid_option(format!(
+                "unsupported parquet compression: {other}"
+            )))
+        }
+    };
+    if level.is_some() && matches!(codec, "none" | "uncompressed" | "snappy" | "lz4") {
+        return Err(invalid_option(format!(
+            "parquet compression {codec} does not take a level"
+        )));
+    }
+    Ok(compression)
+}
+
+fn invalid_option(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
 }
 
 fn append_actor(builder: &mut StructBuilder, actor: &Actor) {
@@ -442,6 +831,23 @@ fn append_string(builder: &mut StringBuilder, value: Option<&str>) {
     }
 }
 
+/// Appends an RFC3339 value to a typed timestamp column, or as-is to a Utf8 column.
+/// Unparseable values become null in typed columns.
+fn append_timestamp(builder: &mut StructBuilder, index: usize, value: Option<&str>) {
+    if let Some(column) = builder.field_builder::<TimestampMicrosecondBuilder>(index) {
+        column.append_option(value.and_then(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|time| time.timestamp_micros())
+        }));
+        return;
+    }
+    append_string(
+        builder.field_builder::<StringBuilder>(index).unwrap(),
+        value,
+    );
+}
+
 fn append_bool(builder: &mut BooleanBuilder, value: Option<bool>) {
     match value {
         Some(value) => builder.append_value(value),
@@ -495,10 +901,7 @@ fn append_cloudtrail(builder: &mut StructBuilder, event: &Event) {
         builder.field_builder::<StringBuilder>(0).unwrap(),
         get_str("eventVersion", "event_version"),
     );
-    append_string(
-        builder.field_builder::<StringBuilder>(1).unwrap(),
-        get_str("eventTime", "event_time"),
-    );
+    append_timestamp(builder, 1, get_str("eventTime", "event_time"));
     append_string(
         builder.field_builder::<StringBuilder>(2).unwrap(),
         get_str("eventSource", "event_source"),
@@ -640,7 +1043,7 @@ fn append_cloudtrail_identity(builder: &mut StructBuilder, value: Option<&Value>
 
 fn append_cloudtrail_null(builder: &mut StructBuilder) {
     append_string(builder.field_builder::<StringBuilder>(0).unwrap(), None);
-    append_string(builder.field_builder::<StringBuilder>(1).unwrap(), None);
+    append_timestamp(builder, 1, None);
     append_string(builder.field_builder::<StringBuilder>(2).unwrap(), None);
     append_string(builder.field_builder::<StringBuilder>(3).unwrap(), None);
     append_string(builder.field_builder::<StringBuilder>(4).unwrap(), None);
@@ -677,7 +1080,21 @@ fn append_cloudtrail_identity_null(builder: &mut StructBuilder) {
     builder.append(false);
 }
 
-fn estimate_event_size(event: &Event, payload_json: Option<&str>) -> u64 {
+/// Serializes a payload into `buf`, replacing its contents; `None` for a
+/// null payload.
+pub(crate) fn encode_payload<'a>(
+    buf: &'a mut Vec<u8>,
+    payload: &Value,
+) -> io::Result<Option<&'a str>> {
+    if payload.is_null() {
+        return Ok(None);
+    }
+    buf.clear();
+    serde_json::to_writer(&mut *buf, payload).map_err(io::Error::other)?;
+    std::str::from_utf8(buf).map(Some).map_err(io::Error::other)
+}
+
+pub(crate) fn estimate_event_size(event: &Event, payload_json: Option<&str>) -> u64 {
     let envelope = &event.envelope;
     let mut size = 0usize;
 
@@ -718,6 +1135,9 @@ fn estimate_event_size(event: &Event, payload_json: Option<&str>) -> u64 {
     if let Some(tenant_id) = &envelope.tenant_id {
         size += tenant_id.len();
     }
+    if let Some(tenant) = &envelope.tenant {
+        size += tenant.len();
+    }
     if let Some(payload) = payload_json {
         size += payload.len();
     }
@@ -729,7 +1149,7 @@ fn map_parquet_err(err: ParquetError) -> io::Error {
     io::Error::new(io::ErrorKind::Other, err)
 }
 
-fn map_arrow_err(err: arrow_schema::ArrowError) -> io::Error {
+pub(crate) fn map_arrow_err(err: arrow_schema::ArrowError) -> io::Error {
     io::Error::new(io::ErrorKind::Other, err)
 }
 
@@ -754,24 +1174,11 @@ struct FileContext {
 }
 
 fn file_context_from_event(event: &Event) -> FileContext {
-    let source = source_file_label(&event.envelope.source);
-    let account_id = event
-        .envelope
-        .tenant_id
-        .clone()
-        .unwrap_or_else(|| "000000000000".to_string());
-    let region = event
-        .payload
-        .get("awsRegion")
-        .or_else(|| event.payload.get("aws_region"))
-        .and_then(|value| value.as_str())
-        .unwrap_or("global")
-        .to_string();
-
+    let (account_id, region) = file_key(event);
     FileContext {
-        source,
-        account_id,
-        region,
+        source: source_file_label(&event.envelope.source),
+        account_id: account_id.to_string(),
+        region: region.to_string(),
     }
 }
 
@@ -786,6 +1193,9 @@ struct RegionState {
     current_size: u64,
     batch: EventBatchBuilder,
     first_event_at: Option<Instant>,
+    stats: FileStats,
+    /// Files written for this key so far, for `{seq}`.
+    files: u64,
 }
 
 impl RegionState {
@@ -794,55 +1204,115 @@ impl RegionState {
             current_size: 0,
             batch: EventBatchBuilder::new(schema, batch_size),
             first_event_at: None,
+            stats: FileStats::default(),
+            files: 0,
         }
     }
 }
 
-fn flush_region(
-    dir: &Path,
-    schema: &SchemaRef,
+/// Takes the region's buffered batch and stats as an encode job.
+fn finish_region(
     key: &RegionKey,
     state: &mut RegionState,
-) -> io::Result<()> {
+    buffered: &mut BufferAccount,
+) -> io::Result<Option<EncodeJob>> {
     if state.batch.len() == 0 {
-        return Ok(());
+        return Ok(None);
     }
 
     let batch = state.batch.finish().map_err(map_arrow_err)?;
-    let stamp = current_stamp();
-    let unique = unique_id();
-    let (mut writer, temp_path) = open_writer(
-        dir,
-        &key.source,
-        &key.account_id,
-        &key.region,
-        &stamp,
-        &unique,
-        "parquet.tmp",
-        schema.clone(),
+    let estimated_size = state.current_size;
+    buffered.release(state.current_size);
+    state.current_size = 0;
+    state.first_event_at = None;
+    state.files += 1;
+    Ok(Some(EncodeJob {
+        batch,
+        key: key.clone(),
+        stamp: current_stamp(),
+        unique: unique_id(),
+        seq: state.files - 1,
+        stats: state.stats.rotate(),
+        estimated_size,
+    }))
+}
+
+/// Encodes a job to a temp file and renames it into place.
+fn encode_file(files: &FileSettings, job: EncodeJob) -> io::Result<EncodedFile> {
+    let EncodeJob {
+        batch,
+        key,
+        stamp,
+        unique,
+        seq,
+        stats,
+        estimated_size,
+    } = job;
+    let final_path = files.filename.path(
+        &files.dir,
+        &FileName {
+            source: &key.source,
+            account_id: &key.account_id,
+            region: &key.region,
+            ts: &stamp,
+            uuid: &unique,
+            seq,
+            ext: "parquet",
+            stats: &stats,
+        },
     )?;
+    let mut temp_path = final_path.clone().into_os_string();
+    temp_path.push(".tmp");
+    let file = File::create(&temp_path)?;
+    let mut writer = ArrowWriter::try_new(file, files.schema.clone(), Some(files.props.clone()))
+        .map_err(map_parquet_err)?;
     writer.write(&batch).map_err(map_parquet_err)?;
+    for (key, value) in event_time_metadata(&stats) {
+        writer.append_key_value_metadata(KeyValue::new(key.to_string(), value));
+    }
     writer.close().map_err(map_parquet_err)?;
-    let final_path = build_file_path(
-        dir,
-        &key.source,
-        &key.account_id,
-        &key.region,
-        &stamp,
-        &unique,
-        "parquet",
-    );
+    let size = fs::metadata(&temp_path)?.len();
     fs::rename(&temp_path, &final_path)?;
-    state.current_size = 0;
-    state.first_event_at = None;
-    Ok(())
+    Ok(EncodedFile {
+        file: WrittenFile {
+            path: final_path,
+            source: key.source,
+            stats,
+        },
+        estimated_size,
+        size,
+    })
+}
+
+/// File key-value metadata giving the file's event count and event-time range,
+/// so loaders can skip files outside a query window from the footer alone.
+fn event_time_metadata(stats: &FileStats) -> Vec<(&'static str, String)> {
+    let mut metadata = vec![(EVENT_COUNT_KEY, stats.events.to_string())];
+    for (key, time) in [
+        (MIN_EVENT_TIME_KEY, stats.first_event_time),
+        (MAX_EVENT_TIME_KEY, stats.last_event_time),
+    ] {
+        if let Some(time) = time {
+            metadata.push((key, time.to_rfc3339_opts(SecondsFormat::Millis, true)));
+        }
+    }
+    metadata
 }
 
 fn source_file_label(source: &str) -> String {
     match source {
+        "alb_access_log" => "AlbAccessLog".to_string(),
         "cloudtrail" => "CloudTrail".to_string(),
         "databricks_audit" => "DatabricksAudit".to_string(),
+        "entra_audit" => "EntraAudit".to_string(),
+        "entra_signin" => "EntraSignIn".to_string(),
+        "github_audit" => "GitHubAudit".to_string(),
+        "google_workspace" => "GoogleWorkspace".to_string(),
+        "kubernetes_audit" => "KubernetesAudit".to_string(),
         "okta_system_log" => "OktaSystemLog".to_string(),
+        "route53_resolver" => "Route53Resolver".to_string(),
+        "s3_access_log" => "S3AccessLog".to_string(),
+        "windows_security" => "WindowsSecurity".to_string(),
         other => other
             .chars()
             .map(|ch| {
@@ -855,3 +1325,669 @@ fn source_file_label(source: &str) -> String {
             .collect(),
     }
 }
+
+/// Reads events written by [`ParquetWriter`] back into [`Event`]s, in file order.
+///
+/// Events come from the `envelope` and `payload_json` columns. When
+/// `payload_json` is null on a CloudTrail row, the payload is rebuilt from the
+/// `cloudtrail` columns. Timestamps are returned as RFC3339 strings whether the
+/// file used typed or string timestamp columns. Open and decode failures are
+/// yielded as `Err` items.
+pub fn read_events(path: impl AsRef<Path>) -> impl Iterator<Item = io::Result<Event>> {
+    let reader = File::open(path).and_then(|file| {
+        ParquetRecordBatchReaderBuilder::try_new(file)
+            .and_then(|builder| builder.build())
+            .map_err(map_parquet_err)
+    });
+    let (reader, error) = match reader {
+        Ok(reader) => (Some(reader), None),
+        Err(err) => (None, Some(err)),
+    };
+    EventReader {
+        reader,
+        error,
+        pending: VecDeque::new(),
+    }
+}
+
+/// Copies a Parquet event file to `output`, keeping only the events `keep` accepts.
+///
+/// Kept rows retain the input schema, compression, and key-value metadata;
+/// the event count and event-time range metadata are recomputed. Returns the
+/// number of events read and kept; nothing is written when none are kept.
+pub fn filter_events(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    mut keep: impl FnMut(&Event) -> bool,
+) -> io::Result<(u64, u64)> {
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(input)?).map_err(map_parquet_err)?;
+    let schema = builder.schema().clone();
+    let metadata = builder.metadata().clone();
+    let reader = builder.build().map_err(map_parquet_err)?;
+
+    let mut props = WriterProperties::builder();
+    if let Some(column) = metadata
+        .row_groups()
+        .first()
+        .and_then(|row_group| row_group.columns().first())
+    {
+        props = props.set_compression(column.compression());
+    }
+    let inherited = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.key != "ARROW:schema" && !entry.key.starts_with("seclog."))
+        .cloned()
+        .collect::<Vec<_>>();
+    let props = props.set_key_value_metadata(Some(inherited)).build();
+
+    let mut writer = None;
+    let mut stats = FileStats::default();
+    let mut read = 0;
+    for batch in reader {
+        let batch = batch.map_err(map_arrow_err)?;
+        let mut runs = Vec::new();
+        let mut run_start = None;
+        for (row, event) in read_event_batch(&batch)?.iter().enumerate() {
+            read += 1;
+            if keep(event) {
+                stats.record(event);
+                run_start.get_or_insert(row);
+            } else if let Some(start) = run_start.take() {
+                runs.push((start, row - start));
+            }
+        }
+        if let Some(start) = run_start {
+            runs.push((start, batch.num_rows() - start));
+        }
+        for (offset, len) in runs {
+            if writer.is_none() {
+                if let Some(parent) = output.as_ref().parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let file = File::create(output.as_ref())?;
+                writer = Some(
+                    ArrowWriter::try_new(file, schema.clone(), Some(props.clone()))
+                        .map_err(map_parquet_err)?,
+                );
+            }
+            if let Some(writer) = writer.as_mut() {
+                writer
+                    .write(&batch.slice(offset, len))
+                    .map_err(map_parquet_err)?;
+            }
+        }
+    }
+    if let Some(mut writer) = writer {
+        for (key, value) in event_time_metadata(&stats) {
+            writer.append_key_value_metadata(KeyValue::new(key.to_string(), value));
+        }
+        writer.close().map_err(map_parquet_err)?;
+    }
+    Ok((read, stats.events))
+}
+
+/// Returns false for Parquet files without an `envelope` column (e.g. actor populations).
+pub fn is_event_file(path: impl AsRef<Path>) -> io::Result<bool> {
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(path)?).map_err(map_parquet_err)?;
+    Ok(builder.schema().column_with_name("envelope").is_some())
+}
+
+struct EventReader {
+    reader: Option<ParquetRecordBatchReader>,
+    error: Option<io::Error>,
+    pending: VecDeque<io::Result<Event>>,
+}
+
+impl Iterator for EventReader {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            let batch = match self.reader.as_mut()?.next()? {
+                Ok(batch) => batch,
+                Err(err) => {
+                    self.reader = None;
+                    return Some(Err(map_arrow_err(err)));
+                }
+            };
+            match read_event_batch(&batch) {
+                Ok(events) => self.pending.extend(events.into_iter().map(Ok)),
+                Err(err) => {
+                    self.reader = None;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+fn read_event_batch(batch: &RecordBatch) -> io::Result<Vec<Event>> {
+    let envelope = batch
+        .column_by_name("envelope")
+        .and_then(|column| column.as_struct_opt())
+        .ok_or_else(|| missing_column("envelope"))?;
+    let payload_json = batch
+        .column_by_name("payload_json")
+        .and_then(|column| column.as_string_opt::<i32>());
+    let cloudtrail = batch
+        .column_by_name("cloudtrail")
+        .and_then(|column| column.as_struct_opt());
+
+    let schema_version = string_field(envelope, "schema_version")?;
+    let timestamp = field(envelope, "timestamp")?;
+    let source = string_field(envelope, "source")?;
+    let event_type = string_field(envelope, "event_type")?;
+    let actor = struct_field(envelope, "actor")?;
+    let target = struct_field(envelope, "target")?;
+    let outcome = string_field(envelope, "outcome")?;
+    let geo = struct_field(envelope, "geo")?;
+    let ip = string_field(envelope, "ip")?;
+    let user_agent = string_field(envelope, "user_agent")?;
+    let session_id = string_field(envelope, "session_id")?;
+    let tenant_id = string_field(envelope, "tenant_id")?;
+    // Files written before multi-tenant runs have no `tenant` column.
+    let tenant = envelope
+        .column_by_name("tenant")
+        .and_then(|column| column.as_string_opt::<i32>());
+
+    let mut events = Vec::with_capacity(batch.num_rows());
+    for row in 0..batch.num_rows() {
+        let source = string_value(source, row).unwrap_or_default();
+        let payload = match payload_json.and_then(|column| string_value(column, row)) {
+            Some(json) => serde_json::from_str(&json).map_err(io::Error::other)?,
+            None => match cloudtrail.filter(|_| source == "cloudtrail") {
+                Some(cloudtrail) => cloudtrail_payload(cloudtrail, row)?,
+                None => Value::Null,
+            },
+        };
+        let envelope = crate::core::event::EventEnvelope {
+            schema_version: string_value(schema_version, row).unwrap_or_default(),
+            timestamp: timestamp_value(timestamp, row, SecondsFormat::Millis).unwrap_or_default(),
+            source,
+            event_type: string_value(event_type, row).unwrap_or_default(),
+            actor: Actor {
+                id: string_value(string_field(actor, "id")?, row).unwrap_or_default(),
+                kind: string_value(string_field(actor, "kind")?, row).unwrap_or_default(),
+                name: string_value(string_field(actor, "name")?, row),
+            },
+            target: if target.is_null(row) {
+                None
+            } else {
+                Some(Target {
+                    id: string_value(string_field(target, "id")?, row).unwrap_or_default(),
+                    kind: string_value(string_field(target, "kind")?, row).unwrap_or_default(),
+                    name: string_value(string_field(target, "name")?, row),
+                })
+            },
+            outcome: match string_value(outcome, row).as_deref() {
+                Some("success") => Outcome::Success,
+                Some("failure") => Outcome::Failure,
+                _ => Outcome::Unknown,
+            },
+            geo: if geo.is_null(row) {
+                None
+            } else {
+                Some(Geo {
+                    country: string_value(string_field(geo, "country")?, row).unwrap_or_default(),
+                    region: string_value(string_field(geo, "region")?, row),
+                    city: string_value(string_field(geo, "city")?, row),
+                    lat: float_value(field(geo, "lat")?, row),
+                    lon: float_value(field(geo, "lon")?, row),
+                })
+            },
+            ip: string_value(ip, row),
+            user_agent: string_value(user_agent, row),
+            session_id: string_value(session_id, row),
+            tenant_id: string_value(tenant_id, row),
+            tenant: tenant.and_then(|column| string_value(column, row)),
+        };
+        events.push(Event {
+            envelope,
+            payload,
+            label: None,
+        });
+    }
+    Ok(events)
+}
+
+/// Rebuilds a CloudTrail record from the flattened `cloudtrail` columns.
+fn cloudtrail_payload(cloudtrail: &StructArray, row: usize) -> io::Result<Value> {
+    if cloudtrail.is_null(row) {
+        return Ok(Value::Null);
+    }
+    let mut payload = Map::new();
+    for name in [
+        "eventVersion",
+        "eventSource",
+        "eventName",
+        "awsRegion",
+        "sourceIPAddress",
+        "userAgent",
+        "errorCode",
+        "errorMessage",
+        "requestID",
+        "eventID",
+        "eventType",
+        "recipientAccountId",
+        "eventCategory",
+    ] {
+        if let Some(value) = string_value(string_field(cloudtrail, name)?, row) {
+            payload.insert(name.to_string(), Value::String(value));
+        }
+    }
+    if let Some(time) = timestamp_value(field(cloudtrail, "eventTime")?, row, SecondsFormat::Secs) {
+        payload.insert("eventTime".to_string(), Value::String(time));
+    }
+    for name in [
+        "readOnly",
+        "managementEvent",
+        "sessionCredentialFromConsole",
+    ] {
+        let column = field(cloudtrail, name)?
+            .as_boolean_opt()
+            .ok_or_else(|| missing_column(name))?;
+        if !column.is_null(row) {
+            payload.insert(name.to_string(), Value::Bool(column.value(row)));
+        }
+    }
+    for (column, name) in [
+        ("requestParametersJson", "requestParameters"),
+        ("responseElementsJson", "responseElements"),
+        ("tlsDetailsJson", "tlsDetails"),
+    ] {
+        if let Some(json) = string_value(string_field(cloudtrail, column)?, row) {
+            payload.insert(
+                name.to_string(),
+                serde_json::from_str(&json).map_err(io::Error::other)?,
+            );
+        }
+    }
+    let identity = struct_field(cloudtrail, "userIdentity")?;
+    if !identity.is_null(row) {
+        let mut fields = Map::new();
+        for name in [
+            "type",
+            "principalId",
+            "arn",
+            "accountId",
+            "accessKeyId",
+            "userName",
+        ] {
+            if let Some(value) = string_value(string_field(identity, name)?, row) {
+                fields.insert(name.to_string(), Value::String(value));
+            }
+        }
+        payload.insert("userIdentity".to_string(), Value::Object(fields));
+    }
+    Ok(Value::Object(payload))
+}
+
+fn missing_column(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("parquet event file is missing column {name}"),
+    )
+}
+
+fn field<'a>(array: &'a StructArray, name: &str) -> io::Result<&'a ArrayRef> {
+    array
+        .column_by_name(name)
+        .ok_or_else(|| missing_column(name))
+}
+
+fn struct_field<'a>(array: &'a StructArray, name: &str) -> io::Result<&'a StructArray> {
+    field(array, name)?
+        .as_struct_opt()
+        .ok_or_else(|| missing_column(name))
+}
+
+fn string_field<'a>(array: &'a StructArray, name: &str) -> io::Result<&'a StringArray> {
+    field(array, name)?
+        .as_string_opt::<i32>()
+        .ok_or_else(|| missing_column(name))
+}
+
+fn string_value(array: &StringArray, row: usize) -> Option<String> {
+    (!array.is_null(row)).then(|| array.value(row).to_string())
+}
+
+fn float_value(array: &ArrayRef, row: usize) -> Option<f64> {
+    let array = array.as_primitive_opt::<Float64Type>()?;
+    (!array.is_null(row)).then(|| array.value(row))
+}
+
+/// Formats a typed timestamp at `precision`, widening it for sub-precision values;
+/// string timestamp columns are returned unchanged.
+fn timestamp_value(array: &ArrayRef, row: usize, precision: SecondsFormat) -> Option<String> {
+    if array.is_null(row) {
+        return None;
+    }
+    if let Some(strings) = array.as_string_opt::<i32>() {
+        return Some(strings.value(row).to_string());
+    }
+    let micros = array
+        .as_primitive_opt::<TimestampMicrosecondType>()?
+        .value(row);
+    let time = Utc.timestamp_micros(micros).single()?;
+    let precision = if micros % 1_000 != 0 {
+        SecondsFormat::Micros
+    } else if micros % 1_000_000 != 0 && precision == SecondsFormat::Secs {
+        SecondsFormat::Millis
+    } else {
+        precision
+    };
+    Some(time.to_rfc3339_opts(precision, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use arrow_array::{Array, StructArray, TimestampMicrosecondArray};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use serde_json::json;
+
+    fn read_timestamp_columns(options: &ParquetOptions, name: &str) -> (ArrayRef, ArrayRef) {
+        let dir =
+            std::env::temp_dir().join(format!("seclog-parquet-{name}-{}", std::process::id()));
+        let mut writer = ParquetWriter::with_options(&dir, 50, None, options).expect("writer");
+        let mut event = test_event(
+            "cloudtrail",
+            "GetObject",
+            json!({
+                "eventTime": "2026-01-01T00:00:01Z",
+                "recipientAccountId": "123456789012",
+                "awsRegion": "us-east-1"
+            }),
+        );
+        event.envelope.timestamp = "2026-01-01T00:00:01.5Z".to_string();
+        event.envelope.actor.id = "actor-1".to_string();
+        event.envelope.actor.kind = "human".to_string();
+        writer.write_event(&event).expect("write");
+        let mut okta = event.clone();
+        okta.envelope.source = "okta_system_log".to_string();
+        writer.write_event(&okta).expect("write non-cloudtrail");
+        writer.close().expect("close");
+
+        let path = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().contains("_CloudTrail_"))
+            .unwrap();
+        let mut reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batch = reader.next().unwrap().unwrap();
+        fs::remove_dir_all(&dir).ok();
+        let column = |index: usize| {
+            batch
+                .column(index)
+                .as_any()
+                .downcast_ref::<StructArray>()
+                .unwrap()
+                .column(1)
+                .clone()
+        };
+        (column(0), column(2))
+    }
+
+    #[test]
+    fn timestamps_are_typed_unless_strings_requested() {
+        let (envelope, cloudtrail) = read_timestamp_columns(&ParquetOptions::default(), "typed");
+        let expected = DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()));
+        assert_eq!(envelope.data_type(), &expected);
+        assert_eq!(cloudtrail.data_type(), &expected);
+        let micros = |array: &ArrayRef| {
+            array
+                .as_any()
+                .downcast_ref::<TimestampMicrosecondArray>()
+                .unwrap()
+                .value(0)
+        };
+        assert_eq!(micros(&envelope), 1_767_225_601_500_000);
+        assert_eq!(micros(&cloudtrail), 1_767_225_601_000_000);
+
+        let strings = ParquetOptions {
+            timestamp_strings: Some(true),
+            ..ParquetOptions::default()
+        };
+        let (envelope, cloudtrail) = read_timestamp_columns(&strings, "strings");
+        assert_eq!(envelope.data_type(), &DataType::Utf8);
+        assert_eq!(cloudtrail.data_type(), &DataType::Utf8);
+    }
+
+    #[test]
+    fn writer_properties_follow_format_options() {
+        let options = ParquetOptions {
+            compression: Some("zstd".to_string()),
+            compression_level: Some(6),
+            row_group_size: Some(2),
+            dictionary: Some(false),
+            statistics: Some("chunk".to_string()),
+            timestamp_strings: None,
+            encoder_threads: None,
+            encoder_queue: None,
+        };
+        let props = writer_properties(&options).expect("props");
+        let column = parquet::schema::types::ColumnPath::from("event_type");
+        assert_eq!(props.max_row_group_size(), 2);
+        assert!(!props.dictionary_enabled(&column));
+        assert_eq!(props.statistics_enabled(&column), EnabledStatistics::Chunk);
+
+        let dir = std::env::temp_dir().join(format!("seclog-parquet-{}", std::process::id()));
+        let path = dir.join("batch.parquet");
+        fs::create_dir_all(&dir).unwrap();
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "event_type",
+            DataType::Utf8,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(arrow_array::StringArray::from(vec!["a", "b", "c"])) as ArrayRef],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, Some(props))
+            .expect("writer");
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        assert!(matches!(
+            reader.metadata().row_group(0).column(0).compression(),
+            Compression::ZSTD(_)
+        ));
+        fs::remove_dir_all(&dir).ok();
+
+        for invalid in [
+            ParquetOptions {
+                compression: Some("lzma".to_string()),
+                ..ParquetOptions::default()
+            },
+            ParquetOptions {
+                compression: Some("snappy".to_string()),
+                compression_level: Some(3),
+                ..ParquetOptions::default()
+            },
+            ParquetOptions {
+                statistics: Some("full".to_string()),
+                ..ParquetOptions::default()
+            },
+        ] {
+            assert_eq!(
+                writer_properties(&invalid).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn background_encoders_report_every_rotated_file() {
+        let dir = std::env::temp_dir().join(format!("seclog-parquet-bg-{}", std::process::id()));
+        let options = ParquetOptions {
+            encoder_threads: Some(2),
+            encoder_queue: Some(1),
+            ..ParquetOptions::default()
+        };
+        // A zero target size rotates after every event.
+        let mut writer = ParquetWriter::with_options(&dir, 0, None, &options).expect("writer");
+        let mut written = Vec::new();
+        for index in 0..20 {
+            let mut event = test_event("okta_system_log", "user.session.start", Value::Null);
+            event.envelope.event_type = format!("event-{index}");
+            writer.write_event(&event).expect("write");
+            written.extend(writer.take_written_files());
+        }
+        writer.close().expect("close");
+        written.extend(writer.take_written_files());
+
+        assert_eq!(written.len(), 20);
+        assert!(written.iter().all(|file| file.stats.events == 1));
+        let files = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 20);
+        assert!(files
+            .iter()
+            .all(|path| path.extension().is_some_and(|ext| ext == "parquet")));
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn rotation_learns_encoded_size_per_source() {
+        let dir = std::env::temp_dir().join(format!("seclog-parquet-size-{}", unique_id()));
+        let options = ParquetOptions {
+            encoder_threads: Some(0),
+            ..ParquetOptions::default()
+        };
+        let mut writer = ParquetWriter::with_options(&dir, 1, None, &options).expect("writer");
+        writer.target_size_bytes = 64 * 1024;
+        let mut written = Vec::new();
+        let mut index = 0;
+        while written.len() < 3 {
+            let mut event = test_event("okta_system_log", "user.session.start", Value::Null);
+            event.envelope.session_id = Some(format!("session-{}", index % 50));
+            event.payload = json!({ "uuid": unique_id(), "index": index });
+            writer.write_event(&event).expect("write");
+            written.extend(writer.take_written_files());
+            index += 1;
+        }
+
+        let sizes = written
+            .iter()
+            .map(|file| fs::metadata(&file.path).expect("metadata").len())
+            .collect::<Vec<_>>();
+        // The first file rotates on the raw estimate; later ones on what
+        // the first actually took on disk.
+        assert!(written[1].stats.events > written[0].stats.events);
+        for size in &sizes[1..] {
+            assert!(*size > 48 * 1024 && *size < 80 * 1024, "{sizes:?}");
+        }
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn read_events_round_trips_written_events() {
+        let dir = std::env::temp_dir().join(format!("seclog-parquet-read-{}", std::process::id()));
+        let mut writer = ParquetWriter::new(&dir, 50, None).expect("writer");
+        let mut cloudtrail = test_event(
+            "cloudtrail",
+            "GetObject",
+            json!({
+                "eventTime": "2026-01-01T00:00:01Z",
+                "eventSource": "s3.amazonaws.com",
+                "eventName": "GetObject",
+                "awsRegion": "us-east-1",
+                "readOnly": true,
+                "errorCode": "AccessDenied",
+                "requestParameters": { "bucketName": "logs" },
+                "userIdentity": { "type": "IAMUser", "arn": "arn:aws:iam::123456789012:user/alice" },
+            }),
+        );
+        cloudtrail.envelope.timestamp = "2026-01-01T00:00:01.500Z".to_string();
+        cloudtrail.envelope.actor = Actor {
+            id: "actor-1".to_string(),
+            kind: "human".to_string(),
+            name: Some("Alice".to_string()),
+        };
+        cloudtrail.envelope.target = Some(Target {
+            id: "bucket-1".to_string(),
+            kind: "s3_bucket".to_string(),
+            name: None,
+        });
+        cloudtrail.envelope.outcome = Outcome::Failure;
+        cloudtrail.envelope.geo = Some(Geo {
+            country: "US".to_string(),
+            region: Some("WA".to_string()),
+            city: None,
+            lat: Some(47.6),
+            lon: None,
+        });
+        cloudtrail.envelope.ip = Some("198.51.100.7".to_string());
+        cloudtrail.envelope.session_id = Some("session-1".to_string());
+        cloudtrail.envelope.tenant_id = Some("tenant-a".to_string());
+        cloudtrail.envelope.tenant = Some("acme".to_string());
+        let mut okta = cloudtrail.clone();
+        okta.envelope.source = "okta_system_log".to_string();
+        okta.envelope.target = None;
+        okta.envelope.geo = None;
+        okta.payload = Value::Null;
+        writer.write_event(&cloudtrail).expect("write");
+        writer.write_event(&okta).expect("write");
+        writer.close().expect("close");
+
+        let mut events = Vec::new();
+        for path in fs::read_dir(&dir).unwrap() {
+            events.extend(read_events(path.unwrap().path()).map(|event| event.expect("event")));
+        }
+        events.sort_by(|a, b| a.envelope.source.cmp(&b.envelope.source));
+        let json = |event: &Event| serde_json::to_value(event).unwrap();
+        assert_eq!(json(&events[0]), json(&cloudtrail));
+        assert_eq!(json(&events[1]), json(&okta));
+
+        let path = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().contains("_CloudTrail_"))
+            .unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap();
+        let metadata = builder
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap();
+        let value = |key: &str| {
+            metadata
+                .iter()
+                .find(|entry| entry.key == key)
+                .and_then(|entry| entry.value.as_deref())
+        };
+        assert_eq!(value(EVENT_COUNT_KEY), Some("1"));
+        assert_eq!(value(MIN_EVENT_TIME_KEY), Some("2026-01-01T00:00:01.500Z"));
+        assert_eq!(value(MAX_EVENT_TIME_KEY), Some("2026-01-01T00:00:01.500Z"));
+        let batch = builder.build().unwrap().next().unwrap().unwrap();
+        let columns = batch.column(2).as_struct();
+        assert_eq!(cloudtrail_payload(columns, 0).unwrap(), cloudtrail.payload);
+
+        assert!(read_events(dir.join("missing.parquet"))
+            .next()
+            .unwrap()
+            .is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,224 @@
+//! Directory-partitioned sink wrapper.
+//!
+//! Routes each event to an inner writer rooted at a per-event subdirectory,
+//! creating writers lazily as new partitions appear.
+
+use crate::core::config::OutputPartitioning;
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+type WriterFactory = Box<dyn FnMut(&Path) -> io::Result<Box<dyn EventWriter>>>;
+type PartitionFn = Box<dyn Fn(&Event) -> PathBuf>;
+
+/// Event writer that fans out to one inner writer per partition directory.
+pub struct PartitionedWriter {
+    dir: PathBuf,
+    partition: PartitionFn,
+    factory: WriterFactory,
+    writers: HashMap<PathBuf, Box<dyn EventWriter>>,
+}
+
+impl PartitionedWriter {
+    /// Creates a partitioned writer; `factory` builds the writer for each partition directory.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        partition: impl Fn(&Event) -> PathBuf + 'static,
+        factory: impl FnMut(&Path) -> io::Result<Box<dyn EventWriter>> + 'static,
+    ) -> Self {
+        Self {
+            dir: dir.into(),
+            partition: Box::new(partition),
+            factory: Box::new(factory),
+            writers: HashMap::new(),
+        }
+    }
+}
+
+impl EventWriter for PartitionedWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let relative = (self.partition)(event);
+        if !self.writers.contains_key(&relative) {
+            let writer = (self.factory)(&self.dir.join(&relative))?;
+            self.writers.insert(relative.clone(), writer);
+        }
+        self.writers
+            .get_mut(&relative)
+            .expect("partition writer exists")
+            .write_event(event)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for writer in self.writers.values_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        for writer in self.writers.values_mut() {
+            writer.sync()?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        for writer in self.writers.values_mut() {
+            writer.close()?;
+        }
+        Ok(())
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        self.writers
+            .values_mut()
+            .flat_map(|writer| writer.take_written_files())
+            .collect()
+    }
+}
+
+/// Hive's placeholder for partitions whose value is missing or unparseable.
+const DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Partitions by owning tenant as `tenant=<id>`.
+pub fn tenant_partition(event: &Event) -> PathBuf {
+    let tenant = event
+        .envelope
+        .tenant
+        .as_deref()
+        .or(event.envelope.tenant_id.as_deref())
+        .unwrap_or("unknown");
+    PathBuf::from(format!("tenant={}", sanitize_segment(tenant)))
+}
+
+/// Partitions by source and simulated event time, e.g. `cloudtrail/date=2024-05-01/hour=13`.
+pub fn time_partition(event: &Event, partitioning: OutputPartitioning) -> PathBuf {
+    if partitioning == OutputPartitioning::None {
+        return PathBuf::new();
+    }
+    PathBuf::from(sanitize_segment(&event.envelope.source))
+        .join(hive_time_partition(event, partitioning))
+}
+
+/// Partitions by simulated event time alone, e.g. `date=2024-05-01/hour=13`.
+pub fn hive_time_partition(event: &Event, partitioning: OutputPartitioning) -> PathBuf {
+    let mut path = PathBuf::new();
+    if partitioning == OutputPartitioning::None {
+        return path;
+    }
+    let timestamp = DateTime::parse_from_rfc3339(&event.envelope.timestamp)
+        .ok()
+        .map(|value| value.with_timezone(&Utc));
+    match timestamp {
+        Some(timestamp) => {
+            path.push(format!("date={}", timestamp.format("%Y-%m-%d")));
+            if partitioning == OutputPartitioning::Hour {
+                path.push(format!("hour={}", timestamp.format("%H")));
+            }
+        }
+        None => {
+            path.push(format!("date={DEFAULT_PARTITION}"));
+            if partitioning == OutputPartitioning::Hour {
+                path.push(format!("hour={DEFAULT_PARTITION}"));
+            }
+        }
+    }
+    path
+}
+
+fn sanitize_segment(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::Value;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct RecordingWriter {
+        dir: PathBuf,
+        seen: Rc<RefCell<Vec<PathBuf>>>,
+    }
+
+    impl EventWriter for RecordingWriter {
+        fn write_event(&mut self, _event: &Event) -> io::Result<u64> {
+            self.seen.borrow_mut().push(self.dir.clone());
+            Ok(1)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn routes_events_to_tenant_directories() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let factory_seen = Rc::clone(&seen);
+        let mut writer = PartitionedWriter::new("out", tenant_partition, move |dir| {
+            Ok(Box::new(RecordingWriter {
+                dir: dir.to_path_buf(),
+                seen: Rc::clone(&factory_seen),
+            }) as Box<dyn EventWriter>)
+        });
+
+        for tenant in ["acme", "globex/emea", "acme"] {
+            writer
+                .write_event(&test_event("cloudtrail", "test.event", Value::Null).owned_by(tenant))
+                .expect("write");
+        }
+
+        assert_eq!(
+            *seen.borrow(),
+            vec![
+                PathBuf::from("out/tenant=acme"),
+                PathBuf::from("out/tenant=globex_emea"),
+                PathBuf::from("out/tenant=acme"),
+            ]
+        );
+        assert_eq!(writer.writers.len(), 2);
+    }
+
+    #[test]
+    fn time_partition_uses_event_timestamp() {
+        let mut event = test_event("cloudtrail", "test.event", Value::Null).owned_by("acme");
+        event.envelope.timestamp = "2024-05-01T13:45:00.123+02:00".to_string();
+        assert_eq!(
+            time_partition(&event, OutputPartitioning::Hour),
+            PathBuf::from("cloudtrail/date=2024-05-01/hour=11")
+        );
+        assert_eq!(
+            time_partition(&event, OutputPartitioning::Date),
+            PathBuf::from("cloudtrail/date=2024-05-01")
+        );
+        assert_eq!(
+            time_partition(&event, OutputPartitioning::None),
+            PathBuf::new()
+        );
+
+        event.envelope.timestamp = "not-a-time".to_string();
+        assert_eq!(
+            time_partition(&event, OutputPartitioning::Date),
+            PathBuf::from("cloudtrail/date=__HIVE_DEFAULT_PARTITION__")
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Write retries and fault injection for file writers.
+//!
+//! Without `output.write_retry` the first failed write stops the run. With it,
+//! [`RetryingWriter`] retries each failed write, flush, or close with
+//! exponential backoff and then either aborts, drops the event (`skip`), or
+//! writes it with its error to a dead-letter directory (`dead_letter`) and
+//! keeps going until the error budget runs out.
+//! [`FaultInjectingWriter`] returns disk-full and permission errors at random
+//! so that behavior can be exercised without breaking a real disk.
+
+use crate::core::config::{
+    FaultInjectionConfig, InjectedFault, WriteFailurePolicy, WriteRetryConfig,
+};
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use super::json::{current_stamp, unique_id};
+use chrono::{SecondsFormat, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Event writer that fails writes and flushes at random before they reach
+/// the inner writer, so a retried call starts from a clean state.
+pub struct FaultInjectingWriter {
+    inner: Box<dyn EventWriter>,
+    probability: f64,
+    faults: Vec<InjectedFault>,
+    rng: StdRng,
+}
+
+impl FaultInjectingWriter {
+    pub fn new(inner: Box<dyn EventWriter>, config: &FaultInjectionConfig) -> io::Result<Self> {
+        if !(0.0..=1.0).contains(&config.probability) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "fault_injection.probability must be between 0.0 and 1.0",
+            ));
+        }
+        if config.errors.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "fault_injection.errors must list at least one error",
+            ));
+        }
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Self {
+            inner,
+            probability: config.probability,
+            faults: config.errors.clone(),
+            rng,
+        })
+    }
+
+    fn maybe_fail(&mut self) -> io::Result<()> {
+        if !self.rng.gen_bool(self.probability) {
+            return Ok(());
+        }
+        let fault = self.faults[self.rng.gen_range(0..self.faults.len())];
+        Err(match fault {
+            InjectedFault::DiskFull => io::Error::new(
+                io::ErrorKind::StorageFull,
+                "no space left on device (injected)",
+            ),
+            InjectedFault::PermissionDenied => io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "permission denied (injected)",
+            ),
+        })
+    }
+}
+
+impl EventWriter for FaultInjectingWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        self.maybe_fail()?;
+        self.inner.write_event(event)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.maybe_fail()?;
+        self.inner.flush()
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.maybe_fail()?;
+        self.inner.sync()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.inner.close()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        self.inner.take_written_files()
+    }
+}
+
+/// Event writer that retries failed calls and applies the failure policy.
+pub struct RetryingWriter {
+    inner: Box<dyn EventWriter>,
+    config: WriteRetryConfig,
+    /// Events and flushes given up on so far.
+    skipped: u64,
+    /// Shared counter of skipped events, e.g. the run's dropped-events metric.
+    skipped_counter: Option<Arc<AtomicU64>>,
+    dead_letter: Option<DeadLetterFile>,
+    /// Shared counter of dead-lettered events.
+    dead_letter_counter: Option<Arc<AtomicU64>>,
+}
+
+impl RetryingWriter {
+    pub fn new(inner: Box<dyn EventWriter>, config: &WriteRetryConfig) -> io::Result<Self> {
+        let dead_letter = match (config.on_failure, &config.dead_letter_dir) {
+            (WriteFailurePolicy::DeadLetter, Some(dir)) => Some(DeadLetterFile::new(dir)),
+            (WriteFailurePolicy::DeadLetter, None) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "write_retry.on_failure = \"dead_letter\" requires write_retry.dead_letter_dir",
+                ))
+            }
+            _ => None,
+        };
+        Ok(Self {
+            inner,
+            config: config.clone(),
+            skipped: 0,
+            skipped_counter: None,
+            dead_letter,
+            dead_letter_counter: None,
+        })
+    }
+
+    /// Also counts skipped events in `counter`.
+    pub fn with_skipped_counter(mut self, counter: Arc<AtomicU64>) -> Self {
+        self.skipped_counter = Some(counter);
+        self
+    }
+
+    /// Also counts dead-lettered events in `counter`.
+    pub fn with_dead_letter_counter(mut self, counter: Arc<AtomicU64>) -> Self {
+        self.dead_letter_counter = Some(counter);
+        self
+    }
+
+    /// Events and flushes given up on under the `skip` or `dead_letter` policy.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Runs `call` until it succeeds or the retries are spent.
+    fn retry<T>(
+        &mut self,
+        mut call: impl FnMut(&mut dyn EventWriter) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut backoff = Duration::from_millis(self.config.initial_backoff_ms);
+        let max_backoff = Duration::from_millis(self.config.max_backoff_ms);
+        let mut attempt = 0;
+        loop {
+            match call(self.inner.as_mut()) {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt >= self.config.retries => return Err(err),
+                Err(err) => {
+                    tracing::debug!(attempt, error = %err, "write failed; retrying");
+                    std::thread::sleep(backoff.min(max_backoff));
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Applies the failure policy to an error that outlasted its retries.
+    fn give_up(&mut self, err: io::Error) -> io::Result<()> {
+        if self.config.on_failure == WriteFailurePolicy::Abort {
+            return Err(err);
+        }
+        self.skipped += 1;
+        tracing::warn!(skipped = self.skipped, error = %err, "giving up on write after retries");
+        if let Some(budget) = self.config.error_budget {
+            if self.skipped > budget {
+                return Err(io::Error::new(
+                    err.kind(),
+                    format!(
+                        "write error budget of {budget} exhausted after {} failures; last error: {err}",
+                        self.skipped
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl EventWriter for RetryingWriter {
+    /// Returns 0 bytes for an event skipped or dead-lettered after its retries.
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let err = match self.retry(|writer| writer.write_event(event)) {
+            Ok(bytes) => return Ok(bytes),
+            Err(err) => err,
+        };
+        // Dead-letter before the budget check so the event that exhausts the
+        // budget is kept too.
+        if let Some(dead_letter) = self.dead_letter.as_mut() {
+            dead_letter.write(event, &err)?;
+            if let Some(counter) = &self.dead_letter_counter {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.give_up(err)?;
+        if self.dead_letter.is_none() {
+            if let Some(counter) = &self.skipped_counter {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Ok(0)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.retry(|writer| writer.flush()) {
+            Ok(()) => Ok(()),
+            Err(err) => self.give_up(err),
+        }
+    }
+
+    /// Retried but never skipped, like `close`: a checkpoint must not commit
+    /// output that was not written.
+    fn sync(&mut self) -> io::Result<()> {
+        self.retry(|writer| writer.sync())
+    }
+
+    /// Closing is retried but never skipped, since a file left open loses data.
+    fn close(&mut self) -> io::Result<()> {
+        self.retry(|writer| writer.close())
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        self.inner.take_written_files()
+    }
+}
+
+/// One dead-letter record: the event a writer gave up on and why.
+#[derive(Serialize)]
+struct DeadLetterRecord<'a> {
+    failed_at: String,
+    error: String,
+    error_kind: String,
+    event: &'a Event,
+}
+
+/// JSON Lines file of dead-lettered events, created on the first failure.
+///
+/// Records are written straight to the file, unbuffered, so they survive the
+/// run aborting later.
+struct DeadLetterFile {
+    dir: PathBuf,
+    file: Option<File>,
+}
+
+impl DeadLetterFile {
+    fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            file: None,
+        }
+    }
+
+    fn write(&mut self, event: &Event, err: &io::Error) -> io::Result<()> {
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => {
+                fs::create_dir_all(&self.dir)?;
+                let path = self.dir.join(format!(
+                    "dead-letter-{}_{}.jsonl",
+                    current_stamp(),
+                    unique_id()
+                ));
+                self.file.insert(File::create(path)?)
+            }
+        };
+        let record = DeadLetterRecord {
+            failed_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            error: err.to_string(),
+            error_kind: format!("{:?}", err.kind()),
+            event,
+        };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        file.write_all(&line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::Value;
+
+    struct CountingWriter(Arc<AtomicU64>);
+
+    impl EventWriter for CountingWriter {
+        fn write_event(&mut self, _event: &Event) -> io::Result<u64> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(10)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn faulty(probability: f64, written: &Arc<AtomicU64>) -> Box<dyn EventWriter> {
+        let config = FaultInjectionConfig {
+            probability,
+            errors: vec![InjectedFault::DiskFull],
+            seed: Some(7),
+        };
+        Box::new(
+            FaultInjectingWriter::new(Box::new(CountingWriter(Arc::clone(written))), &config)
+                .unwrap(),
+        )
+    }
+
+    fn retry_config(retries: u32, on_failure: WriteFailurePolicy) -> WriteRetryConfig {
+        WriteRetryConfig {
+            retries,
+            initial_backoff_ms: 0,
+            max_backoff_ms: 0,
+            on_failure,
+            error_budget: Some(5),
+            dead_letter_dir: None,
+        }
+    }
+
+    #[test]
+    fn retries_injected_faults_and_enforces_error_budget() {
+        let event = test_event("cloudtrail", "GetObject", Value::Null);
+        let written = Arc::new(AtomicU64::new(0));
+        let mut writer = RetryingWriter::new(
+            faulty(0.3, &written),
+            &retry_config(8, WriteFailurePolicy::Abort),
+        )
+        .unwrap();
+        for _ in 0..200 {
+            writer.write_event(&event).unwrap();
+        }
+        assert_eq!(written.load(Ordering::Relaxed), 200);
+
+        let written = Arc::new(AtomicU64::new(0));
+        let mut writer = RetryingWriter::new(
+            faulty(1.0, &written),
+            &retry_config(1, WriteFailurePolicy::Skip),
+        )
+        .unwrap();
+        for _ in 0..5 {
+            assert_eq!(writer.write_event(&event).unwrap(), 0);
+        }
+        let err = writer.write_event(&event).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+        assert!(err.to_string().contains("budget of 5 exhausted"), "{err}");
+        assert_eq!(writer.skipped(), 6);
+
+        let mut writer = RetryingWriter::new(
+            faulty(1.0, &written),
+            &retry_config(2, WriteFailurePolicy::Abort),
+        )
+        .unwrap();
+        assert!(writer.write_event(&event).is_err());
+        assert_eq!(written.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn dead_letters_failed_events_with_their_error() {
+        let event = test_event("cloudtrail", "GetObject", Value::Null);
+        let dir = std::env::temp_dir().join(format!("seclog-dead-letter-{}", unique_id()));
+        let mut config = retry_config(1, WriteFailurePolicy::DeadLetter);
+        assert!(RetryingWriter::new(faulty(1.0, &Arc::default()), &config).is_err());
+
+        config.dead_letter_dir = Some(dir.to_string_lossy().into_owned());
+        let dead_lettered = Arc::new(AtomicU64::new(0));
+        let mut writer = RetryingWriter::new(faulty(1.0, &Arc::default()), &config)
+            .unwrap()
+            .with_dead_letter_counter(Arc::clone(&dead_lettered));
+        for _ in 0..3 {
+            assert_eq!(writer.write_event(&event).unwrap(), 0);
+        }
+        writer.flush().unwrap();
+        assert_eq!(dead_lettered.load(Ordering::Relaxed), 3);
+
+        let files = fs::read_dir(&dir).unwrap().collect::<Vec<_>>();
+        assert_eq!(files.len(), 1);
+        let contents = fs::read_to_string(files[0].as_ref().unwrap().path()).unwrap();
+        let records = contents
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["error_kind"], "StorageFull");
+        assert!(records[0]["error"].as_str().unwrap().contains("injected"));
+        assert_eq!(records[0]["event"]["envelope"]["event_type"], "GetObject");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
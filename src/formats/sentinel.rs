@@ -0,0 +1,729 @@
+//! Microsoft Sentinel sink through the Azure Monitor Logs Ingestion API.
+//!
+//! Uploads JSON arrays of rows to
+//! `{endpoint}/dataCollectionRules/{dcr}/streams/{stream}`, where the data
+//! collection rule maps each stream onto a Log Analytics (Sentinel) table.
+//! Rows are the source-native records with `TimeGenerated` set to the event
+//! time, grouped per stream and kept under the API's 1 MB request limit. Up
+//! to `max_in_flight` uploads are sent at once. Requests carry an Entra ID
+//! token from the client-credentials flow.
+
+// Without the `sentinel` feature only the tests use the batching sink.
+#![cfg_attr(not(feature = "sentinel"), allow(dead_code))]
+
+use super::json::TENANT_KEY;
+use crate::core::config::SentinelOutputConfig;
+use crate::core::event::Event;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use super::async_sink::{AsyncEventSink, SinkFuture, SinkRecord};
+#[cfg(feature = "tokio")]
+use flate2::{write::GzEncoder, Compression};
+#[cfg(feature = "tokio")]
+use std::collections::BTreeMap;
+#[cfg(feature = "tokio")]
+use std::io::Write;
+
+const API_VERSION: &str = "2023-01-01";
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+/// Longest `Retry-After` honored, so a throttled run keeps reporting progress.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Sentinel sink used by the CLI, run behind
+/// [`AsyncSinkWriter`](super::async_sink::AsyncSinkWriter).
+pub struct SentinelSink {
+    inner: PlatformSentinelSink,
+}
+
+impl SentinelSink {
+    pub fn new(config: &SentinelOutputConfig) -> io::Result<Self> {
+        Ok(Self {
+            inner: build_platform_sink(config)?,
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncEventSink for SentinelSink {
+    fn max_in_flight(&self) -> usize {
+        self.inner.max_in_flight()
+    }
+
+    fn write_batch(&mut self, records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+        self.inner.write_batch(records)
+    }
+
+    fn flush(&mut self) -> SinkFuture<'_, ()> {
+        self.inner.flush()
+    }
+
+    fn close(&mut self) -> SinkFuture<'_, ()> {
+        self.inner.close()
+    }
+}
+
+#[cfg(feature = "sentinel")]
+type PlatformSentinelSink = IngestionSink<RealIngestionClient>;
+
+#[cfg(not(feature = "sentinel"))]
+struct PlatformSentinelSink;
+
+#[cfg(feature = "sentinel")]
+fn build_platform_sink(config: &SentinelOutputConfig) -> io::Result<PlatformSentinelSink> {
+    let settings = IngestionSettings::from_config(config)?;
+    let client = RealIngestionClient::from_config(config)?;
+    Ok(IngestionSink::new(settings, client))
+}
+
+#[cfg(not(feature = "sentinel"))]
+fn build_platform_sink(_config: &SentinelOutputConfig) -> io::Result<PlatformSentinelSink> {
+    Err(feature_required())
+}
+
+#[cfg(not(feature = "sentinel"))]
+fn feature_required() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "sentinel output requires building with --features sentinel",
+    )
+}
+
+#[cfg(all(feature = "tokio", not(feature = "sentinel")))]
+impl AsyncEventSink for PlatformSentinelSink {
+    fn write_batch(&mut self, _records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+        Box::pin(async { Err(feature_required()) })
+    }
+
+    fn flush(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn close(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Response to one upload.
+struct UploadResponse {
+    status: u16,
+    body: String,
+    /// `Retry-After` sent with throttled responses.
+    retry_after: Option<Duration>,
+}
+
+/// Uploads one JSON array of rows to a DCR stream.
+///
+/// Each upload owns a clone of the client, so several can be in flight.
+#[cfg(feature = "tokio")]
+trait IngestionClient: Clone + Send + Sync + 'static {
+    fn upload(
+        &self,
+        stream: &str,
+        body: Vec<u8>,
+        gzip: bool,
+    ) -> SinkFuture<'static, UploadResponse>;
+}
+
+/// Stream routing and batching settings resolved from the output config.
+struct IngestionSettings {
+    stream: Option<String>,
+    streams: HashMap<String, String>,
+    batch_size: usize,
+    max_request_bytes: usize,
+    max_in_flight: usize,
+    gzip: bool,
+    max_retries: u32,
+}
+
+impl IngestionSettings {
+    fn from_config(config: &SentinelOutputConfig) -> io::Result<Self> {
+        let gzip = match config.compression.as_deref() {
+            None => false,
+            Some(value) if value.eq_ignore_ascii_case("gzip") => true,
+            Some(other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported sentinel compression: {other}"),
+                ))
+            }
+        };
+        Ok(Self {
+            stream: config.stream.clone(),
+            streams: config.streams.clone(),
+            batch_size: config.batch_size.max(1),
+            max_request_bytes: config.max_request_bytes,
+            max_in_flight: config.max_in_flight.max(1),
+            gzip,
+            max_retries: config.max_retries,
+        })
+    }
+
+    fn stream_for(&self, source: &str) -> io::Result<&str> {
+        self.streams
+            .get(source)
+            .or(self.stream.as_ref())
+            .map(String::as_str)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("no sentinel stream configured for source {source}"),
+                )
+            })
+    }
+}
+
+/// Rows buffered for one stream, comma-joined without the array brackets.
+#[derive(Default)]
+struct StreamBatch {
+    rows: Vec<u8>,
+    count: usize,
+}
+
+#[cfg(feature = "tokio")]
+struct IngestionSink<C: IngestionClient> {
+    client: C,
+    settings: IngestionSettings,
+    batches: BTreeMap<String, StreamBatch>,
+}
+
+#[cfg(feature = "tokio")]
+impl<C: IngestionClient> IngestionSink<C> {
+    fn new(settings: IngestionSettings, client: C) -> Self {
+        Self {
+            client,
+            settings,
+            batches: BTreeMap::new(),
+        }
+    }
+
+    /// Appends `records` to their stream batches and takes an upload whenever
+    /// a batch fills; returns the row bytes appended and the uploads to send.
+    fn append_batch(
+        &mut self,
+        records: &[SinkRecord],
+    ) -> io::Result<(u64, Vec<SinkFuture<'static, ()>>)> {
+        let mut bytes = 0;
+        let mut uploads = Vec::new();
+        for record in records {
+            bytes += self.append(&record.event, &mut uploads)?;
+        }
+        Ok((bytes, uploads))
+    }
+
+    fn append(
+        &mut self,
+        event: &Event,
+        uploads: &mut Vec<SinkFuture<'static, ()>>,
+    ) -> io::Result<u64> {
+        let stream = self
+            .settings
+            .stream_for(&event.envelope.source)?
+            .to_string();
+        let row = serde_json::to_vec(&row_for_event(event)?).map_err(io::Error::other)?;
+        // Two bytes of brackets around a single row.
+        if row.len() + 2 > self.settings.max_request_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "sentinel row of {} bytes for {} exceeds max_request_bytes ({})",
+                    row.len(),
+                    event.envelope.source,
+                    self.settings.max_request_bytes
+                ),
+            ));
+        }
+        let pending = self
+            .batches
+            .get(&stream)
+            .map_or(0, |batch| batch.rows.len());
+        if pending > 0 && pending + row.len() + 3 > self.settings.max_request_bytes {
+            uploads.extend(self.take_upload(&stream)?);
+        }
+        let batch = self.batches.entry(stream.clone()).or_default();
+        if batch.count > 0 {
+            batch.rows.push(b',');
+        }
+        batch.rows.extend_from_slice(&row);
+        batch.count += 1;
+        if batch.count >= self.settings.batch_size {
+            uploads.extend(self.take_upload(&stream)?);
+        }
+        Ok(row.len() as u64)
+    }
+
+    /// Takes the rows buffered for `stream` as one upload, or `None` when
+    /// nothing is buffered.
+    fn take_upload(&mut self, stream: &str) -> io::Result<Option<SinkFuture<'static, ()>>> {
+        let Some(batch) = self.batches.remove(stream) else {
+            return Ok(None);
+        };
+        if batch.count == 0 {
+            return Ok(None);
+        }
+        let mut body = Vec::with_capacity(batch.rows.len() + 2);
+        body.push(b'[');
+        body.extend_from_slice(&batch.rows);
+        body.push(b']');
+        let gzip = self.settings.gzip;
+        let body = if gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&body)?;
+            encoder.finish()?
+        } else {
+            body
+        };
+        let client = self.client.clone();
+        let stream = stream.to_string();
+        let max_retries = self.settings.max_retries;
+        Ok(Some(Box::pin(async move {
+            upload_with_retry(&client, &stream, body, gzip, max_retries).await
+        })))
+    }
+
+    /// Uploads every stream's buffered rows and waits for the uploads.
+    async fn send_all(&mut self) -> io::Result<()> {
+        let streams = self.batches.keys().cloned().collect::<Vec<_>>();
+        for stream in streams {
+            if let Some(upload) = self.take_upload(&stream)? {
+                upload.await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Uploads `body`, retrying throttled and server error responses.
+#[cfg(feature = "tokio")]
+async fn upload_with_retry<C: IngestionClient>(
+    client: &C,
+    stream: &str,
+    body: Vec<u8>,
+    gzip: bool,
+    max_retries: u32,
+) -> io::Result<()> {
+    let mut attempt = 0;
+    loop {
+        let response = client.upload(stream, body.clone(), gzip).await?;
+        if (200..300).contains(&response.status) {
+            return Ok(());
+        }
+        let retryable = response.status == 429 || response.status >= 500;
+        if retryable && attempt < max_retries {
+            let delay = response
+                .retry_after
+                .map(|delay| delay.min(MAX_RETRY_AFTER))
+                .unwrap_or(RETRY_BASE_DELAY * 2_u32.pow(attempt));
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+            continue;
+        }
+        return Err(io::Error::other(format!(
+            "sentinel upload to stream {stream} failed with HTTP {}: {}",
+            response.status,
+            azure_error_text(&response.body)
+        )));
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<C: IngestionClient> AsyncEventSink for IngestionSink<C> {
+    fn max_in_flight(&self) -> usize {
+        self.settings.max_in_flight
+    }
+
+    fn write_batch(&mut self, records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+        let appended = self.append_batch(&records);
+        Box::pin(async move {
+            let (bytes, uploads) = appended?;
+            for upload in uploads {
+                upload.await?;
+            }
+            Ok(bytes)
+        })
+    }
+
+    fn flush(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(self.send_all())
+    }
+
+    fn close(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(self.send_all())
+    }
+}
+
+/// Source-native record with `TimeGenerated` set to the event time and the
+/// owning tenant, if any, under [`TENANT_KEY`]. Records that are not JSON
+/// objects are kept under `RawData`.
+fn row_for_event(event: &Event) -> io::Result<Value> {
+    let record = if event.payload.is_null() {
+        serde_json::to_value(event).map_err(io::Error::other)?
+    } else {
+        event.payload.clone()
+    };
+    let mut row = match record {
+        Value::Object(map) => map,
+        other => {
+            let mut map = Map::new();
+            map.insert("RawData".to_string(), other);
+            map
+        }
+    };
+    // Whole events already carry the tenant in their envelope.
+    let tenant = event.envelope.tenant.as_ref();
+    if let Some(tenant) = tenant.filter(|_| !event.payload.is_null()) {
+        row.insert(TENANT_KEY.to_string(), json!(tenant));
+    }
+    row.entry("TimeGenerated")
+        .or_insert_with(|| json!(event.envelope.timestamp));
+    Ok(Value::Object(row))
+}
+
+/// Extracts `error.code` and `error.message` from an Azure error body, falling
+/// back to the raw body.
+fn azure_error_text(body: &str) -> String {
+    let error = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|value| value.get("error").cloned());
+    match error {
+        Some(error) => format!(
+            "{}: {}",
+            error.get("code").and_then(Value::as_str).unwrap_or(""),
+            error.get("message").and_then(Value::as_str).unwrap_or("")
+        ),
+        None => body.trim().to_string(),
+    }
+}
+
+fn read_env(field: &str, name: &str) -> io::Result<String> {
+    let value = env::var(name).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("sentinel {field} env var {name} is not set"),
+        )
+    })?;
+    if value.trim().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("sentinel {field} env var {name} is empty"),
+        ));
+    }
+    Ok(value)
+}
+
+/// Bearer token and the moment it should be refreshed.
+#[cfg(feature = "sentinel")]
+struct AccessToken {
+    value: String,
+    refresh_at: std::time::Instant,
+}
+
+#[cfg(feature = "sentinel")]
+#[derive(Clone)]
+struct RealIngestionClient {
+    endpoint: String,
+    dcr_immutable_id: String,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    /// Shared by every upload, so one of them fetches a new token while the
+    /// others wait for it.
+    token: std::sync::Arc<tokio::sync::Mutex<Option<AccessToken>>>,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "sentinel")]
+impl RealIngestionClient {
+    fn from_config(config: &SentinelOutputConfig) -> io::Result<Self> {
+        let tenant_id = read_env("tenant_id", &config.tenant_id_env)?;
+        Ok(Self {
+            endpoint: config.endpoint.trim_end_matches('/').to_string(),
+            dcr_immutable_id: config.dcr_immutable_id.clone(),
+            token_url: format!(
+                "{}/{tenant_id}/oauth2/v2.0/token",
+                config.authority_host.trim_end_matches('/')
+            ),
+            client_id: read_env("client_id", &config.client_id_env)?,
+            client_secret: read_env("client_secret", &config.client_secret_env)?,
+            token: Default::default(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// Returns a cached token, fetching a new one five minutes before expiry.
+    async fn access_token(&self) -> io::Result<String> {
+        let mut token = self.token.lock().await;
+        if let Some(token) = token.as_ref() {
+            if std::time::Instant::now() < token.refresh_at {
+                return Ok(token.value.clone());
+            }
+        }
+        let response = self
+            .client
+            .post(&self.token_url)
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("scope", "https://monitor.azure.com//.default"),
+            ])
+            .send()
+            .await
+            .map_err(|err| io::Error::other(format!("sentinel token request failed: {err}")))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let parsed = serde_json::from_str::<Value>(&body).unwrap_or(Value::Null);
+        let Some(value) = parsed.get("access_token").and_then(Value::as_str) else {
+            let reason = parsed
+                .get("error_description")
+                .and_then(Value::as_str)
+                .unwrap_or(body.trim());
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("sentinel token request failed with HTTP {status}: {reason}"),
+            ));
+        };
+        let expires_in = parsed
+            .get("expires_in")
+            .and_then(Value::as_u64)
+            .unwrap_or(3600);
+        *token = Some(AccessToken {
+            value: value.to_string(),
+            refresh_at: std::time::Instant::now()
+                + Duration::from_secs(expires_in.saturating_sub(300)),
+        });
+        Ok(value.to_string())
+    }
+}
+
+#[cfg(feature = "sentinel")]
+impl IngestionClient for RealIngestionClient {
+    fn upload(
+        &self,
+        stream: &str,
+        body: Vec<u8>,
+        gzip: bool,
+    ) -> SinkFuture<'static, UploadResponse> {
+        let client = self.clone();
+        let stream = stream.to_string();
+        let url = format!(
+            "{}/dataCollectionRules/{}/streams/{stream}?api-version={API_VERSION}",
+            self.endpoint, self.dcr_immutable_id
+        );
+        Box::pin(async move {
+            let mut refreshed = false;
+            loop {
+                let token = client.access_token().await?;
+                let mut request = client
+                    .client
+                    .post(&url)
+                    .bearer_auth(token)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.clone());
+                if gzip {
+                    request = request.header(reqwest::header::CONTENT_ENCODING, "gzip");
+                }
+                let response = request.send().await.map_err(|err| {
+                    io::Error::other(format!("sentinel upload to stream {stream} failed: {err}"))
+                })?;
+                let status = response.status().as_u16();
+                // A revoked or early-expired token gets one fresh token.
+                if status == 401 && !refreshed {
+                    *client.token.lock().await = None;
+                    refreshed = true;
+                    continue;
+                }
+                let retry_after = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                return Ok(UploadResponse {
+                    status,
+                    body: response.text().await.unwrap_or_default(),
+                    retry_after,
+                });
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::SentinelOutputType;
+    #[cfg(feature = "tokio")]
+    use crate::fixtures::{sink_records, test_event};
+    #[cfg(feature = "tokio")]
+    use flate2::read::GzDecoder;
+    #[cfg(feature = "tokio")]
+    use std::collections::VecDeque;
+    #[cfg(feature = "tokio")]
+    use std::io::Read;
+    #[cfg(feature = "tokio")]
+    use std::sync::{Arc, Mutex};
+
+    /// Stream and uncompressed body of one upload.
+    #[cfg(feature = "tokio")]
+    type Upload = (String, Vec<u8>);
+
+    /// Replays canned statuses and records each upload.
+    #[cfg(feature = "tokio")]
+    #[derive(Clone, Default)]
+    struct FakeClient {
+        statuses: Arc<Mutex<VecDeque<u16>>>,
+        uploads: Arc<Mutex<Vec<Upload>>>,
+    }
+
+    #[cfg(feature = "tokio")]
+    impl IngestionClient for FakeClient {
+        fn upload(
+            &self,
+            stream: &str,
+            body: Vec<u8>,
+            gzip: bool,
+        ) -> SinkFuture<'static, UploadResponse> {
+            let status = self.statuses.lock().unwrap().pop_front().unwrap_or(204);
+            let uploads = Arc::clone(&self.uploads);
+            let stream = stream.to_string();
+            Box::pin(async move {
+                let body = if gzip {
+                    let mut plain = Vec::new();
+                    GzDecoder::new(body.as_slice()).read_to_end(&mut plain)?;
+                    plain
+                } else {
+                    body
+                };
+                uploads.lock().unwrap().push((stream, body));
+                Ok(UploadResponse {
+                    status,
+                    body: String::new(),
+                    retry_after: Some(Duration::ZERO),
+                })
+            })
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("runtime")
+            .block_on(future)
+    }
+
+    fn test_config() -> SentinelOutputConfig {
+        SentinelOutputConfig {
+            output_type: SentinelOutputType::Sentinel,
+            endpoint: "https://seclog-a1b2.eastus-1.ingest.monitor.azure.com".to_string(),
+            dcr_immutable_id: "dcr-00000000000000000000000000000000".to_string(),
+            stream: Some("Custom-SeclogEvents".to_string()),
+            streams: HashMap::from([(
+                "okta_system_log".to_string(),
+                "Custom-OktaSystemLog".to_string(),
+            )]),
+            tenant_id_env: "AZURE_TENANT_ID".to_string(),
+            client_id_env: "AZURE_CLIENT_ID".to_string(),
+            client_secret_env: "AZURE_CLIENT_SECRET".to_string(),
+            authority_host: "https://login.microsoftonline.com".to_string(),
+            batch_size: 100,
+            max_request_bytes: 150,
+            max_in_flight: 4,
+            flush_interval_ms: 1000,
+            compression: Some("gzip".to_string()),
+            max_retries: 3,
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    fn rows(body: &[u8]) -> Vec<Value> {
+        serde_json::from_slice(body).unwrap()
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn routes_streams_and_splits_uploads_under_the_size_limit() {
+        let settings = IngestionSettings::from_config(&test_config()).unwrap();
+        assert_eq!(settings.max_in_flight, 4);
+        let client = FakeClient {
+            statuses: Arc::new(Mutex::new(VecDeque::from([429]))),
+            ..FakeClient::default()
+        };
+        let mut sink = IngestionSink::new(settings, client);
+        let events = [
+            ("cloudtrail", "GetObject"),
+            ("cloudtrail", "PutObject"),
+            ("cloudtrail", "ListBuckets"),
+            ("okta_system_log", "user.session.start"),
+        ]
+        .map(|(source, name)| {
+            test_event(source, name, json!({ "eventName": name })).at("2026-01-01T00:00:00.250Z")
+        });
+        block_on(async {
+            sink.write_batch(sink_records(&events)).await.unwrap();
+            sink.close().await.unwrap();
+        });
+
+        let uploads = sink.client.uploads.lock().unwrap();
+        assert_eq!(uploads.len(), 4, "throttled upload is retried once");
+        assert_eq!(uploads[0], uploads[1]);
+        assert!(uploads.iter().all(|(_, body)| body.len() <= 150));
+        assert_eq!(
+            rows(&uploads[1].1),
+            vec![
+                json!({ "eventName": "GetObject", "TimeGenerated": "2026-01-01T00:00:00.250Z" }),
+                json!({ "eventName": "PutObject", "TimeGenerated": "2026-01-01T00:00:00.250Z" }),
+            ]
+        );
+        assert_eq!(uploads[2].0, "Custom-OktaSystemLog");
+        assert_eq!(uploads[3].0, "Custom-SeclogEvents");
+        assert_eq!(rows(&uploads[3].1)[0]["eventName"], "ListBuckets");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn batch_uploads_run_independently_of_the_sink() {
+        let config = SentinelOutputConfig {
+            batch_size: 1,
+            compression: None,
+            ..test_config()
+        };
+        let settings = IngestionSettings::from_config(&config).unwrap();
+        let mut sink = IngestionSink::new(settings, FakeClient::default());
+        let first = sink.write_batch(sink_records(&[test_event(
+            "cloudtrail",
+            "GetObject",
+            json!({ "eventName": "GetObject" }),
+        )]));
+        let second = sink.write_batch(sink_records(&[test_event(
+            "okta_system_log",
+            "user.session.start",
+            json!({ "eventName": "user.session.start" }),
+        )]));
+        let (second, first) = block_on(async { (second.await, first.await) });
+        assert!(first.unwrap() > 0 && second.unwrap() > 0);
+
+        let uploads = sink.client.uploads.lock().unwrap();
+        let streams = uploads
+            .iter()
+            .map(|(stream, _)| stream.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(streams, ["Custom-OktaSystemLog", "Custom-SeclogEvents"]);
+    }
+
+    #[cfg(not(feature = "sentinel"))]
+    #[test]
+    fn platform_writer_requires_feature() {
+        let err = SentinelSink::new(&test_config()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "sentinel output requires building with --features sentinel"
+        );
+    }
+}
@@ -0,0 +1,777 @@
+//! Splunk HTTP Event Collector sink.
+//!
+//! Batches events into `/services/collector/event` requests, one JSON envelope
+//! (`time`, `host`, `source`, `sourcetype`, `index`, `event`) per event, with
+//! per-source index and sourcetype routing. Events of a multi-tenant run also
+//! carry their tenant as the indexed field `tenant`. Up to `max_in_flight` requests
+//! are sent at once. Busy indexers (`503`, `429`) are retried with backoff,
+//! and with `ack` enabled every request is tracked until
+//! `/services/collector/ack` reports it indexed.
+
+// Without the `splunk_hec` feature only the tests use the batching sink.
+#![cfg_attr(not(feature = "splunk_hec"), allow(dead_code))]
+
+use crate::core::config::SplunkHecOutputConfig;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::io;
+use std::time::Duration;
+
+#[cfg(feature = "tokio")]
+use super::async_sink::{AsyncEventSink, SinkFuture, SinkRecord};
+#[cfg(feature = "tokio")]
+use crate::core::event::Event;
+#[cfg(feature = "tokio")]
+use flate2::{write::GzEncoder, Compression};
+#[cfg(feature = "tokio")]
+use serde_json::json;
+#[cfg(feature = "tokio")]
+use std::collections::BTreeMap;
+#[cfg(feature = "tokio")]
+use std::io::Write;
+#[cfg(feature = "tokio")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "tokio")]
+use std::time::Instant;
+
+const EVENT_PATH: &str = "/services/collector/event";
+const ACK_PATH: &str = "/services/collector/ack";
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+const CLOSE_ACK_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Splunk HEC sink used by the CLI, run behind
+/// [`AsyncSinkWriter`](super::async_sink::AsyncSinkWriter).
+pub struct SplunkHecSink {
+    inner: PlatformSplunkHecSink,
+}
+
+impl SplunkHecSink {
+    pub fn new(config: &SplunkHecOutputConfig) -> io::Result<Self> {
+        Ok(Self {
+            inner: build_platform_sink(config)?,
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncEventSink for SplunkHecSink {
+    fn max_in_flight(&self) -> usize {
+        self.inner.max_in_flight()
+    }
+
+    fn write_batch(&mut self, records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+        self.inner.write_batch(records)
+    }
+
+    fn flush(&mut self) -> SinkFuture<'_, ()> {
+        self.inner.flush()
+    }
+
+    fn close(&mut self) -> SinkFuture<'_, ()> {
+        self.inner.close()
+    }
+}
+
+#[cfg(feature = "splunk_hec")]
+type PlatformSplunkHecSink = HecSink<RealHecClient>;
+
+#[cfg(not(feature = "splunk_hec"))]
+struct PlatformSplunkHecSink;
+
+#[cfg(feature = "splunk_hec")]
+fn build_platform_sink(config: &SplunkHecOutputConfig) -> io::Result<PlatformSplunkHecSink> {
+    let settings = HecSettings::from_config(config)?;
+    let client = RealHecClient::from_config(config, settings.channel.as_deref())?;
+    Ok(HecSink::new(settings, client))
+}
+
+#[cfg(not(feature = "splunk_hec"))]
+fn build_platform_sink(_config: &SplunkHecOutputConfig) -> io::Result<PlatformSplunkHecSink> {
+    Err(feature_required())
+}
+
+#[cfg(not(feature = "splunk_hec"))]
+fn feature_required() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "splunk_hec output requires building with --features splunk_hec",
+    )
+}
+
+#[cfg(all(feature = "tokio", not(feature = "splunk_hec")))]
+impl AsyncEventSink for PlatformSplunkHecSink {
+    fn write_batch(&mut self, _records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+        Box::pin(async { Err(feature_required()) })
+    }
+
+    fn flush(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn close(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Sends one POST to the HEC and resolves to the status code and response body.
+///
+/// Each request owns a clone of the client, so several can be in flight.
+#[cfg(feature = "tokio")]
+trait HecClient: Clone + Send + Sync + 'static {
+    fn post(
+        &self,
+        path: &'static str,
+        body: Vec<u8>,
+        gzip: bool,
+    ) -> SinkFuture<'static, (u16, String)>;
+}
+
+/// Routing, batching, and ack settings resolved from the output config.
+struct HecSettings {
+    host: Option<String>,
+    index: Option<String>,
+    indexes: HashMap<String, String>,
+    sourcetypes: HashMap<String, String>,
+    batch_size: usize,
+    max_in_flight: usize,
+    gzip: bool,
+    channel: Option<String>,
+    ack: bool,
+    ack_timeout: Duration,
+    max_retries: u32,
+}
+
+impl HecSettings {
+    fn from_config(config: &SplunkHecOutputConfig) -> io::Result<Self> {
+        let gzip = match config.compression.as_deref() {
+            None => false,
+            Some(value) if value.eq_ignore_ascii_case("gzip") => true,
+            Some(other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unsupported splunk_hec compression: {other}"),
+                ))
+            }
+        };
+        let channel = match &config.channel {
+            Some(channel) => Some(channel.clone()),
+            None if config.ack => Some(uuid_like()),
+            None => None,
+        };
+        Ok(Self {
+            host: config.host.clone(),
+            index: config.index.clone(),
+            indexes: config.indexes.clone(),
+            sourcetypes: config.sourcetypes.clone(),
+            batch_size: config.batch_size.max(1),
+            max_in_flight: config.max_in_flight.max(1),
+            gzip,
+            channel,
+            ack: config.ack,
+            ack_timeout: Duration::from_millis(config.ack_timeout_ms),
+            max_retries: config.max_retries,
+        })
+    }
+}
+
+#[cfg(feature = "tokio")]
+struct HecSink<C: HecClient> {
+    client: C,
+    settings: HecSettings,
+    body: Vec<u8>,
+    buffered: usize,
+    /// Ack IDs still waiting to be indexed, with the time each request was
+    /// sent. Requests in flight add theirs when the HEC answers.
+    pending_acks: Arc<Mutex<BTreeMap<u64, Instant>>>,
+}
+
+#[cfg(feature = "tokio")]
+impl<C: HecClient> HecSink<C> {
+    fn new(settings: HecSettings, client: C) -> Self {
+        Self {
+            client,
+            settings,
+            body: Vec::new(),
+            buffered: 0,
+            pending_acks: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Takes the buffered events as one request, or `None` when nothing is buffered.
+    fn take_request(&mut self) -> io::Result<Option<SinkFuture<'static, ()>>> {
+        if self.buffered == 0 {
+            return Ok(None);
+        }
+        let body = std::mem::take(&mut self.body);
+        self.buffered = 0;
+        let gzip = self.settings.gzip;
+        let body = if gzip {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&body)?;
+            encoder.finish()?
+        } else {
+            body
+        };
+        let client = self.client.clone();
+        let max_retries = self.settings.max_retries;
+        let pending_acks = self.settings.ack.then(|| Arc::clone(&self.pending_acks));
+        Ok(Some(Box::pin(async move {
+            let response = post_with_retry(&client, EVENT_PATH, body, gzip, max_retries).await?;
+            if let Some(pending_acks) = pending_acks {
+                let ack_id = serde_json::from_str::<Value>(&response)
+                    .ok()
+                    .and_then(|value| value.get("ackId").and_then(Value::as_u64))
+                    .ok_or_else(|| {
+                        io::Error::other(format!(
+                            "splunk_hec response has no ackId; is indexer acknowledgement enabled on the token? {response}"
+                        ))
+                    })?;
+                pending_acks
+                    .lock()
+                    .expect("pending acks lock")
+                    .insert(ack_id, Instant::now());
+            }
+            Ok(())
+        })))
+    }
+
+    /// Sends what is buffered and waits for the request.
+    async fn send_buffered(&mut self) -> io::Result<()> {
+        match self.take_request()? {
+            Some(request) => request.await,
+            None => Ok(()),
+        }
+    }
+
+    /// Asks which pending requests are indexed and fails on any past `ack_timeout`.
+    async fn poll_acks(&self) -> io::Result<()> {
+        let ids = self
+            .pending_acks
+            .lock()
+            .expect("pending acks lock")
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return Ok(());
+        }
+        let body = serde_json::to_vec(&json!({ "acks": ids })).map_err(io::Error::other)?;
+        let response = post_with_retry(
+            &self.client,
+            ACK_PATH,
+            body,
+            false,
+            self.settings.max_retries,
+        )
+        .await?;
+        let acks = serde_json::from_str::<Value>(&response)
+            .ok()
+            .and_then(|value| value.get("acks").and_then(Value::as_object).cloned())
+            .unwrap_or_default();
+        let mut pending_acks = self.pending_acks.lock().expect("pending acks lock");
+        for (id, indexed) in acks {
+            if indexed.as_bool() == Some(true) {
+                if let Ok(id) = id.parse::<u64>() {
+                    pending_acks.remove(&id);
+                }
+            }
+        }
+
+        let timeout = self.settings.ack_timeout;
+        let expired = pending_acks
+            .values()
+            .filter(|sent| sent.elapsed() > timeout)
+            .count();
+        if expired > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!(
+                    "splunk_hec: {expired} requests were not acknowledged within {}ms",
+                    timeout.as_millis()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn acks_pending(&self) -> bool {
+        !self
+            .pending_acks
+            .lock()
+            .expect("pending acks lock")
+            .is_empty()
+    }
+
+    /// Appends `records` to the body and takes a request every `batch_size`
+    /// events; returns the bytes appended and the requests to send.
+    fn append_batch(
+        &mut self,
+        records: &[SinkRecord],
+    ) -> io::Result<(u64, Vec<SinkFuture<'static, ()>>)> {
+        let mut bytes = 0;
+        let mut requests = Vec::new();
+        for record in records {
+            bytes += self.append(&record.event, &record.bytes)?;
+            if self.buffered >= self.settings.batch_size {
+                requests.extend(self.take_request()?);
+            }
+        }
+        Ok((bytes, requests))
+    }
+
+    fn append(&mut self, event: &Event, record: &[u8]) -> io::Result<u64> {
+        let source = event.envelope.source.as_str();
+        let mut envelope = serde_json::Map::new();
+        envelope.insert(
+            "time".to_string(),
+            json!(epoch_seconds(&event.envelope.timestamp)),
+        );
+        if let Some(host) = &self.settings.host {
+            envelope.insert("host".to_string(), json!(host));
+        }
+        envelope.insert("source".to_string(), json!(format!("seclog:{source}")));
+        let sourcetype = self
+            .settings
+            .sourcetypes
+            .get(source)
+            .cloned()
+            .unwrap_or_else(|| default_sourcetype(source));
+        envelope.insert("sourcetype".to_string(), json!(sourcetype));
+        if let Some(index) = self
+            .settings
+            .indexes
+            .get(source)
+            .or(self.settings.index.as_ref())
+        {
+            envelope.insert("index".to_string(), json!(index));
+        }
+        if let Some(tenant) = &event.envelope.tenant {
+            envelope.insert("fields".to_string(), json!({ "tenant": tenant }));
+        }
+
+        // Splice the source-native record in as `event` without re-parsing it.
+        let mut line = serde_json::to_vec(&envelope).map_err(io::Error::other)?;
+        line.pop();
+        line.extend_from_slice(br#","event":"#);
+        line.extend_from_slice(record);
+        line.extend_from_slice(b"}\n");
+        self.body.extend_from_slice(&line);
+        self.buffered += 1;
+        Ok(line.len() as u64)
+    }
+}
+
+/// Posts `body`, retrying busy responses; returns the success response body.
+#[cfg(feature = "tokio")]
+async fn post_with_retry<C: HecClient>(
+    client: &C,
+    path: &'static str,
+    body: Vec<u8>,
+    gzip: bool,
+    max_retries: u32,
+) -> io::Result<String> {
+    let mut attempt = 0;
+    loop {
+        let (status, response) = client.post(path, body.clone(), gzip).await?;
+        if (200..300).contains(&status) {
+            return Ok(response);
+        }
+        if matches!(status, 429 | 503) && attempt < max_retries {
+            tokio::time::sleep(RETRY_BASE_DELAY * 2_u32.pow(attempt)).await;
+            attempt += 1;
+            continue;
+        }
+        return Err(io::Error::other(format!(
+            "splunk_hec {path} failed with HTTP {status}: {}",
+            hec_error_text(&response)
+        )));
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<C: HecClient> AsyncEventSink for HecSink<C> {
+    fn max_in_flight(&self) -> usize {
+        self.settings.max_in_flight
+    }
+
+    fn write_batch(&mut self, records: Vec<SinkRecord>) -> SinkFuture<'static, u64> {
+        let appended = self.append_batch(&records);
+        Box::pin(async move {
+            let (bytes, requests) = appended?;
+            for request in requests {
+                request.await?;
+            }
+            Ok(bytes)
+        })
+    }
+
+    fn flush(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(async move {
+            self.send_buffered().await?;
+            self.poll_acks().await
+        })
+    }
+
+    fn close(&mut self) -> SinkFuture<'_, ()> {
+        Box::pin(async move {
+            self.send_buffered().await?;
+            loop {
+                self.poll_acks().await?;
+                if !self.acks_pending() {
+                    return Ok(());
+                }
+                tokio::time::sleep(CLOSE_ACK_POLL_INTERVAL).await;
+            }
+        })
+    }
+}
+
+/// Sourcetypes the Splunk add-ons use for each source, or `seclog:<source>`.
+fn default_sourcetype(source: &str) -> String {
+    match source {
+        "cloudtrail" => "aws:cloudtrail",
+        "okta_system_log" => "OktaIM2:log",
+        "databricks_audit" => "databricks:audit",
+        "github_audit" => "github:enterprise:audit",
+        "entra_signin" => "azure:aad:signin",
+        "entra_audit" => "azure:aad:audit",
+        "kubernetes_audit" => "kube:apiserver:audit",
+        "s3_access_log" => "aws:s3:accesslogs",
+        "alb_access_log" => "aws:elb:accesslogs",
+        "route53_resolver" => "aws:route53:resolver",
+        "windows_security" => "WinEventLog:Security",
+        other => return format!("seclog:{other}"),
+    }
+    .to_string()
+}
+
+/// HEC `time`: epoch seconds with millisecond precision.
+fn epoch_seconds(timestamp: &str) -> f64 {
+    let millis = DateTime::parse_from_rfc3339(timestamp)
+        .map(|parsed| parsed.with_timezone(&Utc).timestamp_millis())
+        .unwrap_or_else(|_| Utc::now().timestamp_millis());
+    millis as f64 / 1000.0
+}
+
+/// Extracts `text` and `code` from a HEC error body, falling back to the raw body.
+fn hec_error_text(response: &str) -> String {
+    match serde_json::from_str::<Value>(response) {
+        Ok(value) => match (value.get("text"), value.get("code")) {
+            (Some(text), Some(code)) => format!("{} (code {code})", text.as_str().unwrap_or("")),
+            _ => response.trim().to_string(),
+        },
+        Err(_) => response.trim().to_string(),
+    }
+}
+
+fn uuid_like() -> String {
+    let mut rng = rand::thread_rng();
+    let hex: String = (0..32)
+        .map(|_| std::char::from_digit(rng.gen_range(0..16), 16).unwrap())
+        .collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn read_token(name: &str) -> io::Result<String> {
+    let value = env::var(name).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("splunk_hec token env var {name} is not set"),
+        )
+    })?;
+    if value.trim().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("splunk_hec token env var {name} is empty"),
+        ));
+    }
+    Ok(value)
+}
+
+#[cfg(feature = "splunk_hec")]
+#[derive(Clone)]
+struct RealHecClient {
+    url: String,
+    token: String,
+    channel: Option<String>,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "splunk_hec")]
+impl RealHecClient {
+    fn from_config(config: &SplunkHecOutputConfig, channel: Option<&str>) -> io::Result<Self> {
+        let token = read_token(&config.token_env)?;
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(config.insecure_skip_verify)
+            .build()
+            .map_err(io::Error::other)?;
+        Ok(Self {
+            url: config.url.trim_end_matches('/').to_string(),
+            token,
+            channel: channel.map(str::to_string),
+            client,
+        })
+    }
+}
+
+#[cfg(feature = "splunk_hec")]
+impl HecClient for RealHecClient {
+    fn post(
+        &self,
+        path: &'static str,
+        body: Vec<u8>,
+        gzip: bool,
+    ) -> SinkFuture<'static, (u16, String)> {
+        let mut request = self
+            .client
+            .post(format!("{}{path}", self.url))
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("Splunk {}", self.token),
+            )
+            .body(body);
+        if let Some(channel) = &self.channel {
+            request = request.header("X-Splunk-Request-Channel", channel);
+        }
+        if gzip {
+            request = request.header(reqwest::header::CONTENT_ENCODING, "gzip");
+        }
+        Box::pin(async move {
+            let response = request.send().await.map_err(|err| {
+                io::Error::other(format!("splunk_hec request to {path} failed: {err}"))
+            })?;
+            let status = response.status().as_u16();
+            Ok((status, response.text().await.unwrap_or_default()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::SplunkHecOutputType;
+    #[cfg(feature = "tokio")]
+    use crate::fixtures::{sink_records, test_event};
+    use flate2::read::GzDecoder;
+    #[cfg(feature = "tokio")]
+    use serde_json::json;
+    #[cfg(feature = "tokio")]
+    use std::collections::VecDeque;
+    use std::io::Read;
+
+    /// Path, body, and gzip flag of one request.
+    #[cfg(feature = "tokio")]
+    type Request = (String, Vec<u8>, bool);
+
+    /// Replays canned responses and records each request.
+    #[cfg(feature = "tokio")]
+    #[derive(Clone, Default)]
+    struct FakeClient {
+        responses: Arc<Mutex<VecDeque<(u16, String)>>>,
+        requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    #[cfg(feature = "tokio")]
+    impl FakeClient {
+        fn replying(responses: impl IntoIterator<Item = (u16, &'static str)>) -> Self {
+            let responses = responses
+                .into_iter()
+                .map(|(status, body)| (status, body.to_string()))
+                .collect();
+            Self {
+                responses: Arc::new(Mutex::new(responses)),
+                ..Self::default()
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl HecClient for FakeClient {
+        fn post(
+            &self,
+            path: &'static str,
+            body: Vec<u8>,
+            gzip: bool,
+        ) -> SinkFuture<'static, (u16, String)> {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((path.to_string(), body, gzip));
+            let response = self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or((200, r#"{"text":"Success","code":0}"#.to_string()));
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .expect("runtime")
+            .block_on(future)
+    }
+
+    fn test_config() -> SplunkHecOutputConfig {
+        SplunkHecOutputConfig {
+            output_type: SplunkHecOutputType::SplunkHec,
+            url: "https://splunk.example.com:8088".to_string(),
+            token_env: "SPLUNK_HEC_TOKEN".to_string(),
+            index: Some("seclog".to_string()),
+            indexes: HashMap::from([("okta_system_log".to_string(), "okta".to_string())]),
+            sourcetypes: HashMap::new(),
+            host: Some("seclog-load".to_string()),
+            batch_size: 2,
+            max_in_flight: 4,
+            flush_interval_ms: 1000,
+            compression: Some("gzip".to_string()),
+            ack: false,
+            channel: None,
+            ack_timeout_ms: 60_000,
+            max_retries: 3,
+            insecure_skip_verify: false,
+        }
+    }
+
+    fn gunzip(bytes: &[u8]) -> Vec<Value> {
+        let mut text = String::new();
+        GzDecoder::new(bytes).read_to_string(&mut text).unwrap();
+        text.lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn batches_envelopes_with_index_routing_and_retries_busy_indexers() {
+        let settings = HecSettings::from_config(&test_config()).unwrap();
+        let client = FakeClient::replying([(503, r#"{"text":"Server is busy","code":9}"#)]);
+        let mut sink = HecSink::new(settings, client);
+        let events = [
+            test_event(
+                "cloudtrail",
+                "GetObject",
+                json!({ "eventName": "GetObject" }),
+            )
+            .at("2026-01-01T00:00:00.250Z"),
+            test_event("okta_system_log", "user.session.start", json!({})),
+            test_event("github_audit", "repo.create", json!({})),
+        ];
+        block_on(async {
+            sink.write_batch(sink_records(&events)).await.unwrap();
+            sink.close().await.unwrap();
+        });
+
+        let requests = sink.client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3, "busy response is retried once");
+        assert_eq!(requests[0].1, requests[1].1);
+        assert!(requests
+            .iter()
+            .all(|(path, _, gzip)| path == EVENT_PATH && *gzip));
+
+        let first = gunzip(&requests[1].1);
+        assert_eq!(
+            first[0],
+            json!({
+                "time": 1767225600.25,
+                "host": "seclog-load",
+                "source": "seclog:cloudtrail",
+                "sourcetype": "aws:cloudtrail",
+                "index": "seclog",
+                "event": { "eventName": "GetObject" },
+            })
+        );
+        assert_eq!(first[1]["index"], "okta");
+        assert_eq!(first[1]["sourcetype"], "OktaIM2:log");
+        assert_eq!(
+            gunzip(&requests[2].1)[0]["sourcetype"],
+            "github:enterprise:audit"
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn close_waits_for_indexer_acknowledgement() {
+        let config = SplunkHecOutputConfig {
+            ack: true,
+            compression: None,
+            ..test_config()
+        };
+        let settings = HecSettings::from_config(&config).unwrap();
+        assert!(settings.channel.is_some());
+        let client = FakeClient::replying([
+            (200, r#"{"text":"Success","code":0,"ackId":7}"#),
+            (200, r#"{"acks":{"7":false}}"#),
+            (200, r#"{"acks":{"7":true}}"#),
+        ]);
+        let mut sink = HecSink::new(settings, client);
+        block_on(async {
+            sink.write_batch(sink_records(&[test_event(
+                "cloudtrail",
+                "GetObject",
+                json!({}),
+            )]))
+            .await
+            .unwrap();
+            sink.close().await.unwrap();
+        });
+
+        let requests = sink.client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].0, ACK_PATH);
+        assert_eq!(
+            serde_json::from_slice::<Value>(&requests[1].1).unwrap(),
+            json!({ "acks": [7] })
+        );
+        assert!(!sink.acks_pending());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn splices_queued_record_bytes_without_reserializing() {
+        let config = SplunkHecOutputConfig {
+            compression: None,
+            ..test_config()
+        };
+        let settings = HecSettings::from_config(&config).unwrap();
+        assert_eq!(settings.max_in_flight, 4);
+        let mut sink = HecSink::new(settings, FakeClient::default());
+        let record = SinkRecord {
+            event: test_event("cloudtrail", "GetObject", json!({})).owned_by("acme"),
+            bytes: br#"{"eventName":"AsQueued"}"#.to_vec(),
+        };
+        block_on(async {
+            sink.write_batch(vec![record]).await.unwrap();
+            sink.close().await.unwrap();
+        });
+
+        let requests = sink.client.requests.lock().unwrap();
+        let line = serde_json::from_slice::<Value>(&requests[0].1).unwrap();
+        assert_eq!(line["event"], json!({ "eventName": "AsQueued" }));
+        assert_eq!(line["fields"], json!({ "tenant": "acme" }));
+    }
+
+    #[cfg(not(feature = "splunk_hec"))]
+    #[test]
+    fn platform_writer_requires_feature() {
+        let err = SplunkHecSink::new(&test_config()).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "splunk_hec output requires building with --features splunk_hec"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! NDJSON stream writer.
+//!
+//! Writes one JSON record per line to stdout or stderr so generated events can
+//! be piped into other tools (`seclog gen ... | jq`, `| vector`). Records use
+//! the same JSON as the JSONL writer, one per line instead of a `Records` array.
+
+use super::json::record_bytes_for_event;
+use crate::core::config::OutputStream;
+use crate::core::event::Event;
+use crate::core::traits::EventWriter;
+use std::io::{self, BufWriter, Write};
+
+/// Event writer that streams NDJSON to a standard stream or any `Write`.
+pub struct StdoutWriter {
+    out: BufWriter<Box<dyn Write + Send>>,
+}
+
+impl StdoutWriter {
+    /// Writes to the given standard stream.
+    pub fn for_stream(stream: OutputStream) -> Self {
+        match stream {
+            OutputStream::Stdout => Self::new(Box::new(io::stdout())),
+            OutputStream::Stderr => Self::new(Box::new(io::stderr())),
+        }
+    }
+
+    pub fn new(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: BufWriter::new(out),
+        }
+    }
+}
+
+impl EventWriter for StdoutWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let mut record = record_bytes_for_event(event)?;
+        record.push(b'\n');
+        self.out.write_all(&record)?;
+        Ok(record.len() as u64)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_one_record_per_line() {
+        let buffer = SharedBuffer::default();
+        let mut writer = StdoutWriter::new(Box::new(buffer.clone()));
+        let bytes = writer
+            .write_event(&test_event(
+                "cloudtrail",
+                "GetObject",
+                json!({ "eventName": "GetObject" }),
+            ))
+            .unwrap()
+            + writer
+                .write_event(&test_event(
+                    "cloudtrail",
+                    "PutObject",
+                    json!({ "eventName": "PutObject" }),
+                ))
+                .unwrap();
+        writer.close().unwrap();
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(bytes, output.len() as u64);
+        let names = output
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["eventName"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![json!("GetObject"), json!("PutObject")]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Fan-out sink wrapper.
+//!
+//! Writes every event to each inner writer, so one event stream can feed
+//! several formats (e.g. Parquet for analytics and JSONL for a collector).
+
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use std::io;
+
+/// Event writer that copies each event to all of its inner writers.
+pub struct TeeWriter {
+    writers: Vec<Box<dyn EventWriter>>,
+}
+
+impl TeeWriter {
+    pub fn new(writers: Vec<Box<dyn EventWriter>>) -> Self {
+        Self { writers }
+    }
+}
+
+impl EventWriter for TeeWriter {
+    /// Returns the bytes written across all inner writers.
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let mut bytes = 0;
+        for writer in &mut self.writers {
+            bytes += writer.write_event(event)?;
+        }
+        Ok(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for writer in &mut self.writers {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        for writer in &mut self.writers {
+            writer.sync()?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        for writer in &mut self.writers {
+            writer.close()?;
+        }
+        Ok(())
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        self.writers
+            .iter_mut()
+            .flat_map(|writer| writer.take_written_files())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::Value;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct CountingWriter {
+        name: &'static str,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl EventWriter for CountingWriter {
+        fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+            self.log
+                .borrow_mut()
+                .push(format!("{}:{}", self.name, event.envelope.event_type));
+            Ok(10)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            self.log.borrow_mut().push(format!("{}:close", self.name));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn copies_each_event_to_every_writer() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut writer = TeeWriter::new(vec![
+            Box::new(CountingWriter {
+                name: "parquet",
+                log: Rc::clone(&log),
+            }),
+            Box::new(CountingWriter {
+                name: "jsonl",
+                log: Rc::clone(&log),
+            }),
+        ]);
+        let event = test_event("cloudtrail", "GetObject", Value::Null);
+
+        assert_eq!(writer.write_event(&event).expect("write"), 20);
+        writer.close().expect("close");
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "parquet:GetObject",
+                "jsonl:GetObject",
+                "parquet:close",
+                "jsonl:close"
+            ]
+        );
+    }
+}
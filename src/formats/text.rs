@@ -0,0 +1,405 @@
+//! Raw text line sink.
+//!
+//! Writes S3 server access log records as space-delimited lines under one
+//! directory per bucket, named the way S3 delivers them
+//! (`<bucket>/YYYY-mm-DD-HH-MM-SS-<UNIQUE>`). ALB access log records are
+//! written as space-delimited lines in one file per load balancer, named like
+//! ELB log objects. Windows Security events are
+//! written as rendered event XML, one `<Event>` per line, in one file per
+//! computer. Events from other sources are written as one JSON record per
+//! line per source/account/region, named by `files.filename_template`.
+
+use super::filename::{FileName, FilenameTemplate};
+use super::json::{
+    file_context_from_event, parse_compression, record_bytes_for_event, unique_id, FileBody,
+    JsonlCompression,
+};
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, FileStats, WrittenFile};
+use crate::sources::alb_access::model::AlbAccessLogRecord;
+use crate::sources::s3_access::model::S3AccessLogRecord;
+use crate::sources::windows_security::model::WindowsSecurityEvent;
+use chrono::Utc;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Text writer that buffers lines per bucket (S3 access logs), per load
+/// balancer (ALB access logs), per computer (Windows Security), or per
+/// source/account/region.
+pub struct TextWriter {
+    dir: PathBuf,
+    target_size_bytes: u64,
+    max_age: Option<Duration>,
+    compression: JsonlCompression,
+    filename: FilenameTemplate,
+    files: HashMap<StreamKey, LineBuffer>,
+    written: Vec<WrittenFile>,
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+enum StreamKey {
+    Bucket(String),
+    LoadBalancer {
+        account_id: String,
+        region: String,
+        elb: String,
+    },
+    Computer(String),
+    Source {
+        source: String,
+        account_id: String,
+        region: String,
+    },
+}
+
+impl StreamKey {
+    /// Source label reported for files written under this key.
+    fn source(&self) -> &str {
+        match self {
+            StreamKey::Bucket(_) => "S3AccessLog",
+            StreamKey::LoadBalancer { .. } => "AlbAccessLog",
+            StreamKey::Computer(_) => "WindowsSecurity",
+            StreamKey::Source { source, .. } => source,
+        }
+    }
+}
+
+struct LineBuffer {
+    body: FileBody,
+    first_event_at: Option<Instant>,
+    stats: FileStats,
+    /// Files written for this key so far, for `{seq}`.
+    files: u64,
+}
+
+impl TextWriter {
+    /// Creates a text writer with size-based rotation and optional max age.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        target_size_mb: u64,
+        max_age_seconds: Option<u64>,
+        compression: Option<&str>,
+    ) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let max_age = max_age_seconds
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs);
+        Ok(Self {
+            dir,
+            target_size_bytes: target_size_mb.saturating_mul(1024 * 1024),
+            max_age,
+            compression: parse_compression(compression)?,
+            filename: FilenameTemplate::default(),
+            files: HashMap::new(),
+            written: Vec::new(),
+        })
+    }
+
+    /// Names per-source JSON line files with `template`; S3 access log, ALB,
+    /// and Windows Security files keep their native names.
+    pub fn with_filename_template(mut self, template: FilenameTemplate) -> Self {
+        self.filename = template;
+        self
+    }
+}
+
+impl EventWriter for TextWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let (key, line) = if event.envelope.source == "s3_access_log" {
+            let record = S3AccessLogRecord::deserialize(&event.payload)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            (StreamKey::Bucket(record.bucket.clone()), record.line())
+        } else if event.envelope.source == "alb_access_log" {
+            let record = AlbAccessLogRecord::deserialize(&event.payload)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let (region, account_id) = record.region_and_account().unwrap_or_else(|| {
+                (
+                    "global",
+                    event
+                        .envelope
+                        .tenant_id
+                        .as_deref()
+                        .unwrap_or("000000000000"),
+                )
+            });
+            let key = StreamKey::LoadBalancer {
+                account_id: account_id.to_string(),
+                region: region.to_string(),
+                elb: record.elb.clone(),
+            };
+            (key, record.line())
+        } else if event.envelope.source == "windows_security" {
+            let record = WindowsSecurityEvent::deserialize(&event.payload)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            (
+                StreamKey::Computer(record.system.computer.clone()),
+                record.xml(),
+            )
+        } else {
+            let context = file_context_from_event(event);
+            let record = String::from_utf8(record_bytes_for_event(event)?)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let key = StreamKey::Source {
+                source: context.source,
+                account_id: context.account_id,
+                region: context.region,
+            };
+            (key, record)
+        };
+        let size = line.len() as u64 + 1;
+
+        let compression = self.compression;
+        let stream = self.files.entry(key.clone()).or_insert_with(|| LineBuffer {
+            body: FileBody::new(compression),
+            first_event_at: None,
+            stats: FileStats::default(),
+            files: 0,
+        });
+        if stream.stats.events == 0 {
+            stream.first_event_at = Some(Instant::now());
+        }
+        stream.body.write(line.as_bytes())?;
+        stream.body.write(b"\n")?;
+        stream.stats.record(event);
+
+        if stream.body.len() >= self.target_size_bytes {
+            self.written.extend(flush_stream(
+                &self.dir,
+                &key,
+                stream,
+                self.compression,
+                &self.filename,
+            )?);
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let now = Instant::now();
+        for (key, stream) in self.files.iter_mut() {
+            if stream.stats.events == 0 {
+                continue;
+            }
+            if let (Some(max_age), Some(start)) = (self.max_age, stream.first_event_at) {
+                if now.duration_since(start) < max_age {
+                    continue;
+                }
+            }
+            self.written.extend(flush_stream(
+                &self.dir,
+                key,
+                stream,
+                self.compression,
+                &self.filename,
+            )?);
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        for (key, stream) in self.files.iter_mut() {
+            self.written.extend(flush_stream(
+                &self.dir,
+                key,
+                stream,
+                self.compression,
+                &self.filename,
+            )?);
+        }
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.sync()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        std::mem::take(&mut self.written)
+    }
+}
+
+fn stream_path(
+    dir: &Path,
+    key: &StreamKey,
+    compression: JsonlCompression,
+    filename: &FilenameTemplate,
+    seq: u64,
+    stats: &FileStats,
+) -> io::Result<PathBuf> {
+    let now = Utc::now();
+    let mut path = match key {
+        StreamKey::Bucket(bucket) => {
+            let dir = dir.join(bucket);
+            fs::create_dir_all(&dir)?;
+            dir.join(format!(
+                "{}-{}",
+                now.format("%Y-%m-%d-%H-%M-%S"),
+                unique_id().to_uppercase()
+            ))
+            .into_os_string()
+        }
+        StreamKey::LoadBalancer {
+            account_id,
+            region,
+            elb,
+        } => dir
+            .join(format!(
+                "{account_id}_elasticloadbalancing_{region}_{}_{}_{}.log",
+                elb.replace('/', "."),
+                now.format("%Y%m%dT%H%MZ"),
+                unique_id()
+            ))
+            .into_os_string(),
+        StreamKey::Computer(computer) => dir
+            .join(format!(
+                "{computer}_Security_{}_{}.xml",
+                now.format("%Y%m%dT%H%MZ"),
+                unique_id()
+            ))
+            .into_os_string(),
+        StreamKey::Source {
+            source,
+            account_id,
+            region,
+        } => {
+            let ext = match compression {
+                JsonlCompression::None => "log",
+                JsonlCompression::Gzip => "log.gz",
+            };
+            return filename.path(
+                dir,
+                &FileName {
+                    source,
+                    account_id,
+                    region,
+                    ts: &now.format("%Y%m%dT%H%MZ").to_string(),
+                    uuid: &unique_id(),
+                    seq,
+                    ext,
+                    stats,
+                },
+            );
+        }
+    };
+    if matches!(compression, JsonlCompression::Gzip) {
+        path.push(".gz");
+    }
+    Ok(PathBuf::from(path))
+}
+
+fn flush_stream(
+    dir: &Path,
+    key: &StreamKey,
+    stream: &mut LineBuffer,
+    compression: JsonlCompression,
+    filename: &FilenameTemplate,
+) -> io::Result<Option<WrittenFile>> {
+    if stream.stats.events == 0 {
+        return Ok(None);
+    }
+    let path = stream_path(dir, key, compression, filename, stream.files, &stream.stats)?;
+    stream.files += 1;
+    File::create(&path)?.write_all(&stream.body.finish()?)?;
+    stream.first_event_at = None;
+    Ok(Some(WrittenFile {
+        path,
+        source: key.source().to_string(),
+        stats: stream.stats.rotate(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    #[test]
+    fn writes_access_log_lines_per_bucket() {
+        let dir = std::env::temp_dir().join(format!("seclog-text-{}", unique_id()));
+        let mut writer = TextWriter::new(&dir, 1, None, None).expect("writer");
+        for bucket in ["app-data", "app-data", "ml-artifacts"] {
+            let payload = json!({
+                "bucket_owner": "79a59df900b949e5",
+                "bucket": bucket,
+                "time": "06/Feb/2026:00:00:38 +0000",
+                "remote_ip": "192.0.2.3",
+                "requester": null,
+                "request_id": "3E57427F3EXAMPLE",
+                "operation": "REST.GET.OBJECT",
+                "key": "a.txt",
+                "request_uri": "GET /a.txt HTTP/1.1",
+                "http_status": 200,
+                "host_id": "host",
+                "host_header": format!("{bucket}.s3.us-east-1.amazonaws.com"),
+            });
+            writer
+                .write_event(&test_event("s3_access_log", "REST.GET.OBJECT", payload))
+                .expect("write");
+        }
+        writer
+            .write_event(&test_event(
+                "okta_system_log",
+                "user.session.start",
+                json!({ "eventType": "user.session.start" }),
+            ))
+            .expect("write");
+        writer.close().expect("close");
+
+        let bucket_files = fs::read_dir(dir.join("app-data"))
+            .expect("bucket dir")
+            .map(|entry| entry.expect("entry").path())
+            .collect::<Vec<_>>();
+        assert_eq!(bucket_files.len(), 1);
+        let text = fs::read_to_string(&bucket_files[0]).expect("read");
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(
+            "79a59df900b949e5 app-data [06/Feb/2026:00:00:38 +0000] 192.0.2.3 - 3E57427F3EXAMPLE"
+        ));
+        assert!(dir.join("ml-artifacts").is_dir());
+
+        let okta = fs::read_dir(&dir)
+            .expect("dir")
+            .map(|entry| entry.expect("entry").path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "log"))
+            .expect("okta file");
+        assert_eq!(
+            fs::read_to_string(okta).expect("read"),
+            "{\"eventType\":\"user.session.start\"}\n"
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn names_source_files_from_the_filename_template() {
+        let dir = std::env::temp_dir().join(format!("seclog-text-template-{}", unique_id()));
+        let template =
+            FilenameTemplate::parse("{source}/{account}/{region}-{seq}.{ext}").expect("template");
+        let mut writer = TextWriter::new(&dir, 1, None, Some("gzip"))
+            .expect("writer")
+            .with_filename_template(template);
+        let okta = test_event(
+            "okta_system_log",
+            "user.session.start",
+            json!({ "eventType": "user.session.start" }),
+        )
+        .in_account("123456789012");
+        writer.write_event(&okta).expect("write");
+        writer.flush().expect("flush");
+        writer.write_event(&okta).expect("write");
+        writer.close().expect("close");
+
+        let files = dir.join("OktaSystemLog").join("123456789012");
+        assert!(files.join("global-000000.log.gz").is_file());
+        assert!(files.join("global-000001.log.gz").is_file());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
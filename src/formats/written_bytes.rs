@@ -0,0 +1,95 @@
+//! Record bytes written per source, for `source.limits` byte caps.
+//!
+//! A [`WrittenBytesWriter`] adds the bytes its inner writer reports for every
+//! event to a [`WrittenBytes`] tally shared with the
+//! [`LimitedEventSource`](crate::sources::composite::LimitedEventSource)s of a
+//! run, so a byte cap measures what the outputs produced instead of
+//! serializing each payload a second time.
+
+use crate::core::event::Event;
+use crate::core::traits::{EventWriter, WrittenFile};
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// Tally shared by the writers of a run and the sources they cap.
+pub type SharedWrittenBytes = Arc<WrittenBytes>;
+
+/// Record bytes written per owning tenant and envelope source.
+#[derive(Debug, Default)]
+pub struct WrittenBytes {
+    /// Keyed by `envelope.tenant`, empty outside multi-tenant runs, then by
+    /// `envelope.source`.
+    tenants: Mutex<HashMap<String, HashMap<String, u64>>>,
+}
+
+impl WrittenBytes {
+    pub fn new() -> SharedWrittenBytes {
+        Arc::new(Self::default())
+    }
+
+    /// Adds `bytes` written for `event`.
+    pub fn record(&self, event: &Event, bytes: u64) {
+        let tenant = event.envelope.tenant.as_deref().unwrap_or_default();
+        let source = event.envelope.source.as_str();
+        let mut tenants = self.tenants.lock().expect("written bytes lock");
+        let sources = match tenants.get_mut(tenant) {
+            Some(sources) => sources,
+            None => tenants.entry(tenant.to_string()).or_default(),
+        };
+        match sources.get_mut(source) {
+            Some(total) => *total += bytes,
+            None => {
+                sources.insert(source.to_string(), bytes);
+            }
+        }
+    }
+
+    /// Bytes written for `source` of `tenant`, or of untenanted events with
+    /// `None`.
+    pub fn source(&self, tenant: Option<&str>, source: &str) -> u64 {
+        let tenants = self.tenants.lock().expect("written bytes lock");
+        tenants
+            .get(tenant.unwrap_or_default())
+            .and_then(|sources| sources.get(source))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Writer wrapper that reports its inner writer's record bytes to a
+/// [`WrittenBytes`] tally.
+pub struct WrittenBytesWriter {
+    inner: Box<dyn EventWriter>,
+    written: SharedWrittenBytes,
+}
+
+impl WrittenBytesWriter {
+    pub fn new(inner: Box<dyn EventWriter>, written: SharedWrittenBytes) -> Self {
+        Self { inner, written }
+    }
+}
+
+impl EventWriter for WrittenBytesWriter {
+    fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+        let bytes = self.inner.write_event(event)?;
+        self.written.record(event, bytes);
+        Ok(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.inner.sync()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.inner.close()
+    }
+
+    fn take_written_files(&mut self) -> Vec<WrittenFile> {
+        self.inner.take_written_files()
+    }
+}
@@ -441,7 +441,7 @@ fn map_zerobus_err(err: impl std::fmt::Display) -> io::Error {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::event::{Actor, EventEnvelope};
+    use crate::fixtures::test_event;
     use std::cell::RefCell;
     use std::rc::Rc;
 
@@ -501,7 +501,11 @@ mod tests {
 
     #[test]
     fn event_to_row_preserves_payload_json() {
-        let event = test_event("okta_system_log", "user.session.start");
+        let event = test_event(
+            "okta_system_log",
+            "user.session.start",
+            json!({ "eventType": "user.session.start" }),
+        );
         let row = event_to_row_json(
             &event,
             "run-1",
@@ -533,13 +537,17 @@ mod tests {
         let mut writer = ZerobusBatchWriter::new(&config, factory).unwrap();
 
         writer
-            .write_event(&test_event("cloudtrail", "ConsoleLogin"))
+            .write_event(&test_event("cloudtrail", "ConsoleLogin", json!({})))
             .unwrap();
         writer
-            .write_event(&test_event("okta_system_log", "user.session.start"))
+            .write_event(&test_event(
+                "okta_system_log",
+                "user.session.start",
+                json!({}),
+            ))
             .unwrap();
         writer
-            .write_event(&test_event("cloudtrail", "AssumeRole"))
+            .write_event(&test_event("cloudtrail", "AssumeRole", json!({})))
             .unwrap();
         writer.flush().unwrap();
         writer.close().unwrap();
@@ -569,7 +577,7 @@ mod tests {
         };
         let mut writer = ZerobusBatchWriter::new(&config, factory).unwrap();
         let err = writer
-            .write_event(&test_event("databricks_audit", "IpAccessDenied"))
+            .write_event(&test_event("databricks_audit", "IpAccessDenied", json!({})))
             .unwrap_err();
         assert_eq!(
             err.to_string(),
@@ -647,28 +655,4 @@ mod tests {
             ]),
         }
     }
-
-    fn test_event(source: &str, event_type: &str) -> Event {
-        Event {
-            envelope: EventEnvelope {
-                schema_version: "v1".to_string(),
-                timestamp: "2026-01-01T00:00:00Z".to_string(),
-                source: source.to_string(),
-                event_type: event_type.to_string(),
-                actor: Actor {
-                    id: "user-001".to_string(),
-                    kind: "human".to_string(),
-                    name: Some("Amelia Chen".to_string()),
-                },
-                target: None,
-                outcome: Outcome::Success,
-                geo: None,
-                ip: Some("198.51.100.10".to_string()),
-                user_agent: Some("Mozilla/5.0".to_string()),
-                session_id: Some("session-1".to_string()),
-                tenant_id: Some("tenant-1".to_string()),
-            },
-            payload: json!({ "eventType": event_type }),
-        }
-    }
 }
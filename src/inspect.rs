@@ -0,0 +1,468 @@
+//! Summaries of generated output directories.
+//!
+//! Reads every JSON, JSONL, gzip, and Parquet file under a directory
+//! (including partitioned subdirectories) and tallies events by source, event
+//! type, account, region, and actor. File sink framings are unwrapped first:
+//! `{"Records": [...]}`, Azure Monitor `{"records": [...]}`, and CloudWatch
+//! Logs Firehose records. Used by `seclog inspect` to check that a run matched
+//! the intended distribution.
+
+use crate::formats::cloudtrail_digest::is_digest_file;
+use crate::formats::delta::is_delta_log_file;
+use crate::formats::iceberg::is_iceberg_metadata_file;
+use crate::formats::json::{
+    access_log_time, kubernetes_event_type, read_records, record_source, windows_account,
+};
+use crate::formats::manifest::is_manifest_file;
+use crate::formats::parquet::{is_event_file, read_events};
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Event and error counts for one source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceCounts {
+    pub events: u64,
+    pub errors: u64,
+}
+
+/// Aggregate view of a generated output directory.
+#[derive(Debug, Clone, Default)]
+pub struct OutputSummary {
+    /// Files read, keyed by format (`json`, `json.gz`, `jsonl`, `parquet`): (count, bytes).
+    pub files: BTreeMap<String, (usize, u64)>,
+    /// Files that were not recognized as event output.
+    pub skipped: Vec<PathBuf>,
+    pub events: u64,
+    pub errors: u64,
+    pub first_event: Option<DateTime<Utc>>,
+    pub last_event: Option<DateTime<Utc>>,
+    pub sources: BTreeMap<String, SourceCounts>,
+    /// Keyed by `(source, event_type)`.
+    pub event_types: BTreeMap<(String, String), u64>,
+    pub accounts: BTreeMap<String, u64>,
+    pub regions: BTreeMap<String, u64>,
+    pub actors: HashMap<String, u64>,
+}
+
+impl OutputSummary {
+    /// Fraction of events with a failed outcome.
+    pub fn error_rate(&self) -> f64 {
+        if self.events == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.events as f64
+        }
+    }
+
+    /// Most active actors, busiest first; ties break by actor ID.
+    pub fn top_actors(&self, limit: usize) -> Vec<(&str, u64)> {
+        let mut actors = self
+            .actors
+            .iter()
+            .map(|(actor, count)| (actor.as_str(), *count))
+            .collect::<Vec<_>>();
+        actors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        actors.truncate(limit);
+        actors
+    }
+
+    fn record(&mut self, view: RecordView) {
+        self.events += 1;
+        let counts = self.sources.entry(view.source.clone()).or_default();
+        counts.events += 1;
+        if view.error {
+            counts.errors += 1;
+            self.errors += 1;
+        }
+        *self
+            .event_types
+            .entry((view.source, view.event_type))
+            .or_default() += 1;
+        if let Some(account) = view.account {
+            *self.accounts.entry(account).or_default() += 1;
+        }
+        if let Some(region) = view.region {
+            *self.regions.entry(region).or_default() += 1;
+        }
+        if let Some(actor) = view.actor {
+            *self.actors.entry(actor).or_default() += 1;
+        }
+        if let Some(time) = view.time {
+            self.first_event = Some(self.first_event.map_or(time, |first| first.min(time)));
+            self.last_event = Some(self.last_event.map_or(time, |last| last.max(time)));
+        }
+    }
+}
+
+/// Summarizes every event file under `dir`.
+pub fn inspect_dir(dir: impl AsRef<Path>) -> io::Result<OutputSummary> {
+    let mut paths = Vec::new();
+    collect_files(dir.as_ref(), &mut paths)?;
+    paths.sort();
+
+    let mut summary = OutputSummary::default();
+    for path in paths {
+        let Some(format) = file_format(&path) else {
+            summary.skipped.push(path);
+            continue;
+        };
+        let bytes = fs::metadata(&path)?.len();
+        let result = match format {
+            "parquet" => read_parquet(&path, &mut summary),
+            _ => read_records(&path).map(|records| {
+                for record in &records {
+                    summary.record(record_view(record, None));
+                }
+                true
+            }),
+        };
+        let events = result
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?;
+        if !events {
+            summary.skipped.push(path);
+            continue;
+        }
+        let entry = summary.files.entry(format.to_string()).or_default();
+        entry.0 += 1;
+        entry.1 += bytes;
+    }
+    Ok(summary)
+}
+
+pub(crate) fn collect_files(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn file_format(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    if name.starts_with('.')
+        || is_manifest_file(path)
+        || is_digest_file(path)
+        || is_delta_log_file(path)
+        || is_iceberg_metadata_file(path)
+    {
+        return None;
+    }
+    ["json.gz", "jsonl", "json", "parquet"]
+        .into_iter()
+        .find(|ext| name.ends_with(&format!(".{ext}")))
+}
+
+/// Returns false for Parquet files that are not event output (e.g. actor populations).
+fn read_parquet(path: &Path, summary: &mut OutputSummary) -> io::Result<bool> {
+    if !is_event_file(path)? {
+        return Ok(false);
+    }
+    for event in read_events(path) {
+        let event = event?;
+        if event.payload.is_null() {
+            let whole = serde_json::to_value(&event).map_err(io::Error::other)?;
+            summary.record(record_view(&whole, None));
+        } else {
+            summary.record(record_view(&event.payload, Some(&event.envelope.source)));
+        }
+    }
+    Ok(true)
+}
+
+/// Fields pulled from one source-native record.
+#[derive(Debug, Default)]
+pub(crate) struct RecordView {
+    pub(crate) source: String,
+    pub(crate) event_type: String,
+    pub(crate) account: Option<String>,
+    pub(crate) region: Option<String>,
+    pub(crate) actor: Option<String>,
+    pub(crate) time: Option<DateTime<Utc>>,
+    pub(crate) error: bool,
+}
+
+pub(crate) fn record_view(record: &Value, source: Option<&str>) -> RecordView {
+    let text = |pointer: &str| {
+        record
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    if let Some(envelope) = record.get("envelope") {
+        // Events without a payload are written whole.
+        return RecordView {
+            source: string_at(envelope, "/source").unwrap_or_default(),
+            event_type: string_at(envelope, "/event_type").unwrap_or_default(),
+            actor: string_at(envelope, "/actor/id"),
+            time: string_at(envelope, "/timestamp").and_then(|time| parse_time(&time)),
+            error: envelope["outcome"] == "failure",
+            account: string_at(envelope, "/tenant_id"),
+            region: None,
+        };
+    }
+    let detected = record_source(record).unwrap_or("unknown");
+    let source = source.unwrap_or(detected);
+    match detected {
+        "cloudtrail" => RecordView {
+            source: source.to_string(),
+            event_type: text("/eventName").unwrap_or_default(),
+            account: text("/recipientAccountId").or_else(|| text("/userIdentity/accountId")),
+            region: text("/awsRegion"),
+            actor: text("/userIdentity/arn")
+                .or_else(|| text("/userIdentity/userName"))
+                .or_else(|| text("/userIdentity/principalId")),
+            time: text("/eventTime").and_then(|time| parse_time(&time)),
+            error: record.get("errorCode").is_some_and(|code| !code.is_null()),
+        },
+        "okta_system_log" => RecordView {
+            source: source.to_string(),
+            event_type: text("/eventType").unwrap_or_default(),
+            account: None,
+            region: None,
+            actor: text("/actor/alternateId").or_else(|| text("/actor/id")),
+            time: text("/published").and_then(|time| parse_time(&time)),
+            error: matches!(
+                record.pointer("/outcome/result").and_then(Value::as_str),
+                Some("FAILURE" | "DENY")
+            ),
+        },
+        "github_audit" => RecordView {
+            source: source.to_string(),
+            event_type: text("/action").unwrap_or_default(),
+            account: text("/org"),
+            region: None,
+            actor: text("/actor"),
+            time: record
+                .get("@timestamp")
+                .and_then(Value::as_i64)
+                .and_then(|millis| Utc.timestamp_millis_opt(millis).single()),
+            error: record.get("conclusion").and_then(Value::as_str) == Some("failure"),
+        },
+        "entra_signin" => RecordView {
+            source: source.to_string(),
+            event_type: "Sign-in activity".to_string(),
+            account: text("/homeTenantId"),
+            region: None,
+            actor: text("/userPrincipalName"),
+            time: text("/createdDateTime").and_then(|time| parse_time(&time)),
+            error: record
+                .pointer("/status/errorCode")
+                .and_then(Value::as_u64)
+                .is_some_and(|code| code != 0),
+        },
+        "entra_audit" => RecordView {
+            source: source.to_string(),
+            event_type: text("/activityDisplayName").unwrap_or_default(),
+            account: None,
+            region: None,
+            actor: text("/initiatedBy/app/displayName")
+                .or_else(|| text("/initiatedBy/user/userPrincipalName")),
+            time: text("/activityDateTime").and_then(|time| parse_time(&time)),
+            error: text("/result").is_some_and(|result| result != "success"),
+        },
+        "s3_access_log" => RecordView {
+            source: source.to_string(),
+            event_type: text("/operation").unwrap_or_default(),
+            account: None,
+            region: None,
+            actor: text("/requester"),
+            time: text("/time")
+                .and_then(|time| access_log_time(&time))
+                .and_then(|time| parse_time(&time)),
+            error: record
+                .get("http_status")
+                .and_then(Value::as_u64)
+                .is_some_and(|status| status >= 400),
+        },
+        "alb_access_log" => RecordView {
+            source: source.to_string(),
+            event_type: text("/request_verb").unwrap_or_default(),
+            account: None,
+            region: None,
+            actor: None,
+            time: text("/time").and_then(|time| parse_time(&time)),
+            error: record
+                .get("elb_status_code")
+                .and_then(Value::as_u64)
+                .is_some_and(|status| status >= 400),
+        },
+        "route53_resolver" => RecordView {
+            source: source.to_string(),
+            event_type: text("/query_type").unwrap_or_default(),
+            account: text("/account_id"),
+            region: text("/region"),
+            actor: text("/srcids/instance"),
+            time: text("/query_timestamp").and_then(|time| parse_time(&time)),
+            error: record.get("rcode").and_then(Value::as_str) != Some("NOERROR"),
+        },
+        "kubernetes_audit" => RecordView {
+            source: source.to_string(),
+            event_type: kubernetes_event_type(record).unwrap_or_default(),
+            account: None,
+            region: None,
+            actor: text("/user/username"),
+            time: text("/stageTimestamp").and_then(|time| parse_time(&time)),
+            error: record
+                .pointer("/responseStatus/code")
+                .and_then(Value::as_u64)
+                .is_some_and(|code| code >= 400),
+        },
+        "windows_security" => RecordView {
+            source: source.to_string(),
+            event_type: record
+                .pointer("/System/EventID")
+                .and_then(Value::as_u64)
+                .map(|event_id| event_id.to_string())
+                .unwrap_or_default(),
+            account: None,
+            region: None,
+            actor: windows_account(record),
+            time: text("/System/TimeCreated/SystemTime").and_then(|time| parse_time(&time)),
+            error: record.pointer("/System/EventID").and_then(Value::as_u64) == Some(4625),
+        },
+        "google_workspace" => RecordView {
+            source: source.to_string(),
+            event_type: text("/events/0/name").unwrap_or_default(),
+            account: text("/id/customerId"),
+            region: None,
+            actor: text("/actor/email"),
+            time: text("/id/time").and_then(|time| parse_time(&time)),
+            error: record.pointer("/events/0/name").and_then(Value::as_str)
+                == Some("login_failure"),
+        },
+        "databricks_audit" => RecordView {
+            source: source.to_string(),
+            event_type: format!(
+                "{}.{}",
+                text("/service_name").unwrap_or_default(),
+                text("/action_name").unwrap_or_default()
+            ),
+            account: text("/account_id"),
+            region: None,
+            actor: text("/user_identity/email"),
+            time: text("/event_time").and_then(|time| parse_time(&time)),
+            error: record
+                .pointer("/response/status_code")
+                .and_then(Value::as_i64)
+                .is_some_and(|status| status >= 400),
+        },
+        _ => RecordView {
+            source: source.to_string(),
+            event_type: text("/eventType")
+                .or_else(|| text("/event_type"))
+                .unwrap_or_default(),
+            ..RecordView::default()
+        },
+    }
+}
+
+fn string_at(value: &Value, pointer: &str) -> Option<String> {
+    value
+        .pointer(pointer)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|millis| Utc.timestamp_millis_opt(millis).single())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::traits::EventWriter;
+    use crate::fixtures::test_event;
+    use crate::formats::json::JsonlWriter;
+    use crate::formats::parquet::ParquetWriter;
+    use serde_json::json;
+
+    #[test]
+    fn summarizes_json_and_parquet_output() {
+        let dir = std::env::temp_dir().join(format!("seclog-inspect-{}", std::process::id()));
+        let cloudtrail = |name: &str, region: &str, error: Option<&str>| {
+            test_event(
+                "cloudtrail",
+                "test",
+                json!({
+                    "eventTime": "2026-01-01T00:00:00Z",
+                    "eventSource": "s3.amazonaws.com",
+                    "eventName": name,
+                    "awsRegion": region,
+                    "recipientAccountId": "123456789012",
+                    "userIdentity": { "arn": "arn:aws:iam::123456789012:user/alice" },
+                    "errorCode": error,
+                }),
+            )
+        };
+        let mut json_writer =
+            JsonlWriter::new(dir.join("cloudtrail"), 1, None, Some("gzip")).expect("writer");
+        for event in [
+            cloudtrail("GetObject", "us-east-1", None),
+            cloudtrail("GetObject", "us-east-1", Some("AccessDenied")),
+            cloudtrail("PutObject", "eu-west-1", None),
+        ] {
+            json_writer.write_event(&event).expect("write");
+        }
+        json_writer.close().expect("close");
+
+        let mut parquet_writer = ParquetWriter::new(dir.join("okta"), 1, None).expect("writer");
+        parquet_writer
+            .write_event(&test_event(
+                "okta_system_log",
+                "test",
+                json!({
+                    "eventType": "user.session.start",
+                    "published": "2026-01-02T12:00:00.000Z",
+                    "actor": { "id": "00u1", "alternateId": "bob@example.com" },
+                    "outcome": { "result": "FAILURE" },
+                }),
+            ))
+            .expect("write");
+        parquet_writer.close().expect("close");
+
+        let summary = inspect_dir(&dir).expect("inspect");
+        assert_eq!(summary.events, 4);
+        assert_eq!(summary.files["json.gz"].0, 2);
+        assert_eq!(summary.files["parquet"].0, 1);
+        assert_eq!(
+            summary.sources["cloudtrail"],
+            SourceCounts {
+                events: 3,
+                errors: 1
+            }
+        );
+        assert_eq!(summary.sources["okta_system_log"].errors, 1);
+        assert_eq!(
+            summary.event_types[&("cloudtrail".to_string(), "GetObject".to_string())],
+            2
+        );
+        assert_eq!(summary.regions["us-east-1"], 2);
+        assert_eq!(summary.accounts["123456789012"], 3);
+        assert_eq!(
+            summary.top_actors(1),
+            vec![("arn:aws:iam::123456789012:user/alice", 3)]
+        );
+        assert_eq!(summary.error_rate(), 0.5);
+        assert_eq!(
+            summary.last_event.unwrap().to_rfc3339(),
+            "2026-01-02T12:00:00+00:00"
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,12 +1,28 @@
 //! Seclog library crate.
 //!
-//! Exposes core types, sources, and output formats for the CLI.
+//! Exposes core types, sources, output formats, and the generation pipeline
+//! used by the CLI.
 
 pub mod actors_parquet;
 pub mod api;
+pub mod bench;
+pub mod control;
 pub mod core;
+pub mod fixtures;
 pub mod formats;
+pub mod inspect;
+pub mod pipeline;
+pub mod population;
+pub mod progress;
+pub mod prometheus;
+pub mod reload;
+pub mod replay;
+pub mod report;
+pub mod slice;
 pub mod sources;
+pub mod templates;
+pub mod validate;
+pub mod verify;
 
 pub use core::activity;
 pub use core::actors;
@@ -14,6 +30,8 @@ pub use core::config;
 pub use core::event;
 pub use core::identity;
 pub use core::traits;
+pub use fixtures::{CloudTrailEventBuilder, EntraAuditEventBuilder, EntraSignInEventBuilder};
+pub use sources::cloudtrail::{build_cloudtrail_event, ActorContext, CloudTrailEvent};
 
 #[cfg(feature = "python")]
 mod python;
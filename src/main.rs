@@ -1,42 +1,196 @@
 use chrono::{DateTime, SecondsFormat, Utc};
-use clap::{Parser, Subcommand};
-use seclog::actors_parquet::write_population;
-use seclog::api::build_event_source;
+use clap::{Parser, Subcommand, ValueEnum};
+use seclog::actors_parquet::{read_population, write_population, write_population_with_options};
+use seclog::api::{
+    apply_event_stages, apply_event_stages_with_calendar, build_event_source,
+    build_event_source_with_scenarios, build_event_source_with_written_bytes,
+};
+use seclog::bench::{run_bench, BenchOptions, BenchReport, BenchSink, CountingAllocator};
+use seclog::control::{self, Control};
 use seclog::core::actors::generate_population;
+use seclog::core::attack;
+use seclog::core::checkpoint::{fingerprint, Checkpoint};
 use seclog::core::config::{
-    Config, DatabricksVolumeOutputConfig, FileOutputConfig, FormatConfig, MultiSourceConfig,
-    OutputConfig, PopulationConfig, SourceConfig, ZerobusOutputConfig,
+    Config, FileOutputConfig, FormatConfig, IcebergOptions, MultiSourceConfig,
+    OutputConfig, OutputPartitioning, OutputStream, PopulationConfig, SinkConfig, SourceConfig,
+    StdoutOutputConfig, ZerobusOutputConfig,
 };
 use seclog::core::event::Event;
 use seclog::core::identity::{Identity, IdentityRegistry};
+use seclog::core::traffic::{parse_duration, LoadSchedule};
 use seclog::core::traits::{EventSource, EventWriter};
+use seclog::formats::arrow_ipc::ArrowIpcWriter;
+#[cfg(feature = "tokio")]
+use seclog::formats::async_sink::{AsyncEventSink, AsyncSinkWriter, BlockingSink};
+use seclog::formats::avro::AvroWriter;
+use seclog::formats::buffer_budget::BufferBudget;
+use seclog::formats::cloudwatch_logs::CloudWatchLogsWriter;
+use seclog::formats::csv::CsvWriter;
 use seclog::formats::databricks_volume::DatabricksVolumeWriter;
+use seclog::formats::delta::{DeltaWriter, PartitionColumn};
+use seclog::formats::disk_usage::{
+    parse_byte_size, ByteBudget, DiskUsage, DiskUsageWriter,
+};
+use seclog::formats::duckdb::DuckDbWriter;
+use seclog::formats::filename::FilenameTemplate;
+use seclog::formats::iceberg::{IcebergWriter, DATA_DIR as ICEBERG_DATA_DIR};
 use seclog::formats::json::JsonlWriter;
-use seclog::formats::parquet::ParquetWriter;
+use seclog::formats::labels::LabelWriter;
+use seclog::formats::manifest::{new_run_id, write_run_manifest, ManifestWriter};
+use seclog::formats::ocsf::OcsfWriter;
+use seclog::formats::ordering::OrderingWriter;
+use seclog::formats::parquet::{writer_properties as parquet_writer_properties, ParquetWriter};
+use seclog::formats::partitioned::{
+    hive_time_partition, tenant_partition, time_partition, PartitionedWriter,
+};
+use seclog::formats::resilience::{FaultInjectingWriter, RetryingWriter};
+use seclog::formats::sentinel::SentinelSink;
+use seclog::formats::splunk_hec::SplunkHecSink;
+use seclog::formats::stdout::StdoutWriter;
+use seclog::formats::tee::TeeWriter;
+use seclog::formats::text::TextWriter;
+use seclog::formats::written_bytes::{SharedWrittenBytes, WrittenBytes, WrittenBytesWriter};
 use seclog::formats::zerobus::ZerobusWriter;
+use seclog::inspect::{inspect_dir, OutputSummary};
+use seclog::pipeline::{
+    Checkpointer, EventSink, FlushObserver, InlineSink, Pipeline, RoutedSink, RunLimits,
+    RunObserver, SimClockPacer, SinkCounters, SinkTotals, WriterShards, DEFAULT_MAX_CATCH_UP,
+};
+use seclog::population::{
+    describe_actor, diff_populations, merge_populations, summarize_population, PopulationDiff,
+    PopulationSummary, ERROR_RATE_BUCKETS, RATE_BUCKETS,
+};
+use seclog::progress::{Progress, ProgressBounds};
+use seclog::prometheus::{self, PrometheusMetrics};
+use seclog::reload::ConfigWatcher;
+use seclog::replay::{self, ReplayFormat, ReplayInput, ReplayOptions};
+use seclog::report::{self, list_output_files, RunInfo, RunTally};
+use seclog::slice::{slice_dir, SliceFilter};
+use seclog::templates;
+use seclog::validate::{self, Severity, ValidationIssue};
+use seclog::verify::{verify_dir, Check, VerifyOptions, VerifyReport};
 use serde_json::json;
-use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeSet, HashMap};
-use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn, Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
 
 const ACTOR_POPULATION_SOURCE: &str = "actor_population";
+/// Events handed to a network sink per `write_batch` call.
+#[cfg(feature = "tokio")]
+const NETWORK_BATCH_SIZE: usize = 500;
+/// Events `seclog replay` queues ahead of a network sink.
+const REPLAY_QUEUE_DEPTH: usize = 1024;
+
+/// Counts allocations while `seclog bench` runs.
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Where progress and metrics lines go; set once per command.
+static STATUS: OnceLock<StatusOutput> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusOutput {
+    Stdout,
+    /// Events are streamed to stdout, so status lines must not interleave.
+    Stderr,
+    Quiet,
+}
+
+impl StatusOutput {
+    fn select(output: &OutputConfig, quiet: bool) -> Self {
+        match output {
+            _ if quiet => StatusOutput::Quiet,
+            OutputConfig::Stdout(StdoutOutputConfig {
+                stream: OutputStream::Stdout,
+                ..
+            }) => StatusOutput::Stderr,
+            OutputConfig::ArrowIpc(output) if output.path.as_deref() == Some("-") => {
+                StatusOutput::Stderr
+            }
+            _ => StatusOutput::Stdout,
+        }
+    }
+}
+
+/// Limits the `--progress` line measures completion against; unset without `--progress`.
+static PROGRESS: OnceLock<ProgressBounds> = OnceLock::new();
+
+/// Routes log lines: warnings and errors always go to stderr, other lines to
+/// the stream selected by [`STATUS`] so they never mix with streamed events.
+struct LogWriter;
+
+impl<'a> MakeWriter<'a> for LogWriter {
+    type Writer = Box<dyn io::Write + 'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        Box::new(io::stderr())
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        if *meta.level() <= Level::WARN {
+            return Box::new(io::stderr());
+        }
+        match STATUS.get().copied().unwrap_or(StatusOutput::Stdout) {
+            StatusOutput::Stdout => Box::new(io::stdout()),
+            StatusOutput::Stderr => Box::new(io::stderr()),
+            StatusOutput::Quiet => Box::new(io::sink()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+fn init_logging(format: LogFormat, level: Level) {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .with_ansi(false)
+        .with_writer(LogWriter);
+    match format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().flatten_event(true).init(),
+    }
+}
 
 #[derive(Debug, Parser)]
 #[command(name = "seclog")]
 #[command(about = "SIEM log generator", long_about = None)]
 struct Cli {
+    /// Operational log format: `text` or `json` (one object per line).
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+    /// Most verbose level logged: `error`, `warn`, `info`, `debug`, or `trace`.
+    #[arg(long, global = true, default_value_t = Level::INFO)]
+    log_level: Level,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
+    /// Writes a starter config and actor population config from a built-in template.
+    Init {
+        /// `small-lab`, `enterprise`, `ato-demo`, or `load-test`; lists the templates when omitted.
+        #[arg(short, long)]
+        template: Option<String>,
+        /// Directory for `config.toml` and `actors.toml`.
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+        /// Overwrites existing files.
+        #[arg(long)]
+        force: bool,
+    },
     Gen {
         #[arg(short, long)]
         config: PathBuf,
@@ -48,509 +202,1385 @@ enum Commands {
         max_events: Option<u64>,
         #[arg(long)]
         max_seconds: Option<u64>,
+        /// Stops once file output reaches this size on disk, e.g. `50GB`;
+        /// `SOURCE=SIZE` caps one envelope source instead. Repeatable.
+        #[arg(long)]
+        max_bytes: Vec<String>,
         #[arg(long)]
         until_time: Option<String>,
         #[arg(long, default_value_t = 1000)]
         metrics_interval_ms: u64,
+        #[arg(long)]
+        metrics_listen: Option<String>,
+        /// Serves a loopback HTTP endpoint that pauses, resumes, rescales, and
+        /// flushes the run.
+        #[arg(long)]
+        control_listen: Option<String>,
+        /// Re-reads the config file while running and applies traffic changes
+        /// live.
+        #[arg(long)]
+        watch_config: bool,
         #[arg(long, default_value_t = 0)]
         gen_workers: usize,
         #[arg(long, default_value_t = 0)]
         writer_shards: usize,
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        #[arg(long, default_value_t = 60)]
+        checkpoint_interval_secs: u64,
+        #[arg(long)]
+        resume: Option<PathBuf>,
+        /// Suppresses progress and metrics lines.
+        #[arg(short, long)]
+        quiet: bool,
+        /// Replaces metrics lines with a progress bar, ETA, and per-source
+        /// completion for runs with an event, time, or simulated-time limit.
+        #[arg(long)]
+        progress: bool,
     },
+    /// Generates an actor population, or inspects, diffs, and merges population files.
+    #[command(args_conflicts_with_subcommands = true)]
     Actors {
+        #[command(subcommand)]
+        command: Option<ActorCommands>,
+        #[arg(short, long, required = true)]
+        config: Option<PathBuf>,
+        #[arg(short, long, required = true)]
+        output: Option<PathBuf>,
+    },
+    Scenarios {
+        #[command(subcommand)]
+        command: ScenarioCommands,
+    },
+    /// Summarizes generated JSON and Parquet output under a directory.
+    Inspect {
+        dir: PathBuf,
+        /// Number of rows shown in the event type, account, region, and actor tables.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Checks generated output for duplicate event IDs, out-of-order
+    /// timestamps, missing fields, and gaps between rotated files.
+    Verify {
+        dir: PathBuf,
+        /// Fails when event time jumps by more than this between consecutive
+        /// files of a stream, e.g. `15m`; gaps are only reported when unset.
+        #[arg(long)]
+        max_gap: Option<String>,
+    },
+    /// Checks a generation config (and referenced population configs) without generating.
+    Validate {
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Actor population config, as passed to `seclog actors`.
+        #[arg(short, long)]
+        population: Option<PathBuf>,
+    },
+    /// Re-emits previously generated output through a configured sink.
+    Replay {
+        /// Directory of generated output; subdirectories are included.
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Input files to read: `jsonl` (JSON, JSONL, and gzip JSON) or `parquet`.
+        #[arg(long, default_value = "jsonl")]
+        format: ReplayFormat,
+        /// Config with an `[output]` table; a generation config also works.
+        #[arg(long)]
+        sink: PathBuf,
+        /// Paces events by their original timestamp gaps, sped up by this factor.
+        #[arg(long)]
+        speed: Option<f64>,
+        /// With --speed, lag beyond this duration is dropped instead of caught up.
+        #[arg(long, default_value = "60s")]
+        max_catch_up: String,
+        /// Passes over the input; 0 repeats until --max-events is reached.
+        #[arg(long, default_value_t = 1)]
+        repeat: u64,
+        #[arg(long)]
+        max_events: Option<u64>,
+        /// Suppresses progress and summary lines.
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Copies the events matching a time window, actors, and event types into a new directory.
+    Slice {
+        /// Directory of generated output; subdirectories are included.
+        #[arg(short, long)]
+        input: PathBuf,
+        /// Keeps events at or after this RFC3339 time.
+        #[arg(long)]
+        from: Option<String>,
+        /// Keeps events before this RFC3339 time.
+        #[arg(long)]
+        to: Option<String>,
+        /// Comma-separated actor IDs or names, e.g. principal ARNs or user names.
+        #[arg(long, value_delimiter = ',')]
+        actors: Vec<String>,
+        /// Comma-separated event types, e.g. `ConsoleLogin,AssumeRole`.
+        #[arg(long, value_delimiter = ',')]
+        event_types: Vec<String>,
+        /// New or empty directory; input file paths and formats are kept.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Measures generation and serialization throughput per source without writing output.
+    Bench {
         #[arg(short, long)]
         config: PathBuf,
+        /// Wall-clock time to run, e.g. `30s` or `2m`.
+        #[arg(long, default_value = "10s")]
+        duration: String,
+        /// `null` drops encoded batches; `memory` keeps them to show their footprint.
+        #[arg(long, default_value = "null")]
+        sink: BenchSink,
+        #[arg(long)]
+        max_events: Option<u64>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ActorCommands {
+    /// Prints role, profile, and account distributions and rate histograms.
+    Inspect { file: PathBuf },
+    /// Reports actors added, removed, or changed between two population files.
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        /// Actors listed per section.
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+    },
+    /// Combines population files, rejecting actors that share a principal.
+    Merge {
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
         #[arg(short, long)]
         output: PathBuf,
+        /// Keeps the first actor for each duplicated principal instead of failing.
+        #[arg(long)]
+        skip_duplicates: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ScenarioCommands {
+    /// Lists scenarios and ATT&CK techniques in a config, or the technique catalog.
+    List {
+        #[arg(short, long)]
+        config: Option<PathBuf>,
     },
 }
 
 fn main() {
+    ALLOCATOR.install();
     let cli = Cli::parse();
+    init_logging(cli.log_format, cli.log_level);
 
     if let Err(err) = run(cli) {
-        eprintln!("{err}");
+        // The reader of a stdout stream went away (e.g. `| head`); stop quietly.
+        if err
+            .downcast_ref::<io::Error>()
+            .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe)
+        {
+            return;
+        }
+        error!("{err}");
         std::process::exit(1);
     }
 }
 
 fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     match cli.command {
+        Commands::Init { template: None, .. } => {
+            for template in templates::TEMPLATES {
+                println!("{:<12} {}", template.name, template.summary);
+            }
+        }
+        Commands::Init {
+            template: Some(name),
+            output,
+            force,
+        } => {
+            let template = templates::find(&name).ok_or_else(|| {
+                let names = templates::TEMPLATES
+                    .iter()
+                    .map(|template| template.name)
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown template {name:?} (expected one of {})",
+                    names.join(", ")
+                )
+            })?;
+            let (config, population) = templates::write_template(template, &output, force)?;
+            info!(
+                template = template.name,
+                config = %config.display(),
+                population = %population.display(),
+                "template written"
+            );
+            println!("seclog gen --config {}", config.display());
+        }
         Commands::Gen {
             config,
             output,
             dry_run,
             max_events,
             max_seconds,
+            max_bytes,
             until_time,
             metrics_interval_ms,
+            metrics_listen,
+            control_listen,
+            watch_config,
             gen_workers,
             writer_shards,
+            checkpoint,
+            checkpoint_interval_secs,
+            resume,
+            quiet,
+            progress,
         } => {
             let mut loaded = Config::from_path(&config)?;
 
-            if let Some(dir) = output {
-                loaded
+            match output {
+                Some(dir) if dir.as_os_str() == "-" => {
+                    loaded.output = OutputConfig::Stdout(StdoutOutputConfig::default());
+                }
+                Some(dir) => loaded
                     .output
-                    .override_file_dir(dir.to_string_lossy().to_string())?;
+                    .override_file_dir(dir.to_string_lossy().to_string())?,
+                None => {}
             }
+            let _ = STATUS.set(StatusOutput::select(&loaded.output, quiet));
 
             if dry_run {
                 println!("config loaded: {loaded:#?}");
                 return Ok(());
             }
 
+            let byte_budget = if max_bytes.is_empty() {
+                None
+            } else {
+                if checkpoint.is_some() || resume.is_some() {
+                    return Err(
+                        "--max-bytes cannot be combined with --checkpoint or --resume".into(),
+                    );
+                }
+                if !matches!(loaded.output, OutputConfig::File(_)) {
+                    return Err("--max-bytes needs file output".into());
+                }
+                Some(byte_budget(&max_bytes, &loaded.source)?)
+            };
+
+            let config_fingerprint = fingerprint(&loaded);
+            let resume_state = match resume.as_deref() {
+                Some(path) => {
+                    let state = Checkpoint::load(path)?;
+                    state.ensure_config(&config_fingerprint)?;
+                    Some(state)
+                }
+                None => None,
+            };
+            let checkpoint_path = checkpoint.or(resume);
+
+            let exporter = match metrics_listen.as_deref() {
+                Some(listen) => {
+                    let exporter = Arc::new(PrometheusMetrics::new());
+                    let addr = prometheus::serve(listen, Arc::clone(&exporter))?;
+                    info!(%addr, "metrics endpoint listening on http://{addr}/metrics");
+                    Some(exporter)
+                }
+                None => None,
+            };
+            let control = match control_listen.as_deref() {
+                Some(listen) => {
+                    let control = Arc::new(Control::new());
+                    let addr = control::serve(listen, Arc::clone(&control))?;
+                    info!(%addr, "control endpoint listening on http://{addr}/status");
+                    Some(control)
+                }
+                None => None,
+            };
+            let watcher = if watch_config {
+                let watcher = ConfigWatcher::new(&config, &loaded)?;
+                info!(config = %config.display(), "watching config for traffic and scenario changes");
+                Some(watcher)
+            } else {
+                None
+            };
+
             let requested_gen_workers = gen_workers;
             let requested_writer_shards = writer_shards;
             let gen_workers = normalize_workers(gen_workers);
             let writer_shards = normalize_writer_shards(writer_shards);
             if requested_gen_workers > 1 {
-                eprintln!(
-                    "warning: actor-driven mode uses a single generator for ordered output; forcing gen-workers=1"
+                warn!(
+                    "actor-driven mode uses a single generator for ordered output; forcing gen-workers=1"
                 );
             }
             let queue_depth = 1024;
 
             let start_time = Instant::now();
             let max_duration = max_seconds.map(Duration::from_secs);
-            let start_sim_time = parse_start_time(loaded.traffic.start_time.as_deref())?;
+            let start_sim_time = match &resume_state {
+                Some(state) => parse_start_time(Some(&state.start_time))?,
+                None => parse_start_time(loaded.traffic.start_time.as_deref())?,
+            };
             let until_sim_time = parse_optional_time(
                 until_time
                     .as_deref()
                     .or(loaded.traffic.until_time.as_deref()),
             )?;
-            let time_scale = loaded.traffic.time_scale.unwrap_or(1.0);
-            let time_scale = if time_scale <= 0.0 {
+            if progress {
+                let bounds = ProgressBounds {
+                    max_events,
+                    max_duration,
+                    start_sim_time,
+                    until_sim_time,
+                };
+                if bounds.is_bounded() {
+                    let _ = PROGRESS.set(bounds);
+                } else {
+                    warn!(
+                        "--progress needs --max-events, --max-seconds, or an until time; showing metrics lines"
+                    );
+                }
+            }
+            let load_schedule = if loaded.traffic.phases.is_empty() {
                 None
             } else {
-                Some(time_scale)
+                if loaded.traffic.time_scale.is_some() {
+                    warn!(
+                        "traffic.phases paces output on the wall clock; ignoring traffic.time_scale"
+                    );
+                }
+                Some(LoadSchedule::from_phases(&loaded.traffic.phases)?)
+            };
+            let max_catch_up = match &loaded.traffic.max_catch_up {
+                Some(value) => parse_duration(value).ok_or_else(|| {
+                    format!("traffic.max_catch_up is not a valid duration: {value}")
+                })?,
+                None => DEFAULT_MAX_CATCH_UP,
             };
 
+            let checkpointer = match checkpoint_path {
+                Some(path) => {
+                    let OutputConfig::File(output) = &loaded.output else {
+                        return Err("checkpoint/resume is only supported for file output".into());
+                    };
+                    let output_dirs = file_output_dirs(&loaded.source, output);
+                    let state = match resume_state {
+                        Some(state) => {
+                            let removed = state.remove_uncommitted_files(&output_dirs, &path)?;
+                            info!(
+                                checkpoint = %path.display(),
+                                events = state.events_committed,
+                                removed_files = removed,
+                                "resuming from checkpoint"
+                            );
+                            state
+                        }
+                        None => Checkpoint::new(
+                            config_fingerprint.clone(),
+                            loaded.seed.unwrap_or_else(rand::random),
+                            start_sim_time.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                        ),
+                    };
+                    loaded.seed = Some(state.seed);
+                    Some(Checkpointer::new(
+                        path,
+                        Duration::from_secs(checkpoint_interval_secs.max(1)),
+                        state,
+                        output_dirs,
+                    ))
+                }
+                None => None,
+            };
+
+            let labels = loaded.labels.as_ref().map(LabelWriter::new).transpose()?;
+
             match &loaded.output {
                 OutputConfig::File(output) => {
+                    validate_file_formats(&loaded.source, output)?;
                     if let SourceConfig::Multi(config) = &loaded.source {
-                        run_multi_file_generation(
+                        if config.sources.is_empty() {
+                            return Err("multi source requires at least one child source".into());
+                        }
+                    }
+                }
+                OutputConfig::Zerobus(output) => {
+                    validate_zerobus_table_routes(&loaded.source, output)?
+                }
+                _ => {}
+            }
+            let written = has_byte_limits(&loaded.source).then(WrittenBytes::new);
+            let generator =
+                gen_event_source(&loaded, start_sim_time, watcher.as_ref(), written.as_ref())?;
+            let on_flush = exporter.clone().map(|exporter| -> FlushObserver {
+                Arc::new(move |latency| exporter.observe_flush(latency))
+            });
+
+            let buffered = Arc::new(AtomicU64::new(0));
+            let (sink, flush_interval): (Box<dyn EventSink>, Duration) = match &loaded.output {
+                OutputConfig::File(output) => {
+                    let disk_usage = byte_budget.as_ref().map(|budget| budget.usage().clone());
+                    let sink: Box<dyn EventSink> = match &loaded.source {
+                        SourceConfig::Multi(config) => Box::new(routed_file_sink(
                             config,
                             output,
-                            loaded.seed,
-                            start_sim_time,
-                            time_scale,
-                            until_sim_time,
-                            max_events,
-                            max_duration,
                             writer_shards,
                             queue_depth,
-                            Duration::from_millis(metrics_interval_ms),
-                        )?;
-                    } else {
-                        let generator =
-                            build_event_source(&loaded.source, loaded.seed, start_sim_time)?;
-                        run_file_generation(
-                            generator,
+                            &buffered,
+                            disk_usage,
+                            written.clone(),
+                            on_flush,
+                        )),
+                        _ => Box::new(file_sink(
+                            "all",
                             output,
-                            time_scale,
-                            start_sim_time,
-                            start_time,
-                            until_sim_time,
-                            max_events,
-                            max_duration,
                             writer_shards,
                             queue_depth,
-                            Duration::from_millis(metrics_interval_ms),
-                        )?;
-                    }
+                            false,
+                            &buffered,
+                            disk_usage,
+                            written.clone(),
+                            on_flush,
+                        )),
+                    };
+                    (sink, Duration::from_secs(1))
                 }
                 OutputConfig::Zerobus(output) => {
                     if requested_writer_shards > 1 {
-                        eprintln!(
-                            "warning: zerobus output opens one stream per source; forcing writer-shards=1"
+                        warn!(
+                            "zerobus output opens one stream per source; forcing writer-shards=1"
                         );
                     }
-                    validate_zerobus_table_routes(&loaded.source, output)?;
-                    let generator =
-                        build_event_source(&loaded.source, loaded.seed, start_sim_time)?;
-                    run_zerobus_generation(
-                        generator,
+                    let mut writer = ZerobusWriter::new(output)?;
+                    persist_zerobus_actor_population_if_configured(
                         &loaded.source,
                         output,
-                        time_scale,
-                        start_sim_time,
-                        start_time,
-                        until_sim_time,
-                        max_events,
-                        max_duration,
-                        Duration::from_millis(metrics_interval_ms),
+                        &mut writer,
                     )?;
+                    (
+                        inline_sink(Box::new(writer), written.as_ref(), on_flush),
+                        Duration::from_millis(output.flush_interval_ms),
+                    )
                 }
                 OutputConfig::DatabricksVolume(output) => {
                     if requested_writer_shards > 1 {
-                        eprintln!(
-                            "warning: databricks_volume output uploads rotated files from one writer; forcing writer-shards=1"
+                        warn!(
+                            "databricks_volume output uploads rotated files from one writer; forcing writer-shards=1"
                         );
                     }
-                    let generator =
-                        build_event_source(&loaded.source, loaded.seed, start_sim_time)?;
-                    run_databricks_volume_generation(
-                        generator,
-                        output,
-                        time_scale,
-                        start_sim_time,
-                        start_time,
-                        until_sim_time,
-                        max_events,
-                        max_duration,
-                        Duration::from_millis(metrics_interval_ms),
-                    )?;
+                    (
+                        inline_sink(
+                            blocking_network_writer(
+                                DatabricksVolumeWriter::new(output)?,
+                                queue_depth,
+                            )?,
+                            written.as_ref(),
+                            on_flush,
+                        ),
+                        Duration::from_millis(output.flush_interval_ms),
+                    )
+                }
+                OutputConfig::Stdout(output) => {
+                    if requested_writer_shards > 1 {
+                        warn!("stdout output writes one ordered stream; forcing writer-shards=1");
+                    }
+                    (
+                        inline_sink(
+                            Box::new(StdoutWriter::for_stream(output.stream)),
+                            written.as_ref(),
+                            on_flush,
+                        ),
+                        Duration::from_millis(output.flush_interval_ms),
+                    )
+                }
+                OutputConfig::ArrowIpc(output) => {
+                    if requested_writer_shards > 1 {
+                        warn!(
+                            "arrow_ipc output writes one ordered stream; forcing writer-shards=1"
+                        );
+                    }
+                    let writer = ArrowIpcWriter::from_config(output)?;
+                    if let Some(addr) = writer.local_addr() {
+                        info!(%addr, "serving arrow ipc stream on {addr}");
+                    }
+                    (
+                        inline_sink(Box::new(writer), written.as_ref(), on_flush),
+                        Duration::from_millis(output.flush_interval_ms),
+                    )
+                }
+                OutputConfig::DuckDb(output) => {
+                    if requested_writer_shards > 1 {
+                        warn!(
+                            "duckdb output appends to one table from one writer; forcing writer-shards=1"
+                        );
+                    }
+                    (
+                        inline_sink(
+                            Box::new(DuckDbWriter::new(output)?),
+                            written.as_ref(),
+                            on_flush,
+                        ),
+                        Duration::from_millis(output.flush_interval_ms),
+                    )
+                }
+                OutputConfig::SplunkHec(output) => {
+                    if requested_writer_shards > 1 {
+                        warn!("splunk_hec output sends from one writer; forcing writer-shards=1");
+                    }
+                    (
+                        inline_sink(
+                            network_writer(SplunkHecSink::new(output)?, queue_depth)?,
+                            written.as_ref(),
+                            on_flush,
+                        ),
+                        Duration::from_millis(output.flush_interval_ms),
+                    )
+                }
+                OutputConfig::Sentinel(output) => {
+                    if requested_writer_shards > 1 {
+                        warn!("sentinel output uploads from one writer; forcing writer-shards=1");
+                    }
+                    (
+                        inline_sink(
+                            network_writer(SentinelSink::new(output)?, queue_depth)?,
+                            written.as_ref(),
+                            on_flush,
+                        ),
+                        Duration::from_millis(output.flush_interval_ms),
+                    )
+                }
+            };
+
+            let mut pipeline = Pipeline::new()
+                .add_source(generator)
+                .sink(sink)
+                .time_scale(loaded.traffic.time_scale.unwrap_or(1.0))
+                .max_catch_up(max_catch_up)
+                .flush_interval(Some(flush_interval.max(Duration::from_millis(1))));
+            if let Some(schedule) = load_schedule {
+                pipeline = pipeline.load_schedule(schedule);
+            }
+            if let Some(labels) = labels {
+                pipeline = pipeline.labels(labels);
+            }
+            if let Some(control) = control {
+                pipeline = pipeline.control(control);
+            }
+            if let Some(watcher) = watcher {
+                pipeline = pipeline.watch_config(watcher);
+            }
+            if let Some(budget) = byte_budget {
+                pipeline = pipeline.byte_budget(budget);
+            }
+            if let Some(checkpointer) = checkpointer {
+                pipeline = pipeline.checkpoint(checkpointer);
+            }
+            let mut metrics = Metrics::new(
+                Duration::from_millis(metrics_interval_ms),
+                start_sim_time,
+                exporter,
+                buffered,
+            );
+            let limits = RunLimits {
+                max_events,
+                max_duration,
+                until_time: until_sim_time,
+            };
+            let stats = pipeline.run_observed(limits, &mut metrics)?;
+            if loaded.labels.is_some() {
+                info!(labeled = stats.labeled, "scenario labels written");
+            }
+            let output_files = match &loaded.output {
+                OutputConfig::File(output) => {
+                    let dirs = file_output_dirs(&loaded.source, output);
+                    finish_run_manifest(&dirs, output)?;
+                    Some((dirs, PathBuf::from(&output.dir)))
                 }
+                _ => None,
+            };
+            let tally = metrics.tally;
+
+            if let Some(report_config) = &loaded.report {
+                let files = match output_files {
+                    Some((dirs, base)) => list_output_files(&dirs, &base)?,
+                    None => Vec::new(),
+                };
+                let run = RunInfo {
+                    run_id: manifest_run_id().to_string(),
+                    config_path: config.display().to_string(),
+                    config_fingerprint,
+                    seed: loaded.seed,
+                    wall_seconds: start_time.elapsed().as_secs_f64(),
+                };
+                report::write_report(&tally.finish(run, files), report_config)?;
+                info!(path = %report_config.path, "run report written");
+            }
+        }
+        Commands::Actors {
+            command: Some(ActorCommands::Inspect { file }),
+            ..
+        } => {
+            let population = read_population(&file)?;
+            print_population(&summarize_population(&population));
+        }
+        Commands::Actors {
+            command: Some(ActorCommands::Diff { old, new, top }),
+            ..
+        } => {
+            let read = |path: &Path| {
+                read_population(path)
+                    .map_err(|err| format!("failed to read {}: {err}", path.display()))
+            };
+            let diff = diff_populations(&read(&old)?, &read(&new)?);
+            print_population_diff(&diff, top);
+        }
+        Commands::Actors {
+            command:
+                Some(ActorCommands::Merge {
+                    inputs,
+                    output,
+                    skip_duplicates,
+                }),
+            ..
+        } => {
+            let populations = inputs
+                .iter()
+                .map(|path| {
+                    read_population(path)
+                        .map_err(|err| format!("failed to read {}: {err}", path.display()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let (population, skipped) = merge_populations(populations, skip_duplicates)?;
+            write_population(&output, &population)?;
+            if skipped > 0 {
+                println!("skipped {skipped} actors with duplicate principals");
             }
+            println!(
+                "{} actors written to {}",
+                population.actors.len(),
+                output.display()
+            );
         }
-        Commands::Actors { config, output } => {
+        Commands::Actors {
+            command: None,
+            config,
+            output,
+        } => {
+            let (Some(config), Some(output)) = (config, output) else {
+                return Err("actors requires --config and --output".into());
+            };
             let loaded = PopulationConfig::from_path(&config)?;
             let population = generate_population(&loaded)?;
-            write_population(&output, &population)?;
+            let parquet = loaded.parquet.clone().unwrap_or_default();
+            write_population_with_options(&output, &population, &parquet)?;
             println!("actor population written to {}", output.display());
         }
-    }
-
-    Ok(())
-}
-
-fn identity_registry_from_population_config_path(
-    path: &str,
-) -> Result<IdentityRegistry, Box<dyn std::error::Error>> {
-    let config = PopulationConfig::from_path(path)?;
-    let population = generate_population(&config)?;
-    Ok(IdentityRegistry::from_population(
-        "generated_identity_registry",
-        &population,
-    )?)
-}
-
-#[allow(clippy::too_many_arguments)]
-fn run_file_generation(
-    mut generator: Box<dyn EventSource>,
-    output: &FileOutputConfig,
-    time_scale: Option<f64>,
-    start_sim_time: DateTime<Utc>,
-    start_time: Instant,
-    until_sim_time: Option<DateTime<Utc>>,
-    max_events: Option<u64>,
-    max_duration: Option<Duration>,
-    writer_shards: usize,
-    queue_depth: usize,
-    metrics_interval: Duration,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let counters = WriterCounters::new();
-    let (writer_txs, writer_handles) =
-        spawn_writer_shards(output, writer_shards, queue_depth, &counters);
-    let flush_interval = Some(Duration::from_secs(1));
-    let mut next_flush = flush_interval.map(|interval| Instant::now() + interval);
-    let mut metrics = Metrics::new(metrics_interval, start_sim_time);
-    let mut total_dispatched = 0_u64;
-    let mut last_written_events = 0_u64;
-    let mut last_written_bytes = 0_u64;
-    let mut last_sim_time = start_sim_time;
-    let mut last_wall = Instant::now();
-
-    loop {
-        let loop_start = Instant::now();
-        if let Some(limit) = max_duration {
-            if loop_start.duration_since(start_time) >= limit {
-                break;
-            }
+        Commands::Inspect { dir, top } => {
+            let summary = inspect_dir(&dir)?;
+            print_inspection(&summary, top);
         }
-        if let Some(max) = max_events {
-            if total_dispatched >= max {
-                break;
-            }
+        Commands::Verify { dir, max_gap } => {
+            let max_gap = max_gap
+                .map(|value| {
+                    parse_duration(&value).ok_or_else(|| format!("invalid --max-gap: {value}"))
+                })
+                .transpose()?;
+            let report = verify_dir(&dir, &VerifyOptions { max_gap })?;
+            report_verification(&report)?;
         }
-
-        let Some(event) = generator.next_event() else {
-            break;
-        };
-
-        let event_time = parse_event_time(&event);
-        let metric_event_time = event_time.clone();
-        if should_stop_at_until(event_time, until_sim_time) {
-            break;
+        Commands::Validate { config, population } => {
+            if config.is_none() && population.is_none() {
+                return Err("validate requires --config or --population".into());
+            }
+            let mut issues = Vec::new();
+            if let Some(path) = &config {
+                match Config::from_path(path) {
+                    Ok(loaded) => issues.extend(validate::validate_config(&loaded)),
+                    Err(err) => issues.push(load_issue(path, err)),
+                }
+            }
+            if let Some(path) = &population {
+                match PopulationConfig::from_path(path) {
+                    Ok(loaded) => issues.extend(validate::validate_population(&loaded, "")),
+                    Err(err) => issues.push(load_issue(path, err)),
+                }
+            }
+            report_validation(&issues)?;
         }
-        if let Some(event_time) = event_time {
-            if let Some(scale) = time_scale {
-                throttle_to_sim_time(event_time, last_sim_time, scale, &mut last_wall);
+        Commands::Replay {
+            input,
+            format,
+            sink,
+            speed,
+            max_catch_up,
+            repeat,
+            max_events,
+            quiet,
+        } => {
+            if speed.is_some_and(|speed| speed <= 0.0) {
+                return Err("--speed must be positive".into());
             }
-            last_sim_time = event_time;
+            let max_catch_up = parse_duration(&max_catch_up)
+                .ok_or_else(|| format!("--max-catch-up is not a valid duration: {max_catch_up}"))?;
+            let sink = SinkConfig::from_path(&sink)?;
+            let _ = STATUS.set(StatusOutput::select(&sink.output, quiet));
+            let events = ReplayInput::open(&input, format)?;
+            let mut options = ReplayOptions {
+                speed,
+                max_catch_up,
+                repeat,
+                max_events,
+                flush_interval: None,
+            };
+            let stats = match &sink.output {
+                OutputConfig::File(output) => {
+                    let mut writer =
+                        file_output_writer(output, false, buffer_budget(output), None, None)?;
+                    let stats = replay::replay(&events, writer.as_mut(), &options)?;
+                    writer.close()?;
+                    finish_run_manifest(&[PathBuf::from(&output.dir)], output)?;
+                    stats
+                }
+                OutputConfig::Zerobus(output) => {
+                    options.flush_interval =
+                        Some(Duration::from_millis(output.flush_interval_ms.max(1)));
+                    let mut writer = ZerobusWriter::new(output)?;
+                    let stats = replay::replay(&events, &mut writer, &options)?;
+                    writer.close()?;
+                    stats
+                }
+                OutputConfig::DatabricksVolume(output) => {
+                    options.flush_interval =
+                        Some(Duration::from_millis(output.flush_interval_ms.max(1)));
+                    let mut writer = blocking_network_writer(
+                        DatabricksVolumeWriter::new(output)?,
+                        REPLAY_QUEUE_DEPTH,
+                    )?;
+                    let stats = replay::replay(&events, writer.as_mut(), &options)?;
+                    writer.close()?;
+                    stats
+                }
+                OutputConfig::Stdout(output) => {
+                    options.flush_interval =
+                        Some(Duration::from_millis(output.flush_interval_ms.max(1)));
+                    let mut writer = StdoutWriter::for_stream(output.stream);
+                    let stats = replay::replay(&events, &mut writer, &options)?;
+                    writer.close()?;
+                    stats
+                }
+                OutputConfig::ArrowIpc(output) => {
+                    options.flush_interval =
+                        Some(Duration::from_millis(output.flush_interval_ms.max(1)));
+                    let mut writer = ArrowIpcWriter::from_config(output)?;
+                    let stats = replay::replay(&events, &mut writer, &options)?;
+                    writer.close()?;
+                    stats
+                }
+                OutputConfig::DuckDb(output) => {
+                    options.flush_interval =
+                        Some(Duration::from_millis(output.flush_interval_ms.max(1)));
+                    let mut writer = DuckDbWriter::new(output)?;
+                    let stats = replay::replay(&events, &mut writer, &options)?;
+                    writer.close()?;
+                    stats
+                }
+                OutputConfig::SplunkHec(output) => {
+                    options.flush_interval =
+                        Some(Duration::from_millis(output.flush_interval_ms.max(1)));
+                    let mut writer =
+                        network_writer(SplunkHecSink::new(output)?, REPLAY_QUEUE_DEPTH)?;
+                    let stats = replay::replay(&events, writer.as_mut(), &options)?;
+                    writer.close()?;
+                    stats
+                }
+                OutputConfig::Sentinel(output) => {
+                    options.flush_interval =
+                        Some(Duration::from_millis(output.flush_interval_ms.max(1)));
+                    let mut writer =
+                        network_writer(SentinelSink::new(output)?, REPLAY_QUEUE_DEPTH)?;
+                    let stats = replay::replay(&events, writer.as_mut(), &options)?;
+                    writer.close()?;
+                    stats
+                }
+            };
+            info!(
+                events = stats.events,
+                bytes = stats.bytes,
+                passes = stats.passes,
+                input = %input.display(),
+                "replay finished"
+            );
         }
-
-        dispatch_event(event, &writer_txs, writer_shards)?;
-        total_dispatched += 1;
-
-        let current_events = counters.events.load(Ordering::Relaxed);
-        let current_bytes = counters.bytes.load(Ordering::Relaxed);
-        let loop_events = current_events.saturating_sub(last_written_events);
-        let loop_bytes = current_bytes.saturating_sub(last_written_bytes);
-        last_written_events = current_events;
-        last_written_bytes = current_bytes;
-
-        if let (Some(interval), Some(next)) = (flush_interval, next_flush) {
-            if loop_start >= next {
-                for tx in &writer_txs {
-                    let _ = tx.send(WriterCommand::Flush);
+        Commands::Slice {
+            input,
+            from,
+            to,
+            actors,
+            event_types,
+            output,
+        } => {
+            let filter = SliceFilter {
+                from: parse_optional_time(from.as_deref())?,
+                to: parse_optional_time(to.as_deref())?,
+                actors,
+                event_types,
+            };
+            if let (Some(from), Some(to)) = (filter.from, filter.to) {
+                if from >= to {
+                    return Err("--from must be before --to".into());
                 }
-                next_flush = Some(loop_start + interval);
             }
+            let stats = slice_dir(&input, &output, &filter)?;
+            info!(
+                events = stats.events_written,
+                events_read = stats.events_read,
+                files = stats.files_written,
+                files_read = stats.files_read,
+                output = %output.display(),
+                "slice finished"
+            );
         }
+        Commands::Bench {
+            config,
+            duration,
+            sink,
+            max_events,
+        } => {
+            let duration = parse_duration(&duration)
+                .ok_or_else(|| format!("invalid --duration: {duration}"))?;
+            let loaded = Config::from_path(&config)?;
+            let timestamp_strings = match &loaded.output {
+                OutputConfig::File(output) => match &output.format {
+                    FormatConfig::Parquet(options) | FormatConfig::Delta(options) => {
+                        options.timestamp_strings.unwrap_or(false)
+                    }
+                    FormatConfig::Iceberg(options) => {
+                        options.parquet.timestamp_strings.unwrap_or(false)
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+            let start_sim_time = parse_start_time(loaded.traffic.start_time.as_deref())?;
+            let mut source = apply_event_stages(
+                build_event_source(&loaded.source, loaded.seed, start_sim_time)?,
+                &loaded,
+            )?;
+            let options = BenchOptions {
+                duration,
+                max_events,
+                sink,
+                timestamp_strings,
+            };
+            let report = run_bench(source.as_mut(), &options)?;
+            print_bench(&report, sink);
+        }
+        Commands::Scenarios {
+            command: ScenarioCommands::List { config },
+        } => match config {
+            Some(path) => {
+                let loaded = Config::from_path(&path)?;
+                print_scenarios(&loaded.source);
+            }
+            None => {
+                for technique in attack::TECHNIQUES {
+                    println!("{}", describe_technique(technique.id));
+                }
+            }
+        },
+    }
 
-        metrics.record(
-            loop_events,
-            loop_bytes,
-            Duration::ZERO,
-            0,
-            metric_event_time,
+    Ok(())
+}
+
+fn print_inspection(summary: &OutputSummary, top: usize) {
+    let (files, bytes) = summary
+        .files
+        .values()
+        .fold((0, 0), |(files, bytes), (count, size)| {
+            (files + count, bytes + size)
+        });
+    println!("files: {files} ({bytes} bytes)");
+    for (format, (count, size)) in &summary.files {
+        println!("  {format}: {count} files, {size} bytes");
+    }
+    if !summary.skipped.is_empty() {
+        println!("  skipped: {} non-event files", summary.skipped.len());
+    }
+    println!(
+        "events: {} errors: {} ({:.2}%)",
+        summary.events,
+        summary.errors,
+        summary.error_rate() * 100.0
+    );
+    if let (Some(first), Some(last)) = (summary.first_event, summary.last_event) {
+        println!(
+            "time range: {} .. {} ({}s)",
+            first.to_rfc3339_opts(SecondsFormat::Secs, true),
+            last.to_rfc3339_opts(SecondsFormat::Secs, true),
+            (last - first).num_seconds()
         );
     }
-
-    for tx in &writer_txs {
-        let _ = tx.send(WriterCommand::Close);
+    println!("sources:");
+    for (source, counts) in &summary.sources {
+        println!(
+            "  {source}: {} events, {} errors ({:.2}%)",
+            counts.events,
+            counts.errors,
+            counts.errors as f64 * 100.0 / counts.events.max(1) as f64
+        );
     }
-    drop(writer_txs);
+    let event_types = summary
+        .event_types
+        .iter()
+        .map(|((source, event_type), count)| (format!("{source} {event_type}"), *count));
+    print_top("event types", event_types, summary.events, top);
+    print_top(
+        "accounts",
+        summary
+            .accounts
+            .iter()
+            .map(|(key, count)| (key.clone(), *count)),
+        summary.events,
+        top,
+    );
+    print_top(
+        "regions",
+        summary
+            .regions
+            .iter()
+            .map(|(key, count)| (key.clone(), *count)),
+        summary.events,
+        top,
+    );
+    print_top(
+        "actors",
+        summary
+            .top_actors(top)
+            .into_iter()
+            .map(|(actor, count)| (actor.to_string(), count)),
+        summary.events,
+        top,
+    );
+    if summary.actors.len() > top {
+        println!("  ... {} distinct actors", summary.actors.len());
+    }
+}
 
-    for handle in writer_handles {
-        match handle.join() {
-            Ok(Ok(())) => {}
-            Ok(Err(err)) => return Err(format!("writer thread failed: {err}").into()),
-            Err(_) => return Err("writer thread panicked".into()),
+fn print_population(summary: &PopulationSummary) {
+    println!(
+        "actors: {} ({:.1} events/hour combined)",
+        summary.actors, summary.events_per_hour
+    );
+    let total = summary.actors as u64;
+    let counts = |map: &BTreeMap<String, usize>| {
+        map.iter()
+            .map(|(key, count)| (key.clone(), *count as u64))
+            .collect::<Vec<_>>()
+    };
+    print_top(
+        "kinds",
+        counts(&summary.kinds).into_iter(),
+        total,
+        usize::MAX,
+    );
+    print_top(
+        "roles",
+        counts(&summary.roles).into_iter(),
+        total,
+        usize::MAX,
+    );
+    print_top(
+        "service profiles",
+        counts(&summary.service_profiles).into_iter(),
+        total,
+        usize::MAX,
+    );
+    print_top("accounts", counts(&summary.accounts).into_iter(), total, 20);
+    print_top(
+        "identity types",
+        counts(&summary.identity_types).into_iter(),
+        total,
+        usize::MAX,
+    );
+    print_top(
+        "insider personas",
+        counts(&summary.insider_personas).into_iter(),
+        total,
+        usize::MAX,
+    );
+    print_histogram("rate_per_hour", &RATE_BUCKETS, &summary.rate_histogram);
+    print_histogram(
+        "error_rate",
+        &ERROR_RATE_BUCKETS,
+        &summary.error_rate_histogram,
+    );
+    if summary.duplicate_principals.is_empty() {
+        println!("duplicate principals: none");
+    } else {
+        println!(
+            "duplicate principals: {}",
+            summary.duplicate_principals.len()
+        );
+        for (principal, count) in summary.duplicate_principals.iter().take(10) {
+            println!("  {principal}: {count} actors");
         }
     }
-
-    Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-fn run_multi_file_generation(
-    config: &MultiSourceConfig,
-    default_output: &FileOutputConfig,
-    seed: Option<u64>,
-    start_sim_time: DateTime<Utc>,
-    time_scale: Option<f64>,
-    until_sim_time: Option<DateTime<Utc>>,
-    max_events: Option<u64>,
-    max_duration: Option<Duration>,
-    writer_shards: usize,
-    queue_depth: usize,
-    metrics_interval: Duration,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if config.sources.is_empty() {
-        return Err("multi source requires at least one child source".into());
-    }
-
-    let source_config = SourceConfig::Multi(config.clone());
-    let mut generator = build_event_source(&source_config, seed, start_sim_time)?;
-    let mut writers = RoutedWriters::new(config, default_output, writer_shards, queue_depth)?;
-    let flush_interval = Some(Duration::from_secs(1));
-    let mut next_flush = flush_interval.map(|interval| Instant::now() + interval);
-    let mut metrics = Metrics::new(metrics_interval, start_sim_time);
-    let start_time = Instant::now();
-    let mut last_sim_time = start_sim_time;
-    let mut last_wall = Instant::now();
-    let mut total_dispatched = 0_u64;
-    let mut last_written_events = 0_u64;
-    let mut last_written_bytes = 0_u64;
-
-    loop {
-        let loop_start = Instant::now();
-        if let Some(limit) = max_duration {
-            if loop_start.duration_since(start_time) >= limit {
-                break;
-            }
-        }
-        if let Some(max) = max_events {
-            if total_dispatched >= max {
-                break;
-            }
+fn print_population_diff(diff: &PopulationDiff, limit: usize) {
+    let (old, new) = (&diff.old_summary, &diff.new_summary);
+    println!(
+        "actors: {} -> {} ({:+})",
+        old.actors,
+        new.actors,
+        new.actors as i64 - old.actors as i64
+    );
+    println!(
+        "events/hour: {:.1} -> {:.1} ({:+.1})",
+        old.events_per_hour,
+        new.events_per_hour,
+        new.events_per_hour - old.events_per_hour
+    );
+    println!(
+        "added: {}, removed: {}, changed: {}, unchanged: {}",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.unchanged
+    );
+    if !diff.field_counts.is_empty() {
+        println!("changed attributes:");
+        for (field, count) in &diff.field_counts {
+            println!("  {field}: {count}");
         }
+    }
+    for (title, old, new) in [
+        ("kinds", &old.kinds, &new.kinds),
+        ("roles", &old.roles, &new.roles),
+        (
+            "service profiles",
+            &old.service_profiles,
+            &new.service_profiles,
+        ),
+        ("accounts", &old.accounts, &new.accounts),
+        (
+            "insider personas",
+            &old.insider_personas,
+            &new.insider_personas,
+        ),
+    ] {
+        print_count_drift(title, old, new);
+    }
 
-        let Some(event) = generator.next_event() else {
-            break;
-        };
-
-        let event_time = parse_event_time(&event);
-        let metric_event_time = event_time.clone();
-        if should_stop_at_until(event_time, until_sim_time) {
-            break;
+    for (title, seeds) in [("added", &diff.added), ("removed", &diff.removed)] {
+        if seeds.is_empty() {
+            continue;
         }
-        if let Some(event_time) = event_time {
-            if let Some(scale) = time_scale {
-                throttle_to_sim_time(event_time, last_sim_time, scale, &mut last_wall);
+        println!("{title} actors:");
+        for seed in seeds.iter().take(limit) {
+            println!("  {}", describe_actor(seed));
+        }
+        if seeds.len() > limit {
+            println!("  ... {} more", seeds.len() - limit);
+        }
+    }
+    if !diff.changed.is_empty() {
+        println!("changed actors:");
+        for actor in diff.changed.iter().take(limit) {
+            println!("  {}:", actor.label);
+            for change in &actor.changes {
+                println!("    {}: {} -> {}", change.field, change.old, change.new);
             }
-            last_sim_time = event_time;
         }
+        if diff.changed.len() > limit {
+            println!("  ... {} more", diff.changed.len() - limit);
+        }
+    }
+}
 
-        writers.dispatch(event)?;
-        total_dispatched += 1;
-
-        let current_events = writers.counters.events.load(Ordering::Relaxed);
-        let current_bytes = writers.counters.bytes.load(Ordering::Relaxed);
-        let loop_events = current_events.saturating_sub(last_written_events);
-        let loop_bytes = current_bytes.saturating_sub(last_written_bytes);
-        last_written_events = current_events;
-        last_written_bytes = current_bytes;
+/// Prints keys whose counts differ between two tallies.
+fn print_count_drift(title: &str, old: &BTreeMap<String, usize>, new: &BTreeMap<String, usize>) {
+    let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+    let rows = keys
+        .into_iter()
+        .filter_map(|key| {
+            let (before, after) = (
+                old.get(key).copied().unwrap_or(0),
+                new.get(key).copied().unwrap_or(0),
+            );
+            (before != after).then_some((key, before, after))
+        })
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return;
+    }
+    println!("{title}:");
+    for (key, before, after) in rows {
+        println!(
+            "  {key}: {before} -> {after} ({:+})",
+            after as i64 - before as i64
+        );
+    }
+}
 
-        if let (Some(interval), Some(next)) = (flush_interval, next_flush) {
-            if loop_start >= next {
-                writers.flush_all();
-                next_flush = Some(loop_start + interval);
-            }
+/// Prints bucket counts as bars scaled to the largest bucket.
+fn print_histogram(title: &str, bounds: &[f64], counts: &[usize]) {
+    const WIDTH: usize = 40;
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    println!("{title}:");
+    let mut lower = 0.0;
+    for (index, count) in counts.iter().enumerate() {
+        let range = match bounds.get(index) {
+            Some(upper) => format!("{lower}-{upper}"),
+            None => format!(">={lower}"),
+        };
+        println!(
+            "  {range:>12} {count:>6} {}",
+            "#".repeat((count * WIDTH).div_ceil(max))
+        );
+        if let Some(upper) = bounds.get(index) {
+            lower = *upper;
         }
+    }
+}
 
-        metrics.record(
-            loop_events,
-            loop_bytes,
-            Duration::ZERO,
-            0,
-            metric_event_time,
+/// Prints the `limit` largest counts with their share of `total`.
+fn print_top(title: &str, rows: impl Iterator<Item = (String, u64)>, total: u64, limit: usize) {
+    let mut rows = rows.collect::<Vec<_>>();
+    if rows.is_empty() {
+        return;
+    }
+    let distinct = rows.len();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    println!("{title}:");
+    for (key, count) in rows.iter().take(limit) {
+        println!(
+            "  {key}: {count} ({:.1}%)",
+            *count as f64 * 100.0 / total.max(1) as f64
         );
     }
-
-    writers.close()?;
-    Ok(())
+    if distinct > limit {
+        println!("  ... {} more", distinct - limit);
+    }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn run_zerobus_generation(
-    mut generator: Box<dyn EventSource>,
-    source_config: &SourceConfig,
-    output: &ZerobusOutputConfig,
-    time_scale: Option<f64>,
-    start_sim_time: DateTime<Utc>,
-    start_time: Instant,
-    until_sim_time: Option<DateTime<Utc>>,
-    max_events: Option<u64>,
-    max_duration: Option<Duration>,
-    metrics_interval: Duration,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut writer = ZerobusWriter::new(output)?;
-    persist_zerobus_actor_population_if_configured(source_config, output, &mut writer)?;
-    let flush_interval = Some(Duration::from_millis(output.flush_interval_ms.max(1)));
-    let mut next_flush = flush_interval.map(|interval| Instant::now() + interval);
-    let mut metrics = Metrics::new(metrics_interval, start_sim_time);
-    let mut total_dispatched = 0_u64;
-    let mut loop_bytes = 0_u64;
-    let mut last_sim_time = start_sim_time;
-    let mut last_wall = Instant::now();
-
-    loop {
-        let loop_start = Instant::now();
-        if let Some(limit) = max_duration {
-            if loop_start.duration_since(start_time) >= limit {
-                break;
-            }
-        }
-        if let Some(max) = max_events {
-            if total_dispatched >= max {
-                break;
-            }
-        }
+fn load_issue(path: &Path, err: impl std::fmt::Display) -> ValidationIssue {
+    ValidationIssue {
+        severity: Severity::Error,
+        path: path.display().to_string(),
+        message: err.to_string(),
+    }
+}
 
-        let Some(event) = generator.next_event() else {
-            break;
+fn print_bench(report: &BenchReport, sink: BenchSink) {
+    println!(
+        "events: {} in {:.1}s ({:.0} events/s on one thread)",
+        report.events,
+        report.elapsed.as_secs_f64(),
+        report.events_per_second()
+    );
+    let micros = |time: Duration, events: u64| time.as_secs_f64() * 1e6 / events.max(1) as f64;
+    println!(
+        "{:<20} {:>10} {:>12} {:>10} {:>10} {:>10} {:>10} {:>13} {:>12}",
+        "source",
+        "events",
+        "max ev/s",
+        "gen us",
+        "env us",
+        "payload us",
+        "ct us",
+        "allocs/event",
+        "bytes/event"
+    );
+    for (source, stats) in &report.sources {
+        let per_event = |total: u64| total as f64 / stats.events.max(1) as f64;
+        let (allocations, bytes) = if report.counts_allocations {
+            (
+                format!("{:.1}", per_event(stats.allocations)),
+                format!("{:.0}", per_event(stats.allocated_bytes)),
+            )
+        } else {
+            ("-".to_string(), "-".to_string())
         };
+        println!(
+            "{:<20} {:>10} {:>12.0} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>13} {:>12}",
+            source,
+            stats.events,
+            stats.events_per_second(),
+            micros(stats.generate, stats.events),
+            micros(stats.envelope, stats.events),
+            micros(stats.payload, stats.events),
+            micros(stats.cloudtrail, stats.events),
+            allocations,
+            bytes
+        );
+    }
+    println!(
+        "batch finish: {:.2} us/event over {} batches",
+        micros(report.batch_finish, report.events),
+        report.batches
+    );
+    if sink == BenchSink::Memory {
+        println!(
+            "retained: {:.1} MiB ({:.0} bytes/event)",
+            report.retained_bytes as f64 / (1024.0 * 1024.0),
+            report.retained_bytes as f64 / report.events.max(1) as f64
+        );
+    }
+}
 
-        let event_time = parse_event_time(&event);
-        let metric_event_time = event_time.clone();
-        if should_stop_at_until(event_time, until_sim_time) {
-            break;
-        }
-        if let Some(event_time) = event_time {
-            if let Some(scale) = time_scale {
-                throttle_to_sim_time(event_time, last_sim_time, scale, &mut last_wall);
-            }
-            last_sim_time = event_time;
+fn report_verification(report: &VerifyReport) -> Result<(), Box<dyn std::error::Error>> {
+    println!("files: {} events: {}", report.files, report.events);
+    for (check, result) in &report.checks {
+        let status = if result.violations == 0 { "pass" } else { "fail" };
+        println!("{status} {check}: {} violations", result.violations);
+        for example in &result.examples {
+            println!("  {example}");
         }
-
-        loop_bytes += writer.write_event(&event)?;
-        total_dispatched += 1;
-
-        if let (Some(interval), Some(next)) = (flush_interval, next_flush) {
-            if loop_start >= next {
-                writer.flush()?;
-                next_flush = Some(loop_start + interval);
-            }
+        if result.violations > result.examples.len() as u64 {
+            println!("  ... {} more", result.violations - result.examples.len() as u64);
         }
-
-        metrics.record(1, loop_bytes, Duration::ZERO, 0, metric_event_time);
-        loop_bytes = 0;
     }
+    if !report.checks.contains_key(&Check::RotationGaps) {
+        let largest = report
+            .largest_gap
+            .map_or_else(|| "none".to_string(), |gap| format!("{}s", gap.as_secs()));
+        println!("skip {}: largest gap {largest} (set --max-gap)", Check::RotationGaps);
+    }
+    if !report.passed() {
+        return Err(format!("verification failed: {} violations", report.violations()).into());
+    }
+    println!("verification passed");
+    Ok(())
+}
 
-    writer.close()?;
+fn report_validation(issues: &[ValidationIssue]) -> Result<(), Box<dyn std::error::Error>> {
+    for issue in issues {
+        println!("{issue}");
+    }
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+    if errors > 0 {
+        return Err(format!("config invalid: {errors} errors, {warnings} warnings").into());
+    }
+    println!("config valid: {warnings} warnings");
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
-fn run_databricks_volume_generation(
-    mut generator: Box<dyn EventSource>,
-    output: &DatabricksVolumeOutputConfig,
-    time_scale: Option<f64>,
-    start_sim_time: DateTime<Utc>,
-    start_time: Instant,
-    until_sim_time: Option<DateTime<Utc>>,
-    max_events: Option<u64>,
-    max_duration: Option<Duration>,
-    metrics_interval: Duration,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut writer = DatabricksVolumeWriter::new(output)?;
-    let flush_interval = Some(Duration::from_millis(output.flush_interval_ms.max(1)));
-    let mut next_flush = flush_interval.map(|interval| Instant::now() + interval);
-    let mut metrics = Metrics::new(metrics_interval, start_sim_time);
-    let mut total_dispatched = 0_u64;
-    let mut loop_bytes = 0_u64;
-    let mut last_sim_time = start_sim_time;
-    let mut last_wall = Instant::now();
-
-    loop {
-        let loop_start = Instant::now();
-        if let Some(limit) = max_duration {
-            if loop_start.duration_since(start_time) >= limit {
-                break;
-            }
-        }
-        if let Some(max) = max_events {
-            if total_dispatched >= max {
-                break;
+fn print_scenarios(source: &SourceConfig) {
+    let scenarios = attack::scenarios(source);
+    if scenarios.is_empty() {
+        println!("no scenarios declared (set scenario_id on injected events)");
+        return;
+    }
+    let mut by_tactic: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut techniques = BTreeSet::new();
+    for scenario in &scenarios {
+        println!(
+            "{} sources={} events={}",
+            scenario.id,
+            scenario
+                .sources
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(","),
+            scenario.events
+        );
+        for id in &scenario.technique_ids {
+            println!("  {}", describe_technique(id));
+            techniques.insert(id.as_str());
+            for tactic in attack::technique(id).map_or(&[][..], |technique| technique.tactics) {
+                by_tactic.entry(tactic).or_default().insert(id.as_str());
             }
         }
+    }
+    println!(
+        "coverage: {} scenarios, {} techniques, {} tactics",
+        scenarios.len(),
+        techniques.len(),
+        by_tactic.len()
+    );
+    for (tactic, ids) in by_tactic {
+        println!(
+            "  {tactic}: {}",
+            ids.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+}
 
-        let Some(event) = generator.next_event() else {
-            break;
-        };
-
-        let event_time = parse_event_time(&event);
-        let metric_event_time = event_time.clone();
-        if should_stop_at_until(event_time, until_sim_time) {
-            break;
-        }
-        if let Some(event_time) = event_time {
-            if let Some(scale) = time_scale {
-                throttle_to_sim_time(event_time, last_sim_time, scale, &mut last_wall);
-            }
-            last_sim_time = event_time;
-        }
+fn describe_technique(id: &str) -> String {
+    match attack::technique(id) {
+        Some(technique) => format!(
+            "{:<10} {} [{}]",
+            technique.id,
+            technique.name,
+            technique.tactics.join(", ")
+        ),
+        None => format!("{id:<10} (not in built-in catalog)"),
+    }
+}
 
-        loop_bytes += writer.write_event(&event)?;
-        total_dispatched += 1;
+fn identity_registry_from_population_config_path(
+    path: &str,
+) -> Result<IdentityRegistry, Box<dyn std::error::Error>> {
+    let config = PopulationConfig::from_path(path)?;
+    let population = generate_population(&config)?;
+    Ok(IdentityRegistry::from_population(
+        "generated_identity_registry",
+        &population,
+    )?)
+}
 
-        if let (Some(interval), Some(next)) = (flush_interval, next_flush) {
-            if loop_start >= next {
-                writer.flush()?;
-                next_flush = Some(loop_start + interval);
-            }
+/// Builds the generation source. Under `--watch-config` it follows the
+/// watcher's reloadable calendar and CloudTrail scenario schedule; otherwise
+/// it is staged as [`apply_event_stages`] does. Byte-capped sources read what
+/// the writers report to `written`.
+fn gen_event_source(
+    config: &Config,
+    start_time: DateTime<Utc>,
+    watcher: Option<&ConfigWatcher>,
+    written: Option<&SharedWrittenBytes>,
+) -> Result<Box<dyn EventSource>, Box<dyn std::error::Error>> {
+    let scenarios = watcher.and_then(ConfigWatcher::scenarios);
+    let source = match (written, scenarios) {
+        (Some(written), scenarios) => build_event_source_with_written_bytes(
+            &config.source,
+            config.seed,
+            start_time,
+            scenarios,
+            written,
+        )?,
+        (None, Some(scenarios)) => {
+            build_event_source_with_scenarios(&config.source, config.seed, start_time, scenarios)?
         }
-
-        metrics.record(1, loop_bytes, Duration::ZERO, 0, metric_event_time);
-        loop_bytes = 0;
+        (None, None) => build_event_source(&config.source, config.seed, start_time)?,
+    };
+    match watcher {
+        Some(watcher) => apply_event_stages_with_calendar(source, config, watcher.calendar()),
+        None => apply_event_stages(source, config),
     }
+}
 
-    writer.close()?;
-    Ok(())
+/// A multi source with a `max_bytes` limit, whose writers must report to a
+/// [`WrittenBytes`] tally.
+fn has_byte_limits(source: &SourceConfig) -> bool {
+    let SourceConfig::Multi(config) = source else {
+        return false;
+    };
+    config
+        .limits
+        .iter()
+        .flat_map(HashMap::values)
+        .any(|limits| limits.max_bytes.is_some())
 }
 
 fn persist_zerobus_actor_population_if_configured(
@@ -685,6 +1715,30 @@ fn collect_identity_registry_paths(
                 non_empty_str(&config.identity_registry_path).or(inherited_path),
             );
         }
+        SourceConfig::GitHubAudit(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
+        SourceConfig::KubernetesAudit(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
+        SourceConfig::WindowsSecurity(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
+        SourceConfig::GoogleWorkspace(config) => {
+            insert_optional_path(
+                paths,
+                non_empty_str(&config.identity_registry_path).or(inherited_path),
+            );
+        }
         SourceConfig::Multi(config) => {
             let next_inherited = config
                 .identity_registry_path
@@ -772,6 +1826,8 @@ fn identity_population_row_json(
         "timezone_offset": identity.timezone_offset,
         "weekend_active": identity.weekend_active,
         "service_pattern": &identity.service_pattern,
+        "insider_persona": &identity.insider_persona,
+        "on_call": &identity.on_call,
         "tags_json": serde_json::to_string(&identity.tags)?,
         "aws_principals_json": serde_json::to_string(&identity.aws_principals)?,
         "identity_json": identity_json,
@@ -791,107 +1847,11 @@ fn identity_registry_from_population_config(
     )?)
 }
 
-struct RoutedWriters {
-    routes: HashMap<String, WriterRoute>,
-    handles: Vec<thread::JoinHandle<WorkerResult>>,
-    counters: WriterCounters,
-}
-
-struct WriterRoute {
-    senders: Vec<SyncSender<WriterCommand>>,
-    shards: usize,
-}
-
-impl RoutedWriters {
-    fn new(
-        config: &MultiSourceConfig,
-        default_output: &FileOutputConfig,
-        writer_shards: usize,
-        queue_depth: usize,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let counters = WriterCounters::new();
-        let mut routes = HashMap::new();
-        let mut handles = Vec::new();
-        for source in &config.sources {
-            for key in source_output_keys(source) {
-                if routes.contains_key(&key) {
-                    continue;
-                }
-                let output = config
-                    .outputs
-                    .as_ref()
-                    .and_then(|outputs| outputs.get(&key))
-                    .unwrap_or(default_output);
-                let (senders, route_handles) =
-                    spawn_writer_shards(output, writer_shards, queue_depth, &counters);
-                handles.extend(route_handles);
-                routes.insert(
-                    key,
-                    WriterRoute {
-                        senders,
-                        shards: writer_shards,
-                    },
-                );
-            }
-        }
-
-        Ok(Self {
-            routes,
-            handles,
-            counters,
-        })
-    }
-
-    fn dispatch(&self, event: Event) -> Result<(), Box<dyn std::error::Error>> {
-        let source = event.envelope.source.clone();
-        let route = self
-            .routes
-            .get(&source)
-            .ok_or_else(|| format!("no output route configured for source {source}"))?;
-        dispatch_event(event, &route.senders, route.shards)
-    }
-
-    fn flush_all(&self) {
-        for route in self.routes.values() {
-            for tx in &route.senders {
-                let _ = tx.send(WriterCommand::Flush);
-            }
-        }
-    }
-
-    fn close(self) -> Result<(), Box<dyn std::error::Error>> {
-        for route in self.routes.values() {
-            for tx in &route.senders {
-                let _ = tx.send(WriterCommand::Close);
-            }
-        }
-        drop(self.routes);
-
-        for handle in self.handles {
-            match handle.join() {
-                Ok(Ok(())) => {}
-                Ok(Err(err)) => return Err(format!("writer thread failed: {err}").into()),
-                Err(_) => return Err("writer thread panicked".into()),
-            }
-        }
-        Ok(())
-    }
-}
-
-fn source_output_keys(config: &SourceConfig) -> Vec<String> {
-    match config {
-        SourceConfig::CloudTrail(_) => vec!["cloudtrail".to_string()],
-        SourceConfig::DatabricksAudit(_) => vec!["databricks_audit".to_string()],
-        SourceConfig::OktaSystemLog(_) => vec!["okta_system_log".to_string()],
-        SourceConfig::Multi(config) => config.sources.iter().flat_map(source_output_keys).collect(),
-    }
-}
-
 fn validate_zerobus_table_routes(
     config: &SourceConfig,
     output: &ZerobusOutputConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    for source in source_output_keys(config) {
+    for source in config.envelope_sources() {
         if !output.tables.contains_key(&source) {
             return Err(format!("no zerobus table configured for source {source}").into());
         }
@@ -899,26 +1859,63 @@ fn validate_zerobus_table_routes(
     Ok(())
 }
 
-type WorkerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
-
-enum WriterCommand {
-    Event(Event),
-    Flush,
-    Close,
+fn file_output_dirs(source: &SourceConfig, output: &FileOutputConfig) -> Vec<PathBuf> {
+    let mut dirs = BTreeSet::new();
+    dirs.extend(output.format_dirs().into_iter().map(|(_, dir)| dir));
+    if let SourceConfig::Multi(config) = source {
+        for output in config.outputs.iter().flat_map(|outputs| outputs.values()) {
+            dirs.extend(output.format_dirs().into_iter().map(|(_, dir)| dir));
+        }
+    }
+    dirs.into_iter().collect()
 }
 
-struct WriterCounters {
-    events: Arc<AtomicU64>,
-    bytes: Arc<AtomicU64>,
+/// Rejects invalid Parquet writer options before any writer thread starts.
+fn validate_file_formats(source: &SourceConfig, output: &FileOutputConfig) -> io::Result<()> {
+    let mut outputs = vec![output];
+    if let SourceConfig::Multi(config) = source {
+        outputs.extend(config.outputs.iter().flat_map(|outputs| outputs.values()));
+    }
+    for (format, _) in outputs.iter().flat_map(|output| output.format_dirs()) {
+        if let FormatConfig::Parquet(options)
+        | FormatConfig::Delta(options)
+        | FormatConfig::Iceberg(IcebergOptions {
+            parquet: options, ..
+        }) = format
+        {
+            parquet_writer_properties(options)?;
+        }
+    }
+    Ok(())
 }
 
-impl WriterCounters {
-    fn new() -> Self {
-        Self {
-            events: Arc::new(AtomicU64::new(0)),
-            bytes: Arc::new(AtomicU64::new(0)),
+/// Builds the byte budget for `--max-bytes` values: a total size, or
+/// `SOURCE=SIZE` for one envelope source.
+fn byte_budget(
+    values: &[String],
+    source: &SourceConfig,
+) -> Result<ByteBudget, Box<dyn std::error::Error>> {
+    let emitted = source.envelope_sources();
+    let mut total = None;
+    let mut sources = HashMap::new();
+    for value in values {
+        let (name, size) = match value.split_once('=') {
+            Some((name, size)) => (Some(name.trim()), size),
+            None => (None, value.as_str()),
+        };
+        let bytes =
+            parse_byte_size(size).ok_or_else(|| format!("--max-bytes: invalid size {size:?}"))?;
+        match name {
+            Some(name) if !emitted.iter().any(|emits| emits == name) => {
+                return Err(format!("--max-bytes: no configured source emits {name}").into());
+            }
+            Some(name) => {
+                sources.insert(name.to_string(), bytes);
+            }
+            None => total = Some(bytes),
         }
     }
+    Ok(ByteBudget::new(DiskUsage::new(), total, sources, emitted))
 }
 
 fn normalize_workers(requested: usize) -> usize {
@@ -962,132 +1959,396 @@ fn parse_optional_time(
         .map_err(Into::into)
 }
 
-fn parse_event_time(event: &Event) -> Option<DateTime<Utc>> {
-    DateTime::parse_from_rfc3339(&event.envelope.timestamp)
-        .ok()
-        .map(|dt| dt.with_timezone(&Utc))
+fn build_file_writer(
+    format: &FormatConfig,
+    dir: &Path,
+    target_size_mb: u64,
+    max_age_seconds: Option<u64>,
+    budget: Option<&Arc<BufferBudget>>,
+    gauge: Option<&Arc<AtomicU64>>,
+    filename: &FilenameTemplate,
+) -> io::Result<Box<dyn EventWriter>> {
+    let writer: Box<dyn EventWriter> = match format {
+        FormatConfig::Jsonl(options) => {
+            let mut writer = JsonlWriter::new(
+                dir,
+                target_size_mb,
+                max_age_seconds,
+                options.compression.as_deref(),
+            )?
+            .with_filename_template(filename.clone());
+            if let Some(budget) = budget {
+                writer = writer.with_buffer_budget(budget.clone());
+            }
+            if let Some(gauge) = gauge {
+                writer = writer.with_buffer_gauge(gauge.clone());
+            }
+            if options.s3_layout {
+                writer = writer.with_s3_layout();
+            }
+            match &options.digest {
+                Some(digest) => Box::new(writer.with_cloudtrail_digest(digest.clone())),
+                None => Box::new(writer),
+            }
+        }
+        FormatConfig::Parquet(options)
+        | FormatConfig::Delta(options)
+        | FormatConfig::Iceberg(IcebergOptions {
+            parquet: options, ..
+        }) => {
+            let mut writer =
+                ParquetWriter::with_options(dir, target_size_mb, max_age_seconds, options)?
+                    .with_filename_template(filename.clone());
+            if let Some(budget) = budget {
+                writer = writer.with_buffer_budget(budget.clone());
+            }
+            if let Some(gauge) = gauge {
+                writer = writer.with_buffer_gauge(gauge.clone());
+            }
+            Box::new(writer)
+        }
+        FormatConfig::CloudwatchLogs(options) => Box::new(CloudWatchLogsWriter::new(
+            dir,
+            target_size_mb,
+            max_age_seconds,
+            options.clone(),
+        )?),
+        FormatConfig::AzureMonitor(options) => {
+            let mut writer =
+                JsonlWriter::azure_monitor(dir, target_size_mb, max_age_seconds, options.clone())?
+                    .with_filename_template(filename.clone());
+            if let Some(budget) = budget {
+                writer = writer.with_buffer_budget(budget.clone());
+            }
+            if let Some(gauge) = gauge {
+                writer = writer.with_buffer_gauge(gauge.clone());
+            }
+            Box::new(writer)
+        }
+        FormatConfig::Avro(options) => Box::new(AvroWriter::new(
+            dir,
+            target_size_mb,
+            max_age_seconds,
+            options,
+        )?),
+        FormatConfig::Ocsf(options) => Box::new(OcsfWriter::new(
+            dir,
+            target_size_mb,
+            max_age_seconds,
+            options,
+        )?),
+        FormatConfig::Csv(options) => Box::new(CsvWriter::new(
+            dir,
+            target_size_mb,
+            max_age_seconds,
+            options,
+        )?),
+        FormatConfig::Text(options) => Box::new(
+            TextWriter::new(
+                dir,
+                target_size_mb,
+                max_age_seconds,
+                options.compression.as_deref(),
+            )?
+            .with_filename_template(filename.clone()),
+        ),
+    };
+    Ok(writer)
 }
 
-fn should_stop_at_until(
-    event_time: Option<DateTime<Utc>>,
-    until_time: Option<DateTime<Utc>>,
-) -> bool {
-    matches!((event_time, until_time), (Some(event_time), Some(until_time)) if event_time > until_time)
+/// Buffer budget for a file output; one budget covers every shard, format, and
+/// partition writing to it.
+fn buffer_budget(output: &FileOutputConfig) -> Option<Arc<BufferBudget>> {
+    output
+        .files
+        .max_buffered_mb
+        .map(|mb| BufferBudget::new(mb.saturating_mul(1024 * 1024)))
 }
 
-fn throttle_to_sim_time(
-    current: DateTime<Utc>,
-    previous: DateTime<Utc>,
-    scale: f64,
-    last_wall: &mut Instant,
-) {
-    if scale <= 0.0 {
-        return;
+/// Builds the writer for a file output, partitioning by tenant and/or time when
+/// configured and fanning out to every configured format.
+fn file_output_writer(
+    output: &FileOutputConfig,
+    tenant_partitions: bool,
+    budget: Option<Arc<BufferBudget>>,
+    gauge: Option<Arc<AtomicU64>>,
+    disk_usage: Option<Arc<DiskUsage>>,
+) -> io::Result<Box<dyn EventWriter>> {
+    let mut writers = output
+        .format_dirs()
+        .into_iter()
+        .map(|(format, dir)| {
+            format_writer(
+                output,
+                format,
+                &dir,
+                tenant_partitions,
+                budget.clone(),
+                gauge.clone(),
+            )
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut writer: Box<dyn EventWriter> = if writers.len() == 1 {
+        writers.remove(0)
+    } else {
+        Box::new(TeeWriter::new(writers))
+    };
+    if let Some(ordering) = &output.files.ordering {
+        writer = Box::new(OrderingWriter::new(writer, ordering)?);
     }
-    if current <= previous {
-        return;
+    if let Some(faults) = &output.fault_injection {
+        writer = Box::new(FaultInjectingWriter::new(writer, faults)?);
+    }
+    if let Some(usage) = disk_usage {
+        writer = Box::new(DiskUsageWriter::new(writer, usage));
+    }
+    if output.manifest {
+        return Ok(Box::new(ManifestWriter::new(
+            writer,
+            &output.dir,
+            manifest_run_id(),
+        )));
     }
-    let sim_delta = current - previous;
-    let sim_secs = sim_delta.num_milliseconds().max(0) as f64 / 1000.0;
-    let target = Duration::from_secs_f64(sim_secs / scale);
-    let elapsed = last_wall.elapsed();
-    if target > elapsed {
-        std::thread::sleep(target - elapsed);
+    Ok(writer)
+}
+
+fn format_writer(
+    output: &FileOutputConfig,
+    format: &FormatConfig,
+    dir: &Path,
+    tenant_partitions: bool,
+    budget: Option<Arc<BufferBudget>>,
+    gauge: Option<Arc<AtomicU64>>,
+) -> io::Result<Box<dyn EventWriter>> {
+    let format = format.clone();
+    let partition_by = output.partition_by;
+    let target_size_mb = output.files.target_size_mb;
+    let max_age_seconds = Some(output.files.max_age_seconds);
+    let filename = match &output.files.filename_template {
+        Some(template) => FilenameTemplate::parse(template)?,
+        None => FilenameTemplate::default(),
+    };
+    // Table formats live under one root per format, so their partition
+    // directories leave out the source segment.
+    let table = matches!(format, FormatConfig::Delta(_) | FormatConfig::Iceberg(_));
+    let data_dir = match &format {
+        FormatConfig::Iceberg(_) => dir.join(ICEBERG_DATA_DIR),
+        _ => dir.to_path_buf(),
+    };
+    let inner_format = format.clone();
+    let writer = if !tenant_partitions && partition_by == OutputPartitioning::None {
+        build_file_writer(
+            &inner_format,
+            &data_dir,
+            target_size_mb,
+            max_age_seconds,
+            budget.as_ref(),
+            gauge.as_ref(),
+            &filename,
+        )?
+    } else {
+        let partition = move |event: &Event| {
+            let mut path = if tenant_partitions {
+                tenant_partition(event)
+            } else {
+                PathBuf::new()
+            };
+            if table {
+                path.push(hive_time_partition(event, partition_by));
+            } else {
+                path.push(time_partition(event, partition_by));
+            }
+            path
+        };
+        Box::new(PartitionedWriter::new(data_dir, partition, move |dir| {
+            build_file_writer(
+                &inner_format,
+                dir,
+                target_size_mb,
+                max_age_seconds,
+                budget.as_ref(),
+                gauge.as_ref(),
+                &filename,
+            )
+        }))
+    };
+    match &format {
+        FormatConfig::Delta(options) => Ok(Box::new(DeltaWriter::new(
+            writer,
+            dir,
+            options,
+            PartitionColumn::for_layout(partition_by, tenant_partitions),
+        )?)),
+        FormatConfig::Iceberg(options) => Ok(Box::new(IcebergWriter::new(
+            writer,
+            dir,
+            options,
+            partition_by,
+        )?)),
+        _ => Ok(writer),
+    }
+}
+
+/// Run ID shared by every manifest this process writes.
+fn manifest_run_id() -> &'static str {
+    static RUN_ID: OnceLock<String> = OnceLock::new();
+    RUN_ID.get_or_init(new_run_id)
+}
+
+/// Aggregates this run's writer manifests under `output.dir`, if any were written.
+fn finish_run_manifest(dirs: &[PathBuf], output: &FileOutputConfig) -> io::Result<()> {
+    if let Some(path) = write_run_manifest(dirs, Path::new(&output.dir), manifest_run_id())? {
+        info!(path = %path.display(), "run manifest written");
     }
-    *last_wall = Instant::now();
+    Ok(())
 }
 
-fn spawn_writer_shards(
+/// Writer shards for one file output; each shard builds its own writer stack
+/// and adds its buffered bytes to `buffered`.
+#[allow(clippy::too_many_arguments)]
+fn file_sink(
+    source: &str,
     output: &FileOutputConfig,
     shards: usize,
     queue_depth: usize,
-    counters: &WriterCounters,
-) -> (
-    Vec<SyncSender<WriterCommand>>,
-    Vec<thread::JoinHandle<WorkerResult>>,
-) {
-    let mut senders = Vec::with_capacity(shards);
-    let mut handles = Vec::with_capacity(shards);
+    tenant_partitions: bool,
+    buffered: &Arc<AtomicU64>,
+    disk_usage: Option<Arc<DiskUsage>>,
+    written: Option<SharedWrittenBytes>,
+    on_flush: Option<FlushObserver>,
+) -> WriterShards {
+    let counters = SinkCounters::default();
+    let mut sink = WriterShards::new(queue_depth)
+        .backpressure(output.backpressure)
+        .counters(counters.clone());
+    if let Some(observe) = on_flush {
+        sink = sink.on_flush(observe);
+    }
+    let shared_budget = buffer_budget(output);
     for _ in 0..shards {
-        let (tx, rx): (SyncSender<WriterCommand>, Receiver<WriterCommand>) =
-            sync_channel(queue_depth);
-        let format = output.format.clone();
-        let dir = output.dir.clone();
-        let target_size_mb = output.files.target_size_mb;
-        let max_age_seconds = Some(output.files.max_age_seconds);
-        let events_counter = Arc::clone(&counters.events);
-        let bytes_counter = Arc::clone(&counters.bytes);
-        let handle = thread::spawn(move || -> WorkerResult {
-            let mut writer: Box<dyn EventWriter> = match format {
-                FormatConfig::Jsonl(options) => Box::new(JsonlWriter::new(
-                    &dir,
-                    target_size_mb,
-                    max_age_seconds,
-                    options.compression.as_deref(),
-                )?),
-                FormatConfig::Parquet(_) => {
-                    Box::new(ParquetWriter::new(&dir, target_size_mb, max_age_seconds)?)
-                }
-            };
-            while let Ok(command) = rx.recv() {
-                match command {
-                    WriterCommand::Event(event) => {
-                        let bytes = writer.write_event(&event)?;
-                        events_counter.fetch_add(1, Ordering::Relaxed);
-                        bytes_counter.fetch_add(bytes, Ordering::Relaxed);
-                    }
-                    WriterCommand::Flush => {
-                        writer.flush()?;
-                    }
-                    WriterCommand::Close => {
-                        writer.close()?;
-                        break;
-                    }
-                }
+        let output = output.clone();
+        let counters = counters.clone();
+        let budget = shared_budget.clone();
+        let gauge = Arc::clone(buffered);
+        let disk_usage = disk_usage.clone();
+        let written = written.clone();
+        sink = sink.spawn(source, move || {
+            let mut writer =
+                file_output_writer(&output, tenant_partitions, budget, Some(gauge), disk_usage)?;
+            if let Some(retry) = &output.write_retry {
+                writer = Box::new(
+                    RetryingWriter::new(writer, retry)?
+                        .with_skipped_counter(counters.dropped)
+                        .with_dead_letter_counter(counters.dead_lettered),
+                );
+            }
+            if let Some(written) = written {
+                writer = Box::new(WrittenBytesWriter::new(writer, written));
             }
-            Ok(())
+            debug!(dir = %output.dir, "writer started");
+            Ok(writer)
         });
-        senders.push(tx);
-        handles.push(handle);
     }
-
-    (senders, handles)
+    sink
 }
 
-fn dispatch_event(
-    event: Event,
-    writers: &[SyncSender<WriterCommand>],
+/// Routes each envelope source of a multi-source run to its own file output.
+#[allow(clippy::too_many_arguments)]
+fn routed_file_sink(
+    config: &MultiSourceConfig,
+    default_output: &FileOutputConfig,
     shards: usize,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if writers.is_empty() {
-        return Ok(());
+    queue_depth: usize,
+    buffered: &Arc<AtomicU64>,
+    disk_usage: Option<Arc<DiskUsage>>,
+    written: Option<SharedWrittenBytes>,
+    on_flush: Option<FlushObserver>,
+) -> RoutedSink {
+    let mut sink = RoutedSink::new();
+    for source in &config.sources {
+        for key in source.envelope_sources() {
+            if sink.contains(&key) {
+                continue;
+            }
+            let output = config
+                .outputs
+                .as_ref()
+                .and_then(|outputs| outputs.get(&key))
+                .unwrap_or(default_output);
+            let shards = file_sink(
+                &key,
+                output,
+                shards,
+                queue_depth,
+                !config.tenants.is_empty(),
+                buffered,
+                disk_usage.clone(),
+                written.clone(),
+                on_flush.clone(),
+            );
+            sink = sink.route(key, Box::new(shards));
+        }
     }
-    let idx = writer_index_for_event(&event, shards);
-    writers[idx]
-        .send(WriterCommand::Event(event))
-        .map_err(|_| "writer queue is closed".into())
+    sink
 }
 
-fn writer_index_for_event(event: &Event, shards: usize) -> usize {
-    if shards <= 1 {
-        return 0;
+fn inline_sink(
+    writer: Box<dyn EventWriter>,
+    written: Option<&SharedWrittenBytes>,
+    on_flush: Option<FlushObserver>,
+) -> Box<dyn EventSink> {
+    let writer = match written {
+        Some(written) => Box::new(WrittenBytesWriter::new(writer, Arc::clone(written))),
+        None => writer,
+    };
+    let sink = InlineSink::new(writer);
+    match on_flush {
+        Some(observe) => Box::new(sink.on_flush(observe)),
+        None => Box::new(sink),
     }
-    let account_id = event
-        .envelope
-        .tenant_id
-        .as_deref()
-        .unwrap_or("000000000000");
-    let region = event
-        .payload
-        .get("awsRegion")
-        .or_else(|| event.payload.get("aws_region"))
-        .and_then(|value| value.as_str())
-        .unwrap_or("global");
+}
+
+/// Drives a network sink from its own tokio runtime through
+/// `AsyncSinkWriter`, so requests overlap generation instead of stalling the
+/// pipeline thread; up to `queue_depth` events wait for the sink.
+#[cfg(feature = "tokio")]
+fn network_writer(
+    sink: impl AsyncEventSink + 'static,
+    queue_depth: usize,
+) -> io::Result<Box<dyn EventWriter>> {
+    Ok(Box::new(AsyncSinkWriter::spawn(
+        sink,
+        NETWORK_BATCH_SIZE,
+        queue_depth,
+    )?))
+}
 
-    let mut hasher = DefaultHasher::new();
-    account_id.hash(&mut hasher);
-    region.hash(&mut hasher);
-    (hasher.finish() as usize) % shards
+/// Network outputs need their feature, which brings in tokio, so without it
+/// their constructors fail before this is reached.
+#[cfg(not(feature = "tokio"))]
+fn network_writer<S>(_sink: S, _queue_depth: usize) -> io::Result<Box<dyn EventWriter>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "network outputs require building with a network output feature",
+    ))
+}
+
+/// Like [`network_writer`] for outputs that still upload synchronously; the
+/// writer runs on the sink runtime's blocking pool.
+#[cfg(feature = "tokio")]
+fn blocking_network_writer(
+    writer: impl EventWriter + Send + 'static,
+    queue_depth: usize,
+) -> io::Result<Box<dyn EventWriter>> {
+    network_writer(BlockingSink::new(writer), queue_depth)
+}
+
+#[cfg(not(feature = "tokio"))]
+fn blocking_network_writer(
+    writer: impl EventWriter + 'static,
+    _queue_depth: usize,
+) -> io::Result<Box<dyn EventWriter>> {
+    Ok(Box::new(writer))
 }
 
 struct Metrics {
@@ -1100,10 +2361,31 @@ struct Metrics {
     bytes: u64,
     overruns: Duration,
     missed_events: u64,
+    /// Events dropped by the writer backpressure policy so far.
+    dropped_events: u64,
+    /// Events written to the dead-letter directory so far.
+    dead_lettered_events: u64,
+    /// Latest time-scale lag and the lag forgiven so far.
+    pacing_lag: Duration,
+    forgiven_lag: Duration,
+    exporter: Option<Arc<PrometheusMetrics>>,
+    /// Bytes the run's file writers hold in memory, shared with their shards.
+    buffered: Arc<AtomicU64>,
+    /// Per-source counts for the run report.
+    tally: RunTally,
+    /// Completion of a bounded run when `--progress` is set.
+    progress: Option<Progress>,
+    /// Sink totals at the last dispatch.
+    written: SinkTotals,
 }
 
 impl Metrics {
-    fn new(interval: Duration, start_sim_time: DateTime<Utc>) -> Self {
+    fn new(
+        interval: Duration,
+        start_sim_time: DateTime<Utc>,
+        exporter: Option<Arc<PrometheusMetrics>>,
+        buffered: Arc<AtomicU64>,
+    ) -> Self {
         let now = Instant::now();
         Self {
             interval,
@@ -1115,6 +2397,31 @@ impl Metrics {
             bytes: 0,
             overruns: Duration::ZERO,
             missed_events: 0,
+            dropped_events: 0,
+            dead_lettered_events: 0,
+            pacing_lag: Duration::ZERO,
+            forgiven_lag: Duration::ZERO,
+            exporter,
+            buffered,
+            tally: RunTally::default(),
+            progress: PROGRESS.get().copied().map(Progress::new),
+            written: SinkTotals::default(),
+        }
+    }
+
+    fn record_dropped(&mut self, total: u64) {
+        let delta = total.saturating_sub(self.dropped_events);
+        self.dropped_events = total.max(self.dropped_events);
+        if let Some(exporter) = &self.exporter {
+            exporter.add_dropped(delta);
+        }
+    }
+
+    fn record_dead_lettered(&mut self, total: u64) {
+        let delta = total.saturating_sub(self.dead_lettered_events);
+        self.dead_lettered_events = total.max(self.dead_lettered_events);
+        if let Some(exporter) = &self.exporter {
+            exporter.add_dead_lettered(delta);
         }
     }
 
@@ -1130,6 +2437,10 @@ impl Metrics {
         self.bytes += bytes;
         self.overruns += overrun;
         self.missed_events += missed;
+        if let Some(exporter) = &self.exporter {
+            exporter.add_written(events, bytes);
+            exporter.add_missed(missed);
+        }
         if let Some(event_time) = event_time {
             if event_time > self.sim_high_water {
                 self.sim_high_water = event_time;
@@ -1146,21 +2457,32 @@ impl Metrics {
             } else {
                 0.0
             };
+            if let Some(exporter) = &self.exporter {
+                exporter.set_rates(events_per_sec, bytes_per_sec);
+            }
             let sim_elapsed_ms = (self.sim_high_water - self.start_sim_time)
                 .num_milliseconds()
                 .max(0);
 
-            println!(
-                "metrics events/s={:.1} bytes/s={:.1} avg_event={}B sim_high_water={} sim_elapsed={}s wall_elapsed={:.1}s overruns={}ms missed={}",
-                events_per_sec,
-                bytes_per_sec,
-                avg_event.round() as u64,
-                self.sim_high_water.to_rfc3339_opts(SecondsFormat::Millis, true),
-                sim_elapsed_ms / 1000,
-                self.started_at.elapsed().as_secs_f64(),
-                self.overruns.as_millis(),
-                self.missed_events
-            );
+            if let Some(progress) = &self.progress {
+                info!("{}", progress.line());
+            } else {
+                info!(
+                    events_per_sec = round_tenths(events_per_sec),
+                    bytes_per_sec = round_tenths(bytes_per_sec),
+                    avg_event_bytes = avg_event.round() as u64,
+                    buffered_bytes = self.buffered.load(Ordering::Relaxed),
+                    sim_high_water = %self.sim_high_water.to_rfc3339_opts(SecondsFormat::Millis, true),
+                    sim_elapsed_secs = sim_elapsed_ms / 1000,
+                    wall_elapsed_secs = round_tenths(self.started_at.elapsed().as_secs_f64()),
+                    overruns_ms = self.overruns.as_millis() as u64,
+                    missed = self.missed_events,
+                    dropped = self.dropped_events,
+                    dead_lettered = self.dead_lettered_events,
+                    lag_ms = self.pacing_lag.as_millis() as u64,
+                    "metrics"
+                );
+            }
 
             self.last_report = Instant::now();
             self.events = 0;
@@ -1171,6 +2493,51 @@ impl Metrics {
     }
 }
 
+impl RunObserver for Metrics {
+    fn resumed(&mut self, events: u64) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.resume_from(events);
+        }
+    }
+
+    fn event(&mut self, event: &Event, event_time: Option<DateTime<Utc>>) {
+        self.tally.record(event, event_time);
+        if let Some(progress) = self.progress.as_mut() {
+            progress.record(&event.envelope.source, event_time);
+        }
+        if let Some(exporter) = &self.exporter {
+            exporter.add_source_event(&event.envelope.source);
+        }
+    }
+
+    fn paced(&mut self, pacer: &SimClockPacer) {
+        let forgiven = pacer.forgiven().saturating_sub(self.forgiven_lag);
+        self.pacing_lag = pacer.lag();
+        self.forgiven_lag = pacer.forgiven();
+        if let Some(exporter) = &self.exporter {
+            exporter.set_pacing_lag(self.pacing_lag);
+            exporter.add_forgiven_lag(forgiven);
+        }
+    }
+
+    fn dispatched(&mut self, totals: &SinkTotals, event_time: Option<DateTime<Utc>>) {
+        let events = totals.events.saturating_sub(self.written.events);
+        let bytes = totals.bytes.saturating_sub(self.written.bytes);
+        self.written = *totals;
+        if let Some(exporter) = &self.exporter {
+            exporter.set_queue_depth(totals.queued);
+            exporter.set_buffered_bytes(self.buffered.load(Ordering::Relaxed));
+        }
+        self.record_dropped(totals.dropped);
+        self.record_dead_lettered(totals.dead_lettered);
+        self.record(events, bytes, Duration::ZERO, 0, event_time);
+    }
+}
+
+fn round_tenths(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1215,6 +2582,10 @@ mod tests {
                 SourceConfig::CloudTrail(cloudtrail(None)),
             ],
             outputs: None,
+            link_sessions: false,
+            session_correlation: None,
+            tenants: Vec::new(),
+            limits: None,
         });
 
         assert_eq!(
@@ -1234,6 +2605,10 @@ mod tests {
                 SourceConfig::CloudTrail(cloudtrail(Some("./registry-b.toml"))),
             ],
             outputs: None,
+            link_sessions: false,
+            session_correlation: None,
+            tenants: Vec::new(),
+            limits: None,
         });
 
         let err = identity_registry_path(&config).unwrap_err().to_string();
@@ -1250,6 +2625,10 @@ mod tests {
             population_config: None,
             sources: vec![SourceConfig::CloudTrail(cloudtrail(None))],
             outputs: None,
+            link_sessions: false,
+            session_correlation: None,
+            tenants: Vec::new(),
+            limits: None,
         });
 
         assert_eq!(
@@ -1266,6 +2645,10 @@ mod tests {
             population_config: None,
             sources: vec![SourceConfig::CloudTrail(cloudtrail(None))],
             outputs: None,
+            link_sessions: false,
+            session_correlation: None,
+            tenants: Vec::new(),
+            limits: None,
         });
 
         let err = identity_registry_for_actor_population(&config)
@@ -1284,6 +2667,10 @@ mod tests {
                 "./examples/identity_registry.toml",
             )))],
             outputs: None,
+            link_sessions: false,
+            session_correlation: None,
+            tenants: Vec::new(),
+            limits: None,
         });
 
         let err = match build_event_source(&config, Some(1), Utc::now()) {
@@ -1298,9 +2685,33 @@ mod tests {
             curated: true,
             actor_population_path: None,
             identity_registry_path: identity_registry_path.map(str::to_string),
+            actor_selector: None,
+            arrival: None,
             baseline_source_ips: None,
             regions: None,
             region_distribution: None,
+            catalog_path: None,
+            service_call_graph: false,
+            web_identity: None,
+            entra_federation: None,
+            narratives: None,
+            s3_access_logs: None,
+            resolver_query_logs: None,
+            alb_access_logs: None,
+            data_events: None,
+            resource_pool: None,
+            credential_takeovers: Vec::new(),
+            actor_injections: Vec::new(),
+            error_profiles: None,
+            error_surges: Vec::new(),
+            api_storms: Vec::new(),
+            rate_limits: Vec::new(),
+            policy_remediations: Vec::new(),
+            impossible_travels: Vec::new(),
+            credential_rotation: None,
+            deterministic_ids: false,
+            linked_sessions: false,
+            session_correlation: 1.0,
         }
     }
 
@@ -1332,6 +2743,8 @@ mod tests {
             timezone_offset: None,
             weekend_active: None,
             service_pattern: None,
+            insider_persona: None,
+            on_call: None,
         }
     }
 }
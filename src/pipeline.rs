@@ -0,0 +1,1558 @@
+//! Embeddable generation pipeline.
+//!
+//! `Pipeline` drives event sources into an [`EventSink`] with the merge order,
+//! sim-time throttling, sharding, operator controls, checkpoints, and stop
+//! conditions of `seclog gen`; the CLI runs every output through it, and other
+//! programs and tests can run generation the same way.
+
+use crate::control::Control;
+use crate::core::checkpoint::{Checkpoint, CheckpointError, StreamFingerprint};
+use crate::core::config::BackpressurePolicy;
+use crate::core::event::Event;
+use crate::core::traffic::{LoadPacer, LoadSchedule};
+use crate::core::traits::{EventSource, EventTransform, EventWriter};
+use crate::formats::disk_usage::{BudgetDecision, ByteBudget};
+use crate::formats::json::file_key;
+use crate::formats::labels::LabelWriter;
+use crate::reload::{ConfigWatcher, TrafficReload};
+use crate::sources::composite::CompositeEventSource;
+use crate::sources::transform::TransformedEventSource;
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, info_span, warn};
+
+/// Error raised while building or running a pipeline.
+#[derive(Debug)]
+pub enum PipelineError {
+    NoSources,
+    NoWriters,
+    /// No sink is routed for this envelope source.
+    NoRoute(String),
+    Writer(io::Error),
+    /// A writer thread exited before taking the next command.
+    WriterStopped,
+    WriterPanicked,
+    Checkpoint(CheckpointError),
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PipelineError::NoSources => write!(f, "pipeline requires at least one source"),
+            PipelineError::NoWriters => write!(f, "pipeline requires at least one writer"),
+            PipelineError::NoRoute(source) => {
+                write!(f, "no output route configured for source {source}")
+            }
+            PipelineError::Writer(err) => write!(f, "writer failed: {err}"),
+            PipelineError::WriterStopped => write!(f, "writer thread stopped"),
+            PipelineError::WriterPanicked => write!(f, "writer thread panicked"),
+            PipelineError::Checkpoint(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+impl From<io::Error> for PipelineError {
+    fn from(err: io::Error) -> Self {
+        PipelineError::Writer(err)
+    }
+}
+
+impl From<CheckpointError> for PipelineError {
+    fn from(err: CheckpointError) -> Self {
+        PipelineError::Checkpoint(err)
+    }
+}
+
+/// Conditions that end a run; unset limits are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct RunLimits {
+    /// Stop after dispatching this many events, counting the ones a
+    /// checkpoint resume skipped.
+    pub max_events: Option<u64>,
+    /// Stop after this much wall-clock time.
+    pub max_duration: Option<Duration>,
+    /// Stop at the first event after this simulated time.
+    pub until_time: Option<DateTime<Utc>>,
+}
+
+/// Why a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    SourcesExhausted,
+    MaxEvents,
+    MaxDuration,
+    UntilTime,
+    /// The [`ByteBudget`] was spent.
+    MaxBytes,
+    /// Every load phase has elapsed.
+    PhasesComplete,
+}
+
+/// Totals reported when a run finishes.
+#[derive(Debug, Clone)]
+pub struct RunStats {
+    /// Events handed to the sink.
+    pub events: u64,
+    /// Events a checkpoint resume skipped before the first dispatch.
+    pub resumed: u64,
+    /// Bytes reported by writers.
+    pub bytes: u64,
+    /// Scenario events written to the label sidecar.
+    pub labeled: u64,
+    /// Event counts keyed by `envelope.source`.
+    pub events_by_source: BTreeMap<String, u64>,
+    /// Simulated time of the first and last dispatched events.
+    pub first_event_time: Option<DateTime<Utc>>,
+    pub last_event_time: Option<DateTime<Utc>>,
+    /// Largest time-scale lag recovered by catching up, and the total lag forgiven.
+    pub max_pacing_lag: Duration,
+    pub forgiven_pacing_lag: Duration,
+    /// Wall-clock run time, including the final writer close.
+    pub elapsed: Duration,
+    pub stop_reason: StopReason,
+}
+
+/// Follows a run as it happens, for example to report metrics.
+///
+/// Every method does nothing by default.
+pub trait RunObserver {
+    /// Called before the first event with the number of events a checkpoint
+    /// resume skipped.
+    fn resumed(&mut self, _events: u64) {}
+    /// Called for each event about to be dispatched.
+    fn event(&mut self, _event: &Event, _event_time: Option<DateTime<Utc>>) {}
+    /// Called after the time-scale pacer handled an event.
+    fn paced(&mut self, _pacer: &SimClockPacer) {}
+    /// Called after each dispatch with the sink's running totals.
+    fn dispatched(&mut self, _totals: &SinkTotals, _event_time: Option<DateTime<Utc>>) {}
+}
+
+impl RunObserver for () {}
+
+/// Builder and runner for an in-process generation pipeline.
+///
+/// Sources are merged by event time. By default each writer runs on its own
+/// thread and acts as a shard: events for the same account and region always
+/// reach the same writer, matching `--writer-shards` in the CLI. [`sink`]
+/// replaces the writers with any [`EventSink`].
+///
+/// [`sink`]: Pipeline::sink
+pub struct Pipeline {
+    sources: Vec<Box<dyn EventSource>>,
+    writers: Vec<Box<dyn EventWriter + Send>>,
+    sink: Option<Box<dyn EventSink>>,
+    transforms: Vec<Box<dyn EventTransform>>,
+    time_scale: Option<f64>,
+    max_catch_up: Duration,
+    load_schedule: Option<LoadSchedule>,
+    flush_interval: Option<Duration>,
+    queue_depth: usize,
+    labels: Option<LabelWriter>,
+    control: Option<Arc<Control>>,
+    watcher: Option<ConfigWatcher>,
+    byte_budget: Option<ByteBudget>,
+    checkpointer: Option<Checkpointer>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline that runs as fast as possible and flushes every second.
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            writers: Vec::new(),
+            sink: None,
+            transforms: Vec::new(),
+            time_scale: None,
+            max_catch_up: DEFAULT_MAX_CATCH_UP,
+            load_schedule: None,
+            flush_interval: Some(Duration::from_secs(1)),
+            queue_depth: 1024,
+            labels: None,
+            control: None,
+            watcher: None,
+            byte_budget: None,
+            checkpointer: None,
+        }
+    }
+
+    /// Adds an event source; multiple sources are merged by event time.
+    pub fn add_source(mut self, source: Box<dyn EventSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Adds a writer shard.
+    pub fn add_writer(mut self, writer: Box<dyn EventWriter + Send>) -> Self {
+        self.writers.push(writer);
+        self
+    }
+
+    /// Sends events to `sink` instead of the writers added with [`add_writer`].
+    ///
+    /// [`add_writer`]: Pipeline::add_writer
+    pub fn sink(mut self, sink: Box<dyn EventSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Adds a transform applied to every merged event before it is written.
+    ///
+    /// Transforms run in the order they are added; closures taking
+    /// `&mut Event` work too.
+    pub fn transform(mut self, transform: impl EventTransform + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Paces output so simulated time advances `scale` times faster than the wall clock.
+    pub fn time_scale(mut self, scale: f64) -> Self {
+        self.time_scale = (scale > 0.0).then_some(scale);
+        self
+    }
+
+    /// Caps the lag the time-scale pacer recovers by running unthrottled; see [`SimClockPacer`].
+    pub fn max_catch_up(mut self, max_catch_up: Duration) -> Self {
+        self.max_catch_up = max_catch_up;
+        self
+    }
+
+    /// Paces output by wall-clock load phases; takes precedence over [`time_scale`].
+    ///
+    /// [`time_scale`]: Pipeline::time_scale
+    pub fn load_schedule(mut self, schedule: LoadSchedule) -> Self {
+        self.load_schedule = Some(schedule);
+        self
+    }
+
+    /// Sets how often writers flush; `None` flushes only on close.
+    pub fn flush_interval(mut self, interval: Option<Duration>) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Sets the per-writer queue depth.
+    pub fn queue_depth(mut self, depth: usize) -> Self {
+        self.queue_depth = depth.max(1);
+        self
+    }
+
+    /// Writes ground-truth labels for scenario events to a sidecar.
+    pub fn labels(mut self, labels: LabelWriter) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    /// Follows pause, rate, and flush requests from `control` between events.
+    pub fn control(mut self, control: Arc<Control>) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// Picks up traffic changes from a watched config file between events.
+    pub fn watch_config(mut self, watcher: ConfigWatcher) -> Self {
+        self.watcher = Some(watcher);
+        self
+    }
+
+    /// Stops, or skips sources, once `budget` says their output is large enough.
+    pub fn byte_budget(mut self, budget: ByteBudget) -> Self {
+        self.byte_budget = Some(budget);
+        self
+    }
+
+    /// Resumes from and periodically saves a checkpoint.
+    pub fn checkpoint(mut self, checkpointer: Checkpointer) -> Self {
+        self.checkpointer = Some(checkpointer);
+        self
+    }
+
+    /// Runs until every source is exhausted.
+    pub fn run(self) -> Result<RunStats, PipelineError> {
+        self.run_until(RunLimits::default())
+    }
+
+    /// Runs until a limit is reached or the sources are exhausted, then closes the writers.
+    pub fn run_until(self, limits: RunLimits) -> Result<RunStats, PipelineError> {
+        self.run_observed(limits, &mut ())
+    }
+
+    /// Same as [`run_until`], reporting progress to `observer` along the way.
+    ///
+    /// [`run_until`]: Pipeline::run_until
+    pub fn run_observed(
+        self,
+        limits: RunLimits,
+        observer: &mut dyn RunObserver,
+    ) -> Result<RunStats, PipelineError> {
+        if self.sources.is_empty() {
+            return Err(PipelineError::NoSources);
+        }
+        let mut sink: Box<dyn EventSink> = match self.sink {
+            Some(sink) => sink,
+            None if self.writers.is_empty() => return Err(PipelineError::NoWriters),
+            None => {
+                let mut shards = WriterShards::new(self.queue_depth);
+                for writer in self.writers {
+                    shards = shards.spawn("pipeline", move || Ok(writer as Box<dyn EventWriter>));
+                }
+                Box::new(shards)
+            }
+        };
+
+        let started = Instant::now();
+        let mut source: Box<dyn EventSource> = if self.sources.len() == 1 {
+            self.sources.into_iter().next().expect("one source")
+        } else {
+            Box::new(CompositeEventSource::new(self.sources))
+        };
+        if !self.transforms.is_empty() {
+            source = Box::new(TransformedEventSource::new(source, self.transforms));
+        }
+
+        let load_pacer = self.load_schedule.map(LoadPacer::new);
+        let sim_pacer = match (load_pacer.is_some(), self.time_scale) {
+            (false, Some(scale)) => Some(SimClockPacer::new(scale, self.max_catch_up)),
+            _ => None,
+        };
+        let mut run = Run {
+            started,
+            sim_pacer,
+            load_pacer,
+            next_flush: self.flush_interval.map(|interval| started + interval),
+            flush_interval: self.flush_interval,
+            labels: self.labels,
+            control: self.control,
+            watcher: self.watcher,
+            byte_budget: self.byte_budget,
+            checkpointer: self.checkpointer,
+            stats: RunStats {
+                events: 0,
+                resumed: 0,
+                bytes: 0,
+                labeled: 0,
+                events_by_source: BTreeMap::new(),
+                first_event_time: None,
+                last_event_time: None,
+                max_pacing_lag: Duration::ZERO,
+                forgiven_pacing_lag: Duration::ZERO,
+                elapsed: Duration::ZERO,
+                stop_reason: StopReason::SourcesExhausted,
+            },
+        };
+
+        let outcome = run.drive(source.as_mut(), sink.as_mut(), &limits, observer);
+        let closed = sink.close();
+        let totals = match (outcome, closed) {
+            (Ok(()), Ok(totals)) => totals,
+            // A writer that stopped early says why when it is joined.
+            (Ok(()) | Err(PipelineError::WriterStopped), Err(err)) => return Err(err),
+            (Err(err), _) => return Err(err),
+        };
+        run.finish(totals)
+    }
+}
+
+/// State of one pipeline run.
+struct Run {
+    started: Instant,
+    sim_pacer: Option<SimClockPacer>,
+    load_pacer: Option<LoadPacer>,
+    flush_interval: Option<Duration>,
+    next_flush: Option<Instant>,
+    labels: Option<LabelWriter>,
+    control: Option<Arc<Control>>,
+    watcher: Option<ConfigWatcher>,
+    byte_budget: Option<ByteBudget>,
+    checkpointer: Option<Checkpointer>,
+    stats: RunStats,
+}
+
+impl Run {
+    fn drive(
+        &mut self,
+        source: &mut dyn EventSource,
+        sink: &mut dyn EventSink,
+        limits: &RunLimits,
+        observer: &mut dyn RunObserver,
+    ) -> Result<(), PipelineError> {
+        if let Some(checkpointer) = self.checkpointer.as_mut() {
+            self.stats.resumed = checkpointer.fast_forward(source, self.labels.as_mut())?;
+        }
+        observer.resumed(self.stats.resumed);
+
+        loop {
+            let loop_start = Instant::now();
+            if let Some(limit) = limits.max_duration {
+                if loop_start.duration_since(self.started) >= limit {
+                    self.stats.stop_reason = StopReason::MaxDuration;
+                    return Ok(());
+                }
+            }
+            if let Some(max) = limits.max_events {
+                if self.stats.resumed + self.stats.events >= max {
+                    self.stats.stop_reason = StopReason::MaxEvents;
+                    return Ok(());
+                }
+            }
+            if self
+                .control
+                .as_ref()
+                .is_some_and(|control| control.take_flush())
+            {
+                sink.sync()?;
+            }
+            self.follow_operator();
+            if let Some(pacer) = self.load_pacer.as_mut() {
+                if !pacer.wait_for_slot(self.stats.events) {
+                    self.stats.stop_reason = StopReason::PhasesComplete;
+                    return Ok(());
+                }
+                report_load_phase(pacer);
+            }
+
+            let Some(event) = source.next_event() else {
+                return Ok(());
+            };
+            let event_time = parse_event_time(&event);
+            if should_stop_at_until(event_time, limits.until_time) {
+                self.stats.stop_reason = StopReason::UntilTime;
+                return Ok(());
+            }
+            if let Some(budget) = self.byte_budget.as_mut() {
+                match budget.admit(&event.envelope.source, || sink.sync())? {
+                    BudgetDecision::Write => {}
+                    BudgetDecision::Skip => continue,
+                    BudgetDecision::Stop => {
+                        info!(bytes = budget.total_bytes(), "max bytes reached");
+                        self.stats.stop_reason = StopReason::MaxBytes;
+                        return Ok(());
+                    }
+                }
+            }
+            if let Some(event_time) = event_time {
+                if let Some(pacer) = self.sim_pacer.as_mut() {
+                    pacer.pace(event_time);
+                    observer.paced(pacer);
+                }
+                self.stats.first_event_time.get_or_insert(event_time);
+                self.stats.last_event_time = Some(event_time);
+            }
+
+            observer.event(&event, event_time);
+            if let Some(labels) = self.labels.as_mut() {
+                labels.write_event(&event)?;
+            }
+            if let Some(checkpointer) = self.checkpointer.as_mut() {
+                checkpointer.observe(&event);
+            }
+            *self
+                .stats
+                .events_by_source
+                .entry(event.envelope.source.clone())
+                .or_insert(0) += 1;
+            sink.dispatch(event)?;
+            self.stats.events += 1;
+            if let Some(checkpointer) = self.checkpointer.as_mut() {
+                if checkpointer.is_due(loop_start) {
+                    sink.sync()?;
+                    checkpointer.commit(self.stats.resumed + self.stats.events)?;
+                }
+            }
+            observer.dispatched(&sink.totals(), event_time);
+
+            if let (Some(interval), Some(next)) = (self.flush_interval, self.next_flush) {
+                if loop_start >= next {
+                    sink.flush()?;
+                    self.next_flush = Some(loop_start + interval);
+                }
+            }
+        }
+    }
+
+    /// Closes the labels, saves the final checkpoint, and totals up the run.
+    fn finish(mut self, totals: SinkTotals) -> Result<RunStats, PipelineError> {
+        self.stats.bytes = totals.bytes;
+        if let Some(labels) = self.labels.as_mut() {
+            labels.close()?;
+            self.stats.labeled = labels.labeled();
+        }
+        if let Some(checkpointer) = self.checkpointer.as_mut() {
+            checkpointer.commit(self.stats.resumed + self.stats.events)?;
+        }
+        if let Some(pacer) = &self.sim_pacer {
+            self.stats.max_pacing_lag = pacer.max_lag();
+            self.stats.forgiven_pacing_lag = pacer.forgiven();
+        }
+        self.stats.elapsed = self.started.elapsed();
+        Ok(self.stats)
+    }
+
+    /// Applies config reloads and control requests between events: blocks
+    /// while paused, then hands the pacers the time spent paused, the current
+    /// traffic settings, and the rate multiplier.
+    fn follow_operator(&mut self) {
+        if let Some(watcher) = self.watcher.as_mut() {
+            match watcher.poll() {
+                Ok(Some(reload)) => {
+                    apply_traffic_reload(reload, &mut self.sim_pacer, &mut self.load_pacer)
+                }
+                Ok(None) => {}
+                Err(err) => warn!("config reload failed; keeping current traffic settings: {err}"),
+            }
+        }
+        let Some(control) = self.control.as_ref() else {
+            return;
+        };
+        if control.is_paused() {
+            info!("generation paused");
+            let paused = control.wait_while_paused();
+            info!(paused_secs = paused.as_secs(), "generation resumed");
+            if let Some(pacer) = self.sim_pacer.as_mut() {
+                pacer.resume_after(paused);
+            }
+            if let Some(pacer) = self.load_pacer.as_mut() {
+                pacer.resume_after(paused);
+            }
+        }
+        let multiplier = control.rate_multiplier();
+        if let Some(pacer) = self.sim_pacer.as_mut() {
+            pacer.set_rate_multiplier(multiplier);
+        }
+        if let Some(pacer) = self.load_pacer.as_mut() {
+            pacer.set_rate_multiplier(multiplier);
+        }
+    }
+}
+
+/// Swaps in reloaded traffic settings. `traffic.phases` take precedence over
+/// `traffic.time_scale`, as at startup; a run that gains phases starts them
+/// from the first one, and a run that keeps them stays at its elapsed time.
+fn apply_traffic_reload(
+    reload: TrafficReload,
+    sim_pacer: &mut Option<SimClockPacer>,
+    load_pacer: &mut Option<LoadPacer>,
+) {
+    if reload.ignored_changes {
+        warn!(
+            "config changes outside traffic.time_scale, max_catch_up, phases, calendar, and source scenarios need a restart; ignoring them"
+        );
+    }
+    if !reload.rescheduled.is_empty() {
+        info!(scenarios = %reload.rescheduled.join(","), "re-planned scenarios");
+    }
+    if reload.changed.is_empty() {
+        return;
+    }
+    info!(changed = %reload.changed.join(","), "reloaded traffic config");
+    match (reload.phases, load_pacer.as_mut()) {
+        (Some(schedule), Some(pacer)) => pacer.set_schedule(schedule),
+        (Some(schedule), None) => {
+            *load_pacer = Some(LoadPacer::new(schedule));
+            *sim_pacer = None;
+        }
+        (None, _) => {
+            *load_pacer = None;
+            match (reload.time_scale, sim_pacer.as_mut()) {
+                (Some(scale), Some(pacer)) => {
+                    pacer.set_scale(scale);
+                    pacer.set_max_catch_up(reload.max_catch_up);
+                }
+                (Some(scale), None) => {
+                    *sim_pacer = Some(SimClockPacer::new(scale, reload.max_catch_up));
+                }
+                (None, _) => *sim_pacer = None,
+            }
+        }
+    }
+}
+
+fn report_load_phase(pacer: &mut LoadPacer) {
+    let Some(idx) = pacer.take_phase_change() else {
+        return;
+    };
+    let phases = pacer.schedule().phases();
+    let phase = &phases[idx];
+    let round_tenths = |value: f64| (value * 10.0).round() / 10.0;
+    info!(
+        phase = idx + 1,
+        phases = phases.len(),
+        start_eps = round_tenths(phase.start_eps),
+        end_eps = round_tenths(phase.end_eps),
+        duration_secs = phase.duration.as_secs(),
+        "load phase"
+    );
+}
+
+/// Tracks committed progress and periodically persists it for `gen --resume`.
+pub struct Checkpointer {
+    path: PathBuf,
+    interval: Duration,
+    next_at: Instant,
+    state: Checkpoint,
+    output_dirs: Vec<PathBuf>,
+    stream: StreamFingerprint,
+}
+
+impl Checkpointer {
+    pub fn new(
+        path: PathBuf,
+        interval: Duration,
+        state: Checkpoint,
+        output_dirs: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            path,
+            interval,
+            next_at: Instant::now() + interval,
+            state,
+            output_dirs,
+            stream: StreamFingerprint::default(),
+        }
+    }
+
+    /// Regenerates and discards the events already committed; returns how many were skipped.
+    ///
+    /// Skipped events are still passed to `labels`, so the sidecar is rebuilt in full.
+    fn fast_forward(
+        &mut self,
+        generator: &mut dyn EventSource,
+        mut labels: Option<&mut LabelWriter>,
+    ) -> Result<u64, PipelineError> {
+        let target = self.state.events_committed;
+        let mut stream = StreamFingerprint::default();
+        for _ in 0..target {
+            match generator.next_event() {
+                Some(event) => {
+                    if let Some(labels) = labels.as_deref_mut() {
+                        labels.write_event(&event)?;
+                    }
+                    stream.observe(&event);
+                }
+                None => break,
+            }
+        }
+        self.state.ensure_resume_point(&stream)?;
+        self.stream = stream;
+        Ok(target)
+    }
+
+    fn observe(&mut self, event: &Event) {
+        self.stream.observe(event);
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        now >= self.next_at
+    }
+
+    /// Saves a checkpoint; callers sync the sink first so every dispatched event is on disk.
+    fn commit(&mut self, events_committed: u64) -> Result<(), PipelineError> {
+        self.state
+            .commit(events_committed, &self.stream, &self.output_dirs)?;
+        self.state.save(&self.path)?;
+        self.next_at = Instant::now() + self.interval;
+        Ok(())
+    }
+}
+
+/// Where a pipeline delivers events.
+///
+/// [`WriterShards`] runs writers on their own threads, [`InlineSink`] runs one
+/// on the pipeline thread, and [`RoutedSink`] picks a sink per envelope source.
+pub trait EventSink {
+    /// Hands one event to the sink.
+    fn dispatch(&mut self, event: Event) -> Result<(), PipelineError>;
+    /// Asks writers to flush; threaded sinks do not wait for them.
+    fn flush(&mut self) -> Result<(), PipelineError>;
+    /// Returns once every dispatched event is written out; writers stay open.
+    fn sync(&mut self) -> Result<(), PipelineError>;
+    /// Running totals.
+    fn totals(&self) -> SinkTotals;
+    /// Closes every writer and returns the final totals.
+    fn close(self: Box<Self>) -> Result<SinkTotals, PipelineError>;
+}
+
+/// Running totals of a sink, shared with its writer threads.
+#[derive(Debug, Clone, Default)]
+pub struct SinkCounters {
+    pub events: Arc<AtomicU64>,
+    pub bytes: Arc<AtomicU64>,
+    /// Events dispatched that no writer has taken yet.
+    pub queued: Arc<AtomicU64>,
+    /// Events discarded by a drop backpressure policy or skipped after failed writes.
+    pub dropped: Arc<AtomicU64>,
+    /// Events written to the dead-letter directory after failed writes.
+    pub dead_lettered: Arc<AtomicU64>,
+}
+
+impl SinkCounters {
+    pub fn totals(&self) -> SinkTotals {
+        SinkTotals {
+            events: self.events.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            dead_lettered: self.dead_lettered.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A snapshot of [`SinkCounters`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SinkTotals {
+    pub events: u64,
+    pub bytes: u64,
+    pub queued: u64,
+    pub dropped: u64,
+    pub dead_lettered: u64,
+}
+
+impl std::ops::AddAssign for SinkTotals {
+    fn add_assign(&mut self, other: Self) {
+        self.events += other.events;
+        self.bytes += other.bytes;
+        self.queued += other.queued;
+        self.dropped += other.dropped;
+        self.dead_lettered += other.dead_lettered;
+    }
+}
+
+/// Called with the latency of every writer flush.
+pub type FlushObserver = Arc<dyn Fn(Duration) + Send + Sync>;
+
+/// Writer threads fed by bounded queues, one thread per shard.
+///
+/// Events for the same source, account, and region always reach the same
+/// shard, so each file stream is written by exactly one thread. Set the
+/// options before spawning shards.
+pub struct WriterShards {
+    queues: Vec<Arc<ShardQueue>>,
+    handles: Vec<thread::JoinHandle<io::Result<()>>>,
+    queue_depth: usize,
+    policy: BackpressurePolicy,
+    counters: SinkCounters,
+    on_flush: Option<FlushObserver>,
+}
+
+impl WriterShards {
+    /// Creates a sink with no shards whose queues hold `queue_depth` events each.
+    pub fn new(queue_depth: usize) -> Self {
+        Self {
+            queues: Vec::new(),
+            handles: Vec::new(),
+            queue_depth: queue_depth.max(1),
+            policy: BackpressurePolicy::Block,
+            counters: SinkCounters::default(),
+            on_flush: None,
+        }
+    }
+
+    /// Sets what happens to events when a shard's queue is full.
+    pub fn backpressure(mut self, policy: BackpressurePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Counts into `counters`, for writers that report into them too.
+    pub fn counters(mut self, counters: SinkCounters) -> Self {
+        self.counters = counters;
+        self
+    }
+
+    /// Reports every flush latency to `observe`.
+    pub fn on_flush(mut self, observe: FlushObserver) -> Self {
+        self.on_flush = Some(observe);
+        self
+    }
+
+    /// Starts one more shard. `open` builds its writer on the shard thread, so
+    /// writers need not be `Send`; `source` names the shard in logs.
+    pub fn spawn(
+        mut self,
+        source: &str,
+        open: impl FnOnce() -> io::Result<Box<dyn EventWriter>> + Send + 'static,
+    ) -> Self {
+        let shard = self.queues.len();
+        let queue = Arc::new(ShardQueue::new(self.queue_depth));
+        let shard_queue = Arc::clone(&queue);
+        let counters = self.counters.clone();
+        let on_flush = self.on_flush.clone();
+        let span = info_span!("writer_shard", source, shard);
+        let handle = thread::spawn(move || -> io::Result<()> {
+            let _span = span.enter();
+            // Wakes a blocked producer with an error if this thread stops early.
+            let _stopped = StopOnDrop(&shard_queue);
+            let mut writer = open()?;
+            while let Some(command) = shard_queue.pop() {
+                match command {
+                    WriterCommand::Event(event) => {
+                        counters.queued.fetch_sub(1, Ordering::Relaxed);
+                        let bytes = writer.write_event(&event)?;
+                        counters.events.fetch_add(1, Ordering::Relaxed);
+                        counters.bytes.fetch_add(bytes, Ordering::Relaxed);
+                    }
+                    WriterCommand::Flush => {
+                        let flush_started = Instant::now();
+                        writer.flush()?;
+                        let latency = flush_started.elapsed();
+                        debug!(latency_ms = latency.as_millis() as u64, "writer flushed");
+                        if let Some(observe) = &on_flush {
+                            observe(latency);
+                        }
+                    }
+                    WriterCommand::Sync(ack) => {
+                        writer.sync()?;
+                        let _ = ack.send(());
+                    }
+                }
+            }
+            writer.close()?;
+            debug!("writer closed");
+            Ok(())
+        });
+        self.queues.push(queue);
+        self.handles.push(handle);
+        self
+    }
+
+    /// Number of shards spawned.
+    pub fn len(&self) -> usize {
+        self.queues.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+}
+
+impl EventSink for WriterShards {
+    fn dispatch(&mut self, event: Event) -> Result<(), PipelineError> {
+        if self.queues.is_empty() {
+            return Ok(());
+        }
+        let idx = writer_index_for_event(&event, self.queues.len());
+        let queue = &self.queues[idx];
+        let mut state = queue.lock();
+        while state.events >= queue.capacity && !state.stopped {
+            match self.policy {
+                BackpressurePolicy::Block => {
+                    state = queue.space.wait(state).expect("shard queue lock");
+                }
+                BackpressurePolicy::DropNewest => {
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                }
+                BackpressurePolicy::DropOldest => {
+                    // Evicted here rather than by the writer, so the pipeline
+                    // never waits on a slow write.
+                    if let Some(oldest) = state.commands.iter().position(WriterCommand::is_event) {
+                        state.commands.remove(oldest);
+                        state.events -= 1;
+                        self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+                        self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+        if state.stopped {
+            return Err(PipelineError::WriterStopped);
+        }
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        state.events += 1;
+        state.commands.push_back(WriterCommand::Event(event));
+        queue.ready.notify_one();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), PipelineError> {
+        // A shard that stopped reports its error on the next dispatch or close.
+        for queue in &self.queues {
+            let _ = queue.push(WriterCommand::Flush);
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<(), PipelineError> {
+        let mut acks = Vec::with_capacity(self.queues.len());
+        for queue in &self.queues {
+            let (ack_tx, ack_rx) = sync_channel(1);
+            queue.push(WriterCommand::Sync(ack_tx))?;
+            acks.push(ack_rx);
+        }
+        for ack in acks {
+            ack.recv().map_err(|_| PipelineError::WriterStopped)?;
+        }
+        Ok(())
+    }
+
+    fn totals(&self) -> SinkTotals {
+        self.counters.totals()
+    }
+
+    fn close(self: Box<Self>) -> Result<SinkTotals, PipelineError> {
+        let Self {
+            queues,
+            handles,
+            counters,
+            ..
+        } = *self;
+        // Closing the queues ends each shard once it has written what is queued.
+        for queue in &queues {
+            queue.lock().closed = true;
+            queue.ready.notify_one();
+        }
+        let mut result = Ok(());
+        for handle in handles {
+            let joined = match handle.join() {
+                Ok(Ok(())) => Ok(()),
+                Ok(Err(err)) => Err(PipelineError::Writer(err)),
+                Err(_) => Err(PipelineError::WriterPanicked),
+            };
+            if result.is_ok() {
+                result = joined;
+            }
+        }
+        result.map(|()| counters.totals())
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+enum WriterCommand {
+    Event(Event),
+    Flush,
+    /// Writes out all buffered data, then acknowledges; the writer stays open.
+    Sync(SyncSender<()>),
+}
+
+impl WriterCommand {
+    fn is_event(&self) -> bool {
+        matches!(self, WriterCommand::Event(_))
+    }
+}
+
+/// One shard's queue. Only events count against `capacity`; flush and sync
+/// commands always fit, so they cannot be dropped.
+struct ShardQueue {
+    state: Mutex<QueueState>,
+    /// Signalled when a command is queued or the queue is closed.
+    ready: Condvar,
+    /// Signalled when an event is taken or the shard stops.
+    space: Condvar,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct QueueState {
+    commands: VecDeque<WriterCommand>,
+    events: usize,
+    /// No more commands will be queued.
+    closed: bool,
+    /// The shard thread has exited.
+    stopped: bool,
+}
+
+impl ShardQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            ready: Condvar::new(),
+            space: Condvar::new(),
+            capacity,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().expect("shard queue lock")
+    }
+
+    fn push(&self, command: WriterCommand) -> Result<(), PipelineError> {
+        let mut state = self.lock();
+        if state.stopped {
+            return Err(PipelineError::WriterStopped);
+        }
+        state.commands.push_back(command);
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    /// Waits for the next command; `None` once closed and drained.
+    fn pop(&self) -> Option<WriterCommand> {
+        let mut state = self.lock();
+        loop {
+            if let Some(command) = state.commands.pop_front() {
+                if command.is_event() {
+                    state.events -= 1;
+                    self.space.notify_one();
+                }
+                return Some(command);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.ready.wait(state).expect("shard queue lock");
+        }
+    }
+}
+
+/// Marks the queue stopped when the shard thread exits, however it exits.
+struct StopOnDrop<'a>(&'a ShardQueue);
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap_or_else(|err| err.into_inner());
+        state.stopped = true;
+        // Pending sync acks are dropped, so their waiters see the stop too.
+        state.commands.clear();
+        self.0.space.notify_all();
+    }
+}
+
+/// Runs one writer on the pipeline thread, for outputs that are one ordered
+/// stream (stdout, Arrow IPC) or that batch and upload on their own.
+pub struct InlineSink {
+    writer: Box<dyn EventWriter>,
+    events: u64,
+    bytes: u64,
+    on_flush: Option<FlushObserver>,
+}
+
+impl InlineSink {
+    pub fn new(writer: Box<dyn EventWriter>) -> Self {
+        Self {
+            writer,
+            events: 0,
+            bytes: 0,
+            on_flush: None,
+        }
+    }
+
+    /// Reports every flush latency to `observe`.
+    pub fn on_flush(mut self, observe: FlushObserver) -> Self {
+        self.on_flush = Some(observe);
+        self
+    }
+}
+
+impl EventSink for InlineSink {
+    fn dispatch(&mut self, event: Event) -> Result<(), PipelineError> {
+        self.bytes += self.writer.write_event(&event)?;
+        self.events += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), PipelineError> {
+        let flush_started = Instant::now();
+        self.writer.flush()?;
+        if let Some(observe) = &self.on_flush {
+            observe(flush_started.elapsed());
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<(), PipelineError> {
+        self.writer.sync()?;
+        Ok(())
+    }
+
+    fn totals(&self) -> SinkTotals {
+        SinkTotals {
+            events: self.events,
+            bytes: self.bytes,
+            ..SinkTotals::default()
+        }
+    }
+
+    fn close(mut self: Box<Self>) -> Result<SinkTotals, PipelineError> {
+        self.writer.close()?;
+        Ok(self.totals())
+    }
+}
+
+/// Sends each event to the sink routed for its `envelope.source`.
+#[derive(Default)]
+pub struct RoutedSink {
+    routes: HashMap<String, Box<dyn EventSink>>,
+}
+
+impl RoutedSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Routes events from `source` to `sink`, replacing any earlier route.
+    pub fn route(mut self, source: impl Into<String>, sink: Box<dyn EventSink>) -> Self {
+        self.routes.insert(source.into(), sink);
+        self
+    }
+
+    pub fn contains(&self, source: &str) -> bool {
+        self.routes.contains_key(source)
+    }
+}
+
+impl EventSink for RoutedSink {
+    fn dispatch(&mut self, event: Event) -> Result<(), PipelineError> {
+        match self.routes.get_mut(&event.envelope.source) {
+            Some(sink) => sink.dispatch(event),
+            None => Err(PipelineError::NoRoute(event.envelope.source)),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), PipelineError> {
+        self.routes.values_mut().try_for_each(|sink| sink.flush())
+    }
+
+    fn sync(&mut self) -> Result<(), PipelineError> {
+        self.routes.values_mut().try_for_each(|sink| sink.sync())
+    }
+
+    fn totals(&self) -> SinkTotals {
+        let mut totals = SinkTotals::default();
+        for sink in self.routes.values() {
+            totals += sink.totals();
+        }
+        totals
+    }
+
+    fn close(self: Box<Self>) -> Result<SinkTotals, PipelineError> {
+        let mut totals = SinkTotals::default();
+        let mut result = Ok(());
+        for sink in self.routes.into_values() {
+            match sink.close() {
+                Ok(closed) => totals += closed,
+                Err(err) if result.is_ok() => result = Err(err),
+                Err(_) => {}
+            }
+        }
+        result.map(|()| totals)
+    }
+}
+
+/// Parses the envelope timestamp as UTC.
+pub fn parse_event_time(event: &Event) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&event.envelope.timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Returns true once an event is past the `until_time` cutoff.
+pub fn should_stop_at_until(
+    event_time: Option<DateTime<Utc>>,
+    until_time: Option<DateTime<Utc>>,
+) -> bool {
+    matches!((event_time, until_time), (Some(event_time), Some(until_time)) if event_time > until_time)
+}
+
+/// Default `traffic.max_catch_up`: lag beyond this is dropped instead of replayed as a burst.
+pub const DEFAULT_MAX_CATCH_UP: Duration = Duration::from_secs(60);
+
+/// Paces a loop so simulated time advances `scale` times faster than the wall clock.
+///
+/// Rather than measuring each gap from the previous event, which drifts by
+/// every oversleep, the pacer maps simulated time onto one wall-clock anchor
+/// taken at the first paced event. When the loop stalls, for example on a slow writer, it
+/// falls behind schedule and the pacer stops sleeping until the backlog is
+/// worked off, so the long-run rate still matches `scale`. Lag larger than
+/// `max_catch_up` is forgiven by moving the anchor to the late event, which
+/// bounds the catch-up burst.
+#[derive(Debug, Clone)]
+pub struct SimClockPacer {
+    scale: f64,
+    rate_multiplier: f64,
+    max_catch_up: Duration,
+    anchor: Option<(DateTime<Utc>, Instant)>,
+    high_water: Option<DateTime<Utc>>,
+    lag: Duration,
+    max_lag: Duration,
+    forgiven: Duration,
+}
+
+impl SimClockPacer {
+    pub fn new(scale: f64, max_catch_up: Duration) -> Self {
+        Self {
+            scale,
+            rate_multiplier: 1.0,
+            max_catch_up,
+            anchor: None,
+            high_water: None,
+            lag: Duration::ZERO,
+            max_lag: Duration::ZERO,
+            forgiven: Duration::ZERO,
+        }
+    }
+
+    /// Sleeps until `event_time` is due, or returns at once while catching up.
+    ///
+    /// Events at or before the simulated high-water mark never wait.
+    pub fn pace(&mut self, event_time: DateTime<Utc>) {
+        if self.scale <= 0.0 || self.high_water.is_some_and(|high| event_time <= high) {
+            return;
+        }
+        self.high_water = Some(event_time);
+        let now = Instant::now();
+        let (anchor_sim, anchor_wall) = *self.anchor.get_or_insert((event_time, now));
+        let sim_secs = (event_time - anchor_sim).num_milliseconds().max(0) as f64 / 1000.0;
+        let offset = Duration::try_from_secs_f64(sim_secs / (self.scale * self.rate_multiplier))
+            .unwrap_or(Duration::MAX);
+        let Some(target) = anchor_wall.checked_add(offset) else {
+            return;
+        };
+        if target > now {
+            self.lag = Duration::ZERO;
+            std::thread::sleep(target - now);
+            return;
+        }
+
+        let lag = now - target;
+        if lag > self.max_catch_up {
+            self.forgiven += lag;
+            self.anchor = Some((event_time, now));
+            self.lag = Duration::ZERO;
+        } else {
+            self.lag = lag;
+            self.max_lag = self.max_lag.max(lag);
+        }
+    }
+
+    /// Runs at `scale` from the last paced event on.
+    pub fn set_scale(&mut self, scale: f64) {
+        if scale == self.scale {
+            return;
+        }
+        self.scale = scale;
+        self.reanchor();
+    }
+
+    /// Runs `multiplier` times faster than `scale` from the last paced event on.
+    pub fn set_rate_multiplier(&mut self, multiplier: f64) {
+        if multiplier == self.rate_multiplier {
+            return;
+        }
+        self.rate_multiplier = multiplier;
+        self.reanchor();
+    }
+
+    pub fn set_max_catch_up(&mut self, max_catch_up: Duration) {
+        self.max_catch_up = max_catch_up;
+    }
+
+    /// Makes the last paced event due now, so a new rate applies from here on.
+    fn reanchor(&mut self) {
+        self.anchor = self.high_water.map(|high| (high, Instant::now()));
+    }
+
+    /// Moves the schedule later by `paused`, so time spent paused is not
+    /// caught up as a burst afterwards.
+    pub fn resume_after(&mut self, paused: Duration) {
+        if let Some((_, wall)) = self.anchor.as_mut() {
+            *wall = wall.checked_add(paused).unwrap_or(*wall);
+        }
+    }
+
+    /// How far the last paced event was behind its wall-clock target.
+    pub fn lag(&self) -> Duration {
+        self.lag
+    }
+
+    /// Largest lag the pacer has caught up from.
+    pub fn max_lag(&self) -> Duration {
+        self.max_lag
+    }
+
+    /// Total lag dropped because it exceeded `max_catch_up`.
+    pub fn forgiven(&self) -> Duration {
+        self.forgiven
+    }
+
+    /// True while events are dispatched unthrottled to recover lag.
+    pub fn is_catching_up(&self) -> bool {
+        !self.lag.is_zero()
+    }
+}
+
+/// Picks a writer shard from the key file writers group output by (source,
+/// account, and region), so each file stream is written by exactly one shard.
+pub fn writer_index_for_event(event: &Event, shards: usize) -> usize {
+    if shards <= 1 {
+        return 0;
+    }
+    let (account_id, region) = file_key(event);
+
+    let mut hasher = DefaultHasher::new();
+    event.envelope.source.hash(&mut hasher);
+    account_id.hash(&mut hasher);
+    region.hash(&mut hasher);
+    (hasher.finish() as usize) % shards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    struct QueueSource(VecDeque<Event>);
+
+    impl EventSource for QueueSource {
+        fn next_event(&mut self) -> Option<Event> {
+            self.0.pop_front()
+        }
+    }
+
+    struct CollectingWriter(Arc<Mutex<Vec<Event>>>);
+
+    impl EventWriter for CollectingWriter {
+        fn write_event(&mut self, event: &Event) -> io::Result<u64> {
+            self.0.lock().expect("lock").push(event.clone());
+            Ok(10)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn queue(source: &str, seconds: &[u32]) -> Box<dyn EventSource> {
+        Box::new(QueueSource(
+            seconds
+                .iter()
+                .map(|second| {
+                    test_event(source, "test.event", json!({}))
+                        .at(&format!("2026-01-01T00:00:{second:02}Z"))
+                })
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn merges_sources_and_reports_stats() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let stats = Pipeline::new()
+            .add_source(queue("cloudtrail", &[0, 2, 4]))
+            .add_source(queue("okta_system_log", &[1, 3]))
+            .add_writer(Box::new(CollectingWriter(Arc::clone(&written))))
+            .run()
+            .expect("run");
+
+        assert_eq!(stats.events, 5);
+        assert_eq!(stats.bytes, 50);
+        assert_eq!(stats.stop_reason, StopReason::SourcesExhausted);
+        assert_eq!(stats.events_by_source["cloudtrail"], 3);
+        assert_eq!(stats.events_by_source["okta_system_log"], 2);
+        let timestamps = written
+            .lock()
+            .expect("lock")
+            .iter()
+            .map(|event| event.envelope.timestamp.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            timestamps,
+            (0..5)
+                .map(|second| format!("2026-01-01T00:00:{second:02}Z"))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stops_at_limits() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let stats = Pipeline::new()
+            .add_source(queue("cloudtrail", &[0, 1, 2, 3, 4]))
+            .add_writer(Box::new(CollectingWriter(Arc::clone(&written))))
+            .run_until(RunLimits {
+                until_time: Some("2026-01-01T00:00:02Z".parse().expect("time")),
+                ..RunLimits::default()
+            })
+            .expect("run");
+        assert_eq!(stats.events, 3);
+        assert_eq!(stats.stop_reason, StopReason::UntilTime);
+
+        let stats = Pipeline::new()
+            .add_source(queue("cloudtrail", &[0, 1, 2, 3, 4]))
+            .add_writer(Box::new(CollectingWriter(Arc::clone(&written))))
+            .run_until(RunLimits {
+                max_events: Some(2),
+                ..RunLimits::default()
+            })
+            .expect("run");
+        assert_eq!(stats.events, 2);
+        assert_eq!(stats.stop_reason, StopReason::MaxEvents);
+        assert!(matches!(
+            Pipeline::new().run(),
+            Err(PipelineError::NoSources)
+        ));
+    }
+
+    #[test]
+    fn pacer_catches_up_after_a_stall_and_forgives_large_lag() {
+        let start: DateTime<Utc> = "2026-01-01T00:00:00Z".parse().expect("time");
+        let at = |ms: i64| start + chrono::Duration::milliseconds(ms);
+
+        // 1000x: one simulated second is one wall-clock millisecond.
+        let mut pacer = SimClockPacer::new(1000.0, Duration::from_secs(5));
+        pacer.pace(start);
+        thread::sleep(Duration::from_millis(200));
+        pacer.pace(at(50_000));
+        assert!(pacer.is_catching_up());
+        assert!(pacer.lag() >= Duration::from_millis(100));
+
+        let before = Instant::now();
+        pacer.pace(at(100_000));
+        assert!(before.elapsed() < Duration::from_millis(50));
+        assert!(pacer.is_catching_up());
+        pacer.pace(at(400_000));
+        assert!(!pacer.is_catching_up());
+        assert!(pacer.max_lag() >= Duration::from_millis(100));
+        assert_eq!(pacer.forgiven(), Duration::ZERO);
+
+        let mut pacer = SimClockPacer::new(1000.0, Duration::ZERO);
+        pacer.pace(start);
+        thread::sleep(Duration::from_millis(100));
+        pacer.pace(at(10_000));
+        assert!(!pacer.is_catching_up());
+        assert!(pacer.forgiven() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn pacer_follows_rate_changes_and_skips_pauses() {
+        let start: DateTime<Utc> = "2026-01-01T00:00:00Z".parse().expect("time");
+        let at = |ms: i64| start + chrono::Duration::milliseconds(ms);
+
+        // 1000x doubled: 100 simulated seconds take 50 ms instead of 100 ms.
+        let mut pacer = SimClockPacer::new(1000.0, Duration::from_secs(5));
+        pacer.pace(start);
+        pacer.set_rate_multiplier(2.0);
+        let before = Instant::now();
+        pacer.pace(at(100_000));
+        let elapsed = before.elapsed();
+        assert!(elapsed >= Duration::from_millis(45), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(90), "{elapsed:?}");
+
+        thread::sleep(Duration::from_millis(100));
+        pacer.resume_after(Duration::from_millis(100));
+        pacer.pace(at(110_000));
+        assert!(!pacer.is_catching_up());
+    }
+
+    #[test]
+    fn shards_events_by_their_file_key() {
+        let keyed = |tenant: &str, payload: serde_json::Value| {
+            test_event("entra_signin", "test.event", payload).in_account(tenant)
+        };
+        // Writers key files by `region` when there is no `awsRegion`, so both
+        // events belong to one file stream and must share a shard.
+        for tenant in ["tenant-a", "tenant-b", "tenant-c", "tenant-d"] {
+            let by_aws_region = keyed(tenant, json!({ "awsRegion": "westeurope" }));
+            let by_region = keyed(tenant, json!({ "region": "westeurope" }));
+            assert_eq!(
+                writer_index_for_event(&by_aws_region, 16),
+                writer_index_for_event(&by_region, 16)
+            );
+        }
+        let shards: std::collections::HashSet<_> = ["a", "b", "c", "d", "e", "f", "g", "h"]
+            .iter()
+            .map(|tenant| writer_index_for_event(&keyed(tenant, json!({})), 4))
+            .collect();
+        assert!(shards.len() > 1);
+        assert_eq!(
+            writer_index_for_event(&test_event("cloudtrail", "test.event", json!({})), 1),
+            0
+        );
+    }
+
+    #[test]
+    fn drop_newest_discards_events_that_do_not_fit() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (open_tx, open_rx) = std::sync::mpsc::channel::<()>();
+        let writer = CollectingWriter(Arc::clone(&written));
+        // The shard opens its writer only once released, so nothing drains the
+        // queue while events are dispatched.
+        let mut sink = WriterShards::new(2)
+            .backpressure(BackpressurePolicy::DropNewest)
+            .spawn("okta", move || {
+                let _ = open_rx.recv();
+                Ok(Box::new(writer) as Box<dyn EventWriter>)
+            });
+
+        for _ in 0..5 {
+            sink.dispatch(test_event("okta_system_log", "test.event", json!({})))
+                .unwrap();
+        }
+        let totals = sink.totals();
+        assert_eq!(totals.queued, 2);
+        assert_eq!(totals.dropped, 3);
+
+        open_tx.send(()).unwrap();
+        let totals = Box::new(sink).close().unwrap();
+        assert_eq!(totals.events, 2);
+        assert_eq!(written.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn drop_oldest_evicts_without_waiting_for_a_slow_writer() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let (open_tx, open_rx) = std::sync::mpsc::channel::<()>();
+        let writer = CollectingWriter(Arc::clone(&written));
+        let mut sink = WriterShards::new(2)
+            .backpressure(BackpressurePolicy::DropOldest)
+            .spawn("okta", move || {
+                let _ = open_rx.recv();
+                Ok(Box::new(writer) as Box<dyn EventWriter>)
+            });
+
+        // The writer is stuck opening, so a producer that waited on it would
+        // never finish.
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let producer = thread::spawn(move || {
+            for second in 0..50 {
+                let event = test_event("okta_system_log", "test.event", json!({}))
+                    .at(&format!("2026-01-01T00:00:{second:02}Z"));
+                sink.dispatch(event).unwrap();
+            }
+            done_tx.send(()).unwrap();
+            sink
+        });
+        done_rx
+            .recv_timeout(Duration::from_secs(5))
+            .expect("producer blocked on a full drop_oldest queue");
+        let sink = producer.join().unwrap();
+        let totals = sink.totals();
+        assert_eq!(totals.queued, 2);
+        assert_eq!(totals.dropped, 48);
+
+        open_tx.send(()).unwrap();
+        Box::new(sink).close().unwrap();
+        let timestamps: Vec<_> = written
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| event.envelope.timestamp.clone())
+            .collect();
+        assert_eq!(
+            timestamps,
+            vec!["2026-01-01T00:00:48Z", "2026-01-01T00:00:49Z"]
+        );
+    }
+}
@@ -0,0 +1,421 @@
+//! Summaries, diffs, and merging of actor population files.
+//!
+//! Backs `seclog actors inspect`, `diff`, and `merge`: a population file is
+//! only useful if you can see what is in it and how it drifted from the last
+//! one, and combining populations (e.g. two business units generated
+//! separately) must not silently produce two actors with the same AWS
+//! principal.
+
+use crate::actors_parquet::{kind_to_str, role_to_str, service_profile_to_str};
+use crate::core::actors::{ActorKind, ActorPopulation, ActorSeed, OnCallShift};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// Upper bounds of the `rate_per_hour` histogram buckets; the last bucket is open.
+pub const RATE_BUCKETS: [f64; 8] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 250.0];
+/// Upper bounds of the `error_rate` histogram buckets; the last bucket is open.
+pub const ERROR_RATE_BUCKETS: [f64; 6] = [0.01, 0.02, 0.05, 0.1, 0.2, 0.5];
+
+/// Aggregate view of an actor population.
+#[derive(Debug, Clone, Default)]
+pub struct PopulationSummary {
+    pub actors: usize,
+    pub kinds: BTreeMap<String, usize>,
+    /// Human roles; actors without a role count as `none`.
+    pub roles: BTreeMap<String, usize>,
+    pub service_profiles: BTreeMap<String, usize>,
+    pub accounts: BTreeMap<String, usize>,
+    pub identity_types: BTreeMap<String, usize>,
+    pub insider_personas: BTreeMap<String, usize>,
+    /// Actor counts per `RATE_BUCKETS` bucket, plus the open bucket.
+    pub rate_histogram: Vec<usize>,
+    /// Actor counts per `ERROR_RATE_BUCKETS` bucket, plus the open bucket.
+    pub error_rate_histogram: Vec<usize>,
+    /// Combined `rate_per_hour` of all actors.
+    pub events_per_hour: f64,
+    /// Principal IDs used by more than one actor, with their counts.
+    pub duplicate_principals: BTreeMap<String, usize>,
+}
+
+/// Tallies a population by kind, role, profile, account, and rate.
+pub fn summarize_population(population: &ActorPopulation) -> PopulationSummary {
+    let mut summary = PopulationSummary {
+        actors: population.actors.len(),
+        rate_histogram: vec![0; RATE_BUCKETS.len() + 1],
+        error_rate_histogram: vec![0; ERROR_RATE_BUCKETS.len() + 1],
+        ..PopulationSummary::default()
+    };
+    for seed in &population.actors {
+        *summary
+            .kinds
+            .entry(kind_to_str(&seed.kind).to_string())
+            .or_default() += 1;
+        match seed.kind {
+            ActorKind::Human => {
+                let role = seed.role.as_ref().map(role_to_str).unwrap_or("none");
+                *summary.roles.entry(role.to_string()).or_default() += 1;
+            }
+            ActorKind::Service => {
+                let profile = seed
+                    .service_profile
+                    .as_ref()
+                    .map(service_profile_to_str)
+                    .unwrap_or("generic");
+                *summary
+                    .service_profiles
+                    .entry(profile.to_string())
+                    .or_default() += 1;
+            }
+        }
+        *summary.accounts.entry(seed.account_id.clone()).or_default() += 1;
+        *summary
+            .identity_types
+            .entry(seed.identity_type.clone())
+            .or_default() += 1;
+        if let Some(persona) = &seed.insider_persona {
+            *summary
+                .insider_personas
+                .entry(persona.key().to_string())
+                .or_default() += 1;
+        }
+        summary.rate_histogram[bucket(&RATE_BUCKETS, seed.rate_per_hour)] += 1;
+        summary.error_rate_histogram[bucket(&ERROR_RATE_BUCKETS, seed.error_rate)] += 1;
+        summary.events_per_hour += seed.rate_per_hour;
+    }
+    summary.duplicate_principals = duplicate_principals(&population.actors);
+    summary
+}
+
+/// Index of the first bucket whose upper bound exceeds `value`.
+fn bucket(bounds: &[f64], value: f64) -> usize {
+    bounds
+        .iter()
+        .position(|bound| value < *bound)
+        .unwrap_or(bounds.len())
+}
+
+fn duplicate_principals(actors: &[ActorSeed]) -> BTreeMap<String, usize> {
+    let mut counts = HashMap::<&str, usize>::new();
+    for seed in actors {
+        *counts.entry(seed.principal_id.as_str()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(principal, count)| (principal.to_string(), count))
+        .collect()
+}
+
+/// Error returned when merged populations share principals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicatePrincipals {
+    /// Principal IDs that appear more than once, sorted.
+    pub principals: Vec<String>,
+}
+
+impl fmt::Display for DuplicatePrincipals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const SHOWN: usize = 5;
+        write!(
+            f,
+            "{} duplicate principal(s): {}",
+            self.principals.len(),
+            self.principals
+                .iter()
+                .take(SHOWN)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        if self.principals.len() > SHOWN {
+            write!(f, ", ...")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DuplicatePrincipals {}
+
+/// Concatenates populations in order.
+///
+/// Actors are matched by `principal_id`. With `skip_duplicates` the first
+/// actor for each principal is kept and the rest are dropped; otherwise any
+/// duplicate is an error.
+pub fn merge_populations(
+    populations: Vec<ActorPopulation>,
+    skip_duplicates: bool,
+) -> Result<(ActorPopulation, usize), DuplicatePrincipals> {
+    let mut actors = Vec::new();
+    let mut seen = HashMap::<String, usize>::new();
+    for population in populations {
+        for seed in population.actors {
+            *seen.entry(seed.principal_id.clone()).or_default() += 1;
+            actors.push(seed);
+        }
+    }
+
+    let mut principals = seen
+        .iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(principal, _)| principal.clone())
+        .collect::<Vec<_>>();
+    if principals.is_empty() {
+        return Ok((ActorPopulation { actors }, 0));
+    }
+    if !skip_duplicates {
+        principals.sort();
+        return Err(DuplicatePrincipals { principals });
+    }
+
+    let total = actors.len();
+    let mut kept = HashSet::new();
+    actors.retain(|seed| kept.insert(seed.principal_id.clone()));
+    let skipped = total - actors.len();
+    Ok((ActorPopulation { actors }, skipped))
+}
+
+/// One attribute that differs between two versions of an actor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+/// An actor present in both populations whose attributes changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActorChange {
+    pub principal_id: String,
+    /// `id`, else `user_name`, else the principal.
+    pub label: String,
+    pub changes: Vec<AttributeChange>,
+}
+
+/// Differences between two actor populations.
+#[derive(Debug, Clone, Default)]
+pub struct PopulationDiff {
+    /// Actors only in the new population, in file order.
+    pub added: Vec<ActorSeed>,
+    /// Actors only in the old population, in file order.
+    pub removed: Vec<ActorSeed>,
+    /// Actors in both whose attributes changed, in new-file order.
+    pub changed: Vec<ActorChange>,
+    pub unchanged: usize,
+    /// Changed actors per attribute.
+    pub field_counts: BTreeMap<&'static str, usize>,
+    pub old_summary: PopulationSummary,
+    pub new_summary: PopulationSummary,
+}
+
+/// Renders one actor attribute for comparison and display.
+type FieldRender = fn(&ActorSeed) -> String;
+
+/// Attributes compared by `diff_populations`.
+const DIFF_FIELDS: &[(&str, FieldRender)] = &[
+    ("kind", |seed| kind_to_str(&seed.kind).to_string()),
+    ("role", |seed| {
+        seed.role
+            .as_ref()
+            .map(role_to_str)
+            .unwrap_or("none")
+            .to_string()
+    }),
+    ("service_profile", |seed| {
+        seed.service_profile
+            .as_ref()
+            .map(service_profile_to_str)
+            .unwrap_or("none")
+            .to_string()
+    }),
+    ("identity_type", |seed| seed.identity_type.clone()),
+    ("account_id", |seed| seed.account_id.clone()),
+    ("arn", |seed| seed.arn.clone()),
+    ("user_name", |seed| {
+        seed.user_name.clone().unwrap_or_else(|| "none".to_string())
+    }),
+    ("rate_per_hour", |seed| format!("{:.2}", seed.rate_per_hour)),
+    ("error_rate", |seed| format!("{:.3}", seed.error_rate)),
+    ("insider_persona", |seed| {
+        seed.insider_persona
+            .as_ref()
+            .map(|persona| persona.key())
+            .unwrap_or("none")
+            .to_string()
+    }),
+    ("on_call", |seed| {
+        seed.on_call
+            .as_ref()
+            .map(OnCallShift::key)
+            .unwrap_or_else(|| "none".to_string())
+    }),
+    ("active_hours", |seed| {
+        format!(
+            "{:02}:00+{}h tz{:+}{}",
+            seed.active_start_hour,
+            seed.active_hours,
+            seed.timezone_offset,
+            if seed.weekend_active { " weekends" } else { "" }
+        )
+    }),
+    ("source_ips", |seed| list(&seed.source_ips)),
+    ("user_agents", |seed| list(&seed.user_agents)),
+    ("tags", |seed| list(&seed.tags)),
+];
+
+fn list(values: &[String]) -> String {
+    format!("[{}]", values.join(", "))
+}
+
+/// Compares two populations, matching actors by `principal_id`.
+///
+/// When a principal appears more than once in a file, the first actor wins,
+/// as with `merge --skip-duplicates`.
+pub fn diff_populations(old: &ActorPopulation, new: &ActorPopulation) -> PopulationDiff {
+    let index = |population: &ActorPopulation| {
+        let mut seeds = HashMap::<String, usize>::new();
+        for (idx, seed) in population.actors.iter().enumerate() {
+            seeds.entry(seed.principal_id.clone()).or_insert(idx);
+        }
+        seeds
+    };
+    let old_index = index(old);
+    let new_index = index(new);
+
+    let mut diff = PopulationDiff {
+        old_summary: summarize_population(old),
+        new_summary: summarize_population(new),
+        ..PopulationDiff::default()
+    };
+    for (idx, seed) in old.actors.iter().enumerate() {
+        if old_index.get(&seed.principal_id) == Some(&idx)
+            && !new_index.contains_key(&seed.principal_id)
+        {
+            diff.removed.push(seed.clone());
+        }
+    }
+    for (idx, seed) in new.actors.iter().enumerate() {
+        if new_index.get(&seed.principal_id) != Some(&idx) {
+            continue;
+        }
+        let Some(previous) = old_index
+            .get(&seed.principal_id)
+            .map(|idx| &old.actors[*idx])
+        else {
+            diff.added.push(seed.clone());
+            continue;
+        };
+        let changes = DIFF_FIELDS
+            .iter()
+            .filter_map(|(field, render)| {
+                let (old, new) = (render(previous), render(seed));
+                (old != new).then_some(AttributeChange { field, old, new })
+            })
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            diff.unchanged += 1;
+            continue;
+        }
+        for change in &changes {
+            *diff.field_counts.entry(change.field).or_default() += 1;
+        }
+        diff.changed.push(ActorChange {
+            principal_id: seed.principal_id.clone(),
+            label: actor_label(seed),
+            changes,
+        });
+    }
+    diff
+}
+
+fn actor_label(seed: &ActorSeed) -> String {
+    seed.id
+        .clone()
+        .or_else(|| seed.user_name.clone())
+        .unwrap_or_else(|| seed.principal_id.clone())
+}
+
+/// One-line description of an actor: label, role or profile, account, and rate.
+pub fn describe_actor(seed: &ActorSeed) -> String {
+    let role = match seed.kind {
+        ActorKind::Human => seed.role.as_ref().map(role_to_str).unwrap_or("none"),
+        ActorKind::Service => seed
+            .service_profile
+            .as_ref()
+            .map(service_profile_to_str)
+            .unwrap_or("generic"),
+    };
+    format!(
+        "{} ({} {role}, {}, {:.1}/h)",
+        actor_label(seed),
+        kind_to_str(&seed.kind),
+        seed.account_id,
+        seed.rate_per_hour
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::actors::generate_population;
+    use crate::core::config::PopulationConfig;
+
+    fn population(seed: u64) -> ActorPopulation {
+        let config: PopulationConfig = toml::from_str(&format!(
+            "seed = {seed}\n[population]\nactor_count = 40\nservice_ratio = 0.25\n"
+        ))
+        .unwrap();
+        generate_population(&config).unwrap()
+    }
+
+    #[test]
+    fn summary_counts_every_actor_once() {
+        let population = population(7);
+        let summary = summarize_population(&population);
+        assert_eq!(summary.actors, 40);
+        assert_eq!(summary.kinds.values().sum::<usize>(), 40);
+        assert_eq!(
+            summary.roles.values().sum::<usize>()
+                + summary.service_profiles.values().sum::<usize>(),
+            40
+        );
+        assert_eq!(summary.rate_histogram.iter().sum::<usize>(), 40);
+        assert_eq!(summary.error_rate_histogram.iter().sum::<usize>(), 40);
+        assert!(summary.duplicate_principals.is_empty());
+    }
+
+    #[test]
+    fn merge_detects_duplicate_principals() {
+        let err = merge_populations(vec![population(7), population(7)], false).unwrap_err();
+        assert_eq!(err.principals.len(), 40);
+
+        let (merged, skipped) =
+            merge_populations(vec![population(7), population(7)], true).unwrap();
+        assert_eq!(merged.actors.len(), 40);
+        assert_eq!(skipped, 40);
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_actors() {
+        let old = population(7);
+        let mut new = old.clone();
+        let removed = new.actors.remove(0);
+        new.actors[0].rate_per_hour += 5.0;
+        new.actors[0].source_ips.push("203.0.113.9".to_string());
+        new.actors.extend(population(8).actors.into_iter().take(3));
+
+        let diff = diff_populations(&old, &new);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].principal_id, removed.principal_id);
+        assert_eq!(diff.added.len(), 3);
+        assert_eq!(diff.changed.len(), 1);
+        let fields = diff.changed[0]
+            .changes
+            .iter()
+            .map(|change| change.field)
+            .collect::<Vec<_>>();
+        assert_eq!(fields, ["rate_per_hour", "source_ips"]);
+        assert_eq!(diff.unchanged, 38);
+        assert_eq!(diff.new_summary.actors, 42);
+        assert!(diff_populations(&old, &old).changed.is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+//! Completion tracking for bounded `seclog gen` runs.
+//!
+//! A run stops at whichever of `--max-events`, `--max-seconds`, or the
+//! simulated `until_time` it reaches first, so progress is the furthest of
+//! the three and the ETA the nearest. With a simulated end time each source
+//! also reports how far its own clock has come, which shows a lagging source
+//! in a multi-source backfill.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+const BAR_WIDTH: usize = 30;
+
+/// The limits a run stops at.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressBounds {
+    pub max_events: Option<u64>,
+    pub max_duration: Option<Duration>,
+    pub start_sim_time: DateTime<Utc>,
+    pub until_sim_time: Option<DateTime<Utc>>,
+}
+
+impl ProgressBounds {
+    /// Returns whether any limit is set, so completion can be measured.
+    pub fn is_bounded(&self) -> bool {
+        self.max_events.is_some()
+            || self.max_duration.is_some()
+            || self
+                .until_sim_time
+                .is_some_and(|until| until > self.start_sim_time)
+    }
+}
+
+/// Events and simulated time seen so far in a bounded run.
+#[derive(Debug)]
+pub struct Progress {
+    bounds: ProgressBounds,
+    started_at: Instant,
+    /// Events dispatched, including those replayed from a checkpoint.
+    events: u64,
+    /// Events dispatched before this process started.
+    resumed: u64,
+    first_sim_time: Option<DateTime<Utc>>,
+    sim_high_water: Option<DateTime<Utc>>,
+    sources: BTreeMap<String, SourceProgress>,
+}
+
+#[derive(Debug, Default)]
+struct SourceProgress {
+    events: u64,
+    sim_high_water: Option<DateTime<Utc>>,
+}
+
+impl Progress {
+    pub fn new(bounds: ProgressBounds) -> Self {
+        Self {
+            bounds,
+            started_at: Instant::now(),
+            events: 0,
+            resumed: 0,
+            first_sim_time: None,
+            sim_high_water: None,
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Counts events a resumed run already dispatched toward `--max-events`.
+    pub fn resume_from(&mut self, events: u64) {
+        self.events += events;
+        self.resumed += events;
+    }
+
+    /// Counts one dispatched event of `source`.
+    pub fn record(&mut self, source: &str, event_time: Option<DateTime<Utc>>) {
+        self.events += 1;
+        if !self.sources.contains_key(source) {
+            self.sources
+                .insert(source.to_string(), SourceProgress::default());
+        }
+        let Some(entry) = self.sources.get_mut(source) else {
+            return;
+        };
+        entry.events += 1;
+        let Some(time) = event_time else {
+            return;
+        };
+        if entry.sim_high_water.is_none_or(|high| time > high) {
+            entry.sim_high_water = Some(time);
+        }
+        if self.sim_high_water.is_none_or(|high| time > high) {
+            self.sim_high_water = Some(time);
+        }
+        if self.first_sim_time.is_none() {
+            self.first_sim_time = Some(time);
+        }
+    }
+
+    /// Renders the progress line for the time elapsed so far.
+    pub fn line(&self) -> String {
+        self.line_at(self.started_at.elapsed())
+    }
+
+    fn line_at(&self, elapsed: Duration) -> String {
+        let fraction = self.fraction(elapsed);
+        let filled = ((fraction * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+        let eta = match self.eta(elapsed) {
+            Some(eta) => format_duration(eta),
+            None => "?".to_string(),
+        };
+        let sim = self
+            .sim_high_water
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .unwrap_or_else(|| "-".to_string());
+        let mut line = format!(
+            "progress [{}{}] {:>5.1}% eta={eta} sim={sim} events={}",
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            fraction * 100.0,
+            self.events
+        );
+        for (name, source) in &self.sources {
+            match self.sim_fraction(source.sim_high_water) {
+                Some(done) => line.push_str(&format!(" {name}={:.0}%", done * 100.0)),
+                None => line.push_str(&format!(" {name}={}", source.events)),
+            }
+        }
+        line
+    }
+
+    /// Completion of the limit the run is closest to.
+    fn fraction(&self, elapsed: Duration) -> f64 {
+        let events = self
+            .bounds
+            .max_events
+            .map(|max| self.events as f64 / max.max(1) as f64);
+        let wall = self
+            .bounds
+            .max_duration
+            .map(|max| elapsed.as_secs_f64() / max.as_secs_f64().max(0.001));
+        let sim = self.sim_fraction(self.sim_high_water);
+        [events, wall, sim]
+            .into_iter()
+            .flatten()
+            .fold(0.0_f64, f64::max)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Share of the simulated window covered up to `high_water`.
+    fn sim_fraction(&self, high_water: Option<DateTime<Utc>>) -> Option<f64> {
+        let until = self.bounds.until_sim_time?;
+        let start = self.bounds.start_sim_time;
+        let window = (until - start).num_milliseconds();
+        if window <= 0 {
+            return None;
+        }
+        let done = high_water.map_or(0, |time| (time - start).num_milliseconds());
+        Some((done as f64 / window as f64).clamp(0.0, 1.0))
+    }
+
+    /// Time left until the nearest limit at the rates seen so far.
+    fn eta(&self, elapsed: Duration) -> Option<Duration> {
+        let secs = elapsed.as_secs_f64();
+        let mut etas = Vec::new();
+        if let Some(max) = self.bounds.max_duration {
+            etas.push(max.as_secs_f64() - secs);
+        }
+        if secs > 0.0 {
+            if let Some(max) = self.bounds.max_events {
+                let rate = (self.events - self.resumed) as f64 / secs;
+                if rate > 0.0 {
+                    etas.push(max.saturating_sub(self.events) as f64 / rate);
+                }
+            }
+            if let (Some(until), Some(first), Some(high)) = (
+                self.bounds.until_sim_time,
+                self.first_sim_time,
+                self.sim_high_water,
+            ) {
+                let rate = (high - first).num_milliseconds() as f64 / secs;
+                if rate > 0.0 {
+                    etas.push((until - high).num_milliseconds().max(0) as f64 / rate);
+                }
+            }
+        }
+        etas.into_iter()
+            .reduce(f64::min)
+            .map(|eta| Duration::from_secs_f64(eta.max(0.0)))
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as SimDuration;
+
+    #[test]
+    fn progress_follows_nearest_limit_with_per_source_clocks() {
+        let start = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut progress = Progress::new(ProgressBounds {
+            max_events: Some(1000),
+            max_duration: None,
+            start_sim_time: start,
+            until_sim_time: Some(start + SimDuration::days(10)),
+        });
+        for hour in 0..=120 {
+            progress.record("cloudtrail", Some(start + SimDuration::hours(hour)));
+        }
+        progress.record("okta", Some(start + SimDuration::days(2)));
+
+        let line = progress.line_at(Duration::from_secs(60));
+        assert!(
+            line.starts_with("progress [###############---------------]  50.0% eta=1m00s "),
+            "{line}"
+        );
+        assert!(
+            line.contains("sim=2026-01-06T00:00:00Z events=122"),
+            "{line}"
+        );
+        assert!(line.ends_with(" cloudtrail=50% okta=20%"), "{line}");
+        assert!(!ProgressBounds {
+            max_events: None,
+            max_duration: None,
+            start_sim_time: start,
+            until_sim_time: None,
+        }
+        .is_bounded());
+    }
+}
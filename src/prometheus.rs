@@ -0,0 +1,325 @@
+//! Prometheus text exposition for long-running generation jobs.
+//!
+//! The CLI updates a shared [`PrometheusMetrics`] from its generation loop and
+//! writer threads; [`serve`] answers scrapes on a plain TCP listener so the
+//! exporter needs no HTTP server dependency.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Counters and gauges exported on the metrics endpoint.
+#[derive(Debug, Default)]
+pub struct PrometheusMetrics {
+    events_total: AtomicU64,
+    bytes_total: AtomicU64,
+    missed_events_total: AtomicU64,
+    dropped_events_total: AtomicU64,
+    dead_lettered_events_total: AtomicU64,
+    events_per_second: AtomicU64,
+    bytes_per_second: AtomicU64,
+    queue_depth: AtomicU64,
+    buffered_bytes: AtomicU64,
+    flushes_total: AtomicU64,
+    flush_nanos_total: AtomicU64,
+    last_flush_nanos: AtomicU64,
+    pacing_lag_nanos: AtomicU64,
+    forgiven_lag_nanos_total: AtomicU64,
+    source_events: Mutex<BTreeMap<String, u64>>,
+}
+
+impl PrometheusMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds written events and bytes to the running totals.
+    pub fn add_written(&self, events: u64, bytes: u64) {
+        self.events_total.fetch_add(events, Ordering::Relaxed);
+        self.bytes_total.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn add_missed(&self, events: u64) {
+        self.missed_events_total
+            .fetch_add(events, Ordering::Relaxed);
+    }
+
+    pub fn add_dropped(&self, events: u64) {
+        self.dropped_events_total
+            .fetch_add(events, Ordering::Relaxed);
+    }
+
+    pub fn add_dead_lettered(&self, events: u64) {
+        self.dead_lettered_events_total
+            .fetch_add(events, Ordering::Relaxed);
+    }
+
+    /// Counts one generated event for its normalized source name.
+    pub fn add_source_event(&self, source: &str) {
+        let mut sources = self
+            .source_events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *sources.entry(source.to_string()).or_insert(0) += 1;
+    }
+
+    /// Sets the throughput gauges from the most recent metrics interval.
+    pub fn set_rates(&self, events_per_second: f64, bytes_per_second: f64) {
+        self.events_per_second
+            .store(events_per_second.to_bits(), Ordering::Relaxed);
+        self.bytes_per_second
+            .store(bytes_per_second.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn set_queue_depth(&self, depth: u64) {
+        self.queue_depth.store(depth, Ordering::Relaxed);
+    }
+
+    pub fn set_buffered_bytes(&self, bytes: u64) {
+        self.buffered_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Records one writer flush and its latency.
+    pub fn observe_flush(&self, latency: Duration) {
+        let nanos = latency.as_nanos().min(u64::MAX as u128) as u64;
+        self.flushes_total.fetch_add(1, Ordering::Relaxed);
+        self.flush_nanos_total.fetch_add(nanos, Ordering::Relaxed);
+        self.last_flush_nanos.store(nanos, Ordering::Relaxed);
+    }
+
+    /// Sets how far generation is behind the `time_scale` schedule.
+    pub fn set_pacing_lag(&self, lag: Duration) {
+        let nanos = lag.as_nanos().min(u64::MAX as u128) as u64;
+        self.pacing_lag_nanos.store(nanos, Ordering::Relaxed);
+    }
+
+    /// Adds lag dropped because it exceeded `traffic.max_catch_up`.
+    pub fn add_forgiven_lag(&self, lag: Duration) {
+        let nanos = lag.as_nanos().min(u64::MAX as u128) as u64;
+        self.forgiven_lag_nanos_total
+            .fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        write_metric(
+            &mut out,
+            "seclog_events_total",
+            "counter",
+            "Events written by output sinks.",
+            self.events_total.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_bytes_total",
+            "counter",
+            "Bytes written by output sinks.",
+            self.bytes_total.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_events_per_second",
+            "gauge",
+            "Events written per second over the last metrics interval.",
+            f64::from_bits(self.events_per_second.load(Ordering::Relaxed)),
+        );
+        write_metric(
+            &mut out,
+            "seclog_bytes_per_second",
+            "gauge",
+            "Bytes written per second over the last metrics interval.",
+            f64::from_bits(self.bytes_per_second.load(Ordering::Relaxed)),
+        );
+        write_metric(
+            &mut out,
+            "seclog_writer_queue_depth",
+            "gauge",
+            "Events dispatched to writer threads but not yet written.",
+            self.queue_depth.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_writer_buffered_bytes",
+            "gauge",
+            "Bytes buffered by JSONL and Parquet writers awaiting a file flush.",
+            self.buffered_bytes.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_writer_flushes_total",
+            "counter",
+            "Writer flushes completed.",
+            self.flushes_total.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_writer_flush_seconds_total",
+            "counter",
+            "Total time spent in writer flushes.",
+            self.flush_nanos_total.load(Ordering::Relaxed) as f64 / 1e9,
+        );
+        write_metric(
+            &mut out,
+            "seclog_writer_last_flush_seconds",
+            "gauge",
+            "Latency of the most recent writer flush.",
+            self.last_flush_nanos.load(Ordering::Relaxed) as f64 / 1e9,
+        );
+        write_metric(
+            &mut out,
+            "seclog_missed_events_total",
+            "counter",
+            "Events the generator could not emit on schedule.",
+            self.missed_events_total.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_dropped_events_total",
+            "counter",
+            "Events dropped before reaching an output sink.",
+            self.dropped_events_total.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_dead_lettered_events_total",
+            "counter",
+            "Events written to the dead-letter directory after failed writes.",
+            self.dead_lettered_events_total.load(Ordering::Relaxed) as f64,
+        );
+        write_metric(
+            &mut out,
+            "seclog_pacing_lag_seconds",
+            "gauge",
+            "How far generation is behind the time_scale schedule; non-zero while catching up.",
+            self.pacing_lag_nanos.load(Ordering::Relaxed) as f64 / 1e9,
+        );
+        write_metric(
+            &mut out,
+            "seclog_pacing_forgiven_seconds_total",
+            "counter",
+            "Lag dropped instead of caught up because it exceeded traffic.max_catch_up.",
+            self.forgiven_lag_nanos_total.load(Ordering::Relaxed) as f64 / 1e9,
+        );
+
+        out.push_str("# HELP seclog_source_events_total Events generated per source.\n");
+        out.push_str("# TYPE seclog_source_events_total counter\n");
+        let sources = self
+            .source_events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (source, count) in sources.iter() {
+            let _ = writeln!(
+                out,
+                "seclog_source_events_total{{source=\"{}\"}} {count}",
+                escape_label(source)
+            );
+        }
+        out
+    }
+}
+
+/// Starts a background listener that serves `metrics` to every request.
+///
+/// Returns the bound address, which is useful when `listen` uses port 0.
+pub fn serve(listen: &str, metrics: Arc<PrometheusMetrics>) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(listen)?;
+    let addr = listener.local_addr()?;
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let _ = respond(stream, &metrics);
+        }
+    });
+    Ok(addr)
+}
+
+fn respond(mut stream: TcpStream, metrics: &PrometheusMetrics) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request = Vec::new();
+    let mut buf = [0_u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < 16 * 1024 {
+        let read = stream.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    let body = metrics.render();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters_gauges_and_source_labels() {
+        let metrics = PrometheusMetrics::new();
+        metrics.add_written(3, 300);
+        metrics.add_source_event("cloudtrail");
+        metrics.add_source_event("cloudtrail");
+        metrics.add_source_event("okta_system_log");
+        metrics.set_rates(12.5, 2048.0);
+        metrics.set_queue_depth(7);
+        metrics.set_buffered_bytes(4096);
+        metrics.observe_flush(Duration::from_millis(250));
+        metrics.set_pacing_lag(Duration::from_millis(1500));
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE seclog_events_total counter\nseclog_events_total 3\n"));
+        assert!(text.contains("seclog_bytes_total 300\n"));
+        assert!(text.contains("seclog_events_per_second 12.5\n"));
+        assert!(text.contains("seclog_writer_queue_depth 7\n"));
+        assert!(text.contains("seclog_writer_buffered_bytes 4096\n"));
+        assert!(text.contains("seclog_writer_last_flush_seconds 0.25\n"));
+        assert!(text.contains("seclog_source_events_total{source=\"cloudtrail\"} 2\n"));
+        assert!(text.contains("seclog_source_events_total{source=\"okta_system_log\"} 1\n"));
+        assert!(text.contains("seclog_dropped_events_total 0\n"));
+        assert!(text.contains("seclog_dead_lettered_events_total 0\n"));
+        assert!(text.contains("seclog_pacing_lag_seconds 1.5\n"));
+    }
+
+    #[test]
+    fn serves_metrics_over_http() {
+        let metrics = Arc::new(PrometheusMetrics::new());
+        metrics.add_written(1, 10);
+        let addr = serve("127.0.0.1:0", Arc::clone(&metrics)).unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("seclog_events_total 1\n"));
+    }
+}
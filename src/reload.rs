@@ -0,0 +1,343 @@
+//! Live reload of traffic and scenario settings for `seclog gen --watch-config`.
+//!
+//! [`ConfigWatcher`] polls the config file's modification time and, when it
+//! changes, re-reads the file and hands back the traffic settings a running
+//! job can pick up without restarting: `time_scale`, `max_catch_up`, `phases`,
+//! and `calendar`. The calendar is swapped in place through the
+//! [`SharedCalendar`] the run thins events with.
+//!
+//! When `[source]` is CloudTrail, its `api_storm`, `policy_remediation`, and
+//! `impossible_travel` tables are swapped in through the [`SharedScenarios`]
+//! the generator re-plans from. The rest of the config, including credential
+//! takeovers, actor injections, and the actor population, is fixed when the
+//! run starts.
+
+use crate::core::config::{Config, SourceConfig, TrafficConfig};
+use crate::core::traffic::{parse_duration, LoadSchedule, TrafficCalendar};
+use crate::sources::calendar::SharedCalendar;
+use crate::sources::cloudtrail::{ScenarioSchedule, SharedScenarios};
+use crate::validate::{validate_config, Severity};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the config file's modification time is checked.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Traffic settings read from a changed config file.
+#[derive(Debug)]
+pub struct TrafficReload {
+    /// `traffic.time_scale`, or `None` when generation runs unpaced.
+    pub time_scale: Option<f64>,
+    pub max_catch_up: Duration,
+    /// `traffic.phases`, or `None` when the file has none.
+    pub phases: Option<LoadSchedule>,
+    /// Changed `traffic` fields, in config order.
+    pub changed: Vec<&'static str>,
+    /// Re-planned `[source]` scenario tables, in config order.
+    pub rescheduled: Vec<&'static str>,
+    /// Settings that cannot change live, such as the actor population or
+    /// `traffic.start_time`, differ from the running config; they keep the
+    /// values the run started with.
+    pub ignored_changes: bool,
+}
+
+/// Watches a config file for traffic and scenario changes.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    next_check: Instant,
+    traffic: TrafficConfig,
+    /// The file's other sections, to report changes that are not applied.
+    fixed: serde_json::Value,
+    calendar: SharedCalendar,
+    /// Scenario schedule of a CloudTrail `[source]`.
+    scenarios: Option<SharedScenarios>,
+}
+
+impl ConfigWatcher {
+    /// Starts watching `path`, which `config` was loaded from.
+    pub fn new(path: impl Into<PathBuf>, config: &Config) -> Result<Self, String> {
+        let path = path.into();
+        let calendar = match &config.traffic.calendar {
+            Some(calendar) => Some(TrafficCalendar::from_config(calendar).map_err(|err| err.0)?),
+            None => None,
+        };
+        Ok(Self {
+            modified: modified(&path),
+            next_check: Instant::now() + POLL_INTERVAL,
+            traffic: config.traffic.clone(),
+            fixed: fixed_sections(&Config::from_path(&path).map_err(|err| err.to_string())?),
+            calendar: Arc::new(RwLock::new(calendar)),
+            scenarios: scenario_schedule(config).map(|schedule| Arc::new(RwLock::new(schedule))),
+            path,
+        })
+    }
+
+    /// Calendar the run should thin events with.
+    pub fn calendar(&self) -> &SharedCalendar {
+        &self.calendar
+    }
+
+    /// Scenario schedule a CloudTrail generator should re-plan from; `None`
+    /// when `[source]` is not CloudTrail.
+    pub fn scenarios(&self) -> Option<&SharedScenarios> {
+        self.scenarios.as_ref()
+    }
+
+    /// Re-reads the file if it changed since the last check.
+    ///
+    /// Returns `Ok(None)` when nothing changed, and an error, leaving the
+    /// running settings in place, when the new file is invalid. Changed
+    /// scenarios are validated as `seclog validate` would before they apply.
+    pub fn poll(&mut self) -> Result<Option<TrafficReload>, String> {
+        let now = Instant::now();
+        if now < self.next_check {
+            return Ok(None);
+        }
+        self.next_check = now + POLL_INTERVAL;
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return Ok(None);
+        }
+        self.modified = modified;
+        self.reload().map(Some)
+    }
+
+    fn reload(&mut self) -> Result<TrafficReload, String> {
+        let config = Config::from_path(&self.path).map_err(|err| err.to_string())?;
+        let fixed = fixed_sections(&config);
+        let schedule = scenario_schedule(&config);
+        let rescheduled = match (&self.scenarios, &schedule) {
+            (Some(running), Some(schedule)) => schedule.changed_tables(
+                &running
+                    .read()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            ),
+            _ => Vec::new(),
+        };
+        if !rescheduled.is_empty() {
+            if let Some(issue) = validate_config(&config)
+                .into_iter()
+                .find(|issue| issue.severity == Severity::Error)
+            {
+                return Err(issue.to_string());
+            }
+        }
+        let traffic = config.traffic;
+        let max_catch_up = match &traffic.max_catch_up {
+            Some(value) => parse_duration(value)
+                .ok_or_else(|| format!("traffic.max_catch_up is not a valid duration: {value}"))?,
+            None => crate::pipeline::DEFAULT_MAX_CATCH_UP,
+        };
+        let phases = if traffic.phases.is_empty() {
+            None
+        } else {
+            Some(LoadSchedule::from_phases(&traffic.phases).map_err(|err| err.0)?)
+        };
+        let calendar = match &traffic.calendar {
+            Some(calendar) => Some(TrafficCalendar::from_config(calendar).map_err(|err| err.0)?),
+            None => None,
+        };
+
+        let mut changed = Vec::new();
+        if traffic.time_scale != self.traffic.time_scale {
+            changed.push("time_scale");
+        }
+        if traffic.max_catch_up != self.traffic.max_catch_up {
+            changed.push("max_catch_up");
+        }
+        if !same(&traffic.phases, &self.traffic.phases) {
+            changed.push("phases");
+        }
+        if !same(&traffic.calendar, &self.traffic.calendar) {
+            changed.push("calendar");
+            *self
+                .calendar
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = calendar;
+        }
+        if let (Some(running), Some(mut schedule)) = (&self.scenarios, schedule) {
+            if !rescheduled.is_empty() {
+                let mut running = running
+                    .write()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                schedule.revision = running.revision + 1;
+                *running = schedule;
+            }
+        }
+        let time_scale = traffic.time_scale.unwrap_or(1.0);
+        self.traffic = traffic;
+        Ok(TrafficReload {
+            time_scale: (time_scale > 0.0).then_some(time_scale),
+            max_catch_up,
+            phases,
+            changed,
+            rescheduled,
+            ignored_changes: fixed != self.fixed,
+        })
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+fn scenario_schedule(config: &Config) -> Option<ScenarioSchedule> {
+    match &config.source {
+        SourceConfig::CloudTrail(source) => Some(ScenarioSchedule::from_config(source)),
+        _ => None,
+    }
+}
+
+/// The config without the fields a reload applies.
+fn fixed_sections(config: &Config) -> serde_json::Value {
+    let mut value = serde_json::to_value(config).unwrap_or_default();
+    if let Some(traffic) = value.get_mut("traffic").and_then(|v| v.as_object_mut()) {
+        for field in ["time_scale", "max_catch_up", "phases", "calendar"] {
+            traffic.remove(field);
+        }
+    }
+    if scenario_schedule(config).is_some() {
+        if let Some(source) = value.get_mut("source").and_then(|v| v.as_object_mut()) {
+            for field in ["api_storm", "policy_remediation", "impossible_travel"] {
+                source.remove(field);
+            }
+        }
+    }
+    value
+}
+
+fn same<T: serde::Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const BASE: &str = r#"
+seed = 7
+[traffic]
+time_scale = 60
+[output]
+dir = "./out"
+[output.files]
+target_size_mb = 50
+max_age_seconds = 60
+[output.format]
+type = "jsonl"
+[source]
+type = "cloudtrail"
+curated = true
+"#;
+
+    #[test]
+    fn reloads_traffic_and_keeps_the_last_good_settings() {
+        let dir = std::env::temp_dir().join(format!("seclog-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        fs::write(&path, BASE).unwrap();
+        let config = Config::from_path(&path).unwrap();
+        let mut watcher = ConfigWatcher::new(&path, &config).unwrap();
+        assert!(watcher.calendar().read().unwrap().is_none());
+
+        let rewrite = |watcher: &mut ConfigWatcher, contents: &str| {
+            fs::write(&path, contents).unwrap();
+            watcher.modified = None;
+            watcher.next_check = Instant::now();
+            watcher.poll()
+        };
+
+        let reload = rewrite(
+            &mut watcher,
+            &BASE.replace(
+                "time_scale = 60",
+                "time_scale = 0\ncalendar = { weekdays = [1, 1, 1, 1, 1, 0.5, 0.5] }\n\
+                 phases = [{ duration = \"1m\", eps = 50 }]",
+            ),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(reload.time_scale, None);
+        assert_eq!(reload.changed, ["time_scale", "phases", "calendar"]);
+        assert!(reload.phases.is_some());
+        assert!(!reload.ignored_changes);
+        assert!(watcher.calendar().read().unwrap().is_some());
+
+        let err = rewrite(
+            &mut watcher,
+            &BASE.replace("time_scale = 60", "max_catch_up = \"soon\""),
+        )
+        .unwrap_err();
+        assert!(err.contains("max_catch_up"), "{err}");
+        assert!(watcher.calendar().read().unwrap().is_some());
+
+        let reload = rewrite(&mut watcher, &BASE.replace("seed = 7", "seed = 8"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reload.time_scale, Some(60.0));
+        assert!(reload.ignored_changes);
+        assert!(watcher.calendar().read().unwrap().is_none());
+
+        watcher.next_check = Instant::now();
+        assert!(watcher.poll().unwrap().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reloads_cloudtrail_scenarios_and_rejects_invalid_ones() {
+        let dir =
+            std::env::temp_dir().join(format!("seclog-reload-scenarios-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        let base = BASE.replace(
+            "curated = true",
+            "curated = true\nidentity_registry_path = \"examples/identity_registry.toml\"",
+        );
+        fs::write(&path, &base).unwrap();
+        let config = Config::from_path(&path).unwrap();
+        let mut watcher = ConfigWatcher::new(&path, &config).unwrap();
+        let scenarios = Arc::clone(watcher.scenarios().expect("cloudtrail scenarios"));
+        assert_eq!(scenarios.read().unwrap().revision, 0);
+
+        let rewrite = |watcher: &mut ConfigWatcher, contents: String| {
+            fs::write(&path, contents).unwrap();
+            watcher.modified = None;
+            watcher.next_check = Instant::now();
+            watcher.poll()
+        };
+        let storm = |duration: i64| {
+            format!(
+                "{base}[[source.api_storm]]\nservice = \"ec2.amazonaws.com\"\n\
+                 offset_seconds = 600\nduration_seconds = {duration}\n"
+            )
+        };
+
+        let reload = rewrite(&mut watcher, storm(60)).unwrap().unwrap();
+        assert_eq!(reload.rescheduled, ["api_storm"]);
+        assert!(reload.changed.is_empty());
+        assert!(!reload.ignored_changes);
+        {
+            let schedule = scenarios.read().unwrap();
+            assert_eq!(schedule.revision, 1);
+            assert_eq!(schedule.api_storms[0].duration_seconds, 60);
+        }
+
+        let err = rewrite(&mut watcher, storm(0)).unwrap_err();
+        assert!(err.contains("duration_seconds"), "{err}");
+        assert_eq!(scenarios.read().unwrap().revision, 1);
+
+        let reload = rewrite(&mut watcher, base.clone()).unwrap().unwrap();
+        assert_eq!(reload.rescheduled, ["api_storm"]);
+        let schedule = scenarios.read().unwrap();
+        assert_eq!(schedule.revision, 2);
+        assert!(schedule.api_storms.is_empty());
+        drop(schedule);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
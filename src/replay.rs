@@ -0,0 +1,386 @@
+//! Re-emits previously generated output through an event writer.
+//!
+//! Events are read back from a JSON or Parquet output directory and written to
+//! any [`EventWriter`], optionally paced by the gaps between the original
+//! timestamps. Each file is read as a stream and the files are merged by
+//! envelope timestamp, so memory grows with the files that overlap in time
+//! rather than with the dataset. Used by `seclog replay` to re-send a curated
+//! dataset to a live collector.
+
+use crate::core::event::Event;
+use crate::core::traits::EventWriter;
+use crate::formats::json::{event_from_record, stream_records};
+use crate::formats::parquet::{is_event_file, read_events};
+use crate::inspect::{collect_files, file_format};
+use crate::pipeline::{parse_event_time, SimClockPacer, DEFAULT_MAX_CATCH_UP};
+use chrono::{DateTime, Utc};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// Output format read by [`ReplayInput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayFormat {
+    /// JSON, JSONL, and gzip JSON files, including Azure Monitor and CloudWatch framings.
+    Jsonl,
+    Parquet,
+}
+
+impl FromStr for ReplayFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "jsonl" | "json" => Ok(ReplayFormat::Jsonl),
+            "parquet" => Ok(ReplayFormat::Parquet),
+            other => Err(format!(
+                "unknown replay format {other:?} (expected jsonl or parquet)"
+            )),
+        }
+    }
+}
+
+/// Reads every event file of `format` under `dir` into memory, ordered as
+/// [`ReplayInput::events`] yields them.
+pub fn read_output(dir: impl AsRef<Path>, format: ReplayFormat) -> io::Result<Vec<Event>> {
+    ReplayInput::open(dir, format)?.events().collect()
+}
+
+/// The event files of `format` under a directory, replayable any number of times.
+///
+/// Events within a file are expected in timestamp order, as seclog writes
+/// them; [`ReplayInput::events`] merges the files by envelope timestamp and
+/// keeps each file's own order. Events with equal or unparseable timestamps
+/// keep their file order. Parquet files that are not event output (e.g. actor
+/// populations) and files without events are skipped.
+#[derive(Debug, Clone)]
+pub struct ReplayInput {
+    format: ReplayFormat,
+    /// Event files with their first event time, in merge order.
+    files: Vec<(Option<DateTime<Utc>>, PathBuf)>,
+}
+
+impl ReplayInput {
+    /// Lists the event files under `dir` and reads the first event of each.
+    pub fn open(dir: impl AsRef<Path>, format: ReplayFormat) -> io::Result<Self> {
+        let mut paths = Vec::new();
+        collect_files(dir.as_ref(), &mut paths)?;
+        paths.sort();
+
+        let mut files = Vec::new();
+        for path in paths {
+            let Some(mut events) = open_file(&path, format)? else {
+                continue;
+            };
+            if let Some(first) = events.next() {
+                files.push((parse_event_time(&first?), path));
+            }
+        }
+        files.sort_by_key(|(first, _)| *first);
+        Ok(Self { format, files })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// One pass over every event, merged by envelope timestamp. A file is only
+    /// opened once the merge reaches its first event.
+    pub fn events(&self) -> MergedEvents<'_> {
+        MergedEvents {
+            input: self,
+            unopened: (0..self.files.len()).collect(),
+            streams: Vec::new(),
+            heads: BinaryHeap::new(),
+        }
+    }
+}
+
+type FileEvents = Box<dyn Iterator<Item = io::Result<Event>>>;
+
+/// Opens `path` as an event stream, or `None` when it is not an event file of `format`.
+fn open_file(path: &Path, format: ReplayFormat) -> io::Result<Option<FileEvents>> {
+    let events: FileEvents = match (format, file_format(path)) {
+        (ReplayFormat::Parquet, Some("parquet")) => {
+            if !is_event_file(path).map_err(|err| with_path(path, err))? {
+                return Ok(None);
+            }
+            Box::new(read_events(path.to_path_buf()))
+        }
+        (ReplayFormat::Jsonl, Some("json" | "jsonl" | "json.gz")) => Box::new(
+            stream_records(path.to_path_buf())
+                .map_err(|err| with_path(path, err))?
+                .map(|record| record.and_then(event_from_record)),
+        ),
+        _ => return Ok(None),
+    };
+    let path = path.to_path_buf();
+    let events = events.map(move |event| event.map_err(|err| with_path(&path, err)));
+    Ok(Some(Box::new(events)))
+}
+
+fn with_path(path: &Path, err: io::Error) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+/// Iterator returned by [`ReplayInput::events`].
+pub struct MergedEvents<'a> {
+    input: &'a ReplayInput,
+    /// Files not yet opened, by first event time.
+    unopened: VecDeque<usize>,
+    streams: Vec<FileEvents>,
+    /// Next event of each open stream, keyed by time, file order, and stream.
+    heads: BinaryHeap<Reverse<Head>>,
+}
+
+struct Head {
+    time: Option<DateTime<Utc>>,
+    file: usize,
+    stream: usize,
+    event: Event,
+}
+
+impl Head {
+    fn key(&self) -> (Option<DateTime<Utc>>, usize) {
+        (self.time, self.file)
+    }
+}
+
+impl PartialEq for Head {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Head {}
+
+impl PartialOrd for Head {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Head {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl MergedEvents<'_> {
+    /// Opens every file whose first event is due before the earliest head.
+    fn open_due(&mut self) -> io::Result<()> {
+        while let Some(&file) = self.unopened.front() {
+            let (first, path) = &self.input.files[file];
+            if self
+                .heads
+                .peek()
+                .is_some_and(|Reverse(head)| head.key() <= (*first, file))
+            {
+                return Ok(());
+            }
+            self.unopened.pop_front();
+            let Some(events) = open_file(path, self.input.format)? else {
+                continue;
+            };
+            self.streams.push(events);
+            self.advance(self.streams.len() - 1, file)?;
+        }
+        Ok(())
+    }
+
+    /// Queues the next event of `stream`, if any.
+    fn advance(&mut self, stream: usize, file: usize) -> io::Result<()> {
+        if let Some(event) = self.streams[stream].next() {
+            let event = event?;
+            self.heads.push(Reverse(Head {
+                time: parse_event_time(&event),
+                file,
+                stream,
+                event,
+            }));
+        }
+        Ok(())
+    }
+
+    fn next_event(&mut self) -> io::Result<Option<Event>> {
+        self.open_due()?;
+        let Some(Reverse(head)) = self.heads.pop() else {
+            return Ok(None);
+        };
+        self.advance(head.stream, head.file)?;
+        Ok(Some(head.event))
+    }
+}
+
+impl Iterator for MergedEvents<'_> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+/// Controls how [`replay`] re-emits events.
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Replays original timestamp gaps divided by this factor; `None` writes as fast as possible.
+    pub speed: Option<f64>,
+    /// Lag behind the `speed` schedule beyond this is dropped instead of
+    /// caught up; see [`SimClockPacer`].
+    pub max_catch_up: Duration,
+    /// Number of passes over the events; 0 repeats until `max_events` is reached.
+    pub repeat: u64,
+    /// Stops after this many events across all passes.
+    pub max_events: Option<u64>,
+    /// Flushes the writer at this interval.
+    pub flush_interval: Option<Duration>,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            speed: None,
+            max_catch_up: DEFAULT_MAX_CATCH_UP,
+            repeat: 0,
+            max_events: None,
+            flush_interval: None,
+        }
+    }
+}
+
+/// Totals for a replay run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub events: u64,
+    pub bytes: u64,
+    /// Completed passes; a pass cut short by `max_events` is not counted.
+    pub passes: u64,
+}
+
+/// Writes the events of `input` to `writer`, flushing at the end; closing the
+/// writer is left to the caller.
+///
+/// Pacing restarts at the beginning of each pass, so repeated passes replay
+/// the same gaps rather than waiting out the span between the last and first
+/// event.
+pub fn replay(
+    input: &ReplayInput,
+    writer: &mut dyn EventWriter,
+    options: &ReplayOptions,
+) -> io::Result<ReplayStats> {
+    let mut stats = ReplayStats::default();
+    if input.is_empty() {
+        return Ok(stats);
+    }
+    let mut next_flush = options
+        .flush_interval
+        .map(|interval| Instant::now() + interval);
+
+    'passes: while options.repeat == 0 || stats.passes < options.repeat {
+        let mut pacer = options
+            .speed
+            .map(|speed| SimClockPacer::new(speed, options.max_catch_up));
+        for event in input.events() {
+            if options.max_events.is_some_and(|max| stats.events >= max) {
+                break 'passes;
+            }
+            let event = event?;
+            if let (Some(pacer), Some(time)) = (pacer.as_mut(), parse_event_time(&event)) {
+                pacer.pace(time);
+            }
+            stats.bytes += writer.write_event(&event)?;
+            stats.events += 1;
+
+            if let (Some(interval), Some(next)) = (options.flush_interval, next_flush) {
+                let now = Instant::now();
+                if now >= next {
+                    writer.flush()?;
+                    next_flush = Some(now + interval);
+                }
+            }
+        }
+        stats.passes += 1;
+    }
+    writer.flush()?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::Outcome;
+    use crate::fixtures::test_event;
+    use crate::formats::json::JsonlWriter;
+    use crate::formats::parquet::ParquetWriter;
+    use serde_json::{json, Value};
+    use std::fs;
+
+    #[test]
+    fn replays_json_output_into_parquet() {
+        let dir = std::env::temp_dir().join(format!("seclog-replay-{}", std::process::id()));
+        let mut json_writer =
+            JsonlWriter::new(dir.join("json/cloudtrail"), 1, None, Some("gzip")).expect("writer");
+        for (name, time, result) in [
+            ("PutObject", "2026-01-01T00:00:01Z", Some("AccessDenied")),
+            ("GetObject", "2026-01-01T00:00:03Z", None),
+        ] {
+            let payload = json!({
+                "eventTime": time,
+                "eventSource": "s3.amazonaws.com",
+                "eventName": name,
+                "recipientAccountId": "123456789012",
+                "userIdentity": { "type": "IAMUser", "arn": "arn:aws:iam::123456789012:user/alice" },
+                "errorCode": result,
+            });
+            json_writer
+                .write_event(&test_event("okta_system_log", "test", payload).at(time))
+                .expect("write");
+        }
+        json_writer.close().expect("close");
+        let mut json_writer =
+            JsonlWriter::new(dir.join("json/okta"), 1, None, Some("gzip")).expect("writer");
+        json_writer
+            .write_event(
+                &test_event("okta_system_log", "test", Value::Null).at("2026-01-01T00:00:02Z"),
+            )
+            .expect("write");
+        json_writer.close().expect("close");
+
+        // The two files are merged back by time.
+        let events = read_output(dir.join("json"), ReplayFormat::Jsonl).expect("read");
+        let names = events
+            .iter()
+            .map(|event| event.envelope.event_type.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["PutObject", "test", "GetObject"]);
+        assert_eq!(events[0].envelope.source, "cloudtrail");
+        assert!(matches!(events[0].envelope.outcome, Outcome::Failure));
+        assert_eq!(events[0].envelope.actor.kind, "IAMUser");
+        assert_eq!(events[1].envelope.source, "okta_system_log");
+        assert_eq!(
+            events[2].envelope.tenant_id.as_deref(),
+            Some("123456789012")
+        );
+
+        let mut parquet_writer = ParquetWriter::new(dir.join("parquet"), 1, None).expect("writer");
+        let options = ReplayOptions {
+            speed: Some(1000.0),
+            max_events: Some(5),
+            ..ReplayOptions::default()
+        };
+        let input = ReplayInput::open(dir.join("json"), ReplayFormat::Jsonl).expect("open");
+        let stats = replay(&input, &mut parquet_writer, &options).expect("replay");
+        parquet_writer.close().expect("close");
+        assert_eq!(stats.events, 5);
+        assert_eq!(stats.passes, 1);
+
+        let replayed = read_output(dir.join("parquet"), ReplayFormat::Parquet).expect("read");
+        assert_eq!(replayed.len(), 5);
+        assert_eq!(replayed[0].payload["eventName"], "PutObject");
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,431 @@
+//! Per-run report written at the end of `seclog gen`.
+//!
+//! A generated dataset handed to someone else should say how it was made:
+//! which config and seed produced it, how many events each source and event
+//! type contributed and how many failed, how many actors took part, how long
+//! the run took in wall and simulated time, and which files it wrote.
+//! [`RunTally`] counts events as they are dispatched; [`RunTally::finish`]
+//! adds the run context, and [`write_report`] saves the result as JSON and,
+//! optionally, a self-contained HTML page.
+
+use crate::core::config::RunReportConfig;
+use crate::core::event::{Event, Outcome};
+use crate::formats::manifest::{format_time, relative_path};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Event types listed in the HTML report.
+const HTML_EVENT_TYPES: usize = 25;
+
+/// Running counts of the events one run dispatched.
+#[derive(Debug, Default)]
+pub struct RunTally {
+    sources: BTreeMap<String, SourceTally>,
+    first_event_time: Option<DateTime<Utc>>,
+    last_event_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct SourceTally {
+    events: u64,
+    errors: u64,
+    event_types: BTreeMap<String, u64>,
+    actors: HashSet<String>,
+}
+
+impl RunTally {
+    /// Counts one dispatched event; `event_time` is its parsed timestamp.
+    pub fn record(&mut self, event: &Event, event_time: Option<DateTime<Utc>>) {
+        let envelope = &event.envelope;
+        if !self.sources.contains_key(&envelope.source) {
+            self.sources
+                .insert(envelope.source.clone(), SourceTally::default());
+        }
+        let Some(source) = self.sources.get_mut(&envelope.source) else {
+            return;
+        };
+        source.events += 1;
+        if matches!(envelope.outcome, Outcome::Failure) {
+            source.errors += 1;
+        }
+        match source.event_types.get_mut(&envelope.event_type) {
+            Some(count) => *count += 1,
+            None => {
+                source.event_types.insert(envelope.event_type.clone(), 1);
+            }
+        }
+        if !source.actors.contains(&envelope.actor.id) {
+            source.actors.insert(envelope.actor.id.clone());
+        }
+        if let Some(time) = event_time {
+            if self.first_event_time.is_none_or(|first| time < first) {
+                self.first_event_time = Some(time);
+            }
+            if self.last_event_time.is_none_or(|last| time > last) {
+                self.last_event_time = Some(time);
+            }
+        }
+    }
+
+    /// Builds the report from the counts and the run context.
+    pub fn finish(self, run: RunInfo, files: Vec<ReportFile>) -> RunReport {
+        let mut actors = HashSet::new();
+        let mut sources = BTreeMap::new();
+        let (mut events, mut errors) = (0, 0);
+        for (name, source) in self.sources {
+            events += source.events;
+            errors += source.errors;
+            let report = SourceReport {
+                events: source.events,
+                errors: source.errors,
+                error_rate: ratio(source.errors, source.events),
+                actors: source.actors.len() as u64,
+                event_types: source.event_types,
+            };
+            actors.extend(source.actors);
+            sources.insert(name, report);
+        }
+        let sim_seconds = match (self.first_event_time, self.last_event_time) {
+            (Some(first), Some(last)) => (last - first).num_milliseconds() as f64 / 1000.0,
+            _ => 0.0,
+        };
+        RunReport {
+            run_id: run.run_id,
+            generated_at: format_time(Utc::now()),
+            config_path: run.config_path,
+            config_fingerprint: run.config_fingerprint,
+            seed: run.seed,
+            wall_seconds: run.wall_seconds,
+            first_event_time: self.first_event_time.map(format_time),
+            last_event_time: self.last_event_time.map(format_time),
+            sim_seconds,
+            events,
+            errors,
+            error_rate: ratio(errors, events),
+            actors: actors.len() as u64,
+            sources,
+            files,
+        }
+    }
+}
+
+/// Run context recorded alongside the event counts.
+#[derive(Debug, Clone)]
+pub struct RunInfo {
+    pub run_id: String,
+    pub config_path: String,
+    /// Fingerprint of the loaded config, as used by checkpoints.
+    pub config_fingerprint: String,
+    /// Seed of the run; `None` when the generator seeded itself from entropy.
+    pub seed: Option<u64>,
+    pub wall_seconds: f64,
+}
+
+/// Report of one `seclog gen` run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub run_id: String,
+    pub generated_at: String,
+    pub config_path: String,
+    pub config_fingerprint: String,
+    pub seed: Option<u64>,
+    pub wall_seconds: f64,
+    pub first_event_time: Option<String>,
+    pub last_event_time: Option<String>,
+    /// Simulated time between the first and last event.
+    pub sim_seconds: f64,
+    pub events: u64,
+    /// Events with a failure outcome.
+    pub errors: u64,
+    pub error_rate: f64,
+    /// Distinct envelope actor IDs across all sources.
+    pub actors: u64,
+    pub sources: BTreeMap<String, SourceReport>,
+    pub files: Vec<ReportFile>,
+}
+
+/// Per-source totals in a run report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceReport {
+    pub events: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub actors: u64,
+    pub event_types: BTreeMap<String, u64>,
+}
+
+/// One file in the run's output directories.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportFile {
+    /// Path relative to the report's base directory, `/`-separated.
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// Lists every file under `dirs`, relative to `base`, sorted by path.
+///
+/// Output directories that were never created are skipped.
+pub fn list_output_files(dirs: &[PathBuf], base: &Path) -> io::Result<Vec<ReportFile>> {
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = dirs.to_vec();
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                pending.push(path);
+            } else if seen.insert(path.clone()) {
+                files.push(ReportFile {
+                    path: relative_path(base, &path),
+                    bytes: metadata.len(),
+                });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Writes the JSON report, and the HTML page when `html_path` is set.
+pub fn write_report(report: &RunReport, config: &RunReportConfig) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(report).map_err(io::Error::other)?;
+    write_file(Path::new(&config.path), &json)?;
+    if let Some(path) = &config.html_path {
+        write_file(Path::new(path), render_html(report).as_bytes())?;
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)
+}
+
+/// Renders the report as one HTML page with inline CSS bar charts.
+pub fn render_html(report: &RunReport) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>seclog run {}</title>\n\
+         <style>body{{font-family:sans-serif;margin:2em;color:#222}}\
+         table{{border-collapse:collapse;margin-bottom:2em}}\
+         td,th{{padding:2px 10px;text-align:left;border-bottom:1px solid #ddd}}\
+         td.num{{text-align:right}}.bar{{background:#4a7bd0;height:12px}}</style>\n\
+         </head><body>\n<h1>seclog run {}</h1>\n",
+        escape(&report.run_id),
+        escape(&report.run_id)
+    );
+
+    html.push_str("<table>\n");
+    let rows = [
+        ("Config", report.config_path.clone()),
+        ("Config fingerprint", report.config_fingerprint.clone()),
+        (
+            "Seed",
+            report
+                .seed
+                .map(|seed| seed.to_string())
+                .unwrap_or_else(|| "random".to_string()),
+        ),
+        ("Generated at", report.generated_at.clone()),
+        ("Wall time", format!("{:.1}s", report.wall_seconds)),
+        (
+            "Simulated time",
+            format!(
+                "{} to {} ({:.0}s)",
+                report.first_event_time.as_deref().unwrap_or("-"),
+                report.last_event_time.as_deref().unwrap_or("-"),
+                report.sim_seconds
+            ),
+        ),
+        ("Events", report.events.to_string()),
+        (
+            "Errors",
+            format!("{} ({:.2}%)", report.errors, report.error_rate * 100.0),
+        ),
+        ("Actors", report.actors.to_string()),
+        ("Files", report.files.len().to_string()),
+    ];
+    for (name, value) in rows {
+        let _ = writeln!(html, "<tr><th>{name}</th><td>{}</td></tr>", escape(&value));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Sources</h2>\n<table>\n<tr><th>Source</th><th>Events</th><th></th><th>Errors</th><th>Actors</th></tr>\n");
+    let max = report
+        .sources
+        .values()
+        .map(|source| source.events)
+        .max()
+        .unwrap_or(0);
+    for (name, source) in &report.sources {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td><td>{}</td><td class=\"num\">{:.2}%</td><td class=\"num\">{}</td></tr>",
+            escape(name),
+            source.events,
+            bar(source.events, max),
+            source.error_rate * 100.0,
+            source.actors
+        );
+    }
+    html.push_str("</table>\n");
+
+    let mut event_types = report
+        .sources
+        .iter()
+        .flat_map(|(source, totals)| {
+            totals
+                .event_types
+                .iter()
+                .map(move |(event_type, count)| (source, event_type, *count))
+        })
+        .collect::<Vec<_>>();
+    event_types.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| (a.0, a.1).cmp(&(b.0, b.1))));
+    let _ = writeln!(
+        html,
+        "<h2>Top event types</h2>\n<table>\n<tr><th>Source</th><th>Event type</th><th>Events</th><th></th></tr>"
+    );
+    let max = event_types.first().map(|row| row.2).unwrap_or(0);
+    for (source, event_type, count) in event_types.iter().take(HTML_EVENT_TYPES) {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td class=\"num\">{count}</td><td>{}</td></tr>",
+            escape(source),
+            escape(event_type),
+            bar(*count, max)
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Files</h2>\n<table>\n<tr><th>Path</th><th>Bytes</th></tr>\n");
+    for file in &report.files {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td></tr>",
+            escape(&file.path),
+            file.bytes
+        );
+    }
+    html.push_str("</table>\n</body></html>\n");
+    html
+}
+
+fn bar(value: u64, max: u64) -> String {
+    let width = if max == 0 {
+        0.0
+    } else {
+        value as f64 * 300.0 / max as f64
+    };
+    format!("<div class=\"bar\" style=\"width:{width:.0}px\"></div>")
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use crate::formats::json::unique_id;
+    use serde_json::json;
+
+    #[test]
+    fn report_totals_sources_and_lists_files() {
+        let time = |value: &str| Some(value.parse::<DateTime<Utc>>().unwrap());
+        let mut tally = RunTally::default();
+        tally.record(
+            &test_event("cloudtrail", "GetObject", json!({})).by("alice", "human"),
+            time("2026-01-01T00:01:00Z"),
+        );
+        tally.record(
+            &test_event("cloudtrail", "GetObject", json!({}))
+                .by("bob", "human")
+                .failed(),
+            time("2026-01-01T00:00:00Z"),
+        );
+        tally.record(
+            &test_event("okta", "user.session.start", json!({})).by("alice", "human"),
+            time("2026-01-01T00:02:30Z"),
+        );
+
+        let dir = std::env::temp_dir().join(format!("seclog-report-{}", unique_id()));
+        fs::create_dir_all(dir.join("okta")).unwrap();
+        fs::write(dir.join("okta/events.jsonl"), "{}\n").unwrap();
+        fs::write(dir.join("cloudtrail.json"), "[]").unwrap();
+        let files = list_output_files(&[dir.clone(), dir.join("missing")], &dir).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                ReportFile {
+                    path: "cloudtrail.json".to_string(),
+                    bytes: 2,
+                },
+                ReportFile {
+                    path: "okta/events.jsonl".to_string(),
+                    bytes: 3,
+                },
+            ]
+        );
+
+        let run = RunInfo {
+            run_id: "run-1".to_string(),
+            config_path: "config.toml".to_string(),
+            config_fingerprint: "abc".to_string(),
+            seed: Some(7),
+            wall_seconds: 1.5,
+        };
+        let report = tally.finish(run, files);
+        assert_eq!((report.events, report.errors, report.actors), (3, 1, 2));
+        assert_eq!(report.sim_seconds, 150.0);
+        assert_eq!(
+            report.first_event_time.as_deref(),
+            Some("2026-01-01T00:00:00.000Z")
+        );
+        let cloudtrail = &report.sources["cloudtrail"];
+        assert_eq!((cloudtrail.events, cloudtrail.actors), (2, 2));
+        assert_eq!(cloudtrail.error_rate, 0.5);
+        assert_eq!(cloudtrail.event_types["GetObject"], 2);
+
+        let config = RunReportConfig {
+            path: dir.join("report/report.json").display().to_string(),
+            html_path: Some(dir.join("report/report.html").display().to_string()),
+        };
+        write_report(&report, &config).unwrap();
+        let written: RunReport = serde_json::from_slice(&fs::read(&config.path).unwrap()).unwrap();
+        assert_eq!(written, report);
+        let html = fs::read_to_string(config.html_path.as_ref().unwrap()).unwrap();
+        assert!(html.contains("user.session.start") && html.contains("okta/events.jsonl"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,186 @@
+//! Extracts a subset of generated output into a new directory.
+//!
+//! Used by `seclog slice` to curate small, sharable fixtures out of large
+//! runs. Every JSON and Parquet event file under the input directory is
+//! filtered into the same relative path under the output directory, in its own
+//! format: JSON framing and gzip carry over, and Parquet files keep their
+//! schema and compression. Files left without events are not written.
+
+use crate::core::event::Event;
+use crate::formats::json::{event_from_record, filter_records};
+use crate::formats::parquet::{filter_events, is_event_file};
+use crate::inspect::{collect_files, file_format};
+use crate::pipeline::parse_event_time;
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Which events [`slice_dir`] keeps. Empty lists match every event.
+#[derive(Debug, Clone, Default)]
+pub struct SliceFilter {
+    /// Keeps events at or after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Keeps events before this time.
+    pub to: Option<DateTime<Utc>>,
+    /// Actor IDs or names, e.g. a principal ARN or user name.
+    pub actors: Vec<String>,
+    /// Source-native event types, e.g. `ConsoleLogin`.
+    pub event_types: Vec<String>,
+}
+
+impl SliceFilter {
+    /// Returns whether `event` matches every set criterion. Events without a
+    /// parseable timestamp never match a time bound.
+    pub fn matches(&self, event: &Event) -> bool {
+        if self.from.is_some() || self.to.is_some() {
+            let Some(time) = parse_event_time(event) else {
+                return false;
+            };
+            if self.from.is_some_and(|from| time < from) || self.to.is_some_and(|to| time >= to) {
+                return false;
+            }
+        }
+        let actor = &event.envelope.actor;
+        (self.actors.is_empty()
+            || self
+                .actors
+                .iter()
+                .any(|id| *id == actor.id || actor.name.as_deref() == Some(id)))
+            && (self.event_types.is_empty()
+                || self.event_types.contains(&event.envelope.event_type))
+    }
+}
+
+/// Totals for a slice run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SliceStats {
+    pub files_read: u64,
+    pub files_written: u64,
+    pub events_read: u64,
+    pub events_written: u64,
+}
+
+/// Copies the events under `input` that match `filter` into `output`.
+///
+/// `output` must not exist or be empty, so a slice never mixes with other
+/// files. Parquet files that are not event output (e.g. actor populations),
+/// manifests, and digests are skipped.
+pub fn slice_dir(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    filter: &SliceFilter,
+) -> io::Result<SliceStats> {
+    let (input, output) = (input.as_ref(), output.as_ref());
+    if output.exists() && fs::read_dir(output)?.next().is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} is not empty", output.display()),
+        ));
+    }
+    let mut paths = Vec::new();
+    collect_files(input, &mut paths)?;
+    paths.sort();
+
+    let mut stats = SliceStats::default();
+    for path in paths {
+        let Some(format) = file_format(&path) else {
+            continue;
+        };
+        let target = output.join(path.strip_prefix(input).unwrap_or(&path));
+        let counts = match format {
+            "parquet" => {
+                if !is_event_file(&path)? {
+                    continue;
+                }
+                filter_events(&path, &target, |event| filter.matches(event))
+            }
+            _ => filter_records(&path, &target, |record| {
+                Ok(filter.matches(&event_from_record(record.clone())?))
+            }),
+        };
+        let (read, kept) = counts
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?;
+        stats.files_read += 1;
+        stats.files_written += u64::from(kept > 0);
+        stats.events_read += read;
+        stats.events_written += kept;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::traits::EventWriter;
+    use crate::fixtures::test_event;
+    use crate::formats::json::JsonlWriter;
+    use crate::formats::parquet::ParquetWriter;
+    use crate::replay::{read_output, ReplayFormat};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn slices_json_and_parquet_output_in_place() {
+        let dir = std::env::temp_dir().join(format!("seclog-slice-{}", std::process::id()));
+        let alice = "arn:aws:iam::123456789012:user/alice";
+        let bob = "arn:aws:iam::123456789012:user/bob";
+        let events = [
+            ("2026-01-01T00:00:00Z", alice, "GetObject"),
+            ("2026-01-01T00:01:00Z", bob, "GetObject"),
+            ("2026-01-01T00:02:00Z", alice, "PutObject"),
+            ("2026-01-01T00:03:00Z", alice, "GetObject"),
+        ]
+        .map(|(timestamp, actor, event_name)| {
+            test_event(
+                "cloudtrail",
+                event_name,
+                json!({
+                    "eventTime": timestamp,
+                    "eventSource": "s3.amazonaws.com",
+                    "eventName": event_name,
+                    "recipientAccountId": "123456789012",
+                    "userIdentity": { "type": "IAMUser", "arn": actor },
+                }),
+            )
+            .at(timestamp)
+            .by(actor, "IAMUser")
+            .in_account("123456789012")
+        });
+        let mut json = JsonlWriter::new(dir.join("in/json"), 1, None, Some("gzip")).unwrap();
+        let mut parquet = ParquetWriter::new(dir.join("in/parquet"), 1, None).unwrap();
+        for event in &events {
+            json.write_event(event).unwrap();
+            parquet.write_event(event).unwrap();
+        }
+        json.close().unwrap();
+        parquet.close().unwrap();
+
+        let filter = SliceFilter {
+            from: Some("2026-01-01T00:01:00Z".parse().unwrap()),
+            to: Some("2026-01-01T00:03:00Z".parse().unwrap()),
+            actors: vec![alice.to_string()],
+            event_types: Vec::new(),
+        };
+        let stats = slice_dir(dir.join("in"), dir.join("out"), &filter).unwrap();
+        assert_eq!(stats.files_read, 2);
+        assert_eq!(stats.files_written, 2);
+        assert_eq!(stats.events_read, 8);
+        assert_eq!(stats.events_written, 2);
+
+        for format in [ReplayFormat::Jsonl, ReplayFormat::Parquet] {
+            let sliced = read_output(dir.join("out"), format).unwrap();
+            let names = sliced
+                .iter()
+                .map(|event| event.payload["eventName"].clone())
+                .collect::<Vec<_>>();
+            assert_eq!(names, [Value::from("PutObject")], "{format:?}");
+        }
+        let mut gz = Vec::new();
+        collect_files(&dir.join("out/json"), &mut gz).unwrap();
+        assert!(gz.iter().all(|path| file_format(path) == Some("json.gz")));
+
+        let err = slice_dir(dir.join("in"), dir.join("out"), &filter).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
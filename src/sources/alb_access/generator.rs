@@ -0,0 +1,610 @@
+use super::model::AlbAccessLogRecord;
+use crate::core::config::AlbAccessLogConfig;
+use crate::core::event::{Event, EventEnvelope, Outcome, Target};
+use crate::core::traits::EventSource;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use std::collections::VecDeque;
+
+const DEFAULT_LOAD_BALANCER: &str = "internal-portal";
+const DEFAULT_DOMAIN_NAME: &str = "portal.example.com";
+const DEFAULT_VISIT_RATE: f64 = 0.5;
+const DEFAULT_MAX_REQUESTS: usize = 8;
+const DEFAULT_ERROR_RATE: f64 = 0.01;
+/// Registered targets behind the load balancer.
+const TARGETS: u64 = 3;
+const TARGET_PORT: u16 = 8080;
+/// Size of the HTML error page the load balancer returns itself.
+const ERROR_PAGE_BYTES: u64 = 162;
+
+/// Pages a visit opens with.
+const PAGES: &[&str] = &[
+    "/",
+    "/dashboard",
+    "/projects",
+    "/projects/{id}",
+    "/reports",
+    "/settings",
+    "/admin/users",
+];
+/// API calls the page makes while it is open, with their relative weights.
+const API_CALLS: &[(&str, &str, f64)] = &[
+    ("GET", "/api/v1/me", 3.0),
+    ("GET", "/api/v1/projects", 4.0),
+    ("GET", "/api/v1/projects/{id}", 4.0),
+    ("GET", "/api/v1/reports?range=7d", 2.0),
+    ("GET", "/api/v1/notifications", 2.0),
+    ("POST", "/api/v1/search", 1.5),
+    ("POST", "/api/v1/projects/{id}/runs", 1.0),
+    ("PUT", "/api/v1/settings", 0.5),
+    ("DELETE", "/api/v1/projects/{id}/runs/{id}", 0.3),
+];
+/// Static assets; `{build}` is a per-load-balancer build hash.
+const ASSETS: &[&str] = &[
+    "/static/js/app.{build}.js",
+    "/static/js/vendor.{build}.js",
+    "/static/css/main.{build}.css",
+    "/favicon.ico",
+];
+
+/// Event source that adds Application Load Balancer access logs to CloudTrail
+/// console activity.
+///
+/// Console events (browser user agent, literal source IP) are sometimes
+/// preceded by a visit to an internal web app behind an ALB: one page load
+/// followed by static assets and API calls, all from the same client IP and
+/// user agent as the CloudTrail event, so web-layer and CloudTrail data join
+/// on `client_ip` and `user_agent`. Scenario labels carry over, so console
+/// activity from a takeover IP labels its web requests too.
+pub struct AlbAccessLogSource {
+    source: Box<dyn EventSource>,
+    include_cloudtrail: bool,
+    load_balancer: String,
+    domain_name: String,
+    visit_rate: f64,
+    max_requests: usize,
+    error_rate: f64,
+    pending: VecDeque<Event>,
+    /// Latest time emitted so far; visits never go back before it.
+    last_time: Option<DateTime<Utc>>,
+    rng: StdRng,
+}
+
+/// One request in a visit, before timing and status are drawn.
+struct Request {
+    verb: &'static str,
+    path: String,
+    kind: RequestKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RequestKind {
+    Page,
+    Asset,
+    Api,
+}
+
+/// Fields shared by the requests of one visit.
+struct VisitContext<'a> {
+    cloudtrail: &'a Event,
+    account_id: &'a str,
+    region: &'a str,
+    client_ip: &'a str,
+    client_port: u16,
+    user_agent: &'a str,
+    tls13: bool,
+    conn_trace_id: String,
+}
+
+impl AlbAccessLogSource {
+    pub fn new(
+        source: Box<dyn EventSource>,
+        config: &AlbAccessLogConfig,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        let visit_rate = config.visit_rate.unwrap_or(DEFAULT_VISIT_RATE);
+        let error_rate = config.error_rate.unwrap_or(DEFAULT_ERROR_RATE);
+        for (field, value) in [("visit_rate", visit_rate), ("error_rate", error_rate)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{field} must be between 0.0 and 1.0"));
+            }
+        }
+        let max_requests = config.max_requests.unwrap_or(DEFAULT_MAX_REQUESTS);
+        if max_requests == 0 {
+            return Err("max_requests must be greater than 0".to_string());
+        }
+        let load_balancer = config
+            .load_balancer
+            .clone()
+            .unwrap_or_else(|| DEFAULT_LOAD_BALANCER.to_string());
+        if !is_load_balancer_name(&load_balancer) {
+            return Err(format!(
+                "load_balancer must be 1-32 letters, digits, or inner hyphens: {load_balancer}"
+            ));
+        }
+        let domain_name = config
+            .domain_name
+            .clone()
+            .unwrap_or_else(|| DEFAULT_DOMAIN_NAME.to_string());
+        if domain_name.is_empty() || domain_name.contains(char::is_whitespace) {
+            return Err("domain_name must be a host name without spaces".to_string());
+        }
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Self {
+            source,
+            include_cloudtrail: config.include_cloudtrail.unwrap_or(true),
+            load_balancer,
+            domain_name,
+            visit_rate,
+            max_requests,
+            error_rate,
+            pending: VecDeque::new(),
+            last_time: None,
+            rng,
+        })
+    }
+
+    /// Access log events for the visit a console event implies, in time order.
+    fn visit_events(&mut self, cloudtrail: &Event) -> Vec<Event> {
+        if cloudtrail.envelope.source != "cloudtrail" {
+            return Vec::new();
+        }
+        let payload = &cloudtrail.payload;
+        let text = |pointer: &str| {
+            payload
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty())
+        };
+        let Some(time) = text("/eventTime")
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Utc))
+        else {
+            return Vec::new();
+        };
+        let last_time = self.last_time.unwrap_or(time).min(time);
+        self.last_time = self.last_time.max(Some(time));
+        let client_ip =
+            text("/sourceIPAddress").filter(|address| address.parse::<std::net::IpAddr>().is_ok());
+        let user_agent = text("/userAgent").filter(|agent| agent.starts_with("Mozilla/"));
+        let (Some(client_ip), Some(user_agent)) = (client_ip, user_agent) else {
+            return Vec::new();
+        };
+        if !self.rng.gen_bool(self.visit_rate) {
+            return Vec::new();
+        }
+
+        let requests = self.visit_requests();
+        // Requests follow each other by a few hundred milliseconds and the
+        // visit ends a few seconds before the console call.
+        let mut offsets = Vec::with_capacity(requests.len());
+        let mut elapsed = 0;
+        for _ in &requests {
+            elapsed += self.rng.gen_range(20_000..900_000);
+            offsets.push(elapsed);
+        }
+        let start = (time
+            - Duration::microseconds(elapsed + self.rng.gen_range(500_000..8_000_000)))
+        .max(last_time);
+        let context = VisitContext {
+            cloudtrail,
+            account_id: text("/recipientAccountId").unwrap_or("000000000000"),
+            region: text("/awsRegion").unwrap_or("us-east-1"),
+            client_ip,
+            client_port: self.rng.gen_range(1024..=65535),
+            user_agent,
+            tls13: stable_hash(client_ip) % 10 < 7,
+            conn_trace_id: format!("TID_{:016x}", self.rng.gen::<u64>()),
+        };
+        requests
+            .iter()
+            .zip(offsets)
+            .filter_map(|(request, offset)| {
+                let at = (start + Duration::microseconds(offset)).min(time);
+                self.access_log_event(&context, request, at)
+            })
+            .collect()
+    }
+
+    /// A page load followed by assets and API calls.
+    fn visit_requests(&mut self) -> Vec<Request> {
+        let count = self.rng.gen_range(1..=self.max_requests);
+        let page = PAGES[self.rng.gen_range(0..PAGES.len())];
+        let mut requests = vec![Request {
+            verb: "GET",
+            path: self.fill_ids(page),
+            kind: RequestKind::Page,
+        }];
+        let build = format!("{:08x}", stable_hash(&self.load_balancer) as u32);
+        let total_weight = API_CALLS.iter().map(|(_, _, weight)| weight).sum::<f64>();
+        while requests.len() < count {
+            if requests.len() <= ASSETS.len() && self.rng.gen_bool(0.5) {
+                let asset = ASSETS[requests.len() - 1];
+                requests.push(Request {
+                    verb: "GET",
+                    path: asset.replace("{build}", &build),
+                    kind: RequestKind::Asset,
+                });
+                continue;
+            }
+            let mut point = self.rng.gen_range(0.0..total_weight);
+            let (mut verb, mut path) = (API_CALLS[0].0, API_CALLS[0].1);
+            for (call_verb, call_path, weight) in API_CALLS {
+                if point < *weight {
+                    (verb, path) = (call_verb, call_path);
+                    break;
+                }
+                point -= weight;
+            }
+            requests.push(Request {
+                verb,
+                path: self.fill_ids(path),
+                kind: RequestKind::Api,
+            });
+        }
+        requests
+    }
+
+    fn fill_ids(&mut self, path: &str) -> String {
+        let mut filled = path.to_string();
+        while filled.contains("{id}") {
+            let id = self.rng.gen_range(100..10_000).to_string();
+            filled = filled.replacen("{id}", &id, 1);
+        }
+        filled
+    }
+
+    fn access_log_event(
+        &mut self,
+        context: &VisitContext,
+        request: &Request,
+        time: DateTime<Utc>,
+    ) -> Option<Event> {
+        let load_balancer_id = hex16(&format!(
+            "{}:{}:{}",
+            context.account_id, context.region, self.load_balancer
+        ));
+        let target_group_arn = format!(
+            "arn:aws:elasticloadbalancing:{}:{}:targetgroup/{}-tg/{}",
+            context.region,
+            context.account_id,
+            truncate(&self.load_balancer, 29),
+            hex16(&format!("tg:{load_balancer_id}")),
+        );
+        let target_slot = self.rng.gen_range(0..TARGETS);
+        let target_ip = private_address(&format!("{load_balancer_id}:{target_slot}"));
+
+        let request_processing_time = self.rng.gen_range(0.0..0.002);
+        let failure = self
+            .rng
+            .gen_bool(self.error_rate)
+            .then(|| [502, 503, 504][self.rng.gen_range(0..3)]);
+        let (elb_status_code, target_status_code, target_processing_time, sent_bytes) =
+            match failure {
+                Some(status) => (status, None, -1.0_f64, ERROR_PAGE_BYTES),
+                None => {
+                    let status = self.target_status(request);
+                    let seconds = match request.kind {
+                        RequestKind::Page => self.rng.gen_range(0.02..0.4),
+                        RequestKind::Asset => self.rng.gen_range(0.001..0.02),
+                        RequestKind::Api => self.rng.gen_range(0.005..0.8),
+                    };
+                    let bytes = match (request.kind, status) {
+                        (_, 204 | 302 | 304) => self.rng.gen_range(150..400),
+                        (_, 400..) => self.rng.gen_range(200..600),
+                        (RequestKind::Page, _) => self.rng.gen_range(15_000..90_000),
+                        (RequestKind::Asset, _) => self.rng.gen_range(2_000..600_000),
+                        (RequestKind::Api, _) => self.rng.gen_range(400..25_000),
+                    };
+                    (status, Some(status), seconds, bytes)
+                }
+            };
+        let response_processing_time = if failure.is_some() {
+            -1.0_f64
+        } else {
+            self.rng.gen_range(0.0..0.001)
+        };
+        // 503 means no healthy target was available, so none was tried.
+        let target = (elb_status_code != 503).then(|| format!("{target_ip}:{TARGET_PORT}"));
+        let received_bytes = match request.verb {
+            "POST" | "PUT" => self.rng.gen_range(600..4_000),
+            _ => self.rng.gen_range(300..1_400),
+        };
+        let total = request_processing_time
+            + target_processing_time.max(0.0)
+            + response_processing_time.max(0.0);
+        let created = time - Duration::microseconds((total * 1_000_000.0) as i64);
+        let (ssl_cipher, ssl_protocol) = if context.tls13 {
+            ("TLS_AES_128_GCM_SHA256", "TLSv1.3")
+        } else {
+            ("ECDHE-RSA-AES128-GCM-SHA256", "TLSv1.2")
+        };
+        let matched_rule_priority = match request.kind {
+            RequestKind::Page => "0",
+            RequestKind::Api => "10",
+            RequestKind::Asset => "20",
+        };
+
+        let record = AlbAccessLogRecord {
+            request_type: "h2".to_string(),
+            time: time.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string(),
+            elb: format!("app/{}/{load_balancer_id}", self.load_balancer),
+            client_ip: context.client_ip.to_string(),
+            client_port: context.client_port,
+            target_ip: target.as_ref().map(|_| target_ip.clone()),
+            target_port: target.as_ref().map(|_| TARGET_PORT),
+            request_processing_time,
+            target_processing_time,
+            response_processing_time,
+            elb_status_code,
+            target_status_code,
+            received_bytes,
+            sent_bytes,
+            request_verb: request.verb.to_string(),
+            request_url: format!("https://{}:443{}", self.domain_name, request.path),
+            request_proto: "HTTP/2.0".to_string(),
+            user_agent: Some(context.user_agent.to_string()),
+            ssl_cipher: Some(ssl_cipher.to_string()),
+            ssl_protocol: Some(ssl_protocol.to_string()),
+            target_group_arn: Some(target_group_arn),
+            trace_id: format!(
+                "Root=1-{:08x}-{:024x}",
+                created.timestamp(),
+                self.rng.gen::<u128>() >> 32
+            ),
+            domain_name: Some(self.domain_name.clone()),
+            chosen_cert_arn: Some(format!(
+                "arn:aws:acm:{}:{}:certificate/{}",
+                context.region,
+                context.account_id,
+                uuid(&format!("cert:{load_balancer_id}"))
+            )),
+            matched_rule_priority: matched_rule_priority.to_string(),
+            request_creation_time: created.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string(),
+            actions_executed: "forward".to_string(),
+            redirect_url: None,
+            error_reason: None,
+            target_port_list: target.clone(),
+            target_status_code_list: target_status_code.map(|code| code.to_string()),
+            classification: None,
+            classification_reason: None,
+            conn_trace_id: context.conn_trace_id.clone(),
+        };
+
+        let envelope = &context.cloudtrail.envelope;
+        let path = request.path.split('?').next().unwrap_or(&request.path);
+        Some(Event {
+            envelope: EventEnvelope {
+                schema_version: envelope.schema_version.clone(),
+                timestamp: time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                source: "alb_access_log".to_string(),
+                event_type: request.verb.to_string(),
+                actor: envelope.actor.clone(),
+                target: Some(Target {
+                    id: format!("https://{}{path}", self.domain_name),
+                    kind: "url".to_string(),
+                    name: Some(path.to_string()),
+                }),
+                outcome: if elb_status_code >= 400 {
+                    Outcome::Failure
+                } else {
+                    Outcome::Success
+                },
+                geo: envelope.geo.clone(),
+                ip: Some(context.client_ip.to_string()),
+                user_agent: Some(context.user_agent.to_string()),
+                session_id: envelope.session_id.clone(),
+                tenant_id: envelope.tenant_id.clone(),
+                tenant: None,
+            },
+            payload: serde_json::to_value(&record).ok()?,
+            label: context.cloudtrail.label.clone(),
+        })
+    }
+
+    /// Status the app returns for a request that reached it.
+    fn target_status(&mut self, request: &Request) -> u16 {
+        let draw = self.rng.gen_range(0.0..1.0);
+        match request.kind {
+            RequestKind::Asset if request.path == "/favicon.ico" && draw < 0.3 => 404,
+            RequestKind::Asset if draw < 0.4 => 304,
+            RequestKind::Page if request.path == "/" => 302,
+            RequestKind::Page if request.path.starts_with("/admin") && draw < 0.2 => 403,
+            // Stale links to deleted projects and runs.
+            RequestKind::Api
+                if request
+                    .path
+                    .split('/')
+                    .any(|segment| segment.parse::<u32>().is_ok())
+                    && draw < 0.03 =>
+            {
+                404
+            }
+            RequestKind::Api if draw < 0.05 => 401,
+            RequestKind::Api => match request.verb {
+                "POST" => 201,
+                "DELETE" => 204,
+                _ => 200,
+            },
+            _ => 200,
+        }
+    }
+}
+
+impl EventSource for AlbAccessLogSource {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            let event = self.source.next_event()?;
+            let visit = self.visit_events(&event);
+            self.pending.extend(visit);
+            if self.include_cloudtrail || event.envelope.source != "cloudtrail" {
+                self.pending.push_back(event);
+            }
+        }
+    }
+}
+
+/// ALB names are 1-32 alphanumerics or hyphens, without a leading or trailing hyphen.
+fn is_load_balancer_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+}
+
+fn truncate(value: &str, len: usize) -> &str {
+    &value[..value.len().min(len)]
+}
+
+/// Stable target address inside the load balancer's VPC.
+fn private_address(seed: &str) -> String {
+    let hash = stable_hash(&format!("target:{seed}"));
+    format!(
+        "10.{}.{}.{}",
+        hash & 0xff,
+        (hash >> 8) & 0xff,
+        ((hash >> 16) % 250) + 4
+    )
+}
+
+fn hex16(seed: &str) -> String {
+    format!("{:016x}", stable_hash(seed))
+}
+
+fn uuid(seed: &str) -> String {
+    let hex = format!("{}{}", hex16(seed), hex16(&format!("{seed}:2")));
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn stable_hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::event::EventLabel;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    struct QueueSource(VecDeque<Event>);
+
+    impl EventSource for QueueSource {
+        fn next_event(&mut self) -> Option<Event> {
+            self.0.pop_front()
+        }
+    }
+
+    fn cloudtrail(time: &str, user_agent: &str) -> Event {
+        let mut event = test_event(
+            "cloudtrail",
+            "DescribeInstances",
+            json!({
+                "eventTime": time,
+                "eventSource": "ec2.amazonaws.com",
+                "eventName": "DescribeInstances",
+                "awsRegion": "eu-west-1",
+                "sourceIPAddress": "198.51.100.7",
+                "userAgent": user_agent,
+                "userIdentity": { "type": "IAMUser", "arn": "arn:aws:iam::123456789012:user/alice" },
+                "recipientAccountId": "123456789012",
+            }),
+        )
+        .at(time)
+        .by("arn:aws:iam::123456789012:user/alice", "IAMUser")
+        .with_ip("198.51.100.7")
+        .in_account("123456789012");
+        event.envelope.actor.name = Some("alice".to_string());
+        event.envelope.user_agent = Some(user_agent.to_string());
+        event.envelope.session_id = Some("console-1".to_string());
+        event.label = Some(EventLabel {
+            scenario_id: "console_takeover".to_string(),
+            technique_ids: vec!["T1078.004".to_string()],
+        });
+        event
+    }
+
+    #[test]
+    fn console_activity_is_preceded_by_web_requests_from_the_same_client() {
+        let browser = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+        let events = VecDeque::from([
+            cloudtrail("2026-02-06T00:00:10.000Z", browser),
+            cloudtrail("2026-02-06T00:00:11.000Z", "aws-cli/2.15.0"),
+            cloudtrail("2026-02-06T00:05:00.000Z", browser),
+        ]);
+        let config = AlbAccessLogConfig {
+            visit_rate: Some(1.0),
+            include_cloudtrail: Some(false),
+            ..AlbAccessLogConfig::default()
+        };
+        let mut source =
+            AlbAccessLogSource::new(Box::new(QueueSource(events)), &config, Some(9)).unwrap();
+        let events = std::iter::from_fn(|| source.next_event()).collect::<Vec<_>>();
+        assert!(!events.is_empty());
+        assert!(events
+            .windows(2)
+            .all(|pair| pair[0].envelope.timestamp <= pair[1].envelope.timestamp));
+
+        let mut pages = 0;
+        for event in &events {
+            assert_eq!(event.envelope.source, "alb_access_log");
+            assert!(event.envelope.timestamp.as_str() <= "2026-02-06T00:05:00.000Z");
+            assert_eq!(
+                event.label.as_ref().unwrap().scenario_id,
+                "console_takeover"
+            );
+            let record: AlbAccessLogRecord = serde_json::from_value(event.payload.clone()).unwrap();
+            assert_eq!(record.client_ip, "198.51.100.7");
+            assert_eq!(record.user_agent.as_deref(), Some(browser));
+            assert_eq!(
+                record.region_and_account(),
+                Some(("eu-west-1", "123456789012"))
+            );
+            assert!(record.elb.starts_with("app/internal-portal/"));
+            if record.matched_rule_priority == "0" {
+                pages += 1;
+            }
+            let line = record.line();
+            assert!(line.starts_with(&format!("h2 {} app/internal-portal/", record.time)));
+            assert!(line.contains(&format!(
+                " \"{} https://portal.example.com:443",
+                record.request_verb
+            )));
+            assert!(line.ends_with(&record.conn_trace_id));
+        }
+        // One page load per console event with a browser user agent.
+        assert_eq!(pages, 2);
+
+        assert!(AlbAccessLogSource::new(
+            Box::new(QueueSource(VecDeque::new())),
+            &AlbAccessLogConfig {
+                load_balancer: Some("-bad".to_string()),
+                ..AlbAccessLogConfig::default()
+            },
+            None,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+/// One Application Load Balancer access log entry.
+///
+/// Serialized with one JSON key per log field, named like the columns of the
+/// AWS-documented Athena table; [`AlbAccessLogRecord::line`] renders the
+/// space-delimited text form the load balancer delivers to S3.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlbAccessLogRecord {
+    /// Listener type: `http`, `https`, `h2`, `grpcs`, `ws`, or `wss`.
+    #[serde(rename = "type")]
+    pub request_type: String,
+    /// Time the response was sent, e.g. `2026-02-06T00:00:38.186641Z`.
+    pub time: String,
+    /// Load balancer resource ID, `app/<name>/<id>`.
+    pub elb: String,
+    pub client_ip: String,
+    pub client_port: u16,
+    /// Target address, or `None` when no target was chosen.
+    pub target_ip: Option<String>,
+    pub target_port: Option<u16>,
+    /// Seconds; `-1` when the request was never dispatched.
+    pub request_processing_time: f64,
+    /// Seconds; `-1` when the target did not respond.
+    pub target_processing_time: f64,
+    /// Seconds; `-1` when the target did not respond.
+    pub response_processing_time: f64,
+    pub elb_status_code: u16,
+    /// Status the target returned, or `None` when it did not respond.
+    pub target_status_code: Option<u16>,
+    pub received_bytes: u64,
+    pub sent_bytes: u64,
+    pub request_verb: String,
+    /// Full request URL, e.g. `https://portal.example.com:443/api/v1/me`.
+    pub request_url: String,
+    pub request_proto: String,
+    pub user_agent: Option<String>,
+    pub ssl_cipher: Option<String>,
+    pub ssl_protocol: Option<String>,
+    pub target_group_arn: Option<String>,
+    /// `X-Amzn-Trace-Id` header value.
+    pub trace_id: String,
+    /// SNI host name the client sent.
+    pub domain_name: Option<String>,
+    pub chosen_cert_arn: Option<String>,
+    /// Priority of the listener rule that matched; `0` is the default rule.
+    pub matched_rule_priority: String,
+    pub request_creation_time: String,
+    /// Comma-separated actions, e.g. `forward`.
+    pub actions_executed: String,
+    pub redirect_url: Option<String>,
+    pub error_reason: Option<String>,
+    /// `ip:port` of every target tried.
+    pub target_port_list: Option<String>,
+    pub target_status_code_list: Option<String>,
+    pub classification: Option<String>,
+    pub classification_reason: Option<String>,
+    /// Connection traceability ID shared by requests on one connection.
+    pub conn_trace_id: String,
+}
+
+impl AlbAccessLogRecord {
+    /// Renders the record as one access log line, using `-` for empty fields.
+    pub fn line(&self) -> String {
+        fn text(value: Option<&str>) -> &str {
+            value.filter(|value| !value.is_empty()).unwrap_or("-")
+        }
+        fn quoted(value: Option<&str>) -> String {
+            format!("\"{}\"", text(value).replace('"', "\\\""))
+        }
+        fn seconds(value: f64) -> String {
+            if value < 0.0 {
+                "-1".to_string()
+            } else {
+                format!("{value:.3}")
+            }
+        }
+        fn endpoint(ip: Option<&str>, port: Option<u16>) -> String {
+            match (ip, port) {
+                (Some(ip), Some(port)) => format!("{ip}:{port}"),
+                _ => "-".to_string(),
+            }
+        }
+
+        [
+            self.request_type.clone(),
+            self.time.clone(),
+            self.elb.clone(),
+            format!("{}:{}", self.client_ip, self.client_port),
+            endpoint(self.target_ip.as_deref(), self.target_port),
+            seconds(self.request_processing_time),
+            seconds(self.target_processing_time),
+            seconds(self.response_processing_time),
+            self.elb_status_code.to_string(),
+            self.target_status_code
+                .map_or_else(|| "-".to_string(), |code| code.to_string()),
+            self.received_bytes.to_string(),
+            self.sent_bytes.to_string(),
+            quoted(Some(&format!(
+                "{} {} {}",
+                self.request_verb, self.request_url, self.request_proto
+            ))),
+            quoted(self.user_agent.as_deref()),
+            text(self.ssl_cipher.as_deref()).to_string(),
+            text(self.ssl_protocol.as_deref()).to_string(),
+            text(self.target_group_arn.as_deref()).to_string(),
+            quoted(Some(&self.trace_id)),
+            quoted(self.domain_name.as_deref()),
+            quoted(self.chosen_cert_arn.as_deref()),
+            self.matched_rule_priority.clone(),
+            self.request_creation_time.clone(),
+            quoted(Some(&self.actions_executed)),
+            quoted(self.redirect_url.as_deref()),
+            quoted(self.error_reason.as_deref()),
+            quoted(self.target_port_list.as_deref()),
+            quoted(self.target_status_code_list.as_deref()),
+            quoted(self.classification.as_deref()),
+            quoted(self.classification_reason.as_deref()),
+            self.conn_trace_id.clone(),
+        ]
+        .join(" ")
+    }
+
+    /// Region and account from the target group ARN, when there is one.
+    pub fn region_and_account(&self) -> Option<(&str, &str)> {
+        let mut parts = self.target_group_arn.as_deref()?.split(':').skip(3);
+        Some((parts.next()?, parts.next()?))
+    }
+}
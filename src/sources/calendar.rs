@@ -0,0 +1,93 @@
+use crate::core::event::Event;
+use crate::core::traffic::TrafficCalendar;
+use crate::core::traits::EventSource;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::sync::{Arc, RwLock};
+
+/// Calendar a [`CalendarEventSource`] reads, replaceable while it runs; `None`
+/// keeps every event.
+pub type SharedCalendar = Arc<RwLock<Option<TrafficCalendar>>>;
+
+/// Event source that applies a traffic calendar to a generated stream.
+///
+/// Each actor is either active or idle for a whole UTC day, chosen by a stable
+/// hash of the actor, the date, and the seed, so quiet days have fewer people
+/// working rather than broken sessions. Labeled attack and scenario events are
+/// always kept.
+pub struct CalendarEventSource {
+    source: Box<dyn EventSource>,
+    calendar: SharedCalendar,
+    salt: u64,
+}
+
+impl CalendarEventSource {
+    pub fn new(source: Box<dyn EventSource>, calendar: TrafficCalendar, seed: Option<u64>) -> Self {
+        Self::shared(source, Arc::new(RwLock::new(Some(calendar))), seed)
+    }
+
+    /// Follows `calendar`, so a live config reload can swap it mid-run.
+    pub fn shared(
+        source: Box<dyn EventSource>,
+        calendar: SharedCalendar,
+        seed: Option<u64>,
+    ) -> Self {
+        Self {
+            source,
+            calendar,
+            salt: seed.unwrap_or(0),
+        }
+    }
+
+    fn keeps(&self, event: &Event) -> bool {
+        if event.label.is_some() {
+            return true;
+        }
+        let Some(date) = event_date(&event.envelope.timestamp) else {
+            return true;
+        };
+        let calendar = self
+            .calendar
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(calendar) = calendar.as_ref() else {
+            return true;
+        };
+        let fraction = calendar.keep_fraction(date);
+        fraction >= 1.0 || day_point(self.salt, &event.envelope.actor.id, date) < fraction
+    }
+}
+
+impl EventSource for CalendarEventSource {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            let event = self.source.next_event()?;
+            if self.keeps(&event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+fn event_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|time| time.with_timezone(&Utc).date_naive())
+}
+
+/// Stable point in [0, 1) for an actor on a date.
+fn day_point(salt: u64, actor_id: &str, date: NaiveDate) -> f64 {
+    let mut hash = 0xcbf29ce484222325_u64 ^ salt;
+    for byte in actor_id
+        .as_bytes()
+        .iter()
+        .chain(date.to_string().as_bytes())
+    {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    // Mix the trailing date bytes into the high bits.
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    (hash >> 11) as f64 / (1_u64 << 53) as f64
+}
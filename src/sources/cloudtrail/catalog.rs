@@ -1,7 +1,11 @@
+use super::data_events::DataEventsError;
 use crate::core::config::CloudTrailSourceConfig;
 use rand::distributions::WeightedIndex;
 use rand::prelude::*;
-use std::collections::HashMap;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct WeightedEvent {
@@ -15,6 +19,13 @@ pub enum CatalogError {
     InvalidWeight { name: String, weight: f64 },
     WeightedIndex(rand::distributions::WeightedError),
     Population(String),
+    Catalog(String),
+    DataEvents(DataEventsError),
+    ErrorProfiles(String),
+    Arrival(String),
+    ResourcePool(String),
+    Narratives(String),
+    UnseededIds,
 }
 
 impl std::fmt::Display for CatalogError {
@@ -26,12 +37,151 @@ impl std::fmt::Display for CatalogError {
             }
             CatalogError::WeightedIndex(err) => write!(f, "invalid event weights: {err}"),
             CatalogError::Population(err) => write!(f, "actor population error: {err}"),
+            CatalogError::Catalog(err) => write!(f, "event catalog error: {err}"),
+            CatalogError::DataEvents(err) => write!(f, "{err}"),
+            CatalogError::ErrorProfiles(err) => write!(f, "invalid error profiles: {err}"),
+            CatalogError::Arrival(err) => write!(f, "invalid arrival config: {err}"),
+            CatalogError::ResourcePool(err) => write!(f, "invalid resource_pool: {err}"),
+            CatalogError::Narratives(err) => write!(f, "invalid narratives: {err}"),
+            CatalogError::UnseededIds => write!(f, "deterministic_ids requires a seed"),
         }
     }
 }
 
 impl std::error::Error for CatalogError {}
 
+/// User-supplied CloudTrail event catalog loaded from YAML, JSON, or TOML.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CustomCatalog {
+    /// Event definitions added to (or overriding) the built-in event set.
+    pub events: Vec<CatalogEventDefinition>,
+    /// Transition weights keyed by role/service profile, then previous event.
+    ///
+    /// The previous-event key is an event name, `start` for the first event of a
+    /// session, or `*` as a fallback for any previous event.
+    pub transitions: BTreeMap<String, BTreeMap<String, BTreeMap<String, f64>>>,
+}
+
+/// One event definition from a custom catalog.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogEventDefinition {
+    /// CloudTrail `eventName`.
+    pub name: String,
+    /// CloudTrail `eventSource`, e.g. `secretsmanager.amazonaws.com`.
+    pub event_source: String,
+    /// CloudTrail `readOnly` flag.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Base selection weight.
+    #[serde(default = "default_event_weight")]
+    pub weight: f64,
+    /// Optional `eventType` override (defaults to `AwsApiCall`).
+    #[serde(default)]
+    pub event_type: Option<String>,
+    /// `requestParameters` template with `{{placeholder}}` substitution.
+    #[serde(default)]
+    pub request_parameters: Option<Value>,
+    /// `responseElements` template with `{{placeholder}}` substitution.
+    #[serde(default)]
+    pub response_elements: Option<Value>,
+}
+
+fn default_event_weight() -> f64 {
+    1.0
+}
+
+impl CustomCatalog {
+    /// Loads a catalog file, picking the parser from the file extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| CatalogError::Catalog(format!("{}: {err}", path.display())))?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let catalog = match extension.as_str() {
+            "json" => Self::from_json(&contents),
+            "toml" => {
+                toml::from_str(&contents).map_err(|err| CatalogError::Catalog(err.to_string()))
+            }
+            _ => Self::from_yaml(&contents),
+        }
+        .map_err(|err| CatalogError::Catalog(format!("{}: {err}", path.display())))?;
+        Ok(catalog)
+    }
+
+    /// Parses a catalog from YAML.
+    pub fn from_yaml(contents: &str) -> Result<Self, CatalogError> {
+        let catalog: Self =
+            serde_yaml::from_str(contents).map_err(|err| CatalogError::Catalog(err.to_string()))?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Parses a catalog from JSON.
+    pub fn from_json(contents: &str) -> Result<Self, CatalogError> {
+        let catalog: Self =
+            serde_json::from_str(contents).map_err(|err| CatalogError::Catalog(err.to_string()))?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Returns the transition weights for a profile after `last` (or at session start).
+    pub fn transition_weights(&self, profile: &str, last: Option<&str>) -> Vec<(String, f64)> {
+        let Some(table) = self.transitions.get(profile) else {
+            return Vec::new();
+        };
+        let row = table
+            .get(last.unwrap_or("start"))
+            .or_else(|| table.get("*"));
+        row.map(|weights| {
+            weights
+                .iter()
+                .filter(|(_, weight)| weight.is_finite() && **weight > 0.0)
+                .map(|(name, weight)| (name.clone(), *weight))
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    fn validate(&self) -> Result<(), CatalogError> {
+        for event in &self.events {
+            if event.name.trim().is_empty() {
+                return Err(CatalogError::Catalog(
+                    "event name must not be empty".to_string(),
+                ));
+            }
+            if event.event_source.trim().is_empty() {
+                return Err(CatalogError::Catalog(format!(
+                    "event {} is missing event_source",
+                    event.name
+                )));
+            }
+            if !event.weight.is_finite() || event.weight <= 0.0 {
+                return Err(CatalogError::InvalidWeight {
+                    name: event.name.clone(),
+                    weight: event.weight,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads the custom catalog referenced by `catalog_path`, if any.
+pub fn load_config_catalog(
+    config: &CloudTrailSourceConfig,
+) -> Result<Option<CustomCatalog>, CatalogError> {
+    config
+        .catalog_path
+        .as_ref()
+        .map(CustomCatalog::load)
+        .transpose()
+}
+
 #[derive(Debug)]
 pub struct EventSelector {
     events: Vec<WeightedEvent>,
@@ -67,6 +217,15 @@ impl EventSelector {
 
 pub fn resolve_event_weights(
     config: &CloudTrailSourceConfig,
+) -> Result<Vec<WeightedEvent>, CatalogError> {
+    let catalog = load_config_catalog(config)?;
+    merge_event_weights(config, catalog.as_ref())
+}
+
+/// Resolves event weights from the curated set plus an already-loaded custom catalog.
+pub fn merge_event_weights(
+    config: &CloudTrailSourceConfig,
+    catalog: Option<&CustomCatalog>,
 ) -> Result<Vec<WeightedEvent>, CatalogError> {
     let mut events = HashMap::<String, f64>::new();
 
@@ -76,6 +235,12 @@ pub fn resolve_event_weights(
         }
     }
 
+    if let Some(catalog) = catalog {
+        for event in &catalog.events {
+            events.insert(event.name.clone(), event.weight);
+        }
+    }
+
     let mut resolved = Vec::with_capacity(events.len());
     for (name, weight) in events {
         if !weight.is_finite() || weight <= 0.0 {
@@ -144,12 +309,121 @@ mod tests {
             curated: true,
             actor_population_path: None,
             identity_registry_path: None,
+            actor_selector: None,
+            arrival: None,
             baseline_source_ips: None,
             regions: None,
             region_distribution: None,
+            catalog_path: None,
+            service_call_graph: false,
+            web_identity: None,
+            entra_federation: None,
+            narratives: None,
+            s3_access_logs: None,
+            resolver_query_logs: None,
+            alb_access_logs: None,
+            data_events: None,
+            resource_pool: None,
+            credential_takeovers: Vec::new(),
+            actor_injections: Vec::new(),
+            error_profiles: None,
+            error_surges: Vec::new(),
+            api_storms: Vec::new(),
+            rate_limits: Vec::new(),
+            policy_remediations: Vec::new(),
+            impossible_travels: Vec::new(),
+            credential_rotation: None,
+            deterministic_ids: false,
+            linked_sessions: false,
+            session_correlation: 1.0,
         };
 
         let resolved = resolve_event_weights(&config).expect("curated events");
         assert!(resolved.iter().any(|event| event.name == "ConsoleLogin"));
     }
+
+    #[test]
+    fn custom_catalog_extends_curated_events() {
+        let catalog = CustomCatalog::from_yaml(
+            r#"
+events:
+  - name: GetSecretValue
+    event_source: secretsmanager.amazonaws.com
+    read_only: true
+    weight: 0.7
+    request_parameters:
+      secretId: "arn:aws:secretsmanager:{{region}}:{{account_id}}:secret:app"
+  - name: PutObject
+    event_source: s3.amazonaws.com
+    weight: 3.0
+transitions:
+  developer:
+    start:
+      GetSecretValue: 2.0
+    "*":
+      PutObject: 1.0
+"#,
+        )
+        .expect("catalog");
+        let config = CloudTrailSourceConfig {
+            curated: true,
+            actor_population_path: None,
+            identity_registry_path: None,
+            actor_selector: None,
+            arrival: None,
+            baseline_source_ips: None,
+            regions: None,
+            region_distribution: None,
+            catalog_path: None,
+            service_call_graph: false,
+            web_identity: None,
+            entra_federation: None,
+            narratives: None,
+            s3_access_logs: None,
+            resolver_query_logs: None,
+            alb_access_logs: None,
+            data_events: None,
+            resource_pool: None,
+            credential_takeovers: Vec::new(),
+            actor_injections: Vec::new(),
+            error_profiles: None,
+            error_surges: Vec::new(),
+            api_storms: Vec::new(),
+            rate_limits: Vec::new(),
+            policy_remediations: Vec::new(),
+            impossible_travels: Vec::new(),
+            credential_rotation: None,
+            deterministic_ids: false,
+            linked_sessions: false,
+            session_correlation: 1.0,
+        };
+
+        let resolved = merge_event_weights(&config, Some(&catalog)).expect("events");
+        let weight_of = |name: &str| {
+            resolved
+                .iter()
+                .find(|event| event.name == name)
+                .map(|event| event.weight)
+        };
+        assert_eq!(weight_of("GetSecretValue"), Some(0.7));
+        assert_eq!(weight_of("PutObject"), Some(3.0));
+        assert!(weight_of("ConsoleLogin").is_some());
+
+        assert_eq!(
+            catalog.transition_weights("developer", None),
+            vec![("GetSecretValue".to_string(), 2.0)]
+        );
+        assert_eq!(
+            catalog.transition_weights("developer", Some("ListBuckets")),
+            vec![("PutObject".to_string(), 1.0)]
+        );
+        assert!(catalog.transition_weights("admin", None).is_empty());
+    }
+
+    #[test]
+    fn custom_catalog_rejects_missing_event_source() {
+        let err = CustomCatalog::from_json(r#"{"events":[{"name":"Foo","event_source":""}]}"#)
+            .expect_err("invalid catalog");
+        assert!(matches!(err, CatalogError::Catalog(_)));
+    }
 }
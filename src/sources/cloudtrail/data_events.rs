@@ -0,0 +1,447 @@
+//! CloudTrail data events.
+//!
+//! Data events record resource-level operations (S3 object reads and writes,
+//! Lambda invocations, DynamoDB item access). Real trails log them at far
+//! higher volume than management events, in bursts: one job reads hundreds of
+//! objects from a bucket or hammers a table. Here every management call may
+//! start a burst of data events by the same actor against one resource, and
+//! the total is capped at `max_share` of the CloudTrail stream.
+
+use super::model::{CloudTrailEvent, Resource};
+use super::resources::ResourcePool;
+use super::templates::{
+    base_event, bucket_name, random_alpha, ActorContext, BaseFields, ErrorProfile,
+};
+use crate::core::config::DataEventsConfig;
+use chrono::{DateTime, Duration, Utc};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde_json::json;
+
+const DEFAULT_MAX_SHARE: f64 = 0.8;
+const DEFAULT_BURST_SIZE: u32 = 20;
+/// Chance that a human's management call starts a burst; service calls always do.
+const HUMAN_BURST_CHANCE: f64 = 0.25;
+
+const FUNCTIONS: [&str; 4] = [
+    "order-processor",
+    "thumbnail-resizer",
+    "api-authorizer",
+    "etl-loader",
+];
+const TABLES: [&str; 4] = ["orders", "sessions", "user-profiles", "inventory"];
+
+/// Service a data event burst targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataService {
+    S3,
+    Lambda,
+    DynamoDb,
+}
+
+impl DataService {
+    pub const ALL: [DataService; 3] = [DataService::S3, DataService::Lambda, DataService::DynamoDb];
+
+    /// Key used in `data_events.services`.
+    pub fn key(self) -> &'static str {
+        match self {
+            DataService::S3 => "s3",
+            DataService::Lambda => "lambda",
+            DataService::DynamoDb => "dynamodb",
+        }
+    }
+
+    fn default_weight(self) -> f64 {
+        match self {
+            DataService::S3 => 0.7,
+            DataService::Lambda | DataService::DynamoDb => 0.15,
+        }
+    }
+
+    /// Operations with relative weights.
+    fn operations(self) -> &'static [(&'static str, f64)] {
+        match self {
+            DataService::S3 => &[
+                ("GetObject", 0.6),
+                ("PutObject", 0.25),
+                ("HeadObject", 0.1),
+                ("DeleteObject", 0.05),
+            ],
+            DataService::Lambda => &[("Invoke", 1.0)],
+            DataService::DynamoDb => &[
+                ("GetItem", 0.4),
+                ("Query", 0.25),
+                ("PutItem", 0.2),
+                ("UpdateItem", 0.1),
+                ("DeleteItem", 0.05),
+            ],
+        }
+    }
+
+    /// Range of milliseconds between consecutive events in a burst.
+    fn gap_ms(self) -> (i64, i64) {
+        match self {
+            DataService::S3 => (20, 400),
+            DataService::Lambda => (100, 2_000),
+            DataService::DynamoDb => (5, 100),
+        }
+    }
+}
+
+/// Error for an invalid `data_events` table.
+#[derive(Debug)]
+pub struct DataEventsError(String);
+
+impl std::fmt::Display for DataEventsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid data_events config: {}", self.0)
+    }
+}
+
+impl std::error::Error for DataEventsError {}
+
+/// In-flight burst of data events by one actor against one resource.
+#[derive(Debug, Clone)]
+pub struct DataBurst {
+    pub actor_index: usize,
+    pub next_at: DateTime<Utc>,
+    pub service: DataService,
+    /// Bucket, function name, or table name.
+    pub resource: String,
+    pub region: String,
+    remaining: u32,
+}
+
+/// Schedules data event bursts and keeps them under the configured share.
+pub struct DataEventScheduler {
+    max_share: f64,
+    burst_size: u32,
+    services: WeightedIndex<f64>,
+    bursts: Vec<DataBurst>,
+    management: u64,
+    /// Data events emitted or still queued in bursts.
+    data: u64,
+}
+
+impl DataEventScheduler {
+    pub fn new(config: &DataEventsConfig) -> Result<Self, DataEventsError> {
+        let max_share = config.max_share.unwrap_or(DEFAULT_MAX_SHARE);
+        if !(0.0..1.0).contains(&max_share) {
+            return Err(DataEventsError(format!(
+                "max_share must be in [0, 1), got {max_share}"
+            )));
+        }
+        let burst_size = config.burst_size.unwrap_or(DEFAULT_BURST_SIZE);
+        if burst_size == 0 {
+            return Err(DataEventsError(
+                "burst_size must be greater than 0".to_string(),
+            ));
+        }
+        let configured = config.services.clone().unwrap_or_default();
+        if let Some(key) = configured
+            .keys()
+            .find(|key| !DataService::ALL.iter().any(|service| service.key() == *key))
+        {
+            return Err(DataEventsError(format!(
+                "unknown service {key}; expected s3, lambda, or dynamodb"
+            )));
+        }
+        let weights = DataService::ALL.iter().map(|service| {
+            if config.services.is_some() {
+                configured.get(service.key()).copied().unwrap_or(0.0)
+            } else {
+                service.default_weight()
+            }
+        });
+        let services = WeightedIndex::new(weights)
+            .map_err(|err| DataEventsError(format!("services: {err}")))?;
+        Ok(Self {
+            max_share,
+            burst_size,
+            services,
+            bursts: Vec::new(),
+            management: 0,
+            data: 0,
+        })
+    }
+
+    /// Time of the earliest pending data event.
+    pub fn next_at(&self) -> Option<DateTime<Utc>> {
+        self.bursts.iter().map(|burst| burst.next_at).min()
+    }
+
+    /// Records a management event and maybe starts a burst after it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_management(
+        &mut self,
+        actor_index: usize,
+        account_id: &str,
+        region: &str,
+        now: DateTime<Utc>,
+        service_actor: bool,
+        pool: Option<&mut ResourcePool>,
+        rng: &mut impl Rng,
+    ) {
+        self.management += 1;
+        if self
+            .bursts
+            .iter()
+            .any(|burst| burst.actor_index == actor_index)
+            || (!service_actor && !rng.gen_bool(HUMAN_BURST_CHANCE))
+        {
+            return;
+        }
+        let allowed = (self.management as f64 * self.max_share / (1.0 - self.max_share)) as u64;
+        let budget = allowed.saturating_sub(self.data);
+        let size = u64::from(rng.gen_range(1..=self.burst_size * 2 - 1)).min(budget) as u32;
+        if size == 0 {
+            return;
+        }
+        self.data += u64::from(size);
+        let service = DataService::ALL[self.services.sample(rng)];
+        let resource = match service {
+            DataService::S3 => match pool {
+                Some(pool) => pool.bucket(account_id, rng),
+                None => bucket_name(account_id, rng),
+            },
+            DataService::Lambda => FUNCTIONS[rng.gen_range(0..FUNCTIONS.len())].to_string(),
+            DataService::DynamoDb => TABLES[rng.gen_range(0..TABLES.len())].to_string(),
+        };
+        self.bursts.push(DataBurst {
+            actor_index,
+            next_at: now + gap(service, rng),
+            service,
+            resource,
+            region: region.to_string(),
+            remaining: size,
+        });
+    }
+
+    /// Takes the next event of the earliest burst if it is due by `until`.
+    pub fn next_due(
+        &mut self,
+        until: Option<DateTime<Utc>>,
+        rng: &mut impl Rng,
+    ) -> Option<DataBurst> {
+        let (index, burst) = self
+            .bursts
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, burst)| burst.next_at)?;
+        if until.is_some_and(|until| burst.next_at > until) {
+            return None;
+        }
+        let due = burst.clone();
+        let burst = &mut self.bursts[index];
+        burst.remaining -= 1;
+        if burst.remaining == 0 {
+            self.bursts.swap_remove(index);
+        } else {
+            burst.next_at += gap(burst.service, rng);
+        }
+        Some(due)
+    }
+}
+
+fn gap(service: DataService, rng: &mut impl Rng) -> Duration {
+    let (min, max) = service.gap_ms();
+    Duration::milliseconds(rng.gen_range(min..=max))
+}
+
+/// Builds one data event of `burst` for `actor`.
+pub fn build_data_event(
+    burst: &DataBurst,
+    actor: &ActorContext,
+    rng: &mut impl Rng,
+    event_time: &str,
+) -> CloudTrailEvent {
+    let operations = burst.service.operations();
+    let index = WeightedIndex::new(operations.iter().map(|(_, weight)| *weight))
+        .map(|index| index.sample(rng))
+        .unwrap_or(0);
+    let operation = operations[index].0;
+    let account_id = actor.account_id.clone();
+    let region = actor.region.clone();
+    let base = BaseFields::new(actor, rng, event_time);
+    let mut event = match burst.service {
+        DataService::S3 => {
+            s3_object_event(base, operation, &burst.resource, &account_id, &region, rng)
+        }
+        DataService::Lambda => lambda_invoke(base, &burst.resource, &account_id, &region),
+        DataService::DynamoDb => {
+            dynamodb_item_event(base, operation, &burst.resource, &account_id, &region, rng)
+        }
+    };
+    event.event_category = Some("Data".to_string());
+    event.management_event = Some(false);
+    event
+}
+
+/// Error recorded for failed data events unless error profiles override it.
+pub fn data_error_profile() -> ErrorProfile {
+    ErrorProfile {
+        code: "AccessDenied".to_string(),
+        message: "Access Denied".to_string(),
+    }
+}
+
+fn s3_object_event(
+    base: BaseFields,
+    operation: &str,
+    bucket: &str,
+    account_id: &str,
+    region: &str,
+    rng: &mut impl Rng,
+) -> CloudTrailEvent {
+    let read_only = matches!(operation, "GetObject" | "HeadObject");
+    let key = format!(
+        "data/{}/part-{:05}-{}.parquet",
+        random_alpha(rng, 4).to_lowercase(),
+        rng.gen_range(0..100_000),
+        random_alpha(rng, 8).to_lowercase()
+    );
+    let size = rng.gen_range(512..=64 * 1024 * 1024_u64);
+    let (bytes_in, bytes_out) = match operation {
+        "GetObject" => (0, size),
+        "PutObject" => (size, 0),
+        _ => (0, 0),
+    };
+    let mut event = base_event(base, "s3.amazonaws.com", operation, Some(read_only));
+    event.request_parameters = Some(json!({
+        "bucketName": bucket,
+        "Host": format!("{bucket}.s3.{region}.amazonaws.com"),
+        "key": key,
+    }));
+    if operation == "PutObject" {
+        event.response_elements = Some(json!({
+            "x-amz-server-side-encryption": "AES256",
+        }));
+    }
+    event.additional_event_data = Some(json!({
+        "SignatureVersion": "SigV4",
+        "CipherSuite": "TLS_AES_128_GCM_SHA256",
+        "bytesTransferredIn": bytes_in,
+        "bytesTransferredOut": bytes_out,
+        "AuthenticationMethod": "AuthHeader",
+        "x-amz-id-2": random_alpha(rng, 56),
+    }));
+    event.resources = Some(vec![
+        Resource {
+            account_id: None,
+            resource_type: "AWS::S3::Object".to_string(),
+            arn: format!("arn:aws:s3:::{bucket}/{key}"),
+        },
+        Resource {
+            account_id: Some(account_id.to_string()),
+            resource_type: "AWS::S3::Bucket".to_string(),
+            arn: format!("arn:aws:s3:::{bucket}"),
+        },
+    ]);
+    event
+}
+
+fn lambda_invoke(
+    base: BaseFields,
+    function: &str,
+    account_id: &str,
+    region: &str,
+) -> CloudTrailEvent {
+    let arn = format!("arn:aws:lambda:{region}:{account_id}:function:{function}");
+    let mut event = base_event(base, "lambda.amazonaws.com", "Invoke", Some(false));
+    event.request_parameters = Some(json!({
+        "functionName": arn,
+        "invocationType": "RequestResponse",
+    }));
+    event.additional_event_data = Some(json!({
+        "functionVersion": format!("{arn}:$LATEST"),
+    }));
+    event.resources = Some(vec![Resource {
+        account_id: Some(account_id.to_string()),
+        resource_type: "AWS::Lambda::Function".to_string(),
+        arn,
+    }]);
+    event
+}
+
+fn dynamodb_item_event(
+    base: BaseFields,
+    operation: &str,
+    table: &str,
+    account_id: &str,
+    region: &str,
+    rng: &mut impl Rng,
+) -> CloudTrailEvent {
+    let read_only = matches!(operation, "GetItem" | "Query");
+    let arn = format!("arn:aws:dynamodb:{region}:{account_id}:table/{table}");
+    let mut event = base_event(base, "dynamodb.amazonaws.com", operation, Some(read_only));
+    let key = json!({ "pk": format!("{}#{}", table, random_alpha(rng, 12).to_lowercase()) });
+    event.request_parameters = Some(match operation {
+        "Query" => json!({
+            "tableName": table,
+            "keyConditionExpression": "pk = :pk",
+            "limit": 100,
+        }),
+        "PutItem" => json!({ "tableName": table, "key": key, "returnValues": "NONE" }),
+        _ => json!({ "tableName": table, "key": key }),
+    });
+    event.resources = Some(vec![Resource {
+        account_id: Some(account_id.to_string()),
+        resource_type: "AWS::DynamoDB::Table".to_string(),
+        arn,
+    }]);
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn caps_data_events_at_max_share() {
+        let config = DataEventsConfig {
+            max_share: Some(0.75),
+            burst_size: Some(50),
+            services: Some([("dynamodb".to_string(), 1.0)].into()),
+        };
+        let mut scheduler = DataEventScheduler::new(&config).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let start = Utc::now();
+        let mut management = 0_u64;
+        let mut data = 0_u64;
+        for step in 0..200 {
+            let now = start + Duration::seconds(step);
+            while scheduler.next_due(Some(now), &mut rng).is_some() {
+                data += 1;
+            }
+            scheduler.record_management(0, "123456789012", "us-east-1", now, true, None, &mut rng);
+            management += 1;
+        }
+        while let Some(burst) = scheduler.next_due(None, &mut rng) {
+            assert_eq!(burst.service, DataService::DynamoDb);
+            data += 1;
+        }
+        assert!(
+            data <= management * 3,
+            "{data} data / {management} management"
+        );
+        assert!(
+            data > management * 2,
+            "{data} data / {management} management"
+        );
+
+        for bad in [
+            DataEventsConfig {
+                max_share: Some(1.0),
+                ..DataEventsConfig::default()
+            },
+            DataEventsConfig {
+                services: Some([("sqs".to_string(), 1.0)].into()),
+                ..DataEventsConfig::default()
+            },
+        ] {
+            assert!(DataEventScheduler::new(&bad).is_err());
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Configurable CloudTrail error profiles.
+//!
+//! By default an event fails at its actor's error rate with the single code
+//! from `default_error_profile`. `error_profiles` overrides the rate and the
+//! possible codes per event name (with `*` as the fallback), and `error_surge`
+//! windows raise the error rate for matching events while they are active,
+//! e.g. to simulate a throttling incident or a broken deployment.
+
+use super::templates::ErrorProfile;
+use crate::core::config::{CloudTrailSourceConfig, ErrorCodeConfig, EventErrorConfig};
+use chrono::{DateTime, Duration, Utc};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+/// Error selection for the CloudTrail generator.
+#[derive(Debug, Clone)]
+pub struct ErrorModel {
+    events: HashMap<String, EventErrors>,
+    fallback: Option<EventErrors>,
+    surges: Vec<Surge>,
+}
+
+#[derive(Debug, Clone)]
+struct EventErrors {
+    rate: Option<f64>,
+    codes: Option<ErrorCodes>,
+}
+
+#[derive(Debug, Clone)]
+struct ErrorCodes {
+    profiles: Vec<ErrorProfile>,
+    index: WeightedIndex<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct Surge {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    rate: f64,
+    events: Option<HashSet<String>>,
+    codes: Option<ErrorCodes>,
+}
+
+impl ErrorModel {
+    /// Builds the model from the source config, or `None` when neither error
+    /// profiles nor surges are configured.
+    pub fn from_config(
+        config: &CloudTrailSourceConfig,
+        start_time: DateTime<Utc>,
+    ) -> Result<Option<Self>, String> {
+        if config.error_profiles.is_none() && config.error_surges.is_empty() {
+            return Ok(None);
+        }
+
+        let mut events = HashMap::new();
+        let mut fallback = None;
+        for (name, profile) in config.error_profiles.iter().flatten() {
+            let errors =
+                EventErrors::new(profile).map_err(|err| format!("error_profiles.{name}: {err}"))?;
+            if name == "*" {
+                fallback = Some(errors);
+            } else {
+                events.insert(name.clone(), errors);
+            }
+        }
+
+        let mut surges = Vec::with_capacity(config.error_surges.len());
+        for (index, surge) in config.error_surges.iter().enumerate() {
+            let start = match &surge.start_time {
+                Some(raw) => DateTime::parse_from_rfc3339(raw)
+                    .map_err(|_| format!("error_surge[{index}]: invalid start_time: {raw}"))?
+                    .with_timezone(&Utc),
+                None => start_time + Duration::seconds(surge.offset_seconds.unwrap_or(0)),
+            };
+            if surge.duration_seconds <= 0 {
+                return Err(format!(
+                    "error_surge[{index}]: duration_seconds must be greater than 0"
+                ));
+            }
+            check_rate(surge.rate).map_err(|err| format!("error_surge[{index}]: {err}"))?;
+            surges.push(Surge {
+                start,
+                end: start + Duration::seconds(surge.duration_seconds),
+                rate: surge.rate,
+                events: surge
+                    .events
+                    .as_ref()
+                    .map(|names| names.iter().cloned().collect()),
+                codes: ErrorCodes::new(&surge.errors)
+                    .map_err(|err| format!("error_surge[{index}]: {err}"))?,
+            });
+        }
+
+        Ok(Some(Self {
+            events,
+            fallback,
+            surges,
+        }))
+    }
+
+    /// Returns the error rate and error for `event_name` at `now`.
+    ///
+    /// An active surge replaces the event's rate, and its codes when it has
+    /// any. Events without configured codes keep `default`.
+    pub fn resolve(
+        &self,
+        event_name: &str,
+        actor_rate: f64,
+        now: DateTime<Utc>,
+        default: Option<ErrorProfile>,
+        rng: &mut impl Rng,
+    ) -> (f64, Option<ErrorProfile>) {
+        let errors = self.events.get(event_name).or(self.fallback.as_ref());
+        let mut rate = errors.and_then(|errors| errors.rate).unwrap_or(actor_rate);
+        let mut codes = errors.and_then(|errors| errors.codes.as_ref());
+        if let Some(surge) = self
+            .surges
+            .iter()
+            .find(|surge| surge.applies(event_name, now))
+        {
+            rate = surge.rate;
+            codes = surge.codes.as_ref().or(codes);
+        }
+        let profile = match codes {
+            Some(codes) => Some(codes.pick(rng)),
+            None => default,
+        };
+        (rate, profile)
+    }
+}
+
+impl EventErrors {
+    fn new(config: &EventErrorConfig) -> Result<Self, String> {
+        if let Some(rate) = config.rate {
+            check_rate(rate)?;
+        }
+        Ok(Self {
+            rate: config.rate,
+            codes: ErrorCodes::new(&config.errors)?,
+        })
+    }
+}
+
+impl ErrorCodes {
+    fn new(configs: &[ErrorCodeConfig]) -> Result<Option<Self>, String> {
+        if configs.is_empty() {
+            return Ok(None);
+        }
+        let mut profiles = Vec::with_capacity(configs.len());
+        let mut weights = Vec::with_capacity(configs.len());
+        for config in configs {
+            if config.code.trim().is_empty() {
+                return Err("error code must not be empty".to_string());
+            }
+            let weight = config.weight.unwrap_or(1.0);
+            if !weight.is_finite() || weight <= 0.0 {
+                return Err(format!("weight for {} must be greater than 0", config.code));
+            }
+            profiles.push(ErrorProfile {
+                code: config.code.clone(),
+                message: config
+                    .message
+                    .clone()
+                    .unwrap_or_else(|| default_message(&config.code)),
+            });
+            weights.push(weight);
+        }
+        let index = WeightedIndex::new(weights).map_err(|err| err.to_string())?;
+        Ok(Some(Self { profiles, index }))
+    }
+
+    fn pick(&self, rng: &mut impl Rng) -> ErrorProfile {
+        if self.profiles.len() == 1 {
+            return self.profiles[0].clone();
+        }
+        self.profiles[self.index.sample(rng)].clone()
+    }
+}
+
+impl Surge {
+    fn applies(&self, event_name: &str, now: DateTime<Utc>) -> bool {
+        now >= self.start
+            && now < self.end
+            && self
+                .events
+                .as_ref()
+                .is_none_or(|events| events.contains(event_name))
+    }
+}
+
+fn check_rate(rate: f64) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!("rate must be between 0 and 1, got {rate}"));
+    }
+    Ok(())
+}
+
+/// Message CloudTrail records for common error codes.
+fn default_message(code: &str) -> String {
+    match code {
+        "AccessDenied" | "AccessDeniedException" => "Access denied".to_string(),
+        "ThrottlingException" | "Throttling" => "Rate exceeded".to_string(),
+        "RequestLimitExceeded" => "Request limit exceeded.".to_string(),
+        "ValidationError" | "ValidationException" => "1 validation error detected".to_string(),
+        "UnauthorizedOperation" => "You are not authorized to perform this operation.".to_string(),
+        "InternalError" | "InternalFailure" | "ServiceUnavailable" => {
+            "We encountered an internal error. Please try again.".to_string()
+        }
+        _ => format!("An error occurred ({code})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn surges_override_event_profiles_inside_their_window() {
+        let config: CloudTrailSourceConfig = toml::from_str(
+            r#"
+            curated = true
+
+            [error_profiles.RunInstances]
+            rate = 0.2
+            error = [
+              { code = "ValidationError" },
+              { code = "AccessDenied", weight = 3 },
+            ]
+
+            [[error_surge]]
+            offset_seconds = 3600
+            duration_seconds = 600
+            rate = 0.9
+            events = ["RunInstances"]
+            error = [{ code = "ThrottlingException" }]
+            "#,
+        )
+        .unwrap();
+        let start = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let model = ErrorModel::from_config(&config, start).unwrap().unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let (rate, profile) = model.resolve("ListBuckets", 0.03, start, None, &mut rng);
+        assert_eq!(rate, 0.03);
+        assert!(profile.is_none());
+
+        let codes = (0..200)
+            .map(|_| model.resolve("RunInstances", 0.03, start, None, &mut rng))
+            .map(|(rate, profile)| {
+                assert_eq!(rate, 0.2);
+                profile.unwrap().code
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            codes,
+            HashSet::from(["ValidationError".to_string(), "AccessDenied".to_string()])
+        );
+
+        let during = start + Duration::seconds(3700);
+        let (rate, profile) = model.resolve("RunInstances", 0.03, during, None, &mut rng);
+        assert_eq!(rate, 0.9);
+        assert_eq!(profile.unwrap().code, "ThrottlingException");
+        let (rate, _) = model.resolve("ListBuckets", 0.03, during, None, &mut rng);
+        assert_eq!(rate, 0.03);
+    }
+}
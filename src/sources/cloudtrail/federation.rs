@@ -0,0 +1,634 @@
+//! Entra ID federation for human console sign-ins.
+//!
+//! With `entra_federation` set, a human's console sign-in starts at Entra ID:
+//! an interactive sign-in to the AWS enterprise application, then the STS call
+//! that trades the SAML assertion (or OIDC token) for the role mapped from the
+//! actor's role, then `ConsoleLogin`. The rest of the console session runs
+//! under that assumed role, named after the user principal name, so the Entra
+//! sign-in, the STS call, and the console activity join on the same user.
+
+use super::generator::stable_hash;
+use super::model::{CloudTrailEvent, WebIdFederationData};
+use super::templates::{derived_uuid, ActorContext};
+use super::workflow::RoleSession;
+use crate::core::actors::{ActorRole, ActorSeed};
+use crate::core::config::{EntraFederationConfig, FederationProtocol, OktaRiskConfig};
+use crate::core::event::{Actor, Event, EventEnvelope, Geo, Outcome, Target};
+use crate::sources::entra::model::{
+    EntraConditionalAccessPolicy, EntraDeviceDetail, EntraKeyValue, EntraLocation, EntraMfaDetail,
+    EntraSignInRecord, EntraSignInStatus,
+};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rand::Rng;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+const DEFAULT_APP_NAME: &str = "AWS IAM Identity Center (successor to AWS Single Sign-On)";
+const SAML_AUDIENCE: &str = "https://signin.aws.amazon.com/saml";
+/// AADSTS50126: invalid username or password.
+const INVALID_CREDENTIALS: (u32, &str) = (
+    50126,
+    "Error validating credentials due to invalid username or password.",
+);
+const WINDOWS_HELLO: &str = "Windows Hello for Business";
+/// Second factors users register, weighted toward the Authenticator app.
+const MFA_METHODS: [&str; 5] = [
+    "Mobile app notification",
+    "Mobile app notification",
+    "OATH verification code",
+    "Text message",
+    "FIDO2 security key",
+];
+/// Conditional access policy every MFA sign-in to the AWS application satisfies.
+const MFA_POLICY: &str = "Require MFA for AWS access";
+/// Risk-based policy that makes medium and high risk sign-ins step up to MFA.
+const RISK_POLICY: &str = "Require MFA for risky sign-ins";
+
+/// Entra ID tenant and AWS trust that human console sign-ins go through.
+#[derive(Debug, Clone)]
+pub struct EntraFederation {
+    protocol: FederationProtocol,
+    tenant_id: Option<String>,
+    app_name: String,
+    saml_provider: Option<String>,
+    role_arns: HashMap<String, String>,
+    risk: Option<OktaRiskConfig>,
+}
+
+/// Entra user a federated actor signs in as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FederatedUser {
+    pub user_principal_name: String,
+    pub display_name: String,
+    /// Object ID of the user, also the OIDC token subject.
+    pub object_id: String,
+    pub tenant_id: String,
+}
+
+impl EntraFederation {
+    pub fn new(config: &EntraFederationConfig) -> Self {
+        Self {
+            protocol: config.protocol.unwrap_or_default(),
+            tenant_id: config.tenant_id.clone(),
+            app_name: config
+                .app_name
+                .clone()
+                .unwrap_or_else(|| DEFAULT_APP_NAME.to_string()),
+            saml_provider: config.saml_provider.clone(),
+            role_arns: config.role_arns.clone().unwrap_or_default(),
+            risk: config.risk.clone(),
+        }
+    }
+
+    /// STS call that hands out the federated session.
+    pub fn credential_event(&self) -> &'static str {
+        match self.protocol {
+            FederationProtocol::Saml => "AssumeRoleWithSAML",
+            FederationProtocol::Oidc => "AssumeRoleWithWebIdentity",
+        }
+    }
+
+    pub fn user(&self, seed: &ActorSeed) -> FederatedUser {
+        let user_principal_name = seed.email.clone().unwrap_or_else(|| {
+            format!(
+                "{}@example.com",
+                seed.user_name.as_deref().unwrap_or(&seed.principal_id)
+            )
+        });
+        let domain = user_principal_name
+            .split_once('@')
+            .map_or("example.com", |(_, domain)| domain);
+        let tenant_id = self
+            .tenant_id
+            .clone()
+            .unwrap_or_else(|| derived_uuid(&format!("entra:tenant:{domain}")));
+        FederatedUser {
+            display_name: seed
+                .display_name
+                .clone()
+                .unwrap_or_else(|| user_principal_name.clone()),
+            object_id: derived_uuid(&format!("entra:user:{tenant_id}:{user_principal_name}")),
+            user_principal_name,
+            tenant_id,
+        }
+    }
+
+    /// Entra ID Protection risk of one sign-in. A stable share of users has an
+    /// elevated risk profile, and only their sign-ins are drawn as risky.
+    fn sign_in_risk(&self, user: &FederatedUser, rng: &mut impl Rng) -> SignInRisk {
+        let Some(risk) = &self.risk else {
+            return SignInRisk::None;
+        };
+        let profile = stable_hash(&format!("entra:risk-profile:{}", user.object_id));
+        let risky_user =
+            (profile as f64 / u64::MAX as f64) < risk.risky_actor_ratio.unwrap_or(0.05);
+        if !risky_user || !rng.gen_bool(risk.risky_sign_in_rate.unwrap_or(0.2).clamp(0.0, 1.0)) {
+            return SignInRisk::None;
+        }
+        if rng.gen_bool(risk.anonymized_ip_share.unwrap_or(0.5).clamp(0.0, 1.0)) {
+            SignInRisk::High
+        } else {
+            SignInRisk::Medium
+        }
+    }
+
+    /// Role mapped from the actor's role: the configured name or ARN, or the
+    /// Identity Center permission set role in the actor's account.
+    pub fn role_arn(&self, account_id: &str, role: ActorRole) -> String {
+        let (key, permission_set) = match role {
+            ActorRole::Admin => ("admin", "AdministratorAccess"),
+            ActorRole::Developer => ("developer", "PowerUserAccess"),
+            ActorRole::ReadOnly => ("readonly", "ReadOnlyAccess"),
+            ActorRole::Auditor => ("auditor", "SecurityAudit"),
+        };
+        match self.role_arns.get(key) {
+            Some(arn) if arn.starts_with("arn:") => arn.clone(),
+            Some(name) => format!("arn:aws:iam::{account_id}:role/{name}"),
+            None => format!(
+                "arn:aws:iam::{account_id}:role/aws-reserved/sso.amazonaws.com/AWSReservedSSO_{permission_set}_{:016x}",
+                stable_hash(&format!("{account_id}/{permission_set}"))
+            ),
+        }
+    }
+
+    /// Session the user's console activity runs under after signing in at `now`.
+    pub fn session(
+        &self,
+        seed: &ActorSeed,
+        user: &FederatedUser,
+        role: ActorRole,
+        now: DateTime<Utc>,
+    ) -> RoleSession {
+        RoleSession::for_role(
+            &self.role_arn(&seed.account_id, role),
+            &user.user_principal_name,
+            now,
+        )
+    }
+
+    /// Rewrites the caller of the STS call to the assertion or token subject.
+    pub fn apply_caller(&self, user: &FederatedUser, account_id: &str, actor: &mut ActorContext) {
+        let (principal_id, provider) = match self.protocol {
+            FederationProtocol::Saml => {
+                let qualifier = self.name_qualifier(user, account_id);
+                (
+                    format!("{qualifier}:{}", user.user_principal_name),
+                    qualifier,
+                )
+            }
+            FederationProtocol::Oidc => {
+                let provider = self.oidc_provider_arn(user, account_id);
+                (
+                    format!("{provider}:{}:{}", self.app_id(user), user.object_id),
+                    provider,
+                )
+            }
+        };
+        actor.identity_type = match self.protocol {
+            FederationProtocol::Saml => "SAMLUser",
+            FederationProtocol::Oidc => "WebIdentityUser",
+        }
+        .into();
+        actor.principal_id = principal_id.into();
+        actor.user_name = Some(match self.protocol {
+            FederationProtocol::Saml => user.user_principal_name.as_str().into(),
+            FederationProtocol::Oidc => user.object_id.as_str().into(),
+        });
+        actor.access_key_id = None;
+        actor.mfa_authenticated = false;
+        actor.session_credential_from_console = false;
+        actor.identity_provider = Some(provider);
+    }
+
+    /// Rewrites a call to run under the federated session.
+    pub fn apply_session(
+        &self,
+        session: &RoleSession,
+        user: &FederatedUser,
+        actor: &mut ActorContext,
+    ) {
+        session.apply_to(actor);
+        if self.protocol == FederationProtocol::Oidc {
+            actor.web_id_federation = Some(WebIdFederationData {
+                federated_provider: self.oidc_provider_arn(user, &session.account_id),
+                attributes: Default::default(),
+            });
+        }
+    }
+
+    /// Aligns the STS call with the session it hands out.
+    pub fn record_credentials(
+        &self,
+        session: &RoleSession,
+        user: &FederatedUser,
+        event: &mut CloudTrailEvent,
+        rng: &mut impl Rng,
+    ) {
+        session.record_assume_role(event);
+        let request = event
+            .request_parameters
+            .as_mut()
+            .and_then(|value| value.as_object_mut());
+        let response = event
+            .response_elements
+            .as_mut()
+            .and_then(|value| value.as_object_mut());
+        let (Some(request), Some(response)) = (request, response) else {
+            return;
+        };
+        match self.protocol {
+            FederationProtocol::Saml => {
+                request.insert(
+                    "sAMLAssertionID".to_string(),
+                    json!(format!("_{}", derived_uuid(&rng.gen::<u64>().to_string()))),
+                );
+                request.insert(
+                    "principalArn".to_string(),
+                    json!(self.saml_provider_arn(&session.account_id)),
+                );
+                response.insert("subject".to_string(), json!(user.user_principal_name));
+                response.insert("subjectType".to_string(), json!("persistent"));
+                response.insert(
+                    "issuer".to_string(),
+                    json!(format!("https://sts.windows.net/{}/", user.tenant_id)),
+                );
+                response.insert("audience".to_string(), json!(SAML_AUDIENCE));
+                response.insert(
+                    "nameQualifier".to_string(),
+                    json!(self.name_qualifier(user, &session.account_id)),
+                );
+            }
+            FederationProtocol::Oidc => {
+                response.insert(
+                    "subjectFromWebIdentityToken".to_string(),
+                    json!(user.object_id),
+                );
+                response.insert("audience".to_string(), json!(self.app_id(user)));
+                response.insert(
+                    "provider".to_string(),
+                    json!(self.oidc_provider_arn(user, &session.account_id)),
+                );
+            }
+        }
+    }
+
+    /// Interactive Entra sign-in to the AWS application from the caller's
+    /// browser and address.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sign_in_event(
+        &self,
+        seed: &ActorSeed,
+        user: &FederatedUser,
+        actor: &ActorContext,
+        at: DateTime<Utc>,
+        mfa_authenticated: bool,
+        succeeded: bool,
+        rng: &mut impl Rng,
+    ) -> Event {
+        let app_id = self.app_id(user);
+        let location = location_for(seed.home_location.as_deref());
+        let device_detail = device_detail(user, &actor.user_agent);
+        let mfa_detail = (succeeded && mfa_authenticated).then(|| mfa_detail(user, &device_detail));
+        let risk = self.sign_in_risk(user, rng);
+        let policies = conditional_access_policies(user, succeeded, mfa_authenticated, risk);
+        let remediated = policies
+            .iter()
+            .any(|policy| policy.display_name == RISK_POLICY);
+        let (error_code, failure_reason) = if succeeded {
+            (0, None)
+        } else {
+            (
+                INVALID_CREDENTIALS.0,
+                Some(INVALID_CREDENTIALS.1.to_string()),
+            )
+        };
+        let record = EntraSignInRecord {
+            id: derived_uuid(&format!("entra:signin:{}", rng.gen::<u64>())),
+            created_date_time: at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            user_display_name: user.display_name.clone(),
+            user_principal_name: user.user_principal_name.clone(),
+            user_id: user.object_id.clone(),
+            app_id: app_id.clone(),
+            app_display_name: self.app_name.clone(),
+            ip_address: actor.source_ip.clone(),
+            client_app_used: "Browser".to_string(),
+            user_agent: actor.user_agent.clone(),
+            correlation_id: derived_uuid(&format!("entra:correlation:{}", rng.gen::<u64>())),
+            conditional_access_status: conditional_access_status(&policies).to_string(),
+            is_interactive: true,
+            risk_detail: if remediated {
+                "userPassedMFADrivenByRiskBasedPolicy"
+            } else {
+                "none"
+            }
+            .to_string(),
+            risk_level_aggregated: if remediated { "none" } else { risk.level() }.to_string(),
+            risk_level_during_sign_in: risk.level().to_string(),
+            risk_state: match (risk, remediated) {
+                (SignInRisk::None, _) => "none",
+                (_, true) => "remediated",
+                (_, false) => "atRisk",
+            }
+            .to_string(),
+            resource_display_name: self.app_name.clone(),
+            resource_id: derived_uuid(&format!("entra:sp:{}:{}", user.tenant_id, self.app_name)),
+            resource_tenant_id: user.tenant_id.clone(),
+            home_tenant_id: user.tenant_id.clone(),
+            authentication_requirement: if mfa_authenticated {
+                "multiFactorAuthentication"
+            } else {
+                "singleFactorAuthentication"
+            }
+            .to_string(),
+            token_issuer_type: "AzureAD".to_string(),
+            status: EntraSignInStatus {
+                error_code,
+                failure_reason,
+                additional_details: mfa_detail.as_ref().map(|mfa| {
+                    if mfa.auth_method == WINDOWS_HELLO {
+                        "MFA requirement satisfied by claim in the token"
+                    } else {
+                        "MFA completed in Azure AD"
+                    }
+                    .to_string()
+                }),
+            },
+            mfa_detail,
+            authentication_processing_details: [
+                ("Legacy TLS (TLS 1.0, 1.1, 3DES)", "False"),
+                ("Is CAE Token", "False"),
+            ]
+            .into_iter()
+            .map(|(key, value)| EntraKeyValue {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect(),
+            device_detail,
+            location: location.clone(),
+            applied_conditional_access_policies: policies,
+        };
+        let envelope = EventEnvelope {
+            schema_version: "v1".to_string(),
+            timestamp: at.to_rfc3339_opts(SecondsFormat::Millis, true),
+            source: "entra_signin".to_string(),
+            event_type: "Sign-in activity".to_string(),
+            actor: Actor {
+                id: seed.id.clone().unwrap_or_else(|| seed.principal_id.clone()),
+                kind: "User".to_string(),
+                name: Some(user.user_principal_name.clone()),
+            },
+            target: Some(Target {
+                id: app_id,
+                kind: "application".to_string(),
+                name: Some(self.app_name.clone()),
+            }),
+            outcome: if succeeded {
+                Outcome::Success
+            } else {
+                Outcome::Failure
+            },
+            geo: location.country_or_region.clone().map(|country| Geo {
+                country,
+                region: location.state,
+                city: location.city,
+                lat: None,
+                lon: None,
+            }),
+            ip: Some(actor.source_ip.clone()),
+            user_agent: Some(actor.user_agent.clone()),
+            session_id: Some(record.correlation_id.clone()),
+            tenant_id: Some(user.tenant_id.clone()),
+            tenant: None,
+        };
+        Event {
+            envelope,
+            payload: record.to_value(),
+            label: None,
+        }
+    }
+
+    /// Application (client) ID of the AWS enterprise application.
+    fn app_id(&self, user: &FederatedUser) -> String {
+        derived_uuid(&format!("entra:app:{}:{}", user.tenant_id, self.app_name))
+    }
+
+    fn saml_provider_arn(&self, account_id: &str) -> String {
+        let name = self.saml_provider.clone().unwrap_or_else(|| {
+            format!(
+                "AWSSSO_{:016x}_DO_NOT_DELETE",
+                stable_hash(&format!("sso:{account_id}"))
+            )
+        });
+        format!("arn:aws:iam::{account_id}:saml-provider/{name}")
+    }
+
+    fn oidc_provider_arn(&self, user: &FederatedUser, account_id: &str) -> String {
+        format!(
+            "arn:aws:iam::{account_id}:oidc-provider/login.microsoftonline.com/{}/v2.0",
+            user.tenant_id
+        )
+    }
+
+    /// `nameQualifier` STS derives for SAML subjects: a base64 hash of the
+    /// issuer, account, and provider name (AWS uses SHA-1).
+    fn name_qualifier(&self, user: &FederatedUser, account_id: &str) -> String {
+        let provider_arn = self.saml_provider_arn(account_id);
+        let provider = provider_arn.rsplit('/').next().unwrap_or_default();
+        let digest = Sha256::digest(
+            format!(
+                "https://sts.windows.net/{}/{account_id}/{provider}",
+                user.tenant_id
+            )
+            .as_bytes(),
+        );
+        STANDARD.encode(&digest[..20])
+    }
+}
+
+/// Entra location from a `City, State, Country` home location.
+fn location_for(home_location: Option<&str>) -> EntraLocation {
+    let parts = home_location
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    let country = parts.last().map(|country| {
+        [
+            ("australia", "AU"),
+            ("singapore", "SG"),
+            ("japan", "JP"),
+            ("india", "IN"),
+            ("united kingdom", "GB"),
+            ("germany", "DE"),
+            ("united states", "US"),
+            ("brazil", "BR"),
+            ("nigeria", "NG"),
+        ]
+        .into_iter()
+        .find(|(name, _)| country.eq_ignore_ascii_case(name))
+        .map_or_else(|| country.to_string(), |(_, code)| code.to_string())
+    });
+    EntraLocation {
+        city: parts.first().map(|city| city.to_string()),
+        state: (parts.len() > 2).then(|| parts[1].to_string()),
+        country_or_region: country,
+    }
+}
+
+/// Device behind a browser user agent. Each user keeps one device per
+/// operating system, so its id, name, and posture stay the same across their
+/// sign-ins from it. Windows machines are joined to the tenant, Macs and
+/// phones registered, and Linux and a quarter of the rest are personal,
+/// unregistered devices.
+fn device_detail(user: &FederatedUser, user_agent: &str) -> EntraDeviceDetail {
+    let operating_system = operating_system(user_agent);
+    let key = stable_hash(&format!("{}:{operating_system}", user.object_id));
+    let (trust_type, name_prefix) = match operating_system {
+        "Windows10" if key.is_multiple_of(3) => ("Hybrid Azure AD joined", "WS"),
+        "Windows10" => ("Azure AD joined", "LT"),
+        "MacOs" => ("Azure AD registered", "MBP"),
+        "Ios" => ("Azure AD registered", "iPhone"),
+        "Android" => ("Azure AD registered", "Android"),
+        _ => ("", ""),
+    };
+    let managed = !trust_type.is_empty() && !(key >> 8).is_multiple_of(4);
+    if !managed {
+        return EntraDeviceDetail {
+            device_id: String::new(),
+            display_name: String::new(),
+            operating_system: operating_system.to_string(),
+            browser: browser(user_agent),
+            is_compliant: false,
+            is_managed: false,
+            trust_type: String::new(),
+        };
+    }
+    EntraDeviceDetail {
+        device_id: derived_uuid(&format!(
+            "entra:device:{}:{operating_system}",
+            user.object_id
+        )),
+        display_name: format!("{name_prefix}-{:04X}", (key >> 16) & 0xffff),
+        operating_system: operating_system.to_string(),
+        browser: browser(user_agent),
+        // A few managed devices drift out of compliance.
+        is_compliant: !(key >> 32).is_multiple_of(10),
+        is_managed: true,
+        trust_type: trust_type.to_string(),
+    }
+}
+
+/// The user's registered second factor; Windows Hello on their joined
+/// Windows machines.
+/// Entra ID Protection risk level of a sign-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignInRisk {
+    None,
+    Medium,
+    High,
+}
+
+impl SignInRisk {
+    fn level(self) -> &'static str {
+        match self {
+            SignInRisk::None => "none",
+            SignInRisk::Medium => "medium",
+            SignInRisk::High => "high",
+        }
+    }
+}
+
+/// Policies that applied to a sign-in. Conditional access runs after the
+/// first factor, so a failed password applies none. An MFA sign-in satisfies
+/// the AWS MFA policy and, when risky, the risk-based policy that remediates
+/// it; a risky single-factor sign-in is left at risk.
+fn conditional_access_policies(
+    user: &FederatedUser,
+    succeeded: bool,
+    mfa_authenticated: bool,
+    risk: SignInRisk,
+) -> Vec<EntraConditionalAccessPolicy> {
+    if !succeeded || !mfa_authenticated {
+        return Vec::new();
+    }
+    let risky = risk != SignInRisk::None;
+    [Some(MFA_POLICY), risky.then_some(RISK_POLICY)]
+        .into_iter()
+        .flatten()
+        .map(|name| EntraConditionalAccessPolicy {
+            id: derived_uuid(&format!("entra:ca:{}:{name}", user.tenant_id)),
+            display_name: name.to_string(),
+            enforced_grant_controls: vec!["Mfa".to_string()],
+            enforced_session_controls: Vec::new(),
+            result: "success".to_string(),
+        })
+        .collect()
+}
+
+/// `conditionalAccessStatus` from the applied policies: `notApplied` when
+/// none applied, `failure` when any failed, otherwise `success`.
+fn conditional_access_status(policies: &[EntraConditionalAccessPolicy]) -> &'static str {
+    if policies.is_empty() {
+        "notApplied"
+    } else if policies.iter().any(|policy| policy.result == "failure") {
+        "failure"
+    } else {
+        "success"
+    }
+}
+
+fn mfa_detail(user: &FederatedUser, device: &EntraDeviceDetail) -> EntraMfaDetail {
+    let key = stable_hash(&format!("entra:mfa:{}", user.object_id));
+    let auth_method = if device.trust_type.ends_with("joined") && key.is_multiple_of(2) {
+        WINDOWS_HELLO
+    } else {
+        MFA_METHODS[(key >> 8) as usize % MFA_METHODS.len()]
+    };
+    EntraMfaDetail {
+        auth_method: auth_method.to_string(),
+        auth_detail: (auth_method == "Text message")
+            .then(|| format!("+X XXXXXXXX{:02}", (key >> 16) % 100)),
+    }
+}
+
+fn operating_system(user_agent: &str) -> &'static str {
+    if user_agent.contains("Windows") {
+        "Windows10"
+    } else if user_agent.contains("iPhone") || user_agent.contains("iPad") {
+        "Ios"
+    } else if user_agent.contains("Mac OS X") {
+        "MacOs"
+    } else if user_agent.contains("Android") {
+        "Android"
+    } else if user_agent.contains("Linux") {
+        "Linux"
+    } else {
+        ""
+    }
+}
+
+/// Browser family and version from a user agent, e.g. `Chrome 124.0.0`.
+fn browser(user_agent: &str) -> String {
+    let version = |token: &str| {
+        user_agent.split_whitespace().find_map(|part| {
+            part.strip_prefix(token)
+                .map(|version| version.splitn(4, '.').take(3).collect::<Vec<_>>().join("."))
+        })
+    };
+    if let Some(version) = version("Edg/") {
+        format!("Edge {version}")
+    } else if let Some(version) = version("Firefox/") {
+        format!("Firefox {version}")
+    } else if let Some(version) = version("Chrome/") {
+        format!("Chrome {version}")
+    } else if let Some(version) = version("Version/") {
+        format!("Safari {version}")
+    } else {
+        "Other".to_string()
+    }
+}
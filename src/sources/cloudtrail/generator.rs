@@ -1,28 +1,118 @@
-use super::catalog::{resolve_event_weights, CatalogError, EventSelector, WeightedEvent};
-use super::templates::{build_cloudtrail_event, default_error_profile, ActorContext};
+use super::catalog::{
+    load_config_catalog, merge_event_weights, CatalogError, CatalogEventDefinition, CustomCatalog,
+    EventSelector, WeightedEvent,
+};
+use super::data_events::{build_data_event, data_error_profile, DataEventScheduler};
+use super::errors::ErrorModel;
+use super::federation::{EntraFederation, FederatedUser};
+use super::injection::{plan_injection, InjectedActor, PlannedInjection};
+use super::linked::LinkedSessions;
+use super::model::CloudTrailEvent;
+use super::narrative::NarrativeEngine;
+use super::rate_limit::{Admission, Call, RateLimits};
+use super::remediation::{PolicyRemediation, IAM_REGION};
+use super::resources::ResourcePool;
+use super::rotation::{secret_event, CredentialRotation, RotationKind};
+use super::scenarios::{replan, ScenarioSchedule, SharedScenarios};
+use super::storm::ApiStorm;
+use super::takeover::{attacker_candidates, plan_takeover, Takeover};
+use super::templates::{
+    apply_error, build_cloudtrail_event, build_custom_event, default_error_profile, derived_uuid,
+    envelope_target, event_source_for, read_only_for, ActorContext, ErrorProfile,
+};
+use super::travel::ImpossibleTravel;
+use super::workflow::{RoleSession, ServiceWorkflow, WebIdentity};
 use crate::actors_parquet as actor_store;
 use crate::core::activity::identity_timezone_offset;
-use crate::core::actors::{ActorKind, ActorProfile, ActorRole, ServicePattern, ServiceProfile};
-use crate::core::config::CloudTrailSourceConfig;
-use crate::core::event::{Actor, Event, EventEnvelope, Outcome};
+use crate::core::actors::{
+    ActorKind, ActorProfile, ActorRole, InsiderPersona, OnCallShift, ServicePattern,
+    ServiceProfile, SessionOrigin,
+};
+use crate::core::arrival::Arrivals;
+use crate::core::config::{
+    ApiStormConfig, CloudTrailSourceConfig, EntraFederationConfig, WebIdentityConfig,
+};
+use crate::core::event::{Actor, Event, EventEnvelope, EventLabel, Outcome};
 use crate::core::identity::{AwsPrincipal, Identity, IdentityRegistry};
+use crate::core::selector::ActorSelector;
 use crate::core::traits::EventSource;
 use chrono::{DateTime, Duration, SecondsFormat, Timelike, Utc};
 use rand::distributions::{Distribution, WeightedIndex};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use serde_json::json;
 use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use tracing::warn;
 
 /// CloudTrail event source with weighted event selection and actor sessions.
 pub struct CloudTrailGenerator {
     selector: EventSelector,
     rng: StdRng,
+    /// Inter-arrival process and burst settings for actor scheduling.
+    arrivals: Arrivals,
     actors: Vec<ActorProfile>,
     schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
     event_weights: HashMap<String, f64>,
     allowed_events: HashSet<String>,
     region_selector: RegionSelector,
+    custom_events: HashMap<String, CatalogEventDefinition>,
+    catalog: Option<CustomCatalog>,
+    /// In-flight call-graph workflow per service actor when `service_call_graph`
+    /// or web identity federation applies to it.
+    workflows: Vec<Option<ServiceWorkflow>>,
+    /// Whether service actors follow call-graph workflows.
+    service_call_graph: bool,
+    /// Web identity federation keyed by service profile.
+    web_identity: HashMap<String, WebIdentityConfig>,
+    /// Entra ID federation for human console sign-ins.
+    federation: Option<EntraFederation>,
+    /// Task narratives for human sessions when `narratives` is configured.
+    narratives: Option<NarrativeEngine>,
+    /// Federated user and role session of each actor's latest console sign-in.
+    federated_sessions: HashMap<usize, (FederatedUser, RoleSession)>,
+    /// Events already built that go out before the next scheduled slot.
+    pending: VecDeque<Event>,
+    /// Latest time handed out, so back-dated federation events stay ordered.
+    clock: DateTime<Utc>,
+    /// Data event bursts when `data_events` is configured.
+    data_events: Option<DataEventScheduler>,
+    /// Pooled per-account resources when `resource_pool` is configured.
+    resource_pool: Option<ResourcePool>,
+    /// Generation start, the zero point for insider persona ramps.
+    start_time: DateTime<Utc>,
+    /// Credential takeovers keyed by the attacker's actor index.
+    takeovers: HashMap<usize, Takeover>,
+    /// Configured actor injections not yet joined, earliest first.
+    injections: VecDeque<PlannedInjection>,
+    /// Injected actors keyed by actor index.
+    injected: HashMap<usize, InjectedActor>,
+    /// Configured error profiles and surges; `None` keeps the built-in errors.
+    errors: Option<ErrorModel>,
+    /// Configured throttling storms.
+    storms: Vec<ApiStorm>,
+    /// Per-account caps on actor calls to AWS services.
+    rate_limits: RateLimits,
+    /// Calls deferred by a rate limit, retried at the actor's next slot.
+    deferred: HashMap<usize, String>,
+    /// Configured IAM policy remediation waves.
+    remediations: Vec<PolicyRemediation>,
+    /// Configured impossible travel scenarios.
+    travels: Vec<ImpossibleTravel>,
+    /// Scenario configs the storms, waves, and travels were planned from.
+    planned: ScenarioSchedule,
+    /// Live schedule to re-plan from when `--watch-config` reloads it.
+    scenarios: Option<SharedScenarios>,
+    /// Service credential rotation when `credential_rotation` is configured.
+    rotation: Option<CredentialRotation>,
+    /// Seed for derived event IDs when `deterministic_ids` is set.
+    id_seed: Option<u64>,
+    /// Events emitted so far per envelope actor ID, for derived event IDs.
+    id_sequences: HashMap<String, u64>,
+    /// Human sessions on the shared cross-source clock when `linked_sessions`
+    /// is set on a registry-backed run.
+    linked: Option<LinkedSessions>,
 }
 
 impl CloudTrailGenerator {
@@ -32,9 +122,19 @@ impl CloudTrailGenerator {
         seed: Option<u64>,
         start_time: DateTime<Utc>,
     ) -> Result<Self, CatalogError> {
-        let events = resolve_event_weights(config)?;
+        let catalog = load_config_catalog(config)?;
+        let events = merge_event_weights(config, catalog.as_ref())?;
         let selector = EventSelector::new(events.clone())?;
-        Self::new(selector, events, config, seed, start_time)
+        let (actors, registry) = load_actor_profiles(config)?;
+        Self::new_with_actor_profiles(
+            selector,
+            events,
+            config,
+            seed,
+            start_time,
+            (actors, registry.as_ref()),
+            catalog,
+        )
     }
 
     /// Builds a generator from an in-memory shared identity registry.
@@ -44,10 +144,19 @@ impl CloudTrailGenerator {
         seed: Option<u64>,
         start_time: DateTime<Utc>,
     ) -> Result<Self, CatalogError> {
-        let events = resolve_event_weights(config)?;
+        let catalog = load_config_catalog(config)?;
+        let events = merge_event_weights(config, catalog.as_ref())?;
         let selector = EventSelector::new(events.clone())?;
         let actors = actor_profiles_from_registry(config, &registry)?;
-        Self::new_with_actor_profiles(selector, events, config, seed, start_time, actors)
+        Self::new_with_actor_profiles(
+            selector,
+            events,
+            config,
+            seed,
+            start_time,
+            (actors, Some(&registry)),
+            catalog,
+        )
     }
 
     /// Builds a generator from a prepared selector and event list.
@@ -58,8 +167,17 @@ impl CloudTrailGenerator {
         seed: Option<u64>,
         start_time: DateTime<Utc>,
     ) -> Result<Self, CatalogError> {
-        let actors = load_actor_profiles(config)?;
-        Self::new_with_actor_profiles(selector, events, config, seed, start_time, actors)
+        let (actors, registry) = load_actor_profiles(config)?;
+        let catalog = load_config_catalog(config)?;
+        Self::new_with_actor_profiles(
+            selector,
+            events,
+            config,
+            seed,
+            start_time,
+            (actors, registry.as_ref()),
+            catalog,
+        )
     }
 
     fn new_with_actor_profiles(
@@ -68,8 +186,12 @@ impl CloudTrailGenerator {
         config: &CloudTrailSourceConfig,
         seed: Option<u64>,
         start_time: DateTime<Utc>,
-        mut actors: Vec<ActorProfile>,
+        (mut actors, registry): (Vec<ActorProfile>, Option<&IdentityRegistry>),
+        catalog: Option<CustomCatalog>,
     ) -> Result<Self, CatalogError> {
+        if config.deterministic_ids && seed.is_none() {
+            return Err(CatalogError::UnseededIds);
+        }
         let mut rng = match seed {
             Some(seed) => StdRng::seed_from_u64(seed),
             None => StdRng::from_entropy(),
@@ -81,37 +203,428 @@ impl CloudTrailGenerator {
             allowed_events.insert(event.name);
         }
 
+        if let Some(selector) = &config.actor_selector {
+            let selector = ActorSelector::new(selector)
+                .map_err(|err| CatalogError::Population(format!("actor_selector: {err}")))?;
+            actors = selector.select(actors, |actor| &actor.seed);
+            if actors.is_empty() {
+                return Err(CatalogError::Population(
+                    "actor_selector matched no actors".to_string(),
+                ));
+            }
+        }
         let region_selector =
             build_region_selector(config.regions.as_ref(), config.region_distribution.as_ref());
+        let arrivals =
+            Arrivals::from_config(config.arrival.as_ref()).map_err(CatalogError::Arrival)?;
         shuffle_actors(&mut actors, &mut rng);
-        let schedule = build_schedule(&actors, start_time, &mut rng);
+        let mut linked = registry.filter(|_| config.linked_sessions).map(|registry| {
+            LinkedSessions::new(registry, &actors, start_time, config.session_correlation)
+        });
+        let mut schedule =
+            build_schedule(&actors, start_time, &arrivals, linked.as_mut(), &mut rng);
+        let mut takeovers = HashMap::new();
+        for takeover in &config.credential_takeovers {
+            let (attacker, stolen_at, takeover) = plan_takeover(takeover, &actors, start_time)
+                .map_err(|err| CatalogError::Population(format!("credential_takeover: {err}")))?;
+            schedule.push(Reverse((stolen_at, actors.len())));
+            takeovers.insert(actors.len(), takeover);
+            actors.push(attacker);
+        }
+        let mut injections = config
+            .actor_injections
+            .iter()
+            .map(|injection| plan_injection(injection, start_time))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| CatalogError::Population(format!("actor_injection: {err}")))?;
+        injections.sort_by_key(|injection| injection.at);
+        let custom_events: HashMap<String, CatalogEventDefinition> = catalog
+            .as_ref()
+            .map(|catalog| {
+                catalog
+                    .events
+                    .iter()
+                    .map(|event| (event.name.clone(), event.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let web_identity = config.web_identity.clone().unwrap_or_default();
+        let data_events = config
+            .data_events
+            .as_ref()
+            .map(DataEventScheduler::new)
+            .transpose()
+            .map_err(CatalogError::DataEvents)?;
+        let errors =
+            ErrorModel::from_config(config, start_time).map_err(CatalogError::ErrorProfiles)?;
+        let storms = config
+            .api_storms
+            .iter()
+            .map(|storm| {
+                let service_events = service_events(&allowed_events, &custom_events, storm);
+                let region = region_selector.pick(&mut rng);
+                ApiStorm::plan(storm, &actors, service_events, region, start_time, &mut rng)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| CatalogError::Population(format!("api_storm: {err}")))?;
+        let rate_limits = RateLimits::from_config(&config.rate_limits)
+            .map_err(|err| CatalogError::Population(format!("rate_limit: {err}")))?;
+        let remediations = config
+            .policy_remediations
+            .iter()
+            .map(|wave| PolicyRemediation::plan(wave, &actors, start_time, &mut rng))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| CatalogError::Population(format!("policy_remediation: {err}")))?;
+        let travels = config
+            .impossible_travels
+            .iter()
+            .map(|travel| ImpossibleTravel::plan(travel, &actors, start_time, &mut rng))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| CatalogError::Population(format!("impossible_travel: {err}")))?;
+        let rotation = config
+            .credential_rotation
+            .as_ref()
+            .map(|rotation| {
+                let entra = config.entra_federation.is_some();
+                CredentialRotation::plan(rotation, &actors, entra, start_time, &mut rng)
+            })
+            .transpose()
+            .map_err(|err| CatalogError::Population(format!("credential_rotation: {err}")))?;
+        let resource_pool = match &config.resource_pool {
+            Some(pool) => {
+                let accounts = actors
+                    .iter()
+                    .map(|actor| actor.seed.account_id.clone())
+                    .collect::<Vec<_>>();
+                let pool =
+                    ResourcePool::generate(pool, &accounts, &region_selector.regions, &mut rng)
+                        .map_err(CatalogError::ResourcePool)?;
+                Some(pool)
+            }
+            None => None,
+        };
+        let workflows = actors
+            .iter()
+            .map(|actor| {
+                let federated = web_identity
+                    .contains_key(service_profile_key(actor.seed.service_profile.as_ref()));
+                (matches!(actor.seed.kind, ActorKind::Service)
+                    && (config.service_call_graph || federated))
+                    .then(ServiceWorkflow::default)
+            })
+            .collect();
+        let narratives = config
+            .narratives
+            .as_ref()
+            .map(|narratives| NarrativeEngine::new(narratives, actors.len()))
+            .transpose()
+            .map_err(CatalogError::Narratives)?;
         Ok(Self {
             selector,
             rng,
+            arrivals,
             actors,
             schedule,
             event_weights,
             allowed_events,
             region_selector,
+            custom_events,
+            catalog,
+            workflows,
+            service_call_graph: config.service_call_graph,
+            web_identity,
+            federation: config.entra_federation.as_ref().map(EntraFederation::new),
+            narratives,
+            federated_sessions: HashMap::new(),
+            pending: VecDeque::new(),
+            clock: start_time,
+            data_events,
+            resource_pool,
+            start_time,
+            takeovers,
+            injections: injections.into(),
+            injected: HashMap::new(),
+            errors,
+            storms,
+            rate_limits,
+            deferred: HashMap::new(),
+            remediations,
+            travels,
+            planned: ScenarioSchedule::from_config(config),
+            scenarios: None,
+            rotation,
+            id_seed: seed.filter(|_| config.deterministic_ids),
+            id_sequences: HashMap::new(),
+            linked,
         })
     }
+
+    /// Re-plans storms, remediation waves, and impossible travel whenever
+    /// `scenarios` is replaced, so a live config reload can reschedule them.
+    pub fn follow_scenarios(&mut self, scenarios: SharedScenarios) {
+        self.scenarios = Some(scenarios);
+    }
+
+    /// Picks up a replaced scenario schedule. Unchanged scenarios keep their
+    /// plans; new or edited ones are planned from generation start, as at
+    /// startup, and drop the calls the stream has already passed. A schedule
+    /// that does not plan leaves the current scenarios running.
+    fn replan_scenarios(&mut self) {
+        let Some(scenarios) = &self.scenarios else {
+            return;
+        };
+        let schedule = {
+            let schedule = scenarios
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if schedule.revision == self.planned.revision {
+                return;
+            }
+            schedule.clone()
+        };
+        let (start_time, clock) = (self.start_time, self.clock);
+        let storms = replan(
+            &self.planned.api_storms,
+            self.storms.clone(),
+            &schedule.api_storms,
+            |storm| {
+                let service_events =
+                    service_events(&self.allowed_events, &self.custom_events, storm);
+                let region = self.region_selector.pick(&mut self.rng);
+                let mut storm = ApiStorm::plan(
+                    storm,
+                    &self.actors,
+                    service_events,
+                    region,
+                    start_time,
+                    &mut self.rng,
+                )?;
+                storm.skip_before(clock);
+                Ok(storm)
+            },
+        )
+        .map_err(|err| format!("api_storm: {err}"));
+        let remediations = replan(
+            &self.planned.policy_remediations,
+            self.remediations.clone(),
+            &schedule.policy_remediations,
+            |wave| {
+                let mut wave =
+                    PolicyRemediation::plan(wave, &self.actors, start_time, &mut self.rng)?;
+                wave.skip_before(clock);
+                Ok(wave)
+            },
+        )
+        .map_err(|err| format!("policy_remediation: {err}"));
+        let travels = replan(
+            &self.planned.impossible_travels,
+            self.travels.clone(),
+            &schedule.impossible_travels,
+            |travel| {
+                let mut travel =
+                    ImpossibleTravel::plan(travel, &self.actors, start_time, &mut self.rng)?;
+                travel.skip_before(clock);
+                Ok(travel)
+            },
+        )
+        .map_err(|err| format!("impossible_travel: {err}"));
+        match (storms, remediations, travels) {
+            (Ok(storms), Ok(remediations), Ok(travels)) => {
+                self.storms = storms;
+                self.remediations = remediations;
+                self.travels = travels;
+                self.planned = schedule;
+            }
+            (Err(err), _, _) | (_, Err(err), _) | (_, _, Err(err)) => {
+                warn!("scenario reload failed; keeping current scenarios: {err}");
+                self.planned.revision = schedule.revision;
+            }
+        }
+    }
+
+    /// Adds actors to a running generator and returns their actor indices.
+    ///
+    /// Each new actor's first call is scheduled after `at`; the actors
+    /// already running keep their schedules.
+    pub fn add_actors(&mut self, actors: Vec<ActorProfile>, at: DateTime<Utc>) -> Range<usize> {
+        let first = self.actors.len();
+        for actor in actors {
+            let idx = self.actors.len();
+            let federated = self
+                .web_identity
+                .contains_key(service_profile_key(actor.seed.service_profile.as_ref()));
+            self.workflows.push(
+                (matches!(actor.seed.kind, ActorKind::Service)
+                    && (self.service_call_graph || federated))
+                    .then(ServiceWorkflow::default),
+            );
+            let base = actor.next_available_at(at);
+            let next_at = schedule_from(&actor, base, &self.arrivals, &mut self.rng);
+            self.schedule.push(Reverse((next_at, idx)));
+            self.actors.push(actor);
+        }
+        if let Some(narratives) = self.narratives.as_mut() {
+            narratives.add_actors(self.actors.len() - first);
+        }
+        first..self.actors.len()
+    }
+
+    /// Adds the configured injections due before the next scheduled call.
+    ///
+    /// Returns whether any actors joined, so the caller can re-read the
+    /// schedule.
+    fn inject_due_actors(&mut self) -> bool {
+        let until = self.schedule.peek().map(|Reverse((at, _))| *at);
+        let mut joined = false;
+        while let Some(injection) = self
+            .injections
+            .front()
+            .filter(|injection| until.is_none_or(|until| injection.at <= until))
+        {
+            let at = injection.at;
+            let running_rate = self
+                .actors
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| !self.actor_is_over(*idx, at))
+                .map(|(_, actor)| actor.seed.rate_per_hour)
+                .sum::<f64>();
+            let default_account = self
+                .actors
+                .first()
+                .map(|actor| actor.seed.account_id.clone())
+                .unwrap_or_default();
+            let (actors, state) = injection.actors(running_rate, &default_account, &mut self.rng);
+            self.injections.pop_front();
+            for idx in self.add_actors(actors, at) {
+                self.injected.insert(idx, state.clone());
+            }
+            joined = true;
+        }
+        joined
+    }
+
+    /// Whether the actor is a takeover attacker or injected actor past its end.
+    fn actor_is_over(&self, actor_index: usize, now: DateTime<Utc>) -> bool {
+        self.takeovers
+            .get(&actor_index)
+            .is_some_and(|takeover| takeover.is_over(now))
+            || self
+                .injected
+                .get(&actor_index)
+                .is_some_and(|injected| injected.is_over(now))
+    }
+
+    /// Scenario label of a takeover attacker or labeled injected actor.
+    fn actor_label(&self, actor_index: usize) -> Option<EventLabel> {
+        match self.takeovers.get(&actor_index) {
+            Some(takeover) => takeover.label.clone(),
+            None => self
+                .injected
+                .get(&actor_index)
+                .and_then(|injected| injected.label.clone()),
+        }
+    }
+
+    /// Wraps a built CloudTrail record into an event, deriving its IDs when
+    /// `deterministic_ids` is set.
+    fn finish_event(
+        &mut self,
+        mut cloudtrail: CloudTrailEvent,
+        actor_id: String,
+        label: Option<EventLabel>,
+    ) -> Event {
+        if let Some(seed) = self.id_seed {
+            let sequence = self.id_sequences.entry(actor_id.clone()).or_insert(0);
+            let name = format!("{seed}:cloudtrail:{actor_id}:{sequence}");
+            cloudtrail.event_id = derived_uuid(&format!("{name}:event"));
+            cloudtrail.request_id = derived_uuid(&format!("{name}:request"));
+            *sequence += 1;
+        }
+        Event {
+            envelope: envelope_for(&cloudtrail, actor_id),
+            payload: cloudtrail.to_value(),
+            label,
+        }
+    }
 }
 
 impl EventSource for CloudTrailGenerator {
     fn next_event(&mut self) -> Option<Event> {
+        self.replan_scenarios();
         loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if let Some(event) = self.next_travel_event() {
+                return Some(event);
+            }
+            if let Some(event) = self.next_remediation_event() {
+                return Some(event);
+            }
+            if let Some(event) = self.next_storm_event() {
+                return Some(event);
+            }
+            if let Some(event) = self.next_rotation_event() {
+                return Some(event);
+            }
+            if let Some(event) = self.next_data_event() {
+                return Some(event);
+            }
+            if self.inject_due_actors() {
+                continue;
+            }
             let Reverse((now, actor_index)) = self.schedule.pop()?;
+            let floor = self.clock;
+            self.clock = self.clock.max(now);
+            if self.actor_is_over(actor_index, now) {
+                continue;
+            }
+            let label = self.actor_label(actor_index);
             if !self.actors[actor_index].is_available(now, &mut self.rng) {
-                let next_at = self.actors[actor_index].next_available_at(now);
+                let available_at = self.actors[actor_index].next_available_at(now);
+                let next_at = self
+                    .linked
+                    .as_mut()
+                    .and_then(|linked| {
+                        linked.next_session_from(actor_index, available_at, &self.arrivals)
+                    })
+                    .unwrap_or(available_at);
                 self.schedule.push(Reverse((next_at, actor_index)));
                 continue;
             }
 
-            let event_name = self.pick_event_for_actor(actor_index, now);
+            let opening = self
+                .linked
+                .as_mut()
+                .is_some_and(|linked| linked.take_opening(actor_index));
+            if opening {
+                self.actors[actor_index].end_session();
+            }
+            let event_name = match self.deferred.remove(&actor_index) {
+                Some(event_name) => event_name,
+                None if opening => self.open_linked_session(actor_index, now),
+                None => self.pick_event_for_actor(actor_index, now),
+            };
+            let throttled = match self.admit_call(actor_index, &event_name, now) {
+                Admission::Allow => None,
+                Admission::Throttle(error) => Some(error),
+                Admission::Defer(retry_at) => {
+                    self.deferred.insert(actor_index, event_name);
+                    self.schedule.push(Reverse((retry_at, actor_index)));
+                    continue;
+                }
+            };
+            self.settle_session_origin(actor_index, &event_name);
             let event_time = now.to_rfc3339_opts(SecondsFormat::Millis, true);
 
-            let region = self.region_selector.pick(&mut self.rng);
-            let (actor_context, error_rate, envelope_actor_id) = {
+            let region = match self.takeovers.get(&actor_index) {
+                Some(takeover) if !takeover.regions.is_empty() => {
+                    takeover.regions[self.rng.gen_range(0..takeover.regions.len())].clone()
+                }
+                _ => self.region_selector.pick(&mut self.rng),
+            };
+            let (mut actor_context, error_rate, envelope_actor_id) = {
                 let actor = &mut self.actors[actor_index];
                 let error_rate = actor.seed.error_rate;
                 let envelope_actor_id = actor
@@ -125,66 +638,192 @@ impl EventSource for CloudTrailGenerator {
                     envelope_actor_id,
                 )
             };
-            let error_profile = default_error_profile(&event_name);
-            let cloudtrail = build_cloudtrail_event(
-                &event_name,
-                &actor_context,
-                &mut self.rng,
-                &event_time,
-                error_profile,
-                error_rate,
-            )
+            if label.is_some() {
+                actor_context.mfa_authenticated = false;
+            }
+            let browser_context = actor_context.clone();
+            let federated = self.federate_console_login(actor_index, &event_name, now, floor);
+            match &federated {
+                Some(login) => login.apply_session(&mut actor_context),
+                None => self.apply_federated_session(actor_index, &mut actor_context),
+            }
+            let assumed_session =
+                self.chain_role_session(actor_index, &event_name, now, &mut actor_context);
+            let (error_rate, error_profile) = match throttled {
+                Some(error) => (1.0, Some(error)),
+                None => self.resolve_error(
+                    &event_name,
+                    error_rate,
+                    now,
+                    default_error_profile(&event_name),
+                ),
+            };
+            let mut cloudtrail = match self.custom_events.get(&event_name) {
+                Some(definition) => build_custom_event(
+                    definition,
+                    &actor_context,
+                    &mut self.rng,
+                    &event_time,
+                    error_profile,
+                    error_rate,
+                ),
+                None => build_cloudtrail_event(
+                    &event_name,
+                    &actor_context,
+                    &mut self.rng,
+                    &event_time,
+                    error_profile,
+                    error_rate,
+                ),
+            }
             .ok()?;
+            if let Some(pool) = self.resource_pool.as_mut() {
+                if !self.custom_events.contains_key(&event_name) {
+                    pool.apply(&mut cloudtrail, &mut self.rng);
+                }
+            }
+            if let Some(narratives) = self.narratives.as_mut() {
+                narratives.apply(actor_index, &mut cloudtrail, &actor_context, &mut self.rng);
+            }
+            self.finish_console_call(actor_index, &mut cloudtrail);
+            if let (Some(session), Some(workflow)) =
+                (assumed_session, self.workflows[actor_index].as_mut())
+            {
+                if cloudtrail.error_code.is_none() {
+                    session.record_assume_role(&mut cloudtrail);
+                    workflow.start_session(session);
+                } else {
+                    workflow.abort();
+                }
+            }
 
             {
                 let actor = &mut self.actors[actor_index];
                 actor.consume_session(&mut self.rng);
-                let next_at = schedule_after(actor, now, &mut self.rng);
+                let mut next_at = schedule_after(actor, now, &self.arrivals, &mut self.rng);
+                let session_over = actor.session_remaining == 0
+                    || actor.session_end_at.is_none_or(|end| next_at >= end);
+                if let Some(linked) = self.linked.as_mut().filter(|_| session_over) {
+                    if let Some(at) = linked.next_session_from(actor_index, now, &self.arrivals) {
+                        next_at = at;
+                    }
+                }
                 self.schedule.push(Reverse((next_at, actor_index)));
             }
 
-            let envelope = EventEnvelope {
-                schema_version: "v1".to_string(),
-                timestamp: cloudtrail.event_time.clone(),
-                source: "cloudtrail".to_string(),
-                event_type: cloudtrail.event_name.clone(),
-                actor: Actor {
-                    id: envelope_actor_id,
-                    kind: cloudtrail.user_identity.identity_type.clone(),
-                    name: cloudtrail.user_identity.user_name.clone(),
-                },
-                target: None,
-                outcome: if cloudtrail.error_code.is_some() {
-                    Outcome::Failure
-                } else {
-                    Outcome::Success
-                },
-                geo: None,
-                ip: Some(cloudtrail.source_ip_address.clone()),
-                user_agent: Some(cloudtrail.user_agent.clone()),
-                session_id: None,
-                tenant_id: Some(cloudtrail.recipient_account_id.clone()),
-            };
+            if let Some(data_events) = self.data_events.as_mut() {
+                data_events.record_management(
+                    actor_index,
+                    &cloudtrail.recipient_account_id,
+                    &cloudtrail.aws_region,
+                    now,
+                    matches!(self.actors[actor_index].seed.kind, ActorKind::Service),
+                    self.resource_pool.as_mut(),
+                    &mut self.rng,
+                );
+            }
 
-            return Some(Event {
-                envelope,
-                payload: cloudtrail.to_value(),
-            });
+            if let Some(login) = federated {
+                return Some(self.finish_federated_login(
+                    actor_index,
+                    login,
+                    browser_context,
+                    cloudtrail,
+                    envelope_actor_id,
+                ));
+            }
+            return Some(self.finish_event(cloudtrail, envelope_actor_id, label));
         }
     }
 }
 
-fn load_actor_profiles(config: &CloudTrailSourceConfig) -> Result<Vec<ActorProfile>, CatalogError> {
+/// A federated console sign-in: the Entra sign-in, the STS call, and the
+/// session `ConsoleLogin` and later console calls run under.
+struct FederatedLogin {
+    federation: EntraFederation,
+    user: FederatedUser,
+    session: RoleSession,
+    signed_in_at: DateTime<Utc>,
+    assumed_at: DateTime<Utc>,
+    mfa_authenticated: bool,
+}
+
+impl FederatedLogin {
+    fn apply_session(&self, actor: &mut ActorContext) {
+        self.federation
+            .apply_session(&self.session, &self.user, actor);
+    }
+}
+
+/// Normalized envelope of a CloudTrail record emitted for `actor_id`.
+pub(crate) fn envelope_for(cloudtrail: &CloudTrailEvent, actor_id: String) -> EventEnvelope {
+    EventEnvelope {
+        schema_version: "v1".to_string(),
+        timestamp: cloudtrail.event_time.clone(),
+        source: "cloudtrail".to_string(),
+        event_type: cloudtrail.event_name.clone(),
+        actor: Actor {
+            id: actor_id,
+            kind: cloudtrail.user_identity.identity_type.to_string(),
+            name: cloudtrail
+                .user_identity
+                .user_name
+                .as_deref()
+                .map(str::to_string),
+        },
+        target: envelope_target(cloudtrail),
+        outcome: if cloudtrail.error_code.is_some() {
+            Outcome::Failure
+        } else {
+            Outcome::Success
+        },
+        geo: None,
+        ip: (cloudtrail.source_ip_address != AWS_INTERNAL)
+            .then(|| cloudtrail.source_ip_address.clone()),
+        user_agent: Some(cloudtrail.user_agent.clone()),
+        session_id: None,
+        tenant_id: Some(cloudtrail.recipient_account_id.to_string()),
+        tenant: None,
+    }
+}
+
+/// Enabled events of a storm's service, sorted.
+fn service_events(
+    allowed_events: &HashSet<String>,
+    custom_events: &HashMap<String, CatalogEventDefinition>,
+    storm: &ApiStormConfig,
+) -> Vec<String> {
+    let mut events = allowed_events
+        .iter()
+        .filter(|name| {
+            let source = match custom_events.get(*name) {
+                Some(definition) => definition.event_source.as_str(),
+                None => event_source_for(name),
+            };
+            source == storm.service
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    events.sort();
+    events
+}
+
+/// Loads the configured actors, with the identity registry they came from
+/// when they were built from one.
+fn load_actor_profiles(
+    config: &CloudTrailSourceConfig,
+) -> Result<(Vec<ActorProfile>, Option<IdentityRegistry>), CatalogError> {
     if let Some(path) = config.actor_population_path.as_ref() {
         let population = actor_store::read_population(path)
             .map_err(|err| CatalogError::Population(err.to_string()))?;
-        return Ok(population.profiles());
+        return Ok((population.profiles(), None));
     }
 
     if let Some(path) = config.identity_registry_path.as_ref() {
         let registry = IdentityRegistry::from_path(path)
             .map_err(|err| CatalogError::Population(err.to_string()))?;
-        return actor_profiles_from_registry(config, &registry);
+        let actors = actor_profiles_from_registry(config, &registry)?;
+        return Ok((actors, Some(registry)));
     }
 
     Err(CatalogError::Population(
@@ -272,6 +911,11 @@ fn actor_seed_from_identity(
         event_bias: HashMap::new(),
         service_profile,
         service_pattern,
+        insider_persona: identity
+            .insider_persona
+            .as_deref()
+            .and_then(InsiderPersona::parse),
+        on_call: identity.on_call.as_deref().and_then(OnCallShift::parse),
         user_name,
         display_name: Some(identity.display_name.clone()),
         email: Some(identity.email.clone()),
@@ -394,7 +1038,7 @@ fn access_key_for(identity_type: &str, principal_id: &str) -> String {
     format!("{prefix}{:016X}", stable_hash(principal_id))
 }
 
-fn stable_hash(value: &str) -> u64 {
+pub(super) fn stable_hash(value: &str) -> u64 {
     let mut hash = 0xcbf29ce484222325_u64;
     for byte in value.as_bytes() {
         hash ^= *byte as u64;
@@ -404,51 +1048,650 @@ fn stable_hash(value: &str) -> u64 {
 }
 
 impl CloudTrailGenerator {
+    /// Emits the next data event if one is due before the next actor slot.
+    fn next_data_event(&mut self) -> Option<Event> {
+        let until = self.schedule.peek().map(|Reverse((at, _))| *at);
+        let burst = self.data_events.as_mut()?.next_due(until, &mut self.rng)?;
+        self.clock = self.clock.max(burst.next_at);
+        let actor = &mut self.actors[burst.actor_index];
+        let error_rate = actor.seed.error_rate;
+        let actor_id = actor
+            .seed
+            .id
+            .clone()
+            .unwrap_or_else(|| actor.seed.principal_id.clone());
+        let mut context = actor_context(actor, burst.region.clone(), &mut self.rng);
+        if let Some(session) = self.workflows[burst.actor_index]
+            .as_ref()
+            .and_then(|workflow| workflow.session())
+        {
+            session.apply_to(&mut context);
+        }
+        let event_time = burst.next_at.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let cloudtrail = build_data_event(&burst, &context, &mut self.rng, &event_time);
+        let (error_rate, error_profile) = self.resolve_error(
+            &cloudtrail.event_name,
+            error_rate,
+            burst.next_at,
+            Some(data_error_profile()),
+        );
+        let cloudtrail = apply_error(cloudtrail, &mut self.rng, error_profile, error_rate);
+        let label = self.actor_label(burst.actor_index);
+        Some(self.finish_event(cloudtrail, actor_id, label))
+    }
+
+    /// Checks an actor's call against the rate limits of its account.
+    fn admit_call(
+        &mut self,
+        actor_index: usize,
+        event_name: &str,
+        now: DateTime<Utc>,
+    ) -> Admission {
+        if self.rate_limits.is_empty() {
+            return Admission::Allow;
+        }
+        let (service, read_only) = match self.custom_events.get(event_name) {
+            Some(definition) => (definition.event_source.as_str(), definition.read_only),
+            None => (
+                event_source_for(event_name),
+                read_only_for(event_name).unwrap_or(false),
+            ),
+        };
+        self.rate_limits.admit(Call {
+            service,
+            event_name,
+            read_only,
+            account_id: &self.actors[actor_index].seed.account_id,
+            at: now,
+        })
+    }
+
+    /// Emits the next storm call if one is due before the next actor slot.
+    fn next_storm_event(&mut self) -> Option<Event> {
+        let until = self.schedule.peek().map(|Reverse((at, _))| *at);
+        let storm = self
+            .storms
+            .iter_mut()
+            .filter_map(|storm| storm.next_at().map(|at| (at, storm)))
+            .filter(|(at, _)| until.is_none_or(|until| *at <= until))
+            .min_by_key(|(at, _)| *at)
+            .map(|(_, storm)| storm)?;
+        let call = storm.next_call(&mut self.rng);
+        self.clock = self.clock.max(call.at);
+        let (region, error_rate, error, label) = (
+            storm.region.clone(),
+            storm.error_rate,
+            storm.error.clone(),
+            storm.label.clone(),
+        );
+        let actor = &mut self.actors[call.actor_index];
+        let actor_id = actor
+            .seed
+            .id
+            .clone()
+            .unwrap_or_else(|| actor.seed.principal_id.clone());
+        let context = actor_context(actor, region, &mut self.rng);
+        let event_time = call.at.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let mut cloudtrail = match self.custom_events.get(&call.event_name) {
+            Some(definition) => build_custom_event(
+                definition,
+                &context,
+                &mut self.rng,
+                &event_time,
+                Some(error),
+                error_rate,
+            ),
+            None => build_cloudtrail_event(
+                &call.event_name,
+                &context,
+                &mut self.rng,
+                &event_time,
+                Some(error),
+                error_rate,
+            ),
+        }
+        .ok()?;
+        if let Some(pool) = self.resource_pool.as_mut() {
+            if !self.custom_events.contains_key(&call.event_name) {
+                pool.apply(&mut cloudtrail, &mut self.rng);
+            }
+        }
+        Some(self.finish_event(cloudtrail, actor_id, label))
+    }
+
+    /// Emits the next remediation wave call if one is due before the next
+    /// actor slot and storm call.
+    fn next_remediation_event(&mut self) -> Option<Event> {
+        let until = self
+            .schedule
+            .peek()
+            .map(|Reverse((at, _))| *at)
+            .into_iter()
+            .chain(self.storms.iter().filter_map(ApiStorm::next_at))
+            .chain(self.travels.iter().filter_map(ImpossibleTravel::next_at))
+            .chain(self.rotation.as_ref().and_then(CredentialRotation::next_at))
+            .min();
+        let wave = self
+            .remediations
+            .iter_mut()
+            .filter_map(|wave| wave.next_at().map(|at| (at, wave)))
+            .filter(|(at, _)| until.is_none_or(|until| *at <= until))
+            .min_by_key(|(at, _)| *at)
+            .map(|(_, wave)| wave)?;
+        let call = wave.next_call()?;
+        let label = wave.label.clone();
+        self.clock = self.clock.max(call.at);
+        let actor = &mut self.actors[call.actor_index];
+        let actor_id = actor
+            .seed
+            .id
+            .clone()
+            .unwrap_or_else(|| actor.seed.principal_id.clone());
+        let mut context = actor_context(actor, IAM_REGION.to_string(), &mut self.rng);
+        call.apply_session(&mut context);
+        let event_time = call.at.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let mut cloudtrail = build_cloudtrail_event(
+            &call.event_name,
+            &context,
+            &mut self.rng,
+            &event_time,
+            None,
+            0.0,
+        )
+        .ok()?;
+        cloudtrail.request_parameters = Some(call.request_parameters);
+        Some(self.finish_event(cloudtrail, actor_id, label))
+    }
+
+    /// Emits the next credential rotation call due before the next actor slot
+    /// and storm call. Entra secret changes follow the call that goes with
+    /// them through `pending`.
+    fn next_rotation_event(&mut self) -> Option<Event> {
+        let until = self
+            .schedule
+            .peek()
+            .map(|Reverse((at, _))| *at)
+            .into_iter()
+            .chain(self.storms.iter().filter_map(ApiStorm::next_at))
+            .min();
+        let rotation = self.rotation.as_mut()?;
+        let next_at = rotation.next_at()?;
+        // Rotation only runs alongside other activity, so it ends with it.
+        if until.is_none_or(|until| next_at > until) {
+            return None;
+        }
+        let actors = &self.actors;
+        let step =
+            rotation.next_step(|idx| actors[idx].seed.access_key_id.clone(), &mut self.rng)?;
+        self.clock = self.clock.max(step.at);
+        let actor = &mut self.actors[step.actor_index];
+        let actor_id = actor
+            .seed
+            .id
+            .clone()
+            .unwrap_or_else(|| actor.seed.principal_id.clone());
+        let secret = match (&step.secret, &self.federation) {
+            (Some((removed, added)), Some(federation)) => Some(secret_event(
+                &actor.seed,
+                &federation.user(&actor.seed).tenant_id,
+                step.at,
+                removed,
+                added.as_deref(),
+            )),
+            _ => None,
+        };
+        let user_name = actor
+            .seed
+            .user_name
+            .clone()
+            .unwrap_or_else(|| actor.seed.principal_id.clone());
+        let Some((event_name, request_parameters)) = step.call(&user_name) else {
+            return secret;
+        };
+        let context = actor_context(actor, IAM_REGION.to_string(), &mut self.rng);
+        let event_time = step.at.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let mut cloudtrail =
+            build_cloudtrail_event(event_name, &context, &mut self.rng, &event_time, None, 0.0)
+                .ok()?;
+        cloudtrail.request_parameters = Some(request_parameters);
+        if let RotationKind::Create { new_key } = &step.kind {
+            cloudtrail.response_elements = Some(json!({
+                "accessKey": {
+                    "userName": user_name,
+                    "accessKeyId": new_key,
+                    "status": "Active",
+                    "createDate": event_time,
+                }
+            }));
+            actor.seed.access_key_id = new_key.clone();
+            actor.identity.access_key_id = new_key.as_str().into();
+        }
+        self.pending.extend(secret);
+        Some(self.finish_event(cloudtrail, actor_id, None))
+    }
+
+    /// Emits the next impossible travel event due before the next scheduled
+    /// call: the Entra sign-in from the first city, then console calls from
+    /// the second.
+    fn next_travel_event(&mut self) -> Option<Event> {
+        let until = self
+            .schedule
+            .peek()
+            .map(|Reverse((at, _))| *at)
+            .into_iter()
+            .chain(self.storms.iter().filter_map(ApiStorm::next_at))
+            .chain(
+                self.remediations
+                    .iter()
+                    .filter_map(PolicyRemediation::next_at),
+            )
+            .chain(self.rotation.as_ref().and_then(CredentialRotation::next_at))
+            .min();
+        let travel = self
+            .travels
+            .iter_mut()
+            .filter_map(|travel| travel.next_at().map(|at| (at, travel)))
+            .filter(|(at, _)| until.is_none_or(|until| *at <= until))
+            .min_by_key(|(at, _)| *at)
+            .map(|(_, travel)| travel)?;
+        let call = travel.next_call()?;
+        let (actor_index, user_agent, label) = (
+            travel.actor_index,
+            travel.user_agent.clone(),
+            travel.label.clone(),
+        );
+        self.clock = self.clock.max(call.at);
+        let region = self.region_selector.pick(&mut self.rng);
+        let actor = &mut self.actors[actor_index];
+        let actor_id = actor
+            .seed
+            .id
+            .clone()
+            .unwrap_or_else(|| actor.seed.principal_id.clone());
+        let mut context = actor_context(actor, region, &mut self.rng);
+        context.source_ip = call.source_ip;
+        context.user_agent = user_agent;
+        context.mfa_authenticated = false;
+
+        let Some(event_name) = call.event_name else {
+            let federation = self
+                .federation
+                .clone()
+                .unwrap_or_else(|| EntraFederation::new(&EntraFederationConfig::default()));
+            let mut seed = actor.seed.clone();
+            seed.home_location = Some(call.city.name.to_string());
+            let user = federation.user(&seed);
+            let mut event = federation.sign_in_event(
+                &seed,
+                &user,
+                &context,
+                call.at,
+                true,
+                true,
+                &mut self.rng,
+            );
+            event.payload["location"]["geoCoordinates"] = json!({
+                "latitude": call.city.lat,
+                "longitude": call.city.lon,
+            });
+            event.envelope.geo = Some(call.city.geo());
+            event.label = label;
+            return Some(event);
+        };
+        context.session_credential_from_console = event_name != "ConsoleLogin";
+        let event_time = call.at.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let mut cloudtrail =
+            build_cloudtrail_event(event_name, &context, &mut self.rng, &event_time, None, 0.0)
+                .ok()?;
+        if let Some(pool) = self.resource_pool.as_mut() {
+            pool.apply(&mut cloudtrail, &mut self.rng);
+        }
+        let mut event = self.finish_event(cloudtrail, actor_id, label);
+        event.envelope.geo = Some(call.city.geo());
+        Some(event)
+    }
+
+    /// Error rate and error for an event, from the error model when configured.
+    fn resolve_error(
+        &mut self,
+        event_name: &str,
+        actor_rate: f64,
+        now: DateTime<Utc>,
+        default: Option<ErrorProfile>,
+    ) -> (f64, Option<ErrorProfile>) {
+        match &self.errors {
+            Some(errors) => errors.resolve(event_name, actor_rate, now, default, &mut self.rng),
+            None => (actor_rate, default),
+        }
+    }
+
+    /// Starts a console session on `ConsoleLogin`; any other first call of a
+    /// session makes it programmatic. Every later call in the session shares
+    /// its user agent, console flag, and MFA state.
+    fn settle_session_origin(&mut self, actor_index: usize, event_name: &str) {
+        let actor = &mut self.actors[actor_index];
+        if event_name == "ConsoleLogin" {
+            let mfa_authenticated =
+                !self.takeovers.contains_key(&actor_index) && self.rng.gen_bool(HUMAN_MFA_RATE);
+            actor.start_console_session(mfa_authenticated);
+        } else if actor.session_origin.is_none() {
+            actor.start_programmatic_session();
+        }
+    }
+
+    /// Plans the Entra sign-in and STS call in front of a human's `ConsoleLogin`
+    /// when federation is configured; neither goes back past `floor`.
+    fn federate_console_login(
+        &mut self,
+        actor_index: usize,
+        event_name: &str,
+        now: DateTime<Utc>,
+        floor: DateTime<Utc>,
+    ) -> Option<FederatedLogin> {
+        let federation = self.federation.clone()?;
+        let actor = &self.actors[actor_index];
+        if event_name != "ConsoleLogin"
+            || !matches!(actor.seed.kind, ActorKind::Human)
+            || self.takeovers.contains_key(&actor_index)
+        {
+            return None;
+        }
+        let mfa_authenticated = matches!(
+            actor.session_origin,
+            Some(SessionOrigin::Console {
+                mfa_authenticated: true
+            })
+        );
+        let signed_in_at = (now - Duration::milliseconds(self.rng.gen_range(4_000..12_000)))
+            .max(floor)
+            .min(now);
+        let assumed_at =
+            (now - Duration::milliseconds(self.rng.gen_range(500..3_000))).max(signed_in_at);
+        let user = federation.user(&actor.seed);
+        let session =
+            federation.session(&actor.seed, &user, actor_role_or_default(actor), assumed_at);
+        Some(FederatedLogin {
+            federation,
+            user,
+            session,
+            signed_in_at,
+            assumed_at,
+            mfa_authenticated,
+        })
+    }
+
+    /// Runs console calls after a federated sign-in under its role session.
+    fn apply_federated_session(&self, actor_index: usize, actor: &mut ActorContext) {
+        let (Some(federation), Some((user, session))) = (
+            self.federation.as_ref(),
+            self.federated_sessions.get(&actor_index),
+        ) else {
+            return;
+        };
+        if matches!(
+            self.actors[actor_index].session_origin,
+            Some(SessionOrigin::Console { .. })
+        ) {
+            federation.apply_session(session, user, actor);
+        }
+    }
+
+    /// Returns the Entra sign-in and queues the STS call and `ConsoleLogin`
+    /// behind it. A failed sign-in never reaches AWS, so only the Entra
+    /// failure is emitted.
+    fn finish_federated_login(
+        &mut self,
+        actor_index: usize,
+        login: FederatedLogin,
+        browser: ActorContext,
+        console_login: CloudTrailEvent,
+        actor_id: String,
+    ) -> Event {
+        let succeeded = console_login.error_code.is_none();
+        let sign_in = login.federation.sign_in_event(
+            &self.actors[actor_index].seed,
+            &login.user,
+            &browser,
+            login.signed_in_at,
+            login.mfa_authenticated,
+            succeeded,
+            &mut self.rng,
+        );
+        if !succeeded {
+            self.federated_sessions.remove(&actor_index);
+            return sign_in;
+        }
+        let mut caller = browser;
+        login.federation.apply_caller(
+            &login.user,
+            &self.actors[actor_index].seed.account_id,
+            &mut caller,
+        );
+        let assumed_at = login
+            .assumed_at
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        if let Ok(mut credentials) = build_cloudtrail_event(
+            login.federation.credential_event(),
+            &caller,
+            &mut self.rng,
+            &assumed_at,
+            None,
+            0.0,
+        ) {
+            login.federation.record_credentials(
+                &login.session,
+                &login.user,
+                &mut credentials,
+                &mut self.rng,
+            );
+            let credentials = self.finish_event(credentials, actor_id.clone(), None);
+            self.pending.push_back(credentials);
+        }
+        let console_login = self.finish_event(console_login, actor_id, None);
+        self.pending.push_back(console_login);
+        self.federated_sessions
+            .insert(actor_index, (login.user, login.session));
+        sign_in
+    }
+
+    /// Ends the console session after a failed sign-in, and routes some
+    /// console read calls through the console backend as `AWS Internal`.
+    fn finish_console_call(&mut self, actor_index: usize, cloudtrail: &mut CloudTrailEvent) {
+        let actor = &mut self.actors[actor_index];
+        if !matches!(actor.session_origin, Some(SessionOrigin::Console { .. })) {
+            return;
+        }
+        if cloudtrail.event_name == "ConsoleLogin" {
+            if cloudtrail.error_code.is_some() {
+                actor.session_origin = None;
+            }
+            return;
+        }
+        if cloudtrail.read_only == Some(true) && self.rng.gen_bool(CONSOLE_INTERNAL_RATE) {
+            cloudtrail.source_ip_address = AWS_INTERNAL.to_string();
+            cloudtrail.user_agent = AWS_INTERNAL.to_string();
+        }
+    }
+
+    /// Opens a linked human session with `ConsoleLogin`, the AWS side of the
+    /// identity provider sign-in at the shared instant, when it is enabled.
+    fn open_linked_session(&mut self, actor_index: usize, now: DateTime<Utc>) -> String {
+        let event = self.pick_event_for_actor(actor_index, now);
+        if !self.allowed_events.contains("ConsoleLogin") {
+            return event;
+        }
+        self.actors[actor_index].last_event = Some("ConsoleLogin".to_string());
+        "ConsoleLogin".to_string()
+    }
+
     fn pick_event_for_actor(&mut self, actor_index: usize, now: DateTime<Utc>) -> String {
         {
             let actor = &mut self.actors[actor_index];
             actor.ensure_session(now, &mut self.rng);
         }
-        let (kind, last_event, service_profile, event_bias) = {
+        let (kind, last_event, service_profile, insider_persona, paged) = {
             let actor = &self.actors[actor_index];
             (
                 actor.seed.kind.clone(),
                 actor.last_event.clone(),
                 actor.seed.service_profile.clone(),
-                actor.seed.event_bias.clone(),
+                actor.seed.insider_persona,
+                actor.seed.on_call_off_hours(now),
             )
         };
 
-        let candidates = match kind {
-            ActorKind::Human => {
+        if self.takeovers.contains_key(&actor_index) {
+            let candidates = attacker_candidates(last_event.as_deref());
+            let event = self.pick_weighted_event(actor_index, &candidates);
+            self.actors[actor_index].last_event = Some(event.clone());
+            return event;
+        }
+
+        if let Some(event) = self.next_workflow_step(
+            actor_index,
+            service_profile.as_ref(),
+            last_event.is_none(),
+            now,
+        ) {
+            self.actors[actor_index].last_event = Some(event.clone());
+            return event;
+        }
+
+        // Off-hours on-call sessions sign in as usual, then stay on triage calls.
+        if paged && last_event.is_some() {
+            let event = self.pick_weighted_event(actor_index, &on_call_candidates());
+            self.actors[actor_index].last_event = Some(event.clone());
+            return event;
+        }
+
+        if let (ActorKind::Human, Some(narratives)) = (&kind, self.narratives.as_mut()) {
+            if last_event.is_none() {
                 let role = actor_role_or_default(&self.actors[actor_index]);
-                human_candidates(role, last_event.as_deref())
+                let allowed_events = &self.allowed_events;
+                narratives.start_session(
+                    actor_index,
+                    role,
+                    |name| allowed_events.contains(name),
+                    &mut self.rng,
+                );
+            } else if let Some(event) = narratives.next_step(actor_index) {
+                self.actors[actor_index].last_event = Some(event.clone());
+                return event;
             }
-            ActorKind::Service => {
-                service_profile_candidates(service_profile.as_ref(), last_event.as_deref())
+        }
+
+        let (mut candidates, profile_key) = match kind {
+            ActorKind::Human => {
+                let role = actor_role_or_default(&self.actors[actor_index]);
+                (
+                    human_candidates(role, last_event.as_deref()),
+                    role_key(role),
+                )
             }
+            ActorKind::Service => (
+                service_profile_candidates(service_profile.as_ref(), last_event.as_deref()),
+                service_profile_key(service_profile.as_ref()),
+            ),
         };
+        if let Some(catalog) = &self.catalog {
+            merge_candidates(
+                &mut candidates,
+                catalog.transition_weights(profile_key, last_event.as_deref()),
+            );
+        }
+        if let Some(persona) = insider_persona {
+            let elapsed_days = (now - self.start_time).num_seconds() as f64 / 86_400.0;
+            let ramp = (elapsed_days / INSIDER_RAMP_DAYS).clamp(0.1, 1.0);
+            merge_candidates(&mut candidates, insider_candidates(persona, ramp));
+        }
 
-        let event = self.pick_weighted_event(&candidates, &event_bias);
+        let event = self.pick_weighted_event(actor_index, &candidates);
         let actor = &mut self.actors[actor_index];
         actor.last_event = Some(event.clone());
         event
     }
 
-    fn pick_weighted_event(
+    /// Returns the next call-graph step for a service actor, planning a new
+    /// pass when the previous one finished or the actor started a new session.
+    fn next_workflow_step(
         &mut self,
-        candidates: &[(String, f64)],
-        event_bias: &HashMap<String, f64>,
-    ) -> String {
-        let mut names = Vec::new();
-        let mut weights = Vec::new();
+        actor_index: usize,
+        profile: Option<&ServiceProfile>,
+        new_session: bool,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        let workflow = self.workflows[actor_index].as_mut()?;
+        if new_session || workflow.is_finished() {
+            let session = workflow.session().filter(|_| !new_session).cloned();
+            let allowed_events = &self.allowed_events;
+            let federated = self.web_identity.contains_key(service_profile_key(profile));
+            *workflow = ServiceWorkflow::plan(
+                profile.unwrap_or(&ServiceProfile::Generic),
+                federated,
+                session,
+                now,
+                |name| allowed_events.contains(name),
+                &mut self.rng,
+            );
+        }
+        workflow.next_step()
+    }
+
+    /// Runs a workflow step under the actor's assumed-role session.
+    ///
+    /// For the credential call itself (AssumeRole or AssumeRoleWithWebIdentity),
+    /// returns the session it hands out; the caller records it once the event
+    /// is known to have succeeded.
+    fn chain_role_session(
+        &self,
+        actor_index: usize,
+        event_name: &str,
+        now: DateTime<Utc>,
+        actor_context: &mut ActorContext,
+    ) -> Option<RoleSession> {
+        let workflow = self.workflows[actor_index].as_ref()?;
+        if matches!(event_name, "AssumeRole" | "AssumeRoleWithWebIdentity") {
+            let seed = &self.actors[actor_index].seed;
+            let actor_key = seed.id.as_deref().unwrap_or(&seed.principal_id);
+            let profile = seed
+                .service_profile
+                .as_ref()
+                .unwrap_or(&ServiceProfile::Generic);
+            let web_identity = self
+                .web_identity
+                .get(service_profile_key(Some(profile)))
+                .filter(|_| event_name == "AssumeRoleWithWebIdentity")
+                .map(|config| WebIdentity::new(config, &seed.account_id, profile));
+            if let Some(identity) = &web_identity {
+                identity.apply_caller(actor_context);
+            }
+            return Some(RoleSession::new(
+                &seed.account_id,
+                actor_key,
+                profile,
+                web_identity,
+                now,
+            ));
+        }
+        if let Some(session) = workflow.session() {
+            session.apply_to(actor_context);
+        }
+        None
+    }
+
+    /// Picks one of `candidates`, weighted by the event weights and the
+    /// actor's event bias.
+    fn pick_weighted_event(&mut self, actor_index: usize, candidates: &[(String, f64)]) -> String {
+        let event_bias = &self.actors[actor_index].seed.event_bias;
+        let mut names = Vec::with_capacity(candidates.len());
+        let mut weights = Vec::with_capacity(candidates.len());
 
         for (name, weight) in candidates {
             if !self.allowed_events.contains(name) {
                 continue;
             }
             let base = *self.event_weights.get(name).unwrap_or(&1.0);
-            names.push(name.clone());
+            names.push(name);
             let mut combined = base * *weight;
             if let Some(bias) = event_bias.get(name) {
                 if bias.is_finite() && *bias > 0.0 {
@@ -476,6 +1719,98 @@ fn actor_role_or_default(actor: &ActorProfile) -> ActorRole {
     actor.seed.role.clone().unwrap_or(ActorRole::Developer)
 }
 
+pub(super) fn role_key(role: ActorRole) -> &'static str {
+    match role {
+        ActorRole::Admin => "admin",
+        ActorRole::Developer => "developer",
+        ActorRole::ReadOnly => "readonly",
+        ActorRole::Auditor => "auditor",
+    }
+}
+
+fn service_profile_key(profile: Option<&ServiceProfile>) -> &'static str {
+    match profile.unwrap_or(&ServiceProfile::Generic) {
+        ServiceProfile::Generic => "generic",
+        ServiceProfile::Ec2Reaper => "ec2_reaper",
+        ServiceProfile::DataLakeBot => "datalake_bot",
+        ServiceProfile::LogsShipper => "logs_shipper",
+        ServiceProfile::MetricsCollector => "metrics_collector",
+    }
+}
+
+/// Adds catalog transition weights on top of the built-in candidates.
+fn merge_candidates(candidates: &mut Vec<(String, f64)>, extra: Vec<(String, f64)>) {
+    for (name, weight) in extra {
+        match candidates
+            .iter_mut()
+            .find(|(existing, _)| *existing == name)
+        {
+            Some((_, existing)) => *existing += weight,
+            None => candidates.push((name, weight)),
+        }
+    }
+}
+
+/// Days over which persona-driven activity grows to full strength.
+const INSIDER_RAMP_DAYS: f64 = 30.0;
+/// Share of human calls made with MFA, and of console sign-ins that use it.
+const HUMAN_MFA_RATE: f64 = 0.7;
+/// Share of console read calls the console backend makes on the user's behalf.
+const CONSOLE_INTERNAL_RATE: f64 = 0.15;
+/// `sourceIPAddress` and `userAgent` of calls made by AWS on a user's behalf.
+const AWS_INTERNAL: &str = "AWS Internal";
+
+/// Extra candidates for an insider persona, scaled by `ramp` in `[0.1, 1]` so
+/// the pattern starts close to the actor's peers and drifts over weeks.
+fn insider_candidates(persona: InsiderPersona, ramp: f64) -> Vec<(String, f64)> {
+    let events: &[(&str, f64)] = match persona {
+        InsiderPersona::DataHoarder => &[
+            ("GetObject", 2.5),
+            ("ListBuckets", 0.6),
+            ("ListObjects", 0.6),
+            ("Decrypt", 0.4),
+        ],
+        InsiderPersona::OffHoursAdmin => &[
+            ("CreateUser", 0.3),
+            ("CreateAccessKey", 0.3),
+            ("AttachRolePolicy", 0.3),
+            ("AuthorizeSecurityGroupIngress", 0.3),
+        ],
+        InsiderPersona::PermissionAccumulator => &[
+            ("AttachRolePolicy", 0.6),
+            ("AttachUserPolicy", 0.5),
+            ("PutUserPolicy", 0.4),
+            ("AddUserToGroup", 0.4),
+            ("CreateAccessKey", 0.4),
+        ],
+    };
+    let ramp = match persona {
+        InsiderPersona::OffHoursAdmin => 1.0,
+        _ => ramp,
+    };
+    events
+        .iter()
+        .map(|(name, weight)| (name.to_string(), weight * ramp))
+        .collect()
+}
+
+/// Read, describe, and incident-response calls made by on-call humans outside
+/// their regular window.
+fn on_call_candidates() -> Vec<(String, f64)> {
+    [
+        ("DescribeInstances", 1.2),
+        ("GetMetricData", 1.0),
+        ("DescribeLogStreams", 0.8),
+        ("ListMetrics", 0.5),
+        ("GetCallerIdentity", 0.4),
+        ("StopInstances", 0.2),
+        ("StartInstances", 0.2),
+    ]
+    .into_iter()
+    .map(|(name, weight)| (name.to_string(), weight))
+    .collect()
+}
+
 fn human_candidates(role: ActorRole, last: Option<&str>) -> Vec<(String, f64)> {
     match role {
         ActorRole::Admin => admin_candidates(last),
@@ -617,26 +1952,32 @@ fn metrics_collector_candidates(last: Option<&str>) -> Vec<(String, f64)> {
 
 fn actor_context(actor: &mut ActorProfile, region: String, rng: &mut impl Rng) -> ActorContext {
     let user_agent = actor.current_user_agent(rng);
-    let session_credential_from_console = user_agent.contains("CloudShell")
-        || user_agent.starts_with("Mozilla/")
-        || user_agent.contains("Safari/")
-        || user_agent.contains("Chrome/");
-    let mfa_authenticated = match actor.seed.kind {
-        ActorKind::Human => rng.gen_bool(0.7),
-        ActorKind::Service => false,
+    let (session_credential_from_console, mfa_authenticated) = match actor.session_origin {
+        Some(SessionOrigin::Console { mfa_authenticated }) => (true, mfa_authenticated),
+        _ => (
+            user_agent.contains("CloudShell"),
+            match actor.seed.kind {
+                ActorKind::Human => rng.gen_bool(HUMAN_MFA_RATE),
+                ActorKind::Service => false,
+            },
+        ),
     };
     ActorContext {
-        identity_type: actor.seed.identity_type.clone(),
-        principal_id: actor.seed.principal_id.clone(),
-        arn: actor.seed.arn.clone(),
-        account_id: actor.seed.account_id.clone(),
-        access_key_id: Some(actor.seed.access_key_id.clone()),
-        user_name: actor.seed.user_name.clone(),
+        identity_type: actor.identity.identity_type.clone(),
+        principal_id: actor.identity.principal_id.clone(),
+        arn: actor.identity.arn.clone(),
+        account_id: actor.identity.account_id.clone(),
+        access_key_id: Some(actor.identity.access_key_id.clone()),
+        user_name: actor.identity.user_name.clone(),
         user_agent,
         source_ip: actor.current_source_ip(rng),
         region,
         mfa_authenticated,
         session_credential_from_console,
+        session_issuer: None,
+        session_created_at: None,
+        web_id_federation: None,
+        identity_provider: None,
     }
 }
 
@@ -758,49 +2099,78 @@ fn auditor_candidates(last: Option<&str>) -> Vec<(String, f64)> {
 fn build_schedule(
     actors: &[ActorProfile],
     start_time: DateTime<Utc>,
+    arrivals: &Arrivals,
+    mut linked: Option<&mut LinkedSessions>,
     rng: &mut impl Rng,
 ) -> BinaryHeap<Reverse<(DateTime<Utc>, usize)>> {
     let mut heap = BinaryHeap::with_capacity(actors.len());
     for (idx, actor) in actors.iter().enumerate() {
-        let base = actor.next_available_at(start_time);
-        let next_at = schedule_from(actor, base, rng);
+        let linked_at = linked
+            .as_deref_mut()
+            .and_then(|linked| linked.next_session_from(idx, start_time, arrivals));
+        let next_at = match linked_at {
+            Some(at) => at,
+            None => {
+                let base = actor.next_available_at(start_time);
+                schedule_from(actor, base, arrivals, rng)
+            }
+        };
         heap.push(Reverse((next_at, idx)));
     }
     heap
 }
 
-fn schedule_after(actor: &ActorProfile, now: DateTime<Utc>, rng: &mut impl Rng) -> DateTime<Utc> {
-    let rate = effective_rate(actor, now, rng);
-    let next = now + sample_interval(rate, rng);
+fn schedule_after(
+    actor: &ActorProfile,
+    now: DateTime<Utc>,
+    arrivals: &Arrivals,
+    rng: &mut impl Rng,
+) -> DateTime<Utc> {
+    let rate = effective_rate(actor, now, arrivals, rng);
+    let next = now + sample_interval(rate, arrivals, rng);
     actor.next_available_at(next)
 }
 
-fn schedule_from(actor: &ActorProfile, base: DateTime<Utc>, rng: &mut impl Rng) -> DateTime<Utc> {
-    let rate = effective_rate(actor, base, rng);
-    let next = base + sample_interval(rate, rng);
+fn schedule_from(
+    actor: &ActorProfile,
+    base: DateTime<Utc>,
+    arrivals: &Arrivals,
+    rng: &mut impl Rng,
+) -> DateTime<Utc> {
+    let rate = effective_rate(actor, base, arrivals, rng);
+    let next = base + sample_interval(rate, arrivals, rng);
     actor.next_available_at(next)
 }
 
-fn sample_interval(rate_per_hour: f64, rng: &mut impl Rng) -> Duration {
-    let rate = if rate_per_hour.is_finite() && rate_per_hour > 0.0 {
-        rate_per_hour
-    } else {
-        0.001
-    };
-    let lambda = rate / 3600.0;
+fn sample_interval(rate_per_hour: f64, arrivals: &Arrivals, rng: &mut impl Rng) -> Duration {
     let u: f64 = rng.gen_range(0.0..1.0);
-    let secs = -u.ln() / lambda;
+    let secs = arrivals.interval_secs(rate_per_hour, u);
     Duration::milliseconds((secs * 1000.0).max(1.0) as i64)
 }
 
-fn effective_rate(actor: &ActorProfile, now: DateTime<Utc>, rng: &mut impl Rng) -> f64 {
+fn effective_rate(
+    actor: &ActorProfile,
+    now: DateTime<Utc>,
+    arrivals: &Arrivals,
+    rng: &mut impl Rng,
+) -> f64 {
     let base = if actor.seed.rate_per_hour.is_finite() && actor.seed.rate_per_hour > 0.0 {
         actor.seed.rate_per_hour
     } else {
         0.001
     };
+    if actor.seed.on_call_off_hours(now) {
+        return base * OnCallShift::OFF_HOURS_RATE;
+    }
     if matches!(actor.seed.kind, ActorKind::Human) {
-        return base;
+        let local = now + Duration::hours(actor.seed.timezone_offset as i64);
+        return base
+            * arrivals.workday().multiplier(
+                &actor.seed.principal_id,
+                local,
+                actor.seed.active_start_hour,
+                actor.seed.active_hours,
+            );
     }
 
     let pattern = actor
@@ -811,7 +2181,7 @@ fn effective_rate(actor: &ActorProfile, now: DateTime<Utc>, rng: &mut impl Rng)
     match pattern {
         ServicePattern::Constant => base,
         ServicePattern::Diurnal => base * diurnal_multiplier(actor, now),
-        ServicePattern::Bursty => base * burst_multiplier(rng),
+        ServicePattern::Bursty => base * burst_multiplier(arrivals, rng),
     }
 }
 
@@ -827,9 +2197,9 @@ fn diurnal_multiplier(actor: &ActorProfile, now: DateTime<Utc>) -> f64 {
     }
 }
 
-fn burst_multiplier(rng: &mut impl Rng) -> f64 {
-    if rng.gen_bool(0.12) {
-        rng.gen_range(2.0..5.0)
+fn burst_multiplier(arrivals: &Arrivals, rng: &mut impl Rng) -> f64 {
+    if rng.gen_bool(arrivals.burst_probability()) {
+        rng.gen_range(arrivals.burst_range())
     } else {
         rng.gen_range(0.4..1.0)
     }
@@ -917,7 +2287,14 @@ fn shuffle_actors(actors: &mut [ActorProfile], rng: &mut impl Rng) {
 mod tests {
     use super::*;
     use crate::core::actors::ActorSeed;
+    use crate::core::config::{
+        ActorInjectionConfig, ApiStormConfig, CloudTrailSourceConfig, CredentialRotationConfig,
+        CredentialTakeoverConfig, EntraFederationConfig, ImpossibleTravelConfig, NarrativeConfig,
+        OktaRiskConfig, PolicyRemediationConfig, RateLimitAction, RateLimitConfig,
+        WebIdentityConfig, WebIdentityProvider,
+    };
     use std::collections::HashMap;
+    use std::sync::{Arc, RwLock};
 
     #[test]
     fn schedule_after_does_not_clamp_to_session_end() {
@@ -940,6 +2317,8 @@ mod tests {
             event_bias: HashMap::new(),
             service_profile: None,
             service_pattern: None,
+            insider_persona: None,
+            on_call: None,
             user_name: Some("actor-1".to_string()),
             display_name: Some("Actor One".to_string()),
             email: Some("actor-1@example.com".to_string()),
@@ -956,8 +2335,1010 @@ mod tests {
         actor.session_end_at = Some(session_end);
 
         let mut rng = StdRng::seed_from_u64(42);
-        let next = schedule_after(&actor, now, &mut rng);
+        let next = schedule_after(&actor, now, &Arrivals::default(), &mut rng);
 
         assert!(next > session_end);
     }
+
+    #[test]
+    fn service_call_graph_chains_assumed_role_sessions() {
+        let config = CloudTrailSourceConfig {
+            service_call_graph: true,
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, service_registry(), Some(7), start)
+                .unwrap();
+
+        let mut session: Option<(String, String, String)> = None;
+        let mut chained = 0;
+        let mut sources = HashSet::new();
+        for _ in 0..200 {
+            let event = generator.next_event().unwrap();
+            let payload = &event.payload;
+            let identity = &payload["userIdentity"];
+            if payload["eventName"] == "AssumeRole" {
+                assert_eq!(identity["type"], "IAMUser");
+                if payload.get("errorCode").is_none() {
+                    let response = &payload["responseElements"];
+                    session = Some((
+                        payload["requestParameters"]["roleArn"]
+                            .as_str()
+                            .unwrap()
+                            .to_string(),
+                        response["credentials"]["accessKeyId"]
+                            .as_str()
+                            .unwrap()
+                            .to_string(),
+                        payload["eventTime"].as_str().unwrap().to_string(),
+                    ));
+                    assert_eq!(
+                        response["assumedRoleUser"]["arn"]
+                            .as_str()
+                            .unwrap()
+                            .split('/')
+                            .nth(1),
+                        payload["requestParameters"]["roleArn"]
+                            .as_str()
+                            .unwrap()
+                            .rsplit('/')
+                            .next()
+                    );
+                }
+                continue;
+            }
+            let Some((role_arn, access_key_id, created_at)) = &session else {
+                continue;
+            };
+            assert_eq!(identity["type"], "AssumedRole");
+            assert_eq!(event.envelope.actor.id, "svc-ingest");
+            let context = &identity["sessionContext"];
+            assert_eq!(context["sessionIssuer"]["type"], "Role");
+            assert_eq!(context["sessionIssuer"]["arn"], role_arn.as_str());
+            assert_eq!(identity["accessKeyId"], access_key_id.as_str());
+            assert_eq!(context["attributes"]["creationDate"], created_at.as_str());
+            assert!(identity["arn"]
+                .as_str()
+                .unwrap()
+                .starts_with("arn:aws:sts::123456789012:assumed-role/workload-role-"));
+            sources.insert(payload["eventSource"].as_str().unwrap().to_string());
+            chained += 1;
+        }
+
+        assert!(chained > 100);
+        assert!(sources.contains("s3.amazonaws.com"));
+        assert!(sources.contains("logs.amazonaws.com"));
+    }
+
+    fn service_registry() -> IdentityRegistry {
+        let identity = Identity {
+            actor_id: "svc-ingest".to_string(),
+            email: "svc-ingest@example.com".to_string(),
+            employee_id: "S-1".to_string(),
+            display_name: "Ingest Service".to_string(),
+            role_persona: "Service".to_string(),
+            department: "Platform".to_string(),
+            home_location: "Sydney, NSW, Australia".to_string(),
+            normal_countries_regions: Vec::new(),
+            okta_user_id: "00u-svc".to_string(),
+            databricks_username: "svc-ingest@example.com".to_string(),
+            aws_principals: vec![AwsPrincipal {
+                account_id: "123456789012".to_string(),
+                principal_id: "AIDASVCINGEST".to_string(),
+                arn: "arn:aws:iam::123456789012:user/svc-ingest".to_string(),
+                role_name: None,
+                role_session_name: None,
+                access_key_id: None,
+            }],
+            service_account: true,
+            tags: Vec::new(),
+            rate_per_hour: Some(120.0),
+            active_start_hour: None,
+            active_hours: None,
+            timezone_offset: None,
+            weekend_active: None,
+            service_pattern: None,
+            insider_persona: None,
+            on_call: None,
+        };
+        IdentityRegistry::new("test", vec![identity]).unwrap()
+    }
+
+    #[test]
+    fn credential_takeover_interleaves_attacker_sessions() {
+        let mut registry = service_registry();
+        let mut identity = registry.identities()[0].clone();
+        identity.service_account = false;
+        identity.rate_per_hour = Some(60.0);
+        identity.active_start_hour = Some(0);
+        identity.active_hours = Some(24);
+        identity.weekend_active = Some(true);
+        registry = IdentityRegistry::new("test", vec![identity]).unwrap();
+        let config = CloudTrailSourceConfig {
+            credential_takeovers: vec![CredentialTakeoverConfig {
+                actor_id: "svc-ingest".to_string(),
+                offset_seconds: Some(3600),
+                start_time: None,
+                duration_seconds: Some(7200),
+                events_per_hour: Some(60.0),
+                source_ips: Some(vec!["203.0.113.99".to_string()]),
+                user_agents: None,
+                regions: None,
+                scenario_id: Some("ato".to_string()),
+                technique_id: None,
+                technique_ids: Vec::new(),
+            }],
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, registry, Some(3), start).unwrap();
+
+        let (mut attacker, mut victim_during) = (0, 0);
+        for _ in 0..400 {
+            let event = generator.next_event().unwrap();
+            let time = DateTime::parse_from_rfc3339(&event.envelope.timestamp).unwrap();
+            let during = time >= start + Duration::hours(1) && time < start + Duration::hours(3);
+            assert_eq!(event.envelope.actor.id, "svc-ingest");
+            assert_eq!(
+                event.payload["userIdentity"]["principalId"],
+                "AIDASVCINGEST"
+            );
+            match event.label {
+                Some(label) => {
+                    assert!(during);
+                    assert_eq!(label.scenario_id, "ato");
+                    assert_eq!(label.technique_ids, vec!["T1078.004"]);
+                    assert_eq!(event.payload["sourceIPAddress"], "203.0.113.99");
+                    attacker += 1;
+                }
+                None => {
+                    assert_ne!(event.payload["sourceIPAddress"], "203.0.113.99");
+                    victim_during += usize::from(during);
+                }
+            }
+        }
+        assert!(attacker > 60);
+        assert!(victim_during > 60);
+    }
+
+    #[test]
+    fn actor_injection_takes_traffic_share_mid_run() {
+        let config = CloudTrailSourceConfig {
+            actor_injections: vec![ActorInjectionConfig {
+                id: "svc-noisy".to_string(),
+                offset_seconds: Some(3600),
+                start_time: None,
+                duration_seconds: Some(7200),
+                count: Some(2),
+                service_profile: Some("logs_shipper".to_string()),
+                traffic_share: Some(0.3),
+                events_per_hour: None,
+                account_id: None,
+                error_rate: None,
+                scenario_id: Some("noisy-neighbor".to_string()),
+            }],
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, service_registry(), Some(9), start)
+                .unwrap();
+
+        let (mut injected, mut during) = (0, 0);
+        let mut injected_ids = HashSet::new();
+        loop {
+            let event = generator.next_event().unwrap();
+            let time = DateTime::parse_from_rfc3339(&event.envelope.timestamp).unwrap();
+            if time >= start + Duration::hours(4) {
+                break;
+            }
+            let window = time >= start + Duration::hours(1) && time < start + Duration::hours(3);
+            during += usize::from(window);
+            if event.envelope.actor.id.starts_with("svc-noisy") {
+                assert!(window, "injected call outside its window at {time}");
+                assert_eq!(event.label.unwrap().scenario_id, "noisy-neighbor");
+                assert_eq!(event.payload["recipientAccountId"], "123456789012");
+                injected_ids.insert(event.envelope.actor.id.clone());
+                injected += 1;
+            } else {
+                assert!(event.label.is_none());
+            }
+        }
+        let share = injected as f64 / during as f64;
+        assert!((0.2..0.4).contains(&share), "injected share {share}");
+        assert_eq!(injected_ids.len(), 2);
+    }
+
+    #[test]
+    fn console_sessions_share_browser_and_console_flag() {
+        let mut identity = service_registry().identities()[0].clone();
+        identity.service_account = false;
+        identity.rate_per_hour = Some(60.0);
+        identity.active_start_hour = Some(0);
+        identity.active_hours = Some(24);
+        identity.weekend_active = Some(true);
+        let registry = IdentityRegistry::new("test", vec![identity]).unwrap();
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&service_config(), registry, Some(5), start)
+                .unwrap();
+
+        let (mut console, mut internal, mut programmatic) = (0, 0, 0);
+        for _ in 0..1500 {
+            let event = generator.next_event().unwrap();
+            let payload = &event.payload;
+            if payload["eventName"] == "ConsoleLogin" {
+                assert!(payload.get("sessionCredentialFromConsole").is_none());
+                continue;
+            }
+            let user_agent = payload["userAgent"].as_str().unwrap();
+            if payload["sessionCredentialFromConsole"] == true {
+                if user_agent == AWS_INTERNAL {
+                    assert_eq!(payload["sourceIPAddress"], AWS_INTERNAL);
+                    assert_eq!(payload["readOnly"], true);
+                    assert!(event.envelope.ip.is_none());
+                    internal += 1;
+                } else {
+                    assert!(user_agent.starts_with("Mozilla/"));
+                    console += 1;
+                }
+            } else {
+                assert!(!user_agent.starts_with("Mozilla/"));
+                programmatic += 1;
+            }
+        }
+        assert!(console > 50);
+        assert!(internal > 0);
+        assert!(programmatic > 50);
+    }
+
+    #[test]
+    fn api_storm_throttles_one_service_inside_its_window() {
+        let config = CloudTrailSourceConfig {
+            api_storms: vec![ApiStormConfig {
+                service: "ec2.amazonaws.com".to_string(),
+                offset_seconds: Some(600),
+                start_time: None,
+                duration_seconds: 60,
+                actors: None,
+                events_per_second: Some(10.0),
+                error_rate: Some(0.9),
+                error_code: None,
+                events: None,
+                region: Some("us-west-2".to_string()),
+                scenario_id: None,
+                technique_id: None,
+                technique_ids: Vec::new(),
+            }],
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, service_registry(), Some(5), start)
+                .unwrap();
+
+        let (mut storm, mut throttled) = (0, 0);
+        for _ in 0..2_000 {
+            let event = generator.next_event().unwrap();
+            let Some(label) = event.label else {
+                continue;
+            };
+            let time = DateTime::parse_from_rfc3339(&event.envelope.timestamp).unwrap();
+            assert!(time >= start + Duration::seconds(600));
+            assert!(time < start + Duration::seconds(660));
+            assert_eq!(label.scenario_id, "api_storm");
+            assert_eq!(event.payload["eventSource"], "ec2.amazonaws.com");
+            assert_eq!(event.payload["awsRegion"], "us-west-2");
+            storm += 1;
+            if event.payload["errorCode"] == "RequestLimitExceeded" {
+                throttled += 1;
+            }
+        }
+        assert!((450..=750).contains(&storm), "storm events: {storm}");
+        assert!(throttled * 10 > storm * 8);
+    }
+
+    #[test]
+    fn followed_scenarios_are_replanned_mid_run() {
+        let storm = |offset_seconds, duration_seconds, scenario_id: &str| ApiStormConfig {
+            service: "ec2.amazonaws.com".to_string(),
+            offset_seconds: Some(offset_seconds),
+            start_time: None,
+            duration_seconds,
+            actors: None,
+            events_per_second: Some(1.0),
+            error_rate: None,
+            error_code: None,
+            events: None,
+            region: None,
+            scenario_id: Some(scenario_id.to_string()),
+            technique_id: None,
+            technique_ids: Vec::new(),
+        };
+        let config = CloudTrailSourceConfig {
+            api_storms: vec![storm(600, 60, "moved")],
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let scenarios = Arc::new(RwLock::new(ScenarioSchedule::from_config(&config)));
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, service_registry(), Some(5), start)
+                .unwrap();
+        generator.follow_scenarios(Arc::clone(&scenarios));
+        let offset = |event: &Event| {
+            let time = DateTime::parse_from_rfc3339(&event.envelope.timestamp).unwrap();
+            (time.with_timezone(&Utc) - start).num_seconds()
+        };
+
+        let mut reloaded_at = 0;
+        while reloaded_at < 300 {
+            let event = generator.next_event().unwrap();
+            assert!(event.label.is_none());
+            reloaded_at = offset(&event);
+        }
+        {
+            let mut schedule = scenarios.write().unwrap();
+            schedule.api_storms = vec![storm(1_200, 60, "moved"), storm(0, 3_600, "ongoing")];
+            schedule.revision += 1;
+        }
+
+        let mut labeled = HashMap::<String, Vec<i64>>::new();
+        loop {
+            let event = generator.next_event().unwrap();
+            let at = offset(&event);
+            if at > 1_300 {
+                break;
+            }
+            if let Some(label) = event.label {
+                labeled.entry(label.scenario_id).or_default().push(at);
+            }
+        }
+        let moved = &labeled["moved"];
+        assert!(moved.len() > 30, "moved storm events: {}", moved.len());
+        assert!(moved.iter().all(|at| (1_200..1_260).contains(at)));
+        let ongoing = &labeled["ongoing"];
+        assert!(
+            ongoing.len() > 600,
+            "ongoing storm events: {}",
+            ongoing.len()
+        );
+        assert!(ongoing.iter().all(|at| *at >= reloaded_at));
+    }
+
+    #[test]
+    fn rate_limits_throttle_or_defer_calls_over_the_cap() {
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for on_limit in [RateLimitAction::Throttle, RateLimitAction::Defer] {
+            let config = CloudTrailSourceConfig {
+                rate_limits: vec![RateLimitConfig {
+                    service: "logs.amazonaws.com".to_string(),
+                    max_calls: 2,
+                    window_seconds: Some(600),
+                    events: None,
+                    read_only: None,
+                    on_limit,
+                    error_code: None,
+                }],
+                ..service_config()
+            };
+            let mut generator =
+                CloudTrailGenerator::from_registry(&config, service_registry(), Some(5), start)
+                    .unwrap();
+
+            let (mut counted, mut throttled) = (Vec::new(), 0);
+            for _ in 0..500 {
+                let event = generator.next_event().unwrap();
+                if event.payload["eventSource"] != "logs.amazonaws.com" {
+                    continue;
+                }
+                if event.payload["errorCode"] == "ThrottlingException" {
+                    throttled += 1;
+                } else {
+                    counted.push(DateTime::parse_from_rfc3339(&event.envelope.timestamp).unwrap());
+                }
+            }
+            // No 10 minutes hold more than 2 counted calls.
+            assert!(counted.len() > 20, "counted: {}", counted.len());
+            for calls in counted.windows(3) {
+                assert!(calls[2] - calls[0] >= Duration::seconds(600));
+            }
+            match on_limit {
+                RateLimitAction::Throttle => assert!(throttled > counted.len()),
+                RateLimitAction::Defer => assert_eq!(throttled, 0),
+            }
+        }
+    }
+
+    #[test]
+    fn credential_rotation_swaps_keys_and_rotates_entra_secrets() {
+        let config = CloudTrailSourceConfig {
+            credential_rotation: Some(CredentialRotationConfig {
+                interval_days: Some(1.0),
+                deactivate_after_hours: Some(1.0),
+                delete_after_hours: Some(2.0),
+            }),
+            entra_federation: Some(EntraFederationConfig::default()),
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, service_registry(), Some(5), start)
+                .unwrap();
+
+        let mut key: Option<String> = None;
+        let mut retired = Vec::new();
+        let (mut created, mut deactivated, mut deleted, mut secrets) = (0, 0, 0, 0);
+        for _ in 0..20_000 {
+            let event = generator.next_event().unwrap();
+            if event.envelope.source == "entra_audit" {
+                assert_eq!(event.payload["category"], "ApplicationManagement");
+                assert_eq!(
+                    event.payload["initiatedBy"]["app"]["displayName"],
+                    "Ingest Service"
+                );
+                secrets += 1;
+                continue;
+            }
+            let payload = &event.payload;
+            let params = &payload["requestParameters"];
+            let key = key.get_or_insert_with(|| {
+                payload["userIdentity"]["accessKeyId"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            });
+            assert_eq!(payload["userIdentity"]["accessKeyId"], key.as_str());
+            match payload["eventName"].as_str().unwrap() {
+                "CreateAccessKey" => {
+                    let new_key = payload["responseElements"]["accessKey"]["accessKeyId"]
+                        .as_str()
+                        .unwrap()
+                        .to_string();
+                    retired.push(std::mem::replace(key, new_key));
+                    created += 1;
+                }
+                "UpdateAccessKey" => {
+                    assert_eq!(params["status"], "Inactive");
+                    assert_eq!(params["accessKeyId"], retired.last().unwrap().as_str());
+                    deactivated += 1;
+                }
+                "DeleteAccessKey" => {
+                    assert_eq!(params["accessKeyId"], retired.last().unwrap().as_str());
+                    deleted += 1;
+                }
+                _ => {}
+            }
+        }
+
+        assert!(created >= 2, "rotations: {created}");
+        assert!(deactivated >= created - 1);
+        assert!(deleted >= created - 1);
+        assert!(secrets >= created * 2 - 1);
+    }
+
+    #[test]
+    fn policy_remediation_wave_spans_accounts_with_spot_checks() {
+        let mut identities = service_registry().identities().to_vec();
+        let mut admin = identities[0].clone();
+        admin.actor_id = "user-admin".to_string();
+        admin.email = "admin@example.com".to_string();
+        admin.employee_id = "E-1".to_string();
+        admin.okta_user_id = "00u-admin".to_string();
+        admin.databricks_username = "admin@example.com".to_string();
+        admin.role_persona = "Cloud Admin".to_string();
+        admin.service_account = false;
+        admin.aws_principals[0].account_id = "210987654321".to_string();
+        admin.aws_principals[0].principal_id = "AIDAUSERADMIN".to_string();
+        admin.aws_principals[0].arn = "arn:aws:iam::210987654321:user/admin".to_string();
+        identities.push(admin);
+        let registry = IdentityRegistry::new("test", identities).unwrap();
+        let config = CloudTrailSourceConfig {
+            policy_remediations: vec![PolicyRemediationConfig {
+                offset_seconds: Some(600),
+                duration_seconds: Some(600),
+                changes: Some(30),
+                spot_check_rate: Some(1.0),
+                ..PolicyRemediationConfig::default()
+            }],
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, registry, Some(3), start).unwrap();
+
+        let mut changed = HashSet::new();
+        let (mut changes, mut checks) = (0, 0);
+        let mut last = start;
+        for _ in 0..3_000 {
+            let event = generator.next_event().unwrap();
+            let time = DateTime::parse_from_rfc3339(&event.envelope.timestamp)
+                .unwrap()
+                .with_timezone(&Utc);
+            assert!(time >= last, "out of order at {time}");
+            last = time;
+            let Some(label) = event.label else {
+                continue;
+            };
+            assert_eq!(label.scenario_id, "policy_remediation");
+            assert_eq!(event.payload["eventSource"], "iam.amazonaws.com");
+            assert_eq!(event.payload["awsRegion"], "us-east-1");
+            let account = event.payload["recipientAccountId"].as_str().unwrap();
+            let role = event.payload["requestParameters"]["roleName"]
+                .as_str()
+                .unwrap();
+            let arn = event.payload["userIdentity"]["arn"].as_str().unwrap();
+            match event.payload["eventName"].as_str().unwrap() {
+                "PutRolePolicy" | "DetachRolePolicy" => {
+                    assert!(time >= start + Duration::seconds(600));
+                    assert!(time < start + Duration::seconds(1_200));
+                    assert_eq!(
+                        arn,
+                        format!(
+                            "arn:aws:sts::{account}:assumed-role/SecurityRemediation/svc-ingest"
+                        )
+                    );
+                    changed.insert((account.to_string(), role.to_string()));
+                    changes += 1;
+                }
+                _ => {
+                    assert_eq!(event.envelope.actor.id, "user-admin");
+                    assert!(arn.contains(":assumed-role/SecurityAudit/"));
+                    assert!(changed.contains(&(account.to_string(), role.to_string())));
+                    checks += 1;
+                }
+            }
+        }
+        assert_eq!(changes, 30);
+        assert_eq!(checks, 60);
+        let accounts = changed
+            .iter()
+            .map(|(account, _)| account)
+            .collect::<HashSet<_>>();
+        assert_eq!(accounts.len(), 2);
+    }
+
+    #[test]
+    fn deterministic_ids_follow_actor_sequence() {
+        let config = CloudTrailSourceConfig {
+            deterministic_ids: true,
+            linked_sessions: false,
+            session_correlation: 1.0,
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, service_registry(), Some(9), start)
+                .unwrap();
+
+        let mut sequences = HashMap::<String, u64>::new();
+        let mut ids = HashSet::new();
+        for _ in 0..200 {
+            let event = generator.next_event().unwrap();
+            let actor = event.envelope.actor.id.clone();
+            let sequence = sequences.entry(actor.clone()).or_insert(0);
+            let name = format!("9:cloudtrail:{actor}:{sequence}");
+            assert_eq!(
+                event.payload["eventID"],
+                derived_uuid(&format!("{name}:event"))
+            );
+            assert_eq!(
+                event.payload["requestID"],
+                derived_uuid(&format!("{name}:request"))
+            );
+            assert!(ids.insert(event.payload["eventID"].to_string()));
+            *sequence += 1;
+        }
+
+        assert!(matches!(
+            CloudTrailGenerator::from_registry(&config, service_registry(), None, start),
+            Err(CatalogError::UnseededIds)
+        ));
+    }
+
+    fn service_config() -> CloudTrailSourceConfig {
+        CloudTrailSourceConfig {
+            curated: true,
+            actor_population_path: None,
+            identity_registry_path: None,
+            actor_selector: None,
+            arrival: None,
+            baseline_source_ips: None,
+            regions: None,
+            region_distribution: None,
+            catalog_path: None,
+            service_call_graph: false,
+            web_identity: None,
+            entra_federation: None,
+            narratives: None,
+            s3_access_logs: None,
+            resolver_query_logs: None,
+            alb_access_logs: None,
+            data_events: None,
+            resource_pool: None,
+            credential_takeovers: Vec::new(),
+            actor_injections: Vec::new(),
+            error_profiles: None,
+            error_surges: Vec::new(),
+            api_storms: Vec::new(),
+            rate_limits: Vec::new(),
+            policy_remediations: Vec::new(),
+            impossible_travels: Vec::new(),
+            credential_rotation: None,
+            deterministic_ids: false,
+            linked_sessions: false,
+            session_correlation: 1.0,
+        }
+    }
+
+    #[test]
+    fn web_identity_sessions_carry_federation_data() {
+        let config = CloudTrailSourceConfig {
+            web_identity: Some(HashMap::from([(
+                "generic".to_string(),
+                WebIdentityConfig {
+                    provider: WebIdentityProvider::EksIrsa,
+                    issuer: None,
+                    subject: None,
+                },
+            )])),
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, service_registry(), Some(11), start)
+                .unwrap();
+
+        let mut provider = None;
+        let mut federated = 0;
+        for _ in 0..200 {
+            let event = generator.next_event().unwrap();
+            let payload = &event.payload;
+            let identity = &payload["userIdentity"];
+            assert_ne!(payload["eventName"], "AssumeRole");
+            if payload["eventName"] == "AssumeRoleWithWebIdentity" {
+                assert_eq!(identity["type"], "WebIdentityUser");
+                assert_eq!(
+                    identity["userName"],
+                    "system:serviceaccount:default:workload"
+                );
+                assert!(identity.get("arn").is_none());
+                assert!(identity.get("sessionContext").is_none());
+                if payload.get("errorCode").is_none() {
+                    assert_eq!(
+                        payload["responseElements"]["subjectFromWebIdentityToken"],
+                        "system:serviceaccount:default:workload"
+                    );
+                    provider = identity["identityProvider"].as_str().map(str::to_string);
+                }
+                continue;
+            }
+            let Some(provider) = &provider else {
+                continue;
+            };
+            assert!(provider.starts_with("arn:aws:iam::123456789012:oidc-provider/oidc.eks."));
+            let context = &identity["sessionContext"];
+            assert_eq!(identity["type"], "AssumedRole");
+            assert_eq!(
+                context["webIdFederationData"]["federatedProvider"],
+                provider.as_str()
+            );
+            assert_eq!(context["sessionIssuer"]["type"], "Role");
+            assert!(identity["arn"]
+                .as_str()
+                .unwrap()
+                .contains("/botocore-session-"));
+            federated += 1;
+        }
+        assert!(federated > 100);
+    }
+
+    #[test]
+    fn narratives_run_ordered_steps_on_shared_resources() {
+        let mut identity = service_registry().identities()[0].clone();
+        identity.service_account = false;
+        identity.rate_per_hour = Some(60.0);
+        identity.active_start_hour = Some(0);
+        identity.active_hours = Some(24);
+        identity.weekend_active = Some(true);
+        let registry = IdentityRegistry::new("test", vec![identity]).unwrap();
+        let config = CloudTrailSourceConfig {
+            narratives: Some(NarrativeConfig {
+                session_share: Some(1.0),
+                builtin: Some(true),
+                paths: Vec::new(),
+            }),
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, registry, Some(9), start).unwrap();
+
+        let (mut deploys, mut bucket) = (0, None);
+        for _ in 0..2000 {
+            let event = generator.next_event().unwrap();
+            let params = &event.payload["requestParameters"];
+            match event.payload["eventName"].as_str().unwrap() {
+                "CreateBucket" => bucket = params["bucketName"].as_str().map(str::to_string),
+                "PutObject"
+                    if params["key"]
+                        .as_str()
+                        .unwrap_or("")
+                        .starts_with("releases/") =>
+                {
+                    let bucket = bucket.as_deref().unwrap();
+                    assert_eq!(params["bucketName"], bucket);
+                    let service = bucket.split("-artifacts-").next().unwrap();
+                    assert!(params["key"]
+                        .as_str()
+                        .unwrap()
+                        .starts_with(&format!("releases/{service}-r")));
+                    deploys += 1;
+                }
+                _ => {}
+            }
+        }
+        assert!(deploys > 3, "deploys={deploys}");
+    }
+
+    #[test]
+    fn impossible_travel_signs_in_and_works_from_distant_cities() {
+        let mut identity = service_registry().identities()[0].clone();
+        identity.service_account = false;
+        let registry = IdentityRegistry::new("test", vec![identity]).unwrap();
+        let config = CloudTrailSourceConfig {
+            impossible_travels: vec![ImpossibleTravelConfig {
+                actor_id: "svc-ingest".to_string(),
+                offset_seconds: Some(3_600),
+                sign_in_location: Some("London".to_string()),
+                ..ImpossibleTravelConfig::default()
+            }],
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, registry, Some(11), start).unwrap();
+
+        let mut labeled = Vec::new();
+        let mut last = start;
+        for _ in 0..2_000 {
+            let event = generator.next_event().unwrap();
+            let time = DateTime::parse_from_rfc3339(&event.envelope.timestamp)
+                .unwrap()
+                .with_timezone(&Utc);
+            assert!(time >= last, "out of order at {time}");
+            last = time;
+            if let Some(label) = &event.label {
+                assert_eq!(label.scenario_id, "impossible_travel");
+                assert_eq!(label.technique_ids, vec!["T1078.004".to_string()]);
+                assert_eq!(event.envelope.actor.id, "svc-ingest");
+                labeled.push((time, event));
+            }
+        }
+        assert_eq!(labeled.len(), 7);
+        let (signed_in_at, sign_in) = &labeled[0];
+        assert_eq!(sign_in.envelope.source, "entra_signin");
+        assert_eq!(*signed_in_at, start + Duration::seconds(3_600));
+        let origin = sign_in.envelope.geo.as_ref().unwrap();
+        assert_eq!(origin.country, "GB");
+        assert_eq!(sign_in.payload["location"]["city"], "London");
+        assert!(sign_in.payload["location"]["geoCoordinates"]["latitude"].is_number());
+
+        let (logged_in_at, console_login) = &labeled[1];
+        assert_eq!(console_login.payload["eventName"], "ConsoleLogin");
+        assert_eq!(*logged_in_at - *signed_in_at, Duration::seconds(600));
+        for (_, event) in &labeled[1..] {
+            assert_eq!(event.envelope.source, "cloudtrail");
+            let geo = event.envelope.geo.as_ref().unwrap();
+            assert_eq!(geo.city.as_deref(), Some("Sydney"));
+            assert!(geo.lat.is_some() && geo.lon.is_some());
+            assert_eq!(event.envelope.ip, console_login.envelope.ip);
+            assert_ne!(event.envelope.ip, sign_in.envelope.ip);
+            assert_eq!(event.payload["userAgent"], sign_in.payload["userAgent"]);
+        }
+    }
+
+    #[test]
+    fn entra_sign_ins_report_applied_policies_and_risk() {
+        let mut identity = service_registry().identities()[0].clone();
+        identity.service_account = false;
+        identity.rate_per_hour = Some(60.0);
+        identity.active_start_hour = Some(0);
+        identity.active_hours = Some(24);
+        identity.weekend_active = Some(true);
+        let registry = IdentityRegistry::new("test", vec![identity]).unwrap();
+        let config = CloudTrailSourceConfig {
+            entra_federation: Some(EntraFederationConfig {
+                risk: Some(OktaRiskConfig {
+                    risky_actor_ratio: Some(1.0),
+                    risky_sign_in_rate: Some(1.0),
+                    anonymized_ip_share: Some(1.0),
+                }),
+                ..EntraFederationConfig::default()
+            }),
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, registry, Some(5), start).unwrap();
+
+        let (mut mfa, mut single_factor) = (0, 0);
+        for _ in 0..3_000 {
+            let event = generator.next_event().unwrap();
+            if event.envelope.source != "entra_signin" {
+                continue;
+            }
+            let record = &event.payload;
+            let policies = record["appliedConditionalAccessPolicies"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|policy| {
+                    assert_eq!(policy["result"], "success");
+                    assert_eq!(policy["enforcedGrantControls"], json!(["Mfa"]));
+                    policy["displayName"].as_str().unwrap()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(record["riskLevelDuringSignIn"], "high");
+            let passed_mfa = record["status"]["errorCode"] == 0
+                && record["authenticationRequirement"] == "multiFactorAuthentication";
+            if passed_mfa {
+                mfa += 1;
+                assert_eq!(
+                    policies,
+                    [
+                        "Require MFA for AWS access",
+                        "Require MFA for risky sign-ins"
+                    ]
+                );
+                assert_eq!(record["conditionalAccessStatus"], "success");
+                assert_eq!(record["riskState"], "remediated");
+                assert_eq!(record["riskDetail"], "userPassedMFADrivenByRiskBasedPolicy");
+                assert_eq!(record["riskLevelAggregated"], "none");
+            } else {
+                single_factor += 1;
+                assert!(policies.is_empty());
+                assert_eq!(record["conditionalAccessStatus"], "notApplied");
+                assert_eq!(record["riskState"], "atRisk");
+                assert_eq!(record["riskDetail"], "none");
+                assert_eq!(record["riskLevelAggregated"], "high");
+            }
+        }
+        assert!(
+            mfa > 0 && single_factor > 0,
+            "mfa={mfa} single={single_factor}"
+        );
+    }
+
+    #[test]
+    fn entra_federation_precedes_console_sign_ins() {
+        let mut identity = service_registry().identities()[0].clone();
+        identity.service_account = false;
+        identity.rate_per_hour = Some(60.0);
+        identity.active_start_hour = Some(0);
+        identity.active_hours = Some(24);
+        identity.weekend_active = Some(true);
+        let upn = identity.email.clone();
+        let registry = IdentityRegistry::new("test", vec![identity]).unwrap();
+        let config = CloudTrailSourceConfig {
+            entra_federation: Some(EntraFederationConfig::default()),
+            ..service_config()
+        };
+        let start = DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut generator =
+            CloudTrailGenerator::from_registry(&config, registry, Some(5), start).unwrap();
+
+        let time = |event: &Event| DateTime::parse_from_rfc3339(&event.envelope.timestamp).unwrap();
+        let (mut sign_ins, mut console_calls) = (0, 0);
+        let mut devices = HashMap::new();
+        let mut last = start.fixed_offset();
+        for _ in 0..1500 {
+            let event = generator.next_event().unwrap();
+            assert!(time(&event) >= last);
+            last = time(&event);
+            if event.envelope.source == "entra_signin" {
+                assert_eq!(event.payload["userPrincipalName"], upn.as_str());
+                if event.payload["status"]["errorCode"] != 0 {
+                    assert!(event.payload["mfaDetail"].is_null());
+                    continue;
+                }
+                let device = &event.payload["deviceDetail"];
+                let os = device["operatingSystem"].as_str().unwrap().to_string();
+                let device_id = device["deviceId"].as_str().unwrap().to_string();
+                assert_eq!(
+                    devices.entry(os).or_insert_with(|| device_id.clone()),
+                    &device_id
+                );
+                assert_eq!(device["isManaged"], !device_id.is_empty());
+                assert_eq!(device["trustType"] == "", device_id.is_empty());
+                if device["isCompliant"] == true {
+                    assert_eq!(device["isManaged"], true);
+                }
+                assert_eq!(
+                    event.payload["mfaDetail"]["authMethod"].is_string(),
+                    event.payload["authenticationRequirement"] == "multiFactorAuthentication"
+                );
+                assert!(event.payload["authenticationProcessingDetails"]
+                    .as_array()
+                    .is_some_and(|details| !details.is_empty()));
+                assert_eq!(event.payload["riskState"], "none");
+                assert_eq!(
+                    event.payload["conditionalAccessStatus"] == "success",
+                    event.payload["mfaDetail"].is_object()
+                );
+                let assume = generator.next_event().unwrap();
+                let login = generator.next_event().unwrap();
+                let identity = &assume.payload["userIdentity"];
+                assert_eq!(assume.payload["eventName"], "AssumeRoleWithSAML");
+                assert_eq!(identity["type"], "SAMLUser");
+                assert_eq!(identity["userName"], upn.as_str());
+                assert_eq!(
+                    assume.payload["sourceIPAddress"],
+                    event.payload["ipAddress"]
+                );
+                assert_eq!(
+                    assume.payload["responseElements"]["issuer"],
+                    format!(
+                        "https://sts.windows.net/{}/",
+                        event.payload["homeTenantId"].as_str().unwrap()
+                    )
+                );
+                let role_arn = assume.payload["requestParameters"]["roleArn"]
+                    .as_str()
+                    .unwrap();
+                assert!(role_arn.contains(":role/aws-reserved/sso.amazonaws.com/AWSReservedSSO_"));
+                assert_eq!(login.payload["eventName"], "ConsoleLogin");
+                assert_eq!(
+                    login.payload["userIdentity"]["arn"],
+                    assume.payload["responseElements"]["assumedRoleUser"]["arn"]
+                );
+                assert_eq!(
+                    login.payload["userIdentity"]["sessionContext"]["sessionIssuer"]["arn"],
+                    role_arn
+                );
+                assert!(time(&assume) >= time(&event));
+                assert!(time(&login) - time(&event) <= Duration::seconds(12));
+                last = time(&login);
+                sign_ins += 1;
+            } else if event.payload["sessionCredentialFromConsole"] == true {
+                assert_eq!(event.payload["userIdentity"]["type"], "AssumedRole");
+                console_calls += 1;
+            } else {
+                assert_ne!(event.payload["eventName"], "ConsoleLogin");
+            }
+        }
+        assert!(sign_ins > 10);
+        assert!(console_calls > 50);
+    }
 }
@@ -0,0 +1,150 @@
+//! Service actors that join a running CloudTrail stream.
+//!
+//! An injection adds actors at a configured time without restarting the run,
+//! e.g. a new automation account that suddenly takes 30% of the traffic. With
+//! `traffic_share` the actors' rate is sized against the call rate of the
+//! actors running when they join, so the share holds however the population
+//! was built. Injected actors call around the clock until `duration_seconds`
+//! runs out and can carry a scenario label.
+
+use crate::core::actors::{ActorProfile, ActorSeed, ServiceProfile};
+use crate::core::attack::scenario_label;
+use crate::core::config::ActorInjectionConfig;
+use crate::core::event::EventLabel;
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+
+const DEFAULT_EVENTS_PER_HOUR: f64 = 60.0;
+const DEFAULT_ERROR_RATE: f64 = 0.01;
+const DEFAULT_SERVICE_PROFILE: &str = "generic";
+
+/// An injection waiting for its join time.
+#[derive(Debug, Clone)]
+pub struct PlannedInjection {
+    pub at: DateTime<Utc>,
+    pub end_at: Option<DateTime<Utc>>,
+    config: ActorInjectionConfig,
+    profile: ServiceProfile,
+    label: Option<EventLabel>,
+}
+
+/// Runtime state of one injected actor.
+#[derive(Debug, Clone)]
+pub struct InjectedActor {
+    pub end_at: Option<DateTime<Utc>>,
+    pub label: Option<EventLabel>,
+}
+
+impl InjectedActor {
+    /// Returns whether the actor has stopped calling at `now`.
+    pub fn is_over(&self, now: DateTime<Utc>) -> bool {
+        self.end_at.is_some_and(|end| now >= end)
+    }
+}
+
+/// Resolves an injection's timing and label and checks its settings.
+pub fn plan_injection(
+    config: &ActorInjectionConfig,
+    start_time: DateTime<Utc>,
+) -> Result<PlannedInjection, String> {
+    if config.id.trim().is_empty() {
+        return Err("id must be non-empty".to_string());
+    }
+    let at = match &config.start_time {
+        Some(raw) => DateTime::parse_from_rfc3339(raw)
+            .map_err(|_| format!("invalid start_time: {raw}"))?
+            .with_timezone(&Utc),
+        None => start_time + Duration::seconds(config.offset_seconds.unwrap_or(0)),
+    };
+    let end_at = match config.duration_seconds {
+        Some(seconds) if seconds <= 0 => {
+            return Err("duration_seconds must be greater than 0".to_string())
+        }
+        Some(seconds) => Some(at + Duration::seconds(seconds)),
+        None => None,
+    };
+    if config.count == Some(0) {
+        return Err("count must be greater than 0".to_string());
+    }
+    if let Some(share) = config.traffic_share {
+        if !(share > 0.0 && share < 1.0) {
+            return Err("traffic_share must be between 0.0 and 1.0 (exclusive)".to_string());
+        }
+    }
+    if let Some(rate) = config.events_per_hour {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err("events_per_hour must be greater than 0".to_string());
+        }
+    }
+    if config
+        .error_rate
+        .is_some_and(|rate| !(0.0..=1.0).contains(&rate))
+    {
+        return Err("error_rate must be between 0.0 and 1.0".to_string());
+    }
+    if let Some(account_id) = &config.account_id {
+        if account_id.len() != 12 || !account_id.chars().all(|c| c.is_ascii_digit()) {
+            return Err("account_id must be a 12-digit string".to_string());
+        }
+    }
+    let profile_name = config
+        .service_profile
+        .as_deref()
+        .unwrap_or(DEFAULT_SERVICE_PROFILE);
+    let profile = ServiceProfile::parse(profile_name)
+        .ok_or_else(|| format!("invalid service_profile: {profile_name}"))?;
+    let label = scenario_label(config.scenario_id.as_ref(), None, &[])
+        .map_err(|id| format!("invalid ATT&CK technique ID: {id}"))?;
+    Ok(PlannedInjection {
+        at,
+        end_at,
+        config: config.clone(),
+        profile,
+        label,
+    })
+}
+
+impl PlannedInjection {
+    /// Builds the injected actors.
+    ///
+    /// `running_rate` is the summed call rate of the actors running at the
+    /// join time; `traffic_share` splits `share / (1 - share)` of it across
+    /// the new actors.
+    pub fn actors(
+        &self,
+        running_rate: f64,
+        default_account: &str,
+        rng: &mut impl Rng,
+    ) -> (Vec<ActorProfile>, InjectedActor) {
+        let config = &self.config;
+        let count = config.count.unwrap_or(1);
+        let rate_per_hour = match config.traffic_share {
+            Some(share) => running_rate * share / (1.0 - share) / count as f64,
+            None => config.events_per_hour.unwrap_or(DEFAULT_EVENTS_PER_HOUR),
+        };
+        let account_id = config.account_id.as_deref().unwrap_or(default_account);
+        let actors = (1..=count)
+            .map(|idx| {
+                let id = if count == 1 {
+                    config.id.clone()
+                } else {
+                    format!("{}-{idx}", config.id)
+                };
+                let seed = ActorSeed::always_on_service(
+                    rng,
+                    &id,
+                    account_id,
+                    self.profile.clone(),
+                    rate_per_hour.max(f64::MIN_POSITIVE),
+                    config.error_rate.unwrap_or(DEFAULT_ERROR_RATE),
+                );
+                ActorProfile::from_seed(seed)
+            })
+            .collect();
+        let state = InjectedActor {
+            end_at: self.end_at,
+            label: self.label.clone(),
+        };
+        (actors, state)
+    }
+}
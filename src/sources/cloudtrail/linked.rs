@@ -0,0 +1,103 @@
+//! Linked sessions for human CloudTrail actors.
+//!
+//! With `linked_sessions` set, a registry-backed human starts each session at
+//! the next instant of the identity's shared cross-source clock, after the
+//! identity provider sign-in other linked sources emit for it. Calls inside a
+//! session keep CloudTrail's own scheduler; when the session ends, the actor
+//! waits for the next shared instant.
+
+use crate::core::activity::LinkedSessionClock;
+use crate::core::actors::{ActorKind, ActorProfile};
+use crate::core::arrival::Arrivals;
+use crate::core::identity::{Identity, IdentityRegistry};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+const SOURCE_SALT: &str = "cloudtrail";
+
+/// Shared clock and per-actor session progress of the linked human actors.
+pub struct LinkedSessions {
+    clock: LinkedSessionClock,
+    /// Linked state per actor index; `None` for service and unregistered actors.
+    actors: Vec<Option<LinkedActor>>,
+}
+
+struct LinkedActor {
+    /// Index of the identity on the shared clock.
+    slot: usize,
+    identity: Identity,
+    /// Shared instants taken so far.
+    sessions: usize,
+    /// Whether the actor's next call opens a linked session.
+    opening: bool,
+}
+
+impl LinkedSessions {
+    /// Links every human actor whose actor ID is in `registry`.
+    pub fn new(
+        registry: &IdentityRegistry,
+        actors: &[ActorProfile],
+        start_time: DateTime<Utc>,
+        correlation: f64,
+    ) -> Self {
+        let mut identities: Vec<&Identity> = registry.identities().iter().collect();
+        identities.sort_by(|left, right| left.actor_id.cmp(&right.actor_id));
+        let slots = identities
+            .iter()
+            .enumerate()
+            .map(|(slot, identity)| (identity.actor_id.as_str(), slot))
+            .collect::<HashMap<_, _>>();
+        let actors = actors
+            .iter()
+            .map(|actor| {
+                if !matches!(actor.seed.kind, ActorKind::Human) {
+                    return None;
+                }
+                let slot = *slots.get(actor.seed.id.as_deref()?)?;
+                Some(LinkedActor {
+                    slot,
+                    identity: identities[slot].clone(),
+                    sessions: 0,
+                    opening: false,
+                })
+            })
+            .collect();
+        Self {
+            clock: LinkedSessionClock::new(identities.len(), start_time, correlation),
+            actors,
+        }
+    }
+
+    /// Returns when the linked actor's next session starts, skipping shared
+    /// instants before `from`, or `None` when the actor is not linked.
+    pub fn next_session_from(
+        &mut self,
+        actor_index: usize,
+        from: DateTime<Utc>,
+        arrivals: &Arrivals,
+    ) -> Option<DateTime<Utc>> {
+        let actor = self.actors.get_mut(actor_index)?.as_mut()?;
+        actor.opening = true;
+        loop {
+            let at = self.clock.next_event_at(
+                actor.slot,
+                &actor.identity,
+                actor.sessions,
+                SOURCE_SALT,
+                arrivals,
+            );
+            actor.sessions += 1;
+            if at >= from {
+                return Some(at);
+            }
+        }
+    }
+
+    /// Returns whether the actor's call opens a linked session, clearing it.
+    pub fn take_opening(&mut self, actor_index: usize) -> bool {
+        match self.actors.get_mut(actor_index) {
+            Some(Some(actor)) => std::mem::take(&mut actor.opening),
+            _ => false,
+        }
+    }
+}
@@ -3,16 +3,33 @@
 //! Provides catalog-driven event selection and a `CloudTrailGenerator` source.
 
 pub mod catalog;
+pub mod data_events;
+pub mod errors;
+pub mod federation;
 pub mod generator;
+pub mod injection;
+pub mod linked;
 pub mod model;
+pub mod narrative;
+pub mod rate_limit;
+pub mod remediation;
+pub mod resources;
+pub mod rotation;
+pub mod scenarios;
+pub mod storm;
+pub mod takeover;
 pub mod templates;
+pub mod travel;
+pub mod workflow;
 
 pub use catalog::{
-    resolve_event_weights, resolve_selector, CatalogError, EventSelector, WeightedEvent,
+    load_config_catalog, merge_event_weights, resolve_event_weights, resolve_selector,
+    CatalogError, CatalogEventDefinition, CustomCatalog, EventSelector, WeightedEvent,
 };
 pub use generator::CloudTrailGenerator;
-pub use model::{CloudTrailEvent, UserIdentity};
+pub use model::{CloudTrailEvent, SessionIssuer, UserIdentity};
+pub use scenarios::{ScenarioSchedule, SharedScenarios};
 pub use templates::{
-    apply_error, build_cloudtrail_event, default_error_profile, ActorContext, ErrorProfile,
-    TemplateError,
+    apply_error, build_cloudtrail_event, build_custom_event, default_error_profile, ActorContext,
+    ErrorProfile, TemplateError,
 };
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,17 +18,22 @@ pub struct CloudTrailEvent {
     pub request_parameters: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_elements: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_event_data: Option<Value>,
     #[serde(rename = "requestID")]
     pub request_id: String,
     #[serde(rename = "eventID")]
     pub event_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub read_only: Option<bool>,
+    /// Resources a data event touched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Vec<Resource>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub event_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub management_event: Option<bool>,
-    pub recipient_account_id: String,
+    pub recipient_account_id: Arc<str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub event_category: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -50,14 +56,19 @@ impl CloudTrailEvent {
 #[serde(rename_all = "camelCase")]
 pub struct UserIdentity {
     #[serde(rename = "type")]
-    pub identity_type: String,
-    pub principal_id: String,
-    pub arn: String,
-    pub account_id: String,
+    pub identity_type: Arc<str>,
+    pub principal_id: Arc<str>,
+    /// Empty (and omitted) for `WebIdentityUser` callers.
+    #[serde(default, skip_serializing_if = "str::is_empty")]
+    pub arn: Arc<str>,
+    #[serde(default, skip_serializing_if = "str::is_empty")]
+    pub account_id: Arc<str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<Arc<str>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub access_key_id: Option<String>,
+    pub user_name: Option<Arc<str>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub user_name: Option<String>,
+    pub identity_provider: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_context: Option<SessionContext>,
 }
@@ -70,6 +81,26 @@ pub struct SessionContext {
     pub attributes: SessionAttributes,
 }
 
+/// OIDC provider behind credentials from `AssumeRoleWithWebIdentity`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebIdFederationData {
+    pub federated_provider: String,
+    pub attributes: serde_json::Map<String, Value>,
+}
+
+/// Role that issued the credentials of an assumed-role session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionIssuer {
+    #[serde(rename = "type")]
+    pub issuer_type: String,
+    pub principal_id: String,
+    pub arn: String,
+    pub account_id: String,
+    pub user_name: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionAttributes {
@@ -77,6 +108,18 @@ pub struct SessionAttributes {
     pub mfa_authenticated: String,
 }
 
+/// Entry of a data event's `resources` array.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    #[serde(rename = "ARN")]
+    pub arn: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TlsDetails {
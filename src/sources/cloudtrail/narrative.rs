@@ -0,0 +1,339 @@
+//! Task narratives for human CloudTrail sessions.
+//!
+//! Without narratives every human call is picked from the Markov transitions
+//! after the previous one. With `narratives` configured, a session can instead
+//! pick a task, such as deploying a service or rotating credentials, and run
+//! its ordered steps across services after signing in. A task draws its
+//! parameters once, so its steps share bucket, log group, and user names.
+//! Tasks are data: the built-in set ships as `narratives.yaml`, and files
+//! listed under `narratives.paths` add more.
+
+use super::generator::role_key;
+use super::model::CloudTrailEvent;
+use super::templates::{render_template, substitute_placeholders, ActorContext};
+use crate::core::actors::ActorRole;
+use crate::core::config::NarrativeConfig;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const BUILTIN_NARRATIVES: &str = include_str!("narratives.yaml");
+const DEFAULT_SESSION_SHARE: f64 = 0.5;
+const ROLES: [&str; 4] = ["admin", "developer", "readonly", "auditor"];
+
+/// Narratives loaded from the built-in set or a narrative file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NarrativeSet {
+    pub narratives: Vec<Narrative>,
+}
+
+/// One task a human session can run.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Narrative {
+    pub name: String,
+    /// Roles that run the task; all human roles when empty.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Selection weight among the tasks open to a role.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    /// Task parameters, drawn once per task and referenced as `{{name}}`.
+    #[serde(default)]
+    pub params: BTreeMap<String, NarrativeParam>,
+    pub steps: Vec<NarrativeStep>,
+}
+
+/// A parameter template, or a list to pick one template from.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum NarrativeParam {
+    One(String),
+    Choices(Vec<String>),
+}
+
+/// One call of a task.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NarrativeStep {
+    /// CloudTrail `eventName`.
+    pub event: String,
+    /// Chance that the step runs in one pass of the task.
+    #[serde(default = "default_chance")]
+    pub chance: f64,
+    /// Request parameters set on top of the event's template.
+    #[serde(default)]
+    pub request_parameters: Option<Value>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_chance() -> f64 {
+    1.0
+}
+
+impl NarrativeSet {
+    /// The narratives that ship with seclog.
+    pub fn builtin() -> Self {
+        Self::from_yaml(BUILTIN_NARRATIVES).expect("built-in narratives are valid")
+    }
+
+    /// Loads a narrative file, picking the parser from the file extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents =
+            std::fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => serde_json::from_str::<Self>(&contents).map_err(|err| err.to_string()),
+            "toml" => toml::from_str::<Self>(&contents).map_err(|err| err.to_string()),
+            _ => serde_yaml::from_str::<Self>(&contents).map_err(|err| err.to_string()),
+        }
+        .and_then(|set| set.validate().map(|_| set))
+        .map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    /// Parses narratives from YAML.
+    pub fn from_yaml(contents: &str) -> Result<Self, String> {
+        let set: Self = serde_yaml::from_str(contents).map_err(|err| err.to_string())?;
+        set.validate()?;
+        Ok(set)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for narrative in &self.narratives {
+            let name = &narrative.name;
+            if name.trim().is_empty() {
+                return Err("narrative name must not be empty".to_string());
+            }
+            if !narrative.weight.is_finite() || narrative.weight <= 0.0 {
+                return Err(format!("narrative {name}: weight must be greater than 0"));
+            }
+            if let Some(role) = narrative
+                .roles
+                .iter()
+                .find(|role| !ROLES.contains(&role.as_str()))
+            {
+                return Err(format!(
+                    "narrative {name}: unknown role {role}; expected one of {}",
+                    ROLES.join(", ")
+                ));
+            }
+            if narrative.steps.is_empty() {
+                return Err(format!("narrative {name} has no steps"));
+            }
+            for step in &narrative.steps {
+                if step.event.trim().is_empty() {
+                    return Err(format!("narrative {name}: step event must not be empty"));
+                }
+                if !(0.0..=1.0).contains(&step.chance) {
+                    return Err(format!(
+                        "narrative {name}: chance of {} must be between 0.0 and 1.0",
+                        step.event
+                    ));
+                }
+            }
+            if let Some(param) = narrative.params.iter().find_map(|(param, value)| {
+                matches!(value, NarrativeParam::Choices(choices) if choices.is_empty())
+                    .then_some(param)
+            }) {
+                return Err(format!("narrative {name}: param {param} has no choices"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The narratives configured for a generator and each actor's running task.
+#[derive(Debug, Clone)]
+pub struct NarrativeEngine {
+    narratives: Vec<Narrative>,
+    session_share: f64,
+    running: Vec<Option<RunningTask>>,
+}
+
+/// One pass through a task by one actor.
+#[derive(Debug, Clone)]
+struct RunningTask {
+    narrative: usize,
+    /// Indices of the steps that run in this pass.
+    steps: Vec<usize>,
+    next: usize,
+    /// Step handed out last, applied to the event built for it.
+    current: Option<usize>,
+    params: BTreeMap<String, String>,
+}
+
+impl NarrativeEngine {
+    /// Loads the built-in and configured narratives for `actors` actors.
+    pub fn new(config: &NarrativeConfig, actors: usize) -> Result<Self, String> {
+        let mut narratives = Vec::new();
+        if config.builtin.unwrap_or(true) {
+            narratives.extend(NarrativeSet::builtin().narratives);
+        }
+        for path in &config.paths {
+            narratives.extend(NarrativeSet::load(path)?.narratives);
+        }
+        let session_share = config.session_share.unwrap_or(DEFAULT_SESSION_SHARE);
+        if !(0.0..=1.0).contains(&session_share) {
+            return Err("session_share must be between 0.0 and 1.0".to_string());
+        }
+        if narratives.is_empty() {
+            return Err("no narratives; enable builtin or list narrative files".to_string());
+        }
+        Ok(Self {
+            narratives,
+            session_share,
+            running: vec![None; actors],
+        })
+    }
+
+    /// Makes room for actors added to a running generator.
+    pub fn add_actors(&mut self, count: usize) {
+        self.running.resize(self.running.len() + count, None);
+    }
+
+    /// Ends the actor's task and, for `session_share` of new sessions, plans
+    /// the next one. Steps outside the configured event set are dropped.
+    pub fn start_session(
+        &mut self,
+        actor_index: usize,
+        role: ActorRole,
+        allowed: impl Fn(&str) -> bool,
+        rng: &mut impl Rng,
+    ) {
+        self.running[actor_index] = None;
+        if !rng.gen_bool(self.session_share) {
+            return;
+        }
+        let role = role_key(role);
+        let open = self
+            .narratives
+            .iter()
+            .enumerate()
+            .filter(|(_, narrative)| {
+                narrative.roles.is_empty() || narrative.roles.iter().any(|name| name == role)
+            })
+            .collect::<Vec<_>>();
+        let Ok(index) = WeightedIndex::new(open.iter().map(|(_, narrative)| narrative.weight))
+        else {
+            return;
+        };
+        let (narrative_index, narrative) = open[index.sample(rng)];
+        let steps = narrative
+            .steps
+            .iter()
+            .enumerate()
+            .filter(|(_, step)| rng.gen_bool(step.chance) && allowed(&step.event))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        if steps.is_empty() {
+            return;
+        }
+        let params = narrative
+            .params
+            .iter()
+            .map(|(name, param)| {
+                let template = match param {
+                    NarrativeParam::One(template) => template.clone(),
+                    NarrativeParam::Choices(choices) => {
+                        choices[rng.gen_range(0..choices.len())].clone()
+                    }
+                };
+                (name.clone(), template)
+            })
+            .collect();
+        self.running[actor_index] = Some(RunningTask {
+            narrative: narrative_index,
+            steps,
+            next: 0,
+            current: None,
+            params,
+        });
+    }
+
+    /// Returns the actor's next task step, or `None` once the task is done.
+    pub fn next_step(&mut self, actor_index: usize) -> Option<String> {
+        let task = self.running[actor_index].as_mut()?;
+        let Some(&step) = task.steps.get(task.next) else {
+            self.running[actor_index] = None;
+            return None;
+        };
+        task.next += 1;
+        task.current = Some(step);
+        Some(self.narratives[task.narrative].steps[step].event.clone())
+    }
+
+    /// Sets the request parameters of the step just handed out on `event`.
+    ///
+    /// Parameters are rendered against the actor on the task's first call, so
+    /// templates such as `{{account_id}}` resolve the same way for every step.
+    pub fn apply(
+        &mut self,
+        actor_index: usize,
+        event: &mut CloudTrailEvent,
+        actor: &ActorContext,
+        rng: &mut impl Rng,
+    ) {
+        let Some(task) = self.running.get_mut(actor_index).and_then(Option::as_mut) else {
+            return;
+        };
+        let Some(step) = task.current.take() else {
+            return;
+        };
+        if task.next == 1 {
+            let templates = std::mem::take(&mut task.params);
+            task.params = templates
+                .into_iter()
+                .map(|(name, template)| {
+                    let value = substitute_placeholders(
+                        &template,
+                        actor,
+                        &event.event_time,
+                        &BTreeMap::new(),
+                        rng,
+                    );
+                    (name, value)
+                })
+                .collect();
+        }
+        let Some(Value::Object(overrides)) = self.narratives[task.narrative].steps[step]
+            .request_parameters
+            .as_ref()
+            .map(|template| render_template(template, actor, &event.event_time, &task.params, rng))
+        else {
+            return;
+        };
+        match event.request_parameters.as_mut() {
+            Some(Value::Object(params)) => params.extend(overrides),
+            _ => event.request_parameters = Some(Value::Object(overrides)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_narratives_load() {
+        let set = NarrativeSet::builtin();
+        assert!(set
+            .narratives
+            .iter()
+            .any(|narrative| narrative.name == "deploy_new_service"));
+        let err = NarrativeSet::from_yaml(
+            "narratives:\n  - name: broken\n    roles: [operator]\n    steps:\n      - event: GetObject\n",
+        )
+        .unwrap_err();
+        assert!(err.contains("unknown role operator"), "{err}");
+    }
+}
@@ -0,0 +1,210 @@
+//! Per-account rate limits on AWS service APIs.
+//!
+//! AWS caps how fast an account may call many APIs, IAM mutations being the
+//! tightest, and automation that runs into a cap backs off and retries. With
+//! `[[source.rate_limit]]` entries, regular actor calls to a service count
+//! against a sliding window per account. A call over the cap is throttled,
+//! going out failed with the service's throttling error, or deferred, leaving
+//! the actor to retry the same call once the oldest counted call leaves the
+//! window. Throttled and deferred calls do not count against the cap.
+
+use super::storm::throttling_error;
+use super::templates::ErrorProfile;
+use crate::core::config::{RateLimitAction, RateLimitConfig};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+const DEFAULT_WINDOW_SECONDS: i64 = 3_600;
+
+/// Every configured rate limit and the calls counted against each.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimits {
+    limits: Vec<RateLimit>,
+}
+
+#[derive(Debug, Clone)]
+struct RateLimit {
+    service: String,
+    events: Option<HashSet<String>>,
+    read_only: Option<bool>,
+    max_calls: usize,
+    window: Duration,
+    action: RateLimitAction,
+    error: ErrorProfile,
+    /// Times of counted calls in the window, oldest first, per account.
+    calls: HashMap<String, VecDeque<DateTime<Utc>>>,
+}
+
+/// What to do with an actor's next call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    Allow,
+    /// Emit the call failed with this error.
+    Throttle(ErrorProfile),
+    /// Retry the call at this time.
+    Defer(DateTime<Utc>),
+}
+
+/// An actor call checked against the limits.
+#[derive(Debug, Clone, Copy)]
+pub struct Call<'a> {
+    pub service: &'a str,
+    pub event_name: &'a str,
+    pub read_only: bool,
+    pub account_id: &'a str,
+    pub at: DateTime<Utc>,
+}
+
+impl RateLimits {
+    pub fn from_config(configs: &[RateLimitConfig]) -> Result<Self, String> {
+        let limits = configs
+            .iter()
+            .map(|config| {
+                if config.max_calls == 0 {
+                    return Err("max_calls must be greater than 0".to_string());
+                }
+                let window_seconds = config.window_seconds.unwrap_or(DEFAULT_WINDOW_SECONDS);
+                if window_seconds <= 0 {
+                    return Err("window_seconds must be greater than 0".to_string());
+                }
+                Ok(RateLimit {
+                    service: config.service.clone(),
+                    events: config
+                        .events
+                        .as_ref()
+                        .map(|events| events.iter().cloned().collect()),
+                    read_only: config.read_only,
+                    max_calls: config.max_calls,
+                    window: Duration::seconds(window_seconds),
+                    action: config.on_limit,
+                    error: throttling_error(&config.service, config.error_code.as_deref()),
+                    calls: HashMap::new(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { limits })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// Checks `call` against every limit it falls under and counts it if all
+    /// of them have room. Otherwise the first full limit decides.
+    pub fn admit(&mut self, call: Call<'_>) -> Admission {
+        for limit in self.limits.iter_mut().filter(|limit| limit.covers(&call)) {
+            let calls = limit.calls.entry(call.account_id.to_string()).or_default();
+            while calls
+                .front()
+                .is_some_and(|oldest| *oldest + limit.window <= call.at)
+            {
+                calls.pop_front();
+            }
+            if calls.len() >= limit.max_calls {
+                return match limit.action {
+                    RateLimitAction::Throttle => Admission::Throttle(limit.error.clone()),
+                    RateLimitAction::Defer => Admission::Defer(calls[0] + limit.window),
+                };
+            }
+        }
+        for limit in self.limits.iter_mut().filter(|limit| limit.covers(&call)) {
+            limit
+                .calls
+                .entry(call.account_id.to_string())
+                .or_default()
+                .push_back(call.at);
+        }
+        Admission::Allow
+    }
+}
+
+impl RateLimit {
+    fn covers(&self, call: &Call<'_>) -> bool {
+        self.service == call.service
+            && self
+                .events
+                .as_ref()
+                .is_none_or(|events| events.contains(call.event_name))
+            && self
+                .read_only
+                .is_none_or(|read_only| read_only == call.read_only)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(max_calls: usize, on_limit: RateLimitAction) -> RateLimitConfig {
+        RateLimitConfig {
+            service: "iam.amazonaws.com".to_string(),
+            max_calls,
+            window_seconds: Some(60),
+            events: None,
+            read_only: Some(false),
+            on_limit,
+            error_code: Some("Throttling".to_string()),
+        }
+    }
+
+    fn call(
+        event_name: &'static str,
+        read_only: bool,
+        account_id: &'static str,
+        second: i64,
+    ) -> Call<'static> {
+        let start = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        Call {
+            service: "iam.amazonaws.com",
+            event_name,
+            read_only,
+            account_id,
+            at: start + Duration::seconds(second),
+        }
+    }
+
+    #[test]
+    fn throttles_mutations_over_the_cap_per_account() {
+        let mut limits = RateLimits::from_config(&[limit(2, RateLimitAction::Throttle)]).unwrap();
+        assert_eq!(
+            limits.admit(call("CreateRole", false, "a", 0)),
+            Admission::Allow
+        );
+        assert_eq!(
+            limits.admit(call("CreateRole", false, "a", 10)),
+            Admission::Allow
+        );
+        let Admission::Throttle(error) = limits.admit(call("PutRolePolicy", false, "a", 20)) else {
+            panic!("expected a throttled call");
+        };
+        assert_eq!(error.code, "Throttling");
+        // Reads and other accounts are not capped.
+        assert_eq!(
+            limits.admit(call("GetRole", true, "a", 20)),
+            Admission::Allow
+        );
+        assert_eq!(
+            limits.admit(call("CreateRole", false, "b", 20)),
+            Admission::Allow
+        );
+        // The first call leaves the window a minute later.
+        assert_eq!(
+            limits.admit(call("CreateRole", false, "a", 60)),
+            Admission::Allow
+        );
+    }
+
+    #[test]
+    fn defers_to_when_the_window_has_room() {
+        let mut limits = RateLimits::from_config(&[limit(1, RateLimitAction::Defer)]).unwrap();
+        let first = call("CreateUser", false, "a", 5);
+        assert_eq!(limits.admit(first), Admission::Allow);
+        assert_eq!(
+            limits.admit(call("CreateUser", false, "a", 30)),
+            Admission::Defer(first.at + Duration::seconds(60))
+        );
+        assert!(RateLimits::from_config(&[limit(0, RateLimitAction::Defer)]).is_err());
+    }
+}
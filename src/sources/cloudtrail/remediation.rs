@@ -0,0 +1,342 @@
+//! IAM policy remediation waves across accounts.
+//!
+//! A wave models sanctioned cleanup after a misconfiguration finding: within
+//! an hour one automation actor assumes a remediation role in every affected
+//! account and rewrites dozens of role policies (`PutRolePolicy`,
+//! `DetachRolePolicy`). Human reviewers assume an audit role and spot-check
+//! some of the changed roles a few minutes later. Every wave call carries the
+//! scenario label, so correlation logic can be scored on telling the
+//! automation apart from malicious policy tampering in the regular stream.
+
+use super::templates::{random_alpha, ActorContext};
+use crate::core::actors::{ActorKind, ActorProfile, ActorRole};
+use crate::core::attack::scenario_label;
+use crate::core::config::PolicyRemediationConfig;
+use crate::core::event::EventLabel;
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+const DEFAULT_DURATION_SECONDS: i64 = 3_600;
+const DEFAULT_CHANGES: usize = 60;
+const DEFAULT_ROLE_NAME: &str = "SecurityRemediation";
+const DEFAULT_SPOT_CHECK_RATE: f64 = 0.2;
+/// Role reviewers assume to read the changed policies.
+const REVIEWER_ROLE_NAME: &str = "SecurityAudit";
+/// IAM is global; CloudTrail records its calls in us-east-1.
+pub const IAM_REGION: &str = "us-east-1";
+const TARGET_ROLES: [&str; 8] = [
+    "ci-deploy",
+    "data-pipeline",
+    "lambda-exec",
+    "ecs-task",
+    "legacy-admin",
+    "analytics-read",
+    "backup-operator",
+    "vendor-integration",
+];
+/// Overly broad managed policies the automation detaches.
+const BROAD_POLICIES: [&str; 4] = [
+    "arn:aws:iam::aws:policy/AdministratorAccess",
+    "arn:aws:iam::aws:policy/PowerUserAccess",
+    "arn:aws:iam::aws:policy/IAMFullAccess",
+    "arn:aws:iam::aws:policy/AmazonS3FullAccess",
+];
+const INLINE_POLICY_NAME: &str = "remediation-least-privilege";
+
+/// Planned wave calls, earliest first.
+#[derive(Debug, Clone)]
+pub struct PolicyRemediation {
+    calls: VecDeque<RemediationCall>,
+    pub label: Option<EventLabel>,
+}
+
+/// One wave call to emit.
+#[derive(Debug, Clone)]
+pub struct RemediationCall {
+    pub actor_index: usize,
+    pub event_name: String,
+    pub at: DateTime<Utc>,
+    pub request_parameters: Value,
+    /// Assumed-role session in the account the call lands in.
+    session: AssumedSession,
+}
+
+#[derive(Debug, Clone)]
+struct AssumedSession {
+    account_id: String,
+    role_name: String,
+    role_id: String,
+    session_name: String,
+    access_key_id: String,
+}
+
+impl RemediationCall {
+    /// Runs the call as an assumed-role session in the target account.
+    pub fn apply_session(&self, actor: &mut ActorContext) {
+        let session = &self.session;
+        actor.identity_type = "AssumedRole".into();
+        actor.principal_id = format!("{}:{}", session.role_id, session.session_name).into();
+        actor.arn = format!(
+            "arn:aws:sts::{}:assumed-role/{}/{}",
+            session.account_id, session.role_name, session.session_name
+        )
+        .into();
+        actor.account_id = session.account_id.as_str().into();
+        actor.access_key_id = Some(session.access_key_id.as_str().into());
+        actor.user_name = None;
+        actor.session_issuer = None;
+        actor.web_id_federation = None;
+        actor.identity_provider = None;
+    }
+}
+
+impl PolicyRemediation {
+    /// Resolves a wave config against the actor list and plans every call.
+    pub fn plan(
+        config: &PolicyRemediationConfig,
+        actors: &[ActorProfile],
+        start_time: DateTime<Utc>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        let start = match &config.start_time {
+            Some(raw) => DateTime::parse_from_rfc3339(raw)
+                .map_err(|_| format!("invalid start_time: {raw}"))?
+                .with_timezone(&Utc),
+            None => start_time + Duration::seconds(config.offset_seconds.unwrap_or(0)),
+        };
+        let duration_seconds = config.duration_seconds.unwrap_or(DEFAULT_DURATION_SECONDS);
+        if duration_seconds <= 0 {
+            return Err("duration_seconds must be greater than 0".to_string());
+        }
+        let changes = config.changes.unwrap_or(DEFAULT_CHANGES);
+        if changes == 0 {
+            return Err("changes must be greater than 0".to_string());
+        }
+        let spot_check_rate = config.spot_check_rate.unwrap_or(DEFAULT_SPOT_CHECK_RATE);
+        if !(0.0..=1.0).contains(&spot_check_rate) {
+            return Err("spot_check_rate must be between 0 and 1".to_string());
+        }
+
+        let automation = match &config.actor_id {
+            Some(actor_id) => {
+                let index = find_actor(actors, actor_id)?;
+                if !matches!(actors[index].seed.kind, ActorKind::Service) {
+                    return Err(format!("actor {actor_id} is not a service actor"));
+                }
+                index
+            }
+            None => {
+                let service_actors = actors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, actor)| matches!(actor.seed.kind, ActorKind::Service))
+                    .map(|(index, _)| index)
+                    .collect::<Vec<_>>();
+                if service_actors.is_empty() {
+                    return Err("no service actors available".to_string());
+                }
+                service_actors[rng.gen_range(0..service_actors.len())]
+            }
+        };
+        let reviewers = match &config.reviewers {
+            Some(reviewers) => reviewers
+                .iter()
+                .map(|actor_id| {
+                    let index = find_actor(actors, actor_id)?;
+                    match actors[index].seed.kind {
+                        ActorKind::Human => Ok(index),
+                        ActorKind::Service => Err(format!("actor {actor_id} is not a human actor")),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => actors
+                .iter()
+                .enumerate()
+                .filter(|(_, actor)| {
+                    matches!(actor.seed.kind, ActorKind::Human)
+                        && matches!(actor.seed.role, Some(ActorRole::Admin | ActorRole::Auditor))
+                })
+                .map(|(index, _)| index)
+                .collect(),
+        };
+        let accounts = match &config.accounts {
+            Some(accounts) if accounts.is_empty() => {
+                return Err("accounts must list at least one account".to_string())
+            }
+            Some(accounts) => accounts.clone(),
+            None => actors
+                .iter()
+                .map(|actor| actor.seed.account_id.clone())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+        };
+
+        let role_name = config
+            .role_name
+            .clone()
+            .unwrap_or_else(|| DEFAULT_ROLE_NAME.to_string());
+        let mut sessions = Sessions::default();
+        let automation_session = actors[automation]
+            .seed
+            .id
+            .clone()
+            .unwrap_or_else(|| actors[automation].seed.principal_id.clone());
+
+        let mut calls = Vec::new();
+        for _ in 0..changes {
+            let at = start + Duration::milliseconds(rng.gen_range(0..duration_seconds * 1_000));
+            let account = accounts[rng.gen_range(0..accounts.len())].clone();
+            let target = TARGET_ROLES[rng.gen_range(0..TARGET_ROLES.len())];
+            let detach = rng.gen_bool(0.5);
+            let (event_name, request_parameters) = if detach {
+                let policy = BROAD_POLICIES[rng.gen_range(0..BROAD_POLICIES.len())];
+                (
+                    "DetachRolePolicy",
+                    json!({ "roleName": target, "policyArn": policy }),
+                )
+            } else {
+                (
+                    "PutRolePolicy",
+                    json!({
+                        "roleName": target,
+                        "policyName": INLINE_POLICY_NAME,
+                        "policyDocument": least_privilege_document(&account, target),
+                    }),
+                )
+            };
+            calls.push(RemediationCall {
+                actor_index: automation,
+                event_name: event_name.to_string(),
+                at,
+                request_parameters,
+                session: sessions.get(&account, &role_name, &automation_session, rng),
+            });
+
+            if reviewers.is_empty() || !rng.gen_bool(spot_check_rate) {
+                continue;
+            }
+            let reviewer = reviewers[rng.gen_range(0..reviewers.len())];
+            let seed = &actors[reviewer].seed;
+            let session_name = seed
+                .user_name
+                .clone()
+                .or_else(|| seed.id.clone())
+                .unwrap_or_else(|| seed.principal_id.clone());
+            let detail = if detach {
+                ("ListAttachedRolePolicies", json!({ "roleName": target }))
+            } else {
+                (
+                    "GetRolePolicy",
+                    json!({ "roleName": target, "policyName": INLINE_POLICY_NAME }),
+                )
+            };
+            let mut checked_at = at + Duration::seconds(rng.gen_range(120..900));
+            for (event_name, request_parameters) in
+                [("GetRole", json!({ "roleName": target })), detail]
+            {
+                calls.push(RemediationCall {
+                    actor_index: reviewer,
+                    event_name: event_name.to_string(),
+                    at: checked_at,
+                    request_parameters,
+                    session: sessions.get(&account, REVIEWER_ROLE_NAME, &session_name, rng),
+                });
+                checked_at += Duration::milliseconds(rng.gen_range(2_000..20_000));
+            }
+        }
+        calls.sort_by_key(|call| call.at);
+
+        let label = scenario_label(
+            Some(&config.scenario_id()),
+            config.technique_id.as_ref(),
+            &config.technique_ids,
+        )
+        .map_err(|id| format!("invalid ATT&CK technique ID: {id}"))?;
+        Ok(Self {
+            calls: calls.into(),
+            label,
+        })
+    }
+
+    /// Time of the next wave call, or `None` once the wave is over.
+    pub fn next_at(&self) -> Option<DateTime<Utc>> {
+        self.calls.front().map(|call| call.at)
+    }
+
+    /// Drops the calls before `at`, for a wave planned mid-run.
+    pub fn skip_before(&mut self, at: DateTime<Utc>) {
+        while self.calls.front().is_some_and(|call| call.at < at) {
+            self.calls.pop_front();
+        }
+    }
+
+    /// Takes the next wave call.
+    pub fn next_call(&mut self) -> Option<RemediationCall> {
+        self.calls.pop_front()
+    }
+}
+
+fn find_actor(actors: &[ActorProfile], actor_id: &str) -> Result<usize, String> {
+    actors
+        .iter()
+        .position(|actor| {
+            actor.seed.id.as_deref() == Some(actor_id) || actor.seed.principal_id == actor_id
+        })
+        .ok_or_else(|| format!("actor not found: {actor_id}"))
+}
+
+/// Role IDs and session access keys, stable across a wave.
+#[derive(Default)]
+struct Sessions {
+    role_ids: HashMap<(String, String), String>,
+    access_keys: HashMap<(String, String, String), String>,
+}
+
+impl Sessions {
+    /// Session `session_name` of `role_name` in `account`.
+    fn get(
+        &mut self,
+        account: &str,
+        role_name: &str,
+        session_name: &str,
+        rng: &mut impl Rng,
+    ) -> AssumedSession {
+        let role_id = self
+            .role_ids
+            .entry((account.to_string(), role_name.to_string()))
+            .or_insert_with(|| format!("AROA{}", random_alpha(rng, 17).to_uppercase()))
+            .clone();
+        let access_key_id = self
+            .access_keys
+            .entry((
+                account.to_string(),
+                role_name.to_string(),
+                session_name.to_string(),
+            ))
+            .or_insert_with(|| format!("ASIA{}", random_alpha(rng, 16).to_uppercase()))
+            .clone();
+        AssumedSession {
+            account_id: account.to_string(),
+            role_name: role_name.to_string(),
+            role_id,
+            session_name: session_name.to_string(),
+            access_key_id,
+        }
+    }
+}
+
+/// Inline policy scoping a role to its own bucket.
+fn least_privilege_document(account: &str, role: &str) -> String {
+    json!({
+        "Version": "2012-10-17",
+        "Statement": [{
+            "Effect": "Allow",
+            "Action": ["s3:GetObject", "s3:PutObject"],
+            "Resource": format!("arn:aws:s3:::{role}-{account}/*"),
+        }],
+    })
+    .to_string()
+}
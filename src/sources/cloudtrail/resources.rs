@@ -0,0 +1,586 @@
+//! Stable per-account resource pools.
+//!
+//! Without a pool every template invents fresh bucket, instance, and role
+//! names, so resource-centric analytics see unbounded cardinality. A pool is
+//! generated at startup for every account in the population: buckets and
+//! roles per account, instances, log groups, and KMS keys per account and
+//! region. Built-in templates then draw their resources from it, and
+//! successful create and delete calls (`CreateBucket`, `DeleteBucket`,
+//! `RunInstances`, `TerminateInstances`, `CreateLogGroup`, `CreateRole`)
+//! add resources to or remove them from the pool.
+
+use super::model::CloudTrailEvent;
+use super::templates::{random_alpha, random_uuid};
+use crate::core::config::ResourcePoolConfig;
+use rand::seq::index;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const DEFAULT_BUCKETS: usize = 8;
+const DEFAULT_ROLES: usize = 6;
+const DEFAULT_INSTANCES: usize = 12;
+const DEFAULT_LOG_GROUPS: usize = 10;
+const DEFAULT_KMS_KEYS: usize = 4;
+
+const BUCKET_PREFIXES: [&str; 8] = [
+    "app-data",
+    "analytics-raw",
+    "logs-archive",
+    "ml-artifacts",
+    "backups",
+    "static-assets",
+    "audit-exports",
+    "config-store",
+];
+const ROLE_PREFIXES: [&str; 7] = [
+    "workload",
+    "app-deploy",
+    "ci-runner",
+    "data-pipeline",
+    "lambda-exec",
+    "ecs-task",
+    "readonly-audit",
+];
+const LOG_GROUP_PREFIXES: [&str; 4] = ["/aws/lambda/", "/ecs/", "/app/", "/aws/eks/"];
+const SERVICES: [&str; 8] = [
+    "orders", "billing", "ingest", "auth", "search", "reports", "checkout", "notify",
+];
+const MANAGED_POLICIES: [&str; 4] = [
+    "arn:aws:iam::aws:policy/ReadOnlyAccess",
+    "arn:aws:iam::aws:policy/AmazonS3ReadOnlyAccess",
+    "arn:aws:iam::aws:policy/CloudWatchLogsFullAccess",
+    "arn:aws:iam::aws:policy/AmazonEC2FullAccess",
+];
+
+/// Pool sizes after defaults.
+#[derive(Debug, Clone, Copy)]
+struct PoolSizes {
+    buckets: usize,
+    roles: usize,
+    instances: usize,
+    log_groups: usize,
+    kms_keys: usize,
+}
+
+/// Resources of one account.
+#[derive(Debug, Clone, Default)]
+struct AccountResources {
+    buckets: Vec<String>,
+    /// Role names.
+    roles: Vec<String>,
+    regions: HashMap<String, RegionalResources>,
+}
+
+/// Regional resources of one account.
+#[derive(Debug, Clone, Default)]
+struct RegionalResources {
+    instances: Vec<String>,
+    log_groups: Vec<String>,
+    /// Key IDs (UUIDs).
+    kms_keys: Vec<String>,
+}
+
+/// Kind of a pooled resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Bucket,
+    Role,
+    Instance,
+    LogGroup,
+    KmsKey,
+}
+
+/// Per-account buckets, roles, instances, log groups, and KMS keys.
+#[derive(Debug, Clone)]
+pub struct ResourcePool {
+    sizes: PoolSizes,
+    accounts: HashMap<String, AccountResources>,
+}
+
+impl ResourcePool {
+    /// Generates the pool for every account and region up front.
+    ///
+    /// Accounts or regions first seen later (e.g. an attacker's regions) get
+    /// their resources generated on first use.
+    pub fn generate(
+        config: &ResourcePoolConfig,
+        accounts: &[String],
+        regions: &[String],
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        let sizes = PoolSizes {
+            buckets: config.buckets.unwrap_or(DEFAULT_BUCKETS),
+            roles: config.roles.unwrap_or(DEFAULT_ROLES),
+            instances: config.instances.unwrap_or(DEFAULT_INSTANCES),
+            log_groups: config.log_groups.unwrap_or(DEFAULT_LOG_GROUPS),
+            kms_keys: config.kms_keys.unwrap_or(DEFAULT_KMS_KEYS),
+        };
+        for (name, size) in [
+            ("buckets", sizes.buckets),
+            ("roles", sizes.roles),
+            ("instances", sizes.instances),
+            ("log_groups", sizes.log_groups),
+            ("kms_keys", sizes.kms_keys),
+        ] {
+            if size == 0 {
+                return Err(format!("{name} must be greater than 0"));
+            }
+        }
+
+        let mut pool = Self {
+            sizes,
+            accounts: HashMap::new(),
+        };
+        let mut accounts = accounts.to_vec();
+        accounts.sort();
+        accounts.dedup();
+        for account in &accounts {
+            for region in regions {
+                pool.regional(account, region, rng);
+            }
+        }
+        Ok(pool)
+    }
+
+    /// Number of pooled buckets in an account.
+    pub fn bucket_count(&self, account_id: &str) -> usize {
+        self.accounts
+            .get(account_id)
+            .map_or(0, |account| account.buckets.len())
+    }
+
+    /// Picks one of the account's buckets.
+    pub fn bucket(&mut self, account_id: &str, rng: &mut impl Rng) -> String {
+        self.pick(Kind::Bucket, account_id, "", rng)
+    }
+
+    /// Rewrites the resources a built-in template made up with pooled ones
+    /// and applies successful create and delete calls to the pool.
+    ///
+    /// Failed calls draw resources too but leave the pool unchanged.
+    pub fn apply(&mut self, event: &mut CloudTrailEvent, rng: &mut impl Rng) {
+        let account = event.recipient_account_id.clone();
+        let region = event.aws_region.clone();
+        let succeeded = event.error_code.is_none();
+        match event.event_name.as_str() {
+            "PutObject" | "GetObject" => {
+                let bucket = self.bucket(&account, rng);
+                set_param(event, "bucketName", json!(bucket));
+            }
+            "DeleteObject" => {
+                let bucket = self.bucket(&account, rng);
+                let key = format!(
+                    "data/{}/{}.parquet",
+                    random_alpha(rng, 4),
+                    random_alpha(rng, 10)
+                );
+                event.request_parameters = Some(json!({ "bucketName": bucket, "key": key }));
+            }
+            "CreateBucket" => {
+                let bucket = self.create(Kind::Bucket, &account, &region, succeeded, rng);
+                event.request_parameters = Some(json!({
+                    "bucketName": bucket,
+                    "CreateBucketConfiguration": { "LocationConstraint": region },
+                }));
+            }
+            "DeleteBucket" => {
+                let bucket = self.delete(Kind::Bucket, &account, &region, succeeded, rng);
+                event.request_parameters = Some(json!({ "bucketName": bucket }));
+            }
+            "AssumeRole" | "AssumeRoleWithWebIdentity" => {
+                let role = self.pick(Kind::Role, &account, &region, rng);
+                set_param(event, "roleArn", json!(role_arn(&account, &role)));
+            }
+            "CreateRole" => {
+                let role = self.create(Kind::Role, &account, &region, succeeded, rng);
+                event.request_parameters = Some(json!({
+                    "roleName": role,
+                    "path": "/",
+                }));
+                if succeeded {
+                    event.response_elements = Some(json!({
+                        "role": {
+                            "roleName": role,
+                            "arn": role_arn(&account, &role),
+                            "roleId": format!("AROA{}", random_alpha(rng, 17).to_uppercase()),
+                            "path": "/",
+                        }
+                    }));
+                }
+            }
+            "AttachRolePolicy" => {
+                let role = self.pick(Kind::Role, &account, &region, rng);
+                let policy = MANAGED_POLICIES[rng.gen_range(0..MANAGED_POLICIES.len())];
+                event.request_parameters = Some(json!({
+                    "roleName": role,
+                    "policyArn": policy,
+                }));
+            }
+            "RunInstances" => {
+                let instance = self.create(Kind::Instance, &account, &region, succeeded, rng);
+                if let Some(slot) = event
+                    .response_elements
+                    .as_mut()
+                    .and_then(|response| response.pointer_mut("/instancesSet/0/instanceId"))
+                {
+                    *slot = json!(instance);
+                }
+            }
+            "StartInstances" | "StopInstances" => {
+                let count = event
+                    .request_parameters
+                    .as_ref()
+                    .and_then(|params| params.pointer("/instancesSet/items"))
+                    .and_then(Value::as_array)
+                    .map_or(1, Vec::len);
+                let instances = self.pick_many(Kind::Instance, &account, &region, count, rng);
+                for value in [&mut event.request_parameters, &mut event.response_elements]
+                    .into_iter()
+                    .flatten()
+                {
+                    replace_instance_ids(value, &instances);
+                }
+            }
+            "TerminateInstances" => {
+                let instance = self.delete(Kind::Instance, &account, &region, succeeded, rng);
+                event.request_parameters = Some(json!({
+                    "instancesSet": { "items": [{ "instanceId": instance }] }
+                }));
+                if succeeded {
+                    event.response_elements = Some(json!({
+                        "instancesSet": {
+                            "items": [{
+                                "instanceId": instance,
+                                "currentState": { "code": 32, "name": "shutting-down" },
+                                "previousState": { "code": 16, "name": "running" }
+                            }]
+                        }
+                    }));
+                }
+            }
+            "CreateLogGroup" => {
+                let group = self.create(Kind::LogGroup, &account, &region, succeeded, rng);
+                event.request_parameters = Some(json!({ "logGroupName": group }));
+            }
+            "DescribeLogStreams" => {
+                let group = self.pick(Kind::LogGroup, &account, &region, rng);
+                event.request_parameters = Some(json!({ "logGroupName": group }));
+            }
+            "CreateLogStream" | "PutLogEvents" => {
+                let group = self.pick(Kind::LogGroup, &account, &region, rng);
+                let stream = format!("{}/{}", rng.gen_range(2024..=2026), random_alpha(rng, 12));
+                event.request_parameters = Some(json!({
+                    "logGroupName": group,
+                    "logStreamName": stream,
+                }));
+            }
+            "Encrypt" | "Decrypt" => {
+                let key = self.pick(Kind::KmsKey, &account, &region, rng);
+                event.request_parameters = Some(json!({
+                    "keyId": kms_key_arn(&account, &region, &key),
+                    "encryptionAlgorithm": "SYMMETRIC_DEFAULT",
+                }));
+            }
+            "GenerateDataKey" => {
+                let key = self.pick(Kind::KmsKey, &account, &region, rng);
+                event.request_parameters = Some(json!({
+                    "keyId": kms_key_arn(&account, &region, &key),
+                    "keySpec": "AES_256",
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    /// Picks a resource, generating one if the pool ran dry.
+    fn pick(&mut self, kind: Kind, account: &str, region: &str, rng: &mut impl Rng) -> String {
+        self.pick_many(kind, account, region, 1, rng).remove(0)
+    }
+
+    /// Picks up to `count` distinct resources, generating one if the pool ran dry.
+    fn pick_many(
+        &mut self,
+        kind: Kind,
+        account: &str,
+        region: &str,
+        count: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<String> {
+        let list = self.list(kind, account, region, rng);
+        if list.is_empty() {
+            let name = new_name(kind, account, list, rng);
+            list.push(name);
+        }
+        index::sample(rng, list.len(), count.clamp(1, list.len()))
+            .into_iter()
+            .map(|idx| list[idx].clone())
+            .collect()
+    }
+
+    /// Names a new resource and adds it to the pool when the call succeeded.
+    fn create(
+        &mut self,
+        kind: Kind,
+        account: &str,
+        region: &str,
+        succeeded: bool,
+        rng: &mut impl Rng,
+    ) -> String {
+        let list = self.list(kind, account, region, rng);
+        let name = new_name(kind, account, list, rng);
+        if succeeded {
+            list.push(name.clone());
+        }
+        name
+    }
+
+    /// Picks a resource and removes it from the pool when the call succeeded.
+    fn delete(
+        &mut self,
+        kind: Kind,
+        account: &str,
+        region: &str,
+        succeeded: bool,
+        rng: &mut impl Rng,
+    ) -> String {
+        let name = self.pick(kind, account, region, rng);
+        if succeeded {
+            self.list(kind, account, region, rng)
+                .retain(|existing| *existing != name);
+        }
+        name
+    }
+
+    /// Resource list of a kind, generating the account or region on first use.
+    fn list(
+        &mut self,
+        kind: Kind,
+        account: &str,
+        region: &str,
+        rng: &mut impl Rng,
+    ) -> &mut Vec<String> {
+        match kind {
+            Kind::Bucket => &mut self.account(account, rng).buckets,
+            Kind::Role => &mut self.account(account, rng).roles,
+            Kind::Instance => &mut self.regional(account, region, rng).instances,
+            Kind::LogGroup => &mut self.regional(account, region, rng).log_groups,
+            Kind::KmsKey => &mut self.regional(account, region, rng).kms_keys,
+        }
+    }
+
+    fn account(&mut self, account: &str, rng: &mut impl Rng) -> &mut AccountResources {
+        let sizes = self.sizes;
+        self.accounts.entry(account.to_string()).or_insert_with(|| {
+            let mut resources = AccountResources::default();
+            fill(
+                &mut resources.buckets,
+                sizes.buckets,
+                Kind::Bucket,
+                account,
+                rng,
+            );
+            fill(&mut resources.roles, sizes.roles, Kind::Role, account, rng);
+            resources
+        })
+    }
+
+    fn regional(
+        &mut self,
+        account: &str,
+        region: &str,
+        rng: &mut impl Rng,
+    ) -> &mut RegionalResources {
+        let sizes = self.sizes;
+        self.account(account, rng)
+            .regions
+            .entry(region.to_string())
+            .or_insert_with(|| {
+                let mut resources = RegionalResources::default();
+                fill(
+                    &mut resources.instances,
+                    sizes.instances,
+                    Kind::Instance,
+                    account,
+                    rng,
+                );
+                fill(
+                    &mut resources.log_groups,
+                    sizes.log_groups,
+                    Kind::LogGroup,
+                    account,
+                    rng,
+                );
+                fill(
+                    &mut resources.kms_keys,
+                    sizes.kms_keys,
+                    Kind::KmsKey,
+                    account,
+                    rng,
+                );
+                resources
+            })
+    }
+}
+
+fn fill(list: &mut Vec<String>, size: usize, kind: Kind, account: &str, rng: &mut impl Rng) {
+    while list.len() < size {
+        let name = new_name(kind, account, list, rng);
+        list.push(name);
+    }
+}
+
+/// Names a resource that is not yet in `existing`.
+fn new_name(kind: Kind, account: &str, existing: &[String], rng: &mut impl Rng) -> String {
+    loop {
+        let name = match kind {
+            Kind::Bucket => {
+                let prefix = BUCKET_PREFIXES[rng.gen_range(0..BUCKET_PREFIXES.len())];
+                let plain = format!("{prefix}-{account}");
+                if existing.contains(&plain) {
+                    format!("{plain}-{}", random_alpha(rng, 6).to_lowercase())
+                } else {
+                    plain
+                }
+            }
+            Kind::Role => {
+                let prefix = ROLE_PREFIXES[rng.gen_range(0..ROLE_PREFIXES.len())];
+                format!("{prefix}-role-{}", random_alpha(rng, 4))
+            }
+            Kind::Instance => {
+                let digits: String = (0..17)
+                    .map(|_| std::char::from_digit(rng.gen_range(0..16), 16).unwrap())
+                    .collect();
+                format!("i-{digits}")
+            }
+            Kind::LogGroup => {
+                let prefix = LOG_GROUP_PREFIXES[rng.gen_range(0..LOG_GROUP_PREFIXES.len())];
+                let service = SERVICES[rng.gen_range(0..SERVICES.len())];
+                let plain = format!("{prefix}{service}");
+                if existing.contains(&plain) {
+                    format!("{plain}-{}", random_alpha(rng, 4).to_lowercase())
+                } else {
+                    plain
+                }
+            }
+            Kind::KmsKey => random_uuid(rng),
+        };
+        if !existing.contains(&name) {
+            return name;
+        }
+    }
+}
+
+fn role_arn(account: &str, role: &str) -> String {
+    format!("arn:aws:iam::{account}:role/{role}")
+}
+
+fn kms_key_arn(account: &str, region: &str, key: &str) -> String {
+    format!("arn:aws:kms:{region}:{account}:key/{key}")
+}
+
+fn set_param(event: &mut CloudTrailEvent, key: &str, value: Value) {
+    if let Some(Value::Object(params)) = event.request_parameters.as_mut() {
+        params.insert(key.to_string(), value);
+    }
+}
+
+/// Swaps the instance IDs under `instancesSet.items` for pooled ones.
+fn replace_instance_ids(value: &mut Value, instances: &[String]) {
+    let Some(items) = value
+        .pointer_mut("/instancesSet/items")
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+    items.truncate(instances.len());
+    for (item, instance) in items.iter_mut().zip(instances) {
+        item["instanceId"] = json!(instance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::cloudtrail::templates::{build_cloudtrail_event, ActorContext};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn templates_draw_from_and_mutate_the_pool() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let config = ResourcePoolConfig {
+            buckets: Some(3),
+            roles: Some(2),
+            instances: Some(4),
+            log_groups: None,
+            kms_keys: None,
+        };
+        let account = "123456789012".to_string();
+        let regions = ["us-east-1".to_string()];
+        let mut pool =
+            ResourcePool::generate(&config, std::slice::from_ref(&account), &regions, &mut rng)
+                .expect("pool");
+        assert_eq!(pool.bucket_count(&account), 3);
+        let actor = ActorContext {
+            identity_type: "IAMUser".into(),
+            principal_id: "AIDA_TEST_004".into(),
+            arn: "arn:aws:iam::123456789012:user/dana".into(),
+            account_id: account.as_str().into(),
+            access_key_id: None,
+            user_name: Some("dana".into()),
+            user_agent: "aws-cli/2.15.0".to_string(),
+            source_ip: "10.0.0.5".to_string(),
+            region: "us-east-1".to_string(),
+            mfa_authenticated: false,
+            session_credential_from_console: false,
+            session_issuer: None,
+            session_created_at: None,
+            web_id_federation: None,
+            identity_provider: None,
+        };
+        let mut build = |name: &str, pool: &mut ResourcePool| {
+            let mut event =
+                build_cloudtrail_event(name, &actor, &mut rng, "2025-01-01T00:00:00Z", None, 0.0)
+                    .expect("event");
+            pool.apply(&mut event, &mut rng);
+            event
+        };
+
+        let mut buckets = HashSet::new();
+        let mut instances = HashSet::new();
+        for _ in 0..50 {
+            let event = build("GetObject", &mut pool);
+            buckets.insert(event.request_parameters.unwrap()["bucketName"].clone());
+            let event = build("StopInstances", &mut pool);
+            for item in event.request_parameters.unwrap()["instancesSet"]["items"]
+                .as_array()
+                .unwrap()
+            {
+                instances.insert(item["instanceId"].clone());
+            }
+        }
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(instances.len(), 4);
+
+        let created = build("CreateBucket", &mut pool).request_parameters.unwrap()["bucketName"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(pool.bucket_count(&account), 4);
+        assert!(pool.accounts[&account].buckets.contains(&created));
+        build("DeleteBucket", &mut pool);
+        assert_eq!(pool.bucket_count(&account), 3);
+
+        let event = build("AssumeRole", &mut pool);
+        let role = event.request_parameters.unwrap()["roleArn"]
+            .as_str()
+            .unwrap()
+            .rsplit('/')
+            .next()
+            .unwrap()
+            .to_string();
+        assert!(pool.accounts[&account].roles.contains(&role));
+    }
+}
@@ -0,0 +1,353 @@
+//! Access key and client secret rotation for service actors.
+//!
+//! Long-lived credentials that never change are a tell of synthetic data. With
+//! `credential_rotation` set, each service actor with an access key of its own
+//! (an `IAMUser`) rotates it every `interval_days`: `CreateAccessKey` issues a
+//! new key and the actor's later calls sign with it, `UpdateAccessKey`
+//! deactivates the old key `deactivate_after_hours` later, and
+//! `DeleteAccessKey` removes it `delete_after_hours` after that. With
+//! `entra_federation` also set, every service actor's Entra application gets
+//! a new client secret on the same schedule, and the old secret is removed
+//! when the old key is deleted; both are Entra ID audit events.
+//!
+//! First rotations are spread over the first interval so the population's
+//! credentials do not all turn over at once.
+
+use super::templates::{derived_uuid, random_alpha};
+use crate::core::actors::{ActorKind, ActorProfile, ActorSeed};
+use crate::core::config::CredentialRotationConfig;
+use crate::core::event::{Actor, Event, EventEnvelope, Outcome, Target};
+use crate::sources::entra::model::{
+    EntraAuditApp, EntraAuditInitiator, EntraAuditRecord, EntraAuditTarget, EntraKeyValue,
+    EntraModifiedProperty,
+};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::Rng;
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+const DEFAULT_INTERVAL_DAYS: f64 = 90.0;
+const DEFAULT_DEACTIVATE_AFTER_HOURS: f64 = 24.0;
+const DEFAULT_DELETE_AFTER_HOURS: f64 = 168.0;
+const SECRETS_ACTIVITY: &str = "Update application – Certificates and secrets management";
+
+/// Rotation state of every rotating service actor.
+#[derive(Debug, Clone)]
+pub struct CredentialRotation {
+    interval: Duration,
+    deactivate_after: Duration,
+    delete_after: Duration,
+    /// Whether Entra application client secrets rotate too.
+    entra: bool,
+    actors: Vec<RotatingActor>,
+    /// `(next_at, position in actors)`, earliest first.
+    schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+}
+
+#[derive(Debug, Clone)]
+struct RotatingActor {
+    actor_index: usize,
+    /// Whether the actor has an access key to rotate.
+    access_key: bool,
+    next: Stage,
+    next_at: DateTime<Utc>,
+    /// Rotations so far; numbers the client secrets.
+    rotations: u32,
+}
+
+#[derive(Debug, Clone)]
+enum Stage {
+    Rotate,
+    Deactivate {
+        key: String,
+    },
+    /// Deletes the old access key, if the actor has one, and the old secret.
+    Delete {
+        key: Option<String>,
+    },
+}
+
+/// One rotation step to emit.
+#[derive(Debug, Clone)]
+pub struct RotationStep {
+    pub actor_index: usize,
+    pub at: DateTime<Utc>,
+    pub kind: RotationKind,
+    /// Client secret IDs replaced when Entra secrets rotate:
+    /// `(removed, added)` at a rotation, `(removed, None)` at deletion.
+    pub secret: Option<(String, Option<String>)>,
+}
+
+/// CloudTrail call a rotation step makes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RotationKind {
+    /// Issues `new_key`; the actor switches to it after this call.
+    Create {
+        new_key: String,
+    },
+    Deactivate {
+        key: String,
+    },
+    Delete {
+        key: String,
+    },
+    /// Entra-only actors have no access key; only the secret changes.
+    SecretOnly,
+}
+
+impl CredentialRotation {
+    /// Plans rotations for `actors`; `entra` adds client secret rotation.
+    pub fn plan(
+        config: &CredentialRotationConfig,
+        actors: &[ActorProfile],
+        entra: bool,
+        start_time: DateTime<Utc>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        let hours = |value: Option<f64>, default: f64, field: &str| {
+            let value = value.unwrap_or(default);
+            if value.is_finite() && value > 0.0 {
+                Ok(Duration::seconds((value * 3_600.0) as i64))
+            } else {
+                Err(format!("{field} must be greater than 0"))
+            }
+        };
+        let interval = hours(
+            config.interval_days.map(|days| days * 24.0),
+            DEFAULT_INTERVAL_DAYS * 24.0,
+            "interval_days",
+        )?;
+        let deactivate_after = hours(
+            config.deactivate_after_hours,
+            DEFAULT_DEACTIVATE_AFTER_HOURS,
+            "deactivate_after_hours",
+        )?;
+        let delete_after = hours(
+            config.delete_after_hours,
+            DEFAULT_DELETE_AFTER_HOURS,
+            "delete_after_hours",
+        )?;
+        if deactivate_after + delete_after >= interval {
+            return Err(
+                "deactivate_after_hours plus delete_after_hours must be shorter than interval_days"
+                    .to_string(),
+            );
+        }
+        let actors = actors
+            .iter()
+            .enumerate()
+            .filter(|(_, actor)| matches!(actor.seed.kind, ActorKind::Service))
+            .filter_map(|(actor_index, actor)| {
+                let access_key = actor.seed.identity_type == "IAMUser";
+                (access_key || entra).then(|| RotatingActor {
+                    actor_index,
+                    access_key,
+                    next: Stage::Rotate,
+                    next_at: start_time
+                        + Duration::seconds(rng.gen_range(0..interval.num_seconds().max(1))),
+                    rotations: 0,
+                })
+            })
+            .collect::<Vec<_>>();
+        let schedule = actors
+            .iter()
+            .enumerate()
+            .map(|(idx, actor)| Reverse((actor.next_at, idx)))
+            .collect();
+        Ok(Self {
+            interval,
+            deactivate_after,
+            delete_after,
+            entra,
+            actors,
+            schedule,
+        })
+    }
+
+    /// Time of the next rotation step, if any actor rotates.
+    pub fn next_at(&self) -> Option<DateTime<Utc>> {
+        self.schedule.peek().map(|Reverse((at, _))| *at)
+    }
+
+    /// Takes the earliest rotation step and schedules the actor's next one.
+    /// `current_key` is the rotating actor's access key before the step.
+    pub fn next_step(
+        &mut self,
+        current_key: impl Fn(usize) -> String,
+        rng: &mut impl Rng,
+    ) -> Option<RotationStep> {
+        let entra = self.entra;
+        let (interval, deactivate_after, delete_after) =
+            (self.interval, self.deactivate_after, self.delete_after);
+        let Reverse((_, idx)) = self.schedule.pop()?;
+        let actor = &mut self.actors[idx];
+        let (actor_index, at) = (actor.actor_index, actor.next_at);
+        let secret_id =
+            |rotation: u32| derived_uuid(&format!("entra:secret:{actor_index}:{rotation}"));
+        let (kind, secret) = match std::mem::replace(&mut actor.next, Stage::Rotate) {
+            Stage::Rotate => {
+                let secret = entra.then(|| {
+                    (
+                        secret_id(actor.rotations),
+                        Some(secret_id(actor.rotations + 1)),
+                    )
+                });
+                actor.rotations += 1;
+                if actor.access_key {
+                    let old = current_key(actor_index);
+                    let new_key = format!("AKIA{}", random_alpha(rng, 16).to_ascii_uppercase());
+                    actor.next = Stage::Deactivate { key: old };
+                    actor.next_at = at + deactivate_after;
+                    (RotationKind::Create { new_key }, secret)
+                } else {
+                    actor.next = Stage::Delete { key: None };
+                    actor.next_at = at + deactivate_after + delete_after;
+                    (RotationKind::SecretOnly, secret)
+                }
+            }
+            Stage::Deactivate { key } => {
+                actor.next = Stage::Delete {
+                    key: Some(key.clone()),
+                };
+                actor.next_at = at + delete_after;
+                (RotationKind::Deactivate { key }, None)
+            }
+            Stage::Delete { key } => {
+                actor.next_at = at + interval - deactivate_after - delete_after;
+                let secret = entra.then(|| (secret_id(actor.rotations - 1), None));
+                let kind = key.map_or(RotationKind::SecretOnly, |key| RotationKind::Delete { key });
+                (kind, secret)
+            }
+        };
+        self.schedule.push(Reverse((actor.next_at, idx)));
+        Some(RotationStep {
+            actor_index,
+            at,
+            kind,
+            secret,
+        })
+    }
+}
+
+impl RotationStep {
+    /// CloudTrail event name and request parameters, or `None` when only the
+    /// Entra secret changes.
+    pub fn call(&self, user_name: &str) -> Option<(&'static str, Value)> {
+        match &self.kind {
+            RotationKind::Create { .. } => {
+                Some(("CreateAccessKey", json!({ "userName": user_name })))
+            }
+            RotationKind::Deactivate { key } => Some((
+                "UpdateAccessKey",
+                json!({ "userName": user_name, "accessKeyId": key, "status": "Inactive" }),
+            )),
+            RotationKind::Delete { key } => Some((
+                "DeleteAccessKey",
+                json!({ "userName": user_name, "accessKeyId": key }),
+            )),
+            RotationKind::SecretOnly => None,
+        }
+    }
+}
+
+/// Entra ID audit event for the application of the actor `seed` adding client
+/// secret `added` next to `removed`, or removing `removed` when `added` is
+/// `None`.
+pub fn secret_event(
+    seed: &ActorSeed,
+    tenant_id: &str,
+    at: DateTime<Utc>,
+    removed: &str,
+    added: Option<&str>,
+) -> Event {
+    let name = seed
+        .display_name
+        .clone()
+        .or_else(|| seed.user_name.clone())
+        .unwrap_or_else(|| seed.principal_id.clone());
+    let app_id = derived_uuid(&format!("entra:app:{tenant_id}:{}", seed.principal_id));
+    let object_id = derived_uuid(&format!("entra:app-object:{tenant_id}:{app_id}"));
+    let service_principal_id = derived_uuid(&format!("entra:sp:{tenant_id}:{app_id}"));
+    let time = at.to_rfc3339_opts(SecondsFormat::Millis, true);
+    let key = |id: &str| {
+        format!("[KeyIdentifier={id},KeyType=Password,KeyUsage=Verify,DisplayName=rotated]")
+    };
+    let old_value = json!([key(removed)]).to_string();
+    let new_value = match added {
+        Some(added) => json!([key(removed), key(added)]),
+        None => json!([]),
+    }
+    .to_string();
+    let correlation_id = derived_uuid(&format!("entra:audit:{app_id}:{time}"));
+    let record = EntraAuditRecord {
+        id: format!("Directory_{correlation_id}"),
+        category: "ApplicationManagement".to_string(),
+        correlation_id: correlation_id.clone(),
+        result: "success".to_string(),
+        result_reason: String::new(),
+        activity_display_name: SECRETS_ACTIVITY.to_string(),
+        activity_date_time: time.clone(),
+        logged_by_service: "Core Directory".to_string(),
+        operation_type: "Update".to_string(),
+        initiated_by: EntraAuditInitiator {
+            user: None,
+            app: Some(EntraAuditApp {
+                app_id: app_id.clone(),
+                display_name: name.clone(),
+                service_principal_id,
+                service_principal_name: name.clone(),
+            }),
+        },
+        target_resources: vec![EntraAuditTarget {
+            id: object_id.clone(),
+            display_name: name.clone(),
+            r#type: "Application".to_string(),
+            user_principal_name: None,
+            modified_properties: vec![
+                EntraModifiedProperty {
+                    display_name: "KeyDescription".to_string(),
+                    old_value,
+                    new_value,
+                },
+                EntraModifiedProperty {
+                    display_name: "Included Updated Properties".to_string(),
+                    old_value: String::new(),
+                    new_value: "\"KeyDescription\"".to_string(),
+                },
+            ],
+        }],
+        additional_details: vec![EntraKeyValue {
+            key: "AppId".to_string(),
+            value: app_id.clone(),
+        }],
+    };
+    let envelope = EventEnvelope {
+        schema_version: "v1".to_string(),
+        timestamp: time,
+        source: "entra_audit".to_string(),
+        event_type: SECRETS_ACTIVITY.to_string(),
+        actor: Actor {
+            id: seed.id.clone().unwrap_or_else(|| seed.principal_id.clone()),
+            kind: "ServicePrincipal".to_string(),
+            name: Some(name.clone()),
+        },
+        target: Some(Target {
+            id: object_id,
+            kind: "Application".to_string(),
+            name: Some(name),
+        }),
+        outcome: Outcome::Success,
+        geo: None,
+        ip: None,
+        user_agent: None,
+        session_id: Some(correlation_id),
+        tenant_id: Some(tenant_id.to_string()),
+        tenant: None,
+    };
+    Event {
+        envelope,
+        payload: record.to_value(),
+        label: None,
+    }
+}
@@ -0,0 +1,80 @@
+//! Scenario schedule a running CloudTrail generator can re-plan from.
+//!
+//! Throttling storms, policy remediation waves, and impossible travel run
+//! against actors the generator already has, so `seclog gen --watch-config`
+//! can add, move, or drop them mid-run. Credential takeovers and actor
+//! injections add actors when the generator is built and stay fixed.
+
+use crate::core::config::{
+    ApiStormConfig, CloudTrailSourceConfig, ImpossibleTravelConfig, PolicyRemediationConfig,
+};
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+
+/// Scenario schedule a generator follows, replaceable while it runs.
+pub type SharedScenarios = Arc<RwLock<ScenarioSchedule>>;
+
+/// Reloadable scenario tables of a CloudTrail source.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScenarioSchedule {
+    /// Bumped on every replacement so generators notice the change.
+    #[serde(skip)]
+    pub revision: u64,
+    pub api_storms: Vec<ApiStormConfig>,
+    pub policy_remediations: Vec<PolicyRemediationConfig>,
+    pub impossible_travels: Vec<ImpossibleTravelConfig>,
+}
+
+impl ScenarioSchedule {
+    pub fn from_config(config: &CloudTrailSourceConfig) -> Self {
+        Self {
+            revision: 0,
+            api_storms: config.api_storms.clone(),
+            policy_remediations: config.policy_remediations.clone(),
+            impossible_travels: config.impossible_travels.clone(),
+        }
+    }
+
+    /// Config tables that differ from `other`, in config order.
+    pub fn changed_tables(&self, other: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if !same(&self.api_storms, &other.api_storms) {
+            changed.push("api_storm");
+        }
+        if !same(&self.policy_remediations, &other.policy_remediations) {
+            changed.push("policy_remediation");
+        }
+        if !same(&self.impossible_travels, &other.impossible_travels) {
+            changed.push("impossible_travel");
+        }
+        changed
+    }
+}
+
+/// Plans for `new`, reusing the plan of each config that is unchanged from
+/// `old` so a running scenario carries on where it is.
+pub(super) fn replan<C: Serialize, P>(
+    old: &[C],
+    plans: Vec<P>,
+    new: &[C],
+    mut plan: impl FnMut(&C) -> Result<P, String>,
+) -> Result<Vec<P>, String> {
+    let mut kept = plans.into_iter().map(Some).collect::<Vec<_>>();
+    new.iter()
+        .map(|config| {
+            let reused = old
+                .iter()
+                .zip(kept.iter_mut())
+                .find(|(old, plan)| plan.is_some() && same(*old, config))
+                .and_then(|(_, plan)| plan.take());
+            match reused {
+                Some(plan) => Ok(plan),
+                None => plan(config),
+            }
+        })
+        .collect()
+}
+
+fn same<T: Serialize + ?Sized>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
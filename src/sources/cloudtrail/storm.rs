@@ -0,0 +1,159 @@
+//! Throttling storms against a single AWS service.
+//!
+//! A storm models misbehaving automation or a retry loop gone wrong: for a
+//! short window many service actors hammer one service (e.g. EC2) far above
+//! their normal rate, and most calls come back `ThrottlingException` or
+//! `RequestLimitExceeded`. Storm calls are interleaved with the regular
+//! stream and carry a scenario label so noise-suppression and incident
+//! correlation logic can be scored against them.
+
+use super::templates::ErrorProfile;
+use crate::core::actors::{ActorKind, ActorProfile};
+use crate::core::attack::scenario_label;
+use crate::core::config::ApiStormConfig;
+use crate::core::event::EventLabel;
+use chrono::{DateTime, Duration, Utc};
+use rand::seq::index;
+use rand::Rng;
+
+const DEFAULT_ACTORS: usize = 10;
+const DEFAULT_EVENTS_PER_SECOND: f64 = 20.0;
+const DEFAULT_ERROR_RATE: f64 = 0.85;
+
+/// Active or upcoming storm.
+#[derive(Debug, Clone)]
+pub struct ApiStorm {
+    next_at: DateTime<Utc>,
+    end_at: DateTime<Utc>,
+    actor_indices: Vec<usize>,
+    events: Vec<String>,
+    /// Mean milliseconds between storm calls.
+    interval_ms: f64,
+    pub region: String,
+    pub error_rate: f64,
+    pub error: ErrorProfile,
+    pub label: Option<EventLabel>,
+}
+
+/// One storm call to emit.
+#[derive(Debug, Clone)]
+pub struct StormCall {
+    pub actor_index: usize,
+    pub event_name: String,
+    pub at: DateTime<Utc>,
+}
+
+impl ApiStorm {
+    /// Resolves a storm config against the actor list.
+    ///
+    /// `service_events` lists the enabled events of the storm's service and is
+    /// used unless the config names events; `default_region` applies when the
+    /// config sets none.
+    pub fn plan(
+        config: &ApiStormConfig,
+        actors: &[ActorProfile],
+        service_events: Vec<String>,
+        default_region: String,
+        start_time: DateTime<Utc>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        let start = match &config.start_time {
+            Some(raw) => DateTime::parse_from_rfc3339(raw)
+                .map_err(|_| format!("invalid start_time: {raw}"))?
+                .with_timezone(&Utc),
+            None => start_time + Duration::seconds(config.offset_seconds.unwrap_or(0)),
+        };
+        if config.duration_seconds <= 0 {
+            return Err("duration_seconds must be greater than 0".to_string());
+        }
+        let events_per_second = config
+            .events_per_second
+            .unwrap_or(DEFAULT_EVENTS_PER_SECOND);
+        if !events_per_second.is_finite() || events_per_second <= 0.0 {
+            return Err("events_per_second must be greater than 0".to_string());
+        }
+        let error_rate = config.error_rate.unwrap_or(DEFAULT_ERROR_RATE);
+        if !(0.0..=1.0).contains(&error_rate) {
+            return Err("error_rate must be between 0 and 1".to_string());
+        }
+        let events = config.events.clone().unwrap_or(service_events);
+        if events.is_empty() {
+            return Err(format!("no enabled events for service {}", config.service));
+        }
+
+        let service_actors = actors
+            .iter()
+            .enumerate()
+            .filter(|(_, actor)| matches!(actor.seed.kind, ActorKind::Service))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if service_actors.is_empty() {
+            return Err("no service actors available".to_string());
+        }
+        let count = config
+            .actors
+            .unwrap_or(DEFAULT_ACTORS)
+            .clamp(1, service_actors.len());
+        let actor_indices = index::sample(rng, service_actors.len(), count)
+            .into_iter()
+            .map(|index| service_actors[index])
+            .collect();
+
+        let label = scenario_label(
+            Some(&config.scenario_id()),
+            config.technique_id.as_ref(),
+            &config.technique_ids,
+        )
+        .map_err(|id| format!("invalid ATT&CK technique ID: {id}"))?;
+        Ok(Self {
+            next_at: start,
+            end_at: start + Duration::seconds(config.duration_seconds),
+            actor_indices,
+            events,
+            interval_ms: 1_000.0 / events_per_second,
+            region: config.region.clone().unwrap_or(default_region),
+            error_rate,
+            error: throttling_error(&config.service, config.error_code.as_deref()),
+            label,
+        })
+    }
+
+    /// Time of the next storm call, or `None` once the storm is over.
+    pub fn next_at(&self) -> Option<DateTime<Utc>> {
+        (self.next_at < self.end_at).then_some(self.next_at)
+    }
+
+    /// Drops the calls before `at`, for a storm planned mid-run.
+    pub fn skip_before(&mut self, at: DateTime<Utc>) {
+        self.next_at = self.next_at.max(at);
+    }
+
+    /// Returns the next call and advances the storm clock.
+    pub fn next_call(&mut self, rng: &mut impl Rng) -> StormCall {
+        let call = StormCall {
+            actor_index: self.actor_indices[rng.gen_range(0..self.actor_indices.len())],
+            event_name: self.events[rng.gen_range(0..self.events.len())].clone(),
+            at: self.next_at,
+        };
+        let gap = self.interval_ms * rng.gen_range(0.5..1.5);
+        self.next_at += Duration::milliseconds(gap.max(1.0) as i64);
+        call
+    }
+}
+
+/// Throttling error the service returns; EC2 reports its own code.
+pub(super) fn throttling_error(service: &str, code: Option<&str>) -> ErrorProfile {
+    let code = code.unwrap_or(if service == "ec2.amazonaws.com" {
+        "RequestLimitExceeded"
+    } else {
+        "ThrottlingException"
+    });
+    let message = match code {
+        "RequestLimitExceeded" => "Request limit exceeded.",
+        _ => "Rate exceeded",
+    };
+    ErrorProfile {
+        code: code.to_string(),
+        message: message.to_string(),
+    }
+}
@@ -0,0 +1,154 @@
+//! Compromised-credential takeover of CloudTrail human actors.
+//!
+//! From the configured time an attacker replays the victim's stolen access
+//! key: the attacker runs as a second actor with the victim's identity
+//! (principal, ARN, access key, envelope actor ID) but its own schedule, source
+//! IPs, user agents, and regions, so legitimate and attacker sessions
+//! interleave in one stream. Attacker calls lean on discovery first, then
+//! persistence, collection, and resource hijacking, and carry the scenario
+//! label so evaluations can separate them from the victim's own activity.
+
+use crate::core::actors::{ActorKind, ActorProfile, ActorSeed};
+use crate::core::attack::scenario_label;
+use crate::core::config::CredentialTakeoverConfig;
+use crate::core::event::EventLabel;
+use chrono::{DateTime, Duration, Utc};
+
+const DEFAULT_EVENTS_PER_HOUR: f64 = 30.0;
+const DEFAULT_USER_AGENTS: [&str; 3] = [
+    "aws-cli/2.15.30 Python/3.11.8 Linux/6.6.9-amd64 exe/x86_64.kali.2024 prompt/off command/sts.get-caller-identity",
+    "Boto3/1.34.69 md/Botocore#1.34.69 ua/2.0 os/linux#6.5.0-21-generic md/arch#x86_64 lang/python#3.12.2 md/pyimpl#CPython cfg/retry-mode#legacy Botocore/1.34.69",
+    "S3 Browser/11.5.7 https://s3browser.com",
+];
+
+/// Attacker half of a takeover, attached to the attacker's actor slot.
+#[derive(Debug, Clone)]
+pub struct Takeover {
+    /// When the attacker stops using the credentials.
+    pub end_at: Option<DateTime<Utc>>,
+    /// Regions the attacker calls; empty uses the source regions.
+    pub regions: Vec<String>,
+    pub label: Option<EventLabel>,
+}
+
+impl Takeover {
+    /// Returns whether the attacker is done at `now`.
+    pub fn is_over(&self, now: DateTime<Utc>) -> bool {
+        self.end_at.is_some_and(|end| now >= end)
+    }
+}
+
+/// Resolves a takeover config against the actor list.
+///
+/// Returns the attacker's actor profile, its first event time, and the
+/// attacker state.
+pub fn plan_takeover(
+    config: &CredentialTakeoverConfig,
+    actors: &[ActorProfile],
+    start_time: DateTime<Utc>,
+) -> Result<(ActorProfile, DateTime<Utc>, Takeover), String> {
+    let victim = actors
+        .iter()
+        .find(|actor| {
+            actor.seed.id.as_deref() == Some(config.actor_id.as_str())
+                || actor.seed.principal_id == config.actor_id
+        })
+        .ok_or_else(|| format!("actor not found: {}", config.actor_id))?;
+    if !matches!(victim.seed.kind, ActorKind::Human) {
+        return Err(format!("actor {} is not a human actor", config.actor_id));
+    }
+
+    let stolen_at = match &config.start_time {
+        Some(raw) => DateTime::parse_from_rfc3339(raw)
+            .map_err(|_| format!("invalid start_time: {raw}"))?
+            .with_timezone(&Utc),
+        None => start_time + Duration::seconds(config.offset_seconds.unwrap_or(0)),
+    };
+    let end_at = match config.duration_seconds {
+        Some(seconds) if seconds <= 0 => {
+            return Err("duration_seconds must be greater than 0".to_string())
+        }
+        Some(seconds) => Some(stolen_at + Duration::seconds(seconds)),
+        None => None,
+    };
+    let events_per_hour = config.events_per_hour.unwrap_or(DEFAULT_EVENTS_PER_HOUR);
+    if !events_per_hour.is_finite() || events_per_hour <= 0.0 {
+        return Err("events_per_hour must be greater than 0".to_string());
+    }
+    let label = scenario_label(Some(&config.scenario_id()), None, &config.technique_ids())
+        .map_err(|id| format!("invalid ATT&CK technique ID: {id}"))?;
+
+    let seed = attacker_seed(&victim.seed, config, events_per_hour);
+    let takeover = Takeover {
+        end_at,
+        regions: config.regions.clone().unwrap_or_default(),
+        label,
+    };
+    Ok((ActorProfile::from_seed(seed), stolen_at, takeover))
+}
+
+/// Victim identity with attacker infrastructure and an around-the-clock schedule.
+fn attacker_seed(
+    victim: &ActorSeed,
+    config: &CredentialTakeoverConfig,
+    events_per_hour: f64,
+) -> ActorSeed {
+    let mut seed = victim.clone();
+    seed.rate_per_hour = events_per_hour;
+    seed.error_rate = (victim.error_rate * 3.0).min(0.3);
+    seed.event_bias.clear();
+    seed.insider_persona = None;
+    seed.source_ips = config
+        .source_ips
+        .clone()
+        .filter(|ips| !ips.is_empty())
+        .unwrap_or_else(|| default_source_ips(&victim.principal_id));
+    seed.user_agents = config
+        .user_agents
+        .clone()
+        .filter(|agents| !agents.is_empty())
+        .unwrap_or_else(|| DEFAULT_USER_AGENTS.map(str::to_string).to_vec());
+    seed.active_start_hour = 0;
+    seed.active_hours = 24;
+    seed.weekend_active = true;
+    seed
+}
+
+/// Hosting and VPN exit addresses, stable per victim.
+fn default_source_ips(principal_id: &str) -> Vec<String> {
+    let host = super::generator::stable_hash(principal_id) % 200 + 20;
+    vec![
+        format!("185.220.101.{host}"),
+        format!("45.155.205.{}", (host + 17) % 250),
+        format!("103.75.190.{}", (host + 41) % 250),
+    ]
+}
+
+/// Attacker event candidates: discovery after the first call, then a mix of
+/// persistence, collection, and compute abuse.
+pub fn attacker_candidates(last: Option<&str>) -> Vec<(String, f64)> {
+    let events: &[(&str, f64)] = match last {
+        None => &[("GetCallerIdentity", 3.0), ("ListBuckets", 1.0)],
+        Some("GetCallerIdentity") | Some("ListBuckets") => &[
+            ("DescribeInstances", 1.5),
+            ("ListBuckets", 1.0),
+            ("ListUsers", 1.0),
+            ("ListRoles", 0.8),
+            ("GetObject", 0.8),
+        ],
+        _ => &[
+            ("GetObject", 1.4),
+            ("DescribeInstances", 0.8),
+            ("CreateAccessKey", 0.4),
+            ("CreateUser", 0.3),
+            ("AttachRolePolicy", 0.3),
+            ("RunInstances", 0.4),
+            ("AuthorizeSecurityGroupIngress", 0.3),
+            ("GetCallerIdentity", 0.4),
+        ],
+    };
+    events
+        .iter()
+        .map(|(name, weight)| (name.to_string(), *weight))
+        .collect()
+}
@@ -1,24 +1,107 @@
-use super::model::{CloudTrailEvent, SessionAttributes, SessionContext, TlsDetails, UserIdentity};
+use super::catalog::CatalogEventDefinition;
+use super::generator::stable_hash;
+use super::model::{
+    CloudTrailEvent, SessionAttributes, SessionContext, SessionIssuer, TlsDetails, UserIdentity,
+    WebIdFederationData,
+};
+use super::workflow::RoleSession;
+use crate::core::event::Target;
+use crate::core::util::hex;
+use chrono::{DateTime, Utc};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct ActorContext {
-    pub identity_type: String,
-    pub principal_id: String,
-    pub arn: String,
-    pub account_id: String,
-    pub access_key_id: Option<String>,
-    pub user_name: Option<String>,
+    pub identity_type: Arc<str>,
+    pub principal_id: Arc<str>,
+    pub arn: Arc<str>,
+    pub account_id: Arc<str>,
+    pub access_key_id: Option<Arc<str>>,
+    pub user_name: Option<Arc<str>>,
     pub user_agent: String,
     pub source_ip: String,
     pub region: String,
     pub mfa_authenticated: bool,
     pub session_credential_from_console: bool,
+    /// Issuer of the assumed-role session the call runs under, if any.
+    pub session_issuer: Option<SessionIssuer>,
+    /// Creation time of that session; defaults to the event time.
+    pub session_created_at: Option<String>,
+    /// OIDC provider when the session came from `AssumeRoleWithWebIdentity`.
+    pub web_id_federation: Option<WebIdFederationData>,
+    /// Set for `WebIdentityUser` callers, which carry no ARN, account, or session.
+    pub identity_provider: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// User agent of fixture actors, an AWS CLI call from Linux.
+const FIXTURE_USER_AGENT: &str = "aws-cli/2.15.0 Python/3.11.6 Linux/6.1.0 exe/x86_64.ubuntu.22";
+/// Address of fixture actors, from the TEST-NET-3 documentation range.
+const FIXTURE_SOURCE_IP: &str = "203.0.113.10";
+
+impl ActorContext {
+    /// IAM user `user_name` calling with its own access key from the AWS CLI
+    /// in `us-east-1`. The principal ID and access key are derived from the
+    /// ARN, so the same user always gets the same ones.
+    pub fn iam_user(account_id: &str, user_name: &str) -> Self {
+        let arn = format!("arn:aws:iam::{account_id}:user/{user_name}");
+        let hash = stable_hash(&arn);
+        Self {
+            identity_type: "IAMUser".into(),
+            principal_id: format!("AIDA{hash:016X}").into(),
+            access_key_id: Some(format!("AKIA{:016X}", hash.rotate_left(32)).into()),
+            arn: arn.into(),
+            account_id: account_id.into(),
+            user_name: Some(user_name.into()),
+            user_agent: FIXTURE_USER_AGENT.to_string(),
+            source_ip: FIXTURE_SOURCE_IP.to_string(),
+            region: "us-east-1".to_string(),
+            mfa_authenticated: false,
+            session_credential_from_console: false,
+            session_issuer: None,
+            session_created_at: None,
+            web_id_federation: None,
+            identity_provider: None,
+        }
+    }
+
+    /// Session `session_name` of the role `role_arn`, assumed at `at`.
+    pub fn assumed_role(role_arn: &str, session_name: &str, at: DateTime<Utc>) -> Self {
+        let session = RoleSession::for_role(role_arn, session_name, at);
+        let mut actor = Self::iam_user(&session.account_id, session_name);
+        session.apply_to(&mut actor);
+        actor
+    }
+
+    pub fn with_region(mut self, region: &str) -> Self {
+        self.region = region.to_string();
+        self
+    }
+
+    pub fn with_source_ip(mut self, source_ip: &str) -> Self {
+        self.source_ip = source_ip.to_string();
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Marks the call as made from a console session, signed in with or
+    /// without MFA.
+    pub fn with_console_session(mut self, mfa_authenticated: bool) -> Self {
+        self.session_credential_from_console = true;
+        self.mfa_authenticated = mfa_authenticated;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ErrorProfile {
     pub code: String,
     pub message: String,
@@ -55,6 +138,8 @@ pub fn build_cloudtrail_event(
     let event = match event_name {
         "ConsoleLogin" => console_login(base),
         "AssumeRole" => assume_role(base, rng),
+        "AssumeRoleWithWebIdentity" => assume_role_with_web_identity(base, rng),
+        "AssumeRoleWithSAML" => assume_role_with_saml(base, rng),
         "GetSessionToken" => get_session_token(base, rng),
         "PutObject" => s3_put_object(base, rng),
         "GetObject" => s3_get_object(base, rng),
@@ -67,12 +152,124 @@ pub fn build_cloudtrail_event(
     Ok(apply_error(event, rng, error_profile, error_rate))
 }
 
-struct BaseFields {
+/// Builds a CloudTrail event from a custom catalog definition.
+///
+/// String values in the request/response templates may reference `{{account_id}}`,
+/// `{{region}}`, `{{user_name}}`, `{{principal_id}}`, `{{arn}}`, `{{source_ip}}`,
+/// `{{event_time}}`, `{{uuid}}`, and `{{random_id}}`. Unknown placeholders are kept as-is.
+pub fn build_custom_event(
+    definition: &CatalogEventDefinition,
+    actor: &ActorContext,
+    rng: &mut impl Rng,
+    event_time: &str,
+    error_profile: Option<ErrorProfile>,
+    error_rate: f64,
+) -> Result<CloudTrailEvent, TemplateError> {
+    if definition.name.trim().is_empty() {
+        return Err(TemplateError::EmptyEventName);
+    }
+
+    let base = BaseFields::new(actor, rng, event_time);
+    let mut event = base_event(
+        base,
+        &definition.event_source,
+        &definition.name,
+        Some(definition.read_only),
+    );
+    if let Some(event_type) = &definition.event_type {
+        event.event_type = Some(event_type.clone());
+    }
+    let no_params = BTreeMap::new();
+    event.request_parameters = Some(
+        definition
+            .request_parameters
+            .as_ref()
+            .map(|template| render_template(template, actor, event_time, &no_params, rng))
+            .unwrap_or_else(|| Value::Object(Default::default())),
+    );
+    event.response_elements = definition
+        .response_elements
+        .as_ref()
+        .map(|template| render_template(template, actor, event_time, &no_params, rng));
+
+    Ok(apply_error(event, rng, error_profile, error_rate))
+}
+
+/// Renders the placeholders in every string of `template`; `params` names
+/// extra placeholders, such as a narrative's task parameters.
+pub(super) fn render_template(
+    template: &Value,
+    actor: &ActorContext,
+    event_time: &str,
+    params: &BTreeMap<String, String>,
+    rng: &mut impl Rng,
+) -> Value {
+    match template {
+        Value::String(text) => Value::String(substitute_placeholders(
+            text, actor, event_time, params, rng,
+        )),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_template(item, actor, event_time, params, rng))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    (
+                        key.clone(),
+                        render_template(value, actor, event_time, params, rng),
+                    )
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+pub(super) fn substitute_placeholders(
+    text: &str,
+    actor: &ActorContext,
+    event_time: &str,
+    params: &BTreeMap<String, String>,
+    rng: &mut impl Rng,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let raw = &rest[start..start + 2 + len + 2];
+        let name = rest[start + 2..start + 2 + len].trim();
+        let value = match name {
+            _ if params.contains_key(name) => params[name].clone(),
+            "account_id" => actor.account_id.to_string(),
+            "region" => actor.region.clone(),
+            "user_name" => actor.user_name.as_deref().unwrap_or_default().to_string(),
+            "principal_id" => actor.principal_id.to_string(),
+            "arn" => actor.arn.to_string(),
+            "source_ip" => actor.source_ip.clone(),
+            "event_time" => event_time.to_string(),
+            "uuid" => random_uuid(rng),
+            "random_id" => random_alpha(rng, 8).to_lowercase(),
+            _ => raw.to_string(),
+        };
+        out.push_str(&value);
+        rest = &rest[start + raw.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+pub(super) struct BaseFields {
     event_time: String,
     aws_region: String,
     source_ip_address: String,
     user_agent: String,
-    account_id: String,
+    account_id: Arc<str>,
     user_identity: UserIdentity,
     request_id: String,
     event_id: String,
@@ -80,18 +277,30 @@ struct BaseFields {
 }
 
 impl BaseFields {
-    fn new(actor: &ActorContext, rng: &mut impl Rng, event_time: &str) -> Self {
-        let account_id = actor.account_id.clone();
+    pub(super) fn new(actor: &ActorContext, rng: &mut impl Rng, event_time: &str) -> Self {
+        let account_id = Arc::clone(&actor.account_id);
         let user_name = actor.user_name.clone();
-        let session_context = session_context_for(actor, event_time);
-        let user_identity = UserIdentity {
-            identity_type: actor.identity_type.clone(),
-            principal_id: actor.principal_id.clone(),
-            arn: actor.arn.clone(),
-            account_id: account_id.clone(),
-            access_key_id: actor.access_key_id.clone(),
-            user_name,
-            session_context,
+        let user_identity = match &actor.identity_provider {
+            Some(provider) => UserIdentity {
+                identity_type: Arc::clone(&actor.identity_type),
+                principal_id: Arc::clone(&actor.principal_id),
+                arn: Arc::default(),
+                account_id: Arc::default(),
+                access_key_id: None,
+                user_name,
+                identity_provider: Some(provider.clone()),
+                session_context: None,
+            },
+            None => UserIdentity {
+                identity_type: Arc::clone(&actor.identity_type),
+                principal_id: Arc::clone(&actor.principal_id),
+                arn: Arc::clone(&actor.arn),
+                account_id: Arc::clone(&account_id),
+                access_key_id: actor.access_key_id.clone(),
+                user_name,
+                identity_provider: None,
+                session_context: session_context_for(actor, event_time),
+            },
         };
 
         Self {
@@ -123,6 +332,8 @@ fn console_login(base: BaseFields) -> CloudTrailEvent {
         "ConsoleLogin": "Success",
     }));
     event.event_type = Some("AwsConsoleSignIn".to_string());
+    // The sign-in creates the console session rather than running in one.
+    event.session_credential_from_console = None;
     event
 }
 
@@ -143,6 +354,52 @@ fn assume_role(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
     event
 }
 
+fn assume_role_with_web_identity(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
+    let role_name = format!("workload-role-{}", random_alpha(rng, 4));
+    let role_arn = format!("arn:aws:iam::{}:role/{}", base.account_id, role_name);
+    let mut event = base_event(
+        base,
+        "sts.amazonaws.com",
+        "AssumeRoleWithWebIdentity",
+        Some(true),
+    );
+    event.request_parameters = Some(json!({
+        "roleArn": role_arn,
+        "roleSessionName": format!("botocore-session-{}", rng.gen_range(1_700_000_000..1_800_000_000)),
+    }));
+    event.response_elements = Some(json!({
+        "credentials": {
+            "accessKeyId": format!("ASIA{}", random_alpha(rng, 16).to_uppercase()),
+            "expiration": "2024-01-01T00:00:00Z",
+        },
+        "audience": "sts.amazonaws.com",
+    }));
+    event
+}
+
+fn assume_role_with_saml(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
+    let role_arn = format!(
+        "arn:aws:iam::{}:role/federated-{}",
+        base.account_id,
+        random_alpha(rng, 4)
+    );
+    let principal_arn = format!("arn:aws:iam::{}:saml-provider/idp", base.account_id);
+    let mut event = base_event(base, "sts.amazonaws.com", "AssumeRoleWithSAML", Some(true));
+    event.request_parameters = Some(json!({
+        "roleArn": role_arn,
+        "principalArn": principal_arn,
+        "durationSeconds": 3600,
+    }));
+    event.response_elements = Some(json!({
+        "credentials": {
+            "accessKeyId": format!("ASIA{}", random_alpha(rng, 16).to_uppercase()),
+            "expiration": "2024-01-01T00:00:00Z",
+        },
+        "audience": "https://signin.aws.amazon.com/saml",
+    }));
+    event
+}
+
 fn get_session_token(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
     let mut event = base_event(base, "sts.amazonaws.com", "GetSessionToken", Some(false));
     event.request_parameters = Some(json!({
@@ -160,7 +417,7 @@ fn get_session_token(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
 }
 
 fn s3_put_object(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
-    let bucket = random_bucket_name(rng);
+    let bucket = bucket_name(&base.account_id, rng);
     let key = format!(
         "logs/{}/{}.json",
         random_alpha(rng, 4),
@@ -178,7 +435,7 @@ fn s3_put_object(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
 }
 
 fn s3_get_object(base: BaseFields, rng: &mut impl Rng) -> CloudTrailEvent {
-    let bucket = random_bucket_name(rng);
+    let bucket = bucket_name(&base.account_id, rng);
     let key = format!(
         "data/{}/{}.parquet",
         random_alpha(rng, 4),
@@ -253,7 +510,7 @@ fn generic_event(base: BaseFields, event_name: &str) -> CloudTrailEvent {
     event
 }
 
-fn base_event(
+pub(super) fn base_event(
     base: BaseFields,
     event_source: &str,
     event_name: &str,
@@ -270,9 +527,11 @@ fn base_event(
         user_identity: base.user_identity,
         request_parameters: None,
         response_elements: None,
+        additional_event_data: None,
         request_id: base.request_id,
         event_id: base.event_id,
         read_only,
+        resources: None,
         event_type: Some(event_type_for(event_name).to_string()),
         management_event: Some(true),
         recipient_account_id: base.account_id,
@@ -296,18 +555,42 @@ fn random_account_id(rng: &mut impl Rng) -> String {
     (0..12).map(|_| rng.gen_range(0..10).to_string()).collect()
 }
 
-fn random_bucket_name(rng: &mut impl Rng) -> String {
-    format!("logs-bucket-{}", random_alpha(rng, 6).to_lowercase())
+/// Picks one of a small set of per-account buckets so object events repeat buckets.
+pub(super) fn bucket_name(account_id: &str, rng: &mut impl Rng) -> String {
+    const PREFIXES: [&str; 4] = ["app-data", "analytics-raw", "logs-archive", "ml-artifacts"];
+    format!(
+        "{}-{account_id}",
+        PREFIXES[rng.gen_range(0..PREFIXES.len())]
+    )
 }
 
-fn random_alpha(rng: &mut impl Rng, len: usize) -> String {
+pub(super) fn random_alpha(rng: &mut impl Rng, len: usize) -> String {
     rng.sample_iter(&Alphanumeric)
         .take(len)
         .map(char::from)
         .collect()
 }
 
-fn random_uuid(rng: &mut impl Rng) -> String {
+/// UUID derived from `name`: the first 16 bytes of its SHA-256, stamped as
+/// version 5 with the RFC 4122 variant (v5 proper hashes with SHA-1).
+pub fn derived_uuid(name: &str) -> String {
+    let digest = Sha256::digest(name.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex(&bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+pub(super) fn random_uuid(rng: &mut impl Rng) -> String {
     let mut out = String::with_capacity(36);
     let sections = [8, 4, 4, 4, 12];
     for (idx, count) in sections.iter().enumerate() {
@@ -329,19 +612,31 @@ fn event_type_for(event_name: &str) -> &'static str {
     }
 }
 
-fn read_only_for(event_name: &str) -> Option<bool> {
+pub(super) fn read_only_for(event_name: &str) -> Option<bool> {
     match event_name {
-        "GetObject" | "DescribeInstances" | "GetCallerIdentity" | "DescribeLogStreams"
-        | "GetMetricData" | "ListMetrics" => Some(true),
+        "GetObject"
+        | "DescribeInstances"
+        | "GetCallerIdentity"
+        | "DescribeLogStreams"
+        | "GetMetricData"
+        | "ListMetrics"
+        | "GetRole"
+        | "GetRolePolicy"
+        | "ListAttachedRolePolicies" => Some(true),
         "ConsoleLogin" => Some(true),
         _ => Some(false),
     }
 }
 
-fn event_source_for(event_name: &str) -> &'static str {
+/// Event source of a built-in event name.
+pub fn event_source_for(event_name: &str) -> &'static str {
     match event_name {
         "ConsoleLogin" => "signin.amazonaws.com",
-        "AssumeRole" | "GetSessionToken" | "GetCallerIdentity" => "sts.amazonaws.com",
+        "AssumeRole"
+        | "AssumeRoleWithWebIdentity"
+        | "AssumeRoleWithSAML"
+        | "GetSessionToken"
+        | "GetCallerIdentity" => "sts.amazonaws.com",
         "PutObject" | "GetObject" | "DeleteObject" | "CreateBucket" | "DeleteBucket" => {
             "s3.amazonaws.com"
         }
@@ -352,8 +647,18 @@ fn event_source_for(event_name: &str) -> &'static str {
         | "DescribeInstances"
         | "CreateSecurityGroup"
         | "AuthorizeSecurityGroupIngress" => "ec2.amazonaws.com",
-        "CreateUser" | "DeleteUser" | "CreateAccessKey" | "UpdateAccessKey"
-        | "AttachRolePolicy" | "AddUserToGroup" | "CreateRole" => "iam.amazonaws.com",
+        "CreateUser"
+        | "DeleteUser"
+        | "CreateAccessKey"
+        | "UpdateAccessKey"
+        | "AttachRolePolicy"
+        | "DetachRolePolicy"
+        | "PutRolePolicy"
+        | "GetRole"
+        | "GetRolePolicy"
+        | "ListAttachedRolePolicies"
+        | "AddUserToGroup"
+        | "CreateRole" => "iam.amazonaws.com",
         "CreateLogGroup" | "CreateLogStream" | "DescribeLogStreams" | "PutLogEvents" => {
             "logs.amazonaws.com"
         }
@@ -374,10 +679,22 @@ fn tls_details_for(event_source: &str) -> TlsDetails {
 
 fn session_context_for(actor: &ActorContext, event_time: &str) -> Option<SessionContext> {
     Some(SessionContext {
-        session_issuer: json!({}),
-        web_id_federation_data: json!({}),
+        session_issuer: actor
+            .session_issuer
+            .clone()
+            .or_else(|| assumed_role_issuer(actor))
+            .and_then(|issuer| serde_json::to_value(issuer).ok())
+            .unwrap_or_else(|| json!({})),
+        web_id_federation_data: actor
+            .web_id_federation
+            .as_ref()
+            .and_then(|data| serde_json::to_value(data).ok())
+            .unwrap_or_else(|| json!({})),
         attributes: SessionAttributes {
-            creation_date: event_time.to_string(),
+            creation_date: actor
+                .session_created_at
+                .clone()
+                .unwrap_or_else(|| event_time.to_string()),
             mfa_authenticated: if actor.mfa_authenticated {
                 "true".to_string()
             } else {
@@ -387,6 +704,27 @@ fn session_context_for(actor: &ActorContext, event_time: &str) -> Option<Session
     })
 }
 
+/// Derives the source role of an `AssumedRole` caller from its STS ARN.
+fn assumed_role_issuer(actor: &ActorContext) -> Option<SessionIssuer> {
+    if &*actor.identity_type != "AssumedRole" {
+        return None;
+    }
+    let (_, tail) = actor.arn.split_once(":assumed-role/")?;
+    let role_name = tail.split('/').next().filter(|name| !name.is_empty())?;
+    let role_id = actor
+        .principal_id
+        .split(':')
+        .next()
+        .unwrap_or(&actor.principal_id);
+    Some(SessionIssuer {
+        issuer_type: "Role".to_string(),
+        principal_id: role_id.to_string(),
+        arn: format!("arn:aws:iam::{}:role/{}", actor.account_id, role_name),
+        account_id: actor.account_id.to_string(),
+        user_name: role_name.to_string(),
+    })
+}
+
 fn random_instance_set(rng: &mut impl Rng) -> Vec<String> {
     let count = rng.gen_range(1..=2);
     (0..count)
@@ -394,6 +732,144 @@ fn random_instance_set(rng: &mut impl Rng) -> Vec<String> {
         .collect()
 }
 
+/// Resolves the resource a CloudTrail event acts on, for the envelope target.
+///
+/// Explicit `resources` win (data events list the most specific one first);
+/// otherwise well-known request parameters are checked, then the response.
+pub fn envelope_target(event: &CloudTrailEvent) -> Option<Target> {
+    if let Some(resource) = event.resources.as_ref().and_then(|list| list.first()) {
+        return Some(Target {
+            id: resource.arn.clone(),
+            kind: resource.resource_type.clone(),
+            name: arn_name(&resource.arn),
+        });
+    }
+    [&event.request_parameters, &event.response_elements]
+        .into_iter()
+        .flatten()
+        .find_map(|params| target_from_params(event, params))
+}
+
+fn target_from_params(event: &CloudTrailEvent, params: &Value) -> Option<Target> {
+    let region = event.aws_region.as_str();
+    let account = &*event.recipient_account_id;
+    let text = |key: &str| params.get(key).and_then(Value::as_str);
+
+    if let Some(bucket) = text("bucketName") {
+        return Some(match text("key") {
+            Some(key) => resource_target(
+                "AWS::S3::Object",
+                format!("arn:aws:s3:::{bucket}/{key}"),
+                key,
+            ),
+            None => resource_target("AWS::S3::Bucket", format!("arn:aws:s3:::{bucket}"), bucket),
+        });
+    }
+    if let Some(instance) = first_instance_id(params) {
+        return Some(resource_target(
+            "AWS::EC2::Instance",
+            format!("arn:aws:ec2:{region}:{account}:instance/{instance}"),
+            instance,
+        ));
+    }
+
+    let iam = event.event_source == "iam.amazonaws.com";
+    let named = [
+        ("roleArn", "AWS::IAM::Role", "iam", "role/"),
+        ("roleName", "AWS::IAM::Role", "iam", "role/"),
+        ("policyArn", "AWS::IAM::ManagedPolicy", "iam", "policy/"),
+        (
+            "secretId",
+            "AWS::SecretsManager::Secret",
+            "secretsmanager",
+            "secret:",
+        ),
+        ("keyId", "AWS::KMS::Key", "kms", "key/"),
+        ("logGroupName", "AWS::Logs::LogGroup", "logs", "log-group:"),
+        (
+            "functionName",
+            "AWS::Lambda::Function",
+            "lambda",
+            "function:",
+        ),
+        ("tableName", "AWS::DynamoDB::Table", "dynamodb", "table/"),
+        (
+            "trailName",
+            "AWS::CloudTrail::Trail",
+            "cloudtrail",
+            "trail/",
+        ),
+        (
+            "groupId",
+            "AWS::EC2::SecurityGroup",
+            "ec2",
+            "security-group/",
+        ),
+    ];
+    let iam_named = [
+        ("userName", "AWS::IAM::User", "iam", "user/"),
+        ("groupName", "AWS::IAM::Group", "iam", "group/"),
+    ];
+    let candidates = named.iter().chain(iam_named.iter().filter(|_| iam));
+    for (key, kind, service, prefix) in candidates {
+        let Some(value) = text(key) else {
+            continue;
+        };
+        if value.starts_with("arn:") {
+            return Some(Target {
+                id: value.to_string(),
+                kind: kind.to_string(),
+                name: arn_name(value),
+            });
+        }
+        // IAM is global, so its ARNs carry no region.
+        let region = if *service == "iam" { "" } else { region };
+        return Some(resource_target(
+            kind,
+            format!("arn:aws:{service}:{region}:{account}:{prefix}{value}"),
+            value,
+        ));
+    }
+    None
+}
+
+/// Finds the first instance id in the request or response shapes EC2 uses.
+fn first_instance_id(params: &Value) -> Option<&str> {
+    if let Some(id) = params.get("instanceId").and_then(Value::as_str) {
+        return Some(id);
+    }
+    if let Some(id) = params
+        .get("instanceIds")
+        .and_then(|ids| ids.get(0))
+        .and_then(Value::as_str)
+    {
+        return Some(id);
+    }
+    let set = params.get("instancesSet")?;
+    let items = set.get("items").unwrap_or(set);
+    items.get(0)?.get("instanceId")?.as_str()
+}
+
+fn resource_target(kind: &str, id: String, name: &str) -> Target {
+    Target {
+        id,
+        kind: kind.to_string(),
+        name: Some(name.to_string()),
+    }
+}
+
+/// Takes the resource name from an ARN: the object key for S3, otherwise the
+/// trailing segment.
+fn arn_name(arn: &str) -> Option<String> {
+    let resource = arn.splitn(6, ':').nth(5)?;
+    let name = if arn.starts_with("arn:aws:s3:::") {
+        resource.split_once('/').map_or(resource, |(_, key)| key)
+    } else {
+        resource.rsplit(['/', ':']).next().unwrap_or(resource)
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
 pub fn apply_error(
     mut event: CloudTrailEvent,
     rng: &mut impl Rng,
@@ -435,6 +911,14 @@ pub fn default_error_profile(event_name: &str) -> Option<ErrorProfile> {
             code: "AccessDenied".to_string(),
             message: "Not authorized to assume role".to_string(),
         },
+        "AssumeRoleWithWebIdentity" => ErrorProfile {
+            code: "InvalidIdentityToken".to_string(),
+            message: "Couldn't retrieve verification key from your identity provider".to_string(),
+        },
+        "AssumeRoleWithSAML" => ErrorProfile {
+            code: "InvalidIdentityToken".to_string(),
+            message: "Specified provider doesn't exist.".to_string(),
+        },
         "PutObject" | "GetObject" => ErrorProfile {
             code: "AccessDenied".to_string(),
             message: "Access denied".to_string(),
@@ -456,21 +940,77 @@ mod tests {
     use super::*;
     use rand::SeedableRng;
 
+    #[test]
+    fn custom_event_renders_placeholders() {
+        let definition = CatalogEventDefinition {
+            name: "GetSecretValue".to_string(),
+            event_source: "secretsmanager.amazonaws.com".to_string(),
+            read_only: true,
+            weight: 1.0,
+            event_type: None,
+            request_parameters: Some(json!({
+                "secretId": "arn:aws:secretsmanager:{{region}}:{{account_id}}:secret:{{ user_name }}",
+                "tags": ["{{unknown}}"]
+            })),
+            response_elements: None,
+        };
+        let actor = ActorContext {
+            identity_type: "IAMUser".into(),
+            principal_id: "AIDA_TEST_002".into(),
+            arn: "arn:aws:iam::123456789012:user/alice".into(),
+            account_id: "123456789012".into(),
+            access_key_id: None,
+            user_name: Some("alice".into()),
+            user_agent: "aws-cli/2.15.0".to_string(),
+            source_ip: "10.0.0.2".to_string(),
+            region: "us-east-1".to_string(),
+            mfa_authenticated: false,
+            session_credential_from_console: false,
+            session_issuer: None,
+            session_created_at: None,
+            web_id_federation: None,
+            identity_provider: None,
+        };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let event = build_custom_event(
+            &definition,
+            &actor,
+            &mut rng,
+            "2025-01-01T00:00:00Z",
+            None,
+            0.0,
+        )
+        .expect("event");
+
+        assert_eq!(event.event_source, "secretsmanager.amazonaws.com");
+        assert_eq!(event.read_only, Some(true));
+        let params = event.request_parameters.expect("params");
+        assert_eq!(
+            params["secretId"],
+            "arn:aws:secretsmanager:us-east-1:123456789012:secret:alice"
+        );
+        assert_eq!(params["tags"][0], "{{unknown}}");
+    }
+
     #[test]
     fn console_login_template() {
         let mut rng = rand::rngs::StdRng::seed_from_u64(42);
         let actor = ActorContext {
-            identity_type: "IAMUser".to_string(),
-            principal_id: "AIDA_TEST_001".to_string(),
-            arn: "arn:aws:iam::123456789012:user/test".to_string(),
-            account_id: "123456789012".to_string(),
-            access_key_id: Some("AKIATEST1234567890".to_string()),
-            user_name: Some("test".to_string()),
+            identity_type: "IAMUser".into(),
+            principal_id: "AIDA_TEST_001".into(),
+            arn: "arn:aws:iam::123456789012:user/test".into(),
+            account_id: "123456789012".into(),
+            access_key_id: Some("AKIATEST1234567890".into()),
+            user_name: Some("test".into()),
             user_agent: "aws-cli/2.15.0".to_string(),
             source_ip: "10.0.0.1".to_string(),
             region: "us-east-1".to_string(),
             mfa_authenticated: true,
             session_credential_from_console: false,
+            session_issuer: None,
+            session_created_at: None,
+            web_id_federation: None,
+            identity_provider: None,
         };
         let event = build_cloudtrail_event(
             "ConsoleLogin",
@@ -485,4 +1025,129 @@ mod tests {
         assert_eq!(event.event_name, "ConsoleLogin");
         assert!(event.request_parameters.is_some());
     }
+
+    #[test]
+    fn assumed_role_session_context_names_source_role() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        let mut actor = ActorContext {
+            identity_type: "AssumedRole".into(),
+            principal_id: "AROAEXAMPLEROLEID:svc-ingest".into(),
+            arn: "arn:aws:sts::123456789012:assumed-role/svc-role-ab12/svc-ingest".into(),
+            account_id: "123456789012".into(),
+            access_key_id: Some("ASIAEXAMPLE".into()),
+            user_name: None,
+            user_agent: "Boto3/1.34.0 Python/3.11".to_string(),
+            source_ip: "10.0.0.3".to_string(),
+            region: "us-east-1".to_string(),
+            mfa_authenticated: false,
+            session_credential_from_console: false,
+            session_issuer: None,
+            session_created_at: None,
+            web_id_federation: None,
+            identity_provider: None,
+        };
+        let event = build_cloudtrail_event(
+            "GetObject",
+            &actor,
+            &mut rng,
+            "2025-01-01T00:00:00Z",
+            None,
+            0.0,
+        )
+        .expect("event");
+        let context = serde_json::to_value(event.user_identity.session_context).unwrap();
+        assert_eq!(
+            context["sessionIssuer"],
+            json!({
+                "type": "Role",
+                "principalId": "AROAEXAMPLEROLEID",
+                "arn": "arn:aws:iam::123456789012:role/svc-role-ab12",
+                "accountId": "123456789012",
+                "userName": "svc-role-ab12",
+            })
+        );
+        assert_eq!(context["webIdFederationData"], json!({}));
+
+        actor.identity_type = "IAMUser".into();
+        let event = build_cloudtrail_event(
+            "GetObject",
+            &actor,
+            &mut rng,
+            "2025-01-01T00:00:00Z",
+            None,
+            0.0,
+        )
+        .expect("event");
+        let context = serde_json::to_value(event.user_identity.session_context).unwrap();
+        assert_eq!(context["sessionIssuer"], json!({}));
+    }
+
+    #[test]
+    fn envelope_target_follows_request_parameters() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(9);
+        let actor = ActorContext {
+            identity_type: "IAMUser".into(),
+            principal_id: "AIDA_TEST_003".into(),
+            arn: "arn:aws:iam::123456789012:user/bob".into(),
+            account_id: "123456789012".into(),
+            access_key_id: Some("AKIATEST0000000003".into()),
+            user_name: Some("bob".into()),
+            user_agent: "aws-cli/2.15.0".to_string(),
+            source_ip: "10.0.0.4".to_string(),
+            region: "eu-west-1".to_string(),
+            mfa_authenticated: false,
+            session_credential_from_console: false,
+            session_issuer: None,
+            session_created_at: None,
+            web_id_federation: None,
+            identity_provider: None,
+        };
+        let mut build = |name: &str| {
+            build_cloudtrail_event(name, &actor, &mut rng, "2025-01-01T00:00:00Z", None, 0.0)
+                .expect("event")
+        };
+
+        let event = build("PutObject");
+        let params = event.request_parameters.clone().expect("params");
+        let target = envelope_target(&event).expect("object target");
+        assert_eq!(target.kind, "AWS::S3::Object");
+        assert_eq!(
+            target.id,
+            format!(
+                "arn:aws:s3:::{}/{}",
+                params["bucketName"].as_str().unwrap(),
+                params["key"].as_str().unwrap()
+            )
+        );
+
+        let event = build("StartInstances");
+        let instance = event.request_parameters.as_ref().unwrap()["instancesSet"]["items"][0]
+            ["instanceId"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let target = envelope_target(&event).expect("instance target");
+        assert_eq!(target.kind, "AWS::EC2::Instance");
+        assert_eq!(
+            target.id,
+            format!("arn:aws:ec2:eu-west-1:123456789012:instance/{instance}")
+        );
+        assert_eq!(target.name.as_deref(), Some(instance.as_str()));
+
+        let event = build("AssumeRole");
+        let role_arn = event.request_parameters.as_ref().unwrap()["roleArn"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let target = envelope_target(&event).expect("role target");
+        assert_eq!(target.kind, "AWS::IAM::Role");
+        assert_eq!(target.id, role_arn);
+
+        let mut event = build("ConsoleLogin");
+        assert!(envelope_target(&event).is_none());
+        event.request_parameters = Some(json!({ "userName": "carol" }));
+        event.event_source = "iam.amazonaws.com".to_string();
+        let target = envelope_target(&event).expect("user target");
+        assert_eq!(target.id, "arn:aws:iam::123456789012:user/carol");
+    }
 }
@@ -0,0 +1,317 @@
+//! Impossible travel by a human actor across Entra ID and CloudTrail.
+//!
+//! The actor signs in to Entra ID from one city, and minutes later the same
+//! identity logs in to the AWS console and works from a city thousands of
+//! kilometres away, faster than any flight could carry them. Both halves
+//! carry envelope geo fields with coordinates and the scenario label, so
+//! UEBA rules that compare a user's consecutive locations can be scored
+//! against them.
+
+use crate::core::actors::{
+    is_browser_user_agent, ActorKind, ActorProfile, DEFAULT_BROWSER_USER_AGENT,
+};
+use crate::core::attack::scenario_label;
+use crate::core::config::ImpossibleTravelConfig;
+use crate::core::event::{EventLabel, Geo};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use std::collections::VecDeque;
+
+const DEFAULT_GAP_SECONDS: i64 = 600;
+/// Console reads after the distant `ConsoleLogin`.
+const CONSOLE_CALLS: [&str; 5] = [
+    "DescribeInstances",
+    "ListBuckets",
+    "ListUsers",
+    "ListRoles",
+    "GetObject",
+];
+
+/// City a travel scenario can place an actor in.
+#[derive(Debug, PartialEq)]
+pub struct City {
+    /// `City, State, Country`, as actor home locations are written.
+    pub name: &'static str,
+    pub country_code: &'static str,
+    pub lat: f64,
+    pub lon: f64,
+    /// First three octets of a residential range in the city.
+    ip_prefix: &'static str,
+}
+
+static CITIES: [City; 10] = [
+    City {
+        name: "New York, NY, United States",
+        country_code: "US",
+        lat: 40.71,
+        lon: -74.01,
+        ip_prefix: "72.229.28",
+    },
+    City {
+        name: "San Francisco, CA, United States",
+        country_code: "US",
+        lat: 37.77,
+        lon: -122.42,
+        ip_prefix: "67.169.84",
+    },
+    City {
+        name: "London, England, United Kingdom",
+        country_code: "GB",
+        lat: 51.51,
+        lon: -0.13,
+        ip_prefix: "81.2.69",
+    },
+    City {
+        name: "Frankfurt, Hesse, Germany",
+        country_code: "DE",
+        lat: 50.11,
+        lon: 8.68,
+        ip_prefix: "91.64.12",
+    },
+    City {
+        name: "Mumbai, Maharashtra, India",
+        country_code: "IN",
+        lat: 19.08,
+        lon: 72.88,
+        ip_prefix: "49.36.88",
+    },
+    City {
+        name: "Singapore",
+        country_code: "SG",
+        lat: 1.35,
+        lon: 103.82,
+        ip_prefix: "116.14.62",
+    },
+    City {
+        name: "Tokyo, Tokyo, Japan",
+        country_code: "JP",
+        lat: 35.68,
+        lon: 139.69,
+        ip_prefix: "126.36.40",
+    },
+    City {
+        name: "Sydney, NSW, Australia",
+        country_code: "AU",
+        lat: -33.87,
+        lon: 151.21,
+        ip_prefix: "49.255.14",
+    },
+    City {
+        name: "São Paulo, SP, Brazil",
+        country_code: "BR",
+        lat: -23.55,
+        lon: -46.63,
+        ip_prefix: "177.92.3",
+    },
+    City {
+        name: "Lagos, Lagos, Nigeria",
+        country_code: "NG",
+        lat: 6.52,
+        lon: 3.38,
+        ip_prefix: "105.112.18",
+    },
+];
+
+impl City {
+    /// Known city by full name or city name, ignoring case.
+    pub fn find(name: &str) -> Option<&'static City> {
+        let name = name.trim();
+        CITIES.iter().find(|city| {
+            city.name.eq_ignore_ascii_case(name) || city.short_name().eq_ignore_ascii_case(name)
+        })
+    }
+
+    fn short_name(&self) -> &'static str {
+        self.name.split(',').next().unwrap_or(self.name)
+    }
+
+    /// Envelope geo fields for the city.
+    pub fn geo(&self) -> Geo {
+        let parts = self.name.split(", ").collect::<Vec<_>>();
+        Geo {
+            country: self.country_code.to_string(),
+            region: (parts.len() > 2).then(|| parts[1].to_string()),
+            city: Some(parts[0].to_string()),
+            lat: Some(self.lat),
+            lon: Some(self.lon),
+        }
+    }
+
+    /// Great-circle distance to `other` in kilometres.
+    pub fn distance_km(&self, other: &City) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (other.lon - self.lon).to_radians();
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        6_371.0 * 2.0 * a.sqrt().asin()
+    }
+
+    fn address(&self, rng: &mut impl Rng) -> String {
+        format!("{}.{}", self.ip_prefix, rng.gen_range(2..255))
+    }
+}
+
+/// Planned scenario events, earliest first.
+#[derive(Debug, Clone)]
+pub struct ImpossibleTravel {
+    pub actor_index: usize,
+    /// Browser the actor uses in both cities.
+    pub user_agent: String,
+    calls: VecDeque<TravelCall>,
+    pub label: Option<EventLabel>,
+}
+
+/// One scenario event to emit.
+#[derive(Debug, Clone)]
+pub struct TravelCall {
+    /// CloudTrail event name, or `None` for the Entra sign-in.
+    pub event_name: Option<&'static str>,
+    pub at: DateTime<Utc>,
+    pub city: &'static City,
+    pub source_ip: String,
+}
+
+impl ImpossibleTravel {
+    /// Resolves a scenario config against the actor list and plans every event.
+    pub fn plan(
+        config: &ImpossibleTravelConfig,
+        actors: &[ActorProfile],
+        start_time: DateTime<Utc>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, String> {
+        let actor_index = actors
+            .iter()
+            .position(|actor| {
+                actor.seed.id.as_deref() == Some(config.actor_id.as_str())
+                    || actor.seed.principal_id == config.actor_id
+            })
+            .ok_or_else(|| format!("actor not found: {}", config.actor_id))?;
+        let seed = &actors[actor_index].seed;
+        if !matches!(seed.kind, ActorKind::Human) {
+            return Err(format!("actor {} is not a human actor", config.actor_id));
+        }
+        let signed_in_at = match &config.start_time {
+            Some(raw) => DateTime::parse_from_rfc3339(raw)
+                .map_err(|_| format!("invalid start_time: {raw}"))?
+                .with_timezone(&Utc),
+            None => start_time + Duration::seconds(config.offset_seconds.unwrap_or(0)),
+        };
+        let gap_seconds = config.gap_seconds.unwrap_or(DEFAULT_GAP_SECONDS);
+        if gap_seconds <= 0 {
+            return Err("gap_seconds must be greater than 0".to_string());
+        }
+        let events = config.events();
+        if events == 0 {
+            return Err("events must be greater than 0".to_string());
+        }
+
+        let origin = match &config.sign_in_location {
+            Some(name) => find_city(name, "sign_in_location")?,
+            None => seed
+                .home_location
+                .as_deref()
+                .and_then(City::find)
+                .unwrap_or(&CITIES[0]),
+        };
+        let destination = match &config.activity_location {
+            Some(name) => find_city(name, "activity_location")?,
+            None => CITIES
+                .iter()
+                .max_by(|a, b| origin.distance_km(a).total_cmp(&origin.distance_km(b)))
+                .unwrap_or(&CITIES[0]),
+        };
+        if origin == destination {
+            return Err("activity_location must differ from sign_in_location".to_string());
+        }
+
+        let mut calls = VecDeque::with_capacity(events + 1);
+        calls.push_back(TravelCall {
+            event_name: None,
+            at: signed_in_at,
+            city: origin,
+            source_ip: config
+                .sign_in_ip
+                .clone()
+                .unwrap_or_else(|| origin.address(rng)),
+        });
+        let source_ip = config
+            .activity_ip
+            .clone()
+            .unwrap_or_else(|| destination.address(rng));
+        let mut at = signed_in_at + Duration::seconds(gap_seconds);
+        for index in 0..events {
+            calls.push_back(TravelCall {
+                event_name: Some(if index == 0 {
+                    "ConsoleLogin"
+                } else {
+                    CONSOLE_CALLS[rng.gen_range(0..CONSOLE_CALLS.len())]
+                }),
+                at,
+                city: destination,
+                source_ip: source_ip.clone(),
+            });
+            at += Duration::milliseconds(rng.gen_range(5_000..60_000));
+        }
+
+        let label = scenario_label(Some(&config.scenario_id()), None, &config.technique_ids())
+            .map_err(|id| format!("invalid ATT&CK technique ID: {id}"))?;
+        Ok(Self {
+            actor_index,
+            user_agent: seed
+                .user_agents
+                .iter()
+                .find(|agent| is_browser_user_agent(agent))
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_BROWSER_USER_AGENT.to_string()),
+            calls,
+            label,
+        })
+    }
+
+    /// Time of the next scenario event, or `None` once all are out.
+    pub fn next_at(&self) -> Option<DateTime<Utc>> {
+        self.calls.front().map(|call| call.at)
+    }
+
+    /// Drops the calls before `at`, for a scenario planned mid-run.
+    pub fn skip_before(&mut self, at: DateTime<Utc>) {
+        while self.calls.front().is_some_and(|call| call.at < at) {
+            self.calls.pop_front();
+        }
+    }
+
+    /// Takes the next scenario event.
+    pub fn next_call(&mut self) -> Option<TravelCall> {
+        self.calls.pop_front()
+    }
+}
+
+fn find_city(name: &str, field: &str) -> Result<&'static City, String> {
+    City::find(name).ok_or_else(|| {
+        let known = CITIES
+            .iter()
+            .map(City::short_name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("unknown {field} {name:?} (expected one of {known})")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_the_farthest_known_city() {
+        let origin = City::find("london").unwrap();
+        let farthest = CITIES
+            .iter()
+            .max_by(|a, b| origin.distance_km(a).total_cmp(&origin.distance_km(b)))
+            .unwrap();
+        assert_eq!(farthest.name, "Sydney, NSW, Australia");
+        let km = origin.distance_km(farthest);
+        assert!((16_900.0..17_100.0).contains(&km), "{km}");
+        assert_eq!(City::find("New York, NY, United States"), Some(&CITIES[0]));
+        assert!(City::find("Atlantis").is_none());
+    }
+}
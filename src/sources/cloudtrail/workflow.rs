@@ -0,0 +1,377 @@
+//! Service-to-service call graphs for CloudTrail service actors.
+//!
+//! With `service_call_graph` enabled, a service actor runs causally linked
+//! workflows instead of picking each event independently. A workflow starts by
+//! assuming the actor's workload role; the downstream calls then run under that
+//! assumed-role session, so they share its access key, principal, and creation
+//! time and name the role in `userIdentity.sessionContext.sessionIssuer`.
+//!
+//! Profiles configured with web identity federation (EKS IRSA or GitHub OIDC)
+//! obtain the session through `AssumeRoleWithWebIdentity` instead, and the
+//! downstream calls also carry `webIdFederationData`.
+
+use super::generator::stable_hash;
+use super::model::{CloudTrailEvent, SessionIssuer, WebIdFederationData};
+use super::templates::ActorContext;
+use crate::core::actors::ServiceProfile;
+use crate::core::config::{WebIdentityConfig, WebIdentityProvider};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::Rng;
+use serde_json::json;
+
+/// Lifetime of assumed-role credentials (the STS default of one hour).
+const SESSION_DURATION_SECONDS: i64 = 3600;
+
+/// Workflow steps per profile, each with the chance that it runs in one pass.
+fn steps_for(profile: &ServiceProfile) -> &'static [(&'static str, f64)] {
+    match profile {
+        ServiceProfile::Generic => &[
+            ("AssumeRole", 1.0),
+            ("GetCallerIdentity", 0.4),
+            ("GetObject", 0.9),
+            ("PutObject", 0.7),
+            ("PutLogEvents", 0.9),
+        ],
+        ServiceProfile::Ec2Reaper => &[
+            ("AssumeRole", 1.0),
+            ("DescribeInstances", 1.0),
+            ("StopInstances", 0.5),
+            ("TerminateInstances", 0.6),
+            ("PutLogEvents", 0.5),
+        ],
+        ServiceProfile::DataLakeBot => &[
+            ("AssumeRole", 1.0),
+            ("GenerateDataKey", 0.9),
+            ("PutObject", 1.0),
+            ("GetObject", 0.7),
+            ("Decrypt", 0.7),
+            ("PutLogEvents", 0.6),
+        ],
+        ServiceProfile::LogsShipper => &[
+            ("AssumeRole", 1.0),
+            ("DescribeLogStreams", 0.8),
+            ("CreateLogStream", 0.4),
+            ("PutLogEvents", 1.0),
+        ],
+        ServiceProfile::MetricsCollector => &[
+            ("AssumeRole", 1.0),
+            ("ListMetrics", 0.6),
+            ("GetMetricData", 1.0),
+            ("PutMetricData", 0.9),
+            ("PutLogEvents", 0.4),
+        ],
+    }
+}
+
+fn role_prefix(profile: &ServiceProfile) -> &'static str {
+    match profile {
+        ServiceProfile::Generic => "workload-role",
+        ServiceProfile::Ec2Reaper => "ec2-reaper-role",
+        ServiceProfile::DataLakeBot => "datalake-ingest-role",
+        ServiceProfile::LogsShipper => "logs-shipper-role",
+        ServiceProfile::MetricsCollector => "metrics-collector-role",
+    }
+}
+
+fn service_account_namespace(profile: &ServiceProfile) -> &'static str {
+    match profile {
+        ServiceProfile::Generic => "default",
+        ServiceProfile::Ec2Reaper => "ops",
+        ServiceProfile::DataLakeBot => "data",
+        ServiceProfile::LogsShipper => "logging",
+        ServiceProfile::MetricsCollector => "monitoring",
+    }
+}
+
+/// OIDC identity a service profile federates through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebIdentity {
+    pub provider: WebIdentityProvider,
+    /// `arn:aws:iam::<account>:oidc-provider/<issuer>`.
+    pub provider_arn: String,
+    pub subject: String,
+    pub audience: String,
+}
+
+impl WebIdentity {
+    pub fn new(config: &WebIdentityConfig, account_id: &str, profile: &ServiceProfile) -> Self {
+        let workload = role_prefix(profile).trim_end_matches("-role");
+        let issuer = config
+            .issuer
+            .clone()
+            .unwrap_or_else(|| match config.provider {
+                WebIdentityProvider::EksIrsa => format!(
+                    "oidc.eks.us-east-1.amazonaws.com/id/{:016X}{:016X}",
+                    stable_hash(account_id),
+                    stable_hash(workload)
+                ),
+                WebIdentityProvider::GithubOidc => {
+                    "token.actions.githubusercontent.com".to_string()
+                }
+            });
+        let subject = config
+            .subject
+            .clone()
+            .unwrap_or_else(|| match config.provider {
+                WebIdentityProvider::EksIrsa => format!(
+                    "system:serviceaccount:{}:{workload}",
+                    service_account_namespace(profile)
+                ),
+                WebIdentityProvider::GithubOidc => {
+                    format!("repo:example-org/{workload}:ref:refs/heads/main")
+                }
+            });
+        Self {
+            provider: config.provider,
+            provider_arn: format!(
+                "arn:aws:iam::{account_id}:oidc-provider/{}",
+                issuer.trim_start_matches("https://")
+            ),
+            subject,
+            audience: "sts.amazonaws.com".to_string(),
+        }
+    }
+
+    /// Rewrites the caller of `AssumeRoleWithWebIdentity` to the token's subject.
+    pub fn apply_caller(&self, actor: &mut ActorContext) {
+        actor.identity_type = "WebIdentityUser".into();
+        actor.principal_id =
+            format!("{}:{}:{}", self.provider_arn, self.audience, self.subject).into();
+        actor.user_name = Some(self.subject.as_str().into());
+        actor.access_key_id = None;
+        actor.mfa_authenticated = false;
+        actor.identity_provider = Some(self.provider_arn.clone());
+    }
+
+    pub fn federation_data(&self) -> WebIdFederationData {
+        WebIdFederationData {
+            federated_provider: self.provider_arn.clone(),
+            attributes: Default::default(),
+        }
+    }
+}
+
+/// Credentials from one AssumeRole call, reused by downstream calls until they expire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleSession {
+    pub role_name: String,
+    pub role_arn: String,
+    pub role_id: String,
+    pub account_id: String,
+    pub session_name: String,
+    pub access_key_id: String,
+    /// AssumeRole event time, used as the session `creationDate`.
+    pub created_at: String,
+    pub expires_at: DateTime<Utc>,
+    /// Set when the session came from `AssumeRoleWithWebIdentity`.
+    pub web_identity: Option<WebIdentity>,
+}
+
+impl RoleSession {
+    /// Builds the session an actor assumes at `now`; the role is stable per actor.
+    pub fn new(
+        account_id: &str,
+        actor_key: &str,
+        profile: &ServiceProfile,
+        web_identity: Option<WebIdentity>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let role_name = format!(
+            "{}-{:04x}",
+            role_prefix(profile),
+            stable_hash(actor_key) & 0xffff
+        );
+        let role_arn = format!("arn:aws:iam::{account_id}:role/{role_name}");
+        let session_name = match web_identity.as_ref().map(|identity| identity.provider) {
+            Some(WebIdentityProvider::EksIrsa) => format!("botocore-session-{}", now.timestamp()),
+            Some(WebIdentityProvider::GithubOidc) => "GitHubActions".to_string(),
+            None => format!("{role_name}-{}", now.timestamp()),
+        };
+        Self {
+            role_id: format!("AROA{:016X}", stable_hash(&role_arn)),
+            access_key_id: format!(
+                "ASIA{:016X}",
+                stable_hash(&format!("{role_arn}/{session_name}/{}", now.timestamp()))
+            ),
+            role_name,
+            role_arn,
+            account_id: account_id.to_string(),
+            session_name,
+            created_at: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+            expires_at: now + Duration::seconds(SESSION_DURATION_SECONDS),
+            web_identity,
+        }
+    }
+
+    /// Builds a session for an existing role, e.g. one an identity provider maps to.
+    pub fn for_role(role_arn: &str, session_name: &str, now: DateTime<Utc>) -> Self {
+        let role_name = role_arn.rsplit('/').next().unwrap_or(role_arn).to_string();
+        let account_id = role_arn.split(':').nth(4).unwrap_or_default().to_string();
+        Self {
+            role_id: format!("AROA{:016X}", stable_hash(role_arn)),
+            access_key_id: format!(
+                "ASIA{:016X}",
+                stable_hash(&format!("{role_arn}/{session_name}/{}", now.timestamp()))
+            ),
+            role_name,
+            role_arn: role_arn.to_string(),
+            account_id,
+            session_name: session_name.to_string(),
+            created_at: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+            expires_at: now + Duration::seconds(SESSION_DURATION_SECONDS),
+            web_identity: None,
+        }
+    }
+
+    /// STS call that hands out this session's credentials.
+    pub fn credential_event(&self) -> &'static str {
+        credential_event(self.web_identity.is_some())
+    }
+
+    /// `arn:aws:sts::<account>:assumed-role/<role>/<session>`.
+    pub fn assumed_role_arn(&self) -> String {
+        format!(
+            "arn:aws:sts::{}:assumed-role/{}/{}",
+            self.account_id, self.role_name, self.session_name
+        )
+    }
+
+    /// `<role id>:<session name>`, the principal ID of assumed-role calls.
+    pub fn principal_id(&self) -> String {
+        format!("{}:{}", self.role_id, self.session_name)
+    }
+
+    pub fn issuer(&self) -> SessionIssuer {
+        SessionIssuer {
+            issuer_type: "Role".to_string(),
+            principal_id: self.role_id.clone(),
+            arn: self.role_arn.clone(),
+            account_id: self.account_id.clone(),
+            user_name: self.role_name.clone(),
+        }
+    }
+
+    /// Rewrites the caller identity so a downstream call runs under this session.
+    pub fn apply_to(&self, actor: &mut ActorContext) {
+        actor.identity_type = "AssumedRole".into();
+        actor.principal_id = self.principal_id().into();
+        actor.arn = self.assumed_role_arn().into();
+        actor.account_id = self.account_id.as_str().into();
+        actor.access_key_id = Some(self.access_key_id.as_str().into());
+        actor.user_name = None;
+        actor.mfa_authenticated = false;
+        actor.session_issuer = Some(self.issuer());
+        actor.session_created_at = Some(self.created_at.clone());
+        actor.web_id_federation = self.web_identity.as_ref().map(WebIdentity::federation_data);
+    }
+
+    /// Aligns a successful credential event with the credentials it hands out.
+    pub fn record_assume_role(&self, event: &mut CloudTrailEvent) {
+        event.request_parameters = Some(json!({
+            "roleArn": self.role_arn,
+            "roleSessionName": self.session_name,
+            "durationSeconds": SESSION_DURATION_SECONDS,
+        }));
+        event.response_elements = Some(json!({
+            "credentials": {
+                "accessKeyId": self.access_key_id,
+                "expiration": self.expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            },
+            "assumedRoleUser": {
+                "assumedRoleId": self.principal_id(),
+                "arn": self.assumed_role_arn(),
+            },
+        }));
+        if let (Some(identity), Some(response)) = (
+            &self.web_identity,
+            event
+                .response_elements
+                .as_mut()
+                .and_then(|value| value.as_object_mut()),
+        ) {
+            response.insert(
+                "subjectFromWebIdentityToken".to_string(),
+                json!(identity.subject),
+            );
+            response.insert("audience".to_string(), json!(identity.audience));
+            response.insert("provider".to_string(), json!(identity.provider_arn));
+        }
+    }
+}
+
+/// One pass through a service actor's workflow.
+#[derive(Debug, Clone, Default)]
+pub struct ServiceWorkflow {
+    steps: Vec<String>,
+    next: usize,
+    session: Option<RoleSession>,
+}
+
+impl ServiceWorkflow {
+    /// Plans the next pass for `profile`.
+    ///
+    /// The credential step is skipped while `session` has not expired, and
+    /// steps outside the configured event set are dropped. With `web_identity`
+    /// the credential step is `AssumeRoleWithWebIdentity`, which always runs.
+    pub fn plan(
+        profile: &ServiceProfile,
+        web_identity: bool,
+        session: Option<RoleSession>,
+        now: DateTime<Utc>,
+        allowed: impl Fn(&str) -> bool,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let session = session.filter(|session| now < session.expires_at);
+        let steps = steps_for(profile)
+            .iter()
+            .filter_map(|(name, chance)| {
+                if *name == "AssumeRole" {
+                    let step = credential_event(web_identity);
+                    return (session.is_none() && (web_identity || allowed(step)))
+                        .then(|| step.to_string());
+                }
+                (rng.gen_bool(*chance) && allowed(name)).then(|| name.to_string())
+            })
+            .collect();
+        Self {
+            steps,
+            next: 0,
+            session,
+        }
+    }
+
+    /// Returns the next step, or `None` once the pass is finished.
+    pub fn next_step(&mut self) -> Option<String> {
+        let step = self.steps.get(self.next).cloned();
+        self.next += step.is_some() as usize;
+        step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.steps.len()
+    }
+
+    /// Assumed-role session the downstream steps run under.
+    pub fn session(&self) -> Option<&RoleSession> {
+        self.session.as_ref()
+    }
+
+    /// Records the session returned by this pass's AssumeRole call.
+    pub fn start_session(&mut self, session: RoleSession) {
+        self.session = Some(session);
+    }
+
+    /// Drops the remaining steps after AssumeRole fails.
+    pub fn abort(&mut self) {
+        self.next = self.steps.len();
+        self.session = None;
+    }
+}
+
+fn credential_event(web_identity: bool) -> &'static str {
+    if web_identity {
+        "AssumeRoleWithWebIdentity"
+    } else {
+        "AssumeRole"
+    }
+}
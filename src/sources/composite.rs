@@ -1,6 +1,10 @@
 use crate::core::event::Event;
 use crate::core::traits::EventSource;
+use crate::formats::written_bytes::SharedWrittenBytes;
 use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
 
 /// Event source that merges multiple child sources by envelope timestamp.
 pub struct CompositeEventSource {
@@ -42,6 +46,162 @@ impl EventSource for CompositeEventSource {
     }
 }
 
+/// Event source that stamps a tenant onto every event from a child source.
+///
+/// The tenant goes in `envelope.tenant`; `tenant_id` is only filled in when the
+/// child left it unset, so CloudTrail keeps each `recipientAccountId` as its
+/// file key.
+///
+/// `keep_ratio` below 1.0 thins the child stream so tenants can carry different
+/// relative volumes while sharing one config.
+pub struct TenantEventSource {
+    source: Box<dyn EventSource>,
+    tenant_id: String,
+    keep_ratio: f64,
+    rng: StdRng,
+}
+
+impl TenantEventSource {
+    pub fn new(
+        source: Box<dyn EventSource>,
+        tenant_id: String,
+        keep_ratio: f64,
+        seed: Option<u64>,
+    ) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            source,
+            tenant_id,
+            keep_ratio: keep_ratio.clamp(0.0, 1.0),
+            rng,
+        }
+    }
+}
+
+impl EventSource for TenantEventSource {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            let mut event = self.source.next_event()?;
+            if self.keep_ratio < 1.0 && !self.rng.gen_bool(self.keep_ratio) {
+                continue;
+            }
+            if event.envelope.tenant_id.is_none() {
+                event.envelope.tenant_id = Some(self.tenant_id.clone());
+            }
+            event.envelope.tenant = Some(self.tenant_id.clone());
+            return Some(event);
+        }
+    }
+}
+
+/// Caps on one envelope source; see [`LimitedEventSource`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceLimits {
+    pub max_events: Option<u64>,
+    /// Record bytes the run's writers report for the source.
+    pub max_bytes: Option<u64>,
+    /// Last event time the source may emit.
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Event source that stops each capped envelope source from a child once one
+/// of its limits is reached, and ends the child once all of its sources are
+/// capped, so a chatty source stops costing generation time.
+///
+/// `max_bytes` is checked against the bytes writers report to `written`, so a
+/// source runs over it by the events still queued for its writers.
+pub struct LimitedEventSource {
+    source: Box<dyn EventSource>,
+    caps: HashMap<String, SourceCap>,
+    /// The child also emits sources without limits.
+    uncapped: bool,
+    written: SharedWrittenBytes,
+    /// Tenant the child's events are written for.
+    tenant: Option<String>,
+}
+
+struct SourceCap {
+    limits: SourceLimits,
+    events: u64,
+    reached: bool,
+}
+
+impl LimitedEventSource {
+    /// `emits` lists the envelope sources the child produces; limits for other
+    /// sources are ignored.
+    pub fn new(
+        source: Box<dyn EventSource>,
+        emits: &[String],
+        limits: &HashMap<String, SourceLimits>,
+        written: SharedWrittenBytes,
+        tenant: Option<String>,
+    ) -> Self {
+        let caps = emits
+            .iter()
+            .filter_map(|name| {
+                let cap = SourceCap {
+                    limits: *limits.get(name)?,
+                    events: 0,
+                    reached: false,
+                };
+                Some((name.clone(), cap))
+            })
+            .collect::<HashMap<_, _>>();
+        let uncapped = caps.is_empty() || emits.iter().any(|name| !caps.contains_key(name));
+        Self {
+            source,
+            caps,
+            uncapped,
+            written,
+            tenant,
+        }
+    }
+}
+
+impl SourceCap {
+    fn admits(&mut self, event: &Event, written: impl FnOnce() -> u64) -> bool {
+        if self.reached {
+            return false;
+        }
+        let past_until = self.limits.until.is_some_and(|until| {
+            parse_timestamp(&event.envelope.timestamp).is_some_and(|time| time > until)
+        });
+        if past_until
+            || self.limits.max_events.is_some_and(|max| self.events >= max)
+            || self.limits.max_bytes.is_some_and(|max| written() >= max)
+        {
+            self.reached = true;
+            return false;
+        }
+        self.events += 1;
+        true
+    }
+}
+
+impl EventSource for LimitedEventSource {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            if !self.uncapped && self.caps.values().all(|cap| cap.reached) {
+                return None;
+            }
+            let event = self.source.next_event()?;
+            let admitted = match self.caps.get_mut(&event.envelope.source) {
+                Some(cap) => cap.admits(&event, || {
+                    self.written
+                        .source(self.tenant.as_deref(), &event.envelope.source)
+                }),
+                None => true,
+            };
+            if admitted {
+                return Some(event);
+            }
+        }
+    }
+}
+
 fn compare_events(left: Option<&Event>, right: Option<&Event>) -> std::cmp::Ordering {
     let left = left.expect("left event exists");
     let right = right.expect("right event exists");
@@ -63,7 +223,11 @@ fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::event::{Actor, EventEnvelope, Outcome};
+    use crate::fixtures::{test_event, CloudTrailEventBuilder};
+    use crate::formats::json::file_key;
+    use crate::formats::partitioned::tenant_partition;
+    use crate::formats::written_bytes::WrittenBytes;
+    use crate::sources::cloudtrail::ActorContext;
     use serde_json::Value;
     use std::collections::VecDeque;
 
@@ -88,18 +252,24 @@ mod tests {
     #[test]
     fn composite_merges_sources_by_timestamp_without_rewriting_source() {
         let mut source = CompositeEventSource::new(vec![
-            Box::new(QueueSource::new(vec![event(
+            Box::new(QueueSource::new(vec![test_event(
                 "databricks_audit",
-                "2026-01-01T00:00:20Z",
-            )])),
-            Box::new(QueueSource::new(vec![event(
+                "test.event",
+                Value::Null,
+            )
+            .at("2026-01-01T00:00:20Z")])),
+            Box::new(QueueSource::new(vec![test_event(
                 "cloudtrail",
-                "2026-01-01T00:00:10Z",
-            )])),
-            Box::new(QueueSource::new(vec![event(
+                "test.event",
+                Value::Null,
+            )
+            .at("2026-01-01T00:00:10Z")])),
+            Box::new(QueueSource::new(vec![test_event(
                 "okta_system_log",
-                "2026-01-01T00:00:15Z",
-            )])),
+                "test.event",
+                Value::Null,
+            )
+            .at("2026-01-01T00:00:15Z")])),
         ]);
 
         let sources = vec![
@@ -115,27 +285,164 @@ mod tests {
         assert!(source.next_event().is_none());
     }
 
-    fn event(source: &str, timestamp: &str) -> Event {
-        Event {
-            envelope: EventEnvelope {
-                schema_version: "v1".to_string(),
-                timestamp: timestamp.to_string(),
-                source: source.to_string(),
-                event_type: "test.event".to_string(),
-                actor: Actor {
-                    id: "actor-1".to_string(),
-                    kind: "human".to_string(),
-                    name: None,
+    #[test]
+    fn tenant_source_stamps_owning_tenant() {
+        let mut source = TenantEventSource::new(
+            Box::new(QueueSource::new(vec![
+                test_event("cloudtrail", "test.event", Value::Null).at("2026-01-01T00:00:10Z"),
+                test_event("okta_system_log", "test.event", Value::Null).at("2026-01-01T00:00:11Z"),
+            ])),
+            "tenant-a".to_string(),
+            1.0,
+            Some(7),
+        );
+
+        for _ in 0..2 {
+            let event = source.next_event().expect("event");
+            assert_eq!(event.envelope.tenant.as_deref(), Some("tenant-a"));
+            assert_eq!(event.envelope.tenant_id.as_deref(), Some("tenant-a"));
+        }
+        assert!(source.next_event().is_none());
+    }
+
+    #[test]
+    fn tenant_source_keeps_each_cloudtrail_account_in_its_own_files() {
+        let cloudtrail = |account: &str| {
+            CloudTrailEventBuilder::new("GetObject")
+                .actor(ActorContext::iam_user(account, "alice"))
+                .build_event()
+                .expect("cloudtrail event")
+        };
+        let mut source = TenantEventSource::new(
+            Box::new(QueueSource::new(vec![
+                cloudtrail("111111111111"),
+                cloudtrail("222222222222"),
+            ])),
+            "tenant-b".to_string(),
+            1.0,
+            Some(7),
+        );
+
+        let first = source.next_event().expect("event");
+        let second = source.next_event().expect("event");
+        assert_eq!(file_key(&first).0, "111111111111");
+        assert_eq!(file_key(&second).0, "222222222222");
+        assert_ne!(file_key(&first), file_key(&second));
+        assert_eq!(tenant_partition(&first), tenant_partition(&second));
+        assert_eq!(
+            tenant_partition(&first),
+            std::path::PathBuf::from("tenant=tenant-b")
+        );
+    }
+
+    #[test]
+    fn limited_source_caps_each_source_and_ends_when_all_are_capped() {
+        let events = |count: usize| {
+            (0..count)
+                .flat_map(|second| {
+                    let timestamp = format!("2026-01-01T00:00:{second:02}Z");
+                    [
+                        test_event("cloudtrail", "test.event", Value::Null).at(&timestamp),
+                        test_event("okta_system_log", "test.event", Value::Null).at(&timestamp),
+                    ]
+                })
+                .collect::<Vec<_>>()
+        };
+        let limits = HashMap::from([
+            (
+                "cloudtrail".to_string(),
+                SourceLimits {
+                    max_events: Some(2),
+                    ..SourceLimits::default()
+                },
+            ),
+            (
+                "okta_system_log".to_string(),
+                SourceLimits {
+                    until: parse_timestamp("2026-01-01T00:00:03Z"),
+                    ..SourceLimits::default()
                 },
-                target: None,
-                outcome: Outcome::Success,
-                geo: None,
-                ip: None,
-                user_agent: None,
-                session_id: None,
-                tenant_id: None,
+            ),
+        ]);
+
+        let emits = ["cloudtrail".to_string(), "github_audit".to_string()];
+        let mut source = LimitedEventSource::new(
+            Box::new(QueueSource::new(events(6))),
+            &emits,
+            &limits,
+            WrittenBytes::new(),
+            None,
+        );
+        let mut counts = HashMap::<String, usize>::new();
+        while let Some(event) = source.next_event() {
+            *counts.entry(event.envelope.source).or_default() += 1;
+        }
+        // Okta is not in `emits`, so its limit does not apply to this child.
+        assert_eq!(counts["cloudtrail"], 2);
+        assert_eq!(counts["okta_system_log"], 6);
+
+        let emits = ["cloudtrail".to_string(), "okta_system_log".to_string()];
+        let mut queue = events(6);
+        queue
+            .push(test_event("github_audit", "test.event", Value::Null).at("2026-01-01T00:00:59Z"));
+        let mut source = LimitedEventSource::new(
+            Box::new(QueueSource::new(queue)),
+            &emits,
+            &limits,
+            WrittenBytes::new(),
+            None,
+        );
+        let sources = std::iter::from_fn(|| source.next_event())
+            .map(|event| event.envelope.source)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![
+                "cloudtrail",
+                "okta_system_log",
+                "cloudtrail",
+                "okta_system_log",
+                "okta_system_log",
+                "okta_system_log"
+            ]
+        );
+    }
+
+    #[test]
+    fn limited_source_caps_bytes_by_what_its_tenant_wrote() {
+        let limits = HashMap::from([(
+            "cloudtrail".to_string(),
+            SourceLimits {
+                max_bytes: Some(100),
+                ..SourceLimits::default()
             },
-            payload: Value::Null,
+        )]);
+        let queue = (0..5)
+            .map(|second| {
+                test_event("cloudtrail", "test.event", Value::Null)
+                    .at(&format!("2026-01-01T00:00:{second:02}Z"))
+            })
+            .collect();
+        let written = WrittenBytes::new();
+        let mut source = LimitedEventSource::new(
+            Box::new(QueueSource::new(queue)),
+            &["cloudtrail".to_string()],
+            &limits,
+            written.clone(),
+            Some("tenant-a".to_string()),
+        );
+
+        let other_tenant = test_event("cloudtrail", "test.event", Value::Null)
+            .at("2026-01-01T00:00:00Z")
+            .owned_by("tenant-b");
+        written.record(&other_tenant, 500);
+        let mut admitted = 0;
+        while let Some(mut event) = source.next_event() {
+            admitted += 1;
+            event.envelope.tenant = Some("tenant-a".to_string());
+            written.record(&event, 60);
         }
+        assert_eq!(admitted, 2);
+        assert_eq!(written.source(Some("tenant-a"), "cloudtrail"), 120);
     }
 }
@@ -1,7 +1,11 @@
 use super::model::{
     DatabricksAuditEvent, DatabricksIdentityMetadata, DatabricksResponse, DatabricksUserIdentity,
 };
-use crate::core::activity::{first_identity_event_at, next_identity_event_after};
+use crate::core::activity::{
+    first_identity_event_at, next_identity_event_after, LinkedSessionClock,
+};
+use crate::core::arrival::Arrivals;
+use crate::core::attack::scenario_label;
 use crate::core::config::{DatabricksAuditEventConfig, DatabricksAuditSourceConfig};
 use crate::core::event::{Actor, Event, EventEnvelope, Geo, Outcome};
 use crate::core::identity::{Identity, IdentityRegistry, IdentityRegistryError};
@@ -16,7 +20,9 @@ pub struct DatabricksAuditGenerator {
     injected_events: VecDeque<ScheduledDatabricksEvent>,
     identities: Vec<Identity>,
     schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+    arrivals: Arrivals,
     next_event_idx: Vec<usize>,
+    linked_clock: Option<LinkedSessionClock>,
 }
 
 #[derive(Debug)]
@@ -24,6 +30,8 @@ pub enum DatabricksAuditError {
     IdentityRegistry(IdentityRegistryError),
     MissingIdentity(String),
     InvalidEventTime(String),
+    InvalidTechnique(String),
+    InvalidArrival(String),
     EmptyStream,
 }
 
@@ -40,6 +48,15 @@ impl std::fmt::Display for DatabricksAuditError {
             DatabricksAuditError::InvalidEventTime(value) => {
                 write!(f, "invalid databricks audit event_time: {value}")
             }
+            DatabricksAuditError::InvalidTechnique(value) => {
+                write!(
+                    f,
+                    "invalid ATT&CK technique id on databricks audit event: {value}"
+                )
+            }
+            DatabricksAuditError::InvalidArrival(value) => {
+                write!(f, "invalid databricks audit arrival config: {value}")
+            }
             DatabricksAuditError::EmptyStream => {
                 write!(
                     f,
@@ -88,10 +105,15 @@ impl DatabricksAuditGenerator {
                 .then(left.sequence.cmp(&right.sequence))
                 .then(left.actor_id.cmp(&right.actor_id))
         });
+        let arrivals = Arrivals::from_config(config.arrival.as_ref())
+            .map_err(DatabricksAuditError::InvalidArrival)?;
+        let mut linked_clock = config.linked_sessions.then(|| {
+            LinkedSessionClock::new(identities.len(), start_time, config.session_correlation)
+        });
         let schedule = if config.baseline_events_per_actor == Some(0) {
             BinaryHeap::new()
         } else {
-            build_identity_schedule(&identities, start_time)
+            build_identity_schedule(&identities, start_time, linked_clock.as_mut(), &arrivals)
         };
         let next_event_idx = vec![0; identities.len()];
         Ok(Self {
@@ -99,7 +121,9 @@ impl DatabricksAuditGenerator {
             injected_events: scheduled.into(),
             identities: std::mem::take(&mut identities),
             schedule,
+            arrivals,
             next_event_idx,
+            linked_clock,
         })
     }
 }
@@ -131,12 +155,23 @@ impl DatabricksAuditGenerator {
             baseline_row_for_identity(&self.config, identity, actor_idx, event_idx, event_time);
         let event = event_from_row(identity, row, None);
 
-        let next_at = next_identity_event_after(
-            identity,
-            event_time,
-            self.next_event_idx[actor_idx],
-            "databricks_audit",
-        );
+        let next_at = if let Some(clock) = &mut self.linked_clock {
+            clock.next_event_at(
+                actor_idx,
+                identity,
+                self.next_event_idx[actor_idx],
+                "databricks_audit",
+                &self.arrivals,
+            )
+        } else {
+            next_identity_event_after(
+                identity,
+                event_time,
+                self.next_event_idx[actor_idx],
+                "databricks_audit",
+                &self.arrivals,
+            )
+        };
         self.schedule.push(Reverse((next_at, actor_idx)));
         Some(event)
     }
@@ -162,7 +197,13 @@ fn append_injected_events(
         let event_time = event_time_for_entry(entry, start_time)?;
         let row = audit_row_for_entry(config, identity, entry, event_time, idx);
         let geo = geo_for_entry(entry);
-        let event = event_from_row(identity, row, geo);
+        let mut event = event_from_row(identity, row, geo);
+        event.label = scenario_label(
+            entry.scenario_id.as_ref(),
+            entry.technique_id.as_ref(),
+            &entry.technique_ids,
+        )
+        .map_err(DatabricksAuditError::InvalidTechnique)?;
         scheduled.push(ScheduledDatabricksEvent {
             event_time,
             sequence: idx,
@@ -182,10 +223,16 @@ fn sorted_identities(registry: &IdentityRegistry) -> Vec<Identity> {
 fn build_identity_schedule(
     identities: &[Identity],
     start_time: DateTime<Utc>,
+    mut linked_clock: Option<&mut LinkedSessionClock>,
+    arrivals: &Arrivals,
 ) -> BinaryHeap<Reverse<(DateTime<Utc>, usize)>> {
     let mut schedule = BinaryHeap::with_capacity(identities.len());
     for (idx, identity) in identities.iter().enumerate() {
-        let first_at = first_identity_event_at(identity, start_time, "databricks_audit");
+        let first_at = if let Some(clock) = linked_clock.as_deref_mut() {
+            clock.next_event_at(idx, identity, 0, "databricks_audit", arrivals)
+        } else {
+            first_identity_event_at(identity, start_time, "databricks_audit", arrivals)
+        };
         schedule.push(Reverse((first_at, idx)));
     }
     schedule
@@ -325,8 +372,10 @@ fn event_from_row(identity: &Identity, row: DatabricksAuditEvent, geo: Option<Ge
             user_agent: Some(row.user_agent.clone()),
             session_id: Some(row.session_id.clone()),
             tenant_id: Some(row.account_id.clone()),
+            tenant: None,
         },
         payload: row.to_value(),
+        label: None,
     }
 }
 
@@ -543,6 +592,18 @@ mod tests {
 
         assert_eq!(event.payload["event_time"], "2026-01-01T00:00:10.000Z");
         assert_eq!(event.payload["source_ip_address"], "203.0.113.45");
+        assert_eq!(
+            event.label.as_ref().map(|label| label.scenario_id.as_str()),
+            Some("blocked-ip-login")
+        );
+        assert_eq!(
+            event
+                .label
+                .as_ref()
+                .map(|label| label.technique_ids.clone()),
+            Some(vec!["T1078".to_string(), "T1078.004".to_string()])
+        );
+        assert!(event.payload.get("scenario_id").is_none());
         assert_eq!(
             event
                 .payload
@@ -683,6 +744,9 @@ mod tests {
             account_id: "acc-123".to_string(),
             workspace_id: "1234567890".to_string(),
             baseline_events_per_actor: Some(2),
+            linked_sessions: false,
+            session_correlation: 1.0,
+            arrival: None,
             baseline_source_ips: Some(
                 [
                     (
@@ -722,6 +786,9 @@ mod tests {
                 source_geo_country: Some("Singapore".to_string()),
                 source_geo_region: None,
                 source_geo_city: Some("Singapore".to_string()),
+                scenario_id: Some("blocked-ip-login".to_string()),
+                technique_id: Some("T1078".to_string()),
+                technique_ids: vec!["T1078.004".to_string()],
             }],
         }
     }
@@ -782,6 +849,8 @@ mod tests {
             timezone_offset: None,
             weekend_active: None,
             service_pattern: None,
+            insider_persona: None,
+            on_call: None,
         }
     }
 }
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Entra ID (Azure AD) sign-in log entry.
+///
+/// Follows the Microsoft Graph `signIn` resource, which is also the
+/// `properties` body of `SignInLogs` diagnostic exports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraSignInRecord {
+    pub id: String,
+    pub created_date_time: String,
+    pub user_display_name: String,
+    pub user_principal_name: String,
+    /// Object ID of the user.
+    pub user_id: String,
+    /// Application (client) ID of the enterprise application.
+    pub app_id: String,
+    pub app_display_name: String,
+    pub ip_address: String,
+    /// `Browser` for interactive web sign-ins.
+    pub client_app_used: String,
+    pub user_agent: String,
+    pub correlation_id: String,
+    /// `success`, `failure`, or `notApplied`.
+    pub conditional_access_status: String,
+    pub is_interactive: bool,
+    /// Why the risk state last changed, e.g. `userPassedMFADrivenByRiskBasedPolicy`.
+    pub risk_detail: String,
+    /// User risk after the sign-in: `none`, `low`, `medium`, or `high`.
+    pub risk_level_aggregated: String,
+    pub risk_level_during_sign_in: String,
+    /// `none`, `atRisk`, or `remediated`.
+    pub risk_state: String,
+    pub resource_display_name: String,
+    pub resource_id: String,
+    pub resource_tenant_id: String,
+    pub home_tenant_id: String,
+    /// `singleFactorAuthentication` or `multiFactorAuthentication`.
+    pub authentication_requirement: String,
+    /// Second factor used, when `authenticationRequirement` needed one.
+    pub mfa_detail: Option<EntraMfaDetail>,
+    pub authentication_processing_details: Vec<EntraKeyValue>,
+    pub token_issuer_type: String,
+    pub status: EntraSignInStatus,
+    pub device_detail: EntraDeviceDetail,
+    pub location: EntraLocation,
+    pub applied_conditional_access_policies: Vec<EntraConditionalAccessPolicy>,
+}
+
+impl EntraSignInRecord {
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraSignInStatus {
+    /// `0` on success, otherwise an `AADSTS` error code.
+    pub error_code: u32,
+    pub failure_reason: Option<String>,
+    pub additional_details: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraDeviceDetail {
+    pub device_id: String,
+    pub display_name: String,
+    pub operating_system: String,
+    pub browser: String,
+    pub is_compliant: bool,
+    pub is_managed: bool,
+    /// `Azure AD joined`, `Hybrid Azure AD joined`, `Azure AD registered`, or
+    /// empty for unregistered devices.
+    pub trust_type: String,
+}
+
+/// Conditional access policy evaluated for a sign-in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraConditionalAccessPolicy {
+    pub id: String,
+    pub display_name: String,
+    /// e.g. `Mfa`.
+    pub enforced_grant_controls: Vec<String>,
+    pub enforced_session_controls: Vec<String>,
+    /// `success` or `failure`.
+    pub result: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraMfaDetail {
+    /// e.g. `Mobile app notification` or `FIDO2 security key`.
+    pub auth_method: String,
+    /// Masked phone number for phone methods.
+    pub auth_detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntraKeyValue {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraLocation {
+    pub city: Option<String>,
+    pub state: Option<String>,
+    /// Two-letter country code, as Entra reports it.
+    pub country_or_region: Option<String>,
+}
+
+/// Entra ID (Azure AD) directory audit log entry.
+///
+/// Follows the Microsoft Graph `directoryAudit` resource, which is also the
+/// `properties` body of `AuditLogs` diagnostic exports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraAuditRecord {
+    pub id: String,
+    /// e.g. `ApplicationManagement` or `UserManagement`.
+    pub category: String,
+    pub correlation_id: String,
+    /// `success` or `failure`.
+    pub result: String,
+    pub result_reason: String,
+    pub activity_display_name: String,
+    pub activity_date_time: String,
+    pub logged_by_service: String,
+    /// `Add`, `Update`, `Delete`, or `Assign`.
+    pub operation_type: String,
+    pub initiated_by: EntraAuditInitiator,
+    pub target_resources: Vec<EntraAuditTarget>,
+    pub additional_details: Vec<EntraKeyValue>,
+}
+
+impl EntraAuditRecord {
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+/// User or application that made an audited change; one of the two is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraAuditInitiator {
+    pub user: Option<Value>,
+    pub app: Option<EntraAuditApp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraAuditApp {
+    pub app_id: String,
+    pub display_name: String,
+    pub service_principal_id: String,
+    pub service_principal_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraAuditTarget {
+    pub id: String,
+    pub display_name: String,
+    /// e.g. `Application`, `ServicePrincipal`, or `User`.
+    pub r#type: String,
+    pub user_principal_name: Option<String>,
+    pub modified_properties: Vec<EntraModifiedProperty>,
+}
+
+/// Property change on an audit target; values are JSON-encoded strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntraModifiedProperty {
+    pub display_name: String,
+    pub old_value: String,
+    pub new_value: String,
+}
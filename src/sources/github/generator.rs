@@ -0,0 +1,931 @@
+use super::model::{GitHubActorLocation, GitHubAuditEvent};
+use crate::core::activity::{
+    first_identity_event_at, next_identity_event_after, LinkedSessionClock,
+};
+use crate::core::arrival::Arrivals;
+use crate::core::attack::scenario_label;
+use crate::core::config::{GitHubAuditEventConfig, GitHubAuditSourceConfig};
+use crate::core::event::{Actor, Event, EventEnvelope, Geo, Outcome, Target};
+use crate::core::identity::{Identity, IdentityRegistry, IdentityRegistryError};
+use crate::core::traits::EventSource;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::{json, Map, Number, Value};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use toml::Value as TomlValue;
+
+const DEFAULT_REPOSITORIES: &[&str] = &[
+    "platform-api",
+    "web-app",
+    "data-pipelines",
+    "infra-terraform",
+    "docs",
+];
+const GIT_USER_AGENT: &str = "git/2.45.2";
+const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_6) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+const ACTIONS_USER_AGENT: &str = "GitHub-Actions";
+
+/// GitHub Enterprise audit log generator backed by a shared identity registry.
+///
+/// Registry identities are mapped to GitHub logins derived from their email
+/// local part (`jane.doe@example.com` becomes `jane-doe`); service accounts
+/// act as GitHub Apps (`<login>[bot]`). The SAML NameID carries the email so
+/// entries join back to the other sources.
+pub struct GitHubAuditGenerator {
+    config: GitHubAuditSourceConfig,
+    injected_events: VecDeque<ScheduledGitHubEvent>,
+    identities: Vec<Identity>,
+    schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+    arrivals: Arrivals,
+    next_event_idx: Vec<usize>,
+    linked_clock: Option<LinkedSessionClock>,
+}
+
+#[derive(Debug)]
+pub enum GitHubAuditError {
+    IdentityRegistry(IdentityRegistryError),
+    MissingIdentity(String),
+    InvalidEventTime(String),
+    InvalidTechnique(String),
+    InvalidArrival(String),
+    EmptyStream,
+}
+
+impl std::fmt::Display for GitHubAuditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GitHubAuditError::IdentityRegistry(err) => write!(f, "{err}"),
+            GitHubAuditError::MissingIdentity(actor_id) => {
+                write!(
+                    f,
+                    "github audit event references unknown actor_id: {actor_id}"
+                )
+            }
+            GitHubAuditError::InvalidEventTime(value) => {
+                write!(f, "invalid github audit created_at: {value}")
+            }
+            GitHubAuditError::InvalidTechnique(value) => {
+                write!(
+                    f,
+                    "invalid ATT&CK technique id on github audit event: {value}"
+                )
+            }
+            GitHubAuditError::InvalidArrival(value) => {
+                write!(f, "invalid github audit arrival config: {value}")
+            }
+            GitHubAuditError::EmptyStream => {
+                write!(
+                    f,
+                    "github audit source needs identity registry actors or event entries"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GitHubAuditError {}
+
+impl From<IdentityRegistryError> for GitHubAuditError {
+    fn from(err: IdentityRegistryError) -> Self {
+        GitHubAuditError::IdentityRegistry(err)
+    }
+}
+
+impl GitHubAuditGenerator {
+    pub fn from_config(
+        config: &GitHubAuditSourceConfig,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, GitHubAuditError> {
+        let registry = IdentityRegistry::from_path(&config.identity_registry_path)?;
+        Self::from_registry(config, registry, start_time)
+    }
+
+    pub fn from_registry(
+        config: &GitHubAuditSourceConfig,
+        registry: IdentityRegistry,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, GitHubAuditError> {
+        let mut scheduled = Vec::new();
+        append_injected_events(config, &registry, start_time, &mut scheduled)?;
+        let identities = sorted_identities(&registry);
+
+        if scheduled.is_empty()
+            && (config.baseline_events_per_actor == Some(0) || identities.is_empty())
+        {
+            return Err(GitHubAuditError::EmptyStream);
+        }
+
+        scheduled.sort_by(|left, right| {
+            left.event_time
+                .cmp(&right.event_time)
+                .then(left.sequence.cmp(&right.sequence))
+        });
+        let arrivals = Arrivals::from_config(config.arrival.as_ref())
+            .map_err(GitHubAuditError::InvalidArrival)?;
+        let mut linked_clock = config.linked_sessions.then(|| {
+            LinkedSessionClock::new(identities.len(), start_time, config.session_correlation)
+        });
+        let schedule = if config.baseline_events_per_actor == Some(0) {
+            BinaryHeap::new()
+        } else {
+            build_identity_schedule(&identities, start_time, linked_clock.as_mut(), &arrivals)
+        };
+        let next_event_idx = vec![0; identities.len()];
+        Ok(Self {
+            config: config.clone(),
+            injected_events: scheduled.into(),
+            identities,
+            schedule,
+            arrivals,
+            next_event_idx,
+            linked_clock,
+        })
+    }
+}
+
+impl EventSource for GitHubAuditGenerator {
+    fn next_event(&mut self) -> Option<Event> {
+        let injected_time = self.injected_events.front().map(|event| event.event_time);
+        let scheduled_time = self.schedule.peek().map(|Reverse((time, _))| *time);
+
+        match (injected_time, scheduled_time) {
+            (None, None) => None,
+            (Some(_), None) => self.injected_events.pop_front().map(|item| item.event),
+            (Some(injected), Some(scheduled)) if injected <= scheduled => {
+                self.injected_events.pop_front().map(|item| item.event)
+            }
+            _ => self.next_scheduled_event(),
+        }
+    }
+}
+
+impl GitHubAuditGenerator {
+    fn next_scheduled_event(&mut self) -> Option<Event> {
+        let Reverse((event_time, actor_idx)) = self.schedule.pop()?;
+        let event_idx = self.next_event_idx[actor_idx];
+        self.next_event_idx[actor_idx] += 1;
+
+        let identity = &self.identities[actor_idx];
+        let action = baseline_action(&self.config, &self.identities, actor_idx, event_idx);
+        let actor_ip = baseline_source_ip(&self.config, identity, actor_idx, event_idx);
+        let sequence = actor_idx * 1000 + event_idx;
+        let row = audit_row(
+            &self.config,
+            identity,
+            action,
+            actor_ip,
+            event_time,
+            sequence,
+        );
+        let event = event_from_row(identity, row, None);
+
+        let next_at = if let Some(clock) = &mut self.linked_clock {
+            clock.next_event_at(
+                actor_idx,
+                identity,
+                self.next_event_idx[actor_idx],
+                "github_audit",
+                &self.arrivals,
+            )
+        } else {
+            next_identity_event_after(
+                identity,
+                event_time,
+                self.next_event_idx[actor_idx],
+                "github_audit",
+                &self.arrivals,
+            )
+        };
+        self.schedule.push(Reverse((next_at, actor_idx)));
+        Some(event)
+    }
+}
+
+struct ScheduledGitHubEvent {
+    event_time: DateTime<Utc>,
+    sequence: usize,
+    event: Event,
+}
+
+/// One audit action before the common entry fields are filled in.
+struct AuditAction {
+    action: String,
+    operation_type: &'static str,
+    repo: Option<String>,
+    user: Option<String>,
+    user_agent: String,
+    country_code: Option<String>,
+    fields: Map<String, Value>,
+}
+
+fn append_injected_events(
+    config: &GitHubAuditSourceConfig,
+    registry: &IdentityRegistry,
+    start_time: DateTime<Utc>,
+    scheduled: &mut Vec<ScheduledGitHubEvent>,
+) -> Result<(), GitHubAuditError> {
+    for (idx, entry) in config.events.iter().enumerate() {
+        let identity = registry
+            .get(&entry.actor_id)
+            .ok_or_else(|| GitHubAuditError::MissingIdentity(entry.actor_id.clone()))?;
+        let event_time = event_time_for_entry(entry, start_time)?;
+        let action = action_for_entry(config, identity, entry);
+        let geo = action.country_code.clone().map(|country| Geo {
+            country,
+            region: None,
+            city: None,
+            lat: None,
+            lon: None,
+        });
+        let row = audit_row(
+            config,
+            identity,
+            action,
+            entry.actor_ip.clone(),
+            event_time,
+            idx,
+        );
+        let mut event = event_from_row(identity, row, geo);
+        event.label = scenario_label(
+            entry.scenario_id.as_ref(),
+            entry.technique_id.as_ref(),
+            &entry.technique_ids,
+        )
+        .map_err(GitHubAuditError::InvalidTechnique)?;
+        scheduled.push(ScheduledGitHubEvent {
+            event_time,
+            sequence: idx,
+            event,
+        });
+    }
+    Ok(())
+}
+
+fn action_for_entry(
+    config: &GitHubAuditSourceConfig,
+    identity: &Identity,
+    entry: &GitHubAuditEventConfig,
+) -> AuditAction {
+    let fields = entry
+        .fields
+        .as_ref()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|(key, value)| (key.clone(), toml_value_to_json(value)))
+                .collect()
+        })
+        .unwrap_or_default();
+    AuditAction {
+        action: entry.action.clone(),
+        operation_type: operation_type_for(&entry.action),
+        repo: entry
+            .repo
+            .as_deref()
+            .map(|repo| qualified_repo(config, repo)),
+        user: entry.user.clone(),
+        user_agent: entry
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| default_user_agent(identity, &entry.action).to_string()),
+        country_code: entry.country_code.clone(),
+        fields,
+    }
+}
+
+fn sorted_identities(registry: &IdentityRegistry) -> Vec<Identity> {
+    let mut identities: Vec<&Identity> = registry.identities().iter().collect();
+    identities.sort_by(|left, right| left.actor_id.cmp(&right.actor_id));
+    identities.into_iter().cloned().collect()
+}
+
+fn build_identity_schedule(
+    identities: &[Identity],
+    start_time: DateTime<Utc>,
+    mut linked_clock: Option<&mut LinkedSessionClock>,
+    arrivals: &Arrivals,
+) -> BinaryHeap<Reverse<(DateTime<Utc>, usize)>> {
+    let mut schedule = BinaryHeap::with_capacity(identities.len());
+    for (idx, identity) in identities.iter().enumerate() {
+        let first_at = if let Some(clock) = linked_clock.as_deref_mut() {
+            clock.next_event_at(idx, identity, 0, "github_audit", arrivals)
+        } else {
+            first_identity_event_at(identity, start_time, "github_audit", arrivals)
+        };
+        schedule.push(Reverse((first_at, idx)));
+    }
+    schedule
+}
+
+fn audit_row(
+    config: &GitHubAuditSourceConfig,
+    identity: &Identity,
+    action: AuditAction,
+    actor_ip: String,
+    event_time: DateTime<Utc>,
+    sequence: usize,
+) -> GitHubAuditEvent {
+    let millis = event_time.timestamp_millis();
+    let login = github_login(identity);
+    let user_id = action.user.as_deref().map(stable_id);
+    let repo_id = action.repo.as_deref().map(stable_id);
+    let visibility = action.repo.as_deref().map(|repo| {
+        if repo.ends_with("/docs") {
+            "internal"
+        } else {
+            "private"
+        }
+        .to_string()
+    });
+    GitHubAuditEvent {
+        timestamp: millis,
+        document_id: document_id(sequence, &identity.actor_id),
+        action: action.action,
+        actor_id: stable_id(&login),
+        actor: login,
+        actor_ip,
+        actor_location: GitHubActorLocation {
+            country_code: action
+                .country_code
+                .unwrap_or_else(|| country_code_for(identity).to_string()),
+        },
+        business_id: config.enterprise.as_deref().map(stable_id),
+        business: config.enterprise.clone(),
+        created_at: millis,
+        operation_type: action.operation_type.to_string(),
+        org: config.org.clone(),
+        org_id: stable_id(&config.org),
+        repo: action.repo,
+        repo_id,
+        visibility,
+        user: action.user,
+        user_id,
+        user_agent: action.user_agent,
+        request_id: request_id(sequence, &identity.actor_id),
+        external_identity_nameid: (!identity.service_account).then(|| identity.email.clone()),
+        fields: action.fields,
+    }
+}
+
+fn event_from_row(identity: &Identity, row: GitHubAuditEvent, geo: Option<Geo>) -> Event {
+    let outcome = if row.fields.get("conclusion").and_then(Value::as_str) == Some("failure") {
+        Outcome::Failure
+    } else {
+        Outcome::Success
+    };
+    let actor_kind = if identity.service_account {
+        "service"
+    } else {
+        "human"
+    };
+    let target = row
+        .repo
+        .as_ref()
+        .map(|repo| Target {
+            id: repo.clone(),
+            kind: "repository".to_string(),
+            name: None,
+        })
+        .or_else(|| {
+            row.user.as_ref().map(|user| Target {
+                id: user.clone(),
+                kind: "user".to_string(),
+                name: None,
+            })
+        });
+    let timestamp = DateTime::from_timestamp_millis(row.timestamp)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Millis, true);
+
+    Event {
+        envelope: EventEnvelope {
+            schema_version: "v1".to_string(),
+            timestamp,
+            source: "github_audit".to_string(),
+            event_type: row.action.clone(),
+            actor: Actor {
+                id: identity.actor_id.clone(),
+                kind: actor_kind.to_string(),
+                name: Some(identity.display_name.clone()),
+            },
+            target,
+            outcome,
+            geo,
+            ip: Some(row.actor_ip.clone()),
+            user_agent: Some(row.user_agent.clone()),
+            session_id: None,
+            tenant_id: Some(row.org.clone()),
+            tenant: None,
+        },
+        payload: row.to_value(),
+        label: None,
+    }
+}
+
+fn event_time_for_entry(
+    entry: &GitHubAuditEventConfig,
+    start_time: DateTime<Utc>,
+) -> Result<DateTime<Utc>, GitHubAuditError> {
+    if let Some(raw) = &entry.created_at {
+        let parsed = DateTime::parse_from_rfc3339(raw)
+            .map_err(|_| GitHubAuditError::InvalidEventTime(raw.clone()))?;
+        return Ok(parsed.with_timezone(&Utc));
+    }
+    Ok(start_time + Duration::seconds(entry.offset_seconds.unwrap_or(0)))
+}
+
+/// Picks a baseline action: developers clone, push, and review; administrators
+/// occasionally manage membership and branch protection; service accounts run
+/// workflows.
+fn baseline_action(
+    config: &GitHubAuditSourceConfig,
+    identities: &[Identity],
+    actor_idx: usize,
+    event_idx: usize,
+) -> AuditAction {
+    let identity = &identities[actor_idx];
+    let repositories = config
+        .repositories
+        .as_ref()
+        .filter(|repos| !repos.is_empty())
+        .map(|repos| repos.iter().map(String::as_str).collect::<Vec<_>>())
+        .unwrap_or_else(|| DEFAULT_REPOSITORIES.to_vec());
+    let roll = stable_hash(&format!("{}:{event_idx}", identity.actor_id));
+    let repo = qualified_repo(
+        config,
+        repositories[(roll / 100) as usize % repositories.len()],
+    );
+    let bucket = roll % 100;
+    let mut fields = Map::new();
+
+    let (action, repo, user) = if identity.service_account {
+        let (workflow, branch, trigger) = if bucket < 40 {
+            ("Nightly", "main", "schedule")
+        } else {
+            ("Deploy", "main", "push")
+        };
+        match bucket {
+            0..=54 => {
+                workflow_fields(&mut fields, &repo, workflow, branch, trigger, roll);
+                let conclusion = if bucket >= 50 { "failure" } else { "success" };
+                fields.insert("conclusion".to_string(), json!(conclusion));
+                ("workflows.completed_workflow_run", Some(repo), None)
+            }
+            55..=79 => {
+                workflow_fields(&mut fields, &repo, workflow, branch, trigger, roll);
+                ("workflows.created_workflow_run", Some(repo), None)
+            }
+            _ => {
+                git_fields(&mut fields, &repo);
+                ("git.clone", Some(repo), None)
+            }
+        }
+    } else {
+        let admin = identity.role_persona.contains("administrator");
+        match bucket {
+            0..=29 => {
+                git_fields(&mut fields, &repo);
+                ("git.clone", Some(repo), None)
+            }
+            30..=44 => {
+                git_fields(&mut fields, &repo);
+                ("git.push", Some(repo), None)
+            }
+            45..=59 => {
+                pull_request_fields(&mut fields, &repo, roll);
+                ("pull_request.create", Some(repo), None)
+            }
+            60..=71 => {
+                pull_request_fields(&mut fields, &repo, roll);
+                fields.insert("review_id".to_string(), json!(roll % 900_000_000 + 1));
+                ("pull_request_review.submit", Some(repo), None)
+            }
+            72..=79 => {
+                pull_request_fields(&mut fields, &repo, roll);
+                ("pull_request.merge", Some(repo), None)
+            }
+            80..=94 => {
+                let branch = format!("feature/{:04x}", (roll >> 20) & 0xffff);
+                workflow_fields(&mut fields, &repo, "CI", &branch, "push", roll);
+                ("workflows.created_workflow_run", Some(repo), None)
+            }
+            95..=97 => {
+                let name = format!("{}-{}", repositories[0], roll % 1000);
+                ("repo.create", Some(qualified_repo(config, &name)), None)
+            }
+            _ if admin && bucket == 98 => {
+                let member = &identities[(actor_idx + event_idx + 1) % identities.len()];
+                fields.insert("permission".to_string(), json!("read"));
+                ("org.add_member", None, Some(github_login(member)))
+            }
+            _ if admin => {
+                fields.insert("branch".to_string(), json!("refs/heads/main"));
+                fields.insert("name".to_string(), json!("main"));
+                fields.insert(
+                    "overridden_codes".to_string(),
+                    json!(["required_status_checks"]),
+                );
+                ("protected_branch.policy_override", Some(repo), None)
+            }
+            _ => ("repo.download_zip", Some(repo), None),
+        }
+    };
+    AuditAction {
+        operation_type: operation_type_for(action),
+        user_agent: default_user_agent(identity, action).to_string(),
+        action: action.to_string(),
+        repo,
+        user,
+        country_code: None,
+        fields,
+    }
+}
+
+fn git_fields(fields: &mut Map<String, Value>, repo: &str) {
+    fields.insert("repository".to_string(), json!(repo));
+    fields.insert("repository_public".to_string(), json!(false));
+    fields.insert("transport_protocol".to_string(), json!(1));
+    fields.insert("transport_protocol_name".to_string(), json!("http"));
+}
+
+fn pull_request_fields(fields: &mut Map<String, Value>, repo: &str, roll: u64) {
+    let number = roll % 5000 + 1;
+    fields.insert("pull_request_id".to_string(), json!(roll % 2_000_000_000));
+    fields.insert(
+        "pull_request_url".to_string(),
+        json!(format!("https://github.com/{repo}/pull/{number}")),
+    );
+}
+
+fn workflow_fields(
+    fields: &mut Map<String, Value>,
+    repo: &str,
+    name: &str,
+    branch: &str,
+    trigger: &str,
+    roll: u64,
+) {
+    fields.insert(
+        "workflow_id".to_string(),
+        json!(stable_id(&format!("{repo}:{name}"))),
+    );
+    fields.insert(
+        "workflow_run_id".to_string(),
+        json!(9_000_000_000 + roll % 1_000_000_000),
+    );
+    fields.insert("name".to_string(), json!(name));
+    fields.insert("head_branch".to_string(), json!(branch));
+    fields.insert(
+        "head_sha".to_string(),
+        json!(format!(
+            "{:016x}{:016x}{:08x}",
+            roll,
+            stable_hash(repo),
+            roll as u32 ^ 0x5bd1_e995
+        )),
+    );
+    fields.insert("event".to_string(), json!(trigger));
+    fields.insert("run_attempt".to_string(), json!(1));
+}
+
+fn operation_type_for(action: &str) -> &'static str {
+    let verb = action.rsplit('.').next().unwrap_or(action);
+    if action.starts_with("git.") || verb == "download_zip" {
+        "access"
+    } else if verb.starts_with("create") || verb == "add_member" || verb == "submit" {
+        "create"
+    } else if verb.starts_with("remove") || verb.starts_with("destroy") || verb == "delete" {
+        "remove"
+    } else {
+        "modify"
+    }
+}
+
+fn default_user_agent(identity: &Identity, action: &str) -> &'static str {
+    if action.starts_with("workflows.") || identity.service_account {
+        ACTIONS_USER_AGENT
+    } else if action.starts_with("git.") {
+        GIT_USER_AGENT
+    } else {
+        BROWSER_USER_AGENT
+    }
+}
+
+fn qualified_repo(config: &GitHubAuditSourceConfig, repo: &str) -> String {
+    if repo.contains('/') {
+        repo.to_string()
+    } else {
+        format!("{}/{repo}", config.org)
+    }
+}
+
+/// GitHub login for a registry identity.
+pub fn github_login(identity: &Identity) -> String {
+    let local = identity
+        .email
+        .split_once('@')
+        .map(|(local, _)| local)
+        .unwrap_or(&identity.actor_id);
+    let mut login = String::with_capacity(local.len());
+    for ch in local.chars() {
+        if ch.is_ascii_alphanumeric() {
+            login.push(ch.to_ascii_lowercase());
+        } else if !login.is_empty() && !login.ends_with('-') {
+            login.push('-');
+        }
+    }
+    let login = login.trim_end_matches('-');
+    if identity.service_account {
+        format!("{login}[bot]")
+    } else {
+        login.to_string()
+    }
+}
+
+fn country_code_for(identity: &Identity) -> &'static str {
+    if identity.service_account {
+        return "US";
+    }
+    let region = identity
+        .normal_countries_regions
+        .first()
+        .map(|region| region.to_ascii_lowercase())
+        .unwrap_or_default();
+    [
+        ("australia", "AU"),
+        ("singapore", "SG"),
+        ("japan", "JP"),
+        ("india", "IN"),
+        ("united kingdom", "GB"),
+        ("ireland", "IE"),
+        ("germany", "DE"),
+        ("france", "FR"),
+        ("netherlands", "NL"),
+        ("canada", "CA"),
+    ]
+    .into_iter()
+    .find(|(name, _)| region.contains(name))
+    .map_or("US", |(_, code)| code)
+}
+
+fn baseline_source_ip(
+    config: &GitHubAuditSourceConfig,
+    identity: &Identity,
+    actor_idx: usize,
+    event_idx: usize,
+) -> String {
+    if let Some(source_ips) = config
+        .baseline_source_ips
+        .as_ref()
+        .and_then(|by_actor| by_actor.get(&identity.actor_id))
+    {
+        if !source_ips.is_empty() {
+            return source_ips[event_idx % source_ips.len()].clone();
+        }
+    }
+    let second = 48 + ((actor_idx / 240) % 16);
+    let fourth = 10 + (actor_idx % 240);
+    format!("10.{}.0.{}", second, fourth)
+}
+
+fn document_id(sequence: usize, actor_id: &str) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut state = stable_hash(&format!("{sequence}:{actor_id}"));
+    (0..22)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ALPHABET[(state >> 58) as usize] as char
+        })
+        .collect()
+}
+
+fn request_id(sequence: usize, actor_id: &str) -> String {
+    let hash = stable_hash(&format!("request:{sequence}:{actor_id}"));
+    format!(
+        "{:04X}:{:04X}:{:06X}:{:06X}:{:08X}",
+        hash & 0xffff,
+        (hash >> 16) & 0xffff,
+        (hash >> 32) & 0xff_ffff,
+        sequence & 0xff_ffff,
+        (hash >> 8) as u32
+    )
+}
+
+/// Stable numeric ID in GitHub's database ID range.
+fn stable_id(value: &str) -> u64 {
+    1_000_000 + stable_hash(value) % 150_000_000
+}
+
+fn stable_hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn toml_value_to_json(value: &TomlValue) -> Value {
+    match value {
+        TomlValue::String(value) => Value::String(value.clone()),
+        TomlValue::Integer(value) => Value::Number(Number::from(*value)),
+        TomlValue::Float(value) => Number::from_f64(*value)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        TomlValue::Boolean(value) => Value::Bool(*value),
+        TomlValue::Datetime(value) => Value::String(value.to_string()),
+        TomlValue::Array(values) => Value::Array(values.iter().map(toml_value_to_json).collect()),
+        TomlValue::Table(values) => Value::Object(
+            values
+                .iter()
+                .map(|(key, value)| (key.clone(), toml_value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn github_audit_entries_follow_streaming_shape() {
+        let config = test_config();
+        let collect = || {
+            let mut generator =
+                GitHubAuditGenerator::from_registry(&config, test_registry(), test_start_time())
+                    .unwrap();
+            (0..200)
+                .map_while(|_| generator.next_event())
+                .collect::<Vec<_>>()
+        };
+        let events = collect();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event.payload.clone())
+                .collect::<Vec<_>>(),
+            collect()
+                .into_iter()
+                .map(|event| event.payload)
+                .collect::<Vec<_>>()
+        );
+
+        let injected = events
+            .iter()
+            .find(|event| event.envelope.event_type == "protected_branch.policy_override")
+            .unwrap();
+        assert_eq!(injected.envelope.timestamp, "2026-01-01T00:00:10.000Z");
+        assert_eq!(injected.envelope.tenant_id.as_deref(), Some("example-org"));
+        assert_eq!(injected.payload["@timestamp"], 1_767_225_610_000_i64);
+        assert_eq!(injected.payload["actor"], "primary-user");
+        assert_eq!(
+            injected.payload["external_identity_nameid"],
+            "primary.user@example.com"
+        );
+        assert_eq!(injected.payload["repo"], "example-org/infra");
+        assert_eq!(injected.payload["branch"], "refs/heads/main");
+        assert_eq!(injected.payload["actor_location"]["country_code"], "SG");
+        assert_eq!(injected.payload["operation_type"], "modify");
+        assert_eq!(
+            injected.payload["_document_id"].as_str().map(str::len),
+            Some(22)
+        );
+        assert_eq!(
+            injected
+                .label
+                .as_ref()
+                .map(|label| label.scenario_id.as_str()),
+            Some("branch-protection-bypass")
+        );
+
+        for event in &events {
+            let action = event.payload["action"].as_str().unwrap();
+            if event.envelope.actor.id == "svc-ci" {
+                assert_eq!(event.payload["actor"], "svc-ci[bot]");
+                assert!(event.payload.get("external_identity_nameid").is_none());
+                assert!(action.starts_with("workflows.") || action == "git.clone");
+            } else {
+                assert!(action != "workflows.completed_workflow_run");
+            }
+            if action == "org.add_member" {
+                assert_eq!(event.envelope.actor.id, "user-admin");
+                assert!(event.payload["user"].is_string());
+            }
+            if action.starts_with("git.") {
+                assert_eq!(event.payload["transport_protocol_name"], "http");
+            }
+        }
+        assert!(events
+            .iter()
+            .any(|event| event.payload["action"] == "pull_request.create"));
+    }
+
+    #[test]
+    fn failed_workflow_runs_are_failures() {
+        let mut config = test_config();
+        config.baseline_events_per_actor = Some(0);
+        config.events[0].action = "workflows.completed_workflow_run".to_string();
+        config.events[0].fields = Some(BTreeMap::from([(
+            "conclusion".to_string(),
+            TomlValue::String("failure".to_string()),
+        )]));
+        let mut generator =
+            GitHubAuditGenerator::from_registry(&config, test_registry(), test_start_time())
+                .unwrap();
+        let event = generator.next_event().unwrap();
+        assert!(matches!(event.envelope.outcome, Outcome::Failure));
+        assert_eq!(
+            event.envelope.user_agent.as_deref(),
+            Some(ACTIONS_USER_AGENT)
+        );
+        assert!(generator.next_event().is_none());
+    }
+
+    fn test_start_time() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn test_config() -> GitHubAuditSourceConfig {
+        GitHubAuditSourceConfig {
+            identity_registry_path: String::new(),
+            enterprise: Some("example-enterprise".to_string()),
+            org: "example-org".to_string(),
+            repositories: Some(vec!["infra".to_string(), "web".to_string()]),
+            baseline_events_per_actor: None,
+            baseline_source_ips: None,
+            linked_sessions: false,
+            session_correlation: 1.0,
+            arrival: None,
+            events: vec![GitHubAuditEventConfig {
+                actor_id: "user-primary".to_string(),
+                offset_seconds: Some(10),
+                created_at: None,
+                action: "protected_branch.policy_override".to_string(),
+                actor_ip: "203.0.113.45".to_string(),
+                repo: Some("infra".to_string()),
+                user: None,
+                user_agent: None,
+                country_code: Some("SG".to_string()),
+                fields: Some(BTreeMap::from([(
+                    "branch".to_string(),
+                    TomlValue::String("refs/heads/main".to_string()),
+                )])),
+                scenario_id: Some("branch-protection-bypass".to_string()),
+                technique_id: None,
+                technique_ids: Vec::new(),
+            }],
+        }
+    }
+
+    fn test_registry() -> IdentityRegistry {
+        IdentityRegistry::new(
+            "test",
+            vec![
+                identity(
+                    "user-primary",
+                    "primary.user@example.com",
+                    "Developer",
+                    false,
+                ),
+                identity(
+                    "user-admin",
+                    "admin@example.com",
+                    "Cloud platform administrator",
+                    false,
+                ),
+                identity("svc-ci", "svc.ci@example.com", "Automation", true),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn identity(actor_id: &str, email: &str, persona: &str, service_account: bool) -> Identity {
+        Identity {
+            actor_id: actor_id.to_string(),
+            email: email.to_string(),
+            employee_id: format!("E-{actor_id}"),
+            display_name: actor_id.to_string(),
+            role_persona: persona.to_string(),
+            department: "Test department".to_string(),
+            home_location: "Test location".to_string(),
+            normal_countries_regions: vec!["Australia".to_string()],
+            okta_user_id: format!("okta-{actor_id}"),
+            databricks_username: email.to_string(),
+            aws_principals: Vec::new(),
+            service_account,
+            tags: Vec::new(),
+            rate_per_hour: Some(20.0),
+            active_start_hour: None,
+            active_hours: None,
+            timezone_offset: None,
+            weekend_active: None,
+            service_pattern: None,
+            insider_persona: None,
+            on_call: None,
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// GitHub Enterprise audit log entry as delivered by audit log streaming.
+///
+/// Action-specific fields (`workflow_run_id`, `branch`, `transport_protocol_name`,
+/// ...) sit at the top level alongside the common fields, so they are flattened
+/// in from `fields`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitHubAuditEvent {
+    /// Milliseconds since the Unix epoch.
+    #[serde(rename = "@timestamp")]
+    pub timestamp: i64,
+    #[serde(rename = "_document_id")]
+    pub document_id: String,
+    pub action: String,
+    pub actor: String,
+    pub actor_id: u64,
+    pub actor_ip: String,
+    pub actor_location: GitHubActorLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_id: Option<u64>,
+    /// Milliseconds since the Unix epoch.
+    pub created_at: i64,
+    pub operation_type: String,
+    pub org: String,
+    pub org_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<u64>,
+    pub user_agent: String,
+    pub request_id: String,
+    /// SAML NameID of the actor when the org enforces SSO.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_identity_nameid: Option<String>,
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl GitHubAuditEvent {
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHubActorLocation {
+    pub country_code: String,
+}
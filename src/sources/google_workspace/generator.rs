@@ -0,0 +1,854 @@
+use super::model::{
+    WorkspaceActivity, WorkspaceActivityId, WorkspaceActor, WorkspaceApplicationInfo,
+    WorkspaceEvent, WorkspaceParameter,
+};
+use crate::core::activity::{
+    first_identity_event_at, next_identity_event_after, LinkedSessionClock,
+};
+use crate::core::arrival::Arrivals;
+use crate::core::attack::scenario_label;
+use crate::core::config::{GoogleWorkspaceEventConfig, GoogleWorkspaceSourceConfig};
+use crate::core::event::{Actor, Event, EventEnvelope, Outcome, Target};
+use crate::core::identity::{Identity, IdentityRegistry, IdentityRegistryError};
+use crate::core::traits::EventSource;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use toml::Value as TomlValue;
+
+const ACTIVITY_KIND: &str = "admin#reports#activity";
+const DOCUMENTS: &[(&str, &str)] = &[
+    ("Q3 Planning", "document"),
+    ("Customer Accounts", "spreadsheet"),
+    ("Product Roadmap", "presentation"),
+    ("Incident Postmortem", "document"),
+    ("Payroll Export", "spreadsheet"),
+    ("Architecture Notes", "document"),
+    ("Board Update", "presentation"),
+    ("Vendor Contract", "pdf"),
+];
+const SYNC_APP_NAME: &str = "seclog-drive-sync";
+
+/// Google Workspace activity generator backed by a shared identity registry.
+///
+/// Registry identities are Workspace users under their registry email, so
+/// activities join to the other sources on email. Humans sign in and work in
+/// Drive; administrators also manage users and roles. Service accounts read
+/// and write Drive through an OAuth client, recorded in `actor.applicationInfo`.
+pub struct GoogleWorkspaceGenerator {
+    config: GoogleWorkspaceSourceConfig,
+    injected_events: VecDeque<ScheduledWorkspaceEvent>,
+    identities: Vec<Identity>,
+    schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+    arrivals: Arrivals,
+    next_event_idx: Vec<usize>,
+    linked_clock: Option<LinkedSessionClock>,
+}
+
+#[derive(Debug)]
+pub enum GoogleWorkspaceError {
+    IdentityRegistry(IdentityRegistryError),
+    MissingIdentity(String),
+    InvalidEventTime(String),
+    InvalidTechnique(String),
+    InvalidArrival(String),
+    EmptyStream,
+}
+
+impl std::fmt::Display for GoogleWorkspaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoogleWorkspaceError::IdentityRegistry(err) => write!(f, "{err}"),
+            GoogleWorkspaceError::MissingIdentity(actor_id) => {
+                write!(
+                    f,
+                    "google workspace event references unknown actor_id: {actor_id}"
+                )
+            }
+            GoogleWorkspaceError::InvalidEventTime(value) => {
+                write!(f, "invalid google workspace event time: {value}")
+            }
+            GoogleWorkspaceError::InvalidTechnique(value) => {
+                write!(
+                    f,
+                    "invalid ATT&CK technique id on google workspace event: {value}"
+                )
+            }
+            GoogleWorkspaceError::InvalidArrival(value) => {
+                write!(f, "invalid google workspace arrival config: {value}")
+            }
+            GoogleWorkspaceError::EmptyStream => {
+                write!(
+                    f,
+                    "google workspace source needs identity registry actors or event entries"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GoogleWorkspaceError {}
+
+impl From<IdentityRegistryError> for GoogleWorkspaceError {
+    fn from(err: IdentityRegistryError) -> Self {
+        GoogleWorkspaceError::IdentityRegistry(err)
+    }
+}
+
+impl GoogleWorkspaceGenerator {
+    pub fn from_config(
+        config: &GoogleWorkspaceSourceConfig,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, GoogleWorkspaceError> {
+        let registry = IdentityRegistry::from_path(&config.identity_registry_path)?;
+        Self::from_registry(config, registry, start_time)
+    }
+
+    pub fn from_registry(
+        config: &GoogleWorkspaceSourceConfig,
+        registry: IdentityRegistry,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, GoogleWorkspaceError> {
+        let mut scheduled = Vec::new();
+        append_injected_events(config, &registry, start_time, &mut scheduled)?;
+        let identities = sorted_identities(&registry);
+
+        if scheduled.is_empty()
+            && (config.baseline_events_per_actor == Some(0) || identities.is_empty())
+        {
+            return Err(GoogleWorkspaceError::EmptyStream);
+        }
+
+        scheduled.sort_by(|left, right| {
+            left.event_time
+                .cmp(&right.event_time)
+                .then(left.sequence.cmp(&right.sequence))
+        });
+        let arrivals = Arrivals::from_config(config.arrival.as_ref())
+            .map_err(GoogleWorkspaceError::InvalidArrival)?;
+        let mut linked_clock = config.linked_sessions.then(|| {
+            LinkedSessionClock::new(identities.len(), start_time, config.session_correlation)
+        });
+        let schedule = if config.baseline_events_per_actor == Some(0) {
+            BinaryHeap::new()
+        } else {
+            build_identity_schedule(&identities, start_time, linked_clock.as_mut(), &arrivals)
+        };
+        let next_event_idx = vec![0; identities.len()];
+        Ok(Self {
+            config: config.clone(),
+            injected_events: scheduled.into(),
+            identities,
+            schedule,
+            arrivals,
+            next_event_idx,
+            linked_clock,
+        })
+    }
+}
+
+impl EventSource for GoogleWorkspaceGenerator {
+    fn next_event(&mut self) -> Option<Event> {
+        let injected_time = self.injected_events.front().map(|event| event.event_time);
+        let scheduled_time = self.schedule.peek().map(|Reverse((time, _))| *time);
+
+        match (injected_time, scheduled_time) {
+            (None, None) => None,
+            (Some(_), None) => self.injected_events.pop_front().map(|item| item.event),
+            (Some(injected), Some(scheduled)) if injected <= scheduled => {
+                self.injected_events.pop_front().map(|item| item.event)
+            }
+            _ => self.next_scheduled_event(),
+        }
+    }
+}
+
+impl GoogleWorkspaceGenerator {
+    fn next_scheduled_event(&mut self) -> Option<Event> {
+        let Reverse((event_time, actor_idx)) = self.schedule.pop()?;
+        let event_idx = self.next_event_idx[actor_idx];
+        self.next_event_idx[actor_idx] += 1;
+
+        let identity = &self.identities[actor_idx];
+        let action = baseline_action(&self.config, &self.identities, actor_idx, event_idx);
+        let ip_address = baseline_source_ip(&self.config, identity, actor_idx, event_idx);
+        let sequence = actor_idx * 1000 + event_idx;
+        let activity = activity_row(
+            &self.config,
+            identity,
+            action,
+            ip_address,
+            event_time,
+            sequence,
+        );
+        let event = event_from_activity(identity, activity);
+
+        let next_at = if let Some(clock) = &mut self.linked_clock {
+            clock.next_event_at(
+                actor_idx,
+                identity,
+                self.next_event_idx[actor_idx],
+                "google_workspace",
+                &self.arrivals,
+            )
+        } else {
+            next_identity_event_after(
+                identity,
+                event_time,
+                self.next_event_idx[actor_idx],
+                "google_workspace",
+                &self.arrivals,
+            )
+        };
+        self.schedule.push(Reverse((next_at, actor_idx)));
+        Some(event)
+    }
+}
+
+struct ScheduledWorkspaceEvent {
+    event_time: DateTime<Utc>,
+    sequence: usize,
+    event: Event,
+}
+
+/// One activity before the common record fields are filled in.
+struct WorkspaceAction {
+    application: String,
+    event_type: String,
+    name: String,
+    parameters: Vec<WorkspaceParameter>,
+}
+
+fn append_injected_events(
+    config: &GoogleWorkspaceSourceConfig,
+    registry: &IdentityRegistry,
+    start_time: DateTime<Utc>,
+    scheduled: &mut Vec<ScheduledWorkspaceEvent>,
+) -> Result<(), GoogleWorkspaceError> {
+    for (idx, entry) in config.events.iter().enumerate() {
+        let identity = registry
+            .get(&entry.actor_id)
+            .ok_or_else(|| GoogleWorkspaceError::MissingIdentity(entry.actor_id.clone()))?;
+        let event_time = event_time_for_entry(entry, start_time)?;
+        let activity = activity_row(
+            config,
+            identity,
+            action_for_entry(entry),
+            entry.ip_address.clone(),
+            event_time,
+            idx,
+        );
+        let mut event = event_from_activity(identity, activity);
+        event.label = scenario_label(
+            entry.scenario_id.as_ref(),
+            entry.technique_id.as_ref(),
+            &entry.technique_ids,
+        )
+        .map_err(GoogleWorkspaceError::InvalidTechnique)?;
+        scheduled.push(ScheduledWorkspaceEvent {
+            event_time,
+            sequence: idx,
+            event,
+        });
+    }
+    Ok(())
+}
+
+fn action_for_entry(entry: &GoogleWorkspaceEventConfig) -> WorkspaceAction {
+    let parameters = entry
+        .parameters
+        .as_ref()
+        .map(|parameters| {
+            parameters
+                .iter()
+                .map(|(name, value)| parameter_from_toml(name, value))
+                .collect()
+        })
+        .unwrap_or_default();
+    WorkspaceAction {
+        event_type: entry
+            .event_type
+            .clone()
+            .unwrap_or_else(|| default_event_type(&entry.application, &entry.name).to_string()),
+        application: entry.application.clone(),
+        name: entry.name.clone(),
+        parameters,
+    }
+}
+
+fn sorted_identities(registry: &IdentityRegistry) -> Vec<Identity> {
+    let mut identities: Vec<&Identity> = registry.identities().iter().collect();
+    identities.sort_by(|left, right| left.actor_id.cmp(&right.actor_id));
+    identities.into_iter().cloned().collect()
+}
+
+fn build_identity_schedule(
+    identities: &[Identity],
+    start_time: DateTime<Utc>,
+    mut linked_clock: Option<&mut LinkedSessionClock>,
+    arrivals: &Arrivals,
+) -> BinaryHeap<Reverse<(DateTime<Utc>, usize)>> {
+    let mut schedule = BinaryHeap::with_capacity(identities.len());
+    for (idx, identity) in identities.iter().enumerate() {
+        let first_at = if let Some(clock) = linked_clock.as_deref_mut() {
+            clock.next_event_at(idx, identity, 0, "google_workspace", arrivals)
+        } else {
+            first_identity_event_at(identity, start_time, "google_workspace", arrivals)
+        };
+        schedule.push(Reverse((first_at, idx)));
+    }
+    schedule
+}
+
+fn activity_row(
+    config: &GoogleWorkspaceSourceConfig,
+    identity: &Identity,
+    action: WorkspaceAction,
+    ip_address: String,
+    event_time: DateTime<Utc>,
+    sequence: usize,
+) -> WorkspaceActivity {
+    let seed = format!("{sequence}:{}", identity.actor_id);
+    let application_info = identity.service_account.then(|| WorkspaceApplicationInfo {
+        oauth_client_id: format!("1{:020}", stable_hash(&format!("oauth:{}", identity.email))),
+        application_name: SYNC_APP_NAME.to_string(),
+        impersonation: false,
+    });
+    WorkspaceActivity {
+        kind: ACTIVITY_KIND.to_string(),
+        id: WorkspaceActivityId {
+            time: event_time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            unique_qualifier: (stable_hash(&format!("qualifier:{seed}")) as i64).to_string(),
+            application_name: action.application,
+            customer_id: config.customer_id.clone(),
+        },
+        etag: format!(
+            "\"{}/{}\"",
+            token(&format!("etag:{}", config.customer_id), 27),
+            token(&format!("etag:{seed}"), 27)
+        ),
+        actor: WorkspaceActor {
+            caller_type: Some("USER".to_string()),
+            email: identity.email.clone(),
+            profile_id: profile_id(&identity.email),
+            application_info,
+        },
+        owner_domain: email_domain(&identity.email).to_string(),
+        ip_address: Some(ip_address),
+        events: vec![WorkspaceEvent {
+            event_type: action.event_type,
+            name: action.name,
+            parameters: action.parameters,
+        }],
+    }
+}
+
+fn event_from_activity(identity: &Identity, activity: WorkspaceActivity) -> Event {
+    let event = &activity.events[0];
+    let text = |name: &str| {
+        event
+            .parameters
+            .iter()
+            .find(|parameter| parameter.name == name)
+            .and_then(|parameter| parameter.value.clone())
+    };
+    let outcome = if event.name == "login_failure" {
+        Outcome::Failure
+    } else {
+        Outcome::Success
+    };
+    let actor_kind = if identity.service_account {
+        "service"
+    } else {
+        "human"
+    };
+    let target = text("doc_id")
+        .map(|doc_id| Target {
+            id: doc_id,
+            kind: "file".to_string(),
+            name: text("doc_title"),
+        })
+        .or_else(|| {
+            text("USER_EMAIL").map(|email| Target {
+                id: email,
+                kind: "user".to_string(),
+                name: None,
+            })
+        });
+
+    Event {
+        envelope: EventEnvelope {
+            schema_version: "v1".to_string(),
+            timestamp: activity.id.time.clone(),
+            source: "google_workspace".to_string(),
+            event_type: event.name.clone(),
+            actor: Actor {
+                id: identity.actor_id.clone(),
+                kind: actor_kind.to_string(),
+                name: Some(identity.display_name.clone()),
+            },
+            target,
+            outcome,
+            geo: None,
+            ip: activity.ip_address.clone(),
+            user_agent: None,
+            session_id: None,
+            tenant_id: Some(activity.id.customer_id.clone()),
+            tenant: None,
+        },
+        payload: activity.to_value(),
+        label: None,
+    }
+}
+
+fn event_time_for_entry(
+    entry: &GoogleWorkspaceEventConfig,
+    start_time: DateTime<Utc>,
+) -> Result<DateTime<Utc>, GoogleWorkspaceError> {
+    if let Some(raw) = &entry.time {
+        let parsed = DateTime::parse_from_rfc3339(raw)
+            .map_err(|_| GoogleWorkspaceError::InvalidEventTime(raw.clone()))?;
+        return Ok(parsed.with_timezone(&Utc));
+    }
+    Ok(start_time + Duration::seconds(entry.offset_seconds.unwrap_or(0)))
+}
+
+/// Picks a baseline activity: humans sign in and mostly view, edit, create,
+/// and share Drive files; administrators occasionally manage users and roles;
+/// service accounts sync Drive files through an OAuth client.
+fn baseline_action(
+    config: &GoogleWorkspaceSourceConfig,
+    identities: &[Identity],
+    actor_idx: usize,
+    event_idx: usize,
+) -> WorkspaceAction {
+    let identity = &identities[actor_idx];
+    let roll = stable_hash(&format!("{}:{event_idx}", identity.actor_id));
+    let bucket = roll % 100;
+    let peer = &identities[(actor_idx + event_idx + 1) % identities.len()];
+    // Most files are the actor's own; the rest belong to a colleague.
+    let owner = if (roll >> 8).is_multiple_of(3) {
+        peer
+    } else {
+        identity
+    };
+
+    let (application, name, parameters) = if identity.service_account {
+        match bucket {
+            0..=59 => ("drive", "view", drive_parameters(owner, roll, "private")),
+            60..=89 => ("drive", "edit", drive_parameters(owner, roll, "private")),
+            _ => (
+                "drive",
+                "create",
+                drive_parameters(identity, roll, "private"),
+            ),
+        }
+    } else {
+        let admin = identity.role_persona.contains("administrator");
+        match bucket {
+            0..=11 => ("login", "login_success", login_parameters(config, roll)),
+            12..=13 if !config.saml_sso => {
+                let parameters = vec![
+                    WorkspaceParameter::text("login_type", "google_password"),
+                    WorkspaceParameter::multi("login_challenge_method", &["password"]),
+                    WorkspaceParameter::text(
+                        "login_failure_type",
+                        "login_failure_invalid_password",
+                    ),
+                    WorkspaceParameter::flag("is_suspicious", false),
+                ];
+                ("login", "login_failure", parameters)
+            }
+            12..=13 => ("login", "login_success", login_parameters(config, roll)),
+            14..=15 => (
+                "login",
+                "logout",
+                vec![WorkspaceParameter::text("login_type", login_type(config))],
+            ),
+            16..=49 => ("drive", "view", drive_parameters(owner, roll, "private")),
+            50..=69 => ("drive", "edit", drive_parameters(owner, roll, "private")),
+            70..=77 => (
+                "drive",
+                "create",
+                drive_parameters(identity, roll, "private"),
+            ),
+            78..=84 => (
+                "drive",
+                "download",
+                drive_parameters(owner, roll, "private"),
+            ),
+            85..=89 => {
+                let mut parameters = drive_parameters(identity, roll, "shared_internally");
+                parameters.push(WorkspaceParameter::text("target_user", &peer.email));
+                parameters.push(WorkspaceParameter::text("old_value", "none"));
+                parameters.push(WorkspaceParameter::text("new_value", "can_edit"));
+                ("drive", "change_user_access", parameters)
+            }
+            90..=91 => {
+                let mut parameters = drive_parameters(identity, roll, "people_with_link");
+                parameters.push(WorkspaceParameter::text("visibility_change", "external"));
+                parameters.push(WorkspaceParameter::multi("old_visibility", &["private"]));
+                ("drive", "change_document_visibility", parameters)
+            }
+            92..=95 if admin => (
+                "admin",
+                "CHANGE_PASSWORD",
+                vec![WorkspaceParameter::text("USER_EMAIL", &peer.email)],
+            ),
+            96..=97 if admin => (
+                "admin",
+                "CREATE_USER",
+                vec![WorkspaceParameter::text(
+                    "USER_EMAIL",
+                    format!("new.hire{}@{}", roll % 1000, email_domain(&identity.email)),
+                )],
+            ),
+            98..=99 if admin => (
+                "admin",
+                "ASSIGN_ROLE",
+                vec![
+                    WorkspaceParameter::text("ROLE_NAME", "_GROUPS_ADMIN_ROLE"),
+                    WorkspaceParameter::text("USER_EMAIL", &peer.email),
+                ],
+            ),
+            _ => ("drive", "view", drive_parameters(owner, roll, "private")),
+        }
+    };
+    WorkspaceAction {
+        event_type: default_event_type(application, name).to_string(),
+        application: application.to_string(),
+        name: name.to_string(),
+        parameters,
+    }
+}
+
+fn login_type(config: &GoogleWorkspaceSourceConfig) -> &'static str {
+    if config.saml_sso {
+        "saml"
+    } else {
+        "google_password"
+    }
+}
+
+fn login_parameters(config: &GoogleWorkspaceSourceConfig, roll: u64) -> Vec<WorkspaceParameter> {
+    let challenge: &[&str] = if config.saml_sso {
+        &["none"]
+    } else if roll.is_multiple_of(5) {
+        &["password", "google_authenticator"]
+    } else {
+        &["password", "idv_preregistered_phone"]
+    };
+    vec![
+        WorkspaceParameter::text("login_type", login_type(config)),
+        WorkspaceParameter::multi("login_challenge_method", challenge),
+        WorkspaceParameter::flag("is_multi_factor", !config.saml_sso),
+        WorkspaceParameter::flag("is_suspicious", false),
+    ]
+}
+
+/// Drive file parameters for a file owned by `owner`, picked from the sample set.
+fn drive_parameters(owner: &Identity, roll: u64, visibility: &str) -> Vec<WorkspaceParameter> {
+    let (title, doc_type) = DOCUMENTS[(roll / 100) as usize % DOCUMENTS.len()];
+    let doc_id = token(&format!("doc:{}:{title}", owner.email), 44);
+    vec![
+        WorkspaceParameter::flag("primary_event", true),
+        WorkspaceParameter::flag("billable", true),
+        WorkspaceParameter::text("doc_id", doc_id),
+        WorkspaceParameter::text("doc_title", title),
+        WorkspaceParameter::text("doc_type", doc_type),
+        WorkspaceParameter::text("owner", &owner.email),
+        WorkspaceParameter::flag("owner_is_shared_drive", false),
+        WorkspaceParameter::text("visibility", visibility),
+    ]
+}
+
+/// Reports API event type for an application's event name.
+fn default_event_type<'a>(application: &'a str, name: &str) -> &'a str {
+    match application {
+        "login" => "login",
+        "drive" => "access",
+        "token" => "auth",
+        "admin" => match name {
+            "ASSIGN_ROLE" | "UNASSIGN_ROLE" | "CREATE_ROLE" | "DELETE_ROLE" => {
+                "DELEGATED_ADMIN_SETTINGS"
+            }
+            "CHANGE_APPLICATION_SETTING" | "CREATE_APPLICATION_SETTING" => "APPLICATION_SETTINGS",
+            _ => "USER_SETTINGS",
+        },
+        other => other,
+    }
+}
+
+/// Maps a TOML parameter to the matching Reports API value field.
+fn parameter_from_toml(name: &str, value: &TomlValue) -> WorkspaceParameter {
+    match value {
+        TomlValue::String(value) => WorkspaceParameter::text(name, value.clone()),
+        TomlValue::Integer(value) => WorkspaceParameter::int(name, *value),
+        TomlValue::Boolean(value) => WorkspaceParameter::flag(name, *value),
+        TomlValue::Array(values) if values.iter().all(TomlValue::is_integer) => {
+            WorkspaceParameter {
+                name: name.to_string(),
+                multi_int_value: Some(values.iter().map(TomlValue::to_string).collect()),
+                ..WorkspaceParameter::default()
+            }
+        }
+        TomlValue::Array(values) => WorkspaceParameter {
+            name: name.to_string(),
+            multi_value: Some(
+                values
+                    .iter()
+                    .map(|value| match value {
+                        TomlValue::String(value) => value.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect(),
+            ),
+            ..WorkspaceParameter::default()
+        },
+        other => WorkspaceParameter::text(name, other.to_string()),
+    }
+}
+
+fn baseline_source_ip(
+    config: &GoogleWorkspaceSourceConfig,
+    identity: &Identity,
+    actor_idx: usize,
+    event_idx: usize,
+) -> String {
+    if let Some(source_ips) = config
+        .baseline_source_ips
+        .as_ref()
+        .and_then(|by_actor| by_actor.get(&identity.actor_id))
+    {
+        if !source_ips.is_empty() {
+            return source_ips[event_idx % source_ips.len()].clone();
+        }
+    }
+    let second = 64 + ((actor_idx / 240) % 16);
+    let fourth = 10 + (actor_idx % 240);
+    format!("10.{}.2.{}", second, fourth)
+}
+
+fn email_domain(email: &str) -> &str {
+    email
+        .split_once('@')
+        .map_or("example.com", |(_, domain)| domain)
+}
+
+/// 21-digit Google profile ID for an email.
+fn profile_id(email: &str) -> String {
+    format!("1{:020}", stable_hash(&format!("profile:{email}")))
+}
+
+/// Deterministic URL-safe token such as a Drive file ID or etag part.
+fn token(seed: &str, len: usize) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut state = stable_hash(seed);
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            ALPHABET[(state >> 58) as usize] as char
+        })
+        .collect()
+}
+
+fn stable_hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn workspace_activities_follow_reports_api_shape() {
+        let config = test_config();
+        let collect = || {
+            let mut generator = GoogleWorkspaceGenerator::from_registry(
+                &config,
+                test_registry(),
+                test_start_time(),
+            )
+            .unwrap();
+            (0..300)
+                .map_while(|_| generator.next_event())
+                .collect::<Vec<_>>()
+        };
+        let events = collect();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event.payload.clone())
+                .collect::<Vec<_>>(),
+            collect()
+                .into_iter()
+                .map(|event| event.payload)
+                .collect::<Vec<_>>()
+        );
+
+        let injected = events
+            .iter()
+            .find(|event| event.envelope.event_type == "change_document_visibility")
+            .unwrap();
+        assert_eq!(injected.envelope.timestamp, "2026-01-01T00:00:10.000Z");
+        assert_eq!(injected.envelope.tenant_id.as_deref(), Some("C03az79cb"));
+        assert_eq!(injected.payload["kind"], "admin#reports#activity");
+        assert_eq!(injected.payload["id"]["applicationName"], "drive");
+        assert_eq!(injected.payload["id"]["customerId"], "C03az79cb");
+        assert_eq!(
+            injected.payload["actor"]["email"],
+            "primary.user@example.com"
+        );
+        assert_eq!(
+            injected.payload["actor"]["profileId"]
+                .as_str()
+                .map(str::len),
+            Some(21)
+        );
+        assert_eq!(injected.payload["ownerDomain"], "example.com");
+        assert_eq!(injected.payload["ipAddress"], "203.0.113.45");
+        assert_eq!(injected.payload["events"][0]["type"], "access");
+        assert_eq!(
+            injected.payload["events"][0]["parameters"],
+            json!([
+                { "name": "doc_id", "value": "1AbC" },
+                { "name": "old_visibility", "multiValue": ["private"] },
+                { "name": "primary_event", "boolValue": true },
+                { "name": "visibility", "value": "public_on_the_web" },
+            ])
+        );
+        assert_eq!(
+            injected.envelope.target.as_ref().map(|target| &target.id),
+            Some(&"1AbC".to_string())
+        );
+        assert!(injected.label.is_some());
+
+        for event in &events {
+            let application = event.payload["id"]["applicationName"].as_str().unwrap();
+            let name = event.payload["events"][0]["name"].as_str().unwrap();
+            assert!(event.payload["id"]["uniqueQualifier"]
+                .as_str()
+                .unwrap()
+                .parse::<i64>()
+                .is_ok());
+            if event.envelope.actor.id == "svc-sync" {
+                assert_eq!(application, "drive");
+                assert_eq!(
+                    event.payload["actor"]["applicationInfo"]["applicationName"],
+                    SYNC_APP_NAME
+                );
+            }
+            if application == "admin" {
+                assert_eq!(event.envelope.actor.id, "user-admin");
+                assert!(event.envelope.target.is_some());
+            }
+            if name == "login_failure" {
+                assert!(matches!(event.envelope.outcome, Outcome::Failure));
+            }
+        }
+        for name in ["login_success", "view", "edit"] {
+            assert!(
+                events.iter().any(|event| event.envelope.event_type == name),
+                "{name}"
+            );
+        }
+    }
+
+    fn test_start_time() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn test_config() -> GoogleWorkspaceSourceConfig {
+        GoogleWorkspaceSourceConfig {
+            identity_registry_path: String::new(),
+            customer_id: "C03az79cb".to_string(),
+            saml_sso: false,
+            baseline_events_per_actor: None,
+            baseline_source_ips: None,
+            linked_sessions: false,
+            session_correlation: 1.0,
+            arrival: None,
+            events: vec![GoogleWorkspaceEventConfig {
+                actor_id: "user-primary".to_string(),
+                offset_seconds: Some(10),
+                time: None,
+                application: "drive".to_string(),
+                name: "change_document_visibility".to_string(),
+                event_type: None,
+                ip_address: "203.0.113.45".to_string(),
+                parameters: Some(BTreeMap::from([
+                    ("doc_id".to_string(), TomlValue::String("1AbC".to_string())),
+                    (
+                        "old_visibility".to_string(),
+                        TomlValue::Array(vec![TomlValue::String("private".to_string())]),
+                    ),
+                    ("primary_event".to_string(), TomlValue::Boolean(true)),
+                    (
+                        "visibility".to_string(),
+                        TomlValue::String("public_on_the_web".to_string()),
+                    ),
+                ])),
+                scenario_id: Some("drive-exfil".to_string()),
+                technique_id: Some("T1567".to_string()),
+                technique_ids: Vec::new(),
+            }],
+        }
+    }
+
+    fn test_registry() -> IdentityRegistry {
+        IdentityRegistry::new(
+            "test",
+            vec![
+                identity(
+                    "user-primary",
+                    "primary.user@example.com",
+                    "Developer",
+                    false,
+                ),
+                identity(
+                    "user-admin",
+                    "admin@example.com",
+                    "Workspace administrator",
+                    false,
+                ),
+                identity("svc-sync", "svc.sync@example.com", "Automation", true),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn identity(actor_id: &str, email: &str, persona: &str, service_account: bool) -> Identity {
+        Identity {
+            actor_id: actor_id.to_string(),
+            email: email.to_string(),
+            employee_id: format!("E-{actor_id}"),
+            display_name: actor_id.to_string(),
+            role_persona: persona.to_string(),
+            department: "Test department".to_string(),
+            home_location: "Test location".to_string(),
+            normal_countries_regions: vec!["Australia".to_string()],
+            okta_user_id: format!("okta-{actor_id}"),
+            databricks_username: email.to_string(),
+            aws_principals: Vec::new(),
+            service_account,
+            tags: Vec::new(),
+            rate_per_hour: Some(20.0),
+            active_start_hour: None,
+            active_hours: None,
+            timezone_offset: None,
+            weekend_active: None,
+            service_pattern: None,
+            insider_persona: None,
+            on_call: None,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Google Workspace activity as returned by the Admin SDK Reports API
+/// (`activities.list`), one record per activity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceActivity {
+    /// Always `admin#reports#activity`.
+    pub kind: String,
+    pub id: WorkspaceActivityId,
+    pub etag: String,
+    pub actor: WorkspaceActor,
+    pub owner_domain: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+    pub events: Vec<WorkspaceEvent>,
+}
+
+impl WorkspaceActivity {
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceActivityId {
+    /// RFC 3339 time with milliseconds.
+    pub time: String,
+    /// Signed 64-bit integer, serialized as a string.
+    pub unique_qualifier: String,
+    pub application_name: String,
+    pub customer_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceActor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caller_type: Option<String>,
+    pub email: String,
+    pub profile_id: String,
+    /// OAuth client an API call came through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub application_info: Option<WorkspaceApplicationInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceApplicationInfo {
+    pub oauth_client_id: String,
+    pub application_name: String,
+    pub impersonation: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<WorkspaceParameter>,
+}
+
+/// One event parameter; exactly one value field is set.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceParameter {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Signed 64-bit integer, serialized as a string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub int_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bool_value: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_value: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_int_value: Option<Vec<String>>,
+}
+
+impl WorkspaceParameter {
+    pub fn text(name: &str, value: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            value: Some(value.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn int(name: &str, value: i64) -> Self {
+        Self {
+            name: name.to_string(),
+            int_value: Some(value.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn flag(name: &str, value: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            bool_value: Some(value),
+            ..Self::default()
+        }
+    }
+
+    pub fn multi(name: &str, values: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            multi_value: Some(values.iter().map(|value| value.to_string()).collect()),
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,999 @@
+use super::model::{
+    KubernetesAuditEvent, KubernetesObjectReference, KubernetesResponseStatus, KubernetesUserInfo,
+};
+use crate::core::activity::{
+    first_identity_event_at, next_identity_event_after, LinkedSessionClock,
+};
+use crate::core::arrival::Arrivals;
+use crate::core::attack::scenario_label;
+use crate::core::config::{KubernetesAuditEventConfig, KubernetesAuditSourceConfig};
+use crate::core::event::{Actor, Event, EventEnvelope, EventLabel, Outcome, Target};
+use crate::core::identity::{Identity, IdentityRegistry, IdentityRegistryError};
+use crate::core::traits::EventSource;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::Map;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, VecDeque};
+
+const DEFAULT_NAMESPACES: &[&str] = &["default", "payments", "data-platform", "monitoring"];
+const APPS: &[&str] = &["api", "web", "worker", "ingest"];
+const KUBECTL_USER_AGENT: &str = "kubectl/v1.30.2 (darwin/arm64) kubernetes/3968350";
+
+/// Kubernetes API server audit log generator backed by a shared identity registry.
+///
+/// Humans authenticate through the API server's OIDC authenticator, so their
+/// username is the prefixed registry email (`oidc:jane.doe@example.com`);
+/// service accounts run in-cluster as
+/// `system:serviceaccount:<namespace>:<name>`. Every request is logged at the
+/// `RequestReceived` and `ResponseComplete` stages under one `auditID`, and
+/// requests outside an actor's RBAC grants are denied with a 403.
+pub struct KubernetesAuditGenerator {
+    config: KubernetesAuditSourceConfig,
+    namespaces: Vec<String>,
+    injected_requests: VecDeque<ScheduledRequest>,
+    identities: Vec<Identity>,
+    schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+    arrivals: Arrivals,
+    next_event_idx: Vec<usize>,
+    linked_clock: Option<LinkedSessionClock>,
+    /// `ResponseComplete` events waiting for their stage timestamp.
+    pending: BTreeMap<(DateTime<Utc>, u64), Event>,
+    pending_seq: u64,
+}
+
+#[derive(Debug)]
+pub enum KubernetesAuditError {
+    IdentityRegistry(IdentityRegistryError),
+    MissingIdentity(String),
+    InvalidEventTime(String),
+    InvalidTechnique(String),
+    InvalidArrival(String),
+    EmptyStream,
+}
+
+impl std::fmt::Display for KubernetesAuditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KubernetesAuditError::IdentityRegistry(err) => write!(f, "{err}"),
+            KubernetesAuditError::MissingIdentity(actor_id) => {
+                write!(
+                    f,
+                    "kubernetes audit event references unknown actor_id: {actor_id}"
+                )
+            }
+            KubernetesAuditError::InvalidEventTime(value) => {
+                write!(f, "invalid kubernetes audit request_received_at: {value}")
+            }
+            KubernetesAuditError::InvalidTechnique(value) => {
+                write!(
+                    f,
+                    "invalid ATT&CK technique id on kubernetes audit event: {value}"
+                )
+            }
+            KubernetesAuditError::InvalidArrival(value) => {
+                write!(f, "invalid kubernetes audit arrival config: {value}")
+            }
+            KubernetesAuditError::EmptyStream => {
+                write!(
+                    f,
+                    "kubernetes audit source needs identity registry actors or event entries"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for KubernetesAuditError {}
+
+impl From<IdentityRegistryError> for KubernetesAuditError {
+    fn from(err: IdentityRegistryError) -> Self {
+        KubernetesAuditError::IdentityRegistry(err)
+    }
+}
+
+impl KubernetesAuditGenerator {
+    pub fn from_config(
+        config: &KubernetesAuditSourceConfig,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, KubernetesAuditError> {
+        let registry = IdentityRegistry::from_path(&config.identity_registry_path)?;
+        Self::from_registry(config, registry, start_time)
+    }
+
+    pub fn from_registry(
+        config: &KubernetesAuditSourceConfig,
+        registry: IdentityRegistry,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, KubernetesAuditError> {
+        let namespaces = config
+            .namespaces
+            .as_ref()
+            .filter(|namespaces| !namespaces.is_empty())
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_NAMESPACES.iter().map(|ns| ns.to_string()).collect());
+        let mut scheduled = Vec::new();
+        append_injected_requests(config, &namespaces, &registry, start_time, &mut scheduled)?;
+        let identities = sorted_identities(&registry);
+
+        if scheduled.is_empty()
+            && (config.baseline_events_per_actor == Some(0) || identities.is_empty())
+        {
+            return Err(KubernetesAuditError::EmptyStream);
+        }
+
+        scheduled.sort_by(|left, right| {
+            left.event_time
+                .cmp(&right.event_time)
+                .then(left.sequence.cmp(&right.sequence))
+        });
+        let arrivals = Arrivals::from_config(config.arrival.as_ref())
+            .map_err(KubernetesAuditError::InvalidArrival)?;
+        let mut linked_clock = config.linked_sessions.then(|| {
+            LinkedSessionClock::new(identities.len(), start_time, config.session_correlation)
+        });
+        let schedule = if config.baseline_events_per_actor == Some(0) {
+            BinaryHeap::new()
+        } else {
+            build_identity_schedule(&identities, start_time, linked_clock.as_mut(), &arrivals)
+        };
+        let next_event_idx = vec![0; identities.len()];
+        Ok(Self {
+            config: config.clone(),
+            namespaces,
+            injected_requests: scheduled.into(),
+            identities,
+            schedule,
+            arrivals,
+            next_event_idx,
+            linked_clock,
+            pending: BTreeMap::new(),
+            pending_seq: 0,
+        })
+    }
+}
+
+impl EventSource for KubernetesAuditGenerator {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            let injected_time = self.injected_requests.front().map(|item| item.event_time);
+            let scheduled_time = self.schedule.peek().map(|Reverse((time, _))| *time);
+            let request_time = match (injected_time, scheduled_time) {
+                (Some(injected), Some(scheduled)) => Some(injected.min(scheduled)),
+                (time, None) | (None, time) => time,
+            };
+            let pending_time = self.pending.keys().next().map(|(time, _)| *time);
+            if let Some(pending_time) = pending_time {
+                if request_time.is_none_or(|time| pending_time <= time) {
+                    return self.pending.pop_first().map(|(_, event)| event);
+                }
+            }
+
+            let stages = match (injected_time, scheduled_time) {
+                (None, None) => return None,
+                (Some(injected), Some(scheduled)) if injected > scheduled => {
+                    self.next_scheduled_request()?
+                }
+                (Some(_), _) => self.injected_requests.pop_front()?.stages,
+                (None, Some(_)) => self.next_scheduled_request()?,
+            };
+            self.pending
+                .insert((stages.completed_at, self.pending_seq), stages.completed);
+            self.pending_seq += 1;
+            if !self.config.omit_request_received {
+                return Some(stages.received);
+            }
+        }
+    }
+}
+
+impl KubernetesAuditGenerator {
+    fn next_scheduled_request(&mut self) -> Option<RequestStages> {
+        let Reverse((event_time, actor_idx)) = self.schedule.pop()?;
+        let event_idx = self.next_event_idx[actor_idx];
+        self.next_event_idx[actor_idx] += 1;
+
+        let identity = &self.identities[actor_idx];
+        let request = baseline_request(&self.namespaces, identity, event_idx);
+        let source_ip = baseline_source_ip(&self.config, identity, actor_idx, event_idx);
+        let stages = request_stages(
+            &self.config,
+            &self.namespaces,
+            identity,
+            request,
+            source_ip,
+            event_time,
+            &format!("{}:{event_idx}", identity.actor_id),
+            None,
+        );
+
+        let next_at = if let Some(clock) = &mut self.linked_clock {
+            clock.next_event_at(
+                actor_idx,
+                identity,
+                self.next_event_idx[actor_idx],
+                "kubernetes_audit",
+                &self.arrivals,
+            )
+        } else {
+            next_identity_event_after(
+                identity,
+                event_time,
+                self.next_event_idx[actor_idx],
+                "kubernetes_audit",
+                &self.arrivals,
+            )
+        };
+        self.schedule.push(Reverse((next_at, actor_idx)));
+        Some(stages)
+    }
+}
+
+struct ScheduledRequest {
+    event_time: DateTime<Utc>,
+    sequence: usize,
+    stages: RequestStages,
+}
+
+/// Both stage events for one API request.
+struct RequestStages {
+    received: Event,
+    completed_at: DateTime<Utc>,
+    completed: Event,
+}
+
+/// One API request before the audit event fields are filled in.
+struct ApiRequest {
+    verb: String,
+    resource: String,
+    subresource: Option<String>,
+    api_group: Option<String>,
+    namespace: Option<String>,
+    name: Option<String>,
+    code: u16,
+    user_agent: String,
+}
+
+fn append_injected_requests(
+    config: &KubernetesAuditSourceConfig,
+    namespaces: &[String],
+    registry: &IdentityRegistry,
+    start_time: DateTime<Utc>,
+    scheduled: &mut Vec<ScheduledRequest>,
+) -> Result<(), KubernetesAuditError> {
+    for (idx, entry) in config.events.iter().enumerate() {
+        let identity = registry
+            .get(&entry.actor_id)
+            .ok_or_else(|| KubernetesAuditError::MissingIdentity(entry.actor_id.clone()))?;
+        let event_time = event_time_for_entry(entry, start_time)?;
+        let label = scenario_label(
+            entry.scenario_id.as_ref(),
+            entry.technique_id.as_ref(),
+            &entry.technique_ids,
+        )
+        .map_err(KubernetesAuditError::InvalidTechnique)?;
+        let source_ip = entry
+            .source_ip
+            .clone()
+            .unwrap_or_else(|| baseline_source_ip(config, identity, idx, 0));
+        let stages = request_stages(
+            config,
+            namespaces,
+            identity,
+            request_for_entry(namespaces, identity, entry),
+            source_ip,
+            event_time,
+            &format!("event:{idx}:{}", identity.actor_id),
+            label,
+        );
+        scheduled.push(ScheduledRequest {
+            event_time,
+            sequence: idx,
+            stages,
+        });
+    }
+    Ok(())
+}
+
+fn request_for_entry(
+    namespaces: &[String],
+    identity: &Identity,
+    entry: &KubernetesAuditEventConfig,
+) -> ApiRequest {
+    let default_code = if entry.verb == "create" { 201 } else { 200 };
+    ApiRequest {
+        verb: entry.verb.clone(),
+        resource: entry.resource.clone(),
+        subresource: entry.subresource.clone(),
+        api_group: entry.api_group.clone().filter(|group| !group.is_empty()),
+        namespace: entry.namespace.clone(),
+        name: entry.name.clone(),
+        code: entry.response_code.unwrap_or(default_code),
+        user_agent: entry
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| default_user_agent(namespaces, identity)),
+    }
+}
+
+fn sorted_identities(registry: &IdentityRegistry) -> Vec<Identity> {
+    let mut identities: Vec<&Identity> = registry.identities().iter().collect();
+    identities.sort_by(|left, right| left.actor_id.cmp(&right.actor_id));
+    identities.into_iter().cloned().collect()
+}
+
+fn build_identity_schedule(
+    identities: &[Identity],
+    start_time: DateTime<Utc>,
+    mut linked_clock: Option<&mut LinkedSessionClock>,
+    arrivals: &Arrivals,
+) -> BinaryHeap<Reverse<(DateTime<Utc>, usize)>> {
+    let mut schedule = BinaryHeap::with_capacity(identities.len());
+    for (idx, identity) in identities.iter().enumerate() {
+        let first_at = if let Some(clock) = linked_clock.as_deref_mut() {
+            clock.next_event_at(idx, identity, 0, "kubernetes_audit", arrivals)
+        } else {
+            first_identity_event_at(identity, start_time, "kubernetes_audit", arrivals)
+        };
+        schedule.push(Reverse((first_at, idx)));
+    }
+    schedule
+}
+
+#[allow(clippy::too_many_arguments)]
+fn request_stages(
+    config: &KubernetesAuditSourceConfig,
+    namespaces: &[String],
+    identity: &Identity,
+    request: ApiRequest,
+    source_ip: String,
+    event_time: DateTime<Utc>,
+    audit_key: &str,
+    label: Option<EventLabel>,
+) -> RequestStages {
+    let hash = stable_hash(audit_key);
+    let user = kubernetes_user(config, namespaces, identity);
+    let completed_at = event_time + Duration::microseconds(1_500 + (hash % 180_000) as i64);
+    let received_timestamp = event_time.to_rfc3339_opts(SecondsFormat::Micros, true);
+
+    let mut annotations = BTreeMap::new();
+    if request.code == 403 {
+        annotations.insert(
+            "authorization.k8s.io/decision".to_string(),
+            "forbid".to_string(),
+        );
+        annotations.insert("authorization.k8s.io/reason".to_string(), String::new());
+    } else {
+        annotations.insert(
+            "authorization.k8s.io/decision".to_string(),
+            "allow".to_string(),
+        );
+        annotations.insert(
+            "authorization.k8s.io/reason".to_string(),
+            rbac_reason(config, &user, &request),
+        );
+    }
+
+    let received = KubernetesAuditEvent {
+        kind: "Event".to_string(),
+        api_version: "audit.k8s.io/v1".to_string(),
+        level: "Metadata".to_string(),
+        audit_id: audit_id(hash),
+        stage: "RequestReceived".to_string(),
+        request_uri: request_uri(&request),
+        verb: request.verb.clone(),
+        user: user.clone(),
+        source_ips: vec![source_ip],
+        user_agent: request.user_agent.clone(),
+        object_ref: Some(KubernetesObjectReference {
+            resource: request.resource.clone(),
+            namespace: request.namespace.clone(),
+            name: request.name.clone(),
+            api_group: request.api_group.clone(),
+            api_version: "v1".to_string(),
+            subresource: request.subresource.clone(),
+        }),
+        response_status: None,
+        request_received_timestamp: received_timestamp.clone(),
+        stage_timestamp: received_timestamp,
+        annotations: BTreeMap::new(),
+    };
+    let completed = KubernetesAuditEvent {
+        stage: "ResponseComplete".to_string(),
+        response_status: Some(response_status(&user, &request)),
+        stage_timestamp: completed_at.to_rfc3339_opts(SecondsFormat::Micros, true),
+        annotations,
+        ..received.clone()
+    };
+
+    RequestStages {
+        received: event_from_row(config, identity, &request, received, label.clone()),
+        completed_at,
+        completed: event_from_row(config, identity, &request, completed, label),
+    }
+}
+
+fn event_from_row(
+    config: &KubernetesAuditSourceConfig,
+    identity: &Identity,
+    request: &ApiRequest,
+    row: KubernetesAuditEvent,
+    label: Option<EventLabel>,
+) -> Event {
+    let outcome = match &row.response_status {
+        Some(status) if status.code >= 400 => Outcome::Failure,
+        _ => Outcome::Success,
+    };
+    let actor_kind = if identity.service_account {
+        "service"
+    } else {
+        "human"
+    };
+    let resource = match &request.subresource {
+        Some(subresource) => format!("{}/{subresource}", request.resource),
+        None => request.resource.clone(),
+    };
+    let target_id = [
+        request.namespace.as_deref(),
+        Some(&resource),
+        request.name.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join("/");
+    let timestamp = DateTime::parse_from_rfc3339(&row.stage_timestamp)
+        .map(|time| {
+            time.with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+        })
+        .unwrap_or_else(|_| row.stage_timestamp.clone());
+
+    Event {
+        envelope: EventEnvelope {
+            schema_version: "v1".to_string(),
+            timestamp,
+            source: "kubernetes_audit".to_string(),
+            event_type: format!("{resource}.{}", request.verb),
+            actor: Actor {
+                id: identity.actor_id.clone(),
+                kind: actor_kind.to_string(),
+                name: Some(identity.display_name.clone()),
+            },
+            target: Some(Target {
+                id: target_id,
+                kind: request.resource.clone(),
+                name: request.name.clone(),
+            }),
+            outcome,
+            geo: None,
+            ip: row.source_ips.first().cloned(),
+            user_agent: Some(row.user_agent.clone()),
+            session_id: None,
+            tenant_id: Some(config.cluster.clone()),
+            tenant: None,
+        },
+        payload: row.to_value(),
+        label,
+    }
+}
+
+fn event_time_for_entry(
+    entry: &KubernetesAuditEventConfig,
+    start_time: DateTime<Utc>,
+) -> Result<DateTime<Utc>, KubernetesAuditError> {
+    if let Some(raw) = &entry.request_received_at {
+        let parsed = DateTime::parse_from_rfc3339(raw)
+            .map_err(|_| KubernetesAuditError::InvalidEventTime(raw.clone()))?;
+        return Ok(parsed.with_timezone(&Utc));
+    }
+    Ok(start_time + Duration::seconds(entry.offset_seconds.unwrap_or(0)))
+}
+
+/// Picks a baseline request: developers read workloads, tail logs, and roll
+/// deployments in their namespaces but are denied secrets; administrators
+/// also manage role bindings and read secrets; service accounts renew leader
+/// leases, watch their namespace, and occasionally probe cluster-wide secrets.
+fn baseline_request(namespaces: &[String], identity: &Identity, event_idx: usize) -> ApiRequest {
+    let roll = stable_hash(&format!("{}:{event_idx}", identity.actor_id));
+    let bucket = roll % 100;
+    let app = APPS[(roll / 10_000) as usize % APPS.len()];
+    let pod = format!(
+        "{app}-{:010x}-{:05x}",
+        (roll >> 12) & 0xff_ffff_ffff,
+        (roll >> 52) & 0xf_ffff
+    );
+    let mut request = ApiRequest {
+        verb: "get".to_string(),
+        resource: "pods".to_string(),
+        subresource: None,
+        api_group: None,
+        namespace: None,
+        name: None,
+        code: 200,
+        user_agent: default_user_agent(namespaces, identity),
+    };
+
+    if identity.service_account {
+        let (namespace, name) = service_account(namespaces, identity);
+        request.namespace = Some(namespace);
+        match bucket {
+            0..=44 => {
+                request.verb = "update".to_string();
+                request.resource = "leases".to_string();
+                request.api_group = Some("coordination.k8s.io".to_string());
+                request.name = Some(name);
+            }
+            45..=69 => request.verb = "list".to_string(),
+            70..=84 => request.resource = "configmaps".to_string(),
+            85..=94 => {
+                request.verb = "create".to_string();
+                request.resource = "events".to_string();
+                request.code = 201;
+            }
+            95..=96 => {
+                request.verb = "list".to_string();
+                request.resource = "secrets".to_string();
+                request.namespace = None;
+                request.code = 403;
+            }
+            _ => {
+                request.resource = "secrets".to_string();
+                request.name = Some(format!("{name}-token"));
+            }
+        }
+        return request;
+    }
+
+    let admin = identity.role_persona.contains("administrator");
+    request.namespace = Some(namespaces[(roll / 100) as usize % namespaces.len()].clone());
+    match bucket {
+        0..=29 => request.verb = "list".to_string(),
+        30..=44 => request.name = Some(pod),
+        45..=54 => {
+            request.subresource = Some("log".to_string());
+            request.name = Some(pod);
+        }
+        55..=64 => {
+            request.resource = "deployments".to_string();
+            request.api_group = Some("apps".to_string());
+            request.name = Some(app.to_string());
+        }
+        65..=71 => {
+            request.verb = "patch".to_string();
+            request.resource = "deployments".to_string();
+            request.api_group = Some("apps".to_string());
+            request.name = Some(app.to_string());
+        }
+        72..=79 => {
+            request.verb = "list".to_string();
+            request.resource = "configmaps".to_string();
+        }
+        80..=85 => {
+            request.verb = "list".to_string();
+            request.resource = "events".to_string();
+        }
+        86..=90 => {
+            request.verb = "create".to_string();
+            request.subresource = Some("exec".to_string());
+            request.name = Some(pod);
+            request.code = 101;
+        }
+        91..=94 if admin => {
+            request.verb = "create".to_string();
+            request.resource = "rolebindings".to_string();
+            request.api_group = Some("rbac.authorization.k8s.io".to_string());
+            request.code = 201;
+        }
+        91..=94 => {
+            request.verb = "list".to_string();
+            request.resource = "namespaces".to_string();
+            request.namespace = None;
+        }
+        _ if admin => {
+            request.resource = "secrets".to_string();
+            request.name = Some(format!("{app}-credentials"));
+        }
+        _ => {
+            request.verb = "list".to_string();
+            request.resource = "secrets".to_string();
+            request.code = 403;
+        }
+    }
+    request
+}
+
+fn request_uri(request: &ApiRequest) -> String {
+    let mut uri = match &request.api_group {
+        Some(group) => format!("/apis/{group}/v1"),
+        None => "/api/v1".to_string(),
+    };
+    if let Some(namespace) = &request.namespace {
+        uri.push_str(&format!("/namespaces/{namespace}"));
+    }
+    uri.push('/');
+    uri.push_str(&request.resource);
+    if let Some(name) = &request.name {
+        uri.push('/');
+        uri.push_str(name);
+    }
+    if let Some(subresource) = &request.subresource {
+        uri.push('/');
+        uri.push_str(subresource);
+    }
+    match (request.verb.as_str(), request.subresource.as_deref()) {
+        ("list", _) => uri.push_str("?limit=500"),
+        (_, Some("exec")) => {
+            uri.push_str("?command=sh&container=app&stdin=true&stdout=true&tty=true")
+        }
+        _ => {}
+    }
+    uri
+}
+
+fn response_status(user: &KubernetesUserInfo, request: &ApiRequest) -> KubernetesResponseStatus {
+    let mut status = KubernetesResponseStatus {
+        metadata: Map::new(),
+        status: None,
+        message: None,
+        reason: None,
+        code: request.code,
+    };
+    if request.code < 400 {
+        return status;
+    }
+    status.status = Some("Failure".to_string());
+    match request.code {
+        403 => {
+            let resource = match &request.subresource {
+                Some(subresource) => format!("{}/{subresource}", request.resource),
+                None => request.resource.clone(),
+            };
+            let object = match &request.name {
+                Some(name) => format!("{resource} \"{name}\""),
+                None => resource.clone(),
+            };
+            let scope = match &request.namespace {
+                Some(namespace) => format!("in the namespace \"{namespace}\""),
+                None => "at the cluster scope".to_string(),
+            };
+            status.reason = Some("Forbidden".to_string());
+            status.message = Some(format!(
+                "{object} is forbidden: User \"{}\" cannot {} resource \"{resource}\" in API group \"{}\" {scope}",
+                user.username,
+                request.verb,
+                request.api_group.as_deref().unwrap_or_default()
+            ));
+        }
+        404 => {
+            status.reason = Some("NotFound".to_string());
+            status.message = Some(format!(
+                "{} \"{}\" not found",
+                request.resource,
+                request.name.as_deref().unwrap_or_default()
+            ));
+        }
+        _ => {}
+    }
+    status
+}
+
+fn rbac_reason(
+    config: &KubernetesAuditSourceConfig,
+    user: &KubernetesUserInfo,
+    request: &ApiRequest,
+) -> String {
+    if let Some(account) = user.username.strip_prefix("system:serviceaccount:") {
+        let (namespace, name) = account.split_once(':').unwrap_or(("default", account));
+        return format!(
+            "RBAC: allowed by RoleBinding \"{name}/{namespace}\" of Role \"{name}\" to ServiceAccount \"{name}/{namespace}\""
+        );
+    }
+    let prefix = oidc_prefix(config);
+    if user
+        .groups
+        .iter()
+        .any(|group| group.ends_with("platform-admins"))
+    {
+        return format!(
+            "RBAC: allowed by ClusterRoleBinding \"platform-admins\" of ClusterRole \"cluster-admin\" to Group \"{prefix}platform-admins\""
+        );
+    }
+    match &request.namespace {
+        Some(namespace) => format!(
+            "RBAC: allowed by RoleBinding \"developers/{namespace}\" of ClusterRole \"edit\" to Group \"{prefix}developers\""
+        ),
+        None => format!(
+            "RBAC: allowed by ClusterRoleBinding \"developers-view\" of ClusterRole \"view\" to Group \"{prefix}developers\""
+        ),
+    }
+}
+
+/// Kubernetes user info for a registry identity.
+fn kubernetes_user(
+    config: &KubernetesAuditSourceConfig,
+    namespaces: &[String],
+    identity: &Identity,
+) -> KubernetesUserInfo {
+    if identity.service_account {
+        let (namespace, name) = service_account(namespaces, identity);
+        return KubernetesUserInfo {
+            username: format!("system:serviceaccount:{namespace}:{name}"),
+            uid: Some(audit_id(stable_hash(&format!("uid:{}", identity.actor_id)))),
+            groups: vec![
+                "system:serviceaccounts".to_string(),
+                format!("system:serviceaccounts:{namespace}"),
+                "system:authenticated".to_string(),
+            ],
+        };
+    }
+    let prefix = oidc_prefix(config);
+    let group = if identity.role_persona.contains("administrator") {
+        "platform-admins"
+    } else {
+        "developers"
+    };
+    KubernetesUserInfo {
+        username: format!("{prefix}{}", identity.email),
+        uid: None,
+        groups: vec![
+            format!("{prefix}{group}"),
+            "system:authenticated".to_string(),
+        ],
+    }
+}
+
+fn oidc_prefix(config: &KubernetesAuditSourceConfig) -> &str {
+    config.oidc_prefix.as_deref().unwrap_or("oidc:")
+}
+
+/// Namespace and name of a service account identity, stable per actor.
+fn service_account(namespaces: &[String], identity: &Identity) -> (String, String) {
+    let namespace = &namespaces[stable_hash(&identity.actor_id) as usize % namespaces.len()];
+    let local = identity
+        .email
+        .split_once('@')
+        .map(|(local, _)| local)
+        .unwrap_or(&identity.actor_id);
+    let mut name = String::with_capacity(local.len());
+    for ch in local.chars() {
+        if ch.is_ascii_alphanumeric() {
+            name.push(ch.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    (namespace.clone(), name.trim_end_matches('-').to_string())
+}
+
+fn default_user_agent(namespaces: &[String], identity: &Identity) -> String {
+    if identity.service_account {
+        // In-cluster client-go binaries built without version ldflags.
+        let (_, name) = service_account(namespaces, identity);
+        format!("{name}/v0.0.0 (linux/amd64) kubernetes/$Format")
+    } else {
+        KUBECTL_USER_AGENT.to_string()
+    }
+}
+
+fn baseline_source_ip(
+    config: &KubernetesAuditSourceConfig,
+    identity: &Identity,
+    actor_idx: usize,
+    event_idx: usize,
+) -> String {
+    if let Some(source_ips) = config
+        .baseline_source_ips
+        .as_ref()
+        .and_then(|by_actor| by_actor.get(&identity.actor_id))
+    {
+        if !source_ips.is_empty() {
+            return source_ips[event_idx % source_ips.len()].clone();
+        }
+    }
+    if identity.service_account {
+        // Pod network.
+        return format!(
+            "10.244.{}.{}",
+            (actor_idx / 240) % 250,
+            10 + (actor_idx % 240)
+        );
+    }
+    let second = 80 + ((actor_idx / 240) % 16);
+    let fourth = 10 + (actor_idx % 240);
+    format!("10.{}.0.{}", second, fourth)
+}
+
+fn audit_id(hash: u64) -> String {
+    let low = hash.wrapping_mul(6364136223846793005).wrapping_add(1);
+    format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        hash >> 32,
+        (hash >> 16) & 0xffff,
+        hash & 0xfff,
+        0x8000 | ((low >> 48) & 0x3fff),
+        low & 0xffff_ffff_ffff
+    )
+}
+
+fn stable_hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_emit_received_and_complete_stages() {
+        let config = test_config();
+        let collect = || {
+            let mut generator = KubernetesAuditGenerator::from_registry(
+                &config,
+                test_registry(),
+                test_start_time(),
+            )
+            .unwrap();
+            (0..300)
+                .map_while(|_| generator.next_event())
+                .collect::<Vec<_>>()
+        };
+        let events = collect();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event.payload.clone())
+                .collect::<Vec<_>>(),
+            collect()
+                .into_iter()
+                .map(|event| event.payload)
+                .collect::<Vec<_>>()
+        );
+        assert!(events
+            .windows(2)
+            .all(|pair| pair[0].envelope.timestamp <= pair[1].envelope.timestamp));
+
+        let injected = events
+            .iter()
+            .filter(|event| event.label.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(injected.len(), 2);
+        assert_eq!(injected[0].payload["stage"], "RequestReceived");
+        assert_eq!(injected[1].payload["stage"], "ResponseComplete");
+        assert_eq!(
+            injected[0].payload["auditID"],
+            injected[1].payload["auditID"]
+        );
+        assert!(injected[0].payload.get("responseStatus").is_none());
+        let denied = &injected[1];
+        assert_eq!(denied.envelope.event_type, "secrets.list");
+        assert_eq!(denied.envelope.tenant_id.as_deref(), Some("prod-eks"));
+        assert!(matches!(denied.envelope.outcome, Outcome::Failure));
+        assert_eq!(
+            denied.payload["user"]["username"],
+            "oidc:primary.user@example.com"
+        );
+        assert_eq!(denied.payload["responseStatus"]["code"], 403);
+        assert_eq!(
+            denied.payload["annotations"]["authorization.k8s.io/decision"],
+            "forbid"
+        );
+        assert_eq!(
+            denied.payload["requestURI"],
+            "/api/v1/namespaces/kube-system/secrets?limit=500"
+        );
+
+        for event in &events {
+            let username = event.payload["user"]["username"].as_str().unwrap();
+            if event.envelope.actor.id == "svc-ci" {
+                assert_eq!(username, "system:serviceaccount:payments:svc-ci");
+                assert_eq!(event.envelope.actor.kind, "service");
+            } else {
+                assert!(username.starts_with("oidc:"));
+            }
+        }
+    }
+
+    #[test]
+    fn omitting_request_received_keeps_only_complete_stages() {
+        let mut config = test_config();
+        config.omit_request_received = true;
+        let mut generator =
+            KubernetesAuditGenerator::from_registry(&config, test_registry(), test_start_time())
+                .unwrap();
+        for _ in 0..50 {
+            let event = generator.next_event().unwrap();
+            assert_eq!(event.payload["stage"], "ResponseComplete");
+        }
+    }
+
+    fn test_start_time() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn test_config() -> KubernetesAuditSourceConfig {
+        KubernetesAuditSourceConfig {
+            identity_registry_path: String::new(),
+            cluster: "prod-eks".to_string(),
+            namespaces: Some(vec!["payments".to_string()]),
+            oidc_prefix: None,
+            omit_request_received: false,
+            baseline_events_per_actor: None,
+            baseline_source_ips: None,
+            linked_sessions: false,
+            session_correlation: 1.0,
+            arrival: None,
+            events: vec![KubernetesAuditEventConfig {
+                actor_id: "user-primary".to_string(),
+                offset_seconds: Some(10),
+                request_received_at: None,
+                verb: "list".to_string(),
+                resource: "secrets".to_string(),
+                subresource: None,
+                api_group: None,
+                namespace: Some("kube-system".to_string()),
+                name: None,
+                source_ip: Some("203.0.113.45".to_string()),
+                user_agent: None,
+                response_code: Some(403),
+                scenario_id: Some("secret-enumeration".to_string()),
+                technique_id: Some("T1552.007".to_string()),
+                technique_ids: Vec::new(),
+            }],
+        }
+    }
+
+    fn test_registry() -> IdentityRegistry {
+        IdentityRegistry::new(
+            "test",
+            vec![
+                identity(
+                    "user-primary",
+                    "primary.user@example.com",
+                    "Developer",
+                    false,
+                ),
+                identity(
+                    "user-admin",
+                    "admin@example.com",
+                    "Cloud platform administrator",
+                    false,
+                ),
+                identity("svc-ci", "svc.ci@example.com", "Automation", true),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn identity(actor_id: &str, email: &str, persona: &str, service_account: bool) -> Identity {
+        Identity {
+            actor_id: actor_id.to_string(),
+            email: email.to_string(),
+            employee_id: format!("E-{actor_id}"),
+            display_name: actor_id.to_string(),
+            role_persona: persona.to_string(),
+            department: "Test department".to_string(),
+            home_location: "Test location".to_string(),
+            normal_countries_regions: vec!["Australia".to_string()],
+            okta_user_id: format!("okta-{actor_id}"),
+            databricks_username: email.to_string(),
+            aws_principals: Vec::new(),
+            service_account,
+            tags: Vec::new(),
+            rate_per_hour: Some(20.0),
+            active_start_hour: None,
+            active_hours: None,
+            timezone_offset: None,
+            weekend_active: None,
+            service_pattern: None,
+            insider_persona: None,
+            on_call: None,
+        }
+    }
+}
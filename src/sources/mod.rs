@@ -1,4 +1,16 @@
+pub mod alb_access;
+pub mod calendar;
 pub mod cloudtrail;
 pub mod composite;
 pub mod databricks;
+pub mod entra;
+pub mod github;
+pub mod google_workspace;
+pub mod kubernetes;
 pub mod okta;
+pub mod padding;
+pub mod redaction;
+pub mod route53_resolver;
+pub mod s3_access;
+pub mod transform;
+pub mod windows_security;
@@ -0,0 +1,255 @@
+//! Catalog of the admin operations directory admins perform.
+//!
+//! Operations are grouped by service (directory, applications, admin roles,
+//! security policies) and carry the System Log fields of the event they
+//! produce, the admin roles allowed to run them, and a weight. The built-in
+//! set ships as `admin_catalog.yaml`; files listed under
+//! `directory.catalog_paths` add services, or operations to a service of the
+//! same name.
+
+use serde::Deserialize;
+use std::path::Path;
+
+const BUILTIN_CATALOG: &str = include_str!("admin_catalog.yaml");
+
+/// Okta admin role granted through admin-role group membership.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// Any directory change, including admin group memberships.
+    SuperAdmin,
+    /// Memberships of non-admin groups.
+    GroupMembershipAdmin,
+    /// App assignments.
+    AppAdmin,
+}
+
+impl AdminRole {
+    pub fn group_name(self) -> &'static str {
+        match self {
+            AdminRole::SuperAdmin => "Okta Administrators",
+            AdminRole::GroupMembershipAdmin => "Help Desk",
+            AdminRole::AppAdmin => "Application Administrators",
+        }
+    }
+
+    /// Role name reported in `debugData.privilegeGranted`.
+    pub fn privilege_name(self) -> &'static str {
+        match self {
+            AdminRole::SuperAdmin => "Super administrator",
+            AdminRole::GroupMembershipAdmin => "Group membership administrator",
+            AdminRole::AppAdmin => "Application administrator",
+        }
+    }
+}
+
+/// What an operation targets and how it changes the directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    AddMember,
+    RemoveMember,
+    AssignApp,
+    UnassignApp,
+    GrantRole,
+    RevokeRole,
+    User,
+    Group,
+    App,
+    Policy,
+}
+
+/// Admin operations loaded from the built-in catalog or a catalog file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AdminCatalog {
+    pub services: Vec<AdminService>,
+}
+
+/// Operations of one admin service area.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminService {
+    pub name: String,
+    /// Share of admin operations that go to this service, relative to the others.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    #[serde(default)]
+    pub operations: Vec<AdminOperation>,
+}
+
+/// One admin operation and the System Log event it produces.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminOperation {
+    pub event_type: String,
+    pub display_message: String,
+    #[serde(default)]
+    pub legacy_event_type: Option<String>,
+    /// `debugContext.debugData.requestUri`.
+    pub request_uri: String,
+    pub action: AdminAction,
+    /// Admin roles that may run the operation.
+    pub roles: Vec<AdminRole>,
+    /// Selection weight within the service.
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl AdminCatalog {
+    /// The admin operations that ship with seclog.
+    pub fn builtin() -> Self {
+        Self::from_yaml(BUILTIN_CATALOG).expect("built-in admin catalog is valid")
+    }
+
+    /// The built-in catalog extended with each file in `paths`.
+    pub fn with_paths(paths: &[String]) -> Result<Self, String> {
+        let mut catalog = Self::builtin();
+        for path in paths {
+            catalog.extend(Self::load(path)?);
+        }
+        Ok(catalog)
+    }
+
+    /// Loads a catalog file, picking the parser from the file extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents =
+            std::fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => serde_json::from_str::<Self>(&contents).map_err(|err| err.to_string()),
+            "toml" => toml::from_str::<Self>(&contents).map_err(|err| err.to_string()),
+            _ => serde_yaml::from_str::<Self>(&contents).map_err(|err| err.to_string()),
+        }
+        .and_then(|catalog| catalog.validate().map(|_| catalog))
+        .map_err(|err| format!("{}: {err}", path.display()))
+    }
+
+    /// Parses a catalog from YAML.
+    pub fn from_yaml(contents: &str) -> Result<Self, String> {
+        let catalog: Self = serde_yaml::from_str(contents).map_err(|err| err.to_string())?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Adds `other`'s services; operations of a service with the same name
+    /// join the existing one.
+    pub fn extend(&mut self, other: AdminCatalog) {
+        for service in other.services {
+            match self.services.iter_mut().find(|s| s.name == service.name) {
+                Some(existing) => existing.operations.extend(service.operations),
+                None => self.services.push(service),
+            }
+        }
+    }
+
+    /// Operations open to `roles`, with their weight across all services.
+    pub fn operations_for(&self, roles: &[AdminRole]) -> Vec<(&AdminOperation, f64)> {
+        self.services
+            .iter()
+            .flat_map(|service| {
+                service
+                    .operations
+                    .iter()
+                    .filter(|operation| operation.roles.iter().any(|role| roles.contains(role)))
+                    .map(move |operation| (operation, service.weight * operation.weight))
+            })
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for service in &self.services {
+            let name = &service.name;
+            if name.trim().is_empty() {
+                return Err("service name must not be empty".to_string());
+            }
+            if !service.weight.is_finite() || service.weight <= 0.0 {
+                return Err(format!("service {name}: weight must be greater than 0"));
+            }
+            for operation in &service.operations {
+                let event_type = &operation.event_type;
+                if event_type.trim().is_empty() {
+                    return Err(format!("service {name}: event_type must not be empty"));
+                }
+                if !operation.weight.is_finite() || operation.weight <= 0.0 {
+                    return Err(format!("{event_type}: weight must be greater than 0"));
+                }
+                if operation.roles.is_empty() {
+                    return Err(format!("{event_type}: roles must not be empty"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_catalog_limits_role_assignment_to_super_admins() {
+        let catalog = AdminCatalog::builtin();
+        assert_eq!(
+            catalog
+                .services
+                .iter()
+                .map(|service| service.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Directory", "Applications", "Admin Roles", "Security Policies"]
+        );
+        let help_desk = catalog.operations_for(&[AdminRole::GroupMembershipAdmin]);
+        assert!(help_desk
+            .iter()
+            .all(|(operation, _)| !operation.event_type.contains("privilege")));
+        assert!(help_desk
+            .iter()
+            .any(|(operation, _)| operation.action == AdminAction::AddMember));
+        let super_admin = catalog.operations_for(&[AdminRole::SuperAdmin]);
+        let grant = super_admin
+            .iter()
+            .find(|(operation, _)| operation.action == AdminAction::GrantRole)
+            .unwrap();
+        assert_eq!(grant.0.event_type, "user.account.privilege.grant");
+        assert_eq!(grant.1, 0.5);
+
+        let mut extended = catalog.clone();
+        extended.extend(
+            AdminCatalog::from_yaml(
+                "services:
+  - name: Directory
+    operations:
+      - event_type: user.lifecycle.create
+        display_message: Create Okta user
+        request_uri: /api/v1/users
+        action: user
+        roles: [group_membership_admin]
+  - name: Identity Providers
+    operations:
+      - event_type: system.idp.lifecycle.update
+        display_message: Update identity provider
+        request_uri: /api/v1/idps
+        action: policy
+        roles: [super_admin]
+",
+            )
+            .unwrap(),
+        );
+        assert_eq!(extended.services.len(), 5);
+        assert_eq!(
+            extended.operations_for(&[AdminRole::GroupMembershipAdmin]).len(),
+            help_desk.len() + 1
+        );
+        assert!(AdminCatalog::from_yaml(
+            "services: [{name: X, operations: [{event_type: a, display_message: b, request_uri: c, action: user, roles: []}]}]"
+        )
+        .is_err());
+    }
+}
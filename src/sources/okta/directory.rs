@@ -0,0 +1,468 @@
+//! Okta directory model: groups, admin role assignments, and app assignments.
+//!
+//! The directory is derived from the identity registry: a group per
+//! department, admin-role groups seeded from `admins` (or admin and support
+//! personas), and app registrations with stable per-user assignments. Admins
+//! change group memberships and app assignments as part of their baseline
+//! sessions, and those changes decide who can make the next ones: a user added
+//! to an admin group starts administering, a user removed stops. Which
+//! operations an admin runs comes from the [`AdminCatalog`].
+
+use super::admin_catalog::{AdminAction, AdminCatalog, AdminOperation, AdminRole};
+use super::generator::{app_target, stable_hash, stable_suffix, unit_draw, APP_NAMES};
+use super::model::OktaTarget;
+use crate::core::config::OktaDirectoryConfig;
+use crate::core::identity::Identity;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+const DEFAULT_OPERATION_RATE: f64 = 0.3;
+const DEFAULT_ADMIN_GROUP_SHARE: f64 = 0.05;
+/// Chance that a user starts out assigned to a given app.
+const APP_ASSIGNMENT_RATE: f64 = 0.6;
+
+#[derive(Debug, Clone)]
+struct Group {
+    id: String,
+    name: String,
+    admin_role: Option<AdminRole>,
+    /// Member identity indices.
+    members: BTreeSet<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct App {
+    name: &'static str,
+    /// Assigned identity indices.
+    assigned: BTreeSet<usize>,
+}
+
+/// Policies that policy operations target.
+const POLICIES: [&str; 3] = ["Default Policy", "Authentication policy", "Password Policy"];
+
+/// Admin operation made in place of an app sign-on, and what it acted on.
+#[derive(Debug, Clone)]
+pub struct DirectoryChange {
+    pub operation: AdminOperation,
+    pub kind: ChangeKind,
+}
+
+/// Identity, group, and app indices an admin operation acted on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    AddMember {
+        member: usize,
+        group: usize,
+    },
+    RemoveMember {
+        member: usize,
+        group: usize,
+    },
+    AssignApp {
+        member: usize,
+        app: usize,
+    },
+    UnassignApp {
+        member: usize,
+        app: usize,
+    },
+    GrantRole {
+        member: usize,
+        group: usize,
+    },
+    RevokeRole {
+        member: usize,
+        group: usize,
+    },
+    /// Access request for an admin-role group; changes nothing by itself.
+    RoleRequest {
+        member: usize,
+        group: usize,
+    },
+    User {
+        member: usize,
+    },
+    Group {
+        group: usize,
+    },
+    App {
+        app: usize,
+    },
+    Policy {
+        policy: usize,
+    },
+}
+
+/// Groups, admin roles, and app assignments for one registry.
+#[derive(Debug, Clone)]
+pub struct Directory {
+    groups: Vec<Group>,
+    apps: Vec<App>,
+    catalog: AdminCatalog,
+    operation_rate: f64,
+    admin_group_share: f64,
+}
+
+impl Directory {
+    /// Builds the directory for the sorted registry identities.
+    pub fn new(config: &OktaDirectoryConfig, identities: &[Identity]) -> Result<Self, String> {
+        let operation_rate = config
+            .admin_operation_rate
+            .unwrap_or(DEFAULT_OPERATION_RATE);
+        let admin_group_share = config
+            .admin_group_share
+            .unwrap_or(DEFAULT_ADMIN_GROUP_SHARE);
+        for (name, value) in [
+            ("admin_operation_rate", operation_rate),
+            ("admin_group_share", admin_group_share),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{name} must be between 0.0 and 1.0"));
+            }
+        }
+
+        let humans = (0..identities.len())
+            .filter(|idx| !identities[*idx].service_account)
+            .collect::<Vec<_>>();
+        let persona = |idx: &usize, needles: &[&str]| {
+            let persona = identities[*idx].role_persona.to_ascii_lowercase();
+            needles.iter().any(|needle| persona.contains(needle))
+        };
+        let super_admins = match &config.admins {
+            Some(admins) => admins
+                .iter()
+                .map(|actor_id| {
+                    identities
+                        .iter()
+                        .position(|identity| &identity.actor_id == actor_id)
+                        .ok_or_else(|| format!("admins: unknown actor_id {actor_id}"))
+                })
+                .collect::<Result<BTreeSet<_>, _>>()?,
+            None => humans
+                .iter()
+                .filter(|idx| persona(idx, &["admin"]))
+                .copied()
+                .collect(),
+        };
+        let help_desk = humans
+            .iter()
+            .filter(|idx| persona(idx, &["support", "help desk", "helpdesk"]))
+            .copied()
+            .collect();
+
+        let mut departments = BTreeMap::<&str, BTreeSet<usize>>::new();
+        for idx in &humans {
+            departments
+                .entry(identities[*idx].department.as_str())
+                .or_default()
+                .insert(*idx);
+        }
+        let mut groups = departments
+            .into_iter()
+            .map(|(name, members)| group(name, None, members))
+            .collect::<Vec<_>>();
+        for (role, members) in [
+            (AdminRole::SuperAdmin, super_admins),
+            (AdminRole::GroupMembershipAdmin, help_desk),
+            (AdminRole::AppAdmin, BTreeSet::new()),
+        ] {
+            groups.push(group(role.group_name(), Some(role), members));
+        }
+
+        let apps = APP_NAMES
+            .iter()
+            .map(|name| App {
+                name,
+                assigned: humans
+                    .iter()
+                    .filter(|idx| {
+                        unit_draw(&format!("{}:{name}:assigned", identities[**idx].actor_id))
+                            < APP_ASSIGNMENT_RATE
+                    })
+                    .copied()
+                    .collect(),
+            })
+            .collect();
+        let catalog = AdminCatalog::with_paths(&config.catalog_paths)?;
+        Ok(Self {
+            groups,
+            apps,
+            catalog,
+            operation_rate,
+            admin_group_share,
+        })
+    }
+
+    /// Whether the identity currently holds any admin role.
+    pub fn is_admin(&self, idx: usize) -> bool {
+        !self.roles(idx).is_empty()
+    }
+
+    /// Whether the identity currently holds `role`.
+    pub fn has_role(&self, idx: usize, role: AdminRole) -> bool {
+        self.roles(idx).contains(&role)
+    }
+
+    /// Index of the admin-role group for `role`.
+    pub fn role_group(&self, role: AdminRole) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| group.admin_role == Some(role))
+    }
+
+    /// Current holders of `role`.
+    pub fn role_members(&self, role: AdminRole) -> Vec<usize> {
+        self.role_group(role)
+            .map(|idx| self.groups[idx].members.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Adds or removes a group member; returns whether membership changed.
+    pub fn set_member(&mut self, group: usize, member: usize, present: bool) -> bool {
+        let members = &mut self.groups[group].members;
+        if present {
+            members.insert(member)
+        } else {
+            members.remove(&member)
+        }
+    }
+
+    /// Share of an admin's sessions that make a directory change.
+    pub fn operation_rate(&self) -> f64 {
+        self.operation_rate
+    }
+
+    fn roles(&self, idx: usize) -> Vec<AdminRole> {
+        self.groups
+            .iter()
+            .filter(|group| group.members.contains(&idx))
+            .filter_map(|group| group.admin_role)
+            .collect()
+    }
+
+    /// Picks and applies the admin operation an admin runs in this session,
+    /// if any, with chance `operation_rate`. Only operations the actor's roles
+    /// allow are considered.
+    pub fn change_for(
+        &mut self,
+        identities: &[Identity],
+        actor_idx: usize,
+        event_idx: usize,
+        operation_rate: f64,
+    ) -> Option<DirectoryChange> {
+        let roles = self.roles(actor_idx);
+        if roles.is_empty() {
+            return None;
+        }
+        let actor_id = &identities[actor_idx].actor_id;
+        let key = |salt: &str| format!("{actor_id}:{event_idx}:directory:{salt}");
+        if unit_draw(&key("operate")) >= operation_rate {
+            return None;
+        }
+        let operations = self.catalog.operations_for(&roles);
+        let total = operations.iter().map(|(_, weight)| weight).sum::<f64>();
+        let mut draw = unit_draw(&key("kind")) * total;
+        let operation = operations
+            .iter()
+            .find(|(_, weight)| {
+                draw -= weight;
+                draw < 0.0
+            })
+            .or(operations.last())?
+            .0
+            .clone();
+        let admin_group = roles.contains(&AdminRole::SuperAdmin)
+            && unit_draw(&key("admin-group")) < self.admin_group_share;
+        let pick = |len: usize, salt: &str| (stable_hash(&key(salt)) % len as u64) as usize;
+        let humans = |exclude: &dyn Fn(usize) -> bool| {
+            (0..identities.len())
+                .filter(|idx| {
+                    !identities[*idx].service_account && *idx != actor_idx && !exclude(*idx)
+                })
+                .collect::<Vec<_>>()
+        };
+        let pick_group = |groups: &[Group], admin: bool| {
+            let candidates = (0..groups.len())
+                .filter(|idx| groups[*idx].admin_role.is_some() == admin)
+                .collect::<Vec<_>>();
+            (!candidates.is_empty()).then(|| candidates[pick(candidates.len(), "group")])
+        };
+
+        let kind = match operation.action {
+            AdminAction::AddMember | AdminAction::GrantRole => {
+                let grant = operation.action == AdminAction::GrantRole;
+                let group_idx = pick_group(&self.groups, grant || admin_group)?;
+                let group = &mut self.groups[group_idx];
+                let members = humans(&|idx| group.members.contains(&idx));
+                if members.is_empty() {
+                    return None;
+                }
+                let member = members[pick(members.len(), "member")];
+                group.members.insert(member);
+                if grant {
+                    ChangeKind::GrantRole {
+                        member,
+                        group: group_idx,
+                    }
+                } else {
+                    ChangeKind::AddMember {
+                        member,
+                        group: group_idx,
+                    }
+                }
+            }
+            AdminAction::RemoveMember | AdminAction::RevokeRole => {
+                let revoke = operation.action == AdminAction::RevokeRole;
+                let admin = revoke || admin_group;
+                let group_idx = pick_group(&self.groups, admin)?;
+                let group = &mut self.groups[group_idx];
+                let members = group
+                    .members
+                    .iter()
+                    .copied()
+                    .filter(|idx| *idx != actor_idx)
+                    .collect::<Vec<_>>();
+                // Admin groups keep at least one other member.
+                if members.is_empty() || (admin && group.members.len() < 2) {
+                    return None;
+                }
+                let member = members[pick(members.len(), "member")];
+                group.members.remove(&member);
+                if revoke {
+                    ChangeKind::RevokeRole {
+                        member,
+                        group: group_idx,
+                    }
+                } else {
+                    ChangeKind::RemoveMember {
+                        member,
+                        group: group_idx,
+                    }
+                }
+            }
+            AdminAction::AssignApp => {
+                let app_idx = pick(self.apps.len(), "app");
+                let app = &mut self.apps[app_idx];
+                let members = (0..identities.len())
+                    .filter(|idx| !identities[*idx].service_account && !app.assigned.contains(idx))
+                    .collect::<Vec<_>>();
+                if members.is_empty() {
+                    return None;
+                }
+                let member = members[pick(members.len(), "member")];
+                app.assigned.insert(member);
+                ChangeKind::AssignApp {
+                    member,
+                    app: app_idx,
+                }
+            }
+            AdminAction::UnassignApp => {
+                let app_idx = pick(self.apps.len(), "app");
+                let app = &mut self.apps[app_idx];
+                let members = app.assigned.iter().copied().collect::<Vec<_>>();
+                if members.is_empty() {
+                    return None;
+                }
+                let member = members[pick(members.len(), "member")];
+                app.assigned.remove(&member);
+                ChangeKind::UnassignApp {
+                    member,
+                    app: app_idx,
+                }
+            }
+            AdminAction::User => {
+                let members = humans(&|_| false);
+                if members.is_empty() {
+                    return None;
+                }
+                ChangeKind::User {
+                    member: members[pick(members.len(), "member")],
+                }
+            }
+            AdminAction::Group => ChangeKind::Group {
+                group: pick_group(&self.groups, false)?,
+            },
+            AdminAction::App => ChangeKind::App {
+                app: pick(self.apps.len(), "app"),
+            },
+            AdminAction::Policy => ChangeKind::Policy {
+                policy: pick(POLICIES.len(), "policy"),
+            },
+        };
+        Some(DirectoryChange { operation, kind })
+    }
+
+    /// System Log targets of a change: the user first, then the group or app.
+    pub fn targets(
+        &self,
+        change: &DirectoryChange,
+        identities: &[Identity],
+        actor: &Identity,
+    ) -> Vec<OktaTarget> {
+        let user = |member: usize| {
+            let member = &identities[member];
+            OktaTarget {
+                alternate_id: Some(member.email.clone()),
+                change_details: None,
+                detail_entry: Value::Null,
+                display_name: Some(member.display_name.clone()),
+                id: member.okta_user_id.clone(),
+                target_type: "User".to_string(),
+            }
+        };
+        let group = |group: usize| {
+            let group = &self.groups[group];
+            OktaTarget {
+                alternate_id: Some("unknown".to_string()),
+                change_details: None,
+                detail_entry: Value::Null,
+                display_name: Some(group.name.clone()),
+                id: group.id.clone(),
+                target_type: "UserGroup".to_string(),
+            }
+        };
+        let app = |app: usize| app_target(self.apps[app].name, &actor.department);
+        match &change.kind {
+            ChangeKind::AddMember { member, group: idx }
+            | ChangeKind::RemoveMember { member, group: idx }
+            | ChangeKind::RoleRequest { member, group: idx } => vec![user(*member), group(*idx)],
+            ChangeKind::AssignApp { member, app: idx }
+            | ChangeKind::UnassignApp { member, app: idx } => vec![user(*member), app(*idx)],
+            ChangeKind::GrantRole { member, .. }
+            | ChangeKind::RevokeRole { member, .. }
+            | ChangeKind::User { member } => vec![user(*member)],
+            ChangeKind::Group { group: idx } => vec![group(*idx)],
+            ChangeKind::App { app: idx } => vec![app(*idx)],
+            ChangeKind::Policy { policy } => {
+                let name = POLICIES[*policy];
+                vec![OktaTarget {
+                    alternate_id: Some("unknown".to_string()),
+                    change_details: None,
+                    detail_entry: Value::Null,
+                    display_name: Some(name.to_string()),
+                    id: format!("00p{}", stable_suffix(&format!("policy:{name}"))),
+                    target_type: "PolicyEntity".to_string(),
+                }]
+            }
+        }
+    }
+
+    /// Admin role a `GrantRole` or `RevokeRole` change is about.
+    pub fn privilege(&self, change: &DirectoryChange) -> Option<AdminRole> {
+        match change.kind {
+            ChangeKind::GrantRole { group, .. } | ChangeKind::RevokeRole { group, .. } => {
+                self.groups[group].admin_role
+            }
+            _ => None,
+        }
+    }
+}
+
+fn group(name: &str, admin_role: Option<AdminRole>, members: BTreeSet<usize>) -> Group {
+    Group {
+        id: format!("00g{}", stable_suffix(&format!("group:{name}"))),
+        name: name.to_string(),
+        admin_role,
+        members,
+    }
+}
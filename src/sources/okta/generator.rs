@@ -1,9 +1,16 @@
+use super::admin_catalog::AdminOperation;
+use super::directory::{ChangeKind, Directory, DirectoryChange};
 use super::model::{
     OktaActor, OktaAuthenticationContext, OktaClient, OktaDebugContext, OktaDevice,
     OktaGeographicalContext, OktaIpChainEntry, OktaLogEvent, OktaOutcome, OktaRequest,
     OktaSecurityContext, OktaTarget, OktaTransaction, OktaUserAgent,
 };
-use crate::core::activity::{first_identity_event_at, next_identity_event_after};
+use super::privileged_access::{AccessStep, PrivilegedAccess};
+use crate::core::activity::{
+    first_identity_event_at, next_identity_event_after, LinkedSessionClock,
+};
+use crate::core::arrival::Arrivals;
+use crate::core::attack::scenario_label;
 use crate::core::config::{
     OktaDeviceConfig, OktaOutcomeResult, OktaSecurityContextConfig, OktaSeverity,
     OktaSystemLogEventConfig, OktaSystemLogSourceConfig, OktaTargetConfig, OktaTransactionType,
@@ -23,7 +30,13 @@ pub struct OktaSystemLogGenerator {
     injected_events: VecDeque<ScheduledOktaEvent>,
     identities: Vec<Identity>,
     schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+    arrivals: Arrivals,
     next_event_idx: Vec<usize>,
+    linked_clock: Option<LinkedSessionClock>,
+    /// Groups, admin roles, and app assignments when `directory` is configured.
+    directory: Option<Directory>,
+    /// Open super admin activations when `privileged_access` is configured.
+    privileged_access: Option<PrivilegedAccess>,
 }
 
 #[derive(Debug)]
@@ -31,6 +44,10 @@ pub enum OktaSystemLogError {
     IdentityRegistry(IdentityRegistryError),
     MissingIdentity(String),
     InvalidPublishedTime(String),
+    InvalidTechnique(String),
+    InvalidArrival(String),
+    InvalidDirectory(String),
+    InvalidPrivilegedAccess(String),
     EmptyStream,
 }
 
@@ -47,6 +64,21 @@ impl std::fmt::Display for OktaSystemLogError {
             OktaSystemLogError::InvalidPublishedTime(value) => {
                 write!(f, "invalid okta system log published timestamp: {value}")
             }
+            OktaSystemLogError::InvalidTechnique(value) => {
+                write!(f, "invalid ATT&CK technique id on okta event: {value}")
+            }
+            OktaSystemLogError::InvalidArrival(value) => {
+                write!(f, "invalid okta system log arrival config: {value}")
+            }
+            OktaSystemLogError::InvalidDirectory(value) => {
+                write!(f, "invalid okta system log directory config: {value}")
+            }
+            OktaSystemLogError::InvalidPrivilegedAccess(value) => {
+                write!(
+                    f,
+                    "invalid okta system log privileged_access config: {value}"
+                )
+            }
             OktaSystemLogError::EmptyStream => {
                 write!(
                     f,
@@ -95,18 +127,45 @@ impl OktaSystemLogGenerator {
                 .then(left.sequence.cmp(&right.sequence))
                 .then(left.actor_id.cmp(&right.actor_id))
         });
+        let arrivals = Arrivals::from_config(config.arrival.as_ref())
+            .map_err(OktaSystemLogError::InvalidArrival)?;
+        let mut linked_clock = config.linked_sessions.then(|| {
+            LinkedSessionClock::new(identities.len(), start_time, config.session_correlation)
+        });
         let schedule = if config.baseline_events_per_actor == Some(0) {
             BinaryHeap::new()
         } else {
-            build_identity_schedule(&identities, start_time)
+            build_identity_schedule(&identities, start_time, linked_clock.as_mut(), &arrivals)
         };
         let next_event_idx = vec![0; identities.len()];
+        let directory = config
+            .directory
+            .as_ref()
+            .map(|directory| Directory::new(directory, &identities))
+            .transpose()
+            .map_err(OktaSystemLogError::InvalidDirectory)?;
+        let privileged_access = match (&config.privileged_access, &directory) {
+            (None, _) => None,
+            (Some(_), None) => {
+                return Err(OktaSystemLogError::InvalidPrivilegedAccess(
+                    "requires [source.directory]".to_string(),
+                ))
+            }
+            (Some(access), Some(directory)) => Some(
+                PrivilegedAccess::new(access, directory, &identities)
+                    .map_err(OktaSystemLogError::InvalidPrivilegedAccess)?,
+            ),
+        };
         Ok(Self {
             config: config.clone(),
             injected_events: scheduled.into(),
             identities: std::mem::take(&mut identities),
             schedule,
+            arrivals,
             next_event_idx,
+            linked_clock,
+            directory,
+            privileged_access,
         })
     }
 }
@@ -115,14 +174,25 @@ impl EventSource for OktaSystemLogGenerator {
     fn next_event(&mut self) -> Option<Event> {
         let injected_time = self.injected_events.front().map(|event| event.published);
         let scheduled_time = self.schedule.peek().map(|Reverse((time, _))| *time);
+        let access_time = self
+            .privileged_access
+            .as_ref()
+            .and_then(PrivilegedAccess::next_at);
 
-        match (injected_time, scheduled_time) {
-            (None, None) => None,
-            (Some(_), None) => self.injected_events.pop_front().map(|item| item.event),
-            (Some(injected), Some(scheduled)) if injected <= scheduled => {
-                self.injected_events.pop_front().map(|item| item.event)
+        // Ties go to injected events, then activation steps, then sessions.
+        let next = [injected_time, access_time, scheduled_time]
+            .into_iter()
+            .flatten()
+            .min()?;
+        if injected_time == Some(next) {
+            self.injected_events.pop_front().map(|item| item.event)
+        } else if access_time == Some(next) {
+            match self.next_access_event() {
+                Some(event) => Some(event),
+                None => self.next_event(),
             }
-            _ => self.next_scheduled_event(),
+        } else {
+            self.next_scheduled_event()
         }
     }
 }
@@ -135,24 +205,154 @@ impl OktaSystemLogGenerator {
 
         let identity = &self.identities[actor_idx];
         let sequence = actor_idx * 1_000_000 + event_idx;
+        let admin = match &self.directory {
+            Some(directory) => directory.is_admin(actor_idx),
+            None => identity.role_persona.to_ascii_lowercase().contains("admin"),
+        };
+        // Eligible users may request super admin in place of the app sign-on.
+        let request = match (self.privileged_access.as_mut(), &self.directory) {
+            (Some(access), Some(directory)) => access.request(
+                directory,
+                &self.identities,
+                actor_idx,
+                event_idx,
+                published,
+                sequence,
+            ),
+            _ => None,
+        };
+        let elevated = self
+            .privileged_access
+            .as_ref()
+            .and_then(|access| access.elevated_operation_rate(actor_idx));
+        // Admin sessions may make a directory change in place of the app sign-on;
+        // activated admins may in any session.
+        let change = match self.directory.as_mut() {
+            Some(directory)
+                if request.is_none()
+                    && !identity.service_account
+                    && (elevated.is_some() || event_idx % 5 == 3) =>
+            {
+                let rate = elevated.unwrap_or_else(|| directory.operation_rate());
+                directory
+                    .change_for(&self.identities, actor_idx, event_idx, rate)
+                    .map(|change| directory_event(directory, change, &self.identities, identity))
+            }
+            _ => None,
+        };
+        let event = match request {
+            Some(step) => self.access_event(step),
+            None => {
+                let label = match (&change, &self.privileged_access) {
+                    (Some(_), Some(access)) if elevated.is_some() => access.label_for(actor_idx),
+                    _ => None,
+                };
+                let row = baseline_log_event_for_identity(
+                    &self.config,
+                    identity,
+                    actor_idx,
+                    event_idx,
+                    published,
+                    sequence,
+                    admin,
+                    change,
+                );
+                let mut event = event_from_row(&self.config, identity, row);
+                event.label = label;
+                event
+            }
+        };
+
+        let next_at = if let Some(clock) = &mut self.linked_clock {
+            clock.next_event_at(
+                actor_idx,
+                identity,
+                self.next_event_idx[actor_idx],
+                "okta_system_log",
+                &self.arrivals,
+            )
+        } else {
+            next_identity_event_after(
+                identity,
+                published,
+                self.next_event_idx[actor_idx],
+                "okta_system_log",
+                &self.arrivals,
+            )
+        };
+        self.schedule.push(Reverse((next_at, actor_idx)));
+        Some(event)
+    }
+
+    /// Applies the next due activation step; `None` if it was dropped.
+    fn next_access_event(&mut self) -> Option<Event> {
+        let access = self.privileged_access.as_mut()?;
+        let step = access.next_step(self.directory.as_mut()?)?;
+        Some(self.access_event(step))
+    }
+
+    fn access_event(&self, step: AccessStep) -> Event {
+        let directory = self
+            .directory
+            .as_ref()
+            .expect("privileged access requires a directory");
+        let identity = &self.identities[step.actor_idx];
+        let mut change = directory_event(directory, step.change, &self.identities, identity);
+        change.debug_data.insert(
+            "accessRequestId".to_string(),
+            Value::String(step.request_id),
+        );
+        if let Some(resolution) = step.resolution {
+            change.debug_data.insert(
+                "resolution".to_string(),
+                Value::String(resolution.to_string()),
+            );
+        }
         let row = baseline_log_event_for_identity(
             &self.config,
             identity,
-            actor_idx,
-            event_idx,
-            published,
-            sequence,
+            step.actor_idx,
+            self.next_event_idx[step.actor_idx],
+            step.published,
+            step.sequence,
+            directory.is_admin(step.actor_idx),
+            Some(change),
         );
-        let event = event_from_row(&self.config, identity, row);
+        let mut event = event_from_row(&self.config, identity, row);
+        event.label = step.label;
+        event
+    }
+}
 
-        let next_at = next_identity_event_after(
-            identity,
-            published,
-            self.next_event_idx[actor_idx],
-            "okta_system_log",
+/// Admin operation an admin session runs, with its System Log targets.
+struct DirectoryEvent {
+    change: DirectoryChange,
+    targets: Vec<OktaTarget>,
+    /// Entries added to `debugData`, such as the role a role change is about.
+    debug_data: Map<String, Value>,
+}
+
+fn directory_event(
+    directory: &Directory,
+    change: DirectoryChange,
+    identities: &[Identity],
+    actor: &Identity,
+) -> DirectoryEvent {
+    let mut debug_data = Map::new();
+    if let Some(privilege) = directory.privilege(&change) {
+        let key = match change.kind {
+            ChangeKind::RevokeRole { .. } => "privilegeRevoked",
+            _ => "privilegeGranted",
+        };
+        debug_data.insert(
+            key.to_string(),
+            Value::String(privilege.privilege_name().to_string()),
         );
-        self.schedule.push(Reverse((next_at, actor_idx)));
-        Some(event)
+    }
+    DirectoryEvent {
+        targets: directory.targets(&change, identities, actor),
+        change,
+        debug_data,
     }
 }
 
@@ -163,15 +363,15 @@ struct ScheduledOktaEvent {
     event: Event,
 }
 
-struct BaselineTemplate {
-    event_type: &'static str,
-    display_message: &'static str,
-    legacy_event_type: Option<&'static str>,
+struct BaselineTemplate<'a> {
+    event_type: &'a str,
+    display_message: &'a str,
+    legacy_event_type: Option<&'a str>,
     outcome_result: OktaOutcomeResult,
     outcome_reason: Option<&'static str>,
     severity: OktaSeverity,
     credential_type: Option<&'static str>,
-    debug_request_uri: &'static str,
+    debug_request_uri: &'a str,
 }
 
 fn append_injected_events(
@@ -186,7 +386,13 @@ fn append_injected_events(
             .ok_or_else(|| OktaSystemLogError::MissingIdentity(entry.actor_id.clone()))?;
         let published = published_for_entry(entry, start_time)?;
         let row = log_event_for_entry(identity, entry, published, idx);
-        let event = event_from_row(config, identity, row);
+        let mut event = event_from_row(config, identity, row);
+        event.label = scenario_label(
+            entry.scenario_id.as_ref(),
+            entry.technique_id.as_ref(),
+            &entry.technique_ids,
+        )
+        .map_err(OktaSystemLogError::InvalidTechnique)?;
         scheduled.push(ScheduledOktaEvent {
             published,
             sequence: idx,
@@ -206,10 +412,16 @@ fn sorted_identities(registry: &IdentityRegistry) -> Vec<Identity> {
 fn build_identity_schedule(
     identities: &[Identity],
     start_time: DateTime<Utc>,
+    mut linked_clock: Option<&mut LinkedSessionClock>,
+    arrivals: &Arrivals,
 ) -> BinaryHeap<Reverse<(DateTime<Utc>, usize)>> {
     let mut schedule = BinaryHeap::with_capacity(identities.len());
     for (idx, identity) in identities.iter().enumerate() {
-        let first_at = first_identity_event_at(identity, start_time, "okta_system_log");
+        let first_at = if let Some(clock) = linked_clock.as_deref_mut() {
+            clock.next_event_at(idx, identity, 0, "okta_system_log", arrivals)
+        } else {
+            first_identity_event_at(identity, start_time, "okta_system_log", arrivals)
+        };
         schedule.push(Reverse((first_at, idx)));
     }
     schedule
@@ -320,6 +532,7 @@ fn log_event_for_entry(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn baseline_log_event_for_identity(
     config: &OktaSystemLogSourceConfig,
     identity: &Identity,
@@ -327,11 +540,51 @@ fn baseline_log_event_for_identity(
     event_idx: usize,
     published: DateTime<Utc>,
     sequence: usize,
+    admin: bool,
+    change: Option<DirectoryEvent>,
 ) -> OktaLogEvent {
-    let template = baseline_template(identity, event_idx);
-    let source_ip = baseline_source_ip(config, identity, actor_idx, event_idx);
-    let geo = baseline_geo_context(identity);
-    let targets = baseline_targets_for_event(identity, template.event_type, event_idx);
+    let profile = sign_in_profile(config, identity, event_idx, admin);
+    let template = match &change {
+        Some(change) => directory_template(&change.change.operation),
+        None => apply_sign_in_profile(baseline_template(identity, event_idx), &profile),
+    };
+    let risky_origin = profile
+        .risk
+        .and_then(|risk| risky_origin(identity, event_idx, risk));
+    let (source_ip, geo, security_context, zone) = match risky_origin {
+        Some((ip, geo, security_context)) => (ip, geo, security_context, None),
+        None => (
+            baseline_source_ip(config, identity, actor_idx, event_idx),
+            baseline_geo_context(identity),
+            baseline_security_context(),
+            Some(zone_for_identity(identity)),
+        ),
+    };
+    let session_start = template.event_type == "user.session.start";
+    let legacy_client = profile.legacy_client && session_start;
+    let mut targets = match &change {
+        Some(change) => change.targets.clone(),
+        None => baseline_targets_for_event(identity, template.event_type, event_idx),
+    };
+    apply_sign_on_rule(&mut targets, template.event_type, &profile);
+    let mut debug_data = baseline_debug_data(identity, template.debug_request_uri);
+    if session_start {
+        if let (Some(risk), Value::Object(debug_data)) = (profile.risk, &mut debug_data) {
+            insert_risk_debug_data(debug_data, risk);
+        }
+    }
+    if let (Some(change), Value::Object(debug_data)) = (&change, &mut debug_data) {
+        debug_data.extend(change.debug_data.clone());
+    }
+    let user_agent = if legacy_client {
+        legacy_user_agent()
+    } else {
+        OktaUserAgent {
+            browser: Some(default_browser().to_string()),
+            os: Some(default_os().to_string()),
+            raw_user_agent: default_user_agent(),
+        }
+    };
     let transaction_type = if identity.service_account {
         OktaTransactionType::Job
     } else {
@@ -371,16 +624,10 @@ fn baseline_log_event_for_identity(
             geographical_context: geo.clone(),
             id: client_id_for_baseline(identity, template.event_type, sequence),
             ip_address: source_ip.clone(),
-            user_agent: OktaUserAgent {
-                browser: Some(default_browser().to_string()),
-                os: Some(default_os().to_string()),
-                raw_user_agent: default_user_agent(),
-            },
-            zone: Some(zone_for_identity(identity)),
-        },
-        debug_context: OktaDebugContext {
-            debug_data: baseline_debug_data(identity, template.debug_request_uri),
+            user_agent,
+            zone,
         },
+        debug_context: OktaDebugContext { debug_data },
         device: baseline_device(identity, sequence),
         display_message: template.display_message.to_string(),
         event_type: template.event_type.to_string(),
@@ -393,7 +640,7 @@ fn baseline_log_event_for_identity(
         request: OktaRequest {
             ip_chain: vec![ip_chain_entry(&source_ip, geo)],
         },
-        security_context: baseline_security_context(),
+        security_context,
         severity: template.severity,
         target: targets,
         transaction: OktaTransaction {
@@ -447,8 +694,10 @@ fn event_from_row(
             user_agent: Some(row.client.user_agent.raw_user_agent.clone()),
             session_id: Some(row.authentication_context.external_session_id.clone()),
             tenant_id: config.org_id.clone(),
+            tenant: None,
         },
         payload: row.to_value(),
+        label: None,
     }
 }
 
@@ -464,7 +713,7 @@ fn published_for_entry(
     Ok(start_time + Duration::seconds(entry.offset_seconds.unwrap_or(0)))
 }
 
-fn baseline_template(identity: &Identity, event_idx: usize) -> BaselineTemplate {
+fn baseline_template(identity: &Identity, event_idx: usize) -> BaselineTemplate<'static> {
     if identity.service_account {
         match event_idx % 3 {
             0 => BaselineTemplate {
@@ -554,6 +803,236 @@ fn baseline_template(identity: &Identity, event_idx: usize) -> BaselineTemplate
     }
 }
 
+/// Admin console template for a catalog admin operation.
+fn directory_template(operation: &AdminOperation) -> BaselineTemplate<'_> {
+    BaselineTemplate {
+        event_type: &operation.event_type,
+        display_message: &operation.display_message,
+        legacy_event_type: operation.legacy_event_type.as_deref(),
+        outcome_result: OktaOutcomeResult::Success,
+        outcome_reason: None,
+        severity: OktaSeverity::Info,
+        credential_type: None,
+        debug_request_uri: &operation.request_uri,
+    }
+}
+
+/// Sign-on policy and risk outcome shared by the events of one baseline session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SignInProfile {
+    legacy_client: bool,
+    legacy_blocked: bool,
+    admin_mfa: bool,
+    /// `None` when risk modeling is disabled.
+    risk: Option<SignInRisk>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignInRisk {
+    Low,
+    /// Sign-in from an unfamiliar location.
+    Medium,
+    /// Sign-in through an anonymizing proxy.
+    High,
+}
+
+impl SignInRisk {
+    fn level(self) -> &'static str {
+        match self {
+            SignInRisk::Low => "LOW",
+            SignInRisk::Medium => "MEDIUM",
+            SignInRisk::High => "HIGH",
+        }
+    }
+}
+
+/// Human baseline rows cycle through five templates, so each cycle is one session.
+fn sign_in_profile(
+    config: &OktaSystemLogSourceConfig,
+    identity: &Identity,
+    event_idx: usize,
+    admin: bool,
+) -> SignInProfile {
+    let mut profile = SignInProfile::default();
+    if identity.service_account {
+        return profile;
+    }
+    let session = event_idx / 5;
+    let draw = |salt: &str| unit_draw(&format!("{}:{session}:{salt}", identity.actor_id));
+    if let Some(policy) = &config.sign_on_policy {
+        profile.legacy_client = draw("legacy-auth") < policy.legacy_auth_rate.unwrap_or(0.02);
+        profile.legacy_blocked = profile.legacy_client && policy.block_legacy_auth.unwrap_or(true);
+        profile.admin_mfa = policy.require_mfa_for_admins.unwrap_or(true) && admin;
+    }
+    if let Some(risk) = &config.risk {
+        let risky_actor = unit_draw(&format!("{}:risk-profile", identity.actor_id))
+            < risk.risky_actor_ratio.unwrap_or(0.05);
+        let level = if risky_actor && draw("risk") < risk.risky_sign_in_rate.unwrap_or(0.2) {
+            if draw("anonymized") < risk.anonymized_ip_share.unwrap_or(0.5) {
+                SignInRisk::High
+            } else {
+                SignInRisk::Medium
+            }
+        } else {
+            SignInRisk::Low
+        };
+        profile.risk = Some(level);
+    }
+    profile
+}
+
+fn apply_sign_in_profile<'a>(
+    mut template: BaselineTemplate<'a>,
+    profile: &SignInProfile,
+) -> BaselineTemplate<'a> {
+    match template.event_type {
+        "user.session.start" if profile.legacy_client => {
+            template.debug_request_uri = "/app/office365/sso/wsfed/active";
+            if profile.legacy_blocked {
+                template.legacy_event_type = Some("core.user_auth.login_failed");
+                template.outcome_result = OktaOutcomeResult::Failure;
+                template.outcome_reason = Some("Sign-on policy denied legacy authentication");
+                template.severity = OktaSeverity::Warn;
+            }
+        }
+        "policy.evaluate_sign_on" if sign_on_rule(profile).is_some() => {
+            template.outcome_result = OktaOutcomeResult::Challenge;
+            template.outcome_reason = Some("Sign-on policy evaluation resulted in CHALLENGE");
+        }
+        _ => {}
+    }
+    template
+}
+
+/// Rule that matched the session's policy evaluation, when it is not the catch-all.
+fn sign_on_rule(profile: &SignInProfile) -> Option<(&'static str, &'static str)> {
+    if matches!(profile.risk, Some(SignInRisk::Medium | SignInRisk::High)) {
+        Some(("rul-risk", "Risky sign-in: require MFA"))
+    } else if profile.admin_mfa {
+        Some(("rul-admin-mfa", "Admins: require MFA"))
+    } else {
+        None
+    }
+}
+
+fn apply_sign_on_rule(targets: &mut Vec<OktaTarget>, event_type: &str, profile: &SignInProfile) {
+    if event_type == "user.session.start" && profile.legacy_blocked {
+        targets.push(policy_rule_target(
+            "rul-legacy",
+            "Authentication policy",
+            "Block legacy authentication",
+            Some("DENY"),
+        ));
+        return;
+    }
+    if event_type != "policy.evaluate_sign_on" {
+        return;
+    }
+    let Some((id_seed, rule)) = sign_on_rule(profile) else {
+        return;
+    };
+    for target in targets.iter_mut() {
+        if target.target_type == "AppInstance" {
+            if let Value::Object(detail) = &mut target.detail_entry {
+                detail.insert(
+                    "signOnModeEvaluationResult".to_string(),
+                    Value::String("CHALLENGE".to_string()),
+                );
+            }
+        } else if target.alternate_id.as_deref() == Some("Authentication policy") {
+            *target = policy_rule_target(id_seed, "Authentication policy", rule, Some("CHALLENGE"));
+        }
+    }
+}
+
+/// Source IP, location, and network context for medium and high risk sessions.
+fn risky_origin(
+    identity: &Identity,
+    event_idx: usize,
+    risk: SignInRisk,
+) -> Option<(String, OktaGeographicalContext, OktaSecurityContext)> {
+    const LOCATIONS: [(&str, &str, f64, f64); 4] = [
+        ("Romania", "Bucharest", 44.4268, 26.1025),
+        ("Brazil", "Sao Paulo", -23.5505, -46.6333),
+        ("Vietnam", "Hanoi", 21.0278, 105.8342),
+        ("Netherlands", "Amsterdam", 52.3676, 4.9041),
+    ];
+    let seed = stable_hash(&format!("{}:{}:origin", identity.actor_id, event_idx / 5));
+    let (country, city, lat, lon) = LOCATIONS[(seed % LOCATIONS.len() as u64) as usize];
+    let geo = OktaGeographicalContext {
+        city: Some(city.to_string()),
+        country: Some(country.to_string()),
+        geolocation: geolocation_value(Some(lat), Some(lon)),
+        postal_code: Value::Null,
+        state: None,
+    };
+    let (ip, as_number, as_org, is_proxy) = match risk {
+        SignInRisk::Low => return None,
+        SignInRisk::Medium => (
+            format!("203.0.113.{}", 100 + seed % 100),
+            64513,
+            "Residential Broadband",
+            false,
+        ),
+        SignInRisk::High => (
+            format!("198.51.100.{}", 200 + seed % 50),
+            64514,
+            "Anonymizing VPN Service",
+            true,
+        ),
+    };
+    let security_context = OktaSecurityContext {
+        as_number: Some(as_number),
+        as_org: Some(as_org.to_string()),
+        bot_protection: None,
+        domain: None,
+        ip_details: None,
+        isp: Some(as_org.to_string()),
+        is_proxy: Some(is_proxy),
+        risk: None,
+        user_behaviours: None,
+    };
+    Some((ip, geo, security_context))
+}
+
+/// Okta records sign-in risk and behavior detection as formatted strings.
+fn insert_risk_debug_data(debug_data: &mut Map<String, Value>, risk: SignInRisk) {
+    let (reasons, behavior) = match risk {
+        SignInRisk::Low => (None, "NEGATIVE"),
+        SignInRisk::Medium => (Some("Anomalous Location"), "POSITIVE"),
+        SignInRisk::High => (Some("Anomalous Location, Anonymizing Proxy"), "POSITIVE"),
+    };
+    let risk_value = match reasons {
+        Some(reasons) => format!("{{reasons={reasons}, level={}}}", risk.level()),
+        None => format!("{{level={}}}", risk.level()),
+    };
+    let new_device = if risk == SignInRisk::High {
+        "POSITIVE"
+    } else {
+        "NEGATIVE"
+    };
+    debug_data.insert("risk".to_string(), Value::String(risk_value));
+    debug_data.insert(
+        "behaviors".to_string(),
+        Value::String(format!(
+            "{{New Geo-Location={behavior}, New Device={new_device}, New IP={behavior}, New State={behavior}, New Country={behavior}, Velocity={behavior}, New City={behavior}}}"
+        )),
+    );
+    debug_data.insert(
+        "threatSuspected".to_string(),
+        Value::String((risk == SignInRisk::High).to_string()),
+    );
+}
+
+fn legacy_user_agent() -> OktaUserAgent {
+    OktaUserAgent {
+        browser: Some("UNKNOWN".to_string()),
+        os: Some("Windows 10".to_string()),
+        raw_user_agent:
+            "Microsoft Office/16.0 (Windows NT 10.0; Microsoft Outlook 16.0.17328; Pro)".to_string(),
+    }
+}
+
 fn baseline_targets_for_event(
     identity: &Identity,
     event_type: &str,
@@ -615,19 +1094,24 @@ fn default_targets_for_event(
     baseline_targets_for_event(identity, event_type, sequence)
 }
 
+/// App registrations users sign on to.
+pub(super) const APP_NAMES: [&str; 3] = [
+    "Operations Portal",
+    "Analytics Workspace",
+    "Service Console",
+];
+
 fn app_instance_target(identity: &Identity, event_idx: usize) -> OktaTarget {
-    let names = [
-        "Operations Portal",
-        "Analytics Workspace",
-        "Service Console",
-    ];
-    let display_name = names[event_idx % names.len()];
+    app_target(APP_NAMES[event_idx % APP_NAMES.len()], &identity.department)
+}
+
+pub(super) fn app_target(display_name: &str, owner: &str) -> OktaTarget {
     OktaTarget {
         alternate_id: Some(display_name.to_string()),
         change_details: None,
         detail_entry: object_value([
             ("signOnModeType", Value::String("OIDC".to_string())),
-            ("appOwner", Value::String(identity.department.clone())),
+            ("appOwner", Value::String(owner.to_string())),
         ]),
         display_name: Some(display_name.to_string()),
         id: format!("0oa{}", stable_suffix(display_name)),
@@ -1147,11 +1631,16 @@ fn deterministic_uuid(sequence: usize, actor_id: &str) -> String {
     )
 }
 
-fn stable_suffix(value: &str) -> String {
+pub(super) fn stable_suffix(value: &str) -> String {
     format!("{:08x}", stable_hash(value) as u32)
 }
 
-fn stable_hash(value: &str) -> u64 {
+/// Deterministic draw in `[0, 1)` so baseline output stays reproducible.
+pub(super) fn unit_draw(value: &str) -> f64 {
+    (stable_hash(value) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub(super) fn stable_hash(value: &str) -> u64 {
     let mut hash = 0xcbf29ce484222325_u64;
     for byte in value.as_bytes() {
         hash ^= *byte as u64;
@@ -1163,9 +1652,14 @@ fn stable_hash(value: &str) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::{
+        OktaDirectoryConfig, OktaPrivilegedAccessConfig, OktaRiskConfig, OktaSignOnPolicyConfig,
+    };
     use crate::core::identity::{AwsPrincipal, IdentityRegistry};
     use crate::core::traits::EventSource;
+    use crate::sources::okta::admin_catalog::AdminCatalog;
     use serde_json::Value;
+    use std::collections::BTreeSet;
 
     #[test]
     fn okta_system_log_output_is_deterministic() {
@@ -1349,6 +1843,284 @@ mod tests {
         assert!(targets.iter().any(|target| target["type"] == "Rule"));
     }
 
+    #[test]
+    fn sign_on_policy_and_risk_shape_baseline_sign_ins() {
+        let mut config = test_config();
+        config.events.clear();
+        config.sign_on_policy = Some(OktaSignOnPolicyConfig {
+            legacy_auth_rate: Some(1.0),
+            ..OktaSignOnPolicyConfig::default()
+        });
+        config.risk = Some(OktaRiskConfig {
+            risky_actor_ratio: Some(1.0),
+            risky_sign_in_rate: Some(1.0),
+            anonymized_ip_share: Some(1.0),
+        });
+        let events = collect_events(generator(&config, test_start_time()));
+        let human = |event: &&Event| event.envelope.actor.kind == "human";
+
+        let start = events
+            .iter()
+            .filter(human)
+            .find(|event| event.envelope.event_type == "user.session.start")
+            .unwrap();
+        assert_eq!(start.payload["outcome"]["result"], "FAILURE");
+        assert!(start.payload["target"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|target| target["displayName"] == "Block legacy authentication"));
+        assert_eq!(
+            start.payload["debugContext"]["debugData"]["risk"],
+            "{reasons=Anomalous Location, Anonymizing Proxy, level=HIGH}"
+        );
+        assert_eq!(start.payload["securityContext"]["isProxy"], true);
+        assert!(start.payload["client"]["userAgent"]["rawUserAgent"]
+            .as_str()
+            .unwrap()
+            .starts_with("Microsoft Office/16.0"));
+
+        let policy = events
+            .iter()
+            .filter(human)
+            .find(|event| event.envelope.event_type == "policy.evaluate_sign_on")
+            .unwrap();
+        assert_eq!(policy.payload["outcome"]["result"], "CHALLENGE");
+        assert!(policy.payload["target"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|target| target["displayName"] == "Risky sign-in: require MFA"));
+        assert!(events
+            .iter()
+            .filter(|event| !human(event))
+            .all(|event| event.payload["securityContext"]["isProxy"] == false));
+
+        config.sign_on_policy = None;
+        config.risk = Some(OktaRiskConfig {
+            risky_actor_ratio: Some(0.0),
+            ..OktaRiskConfig::default()
+        });
+        let start = collect_events(generator(&config, test_start_time()))
+            .into_iter()
+            .find(|event| event.envelope.event_type == "user.session.start")
+            .unwrap();
+        assert_eq!(start.payload["outcome"]["result"], "SUCCESS");
+        assert_eq!(
+            start.payload["debugContext"]["debugData"]["risk"],
+            "{level=LOW}"
+        );
+
+        let rate = (0..10_000)
+            .filter(|idx| unit_draw(&format!("user-{idx}:0:legacy-auth")) < 0.02)
+            .count();
+        assert!((100..300).contains(&rate), "legacy draw count {rate}");
+    }
+
+    #[test]
+    fn directory_changes_follow_admin_group_membership() {
+        let mut config = test_config();
+        config.events.clear();
+        config.directory = Some(OktaDirectoryConfig {
+            admins: Some(vec!["user-primary".to_string()]),
+            admin_operation_rate: Some(1.0),
+            admin_group_share: Some(0.5),
+            catalog_paths: Vec::new(),
+        });
+        let mut generator = generator(&config, test_start_time());
+        let events = (0..600)
+            .filter_map(|_| generator.next_event())
+            .collect::<Vec<_>>();
+
+        let admin_groups = [
+            "Okta Administrators",
+            "Help Desk",
+            "Application Administrators",
+        ];
+        let mut members = BTreeMap::from([("Okta Administrators", vec!["00u-primary"])]);
+        let mut acting_admins = BTreeSet::new();
+        let privileges = [
+            "Super administrator",
+            "Group membership administrator",
+            "Application administrator",
+        ];
+        let catalog = AdminCatalog::builtin();
+        let admin_operations = catalog
+            .services
+            .iter()
+            .flat_map(|service| &service.operations)
+            .map(|operation| operation.event_type.as_str())
+            .collect::<BTreeSet<_>>();
+        let mut admin_group_changes = 0;
+        for event in &events {
+            let event_type = event.envelope.event_type.as_str();
+            let actor = event.payload["actor"]["id"].as_str().unwrap();
+            let debug_data = &event.payload["debugContext"]["debugData"];
+            let targets = event.payload["target"].as_array().unwrap();
+            let (add, group) = match event_type {
+                "group.user_membership.add" | "group.user_membership.remove" => {
+                    assert_eq!(targets[1]["type"], "UserGroup");
+                    assert!(targets[1]["id"].as_str().unwrap().starts_with("00g"));
+                    (
+                        event_type.ends_with("add"),
+                        targets[1]["displayName"].as_str().unwrap(),
+                    )
+                }
+                "user.account.privilege.grant" | "user.account.privilege.revoke" => {
+                    assert!(
+                        members["Okta Administrators"].contains(&actor),
+                        "{actor} changed a role without super admin"
+                    );
+                    let grant = event_type.ends_with("grant");
+                    let key = if grant {
+                        "privilegeGranted"
+                    } else {
+                        "privilegeRevoked"
+                    };
+                    let privilege = debug_data[key].as_str().unwrap();
+                    let role = privileges.iter().position(|name| *name == privilege);
+                    (grant, admin_groups[role.unwrap()])
+                }
+                _ if admin_operations.contains(event_type) => {
+                    assert!(
+                        members.values().any(|ids| ids.contains(&actor)),
+                        "{actor} ran {event_type} without an admin role"
+                    );
+                    acting_admins.insert(actor);
+                    continue;
+                }
+                _ => continue,
+            };
+            assert!(
+                members.values().any(|ids| ids.contains(&actor)),
+                "{actor} changed the directory without an admin role"
+            );
+            acting_admins.insert(actor);
+            assert_eq!(targets[0]["type"], "User");
+            let user = targets[0]["id"].as_str().unwrap();
+            if let Some(group) = admin_groups.iter().find(|name| **name == group) {
+                admin_group_changes += 1;
+                let ids = members.entry(group).or_default();
+                if add {
+                    ids.push(user);
+                } else {
+                    ids.retain(|id| *id != user);
+                }
+            }
+        }
+        assert!(acting_admins.contains("00u-primary"));
+        assert!(
+            acting_admins.len() > 1,
+            "no promoted user went on to administer"
+        );
+        assert!(admin_group_changes > 0);
+    }
+
+    #[test]
+    fn privileged_access_activations_are_approved_time_boxed_grants() {
+        let mut config = test_config();
+        config.events.clear();
+        config.directory = Some(OktaDirectoryConfig {
+            admins: Some(vec!["user-primary".to_string()]),
+            admin_operation_rate: Some(0.0),
+            ..OktaDirectoryConfig::default()
+        });
+        config.privileged_access = Some(OktaPrivilegedAccessConfig {
+            eligible: Some(vec![
+                "user-traveler".to_string(),
+                "user-singapore".to_string(),
+            ]),
+            activation_rate: Some(0.3),
+            duration_minutes: Some(30),
+            elevated_operation_rate: Some(1.0),
+            unapproved_share: Some(0.5),
+            ..OktaPrivilegedAccessConfig::default()
+        });
+        let mut generator = generator(&config, test_start_time());
+        let events = (0..1500)
+            .filter_map(|_| generator.next_event())
+            .collect::<Vec<_>>();
+
+        let catalog = AdminCatalog::builtin();
+        let admin_operations = catalog
+            .services
+            .iter()
+            .flat_map(|service| &service.operations)
+            .map(|operation| operation.event_type.as_str())
+            .collect::<BTreeSet<_>>();
+        let mut flows = BTreeMap::<&str, Vec<&Event>>::new();
+        let mut elevated_operations = 0;
+        let mut labeled_operations = 0;
+        for event in &events {
+            let debug_data = &event.payload["debugContext"]["debugData"];
+            match debug_data["accessRequestId"].as_str() {
+                Some(request_id) => flows.entry(request_id).or_default().push(event),
+                None if admin_operations.contains(event.envelope.event_type.as_str()) => {
+                    assert_ne!(event.envelope.actor.id, "user-primary");
+                    elevated_operations += 1;
+                    labeled_operations += usize::from(event.label.is_some());
+                }
+                None => assert!(event.label.is_none()),
+            }
+        }
+
+        let (mut approved, mut unapproved) = (0, 0);
+        for flow in flows.values() {
+            let requester = flow[0].envelope.actor.id.as_str();
+            let types = flow
+                .iter()
+                .map(|event| event.envelope.event_type.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(types[0], "access.request.create");
+            assert_eq!(
+                flow[0].payload["target"][1]["displayName"],
+                "Okta Administrators"
+            );
+            let grant = if types.get(1) == Some(&"access.request.resolve") {
+                approved += 1;
+                assert_ne!(flow[1].envelope.actor.id, requester);
+                assert_eq!(
+                    flow[1].payload["debugContext"]["debugData"]["resolution"],
+                    "APPROVED"
+                );
+                assert!(flow.iter().all(|event| event.label.is_none()));
+                2
+            } else {
+                unapproved += 1;
+                assert_eq!(flow[1].envelope.actor.id, requester, "self-granted");
+                for event in flow {
+                    let label = event.label.as_ref().unwrap();
+                    assert_eq!(label.scenario_id, "unapproved_privileged_activation");
+                    assert_eq!(label.technique_ids, ["T1098.003"]);
+                }
+                1
+            };
+            let Some(grant_event) = flow.get(grant) else {
+                continue;
+            };
+            assert_eq!(types[grant], "user.account.privilege.grant");
+            assert_eq!(
+                grant_event.payload["debugContext"]["debugData"]["privilegeGranted"],
+                "Super administrator"
+            );
+            if let Some(revoke) = flow.get(grant + 1) {
+                assert_eq!(types[grant + 1], "user.account.privilege.revoke");
+                let granted_at = DateTime::parse_from_rfc3339(&grant_event.envelope.timestamp);
+                let revoked_at = DateTime::parse_from_rfc3339(&revoke.envelope.timestamp);
+                assert_eq!(
+                    revoked_at.unwrap() - granted_at.unwrap(),
+                    Duration::minutes(30)
+                );
+            }
+        }
+        assert!(
+            approved > 0 && unapproved > 0,
+            "{approved} approved, {unapproved} unapproved"
+        );
+        assert!(elevated_operations > 0);
+        assert!(labeled_operations > 0 && labeled_operations < elevated_operations);
+    }
+
     #[test]
     fn example_primary_actor_has_no_singapore_baseline_travel() {
         let registry = IdentityRegistry::from_path("examples/identity_registry.toml").unwrap();
@@ -1518,6 +2290,13 @@ mod tests {
             identity_registry_path: "unused-in-unit-test.toml".to_string(),
             org_id: Some("okta-test-org".to_string()),
             baseline_events_per_actor: Some(2),
+            linked_sessions: false,
+            session_correlation: 1.0,
+            arrival: None,
+            sign_on_policy: None,
+            risk: None,
+            directory: None,
+            privileged_access: None,
             baseline_source_ips: Some(
                 [
                     (
@@ -1546,6 +2325,13 @@ mod tests {
             identity_registry_path: "examples/identity_registry.toml".to_string(),
             org_id: Some("okta-example-org".to_string()),
             baseline_events_per_actor: Some(2),
+            linked_sessions: false,
+            session_correlation: 1.0,
+            arrival: None,
+            sign_on_policy: None,
+            risk: None,
+            directory: None,
+            privileged_access: None,
             baseline_source_ips: Some(
                 [
                     ("user-001".to_string(), vec!["198.51.100.10".to_string()]),
@@ -1627,6 +2413,9 @@ mod tests {
                     display_name: Some("Operations Portal".to_string()),
                 },
             ],
+            scenario_id: None,
+            technique_id: None,
+            technique_ids: Vec::new(),
         }
     }
 
@@ -1701,6 +2490,8 @@ mod tests {
             timezone_offset: None,
             weekend_active: None,
             service_pattern: None,
+            insider_persona: None,
+            on_call: None,
         }
     }
 }
@@ -1,4 +1,7 @@
+pub mod admin_catalog;
+pub mod directory;
 pub mod generator;
 pub mod model;
+pub mod privileged_access;
 
 pub use generator::OktaSystemLogGenerator;
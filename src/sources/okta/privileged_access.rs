@@ -0,0 +1,341 @@
+//! Just-in-time super admin activations through access requests.
+//!
+//! Eligible users request the super admin role from one of their sessions.
+//! An approved request is resolved by another super admin, who then grants
+//! the role; an unapproved one is granted by the requester with no
+//! resolution, and its events carry the scenario label. The requester is a
+//! member of `Okta Administrators` until the activation expires and the role
+//! is revoked, and administers at `elevated_operation_rate` meanwhile.
+
+use super::admin_catalog::{AdminAction, AdminOperation, AdminRole};
+use super::directory::{ChangeKind, Directory, DirectoryChange};
+use super::generator::{stable_hash, stable_suffix, unit_draw};
+use crate::core::attack::scenario_label;
+use crate::core::config::OktaPrivilegedAccessConfig;
+use crate::core::event::EventLabel;
+use crate::core::identity::Identity;
+use chrono::{DateTime, Duration, Utc};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+
+const DEFAULT_ACTIVATION_RATE: f64 = 0.05;
+const DEFAULT_DURATION_MINUTES: u64 = 60;
+const DEFAULT_ELEVATED_OPERATION_RATE: f64 = 0.5;
+const DEFAULT_UNAPPROVED_SHARE: f64 = 0.1;
+/// Sequence numbers of activation events start here, clear of session events.
+const SEQUENCE_BASE: usize = usize::MAX / 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Approve,
+    Grant,
+    Revoke,
+}
+
+#[derive(Debug, Clone)]
+struct Activation {
+    request_id: String,
+    approved: bool,
+    /// Super admin group index.
+    group: usize,
+    /// Approver once the request is resolved.
+    granter: Option<usize>,
+    /// Next step of the flow.
+    stage: Stage,
+}
+
+/// One System Log event of an activation flow.
+#[derive(Debug, Clone)]
+pub struct AccessStep {
+    pub actor_idx: usize,
+    pub published: DateTime<Utc>,
+    pub sequence: usize,
+    pub change: DirectoryChange,
+    /// `debugData.accessRequestId`, shared by the events of one activation.
+    pub request_id: String,
+    /// `debugData.resolution` on the approval.
+    pub resolution: Option<&'static str>,
+    pub label: Option<EventLabel>,
+}
+
+/// Activation state for the eligible users of one directory.
+#[derive(Debug, Clone)]
+pub struct PrivilegedAccess {
+    eligible: BTreeSet<usize>,
+    activation_rate: f64,
+    duration: Duration,
+    elevated_operation_rate: f64,
+    unapproved_share: f64,
+    label: Option<EventLabel>,
+    /// Open activations by requester.
+    activations: BTreeMap<usize, Activation>,
+    /// When each open activation takes its next step.
+    pending: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+    next_sequence: usize,
+}
+
+impl PrivilegedAccess {
+    pub fn new(
+        config: &OktaPrivilegedAccessConfig,
+        directory: &Directory,
+        identities: &[Identity],
+    ) -> Result<Self, String> {
+        let activation_rate = config.activation_rate.unwrap_or(DEFAULT_ACTIVATION_RATE);
+        let elevated_operation_rate = config
+            .elevated_operation_rate
+            .unwrap_or(DEFAULT_ELEVATED_OPERATION_RATE);
+        let unapproved_share = config.unapproved_share.unwrap_or(DEFAULT_UNAPPROVED_SHARE);
+        for (name, value) in [
+            ("activation_rate", activation_rate),
+            ("elevated_operation_rate", elevated_operation_rate),
+            ("unapproved_share", unapproved_share),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{name} must be between 0.0 and 1.0"));
+            }
+        }
+        let duration_minutes = config.duration_minutes.unwrap_or(DEFAULT_DURATION_MINUTES);
+        if duration_minutes == 0 {
+            return Err("duration_minutes must be greater than 0".to_string());
+        }
+        let eligible = match &config.eligible {
+            Some(eligible) => eligible
+                .iter()
+                .map(|actor_id| {
+                    identities
+                        .iter()
+                        .position(|identity| &identity.actor_id == actor_id)
+                        .ok_or_else(|| format!("eligible: unknown actor_id {actor_id}"))
+                })
+                .collect::<Result<BTreeSet<_>, _>>()?,
+            None => directory
+                .role_members(AdminRole::GroupMembershipAdmin)
+                .into_iter()
+                .collect(),
+        };
+        let label = scenario_label(Some(&config.scenario_id()), None, &config.technique_ids())?;
+        Ok(Self {
+            eligible,
+            activation_rate,
+            duration: Duration::minutes(duration_minutes as i64),
+            elevated_operation_rate,
+            unapproved_share,
+            label,
+            activations: BTreeMap::new(),
+            pending: BinaryHeap::new(),
+            next_sequence: SEQUENCE_BASE,
+        })
+    }
+
+    /// When the next activation step is due.
+    pub fn next_at(&self) -> Option<DateTime<Utc>> {
+        self.pending.peek().map(|Reverse((time, _))| *time)
+    }
+
+    /// Admin operation rate of an actor holding an activated role.
+    pub fn elevated_operation_rate(&self, actor_idx: usize) -> Option<f64> {
+        self.activations
+            .get(&actor_idx)
+            .filter(|activation| activation.stage == Stage::Revoke)
+            .map(|_| self.elevated_operation_rate)
+    }
+
+    /// Label for the activity of an actor's unapproved activation.
+    pub fn label_for(&self, actor_idx: usize) -> Option<EventLabel> {
+        self.activations
+            .get(&actor_idx)
+            .filter(|activation| !activation.approved)
+            .and_then(|_| self.label.clone())
+    }
+
+    /// Opens an activation in place of the session's app sign-on, if the
+    /// actor is eligible and draws one.
+    pub fn request(
+        &mut self,
+        directory: &Directory,
+        identities: &[Identity],
+        actor_idx: usize,
+        event_idx: usize,
+        published: DateTime<Utc>,
+        sequence: usize,
+    ) -> Option<AccessStep> {
+        if !self.eligible.contains(&actor_idx)
+            || self.activations.contains_key(&actor_idx)
+            || directory.has_role(actor_idx, AdminRole::SuperAdmin)
+        {
+            return None;
+        }
+        let actor_id = &identities[actor_idx].actor_id;
+        let key = |salt: &str| format!("{actor_id}:{event_idx}:privileged_access:{salt}");
+        if unit_draw(&key("activate")) >= self.activation_rate {
+            return None;
+        }
+        let group = directory.role_group(AdminRole::SuperAdmin)?;
+        let approved = unit_draw(&key("approved")) >= self.unapproved_share;
+        // Approvals take a few minutes; a self-grant follows within a minute or two.
+        let wait = if approved {
+            120 + stable_hash(&key("wait")) % 780
+        } else {
+            30 + stable_hash(&key("wait")) % 60
+        };
+        let request_id = format!("req{}", stable_suffix(&key("request")));
+        self.pending.push(Reverse((
+            published + Duration::seconds(wait as i64),
+            actor_idx,
+        )));
+        self.activations.insert(
+            actor_idx,
+            Activation {
+                request_id: request_id.clone(),
+                approved,
+                group,
+                granter: None,
+                stage: if approved {
+                    Stage::Approve
+                } else {
+                    Stage::Grant
+                },
+            },
+        );
+        Some(AccessStep {
+            actor_idx,
+            published,
+            sequence,
+            change: DirectoryChange {
+                operation: request_operation(),
+                kind: ChangeKind::RoleRequest {
+                    member: actor_idx,
+                    group,
+                },
+            },
+            request_id,
+            resolution: None,
+            label: self.label_for(actor_idx),
+        })
+    }
+
+    /// Takes the next due step and applies it to the directory. Returns
+    /// `None` when the step is dropped: no super admin is left to approve, or
+    /// the role was already revoked by another admin.
+    pub fn next_step(&mut self, directory: &mut Directory) -> Option<AccessStep> {
+        let Reverse((published, requester)) = self.pending.pop()?;
+        let label = self.label_for(requester);
+        let activation = self.activations.get_mut(&requester)?;
+        let group = activation.group;
+        let request_id = activation.request_id.clone();
+        let (actor_idx, operation, kind, resolution) = match activation.stage {
+            Stage::Approve => {
+                let approvers = directory
+                    .role_members(AdminRole::SuperAdmin)
+                    .into_iter()
+                    .filter(|idx| *idx != requester)
+                    .collect::<Vec<_>>();
+                if approvers.is_empty() {
+                    self.activations.remove(&requester);
+                    return None;
+                }
+                let pick = stable_hash(&format!("{request_id}:approver"));
+                let approver = approvers[(pick % approvers.len() as u64) as usize];
+                activation.granter = Some(approver);
+                activation.stage = Stage::Grant;
+                self.pending
+                    .push(Reverse((published + Duration::seconds(5), requester)));
+                (
+                    approver,
+                    resolve_operation(),
+                    ChangeKind::RoleRequest {
+                        member: requester,
+                        group,
+                    },
+                    Some("APPROVED"),
+                )
+            }
+            Stage::Grant => {
+                directory.set_member(group, requester, true);
+                activation.stage = Stage::Revoke;
+                self.pending
+                    .push(Reverse((published + self.duration, requester)));
+                (
+                    activation.granter.unwrap_or(requester),
+                    role_operation(AdminAction::GrantRole),
+                    ChangeKind::GrantRole {
+                        member: requester,
+                        group,
+                    },
+                    None,
+                )
+            }
+            Stage::Revoke => {
+                let granter = activation.granter.unwrap_or(requester);
+                self.activations.remove(&requester);
+                if !directory.set_member(group, requester, false) {
+                    return None;
+                }
+                (
+                    granter,
+                    role_operation(AdminAction::RevokeRole),
+                    ChangeKind::RevokeRole {
+                        member: requester,
+                        group,
+                    },
+                    None,
+                )
+            }
+        };
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        Some(AccessStep {
+            actor_idx,
+            published,
+            sequence,
+            change: DirectoryChange { operation, kind },
+            request_id,
+            resolution,
+            label,
+        })
+    }
+}
+
+fn request_operation() -> AdminOperation {
+    access_request_operation("access.request.create", "Create access request")
+}
+
+fn resolve_operation() -> AdminOperation {
+    access_request_operation("access.request.resolve", "Resolve access request")
+}
+
+fn access_request_operation(event_type: &str, display_message: &str) -> AdminOperation {
+    AdminOperation {
+        event_type: event_type.to_string(),
+        display_message: display_message.to_string(),
+        legacy_event_type: None,
+        request_uri: "/governance/api/v1/requests".to_string(),
+        action: AdminAction::GrantRole,
+        roles: vec![AdminRole::SuperAdmin],
+        weight: 1.0,
+    }
+}
+
+fn role_operation(action: AdminAction) -> AdminOperation {
+    let (event_type, display_message, legacy_event_type) = match action {
+        AdminAction::RevokeRole => (
+            "user.account.privilege.revoke",
+            "Revoke user privilege",
+            "core.user.admin_privilege.revoked",
+        ),
+        _ => (
+            "user.account.privilege.grant",
+            "Grant user privilege",
+            "core.user.admin_privilege.granted",
+        ),
+    };
+    AdminOperation {
+        event_type: event_type.to_string(),
+        display_message: display_message.to_string(),
+        legacy_event_type: Some(legacy_event_type.to_string()),
+        request_uri: "/api/v1/users".to_string(),
+        action,
+        roles: vec![AdminRole::SuperAdmin],
+        weight: 1.0,
+    }
+}
@@ -0,0 +1,266 @@
+use crate::core::config::PaddingConfig;
+use crate::core::event::Event;
+use crate::core::traits::EventSource;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const HEX: &[u8] = b"0123456789abcdef";
+
+/// Error for an invalid `[padding]` table.
+#[derive(Debug)]
+pub struct PaddingError(String);
+
+impl std::fmt::Display for PaddingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid padding config: {}", self.0)
+    }
+}
+
+impl std::error::Error for PaddingError {}
+
+/// Event source that grows payloads to a target serialized size.
+///
+/// Filler goes where the source would naturally carry bulk: CloudTrail
+/// `additionalEventData`, Okta `debugContext.debugData`, and Databricks
+/// `request_params.commandText`. Payloads already at or above the target are
+/// left unchanged.
+pub struct PaddedEventSource {
+    source: Box<dyn EventSource>,
+    target_bytes: Option<usize>,
+    source_target_bytes: HashMap<String, usize>,
+    jitter: f64,
+    rng: StdRng,
+}
+
+impl PaddedEventSource {
+    pub fn new(
+        source: Box<dyn EventSource>,
+        config: &PaddingConfig,
+        seed: Option<u64>,
+    ) -> Result<Self, PaddingError> {
+        let jitter = config.jitter.unwrap_or(0.0);
+        if !(0.0..1.0).contains(&jitter) {
+            return Err(PaddingError(format!(
+                "jitter must be in [0, 1), got {jitter}"
+            )));
+        }
+        let source_target_bytes = config.source_target_bytes.clone().unwrap_or_default();
+        if config.target_bytes == Some(0) || source_target_bytes.values().any(|bytes| *bytes == 0) {
+            return Err(PaddingError("target bytes must be positive".to_string()));
+        }
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Self {
+            source,
+            target_bytes: config.target_bytes,
+            source_target_bytes,
+            jitter,
+            rng,
+        })
+    }
+
+    fn target_for(&mut self, source: &str) -> Option<usize> {
+        let target = self
+            .source_target_bytes
+            .get(source)
+            .copied()
+            .or(self.target_bytes)?;
+        if self.jitter == 0.0 {
+            return Some(target);
+        }
+        let scale = self.rng.gen_range(1.0 - self.jitter..=1.0 + self.jitter);
+        Some((target as f64 * scale).round() as usize)
+    }
+}
+
+impl EventSource for PaddedEventSource {
+    fn next_event(&mut self) -> Option<Event> {
+        let mut event = self.source.next_event()?;
+        if let Some(target) = self.target_for(&event.envelope.source) {
+            pad_payload(
+                &mut event.payload,
+                &event.envelope.source,
+                target,
+                &mut self.rng,
+            );
+        }
+        Some(event)
+    }
+}
+
+/// Pads `payload` in place so its JSON encoding is close to `target` bytes.
+pub fn pad_payload(payload: &mut Value, source: &str, target: usize, rng: &mut impl Rng) {
+    let size = encoded_len(payload);
+    if size >= target {
+        return;
+    }
+    let Some(object) = payload.as_object_mut() else {
+        return;
+    };
+    match source {
+        "cloudtrail" => {
+            let data = child_object(object, "additionalEventData");
+            for (key, value) in [
+                ("SignatureVersion", json!("SigV4")),
+                ("CipherSuite", json!("ECDHE-RSA-AES128-GCM-SHA256")),
+                ("AuthenticationMethod", json!("AuthHeader")),
+            ] {
+                data.entry(key).or_insert(value);
+            }
+            data.insert("x-amz-id-2".to_string(), json!(""));
+            let need = target.saturating_sub(encoded_len(payload));
+            set_filler(payload, "/additionalEventData/x-amz-id-2", |filler| {
+                filler.push_str(&random_chars(rng, BASE64, need));
+            });
+        }
+        "okta_system_log" => {
+            let data = child_object(child_object(object, "debugContext"), "debugData");
+            data.insert("url".to_string(), json!("/idp/idx/introspect?stateHandle="));
+            let need = target.saturating_sub(encoded_len(payload));
+            set_filler(payload, "/debugContext/debugData/url", |filler| {
+                filler.push_str(&random_chars(rng, BASE64, need).replace(['+', '/'], "-"));
+            });
+        }
+        "databricks_audit" => {
+            let params = child_object(object, "request_params");
+            let prefix = "SELECT * FROM main.analytics.events WHERE request_id IN (";
+            params.insert("commandText".to_string(), json!(format!("{prefix})")));
+            let mut need = target.saturating_sub(encoded_len(payload));
+            set_filler(payload, "/request_params/commandText", |text| {
+                text.pop();
+                let mut first = true;
+                while need >= 20 {
+                    if !first {
+                        text.push_str(", ");
+                    }
+                    text.push('\'');
+                    text.push_str(&random_chars(rng, HEX, 16));
+                    text.push('\'');
+                    need -= if first { 18 } else { 20 };
+                    first = false;
+                }
+                text.push(')');
+            });
+        }
+        _ => {
+            object.insert("padding".to_string(), json!(""));
+            let need = target.saturating_sub(encoded_len(payload));
+            set_filler(payload, "/padding", |filler| {
+                filler.push_str(&random_chars(rng, BASE64, need));
+            });
+        }
+    }
+}
+
+fn encoded_len(value: &Value) -> usize {
+    serde_json::to_vec(value)
+        .map(|bytes| bytes.len())
+        .unwrap_or(0)
+}
+
+/// Returns the object at `key`, replacing a missing or non-object value.
+fn child_object<'a>(object: &'a mut Map<String, Value>, key: &str) -> &'a mut Map<String, Value> {
+    let value = object
+        .entry(key)
+        .or_insert_with(|| Value::Object(Map::new()));
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    value.as_object_mut().expect("object")
+}
+
+fn set_filler(payload: &mut Value, pointer: &str, fill: impl FnOnce(&mut String)) {
+    if let Some(Value::String(text)) = payload.pointer_mut(pointer) {
+        fill(text);
+    }
+}
+
+fn random_chars(rng: &mut impl Rng, alphabet: &[u8], len: usize) -> String {
+    (0..len)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())] as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+
+    struct Fixed(Vec<Event>);
+
+    impl EventSource for Fixed {
+        fn next_event(&mut self) -> Option<Event> {
+            self.0.pop()
+        }
+    }
+
+    #[test]
+    fn pads_payloads_to_source_targets() {
+        let events = vec![
+            test_event("other", "test", json!({ "id": 1 })),
+            test_event(
+                "databricks_audit",
+                "runCommand",
+                json!({ "action_name": "runCommand", "request_params": {} }),
+            ),
+            test_event(
+                "okta_system_log",
+                "user.session.start",
+                json!({ "eventType": "user.session.start", "debugContext": { "debugData": {} } }),
+            ),
+            test_event(
+                "cloudtrail",
+                "GetObject",
+                json!({ "eventName": "GetObject" }),
+            ),
+        ];
+        let config = PaddingConfig {
+            target_bytes: Some(600),
+            source_target_bytes: Some(HashMap::from([("cloudtrail".to_string(), 2048)])),
+            jitter: None,
+        };
+        let mut source =
+            PaddedEventSource::new(Box::new(Fixed(events)), &config, Some(1)).expect("padding");
+
+        let cloudtrail = source.next_event().unwrap();
+        assert_eq!(encoded_len(&cloudtrail.payload), 2048);
+        assert_eq!(
+            cloudtrail.payload["additionalEventData"]["SignatureVersion"],
+            "SigV4"
+        );
+        let okta = source.next_event().unwrap();
+        assert_eq!(encoded_len(&okta.payload), 600);
+        assert!(okta.payload["debugContext"]["debugData"]["url"]
+            .as_str()
+            .unwrap()
+            .starts_with("/idp/idx/introspect?stateHandle="));
+        let databricks = source.next_event().unwrap();
+        let size = encoded_len(&databricks.payload);
+        assert!((580..=600).contains(&size), "{size}");
+        assert!(databricks.payload["request_params"]["commandText"]
+            .as_str()
+            .unwrap()
+            .ends_with("')"));
+        let other = source.next_event().unwrap();
+        assert_eq!(encoded_len(&other.payload), 600);
+
+        let mut big = json!({ "eventName": "x".repeat(700) });
+        pad_payload(&mut big, "cloudtrail", 600, &mut StdRng::seed_from_u64(1));
+        assert!(big.get("additionalEventData").is_none());
+
+        assert!(PaddedEventSource::new(
+            Box::new(Fixed(Vec::new())),
+            &PaddingConfig {
+                jitter: Some(1.5),
+                ..config
+            },
+            None
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,369 @@
+use crate::core::config::{RedactionAction, RedactionConfig, RedactionFieldConfig};
+use crate::core::event::{Event, EventEnvelope};
+use crate::core::traits::EventSource;
+use crate::core::util::hex;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+
+/// Envelope fields that are optional and can therefore be dropped.
+const DROPPABLE_ENVELOPE_FIELDS: &[&str] = &[
+    "actor.name",
+    "target",
+    "target.name",
+    "geo",
+    "geo.region",
+    "geo.city",
+    "geo.lat",
+    "geo.lon",
+    "ip",
+    "user_agent",
+    "session_id",
+    "tenant_id",
+];
+
+/// Hex characters kept from each salted digest.
+const HASH_CHARS: usize = 16;
+
+/// Error for an invalid `[redaction]` table.
+#[derive(Debug)]
+pub struct RedactionError(String);
+
+impl std::fmt::Display for RedactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid redaction config: {}", self.0)
+    }
+}
+
+impl std::error::Error for RedactionError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Root {
+    Envelope,
+    Payload,
+}
+
+struct Rule {
+    root: Root,
+    path: Vec<String>,
+    action: RedactionAction,
+    sources: Option<Vec<String>>,
+    keep: usize,
+    ipv4_prefix: u8,
+    ipv6_prefix: u8,
+}
+
+impl Rule {
+    fn new(config: &RedactionFieldConfig) -> Result<Self, RedactionError> {
+        let mut segments = config.path.split('.').map(str::to_string);
+        let root = match segments.next().as_deref() {
+            Some("envelope") => Root::Envelope,
+            Some("payload") => Root::Payload,
+            _ => {
+                return Err(RedactionError(format!(
+                    "path {} must start with payload. or envelope.",
+                    config.path
+                )))
+            }
+        };
+        let path = segments.collect::<Vec<_>>();
+        if path.is_empty() || path.iter().any(String::is_empty) {
+            return Err(RedactionError(format!(
+                "path {} must name a field",
+                config.path
+            )));
+        }
+        if root == Root::Envelope
+            && config.action == RedactionAction::Drop
+            && !DROPPABLE_ENVELOPE_FIELDS.contains(&path.join(".").as_str())
+        {
+            return Err(RedactionError(format!(
+                "{} is a required envelope field and cannot be dropped",
+                config.path
+            )));
+        }
+        let ipv4_prefix = config.ipv4_prefix.unwrap_or(24);
+        let ipv6_prefix = config.ipv6_prefix.unwrap_or(48);
+        if ipv4_prefix > 32 || ipv6_prefix > 128 {
+            return Err(RedactionError(format!(
+                "{}: ipv4_prefix must be at most 32 and ipv6_prefix at most 128",
+                config.path
+            )));
+        }
+        Ok(Self {
+            root,
+            path,
+            action: config.action,
+            sources: config.sources.clone(),
+            keep: config.keep.unwrap_or(0),
+            ipv4_prefix,
+            ipv6_prefix,
+        })
+    }
+
+    fn applies_to(&self, source: &str) -> bool {
+        self.sources
+            .as_ref()
+            .is_none_or(|sources| sources.iter().any(|name| name == source))
+    }
+}
+
+/// Event source that hashes, masks, or drops configured fields.
+///
+/// Rules address fields by dotted path under `payload` or `envelope`. Only
+/// string values are rewritten; `drop` removes a field of any type. Hashes are
+/// salted and deterministic, so the same user or host still correlates across
+/// events and sources after redaction.
+pub struct RedactedEventSource {
+    source: Box<dyn EventSource>,
+    rules: Vec<Rule>,
+    salt: String,
+}
+
+impl RedactedEventSource {
+    pub fn new(
+        source: Box<dyn EventSource>,
+        config: &RedactionConfig,
+    ) -> Result<Self, RedactionError> {
+        let rules = config
+            .fields
+            .iter()
+            .map(Rule::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            source,
+            rules,
+            salt: config.salt.clone().unwrap_or_default(),
+        })
+    }
+
+    fn redact(&self, event: &mut Event) {
+        let source = event.envelope.source.clone();
+        let rules = self
+            .rules
+            .iter()
+            .filter(|rule| rule.applies_to(&source))
+            .collect::<Vec<_>>();
+        for rule in rules.iter().filter(|rule| rule.root == Root::Payload) {
+            redact_value(&mut event.payload, &rule.path, rule, &self.salt);
+        }
+        if !rules.iter().any(|rule| rule.root == Root::Envelope) {
+            return;
+        }
+        let Ok(mut envelope) = serde_json::to_value(&event.envelope) else {
+            return;
+        };
+        for rule in rules.iter().filter(|rule| rule.root == Root::Envelope) {
+            redact_value(&mut envelope, &rule.path, rule, &self.salt);
+        }
+        if let Ok(envelope) = serde_json::from_value::<EventEnvelope>(envelope) {
+            event.envelope = envelope;
+        }
+    }
+}
+
+impl EventSource for RedactedEventSource {
+    fn next_event(&mut self) -> Option<Event> {
+        let mut event = self.source.next_event()?;
+        self.redact(&mut event);
+        Some(event)
+    }
+}
+
+fn redact_value(value: &mut Value, path: &[String], rule: &Rule, salt: &str) {
+    let Some((key, rest)) = path.split_first() else {
+        return;
+    };
+    let drop = rest.is_empty() && rule.action == RedactionAction::Drop;
+    match value {
+        Value::Object(map) if drop => {
+            if key == "*" {
+                map.clear();
+            } else {
+                map.remove(key);
+            }
+        }
+        Value::Object(map) if key == "*" => {
+            for child in map.values_mut() {
+                redact_child(child, rest, rule, salt);
+            }
+        }
+        Value::Object(map) => {
+            if let Some(child) = map.get_mut(key) {
+                redact_child(child, rest, rule, salt);
+            }
+        }
+        Value::Array(items) if key == "*" && drop => items.clear(),
+        Value::Array(items) if key == "*" => {
+            for child in items {
+                redact_child(child, rest, rule, salt);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn redact_child(child: &mut Value, rest: &[String], rule: &Rule, salt: &str) {
+    if !rest.is_empty() {
+        redact_value(child, rest, rule, salt);
+        return;
+    }
+    let Value::String(text) = child else {
+        return;
+    };
+    match rule.action {
+        RedactionAction::Hash => *text = hash_value(salt, text),
+        RedactionAction::Mask => *text = mask_value(text, rule.keep),
+        RedactionAction::AnonymizeIp => {
+            if let Some(ip) = anonymize_ip(text, rule.ipv4_prefix, rule.ipv6_prefix) {
+                *text = ip;
+            }
+        }
+        RedactionAction::Drop => {}
+    }
+}
+
+fn hash_value(salt: &str, value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update([0]);
+    hasher.update(value.as_bytes());
+    let mut digest = hex(&hasher.finalize());
+    digest.truncate(HASH_CHARS);
+    digest
+}
+
+fn mask_value(value: &str, keep: usize) -> String {
+    let len = value.chars().count();
+    let masked = len.saturating_sub(keep);
+    "*".repeat(masked) + &value.chars().skip(masked).collect::<String>()
+}
+
+/// Zeroes host bits; returns `None` for values that are not IP addresses
+/// (e.g. CloudTrail's `AWS Internal` or a service principal).
+fn anonymize_ip(value: &str, ipv4_prefix: u8, ipv6_prefix: u8) -> Option<String> {
+    let ip = match value.parse::<IpAddr>().ok()? {
+        IpAddr::V4(ip) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(ipv4_prefix))
+                .unwrap_or(0);
+            IpAddr::from((u32::from(ip) & mask).to_be_bytes())
+        }
+        IpAddr::V6(ip) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(ipv6_prefix))
+                .unwrap_or(0);
+            IpAddr::from((u128::from(ip) & mask).to_be_bytes())
+        }
+    };
+    Some(ip.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    struct Fixed(Vec<Event>);
+
+    impl EventSource for Fixed {
+        fn next_event(&mut self) -> Option<Event> {
+            self.0.pop()
+        }
+    }
+
+    fn rule(path: &str, action: RedactionAction) -> RedactionFieldConfig {
+        RedactionFieldConfig {
+            path: path.to_string(),
+            action,
+            sources: None,
+            keep: None,
+            ipv4_prefix: None,
+            ipv6_prefix: None,
+        }
+    }
+
+    #[test]
+    fn redacts_payload_and_envelope_fields() {
+        let payload = json!({
+            "sourceIPAddress": "203.0.113.77",
+            "userIdentity": { "userName": "alice", "accessKeyId": "AKIAEXAMPLE1234" },
+            "resources": [{ "ARN": "arn:aws:s3:::a" }, { "ARN": "arn:aws:s3:::b" }],
+            "requestParameters": { "password": "hunter2" }
+        });
+        let events = [
+            (
+                "okta_system_log",
+                json!({ "client": { "ipAddress": "2001:db8:1:2::9" } }),
+            ),
+            ("cloudtrail", payload),
+        ]
+        .into_iter()
+        .map(|(source, payload)| {
+            let mut event = test_event(source, "test", payload)
+                .by("alice", "human")
+                .with_ip("203.0.113.77");
+            event.envelope.actor.name = Some("Alice Example".to_string());
+            event
+        })
+        .collect::<Vec<_>>();
+        let config = RedactionConfig {
+            salt: Some("pepper".to_string()),
+            fields: vec![
+                rule("payload.sourceIPAddress", RedactionAction::AnonymizeIp),
+                rule("payload.client.ipAddress", RedactionAction::AnonymizeIp),
+                rule("payload.userIdentity.userName", RedactionAction::Hash),
+                RedactionFieldConfig {
+                    keep: Some(4),
+                    ..rule("payload.userIdentity.accessKeyId", RedactionAction::Mask)
+                },
+                rule("payload.resources.*.ARN", RedactionAction::Hash),
+                rule("payload.requestParameters.password", RedactionAction::Drop),
+                rule("envelope.actor.id", RedactionAction::Hash),
+                RedactionFieldConfig {
+                    sources: Some(vec!["cloudtrail".to_string()]),
+                    ..rule("envelope.actor.name", RedactionAction::Drop)
+                },
+                rule("envelope.ip", RedactionAction::AnonymizeIp),
+            ],
+        };
+        let mut source =
+            RedactedEventSource::new(Box::new(Fixed(events)), &config).expect("redaction");
+
+        let cloudtrail = source.next_event().unwrap();
+        let payload = &cloudtrail.payload;
+        assert_eq!(payload["sourceIPAddress"], "203.0.113.0");
+        let user = payload["userIdentity"]["userName"].as_str().unwrap();
+        assert_eq!(user, hash_value("pepper", "alice"));
+        assert_eq!(user.len(), HASH_CHARS);
+        assert_eq!(payload["userIdentity"]["accessKeyId"], "***********1234");
+        assert_ne!(
+            payload["resources"][0]["ARN"],
+            payload["resources"][1]["ARN"]
+        );
+        assert_eq!(payload["requestParameters"], json!({}));
+        assert_eq!(cloudtrail.envelope.actor.id, user);
+        assert_eq!(cloudtrail.envelope.actor.name, None);
+        assert_eq!(cloudtrail.envelope.ip.as_deref(), Some("203.0.113.0"));
+
+        let okta = source.next_event().unwrap();
+        assert_eq!(okta.payload["client"]["ipAddress"], "2001:db8:1::");
+        assert_eq!(okta.envelope.actor.name.as_deref(), Some("Alice Example"));
+
+        for bad in [
+            rule("userIdentity.userName", RedactionAction::Hash),
+            rule("envelope.timestamp", RedactionAction::Drop),
+            RedactionFieldConfig {
+                ipv4_prefix: Some(33),
+                ..rule("payload.sourceIPAddress", RedactionAction::AnonymizeIp)
+            },
+        ] {
+            let config = RedactionConfig {
+                salt: None,
+                fields: vec![bad],
+            };
+            assert!(RedactedEventSource::new(Box::new(Fixed(Vec::new())), &config).is_err());
+        }
+    }
+}
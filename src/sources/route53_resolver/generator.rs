@@ -0,0 +1,598 @@
+use super::model::{ResolverAnswer, ResolverQueryLogRecord, ResolverSourceIds};
+use crate::core::config::ResolverQueryLogConfig;
+use crate::core::event::{Event, EventEnvelope, EventLabel, Outcome, Target};
+use crate::core::traits::EventSource;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+
+const DEFAULT_EXTERNAL_LOOKUP_RATE: f64 = 0.3;
+const DEFAULT_CACHE_TTL_SECONDS: u64 = 60;
+/// Chance that a client also asks for the AAAA record of a name.
+const AAAA_RATE: f64 = 0.2;
+
+/// Third-party names workloads commonly resolve: package registries, source
+/// hosting, telemetry, and OS updates.
+const DEFAULT_DOMAINS: &[(&str, f64)] = &[
+    ("api.github.com", 4.0),
+    ("github.com", 3.0),
+    ("objects.githubusercontent.com", 2.0),
+    ("registry-1.docker.io", 5.0),
+    ("auth.docker.io", 2.0),
+    ("production.cloudflare.docker.com", 3.0),
+    ("pypi.org", 3.0),
+    ("files.pythonhosted.org", 3.0),
+    ("registry.npmjs.org", 3.0),
+    ("repo1.maven.org", 1.0),
+    ("proxy.golang.org", 1.0),
+    ("api.datadoghq.com", 4.0),
+    ("http-intake.logs.datadoghq.com", 4.0),
+    ("sentry.io", 2.0),
+    ("hooks.slack.com", 1.0),
+    ("api.stripe.com", 1.0),
+    ("oauth2.googleapis.com", 1.0),
+    ("www.googleapis.com", 2.0),
+    ("login.microsoftonline.com", 1.0),
+    ("security.ubuntu.com", 2.0),
+    ("archive.ubuntu.com", 2.0),
+    ("dl-cdn.alpinelinux.org", 1.0),
+];
+
+/// Placeholder command-and-control names; real runs should list the
+/// indicators their detections look for.
+const DEFAULT_KNOWN_BAD_DOMAINS: &[&str] = &[
+    "update-check.cdn-statics.top",
+    "telemetry.secure-sync.xyz",
+    "api.pastebin-mirror.cc",
+    "login-verify.account-portal.info",
+    "c2.dropzone-files.ru",
+];
+
+const DGA_TLDS: &[&str] = &["com", "net", "org", "info", "biz", "xyz", "top", "ru"];
+/// First octets for third-party answer addresses.
+const EXTERNAL_PREFIXES: &[u8] = &[13, 18, 34, 35, 104, 140, 151, 185];
+/// First two octets for AWS endpoint answer addresses.
+const AWS_PREFIXES: &[(u8, u8)] = &[(52, 94), (52, 119), (54, 239), (3, 5), (52, 216)];
+
+/// Event source that adds Route 53 Resolver query logs to CloudTrail workload
+/// activity.
+///
+/// Calls made by assumed-role sessions outside the console (the workload
+/// identities service actors use) are preceded by a lookup of the service
+/// endpoint from the workload's private VPC address, and sometimes by a lookup
+/// of a third-party domain. A configurable share of those third-party lookups
+/// goes to DGA-looking names (NXDOMAIN) or known-bad domains; both are labeled
+/// for detection testing. Workloads cache answers, so a name is looked up
+/// again only after `cache_ttl_seconds`.
+pub struct ResolverQueryLogSource {
+    source: Box<dyn EventSource>,
+    include_cloudtrail: bool,
+    external_lookup_rate: f64,
+    domains: Vec<(String, f64)>,
+    total_weight: f64,
+    dga_share: f64,
+    known_bad_share: f64,
+    known_bad_domains: Vec<String>,
+    cache_ttl: Duration,
+    /// Last lookup time per source address and name.
+    cache: HashMap<(String, String), DateTime<Utc>>,
+    pending: VecDeque<Event>,
+    /// Latest time emitted so far; lookups never go back before it.
+    last_time: Option<DateTime<Utc>>,
+    rng: StdRng,
+}
+
+/// Name a workload resolves before or alongside an API call.
+struct Lookup {
+    name: String,
+    rcode: &'static str,
+    answer: Option<String>,
+    label: Option<EventLabel>,
+}
+
+impl ResolverQueryLogSource {
+    pub fn new(
+        source: Box<dyn EventSource>,
+        config: &ResolverQueryLogConfig,
+        seed: Option<u64>,
+    ) -> Result<Self, String> {
+        let external_lookup_rate = config
+            .external_lookup_rate
+            .unwrap_or(DEFAULT_EXTERNAL_LOOKUP_RATE);
+        let dga_share = config.dga_share();
+        let known_bad_share = config.known_bad_share();
+        for (field, value) in [
+            ("external_lookup_rate", external_lookup_rate),
+            ("dga_share", dga_share),
+            ("known_bad_share", known_bad_share),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{field} must be between 0.0 and 1.0"));
+            }
+        }
+        if dga_share + known_bad_share > 1.0 {
+            return Err("dga_share and known_bad_share must add up to at most 1.0".to_string());
+        }
+        let domains = match &config.domains {
+            Some(domains) => domains
+                .iter()
+                .map(|domain| (domain.name.trim().to_string(), domain.weight))
+                .collect::<Vec<_>>(),
+            None => DEFAULT_DOMAINS
+                .iter()
+                .map(|(name, weight)| (name.to_string(), *weight))
+                .collect(),
+        };
+        if domains.is_empty()
+            || domains
+                .iter()
+                .any(|(name, weight)| name.is_empty() || !weight.is_finite() || *weight <= 0.0)
+        {
+            return Err("domains need at least one name with a weight greater than 0".to_string());
+        }
+        let known_bad_domains = match &config.known_bad_domains {
+            Some(domains) => domains.clone(),
+            None => DEFAULT_KNOWN_BAD_DOMAINS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        };
+        if known_bad_share > 0.0 && known_bad_domains.is_empty() {
+            return Err("known_bad_domains must not be empty".to_string());
+        }
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Ok(Self {
+            source,
+            include_cloudtrail: config.include_cloudtrail.unwrap_or(true),
+            external_lookup_rate,
+            total_weight: domains.iter().map(|(_, weight)| weight).sum(),
+            domains,
+            dga_share,
+            known_bad_share,
+            known_bad_domains,
+            cache_ttl: Duration::seconds(
+                config
+                    .cache_ttl_seconds
+                    .unwrap_or(DEFAULT_CACHE_TTL_SECONDS)
+                    .min(u64::from(u32::MAX)) as i64,
+            ),
+            cache: HashMap::new(),
+            pending: VecDeque::new(),
+            last_time: None,
+            rng,
+        })
+    }
+
+    /// Query log events for the lookups a CloudTrail event implies, in time order.
+    fn query_events(&mut self, cloudtrail: &Event) -> Vec<Event> {
+        if cloudtrail.envelope.source != "cloudtrail" {
+            return Vec::new();
+        }
+        let payload = &cloudtrail.payload;
+        let text = |pointer: &str| {
+            payload
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty())
+        };
+        let Some(time) = text("/eventTime")
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Utc))
+        else {
+            return Vec::new();
+        };
+        let workload = text("/userIdentity/arn").or_else(|| text("/userIdentity/principalId"));
+        let source_ip = text("/sourceIPAddress").unwrap_or_default();
+        let in_vpc = text("/userIdentity/type") == Some("AssumedRole")
+            && source_ip != "AWS Internal"
+            && !text("/userAgent").is_some_and(|agent| agent.starts_with("Mozilla/"));
+        let (Some(workload), Some(event_source), true) = (workload, text("/eventSource"), in_vpc)
+        else {
+            self.last_time = self.last_time.max(Some(time));
+            return Vec::new();
+        };
+        let region = text("/awsRegion").unwrap_or("us-east-1");
+        let account_id = text("/recipientAccountId").unwrap_or("000000000000");
+        let srcaddr = if is_private(source_ip) {
+            source_ip.to_string()
+        } else {
+            private_address(workload)
+        };
+
+        let mut lookups = Vec::new();
+        if let Some(endpoint) =
+            api_endpoint(event_source, region, text("/requestParameters/bucketName"))
+        {
+            lookups.push(Lookup {
+                answer: Some(aws_address(&endpoint)),
+                name: endpoint,
+                rcode: "NOERROR",
+                label: None,
+            });
+        }
+        if self.rng.gen_bool(self.external_lookup_rate) {
+            lookups.push(self.external_lookup());
+        }
+
+        let lookup_time = (time - Duration::milliseconds(self.rng.gen_range(2..150)))
+            .max(self.last_time.unwrap_or(time).min(time));
+        self.last_time = self.last_time.max(Some(time));
+        let context = QueryContext {
+            cloudtrail,
+            time: lookup_time,
+            account_id,
+            region,
+            vpc_id: hex_id("vpc-", &format!("{account_id}:{region}")),
+            instance: hex_id("i-", workload),
+            srcaddr: &srcaddr,
+        };
+        let mut events = Vec::new();
+        for lookup in lookups {
+            // Cached answers need no query; flagged lookups always go out.
+            if lookup.label.is_none() {
+                let key = (srcaddr.clone(), lookup.name.clone());
+                match self.cache.get(&key) {
+                    Some(last) if lookup_time - *last < self.cache_ttl => continue,
+                    _ => {
+                        self.cache.insert(key, lookup_time);
+                    }
+                }
+            }
+            events.extend(self.query_event(&context, &lookup, "A"));
+            if self.rng.gen_bool(AAAA_RATE) {
+                events.extend(self.query_event(&context, &lookup, "AAAA"));
+            }
+        }
+        events
+    }
+
+    fn external_lookup(&mut self) -> Lookup {
+        let draw = self.rng.gen_range(0.0..1.0);
+        if draw < self.dga_share {
+            return Lookup {
+                name: dga_name(&mut self.rng),
+                rcode: "NXDOMAIN",
+                answer: None,
+                label: Some(label(
+                    ResolverQueryLogConfig::DGA_SCENARIO_ID,
+                    ResolverQueryLogConfig::DGA_TECHNIQUE_ID,
+                )),
+            };
+        }
+        if draw < self.dga_share + self.known_bad_share {
+            let name =
+                self.known_bad_domains[self.rng.gen_range(0..self.known_bad_domains.len())].clone();
+            return Lookup {
+                answer: Some(external_address(&name)),
+                name,
+                rcode: "NOERROR",
+                label: Some(label(
+                    ResolverQueryLogConfig::KNOWN_BAD_SCENARIO_ID,
+                    ResolverQueryLogConfig::KNOWN_BAD_TECHNIQUE_ID,
+                )),
+            };
+        }
+        let mut point = self.rng.gen_range(0.0..self.total_weight);
+        let mut name = &self.domains[self.domains.len() - 1].0;
+        for (domain, weight) in &self.domains {
+            if point < *weight {
+                name = domain;
+                break;
+            }
+            point -= weight;
+        }
+        Lookup {
+            answer: Some(external_address(name)),
+            name: name.clone(),
+            rcode: "NOERROR",
+            label: None,
+        }
+    }
+
+    fn query_event(
+        &mut self,
+        context: &QueryContext,
+        lookup: &Lookup,
+        query_type: &str,
+    ) -> Option<Event> {
+        // Dual-stack lookups of IPv4-only names come back empty.
+        let answers = match (&lookup.answer, query_type) {
+            (Some(address), "A") => vec![ResolverAnswer {
+                rdata: address.clone(),
+                answer_type: "A".to_string(),
+                class: "IN".to_string(),
+            }],
+            _ => Vec::new(),
+        };
+        let record = ResolverQueryLogRecord {
+            version: "1.100000".to_string(),
+            account_id: context.account_id.to_string(),
+            region: context.region.to_string(),
+            vpc_id: context.vpc_id.clone(),
+            query_timestamp: context.time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            query_name: format!("{}.", lookup.name),
+            query_type: query_type.to_string(),
+            query_class: "IN".to_string(),
+            rcode: lookup.rcode.to_string(),
+            answers,
+            srcaddr: context.srcaddr.to_string(),
+            srcport: self.rng.gen_range(1024..65536).to_string(),
+            transport: "UDP".to_string(),
+            srcids: ResolverSourceIds {
+                instance: context.instance.clone(),
+            },
+        };
+        let envelope = &context.cloudtrail.envelope;
+        Some(Event {
+            envelope: EventEnvelope {
+                schema_version: envelope.schema_version.clone(),
+                timestamp: context.time.to_rfc3339_opts(SecondsFormat::Millis, true),
+                source: "route53_resolver".to_string(),
+                event_type: query_type.to_string(),
+                actor: envelope.actor.clone(),
+                target: Some(Target {
+                    id: lookup.name.clone(),
+                    kind: "domain".to_string(),
+                    name: Some(lookup.name.clone()),
+                }),
+                outcome: if lookup.rcode == "NOERROR" {
+                    Outcome::Success
+                } else {
+                    Outcome::Failure
+                },
+                geo: None,
+                ip: Some(context.srcaddr.to_string()),
+                user_agent: None,
+                session_id: None,
+                tenant_id: envelope.tenant_id.clone(),
+                tenant: None,
+            },
+            payload: serde_json::to_value(&record).ok()?,
+            label: lookup.label.clone(),
+        })
+    }
+}
+
+/// Fields shared by the queries behind one CloudTrail event.
+struct QueryContext<'a> {
+    cloudtrail: &'a Event,
+    time: DateTime<Utc>,
+    account_id: &'a str,
+    region: &'a str,
+    vpc_id: String,
+    instance: String,
+    srcaddr: &'a str,
+}
+
+impl EventSource for ResolverQueryLogSource {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            let event = self.source.next_event()?;
+            let queries = self.query_events(&event);
+            self.pending.extend(queries);
+            if self.include_cloudtrail || event.envelope.source != "cloudtrail" {
+                self.pending.push_back(event);
+            }
+        }
+    }
+}
+
+/// Regional endpoint a call to `event_source` resolves; S3 calls that name a
+/// bucket use the virtual-hosted bucket endpoint.
+fn api_endpoint(event_source: &str, region: &str, bucket: Option<&str>) -> Option<String> {
+    let service = event_source.strip_suffix(".amazonaws.com")?;
+    let endpoint = match (service, bucket) {
+        ("iam" | "organizations" | "route53" | "cloudfront", _) => event_source.to_string(),
+        ("s3", Some(bucket)) => format!("{bucket}.s3.{region}.amazonaws.com"),
+        _ => format!("{service}.{region}.amazonaws.com"),
+    };
+    Some(endpoint)
+}
+
+fn label(scenario_id: &str, technique_id: &str) -> EventLabel {
+    EventLabel {
+        scenario_id: scenario_id.to_string(),
+        technique_ids: vec![technique_id.to_string()],
+    }
+}
+
+/// Random lowercase label with a common DGA top-level domain.
+fn dga_name(rng: &mut impl Rng) -> String {
+    let len = rng.gen_range(10..=18);
+    let label = (0..len)
+        .map(|_| char::from(b'a' + rng.gen_range(0..26)))
+        .collect::<String>();
+    format!("{label}.{}", DGA_TLDS[rng.gen_range(0..DGA_TLDS.len())])
+}
+
+fn is_private(address: &str) -> bool {
+    match address.parse::<std::net::Ipv4Addr>() {
+        Ok(address) => address.is_private(),
+        Err(_) => false,
+    }
+}
+
+/// Stable VPC address for a workload without one in CloudTrail.
+fn private_address(workload: &str) -> String {
+    let hash = stable_hash(&format!("srcaddr:{workload}"));
+    format!(
+        "10.{}.{}.{}",
+        hash & 0xff,
+        (hash >> 8) & 0xff,
+        ((hash >> 16) % 250) + 4
+    )
+}
+
+fn aws_address(name: &str) -> String {
+    let hash = stable_hash(name);
+    let (first, second) = AWS_PREFIXES[(hash % AWS_PREFIXES.len() as u64) as usize];
+    format!(
+        "{first}.{second}.{}.{}",
+        (hash >> 8) & 0xff,
+        ((hash >> 16) % 254) + 1
+    )
+}
+
+fn external_address(name: &str) -> String {
+    let hash = stable_hash(name);
+    let first = EXTERNAL_PREFIXES[(hash % EXTERNAL_PREFIXES.len() as u64) as usize];
+    format!(
+        "{first}.{}.{}.{}",
+        (hash >> 8) & 0xff,
+        (hash >> 16) & 0xff,
+        ((hash >> 24) % 254) + 1
+    )
+}
+
+/// `prefix` followed by 17 hex characters derived from `seed`.
+fn hex_id(prefix: &str, seed: &str) -> String {
+    let hex = format!(
+        "{:016x}{:016x}",
+        stable_hash(&format!("{prefix}{seed}")),
+        stable_hash(&format!("{seed}{prefix}"))
+    );
+    format!("{prefix}{}", &hex[..17])
+}
+
+fn stable_hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    struct QueueSource(VecDeque<Event>);
+
+    impl EventSource for QueueSource {
+        fn next_event(&mut self) -> Option<Event> {
+            self.0.pop_front()
+        }
+    }
+
+    fn cloudtrail(time: &str, name: &str, identity_type: &str) -> Event {
+        test_event(
+            "cloudtrail",
+            name,
+            json!({
+                "eventTime": time,
+                "eventSource": "s3.amazonaws.com",
+                "eventName": name,
+                "awsRegion": "us-west-2",
+                "sourceIPAddress": "3.5.140.7",
+                "userAgent": "aws-sdk-go/1.44.0",
+                "userIdentity": {
+                    "type": identity_type,
+                    "arn": "arn:aws:sts::123456789012:assumed-role/etl/svc-etl",
+                },
+                "requestParameters": { "bucketName": "etl-bucket" },
+                "recipientAccountId": "123456789012",
+            }),
+        )
+        .at(time)
+        .by("svc-etl", identity_type)
+        .with_ip("3.5.140.7")
+        .in_account("123456789012")
+    }
+
+    #[test]
+    fn workload_calls_resolve_endpoints_and_flag_suspicious_lookups() {
+        let events = VecDeque::from([
+            cloudtrail("2026-02-06T00:00:00.300Z", "GetObject", "AssumedRole"),
+            cloudtrail("2026-02-06T00:00:10.000Z", "PutObject", "AssumedRole"),
+            cloudtrail("2026-02-06T00:01:30.000Z", "GetObject", "AssumedRole"),
+            cloudtrail("2026-02-06T00:02:00.000Z", "GetObject", "IAMUser"),
+        ]);
+        let config = ResolverQueryLogConfig {
+            external_lookup_rate: Some(0.0),
+            ..ResolverQueryLogConfig::default()
+        };
+        let mut source =
+            ResolverQueryLogSource::new(Box::new(QueueSource(events)), &config, Some(5)).unwrap();
+        let events = std::iter::from_fn(|| source.next_event()).collect::<Vec<_>>();
+        let queries = events
+            .iter()
+            .filter(|event| {
+                event.envelope.source == "route53_resolver" && event.envelope.event_type == "A"
+            })
+            .collect::<Vec<_>>();
+        // The second call hits the cached answer; the IAM user is not a workload.
+        assert_eq!(queries.len(), 2);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| event.envelope.source == "cloudtrail")
+                .count(),
+            4
+        );
+        let times = events
+            .iter()
+            .map(|event| event.envelope.timestamp.clone())
+            .collect::<Vec<_>>();
+        assert!(times.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        let record: ResolverQueryLogRecord =
+            serde_json::from_value(queries[0].payload.clone()).unwrap();
+        assert_eq!(record.query_name, "etl-bucket.s3.us-west-2.amazonaws.com.");
+        assert_eq!(record.rcode, "NOERROR");
+        assert_eq!(record.answers.len(), 1);
+        assert!(record.srcaddr.starts_with("10."));
+        assert!(record.vpc_id.starts_with("vpc-") && record.vpc_id.len() == 21);
+        assert_eq!(record.srcids.instance.len(), 19);
+        assert_eq!(record.query_timestamp, "2026-02-06T00:00:00Z");
+
+        let config = ResolverQueryLogConfig {
+            include_cloudtrail: Some(false),
+            external_lookup_rate: Some(1.0),
+            dga_share: Some(0.5),
+            known_bad_share: Some(0.5),
+            known_bad_domains: Some(vec!["bad.example".to_string()]),
+            ..ResolverQueryLogConfig::default()
+        };
+        let events = (0..20)
+            .map(|idx| {
+                cloudtrail(
+                    &format!("2026-02-06T00:00:{idx:02}.500Z"),
+                    "GetObject",
+                    "AssumedRole",
+                )
+            })
+            .collect();
+        let mut source =
+            ResolverQueryLogSource::new(Box::new(QueueSource(events)), &config, Some(5)).unwrap();
+        let events = std::iter::from_fn(|| source.next_event()).collect::<Vec<_>>();
+        assert!(events
+            .iter()
+            .all(|event| event.envelope.source == "route53_resolver"));
+        let flagged = events
+            .iter()
+            .filter_map(|event| Some((event, event.label.as_ref()?)))
+            .collect::<Vec<_>>();
+        assert!(flagged.len() >= 20);
+        for (event, label) in flagged {
+            let name = event.payload["query_name"].as_str().unwrap();
+            if label.scenario_id == ResolverQueryLogConfig::DGA_SCENARIO_ID {
+                assert_eq!(event.payload["rcode"], "NXDOMAIN");
+                assert!(matches!(event.envelope.outcome, Outcome::Failure));
+            } else {
+                assert_eq!(name, "bad.example.");
+                assert_eq!(label.technique_ids, vec!["T1071.004"]);
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod generator;
+pub mod model;
+
+pub use generator::ResolverQueryLogSource;
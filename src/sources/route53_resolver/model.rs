@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// One Route 53 Resolver query log record, in the JSON layout Resolver
+/// delivers to CloudWatch Logs, S3, and Firehose.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolverQueryLogRecord {
+    /// Log format version, `1.100000`.
+    pub version: String,
+    pub account_id: String,
+    pub region: String,
+    /// VPC the query originated in.
+    pub vpc_id: String,
+    /// Query time with second precision, e.g. `2026-02-06T00:00:38Z`.
+    pub query_timestamp: String,
+    /// Queried name with its trailing dot.
+    pub query_name: String,
+    /// Record type, e.g. `A` or `AAAA`.
+    pub query_type: String,
+    pub query_class: String,
+    /// DNS response code, e.g. `NOERROR` or `NXDOMAIN`.
+    pub rcode: String,
+    pub answers: Vec<ResolverAnswer>,
+    /// Private address of the querying resource.
+    pub srcaddr: String,
+    pub srcport: String,
+    pub transport: String,
+    pub srcids: ResolverSourceIds,
+}
+
+/// One answer record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolverAnswer {
+    #[serde(rename = "Rdata")]
+    pub rdata: String,
+    #[serde(rename = "Type")]
+    pub answer_type: String,
+    #[serde(rename = "Class")]
+    pub class: String,
+}
+
+/// Resource that sent the query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResolverSourceIds {
+    /// EC2 instance ID.
+    pub instance: String,
+}
@@ -0,0 +1,347 @@
+use super::model::S3AccessLogRecord;
+use crate::core::config::S3AccessLogConfig;
+use crate::core::event::{Event, EventEnvelope, Outcome, Target};
+use crate::core::traits::EventSource;
+use chrono::DateTime;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+/// Size of the XML error document S3 returns for a failed request.
+const ERROR_BODY_BYTES: u64 = 243;
+
+/// Event source that pairs CloudTrail S3 events with S3 server access log records.
+///
+/// Every S3 event from the child source whose operation has an access log
+/// equivalent (`GetObject`, `PutObject`, ...) and that names a bucket is
+/// followed by an `s3_access_log` event for the same request: same request ID,
+/// requester ARN, source IP, user agent, bucket, key, time, and outcome, so
+/// the two logs join the way they do in AWS. Scenario labels carry over.
+pub struct S3AccessLogSource {
+    source: Box<dyn EventSource>,
+    include_cloudtrail: bool,
+    bucket_owner: Option<String>,
+    pending: Option<Event>,
+    rng: StdRng,
+}
+
+impl S3AccessLogSource {
+    pub fn new(
+        source: Box<dyn EventSource>,
+        config: &S3AccessLogConfig,
+        seed: Option<u64>,
+    ) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Self {
+            source,
+            include_cloudtrail: config.include_cloudtrail.unwrap_or(true),
+            bucket_owner: config.bucket_owner.clone(),
+            pending: None,
+            rng,
+        }
+    }
+
+    fn access_log_event(&mut self, cloudtrail: &Event) -> Option<Event> {
+        let payload = &cloudtrail.payload;
+        let text = |pointer: &str| {
+            payload
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty())
+        };
+        if text("/eventSource") != Some("s3.amazonaws.com") {
+            return None;
+        }
+        let (method, resource) = operation_for(text("/eventName")?)?;
+        let bucket = text("/requestParameters/bucketName")?;
+        let key = text("/requestParameters/key")
+            .filter(|_| resource == "OBJECT")
+            .map(url_encode);
+        let region = text("/awsRegion").unwrap_or("us-east-1");
+        let time = DateTime::parse_from_rfc3339(text("/eventTime")?).ok()?;
+        let error_code = text("/errorCode");
+        let http_status = match error_code {
+            Some(code) => error_status(code),
+            None if method == "DELETE" => 204,
+            None => 200,
+        };
+
+        let object_size = key
+            .as_ref()
+            .filter(|_| matches!(method, "GET" | "PUT" | "HEAD"))
+            .map(|_| object_size(&mut self.rng));
+        let bytes_sent = match (error_code, method) {
+            (Some(_), _) => Some(ERROR_BODY_BYTES),
+            (None, "GET") => object_size,
+            _ => None,
+        };
+        let turn_around_time = self.rng.gen_range(4..40);
+        // Transfer time scales with the payload at roughly 20-80 MB/s.
+        let transfer_bytes = match method {
+            "PUT" => object_size.unwrap_or(0),
+            _ => bytes_sent.unwrap_or(0),
+        };
+        let transfer_time = transfer_bytes / self.rng.gen_range(20_000..80_000);
+        // Requests are virtual-hosted style, so the bucket is in the host header.
+        let request_path = match (&key, resource) {
+            (Some(key), _) => format!("/{key}"),
+            (None, "BUCKETPOLICY") => "/?policy".to_string(),
+            (None, "ACL") => "/?acl".to_string(),
+            (None, _) => "/".to_string(),
+        };
+        let http_method = if method == "COPY" { "PUT" } else { method };
+        let account_id = text("/recipientAccountId").unwrap_or("000000000000");
+
+        let record = S3AccessLogRecord {
+            bucket_owner: self
+                .bucket_owner
+                .clone()
+                .unwrap_or_else(|| canonical_user_id(account_id)),
+            bucket: bucket.to_string(),
+            time: time.format("%d/%b/%Y:%H:%M:%S %z").to_string(),
+            remote_ip: text("/sourceIPAddress").unwrap_or("-").to_string(),
+            requester: text("/userIdentity/arn").map(str::to_string),
+            request_id: text("/requestID").unwrap_or("-").to_string(),
+            operation: format!("REST.{method}.{resource}"),
+            key: key.clone(),
+            request_uri: format!("{http_method} {request_path} HTTP/1.1"),
+            http_status,
+            error_code: error_code.map(str::to_string),
+            bytes_sent,
+            object_size,
+            total_time: Some(turn_around_time + transfer_time + self.rng.gen_range(1..6)),
+            turn_around_time: Some(turn_around_time),
+            referer: None,
+            user_agent: text("/userAgent").map(str::to_string),
+            version_id: None,
+            host_id: host_id(&mut self.rng),
+            signature_version: Some("SigV4".to_string()),
+            cipher_suite: text("/tlsDetails/cipherSuite").map(str::to_string),
+            authentication_type: Some("AuthHeader".to_string()),
+            host_header: format!("{bucket}.s3.{region}.amazonaws.com"),
+            tls_version: text("/tlsDetails/tlsVersion").map(str::to_string),
+            access_point_arn: None,
+            acl_required: None,
+        };
+
+        let envelope = &cloudtrail.envelope;
+        Some(Event {
+            envelope: EventEnvelope {
+                schema_version: envelope.schema_version.clone(),
+                timestamp: envelope.timestamp.clone(),
+                source: "s3_access_log".to_string(),
+                event_type: record.operation.clone(),
+                actor: envelope.actor.clone(),
+                target: Some(Target {
+                    id: match &key {
+                        Some(key) => format!("arn:aws:s3:::{bucket}/{key}"),
+                        None => format!("arn:aws:s3:::{bucket}"),
+                    },
+                    kind: if key.is_some() { "object" } else { "bucket" }.to_string(),
+                    name: Some(bucket.to_string()),
+                }),
+                outcome: if http_status >= 400 {
+                    Outcome::Failure
+                } else {
+                    Outcome::Success
+                },
+                geo: envelope.geo.clone(),
+                ip: envelope.ip.clone(),
+                user_agent: envelope.user_agent.clone(),
+                session_id: envelope.session_id.clone(),
+                tenant_id: envelope.tenant_id.clone(),
+                tenant: None,
+            },
+            payload: serde_json::to_value(&record).ok()?,
+            label: cloudtrail.label.clone(),
+        })
+    }
+}
+
+impl EventSource for S3AccessLogSource {
+    fn next_event(&mut self) -> Option<Event> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+        loop {
+            let event = self.source.next_event()?;
+            let access_log = self.access_log_event(&event);
+            if self.include_cloudtrail || event.envelope.source != "cloudtrail" {
+                self.pending = access_log;
+                return Some(event);
+            }
+            if access_log.is_some() {
+                return access_log;
+            }
+        }
+    }
+}
+
+/// Maps a CloudTrail S3 API name to the access log HTTP method and resource type.
+fn operation_for(event_name: &str) -> Option<(&'static str, &'static str)> {
+    let operation = match event_name {
+        "GetObject" => ("GET", "OBJECT"),
+        "PutObject" => ("PUT", "OBJECT"),
+        "DeleteObject" => ("DELETE", "OBJECT"),
+        "HeadObject" => ("HEAD", "OBJECT"),
+        "CopyObject" => ("COPY", "OBJECT"),
+        "ListObjects" | "ListObjectsV2" => ("GET", "BUCKET"),
+        "CreateBucket" => ("PUT", "BUCKET"),
+        "DeleteBucket" => ("DELETE", "BUCKET"),
+        "GetBucketPolicy" => ("GET", "BUCKETPOLICY"),
+        "PutBucketPolicy" => ("PUT", "BUCKETPOLICY"),
+        "DeleteBucketPolicy" => ("DELETE", "BUCKETPOLICY"),
+        "GetBucketAcl" => ("GET", "ACL"),
+        "PutBucketAcl" => ("PUT", "ACL"),
+        _ => return None,
+    };
+    Some(operation)
+}
+
+fn error_status(code: &str) -> u16 {
+    match code {
+        "AccessDenied" | "AllAccessDisabled" | "InvalidAccessKeyId" | "SignatureDoesNotMatch" => {
+            403
+        }
+        "NoSuchKey" | "NoSuchBucket" | "NoSuchBucketPolicy" => 404,
+        "SlowDown" | "ServiceUnavailable" => 503,
+        "InternalError" => 500,
+        _ => 400,
+    }
+}
+
+/// Object sizes spread log-uniformly between 1 KiB and ~100 MB.
+fn object_size(rng: &mut impl Rng) -> u64 {
+    10f64.powf(rng.gen_range(3.0..8.0)) as u64
+}
+
+/// Percent-encodes an object key the way it appears in the log's `Key` field.
+fn url_encode(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~/!*'()".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// 64 hex characters derived from the account, stable across runs.
+fn canonical_user_id(account_id: &str) -> String {
+    (0..4u8)
+        .map(|salt| format!("{:016x}", stable_hash(&format!("{salt}:{account_id}"))))
+        .collect()
+}
+
+fn host_id(rng: &mut impl Rng) -> String {
+    let mut id = (0..75)
+        .map(|_| char::from(BASE64[rng.gen_range(0..BASE64.len())]))
+        .collect::<String>();
+    id.push('=');
+    id
+}
+
+fn stable_hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+    use std::collections::VecDeque;
+
+    struct QueueSource(VecDeque<Event>);
+
+    impl EventSource for QueueSource {
+        fn next_event(&mut self) -> Option<Event> {
+            self.0.pop_front()
+        }
+    }
+
+    fn cloudtrail(name: &str, error_code: Option<&str>) -> Event {
+        let mut event = test_event(
+            "cloudtrail",
+            name,
+            json!({
+                "eventTime": "2026-02-06T00:00:38.120Z",
+                "eventSource": "s3.amazonaws.com",
+                "eventName": name,
+                "awsRegion": "us-west-2",
+                "sourceIPAddress": "192.0.2.3",
+                "userAgent": "aws-cli/2.15.0",
+                "userIdentity": { "arn": "arn:aws:iam::123456789012:user/alice" },
+                "requestParameters": { "bucketName": "logs-bucket-abc123", "key": "data/a b.parquet" },
+                "requestID": "3E57427F3EXAMPLE",
+                "recipientAccountId": "123456789012",
+                "tlsDetails": { "tlsVersion": "TLSv1.2" },
+                "errorCode": error_code,
+            }),
+        )
+        .at("2026-02-06T00:00:38.120Z")
+        .by("alice", "human")
+        .with_ip("192.0.2.3")
+        .in_account("123456789012");
+        if error_code.is_some() {
+            event = event.failed();
+        }
+        event
+    }
+
+    #[test]
+    fn pairs_s3_events_with_access_log_lines() {
+        let events = VecDeque::from([
+            cloudtrail("GetObject", None),
+            cloudtrail("ListBuckets", None),
+            cloudtrail("PutObject", Some("AccessDenied")),
+        ]);
+        let mut source = S3AccessLogSource::new(
+            Box::new(QueueSource(events)),
+            &S3AccessLogConfig::default(),
+            Some(3),
+        );
+        let events = std::iter::from_fn(|| source.next_event()).collect::<Vec<_>>();
+        let sources = events
+            .iter()
+            .map(|event| event.envelope.source.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![
+                "cloudtrail",
+                "s3_access_log",
+                "cloudtrail",
+                "cloudtrail",
+                "s3_access_log"
+            ]
+        );
+
+        let get: S3AccessLogRecord = serde_json::from_value(events[1].payload.clone()).unwrap();
+        assert_eq!(get.operation, "REST.GET.OBJECT");
+        assert_eq!(get.request_id, "3E57427F3EXAMPLE");
+        assert_eq!(get.bytes_sent, get.object_size);
+        assert_eq!(get.bucket_owner.len(), 64);
+        let line = get.line();
+        assert!(line.contains(" logs-bucket-abc123 [06/Feb/2026:00:00:38 +0000] 192.0.2.3 "));
+        assert!(line.contains(" \"GET /data/a%20b.parquet HTTP/1.1\" 200 - "));
+        assert!(line.ends_with(" logs-bucket-abc123.s3.us-west-2.amazonaws.com TLSv1.2 - -"));
+
+        let denied = &events[4];
+        assert!(matches!(denied.envelope.outcome, Outcome::Failure));
+        assert_eq!(denied.payload["http_status"], 403);
+        assert_eq!(denied.payload["error_code"], "AccessDenied");
+        assert_eq!(denied.payload["bytes_sent"], ERROR_BODY_BYTES);
+    }
+}
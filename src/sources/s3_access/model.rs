@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+/// One S3 server access log record.
+///
+/// Serialized with one JSON key per log field; [`S3AccessLogRecord::line`]
+/// renders the space-delimited text form S3 delivers to the target bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct S3AccessLogRecord {
+    /// Canonical user ID of the source bucket owner.
+    pub bucket_owner: String,
+    pub bucket: String,
+    /// Request time as `06/Feb/2019:00:00:38 +0000`.
+    pub time: String,
+    pub remote_ip: String,
+    /// IAM ARN of the requester, or `None` for anonymous requests.
+    pub requester: Option<String>,
+    pub request_id: String,
+    /// `REST.<HTTP method>.<resource type>`, e.g. `REST.GET.OBJECT`.
+    pub operation: String,
+    pub key: Option<String>,
+    pub request_uri: String,
+    pub http_status: u16,
+    pub error_code: Option<String>,
+    pub bytes_sent: Option<u64>,
+    pub object_size: Option<u64>,
+    /// Milliseconds from request received to last response byte sent.
+    pub total_time: Option<u64>,
+    /// Milliseconds S3 spent processing the request.
+    pub turn_around_time: Option<u64>,
+    pub referer: Option<String>,
+    pub user_agent: Option<String>,
+    pub version_id: Option<String>,
+    pub host_id: String,
+    pub signature_version: Option<String>,
+    pub cipher_suite: Option<String>,
+    pub authentication_type: Option<String>,
+    pub host_header: String,
+    pub tls_version: Option<String>,
+    pub access_point_arn: Option<String>,
+    pub acl_required: Option<String>,
+}
+
+impl S3AccessLogRecord {
+    /// Renders the record as one access log line, using `-` for empty fields.
+    pub fn line(&self) -> String {
+        fn text(value: Option<&str>) -> &str {
+            value.filter(|value| !value.is_empty()).unwrap_or("-")
+        }
+        fn number(value: Option<u64>) -> String {
+            value.map_or_else(|| "-".to_string(), |value| value.to_string())
+        }
+        fn quoted(value: Option<&str>) -> String {
+            format!("\"{}\"", text(value).replace('"', "\\\""))
+        }
+
+        [
+            self.bucket_owner.clone(),
+            self.bucket.clone(),
+            format!("[{}]", self.time),
+            self.remote_ip.clone(),
+            text(self.requester.as_deref()).to_string(),
+            self.request_id.clone(),
+            self.operation.clone(),
+            text(self.key.as_deref()).to_string(),
+            quoted(Some(&self.request_uri)),
+            self.http_status.to_string(),
+            text(self.error_code.as_deref()).to_string(),
+            number(self.bytes_sent),
+            number(self.object_size),
+            number(self.total_time),
+            number(self.turn_around_time),
+            quoted(self.referer.as_deref()),
+            quoted(self.user_agent.as_deref()),
+            text(self.version_id.as_deref()).to_string(),
+            self.host_id.clone(),
+            text(self.signature_version.as_deref()).to_string(),
+            text(self.cipher_suite.as_deref()).to_string(),
+            text(self.authentication_type.as_deref()).to_string(),
+            self.host_header.clone(),
+            text(self.tls_version.as_deref()).to_string(),
+            text(self.access_point_arn.as_deref()).to_string(),
+            text(self.acl_required.as_deref()).to_string(),
+        ]
+        .join(" ")
+    }
+}
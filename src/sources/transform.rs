@@ -0,0 +1,257 @@
+use crate::core::config::{TransformAction, TransformConfig};
+use crate::core::event::Event;
+use crate::core::traits::{EventSource, EventTransform};
+use serde_json::{Map, Value};
+
+/// Error for an invalid `[[transforms]]` entry.
+#[derive(Debug)]
+pub struct TransformError(String);
+
+impl std::fmt::Display for TransformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid transform: {}", self.0)
+    }
+}
+
+impl std::error::Error for TransformError {}
+
+/// Config-defined payload rewrite: set, rename, or remove one field.
+///
+/// Rules address fields by dotted path under `payload` and can be limited to
+/// envelope event types and sources. Paths that do not exist are skipped,
+/// except that `set` creates missing parent objects.
+#[derive(Debug, Clone)]
+pub struct FieldTransform {
+    action: TransformAction,
+    path: Vec<String>,
+    to: Vec<String>,
+    value: Value,
+    event_types: Option<Vec<String>>,
+    sources: Option<Vec<String>>,
+}
+
+impl FieldTransform {
+    pub fn new(config: &TransformConfig) -> Result<Self, TransformError> {
+        let path = payload_path(&config.path)?;
+        let to = match (config.action, &config.to) {
+            (TransformAction::Rename, Some(to)) => payload_path(to)?,
+            (TransformAction::Rename, None) => {
+                return Err(TransformError(format!(
+                    "rename of {} needs `to`",
+                    config.path
+                )))
+            }
+            _ => Vec::new(),
+        };
+        let value = match (config.action, &config.value) {
+            (TransformAction::Set, Some(value)) => value.clone(),
+            (TransformAction::Set, None) => {
+                return Err(TransformError(format!(
+                    "set of {} needs `value`",
+                    config.path
+                )))
+            }
+            _ => Value::Null,
+        };
+        Ok(Self {
+            action: config.action,
+            path,
+            to,
+            value,
+            event_types: config.event_types.clone(),
+            sources: config.sources.clone(),
+        })
+    }
+
+    fn applies_to(&self, event: &Event) -> bool {
+        let matches = |names: &Option<Vec<String>>, actual: &str| {
+            names
+                .as_ref()
+                .is_none_or(|names| names.iter().any(|name| name == actual))
+        };
+        matches(&self.event_types, &event.envelope.event_type)
+            && matches(&self.sources, &event.envelope.source)
+    }
+}
+
+impl EventTransform for FieldTransform {
+    fn transform(&mut self, event: &mut Event) {
+        if !self.applies_to(event) {
+            return;
+        }
+        match self.action {
+            TransformAction::Set => set_field(&mut event.payload, &self.path, self.value.clone()),
+            TransformAction::Rename => {
+                if let Some(value) = remove_field(&mut event.payload, &self.path) {
+                    set_field(&mut event.payload, &self.to, value);
+                }
+            }
+            TransformAction::Remove => {
+                remove_field(&mut event.payload, &self.path);
+            }
+        }
+    }
+}
+
+/// Builds the transforms for a `[[transforms]]` list, in order.
+pub fn transforms_from_config(
+    configs: &[TransformConfig],
+) -> Result<Vec<Box<dyn EventTransform>>, TransformError> {
+    configs
+        .iter()
+        .map(|config| Ok(Box::new(FieldTransform::new(config)?) as Box<dyn EventTransform>))
+        .collect()
+}
+
+/// Event source that runs every event through a list of transforms.
+pub struct TransformedEventSource {
+    source: Box<dyn EventSource>,
+    transforms: Vec<Box<dyn EventTransform>>,
+}
+
+impl TransformedEventSource {
+    pub fn new(source: Box<dyn EventSource>, transforms: Vec<Box<dyn EventTransform>>) -> Self {
+        Self { source, transforms }
+    }
+}
+
+impl EventSource for TransformedEventSource {
+    fn next_event(&mut self) -> Option<Event> {
+        let mut event = self.source.next_event()?;
+        for transform in &mut self.transforms {
+            transform.transform(&mut event);
+        }
+        Some(event)
+    }
+}
+
+fn payload_path(path: &str) -> Result<Vec<String>, TransformError> {
+    let Some(rest) = path.strip_prefix("payload.") else {
+        return Err(TransformError(format!(
+            "path {path} must start with payload."
+        )));
+    };
+    let segments = rest.split('.').map(str::to_string).collect::<Vec<_>>();
+    if segments.iter().any(String::is_empty) {
+        return Err(TransformError(format!("path {path} must name a field")));
+    }
+    Ok(segments)
+}
+
+fn set_field(value: &mut Value, path: &[String], new_value: Value) {
+    let Some((key, rest)) = path.split_first() else {
+        *value = new_value;
+        return;
+    };
+    if !value.is_object() {
+        if !value.is_null() {
+            return;
+        }
+        *value = Value::Object(Map::new());
+    }
+    if let Value::Object(map) = value {
+        set_field(
+            map.entry(key.clone()).or_insert(Value::Null),
+            rest,
+            new_value,
+        );
+    }
+}
+
+fn remove_field(value: &mut Value, path: &[String]) -> Option<Value> {
+    let (key, rest) = path.split_first()?;
+    let map = value.as_object_mut()?;
+    if rest.is_empty() {
+        map.remove(key)
+    } else {
+        remove_field(map.get_mut(key)?, rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_event;
+    use serde_json::json;
+
+    struct Fixed(Vec<Event>);
+
+    impl EventSource for Fixed {
+        fn next_event(&mut self) -> Option<Event> {
+            self.0.pop()
+        }
+    }
+
+    #[test]
+    fn config_rules_then_closures_apply_in_order() {
+        #[derive(serde::Deserialize)]
+        struct Transforms {
+            transforms: Vec<TransformConfig>,
+        }
+        let Transforms {
+            transforms: configs,
+        } = toml::from_str(
+            r#"
+            [[transforms]]
+            action = "rename"
+            path = "payload.sourceIPAddress"
+            to = "payload.client.ip"
+
+            [[transforms]]
+            action = "set"
+            path = "payload.collector"
+            value = { name = "edge", version = 2 }
+            event_types = ["ConsoleLogin"]
+
+            [[transforms]]
+            action = "remove"
+            path = "payload.userAgent"
+            "#,
+        )
+        .unwrap();
+        let mut transforms = transforms_from_config(&configs).unwrap();
+        transforms.push(Box::new(|event: &mut Event| {
+            event.payload["tagged"] = json!(true);
+        }));
+        let source = Fixed(vec![
+            test_event(
+                "cloudtrail",
+                "GetObject",
+                json!({ "sourceIPAddress": "198.51.100.1", "userAgent": "cli" }),
+            ),
+            test_event(
+                "cloudtrail",
+                "ConsoleLogin",
+                json!({ "sourceIPAddress": "203.0.113.9", "userAgent": "browser" }),
+            ),
+        ]);
+        let mut source = TransformedEventSource::new(Box::new(source), transforms);
+
+        let login = source.next_event().unwrap();
+        assert_eq!(
+            login.payload,
+            json!({
+                "client": { "ip": "203.0.113.9" },
+                "collector": { "name": "edge", "version": 2 },
+                "tagged": true,
+            })
+        );
+        let get = source.next_event().unwrap();
+        assert_eq!(
+            get.payload,
+            json!({ "client": { "ip": "198.51.100.1" }, "tagged": true })
+        );
+
+        for bad in [
+            r#"action = "rename"
+            path = "payload.a""#,
+            r#"action = "set"
+            path = "payload.a""#,
+            r#"action = "remove"
+            path = "envelope.ip""#,
+        ] {
+            let config: TransformConfig = toml::from_str(bad).unwrap();
+            assert!(FieldTransform::new(&config).is_err(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,1362 @@
+use super::model::{
+    WindowsCorrelation, WindowsEventProvider, WindowsEventSystem, WindowsExecution,
+    WindowsSecurityEvent, WindowsTimeCreated,
+};
+use crate::core::activity::{
+    first_identity_event_at, next_identity_event_after, LinkedSessionClock,
+};
+use crate::core::arrival::Arrivals;
+use crate::core::attack::scenario_label;
+use crate::core::config::{WindowsSecurityEventConfig, WindowsSecuritySourceConfig};
+use crate::core::event::{Actor, Event, EventEnvelope, Outcome, Target};
+use crate::core::identity::{Identity, IdentityRegistry, IdentityRegistryError};
+use crate::core::traits::EventSource;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::json;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+
+const DEFAULT_SERVERS: &[&str] = &["SRV-APP01", "SRV-FILE01", "SRV-SQL01"];
+const PROVIDER_NAME: &str = "Microsoft-Windows-Security-Auditing";
+const PROVIDER_GUID: &str = "{54849625-5478-4994-A5BA-3E3B0328C30D}";
+const AUDIT_SUCCESS: &str = "0x8020000000000000";
+const AUDIT_FAILURE: &str = "0x8010000000000000";
+const SYSTEM_SID: &str = "S-1-5-18";
+const NULL_SID: &str = "S-1-0-0";
+const SYSTEM_LOGON_ID: &str = "0x3e7";
+const SVCHOST: &str = "C:\\Windows\\System32\\svchost.exe";
+const SERVICES: &str = "C:\\Windows\\System32\\services.exe";
+const EXPLORER: &str = "C:\\Windows\\explorer.exe";
+const USERINIT: &str = "C:\\Windows\\System32\\userinit.exe";
+const ADMIN_PRIVILEGES: &str = "SeSecurityPrivilege\r\n\t\t\tSeTakeOwnershipPrivilege\r\n\t\t\tSeLoadDriverPrivilege\r\n\t\t\tSeBackupPrivilege\r\n\t\t\tSeRestorePrivilege\r\n\t\t\tSeDebugPrivilege\r\n\t\t\tSeSystemEnvironmentPrivilege\r\n\t\t\tSeImpersonatePrivilege\r\n\t\t\tSeDelegateSessionUserImpersonatePrivilege";
+const SERVICE_PRIVILEGES: &str = "SeAssignPrimaryTokenPrivilege\r\n\t\t\tSeImpersonatePrivilege";
+/// A human's next activity after this much idle time starts a new logon session.
+const SESSION_IDLE_HOURS: i64 = 3;
+/// Service accounts start a new batch logon every this many baseline events.
+const SERVICE_SESSION_EVENTS: usize = 4;
+
+/// Windows Security event log generator backed by a shared identity registry.
+///
+/// Humans log on interactively to their own workstation at the start of each
+/// working session (4624, preceded by an occasional mistyped password 4625),
+/// then launch processes under that logon (4688). Administrators receive
+/// special privileges at logon (4672) and sometimes RDP to a server. Service
+/// accounts run batch logons on a server and start their jobs from there.
+pub struct WindowsSecurityGenerator {
+    config: WindowsSecuritySourceConfig,
+    directory: Directory,
+    injected_events: VecDeque<ScheduledEvent>,
+    identities: Vec<Identity>,
+    schedule: BinaryHeap<Reverse<(DateTime<Utc>, usize)>>,
+    arrivals: Arrivals,
+    next_event_idx: Vec<usize>,
+    linked_clock: Option<LinkedSessionClock>,
+    sessions: Vec<Option<LogonSession>>,
+    /// Events built ahead of their `TimeCreated`, keyed by time and build order.
+    pending: BTreeMap<(DateTime<Utc>, u64), Event>,
+    pending_seq: u64,
+    /// Last `EventRecordID` written by each computer.
+    record_ids: HashMap<String, u64>,
+}
+
+#[derive(Debug)]
+pub enum WindowsSecurityError {
+    IdentityRegistry(IdentityRegistryError),
+    MissingIdentity(String),
+    InvalidEventTime(String),
+    InvalidTechnique(String),
+    InvalidArrival(String),
+    UnsupportedEventId(u32),
+    EmptyStream,
+}
+
+impl std::fmt::Display for WindowsSecurityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowsSecurityError::IdentityRegistry(err) => write!(f, "{err}"),
+            WindowsSecurityError::MissingIdentity(actor_id) => {
+                write!(
+                    f,
+                    "windows security event references unknown actor_id: {actor_id}"
+                )
+            }
+            WindowsSecurityError::InvalidEventTime(value) => {
+                write!(f, "invalid windows security time_created: {value}")
+            }
+            WindowsSecurityError::InvalidTechnique(value) => {
+                write!(
+                    f,
+                    "invalid ATT&CK technique id on windows security event: {value}"
+                )
+            }
+            WindowsSecurityError::UnsupportedEventId(event_id) => {
+                write!(
+                    f,
+                    "unsupported windows security event_id {event_id} (expected 4624, 4625, 4672, or 4688)"
+                )
+            }
+            WindowsSecurityError::InvalidArrival(value) => {
+                write!(f, "invalid windows security arrival config: {value}")
+            }
+            WindowsSecurityError::EmptyStream => {
+                write!(
+                    f,
+                    "windows security source needs identity registry actors or event entries"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for WindowsSecurityError {}
+
+impl From<IdentityRegistryError> for WindowsSecurityError {
+    fn from(err: IdentityRegistryError) -> Self {
+        WindowsSecurityError::IdentityRegistry(err)
+    }
+}
+
+impl WindowsSecurityGenerator {
+    pub fn from_config(
+        config: &WindowsSecuritySourceConfig,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, WindowsSecurityError> {
+        let registry = IdentityRegistry::from_path(&config.identity_registry_path)?;
+        Self::from_registry(config, registry, start_time)
+    }
+
+    pub fn from_registry(
+        config: &WindowsSecuritySourceConfig,
+        registry: IdentityRegistry,
+        start_time: DateTime<Utc>,
+    ) -> Result<Self, WindowsSecurityError> {
+        let directory = Directory::new(config);
+        let mut scheduled = Vec::new();
+        append_injected_events(config, &directory, &registry, start_time, &mut scheduled)?;
+        let identities = sorted_identities(&registry);
+
+        if scheduled.is_empty()
+            && (config.baseline_events_per_actor == Some(0) || identities.is_empty())
+        {
+            return Err(WindowsSecurityError::EmptyStream);
+        }
+
+        scheduled.sort_by(|left, right| {
+            left.event_time
+                .cmp(&right.event_time)
+                .then(left.sequence.cmp(&right.sequence))
+        });
+        let arrivals = Arrivals::from_config(config.arrival.as_ref())
+            .map_err(WindowsSecurityError::InvalidArrival)?;
+        let mut linked_clock = config.linked_sessions.then(|| {
+            LinkedSessionClock::new(identities.len(), start_time, config.session_correlation)
+        });
+        let schedule = if config.baseline_events_per_actor == Some(0) {
+            BinaryHeap::new()
+        } else {
+            build_identity_schedule(&identities, start_time, linked_clock.as_mut(), &arrivals)
+        };
+        let next_event_idx = vec![0; identities.len()];
+        let sessions = vec![None; identities.len()];
+        Ok(Self {
+            config: config.clone(),
+            directory,
+            injected_events: scheduled.into(),
+            identities,
+            schedule,
+            arrivals,
+            next_event_idx,
+            linked_clock,
+            sessions,
+            pending: BTreeMap::new(),
+            pending_seq: 0,
+            record_ids: HashMap::new(),
+        })
+    }
+}
+
+impl EventSource for WindowsSecurityGenerator {
+    fn next_event(&mut self) -> Option<Event> {
+        loop {
+            let injected_time = self.injected_events.front().map(|item| item.event_time);
+            let scheduled_time = self.schedule.peek().map(|Reverse((time, _))| *time);
+            let next_time = match (injected_time, scheduled_time) {
+                (Some(injected), Some(scheduled)) => Some(injected.min(scheduled)),
+                (time, None) | (None, time) => time,
+            };
+            let pending_time = self.pending.keys().next().map(|(time, _)| *time);
+            if let Some(pending_time) = pending_time {
+                if next_time.is_none_or(|time| pending_time <= time) {
+                    let (_, event) = self.pending.pop_first()?;
+                    return Some(self.assign_record_id(event));
+                }
+            }
+
+            match (injected_time, scheduled_time) {
+                (None, None) => return None,
+                (Some(injected), Some(scheduled)) if injected > scheduled => {
+                    self.queue_scheduled_events()?
+                }
+                (Some(_), _) => {
+                    let item = self.injected_events.pop_front()?;
+                    self.queue(item.event_time, item.event);
+                }
+                (None, Some(_)) => self.queue_scheduled_events()?,
+            }
+        }
+    }
+}
+
+impl WindowsSecurityGenerator {
+    fn queue(&mut self, time: DateTime<Utc>, event: Event) {
+        self.pending.insert((time, self.pending_seq), event);
+        self.pending_seq += 1;
+    }
+
+    /// Numbers events per computer in the order they are written.
+    fn assign_record_id(&mut self, mut event: Event) -> Event {
+        let computer = event.payload["System"]["Computer"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let record_id = self
+            .record_ids
+            .entry(computer)
+            .or_insert_with_key(|computer| 40_000 + stable_hash(computer) % 900_000);
+        *record_id += 1;
+        event.payload["System"]["EventRecordID"] = json!(*record_id);
+        event
+    }
+
+    fn queue_scheduled_events(&mut self) -> Option<()> {
+        let Reverse((event_time, actor_idx)) = self.schedule.pop()?;
+        let event_idx = self.next_event_idx[actor_idx];
+        self.next_event_idx[actor_idx] += 1;
+
+        let identity = &self.identities[actor_idx];
+        let source_ip = baseline_source_ip(&self.config, identity, actor_idx, event_idx);
+        let session = self.sessions[actor_idx].take();
+        let (session, events) = baseline_events(
+            &self.directory,
+            identity,
+            session,
+            &source_ip,
+            event_time,
+            event_idx,
+        );
+        self.sessions[actor_idx] = Some(session);
+
+        let next_at = if let Some(clock) = &mut self.linked_clock {
+            clock.next_event_at(
+                actor_idx,
+                identity,
+                self.next_event_idx[actor_idx],
+                "windows_security",
+                &self.arrivals,
+            )
+        } else {
+            next_identity_event_after(
+                identity,
+                event_time,
+                self.next_event_idx[actor_idx],
+                "windows_security",
+                &self.arrivals,
+            )
+        };
+        self.schedule.push(Reverse((next_at, actor_idx)));
+        for (time, event) in events {
+            self.queue(time, event);
+        }
+        Some(())
+    }
+}
+
+struct ScheduledEvent {
+    event_time: DateTime<Utc>,
+    sequence: usize,
+    event: Event,
+}
+
+/// The logon an actor's baseline processes run under.
+#[derive(Debug, Clone)]
+struct LogonSession {
+    logon_id: String,
+    host: String,
+    /// Process ID of the session shell (`explorer.exe`, or the service binary).
+    shell_pid: String,
+    last_at: DateTime<Utc>,
+}
+
+/// Domain naming shared by every event.
+struct Directory {
+    domain: String,
+    netbios: String,
+    domain_sid: String,
+    servers: Vec<String>,
+}
+
+impl Directory {
+    fn new(config: &WindowsSecuritySourceConfig) -> Self {
+        let domain = config.domain.trim().to_ascii_lowercase();
+        let netbios = config
+            .netbios_domain
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| domain.split('.').next().unwrap_or_default().to_string())
+            .to_ascii_uppercase();
+        let hash = stable_hash(&format!("domain:{domain}"));
+        let domain_sid = format!(
+            "S-1-5-21-{}-{}-{}",
+            hash & 0xffff_ffff,
+            (hash >> 32) | 0x1000_0000,
+            stable_hash(&format!("{hash}")) & 0x7fff_ffff
+        );
+        let servers = config
+            .servers
+            .as_ref()
+            .filter(|servers| !servers.is_empty())
+            .cloned()
+            .unwrap_or_else(|| {
+                DEFAULT_SERVERS
+                    .iter()
+                    .map(|host| host.to_string())
+                    .collect()
+            });
+        Self {
+            domain,
+            netbios,
+            domain_sid,
+            servers,
+        }
+    }
+
+    /// Splits a host name or FQDN into the upper-case host and the FQDN.
+    fn computer(&self, name: &str) -> (String, String) {
+        match name.split_once('.') {
+            Some((host, _)) => (host.to_ascii_uppercase(), name.to_string()),
+            None => (
+                name.to_ascii_uppercase(),
+                format!("{}.{}", name.to_ascii_uppercase(), self.domain),
+            ),
+        }
+    }
+
+    /// The identity's own machine: a workstation for humans, a server for service accounts.
+    fn home_host(&self, identity: &Identity) -> String {
+        if identity.service_account {
+            self.servers[stable_hash(&identity.actor_id) as usize % self.servers.len()].clone()
+        } else {
+            format!("WKS-{:04X}", stable_hash(&identity.actor_id) & 0xffff)
+        }
+    }
+
+    fn account(&self, identity: &Identity) -> Account {
+        let local = identity
+            .email
+            .split_once('@')
+            .map(|(local, _)| local)
+            .unwrap_or(&identity.actor_id);
+        Account {
+            sid: format!(
+                "{}-{}",
+                self.domain_sid,
+                1_100 + stable_hash(&identity.actor_id) % 50_000
+            ),
+            name: local.chars().take(20).collect(),
+            domain: self.netbios.clone(),
+        }
+    }
+
+    /// The computer account (`HOST$`) that logs logons on a machine.
+    fn machine_account(&self, host: &str) -> Account {
+        Account {
+            sid: SYSTEM_SID.to_string(),
+            name: format!("{host}$"),
+            domain: self.netbios.clone(),
+        }
+    }
+}
+
+struct Account {
+    sid: String,
+    name: String,
+    domain: String,
+}
+
+/// Process launch for a 4688 event.
+struct ProcessLaunch {
+    image: String,
+    parent_image: String,
+    parent_pid: String,
+    command_line: String,
+    elevated: bool,
+}
+
+fn append_injected_events(
+    config: &WindowsSecuritySourceConfig,
+    directory: &Directory,
+    registry: &IdentityRegistry,
+    start_time: DateTime<Utc>,
+    scheduled: &mut Vec<ScheduledEvent>,
+) -> Result<(), WindowsSecurityError> {
+    for (idx, entry) in config.events.iter().enumerate() {
+        let identity = registry
+            .get(&entry.actor_id)
+            .ok_or_else(|| WindowsSecurityError::MissingIdentity(entry.actor_id.clone()))?;
+        let event_time = event_time_for_entry(entry, start_time)?;
+        let label = scenario_label(
+            entry.scenario_id.as_ref(),
+            entry.technique_id.as_ref(),
+            &entry.technique_ids,
+        )
+        .map_err(WindowsSecurityError::InvalidTechnique)?;
+        let mut event = injected_event(config, directory, identity, entry, idx, event_time)?;
+        event.label = label;
+        scheduled.push(ScheduledEvent {
+            event_time,
+            sequence: idx,
+            event,
+        });
+    }
+    Ok(())
+}
+
+fn injected_event(
+    config: &WindowsSecuritySourceConfig,
+    directory: &Directory,
+    identity: &Identity,
+    entry: &WindowsSecurityEventConfig,
+    idx: usize,
+    event_time: DateTime<Utc>,
+) -> Result<Event, WindowsSecurityError> {
+    let host = entry
+        .computer
+        .clone()
+        .unwrap_or_else(|| directory.home_host(identity));
+    let key = format!("event:{idx}:{}", identity.actor_id);
+    let logon_id = entry
+        .logon_id
+        .clone()
+        .unwrap_or_else(|| logon_id(&format!("{key}:logon")));
+    let source_ip = entry
+        .ip_address
+        .clone()
+        .unwrap_or_else(|| baseline_source_ip(config, identity, idx, 0));
+    let logon_type = entry.logon_type.unwrap_or(2);
+    let (mut row, session) = match entry.event_id {
+        4624 => (
+            logon_event(
+                directory, identity, &host, logon_type, &logon_id, &source_ip, event_time, &key,
+            ),
+            Some(logon_id.as_str()),
+        ),
+        4625 => (
+            failed_logon_event(
+                directory, identity, &host, logon_type, &source_ip, event_time, &key,
+            ),
+            None,
+        ),
+        4672 => (
+            special_privileges_event(directory, identity, &host, &logon_id, event_time, &key),
+            Some(logon_id.as_str()),
+        ),
+        4688 => {
+            let image = entry
+                .process_name
+                .clone()
+                .unwrap_or_else(|| "C:\\Windows\\System32\\cmd.exe".to_string());
+            let launch = ProcessLaunch {
+                command_line: entry
+                    .command_line
+                    .clone()
+                    .unwrap_or_else(|| format!("\"{image}\" ")),
+                image,
+                parent_image: entry
+                    .parent_process_name
+                    .clone()
+                    .unwrap_or_else(|| EXPLORER.to_string()),
+                parent_pid: process_id(&format!("{key}:parent")),
+                elevated: false,
+            };
+            (
+                process_event(
+                    directory, identity, &host, &logon_id, &launch, event_time, &key,
+                ),
+                Some(logon_id.as_str()),
+            )
+        }
+        other => return Err(WindowsSecurityError::UnsupportedEventId(other)),
+    };
+    for (name, value) in &entry.fields {
+        row.event_data.insert(name.clone(), value.clone());
+    }
+    Ok(event_from_row(
+        directory, identity, row, &source_ip, session,
+    ))
+}
+
+fn sorted_identities(registry: &IdentityRegistry) -> Vec<Identity> {
+    let mut identities: Vec<&Identity> = registry.identities().iter().collect();
+    identities.sort_by(|left, right| left.actor_id.cmp(&right.actor_id));
+    identities.into_iter().cloned().collect()
+}
+
+fn build_identity_schedule(
+    identities: &[Identity],
+    start_time: DateTime<Utc>,
+    mut linked_clock: Option<&mut LinkedSessionClock>,
+    arrivals: &Arrivals,
+) -> BinaryHeap<Reverse<(DateTime<Utc>, usize)>> {
+    let mut schedule = BinaryHeap::with_capacity(identities.len());
+    for (idx, identity) in identities.iter().enumerate() {
+        let first_at = if let Some(clock) = linked_clock.as_deref_mut() {
+            clock.next_event_at(idx, identity, 0, "windows_security", arrivals)
+        } else {
+            first_identity_event_at(identity, start_time, "windows_security", arrivals)
+        };
+        schedule.push(Reverse((first_at, idx)));
+    }
+    schedule
+}
+
+fn event_time_for_entry(
+    entry: &WindowsSecurityEventConfig,
+    start_time: DateTime<Utc>,
+) -> Result<DateTime<Utc>, WindowsSecurityError> {
+    if let Some(raw) = &entry.time_created {
+        let parsed = DateTime::parse_from_rfc3339(raw)
+            .map_err(|_| WindowsSecurityError::InvalidEventTime(raw.clone()))?;
+        return Ok(parsed.with_timezone(&Utc));
+    }
+    Ok(start_time + Duration::seconds(entry.offset_seconds.unwrap_or(0)))
+}
+
+/// Builds the events for one baseline activity instant.
+///
+/// The first activity after [`SESSION_IDLE_HOURS`] of idle time (or every
+/// [`SERVICE_SESSION_EVENTS`] events for service accounts) opens a new logon
+/// session; other instants launch one process under the current session.
+fn baseline_events(
+    directory: &Directory,
+    identity: &Identity,
+    session: Option<LogonSession>,
+    source_ip: &str,
+    event_time: DateTime<Utc>,
+    event_idx: usize,
+) -> (LogonSession, Vec<(DateTime<Utc>, Event)>) {
+    let key = format!("{}:{event_idx}", identity.actor_id);
+    let roll = stable_hash(&key);
+    let admin = identity.role_persona.contains("administrator");
+    let expired = |session: &LogonSession| {
+        if identity.service_account {
+            event_idx.is_multiple_of(SERVICE_SESSION_EVENTS)
+        } else {
+            event_time - session.last_at > Duration::hours(SESSION_IDLE_HOURS)
+        }
+    };
+    let mut events = Vec::new();
+    let event = |row: WindowsSecurityEvent, session: Option<&str>| {
+        event_from_row(directory, identity, row, source_ip, session)
+    };
+
+    if let Some(mut session) = session.filter(|session| !expired(session)) {
+        let launch = baseline_process(identity, &session, roll, admin);
+        let row = process_event(
+            directory,
+            identity,
+            &session.host,
+            &session.logon_id,
+            &launch,
+            event_time,
+            &key,
+        );
+        events.push((event_time, event(row, Some(&session.logon_id))));
+        session.last_at = event_time;
+        return (session, events);
+    }
+
+    // Open a new logon session.
+    let mut logon_at = event_time;
+    let (host, logon_type) = if identity.service_account {
+        (
+            directory.home_host(identity),
+            if event_idx == 0 { 5 } else { 4 },
+        )
+    } else if admin && (roll / 100).is_multiple_of(4) {
+        let server = &directory.servers[(roll / 1_000) as usize % directory.servers.len()];
+        (server.clone(), 10)
+    } else {
+        (directory.home_host(identity), 2)
+    };
+    if !identity.service_account && (roll / 10_000).is_multiple_of(12) {
+        // Mistyped password before the successful logon.
+        let row = failed_logon_event(
+            directory, identity, &host, logon_type, source_ip, logon_at, &key,
+        );
+        events.push((logon_at, event(row, None)));
+        logon_at += Duration::seconds(5 + (roll % 20) as i64);
+    }
+    let logon_id = logon_id(&format!("{key}:logon"));
+    let row = logon_event(
+        directory, identity, &host, logon_type, &logon_id, source_ip, logon_at, &key,
+    );
+    events.push((logon_at, event(row, Some(&logon_id))));
+    if admin || identity.service_account {
+        let row = special_privileges_event(directory, identity, &host, &logon_id, logon_at, &key);
+        events.push((logon_at, event(row, Some(&logon_id))));
+    }
+
+    let mut session = LogonSession {
+        logon_id,
+        host,
+        shell_pid: String::new(),
+        last_at: logon_at,
+    };
+    // The session shell: explorer for desktop logons, the job binary for services.
+    let (image, parent_image) = if identity.service_account {
+        (service_binary(directory, identity), SVCHOST.to_string())
+    } else {
+        (EXPLORER.to_string(), USERINIT.to_string())
+    };
+    let launch = ProcessLaunch {
+        command_line: if identity.service_account {
+            format!("\"{image}\" --run")
+        } else {
+            EXPLORER.to_string()
+        },
+        image,
+        parent_image,
+        parent_pid: process_id(&format!("{key}:shell-parent")),
+        elevated: identity.service_account,
+    };
+    let shell_at = logon_at + Duration::milliseconds(1_500 + (roll % 2_000) as i64);
+    let row = process_event(
+        directory,
+        identity,
+        &session.host,
+        &session.logon_id,
+        &launch,
+        shell_at,
+        &key,
+    );
+    session.shell_pid = row.event_data["NewProcessId"].clone();
+    events.push((shell_at, event(row, Some(&session.logon_id))));
+    (session, events)
+}
+
+/// Picks a process for a baseline activity instant inside a session.
+fn baseline_process(
+    identity: &Identity,
+    session: &LogonSession,
+    roll: u64,
+    admin: bool,
+) -> ProcessLaunch {
+    let bucket = roll % 100;
+    let launch = |image: &str, command_line: String| ProcessLaunch {
+        image: image.to_string(),
+        parent_image: EXPLORER.to_string(),
+        parent_pid: session.shell_pid.clone(),
+        command_line,
+        elevated: false,
+    };
+    let quoted = |image: &str| format!("\"{image}\" ");
+
+    if identity.service_account {
+        let parent = ProcessLaunch {
+            image: String::new(),
+            parent_image: String::new(),
+            parent_pid: session.shell_pid.clone(),
+            command_line: String::new(),
+            elevated: true,
+        };
+        let job = identity
+            .email
+            .split_once('@')
+            .map(|(local, _)| local)
+            .unwrap_or(&identity.actor_id);
+        return match bucket {
+            0..=59 => ProcessLaunch {
+                image: "C:\\Windows\\System32\\cmd.exe".to_string(),
+                command_line: format!("C:\\Windows\\system32\\cmd.exe /c \"D:\\jobs\\{job}\\run.cmd\""),
+                ..parent
+            },
+            60..=84 => ProcessLaunch {
+                image: "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe".to_string(),
+                command_line: format!(
+                    "powershell.exe -NoProfile -NonInteractive -ExecutionPolicy Bypass -File D:\\jobs\\{job}\\maintenance.ps1"
+                ),
+                ..parent
+            },
+            _ => ProcessLaunch {
+                image: "C:\\Windows\\System32\\robocopy.exe".to_string(),
+                command_line: format!("robocopy D:\\jobs\\{job}\\out \\\\SRV-FILE01\\exports\\{job} /MIR /R:2 /W:5"),
+                ..parent
+            },
+        };
+    }
+
+    match bucket {
+        0..=19 => {
+            let image = "C:\\Program Files\\Google\\Chrome\\Application\\chrome.exe";
+            launch(image, quoted(image))
+        }
+        20..=34 => {
+            let image = "C:\\Program Files\\Microsoft Office\\root\\Office16\\OUTLOOK.EXE";
+            launch(image, quoted(image))
+        }
+        35..=47 => {
+            let image = "C:\\Program Files\\Microsoft Office\\root\\Office16\\EXCEL.EXE";
+            launch(image, format!("\"{image}\" /dde"))
+        }
+        48..=59 => {
+            let image = "C:\\Program Files\\Microsoft VS Code\\Code.exe";
+            launch(image, quoted(image))
+        }
+        60..=69 => {
+            let image = "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe";
+            launch(image, quoted(image))
+        }
+        70..=79 => ProcessLaunch {
+            parent_image: "C:\\Windows\\System32\\WindowsPowerShell\\v1.0\\powershell.exe"
+                .to_string(),
+            parent_pid: process_id(&format!("{}:{roll}:shell", session.logon_id)),
+            ..launch(
+                "C:\\Program Files\\Git\\cmd\\git.exe",
+                if roll.is_multiple_of(2) {
+                    "git pull --rebase".to_string()
+                } else {
+                    "git status".to_string()
+                },
+            )
+        },
+        80..=87 => launch(
+            "C:\\Windows\\System32\\cmd.exe",
+            "\"C:\\Windows\\system32\\cmd.exe\" ".to_string(),
+        ),
+        88..=93 => launch(
+            "C:\\Windows\\System32\\notepad.exe",
+            "\"C:\\Windows\\system32\\notepad.exe\" ".to_string(),
+        ),
+        _ if admin => ProcessLaunch {
+            elevated: true,
+            ..launch(
+                "C:\\Windows\\System32\\mmc.exe",
+                "\"C:\\Windows\\system32\\mmc.exe\" \"C:\\Windows\\system32\\dsa.msc\"".to_string(),
+            )
+        },
+        _ => launch(
+            "C:\\Windows\\System32\\Taskmgr.exe",
+            "\"C:\\Windows\\system32\\taskmgr.exe\" /4".to_string(),
+        ),
+    }
+}
+
+fn service_binary(directory: &Directory, identity: &Identity) -> String {
+    let account = directory.account(identity);
+    format!("D:\\jobs\\{0}\\{0}.exe", account.name)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn logon_event(
+    directory: &Directory,
+    identity: &Identity,
+    host: &str,
+    logon_type: u8,
+    logon_id: &str,
+    source_ip: &str,
+    time: DateTime<Utc>,
+    key: &str,
+) -> WindowsSecurityEvent {
+    let (host, computer) = directory.computer(host);
+    let account = directory.account(identity);
+    let admin = identity.service_account || identity.role_persona.contains("administrator");
+    let logon = logon_fields(
+        directory,
+        identity,
+        &host,
+        logon_type,
+        source_ip,
+        &format!("{key}:4624"),
+    );
+    let hash = stable_hash(&format!("{key}:linked"));
+    let data = [
+        ("SubjectUserSid", logon.subject.sid),
+        ("SubjectUserName", logon.subject.name),
+        ("SubjectDomainName", logon.subject.domain),
+        ("SubjectLogonId", logon.subject_logon_id),
+        ("TargetUserSid", account.sid),
+        ("TargetUserName", account.name),
+        ("TargetDomainName", account.domain),
+        ("TargetLogonId", logon_id.to_string()),
+        ("LogonType", logon_type.to_string()),
+        ("LogonProcessName", logon.logon_process),
+        ("AuthenticationPackageName", logon.package),
+        ("WorkstationName", logon.workstation),
+        ("LogonGuid", guid(stable_hash(&format!("{key}:guid")))),
+        ("TransmittedServices", "-".to_string()),
+        ("LmPackageName", logon.lm_package),
+        ("KeyLength", "0".to_string()),
+        ("ProcessId", logon.process_id),
+        ("ProcessName", logon.process_name),
+        ("IpAddress", logon.ip_address),
+        ("IpPort", logon.ip_port),
+        ("ImpersonationLevel", "%%1833".to_string()),
+        (
+            "RestrictedAdminMode",
+            if logon_type == 10 { "%%1843" } else { "-" }.to_string(),
+        ),
+        ("TargetOutboundUserName", "-".to_string()),
+        ("TargetOutboundDomainName", "-".to_string()),
+        ("VirtualAccount", "%%1843".to_string()),
+        (
+            "TargetLinkedLogonId",
+            if admin && matches!(logon_type, 2 | 10) {
+                format!("0x{:x}", 0x10_0000 + (hash & 0xff_ffff))
+            } else {
+                "0x0".to_string()
+            },
+        ),
+        (
+            "ElevatedToken",
+            if admin { "%%1842" } else { "%%1843" }.to_string(),
+        ),
+    ];
+    windows_event(4624, computer, time, data, key)
+}
+
+fn failed_logon_event(
+    directory: &Directory,
+    identity: &Identity,
+    host: &str,
+    logon_type: u8,
+    source_ip: &str,
+    time: DateTime<Utc>,
+    key: &str,
+) -> WindowsSecurityEvent {
+    let (host, computer) = directory.computer(host);
+    let account = directory.account(identity);
+    let logon = logon_fields(
+        directory,
+        identity,
+        &host,
+        logon_type,
+        source_ip,
+        &format!("{key}:4625"),
+    );
+    let data = [
+        ("SubjectUserSid", logon.subject.sid),
+        ("SubjectUserName", logon.subject.name),
+        ("SubjectDomainName", logon.subject.domain),
+        ("SubjectLogonId", logon.subject_logon_id),
+        ("TargetUserSid", NULL_SID.to_string()),
+        ("TargetUserName", account.name),
+        ("TargetDomainName", account.domain),
+        ("Status", "0xc000006d".to_string()),
+        ("FailureReason", "%%2313".to_string()),
+        ("SubStatus", "0xc000006a".to_string()),
+        ("LogonType", logon_type.to_string()),
+        ("LogonProcessName", logon.logon_process),
+        ("AuthenticationPackageName", logon.package),
+        ("WorkstationName", logon.workstation),
+        ("TransmittedServices", "-".to_string()),
+        ("LmPackageName", logon.lm_package),
+        ("KeyLength", "0".to_string()),
+        ("ProcessId", logon.process_id),
+        ("ProcessName", logon.process_name),
+        ("IpAddress", logon.ip_address),
+        ("IpPort", logon.ip_port),
+    ];
+    windows_event(4625, computer, time, data, key)
+}
+
+fn special_privileges_event(
+    directory: &Directory,
+    identity: &Identity,
+    host: &str,
+    logon_id: &str,
+    time: DateTime<Utc>,
+    key: &str,
+) -> WindowsSecurityEvent {
+    let (_, computer) = directory.computer(host);
+    let account = directory.account(identity);
+    let privileges = if identity.service_account {
+        SERVICE_PRIVILEGES
+    } else {
+        ADMIN_PRIVILEGES
+    };
+    let data = [
+        ("SubjectUserSid", account.sid),
+        ("SubjectUserName", account.name),
+        ("SubjectDomainName", account.domain),
+        ("SubjectLogonId", logon_id.to_string()),
+        ("PrivilegeList", privileges.to_string()),
+    ];
+    windows_event(4672, computer, time, data, key)
+}
+
+fn process_event(
+    directory: &Directory,
+    identity: &Identity,
+    host: &str,
+    logon_id: &str,
+    launch: &ProcessLaunch,
+    time: DateTime<Utc>,
+    key: &str,
+) -> WindowsSecurityEvent {
+    let (_, computer) = directory.computer(host);
+    let account = directory.account(identity);
+    let (elevation, label) = match (identity.service_account, launch.elevated) {
+        (true, _) => ("%%1936", "S-1-16-12288"),
+        (false, true) => ("%%1937", "S-1-16-12288"),
+        (false, false) => ("%%1938", "S-1-16-8192"),
+    };
+    let data = [
+        ("SubjectUserSid", account.sid),
+        ("SubjectUserName", account.name),
+        ("SubjectDomainName", account.domain),
+        ("SubjectLogonId", logon_id.to_string()),
+        (
+            "NewProcessId",
+            process_id(&format!("{key}:{time}:{}", launch.image)),
+        ),
+        ("NewProcessName", launch.image.clone()),
+        ("TokenElevationType", elevation.to_string()),
+        ("ProcessId", launch.parent_pid.clone()),
+        ("CommandLine", launch.command_line.clone()),
+        ("TargetUserSid", NULL_SID.to_string()),
+        ("TargetUserName", "-".to_string()),
+        ("TargetDomainName", "-".to_string()),
+        ("TargetLogonId", "0x0".to_string()),
+        ("ParentProcessName", launch.parent_image.clone()),
+        ("MandatoryLabel", label.to_string()),
+    ];
+    windows_event(4688, computer, time, data, key)
+}
+
+/// Logon fields that depend on the logon type.
+struct LogonFields {
+    subject: Account,
+    subject_logon_id: String,
+    logon_process: String,
+    package: String,
+    workstation: String,
+    lm_package: String,
+    process_id: String,
+    process_name: String,
+    ip_address: String,
+    ip_port: String,
+}
+
+fn logon_fields(
+    directory: &Directory,
+    identity: &Identity,
+    host: &str,
+    logon_type: u8,
+    source_ip: &str,
+    key: &str,
+) -> LogonFields {
+    let hash = stable_hash(key);
+    let local = LogonFields {
+        subject: directory.machine_account(host),
+        subject_logon_id: SYSTEM_LOGON_ID.to_string(),
+        logon_process: "User32 ".to_string(),
+        package: "Negotiate".to_string(),
+        workstation: host.to_string(),
+        lm_package: "-".to_string(),
+        process_id: process_id(&format!("winlogon:{host}")),
+        process_name: SVCHOST.to_string(),
+        ip_address: "127.0.0.1".to_string(),
+        ip_port: "0".to_string(),
+    };
+    match logon_type {
+        3 => LogonFields {
+            subject: Account {
+                sid: NULL_SID.to_string(),
+                name: "-".to_string(),
+                domain: "-".to_string(),
+            },
+            subject_logon_id: "0x0".to_string(),
+            logon_process: "NtLmSsp ".to_string(),
+            package: "NTLM".to_string(),
+            workstation: directory.home_host(identity),
+            lm_package: "NTLM V2".to_string(),
+            process_id: "0x0".to_string(),
+            process_name: "-".to_string(),
+            ip_address: source_ip.to_string(),
+            ip_port: (49_152 + hash % 16_383).to_string(),
+        },
+        4 | 5 => LogonFields {
+            logon_process: "Advapi  ".to_string(),
+            workstation: "-".to_string(),
+            process_id: process_id(&format!("services:{host}")),
+            process_name: if logon_type == 5 { SERVICES } else { SVCHOST }.to_string(),
+            ip_address: "-".to_string(),
+            ip_port: "-".to_string(),
+            ..local
+        },
+        10 => LogonFields {
+            workstation: directory.home_host(identity),
+            ip_address: source_ip.to_string(),
+            ip_port: (49_152 + hash % 16_383).to_string(),
+            ..local
+        },
+        _ => local,
+    }
+}
+
+fn windows_event<const N: usize>(
+    event_id: u32,
+    computer: String,
+    time: DateTime<Utc>,
+    data: [(&str, String); N],
+    key: &str,
+) -> WindowsSecurityEvent {
+    let (version, task) = match event_id {
+        4624 => (2, 12544),
+        4625 => (0, 12544),
+        4672 => (0, 12548),
+        _ => (2, 13312),
+    };
+    let correlation = matches!(event_id, 4624 | 4625).then(|| WindowsCorrelation {
+        activity_id: guid(stable_hash(&format!("activity:{computer}"))),
+    });
+    WindowsSecurityEvent {
+        system: WindowsEventSystem {
+            provider: WindowsEventProvider {
+                name: PROVIDER_NAME.to_string(),
+                guid: PROVIDER_GUID.to_string(),
+            },
+            event_id,
+            version,
+            level: 0,
+            task,
+            opcode: 0,
+            keywords: if event_id == 4625 {
+                AUDIT_FAILURE
+            } else {
+                AUDIT_SUCCESS
+            }
+            .to_string(),
+            time_created: WindowsTimeCreated {
+                system_time: format!(
+                    "{}.{:07}Z",
+                    time.format("%Y-%m-%dT%H:%M:%S"),
+                    time.timestamp_subsec_nanos() / 100
+                ),
+            },
+            event_record_id: 0,
+            correlation,
+            execution: WindowsExecution {
+                process_id: 600 + (stable_hash(&format!("lsass:{computer}")) % 200) as u32 * 4,
+                thread_id: 700
+                    + (stable_hash(&format!("{key}:{event_id}:thread")) % 4_000) as u32 * 4,
+            },
+            channel: "Security".to_string(),
+            computer,
+        },
+        event_data: data
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    }
+}
+
+fn event_from_row(
+    directory: &Directory,
+    identity: &Identity,
+    row: WindowsSecurityEvent,
+    source_ip: &str,
+    logon_id: Option<&str>,
+) -> Event {
+    let outcome = if row.system.event_id == 4625 {
+        Outcome::Failure
+    } else {
+        Outcome::Success
+    };
+    let actor_kind = if identity.service_account {
+        "service"
+    } else {
+        "human"
+    };
+    let computer = row.system.computer.clone();
+    let host = computer.split('.').next().unwrap_or_default().to_string();
+    let time = DateTime::parse_from_rfc3339(&row.system.time_created.system_time)
+        .map(|time| {
+            time.with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+        })
+        .unwrap_or_else(|_| row.system.time_created.system_time.clone());
+
+    Event {
+        envelope: EventEnvelope {
+            schema_version: "v1".to_string(),
+            timestamp: time,
+            source: "windows_security".to_string(),
+            event_type: row.system.event_id.to_string(),
+            actor: Actor {
+                id: identity.actor_id.clone(),
+                kind: actor_kind.to_string(),
+                name: Some(identity.display_name.clone()),
+            },
+            target: Some(Target {
+                id: computer,
+                kind: "host".to_string(),
+                name: Some(host),
+            }),
+            outcome,
+            geo: None,
+            ip: Some(source_ip.to_string()),
+            user_agent: None,
+            session_id: logon_id.map(str::to_string),
+            tenant_id: Some(directory.domain.clone()),
+            tenant: None,
+        },
+        payload: row.to_value(),
+        label: None,
+    }
+}
+
+fn baseline_source_ip(
+    config: &WindowsSecuritySourceConfig,
+    identity: &Identity,
+    actor_idx: usize,
+    event_idx: usize,
+) -> String {
+    if let Some(source_ips) = config
+        .baseline_source_ips
+        .as_ref()
+        .and_then(|by_actor| by_actor.get(&identity.actor_id))
+    {
+        if !source_ips.is_empty() {
+            return source_ips[event_idx % source_ips.len()].clone();
+        }
+    }
+    let second = if identity.service_account {
+        20
+    } else {
+        96 + ((actor_idx / 240) % 16)
+    };
+    let fourth = 10 + (actor_idx % 240);
+    format!("10.{}.0.{}", second, fourth)
+}
+
+fn logon_id(key: &str) -> String {
+    format!("0x{:x}", 0x10_0000 + (stable_hash(key) & 0xfff_ffff))
+}
+
+fn process_id(key: &str) -> String {
+    format!("0x{:x}", 0x100 + (stable_hash(key) % 0x4000) * 4)
+}
+
+fn guid(hash: u64) -> String {
+    let low = hash.wrapping_mul(6364136223846793005).wrapping_add(1);
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{:04X}-{:012X}}}",
+        hash >> 32,
+        (hash >> 16) & 0xffff,
+        hash & 0xffff,
+        (low >> 48) & 0xffff,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+fn stable_hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_chain_logons_to_processes() {
+        let mut config = test_config();
+        config.events.clear();
+        let collect = || {
+            let mut generator = WindowsSecurityGenerator::from_registry(
+                &config,
+                test_registry(),
+                test_start_time(),
+            )
+            .unwrap();
+            (0..400)
+                .map_while(|_| generator.next_event())
+                .collect::<Vec<_>>()
+        };
+        let events = collect();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| event.payload.clone())
+                .collect::<Vec<_>>(),
+            collect()
+                .into_iter()
+                .map(|event| event.payload)
+                .collect::<Vec<_>>()
+        );
+        assert!(events
+            .windows(2)
+            .all(|pair| pair[0].envelope.timestamp <= pair[1].envelope.timestamp));
+
+        let mut logons = BTreeMap::new();
+        let mut record_ids: HashMap<String, u64> = HashMap::new();
+        for event in &events {
+            let system = &event.payload["System"];
+            let data = &event.payload["EventData"];
+            let computer = system["Computer"].as_str().unwrap().to_string();
+            let record_id = system["EventRecordID"].as_u64().unwrap();
+            if let Some(previous) = record_ids.insert(computer, record_id) {
+                assert_eq!(record_id, previous + 1);
+            }
+            match system["EventID"].as_u64().unwrap() {
+                4624 => {
+                    let logon_id = data["TargetLogonId"].as_str().unwrap();
+                    logons.insert(logon_id.to_string(), event.envelope.actor.id.clone());
+                }
+                4672 => {
+                    let logon_id = data["SubjectLogonId"].as_str().unwrap();
+                    assert_eq!(logons[logon_id], event.envelope.actor.id);
+                    assert_ne!(event.envelope.actor.id, "user-primary");
+                }
+                4688 => {
+                    let logon_id = data["SubjectLogonId"].as_str().unwrap();
+                    assert_eq!(logons[logon_id], event.envelope.actor.id);
+                    assert_eq!(event.envelope.session_id.as_deref(), Some(logon_id));
+                }
+                4625 => assert!(matches!(event.envelope.outcome, Outcome::Failure)),
+                other => panic!("unexpected event id {other}"),
+            }
+        }
+        assert!(logons.values().any(|actor| actor == "svc-ci"));
+    }
+
+    #[test]
+    fn injected_events_render_as_event_xml() {
+        let mut generator = WindowsSecurityGenerator::from_registry(
+            &test_config(),
+            test_registry(),
+            test_start_time(),
+        )
+        .unwrap();
+        let event = std::iter::from_fn(|| generator.next_event())
+            .find(|event| event.label.is_some())
+            .unwrap();
+        assert_eq!(event.envelope.event_type, "4625");
+        assert_eq!(
+            event.envelope.tenant_id.as_deref(),
+            Some("corp.example.com")
+        );
+        let row: WindowsSecurityEvent = serde_json::from_value(event.payload).unwrap();
+        assert_eq!(row.system.computer, "SRV-FILE01.corp.example.com");
+        assert_eq!(row.event_data["TargetUserName"], "primary.user");
+        assert_eq!(row.event_data["SubStatus"], "0xc0000234");
+
+        let xml = row.xml();
+        assert!(xml.starts_with(
+            "<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'><System><Provider Name='Microsoft-Windows-Security-Auditing'"
+        ));
+        assert!(xml.contains("<EventID>4625</EventID><Version>0</Version>"));
+        assert!(xml.contains("<Computer>SRV-FILE01.corp.example.com</Computer>"));
+        assert!(xml.contains(
+            "<Data Name='LogonType'>3</Data><Data Name='LogonProcessName'>NtLmSsp </Data><Data Name='AuthenticationPackageName'>NTLM</Data>"
+        ));
+        assert!(xml.contains("<Data Name='IpAddress'>203.0.113.45</Data>"));
+
+        let mut config = test_config();
+        config.events[0].event_id = 4720;
+        assert!(matches!(
+            WindowsSecurityGenerator::from_registry(&config, test_registry(), test_start_time()),
+            Err(WindowsSecurityError::UnsupportedEventId(4720))
+        ));
+    }
+
+    fn test_start_time() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-05T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn test_config() -> WindowsSecuritySourceConfig {
+        WindowsSecuritySourceConfig {
+            identity_registry_path: String::new(),
+            domain: "corp.example.com".to_string(),
+            netbios_domain: None,
+            servers: None,
+            baseline_events_per_actor: None,
+            baseline_source_ips: None,
+            linked_sessions: false,
+            session_correlation: 1.0,
+            arrival: None,
+            events: vec![WindowsSecurityEventConfig {
+                actor_id: "user-primary".to_string(),
+                offset_seconds: Some(10),
+                time_created: None,
+                event_id: 4625,
+                computer: Some("SRV-FILE01".to_string()),
+                logon_id: None,
+                logon_type: Some(3),
+                ip_address: Some("203.0.113.45".to_string()),
+                process_name: None,
+                parent_process_name: None,
+                command_line: None,
+                fields: BTreeMap::from([("SubStatus".to_string(), "0xc0000234".to_string())]),
+                scenario_id: Some("password-spray".to_string()),
+                technique_id: Some("T1110.003".to_string()),
+                technique_ids: Vec::new(),
+            }],
+        }
+    }
+
+    fn test_registry() -> IdentityRegistry {
+        IdentityRegistry::new(
+            "test",
+            vec![
+                identity(
+                    "user-primary",
+                    "primary.user@example.com",
+                    "Developer",
+                    false,
+                ),
+                identity(
+                    "user-admin",
+                    "admin@example.com",
+                    "Cloud platform administrator",
+                    false,
+                ),
+                identity("svc-ci", "svc.ci@example.com", "Automation", true),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn identity(actor_id: &str, email: &str, persona: &str, service_account: bool) -> Identity {
+        Identity {
+            actor_id: actor_id.to_string(),
+            email: email.to_string(),
+            employee_id: format!("E-{actor_id}"),
+            display_name: actor_id.to_string(),
+            role_persona: persona.to_string(),
+            department: "Test department".to_string(),
+            home_location: "Test location".to_string(),
+            normal_countries_regions: vec!["Australia".to_string()],
+            okta_user_id: format!("okta-{actor_id}"),
+            databricks_username: email.to_string(),
+            aws_principals: Vec::new(),
+            service_account,
+            tags: Vec::new(),
+            rate_per_hour: Some(20.0),
+            active_start_hour: None,
+            active_hours: None,
+            timezone_offset: None,
+            weekend_active: None,
+            service_pattern: None,
+            insider_persona: None,
+            on_call: None,
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Windows Security channel event, laid out like the rendered event XML.
+///
+/// Serialized as `{"System": {...}, "EventData": {...}}`;
+/// [`WindowsSecurityEvent::xml`] renders the `<Event>` element that
+/// `wevtutil qe Security` prints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowsSecurityEvent {
+    #[serde(rename = "System")]
+    pub system: WindowsEventSystem,
+    #[serde(rename = "EventData")]
+    pub event_data: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WindowsEventSystem {
+    pub provider: WindowsEventProvider,
+    #[serde(rename = "EventID")]
+    pub event_id: u32,
+    pub version: u8,
+    pub level: u8,
+    pub task: u16,
+    pub opcode: u8,
+    /// Hex keyword mask; `0x8020000000000000` is Audit Success and `0x8010000000000000` Audit Failure.
+    pub keywords: String,
+    pub time_created: WindowsTimeCreated,
+    /// Per-computer sequence number, assigned in emission order.
+    #[serde(rename = "EventRecordID")]
+    pub event_record_id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<WindowsCorrelation>,
+    pub execution: WindowsExecution,
+    pub channel: String,
+    pub computer: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WindowsEventProvider {
+    pub name: String,
+    pub guid: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WindowsTimeCreated {
+    /// RFC 3339 with 100 ns precision (`2026-01-01T09:00:00.1234567Z`).
+    pub system_time: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowsCorrelation {
+    #[serde(rename = "ActivityID")]
+    pub activity_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowsExecution {
+    #[serde(rename = "ProcessID")]
+    pub process_id: u32,
+    #[serde(rename = "ThreadID")]
+    pub thread_id: u32,
+}
+
+impl WindowsSecurityEvent {
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// Renders the event as one line of event XML.
+    ///
+    /// `Data` elements follow the event's manifest order; fields the
+    /// manifest does not list are appended in name order.
+    pub fn xml(&self) -> String {
+        let system = &self.system;
+        let mut xml = String::from(
+            "<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'><System>",
+        );
+        xml.push_str(&format!(
+            "<Provider Name='{}' Guid='{}'/>",
+            escape(&system.provider.name),
+            escape(&system.provider.guid)
+        ));
+        xml.push_str(&format!(
+            "<EventID>{}</EventID><Version>{}</Version><Level>{}</Level><Task>{}</Task><Opcode>{}</Opcode><Keywords>{}</Keywords>",
+            system.event_id,
+            system.version,
+            system.level,
+            system.task,
+            system.opcode,
+            escape(&system.keywords)
+        ));
+        xml.push_str(&format!(
+            "<TimeCreated SystemTime='{}'/><EventRecordID>{}</EventRecordID>",
+            escape(&system.time_created.system_time),
+            system.event_record_id
+        ));
+        match &system.correlation {
+            Some(correlation) => xml.push_str(&format!(
+                "<Correlation ActivityID='{}'/>",
+                escape(&correlation.activity_id)
+            )),
+            None => xml.push_str("<Correlation/>"),
+        }
+        xml.push_str(&format!(
+            "<Execution ProcessID='{}' ThreadID='{}'/><Channel>{}</Channel><Computer>{}</Computer><Security/></System><EventData>",
+            system.execution.process_id,
+            system.execution.thread_id,
+            escape(&system.channel),
+            escape(&system.computer)
+        ));
+
+        let manifest = event_data_fields(system.event_id);
+        let extra = self
+            .event_data
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !manifest.contains(name));
+        for name in manifest.iter().copied().chain(extra) {
+            if let Some(value) = self.event_data.get(name) {
+                xml.push_str(&format!(
+                    "<Data Name='{}'>{}</Data>",
+                    escape(name),
+                    escape(value)
+                ));
+            }
+        }
+        xml.push_str("</EventData></Event>");
+        xml
+    }
+}
+
+/// `EventData` field order from the Security-Auditing manifest.
+pub fn event_data_fields(event_id: u32) -> &'static [&'static str] {
+    match event_id {
+        4624 => &[
+            "SubjectUserSid",
+            "SubjectUserName",
+            "SubjectDomainName",
+            "SubjectLogonId",
+            "TargetUserSid",
+            "TargetUserName",
+            "TargetDomainName",
+            "TargetLogonId",
+            "LogonType",
+            "LogonProcessName",
+            "AuthenticationPackageName",
+            "WorkstationName",
+            "LogonGuid",
+            "TransmittedServices",
+            "LmPackageName",
+            "KeyLength",
+            "ProcessId",
+            "ProcessName",
+            "IpAddress",
+            "IpPort",
+            "ImpersonationLevel",
+            "RestrictedAdminMode",
+            "TargetOutboundUserName",
+            "TargetOutboundDomainName",
+            "VirtualAccount",
+            "TargetLinkedLogonId",
+            "ElevatedToken",
+        ],
+        4625 => &[
+            "SubjectUserSid",
+            "SubjectUserName",
+            "SubjectDomainName",
+            "SubjectLogonId",
+            "TargetUserSid",
+            "TargetUserName",
+            "TargetDomainName",
+            "Status",
+            "FailureReason",
+            "SubStatus",
+            "LogonType",
+            "LogonProcessName",
+            "AuthenticationPackageName",
+            "WorkstationName",
+            "TransmittedServices",
+            "LmPackageName",
+            "KeyLength",
+            "ProcessId",
+            "ProcessName",
+            "IpAddress",
+            "IpPort",
+        ],
+        4672 => &[
+            "SubjectUserSid",
+            "SubjectUserName",
+            "SubjectDomainName",
+            "SubjectLogonId",
+            "PrivilegeList",
+        ],
+        4688 => &[
+            "SubjectUserSid",
+            "SubjectUserName",
+            "SubjectDomainName",
+            "SubjectLogonId",
+            "NewProcessId",
+            "NewProcessName",
+            "TokenElevationType",
+            "ProcessId",
+            "CommandLine",
+            "TargetUserSid",
+            "TargetUserName",
+            "TargetDomainName",
+            "TargetLogonId",
+            "ParentProcessName",
+            "MandatoryLabel",
+        ],
+        _ => &[],
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\'' => escaped.push_str("&apos;"),
+            '"' => escaped.push_str("&quot;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
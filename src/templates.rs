@@ -0,0 +1,143 @@
+//! Starter configs built into the binary for `seclog init`.
+//!
+//! Each [`Template`] pairs a generation config with the actor population config
+//! it reads. [`write_template`] writes both side by side, so `seclog gen` runs
+//! from the written config without further edits.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name of the written generation config.
+pub const CONFIG_FILE: &str = "config.toml";
+/// File name of the written population config.
+pub const POPULATION_FILE: &str = "actors.toml";
+
+/// A built-in config and population pair.
+#[derive(Debug, Clone, Copy)]
+pub struct Template {
+    pub name: &'static str,
+    pub summary: &'static str,
+    /// Generation config; relative paths start with `./`.
+    pub config: &'static str,
+    /// Actor population config read through `population_config_path`.
+    pub population: &'static str,
+}
+
+/// Built-in templates, smallest first.
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "small-lab",
+        summary: "30 actors in one AWS account and Okta org; one day of CloudTrail and Okta as JSON",
+        config: include_str!("templates/small_lab/config.toml"),
+        population: include_str!("templates/small_lab/actors.toml"),
+    },
+    Template {
+        name: "enterprise",
+        summary: "2,000 actors across 12 skewed AWS accounts, Entra ID, Okta, and GitHub; one week backfilled to Parquet",
+        config: include_str!("templates/enterprise/config.toml"),
+        population: include_str!("templates/enterprise/actors.toml"),
+    },
+    Template {
+        name: "ato-demo",
+        summary: "Impossible travel and a stolen-credential takeover of one user, with scenario labels",
+        config: include_str!("templates/ato_demo/config.toml"),
+        population: include_str!("templates/ato_demo/actors.toml"),
+    },
+    Template {
+        name: "load-test",
+        summary: "CloudTrail ramp, spike, and soak phases up to 10,000 events per second to Parquet",
+        config: include_str!("templates/load_test/config.toml"),
+        population: include_str!("templates/load_test/actors.toml"),
+    },
+];
+
+/// Looks up a template by name.
+pub fn find(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|template| template.name == name)
+}
+
+/// Writes `template` into `dir`, creating it if needed, and returns the config
+/// and population paths.
+///
+/// Relative paths in the config, such as `population_config_path` and
+/// `output.dir`, are rewritten to sit under `dir`, so the config works from
+/// the current directory. Existing files are kept, and the call fails, unless
+/// `force` is set.
+pub fn write_template(
+    template: &Template,
+    dir: impl AsRef<Path>,
+    force: bool,
+) -> io::Result<(PathBuf, PathBuf)> {
+    let dir = dir.as_ref();
+    let config_path = dir.join(CONFIG_FILE);
+    let population_path = dir.join(POPULATION_FILE);
+    if !force {
+        if let Some(existing) = [&config_path, &population_path]
+            .into_iter()
+            .find(|path| path.exists())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists; pass --force to overwrite",
+                    existing.display()
+                ),
+            ));
+        }
+    }
+
+    let config = if dir == Path::new(".") {
+        template.config.to_string()
+    } else {
+        let prefix = dir.display().to_string().replace('\\', "/");
+        template
+            .config
+            .replace("\"./", &format!("\"{}/", prefix.trim_end_matches('/')))
+    };
+    fs::create_dir_all(dir)?;
+    fs::write(&config_path, config)?;
+    fs::write(&population_path, template.population)?;
+    Ok((config_path, population_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{Config, PopulationConfig, SourceConfig};
+    use crate::validate::{validate_config, validate_population, Severity};
+
+    #[test]
+    fn templates_write_configs_that_validate() {
+        let dir = std::env::temp_dir().join(format!("seclog-templates-{}", std::process::id()));
+        for template in TEMPLATES {
+            let target = dir.join(template.name);
+            let (config_path, population_path) = write_template(template, &target, false).unwrap();
+
+            let config = Config::from_path(&config_path).unwrap();
+            let SourceConfig::Multi(source) = &config.source else {
+                panic!("{}: expected a multi source", template.name);
+            };
+            assert_eq!(
+                source.population_config_path.as_deref().map(Path::new),
+                Some(population_path.as_path()),
+                "{}",
+                template.name
+            );
+            let population = PopulationConfig::from_path(&population_path).unwrap();
+            let errors = validate_config(&config)
+                .into_iter()
+                .chain(validate_population(&population, ""))
+                .filter(|issue| issue.severity == Severity::Error)
+                .collect::<Vec<_>>();
+            assert!(errors.is_empty(), "{}: {errors:?}", template.name);
+
+            let err = write_template(template, &target, false).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+            write_template(template, &target, true).unwrap();
+        }
+        assert!(find("ato-demo").is_some());
+        assert!(find("ato_demo").is_none());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,1807 @@
+//! Static validation for generation and population configs.
+//!
+//! Checks run without generating events: field ranges, cross-field
+//! consistency (region weights, output routes, tenant ids), and referenced
+//! files. Every issue carries the dotted config path it applies to, so
+//! `seclog validate` can report all problems in one pass instead of failing on
+//! the first one at generation time.
+
+use crate::api::{apply_event_stages, build_event_source};
+use crate::core::actors::InsiderPersona;
+use crate::core::arrival::Arrivals;
+use crate::core::attack::is_valid_technique_id;
+use crate::core::config::{
+    AlbAccessLogConfig, ApiStormConfig, ArrivalConfig, CloudTrailSourceConfig, Config,
+    CredentialTakeoverConfig, DataEventsConfig, EntraFederationConfig, ErrorCodeConfig,
+    ErrorRateConfig, ErrorSurgeConfig, FileOutputConfig, FormatConfig, IcebergOptions,
+    ImpossibleTravelConfig, OutputConfig, OutputPartitioning, PaddingConfig,
+    PolicyRemediationConfig, PopulationConfig, RateLimitConfig, RedactionAction, RedactionConfig,
+    ResolverQueryLogConfig, SourceConfig, TrafficConfig, WriteFailurePolicy,
+};
+use crate::core::ip_plan::IpPlan;
+use crate::core::selector::ActorSelector;
+use crate::core::traffic::{parse_duration, LoadSchedule, TrafficCalendar};
+use crate::formats::databricks_volume::normalize_volume_path;
+use crate::formats::filename::FilenameTemplate;
+use crate::formats::ocsf::OcsfWriter;
+use crate::formats::parquet::writer_properties;
+use crate::sources::cloudtrail::data_events::DataService;
+use crate::sources::cloudtrail::injection::plan_injection;
+use crate::sources::cloudtrail::narrative::NarrativeSet;
+use crate::sources::cloudtrail::travel::City;
+use crate::sources::okta::admin_catalog::AdminCatalog;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::str::FromStr;
+
+const SERVICE_PROFILES: &[&str] = &[
+    "generic",
+    "ec2_reaper",
+    "datalake_bot",
+    "logs_shipper",
+    "metrics_collector",
+];
+const ROLES: &[&str] = &["admin", "developer", "readonly", "auditor"];
+
+/// How serious a validation finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The config is accepted but part of it is ignored or likely unintended.
+    Warning,
+    /// Generation would fail or silently misbehave.
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// One validation finding, keyed by its config path (e.g. `source.sources[0].regions`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.path, self.message)
+    }
+}
+
+/// Returns whether any issue is an error.
+pub fn has_errors(issues: &[ValidationIssue]) -> bool {
+    issues.iter().any(|issue| issue.severity == Severity::Error)
+}
+
+#[derive(Default)]
+struct Issues(Vec<ValidationIssue>);
+
+impl Issues {
+    fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Error, path.into(), message.into());
+    }
+
+    fn warning(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Warning, path.into(), message.into());
+    }
+
+    fn push(&mut self, severity: Severity, path: String, message: String) {
+        self.0.push(ValidationIssue {
+            severity,
+            path,
+            message,
+        });
+    }
+}
+
+/// Validates a generation config, including referenced population configs.
+///
+/// When the static checks pass, the event source is also constructed (without
+/// pulling any events) so catalog, registry, and injected-event errors surface.
+pub fn validate_config(config: &Config) -> Vec<ValidationIssue> {
+    let mut issues = Issues::default();
+    let start_time = check_traffic(&config.traffic, &mut issues);
+    check_output(&config.output, &config.source, &mut issues);
+    check_source(&config.source, "source", false, &mut issues);
+    if config.seed.is_none() {
+        check_id_seed(&config.source, "source", &mut issues);
+    }
+    if let Some(padding) = &config.padding {
+        check_padding(padding, &config.source, &mut issues);
+    }
+    if let Some(redaction) = &config.redaction {
+        check_redaction(redaction, &config.source, &mut issues);
+    }
+
+    if !has_errors(&issues.0) {
+        let built = build_event_source(
+            &config.source,
+            config.seed,
+            start_time.unwrap_or_else(Utc::now),
+        )
+        .and_then(|source| apply_event_stages(source, config));
+        if let Err(err) = built {
+            issues.error("source", err.to_string());
+        }
+    }
+    issues.0
+}
+
+/// Validates an actor population config; `prefix` is prepended to every path.
+pub fn validate_population(config: &PopulationConfig, prefix: &str) -> Vec<ValidationIssue> {
+    let mut issues = Issues::default();
+    check_population(config, prefix, &mut issues);
+    issues.0
+}
+
+fn join(prefix: &str, field: &str) -> String {
+    if prefix.is_empty() {
+        field.to_string()
+    } else {
+        format!("{prefix}.{field}")
+    }
+}
+
+fn check_traffic(traffic: &TrafficConfig, issues: &mut Issues) -> Option<DateTime<Utc>> {
+    let parse = |field: &str, value: Option<&String>, issues: &mut Issues| {
+        let value = value?;
+        match DateTime::parse_from_rfc3339(value) {
+            Ok(time) => Some(time.with_timezone(&Utc)),
+            Err(err) => {
+                issues.error(
+                    format!("traffic.{field}"),
+                    format!("not an RFC3339 timestamp ({err}): {value}"),
+                );
+                None
+            }
+        }
+    };
+    let start = parse("start_time", traffic.start_time.as_ref(), issues);
+    let until = parse("until_time", traffic.until_time.as_ref(), issues);
+    if let (Some(start), Some(until)) = (start, until) {
+        if until <= start {
+            issues.error("traffic.until_time", "must be after traffic.start_time");
+        }
+    }
+    if let Some(scale) = traffic.time_scale {
+        if !scale.is_finite() || scale <= 0.0 {
+            issues.error("traffic.time_scale", "must be greater than 0");
+        }
+    }
+    if let Some(max_catch_up) = &traffic.max_catch_up {
+        if parse_duration(max_catch_up).is_none() {
+            issues.error(
+                "traffic.max_catch_up",
+                format!("not a valid duration: {max_catch_up}"),
+            );
+        }
+    }
+    if !traffic.phases.is_empty() {
+        if let Err(err) = LoadSchedule::from_phases(&traffic.phases) {
+            issues.error("traffic.phases", err.0);
+        }
+    }
+    if let Some(calendar) = &traffic.calendar {
+        if let Err(err) = TrafficCalendar::from_config(calendar) {
+            issues.error("traffic.calendar", err.0);
+        }
+    }
+    start
+}
+
+fn check_output(output: &OutputConfig, source: &SourceConfig, issues: &mut Issues) {
+    let keys = source.envelope_sources();
+    match output {
+        OutputConfig::File(file) => check_file_output(file, "output", issues),
+        OutputConfig::Zerobus(zerobus) => {
+            for key in &keys {
+                if !zerobus.tables.contains_key(key) {
+                    issues.error(
+                        "output.tables",
+                        format!("no zerobus table configured for source {key}"),
+                    );
+                }
+            }
+            if zerobus.batch_size == 0 {
+                issues.error("output.batch_size", "must be greater than 0");
+            }
+        }
+        OutputConfig::DatabricksVolume(volume) => {
+            if let Err(err) = normalize_volume_path(&volume.volume_path) {
+                issues.error("output.volume_path", err.to_string());
+            }
+        }
+        OutputConfig::Stdout(_) => {}
+        OutputConfig::ArrowIpc(arrow) => {
+            match (&arrow.path, &arrow.listen) {
+                (Some(_), Some(_)) => {
+                    issues.error("output.listen", "cannot be combined with output.path")
+                }
+                (None, None) => issues.error("output.path", "set output.path or output.listen"),
+                _ => {}
+            }
+            if arrow.batch_size == 0 {
+                issues.error("output.batch_size", "must be greater than 0");
+            }
+        }
+        OutputConfig::DuckDb(duckdb) => {
+            if duckdb.path.trim().is_empty() {
+                issues.error("output.path", "must not be empty");
+            }
+            if duckdb.table.split('.').any(|part| part.trim().is_empty()) {
+                issues.error(
+                    "output.table",
+                    format!("not a table name: {:?}", duckdb.table),
+                );
+            }
+            if duckdb.batch_size == 0 {
+                issues.error("output.batch_size", "must be greater than 0");
+            }
+        }
+        OutputConfig::SplunkHec(hec) => {
+            if !(hec.url.starts_with("https://") || hec.url.starts_with("http://")) {
+                issues.error("output.url", format!("not an http(s) URL: {}", hec.url));
+            }
+            if hec.batch_size == 0 {
+                issues.error("output.batch_size", "must be greater than 0");
+            }
+            if let Some(compression) = &hec.compression {
+                if !compression.eq_ignore_ascii_case("gzip") {
+                    issues.error(
+                        "output.compression",
+                        format!("unsupported compression {compression}; use gzip"),
+                    );
+                }
+            }
+            if let Some(channel) = &hec.channel {
+                if !is_guid(channel) {
+                    issues.error("output.channel", format!("not a GUID: {channel}"));
+                }
+            }
+            for (field, map) in [("indexes", &hec.indexes), ("sourcetypes", &hec.sourcetypes)] {
+                for key in map.keys() {
+                    if !keys.contains(key) {
+                        issues.warning(
+                            format!("output.{field}.{key}"),
+                            "the configured source does not emit this envelope source",
+                        );
+                    }
+                }
+            }
+        }
+        OutputConfig::Sentinel(sentinel) => {
+            if !(sentinel.endpoint.starts_with("https://")
+                || sentinel.endpoint.starts_with("http://"))
+            {
+                issues.error(
+                    "output.endpoint",
+                    format!("not an http(s) URL: {}", sentinel.endpoint),
+                );
+            }
+            if !sentinel.dcr_immutable_id.starts_with("dcr-") {
+                issues.error(
+                    "output.dcr_immutable_id",
+                    format!(
+                        "expected a dcr-... immutable ID, got {}",
+                        sentinel.dcr_immutable_id
+                    ),
+                );
+            }
+            if sentinel.stream.is_none() {
+                for key in &keys {
+                    if !sentinel.streams.contains_key(key) {
+                        issues.error(
+                            "output.streams",
+                            format!("no sentinel stream configured for source {key}"),
+                        );
+                    }
+                }
+            }
+            for (key, stream) in sentinel
+                .streams
+                .iter()
+                .map(|(key, stream)| (Some(key), stream))
+                .chain(sentinel.stream.iter().map(|stream| (None, stream)))
+            {
+                if !stream.starts_with("Custom-") && !stream.starts_with("Microsoft-") {
+                    let path = match key {
+                        Some(key) => format!("output.streams.{key}"),
+                        None => "output.stream".to_string(),
+                    };
+                    issues.warning(
+                        path,
+                        format!("DCR stream names start with Custom- or Microsoft-: {stream}"),
+                    );
+                }
+            }
+            for key in sentinel.streams.keys() {
+                if !keys.contains(key) {
+                    issues.warning(
+                        format!("output.streams.{key}"),
+                        "the configured source does not emit this envelope source",
+                    );
+                }
+            }
+            if sentinel.batch_size == 0 {
+                issues.error("output.batch_size", "must be greater than 0");
+            }
+            if sentinel.max_request_bytes == 0 {
+                issues.error("output.max_request_bytes", "must be greater than 0");
+            } else if sentinel.max_request_bytes > 1 << 20 {
+                issues.warning(
+                    "output.max_request_bytes",
+                    "the Logs Ingestion API rejects requests over 1 MB",
+                );
+            }
+            if let Some(compression) = &sentinel.compression {
+                if !compression.eq_ignore_ascii_case("gzip") {
+                    issues.error(
+                        "output.compression",
+                        format!("unsupported compression {compression}; use gzip"),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn check_file_output(output: &FileOutputConfig, path: &str, issues: &mut Issues) {
+    if output.dir.trim().is_empty() {
+        issues.error(join(path, "dir"), "must not be empty");
+    }
+    if output.files.target_size_mb == 0 {
+        issues.error(join(path, "files.target_size_mb"), "must be greater than 0");
+    }
+    if output.files.max_buffered_mb == Some(0) {
+        issues.error(
+            join(path, "files.max_buffered_mb"),
+            "must be greater than 0",
+        );
+    }
+    if let Some(ordering) = &output.files.ordering {
+        if !ordering.window_seconds.is_finite() || ordering.window_seconds < 0.0 {
+            issues.error(
+                join(path, "files.ordering.window_seconds"),
+                "must be 0 or greater",
+            );
+        }
+    }
+    if let Some(template) = &output.files.filename_template {
+        if let Err(err) = FilenameTemplate::parse(template) {
+            issues.error(join(path, "files.filename_template"), err.to_string());
+        }
+        if !output
+            .format_dirs()
+            .iter()
+            .any(|(format, _)| format.uses_filename_template())
+        {
+            issues.warning(
+                join(path, "files.filename_template"),
+                "only applies to jsonl, azure_monitor, parquet, delta, iceberg, and text formats",
+            );
+        }
+    }
+    let mut dirs = BTreeSet::new();
+    for (index, (format, dir)) in output.format_dirs().into_iter().enumerate() {
+        let format_path = match index {
+            0 => "format".to_string(),
+            index => format!("formats[{}]", index - 1),
+        };
+        if !dirs.insert(dir.clone()) {
+            issues.error(
+                join(path, &format_path),
+                format!("writes to {} like another format", dir.display()),
+            );
+        }
+        check_format(format, &join(path, &format_path), issues);
+        if let FormatConfig::Iceberg(options) = format {
+            if output.partition_by != OutputPartitioning::None
+                && options.parquet.timestamp_strings == Some(true)
+            {
+                issues.error(
+                    join(path, &format!("{format_path}.timestamp_strings")),
+                    "iceberg partitioning requires timestamp columns",
+                );
+            }
+        }
+    }
+    if let Some(retry) = &output.write_retry {
+        if retry.initial_backoff_ms > retry.max_backoff_ms {
+            issues.error(
+                join(path, "write_retry.initial_backoff_ms"),
+                "must not exceed max_backoff_ms",
+            );
+        }
+        if retry.error_budget.is_some() && retry.on_failure == WriteFailurePolicy::Abort {
+            issues.warning(
+                join(path, "write_retry.error_budget"),
+                "only applies with on_failure = \"skip\" or \"dead_letter\"",
+            );
+        }
+        match (retry.on_failure, &retry.dead_letter_dir) {
+            (WriteFailurePolicy::DeadLetter, None) => issues.error(
+                join(path, "write_retry.dead_letter_dir"),
+                "is required with on_failure = \"dead_letter\"",
+            ),
+            (WriteFailurePolicy::DeadLetter, Some(_)) | (_, None) => {}
+            (_, Some(_)) => issues.warning(
+                join(path, "write_retry.dead_letter_dir"),
+                "only applies with on_failure = \"dead_letter\"",
+            ),
+        }
+    }
+    if let Some(faults) = &output.fault_injection {
+        if !(0.0..=1.0).contains(&faults.probability) {
+            issues.error(
+                join(path, "fault_injection.probability"),
+                "must be between 0.0 and 1.0",
+            );
+        }
+        if faults.errors.is_empty() {
+            issues.error(
+                join(path, "fault_injection.errors"),
+                "must list at least one error",
+            );
+        }
+        if output.write_retry.is_none() {
+            issues.warning(
+                join(path, "fault_injection"),
+                "without write_retry the first injected error aborts the run",
+            );
+        }
+    }
+}
+
+fn check_format(format: &FormatConfig, path: &str, issues: &mut Issues) {
+    match format {
+        FormatConfig::Parquet(options)
+        | FormatConfig::Delta(options)
+        | FormatConfig::Iceberg(IcebergOptions {
+            parquet: options, ..
+        }) => {
+            if let Err(err) = writer_properties(options) {
+                issues.error(path.to_string(), err.to_string());
+            }
+            if options.encoder_queue == Some(0) {
+                issues.error(join(path, "encoder_queue"), "must be greater than 0");
+            }
+        }
+        FormatConfig::CloudwatchLogs(options) if options.events_per_message == 0 => {
+            issues.error(join(path, "events_per_message"), "must be greater than 0");
+        }
+        FormatConfig::Jsonl(options) => {
+            if let Some(digest) = &options.digest {
+                if digest.interval_seconds == 0 {
+                    issues.error(
+                        join(path, "digest.interval_seconds"),
+                        "must be greater than 0",
+                    );
+                }
+                if digest.s3_bucket.trim().is_empty() {
+                    issues.error(join(path, "digest.s3_bucket"), "must not be empty");
+                }
+            }
+        }
+        FormatConfig::Avro(options) => {
+            if let Some(compression) = options.compression.as_deref() {
+                if !matches!(compression.trim(), "" | "none" | "null" | "deflate") {
+                    issues.error(
+                        join(path, "compression"),
+                        format!("must be none or deflate, got {compression}"),
+                    );
+                }
+            }
+        }
+        FormatConfig::Ocsf(options) => {
+            if let Err(err) = OcsfWriter::check_options(options) {
+                issues.error(join(path, "compression"), err.to_string());
+            }
+            if options.sources.as_ref().is_some_and(Vec::is_empty) {
+                issues.warning(join(path, "sources"), "is empty, so no events are written");
+            }
+        }
+        FormatConfig::Csv(options) => {
+            for (idx, column) in options.columns.iter().enumerate() {
+                let column_path = format!("{}[{idx}]", join(path, "columns"));
+                if column.name.trim().is_empty() {
+                    issues.error(join(&column_path, "name"), "must not be empty");
+                }
+                let valid = column
+                    .path
+                    .strip_prefix("payload.")
+                    .is_some_and(|rest| !rest.split('.').any(str::is_empty));
+                if !valid {
+                    issues.error(
+                        join(&column_path, "path"),
+                        format!("must be a field under payload., got {}", column.path),
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_source(config: &SourceConfig, path: &str, inherited: bool, issues: &mut Issues) {
+    match config {
+        SourceConfig::CloudTrail(config) => check_cloudtrail(config, path, issues),
+        SourceConfig::DatabricksAudit(config) => {
+            check_registry_path(&config.identity_registry_path, path, inherited, issues);
+            check_ip_pools(config.baseline_source_ips.as_ref(), path, issues);
+            check_arrival(config.arrival.as_ref(), path, issues);
+            check_session_correlation(Some(config.session_correlation), path, issues);
+        }
+        SourceConfig::OktaSystemLog(config) => {
+            check_registry_path(&config.identity_registry_path, path, inherited, issues);
+            check_ip_pools(config.baseline_source_ips.as_ref(), path, issues);
+            check_arrival(config.arrival.as_ref(), path, issues);
+            check_session_correlation(Some(config.session_correlation), path, issues);
+            if let Some(directory) = &config.directory {
+                let field = join(path, "directory");
+                for (name, rate) in [
+                    ("admin_operation_rate", directory.admin_operation_rate),
+                    ("admin_group_share", directory.admin_group_share),
+                ] {
+                    if rate.is_some_and(|rate| !(0.0..=1.0).contains(&rate)) {
+                        issues.error(join(&field, name), "must be between 0.0 and 1.0");
+                    }
+                }
+                for (idx, catalog_path) in directory.catalog_paths.iter().enumerate() {
+                    let path_field = format!("{}[{idx}]", join(&field, "catalog_paths"));
+                    if check_file(catalog_path, &path_field, issues) {
+                        if let Err(err) = AdminCatalog::load(catalog_path) {
+                            issues.error(&path_field, err);
+                        }
+                    }
+                }
+            }
+            if let Some(access) = &config.privileged_access {
+                let field = join(path, "privileged_access");
+                if config.directory.is_none() {
+                    issues.error(&field, "requires [source.directory]");
+                }
+                for (name, rate) in [
+                    ("activation_rate", access.activation_rate),
+                    ("elevated_operation_rate", access.elevated_operation_rate),
+                    ("unapproved_share", access.unapproved_share),
+                ] {
+                    if rate.is_some_and(|rate| !(0.0..=1.0).contains(&rate)) {
+                        issues.error(join(&field, name), "must be between 0.0 and 1.0");
+                    }
+                }
+                if access.duration_minutes == Some(0) {
+                    issues.error(join(&field, "duration_minutes"), "must be greater than 0");
+                }
+                for id in &access.technique_ids {
+                    if !is_valid_technique_id(id) {
+                        issues.error(&field, format!("invalid ATT&CK technique ID: {id}"));
+                    }
+                }
+            }
+        }
+        SourceConfig::GitHubAudit(config) => {
+            check_registry_path(&config.identity_registry_path, path, inherited, issues);
+            check_ip_pools(config.baseline_source_ips.as_ref(), path, issues);
+            check_arrival(config.arrival.as_ref(), path, issues);
+            check_session_correlation(Some(config.session_correlation), path, issues);
+            if config.org.trim().is_empty() {
+                issues.error(join(path, "org"), "must not be empty");
+            }
+        }
+        SourceConfig::KubernetesAudit(config) => {
+            check_registry_path(&config.identity_registry_path, path, inherited, issues);
+            check_ip_pools(config.baseline_source_ips.as_ref(), path, issues);
+            check_arrival(config.arrival.as_ref(), path, issues);
+            check_session_correlation(Some(config.session_correlation), path, issues);
+            if config.cluster.trim().is_empty() {
+                issues.error(join(path, "cluster"), "must not be empty");
+            }
+        }
+        SourceConfig::WindowsSecurity(config) => {
+            check_registry_path(&config.identity_registry_path, path, inherited, issues);
+            check_ip_pools(config.baseline_source_ips.as_ref(), path, issues);
+            check_arrival(config.arrival.as_ref(), path, issues);
+            check_session_correlation(Some(config.session_correlation), path, issues);
+            if config.domain.trim().is_empty() {
+                issues.error(join(path, "domain"), "must not be empty");
+            }
+            for (idx, event) in config.events.iter().enumerate() {
+                if !matches!(event.event_id, 4624 | 4625 | 4672 | 4688) {
+                    issues.error(
+                        format!("{}[{idx}].event_id", join(path, "event")),
+                        format!(
+                            "unsupported event id {} (expected 4624, 4625, 4672, or 4688)",
+                            event.event_id
+                        ),
+                    );
+                }
+            }
+        }
+        SourceConfig::GoogleWorkspace(config) => {
+            check_registry_path(&config.identity_registry_path, path, inherited, issues);
+            check_ip_pools(config.baseline_source_ips.as_ref(), path, issues);
+            check_arrival(config.arrival.as_ref(), path, issues);
+            check_session_correlation(Some(config.session_correlation), path, issues);
+            if config.customer_id.trim().is_empty() {
+                issues.error(join(path, "customer_id"), "must not be empty");
+            }
+            for (idx, event) in config.events.iter().enumerate() {
+                if let Some(time) = &event.time {
+                    if DateTime::parse_from_rfc3339(time).is_err() {
+                        issues.error(
+                            format!("{}[{idx}].time", join(path, "event")),
+                            format!("not an RFC 3339 timestamp: {time}"),
+                        );
+                    }
+                }
+            }
+        }
+        SourceConfig::Multi(config) => {
+            if config.sources.is_empty() {
+                issues.error(
+                    join(path, "sources"),
+                    "multi source requires at least one child source",
+                );
+            }
+            let identities = [
+                config.identity_registry_path.is_some(),
+                config.population_config_path.is_some(),
+                config.population_config.is_some(),
+            ]
+            .into_iter()
+            .filter(|set| *set)
+            .count();
+            if identities > 1 {
+                issues.error(
+                    path,
+                    "set only one of identity_registry_path, population_config_path, or population_config",
+                );
+            }
+            if let Some(registry) = &config.identity_registry_path {
+                check_file(registry, &join(path, "identity_registry_path"), issues);
+            }
+            check_population_source(
+                config.population_config_path.as_deref(),
+                config.population_config.as_ref(),
+                path,
+                issues,
+            );
+            check_session_correlation(config.session_correlation, path, issues);
+
+            let mut ids = BTreeSet::new();
+            for (idx, tenant) in config.tenants.iter().enumerate() {
+                let tenant_path = format!("{}[{idx}]", join(path, "tenants"));
+                if tenant.id.trim().is_empty() {
+                    issues.error(join(&tenant_path, "id"), "must not be empty");
+                } else if !ids.insert(tenant.id.as_str()) {
+                    issues.error(
+                        join(&tenant_path, "id"),
+                        format!("duplicate tenant id: {}", tenant.id),
+                    );
+                }
+                let weight = tenant.weight.unwrap_or(1.0);
+                if !weight.is_finite() || weight <= 0.0 {
+                    issues.error(join(&tenant_path, "weight"), "must be greater than 0");
+                }
+                if let Some(registry) = &tenant.identity_registry_path {
+                    check_file(
+                        registry,
+                        &join(&tenant_path, "identity_registry_path"),
+                        issues,
+                    );
+                }
+                check_population_source(
+                    tenant.population_config_path.as_deref(),
+                    tenant.population_config.as_ref(),
+                    &tenant_path,
+                    issues,
+                );
+            }
+            if !config.tenants.is_empty() && identities > 0 {
+                issues.error(
+                    path,
+                    "multi source with tenants must configure identities per tenant",
+                );
+            }
+
+            let inherited = inherited || identities > 0 || !config.tenants.is_empty();
+            for (idx, source) in config.sources.iter().enumerate() {
+                check_source(
+                    source,
+                    &format!("{}[{idx}]", join(path, "sources")),
+                    inherited,
+                    issues,
+                );
+            }
+
+            if let Some(outputs) = &config.outputs {
+                let keys = SourceConfig::Multi(config.clone()).envelope_sources();
+                let mut routes = outputs.keys().collect::<Vec<_>>();
+                routes.sort();
+                for key in routes {
+                    let output_path = format!("{}.{key}", join(path, "outputs"));
+                    if !keys.contains(key) {
+                        issues.warning(
+                            &output_path,
+                            format!("no child source emits {key}; output is unused"),
+                        );
+                    }
+                    check_file_output(&outputs[key], &output_path, issues);
+                }
+            }
+
+            if let Some(limits) = &config.limits {
+                let keys = SourceConfig::Multi(config.clone()).envelope_sources();
+                let mut names = limits.keys().collect::<Vec<_>>();
+                names.sort();
+                for name in names {
+                    let limit_path = format!("{}.{name}", join(path, "limits"));
+                    if !keys.contains(name) {
+                        issues.warning(
+                            &limit_path,
+                            format!("no child source emits {name}; limit is unused"),
+                        );
+                    }
+                    if let Some(value) = &limits[name].max_sim_duration {
+                        if parse_duration(value).is_none() {
+                            issues.error(
+                                join(&limit_path, "max_sim_duration"),
+                                format!("invalid duration {value:?}"),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Without a top-level seed, `deterministic_ids` would give every run the same IDs.
+fn check_id_seed(config: &SourceConfig, path: &str, issues: &mut Issues) {
+    match config {
+        SourceConfig::CloudTrail(config) if config.deterministic_ids => {
+            issues.error(join(path, "deterministic_ids"), "requires a top-level seed");
+        }
+        SourceConfig::Multi(config) => {
+            for (idx, source) in config.sources.iter().enumerate() {
+                check_id_seed(source, &format!("{}[{idx}]", join(path, "sources")), issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_cloudtrail(config: &CloudTrailSourceConfig, path: &str, issues: &mut Issues) {
+    check_arrival(config.arrival.as_ref(), path, issues);
+    if let Some(population) = &config.actor_population_path {
+        check_file(population, &join(path, "actor_population_path"), issues);
+    }
+    if let Some(registry) = &config.identity_registry_path {
+        check_file(registry, &join(path, "identity_registry_path"), issues);
+    }
+    if let Some(catalog) = &config.catalog_path {
+        check_file(catalog, &join(path, "catalog_path"), issues);
+    }
+    if let Some(selector) = &config.actor_selector {
+        if let Err(err) = ActorSelector::new(selector) {
+            issues.error(join(path, "actor_selector"), err);
+        }
+    }
+    if let Some(regions) = &config.regions {
+        if regions.iter().all(|region| region.trim().is_empty()) {
+            issues.warning(
+                join(path, "regions"),
+                "no non-empty regions; the default region set is used",
+            );
+        }
+    }
+    if let Some(distribution) = &config.region_distribution {
+        let field = join(path, "region_distribution");
+        let regions = config.regions.as_ref().map_or(4, Vec::len);
+        if distribution.len() != regions {
+            issues.error(
+                &field,
+                format!(
+                    "has {} weights but there are {regions} regions",
+                    distribution.len()
+                ),
+            );
+        }
+        check_weights(distribution.iter().copied(), &field, issues);
+    }
+    if let Some(web_identity) = &config.web_identity {
+        let mut profiles = web_identity.keys().collect::<Vec<_>>();
+        profiles.sort();
+        for profile in profiles {
+            if !SERVICE_PROFILES.contains(&profile.as_str()) {
+                issues.error(
+                    format!("{}.{profile}", join(path, "web_identity")),
+                    format!(
+                        "unknown service profile; expected one of {}",
+                        SERVICE_PROFILES.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+    if let Some(federation) = &config.entra_federation {
+        check_entra_federation(federation, &join(path, "entra_federation"), issues);
+    }
+    if let Some(narratives) = &config.narratives {
+        let field = join(path, "narratives");
+        if narratives
+            .session_share
+            .is_some_and(|share| !(0.0..=1.0).contains(&share))
+        {
+            issues.error(join(&field, "session_share"), "must be between 0.0 and 1.0");
+        }
+        for (idx, narrative_path) in narratives.paths.iter().enumerate() {
+            let path_field = format!("{}[{idx}]", join(&field, "paths"));
+            if check_file(narrative_path, &path_field, issues) {
+                if let Err(err) = NarrativeSet::load(narrative_path) {
+                    issues.error(&path_field, err);
+                }
+            }
+        }
+        if narratives.builtin == Some(false) && narratives.paths.is_empty() {
+            issues.error(
+                &field,
+                "no narratives; enable builtin or list narrative files",
+            );
+        }
+    }
+    if let Some(owner) = config
+        .s3_access_logs
+        .as_ref()
+        .and_then(|access_logs| access_logs.bucket_owner.as_ref())
+    {
+        if owner.trim().is_empty() || owner.contains(char::is_whitespace) {
+            issues.error(
+                join(path, "s3_access_logs.bucket_owner"),
+                "must be a non-empty canonical user ID without spaces",
+            );
+        }
+    }
+    if let Some(resolver) = &config.resolver_query_logs {
+        check_resolver_query_logs(resolver, &join(path, "resolver_query_logs"), issues);
+    }
+    if let Some(access_logs) = &config.alb_access_logs {
+        check_alb_access_logs(access_logs, &join(path, "alb_access_logs"), issues);
+    }
+    if let Some(data_events) = &config.data_events {
+        check_data_events(data_events, &join(path, "data_events"), issues);
+    }
+    if let Some(pool) = &config.resource_pool {
+        let field = join(path, "resource_pool");
+        for (name, size) in [
+            ("buckets", pool.buckets),
+            ("roles", pool.roles),
+            ("instances", pool.instances),
+            ("log_groups", pool.log_groups),
+            ("kms_keys", pool.kms_keys),
+        ] {
+            if size == Some(0) {
+                issues.error(join(&field, name), "must be greater than 0");
+            }
+        }
+    }
+    for (idx, takeover) in config.credential_takeovers.iter().enumerate() {
+        let field = format!("{}[{idx}]", join(path, "credential_takeover"));
+        check_credential_takeover(takeover, &field, issues);
+    }
+    for (idx, injection) in config.actor_injections.iter().enumerate() {
+        let field = format!("{}[{idx}]", join(path, "actor_injection"));
+        if let Err(err) = plan_injection(injection, Utc::now()) {
+            issues.error(field, err);
+        }
+    }
+    if let Some(profiles) = &config.error_profiles {
+        let mut names = profiles.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            let field = format!("{}.{name}", join(path, "error_profiles"));
+            let profile = &profiles[name];
+            if profile
+                .rate
+                .is_some_and(|rate| !(0.0..=1.0).contains(&rate))
+            {
+                issues.error(join(&field, "rate"), "must be between 0.0 and 1.0");
+            }
+            check_error_codes(&profile.errors, &join(&field, "error"), issues);
+        }
+    }
+    for (idx, surge) in config.error_surges.iter().enumerate() {
+        let field = format!("{}[{idx}]", join(path, "error_surge"));
+        check_error_surge(surge, &field, issues);
+    }
+    for (idx, storm) in config.api_storms.iter().enumerate() {
+        let field = format!("{}[{idx}]", join(path, "api_storm"));
+        check_api_storm(storm, &field, issues);
+    }
+    for (idx, limit) in config.rate_limits.iter().enumerate() {
+        let field = format!("{}[{idx}]", join(path, "rate_limit"));
+        check_rate_limit(limit, &field, issues);
+    }
+    for (idx, wave) in config.policy_remediations.iter().enumerate() {
+        let field = format!("{}[{idx}]", join(path, "policy_remediation"));
+        check_policy_remediation(wave, &field, issues);
+    }
+    for (idx, travel) in config.impossible_travels.iter().enumerate() {
+        let field = format!("{}[{idx}]", join(path, "impossible_travel"));
+        check_impossible_travel(travel, &field, issues);
+    }
+    check_ip_pools(config.baseline_source_ips.as_ref(), path, issues);
+}
+
+fn check_credential_takeover(config: &CredentialTakeoverConfig, path: &str, issues: &mut Issues) {
+    if config.actor_id.trim().is_empty() {
+        issues.error(join(path, "actor_id"), "must be non-empty");
+    }
+    if let Some(start_time) = &config.start_time {
+        if DateTime::parse_from_rfc3339(start_time).is_err() {
+            issues.error(
+                join(path, "start_time"),
+                format!("invalid RFC 3339 time: {start_time}"),
+            );
+        }
+    }
+    if config.duration_seconds.is_some_and(|seconds| seconds <= 0) {
+        issues.error(join(path, "duration_seconds"), "must be greater than 0");
+    }
+    if config
+        .events_per_hour
+        .is_some_and(|rate| !rate.is_finite() || rate <= 0.0)
+    {
+        issues.error(join(path, "events_per_hour"), "must be greater than 0");
+    }
+    for id in config.technique_ids() {
+        if !is_valid_technique_id(&id) {
+            issues.error(path, format!("invalid ATT&CK technique ID: {id}"));
+        }
+    }
+}
+
+fn check_api_storm(config: &ApiStormConfig, path: &str, issues: &mut Issues) {
+    if config.service.trim().is_empty() {
+        issues.error(join(path, "service"), "must be non-empty");
+    }
+    if let Some(start_time) = &config.start_time {
+        if DateTime::parse_from_rfc3339(start_time).is_err() {
+            issues.error(
+                join(path, "start_time"),
+                format!("invalid RFC 3339 time: {start_time}"),
+            );
+        }
+    }
+    if config.duration_seconds <= 0 {
+        issues.error(join(path, "duration_seconds"), "must be greater than 0");
+    }
+    if config.actors == Some(0) {
+        issues.error(join(path, "actors"), "must be greater than 0");
+    }
+    if config
+        .events_per_second
+        .is_some_and(|rate| !rate.is_finite() || rate <= 0.0)
+    {
+        issues.error(join(path, "events_per_second"), "must be greater than 0");
+    }
+    if config
+        .error_rate
+        .is_some_and(|rate| !(0.0..=1.0).contains(&rate))
+    {
+        issues.error(join(path, "error_rate"), "must be between 0.0 and 1.0");
+    }
+    if config
+        .events
+        .as_ref()
+        .is_some_and(|events| events.is_empty())
+    {
+        issues.error(join(path, "events"), "must list at least one event");
+    }
+    for id in config.technique_id.iter().chain(&config.technique_ids) {
+        if !is_valid_technique_id(id) {
+            issues.error(path, format!("invalid ATT&CK technique ID: {id}"));
+        }
+    }
+}
+
+fn check_rate_limit(config: &RateLimitConfig, path: &str, issues: &mut Issues) {
+    if config.service.trim().is_empty() {
+        issues.error(join(path, "service"), "must be non-empty");
+    }
+    if config.max_calls == 0 {
+        issues.error(join(path, "max_calls"), "must be greater than 0");
+    }
+    if config.window_seconds.is_some_and(|seconds| seconds <= 0) {
+        issues.error(join(path, "window_seconds"), "must be greater than 0");
+    }
+    if config
+        .events
+        .as_ref()
+        .is_some_and(|events| events.is_empty())
+    {
+        issues.error(join(path, "events"), "must list at least one event");
+    }
+}
+
+fn check_policy_remediation(config: &PolicyRemediationConfig, path: &str, issues: &mut Issues) {
+    if let Some(start_time) = &config.start_time {
+        if DateTime::parse_from_rfc3339(start_time).is_err() {
+            issues.error(
+                join(path, "start_time"),
+                format!("invalid RFC 3339 time: {start_time}"),
+            );
+        }
+    }
+    if config.duration_seconds.is_some_and(|seconds| seconds <= 0) {
+        issues.error(join(path, "duration_seconds"), "must be greater than 0");
+    }
+    if config.changes == Some(0) {
+        issues.error(join(path, "changes"), "must be greater than 0");
+    }
+    if let Some(accounts) = &config.accounts {
+        if accounts.is_empty() {
+            issues.error(join(path, "accounts"), "must list at least one account");
+        }
+        if !accounts.iter().all(|account| is_account_id(account)) {
+            issues.error(join(path, "accounts"), "must be 12-digit strings");
+        }
+    }
+    if config
+        .spot_check_rate
+        .is_some_and(|rate| !(0.0..=1.0).contains(&rate))
+    {
+        issues.error(join(path, "spot_check_rate"), "must be between 0.0 and 1.0");
+    }
+    for id in config.technique_id.iter().chain(&config.technique_ids) {
+        if !is_valid_technique_id(id) {
+            issues.error(path, format!("invalid ATT&CK technique ID: {id}"));
+        }
+    }
+}
+
+fn check_impossible_travel(config: &ImpossibleTravelConfig, path: &str, issues: &mut Issues) {
+    if config.actor_id.trim().is_empty() {
+        issues.error(join(path, "actor_id"), "must be non-empty");
+    }
+    if let Some(start_time) = &config.start_time {
+        if DateTime::parse_from_rfc3339(start_time).is_err() {
+            issues.error(
+                join(path, "start_time"),
+                format!("invalid RFC 3339 time: {start_time}"),
+            );
+        }
+    }
+    if config.gap_seconds.is_some_and(|seconds| seconds <= 0) {
+        issues.error(join(path, "gap_seconds"), "must be greater than 0");
+    }
+    if config.events == Some(0) {
+        issues.error(join(path, "events"), "must be greater than 0");
+    }
+    let mut cities = Vec::new();
+    for (name, location) in [
+        ("sign_in_location", &config.sign_in_location),
+        ("activity_location", &config.activity_location),
+    ] {
+        if let Some(location) = location {
+            match City::find(location) {
+                Some(city) => cities.push(city),
+                None => issues.error(join(path, name), format!("unknown city: {location}")),
+            }
+        }
+    }
+    if cities.len() == 2 && cities[0] == cities[1] {
+        issues.error(
+            join(path, "activity_location"),
+            "must differ from sign_in_location",
+        );
+    }
+    for (name, ip) in [
+        ("sign_in_ip", &config.sign_in_ip),
+        ("activity_ip", &config.activity_ip),
+    ] {
+        if let Some(ip) = ip
+            .as_ref()
+            .filter(|ip| ip.parse::<std::net::IpAddr>().is_err())
+        {
+            issues.error(join(path, name), format!("not an IP address: {ip}"));
+        }
+    }
+    for id in config.technique_ids() {
+        if !is_valid_technique_id(&id) {
+            issues.error(path, format!("invalid ATT&CK technique ID: {id}"));
+        }
+    }
+}
+
+fn check_error_surge(config: &ErrorSurgeConfig, path: &str, issues: &mut Issues) {
+    if let Some(start_time) = &config.start_time {
+        if DateTime::parse_from_rfc3339(start_time).is_err() {
+            issues.error(
+                join(path, "start_time"),
+                format!("invalid RFC 3339 time: {start_time}"),
+            );
+        }
+    }
+    if config.duration_seconds <= 0 {
+        issues.error(join(path, "duration_seconds"), "must be greater than 0");
+    }
+    if !(0.0..=1.0).contains(&config.rate) {
+        issues.error(join(path, "rate"), "must be between 0.0 and 1.0");
+    }
+    if config
+        .events
+        .as_ref()
+        .is_some_and(|events| events.is_empty())
+    {
+        issues.error(join(path, "events"), "must list at least one event");
+    }
+    check_error_codes(&config.errors, &join(path, "error"), issues);
+}
+
+fn check_error_codes(codes: &[ErrorCodeConfig], path: &str, issues: &mut Issues) {
+    for (idx, code) in codes.iter().enumerate() {
+        let field = format!("{path}[{idx}]");
+        if code.code.trim().is_empty() {
+            issues.error(join(&field, "code"), "must be non-empty");
+        }
+        if code
+            .weight
+            .is_some_and(|weight| !weight.is_finite() || weight <= 0.0)
+        {
+            issues.error(join(&field, "weight"), "must be greater than 0");
+        }
+    }
+}
+
+fn check_resolver_query_logs(config: &ResolverQueryLogConfig, path: &str, issues: &mut Issues) {
+    for (field, value) in [
+        ("external_lookup_rate", config.external_lookup_rate),
+        ("dga_share", config.dga_share),
+        ("known_bad_share", config.known_bad_share),
+    ] {
+        if value.is_some_and(|value| !(0.0..=1.0).contains(&value)) {
+            issues.error(join(path, field), "must be between 0.0 and 1.0");
+        }
+    }
+    if config.dga_share() + config.known_bad_share() > 1.0 {
+        issues.error(
+            join(path, "known_bad_share"),
+            "dga_share and known_bad_share must add up to at most 1.0",
+        );
+    }
+    if let Some(domains) = &config.domains {
+        if domains.is_empty() {
+            issues.error(join(path, "domains"), "must not be empty");
+        }
+        for (idx, domain) in domains.iter().enumerate() {
+            if domain.name.trim().is_empty() {
+                issues.error(format!("{path}.domains[{idx}].name"), "must not be empty");
+            }
+            if !domain.weight.is_finite() || domain.weight <= 0.0 {
+                issues.error(
+                    format!("{path}.domains[{idx}].weight"),
+                    "must be greater than 0",
+                );
+            }
+        }
+    }
+    if config
+        .known_bad_domains
+        .as_ref()
+        .is_some_and(|domains| domains.is_empty())
+        && config.known_bad_share() > 0.0
+    {
+        issues.error(
+            join(path, "known_bad_domains"),
+            "must not be empty while known_bad_share is above 0",
+        );
+    }
+}
+
+fn is_guid(value: &str) -> bool {
+    let groups = value.split('-').map(str::len).collect::<Vec<_>>();
+    groups == [8, 4, 4, 4, 12] && value.chars().all(|ch| ch == '-' || ch.is_ascii_hexdigit())
+}
+
+fn check_entra_federation(config: &EntraFederationConfig, path: &str, issues: &mut Issues) {
+    if let Some(tenant_id) = &config.tenant_id {
+        if !is_guid(tenant_id) {
+            issues.error(join(path, "tenant_id"), "must be a GUID");
+        }
+    }
+    if config.saml_provider.as_ref().is_some_and(|name| {
+        !(1..=128).contains(&name.len())
+            || !name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-'))
+    }) {
+        issues.error(
+            join(path, "saml_provider"),
+            "must be 1-128 letters, digits, periods, underscores, or hyphens",
+        );
+    }
+    if let Some(role_arns) = &config.role_arns {
+        let mut roles = role_arns.iter().collect::<Vec<_>>();
+        roles.sort();
+        for (role, arn) in roles {
+            let field = format!("{}.{role}", join(path, "role_arns"));
+            if !ROLES.contains(&role.as_str()) {
+                issues.error(
+                    field,
+                    format!("unknown role; expected one of {}", ROLES.join(", ")),
+                );
+            } else if arn.is_empty() || arn.contains(char::is_whitespace) {
+                issues.error(field, "must be a role name or ARN without spaces");
+            } else if arn.starts_with("arn:") && !arn.contains(":role/") {
+                issues.error(field, "must be an IAM role ARN");
+            }
+        }
+    }
+}
+
+fn check_alb_access_logs(config: &AlbAccessLogConfig, path: &str, issues: &mut Issues) {
+    for (field, value) in [
+        ("visit_rate", config.visit_rate),
+        ("error_rate", config.error_rate),
+    ] {
+        if value.is_some_and(|value| !(0.0..=1.0).contains(&value)) {
+            issues.error(join(path, field), "must be between 0.0 and 1.0");
+        }
+    }
+    if config.max_requests == Some(0) {
+        issues.error(join(path, "max_requests"), "must be greater than 0");
+    }
+    if let Some(name) = &config.load_balancer {
+        let valid = (1..=32).contains(&name.len())
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+            && !name.starts_with('-')
+            && !name.ends_with('-');
+        if !valid {
+            issues.error(
+                join(path, "load_balancer"),
+                "must be 1-32 letters, digits, or hyphens, not starting or ending with a hyphen",
+            );
+        }
+    }
+    if config
+        .domain_name
+        .as_ref()
+        .is_some_and(|name| name.is_empty() || name.contains(char::is_whitespace))
+    {
+        issues.error(
+            join(path, "domain_name"),
+            "must be a host name without spaces",
+        );
+    }
+}
+
+fn check_data_events(config: &DataEventsConfig, path: &str, issues: &mut Issues) {
+    if let Some(share) = config.max_share {
+        if !(0.0..1.0).contains(&share) {
+            issues.error(join(path, "max_share"), "must be in [0, 1)");
+        }
+    }
+    if config.burst_size == Some(0) {
+        issues.error(join(path, "burst_size"), "must be greater than 0");
+    }
+    if let Some(services) = &config.services {
+        let field = join(path, "services");
+        let mut keys = services.keys().collect::<Vec<_>>();
+        keys.sort();
+        for key in keys {
+            let weight = services[key];
+            if !DataService::ALL.iter().any(|service| service.key() == key) {
+                let expected = DataService::ALL.map(DataService::key).join(", ");
+                issues.error(
+                    format!("{field}.{key}"),
+                    format!("unknown data event service; expected one of {expected}"),
+                );
+            } else if !weight.is_finite() || weight < 0.0 {
+                issues.error(
+                    format!("{field}.{key}"),
+                    format!("invalid weight: {weight}"),
+                );
+            }
+        }
+        if !services.is_empty() && services.values().all(|weight| *weight <= 0.0) {
+            issues.error(field, "weights must sum to more than 0");
+        }
+    }
+}
+
+fn unknown_insider_persona(name: &str) -> String {
+    let expected = InsiderPersona::ALL.map(InsiderPersona::key).join(", ");
+    format!("unknown insider persona {name}; expected one of {expected}")
+}
+
+fn check_registry_path(registry: &str, path: &str, inherited: bool, issues: &mut Issues) {
+    let field = join(path, "identity_registry_path");
+    if !registry.trim().is_empty() {
+        check_file(registry, &field, issues);
+    } else if !inherited {
+        issues.error(
+            field,
+            "required unless inherited from a multi source registry or population",
+        );
+    }
+}
+
+fn check_arrival(arrival: Option<&ArrivalConfig>, path: &str, issues: &mut Issues) {
+    if let Err(err) = Arrivals::from_config(arrival) {
+        issues.error(join(path, "arrival"), err);
+    }
+}
+
+fn check_session_correlation(correlation: Option<f64>, path: &str, issues: &mut Issues) {
+    if correlation.is_some_and(|value| !(0.0..=1.0).contains(&value)) {
+        issues.error(
+            join(path, "session_correlation"),
+            "must be between 0.0 and 1.0",
+        );
+    }
+}
+
+fn check_ip_pools(
+    pools: Option<&std::collections::HashMap<String, Vec<String>>>,
+    path: &str,
+    issues: &mut Issues,
+) {
+    let Some(pools) = pools else {
+        return;
+    };
+    let mut actors = pools.keys().collect::<Vec<_>>();
+    actors.sort();
+    for actor in actors {
+        let field = format!("{}.{actor}", join(path, "baseline_source_ips"));
+        for ip in &pools[actor] {
+            if ip.parse::<std::net::IpAddr>().is_err() {
+                issues.error(&field, format!("not an IP address: {ip}"));
+            }
+        }
+    }
+}
+
+fn check_population_source(
+    population_path: Option<&str>,
+    inline: Option<&PopulationConfig>,
+    path: &str,
+    issues: &mut Issues,
+) {
+    if let Some(population_path) = population_path {
+        let field = join(path, "population_config_path");
+        if check_file(population_path, &field, issues) {
+            match PopulationConfig::from_path(population_path) {
+                Ok(population) => check_population(&population, population_path, issues),
+                Err(err) => issues.error(field, err.to_string()),
+            }
+        }
+    }
+    if let Some(population) = inline {
+        check_population(population, &join(path, "population_config"), issues);
+    }
+}
+
+fn check_population(config: &PopulationConfig, prefix: &str, issues: &mut Issues) {
+    if let Some(timezones) = &config.timezone_distribution {
+        let field = join(prefix, "timezone_distribution");
+        for (idx, timezone) in timezones.iter().enumerate() {
+            if Tz::from_str(&timezone.name).is_err() {
+                issues.error(
+                    format!("{field}[{idx}].name"),
+                    format!("unknown IANA timezone: {}", timezone.name),
+                );
+            }
+        }
+        check_weights(timezones.iter().map(|entry| entry.weight), &field, issues);
+    }
+    if let Some(parquet) = &config.parquet {
+        if let Err(err) = writer_properties(parquet) {
+            issues.error(join(prefix, "parquet"), err.to_string());
+        }
+    }
+    if let Some(plan) = &config.ip_plan {
+        if let Err(err) = IpPlan::new(plan) {
+            issues.error(join(prefix, "ip_plan"), err);
+        }
+    }
+
+    let population = &config.population;
+    let prefix = join(prefix, "population");
+    if population.actor_count == Some(0) && population.actor.as_ref().is_none_or(Vec::is_empty) {
+        issues.error(join(&prefix, "actor_count"), "population has no actors");
+    }
+    for (field, ratio) in [
+        ("service_ratio", population.service_ratio),
+        ("hot_actor_ratio", population.hot_actor_ratio),
+    ] {
+        if ratio.is_some_and(|ratio| !(0.0..=1.0).contains(&ratio)) {
+            issues.error(join(&prefix, field), "must be between 0.0 and 1.0");
+        }
+    }
+    if population
+        .hot_actor_multiplier
+        .is_some_and(|multiplier| !multiplier.is_finite() || multiplier < 1.0)
+    {
+        issues.error(
+            join(&prefix, "hot_actor_multiplier"),
+            "must be at least 1.0",
+        );
+    }
+    if population
+        .service_events_per_hour
+        .is_some_and(|rate| !rate.is_finite() || rate <= 0.0)
+    {
+        issues.error(
+            join(&prefix, "service_events_per_hour"),
+            "must be greater than 0",
+        );
+    }
+    if let Some(accounts) = &population.account_ids {
+        for (idx, account) in accounts.iter().enumerate() {
+            if !is_account_id(account) {
+                issues.error(
+                    format!("{}[{idx}]", join(&prefix, "account_ids")),
+                    format!("must be a 12-digit string: {account}"),
+                );
+            }
+        }
+    }
+    for (field, rate) in [
+        ("error_rate", &population.error_rate),
+        ("human_error_rate", &population.human_error_rate),
+        ("service_error_rate", &population.service_error_rate),
+    ] {
+        if let Some(rate) = rate {
+            check_error_rate(rate, &join(&prefix, field), issues);
+        }
+    }
+
+    if let Some(roles) = &population.role {
+        let field = join(&prefix, "role");
+        for (idx, role) in roles.iter().enumerate() {
+            let role_path = format!("{field}[{idx}]");
+            if !ROLES.contains(&role.name.as_str()) {
+                issues.warning(
+                    join(&role_path, "name"),
+                    format!(
+                        "unknown role {} is ignored; expected one of {}",
+                        role.name,
+                        ROLES.join(", ")
+                    ),
+                );
+            }
+            if !role.events_per_hour.is_finite() || role.events_per_hour <= 0.0 {
+                issues.error(
+                    join(&role_path, "events_per_hour"),
+                    "must be greater than 0",
+                );
+            }
+        }
+        check_weights(roles.iter().map(|role| role.weight), &field, issues);
+    }
+    if let Some(profiles) = &population.service_profiles {
+        let field = join(&prefix, "service_profiles");
+        for (idx, profile) in profiles.iter().enumerate() {
+            if !is_service_profile(&profile.name) {
+                issues.warning(
+                    format!("{field}[{idx}].name"),
+                    format!("unknown service profile {} is ignored", profile.name),
+                );
+            }
+            if profile
+                .events_per_hour
+                .is_some_and(|rate| !rate.is_finite() || rate <= 0.0)
+            {
+                issues.error(
+                    format!("{field}[{idx}].events_per_hour"),
+                    "must be greater than 0",
+                );
+            }
+        }
+        check_weights(
+            profiles.iter().map(|profile| profile.weight),
+            &field,
+            issues,
+        );
+    }
+
+    for (idx, entry) in population.insider_persona.iter().flatten().enumerate() {
+        if InsiderPersona::parse(&entry.name).is_none() {
+            issues.error(
+                format!("{}[{idx}].name", join(&prefix, "insider_persona")),
+                unknown_insider_persona(&entry.name),
+            );
+        }
+    }
+
+    if let Some(on_call) = &population.on_call {
+        if on_call.shift_days == Some(0) {
+            issues.error(
+                join(&join(&prefix, "on_call"), "shift_days"),
+                "must be at least 1",
+            );
+        }
+        if on_call.count > population.actor_count.unwrap_or(500) {
+            issues.error(
+                join(&join(&prefix, "on_call"), "count"),
+                "exceeds population.actor_count",
+            );
+        }
+    }
+
+    let mut ids = BTreeSet::new();
+    for (idx, actor) in population.actor.iter().flatten().enumerate() {
+        let actor_path = format!("{}[{idx}]", join(&prefix, "actor"));
+        if !ids.insert(actor.id.as_str()) {
+            issues.error(
+                join(&actor_path, "id"),
+                format!("duplicate actor id: {}", actor.id),
+            );
+        }
+        match actor.kind.as_str() {
+            "human" => {
+                if let Some(role) = &actor.role {
+                    if !ROLES.contains(&role.as_str()) {
+                        issues.error(join(&actor_path, "role"), format!("unknown role: {role}"));
+                    }
+                }
+            }
+            "service" => {
+                if let Some(profile) = &actor.service_profile {
+                    if !is_service_profile(profile) {
+                        issues.error(
+                            join(&actor_path, "service_profile"),
+                            format!("unknown service profile: {profile}"),
+                        );
+                    }
+                }
+            }
+            kind => issues.error(
+                join(&actor_path, "kind"),
+                format!("must be human or service, got {kind}"),
+            ),
+        }
+        if actor
+            .error_rate
+            .is_some_and(|rate| !(0.0..=1.0).contains(&rate))
+        {
+            issues.error(
+                join(&actor_path, "error_rate"),
+                "must be between 0.0 and 1.0",
+            );
+        }
+        if actor
+            .account_id
+            .as_deref()
+            .is_some_and(|account| !is_account_id(account))
+        {
+            issues.error(join(&actor_path, "account_id"), "must be a 12-digit string");
+        }
+        if let Some(timezone) = &actor.timezone {
+            if Tz::from_str(timezone).is_err() {
+                issues.error(
+                    join(&actor_path, "timezone"),
+                    format!("unknown IANA timezone: {timezone}"),
+                );
+            }
+        }
+        if actor.active_start_hour.is_some_and(|hour| hour > 23) {
+            issues.error(join(&actor_path, "active_start_hour"), "must be 0-23");
+        }
+        if actor
+            .active_hours
+            .is_some_and(|hours| hours == 0 || hours > 24)
+        {
+            issues.error(join(&actor_path, "active_hours"), "must be 1-24");
+        }
+        if let Some(persona) = &actor.insider_persona {
+            if InsiderPersona::parse(persona).is_none() {
+                issues.error(
+                    join(&actor_path, "insider_persona"),
+                    unknown_insider_persona(persona),
+                );
+            } else if actor.kind == "service" {
+                issues.error(
+                    join(&actor_path, "insider_persona"),
+                    "only human actors can have an insider persona",
+                );
+            }
+        }
+    }
+}
+
+fn check_error_rate(rate: &ErrorRateConfig, path: &str, issues: &mut Issues) {
+    let in_range = |value: f64| (0.0..=1.0).contains(&value);
+    if !in_range(rate.min) || !in_range(rate.max) {
+        issues.error(path, "min and max must be between 0.0 and 1.0");
+    } else if rate.min > rate.max {
+        issues.error(path, "min must not exceed max");
+    }
+}
+
+fn check_padding(padding: &PaddingConfig, source: &SourceConfig, issues: &mut Issues) {
+    if padding
+        .jitter
+        .is_some_and(|jitter| !(0.0..1.0).contains(&jitter))
+    {
+        issues.error("padding.jitter", "must be in [0, 1)");
+    }
+    if padding.target_bytes == Some(0) {
+        issues.error("padding.target_bytes", "must be greater than 0");
+    }
+    let keys = source.envelope_sources();
+    let mut targets = padding
+        .source_target_bytes
+        .iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    targets.sort();
+    for (key, bytes) in targets {
+        let field = format!("padding.source_target_bytes.{key}");
+        if *bytes == 0 {
+            issues.error(&field, "must be greater than 0");
+        }
+        if !keys.contains(key) {
+            issues.warning(field, format!("no configured source emits {key}"));
+        }
+    }
+}
+
+fn check_redaction(redaction: &RedactionConfig, source: &SourceConfig, issues: &mut Issues) {
+    let keys = source.envelope_sources();
+    for (idx, field) in redaction.fields.iter().enumerate() {
+        let path = format!("redaction.fields[{idx}]");
+        if !field.path.starts_with("payload.") && !field.path.starts_with("envelope.") {
+            issues.error(
+                format!("{path}.path"),
+                "must start with payload. or envelope.",
+            );
+        }
+        if field.ipv4_prefix.is_some_and(|bits| bits > 32) {
+            issues.error(format!("{path}.ipv4_prefix"), "must be at most 32");
+        }
+        if field.ipv6_prefix.is_some_and(|bits| bits > 128) {
+            issues.error(format!("{path}.ipv6_prefix"), "must be at most 128");
+        }
+        for key in field.sources.iter().flatten() {
+            if !keys.contains(key) {
+                issues.warning(
+                    format!("{path}.sources"),
+                    format!("no configured source emits {key}"),
+                );
+            }
+        }
+    }
+    if redaction.salt.is_none()
+        && redaction
+            .fields
+            .iter()
+            .any(|field| field.action == RedactionAction::Hash)
+    {
+        issues.warning(
+            "redaction.salt",
+            "hashed values without a salt can be reversed by hashing candidate inputs",
+        );
+    }
+}
+
+/// Flags negative or non-finite weights and weight lists that sum to zero.
+fn check_weights(weights: impl Iterator<Item = f64>, path: &str, issues: &mut Issues) {
+    let mut total = 0.0;
+    let mut count = 0;
+    for (idx, weight) in weights.enumerate() {
+        count += 1;
+        if !weight.is_finite() || weight < 0.0 {
+            issues.error(
+                format!("{path}[{idx}]"),
+                format!("invalid weight: {weight}"),
+            );
+        } else {
+            total += weight;
+        }
+    }
+    if count > 0 && total <= 0.0 {
+        issues.error(path, "weights must sum to more than 0");
+    }
+}
+
+/// Returns whether `path` exists, recording an error when it does not.
+fn check_file(path: &str, field: &str, issues: &mut Issues) -> bool {
+    if Path::new(path).exists() {
+        return true;
+    }
+    issues.error(field, format!("file not found: {path}"));
+    false
+}
+
+fn is_account_id(value: &str) -> bool {
+    let trimmed = value.trim();
+    trimmed.len() == 12 && trimmed.chars().all(|ch| ch.is_ascii_digit())
+}
+
+fn is_service_profile(name: &str) -> bool {
+    SERVICE_PROFILES.contains(&name.trim().to_lowercase().replace('-', "_").as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(issues: &[ValidationIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.path.as_str()).collect()
+    }
+
+    #[test]
+    fn examples_validate_cleanly() {
+        for path in [
+            "examples/all_sources.toml",
+            "examples/all_sources_zerobus.toml",
+        ] {
+            let config = Config::from_path(path).unwrap();
+            let issues = validate_config(&config);
+            assert!(!has_errors(&issues), "{path}: {issues:?}");
+        }
+    }
+
+    #[test]
+    fn reports_cross_field_errors_with_paths() {
+        let mut config = Config::from_path("examples/all_sources.toml").unwrap();
+        config.seed = None;
+        config.traffic.until_time = Some("yesterday".to_string());
+        config.padding = Some(PaddingConfig {
+            target_bytes: None,
+            source_target_bytes: Some([("github_audit".to_string(), 512)].into()),
+            jitter: Some(2.0),
+        });
+        let SourceConfig::Multi(multi) = &mut config.source else {
+            panic!("expected multi source");
+        };
+        let SourceConfig::CloudTrail(cloudtrail) = &mut multi.sources[0] else {
+            panic!("expected cloudtrail source");
+        };
+        cloudtrail.regions = Some(vec!["us-east-1".to_string(), "eu-west-1".to_string()]);
+        cloudtrail.region_distribution = Some(vec![1.0]);
+        cloudtrail.catalog_path = Some("examples/missing-catalog.yaml".to_string());
+        cloudtrail.deterministic_ids = true;
+        let population = multi
+            .population_config
+            .get_or_insert_with(|| PopulationConfig::from_path("examples/actors.toml").unwrap());
+        population.population.service_ratio = Some(1.5);
+
+        let issues = validate_config(&config);
+        assert!(has_errors(&issues));
+        let found = paths(&issues);
+        for expected in [
+            "traffic.until_time",
+            "source.sources[0].region_distribution",
+            "source.sources[0].catalog_path",
+            "source.sources[0].deterministic_ids",
+            "source",
+            "source.population_config.population.service_ratio",
+            "padding.jitter",
+        ] {
+            assert!(found.contains(&expected), "{expected}: {found:?}");
+        }
+        let unused = issues
+            .iter()
+            .find(|issue| issue.path == "padding.source_target_bytes.github_audit")
+            .unwrap();
+        assert_eq!(unused.severity, Severity::Warning);
+        assert_eq!(
+            unused.to_string(),
+            "warning: padding.source_target_bytes.github_audit: no configured source emits github_audit"
+        );
+    }
+}
@@ -0,0 +1,389 @@
+//! Quality gates for generated output directories.
+//!
+//! Reads the same files as `seclog inspect` and checks every record for
+//! duplicate event IDs, timestamps that go backwards within a file, missing
+//! required fields, and (when a limit is given) gaps in event time between a
+//! stream's consecutive files. Used by `seclog verify` to pass or fail a
+//! dataset before it is shared.
+
+use crate::formats::json::read_records;
+use crate::formats::parquet::{is_event_file, read_events};
+use crate::inspect::{collect_files, file_format, record_view};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Examples kept per check; violations past this are only counted.
+const MAX_EXAMPLES: usize = 10;
+
+/// Envelope fields every whole event must carry.
+const ENVELOPE_FIELDS: &[&str] = &[
+    "/envelope/schema_version",
+    "/envelope/timestamp",
+    "/envelope/source",
+    "/envelope/event_type",
+    "/envelope/actor/id",
+    "/envelope/actor/kind",
+    "/envelope/outcome",
+];
+
+/// One of the checks run by [`verify_dir`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Check {
+    /// The same event ID appears more than once for a source.
+    DuplicateIds,
+    /// A record's timestamp is earlier than one before it in the same file.
+    TimestampOrder,
+    /// A record lacks an envelope field, event type, or parseable timestamp.
+    RequiredFields,
+    /// Event time jumps by more than `max_gap` between a stream's files.
+    RotationGaps,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Check::DuplicateIds => "duplicate_ids",
+            Check::TimestampOrder => "timestamp_order",
+            Check::RequiredFields => "required_fields",
+            Check::RotationGaps => "rotation_gaps",
+        })
+    }
+}
+
+/// Violation count and the first few examples for one check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckResult {
+    pub violations: u64,
+    pub examples: Vec<String>,
+}
+
+/// Limits applied by [`verify_dir`].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// Largest allowed event-time gap between consecutive files of a stream;
+    /// rotation gaps are not checked when unset.
+    pub max_gap: Option<Duration>,
+}
+
+/// Pass/fail result of verifying an output directory.
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub files: usize,
+    pub events: u64,
+    /// Results for the checks that ran.
+    pub checks: BTreeMap<Check, CheckResult>,
+    /// Largest event-time gap between consecutive files of any stream.
+    pub largest_gap: Option<Duration>,
+}
+
+impl VerifyReport {
+    pub fn violations(&self) -> u64 {
+        self.checks.values().map(|result| result.violations).sum()
+    }
+
+    pub fn passed(&self) -> bool {
+        self.violations() == 0
+    }
+
+    fn violation(&mut self, check: Check, example: impl FnOnce() -> String) {
+        let result = self.checks.entry(check).or_default();
+        result.violations += 1;
+        if result.examples.len() < MAX_EXAMPLES {
+            result.examples.push(example());
+        }
+    }
+}
+
+/// Files sharing a directory, source, account, and region.
+type StreamKey = (PathBuf, String, Option<String>, Option<String>);
+
+/// Event-time span of one file, for the rotation gap check.
+struct FileSpan {
+    path: PathBuf,
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct Verifier {
+    report: VerifyReport,
+    /// File index of the first occurrence of each `(source, event ID)`.
+    seen_ids: HashMap<(String, String), usize>,
+    paths: Vec<PathBuf>,
+    streams: BTreeMap<StreamKey, Vec<FileSpan>>,
+}
+
+/// Runs every check over the event files under `dir`.
+pub fn verify_dir(dir: impl AsRef<Path>, options: &VerifyOptions) -> io::Result<VerifyReport> {
+    let mut paths = Vec::new();
+    collect_files(dir.as_ref(), &mut paths)?;
+    paths.sort();
+
+    let mut verifier = Verifier::default();
+    for check in [Check::DuplicateIds, Check::TimestampOrder, Check::RequiredFields] {
+        verifier.report.checks.insert(check, CheckResult::default());
+    }
+    if options.max_gap.is_some() {
+        verifier
+            .report
+            .checks
+            .insert(Check::RotationGaps, CheckResult::default());
+    }
+    for path in paths {
+        let Some(format) = file_format(&path) else {
+            continue;
+        };
+        let records = match format {
+            "parquet" => {
+                if !is_event_file(&path)? {
+                    continue;
+                }
+                read_events(&path)
+                    .map(|event| {
+                        let event = event?;
+                        let source = event.envelope.source.clone();
+                        let whole = serde_json::to_value(&event).map_err(io::Error::other)?;
+                        Ok((whole, Some(source)))
+                    })
+                    .collect::<io::Result<Vec<_>>>()
+            }
+            _ => read_records(&path)
+                .map(|records| records.into_iter().map(|record| (record, None)).collect()),
+        };
+        let records = records
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?;
+        verifier.verify_file(path, &records);
+    }
+    verifier.check_gaps(options.max_gap);
+    Ok(verifier.report)
+}
+
+impl Verifier {
+    /// Checks one file's records, given as whole events (Parquet) or
+    /// source-native records (JSON) with the envelope source when known.
+    fn verify_file(&mut self, path: PathBuf, records: &[(Value, Option<String>)]) {
+        let file = self.paths.len();
+        self.report.files += 1;
+        let mut stream = None;
+        let mut span: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+        for (index, (record, source)) in records.iter().enumerate() {
+            self.report.events += 1;
+            let whole = record.get("envelope").is_some();
+            // Whole events are checked against their payload when they have one.
+            let native = match record.get("payload") {
+                Some(payload) if whole && !payload.is_null() => payload,
+                _ => record,
+            };
+            if whole {
+                for pointer in ENVELOPE_FIELDS {
+                    if !record.pointer(pointer).is_some_and(is_present) {
+                        self.report.violation(Check::RequiredFields, || {
+                            format!("{}: record {index} has no {pointer}", path.display())
+                        });
+                    }
+                }
+            }
+            let view = record_view(native, source.as_deref());
+            if view.source.is_empty() || view.source == "unknown" {
+                self.report.violation(Check::RequiredFields, || {
+                    format!("{}: record {index} is not a recognized event", path.display())
+                });
+            } else if view.event_type.is_empty() {
+                self.report.violation(Check::RequiredFields, || {
+                    format!("{}: record {index} has no event type", path.display())
+                });
+            }
+            let Some(time) = view.time else {
+                self.report.violation(Check::RequiredFields, || {
+                    format!("{}: record {index} has no valid timestamp", path.display())
+                });
+                continue;
+            };
+
+            if let Some(id) = event_id(native, &view.source) {
+                match self.seen_ids.get(&(view.source.clone(), id.clone())) {
+                    Some(&first) => {
+                        let first = self.paths.get(first).unwrap_or(&path);
+                        self.report.violation(Check::DuplicateIds, || {
+                            format!(
+                                "{} {id} in {} also in {}",
+                                view.source,
+                                path.display(),
+                                first.display()
+                            )
+                        });
+                    }
+                    None => {
+                        self.seen_ids.insert((view.source.clone(), id), file);
+                    }
+                }
+            }
+
+            span = Some(match span {
+                Some((first, last)) => {
+                    if time < last {
+                        self.report.violation(Check::TimestampOrder, || {
+                            format!(
+                                "{}: record {index} at {} follows {}",
+                                path.display(),
+                                time.to_rfc3339(),
+                                last.to_rfc3339()
+                            )
+                        });
+                    }
+                    (first.min(time), last.max(time))
+                }
+                None => (time, time),
+            });
+            stream.get_or_insert_with(|| {
+                (
+                    path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                    view.source.clone(),
+                    view.account.clone(),
+                    view.region.clone(),
+                )
+            });
+        }
+        if let (Some(stream), Some((first, last))) = (stream, span) {
+            self.streams.entry(stream).or_default().push(FileSpan {
+                path: path.clone(),
+                first,
+                last,
+            });
+        }
+        self.paths.push(path);
+    }
+
+    /// Compares each stream's consecutive files by event time.
+    fn check_gaps(&mut self, max_gap: Option<Duration>) {
+        for files in self.streams.values_mut() {
+            files.sort_by_key(|file| file.first);
+            for pair in files.windows(2) {
+                let Ok(gap) = (pair[1].first - pair[0].last).to_std() else {
+                    continue;
+                };
+                if self.report.largest_gap.is_none_or(|largest| gap > largest) {
+                    self.report.largest_gap = Some(gap);
+                }
+                if max_gap.is_some_and(|max_gap| gap > max_gap) {
+                    self.report.violation(Check::RotationGaps, || {
+                        format!(
+                            "{}s between {} and {}",
+                            gap.as_secs(),
+                            pair[0].path.display(),
+                            pair[1].path.display()
+                        )
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn is_present(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::String(text) => !text.is_empty(),
+        _ => true,
+    }
+}
+
+/// Unique ID of a source-native record; `None` for sources without one.
+fn event_id(record: &Value, source: &str) -> Option<String> {
+    let text = |pointer: &str| {
+        record
+            .pointer(pointer)
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    match source {
+        "cloudtrail" => text("/eventID"),
+        "okta_system_log" => text("/uuid"),
+        "github_audit" => text("/_document_id"),
+        "entra_signin" | "entra_audit" => text("/id"),
+        "s3_access_log" => text("/request_id"),
+        "alb_access_log" => text("/trace_id"),
+        "kubernetes_audit" => text("/auditID"),
+        "google_workspace" => text("/id/uniqueQualifier"),
+        "databricks_audit" => text("/event_id"),
+        // Record IDs count up per computer.
+        "windows_security" => {
+            let computer = text("/System/Computer")?;
+            let record_id = record.pointer("/System/EventRecordID")?.as_u64()?;
+            Some(format!("{computer}/{record_id}"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn cloudtrail(id: &str, time: &str) -> Value {
+        json!({
+            "eventID": id,
+            "eventTime": time,
+            "eventSource": "s3.amazonaws.com",
+            "eventName": "GetObject",
+            "awsRegion": "us-east-1",
+            "recipientAccountId": "123456789012",
+        })
+    }
+
+    fn write_records(path: &Path, records: Vec<Value>) {
+        fs::write(path, json!({ "Records": records }).to_string()).unwrap();
+    }
+
+    #[test]
+    fn flags_duplicates_disorder_missing_fields_and_gaps() {
+        let dir = std::env::temp_dir().join(format!("seclog-verify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        write_records(
+            &dir.join("a.json"),
+            vec![
+                cloudtrail("1", "2026-01-01T00:00:00Z"),
+                cloudtrail("2", "2026-01-01T00:05:00Z"),
+                cloudtrail("3", "2026-01-01T00:04:00Z"),
+            ],
+        );
+        let options = VerifyOptions {
+            max_gap: Some(Duration::from_secs(3600)),
+        };
+        let report = verify_dir(&dir, &options).unwrap();
+        assert_eq!(report.events, 3);
+        assert_eq!(report.checks[&Check::TimestampOrder].violations, 1);
+        assert_eq!(report.violations(), 1);
+
+        let mut missing_name = cloudtrail("5", "2026-01-01T03:00:01Z");
+        missing_name["eventName"] = Value::Null;
+        write_records(
+            &dir.join("b.json"),
+            vec![
+                cloudtrail("2", "2026-01-01T03:00:00Z"),
+                missing_name,
+                cloudtrail("6", "not a time"),
+            ],
+        );
+        let report = verify_dir(&dir, &options).unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.files, 2);
+        assert_eq!(report.checks[&Check::DuplicateIds].violations, 1);
+        assert!(report.checks[&Check::DuplicateIds].examples[0].contains("cloudtrail 2"));
+        assert_eq!(report.checks[&Check::RequiredFields].violations, 2);
+        assert_eq!(report.checks[&Check::RotationGaps].violations, 1);
+        assert_eq!(report.largest_gap, Some(Duration::from_secs(10_500)));
+
+        let report = verify_dir(&dir, &VerifyOptions::default()).unwrap();
+        assert!(!report.checks.contains_key(&Check::RotationGaps));
+        fs::remove_dir_all(&dir).ok();
+    }
+}